@@ -0,0 +1,490 @@
+//! Whether an account can log in, and the modify that enables, disables or
+//! unlocks it.
+//!
+//! Active Directory keeps this in `userAccountControl` and `lockoutTime`.
+//! OpenLDAP's password policy overlay locks an account with
+//! `pwdAccountLockedTime`, where an administrator's lock is the fixed time
+//! [`PERMANENT_LOCK`]. 389 Directory Server disables with `nsAccountLock`
+//! and locks out with `accountUnlockTime` and `passwordRetryCount`.
+
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use ldap3::Mod;
+
+use crate::entry::LdapEntry;
+use crate::server_detect::ServerType;
+use crate::time::{parse_filetime, parse_generalized_time};
+
+/// Operational attributes holding account state, which a plain `*` read
+/// leaves out.
+pub const ACCOUNT_STATE_ATTRS: [&str; 4] = [
+    "nsAccountLock",
+    "pwdAccountLockedTime",
+    "accountUnlockTime",
+    "passwordExpirationTime",
+];
+
+/// The `pwdAccountLockedTime` of an account locked by an administrator
+/// rather than by failed logins: it stays locked until unlocked by hand.
+pub const PERMANENT_LOCK: &str = "000001010000Z";
+
+/// `userAccountControl` bits.
+const ACCOUNTDISABLE: u32 = 0x0000_0002;
+const LOCKOUT: u32 = 0x0000_0010;
+const PASSWORD_EXPIRED: u32 = 0x0080_0000;
+
+/// Object classes of entries that log in.
+const ACCOUNT_CLASSES: &[&str] = &[
+    "person",
+    "organizationalPerson",
+    "inetOrgPerson",
+    "user",
+    "account",
+    "posixAccount",
+    "shadowAccount",
+];
+
+/// Whether an account can log in, as far as its attributes tell.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AccountStatus {
+    pub disabled: bool,
+    pub locked: bool,
+    pub expired: bool,
+}
+
+impl AccountStatus {
+    /// `enabled`, or what keeps the account out, e.g. `disabled, locked`.
+    pub fn summary(&self) -> String {
+        let reasons: Vec<&str> = [
+            (self.disabled, "disabled"),
+            (self.locked, "locked"),
+            (self.expired, "expired"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect();
+        if reasons.is_empty() {
+            "enabled".to_string()
+        } else {
+            reasons.join(", ")
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        *self == AccountStatus::default()
+    }
+}
+
+/// The `userAccountControl` of an Active Directory entry. Servers may send
+/// it signed.
+fn account_control(entry: &LdapEntry) -> Option<u32> {
+    let value: i64 = entry
+        .first_value("userAccountControl")?
+        .trim()
+        .parse()
+        .ok()?;
+    u32::try_from(value)
+        .or_else(|_| i32::try_from(value).map(|v| v as u32))
+        .ok()
+}
+
+/// Whether `entry` is something that logs in.
+pub fn is_account(entry: &LdapEntry) -> bool {
+    entry.first_value("userAccountControl").is_some()
+        || ACCOUNT_STATE_ATTRS
+            .iter()
+            .any(|attr| entry.first_value(attr).is_some())
+        || entry.object_classes().iter().any(|class| {
+            ACCOUNT_CLASSES
+                .iter()
+                .any(|c| c.eq_ignore_ascii_case(class))
+        })
+}
+
+/// The status of the account `entry` is, at `now`; `None` when it is not
+/// an account.
+pub fn account_status(entry: &LdapEntry, now: DateTime<Utc>) -> Option<AccountStatus> {
+    if !is_account(entry) {
+        return None;
+    }
+    let past = |at: Option<DateTime<Utc>>| at.is_some_and(|at| at <= now);
+    if let Some(uac) = account_control(entry) {
+        let lockout_time = entry.first_value("lockoutTime").and_then(parse_filetime);
+        let expires = entry.first_value("accountExpires").and_then(parse_filetime);
+        return Some(AccountStatus {
+            disabled: uac & ACCOUNTDISABLE != 0,
+            locked: uac & LOCKOUT != 0 || lockout_time.is_some(),
+            expired: uac & PASSWORD_EXPIRED != 0 || past(expires),
+        });
+    }
+    let locked_time = entry.first_value("pwdAccountLockedTime");
+    let unlock_at = entry
+        .first_value("accountUnlockTime")
+        .and_then(parse_generalized_time);
+    let password_expires = entry
+        .first_value("passwordExpirationTime")
+        .and_then(parse_generalized_time);
+    Some(AccountStatus {
+        disabled: entry
+            .first_value("nsAccountLock")
+            .is_some_and(|v| v.eq_ignore_ascii_case("true"))
+            || locked_time == Some(PERMANENT_LOCK),
+        locked: locked_time.is_some_and(|t| t != PERMANENT_LOCK)
+            || unlock_at.is_some_and(|at| at > now),
+        expired: past(password_expires),
+    })
+}
+
+/// What can be done to an account.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountAction {
+    Enable,
+    Disable,
+    Unlock,
+}
+
+impl AccountAction {
+    pub fn verb(self) -> &'static str {
+        match self {
+            AccountAction::Enable => "enable",
+            AccountAction::Disable => "disable",
+            AccountAction::Unlock => "unlock",
+        }
+    }
+
+    pub fn title(self) -> &'static str {
+        match self {
+            AccountAction::Enable => "Enable",
+            AccountAction::Disable => "Disable",
+            AccountAction::Unlock => "Unlock",
+        }
+    }
+
+    /// The past tense, for reporting it done.
+    pub fn done(self) -> &'static str {
+        match self {
+            AccountAction::Enable => "Enabled",
+            AccountAction::Disable => "Disabled",
+            AccountAction::Unlock => "Unlocked",
+        }
+    }
+}
+
+/// The modify that carries out an [`AccountAction`], with one line per
+/// attribute saying what it becomes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AccountChange {
+    pub mods: Vec<Mod<String>>,
+    pub lines: Vec<String>,
+}
+
+impl AccountChange {
+    fn new() -> Self {
+        Self {
+            mods: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    fn set(mut self, attr: &str, old: Option<&str>, value: String) -> Self {
+        self.lines.push(match old {
+            Some(old) => format!("{}: {} → {}", attr, old, value),
+            None => format!("{}: (none) → {}", attr, value),
+        });
+        self.mods
+            .push(Mod::Replace(attr.to_string(), HashSet::from([value])));
+        self
+    }
+
+    fn remove(mut self, attr: &str, old: &str) -> Self {
+        self.lines.push(format!("{}: {} → (removed)", attr, old));
+        self.mods
+            .push(Mod::Delete(attr.to_string(), HashSet::new()));
+        self
+    }
+}
+
+/// The change that does `action` to the account `entry` on a server of
+/// `server_type`, or why there is none: the account is already that way,
+/// or loom does not know how it is done there.
+pub fn account_change(
+    entry: &LdapEntry,
+    server_type: &ServerType,
+    action: AccountAction,
+) -> Result<AccountChange, String> {
+    if !is_account(entry) {
+        return Err(format!("{} is not an account", entry.dn));
+    }
+    let already = |state: &str| Err(format!("The account is already {}", state));
+    let not_locked = || Err("The account is not locked".to_string());
+    match server_type {
+        ServerType::ActiveDirectory => {
+            let old = entry.first_value("userAccountControl");
+            let uac = account_control(entry)
+                .ok_or_else(|| format!("{} has no userAccountControl", entry.dn))?;
+            let set_uac = |uac: u32| {
+                // Written back signed, as Active Directory stores it
+                AccountChange::new().set("userAccountControl", old, (uac as i32).to_string())
+            };
+            match action {
+                AccountAction::Enable if uac & ACCOUNTDISABLE == 0 => already("enabled"),
+                AccountAction::Enable => Ok(set_uac(uac & !ACCOUNTDISABLE)),
+                AccountAction::Disable if uac & ACCOUNTDISABLE != 0 => already("disabled"),
+                AccountAction::Disable => Ok(set_uac(uac | ACCOUNTDISABLE)),
+                AccountAction::Unlock => match entry.first_value("lockoutTime") {
+                    Some(old) if parse_filetime(old).is_some() => {
+                        Ok(AccountChange::new().set("lockoutTime", Some(old), "0".to_string()))
+                    }
+                    _ => not_locked(),
+                },
+            }
+        }
+        ServerType::OpenLdap => {
+            let locked_time = entry.first_value("pwdAccountLockedTime");
+            match (action, locked_time) {
+                (AccountAction::Enable, None) => already("enabled"),
+                (AccountAction::Enable, Some(old)) => {
+                    Ok(AccountChange::new().remove("pwdAccountLockedTime", old))
+                }
+                (AccountAction::Disable, Some(PERMANENT_LOCK)) => already("disabled"),
+                (AccountAction::Disable, old) => Ok(AccountChange::new().set(
+                    "pwdAccountLockedTime",
+                    old,
+                    PERMANENT_LOCK.to_string(),
+                )),
+                (AccountAction::Unlock, Some(PERMANENT_LOCK)) => {
+                    Err("The account is disabled rather than locked; enable it".to_string())
+                }
+                (AccountAction::Unlock, Some(old)) => {
+                    Ok(AccountChange::new().remove("pwdAccountLockedTime", old))
+                }
+                (AccountAction::Unlock, None) => not_locked(),
+            }
+        }
+        ServerType::Directory389 => {
+            let lock = entry.first_value("nsAccountLock");
+            let disabled = lock.is_some_and(|v| v.eq_ignore_ascii_case("true"));
+            match (action, lock) {
+                (AccountAction::Enable, Some(old)) if disabled => {
+                    Ok(AccountChange::new().remove("nsAccountLock", old))
+                }
+                (AccountAction::Enable, _) => already("enabled"),
+                (AccountAction::Disable, _) if disabled => already("disabled"),
+                (AccountAction::Disable, old) => {
+                    Ok(AccountChange::new().set("nsAccountLock", old, "TRUE".to_string()))
+                }
+                (AccountAction::Unlock, _) => match entry.first_value("accountUnlockTime") {
+                    Some(old) => Ok(AccountChange::new()
+                        .set(
+                            "passwordRetryCount",
+                            entry.first_value("passwordRetryCount"),
+                            "0".to_string(),
+                        )
+                        .remove("accountUnlockTime", old)),
+                    None => not_locked(),
+                },
+            }
+        }
+        other => Err(format!(
+            "Don't know how to {} an account on this server ({})",
+            action.verb(),
+            other
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures::EntryBuilder;
+
+    const ADA: &str = "uid=ada,ou=people,dc=example,dc=com";
+
+    fn now() -> DateTime<Utc> {
+        parse_generalized_time("20250601120000Z").unwrap()
+    }
+
+    fn status(attrs: &[(&str, &str)]) -> Option<String> {
+        let entry = attrs
+            .iter()
+            .fold(EntryBuilder::new(ADA), |b, (attr, value)| {
+                b.attr(attr, *value)
+            })
+            .build();
+        account_status(&entry, now()).map(|s| s.summary())
+    }
+
+    #[test]
+    fn test_active_directory_status() {
+        assert_eq!(
+            status(&[("userAccountControl", "512")]).as_deref(),
+            Some("enabled")
+        );
+        assert_eq!(
+            status(&[("userAccountControl", "514")]).as_deref(),
+            Some("disabled")
+        );
+        // 2024-01-01 lockout, and an account that expired on 2025-01-01
+        assert_eq!(
+            status(&[
+                ("userAccountControl", "514"),
+                ("lockoutTime", "133485408000000000"),
+                ("accountExpires", "133801632000000000"),
+            ])
+            .as_deref(),
+            Some("disabled, locked, expired")
+        );
+        // Zero and the "never" sentinel mean neither
+        assert_eq!(
+            status(&[
+                ("userAccountControl", "66048"),
+                ("lockoutTime", "0"),
+                ("accountExpires", "9223372036854775807"),
+            ])
+            .as_deref(),
+            Some("enabled")
+        );
+        assert_eq!(
+            status(&[("userAccountControl", "8389120")]).as_deref(),
+            Some("expired")
+        );
+    }
+
+    #[test]
+    fn test_openldap_and_389_status() {
+        assert_eq!(
+            status(&[("objectClass", "inetOrgPerson")]).as_deref(),
+            Some("enabled")
+        );
+        assert_eq!(
+            status(&[("pwdAccountLockedTime", PERMANENT_LOCK)]).as_deref(),
+            Some("disabled")
+        );
+        assert_eq!(
+            status(&[("pwdAccountLockedTime", "20250601110000Z")]).as_deref(),
+            Some("locked")
+        );
+        assert_eq!(
+            status(&[
+                ("nsAccountLock", "true"),
+                ("accountUnlockTime", "20250601130000Z"),
+                ("passwordExpirationTime", "20250501000000Z"),
+            ])
+            .as_deref(),
+            Some("disabled, locked, expired")
+        );
+        // A lockout that has run out, and a password good for another year
+        assert_eq!(
+            status(&[
+                ("nsAccountLock", "false"),
+                ("accountUnlockTime", "20250601110000Z"),
+                ("passwordExpirationTime", "20260601000000Z"),
+            ])
+            .as_deref(),
+            Some("enabled")
+        );
+        assert_eq!(status(&[("objectClass", "organizationalUnit")]), None);
+    }
+
+    #[test]
+    fn test_active_directory_changes() {
+        let ad = ServerType::ActiveDirectory;
+        let user = EntryBuilder::new(ADA)
+            .attr("userAccountControl", "512")
+            .attr("lockoutTime", "0")
+            .build();
+        let change = account_change(&user, &ad, AccountAction::Disable).unwrap();
+        assert_eq!(change.lines, ["userAccountControl: 512 → 514"]);
+        assert_eq!(
+            change.mods,
+            vec![Mod::Replace(
+                "userAccountControl".to_string(),
+                HashSet::from(["514".to_string()])
+            )]
+        );
+        assert_eq!(
+            account_change(&user, &ad, AccountAction::Enable).unwrap_err(),
+            "The account is already enabled"
+        );
+        assert_eq!(
+            account_change(&user, &ad, AccountAction::Unlock).unwrap_err(),
+            "The account is not locked"
+        );
+
+        let locked = EntryBuilder::new(ADA)
+            .attr("userAccountControl", "514")
+            .attr("lockoutTime", "133485408000000000")
+            .build();
+        let change = account_change(&locked, &ad, AccountAction::Enable).unwrap();
+        assert_eq!(change.lines, ["userAccountControl: 514 → 512"]);
+        let change = account_change(&locked, &ad, AccountAction::Unlock).unwrap();
+        assert_eq!(change.lines, ["lockoutTime: 133485408000000000 → 0"]);
+    }
+
+    #[test]
+    fn test_openldap_and_389_changes() {
+        let person = EntryBuilder::new(ADA)
+            .object_classes(&["inetOrgPerson"])
+            .build();
+        let change =
+            account_change(&person, &ServerType::OpenLdap, AccountAction::Disable).unwrap();
+        assert_eq!(
+            change.lines,
+            ["pwdAccountLockedTime: (none) → 000001010000Z"]
+        );
+        let locked = EntryBuilder::new(ADA)
+            .attr("pwdAccountLockedTime", "20250601110000Z")
+            .build();
+        let change = account_change(&locked, &ServerType::OpenLdap, AccountAction::Unlock).unwrap();
+        assert_eq!(
+            change.mods,
+            vec![Mod::Delete(
+                "pwdAccountLockedTime".to_string(),
+                HashSet::new()
+            )]
+        );
+        let disabled = EntryBuilder::new(ADA)
+            .attr("pwdAccountLockedTime", PERMANENT_LOCK)
+            .build();
+        assert!(account_change(&disabled, &ServerType::OpenLdap, AccountAction::Unlock).is_err());
+
+        let ds = ServerType::Directory389;
+        let change = account_change(&person, &ds, AccountAction::Disable).unwrap();
+        assert_eq!(change.lines, ["nsAccountLock: (none) → TRUE"]);
+        let locked_out = EntryBuilder::new(ADA)
+            .attr("nsAccountLock", "TRUE")
+            .attr("passwordRetryCount", "5")
+            .attr("accountUnlockTime", "20250601130000Z")
+            .build();
+        let change = account_change(&locked_out, &ds, AccountAction::Enable).unwrap();
+        assert_eq!(change.lines, ["nsAccountLock: TRUE → (removed)"]);
+        let change = account_change(&locked_out, &ds, AccountAction::Unlock).unwrap();
+        assert_eq!(
+            change.lines,
+            [
+                "passwordRetryCount: 5 → 0",
+                "accountUnlockTime: 20250601130000Z → (removed)"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unsupported_servers_and_non_accounts() {
+        let person = EntryBuilder::new(ADA)
+            .object_classes(&["inetOrgPerson"])
+            .build();
+        assert_eq!(
+            account_change(&person, &ServerType::EDirectory, AccountAction::Unlock).unwrap_err(),
+            "Don't know how to unlock an account on this server (eDirectory)"
+        );
+        let ou = EntryBuilder::new(ADA)
+            .object_classes(&["organizationalUnit"])
+            .build();
+        assert!(
+            account_change(&ou, &ServerType::OpenLdap, AccountAction::Disable)
+                .unwrap_err()
+                .ends_with("is not an account")
+        );
+    }
+}
@@ -0,0 +1,306 @@
+//! Operations in flight against one connection.
+//!
+//! Each background task registers when it starts and holds an [`OpGuard`]
+//! until it ends, however it ends. The registry backs the busy indicator
+//! and the list of outstanding operations, where they can be cancelled.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// How long a finished operation stays listed.
+pub const LINGER: Duration = Duration::from_secs(3);
+
+pub type OpId = u64;
+
+/// Called when an operation is cancelled, e.g. to abort its task.
+pub type CancelFn = Box<dyn FnOnce() + Send>;
+
+/// How an operation ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OpEnd {
+    Finished,
+    Cancelled,
+}
+
+/// One registered operation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Operation {
+    pub id: OpId,
+    pub label: String,
+    pub started: Instant,
+    /// When and how it ended; `None` while it runs.
+    pub ended: Option<(Instant, OpEnd)>,
+}
+
+impl Operation {
+    pub fn is_running(&self) -> bool {
+        self.ended.is_none()
+    }
+
+    /// Time taken so far, or in total once ended.
+    pub fn elapsed(&self, now: Instant) -> Duration {
+        let end = self.ended.map_or(now, |(at, _)| at);
+        end.saturating_duration_since(self.started)
+    }
+
+    /// `12.3s`, `finished 2.1s` or `cancelled after 2.1s`.
+    pub fn describe(&self, now: Instant) -> String {
+        let secs = self.elapsed(now).as_secs_f64();
+        match self.ended {
+            None => format!("{:.1}s", secs),
+            Some((_, OpEnd::Finished)) => format!("finished {:.1}s", secs),
+            Some((_, OpEnd::Cancelled)) => format!("cancelled after {:.1}s", secs),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Inner {
+    next_id: OpId,
+    ops: Vec<Operation>,
+    cancel: HashMap<OpId, Vec<CancelFn>>,
+}
+
+/// The operations of one connection. Clones share the same list.
+#[derive(Clone, Default)]
+pub struct ActivityRegistry {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl std::fmt::Debug for ActivityRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActivityRegistry")
+            .field("running", &self.running())
+            .finish()
+    }
+}
+
+impl ActivityRegistry {
+    fn lock(&self) -> MutexGuard<'_, Inner> {
+        // A panic while holding the lock leaves the list usable
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Register an operation; it runs until the guard is dropped.
+    pub fn begin(&self, label: impl Into<String>) -> OpGuard {
+        let mut inner = self.lock();
+        inner.next_id += 1;
+        let id = inner.next_id;
+        inner.ops.push(Operation {
+            id,
+            label: label.into(),
+            started: Instant::now(),
+            ended: None,
+        });
+        OpGuard {
+            registry: self.clone(),
+            id,
+        }
+    }
+
+    /// Run `cancel` if the operation is cancelled. Ignored once it ended.
+    pub fn on_cancel(&self, id: OpId, cancel: CancelFn) {
+        let mut inner = self.lock();
+        if inner.ops.iter().any(|op| op.id == id && op.is_running()) {
+            inner.cancel.entry(id).or_default().push(cancel);
+        }
+    }
+
+    /// Cancel a running operation. Returns whether it was running.
+    pub fn cancel(&self, id: OpId) -> bool {
+        let callbacks = {
+            let mut inner = self.lock();
+            let Some(op) = inner
+                .ops
+                .iter_mut()
+                .find(|op| op.id == id && op.is_running())
+            else {
+                return false;
+            };
+            op.ended = Some((Instant::now(), OpEnd::Cancelled));
+            inner.cancel.remove(&id).unwrap_or_default()
+        };
+        // Outside the lock: a callback may end other operations
+        for cancel in callbacks {
+            cancel();
+        }
+        true
+    }
+
+    /// Cancel every running operation. Returns how many there were.
+    pub fn cancel_all(&self) -> usize {
+        let running: Vec<OpId> = self
+            .lock()
+            .ops
+            .iter()
+            .filter(|op| op.is_running())
+            .map(|op| op.id)
+            .collect();
+        running.into_iter().filter(|&id| self.cancel(id)).count()
+    }
+
+    /// Running operations, oldest first, then those that ended less than
+    /// [`LINGER`] before `now`. Older ones are dropped.
+    pub fn list(&self, now: Instant) -> Vec<Operation> {
+        let mut inner = self.lock();
+        inner.ops.retain(|op| match op.ended {
+            None => true,
+            Some((at, _)) => now.saturating_duration_since(at) < LINGER,
+        });
+        let (mut running, ended): (Vec<_>, Vec<_>) =
+            inner.ops.iter().cloned().partition(|op| op.is_running());
+        running.extend(ended);
+        running
+    }
+
+    pub fn running(&self) -> usize {
+        self.lock().ops.iter().filter(|op| op.is_running()).count()
+    }
+
+    pub fn is_busy(&self) -> bool {
+        self.running() > 0
+    }
+
+    /// The most recently started operation still running.
+    pub fn newest_running(&self) -> Option<Operation> {
+        self.lock()
+            .ops
+            .iter()
+            .rev()
+            .find(|op| op.is_running())
+            .cloned()
+    }
+
+    fn finish(&self, id: OpId) {
+        let mut inner = self.lock();
+        inner.cancel.remove(&id);
+        if let Some(op) = inner
+            .ops
+            .iter_mut()
+            .find(|op| op.id == id && op.is_running())
+        {
+            op.ended = Some((Instant::now(), OpEnd::Finished));
+        }
+    }
+}
+
+/// Held by a running operation; dropping it marks the operation finished.
+pub struct OpGuard {
+    registry: ActivityRegistry,
+    id: OpId,
+}
+
+impl OpGuard {
+    pub fn id(&self) -> OpId {
+        self.id
+    }
+}
+
+impl Drop for OpGuard {
+    fn drop(&mut self) {
+        self.registry.finish(self.id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_register_and_finish() {
+        let registry = ActivityRegistry::default();
+        let search = registry.begin("Search (cn=*)");
+        let modify = registry.begin("Modify cn=alice");
+        assert_eq!(registry.running(), 2);
+
+        drop(search);
+        assert_eq!(registry.running(), 1);
+        let now = Instant::now();
+        let list = registry.list(now);
+        // Running first, then the one that just finished
+        assert_eq!(list[0].label, "Modify cn=alice");
+        assert!(list[1].describe(now).starts_with("finished "));
+        drop(modify);
+        assert!(!registry.is_busy());
+    }
+
+    #[test]
+    fn test_cancel_runs_callbacks_once() {
+        let registry = ActivityRegistry::default();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let op = registry.begin("Search");
+        for _ in 0..2 {
+            let calls = calls.clone();
+            registry.on_cancel(
+                op.id(),
+                Box::new(move || {
+                    calls.fetch_add(1, Ordering::Relaxed);
+                }),
+            );
+        }
+
+        assert!(registry.cancel(op.id()));
+        assert_eq!(calls.load(Ordering::Relaxed), 2);
+        assert!(!registry.cancel(op.id()), "already cancelled");
+        // The task ending afterwards keeps it listed as cancelled
+        let id = op.id();
+        drop(op);
+        let list = registry.list(Instant::now());
+        assert_eq!(list[0].id, id);
+        assert_eq!(list[0].ended.map(|(_, end)| end), Some(OpEnd::Cancelled));
+
+        // Callbacks for an operation that already ended are dropped
+        let done = registry.begin("Modify");
+        let done_id = done.id();
+        drop(done);
+        let calls_before = calls.load(Ordering::Relaxed);
+        let counter = calls.clone();
+        registry.on_cancel(
+            done_id,
+            Box::new(move || {
+                counter.fetch_add(1, Ordering::Relaxed);
+            }),
+        );
+        assert!(!registry.cancel(done_id));
+        assert_eq!(calls.load(Ordering::Relaxed), calls_before);
+    }
+
+    #[test]
+    fn test_cancel_all() {
+        let registry = ActivityRegistry::default();
+        let _a = registry.begin("a");
+        let _b = registry.begin("b");
+        drop(registry.begin("c"));
+        assert_eq!(registry.cancel_all(), 2);
+        assert_eq!(registry.running(), 0);
+    }
+
+    #[test]
+    fn test_newest_running() {
+        let registry = ActivityRegistry::default();
+        assert!(registry.newest_running().is_none());
+        let a = registry.begin("a");
+        let b = registry.begin("b");
+        drop(registry.begin("c"));
+        assert_eq!(registry.newest_running().unwrap().id, b.id());
+        drop(b);
+        assert_eq!(registry.newest_running().unwrap().id, a.id());
+    }
+
+    #[test]
+    fn test_finished_operations_age_out() {
+        let registry = ActivityRegistry::default();
+        let running = registry.begin("Search");
+        drop(registry.begin("Modify"));
+
+        let now = Instant::now();
+        assert_eq!(registry.list(now).len(), 2);
+        let later = now + LINGER + Duration::from_millis(1);
+        let list = registry.list(later);
+        assert_eq!(list.len(), 1);
+        assert_eq!(list[0].id, running.id());
+        assert!(list[0].elapsed(later) > LINGER);
+    }
+}
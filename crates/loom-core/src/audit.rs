@@ -0,0 +1,412 @@
+//! Audit log of the writes sent to a directory.
+//!
+//! Every modify, add, delete, modrdn and password set a connection sends
+//! is recorded once the server has answered: when, on which profile and
+//! as whom, the target DN, what was asked and how it went. Password
+//! records name the attributes only, never the values. Records go to an
+//! [`AuditSink`]; [`AuditFile`] appends them to a file, one per line, and
+//! rotates it by size.
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use ldap3::Mod;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::connection::LdapConnection;
+use crate::error::CoreError;
+use crate::redact::is_password_attr;
+
+/// Values longer than this are cut in modify summaries.
+const MAX_VALUE_CHARS: usize = 64;
+
+/// How records are written to the audit log file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditFormat {
+    /// One tab-separated line per record.
+    #[default]
+    Text,
+    /// One JSON object per line.
+    Jsonl,
+}
+
+impl AuditFormat {
+    pub fn is_default(&self) -> bool {
+        *self == AuditFormat::default()
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            AuditFormat::Text => "text",
+            AuditFormat::Jsonl => "jsonl",
+        }
+    }
+}
+
+/// One write and how the server answered it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct AuditRecord {
+    pub timestamp: DateTime<Utc>,
+    pub profile: String,
+    /// Who the connection is bound as, when the server said.
+    pub identity: Option<String>,
+    pub dn: String,
+    /// What was asked, e.g. `modify: replace title=Engineer`.
+    pub operation: String,
+    pub success: bool,
+    /// The error the write failed with; empty on success.
+    pub message: String,
+}
+
+impl AuditRecord {
+    /// The record as a line of `format`, without the newline.
+    pub fn to_line(&self, format: AuditFormat) -> String {
+        match format {
+            AuditFormat::Text => [
+                self.timestamp.to_rfc3339_opts(SecondsFormat::Secs, true),
+                self.profile.clone(),
+                self.identity.clone().unwrap_or_else(|| "-".to_string()),
+                self.dn.clone(),
+                self.operation.clone(),
+                if self.success {
+                    "ok".to_string()
+                } else {
+                    format!("failed: {}", self.message)
+                },
+            ]
+            .map(|field| field.replace(['\t', '\n'], " "))
+            .join("\t"),
+            AuditFormat::Jsonl => serde_json::to_string(self).unwrap_or_default(),
+        }
+    }
+}
+
+/// Where audit records go.
+pub trait AuditSink: Send + Sync {
+    fn record(&self, record: &AuditRecord) -> io::Result<()>;
+}
+
+/// When the audit log file is rotated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rotation {
+    /// The file is rotated before a record would take it past this size.
+    pub max_bytes: u64,
+    /// Rotated files kept, `audit.log.1` being the newest; 0 keeps none.
+    pub keep: usize,
+}
+
+/// An audit log file records are appended to.
+pub struct AuditFile {
+    path: PathBuf,
+    format: AuditFormat,
+    rotation: Rotation,
+    /// Held while a record is written, so lines from several connections
+    /// do not interleave with a rotation.
+    lock: Mutex<()>,
+}
+
+impl AuditFile {
+    pub fn new(path: PathBuf, format: AuditFormat, rotation: Rotation) -> Self {
+        Self {
+            path,
+            format,
+            rotation,
+            lock: Mutex::new(()),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn format(&self) -> AuditFormat {
+        self.format
+    }
+
+    /// `audit.log.n`, the `n`th newest rotated file.
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.as_os_str().to_owned();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    /// Shift the rotated files up by one, dropping the oldest, and move
+    /// the current file to `.1`.
+    fn rotate(&self) -> io::Result<()> {
+        let keep = self.rotation.keep;
+        if keep == 0 {
+            return fs::remove_file(&self.path);
+        }
+        let oldest = self.rotated(keep);
+        if oldest.exists() {
+            fs::remove_file(&oldest)?;
+        }
+        for n in (1..keep).rev() {
+            let from = self.rotated(n);
+            if from.exists() {
+                fs::rename(&from, self.rotated(n + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated(1))
+    }
+}
+
+impl AuditSink for AuditFile {
+    fn record(&self, record: &AuditRecord) -> io::Result<()> {
+        let _guard = self.lock.lock().unwrap_or_else(|e| e.into_inner());
+        let line = format!("{}\n", record.to_line(self.format));
+        let size = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if size > 0 && size + line.len() as u64 > self.rotation.max_bytes {
+            self.rotate()?;
+        }
+        if let Some(dir) = self.path.parent().filter(|d| !d.as_os_str().is_empty()) {
+            fs::create_dir_all(dir)?;
+        }
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?
+            .write_all(line.as_bytes())
+    }
+}
+
+/// Records a connection's writes: the sink, and the profile and identity
+/// each record carries.
+#[derive(Clone)]
+pub struct Auditor {
+    sink: Arc<dyn AuditSink>,
+    profile: String,
+    identity: Option<String>,
+}
+
+impl Auditor {
+    pub fn new(sink: Arc<dyn AuditSink>, profile: String, identity: Option<String>) -> Self {
+        Self {
+            sink,
+            profile,
+            identity,
+        }
+    }
+
+    /// Record `operation` on `dn` with its outcome. A record that cannot
+    /// be written is logged, not failed: the write already happened.
+    pub fn record(&self, dn: &str, operation: String, result: &Result<(), CoreError>) {
+        let record = AuditRecord {
+            timestamp: Utc::now(),
+            profile: self.profile.clone(),
+            identity: self.identity.clone(),
+            dn: dn.to_string(),
+            operation,
+            success: result.is_ok(),
+            message: result
+                .as_ref()
+                .err()
+                .map(|e| e.to_string())
+                .unwrap_or_default(),
+        };
+        if let Err(e) = self.sink.record(&record) {
+            warn!("Audit record for {} not written: {}", dn, e);
+        }
+    }
+}
+
+impl LdapConnection {
+    /// Record a write with the connection's auditor, if it has one.
+    /// `operation` is only worked out when it does.
+    pub(crate) fn audit_write(
+        &self,
+        dn: &str,
+        operation: impl FnOnce() -> String,
+        result: &Result<(), CoreError>,
+    ) {
+        if let Some(auditor) = &self.audit {
+            auditor.record(dn, operation(), result);
+        }
+    }
+}
+
+/// `modify: replace title=Engineer; delete mail`, with the values of
+/// password attributes left out and long values cut.
+pub fn modify_summary(mods: &[Mod<String>]) -> String {
+    let parts: Vec<String> = mods
+        .iter()
+        .map(|m| {
+            let (op, attr, values) = match m {
+                Mod::Add(attr, values) => ("add", attr, values),
+                Mod::Delete(attr, values) => ("delete", attr, values),
+                Mod::Replace(attr, values) => ("replace", attr, values),
+                Mod::Increment(attr, value) => {
+                    return format!("increment {} by {}", attr, value);
+                }
+            };
+            if values.is_empty() || is_password_attr(attr) {
+                return format!("{} {}", op, attr);
+            }
+            let mut values: Vec<String> = values.iter().map(|v| shorten(v)).collect();
+            values.sort();
+            format!("{} {}={}", op, attr, values.join(", "))
+        })
+        .collect();
+    format!("modify: {}", parts.join("; "))
+}
+
+/// `add: objectClass, cn, sn`: the attributes of a new entry.
+pub fn add_summary<'a>(names: impl IntoIterator<Item = &'a String>) -> String {
+    let names: Vec<&str> = names.into_iter().map(String::as_str).collect();
+    format!("add: {}", names.join(", "))
+}
+
+/// `modrdn: cn=New (old RDN deleted), under ou=Other`.
+pub fn rename_summary(new_rdn: &str, delete_old_rdn: bool, new_superior: Option<&str>) -> String {
+    let mut summary = format!("modrdn: {}", new_rdn);
+    if delete_old_rdn {
+        summary.push_str(" (old RDN deleted)");
+    }
+    if let Some(parent) = new_superior {
+        summary.push_str(&format!(", under {}", parent));
+    }
+    summary
+}
+
+fn shorten(value: &str) -> String {
+    if value.chars().count() <= MAX_VALUE_CHARS {
+        return value.to_string();
+    }
+    let cut: String = value.chars().take(MAX_VALUE_CHARS).collect();
+    format!("{}...", cut)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    use chrono::TimeZone;
+
+    use crate::testing::MemoryAudit;
+
+    fn record(success: bool) -> AuditRecord {
+        AuditRecord {
+            timestamp: Utc.with_ymd_and_hms(2026, 3, 2, 9, 30, 0).unwrap(),
+            profile: "Prod AD".to_string(),
+            identity: Some("u:CONTOSO\\admin".to_string()),
+            dn: "cn=Lisa Park,dc=contoso,dc=com".to_string(),
+            operation: "modify: replace title=Engineer".to_string(),
+            success,
+            message: if success {
+                String::new()
+            } else {
+                "Modify failed rc=50: insufficient access".to_string()
+            },
+        }
+    }
+
+    #[test]
+    fn test_record_lines() {
+        assert_eq!(
+            record(true).to_line(AuditFormat::Text),
+            "2026-03-02T09:30:00Z\tProd AD\tu:CONTOSO\\admin\tcn=Lisa Park,dc=contoso,dc=com\t\
+             modify: replace title=Engineer\tok"
+        );
+        assert!(record(false)
+            .to_line(AuditFormat::Text)
+            .ends_with("\tfailed: Modify failed rc=50: insufficient access"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&record(false).to_line(AuditFormat::Jsonl)).unwrap();
+        assert_eq!(json["timestamp"], "2026-03-02T09:30:00Z");
+        assert_eq!(json["profile"], "Prod AD");
+        assert_eq!(json["success"], false);
+        assert_eq!(json["message"], "Modify failed rc=50: insufficient access");
+    }
+
+    #[test]
+    fn test_summaries_keep_passwords_out() {
+        let mods = vec![
+            Mod::Replace(
+                "userPassword".to_string(),
+                HashSet::from(["hunter2".to_string()]),
+            ),
+            Mod::Add("mail".to_string(), HashSet::from(["a@b.c".to_string()])),
+            Mod::Delete("description".to_string(), HashSet::new()),
+            Mod::Replace("jpegPhoto".to_string(), HashSet::from(["x".repeat(100)])),
+        ];
+        let summary = modify_summary(&mods);
+        assert!(!summary.contains("hunter2"));
+        assert!(summary.starts_with(
+            "modify: replace userPassword; add mail=a@b.c; delete description; replace jpegPhoto="
+        ));
+        assert!(summary.ends_with(&format!("{}...", "x".repeat(MAX_VALUE_CHARS))));
+
+        assert_eq!(
+            add_summary(&["objectClass".to_string(), "unicodePwd".to_string()]),
+            "add: objectClass, unicodePwd"
+        );
+        assert_eq!(
+            rename_summary("cn=New", true, Some("ou=Other,dc=example,dc=com")),
+            "modrdn: cn=New (old RDN deleted), under ou=Other,dc=example,dc=com"
+        );
+    }
+
+    #[test]
+    fn test_auditor_records_outcome() {
+        let sink = Arc::new(MemoryAudit::default());
+        let auditor = Auditor::new(sink.clone(), "Lab".to_string(), None);
+        auditor.record("cn=a,dc=example,dc=com", "delete".to_string(), &Ok(()));
+        auditor.record(
+            "cn=b,dc=example,dc=com",
+            "delete".to_string(),
+            &Err(CoreError::DeleteFailed(
+                "rc=66: not allowed on non-leaf".to_string(),
+            )),
+        );
+        let records = sink.records();
+        assert_eq!(records.len(), 2);
+        assert!(records[0].success && records[0].message.is_empty());
+        assert_eq!(records[1].profile, "Lab");
+        assert!(!records[1].success);
+        assert!(records[1].message.contains("not allowed on non-leaf"));
+    }
+
+    #[test]
+    fn test_file_rotates_by_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("audit.log");
+        let line_len = record(true).to_line(AuditFormat::Text).len() as u64 + 1;
+        let log = AuditFile::new(
+            path.clone(),
+            AuditFormat::Text,
+            Rotation {
+                max_bytes: line_len * 2,
+                keep: 2,
+            },
+        );
+        for _ in 0..7 {
+            log.record(&record(true)).unwrap();
+        }
+        // Two records a file: the current one and two rotated, the rest gone
+        let lines = |p: &Path| fs::read_to_string(p).unwrap().lines().count();
+        assert_eq!(lines(&path), 1);
+        assert_eq!(lines(&log.rotated(1)), 2);
+        assert_eq!(lines(&log.rotated(2)), 2);
+        assert!(!log.rotated(3).exists());
+
+        // Keeping none starts the file over
+        let log = AuditFile::new(
+            path.clone(),
+            AuditFormat::Jsonl,
+            Rotation {
+                max_bytes: 1,
+                keep: 0,
+            },
+        );
+        log.record(&record(false)).unwrap();
+        log.record(&record(false)).unwrap();
+        assert_eq!(lines(&path), 1);
+    }
+}
@@ -1,10 +1,18 @@
-use crate::connection::LdapConnection;
+use ldap3::exop::WhoAmI;
+
+use crate::connection::{AuthMethod, LdapConnection};
 use crate::error::CoreError;
-use tracing::{error, info};
+use crate::redact;
+use tracing::{debug, error, info};
 
 impl LdapConnection {
     /// Perform a simple bind with the given DN and password.
     pub async fn simple_bind(&mut self, bind_dn: &str, password: &str) -> Result<(), CoreError> {
+        debug!(
+            "simple_bind dn={} password={}",
+            bind_dn,
+            redact::secret(password)
+        );
         let result = self
             .ldap
             .simple_bind(bind_dn, password)
@@ -46,4 +54,134 @@ impl LdapConnection {
         info!("Bound anonymously");
         Ok(())
     }
+
+    /// Authenticate the way the profile asks: a simple bind with
+    /// `bind_dn` and `password` (anonymous without a DN), SASL EXTERNAL
+    /// or GSSAPI. The DN and password are ignored by the SASL methods.
+    pub async fn authenticate(
+        &mut self,
+        bind_dn: Option<&str>,
+        password: &str,
+    ) -> Result<(), CoreError> {
+        match (self.settings.auth, bind_dn) {
+            (AuthMethod::Simple, Some(dn)) => self.simple_bind(dn, password).await,
+            (AuthMethod::Simple, None) => self.anonymous_bind().await,
+            (AuthMethod::SaslExternal, _) => self.sasl_external_bind().await,
+            (AuthMethod::Gssapi, _) => self.gssapi_bind().await,
+        }
+    }
+
+    /// Bind with SASL EXTERNAL, letting the server take the identity from
+    /// the TLS client certificate.
+    pub async fn sasl_external_bind(&mut self) -> Result<(), CoreError> {
+        if self.settings.tls_options.client_cert.is_none() {
+            return Err(CoreError::BindFailed(
+                "SASL EXTERNAL needs a client certificate; set one in the profile".to_string(),
+            ));
+        }
+        let result = self
+            .ldap
+            .sasl_external_bind()
+            .await
+            .map_err(CoreError::Ldap)?;
+
+        if result.rc != 0 {
+            error!(
+                "SASL EXTERNAL bind failed: rc={}, {}",
+                result.rc, result.text
+            );
+            return Err(CoreError::BindFailed(format!(
+                "SASL EXTERNAL bind returned rc={}: {}",
+                result.rc, result.text
+            )));
+        }
+
+        info!("Bound with SASL EXTERNAL");
+        Ok(())
+    }
+
+    /// Bind with SASL GSSAPI from the ambient Kerberos credential cache,
+    /// asking for a ticket to `ldap/<host>`.
+    #[cfg(feature = "gssapi")]
+    pub async fn gssapi_bind(&mut self) -> Result<(), CoreError> {
+        let host = self.settings.host.clone();
+        let result = self.ldap.sasl_gssapi_bind(&host).await.map_err(|e| {
+            error!("GSSAPI bind to {} failed: {}", host, e);
+            CoreError::BindFailed(format!(
+                "GSSAPI bind failed: {}; check for a Kerberos ticket with klist, or run kinit",
+                e
+            ))
+        })?;
+
+        if result.rc != 0 {
+            error!("GSSAPI bind failed: rc={}, {}", result.rc, result.text);
+            return Err(CoreError::BindFailed(format!(
+                "GSSAPI bind returned rc={}: {}",
+                result.rc, result.text
+            )));
+        }
+
+        info!("Bound with GSSAPI to {}", host);
+        Ok(())
+    }
+
+    /// Profiles asking for GSSAPI are refused before connecting when the
+    /// `gssapi` feature is off; see [`AuthMethod::available`].
+    #[cfg(not(feature = "gssapi"))]
+    pub async fn gssapi_bind(&mut self) -> Result<(), CoreError> {
+        Err(CoreError::BindFailed(
+            AuthMethod::Gssapi.available().unwrap_err(),
+        ))
+    }
+
+    /// Ask the server who the connection is bound as (RFC 4532).
+    /// Returns `None` for an anonymous connection or when the server
+    /// does not support the operation.
+    pub async fn whoami(&mut self) -> Result<Option<String>, CoreError> {
+        let (exop, res) = self
+            .ldap
+            .extended(WhoAmI)
+            .await
+            .map_err(CoreError::Ldap)?
+            .success()
+            .map_err(|e| {
+                debug!("WhoAmI failed: {}", e);
+                CoreError::Ldap(e)
+            })?;
+        debug!("WhoAmI result rc={} text={}", res.rc, res.text);
+        let authzid = exop
+            .val
+            .map(|val| String::from_utf8_lossy(&val).into_owned())
+            .unwrap_or_default();
+        Ok(identity_from_authzid(&authzid))
+    }
+}
+
+/// Strip the `dn:` or `u:` prefix from a WhoAmI authorization identity.
+fn identity_from_authzid(authzid: &str) -> Option<String> {
+    let id = authzid
+        .strip_prefix("dn:")
+        .or_else(|| authzid.strip_prefix("u:"))
+        .unwrap_or(authzid)
+        .trim();
+    (!id.is_empty()).then(|| id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identity_from_authzid() {
+        assert_eq!(
+            identity_from_authzid("dn:cn=admin,dc=example,dc=com").as_deref(),
+            Some("cn=admin,dc=example,dc=com")
+        );
+        assert_eq!(
+            identity_from_authzid("u:jdoe@EXAMPLE.COM").as_deref(),
+            Some("jdoe@EXAMPLE.COM")
+        );
+        assert_eq!(identity_from_authzid(""), None);
+        assert_eq!(identity_from_authzid("dn:"), None);
+    }
 }
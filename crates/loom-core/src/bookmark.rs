@@ -0,0 +1,165 @@
+//! Bookmarks: saved DNs and saved searches, kept per connection profile.
+
+use serde::{Deserialize, Serialize};
+
+use crate::Scope;
+
+/// Search scope as written in the config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SearchScope {
+    Base,
+    One,
+    #[default]
+    Sub,
+}
+
+impl SearchScope {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SearchScope::Base => "base",
+            SearchScope::One => "one",
+            SearchScope::Sub => "sub",
+        }
+    }
+}
+
+impl From<Scope> for SearchScope {
+    fn from(scope: Scope) -> Self {
+        match scope {
+            Scope::Base => SearchScope::Base,
+            Scope::OneLevel => SearchScope::One,
+            Scope::Subtree => SearchScope::Sub,
+        }
+    }
+}
+
+impl From<SearchScope> for Scope {
+    fn from(scope: SearchScope) -> Self {
+        match scope {
+            SearchScope::Base => Scope::Base,
+            SearchScope::One => Scope::OneLevel,
+            SearchScope::Sub => Scope::Subtree,
+        }
+    }
+}
+
+/// A search with every parameter spelled out, as the search form runs it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub base: String,
+    #[serde(default)]
+    pub scope: SearchScope,
+    pub filter: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<String>,
+}
+
+impl SavedSearch {
+    /// Attributes to request; all user attributes when none were saved.
+    pub fn attrs(&self) -> Vec<String> {
+        if self.attributes.is_empty() {
+            vec!["*".to_string()]
+        } else {
+            self.attributes.clone()
+        }
+    }
+}
+
+/// What a bookmark leads to.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BookmarkTarget {
+    /// An entry, revealed in the tree.
+    Dn(String),
+    /// A search, run again when picked.
+    Search(SavedSearch),
+}
+
+impl BookmarkTarget {
+    /// One-line description, e.g. the DN or "sub (uid=*) under dc=example".
+    pub fn summary(&self) -> String {
+        match self {
+            BookmarkTarget::Dn(dn) => dn.clone(),
+            BookmarkTarget::Search(search) => format!(
+                "{} {} under {}",
+                search.scope.label(),
+                search.filter,
+                search.base
+            ),
+        }
+    }
+}
+
+/// A named bookmark. In the config file bookmarks are grouped under
+/// their profile's name, so `profile` is only filled in when they are
+/// listed with [`AppConfig::bookmarks`](crate::config::AppConfig::bookmarks).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bookmark {
+    pub name: String,
+    #[serde(skip)]
+    pub profile: String,
+    #[serde(flatten)]
+    pub target: BookmarkTarget,
+}
+
+impl Bookmark {
+    pub fn new(
+        name: impl Into<String>,
+        profile: impl Into<String>,
+        target: BookmarkTarget,
+    ) -> Self {
+        Self {
+            name: name.into(),
+            profile: profile.into(),
+            target,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bookmarks_round_trip_through_toml() {
+        #[derive(Debug, Serialize, Deserialize)]
+        struct File {
+            bookmarks: Vec<Bookmark>,
+        }
+        let toml_str = r#"
+[[bookmarks]]
+name = "admins"
+dn = "cn=admins,ou=groups,dc=example,dc=com"
+
+[[bookmarks]]
+name = "locked"
+
+[bookmarks.search]
+base = "ou=people,dc=example,dc=com"
+scope = "one"
+filter = "(pwdAccountLockedTime=*)"
+attributes = ["uid", "cn"]
+"#;
+        let file: File = toml::from_str(toml_str).unwrap();
+        assert_eq!(
+            file.bookmarks[0].target,
+            BookmarkTarget::Dn("cn=admins,ou=groups,dc=example,dc=com".to_string())
+        );
+        let BookmarkTarget::Search(search) = &file.bookmarks[1].target else {
+            panic!("expected a search bookmark");
+        };
+        assert_eq!(search.scope, SearchScope::One);
+        assert_eq!(Scope::from(search.scope), Scope::OneLevel);
+        assert_eq!(search.attrs(), ["uid", "cn"]);
+        assert_eq!(
+            file.bookmarks[1].target.summary(),
+            "one (pwdAccountLockedTime=*) under ou=people,dc=example,dc=com"
+        );
+
+        let serialized = toml::to_string_pretty(&file).unwrap();
+        assert!(!serialized.contains("profile"));
+        let reloaded: File = toml::from_str(&serialized).unwrap();
+        assert_eq!(reloaded.bookmarks, file.bookmarks);
+    }
+}
@@ -5,12 +5,17 @@ use tracing::{debug, info};
 
 use crate::connection::LdapConnection;
 use crate::error::CoreError;
+#[cfg(feature = "scripting")]
+use crate::script::{plan_scripted_update, EntryScript, ScriptedOp};
+use crate::transaction::Change;
 
 /// A single bulk modification operation.
 #[derive(Debug, Clone)]
 pub enum BulkMod {
     /// Replace all values of an attribute with a new value.
     ReplaceAttribute { attr: String, value: String },
+    /// Replace all values of an attribute; no values removes it.
+    ReplaceValues { attr: String, values: Vec<String> },
     /// Add a value to an attribute.
     AddValue { attr: String, value: String },
     /// Delete all values of an attribute.
@@ -28,6 +33,43 @@ pub struct BulkResult {
     pub errors: Vec<(String, String)>, // (dn, error_message)
 }
 
+/// One modification applied to each entry of a selection.
+#[derive(Debug, Clone)]
+pub enum BatchChange {
+    /// Modify every selected entry itself.
+    Entry(BulkMod),
+    /// Add every selected entry's DN to `attr` of the group `group_dn`,
+    /// so membership is kept on the group side.
+    AddToGroup { group_dn: String, attr: String },
+}
+
+impl BatchChange {
+    /// The DN to modify for the selected entry `dn`, and how.
+    pub fn for_entry(&self, dn: &str) -> (String, Vec<BulkMod>) {
+        match self {
+            BatchChange::Entry(modification) => (dn.to_string(), vec![modification.clone()]),
+            BatchChange::AddToGroup { group_dn, attr } => (
+                group_dn.clone(),
+                vec![BulkMod::AddValue {
+                    attr: attr.clone(),
+                    value: dn.to_string(),
+                }],
+            ),
+        }
+    }
+
+    /// The modify records applying this to every DN in `dns`, one per
+    /// entry, e.g. for an LDIF changefile.
+    pub fn changes(&self, dns: &[String]) -> Vec<Change> {
+        dns.iter()
+            .map(|dn| {
+                let (target, mods) = self.for_entry(dn);
+                Change::modify(&target, &mods)
+            })
+            .collect()
+    }
+}
+
 impl LdapConnection {
     /// Execute a bulk update: search for entries matching the filter,
     /// then apply the modifications to each.
@@ -40,25 +82,61 @@ impl LdapConnection {
         let base_dn = self.base_dn.clone();
         let entries = self.search_subtree(&base_dn, filter, &["dn"]).await?;
 
-        let total = entries.len();
-        info!("Bulk update: {} entries match filter '{}'", total, filter);
+        info!(
+            "Bulk update: {} entries match filter '{}'",
+            entries.len(),
+            filter
+        );
+
+        let changes: Vec<(String, Vec<BulkMod>)> = entries
+            .into_iter()
+            .map(|entry| (entry.dn, modifications.to_vec()))
+            .collect();
+        Ok(self.bulk_apply(&changes).await)
+    }
 
+    /// Compute the new values of `attr` per entry with a script, then apply
+    /// them. Entries whose script fails are counted as failed and reported
+    /// in `errors`; the rest of the batch still runs.
+    #[cfg(feature = "scripting")]
+    pub async fn bulk_update_scripted(
+        &mut self,
+        filter: &str,
+        attr: &str,
+        op: ScriptedOp,
+        script: &EntryScript,
+    ) -> Result<BulkResult, CoreError> {
+        let base_dn = self.base_dn.clone();
+        let entries = self.search_subtree(&base_dn, filter, &["*"]).await?;
+        info!(
+            "Scripted bulk update: {} entries match filter '{}'",
+            entries.len(),
+            filter
+        );
+
+        let plan = plan_scripted_update(&entries, attr, op, script);
+        let mut result = self.bulk_apply(&plan.changes).await;
+        result.total = entries.len();
+        result.failed += plan.errors.len();
+        result.errors.extend(plan.errors);
+        Ok(result)
+    }
+
+    /// Apply a separate list of modifications to each DN, one modify
+    /// request per entry. Failures are collected rather than stopping.
+    /// Protected entries are never modified here and count as failures.
+    pub async fn bulk_apply(&mut self, changes: &[(String, Vec<BulkMod>)]) -> BulkResult {
+        let total = changes.len();
         let mut succeeded = 0;
         let mut failed = 0;
         let mut errors = Vec::new();
 
-        for entry in &entries {
-            let mods = build_ldap_mods(modifications);
-
-            match self.modify_entry(&entry.dn, mods).await {
-                Ok(()) => {
-                    succeeded += 1;
-                    debug!("Bulk modified: {}", entry.dn);
-                }
+        for (dn, modifications) in changes {
+            match self.apply_one(dn, modifications).await {
+                Ok(()) => succeeded += 1,
                 Err(e) => {
                     failed += 1;
-                    errors.push((entry.dn.clone(), e.to_string()));
-                    debug!("Bulk modify failed for {}: {}", entry.dn, e);
+                    errors.push((dn.clone(), e));
                 }
             }
         }
@@ -68,17 +146,68 @@ impl LdapConnection {
             succeeded, failed, total
         );
 
-        Ok(BulkResult {
+        BulkResult {
             total,
             succeeded,
             failed,
             errors,
-        })
+        }
+    }
+}
+
+impl LdapConnection {
+    /// Apply `change` to each selected entry with one modify request per
+    /// entry. A failure is reported against the selected entry's DN, even
+    /// when the group was the entry modified, and the rest still run.
+    pub async fn batch_modify(&mut self, dns: &[String], change: &BatchChange) -> BulkResult {
+        let total = dns.len();
+        let mut succeeded = 0;
+        let mut errors = Vec::new();
+
+        for dn in dns {
+            let (target, modifications) = change.for_entry(dn);
+            match self.apply_one(&target, &modifications).await {
+                Ok(()) => succeeded += 1,
+                Err(e) => errors.push((dn.clone(), e)),
+            }
+        }
+
+        info!(
+            "Batch modify complete: {} succeeded, {} failed out of {}",
+            succeeded,
+            errors.len(),
+            total
+        );
+
+        BulkResult {
+            total,
+            succeeded,
+            failed: errors.len(),
+            errors,
+        }
+    }
+
+    /// Send one modify request, unless the entry is protected.
+    async fn apply_one(&mut self, dn: &str, modifications: &[BulkMod]) -> Result<(), String> {
+        if let Some(pattern) = self.protected.check(dn) {
+            debug!("Bulk modify skipped protected entry {}", dn);
+            return Err(format!("protected by '{}', skipped", pattern));
+        }
+        match self.modify_entry(dn, build_ldap_mods(modifications)).await {
+            Ok(()) => {
+                debug!("Bulk modified: {}", dn);
+                Ok(())
+            }
+            Err(e) => {
+                debug!("Bulk modify failed for {}: {}", dn, e);
+                Err(e.to_string())
+            }
+        }
     }
 }
 
 /// Convert BulkMod operations to ldap3 Mod operations.
-fn build_ldap_mods(modifications: &[BulkMod]) -> Vec<Mod<String>> {
+pub(crate) fn build_ldap_mods(modifications: &[BulkMod]) -> Vec<Mod<String>> {
     let mut mods = Vec::new();
 
     for m in modifications {
@@ -86,6 +215,9 @@ fn build_ldap_mods(modifications: &[BulkMod]) -> Vec<Mod<String>> {
             BulkMod::ReplaceAttribute { attr, value } => {
                 mods.push(Mod::Replace(attr.clone(), HashSet::from([value.clone()])));
             }
+            BulkMod::ReplaceValues { attr, values } => {
+                mods.push(Mod::Replace(attr.clone(), values.iter().cloned().collect()));
+            }
             BulkMod::AddValue { attr, value } => {
                 mods.push(Mod::Add(attr.clone(), HashSet::from([value.clone()])));
             }
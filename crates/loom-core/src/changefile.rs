@@ -0,0 +1,525 @@
+//! Applying an LDIF changefile record by record. Content records count
+//! as adds. Each record's outcome is kept for the report, and the run
+//! either stops at the first failure or carries on past it. A dry run
+//! writes nothing: it checks that each record's target exists, or for an
+//! add that it does not, following the adds, deletes and renames made
+//! earlier in the file.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::Path;
+
+use ldap3::Scope;
+
+use crate::dn::normalize;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::errors::parse_error_message;
+use crate::export::{export_entries_as, CsvOptions, ExportFormat};
+use crate::import::ldif::{parse_file, LdifRecord};
+use crate::ops::DirectoryOps;
+use crate::protect::ProtectedDns;
+use crate::transaction::{Change, ChangeOutcome};
+
+/// Columns of an exported report, after the DN.
+const REPORT_COLUMNS: [&str; 3] = ["change", "result", "error"];
+
+/// The result code of a search whose base does not exist.
+const NO_SUCH_OBJECT: u32 = 32;
+
+/// DNs listed in a preview.
+const PREVIEW_DNS: usize = 5;
+
+/// Read the records of an LDIF file as changes to apply.
+pub fn read_changefile(path: &Path) -> Result<Vec<Change>, CoreError> {
+    Ok(parse_file(path)?
+        .into_iter()
+        .map(|record| match record {
+            LdifRecord::Content(entry) => Change::add(&entry),
+            LdifRecord::Change(change) => change,
+        })
+        .collect())
+}
+
+/// What a changefile holds, for confirming before it is applied.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangefilePreview {
+    pub adds: usize,
+    pub modifies: usize,
+    pub deletes: usize,
+    pub renames: usize,
+    /// DNs of the first few records.
+    pub first_dns: Vec<String>,
+}
+
+impl ChangefilePreview {
+    pub fn of(changes: &[Change]) -> Self {
+        let mut preview = Self {
+            first_dns: changes
+                .iter()
+                .take(PREVIEW_DNS)
+                .map(|c| c.dn().to_string())
+                .collect(),
+            ..Default::default()
+        };
+        for change in changes {
+            match change {
+                Change::Add { .. } => preview.adds += 1,
+                Change::Modify { .. } => preview.modifies += 1,
+                Change::Delete { .. } => preview.deletes += 1,
+                Change::Rename { .. } => preview.renames += 1,
+            }
+        }
+        preview
+    }
+
+    pub fn total(&self) -> usize {
+        self.adds + self.modifies + self.deletes + self.renames
+    }
+}
+
+/// How a changefile is applied.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ApplyOptions {
+    /// Leave the rest unattempted after the first failure.
+    pub stop_on_error: bool,
+    /// Only check the targets; nothing is written.
+    pub dry_run: bool,
+}
+
+/// The outcome of one record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordResult {
+    pub dn: String,
+    /// What the record does, as [`Change::summary`] puts it.
+    pub change: String,
+    pub outcome: ChangeOutcome,
+}
+
+/// The outcome of [`apply_changefile`], one result per record in order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApplyReport {
+    pub records: Vec<RecordResult>,
+    pub dry_run: bool,
+}
+
+impl ApplyReport {
+    pub fn applied(&self) -> usize {
+        self.count(|o| *o == ChangeOutcome::Applied)
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|o| matches!(o, ChangeOutcome::Failed(_)))
+    }
+
+    pub fn not_attempted(&self) -> usize {
+        self.count(|o| *o == ChangeOutcome::NotAttempted)
+    }
+
+    fn count(&self, f: impl Fn(&ChangeOutcome) -> bool) -> usize {
+        self.records.iter().filter(|r| f(&r.outcome)).count()
+    }
+
+    /// `12 applied, 1 failed, 3 not attempted`, or for a dry run
+    /// `12 would apply, 1 would fail`.
+    pub fn summary(&self) -> String {
+        let mut summary = if self.dry_run {
+            format!(
+                "{} would apply, {} would fail",
+                self.applied(),
+                self.failed()
+            )
+        } else {
+            format!("{} applied, {} failed", self.applied(), self.failed())
+        };
+        if self.not_attempted() > 0 {
+            summary.push_str(&format!(", {} not attempted", self.not_attempted()));
+        }
+        summary
+    }
+
+    /// One row per record, with the columns of [`REPORT_COLUMNS`].
+    pub fn rows(&self) -> Vec<LdapEntry> {
+        self.records
+            .iter()
+            .map(|record| {
+                let (result, error) = match &record.outcome {
+                    ChangeOutcome::Applied if self.dry_run => ("ok", ""),
+                    ChangeOutcome::Applied => ("applied", ""),
+                    ChangeOutcome::Failed(e) => ("failed", e.as_str()),
+                    ChangeOutcome::RolledBack => ("rolled back", ""),
+                    ChangeOutcome::NotAttempted => ("not attempted", ""),
+                };
+                let attributes: BTreeMap<String, Vec<String>> = REPORT_COLUMNS
+                    .iter()
+                    .zip([record.change.as_str(), result, error])
+                    .filter(|(_, v)| !v.is_empty())
+                    .map(|(c, v)| (c.to_string(), vec![v.to_string()]))
+                    .collect();
+                LdapEntry::new(record.dn.clone(), attributes)
+            })
+            .collect()
+    }
+
+    /// Write the report to `path` as CSV, JSON or JSON Lines, by its
+    /// extension. Returns the number of rows written.
+    pub fn export(&self, path: &Path) -> Result<usize, CoreError> {
+        let format = match ExportFormat::from_path(path) {
+            Some(f @ (ExportFormat::Csv | ExportFormat::Json | ExportFormat::JsonLines)) => f,
+            _ => {
+                return Err(CoreError::ExportError(
+                    "Save the report as .csv, .json or .jsonl".to_string(),
+                ))
+            }
+        };
+        let columns: Vec<String> = REPORT_COLUMNS.iter().map(|c| c.to_string()).collect();
+        export_entries_as(self.rows(), path, format, &columns, &CsvOptions::default())
+    }
+}
+
+/// Apply `changes` in order, one request each. Records for protected
+/// entries fail without a request. `progress` gets the number of records
+/// done and the total after each one. Dropping the returned future, e.g.
+/// by aborting its task, stops the run between records.
+pub async fn apply_changefile<F>(
+    ops: &mut dyn DirectoryOps,
+    changes: &[Change],
+    options: ApplyOptions,
+    protected: &ProtectedDns,
+    mut progress: F,
+) -> ApplyReport
+where
+    F: FnMut(usize, usize) + Send,
+{
+    let mut report = ApplyReport {
+        records: Vec::with_capacity(changes.len()),
+        dry_run: options.dry_run,
+    };
+    let mut checker = DryRun::default();
+    let mut stopped = false;
+    for (i, change) in changes.iter().enumerate() {
+        let outcome = if stopped {
+            ChangeOutcome::NotAttempted
+        } else {
+            let result = if let Some(pattern) = protected.check(change.dn()) {
+                Err(format!("protected by '{}'", pattern))
+            } else if options.dry_run {
+                checker.check(ops, change).await
+            } else {
+                change.apply(ops).await.map_err(|e| e.to_string())
+            };
+            match result {
+                Ok(()) => ChangeOutcome::Applied,
+                Err(e) => {
+                    stopped = options.stop_on_error;
+                    ChangeOutcome::Failed(e)
+                }
+            }
+        };
+        report.records.push(RecordResult {
+            dn: change.dn().to_string(),
+            change: change.summary(),
+            outcome,
+        });
+        progress(i + 1, changes.len());
+    }
+    report
+}
+
+/// Entries a dry run has added or removed so far, by normalized DN.
+#[derive(Default)]
+struct DryRun {
+    added: HashSet<String>,
+    removed: HashSet<String>,
+}
+
+impl DryRun {
+    async fn check(&mut self, ops: &mut dyn DirectoryOps, change: &Change) -> Result<(), String> {
+        let dn = change.dn();
+        let exists = self.exists(ops, dn).await?;
+        match change {
+            Change::Add { .. } if exists => return Err("entry already exists".to_string()),
+            Change::Add { .. } => {}
+            _ if !exists => return Err("no such entry".to_string()),
+            Change::Modify { .. } => {}
+            Change::Delete { .. } => self.remove(dn),
+            Change::Rename {
+                new_rdn,
+                new_superior,
+                ..
+            } => {
+                let new_dn = crate::dn::renamed(dn, new_rdn, new_superior.as_deref())
+                    .map_err(|e| e.to_string())?;
+                self.remove(dn);
+                self.add(&new_dn);
+                return Ok(());
+            }
+        }
+        if let Change::Add { .. } = change {
+            self.add(dn);
+        }
+        Ok(())
+    }
+
+    fn add(&mut self, dn: &str) {
+        let key = normalize(dn);
+        self.removed.remove(&key);
+        self.added.insert(key);
+    }
+
+    fn remove(&mut self, dn: &str) {
+        let key = normalize(dn);
+        self.added.remove(&key);
+        self.removed.insert(key);
+    }
+
+    /// Whether `dn` exists once the records so far are applied.
+    async fn exists(&self, ops: &mut dyn DirectoryOps, dn: &str) -> Result<bool, String> {
+        let key = normalize(dn);
+        if self.added.contains(&key) {
+            return Ok(true);
+        }
+        if self.removed.contains(&key) {
+            return Ok(false);
+        }
+        match ops
+            .search(dn, Scope::Base, "(objectClass=*)", &["1.1"])
+            .await
+        {
+            Ok(found) => Ok(!found.is_empty()),
+            Err(e) if parse_error_message(&e.to_string()).code == Some(NO_SUCH_OBJECT) => Ok(false),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulk::BulkMod;
+    use crate::protect::ProtectionMode;
+    use crate::server_detect::ServerType;
+    use crate::testing::fixtures::{domain, org_unit, person};
+    use crate::testing::{MockDirectory, Operation};
+
+    const BASE: &str = "dc=example,dc=com";
+
+    fn directory() -> MockDirectory {
+        MockDirectory::with_entries(vec![
+            domain(BASE),
+            org_unit(BASE, "people"),
+            person(&format!("ou=people,{}", BASE), "Ada", "Lovelace"),
+        ])
+    }
+
+    fn changes() -> Vec<Change> {
+        let people = format!("ou=people,{}", BASE);
+        let grace = person(&people, "Grace", "Hopper");
+        vec![
+            Change::add(&grace),
+            Change::modify(
+                &grace.dn,
+                &[BulkMod::AddValue {
+                    attr: "description".to_string(),
+                    value: "admiral".to_string(),
+                }],
+            ),
+            Change::Delete {
+                dn: format!("cn=Nobody,{}", people),
+            },
+            Change::Delete {
+                dn: format!("uid=ada.lovelace,{}", people),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_preview_counts_and_content_records_as_adds() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("changes.ldif");
+        std::fs::write(
+            &path,
+            "dn: cn=Grace,dc=example,dc=com\nobjectClass: person\ncn: Grace\nsn: Hopper\n\n\
+             dn: cn=Ada,dc=example,dc=com\nchangetype: modrdn\nnewrdn: cn=Augusta\ndeleteoldrdn: 1\n\n\
+             dn: cn=Old,dc=example,dc=com\nchangetype: delete\n",
+        )
+        .unwrap();
+        let changes = read_changefile(&path).unwrap();
+        let preview = ChangefilePreview::of(&changes);
+        assert_eq!((preview.adds, preview.renames, preview.deletes), (1, 1, 1));
+        assert_eq!(preview.total(), 3);
+        assert_eq!(preview.first_dns[0], "cn=Grace,dc=example,dc=com");
+    }
+
+    #[tokio::test]
+    async fn test_binary_values_are_added() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("changes.ldif");
+        std::fs::write(
+            &path,
+            "dn: cn=Grace,ou=people,dc=example,dc=com\nobjectClass: person\ncn: Grace\n\
+             sn: Hopper\njpegPhoto:: /9j/\n\n\
+             dn: cn=Alan,ou=people,dc=example,dc=com\nchangetype: add\nobjectClass: person\n\
+             cn: Alan\nsn: Turing\nobjectGUID:: AAEC/w==\n",
+        )
+        .unwrap();
+        let changes = read_changefile(&path).unwrap();
+        let mut target = directory();
+        let report = apply_changefile(
+            &mut target,
+            &changes,
+            ApplyOptions::default(),
+            &ProtectedDns::default(),
+            |_, _| {},
+        )
+        .await;
+        assert_eq!(report.applied(), 2);
+        let grace = target
+            .entry("cn=Grace,ou=people,dc=example,dc=com")
+            .unwrap();
+        assert_eq!(
+            grace.binary_attributes["jpegPhoto"],
+            vec![vec![0xff, 0xd8, 0xff]]
+        );
+        let alan = target.entry("cn=Alan,ou=people,dc=example,dc=com").unwrap();
+        assert_eq!(
+            alan.binary_attributes["objectGUID"],
+            vec![vec![0, 1, 2, 0xff]]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_continue_past_failures() {
+        let mut dir = directory();
+        let report = apply_changefile(
+            &mut dir,
+            &changes(),
+            ApplyOptions::default(),
+            &ProtectedDns::default(),
+            |_, _| {},
+        )
+        .await;
+        assert_eq!(report.applied(), 3);
+        assert!(matches!(
+            report.records[2].outcome,
+            ChangeOutcome::Failed(_)
+        ));
+        assert_eq!(report.summary(), "3 applied, 1 failed");
+        // The Lovelace delete went through after the failure
+        assert!(dir
+            .entry(&format!("uid=ada.lovelace,ou=people,{}", BASE))
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_stop_on_first_error() {
+        let mut dir = directory();
+        dir.fail_next(Operation::Modify, 50, "insufficient access");
+        let options = ApplyOptions {
+            stop_on_error: true,
+            dry_run: false,
+        };
+        let mut done = Vec::new();
+        let report = apply_changefile(
+            &mut dir,
+            &changes(),
+            options,
+            &ProtectedDns::default(),
+            |d, _| done.push(d),
+        )
+        .await;
+        let outcomes: Vec<&ChangeOutcome> = report.records.iter().map(|r| &r.outcome).collect();
+        assert_eq!(outcomes[0], &ChangeOutcome::Applied);
+        assert!(
+            matches!(outcomes[1], ChangeOutcome::Failed(e) if e.contains("insufficient access"))
+        );
+        assert_eq!(outcomes[2..], [&ChangeOutcome::NotAttempted; 2]);
+        assert_eq!(done, vec![1, 2, 3, 4]);
+        assert_eq!(report.summary(), "1 applied, 1 failed, 2 not attempted");
+    }
+
+    #[tokio::test]
+    async fn test_protected_records_are_skipped() {
+        let mut dir = directory();
+        let protected = ProtectedDns::for_server(
+            &ServerType::Unknown(String::new()),
+            &[format!("uid=ada.lovelace,ou=people,{}", BASE)],
+            ProtectionMode::Confirm,
+        );
+        let report = apply_changefile(
+            &mut dir,
+            &changes(),
+            ApplyOptions::default(),
+            &protected,
+            |_, _| {},
+        )
+        .await;
+        assert!(matches!(
+            &report.records[3].outcome,
+            ChangeOutcome::Failed(e) if e.starts_with("protected by")
+        ));
+        assert!(dir
+            .entry(&format!("uid=ada.lovelace,ou=people,{}", BASE))
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_checks_targets_without_writing() {
+        let mut dir = directory();
+        let mut changes = changes();
+        // Adding an entry that is already there fails, re-adding a deleted one does not
+        changes.push(Change::add(&org_unit(BASE, "people")));
+        changes.push(Change::add(&person(
+            &format!("ou=people,{}", BASE),
+            "Ada",
+            "Lovelace",
+        )));
+        let options = ApplyOptions {
+            stop_on_error: false,
+            dry_run: true,
+        };
+        let report = apply_changefile(
+            &mut dir,
+            &changes,
+            options,
+            &ProtectedDns::default(),
+            |_, _| {},
+        )
+        .await;
+        let failed: Vec<usize> = report
+            .records
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| matches!(r.outcome, ChangeOutcome::Failed(_)))
+            .map(|(i, _)| i)
+            .collect();
+        // The modify follows the add before it
+        assert_eq!(failed, vec![2, 4]);
+        assert_eq!(report.rows()[0].first_value("result"), Some("ok"));
+        assert_eq!(report.summary(), "4 would apply, 2 would fail");
+        // Nothing was written
+        assert!(dir
+            .entry(&format!("uid=grace.hopper,ou=people,{}", BASE))
+            .is_none());
+        assert!(dir
+            .entry(&format!("uid=ada.lovelace,ou=people,{}", BASE))
+            .is_some());
+    }
+
+    #[test]
+    fn test_export_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let report = ApplyReport {
+            records: vec![RecordResult {
+                dn: "cn=x,dc=example,dc=com".to_string(),
+                change: "delete".to_string(),
+                outcome: ChangeOutcome::Failed("no such object".to_string()),
+            }],
+            dry_run: false,
+        };
+        let path = dir.path().join("report.csv");
+        assert_eq!(report.export(&path).unwrap(), 1);
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert!(csv.contains("delete,failed,no such object"));
+        assert!(report.export(&dir.path().join("report.ldif")).is_err());
+    }
+}
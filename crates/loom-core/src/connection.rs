@@ -1,11 +1,44 @@
+use std::future::Future;
+use std::sync::atomic::{AtomicI32, Ordering};
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
 use ldap3::{Ldap, LdapConnAsync, LdapConnSettings};
 use tracing::{error, info, warn};
 
+use crate::audit::Auditor;
 use crate::error::CoreError;
-use crate::tls::{self, CertificateInfo, TrustStore};
+use crate::health::{ConnectionHealth, HealthHandle};
+use crate::protect::ProtectedDns;
+use crate::resolve::{resolve_host, Resolver, SystemResolver};
+use crate::tls::{self, CertificateInfo, TlsOptions, TrustStore};
+
+tokio::task_local! {
+    static CURRENT_REQUEST: RequestSlot;
+}
+
+/// The message id of the search a cancellable operation is waiting on,
+/// noted as the request is sent so a cancel abandons that request and not
+/// whatever the connection sent last.
+#[derive(Debug, Clone, Default)]
+pub struct RequestSlot(Arc<AtomicI32>);
+
+impl RequestSlot {
+    /// Run `task`, noting in this slot each search request it sends.
+    pub async fn scope<F: Future>(&self, task: F) -> F::Output {
+        CURRENT_REQUEST.scope(self.clone(), task).await
+    }
+
+    /// The id of the latest request noted; 0 before any was sent.
+    pub fn msgid(&self) -> i32 {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Note `msgid` for the operation running this task, if it is tracked.
+pub(crate) fn note_request(msgid: i32) {
+    let _ = CURRENT_REQUEST.try_with(|slot| slot.0.store(msgid, Ordering::SeqCst));
+}
 
 /// TLS mode for LDAP connections.
 #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -40,6 +73,56 @@ impl TlsMode {
     }
 }
 
+/// How a connection authenticates after it is established.
+#[derive(Debug, Clone, Copy, Default, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum AuthMethod {
+    /// Simple bind with a DN and password, or anonymous without a DN.
+    #[default]
+    Simple,
+    /// SASL EXTERNAL, using the TLS client certificate as the identity.
+    SaslExternal,
+    /// SASL GSSAPI, using the ambient Kerberos ticket cache.
+    Gssapi,
+}
+
+impl AuthMethod {
+    /// Cycle to the next method (for toggling in the UI).
+    pub fn next(&self) -> Self {
+        match self {
+            AuthMethod::Simple => AuthMethod::SaslExternal,
+            AuthMethod::SaslExternal => AuthMethod::Gssapi,
+            AuthMethod::Gssapi => AuthMethod::Simple,
+        }
+    }
+
+    /// Human-readable label for display.
+    pub fn label(&self) -> &'static str {
+        match self {
+            AuthMethod::Simple => "Simple",
+            AuthMethod::SaslExternal => "SASL EXTERNAL",
+            AuthMethod::Gssapi => "GSSAPI (Kerberos)",
+        }
+    }
+
+    /// Whether this build can bind this way: GSSAPI needs the `gssapi`
+    /// feature.
+    pub fn available(&self) -> Result<(), String> {
+        match self {
+            AuthMethod::Gssapi if !cfg!(feature = "gssapi") => Err(
+                "this build of loom has no Kerberos (GSSAPI) support; rebuild with --features gssapi"
+                    .to_string(),
+            ),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether this method binds with a DN and password.
+    pub fn is_simple(&self) -> bool {
+        *self == AuthMethod::Simple
+    }
+}
+
 /// Settings for an LDAP connection.
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct ConnectionSettings {
@@ -58,8 +141,17 @@ pub struct ConnectionSettings {
     /// to bypass server-side schema violations from directory plugins.
     #[serde(default)]
     pub relax_rules: bool,
+    /// Custom CA, client certificate and verification switch.
+    #[serde(flatten)]
+    pub tls_options: TlsOptions,
+    /// How to authenticate once connected.
+    #[serde(default)]
+    pub auth: AuthMethod,
 }
 
+/// Where the TLS verifier leaves a certificate it rejected.
+type CertSlot = Arc<Mutex<Option<CertificateInfo>>>;
+
 fn default_port() -> u16 {
     389
 }
@@ -76,9 +168,24 @@ pub struct LdapConnection {
     pub settings: ConnectionSettings,
     pub base_dn: String,
     /// Credentials stored for reconnection.
-    bind_credentials: Option<(String, String)>, // (bind_dn, password)
+    pub(crate) bind_credentials: Option<(String, String)>, // (bind_dn, password)
     /// Optional trust store for custom certificate verification.
-    trust_store: Option<Arc<TrustStore>>,
+    pub(crate) trust_store: Option<Arc<TrustStore>>,
+    /// Entries bulk operations leave alone.
+    pub protected: ProtectedDns,
+    /// Identifier of the open transaction, attached to every write.
+    pub(crate) txn_id: Option<String>,
+    /// Shared with whoever watches the connection for drops.
+    pub(crate) health: HealthHandle,
+    /// Searches ask for pages; cleared when the root DSE does not
+    /// advertise the paged results control.
+    pub paged_results: bool,
+    /// Large child listings are read a window at a time with the virtual
+    /// list view control; set when the root DSE advertises it along with
+    /// server-side sort.
+    pub vlv: bool,
+    /// Records every write sent, when an audit log is kept.
+    pub audit: Option<Auditor>,
 }
 
 impl LdapConnection {
@@ -89,6 +196,16 @@ impl LdapConnection {
     pub async fn connect(
         settings: ConnectionSettings,
         trust_store: Option<Arc<TrustStore>>,
+    ) -> Result<Self, CoreError> {
+        Self::connect_with_resolver(settings, trust_store, &SystemResolver).await
+    }
+
+    /// Like [`connect`](Self::connect), but resolves the host with `resolver`
+    /// first so DNS failures surface as `CoreError::ResolveFailed`.
+    pub async fn connect_with_resolver(
+        settings: ConnectionSettings,
+        trust_store: Option<Arc<TrustStore>>,
+        resolver: &dyn Resolver,
     ) -> Result<Self, CoreError> {
         info!(
             "Connecting to {}:{} (TLS mode: {})",
@@ -97,6 +214,7 @@ impl LdapConnection {
             settings.tls_mode.label()
         );
         let timeout = Duration::from_secs(settings.timeout_secs);
+        resolve_host(resolver, &settings.host, settings.port, timeout).await?;
 
         let ldap = match settings.tls_mode {
             TlsMode::Auto => Self::auto_connect(&settings, timeout, trust_store.as_ref()).await?,
@@ -115,6 +233,12 @@ impl LdapConnection {
             base_dn,
             bind_credentials: None,
             trust_store,
+            protected: ProtectedDns::default(),
+            txn_id: None,
+            health: HealthHandle::default(),
+            paged_results: true,
+            vlv: false,
+            audit: None,
         })
     }
 
@@ -139,9 +263,10 @@ impl LdapConnection {
                 info!("Connected via LDAPS on port {}", ldaps_port);
                 return Ok(ldap);
             }
-            Err(CoreError::CertificateNotTrusted(info)) => {
-                // Bubble up cert trust errors immediately instead of falling through
-                return Err(CoreError::CertificateNotTrusted(info));
+            Err(e @ (CoreError::CertificateNotTrusted(_) | CoreError::TlsConfig(_))) => {
+                // Bubble up cert trust and TLS setup errors immediately
+                // instead of falling through
+                return Err(e);
             }
             Err(e) => {
                 error!(
@@ -176,49 +301,58 @@ impl LdapConnection {
         Ok(ldap)
     }
 
-    /// Build LdapConnSettings, optionally with a custom TLS config from the trust store.
+    /// Build LdapConnSettings, with a custom TLS config when there is a
+    /// trust store or the profile sets TLS options.
     /// Returns (conn_settings, captured_cert_slot).
     fn build_conn_settings(
         settings: &ConnectionSettings,
         timeout: Duration,
         trust_store: Option<&Arc<TrustStore>>,
         starttls: bool,
-    ) -> (
-        LdapConnSettings,
-        Option<Arc<Mutex<Option<CertificateInfo>>>>,
-    ) {
+    ) -> Result<(LdapConnSettings, Option<CertSlot>), CoreError> {
         let mut conn_settings = LdapConnSettings::new().set_conn_timeout(timeout);
         if starttls {
             conn_settings = conn_settings.set_starttls(true);
         }
 
-        let captured = if let Some(store) = trust_store {
-            let slot: Arc<Mutex<Option<CertificateInfo>>> = Arc::new(Mutex::new(None));
+        let store = match trust_store {
+            Some(store) => Some(store.clone()),
+            None if settings.tls_options != TlsOptions::default() => {
+                Some(Arc::new(TrustStore::from_config(&[])))
+            }
+            None => None,
+        };
+        let captured = if let Some(store) = store {
+            let slot: CertSlot = Arc::new(Mutex::new(None));
             let tls_config = tls::build_client_config(
-                store.clone(),
+                store,
                 slot.clone(),
                 &settings.host,
                 settings.port,
-            );
+                &settings.tls_options,
+            )?;
             conn_settings = conn_settings.set_config(tls_config);
             Some(slot)
         } else {
             None
         };
 
-        (conn_settings, captured)
+        Ok((conn_settings, captured))
     }
 
     /// Check if a captured certificate slot has a value and return the appropriate error.
     fn check_captured_cert(
-        captured: Option<&Arc<Mutex<Option<CertificateInfo>>>>,
+        captured: Option<&CertSlot>,
         err: impl std::fmt::Display,
         protocol: &str,
     ) -> CoreError {
         if let Some(slot) = captured {
             if let Ok(mut guard) = slot.lock() {
                 if let Some(info) = guard.take() {
-                    error!("{} certificate not trusted for {}", protocol, info.subject);
+                    error!(
+                        "{} certificate {} rejected: {}",
+                        protocol, info.subject, info.problem
+                    );
                     return CoreError::CertificateNotTrusted(Box::new(info));
                 }
             }
@@ -238,7 +372,7 @@ impl LdapConnection {
         );
         let url = format!("ldaps://{}:{}", settings.host, settings.port);
         let (conn_settings, captured) =
-            Self::build_conn_settings(settings, timeout, trust_store, false);
+            Self::build_conn_settings(settings, timeout, trust_store, false)?;
         let (conn, ldap) = LdapConnAsync::with_settings(conn_settings, &url)
             .await
             .map_err(|e| Self::check_captured_cert(captured.as_ref(), e, "LDAPS"))?;
@@ -257,7 +391,7 @@ impl LdapConnection {
         );
         let url = format!("ldap://{}:{}", settings.host, settings.port);
         let (conn_settings, captured) =
-            Self::build_conn_settings(settings, timeout, trust_store, true);
+            Self::build_conn_settings(settings, timeout, trust_store, true)?;
         let (conn, ldap) = LdapConnAsync::with_settings(conn_settings, &url)
             .await
             .map_err(|e| Self::check_captured_cert(captured.as_ref(), e, "StartTLS"))?;
@@ -294,8 +428,12 @@ impl LdapConnection {
     }
 
     /// Attempt to reconnect using stored settings and credentials.
-    /// Returns Ok(()) if reconnection and re-bind succeed.
+    /// Returns Ok(()) if reconnection and re-bind succeed. While a
+    /// background reconnect is under way this fails fast instead.
     pub async fn reconnect(&mut self) -> Result<(), CoreError> {
+        if self.health.get() != ConnectionHealth::Connected {
+            return Err(CoreError::ConnectionLost);
+        }
         info!(
             "Attempting reconnect to {}:{}",
             self.settings.host, self.settings.port
@@ -312,14 +450,15 @@ impl LdapConnection {
         };
 
         self.ldap = ldap;
+        // A transaction does not survive the connection it was opened on
+        self.txn_id = None;
 
-        // Re-bind with stored credentials
-        if let Some((ref bind_dn, ref password)) = self.bind_credentials {
-            let bind_dn = bind_dn.clone();
-            let password = password.clone();
-            self.simple_bind(&bind_dn, &password).await?;
-        } else {
-            self.anonymous_bind().await?;
+        // Re-bind with stored credentials, or the profile's SASL mechanism
+        match self.bind_credentials.clone() {
+            Some((bind_dn, password)) if self.settings.auth.is_simple() => {
+                self.simple_bind(&bind_dn, &password).await?;
+            }
+            _ => self.authenticate(None, "").await?,
         }
 
         info!("Reconnected successfully");
@@ -339,13 +478,41 @@ impl LdapConnection {
                     || msg.contains("timed out")
             }
             CoreError::Timeout => true,
-            CoreError::ConnectionFailed(_) => true,
+            CoreError::ConnectionFailed(_) | CoreError::ConnectionLost => true,
             _ => false,
         }
     }
 
+    /// Ask the server to abandon the request `msgid`, after the task waiting
+    /// for it was cancelled. Abandoning a request that already completed has
+    /// no effect.
+    pub async fn abandon(&mut self, msgid: i32) -> Result<(), CoreError> {
+        if msgid == 0 {
+            return Ok(());
+        }
+        self.ldap.abandon(msgid).await.map_err(CoreError::Ldap)
+    }
+
     /// Unbind and close the connection.
     pub async fn disconnect(&mut self) -> Result<(), CoreError> {
         self.ldap.unbind().await.map_err(CoreError::Ldap)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_request_slot_notes_only_its_own_task() {
+        let slot = RequestSlot::default();
+        note_request(7);
+        assert_eq!(slot.msgid(), 0);
+        slot.scope(async {
+            note_request(3);
+            note_request(4);
+        })
+        .await;
+        assert_eq!(slot.msgid(), 4);
+    }
+}
@@ -0,0 +1,239 @@
+//! "Copy as" templates that wrap a DN or value before it goes to the
+//! clipboard, e.g. `(member={value})` or an `ldapsearch` command line.
+//!
+//! Placeholders: `{value}`, `{dn}`, `{host}` and `{base}`. Use `{{` and `}}`
+//! for literal braces. A template's `escape` mode applies to `{value}` and
+//! `{dn}`; host and base are inserted verbatim.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::filter::escape_filter_value;
+
+/// How substituted values are escaped.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TemplateEscape {
+    #[default]
+    None,
+    /// RFC 4515 filter value escaping.
+    Filter,
+    /// POSIX shell single-quoting.
+    Shell,
+    /// PowerShell single-quoting.
+    PowerShell,
+}
+
+/// A configured template: either a bare string or a table with an escape mode.
+///
+/// ```toml
+/// [copy_templates]
+/// cn_filter = "(cn={value})"
+/// filter_member = { template = "(member={value})", escape = "filter" }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CopyTemplate {
+    Plain(String),
+    Detailed {
+        template: String,
+        #[serde(default)]
+        escape: TemplateEscape,
+    },
+}
+
+/// The values a template can refer to.
+#[derive(Debug, Clone, Copy)]
+pub struct CopyContext<'a> {
+    /// The selected value; the DN itself when copying from the tree.
+    pub value: &'a str,
+    pub dn: &'a str,
+    pub host: &'a str,
+    pub base: &'a str,
+}
+
+impl CopyTemplate {
+    pub fn template(&self) -> &str {
+        match self {
+            Self::Plain(template) | Self::Detailed { template, .. } => template,
+        }
+    }
+
+    pub fn escape(&self) -> TemplateEscape {
+        match self {
+            Self::Plain(_) => TemplateEscape::None,
+            Self::Detailed { escape, .. } => *escape,
+        }
+    }
+
+    /// Substitute the placeholders of this template.
+    pub fn render(&self, ctx: &CopyContext) -> Result<String, String> {
+        let escape = self.escape();
+        let mut out = String::new();
+        let mut chars = self.template().chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '{' if chars.peek() == Some(&'{') => {
+                    chars.next();
+                    out.push('{');
+                }
+                '}' if chars.peek() == Some(&'}') => {
+                    chars.next();
+                    out.push('}');
+                }
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('}') => break,
+                            Some(c) => name.push(c),
+                            None => return Err(format!("Unclosed placeholder {{{}", name)),
+                        }
+                    }
+                    match name.as_str() {
+                        "value" => out.push_str(&escape_value(ctx.value, escape)),
+                        "dn" => out.push_str(&escape_value(ctx.dn, escape)),
+                        "host" => out.push_str(ctx.host),
+                        "base" => out.push_str(ctx.base),
+                        _ => return Err(format!("Unknown placeholder {{{}}}", name)),
+                    }
+                }
+                c => out.push(c),
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Escape a substituted value for the given mode.
+pub fn escape_value(value: &str, escape: TemplateEscape) -> String {
+    match escape {
+        TemplateEscape::None => value.to_string(),
+        TemplateEscape::Filter => escape_filter_value(value),
+        TemplateEscape::Shell => format!("'{}'", value.replace('\'', "'\\''")),
+        TemplateEscape::PowerShell => format!("'{}'", value.replace('\'', "''")),
+    }
+}
+
+/// Templates offered when `[copy_templates]` is not configured.
+pub fn builtin_templates() -> Vec<(String, CopyTemplate)> {
+    let detailed = |template: &str, escape| CopyTemplate::Detailed {
+        template: template.to_string(),
+        escape,
+    };
+    vec![
+        (
+            "filter_member".to_string(),
+            detailed("(member={value})", TemplateEscape::Filter),
+        ),
+        (
+            "ldapsearch".to_string(),
+            detailed(
+                "ldapsearch -H ldap://{host} -b {dn} -s base",
+                TemplateEscape::Shell,
+            ),
+        ),
+        (
+            "powershell".to_string(),
+            detailed("Get-ADUser -Identity {value}", TemplateEscape::PowerShell),
+        ),
+    ]
+}
+
+/// The configured templates, or the built-in set when none are configured.
+pub fn effective_templates(
+    configured: &BTreeMap<String, CopyTemplate>,
+) -> Vec<(String, CopyTemplate)> {
+    if configured.is_empty() {
+        builtin_templates()
+    } else {
+        configured
+            .iter()
+            .map(|(name, t)| (name.clone(), t.clone()))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ctx<'a>(value: &'a str) -> CopyContext<'a> {
+        CopyContext {
+            value,
+            dn: "cn=Smith\\, John (Admin),ou=People,dc=example,dc=com",
+            host: "ldap.example.com",
+            base: "dc=example,dc=com",
+        }
+    }
+
+    #[test]
+    fn test_filter_escaping_of_value() {
+        let (_, member) = &builtin_templates()[0];
+        let dn = ctx("").dn;
+        assert_eq!(
+            member.render(&ctx(dn)).unwrap(),
+            "(member=cn=Smith\\5c, John \\28Admin\\29,ou=People,dc=example,dc=com)"
+        );
+        let plain = CopyTemplate::Plain("(cn={value})".to_string());
+        assert_eq!(plain.render(&ctx("a*")).unwrap(), "(cn=a*)");
+    }
+
+    #[test]
+    fn test_all_placeholders_and_literal_braces() {
+        let t = CopyTemplate::Plain("{{{host}}} {base} {dn} {value}".to_string());
+        assert_eq!(
+            t.render(&ctx("v")).unwrap(),
+            "{ldap.example.com} dc=example,dc=com cn=Smith\\, John (Admin),ou=People,dc=example,dc=com v"
+        );
+    }
+
+    #[test]
+    fn test_shell_and_powershell_quoting() {
+        let templates = builtin_templates();
+        assert_eq!(
+            templates[1].1.render(&ctx("")).unwrap(),
+            "ldapsearch -H ldap://ldap.example.com -b 'cn=Smith\\, John (Admin),ou=People,dc=example,dc=com' -s base"
+        );
+        assert_eq!(escape_value("it's", TemplateEscape::Shell), "'it'\\''s'");
+        assert_eq!(
+            templates[2].1.render(&ctx("o'brien")).unwrap(),
+            "Get-ADUser -Identity 'o''brien'"
+        );
+    }
+
+    #[test]
+    fn test_unknown_placeholder_rejected() {
+        let t = CopyTemplate::Plain("(uid={uid})".to_string());
+        assert_eq!(
+            t.render(&ctx("x")).unwrap_err(),
+            "Unknown placeholder {uid}"
+        );
+        let t = CopyTemplate::Plain("(uid={value".to_string());
+        assert!(t.render(&ctx("x")).is_err());
+    }
+
+    #[test]
+    fn test_config_forms_parse() {
+        #[derive(Deserialize)]
+        struct File {
+            copy_templates: BTreeMap<String, CopyTemplate>,
+        }
+        let file: File = toml::from_str(
+            r#"
+[copy_templates]
+cn_filter = "(cn={value})"
+filter_member = { template = "(member={value})", escape = "filter" }
+ps = { template = "Get-ADGroup {value}", escape = "powershell" }
+"#,
+        )
+        .unwrap();
+        let templates = effective_templates(&file.copy_templates);
+        assert_eq!(templates.len(), 3);
+        assert_eq!(templates[0].1.escape(), TemplateEscape::None);
+        assert_eq!(templates[1].1.escape(), TemplateEscape::Filter);
+        assert_eq!(templates[2].1.escape(), TemplateEscape::PowerShell);
+        assert_eq!(effective_templates(&BTreeMap::new()).len(), 3);
+    }
+}
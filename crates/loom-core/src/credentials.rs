@@ -10,6 +10,7 @@ pub enum CredentialMethod {
     #[default]
     Prompt,
     Command,
+    #[serde(alias = "keyring")]
     Keychain,
     Vault,
 }
@@ -64,6 +65,25 @@ impl CredentialProvider {
             .map_err(|e| CoreError::CredentialError(format!("Keychain store failed: {}", e)))
     }
 
+    /// Move a profile's keychain password to its new name. A profile
+    /// without one is left alone.
+    pub fn rename_in_keychain(old_name: &str, new_name: &str) -> Result<(), CoreError> {
+        let entry = keyring::Entry::new("loom", old_name)
+            .map_err(|e| CoreError::CredentialError(format!("Keychain access failed: {}", e)))?;
+        let password = match entry.get_password() {
+            Ok(password) => password,
+            Err(keyring::Error::NoEntry) => return Ok(()),
+            Err(e) => {
+                return Err(CoreError::CredentialError(format!(
+                    "Keychain get failed: {}",
+                    e
+                )))
+            }
+        };
+        Self::store_in_keychain(new_name, &password)?;
+        Self::delete_from_keychain(old_name)
+    }
+
     /// Delete a password from the OS keychain.
     pub fn delete_from_keychain(connection_name: &str) -> Result<(), CoreError> {
         let entry = keyring::Entry::new("loom", connection_name)
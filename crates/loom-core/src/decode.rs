@@ -0,0 +1,374 @@
+//! Readable forms of Active Directory values stored as raw bytes or bare
+//! integers: GUIDs, SIDs, FILETIME timestamps and account control flags.
+//!
+//! Attributes are recognized by name, since their schema syntaxes (Octet
+//! String, Large Integer, Integer) are shared with values that mean nothing
+//! in particular.
+
+use std::collections::BTreeMap;
+
+use chrono::SecondsFormat;
+
+use crate::entry::LdapEntry;
+use crate::time::{is_filetime_never, parse_filetime, FILETIME_ATTRIBUTES};
+
+/// Attributes holding a GUID in Microsoft's mixed-endian layout.
+const GUID_ATTRIBUTES: &[&str] = &[
+    "objectGUID",
+    "schemaIDGUID",
+    "attributeSecurityGUID",
+    "msExchMailboxGuid",
+    "mS-DS-ConsistencyGuid",
+];
+
+/// Attributes holding binary security identifiers.
+const SID_ATTRIBUTES: &[&str] = &[
+    "objectSid",
+    "sIDHistory",
+    "securityIdentifier",
+    "tokenGroups",
+    "tokenGroupsGlobalAndUniversal",
+    "msExchMasterAccountSid",
+];
+
+/// Attributes holding `userAccountControl` style flags.
+const ACCOUNT_CONTROL_ATTRIBUTES: &[&str] =
+    &["userAccountControl", "msDS-User-Account-Control-Computed"];
+
+/// `userAccountControl` bits, lowest first.
+const ACCOUNT_CONTROL_FLAGS: &[(u32, &str)] = &[
+    (0x0000_0001, "SCRIPT"),
+    (0x0000_0002, "DISABLED"),
+    (0x0000_0008, "HOMEDIR_REQUIRED"),
+    (0x0000_0010, "LOCKOUT"),
+    (0x0000_0020, "PASSWD_NOTREQD"),
+    (0x0000_0040, "PASSWD_CANT_CHANGE"),
+    (0x0000_0080, "ENCRYPTED_TEXT_PWD_ALLOWED"),
+    (0x0000_0100, "TEMP_DUPLICATE_ACCOUNT"),
+    (0x0000_0200, "NORMAL_ACCOUNT"),
+    (0x0000_0800, "INTERDOMAIN_TRUST_ACCOUNT"),
+    (0x0000_1000, "WORKSTATION_TRUST_ACCOUNT"),
+    (0x0000_2000, "SERVER_TRUST_ACCOUNT"),
+    (0x0001_0000, "DONT_EXPIRE_PASSWORD"),
+    (0x0002_0000, "MNS_LOGON_ACCOUNT"),
+    (0x0004_0000, "SMARTCARD_REQUIRED"),
+    (0x0008_0000, "TRUSTED_FOR_DELEGATION"),
+    (0x0010_0000, "NOT_DELEGATED"),
+    (0x0020_0000, "USE_DES_KEY_ONLY"),
+    (0x0040_0000, "DONT_REQ_PREAUTH"),
+    (0x0080_0000, "PASSWORD_EXPIRED"),
+    (0x0100_0000, "TRUSTED_TO_AUTH_FOR_DELEGATION"),
+    (0x0400_0000, "PARTIAL_SECRETS_ACCOUNT"),
+];
+
+/// Bytes shown by [`to_hex`] before the rest is left out.
+const HEX_LIMIT: usize = 64;
+
+/// How an attribute's values are encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Guid,
+    Sid,
+    FileTime,
+    AccountControl,
+}
+
+fn listed(list: &[&str], attr: &str) -> bool {
+    list.iter().any(|a| a.eq_ignore_ascii_case(attr))
+}
+
+/// The encoding of `attr`'s values, if it is one loom decodes.
+pub fn value_kind(attr: &str) -> Option<ValueKind> {
+    if listed(GUID_ATTRIBUTES, attr) {
+        Some(ValueKind::Guid)
+    } else if listed(SID_ATTRIBUTES, attr) {
+        Some(ValueKind::Sid)
+    } else if listed(FILETIME_ATTRIBUTES, attr) {
+        Some(ValueKind::FileTime)
+    } else if listed(ACCOUNT_CONTROL_ATTRIBUTES, attr) {
+        Some(ValueKind::AccountControl)
+    } else {
+        None
+    }
+}
+
+/// A 16-byte GUID as `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`. The first
+/// three groups are stored little-endian.
+pub fn format_guid(bytes: &[u8]) -> Option<String> {
+    let b: &[u8; 16] = bytes.try_into().ok()?;
+    Some(format!(
+        "{:02x}{:02x}{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+        b[3], b[2], b[1], b[0], b[5], b[4], b[7], b[6],
+        b[8], b[9], b[10], b[11], b[12], b[13], b[14], b[15]
+    ))
+}
+
+/// A binary SID as `S-1-5-21-...`: revision, sub-authority count, a 48-bit
+/// big-endian authority, then the little-endian 32-bit sub-authorities.
+pub fn format_sid(bytes: &[u8]) -> Option<String> {
+    let (&revision, rest) = bytes.split_first()?;
+    let (&count, rest) = rest.split_first()?;
+    if rest.len() != 6 + 4 * count as usize {
+        return None;
+    }
+    let (authority, subs) = rest.split_at(6);
+    let authority = authority
+        .iter()
+        .fold(0u64, |acc, &b| (acc << 8) | u64::from(b));
+    let mut sid = if authority >> 32 == 0 {
+        format!("S-{}-{}", revision, authority)
+    } else {
+        format!("S-{}-0x{:012X}", revision, authority)
+    };
+    for sub in subs.chunks_exact(4) {
+        let sub = u32::from_le_bytes([sub[0], sub[1], sub[2], sub[3]]);
+        sid.push_str(&format!("-{}", sub));
+    }
+    Some(sid)
+}
+
+/// A FILETIME integer as an RFC 3339 timestamp in UTC, or `never` for the
+/// "not set" sentinels.
+pub fn format_filetime(value: &str) -> Option<String> {
+    if is_filetime_never(value) {
+        return Some("never".to_string());
+    }
+    parse_filetime(value).map(|t| t.to_rfc3339_opts(SecondsFormat::Secs, true))
+}
+
+/// The names of the flags set in a `userAccountControl` value, lowest bit
+/// first; bits without a name are listed as one hex remainder.
+pub fn account_control_flags(value: u32) -> Vec<String> {
+    let mut flags: Vec<String> = ACCOUNT_CONTROL_FLAGS
+        .iter()
+        .filter(|(bit, _)| value & bit != 0)
+        .map(|(_, name)| name.to_string())
+        .collect();
+    let known = ACCOUNT_CONTROL_FLAGS
+        .iter()
+        .fold(0, |acc, (bit, _)| acc | bit);
+    if value & !known != 0 {
+        flags.push(format!("0x{:x}", value & !known));
+    }
+    flags
+}
+
+/// A `userAccountControl` value as its flags, e.g.
+/// `DISABLED, NORMAL_ACCOUNT`. Servers may send it signed.
+pub fn format_account_control(value: &str) -> Option<String> {
+    let value: i64 = value.trim().parse().ok()?;
+    let value = u32::try_from(value).or_else(|_| i32::try_from(value).map(|v| v as u32));
+    let flags = account_control_flags(value.ok()?);
+    if flags.is_empty() {
+        return Some("(none)".to_string());
+    }
+    Some(flags.join(", "))
+}
+
+/// The readable form of a binary value of `attr`, if it is one loom
+/// decodes and the bytes are well formed.
+pub fn decode_bytes(attr: &str, bytes: &[u8]) -> Option<String> {
+    match value_kind(attr)? {
+        ValueKind::Guid => format_guid(bytes),
+        ValueKind::Sid => format_sid(bytes),
+        ValueKind::FileTime | ValueKind::AccountControl => {
+            decode_text(attr, std::str::from_utf8(bytes).ok()?)
+        }
+    }
+}
+
+/// The readable form of a text value of `attr`. GUIDs and SIDs whose bytes
+/// happen to be valid UTF-8 arrive as text, and are decoded from those.
+pub fn decode_text(attr: &str, value: &str) -> Option<String> {
+    match value_kind(attr)? {
+        ValueKind::Guid => format_guid(value.as_bytes()),
+        ValueKind::Sid => format_sid(value.as_bytes()),
+        ValueKind::FileTime => format_filetime(value),
+        ValueKind::AccountControl => format_account_control(value),
+    }
+}
+
+/// Bytes as space-separated hex pairs, cut off after the first
+/// [`HEX_LIMIT`] with the total length noted.
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut hex = bytes
+        .iter()
+        .take(HEX_LIMIT)
+        .map(|b| format!("{:02x}", b))
+        .collect::<Vec<_>>()
+        .join(" ");
+    if bytes.len() > HEX_LIMIT {
+        hex.push_str(&format!(" \u{2026} ({} bytes)", bytes.len()));
+    }
+    hex
+}
+
+/// A copy of `entry` with every value loom can decode in its readable form.
+/// Binary attributes whose values all decode become text attributes.
+pub fn decode_entry(entry: &LdapEntry) -> LdapEntry {
+    let mut attributes: BTreeMap<String, Vec<String>> = entry
+        .attributes
+        .iter()
+        .map(|(name, values)| {
+            let decoded = values
+                .iter()
+                .map(|v| decode_text(name, v).unwrap_or_else(|| v.clone()))
+                .collect();
+            (name.clone(), decoded)
+        })
+        .collect();
+    let mut binary = BTreeMap::new();
+    for (name, values) in &entry.binary_attributes {
+        let decoded: Option<Vec<String>> = values.iter().map(|v| decode_bytes(name, v)).collect();
+        match decoded {
+            Some(decoded) => {
+                attributes.insert(name.clone(), decoded);
+            }
+            None => {
+                binary.insert(name.clone(), values.clone());
+            }
+        }
+    }
+    LdapEntry::new(entry.dn.clone(), attributes).with_binary_attributes(binary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUID: [u8; 16] = [
+        0x78, 0x56, 0x34, 0x12, 0x34, 0x12, 0x78, 0x56, 0x9a, 0xbc, 0xde, 0xf0, 0x12, 0x34, 0x56,
+        0x78,
+    ];
+
+    /// S-1-5-21-3623811015-3361044348-30300820-1013
+    const DOMAIN_SID: [u8; 28] = [
+        0x01, 0x05, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x15, 0x00, 0x00, 0x00, 0xc7, 0xf7, 0xfe,
+        0xd7, 0x7c, 0x77, 0x55, 0xc8, 0x94, 0x5a, 0xce, 0x01, 0xf5, 0x03, 0x00, 0x00,
+    ];
+
+    #[test]
+    fn test_format_guid() {
+        assert_eq!(
+            format_guid(&GUID).as_deref(),
+            Some("12345678-1234-5678-9abc-def012345678")
+        );
+        assert_eq!(format_guid(&GUID[..15]), None);
+    }
+
+    #[test]
+    fn test_format_sid() {
+        assert_eq!(
+            format_sid(&DOMAIN_SID).as_deref(),
+            Some("S-1-5-21-3623811015-3361044348-30300820-1013")
+        );
+        // BUILTIN\Administrators
+        let admins = [
+            0x01, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05, 0x20, 0x00, 0x00, 0x00, 0x20, 0x02,
+            0x00, 0x00,
+        ];
+        assert_eq!(format_sid(&admins).as_deref(), Some("S-1-5-32-544"));
+        // Everyone: no sub-authority but one
+        let everyone = [0x01, 0x01, 0, 0, 0, 0, 0, 0x01, 0, 0, 0, 0];
+        assert_eq!(format_sid(&everyone).as_deref(), Some("S-1-1-0"));
+        // The count does not match the length
+        assert_eq!(format_sid(&DOMAIN_SID[..24]), None);
+        assert_eq!(format_sid(&[]), None);
+    }
+
+    #[test]
+    fn test_format_filetime() {
+        assert_eq!(
+            format_filetime("133511778000000000").as_deref(),
+            Some("2024-01-31T12:30:00Z")
+        );
+        assert_eq!(format_filetime("0").as_deref(), Some("never"));
+        assert_eq!(
+            format_filetime("9223372036854775807").as_deref(),
+            Some("never")
+        );
+        assert_eq!(format_filetime("soon"), None);
+    }
+
+    #[test]
+    fn test_format_account_control() {
+        assert_eq!(
+            format_account_control("514").as_deref(),
+            Some("DISABLED, NORMAL_ACCOUNT")
+        );
+        assert_eq!(
+            format_account_control("66048").as_deref(),
+            Some("NORMAL_ACCOUNT, DONT_EXPIRE_PASSWORD")
+        );
+        assert_eq!(
+            format_account_control("4096").as_deref(),
+            Some("WORKSTATION_TRUST_ACCOUNT")
+        );
+        assert_eq!(
+            format_account_control("1049088").as_deref(),
+            Some("NORMAL_ACCOUNT, NOT_DELEGATED")
+        );
+        // Sent signed, with a bit that has no name
+        assert_eq!(
+            format_account_control("-2147483136").as_deref(),
+            Some("NORMAL_ACCOUNT, 0x80000000")
+        );
+        assert_eq!(format_account_control("0").as_deref(), Some("(none)"));
+        assert_eq!(format_account_control("lots"), None);
+    }
+
+    #[test]
+    fn test_decode_by_attribute_name() {
+        assert_eq!(value_kind("OBJECTGUID"), Some(ValueKind::Guid));
+        assert_eq!(value_kind("pwdLastSet"), Some(ValueKind::FileTime));
+        assert_eq!(value_kind("cn"), None);
+        assert_eq!(decode_text("cn", "514"), None);
+        assert_eq!(
+            decode_text("userAccountControl", "514").as_deref(),
+            Some("DISABLED, NORMAL_ACCOUNT")
+        );
+        assert_eq!(
+            decode_bytes("objectSid", &DOMAIN_SID).as_deref(),
+            Some("S-1-5-21-3623811015-3361044348-30300820-1013")
+        );
+        // A GUID that is all ASCII arrives as text
+        let ascii = "0123456789abcdef";
+        assert_eq!(
+            decode_text("objectGUID", ascii).as_deref(),
+            Some("33323130-3534-3736-3839-616263646566")
+        );
+    }
+
+    #[test]
+    fn test_to_hex() {
+        assert_eq!(to_hex(&[0x00, 0xab, 0xff]), "00 ab ff");
+        let long = to_hex(&[0u8; 100]);
+        assert!(long.ends_with("00 \u{2026} (100 bytes)"));
+    }
+
+    #[test]
+    fn test_decode_entry() {
+        let entry = LdapEntry::new(
+            "cn=jdoe,dc=example,dc=com".to_string(),
+            BTreeMap::from([
+                ("cn".to_string(), vec!["jdoe".to_string()]),
+                ("accountExpires".to_string(), vec!["0".to_string()]),
+            ]),
+        )
+        .with_binary_attributes(BTreeMap::from([
+            ("objectGUID".to_string(), vec![GUID.to_vec()]),
+            ("jpegPhoto".to_string(), vec![vec![0xff, 0xd8]]),
+        ]));
+        let decoded = decode_entry(&entry);
+        assert_eq!(decoded.attributes["cn"], vec!["jdoe"]);
+        assert_eq!(decoded.attributes["accountExpires"], vec!["never"]);
+        assert_eq!(
+            decoded.attributes["objectGUID"],
+            vec!["12345678-1234-5678-9abc-def012345678"]
+        );
+        assert_eq!(
+            decoded.binary_attributes.keys().collect::<Vec<_>>(),
+            vec!["jpegPhoto"]
+        );
+    }
+}
@@ -0,0 +1,362 @@
+//! Deleting a whole subtree. LDAP only deletes leaves, so the entries
+//! under a base DN go children first, deepest first, and the run stops
+//! at the first refusal rather than leave holes all over the subtree.
+//! Protected entries below the base stop it before anything is deleted.
+//! A hand-picked set of entries goes the same way, but each delete is
+//! tried on its own and failures are collected like a batch modify's.
+
+use ldap3::Scope;
+
+use crate::dn::{self, Dn};
+use crate::error::CoreError;
+use crate::freshness::{check_before_delete, read_tracked, DeleteCheck, TrackedEntry};
+use crate::ops::DirectoryOps;
+use crate::protect::ProtectedDns;
+
+/// Order DNs so every entry comes before its ancestors: deepest first,
+/// keeping the given order among entries of the same depth.
+pub fn deletion_order(mut dns: Vec<String>) -> Vec<String> {
+    dns.sort_by_key(|d| std::cmp::Reverse(rdn_count(d)));
+    dns
+}
+
+/// RDNs in `d`, parsed where possible so escaped commas don't count.
+fn rdn_count(d: &str) -> usize {
+    Dn::parse(d)
+        .map(|d| d.depth())
+        .unwrap_or_else(|_| dn::depth(d))
+}
+
+/// Outcome of a [`delete_subtree`] run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubtreeDeletion {
+    /// Entries removed, in the order they went.
+    pub deleted: Vec<String>,
+    /// Entries the subtree held when the run started, base included.
+    pub total: usize,
+    /// The entry that could not be deleted and why; nothing after it
+    /// was attempted.
+    pub failure: Option<(String, String)>,
+}
+
+impl SubtreeDeletion {
+    pub fn is_complete(&self) -> bool {
+        self.failure.is_none() && self.deleted.len() == self.total
+    }
+}
+
+/// Outcome of a [`delete_entries`] run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EntriesDeletion {
+    /// Entries removed, in the order they went.
+    pub deleted: Vec<String>,
+    /// `(dn, error)` of each entry left in place.
+    pub errors: Vec<(String, String)>,
+}
+
+/// Delete each of `dns`, children before parents so a picked parent can
+/// go after its picked children. Protected entries are skipped and a
+/// failed delete does not stop the rest; both are reported as errors.
+pub async fn delete_entries(
+    ops: &mut dyn DirectoryOps,
+    dns: Vec<String>,
+    protected: &ProtectedDns,
+) -> EntriesDeletion {
+    let mut result = EntriesDeletion::default();
+    for target in deletion_order(dns) {
+        if let Some(pattern) = protected.check(&target) {
+            let error = format!("protected by '{}'", pattern);
+            result.errors.push((target, error));
+            continue;
+        }
+        match ops.delete_entry(&target).await {
+            Ok(()) => result.deleted.push(target),
+            Err(e) => result.errors.push((target, e.to_string())),
+        }
+    }
+    result
+}
+
+/// Read `base` and count its children again just before its subtree is
+/// deleted, and compare them with what the delete was confirmed against:
+/// the root as `loaded` and, when they had been listed, `loaded_children`.
+/// Returns the verdict with the fresh copy of the root.
+pub async fn recheck_subtree_root(
+    ops: &mut dyn DirectoryOps,
+    base: &str,
+    loaded: Option<&TrackedEntry>,
+    loaded_children: Option<usize>,
+) -> Result<(DeleteCheck, Option<TrackedEntry>), CoreError> {
+    let fresh = read_tracked(ops, base).await?;
+    let check = check_before_delete(loaded, fresh.as_ref());
+    if check != DeleteCheck::Proceed {
+        return Ok((check, fresh));
+    }
+    if let Some(loaded) = loaded_children {
+        let now = ops
+            .search_pages(
+                base,
+                Scope::OneLevel,
+                "(objectClass=*)",
+                &["1.1"],
+                &mut |_| {},
+            )
+            .await?;
+        if now != loaded {
+            return Ok((DeleteCheck::ChildrenChanged { loaded, now }, fresh));
+        }
+    }
+    Ok((DeleteCheck::Proceed, fresh))
+}
+
+/// Delete `base` and everything below it, children before parents.
+/// `progress` gets the number deleted so far and the total after each
+/// entry. Failing to list the subtree, or finding a protected entry
+/// below `base`, is an error; a failed delete ends the run and is
+/// reported in the result. Whether `base` itself may go is the caller's
+/// check.
+pub async fn delete_subtree<F>(
+    ops: &mut dyn DirectoryOps,
+    base: &str,
+    protected: &ProtectedDns,
+    mut progress: F,
+) -> Result<SubtreeDeletion, CoreError>
+where
+    F: FnMut(usize, usize) + Send,
+{
+    let mut dns = Vec::new();
+    ops.search_pages(
+        base,
+        Scope::Subtree,
+        "(objectClass=*)",
+        &["1.1"],
+        &mut |page| dns.extend(page.into_iter().map(|e| e.dn)),
+    )
+    .await?;
+    let base_key = dn::normalize(base);
+    if let Some((target, pattern)) = dns
+        .iter()
+        .filter(|d| dn::normalize(d) != base_key)
+        .find_map(|d| protected.check(d).map(|p| (d, p)))
+    {
+        return Err(CoreError::DeleteFailed(format!(
+            "{} is protected by '{}'; nothing was deleted",
+            target, pattern
+        )));
+    }
+
+    let order = deletion_order(dns);
+    let total = order.len();
+    progress(0, total);
+    let mut deleted = Vec::with_capacity(total);
+    for target in order {
+        if let Err(e) = ops.delete_entry(&target).await {
+            return Ok(SubtreeDeletion {
+                deleted,
+                total,
+                failure: Some((target, e.to_string())),
+            });
+        }
+        deleted.push(target);
+        progress(deleted.len(), total);
+    }
+    Ok(SubtreeDeletion {
+        deleted,
+        total,
+        failure: None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protect::ProtectionMode;
+    use crate::server_detect::ServerType;
+    use crate::testing::fixtures::{domain, org_unit, person};
+    use crate::testing::{MockDirectory, Operation};
+
+    const BASE: &str = "dc=example,dc=com";
+
+    fn directory() -> MockDirectory {
+        let people = format!("ou=people,{}", BASE);
+        MockDirectory::with_entries([
+            domain(BASE),
+            org_unit(BASE, "people"),
+            org_unit(&people, "contractors"),
+            person(&people, "Ada", "Lovelace"),
+            person(&format!("ou=contractors,{}", people), "Alan", "Turing"),
+            org_unit(BASE, "groups"),
+        ])
+    }
+
+    #[test]
+    fn test_deletion_order_deepest_first() {
+        let order = deletion_order(vec![
+            "ou=people,dc=example,dc=com".to_string(),
+            "cn=a,ou=people,dc=example,dc=com".to_string(),
+            "cn=x,ou=sub,ou=people,dc=example,dc=com".to_string(),
+            "ou=sub,ou=people,dc=example,dc=com".to_string(),
+            "cn=b,ou=people,dc=example,dc=com".to_string(),
+        ]);
+        assert_eq!(
+            order,
+            vec![
+                "cn=x,ou=sub,ou=people,dc=example,dc=com",
+                "cn=a,ou=people,dc=example,dc=com",
+                "ou=sub,ou=people,dc=example,dc=com",
+                "cn=b,ou=people,dc=example,dc=com",
+                "ou=people,dc=example,dc=com",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deletion_order_ignores_escaped_commas() {
+        let order = deletion_order(vec![
+            "cn=Smith\\, John,ou=people,dc=example,dc=com".to_string(),
+            "cn=a,ou=x,ou=people,dc=example,dc=com".to_string(),
+        ]);
+        assert_eq!(order[0], "cn=a,ou=x,ou=people,dc=example,dc=com");
+    }
+
+    #[tokio::test]
+    async fn test_delete_subtree_children_first() {
+        let mut dir = directory();
+        let base = format!("ou=people,{}", BASE);
+        let mut seen = Vec::new();
+        let result = delete_subtree(&mut dir, &base, &ProtectedDns::default(), |done, total| {
+            seen.push((done, total))
+        })
+        .await
+        .unwrap();
+
+        assert!(result.is_complete());
+        assert_eq!(result.total, 4);
+        assert_eq!(result.deleted.last(), Some(&base));
+        assert_eq!(seen.first(), Some(&(0, 4)));
+        assert_eq!(seen.last(), Some(&(4, 4)));
+        let left: Vec<&str> = dir.entries().iter().map(|e| e.dn.as_str()).collect();
+        assert_eq!(left.len(), 2);
+        assert!(dir.entry(&format!("ou=groups,{}", BASE)).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_recheck_subtree_root() {
+        let mut dir = directory();
+        let base = format!("ou=people,{}", BASE);
+        let loaded = read_tracked(&mut dir, &base).await.unwrap();
+        let (check, fresh) = recheck_subtree_root(&mut dir, &base, loaded.as_ref(), Some(2))
+            .await
+            .unwrap();
+        assert_eq!(check, DeleteCheck::Proceed);
+        assert_eq!(fresh.unwrap().entry.dn, base);
+
+        // An entry added below the root since the delete was confirmed
+        dir.add_entry(
+            &format!("uid=grace.hopper,{}", base),
+            crate::ops::add_attributes(&person(&base, "Grace", "Hopper")),
+            Vec::new(),
+        )
+        .await
+        .unwrap();
+        let (check, _) = recheck_subtree_root(&mut dir, &base, loaded.as_ref(), Some(2))
+            .await
+            .unwrap();
+        assert_eq!(check, DeleteCheck::ChildrenChanged { loaded: 2, now: 3 });
+        // Children never listed are not counted
+        let (check, _) = recheck_subtree_root(&mut dir, &base, loaded.as_ref(), None)
+            .await
+            .unwrap();
+        assert_eq!(check, DeleteCheck::Proceed);
+
+        // The root itself modified since it was loaded
+        let mut stale = loaded.clone().unwrap();
+        stale.marker.entry_csn = Some("20260101000000.000000Z#000000#000#000000".to_string());
+        let (check, _) = recheck_subtree_root(&mut dir, &base, Some(&stale), Some(3))
+            .await
+            .unwrap();
+        assert!(matches!(check, DeleteCheck::Changed(_)));
+    }
+
+    #[tokio::test]
+    async fn test_delete_subtree_stops_at_first_failure() {
+        let mut dir = directory();
+        let base = format!("ou=people,{}", BASE);
+        let contractors = format!("ou=contractors,{}", base);
+        dir.fail_on(Operation::Delete, &contractors, 50, "insufficient access");
+
+        let result = delete_subtree(&mut dir, &base, &ProtectedDns::default(), |_, _| {})
+            .await
+            .unwrap();
+        assert!(!result.is_complete());
+        let (dn, error) = result.failure.unwrap();
+        assert_eq!(dn, contractors);
+        assert!(error.contains("insufficient access"), "{}", error);
+        assert!(result
+            .deleted
+            .contains(&format!("uid=alan.turing,{}", contractors)));
+        assert!(dir.entry(&base).is_some());
+        // The failed delete was the last one attempted
+        let last = dir
+            .log()
+            .iter()
+            .rev()
+            .find(|(op, _)| *op == Operation::Delete)
+            .map(|(_, dn)| dn.clone());
+        assert_eq!(last, Some(contractors));
+    }
+
+    #[tokio::test]
+    async fn test_protected_descendant_stops_everything() {
+        let mut dir = directory();
+        let base = format!("ou=people,{}", BASE);
+        let ada = format!("uid=ada.lovelace,{}", base);
+        let protected = ProtectedDns::for_server(
+            &ServerType::OpenLdap,
+            std::slice::from_ref(&ada),
+            ProtectionMode::Confirm,
+        );
+
+        let err = delete_subtree(&mut dir, &base, &protected, |_, _| {})
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains(&ada), "{}", err);
+        assert_eq!(dir.entries().len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_delete_entries_collects_failures() {
+        let mut dir = directory();
+        let people = format!("ou=people,{}", BASE);
+        let contractors = format!("ou=contractors,{}", people);
+        let alan = format!("uid=alan.turing,{}", contractors);
+        let ada = format!("uid=ada.lovelace,{}", people);
+        let groups = format!("ou=groups,{}", BASE);
+        let protected = ProtectedDns::for_server(
+            &ServerType::OpenLdap,
+            std::slice::from_ref(&groups),
+            ProtectionMode::Confirm,
+        );
+        dir.fail_on(Operation::Delete, &ada, 50, "insufficient access");
+
+        let result = delete_entries(
+            &mut dir,
+            vec![
+                contractors.clone(),
+                ada.clone(),
+                groups.clone(),
+                alan.clone(),
+            ],
+            &protected,
+        )
+        .await;
+        // The picked child went before its picked parent
+        assert_eq!(result.deleted, vec![alan, contractors]);
+        assert_eq!(result.errors.len(), 2);
+        assert_eq!(result.errors[0].0, ada);
+        assert!(result.errors[0].1.contains("insufficient access"));
+        assert_eq!(result.errors[1].0, groups);
+        assert!(result.errors[1].1.contains("protected"));
+        assert!(dir.entry(&groups).is_some());
+    }
+}
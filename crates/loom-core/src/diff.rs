@@ -0,0 +1,315 @@
+//! Attribute-level differences between two entries, or two copies of one.
+
+use std::fmt;
+
+use crate::entry::LdapEntry;
+
+/// How one attribute differs between two copies of an entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeChange {
+    pub attr: String,
+    /// Values only in the old copy.
+    pub removed: Vec<String>,
+    /// Values only in the new copy.
+    pub added: Vec<String>,
+}
+
+impl fmt::Display for AttributeChange {
+    /// One line per attribute, e.g. `~ description: "old" -> "new"`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.removed.as_slice(), self.added.as_slice()) {
+            ([], added) => write!(f, "+ {}: {}", self.attr, join_quoted(added)),
+            (removed, []) => write!(f, "- {}: {}", self.attr, join_quoted(removed)),
+            (removed, added) => write!(
+                f,
+                "~ {}: {} -> {}",
+                self.attr,
+                join_quoted(removed),
+                join_quoted(added)
+            ),
+        }
+    }
+}
+
+fn join_quoted(values: &[String]) -> String {
+    values
+        .iter()
+        .map(|v| format!("\"{}\"", v))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// How one attribute compares between two entries.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffKind {
+    Same,
+    /// Only the right entry has the attribute.
+    Added,
+    /// Only the left entry has the attribute.
+    Removed,
+    /// Both have it, with different values.
+    Changed,
+}
+
+/// One attribute of an [`EntryDiff`], with its values on each side.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AttributeDiff {
+    pub attr: String,
+    pub left: Vec<String>,
+    pub right: Vec<String>,
+}
+
+impl AttributeDiff {
+    pub fn kind(&self) -> DiffKind {
+        match (self.left.is_empty(), self.right.is_empty()) {
+            (true, _) => DiffKind::Added,
+            (_, true) => DiffKind::Removed,
+            _ if self.only_left().is_empty() && self.only_right().is_empty() => DiffKind::Same,
+            _ => DiffKind::Changed,
+        }
+    }
+
+    /// Values only the left entry holds.
+    pub fn only_left(&self) -> Vec<&String> {
+        self.left
+            .iter()
+            .filter(|v| !self.right.contains(v))
+            .collect()
+    }
+
+    /// Values only the right entry holds.
+    pub fn only_right(&self) -> Vec<&String> {
+        self.right
+            .iter()
+            .filter(|v| !self.left.contains(v))
+            .collect()
+    }
+
+    /// Values both entries hold.
+    pub fn common(&self) -> Vec<&String> {
+        self.left
+            .iter()
+            .filter(|v| self.right.contains(v))
+            .collect()
+    }
+
+    /// The change from left to right, or `None` when the values match.
+    pub fn change(&self) -> Option<AttributeChange> {
+        let removed: Vec<String> = self.only_left().into_iter().cloned().collect();
+        let added: Vec<String> = self.only_right().into_iter().cloned().collect();
+        (!removed.is_empty() || !added.is_empty()).then(|| AttributeChange {
+            attr: self.attr.clone(),
+            removed,
+            added,
+        })
+    }
+}
+
+/// Two entries compared attribute by attribute, every attribute of
+/// either entry aligned on one row, ordered by name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryDiff {
+    pub left_dn: String,
+    pub right_dn: String,
+    pub attributes: Vec<AttributeDiff>,
+}
+
+impl EntryDiff {
+    /// Whether the entries hold the same values.
+    pub fn is_empty(&self) -> bool {
+        self.attributes.iter().all(|a| a.kind() == DiffKind::Same)
+    }
+
+    /// The attributes that differ, as changes from left to right.
+    pub fn changes(&self) -> Vec<AttributeChange> {
+        self.attributes
+            .iter()
+            .filter_map(AttributeDiff::change)
+            .collect()
+    }
+
+    /// Attributes of one kind, e.g. only those the right entry added.
+    pub fn of_kind(&self, kind: DiffKind) -> impl Iterator<Item = &AttributeDiff> {
+        self.attributes.iter().filter(move |a| a.kind() == kind)
+    }
+
+    /// This diff without the attributes `skip` picks, e.g. operational ones.
+    pub fn without(&self, skip: impl Fn(&str) -> bool) -> EntryDiff {
+        EntryDiff {
+            left_dn: self.left_dn.clone(),
+            right_dn: self.right_dn.clone(),
+            attributes: self
+                .attributes
+                .iter()
+                .filter(|a| !skip(&a.attr))
+                .cloned()
+                .collect(),
+        }
+    }
+
+    /// The diff as unified-diff-style text: LDIF lines prefixed with a
+    /// space when both entries hold the value, `-` when only the left
+    /// does and `+` when only the right does.
+    pub fn to_unified(&self) -> String {
+        let mut out = format!("--- {}\n+++ {}\n", self.left_dn, self.right_dn);
+        for attr in &self.attributes {
+            for value in attr.common() {
+                out.push_str(&format!(" {}: {}\n", attr.attr, value));
+            }
+            for value in attr.only_left() {
+                out.push_str(&format!("-{}: {}\n", attr.attr, value));
+            }
+            for value in attr.only_right() {
+                out.push_str(&format!("+{}: {}\n", attr.attr, value));
+            }
+        }
+        out
+    }
+}
+
+/// Compare two entries. Attribute names match case-insensitively and keep
+/// the left entry's spelling; values compare exactly, binary ones as their
+/// base64.
+pub fn diff_entries(left: &LdapEntry, right: &LdapEntry) -> EntryDiff {
+    let mut names: Vec<&String> = left.attribute_names().collect();
+    for name in right.attribute_names() {
+        if !names.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+            names.push(name);
+        }
+    }
+    names.sort_by_key(|n| n.to_lowercase());
+
+    let values = |entry: &LdapEntry, name: &str| -> Vec<String> {
+        entry
+            .values_ci(name)
+            .map(|v| v.to_text())
+            .unwrap_or_default()
+    };
+
+    EntryDiff {
+        left_dn: left.dn.clone(),
+        right_dn: right.dn.clone(),
+        attributes: names
+            .into_iter()
+            .map(|name| AttributeDiff {
+                attr: name.clone(),
+                left: values(left, name),
+                right: values(right, name),
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn entry(attrs: &[(&str, &[&str])]) -> LdapEntry {
+        let attributes: BTreeMap<String, Vec<String>> = attrs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.iter().map(|s| s.to_string()).collect()))
+            .collect();
+        LdapEntry::new("cn=svc,dc=example,dc=com".to_string(), attributes)
+    }
+
+    #[test]
+    fn test_diff_entries() {
+        let old = entry(&[
+            ("cn", &["svc"]),
+            ("description", &["Build server"]),
+            ("member", &["uid=a", "uid=b"]),
+        ]);
+        let new = entry(&[
+            ("CN", &["svc"]),
+            ("description", &["Payroll export"]),
+            ("member", &["uid=a"]),
+            ("mail", &["svc@example.com"]),
+        ]);
+        let changes = diff_entries(&old, &new).changes();
+        let lines: Vec<String> = changes.iter().map(|c| c.to_string()).collect();
+        assert_eq!(
+            lines,
+            vec![
+                "~ description: \"Build server\" -> \"Payroll export\"",
+                "+ mail: \"svc@example.com\"",
+                "- member: \"uid=b\"",
+            ]
+        );
+        assert!(diff_entries(&old, &old).is_empty());
+    }
+
+    #[test]
+    fn test_entry_diff_kinds() {
+        let left = entry(&[
+            ("cn", &["svc"]),
+            ("description", &["Build server"]),
+            ("member", &["uid=a", "uid=b"]),
+            ("seeAlso", &["cn=x"]),
+        ]);
+        let mut right = entry(&[
+            ("CN", &["svc"]),
+            ("member", &["uid=b", "uid=c"]),
+            ("mail", &["svc@example.com"]),
+            ("seeAlso", &["cn=x"]),
+        ]);
+        right.dn = "cn=svc2,dc=example,dc=com".to_string();
+        let diff = diff_entries(&left, &right);
+
+        let kinds: Vec<(&str, DiffKind)> = diff
+            .attributes
+            .iter()
+            .map(|a| (a.attr.as_str(), a.kind()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("cn", DiffKind::Same),
+                ("description", DiffKind::Removed),
+                ("mail", DiffKind::Added),
+                ("member", DiffKind::Changed),
+                ("seeAlso", DiffKind::Same),
+            ]
+        );
+        let member = diff.of_kind(DiffKind::Changed).next().unwrap();
+        assert_eq!(member.only_left(), vec!["uid=a"]);
+        assert_eq!(member.only_right(), vec!["uid=c"]);
+        assert_eq!(member.common(), vec!["uid=b"]);
+        assert!(!diff.is_empty());
+        assert!(diff_entries(&left, &left).is_empty());
+    }
+
+    #[test]
+    fn test_binary_values_are_compared() {
+        let photo =
+            |bytes: &[u8]| BTreeMap::from([("jpegPhoto".to_string(), vec![bytes.to_vec()])]);
+        let left = entry(&[("cn", &["svc"])]).with_binary_attributes(photo(&[0xff, 0xd8]));
+        let right = entry(&[("cn", &["svc"])]).with_binary_attributes(photo(&[0x89, 0x50]));
+        assert!(diff_entries(&left, &left.clone()).is_empty());
+        let changes = diff_entries(&left, &right).changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].to_string(), "~ jpegPhoto: \"/9g=\" -> \"iVA=\"");
+        let added = diff_entries(&entry(&[("cn", &["svc"])]), &right);
+        assert_eq!(
+            added.of_kind(DiffKind::Added).next().unwrap().attr,
+            "jpegPhoto"
+        );
+    }
+
+    #[test]
+    fn test_entry_diff_without_and_unified_text() {
+        let left = entry(&[("cn", &["svc"]), ("modifyTimestamp", &["20240101000000Z"])]);
+        let right = entry(&[
+            ("cn", &["svc"]),
+            ("description", &["new"]),
+            ("modifyTimestamp", &["20250101000000Z"]),
+        ]);
+        let diff = diff_entries(&left, &right)
+            .without(|attr| attr.eq_ignore_ascii_case("modifyTimestamp"));
+        assert_eq!(diff.attributes.len(), 2);
+        assert_eq!(
+            diff.to_unified(),
+            "--- cn=svc,dc=example,dc=com\n+++ cn=svc,dc=example,dc=com\n cn: svc\n+description: new\n"
+        );
+    }
+}
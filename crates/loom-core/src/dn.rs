@@ -1,11 +1,549 @@
-/// Get the parent DN (everything after the first comma).
+//! Distinguished names: a parsed [`Dn`] model following RFC 4514, and
+//! string helpers for code that only holds the DN as text.
+//!
+//! Attribute types and values compare case-insensitively, as almost
+//! every naming attribute uses a case-ignoring match.
+
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+
+/// Why a DN failed to parse, and where.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnError {
+    /// Byte offset into the DN as given.
+    pub position: usize,
+    pub message: String,
+}
+
+impl DnError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for DnError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for DnError {}
+
+/// One `type=value` pair of an RDN, with the value unescaped.
+#[derive(Debug, Clone)]
+pub struct Ava {
+    attr: String,
+    value: String,
+    /// The value was given as `#` and BER-encoded hex, kept as written.
+    hex: bool,
+}
+
+impl Ava {
+    pub fn new(attr: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            attr: attr.into(),
+            value: value.into(),
+            hex: false,
+        }
+    }
+
+    pub fn attr(&self) -> &str {
+        &self.attr
+    }
+
+    /// The value with escapes resolved; a `#` hex value as written.
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    fn key(&self) -> (String, bool, String) {
+        (
+            self.attr.to_ascii_lowercase(),
+            self.hex,
+            self.value.to_lowercase(),
+        )
+    }
+}
+
+impl PartialEq for Ava {
+    fn eq(&self, other: &Self) -> bool {
+        self.attr.eq_ignore_ascii_case(&other.attr)
+            && self.hex == other.hex
+            && (self.value == other.value
+                || self.value.to_lowercase() == other.value.to_lowercase())
+    }
+}
+
+impl Eq for Ava {}
+
+impl fmt::Display for Ava {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.hex {
+            write!(f, "{}={}", self.attr, self.value)
+        } else {
+            write!(f, "{}={}", self.attr, escape_value(&self.value))
+        }
+    }
+}
+
+/// A relative distinguished name: one or more AVAs joined by `+`.
+#[derive(Debug, Clone)]
+pub struct Rdn {
+    avas: Vec<Ava>,
+}
+
+impl Rdn {
+    /// A single-valued RDN such as `cn=Smith, John`, from its unescaped
+    /// value.
+    pub fn new(attr: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            avas: vec![Ava::new(attr, value)],
+        }
+    }
+
+    /// Parse one RDN, such as `cn=foo+sn=bar`.
+    pub fn parse(input: &str) -> Result<Self, DnError> {
+        let mut parser = Parser::new(input);
+        parser.skip_spaces();
+        let rdn = parser.rdn()?;
+        parser.skip_spaces();
+        if let Some(c) = parser.peek() {
+            return Err(DnError::new(
+                parser.pos,
+                format!("Unexpected '{}' at position {}", c as char, parser.pos + 1),
+            ));
+        }
+        Ok(rdn)
+    }
+
+    /// The AVAs in the order written.
+    pub fn avas(&self) -> &[Ava] {
+        &self.avas
+    }
+
+    pub fn is_multi_valued(&self) -> bool {
+        self.avas.len() > 1
+    }
+
+    /// The first AVA's value, which names the entry in a tree.
+    pub fn value(&self) -> &str {
+        &self.avas[0].value
+    }
+
+    /// AVAs sorted case-insensitively, so `cn=a+sn=b` and `SN=b+cn=A`
+    /// compare alike.
+    fn key(&self) -> Vec<(String, bool, String)> {
+        let mut key: Vec<_> = self.avas.iter().map(Ava::key).collect();
+        key.sort();
+        key
+    }
+}
+
+impl PartialEq for Rdn {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.avas.as_slice(), other.avas.as_slice()) {
+            ([a], [b]) => a == b,
+            _ => self.key() == other.key(),
+        }
+    }
+}
+
+impl Eq for Rdn {}
+
+impl Hash for Rdn {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl PartialOrd for Rdn {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rdn {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl fmt::Display for Rdn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, ava) in self.avas.iter().enumerate() {
+            if i > 0 {
+                f.write_str("+")?;
+            }
+            write!(f, "{}", ava)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Rdn {
+    type Err = DnError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// A parsed distinguished name. The empty DN is the root of the tree,
+/// and the [`Default`].
+///
+/// DNs order hierarchically, comparing RDNs from the root down, so a
+/// sorted list puts every entry right after its parent.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Dn {
+    /// Leaf first, as written.
+    rdns: Vec<Rdn>,
+}
+
+impl Dn {
+    /// Parse a DN in its RFC 4514 string form. Backslash escapes, both
+    /// `\,` and hex such as `\2C`, are resolved; unescaped spaces around
+    /// separators are dropped, escaped ones kept. An empty RDN, as in
+    /// `cn=a,,dc=com`, is an error.
+    pub fn parse(input: &str) -> Result<Self, DnError> {
+        let mut parser = Parser::new(input);
+        parser.skip_spaces();
+        if parser.peek().is_none() {
+            return Ok(Self::default());
+        }
+        let mut rdns = vec![parser.rdn()?];
+        loop {
+            parser.skip_spaces();
+            match parser.peek() {
+                None => break,
+                Some(b',') => {
+                    parser.pos += 1;
+                    parser.skip_spaces();
+                    rdns.push(parser.rdn()?);
+                }
+                Some(c) => {
+                    return Err(DnError::new(
+                        parser.pos,
+                        format!("Unexpected '{}' at position {}", c as char, parser.pos + 1),
+                    ))
+                }
+            }
+        }
+        Ok(Self { rdns })
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.rdns.is_empty()
+    }
+
+    /// The leaf RDN; `None` for the root.
+    pub fn rdn(&self) -> Option<&Rdn> {
+        self.rdns.first()
+    }
+
+    /// The RDNs from the leaf up.
+    pub fn rdns(&self) -> &[Rdn] {
+        &self.rdns
+    }
+
+    /// The DN one level up; `None` for the root.
+    pub fn parent(&self) -> Option<Dn> {
+        (!self.is_root()).then(|| Self {
+            rdns: self.rdns[1..].to_vec(),
+        })
+    }
+
+    /// Number of RDNs; 0 for the root.
+    pub fn depth(&self) -> usize {
+        self.rdns.len()
+    }
+
+    /// Whether this DN lies strictly below `ancestor`. Everything lies
+    /// below the root.
+    pub fn is_descendant_of(&self, ancestor: &Dn) -> bool {
+        self.depth() > ancestor.depth() && self.rdns.ends_with(&ancestor.rdns)
+    }
+
+    /// Make this the DN of a child named `rdn`.
+    pub fn append_child(&mut self, rdn: Rdn) {
+        self.rdns.insert(0, rdn);
+    }
+}
+
+impl PartialOrd for Dn {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Dn {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.rdns.iter().rev().cmp(other.rdns.iter().rev())
+    }
+}
+
+impl fmt::Display for Dn {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, rdn) in self.rdns.iter().enumerate() {
+            if i > 0 {
+                f.write_str(",")?;
+            }
+            write!(f, "{}", rdn)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Dn {
+    type Err = DnError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Escape a value for a DN string: the RFC 4514 specials anywhere, a
+/// leading `#` or space, a trailing space, and NUL.
+pub fn escape_value(value: &str) -> Cow<'_, str> {
+    let last = value.chars().count().saturating_sub(1);
+    let needs = |i: usize, c: char| {
+        matches!(c, '"' | '+' | ',' | ';' | '<' | '>' | '\\')
+            || (i == 0 && (c == '#' || c == ' '))
+            || (i == last && c == ' ')
+    };
+    if !value
+        .chars()
+        .enumerate()
+        .any(|(i, c)| needs(i, c) || c == '\0')
+    {
+        return Cow::Borrowed(value);
+    }
+    let mut out = String::with_capacity(value.len() + 4);
+    for (i, c) in value.chars().enumerate() {
+        if c == '\0' {
+            out.push_str("\\00");
+            continue;
+        }
+        if needs(i, c) {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    Cow::Owned(out)
+}
+
+/// Byte-wise reader over a DN string.
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(input: &'a str) -> Self {
+        Self {
+            input: input.as_bytes(),
+            pos: 0,
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn skip_spaces(&mut self) {
+        while self.peek() == Some(b' ') {
+            self.pos += 1;
+        }
+    }
+
+    /// attributeTypeAndValue *( "+" attributeTypeAndValue )
+    fn rdn(&mut self) -> Result<Rdn, DnError> {
+        let mut avas = vec![self.ava()?];
+        loop {
+            self.skip_spaces();
+            if self.peek() != Some(b'+') {
+                return Ok(Rdn { avas });
+            }
+            self.pos += 1;
+            self.skip_spaces();
+            avas.push(self.ava()?);
+        }
+    }
+
+    fn ava(&mut self) -> Result<Ava, DnError> {
+        let start = self.pos;
+        while self
+            .peek()
+            .is_some_and(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'.')
+        {
+            self.pos += 1;
+        }
+        if self.pos == start {
+            return Err(match self.peek() {
+                None | Some(b',') => {
+                    DnError::new(start, format!("Empty RDN at position {}", start + 1))
+                }
+                Some(c) => DnError::new(
+                    start,
+                    format!(
+                        "Expected attribute type at position {}, found '{}'",
+                        start + 1,
+                        c as char
+                    ),
+                ),
+            });
+        }
+        let attr = String::from_utf8_lossy(&self.input[start..self.pos]).into_owned();
+        self.skip_spaces();
+        if self.peek() != Some(b'=') {
+            return Err(DnError::new(
+                self.pos,
+                format!("Expected '=' after '{}' at position {}", attr, self.pos + 1),
+            ));
+        }
+        self.pos += 1;
+        self.skip_spaces();
+        if self.peek() == Some(b'#') {
+            return self.hex_value(attr);
+        }
+        let value = self.string_value()?;
+        Ok(Ava {
+            attr,
+            value,
+            hex: false,
+        })
+    }
+
+    /// `#` followed by the hex of a BER encoding, kept as written.
+    fn hex_value(&mut self, attr: String) -> Result<Ava, DnError> {
+        let start = self.pos;
+        self.pos += 1;
+        while self.peek().is_some_and(|c| c.is_ascii_hexdigit()) {
+            self.pos += 1;
+        }
+        let digits = self.pos - start - 1;
+        if digits == 0 || digits % 2 != 0 {
+            return Err(DnError::new(
+                start,
+                format!(
+                    "Hex value at position {} needs an even number of digits",
+                    start + 1
+                ),
+            ));
+        }
+        let value = String::from_utf8_lossy(&self.input[start..self.pos]).into_owned();
+        Ok(Ava {
+            attr,
+            value,
+            hex: true,
+        })
+    }
+
+    /// A string value up to the next unescaped `,` or `+`, with escapes
+    /// resolved and unescaped trailing spaces dropped.
+    fn string_value(&mut self) -> Result<String, DnError> {
+        let mut bytes = Vec::new();
+        // Length of `bytes` without unescaped trailing spaces
+        let mut keep = 0;
+        while let Some(c) = self.peek() {
+            match c {
+                b',' | b'+' => break,
+                b'\\' => {
+                    let at = self.pos;
+                    let next = self.input.get(at + 1).copied();
+                    let hex = self
+                        .input
+                        .get(at + 1..at + 3)
+                        .filter(|pair| pair.iter().all(u8::is_ascii_hexdigit))
+                        .and_then(|pair| {
+                            u8::from_str_radix(std::str::from_utf8(pair).ok()?, 16).ok()
+                        });
+                    match (hex, next) {
+                        (Some(byte), _) => {
+                            bytes.push(byte);
+                            self.pos += 3;
+                        }
+                        (None, Some(next)) if b"\"+,;<>\\ #=".contains(&next) => {
+                            bytes.push(next);
+                            self.pos += 2;
+                        }
+                        (None, Some(_)) => {
+                            return Err(DnError::new(
+                                at,
+                                format!("Invalid escape at position {}", at + 1),
+                            ))
+                        }
+                        (None, None) => {
+                            return Err(DnError::new(
+                                at,
+                                format!("Dangling '\\' at position {}", at + 1),
+                            ))
+                        }
+                    }
+                    keep = bytes.len();
+                }
+                b' ' => {
+                    bytes.push(c);
+                    self.pos += 1;
+                }
+                _ => {
+                    bytes.push(c);
+                    self.pos += 1;
+                    keep = bytes.len();
+                }
+            }
+        }
+        bytes.truncate(keep);
+        String::from_utf8(bytes).map_err(|_| {
+            DnError::new(
+                self.pos,
+                format!(
+                    "Escaped value before position {} is not UTF-8",
+                    self.pos + 1
+                ),
+            )
+        })
+    }
+}
+
+/// Byte offsets of the commas that separate RDNs, skipping escaped ones.
+fn separators(dn: &str) -> impl Iterator<Item = usize> + '_ {
+    let mut escaped = false;
+    dn.bytes().enumerate().filter_map(move |(i, c)| {
+        let split = c == b',' && !escaped;
+        escaped = c == b'\\' && !escaped;
+        split.then_some(i)
+    })
+}
+
+/// The RDNs of a DN as written, leaf first, split at unescaped commas.
+pub fn components(dn: &str) -> Vec<&str> {
+    if dn.trim().is_empty() {
+        return Vec::new();
+    }
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for i in separators(dn) {
+        parts.push(dn[start..i].trim());
+        start = i + 1;
+    }
+    parts.push(dn[start..].trim());
+    parts
+}
+
+/// Get the parent DN (everything after the first unescaped comma).
 pub fn parent_dn(dn: &str) -> Option<&str> {
-    dn.find(',').map(|i| &dn[i + 1..])
+    separators(dn).next().map(|i| dn[i + 1..].trim_start())
 }
 
-/// Get the RDN (first component before the first comma).
+/// Get the RDN (everything before the first unescaped comma).
 pub fn rdn(dn: &str) -> &str {
-    dn.split(',').next().unwrap_or(dn)
+    separators(dn).next().map_or(dn, |i| &dn[..i])
 }
 
 /// Get the depth of a DN (number of components).
@@ -13,7 +551,7 @@ pub fn depth(dn: &str) -> usize {
     if dn.is_empty() {
         0
     } else {
-        dn.split(',').count()
+        separators(dn).count() + 1
     }
 }
 
@@ -22,15 +560,71 @@ pub fn is_ancestor(dn: &str, ancestor: &str) -> bool {
     if ancestor.is_empty() {
         return true;
     }
+    if let (Ok(dn), Ok(ancestor)) = (Dn::parse(dn), Dn::parse(ancestor)) {
+        return dn.is_descendant_of(&ancestor);
+    }
     let dn_lower = dn.to_lowercase();
     let ancestor_lower = ancestor.to_lowercase();
     dn_lower.ends_with(&ancestor_lower) && dn_lower.len() > ancestor_lower.len()
 }
 
-/// Get the display name from an RDN (the value part after '=').
-pub fn rdn_display_name(dn: &str) -> &str {
+/// Normalize a DN for comparison: lowercase, with whitespace around `,`
+/// and `=` removed and escapes written one way. Text that is not a valid
+/// DN, such as a protection pattern, is only trimmed and lowercased.
+pub fn normalize(dn: &str) -> String {
+    if let Ok(parsed) = Dn::parse(dn) {
+        return parsed.to_string().to_lowercase();
+    }
+    dn.split(',')
+        .map(|rdn| {
+            rdn.split('=')
+                .map(|part| part.trim())
+                .collect::<Vec<_>>()
+                .join("=")
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+        .to_lowercase()
+}
+
+/// The DN `dn` would have after a ModifyDN to `new_rdn` and, with
+/// `new_parent`, under a new superior. Both are checked as typed, and an
+/// entry cannot move below itself.
+pub fn renamed(dn: &str, new_rdn: &str, new_parent: Option<&str>) -> Result<String, DnError> {
+    let new_rdn = new_rdn.trim();
+    if new_rdn.is_empty() {
+        return Err(DnError::new(0, "The RDN is empty"));
+    }
+    Rdn::parse(new_rdn)?;
+    let parent = match new_parent.map(str::trim).filter(|p| !p.is_empty()) {
+        Some(parent) => {
+            let parsed = Dn::parse(parent)?;
+            if let Ok(current) = Dn::parse(dn) {
+                if parsed == current || parsed.is_descendant_of(&current) {
+                    return Err(DnError::new(0, "An entry cannot move below itself"));
+                }
+            }
+            parent
+        }
+        None => parent_dn(dn).unwrap_or_default(),
+    };
+    Ok(if parent.is_empty() {
+        new_rdn.to_string()
+    } else {
+        format!("{},{}", new_rdn, parent)
+    })
+}
+
+/// Get the display name from an RDN: its first value, unescaped.
+pub fn rdn_display_name(dn: &str) -> Cow<'_, str> {
     let r = rdn(dn);
-    r.find('=').map(|i| &r[i + 1..]).unwrap_or(r)
+    let raw = r.find('=').map(|i| r[i + 1..].trim()).unwrap_or(r);
+    if !raw.contains(['\\', '+']) {
+        return Cow::Borrowed(raw);
+    }
+    Rdn::parse(r).map_or(Cow::Borrowed(raw), |rdn| {
+        Cow::Owned(rdn.value().to_string())
+    })
 }
 
 #[cfg(test)]
@@ -102,8 +696,234 @@ mod tests {
         assert_eq!(depth("cn=user,ou=people,ou=dept,dc=example,dc=com"), 5);
     }
 
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("CN = Admin , DC=Example,dc= com"),
+            "cn=admin,dc=example,dc=com"
+        );
+    }
+
     #[test]
     fn test_rdn_display_name_no_equals() {
         assert_eq!(rdn_display_name("nodots"), "nodots");
     }
+
+    fn values(dn: &str) -> Vec<String> {
+        Dn::parse(dn)
+            .unwrap()
+            .rdns()
+            .iter()
+            .flat_map(Rdn::avas)
+            .map(|ava| ava.value().to_string())
+            .collect()
+    }
+
+    fn error(dn: &str) -> DnError {
+        Dn::parse(dn).unwrap_err()
+    }
+
+    #[test]
+    fn test_parse_escaped_characters() {
+        let dn = Dn::parse(r"cn=Smith\, John,ou=People,dc=example,dc=com").unwrap();
+        assert_eq!(dn.depth(), 4);
+        assert_eq!(dn.rdn().unwrap().value(), "Smith, John");
+        assert_eq!(
+            values(r#"cn=\"Q\" \+ \; \<x\> \\ y,dc=com"#),
+            vec![r#""Q" + ; <x> \ y"#, "com"]
+        );
+        assert_eq!(values(r"cn=\#1 a\=b,dc=com"), vec!["#1 a=b", "com"]);
+        assert_eq!(error(r"cn=a\q").message, "Invalid escape at position 5");
+        assert_eq!(error(r"cn=a\").message, "Dangling '\\' at position 5");
+    }
+
+    #[test]
+    fn test_parse_hex_escapes() {
+        assert_eq!(
+            values(r"cn=Smith\2C John,dc=com"),
+            vec!["Smith, John", "com"]
+        );
+        assert_eq!(values(r"cn=\2c\5C\2B"), vec![r",\+"]);
+        // Multi-byte UTF-8 spelled out byte by byte
+        assert_eq!(values(r"cn=Lu\C4\8Di\C4\87"), vec!["Lučić"]);
+        assert_eq!(
+            error(r"cn=\C4,dc=com").message,
+            "Escaped value before position 7 is not UTF-8"
+        );
+        // A hex-encoded BER value is kept as written
+        let dn = Dn::parse("1.3.6.1.4.1.1466.0=#04024869,dc=com").unwrap();
+        assert_eq!(dn.rdn().unwrap().avas()[0].attr(), "1.3.6.1.4.1.1466.0");
+        assert_eq!(dn.rdn().unwrap().value(), "#04024869");
+        assert_eq!(dn.to_string(), "1.3.6.1.4.1.1466.0=#04024869,dc=com");
+        assert!(Dn::parse("cn=#0").is_err());
+    }
+
+    #[test]
+    fn test_parse_spaces() {
+        // Unescaped spaces around separators go, escaped ones stay
+        assert_eq!(
+            Dn::parse("  cn = Admin ,  dc=example , dc=com  ").unwrap(),
+            Dn::parse("cn=Admin,dc=example,dc=com").unwrap()
+        );
+        assert_eq!(values(r"cn=trailing\ ,dc=com"), vec!["trailing ", "com"]);
+        assert_eq!(values(r"cn=\20lead"), vec![" lead"]);
+        assert_eq!(values(r"cn=a b  c  "), vec!["a b  c"]);
+        assert_eq!(values(r"cn=a\  "), vec!["a "]);
+        assert_eq!(values("cn="), vec![""]);
+    }
+
+    #[test]
+    fn test_parse_empty_rdns() {
+        assert!(Dn::parse("").unwrap().is_root());
+        assert!(Dn::parse("   ").unwrap().is_root());
+        assert_eq!(error("cn=a,,dc=com").message, "Empty RDN at position 6");
+        assert_eq!(error(",dc=com").message, "Empty RDN at position 1");
+        assert_eq!(error("cn=a,dc=com,").message, "Empty RDN at position 13");
+        assert_eq!(error("cn=a, ,dc=com").position, 6);
+        assert_eq!(
+            error("cn=a,=x").message,
+            "Expected attribute type at position 6, found '='"
+        );
+        assert_eq!(
+            error("cn=a,dc").message,
+            "Expected '=' after 'dc' at position 8"
+        );
+        assert!(Dn::parse("cn=a+,dc=com").is_err());
+    }
+
+    #[test]
+    fn test_multi_valued_rdn() {
+        let dn = Dn::parse("cn=foo+sn=bar,dc=com").unwrap();
+        let rdn = dn.rdn().unwrap();
+        assert!(rdn.is_multi_valued());
+        assert_eq!(rdn.avas().len(), 2);
+        assert_eq!(rdn.avas()[1].attr(), "sn");
+        assert_eq!(rdn.value(), "foo");
+        assert_eq!(dn.depth(), 2);
+        // AVA order does not matter, nor does case
+        assert_eq!(dn, Dn::parse("SN=Bar + CN=FOO,DC=com").unwrap());
+        assert_ne!(dn, Dn::parse("cn=foo+cn=foo,dc=com").unwrap());
+        assert_eq!(values(r"cn=a\+b,dc=com"), vec!["a+b", "com"]);
+    }
+
+    #[test]
+    fn test_case_insensitive_equality() {
+        assert_eq!(
+            Dn::parse("CN=Admin,DC=Example,dc=COM").unwrap(),
+            Dn::parse("cn=admin,dc=example,dc=com").unwrap()
+        );
+        assert_eq!(Ava::new("mail", "x"), Ava::new("MAIL", "x"));
+        assert_ne!(Ava::new("mail", "x"), Ava::new("uid", "x"));
+    }
+
+    #[test]
+    fn test_hierarchy() {
+        let base = Dn::parse("dc=example,dc=com").unwrap();
+        let user = Dn::parse(r"cn=Smith\, John,ou=People,dc=example,dc=com").unwrap();
+        assert_eq!(
+            user.parent().unwrap().to_string(),
+            "ou=People,dc=example,dc=com"
+        );
+        assert!(user.is_descendant_of(&base));
+        assert!(user.is_descendant_of(&Dn::default()));
+        assert!(!base.is_descendant_of(&base));
+        assert!(!base.is_descendant_of(&user));
+        assert!(!Dn::parse("dc=xexample,dc=com")
+            .unwrap()
+            .is_descendant_of(&base));
+        assert_eq!(Dn::default().parent(), None);
+        assert_eq!(base.parent().unwrap().parent(), Some(Dn::default()));
+
+        let mut child = base.clone();
+        child.append_child(Rdn::new("ou", "Sales, EMEA"));
+        assert_eq!(child.depth(), 3);
+        assert_eq!(child.parent(), Some(base));
+        assert_eq!(child.to_string(), r"ou=Sales\, EMEA,dc=example,dc=com");
+        assert_eq!(Dn::parse(&child.to_string()).unwrap(), child);
+    }
+
+    #[test]
+    fn test_hierarchical_order() {
+        let mut dns: Vec<Dn> = [
+            "uid=b,ou=People,dc=example,dc=com",
+            "ou=Groups,dc=example,dc=com",
+            "dc=example,dc=com",
+            "uid=a,ou=People,dc=example,dc=com",
+            "ou=People,dc=example,dc=com",
+        ]
+        .iter()
+        .map(|s| s.parse().unwrap())
+        .collect();
+        dns.sort();
+        let sorted: Vec<String> = dns.iter().map(Dn::to_string).collect();
+        assert_eq!(
+            sorted,
+            vec![
+                "dc=example,dc=com",
+                "ou=Groups,dc=example,dc=com",
+                "ou=People,dc=example,dc=com",
+                "uid=a,ou=People,dc=example,dc=com",
+                "uid=b,ou=People,dc=example,dc=com",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_display_escapes() {
+        assert_eq!(escape_value("plain"), "plain");
+        assert_eq!(escape_value(" #a,b+c "), r"\ #a\,b\+c\ ");
+        assert_eq!(escape_value("#x"), r"\#x");
+        assert_eq!(escape_value("a\0"), r"a\00");
+        let dn = Dn::parse(r"cn=a\2Cb\20,dc=com").unwrap();
+        assert_eq!(dn.to_string(), r"cn=a\,b\ ,dc=com");
+    }
+
+    #[test]
+    fn test_string_helpers_skip_escaped_commas() {
+        let dn = r"cn=Smith\, John,ou=People,dc=example,dc=com";
+        assert_eq!(rdn(dn), r"cn=Smith\, John");
+        assert_eq!(parent_dn(dn), Some("ou=People,dc=example,dc=com"));
+        assert_eq!(depth(dn), 4);
+        assert_eq!(rdn_display_name(dn), "Smith, John");
+        assert_eq!(rdn_display_name(r"cn=a\\,dc=com"), r"a\");
+        assert_eq!(
+            components(dn),
+            vec![r"cn=Smith\, John", "ou=People", "dc=example", "dc=com"]
+        );
+        assert!(components("").is_empty());
+        assert_eq!(
+            normalize(r"CN=Smith\2C John , DC=Com"),
+            r"cn=smith\, john,dc=com"
+        );
+        assert!(!is_ancestor("dc=xexample,dc=com", "dc=example,dc=com"));
+    }
+
+    #[test]
+    fn test_renamed() {
+        let dn = "cn=Old,ou=People,dc=example,dc=com";
+        assert_eq!(
+            renamed(dn, " cn=New ", None).unwrap(),
+            "cn=New,ou=People,dc=example,dc=com"
+        );
+        assert_eq!(
+            renamed(dn, "cn=Old", Some("ou=Staff,dc=example,dc=com")).unwrap(),
+            "cn=Old,ou=Staff,dc=example,dc=com"
+        );
+        // A blank parent keeps the entry where it is
+        assert_eq!(
+            renamed(dn, "uid=x", Some("  ")).unwrap(),
+            "uid=x,ou=People,dc=example,dc=com"
+        );
+
+        assert!(renamed(dn, "", None).is_err());
+        assert!(renamed(dn, "cn=a,ou=b", None).is_err());
+        assert!(renamed(dn, "cn=New", Some("ou=Staff,example")).is_err());
+        let err = renamed(
+            dn,
+            "cn=Old",
+            Some("ou=x,CN=old,ou=people,dc=example,dc=com"),
+        )
+        .unwrap_err();
+        assert!(err.message.contains("below itself"));
+    }
 }
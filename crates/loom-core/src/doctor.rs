@@ -0,0 +1,340 @@
+//! Environment self-checks for `loom doctor` and the `:doctor` popup.
+//!
+//! Each check is a standalone function returning a [`CheckResult`], so the
+//! list is easy to extend and every check can be unit-tested in isolation.
+
+use std::path::Path;
+
+use crate::config::AppConfig;
+use crate::credentials::CredentialMethod;
+
+/// Outcome of a single check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckStatus {
+    Pass,
+    Warn,
+    Fail,
+}
+
+impl CheckStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "PASS",
+            CheckStatus::Warn => "WARN",
+            CheckStatus::Fail => "FAIL",
+        }
+    }
+}
+
+/// A single line of the doctor report.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    pub name: String,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// What the user can do about a warning or failure.
+    pub hint: Option<String>,
+}
+
+impl CheckResult {
+    pub fn pass(name: &str, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    pub fn warn(name: &str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Warn,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+
+    pub fn fail(name: &str, detail: impl Into<String>, hint: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// The collected results of all checks.
+#[derive(Debug, Clone, Default)]
+pub struct DoctorReport {
+    pub checks: Vec<CheckResult>,
+    /// Strings (hosts, DNs, profile names, home directory) hidden in redacted output.
+    redactions: Vec<String>,
+}
+
+impl DoctorReport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, check: CheckResult) {
+        self.checks.push(check);
+    }
+
+    /// Register a sensitive string to be hidden by `to_text(true)`.
+    pub fn add_redaction(&mut self, value: &str) {
+        if !value.trim().is_empty() && !self.redactions.iter().any(|r| r == value) {
+            self.redactions.push(value.to_string());
+        }
+    }
+
+    /// The most severe status across all checks.
+    pub fn worst(&self) -> CheckStatus {
+        self.checks
+            .iter()
+            .map(|c| c.status)
+            .max()
+            .unwrap_or(CheckStatus::Pass)
+    }
+
+    /// Plain-text report, one line per check plus hints.
+    /// With `redact`, registered sensitive strings and the home directory are masked.
+    pub fn to_text(&self, redact: bool) -> String {
+        let mut out = format!("loom doctor report (loom {})\n", env!("CARGO_PKG_VERSION"));
+        for check in &self.checks {
+            out.push_str(&format!(
+                "[{}] {}: {}\n",
+                check.status.label(),
+                check.name,
+                check.detail
+            ));
+            if let Some(ref hint) = check.hint {
+                out.push_str(&format!("       hint: {}\n", hint));
+            }
+        }
+        if redact {
+            self.redact(&out)
+        } else {
+            out
+        }
+    }
+
+    fn redact(&self, text: &str) -> String {
+        let mut out = text.to_string();
+        // Longest first so "dc=example,dc=com" is masked before "example"
+        let mut values: Vec<&String> = self.redactions.iter().collect();
+        values.sort_by_key(|v| std::cmp::Reverse(v.len()));
+        for value in values {
+            out = out.replace(value.as_str(), "<redacted>");
+        }
+        if let Some(home) = dirs::home_dir() {
+            let home = home.display().to_string();
+            if !home.is_empty() && home != "/" {
+                out = out.replace(&home, "~");
+            }
+        }
+        out
+    }
+}
+
+/// Check that the config file exists and parses.
+/// A missing file is only a warning: defaults are used.
+pub fn check_config_file(path: &Path) -> CheckResult {
+    const NAME: &str = "Config file";
+    if !path.exists() {
+        return CheckResult::warn(
+            NAME,
+            format!("{} not found, using defaults", path.display()),
+            "Save a profile (F10) or create the file to persist settings",
+        );
+    }
+    match std::fs::read_to_string(path) {
+        Ok(content) => match AppConfig::from_toml(&content) {
+            Ok(_) => CheckResult::pass(NAME, format!("{} parsed OK", path.display())),
+            Err(e) => CheckResult::fail(
+                NAME,
+                format!("{} does not parse: {}", path.display(), e.message()),
+                "Fix the TOML syntax; until then loom starts with defaults",
+            ),
+        },
+        Err(e) => CheckResult::fail(
+            NAME,
+            format!("{} unreadable: {}", path.display(), e),
+            "Check the file permissions",
+        ),
+    }
+}
+
+/// Report the application version and the config format version.
+pub fn check_config_version(content: Option<&str>) -> CheckResult {
+    let declared = content
+        .and_then(|c| c.parse::<toml::Table>().ok())
+        .and_then(|t| t.get("version").and_then(|v| v.as_integer()));
+    let format = match declared {
+        Some(v) => format!("config format v{}", v),
+        None => "config format unversioned".to_string(),
+    };
+    CheckResult::pass(
+        "Version",
+        format!("loom {}, {}", env!("CARGO_PKG_VERSION"), format),
+    )
+}
+
+/// Count profiles and list which rely on stored secrets.
+/// Takes (profile name, credential method) pairs.
+pub fn check_profiles(profiles: &[(&str, &CredentialMethod)]) -> CheckResult {
+    const NAME: &str = "Profiles";
+    if profiles.is_empty() {
+        return CheckResult::warn(
+            NAME,
+            "no profiles configured",
+            "Press F2 to connect, then F10 to save the connection",
+        );
+    }
+    let stored: Vec<String> = profiles
+        .iter()
+        .filter_map(|(name, method)| match method {
+            CredentialMethod::Keychain => Some(format!("{} (keychain)", name)),
+            CredentialMethod::Vault => Some(format!("{} (vault)", name)),
+            CredentialMethod::Command => Some(format!("{} (command)", name)),
+            CredentialMethod::Prompt => None,
+        })
+        .collect();
+    let detail = if stored.is_empty() {
+        format!("{} configured, none with stored secrets", profiles.len())
+    } else {
+        format!(
+            "{} configured, stored secrets: {}",
+            profiles.len(),
+            stored.join(", ")
+        )
+    };
+    CheckResult::pass(NAME, detail)
+}
+
+/// Check that a directory exists and is writable. A missing directory is
+/// reported, not created.
+pub fn check_directory_writable(name: &str, dir: &Path) -> CheckResult {
+    if !dir.is_dir() {
+        return CheckResult::warn(
+            name,
+            format!("{} does not exist", dir.display()),
+            "loom creates it on first save; otherwise create it yourself",
+        );
+    }
+    let probe = dir.join(".loom-doctor-probe");
+    match std::fs::write(&probe, b"ok") {
+        Ok(()) => {
+            let _ = std::fs::remove_file(&probe);
+            CheckResult::pass(name, format!("{} is writable", dir.display()))
+        }
+        Err(e) => CheckResult::fail(
+            name,
+            format!("{} is not writable: {}", dir.display(), e),
+            "Fix the directory permissions",
+        ),
+    }
+}
+
+/// Check that the OS keyring can be reached (without reading any real secret).
+pub fn check_keyring() -> CheckResult {
+    const NAME: &str = "Keyring";
+    let entry = match keyring::Entry::new("loom", "__loom_doctor__") {
+        Ok(entry) => entry,
+        Err(e) => {
+            return CheckResult::warn(
+                NAME,
+                format!("unavailable: {}", e),
+                "Use the vault or password_command credential methods instead",
+            )
+        }
+    };
+    match entry.get_password() {
+        Ok(_) | Err(keyring::Error::NoEntry) => CheckResult::pass(NAME, "available"),
+        Err(e) => CheckResult::warn(
+            NAME,
+            format!("unavailable: {}", e),
+            "Start a Secret Service provider (e.g. GNOME Keyring) or use the vault",
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_file_missing_is_warning() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = check_config_file(&dir.path().join("config.toml"));
+        assert_eq!(result.status, CheckStatus::Warn);
+        assert!(result.hint.is_some());
+    }
+
+    #[test]
+    fn test_config_file_parse_status() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, "[general]\ntheme = \"nord\"\n").unwrap();
+        assert_eq!(check_config_file(&path).status, CheckStatus::Pass);
+
+        std::fs::write(&path, "[general\ntheme = ").unwrap();
+        assert_eq!(check_config_file(&path).status, CheckStatus::Fail);
+    }
+
+    #[test]
+    fn test_config_version() {
+        assert!(check_config_version(Some("version = 2\n"))
+            .detail
+            .contains("config format v2"));
+        assert!(check_config_version(None).detail.contains("unversioned"));
+    }
+
+    #[test]
+    fn test_profiles_with_stored_secrets() {
+        assert_eq!(check_profiles(&[]).status, CheckStatus::Warn);
+        let result = check_profiles(&[
+            ("prod", &CredentialMethod::Keychain),
+            ("lab", &CredentialMethod::Prompt),
+        ]);
+        assert_eq!(result.status, CheckStatus::Pass);
+        assert!(result.detail.contains("2 configured"));
+        assert!(result.detail.contains("prod (keychain)"));
+        assert!(!result.detail.contains("lab"));
+    }
+
+    #[test]
+    fn test_directory_writable() {
+        let dir = tempfile::tempdir().unwrap();
+        let result = check_directory_writable("Data dir", dir.path());
+        assert_eq!(result.status, CheckStatus::Pass);
+        assert!(!dir.path().join(".loom-doctor-probe").exists());
+
+        let missing = dir.path().join("nested");
+        let result = check_directory_writable("Data dir", &missing);
+        assert_eq!(result.status, CheckStatus::Warn);
+        assert!(!missing.exists());
+    }
+
+    #[test]
+    fn test_report_redaction_and_worst() {
+        let mut report = DoctorReport::new();
+        report.push(CheckResult::pass(
+            "Profiles",
+            "prod.example.com as cn=admin,dc=example,dc=com",
+        ));
+        report.push(CheckResult::warn("Keyring", "unavailable", "use the vault"));
+        report.add_redaction("prod.example.com");
+        report.add_redaction("cn=admin,dc=example,dc=com");
+
+        assert_eq!(report.worst(), CheckStatus::Warn);
+        let plain = report.to_text(false);
+        assert!(plain.contains("prod.example.com"));
+        let redacted = report.to_text(true);
+        assert!(!redacted.contains("prod.example.com"));
+        assert!(!redacted.contains("cn=admin"));
+        assert!(redacted.contains("[WARN] Keyring: unavailable"));
+        assert!(redacted.contains("hint: use the vault"));
+    }
+}
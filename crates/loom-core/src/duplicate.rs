@@ -0,0 +1,361 @@
+//! Duplicating entries: one entry as the template for a new one, or a
+//! whole branch copied under a new parent. Attributes the server keeps
+//! for itself are left out, since an add that carries them is refused.
+
+use std::collections::BTreeMap;
+
+use ldap3::Scope;
+
+use crate::dn::{Dn, Rdn};
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::ops::{add_attributes, add_binary_attributes, DirectoryOps};
+use crate::schema::{AttributeSyntax, SchemaCache};
+
+/// Attributes servers maintain themselves, for when the schema is not
+/// loaded or does not mark them `NO-USER-MODIFICATION`.
+pub const SERVER_MAINTAINED: &[&str] = &[
+    // RFC 4512 and OpenLDAP
+    "entryUUID",
+    "entryCSN",
+    "entryDN",
+    "createTimestamp",
+    "modifyTimestamp",
+    "creatorsName",
+    "modifiersName",
+    "structuralObjectClass",
+    "subschemaSubentry",
+    "hasSubordinates",
+    "numSubordinates",
+    "subordinateCount",
+    "contextCSN",
+    "pwdChangedTime",
+    "memberOf",
+    "isMemberOf",
+    // 389 Directory Server
+    "nsUniqueId",
+    // Active Directory
+    "objectGUID",
+    "objectSid",
+    "whenCreated",
+    "whenChanged",
+    "uSNCreated",
+    "uSNChanged",
+    // OpenDJ
+    "ds-entry-unique-id",
+];
+
+/// Whether the server sets `attr` itself, by the list above or by the
+/// schema.
+pub fn is_server_maintained(attr: &str, schema: Option<&SchemaCache>) -> bool {
+    SERVER_MAINTAINED
+        .iter()
+        .any(|a| a.eq_ignore_ascii_case(attr))
+        || schema
+            .and_then(|s| s.get_attribute_type(attr))
+            .is_some_and(|at| at.no_user_modification)
+}
+
+/// The text attributes of `entry` a copy of it can carry.
+pub fn copyable_attributes(
+    entry: &LdapEntry,
+    schema: Option<&SchemaCache>,
+) -> BTreeMap<String, Vec<String>> {
+    entry
+        .attributes
+        .iter()
+        .filter(|(attr, _)| !is_server_maintained(attr, schema))
+        .map(|(attr, values)| (attr.clone(), values.clone()))
+        .collect()
+}
+
+/// `dn` moved from below (or at) `from` to the same place below `to`;
+/// `None` when `dn` is not in that subtree or does not parse.
+pub fn rebase(dn: &str, from: &Dn, to: &Dn) -> Option<String> {
+    let parsed = Dn::parse(dn).ok()?;
+    if parsed == *from {
+        return Some(to.to_string());
+    }
+    if !parsed.is_descendant_of(from) {
+        return None;
+    }
+    let mut moved = to.clone();
+    let below = &parsed.rdns()[..parsed.depth() - from.depth()];
+    for rdn in below.iter().rev() {
+        moved.append_child(rdn.clone());
+    }
+    Some(moved.to_string())
+}
+
+/// The entries to add to copy the branch at `from` to `to`, parents
+/// first. DNs are rewritten, and so are DN values that point into the
+/// branch, such as the members of a copied group. When the top entry is
+/// renamed its naming attribute follows the new RDN. `entries` is the
+/// branch as read, `from` included.
+pub fn plan_subtree_copy(
+    entries: &[LdapEntry],
+    from: &str,
+    to: &str,
+    schema: Option<&SchemaCache>,
+) -> Result<Vec<LdapEntry>, CoreError> {
+    let parse =
+        |d: &str| Dn::parse(d).map_err(|e| CoreError::DnParseError(format!("{}: {}", d, e)));
+    let from_dn = parse(from)?;
+    let to_dn = parse(to)?;
+    if to_dn == from_dn || to_dn.is_descendant_of(&from_dn) {
+        return Err(CoreError::DnParseError(format!(
+            "Cannot copy {} into its own subtree",
+            from
+        )));
+    }
+
+    let mut plan = Vec::with_capacity(entries.len());
+    for entry in entries {
+        let Some(dn) = rebase(&entry.dn, &from_dn, &to_dn) else {
+            continue;
+        };
+        let mut attributes = copyable_attributes(entry, schema);
+        for (attr, values) in attributes.iter_mut() {
+            let syntax = schema.map(|s| s.attribute_syntax(attr));
+            if syntax.is_some_and(|s| s != AttributeSyntax::Dn) {
+                continue;
+            }
+            for value in values.iter_mut() {
+                if let Some(moved) = rebase(value, &from_dn, &to_dn) {
+                    *value = moved;
+                }
+            }
+        }
+        if parse(&entry.dn)? == from_dn {
+            rename_naming_values(&mut attributes, from_dn.rdn(), to_dn.rdn());
+        }
+        plan.push(LdapEntry::new(dn, attributes));
+    }
+    plan.sort_by_cached_key(|e| Dn::parse(&e.dn).map(|d| d.depth()).unwrap_or(0));
+    Ok(plan)
+}
+
+/// Swap the values named by `old` for those named by `new`.
+fn rename_naming_values(
+    attributes: &mut BTreeMap<String, Vec<String>>,
+    old: Option<&Rdn>,
+    new: Option<&Rdn>,
+) {
+    let (Some(old), Some(new)) = (old, new) else {
+        return;
+    };
+    for ava in old.avas().iter().filter(|a| !new.avas().contains(a)) {
+        if let Some(values) = values_ci(attributes, ava.attr()) {
+            values.retain(|v| !v.eq_ignore_ascii_case(ava.value()));
+        }
+    }
+    attributes.retain(|_, values| !values.is_empty());
+    for ava in new.avas() {
+        match values_ci(attributes, ava.attr()) {
+            Some(values) => {
+                if !values.iter().any(|v| v.eq_ignore_ascii_case(ava.value())) {
+                    values.push(ava.value().to_string());
+                }
+            }
+            None => {
+                attributes.insert(ava.attr().to_string(), vec![ava.value().to_string()]);
+            }
+        }
+    }
+}
+
+fn values_ci<'a>(
+    attributes: &'a mut BTreeMap<String, Vec<String>>,
+    attr: &str,
+) -> Option<&'a mut Vec<String>> {
+    attributes
+        .iter_mut()
+        .find(|(name, _)| name.eq_ignore_ascii_case(attr))
+        .map(|(_, values)| values)
+}
+
+/// Read the branch at `base`, every entry with all user attributes.
+pub async fn read_subtree(
+    ops: &mut dyn DirectoryOps,
+    base: &str,
+) -> Result<Vec<LdapEntry>, CoreError> {
+    let mut entries = Vec::new();
+    ops.search_pages(
+        base,
+        Scope::Subtree,
+        "(objectClass=*)",
+        &["*"],
+        &mut |page| entries.extend(page),
+    )
+    .await?;
+    Ok(entries)
+}
+
+/// Outcome of a [`copy_entries`] run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubtreeCopy {
+    /// Entries added, in the order they went.
+    pub added: Vec<String>,
+    /// Entries in the plan.
+    pub total: usize,
+    /// The entry that could not be added and why; nothing after it was
+    /// attempted.
+    pub failure: Option<(String, String)>,
+}
+
+impl SubtreeCopy {
+    pub fn is_complete(&self) -> bool {
+        self.failure.is_none() && self.added.len() == self.total
+    }
+}
+
+/// Add the entries of a [`plan_subtree_copy`] plan in order, stopping at
+/// the first refusal since the entries below it could not go in either.
+/// `progress` gets the number added so far and the total.
+pub async fn copy_entries<F>(
+    ops: &mut dyn DirectoryOps,
+    plan: &[LdapEntry],
+    mut progress: F,
+) -> SubtreeCopy
+where
+    F: FnMut(usize, usize) + Send,
+{
+    let total = plan.len();
+    progress(0, total);
+    let mut added = Vec::with_capacity(total);
+    for entry in plan {
+        if let Err(e) = ops
+            .add_entry(
+                &entry.dn,
+                add_attributes(entry),
+                add_binary_attributes(entry),
+            )
+            .await
+        {
+            return SubtreeCopy {
+                added,
+                total,
+                failure: Some((entry.dn.clone(), e.to_string())),
+            };
+        }
+        added.push(entry.dn.clone());
+        progress(added.len(), total);
+    }
+    SubtreeCopy {
+        added,
+        total,
+        failure: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures::{domain, group, org_unit, person};
+    use crate::testing::{MockDirectory, Operation};
+
+    const BASE: &str = "dc=example,dc=com";
+
+    fn branch() -> Vec<LdapEntry> {
+        let people = format!("ou=people,{}", BASE);
+        let ada = person(&people, "Ada", "Lovelace");
+        let mut team = group(&people, "team", &[ada.dn.as_str()]);
+        team.attributes
+            .insert("entryUUID".to_string(), vec!["1234".to_string()]);
+        let mut ou = org_unit(BASE, "people");
+        ou.attributes.insert(
+            "modifyTimestamp".to_string(),
+            vec!["20240101000000Z".to_string()],
+        );
+        vec![team, ada, ou]
+    }
+
+    #[test]
+    fn test_copyable_attributes_strip_operational() {
+        let mut entry = person(BASE, "Ada", "Lovelace");
+        entry
+            .attributes
+            .insert("EntryUUID".to_string(), vec!["x".to_string()]);
+        entry
+            .attributes
+            .insert("memberOf".to_string(), vec!["cn=g".to_string()]);
+        let attrs = copyable_attributes(&entry, None);
+        assert!(attrs.contains_key("sn"));
+        assert!(!attrs.contains_key("EntryUUID"));
+        assert!(!attrs.contains_key("memberOf"));
+    }
+
+    #[test]
+    fn test_rebase() {
+        let from = Dn::parse("ou=people,dc=example,dc=com").unwrap();
+        let to = Dn::parse("ou=staff,o=copy").unwrap();
+        assert_eq!(
+            rebase("uid=a,ou=people,dc=example,dc=com", &from, &to).as_deref(),
+            Some("uid=a,ou=staff,o=copy")
+        );
+        assert_eq!(
+            rebase("ou=people,dc=example,dc=com", &from, &to).as_deref(),
+            Some("ou=staff,o=copy")
+        );
+        assert_eq!(rebase("uid=a,dc=example,dc=com", &from, &to), None);
+        assert_eq!(rebase("not a dn", &from, &to), None);
+    }
+
+    #[test]
+    fn test_plan_rewrites_dns_and_values() {
+        let from = format!("ou=people,{}", BASE);
+        let plan = plan_subtree_copy(&branch(), &from, "ou=staff,o=copy", None).unwrap();
+        let dns: Vec<&str> = plan.iter().map(|e| e.dn.as_str()).collect();
+        assert_eq!(dns[0], "ou=staff,o=copy");
+        assert!(dns.contains(&"uid=ada.lovelace,ou=staff,o=copy"));
+
+        let top = &plan[0];
+        assert_eq!(top.attributes["ou"], vec!["staff".to_string()]);
+        assert!(!top.attributes.contains_key("modifyTimestamp"));
+
+        let team = plan.iter().find(|e| e.dn.starts_with("cn=team")).unwrap();
+        assert_eq!(
+            team.attributes["member"],
+            vec!["uid=ada.lovelace,ou=staff,o=copy".to_string()]
+        );
+        assert!(!team.attributes.contains_key("entryUUID"));
+    }
+
+    #[test]
+    fn test_plan_refuses_copy_into_itself() {
+        let from = format!("ou=people,{}", BASE);
+        let err = plan_subtree_copy(&branch(), &from, &format!("ou=x,{}", from), None).unwrap_err();
+        assert!(err.to_string().contains("own subtree"), "{}", err);
+        assert!(plan_subtree_copy(&branch(), &from, "not a dn", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_copy_entries_stops_at_first_failure() {
+        let mut dir = MockDirectory::with_entries([domain(BASE)]);
+        let from = format!("ou=people,{}", BASE);
+        let to = format!("ou=staff,{}", BASE);
+        let plan = plan_subtree_copy(&branch(), &from, &to, None).unwrap();
+
+        let mut seen = Vec::new();
+        let result = copy_entries(&mut dir, &plan, |done, total| seen.push((done, total))).await;
+        assert!(result.is_complete());
+        assert_eq!(seen.last(), Some(&(3, 3)));
+        assert!(dir.entry(&format!("uid=ada.lovelace,{}", to)).is_some());
+
+        let mut dir = MockDirectory::with_entries([domain(BASE)]);
+        dir.fail_on(Operation::Add, &to, 50, "insufficient access");
+        let result = copy_entries(&mut dir, &plan, |_, _| {}).await;
+        assert!(result.added.is_empty());
+        assert_eq!(result.failure.unwrap().0, to);
+        assert_eq!(dir.entries().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_read_subtree() {
+        let mut dir = MockDirectory::with_entries(std::iter::once(domain(BASE)).chain(branch()));
+        let entries = read_subtree(&mut dir, &format!("ou=people,{}", BASE))
+            .await
+            .unwrap();
+        assert_eq!(entries.len(), 3);
+    }
+}
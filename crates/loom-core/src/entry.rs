@@ -1,26 +1,107 @@
 use std::collections::BTreeMap;
 
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 
 /// A single LDAP entry with its DN and attributes.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// Values that are not valid UTF-8 (`objectGUID`, `jpegPhoto`, ...) are
+/// kept apart in `binary_attributes`; an attribute is in one map or the
+/// other, never both. As JSON, binary values sit among the text ones as
+/// `{"_base64": "..."}`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "EntryRepr", try_from = "EntryRepr")]
 pub struct LdapEntry {
     pub dn: String,
     pub attributes: BTreeMap<String, Vec<String>>,
+    pub binary_attributes: BTreeMap<String, Vec<Vec<u8>>>,
+}
+
+/// The values of one attribute, borrowed from an entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttrValues<'a> {
+    Text(&'a [String]),
+    Binary(&'a [Vec<u8>]),
+}
+
+impl<'a> AttrValues<'a> {
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Text(values) => values.len(),
+            Self::Binary(values) => values.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The values as text, binary ones base64-encoded.
+    pub fn to_text(&self) -> Vec<String> {
+        match self {
+            Self::Text(values) => values.to_vec(),
+            Self::Binary(values) => values
+                .iter()
+                .map(|v| base64::engine::general_purpose::STANDARD.encode(v))
+                .collect(),
+        }
+    }
 }
 
 impl LdapEntry {
     pub fn new(dn: String, attributes: BTreeMap<String, Vec<String>>) -> Self {
-        Self { dn, attributes }
+        Self {
+            dn,
+            attributes,
+            binary_attributes: BTreeMap::new(),
+        }
+    }
+
+    /// Add binary attributes to an entry.
+    pub fn with_binary_attributes(mut self, binary: BTreeMap<String, Vec<Vec<u8>>>) -> Self {
+        self.binary_attributes = binary;
+        self
     }
 
     pub fn from_search_entry(entry: ldap3::SearchEntry) -> Self {
         Self {
             dn: entry.dn,
             attributes: entry.attrs.into_iter().collect(),
+            binary_attributes: entry.bin_attrs.into_iter().collect(),
         }
     }
 
+    /// Get the values of an attribute of either kind (case-insensitive).
+    pub fn values_ci(&self, attr: &str) -> Option<AttrValues<'_>> {
+        crate::util::find_values_ci(&self.attributes, attr)
+            .map(|v| AttrValues::Text(v))
+            .or_else(|| {
+                crate::util::find_bytes_ci(&self.binary_attributes, attr)
+                    .map(|v| AttrValues::Binary(v))
+            })
+    }
+
+    /// Every attribute of either kind, alphabetical.
+    pub fn all_values(&self) -> Vec<(&String, AttrValues<'_>)> {
+        let mut all: Vec<_> = self
+            .attributes
+            .iter()
+            .map(|(k, v)| (k, AttrValues::Text(v)))
+            .chain(
+                self.binary_attributes
+                    .iter()
+                    .map(|(k, v)| (k, AttrValues::Binary(v))),
+            )
+            .collect();
+        all.sort_by(|a, b| a.0.cmp(b.0));
+        all
+    }
+
+    /// The names of every attribute of either kind.
+    pub fn attribute_names(&self) -> impl Iterator<Item = &String> {
+        self.attributes.keys().chain(self.binary_attributes.keys())
+    }
+
     /// Get the first value of an attribute, if present.
     pub fn first_value(&self, attr: &str) -> Option<&str> {
         self.attributes
@@ -31,7 +112,7 @@ impl LdapEntry {
 
     /// Get the RDN (first component of the DN).
     pub fn rdn(&self) -> &str {
-        self.dn.split(',').next().unwrap_or(&self.dn)
+        crate::dn::rdn(&self.dn)
     }
 
     /// Get all object classes for this entry.
@@ -43,6 +124,79 @@ impl LdapEntry {
     }
 }
 
+/// How an entry reads and writes as JSON.
+#[derive(Serialize, Deserialize)]
+struct EntryRepr {
+    dn: String,
+    attributes: BTreeMap<String, Vec<ReprValue>>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum ReprValue {
+    Text(String),
+    Binary {
+        #[serde(rename = "_base64")]
+        base64: String,
+    },
+}
+
+impl From<LdapEntry> for EntryRepr {
+    fn from(entry: LdapEntry) -> Self {
+        let mut attributes: BTreeMap<String, Vec<ReprValue>> = entry
+            .attributes
+            .into_iter()
+            .map(|(k, v)| (k, v.into_iter().map(ReprValue::Text).collect()))
+            .collect();
+        for (k, values) in entry.binary_attributes {
+            attributes
+                .entry(k)
+                .or_default()
+                .extend(values.iter().map(|v| ReprValue::Binary {
+                    base64: base64::engine::general_purpose::STANDARD.encode(v),
+                }));
+        }
+        Self {
+            dn: entry.dn,
+            attributes,
+        }
+    }
+}
+
+impl TryFrom<EntryRepr> for LdapEntry {
+    type Error = String;
+
+    /// An attribute with any base64 value is binary as a whole.
+    fn try_from(repr: EntryRepr) -> Result<Self, Self::Error> {
+        let mut entry = LdapEntry::new(repr.dn, BTreeMap::new());
+        for (attr, values) in repr.attributes {
+            let binary = values.iter().any(|v| matches!(v, ReprValue::Binary { .. }));
+            if !binary {
+                let text = values
+                    .into_iter()
+                    .filter_map(|v| match v {
+                        ReprValue::Text(s) => Some(s),
+                        ReprValue::Binary { .. } => None,
+                    })
+                    .collect();
+                entry.attributes.insert(attr, text);
+                continue;
+            }
+            let bytes = values
+                .into_iter()
+                .map(|v| match v {
+                    ReprValue::Text(s) => Ok(s.into_bytes()),
+                    ReprValue::Binary { base64 } => base64::engine::general_purpose::STANDARD
+                        .decode(base64)
+                        .map_err(|e| format!("invalid base64 in {}: {}", attr, e)),
+                })
+                .collect::<Result<_, _>>()?;
+            entry.binary_attributes.insert(attr, bytes);
+        }
+        Ok(entry)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -122,4 +276,64 @@ mod tests {
         assert_eq!(deserialized.dn, entry.dn);
         assert_eq!(deserialized.first_value("cn"), Some("Test"));
     }
+
+    fn with_guid() -> LdapEntry {
+        LdapEntry::new(
+            "cn=Test,dc=example".to_string(),
+            BTreeMap::from([("cn".to_string(), vec!["Test".to_string()])]),
+        )
+        .with_binary_attributes(BTreeMap::from([(
+            "objectGUID".to_string(),
+            vec![vec![0xff, 0x00, 0x10]],
+        )]))
+    }
+
+    #[test]
+    fn test_binary_values_serialize_as_base64() {
+        let entry = with_guid();
+        let json: serde_json::Value = serde_json::to_value(&entry).unwrap();
+        assert_eq!(json["attributes"]["cn"], serde_json::json!(["Test"]));
+        assert_eq!(
+            json["attributes"]["objectGUID"],
+            serde_json::json!([{ "_base64": "/wAQ" }])
+        );
+
+        let deserialized: LdapEntry = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, entry);
+
+        let bad = serde_json::json!({
+            "dn": "cn=Test",
+            "attributes": { "objectGUID": [{ "_base64": "not base64!" }] }
+        });
+        assert!(serde_json::from_value::<LdapEntry>(bad).is_err());
+    }
+
+    #[test]
+    fn test_values_of_either_kind() {
+        let entry = with_guid();
+        assert_eq!(
+            entry.values_ci("OBJECTGUID"),
+            Some(AttrValues::Binary(&[vec![0xff, 0x00, 0x10]]))
+        );
+        assert_eq!(
+            entry.values_ci("objectguid").unwrap().to_text(),
+            vec!["/wAQ"]
+        );
+        let names: Vec<&str> = entry
+            .all_values()
+            .into_iter()
+            .map(|(name, _)| name.as_str())
+            .collect();
+        assert_eq!(names, vec!["cn", "objectGUID"]);
+
+        assert_eq!(
+            crate::util::get_first_bytes(&entry.binary_attributes, "objectguid"),
+            Some(vec![0xff, 0x00, 0x10])
+        );
+        assert_eq!(
+            crate::util::get_first_lossy(&entry, "objectGUID"),
+            Some("\u{fffd}\0\u{10}".to_string())
+        );
+        assert_eq!(crate::util::get_values_lossy(&entry, "CN"), vec!["Test"]);
+    }
 }
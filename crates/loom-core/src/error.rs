@@ -7,9 +7,15 @@ pub enum CoreError {
     #[error("connection failed: {0}")]
     ConnectionFailed(String),
 
-    #[error("certificate not trusted for {}", .0.host)]
+    #[error("could not resolve {host}: {reason}")]
+    ResolveFailed { host: String, reason: String },
+
+    #[error("certificate {} for {} rejected: {}", .0.subject, .0.host, .0.problem)]
     CertificateNotTrusted(Box<CertificateInfo>),
 
+    #[error("TLS setup failed: {0}")]
+    TlsConfig(String),
+
     #[error("bind failed: {0}")]
     BindFailed(String),
 
@@ -25,6 +31,12 @@ pub enum CoreError {
     #[error("delete failed: {0}")]
     DeleteFailed(String),
 
+    #[error("password change failed: {0}")]
+    PasswordFailed(String),
+
+    #[error("transaction failed: {0}")]
+    TransactionFailed(String),
+
     #[error("schema error: {0}")]
     SchemaError(String),
 
@@ -43,9 +55,18 @@ pub enum CoreError {
     #[error("vault error: {0}")]
     VaultError(String),
 
+    #[error("snapshot error: {0}")]
+    SnapshotError(String),
+
     #[error("timeout")]
     Timeout,
 
+    #[error("connection lost, reconnecting")]
+    ConnectionLost,
+
+    #[error("cancelled")]
+    Cancelled,
+
     #[error("ldap error: {0}")]
     Ldap(#[from] ldap3::LdapError),
 }
@@ -0,0 +1,588 @@
+//! Human-readable explanations for LDAP result codes and Active Directory
+//! diagnostic messages.
+//!
+//! The tables below are plain data: add a row to `RESULT_CODES`,
+//! `AD_DATA_CODES` or `AD_ERROR_CODES` to extend the explanations.
+
+/// (result code, name, summary, remedies)
+type ResultCodeRow = (u32, &'static str, &'static str, &'static [&'static str]);
+
+/// (code, summary, remedies)
+type CodeRow = (&'static str, &'static str, &'static [&'static str]);
+
+const RESULT_CODES: &[ResultCodeRow] = &[
+    (
+        1,
+        "operationsError",
+        "The server reported an internal sequencing error.",
+        &[
+            "Retry the operation.",
+            "On Active Directory this often means the bind is required before the operation.",
+        ],
+    ),
+    (
+        2,
+        "protocolError",
+        "The server could not understand the request.",
+        &["Check the request is supported by this server (LDAPv3)."],
+    ),
+    (
+        3,
+        "timeLimitExceeded",
+        "The server's time limit was reached before the operation finished.",
+        &[
+            "Narrow the search base or filter.",
+            "Use an indexed attribute in the filter.",
+        ],
+    ),
+    (
+        4,
+        "sizeLimitExceeded",
+        "More entries matched than the server is willing to return.",
+        &[
+            "Narrow the search base or filter.",
+            "Lower the page size or ask the administrator to raise the size limit.",
+        ],
+    ),
+    (
+        7,
+        "authMethodNotSupported",
+        "The authentication method is not supported by the server.",
+        &["Use a simple bind or a SASL mechanism the server advertises."],
+    ),
+    (
+        8,
+        "strongerAuthRequired",
+        "The server requires a stronger (usually encrypted) authentication.",
+        &[
+            "Switch the profile to LDAPS or StartTLS.",
+            "Active Directory may require LDAP signing; use TLS.",
+        ],
+    ),
+    (
+        10,
+        "referral",
+        "The entry lives on another server; a referral was returned.",
+        &[
+            "Connect to the server holding the naming context.",
+            "Use the Global Catalog (port 3268/3269) for forest-wide searches.",
+        ],
+    ),
+    (
+        11,
+        "adminLimitExceeded",
+        "An administrative limit on the server was exceeded.",
+        &["Narrow the search or use paging."],
+    ),
+    (
+        12,
+        "unavailableCriticalExtension",
+        "A control marked critical is not supported by the server.",
+        &["Disable options that send controls (e.g. relax rules) for this server."],
+    ),
+    (
+        13,
+        "confidentialityRequired",
+        "The operation requires an encrypted connection.",
+        &["Switch the profile to LDAPS or StartTLS."],
+    ),
+    (
+        16,
+        "noSuchAttribute",
+        "The attribute or value to modify does not exist on the entry.",
+        &["Refresh the entry; the value may already have been removed."],
+    ),
+    (
+        17,
+        "undefinedAttributeType",
+        "The attribute type is not defined in the schema.",
+        &["Check the attribute name spelling in the schema viewer."],
+    ),
+    (
+        18,
+        "inappropriateMatching",
+        "The filter uses a matching rule the attribute does not support.",
+        &["Use a different comparison operator for this attribute."],
+    ),
+    (
+        19,
+        "constraintViolation",
+        "A value violates a schema or policy constraint.",
+        &["Check value length, uniqueness and password policy requirements."],
+    ),
+    (
+        20,
+        "attributeOrValueExists",
+        "The value already exists on the entry.",
+        &["Refresh the entry; the value may already be present."],
+    ),
+    (
+        21,
+        "invalidAttributeSyntax",
+        "A value does not match the attribute's syntax.",
+        &["Check the value format (e.g. DN, integer, GeneralizedTime)."],
+    ),
+    (
+        32,
+        "noSuchObject",
+        "The target entry or one of its parents does not exist.",
+        &[
+            "Check the DN; the matched DN shows how far the server got.",
+            "Refresh the tree; the entry may have been moved or deleted.",
+        ],
+    ),
+    (
+        33,
+        "aliasProblem",
+        "An alias points to an entry that does not exist.",
+        &[],
+    ),
+    (
+        34,
+        "invalidDNSyntax",
+        "The DN is not syntactically valid.",
+        &["Check for unescaped commas, plus signs or quotes in RDN values."],
+    ),
+    (
+        48,
+        "inappropriateAuthentication",
+        "Anonymous or unauthenticated binds are not allowed here.",
+        &["Configure a bind DN and password for the profile."],
+    ),
+    (
+        49,
+        "invalidCredentials",
+        "The bind DN or password is wrong, or the account cannot log in.",
+        &[
+            "Check the bind DN and password.",
+            "On Active Directory, the data code identifies the exact reason.",
+        ],
+    ),
+    (
+        50,
+        "insufficientAccessRights",
+        "The bound identity is not allowed to perform this operation.",
+        &["Bind as an account with the required rights, or ask for an ACL change."],
+    ),
+    (
+        51,
+        "busy",
+        "The server is too busy to handle the request.",
+        &["Retry later."],
+    ),
+    (
+        52,
+        "unavailable",
+        "The server is shutting down or unavailable.",
+        &["Retry later or connect to another replica."],
+    ),
+    (
+        53,
+        "unwillingToPerform",
+        "The server refuses to perform the operation.",
+        &[
+            "Password changes on Active Directory require an encrypted connection.",
+            "The operation may be blocked by server policy or a system-only attribute.",
+        ],
+    ),
+    (
+        54,
+        "loopDetect",
+        "The server detected an alias or referral loop.",
+        &[],
+    ),
+    (
+        64,
+        "namingViolation",
+        "The entry's DN breaks the naming rules of the directory.",
+        &["Check the RDN attribute and that the parent allows this child class."],
+    ),
+    (
+        65,
+        "objectClassViolation",
+        "The entry does not satisfy its object classes.",
+        &["Add required (MUST) attributes or remove attributes not allowed by the classes."],
+    ),
+    (
+        66,
+        "notAllowedOnNonLeaf",
+        "The operation is not allowed on an entry with children.",
+        &["Delete or move the children first."],
+    ),
+    (
+        67,
+        "notAllowedOnRDN",
+        "The change would remove the entry's naming attribute.",
+        &["Rename the entry instead of modifying its RDN value."],
+    ),
+    (
+        68,
+        "entryAlreadyExists",
+        "An entry with this DN already exists.",
+        &["Choose a different RDN value."],
+    ),
+    (
+        69,
+        "objectClassModsProhibited",
+        "Object class changes are not allowed on this entry.",
+        &[],
+    ),
+    (
+        71,
+        "affectsMultipleDSAs",
+        "The operation would span multiple servers.",
+        &[],
+    ),
+    (
+        80,
+        "other",
+        "The server reported an unspecified error.",
+        &["Read the diagnostic message for details."],
+    ),
+];
+
+/// Active Directory bind failure sub-codes (the `data XXX` part of rc=49).
+const AD_DATA_CODES: &[CodeRow] = &[
+    (
+        "525",
+        "User not found.",
+        &["Check the bind DN or userPrincipalName."],
+    ),
+    (
+        "52e",
+        "Invalid credentials: the password is wrong.",
+        &["Re-enter the password."],
+    ),
+    (
+        "530",
+        "Not permitted to log on at this time.",
+        &["Check the account's logon hours."],
+    ),
+    (
+        "531",
+        "Not permitted to log on from this workstation.",
+        &["Check the account's userWorkstations."],
+    ),
+    (
+        "532",
+        "The password has expired.",
+        &["Change the password, then bind again."],
+    ),
+    (
+        "533",
+        "The account is disabled.",
+        &["Ask an administrator to enable the account."],
+    ),
+    (
+        "568",
+        "The account has too many security identifiers (token too large).",
+        &[],
+    ),
+    (
+        "701",
+        "The account has expired.",
+        &["Ask an administrator to extend accountExpires."],
+    ),
+    (
+        "773",
+        "The user must reset the password before logging on.",
+        &["Reset the password, then bind again."],
+    ),
+    (
+        "775",
+        "The account is locked out.",
+        &["Wait for the lockout to expire or ask an administrator to unlock it."],
+    ),
+];
+
+/// Active Directory Win32 error codes (the 8-digit hex prefix of the diagnostic).
+const AD_ERROR_CODES: &[CodeRow] = &[
+    ("0000001F", "A device attached to the system is not functioning (often a password change over an unencrypted connection).", &[
+        "Use LDAPS or StartTLS for password changes.",
+    ]),
+    ("00000057", "A parameter is incorrect.", &["Check the attribute values being written."]),
+    ("0000052D", "The password does not meet the password policy.", &[
+        "Use a longer or more complex password, or one not in the password history.",
+    ]),
+    ("0000202B", "A referral was returned.", &["Connect to the server holding the naming context."]),
+    ("00002071", "The object already exists.", &["Choose a different RDN value."]),
+    ("0000208D", "The directory object was not found.", &["Check the DN and refresh the tree."]),
+    ("00002098", "Insufficient access rights to perform the operation.", &[
+        "Bind as an account with the required rights.",
+    ]),
+];
+
+/// The parts of a failed LDAP operation, extracted from an error message.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ErrorDetails {
+    pub code: Option<u32>,
+    pub matched_dn: Option<String>,
+    pub diagnostic: String,
+}
+
+/// A curated explanation of an LDAP error.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Explanation {
+    pub code: u32,
+    pub name: &'static str,
+    pub summary: String,
+    pub remedies: Vec<String>,
+    /// Active Directory `data` sub-code, e.g. `52e`.
+    pub ad_data_code: Option<String>,
+    /// Active Directory Win32 error code, e.g. `00002077`.
+    pub ad_error_code: Option<String>,
+    /// Active Directory internal source location, e.g. `DSID-03190E49`.
+    pub dsid: Option<String>,
+}
+
+/// Name of an LDAP result code, or "unknown".
+pub fn result_code_name(code: u32) -> &'static str {
+    RESULT_CODES
+        .iter()
+        .find(|row| row.0 == code)
+        .map(|row| row.1)
+        .unwrap_or("unknown")
+}
+
+/// Explain a result code, refined by the server's diagnostic message.
+pub fn explain(code: u32, diagnostic: &str) -> Explanation {
+    let row = RESULT_CODES.iter().find(|row| row.0 == code);
+    let mut summary = row
+        .map(|row| row.2.to_string())
+        .unwrap_or_else(|| format!("Unrecognised result code {}.", code));
+    let mut remedies: Vec<String> = row
+        .map(|row| row.3.iter().map(|s| s.to_string()).collect())
+        .unwrap_or_default();
+
+    let ad_data_code = extract_ad_data_code(diagnostic);
+    let ad_error_code = extract_ad_error_code(diagnostic);
+    let dsid = extract_dsid(diagnostic);
+
+    // The most specific explanation wins: bind data code, then Win32 code.
+    let specific = ad_data_code
+        .as_deref()
+        .and_then(|c| lookup(AD_DATA_CODES, c))
+        .or_else(|| {
+            ad_error_code
+                .as_deref()
+                .and_then(|c| lookup(AD_ERROR_CODES, c))
+        });
+    if let Some((detail, extra)) = specific {
+        summary = format!("{} {}", summary, detail);
+        let mut merged: Vec<String> = extra.iter().map(|s| s.to_string()).collect();
+        merged.extend(remedies);
+        remedies = merged;
+    }
+
+    Explanation {
+        code,
+        name: result_code_name(code),
+        summary,
+        remedies,
+        ad_data_code,
+        ad_error_code,
+        dsid,
+    }
+}
+
+fn lookup(
+    table: &'static [CodeRow],
+    code: &str,
+) -> Option<(&'static str, &'static [&'static str])> {
+    table
+        .iter()
+        .find(|row| row.0.eq_ignore_ascii_case(code))
+        .map(|row| (row.1, row.2))
+}
+
+/// Extract the AD bind sub-code from a diagnostic like
+/// `80090308: LdapErr: DSID-0C09044E, comment: AcceptSecurityContext error, data 52e, v4563`.
+pub fn extract_ad_data_code(diagnostic: &str) -> Option<String> {
+    let lower = diagnostic.to_lowercase();
+    let idx = lower.find("data ")?;
+    let code: String = lower[idx + 5..]
+        .chars()
+        .take_while(|c| c.is_ascii_hexdigit())
+        .collect();
+    if code.is_empty() {
+        None
+    } else {
+        Some(code)
+    }
+}
+
+/// Extract the leading 8-digit hex Win32 error code, e.g. `00002077` from
+/// `00002077: SvcErr: DSID-03190E49, problem 5003 (WILL_NOT_PERFORM)`.
+pub fn extract_ad_error_code(diagnostic: &str) -> Option<String> {
+    let trimmed = diagnostic.trim_start();
+    let code: String = trimmed.chars().take_while(|c| *c != ':').collect();
+    if code.len() == 8 && code.chars().all(|c| c.is_ascii_hexdigit()) {
+        Some(code.to_uppercase())
+    } else {
+        None
+    }
+}
+
+/// Extract the AD `DSID-XXXXXXXX` source location.
+pub fn extract_dsid(diagnostic: &str) -> Option<String> {
+    let idx = diagnostic.find("DSID-")?;
+    let id: String = diagnostic[idx + 5..]
+        .chars()
+        .take_while(|c| c.is_ascii_hexdigit())
+        .collect();
+    if id.is_empty() {
+        None
+    } else {
+        Some(format!("DSID-{}", id))
+    }
+}
+
+/// Pull the result code, matched DN and diagnostic out of an error message.
+///
+/// Understands ldap3's `rc=53 (unwillingToPerform), dn: "...", text: "..."`
+/// as well as the `rc=49: text` / `rc=49, text` forms used in loom-core.
+pub fn parse_error_message(message: &str) -> ErrorDetails {
+    let Some(idx) = message.find("rc=") else {
+        return ErrorDetails {
+            code: None,
+            matched_dn: None,
+            diagnostic: message.trim().to_string(),
+        };
+    };
+    let rest = &message[idx + 3..];
+    let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+    let code = digits.parse::<u32>().ok();
+    let rest = &rest[digits.len()..];
+
+    let matched_dn = quoted_field(rest, "dn: \"").filter(|s| !s.is_empty());
+    let diagnostic = match quoted_field(rest, "text: \"") {
+        Some(text) => text,
+        None => rest
+            .trim_start_matches(|c: char| c == ':' || c == ',' || c.is_whitespace())
+            .trim()
+            .to_string(),
+    };
+
+    ErrorDetails {
+        code,
+        matched_dn,
+        diagnostic,
+    }
+}
+
+fn quoted_field(s: &str, prefix: &str) -> Option<String> {
+    let start = s.find(prefix)? + prefix.len();
+    let end = s[start..].find('"')?;
+    Some(s[start..start + end].to_string())
+}
+
+/// Render a full, copyable report of an error and its explanation.
+pub fn format_report(message: &str) -> String {
+    let details = parse_error_message(message);
+    let mut out = format!("Error: {}\n", message.trim());
+    if let Some(code) = details.code {
+        out.push_str(&format!(
+            "Result code: {} ({})\n",
+            code,
+            result_code_name(code)
+        ));
+    }
+    if let Some(ref dn) = details.matched_dn {
+        out.push_str(&format!("Matched DN: {}\n", dn));
+    }
+    if !details.diagnostic.is_empty() {
+        out.push_str(&format!("Diagnostic: {}\n", details.diagnostic));
+    }
+    if let Some(code) = details.code {
+        let explanation = explain(code, &details.diagnostic);
+        out.push_str(&format!("Explanation: {}\n", explanation.summary));
+        for remedy in &explanation.remedies {
+            out.push_str(&format!("  - {}\n", remedy));
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_ad_data_code() {
+        let diag = "80090308: LdapErr: DSID-0C09044E, comment: AcceptSecurityContext error, data 52e, v4563";
+        assert_eq!(extract_ad_data_code(diag).as_deref(), Some("52e"));
+        assert_eq!(
+            extract_ad_data_code("... data 775, v2580").as_deref(),
+            Some("775")
+        );
+        assert_eq!(extract_ad_data_code("no code here"), None);
+    }
+
+    #[test]
+    fn test_extract_ad_error_code_and_dsid() {
+        let diag = "00002077: SvcErr: DSID-03190E49, problem 5003 (WILL_NOT_PERFORM), data 0";
+        assert_eq!(extract_ad_error_code(diag).as_deref(), Some("00002077"));
+        assert_eq!(extract_dsid(diag).as_deref(), Some("DSID-03190E49"));
+        assert_eq!(extract_ad_error_code("Invalid credentials"), None);
+        assert_eq!(extract_dsid("Invalid credentials"), None);
+    }
+
+    #[test]
+    fn test_explain_bind_data_codes_differ() {
+        let base = "80090308: LdapErr: DSID-0C09044E, comment: AcceptSecurityContext error";
+        let wrong_pw = explain(49, &format!("{}, data 52e, v4563", base));
+        let locked = explain(49, &format!("{}, data 775, v4563", base));
+        let expired = explain(49, &format!("{}, data 532, v4563", base));
+        assert_eq!(wrong_pw.name, "invalidCredentials");
+        assert!(wrong_pw.summary.contains("password is wrong"));
+        assert!(locked.summary.contains("locked out"));
+        assert!(expired.summary.contains("expired"));
+        assert_eq!(locked.ad_data_code.as_deref(), Some("775"));
+        assert_eq!(locked.dsid.as_deref(), Some("DSID-0C09044E"));
+    }
+
+    #[test]
+    fn test_explain_ad_error_code() {
+        let e = explain(
+            19,
+            "0000052D: Constraint violation - check_password_restrictions",
+        );
+        assert_eq!(e.ad_error_code.as_deref(), Some("0000052D"));
+        assert!(e.summary.contains("password policy"));
+    }
+
+    #[test]
+    fn test_explain_unknown_code() {
+        let e = explain(4242, "");
+        assert_eq!(e.name, "unknown");
+        assert!(e.remedies.is_empty());
+    }
+
+    #[test]
+    fn test_parse_ldap3_message() {
+        let msg = "search failed: LDAP operation result: rc=32 (noSuchObject), dn: \"dc=example,dc=com\", text: \"0000208D: NameErr: DSID-03100241\"";
+        let d = parse_error_message(msg);
+        assert_eq!(d.code, Some(32));
+        assert_eq!(d.matched_dn.as_deref(), Some("dc=example,dc=com"));
+        assert_eq!(d.diagnostic, "0000208D: NameErr: DSID-03100241");
+    }
+
+    #[test]
+    fn test_parse_loom_message() {
+        let d = parse_error_message("modify failed: Modify cn=x failed rc=53: will not perform");
+        assert_eq!(d.code, Some(53));
+        assert_eq!(d.matched_dn, None);
+        assert_eq!(d.diagnostic, "will not perform");
+
+        let d = parse_error_message("connection refused");
+        assert_eq!(d.code, None);
+        assert_eq!(d.diagnostic, "connection refused");
+    }
+
+    #[test]
+    fn test_format_report() {
+        let report = format_report("bind failed: LDAP bind returned rc=49: data 775");
+        assert!(report.contains("Result code: 49 (invalidCredentials)"));
+        assert!(report.contains("locked out"));
+    }
+}
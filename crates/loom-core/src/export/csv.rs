@@ -1,108 +1,335 @@
-use std::collections::BTreeSet;
+use std::borrow::Borrow;
 use std::path::Path;
 
 use crate::entry::LdapEntry;
 use crate::error::CoreError;
 
-use super::requested_attrs;
+use super::AttrSelection;
+
+/// How rows are laid out in CSV and Excel exports.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Explode {
+    /// One row per entry, multi-valued attributes joined in one cell.
+    #[default]
+    None,
+    /// One row per value of the named attribute, with the other columns
+    /// repeated; a single row with that column empty when it has none.
+    Attribute(String),
+    /// One row per value, as `dn, attribute, value`.
+    Long,
+}
+
+/// Options for the tabular exports, CSV and Excel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CsvOptions {
+    pub explode: Explode,
+    /// Joins the values of a multi-valued attribute in one cell. A value
+    /// containing it is quoted, with `""` for a quote inside.
+    pub multi_value_separator: String,
+    /// Quote every field, not only those that need it. CSV only.
+    pub quote_all: bool,
+    /// Field delimiter. CSV only.
+    pub delimiter: u8,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            explode: Explode::None,
+            multi_value_separator: "; ".to_string(),
+            quote_all: false,
+            delimiter: b',',
+        }
+    }
+}
+
+/// Join the values of one attribute into a cell, quoting those that
+/// contain `separator` or start with a quote so they split back apart.
+pub fn join_values(values: &[String], separator: &str) -> String {
+    let quoted = |value: &String| {
+        if (!separator.is_empty() && value.contains(separator)) || value.starts_with('"') {
+            format!("\"{}\"", value.replace('"', "\"\""))
+        } else {
+            value.clone()
+        }
+    };
+    values
+        .iter()
+        .map(quoted)
+        .collect::<Vec<_>>()
+        .join(separator)
+}
 
 /// Export entries to CSV format.
 ///
 /// Columns: dn, then all unique attribute names sorted alphabetically.
-/// Multi-valued attributes are joined with "; ".
-pub fn export(
-    entries: &[LdapEntry],
+/// Multi-valued attributes are joined with the separator in `options`,
+/// "; " by default, unless `options` explode them into rows.
+pub fn export<I>(
+    entries: I,
     path: &Path,
     attributes: &[String],
-) -> Result<usize, CoreError> {
+    options: &CsvOptions,
+) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+{
     let file = std::fs::File::create(path)
         .map_err(|e| CoreError::ExportError(format!("Failed to create file: {}", e)))?;
     let writer = std::io::BufWriter::new(file);
 
-    write_csv(writer, entries, attributes)
+    write_to(entries, writer, attributes, options)
 }
 
-/// Write entries in CSV format to any writer.
-pub fn write_csv<W: std::io::Write>(
+/// Write entries in CSV format to any writer, laid out per `options`.
+pub fn write_to<I, W>(
+    entries: I,
     writer: W,
-    entries: &[LdapEntry],
     attributes: &[String],
-) -> Result<usize, CoreError> {
-    if entries.is_empty() {
-        return Ok(0);
+    options: &CsvOptions,
+) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+    W: std::io::Write,
+{
+    let mut csv_writer = csv_writer(writer, options);
+    let count = write_rows(entries, attributes, options, |record| {
+        write_record(&mut csv_writer, record)
+    })?;
+
+    csv_writer
+        .flush()
+        .map_err(|e| CoreError::ExportError(format!("CSV flush failed: {}", e)))?;
+
+    Ok(count)
+}
+
+/// Write a header and rows of text as they are, such as a table the way
+/// a view shows it, returning how many rows there were.
+pub fn write_table<W>(
+    header: &[String],
+    rows: &[Vec<String>],
+    writer: W,
+    options: &CsvOptions,
+) -> Result<usize, CoreError>
+where
+    W: std::io::Write,
+{
+    let mut csv_writer = csv_writer(writer, options);
+    write_record(&mut csv_writer, header)?;
+    for row in rows {
+        write_record(&mut csv_writer, row)?;
     }
+    csv_writer
+        .flush()
+        .map_err(|e| CoreError::ExportError(format!("CSV flush failed: {}", e)))?;
+    Ok(rows.len())
+}
 
-    let attr_names: Vec<String> = if let Some(attrs) = requested_attrs(attributes) {
-        attrs.to_vec()
+fn csv_writer<W: std::io::Write>(writer: W, options: &CsvOptions) -> csv::Writer<W> {
+    let quote_style = if options.quote_all {
+        csv::QuoteStyle::Always
     } else {
-        let mut all_attrs: BTreeSet<String> = BTreeSet::new();
-        for entry in entries {
-            for key in entry.attributes.keys() {
-                all_attrs.insert(key.clone());
-            }
-        }
-        all_attrs.into_iter().collect()
+        csv::QuoteStyle::Necessary
     };
+    csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .quote_style(quote_style)
+        .from_writer(writer)
+}
 
-    let mut csv_writer = csv::Writer::from_writer(writer);
+fn write_record<W: std::io::Write>(
+    writer: &mut csv::Writer<W>,
+    record: &[String],
+) -> Result<(), CoreError> {
+    writer
+        .write_record(record)
+        .map_err(|e| CoreError::ExportError(format!("CSV write failed: {}", e)))
+}
 
-    // Header: dn + attribute names
-    let mut header = vec!["dn".to_string()];
-    header.extend(attr_names.iter().cloned());
-    csv_writer
-        .write_record(&header)
-        .map_err(|e| CoreError::ExportError(format!("CSV write failed: {}", e)))?;
+/// Lay entries out as a header and rows, shared by the CSV and Excel
+/// exports.
+pub fn table(
+    entries: &[LdapEntry],
+    attributes: &[String],
+    options: &CsvOptions,
+) -> (Vec<String>, Vec<Vec<String>>) {
+    let selection = AttrSelection::parse(attributes);
+    let layout = Layout::new(selection.columns(entries), &selection, options);
+    let rows = entries
+        .iter()
+        .flat_map(|entry| layout.rows(entry))
+        .collect();
+    (layout.header(), rows)
+}
 
-    // Data rows
-    for entry in entries {
-        let mut record = vec![entry.dn.clone()];
-        for attr in &attr_names {
-            let value = entry
-                .attributes
-                .get(attr)
-                .map(|vals| vals.join("; "))
-                .unwrap_or_default();
-            record.push(value);
+/// Lay entries out one at a time, handing the header and then each row
+/// to `sink`, and return how many entries there were. Nothing is handed
+/// over when there are none.
+///
+/// Rows go out as entries arrive, except when the columns depend on the
+/// entries (`["*"]`, `["+"]`) outside the long layout: they are then
+/// every attribute of every entry, so the entries are gathered first.
+pub(crate) fn write_rows<I, F>(
+    entries: I,
+    attributes: &[String],
+    options: &CsvOptions,
+    sink: F,
+) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+    F: FnMut(&[String]) -> Result<(), CoreError>,
+{
+    let selection = AttrSelection::parse(attributes);
+    match selection.fixed() {
+        Some(names) => Layout::new(names.to_vec(), &selection, options).write(entries, sink),
+        None if options.explode == Explode::Long => {
+            Layout::new(Vec::new(), &selection, options).write(entries, sink)
+        }
+        None => {
+            let entries: Vec<I::Item> = entries.into_iter().collect();
+            Layout::new(selection.columns(&entries), &selection, options).write(entries, sink)
         }
-        csv_writer
-            .write_record(&record)
-            .map_err(|e| CoreError::ExportError(format!("CSV write failed: {}", e)))?;
     }
+}
 
-    csv_writer
-        .flush()
-        .map_err(|e| CoreError::ExportError(format!("CSV flush failed: {}", e)))?;
+/// The columns of a tabular export, fixed before the first row.
+struct Layout<'a> {
+    /// Attribute columns after the DN.
+    attr_names: Vec<String>,
+    /// Picks each entry's attributes in the long form, which has no
+    /// attribute columns.
+    selection: &'a AttrSelection,
+    explode: &'a Explode,
+    separator: &'a str,
+    /// Index in `attr_names` of the exploded attribute.
+    exploded: usize,
+}
+
+impl<'a> Layout<'a> {
+    fn new(
+        mut attr_names: Vec<String>,
+        selection: &'a AttrSelection,
+        options: &'a CsvOptions,
+    ) -> Self {
+        let mut exploded = 0;
+        if let Explode::Attribute(name) = &options.explode {
+            // The exploded attribute is a column even when not asked for
+            match attr_names.iter().position(|a| a.eq_ignore_ascii_case(name)) {
+                Some(i) => exploded = i,
+                None => attr_names.insert(0, name.clone()),
+            }
+        }
+        Self {
+            attr_names,
+            selection,
+            explode: &options.explode,
+            separator: &options.multi_value_separator,
+            exploded,
+        }
+    }
+
+    fn header(&self) -> Vec<String> {
+        if *self.explode == Explode::Long {
+            return ["dn", "attribute", "value"].map(String::from).to_vec();
+        }
+        let mut header = vec!["dn".to_string()];
+        header.extend(self.attr_names.iter().cloned());
+        header
+    }
+
+    /// The rows for one entry.
+    fn rows(&self, entry: &LdapEntry) -> Vec<Vec<String>> {
+        let joined = |attr: &str| {
+            entry
+                .values_ci(attr)
+                .map(|vals| join_values(&vals.to_text(), self.separator))
+                .unwrap_or_default()
+        };
+        let mut record = vec![entry.dn.clone()];
+
+        match self.explode {
+            Explode::None => {
+                record.extend(self.attr_names.iter().map(|attr| joined(attr)));
+                vec![record]
+            }
+            Explode::Attribute(name) => {
+                record.extend(self.attr_names.iter().map(|attr| joined(attr)));
+                let values = entry
+                    .values_ci(name)
+                    .map(|v| v.to_text())
+                    .unwrap_or_default();
+                if values.is_empty() {
+                    return vec![record];
+                }
+                values
+                    .iter()
+                    .map(|value| {
+                        let mut row = record.clone();
+                        row[self.exploded + 1] = value.clone();
+                        row
+                    })
+                    .collect()
+            }
+            Explode::Long => self
+                .selection
+                .pick(entry)
+                .into_iter()
+                .flat_map(|(attr, values)| {
+                    values
+                        .to_text()
+                        .into_iter()
+                        .map(move |value| vec![entry.dn.clone(), attr.clone(), value])
+                })
+                .collect(),
+        }
+    }
 
-    Ok(entries.len())
+    /// Hand the header and rows for `entries` to `sink`.
+    fn write<I, F>(&self, entries: I, mut sink: F) -> Result<usize, CoreError>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<LdapEntry>,
+        F: FnMut(&[String]) -> Result<(), CoreError>,
+    {
+        let mut count = 0;
+        for entry in entries {
+            if count == 0 {
+                sink(&self.header())?;
+            }
+            for row in self.rows(entry.borrow()) {
+                sink(&row)?;
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::BTreeMap;
+    use crate::testing::fixtures::EntryBuilder;
 
     #[test]
     fn test_export_csv() {
         let entries = vec![
-            LdapEntry::new(
-                "cn=Alice,dc=example,dc=com".to_string(),
-                BTreeMap::from([
-                    ("cn".to_string(), vec!["Alice".to_string()]),
-                    ("mail".to_string(), vec!["alice@example.com".to_string()]),
-                ]),
-            ),
-            LdapEntry::new(
-                "cn=Bob,dc=example,dc=com".to_string(),
-                BTreeMap::from([
-                    ("cn".to_string(), vec!["Bob".to_string()]),
-                    ("sn".to_string(), vec!["Jones".to_string()]),
-                ]),
-            ),
+            EntryBuilder::new("cn=Alice,dc=example,dc=com")
+                .attr("cn", "Alice")
+                .attr("mail", "alice@example.com")
+                .build(),
+            EntryBuilder::new("cn=Bob,dc=example,dc=com")
+                .attr("cn", "Bob")
+                .attr("sn", "Jones")
+                .build(),
         ];
 
         let star = vec!["*".to_string()];
         let mut buf = Vec::new();
-        let count = write_csv(&mut buf, &entries, &star).unwrap();
+        let count = write_to(&entries, &mut buf, &star, &CsvOptions::default()).unwrap();
         assert_eq!(count, 2);
 
         let output = String::from_utf8(buf).unwrap();
@@ -113,46 +340,54 @@ mod tests {
 
     #[test]
     fn test_multi_valued() {
-        let entries = vec![LdapEntry::new(
-            "cn=Test,dc=example,dc=com".to_string(),
-            BTreeMap::from([(
-                "objectClass".to_string(),
-                vec!["top".to_string(), "person".to_string()],
-            )]),
-        )];
+        let entries = vec![EntryBuilder::new("cn=Test,dc=example,dc=com")
+            .object_classes(&["top", "person"])
+            .build()];
 
         let star = vec!["*".to_string()];
         let mut buf = Vec::new();
-        write_csv(&mut buf, &entries, &star).unwrap();
+        write_to(&entries, &mut buf, &star, &CsvOptions::default()).unwrap();
         let output = String::from_utf8(buf).unwrap();
         assert!(output.contains("top; person"));
     }
 
+    #[test]
+    fn test_binary_values_as_base64() {
+        let entries = vec![EntryBuilder::new("cn=Test,dc=example,dc=com")
+            .attr("cn", "Test")
+            .binary("objectGUID", &[0xff, 0x00, 0x10])
+            .binary("objectGUID", &[0x01])
+            .build()];
+
+        let star = vec!["*".to_string()];
+        let mut buf = Vec::new();
+        write_to(&entries, &mut buf, &star, &CsvOptions::default()).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(
+            output,
+            "dn,cn,objectGUID\n\"cn=Test,dc=example,dc=com\",Test,/wAQ; AQ==\n"
+        );
+    }
+
     #[test]
     fn test_filtered_ordered_attributes() {
         let entries = vec![
-            LdapEntry::new(
-                "cn=Alice,dc=example,dc=com".to_string(),
-                BTreeMap::from([
-                    ("cn".to_string(), vec!["Alice".to_string()]),
-                    ("mail".to_string(), vec!["alice@example.com".to_string()]),
-                    ("sn".to_string(), vec!["Smith".to_string()]),
-                    ("title".to_string(), vec!["Engineer".to_string()]),
-                ]),
-            ),
-            LdapEntry::new(
-                "cn=Bob,dc=example,dc=com".to_string(),
-                BTreeMap::from([
-                    ("cn".to_string(), vec!["Bob".to_string()]),
-                    ("sn".to_string(), vec!["Jones".to_string()]),
-                ]),
-            ),
+            EntryBuilder::new("cn=Alice,dc=example,dc=com")
+                .attr("cn", "Alice")
+                .attr("mail", "alice@example.com")
+                .attr("sn", "Smith")
+                .attr("title", "Engineer")
+                .build(),
+            EntryBuilder::new("cn=Bob,dc=example,dc=com")
+                .attr("cn", "Bob")
+                .attr("sn", "Jones")
+                .build(),
         ];
 
         // Request only mail, cn (in that order) — sn and title should be excluded
         let attrs = vec!["mail".to_string(), "cn".to_string()];
         let mut buf = Vec::new();
-        let count = write_csv(&mut buf, &entries, &attrs).unwrap();
+        let count = write_to(&entries, &mut buf, &attrs, &CsvOptions::default()).unwrap();
         assert_eq!(count, 2);
 
         let output = String::from_utf8(buf).unwrap();
@@ -167,4 +402,103 @@ mod tests {
         // Bob has no mail → empty cell
         assert_eq!(lines[2], "\"cn=Bob,dc=example,dc=com\",,Bob");
     }
+
+    /// Entries with zero, one and five `memberOf` values.
+    fn members() -> Vec<LdapEntry> {
+        vec![
+            EntryBuilder::new("uid=zero,dc=example,dc=com")
+                .attr("uid", "zero")
+                .build(),
+            EntryBuilder::new("uid=one,dc=example,dc=com")
+                .attr("uid", "one")
+                .attr("memberOf", "cn=g1")
+                .build(),
+            EntryBuilder::new("uid=five,dc=example,dc=com")
+                .attr("uid", "five")
+                .attrs("memberOf", &["cn=g1", "cn=g2", "cn=g3", "cn=g4", "cn=g5"])
+                .build(),
+        ]
+    }
+
+    fn csv_lines(entries: &[LdapEntry], attrs: &[&str], explode: Explode) -> Vec<String> {
+        let attrs: Vec<String> = attrs.iter().map(|a| a.to_string()).collect();
+        let mut buf = Vec::new();
+        let count = write_to(
+            entries,
+            &mut buf,
+            &attrs,
+            &CsvOptions {
+                explode,
+                ..CsvOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(count, entries.len());
+        String::from_utf8(buf)
+            .unwrap()
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_explode_attribute() {
+        let lines = csv_lines(
+            &members(),
+            &["uid", "memberOf"],
+            Explode::Attribute("memberof".to_string()),
+        );
+        assert_eq!(lines[0], "dn,uid,memberOf");
+        // zero values: one row, empty cell; one value: one row; five: five rows
+        assert_eq!(lines.len(), 1 + 1 + 1 + 5);
+        assert_eq!(lines[1], "\"uid=zero,dc=example,dc=com\",zero,");
+        assert_eq!(lines[2], "\"uid=one,dc=example,dc=com\",one,cn=g1");
+        for (i, line) in lines[3..].iter().enumerate() {
+            assert_eq!(
+                *line,
+                format!("\"uid=five,dc=example,dc=com\",five,cn=g{}", i + 1)
+            );
+        }
+
+        // Not among the requested columns: added after the DN
+        let lines = csv_lines(
+            &members()[2..],
+            &["uid"],
+            Explode::Attribute("memberOf".to_string()),
+        );
+        assert_eq!(lines[0], "dn,memberOf,uid");
+        assert_eq!(lines.len(), 6);
+    }
+
+    #[test]
+    fn test_explode_long() {
+        let lines = csv_lines(&members(), &["*"], Explode::Long);
+        assert_eq!(lines[0], "dn,attribute,value");
+        // zero: uid only; one: uid + 1 memberOf; five: uid + 5 memberOf
+        assert_eq!(lines.len(), 1 + 1 + 2 + 6);
+        assert_eq!(lines[1], "\"uid=zero,dc=example,dc=com\",uid,zero");
+        assert_eq!(lines[2], "\"uid=one,dc=example,dc=com\",memberOf,cn=g1");
+        assert_eq!(
+            lines
+                .iter()
+                .filter(|l| l.contains("five,dc=") && l.contains("memberOf"))
+                .count(),
+            5
+        );
+    }
+
+    #[test]
+    fn test_layout_row_counts_with_selection() {
+        // Wide: a row per entry, whatever its values
+        let wide = csv_lines(&members(), &["memberOf"], Explode::None);
+        assert_eq!(wide.len(), 1 + 3);
+        assert_eq!(wide[0], "dn,memberOf");
+
+        // Long: a row per selected value; zero has no memberOf
+        let long = csv_lines(&members(), &["memberOf"], Explode::Long);
+        assert_eq!(long.len(), 1 + 1 + 5);
+        assert!(long[1..].iter().all(|l| l.contains(",memberOf,cn=g")));
+        let long = csv_lines(&members(), &["uid", "memberOf"], Explode::Long);
+        assert_eq!(long.len(), 1 + 1 + 2 + 6);
+    }
 }
@@ -0,0 +1,238 @@
+use std::borrow::Borrow;
+use std::io::Write;
+use std::path::Path;
+
+use crate::entry::{AttrValues, LdapEntry};
+use crate::error::CoreError;
+
+use super::AttrSelection;
+
+/// Export entries to DSML v2, as the `searchResultEntry` elements of a
+/// search response.
+pub fn export<I>(entries: I, path: &Path, attributes: &[String]) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+{
+    let file = std::fs::File::create(path)
+        .map_err(|e| CoreError::ExportError(format!("Failed to create file: {}", e)))?;
+    let writer = std::io::BufWriter::new(file);
+
+    write_to(entries, writer, attributes)
+}
+
+/// Write entries in DSML v2 to any writer.
+pub fn write_to<I, W>(entries: I, mut writer: W, attributes: &[String]) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+    W: Write,
+{
+    let write_err = |e: std::io::Error| CoreError::ExportError(format!("Write failed: {}", e));
+    let selection = AttrSelection::parse(attributes);
+    let mut count = 0;
+
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#).map_err(write_err)?;
+    writeln!(
+        writer,
+        r#"<batchResponse xmlns="urn:oasis:names:tc:DSML:2:0:core" xmlns:xsd="http://www.w3.org/2001/XMLSchema" xmlns:xsi="http://www.w3.org/2001/XMLSchema-instance">"#
+    )
+    .map_err(write_err)?;
+    writeln!(writer, "  <searchResponse>").map_err(write_err)?;
+
+    for entry in entries {
+        let entry = entry.borrow();
+        writeln!(
+            writer,
+            r#"    <searchResultEntry dn="{}">"#,
+            escape(&entry.dn)
+        )
+        .map_err(write_err)?;
+
+        for (attr, values) in selection.pick(entry) {
+            writeln!(writer, r#"      <attr name="{}">"#, escape(attr)).map_err(write_err)?;
+            let values: Vec<(String, bool)> = match values {
+                AttrValues::Text(values) => values
+                    .iter()
+                    .map(|value| match needs_base64(attr, value) {
+                        true => (base64_encode(value), true),
+                        false => (escape(value), false),
+                    })
+                    .collect(),
+                AttrValues::Binary(values) => values
+                    .iter()
+                    .map(|value| (base64_encode(value), true))
+                    .collect(),
+            };
+            for (value, base64) in values {
+                if base64 {
+                    writeln!(
+                        writer,
+                        r#"        <value xsi:type="xsd:base64Binary">{}</value>"#,
+                        value
+                    )
+                } else {
+                    writeln!(writer, "        <value>{}</value>", value)
+                }
+                .map_err(write_err)?;
+            }
+            writeln!(writer, "      </attr>").map_err(write_err)?;
+        }
+
+        writeln!(writer, "    </searchResultEntry>").map_err(write_err)?;
+        count += 1;
+    }
+
+    writeln!(writer, "    <searchResultDone>").map_err(write_err)?;
+    writeln!(writer, r#"      <resultCode code="0"/>"#).map_err(write_err)?;
+    writeln!(writer, "    </searchResultDone>").map_err(write_err)?;
+    writeln!(writer, "  </searchResponse>").map_err(write_err)?;
+    writeln!(writer, "</batchResponse>").map_err(write_err)?;
+
+    writer
+        .flush()
+        .map_err(|e| CoreError::ExportError(format!("Flush failed: {}", e)))?;
+
+    Ok(count)
+}
+
+/// Check if a value must be base64-encoded: binary attributes, and
+/// values with characters XML 1.0 cannot carry.
+fn needs_base64(attr: &str, value: &str) -> bool {
+    attr.to_ascii_lowercase().ends_with(";binary")
+        || value
+            .chars()
+            .any(|c| (c < ' ' && !matches!(c, '\t' | '\n')) || matches!(c, '\u{FFFE}' | '\u{FFFF}'))
+}
+
+/// Escape text for an XML attribute or element. Carriage returns are
+/// kept as a character reference so parsers do not fold them away.
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            '\r' => out.push_str("&#13;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn base64_encode(s: impl AsRef<[u8]>) -> String {
+    use base64::Engine;
+    base64::engine::general_purpose::STANDARD.encode(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures::EntryBuilder;
+
+    const DSML_NS: &str = "urn:oasis:names:tc:DSML:2:0:core";
+
+    fn to_string(entries: &[LdapEntry], attributes: &[&str]) -> String {
+        let attributes: Vec<String> = attributes.iter().map(|a| a.to_string()).collect();
+        let mut buf = Vec::new();
+        let count = write_to(entries, &mut buf, &attributes).unwrap();
+        assert_eq!(count, entries.len());
+        String::from_utf8(buf).unwrap()
+    }
+
+    /// (dn, [(attr, [(value, base64)])]) of each searchResultEntry.
+    type Parsed = Vec<(String, Vec<(String, Vec<(String, bool)>)>)>;
+
+    fn parse(xml: &str) -> Parsed {
+        let doc = roxmltree::Document::parse(xml).unwrap();
+        let root = doc.root_element();
+        assert!(root.has_tag_name((DSML_NS, "batchResponse")));
+        let response = root
+            .children()
+            .find(|n| n.has_tag_name((DSML_NS, "searchResponse")))
+            .unwrap();
+        assert!(response
+            .children()
+            .any(|n| n.has_tag_name((DSML_NS, "searchResultDone"))));
+        response
+            .children()
+            .filter(|n| n.has_tag_name((DSML_NS, "searchResultEntry")))
+            .map(|entry| {
+                let attrs = entry
+                    .children()
+                    .filter(|n| n.has_tag_name((DSML_NS, "attr")))
+                    .map(|attr| {
+                        let values = attr
+                            .children()
+                            .filter(|n| n.has_tag_name((DSML_NS, "value")))
+                            .map(|v| {
+                                let base64 = v.attribute((
+                                    "http://www.w3.org/2001/XMLSchema-instance",
+                                    "type",
+                                )) == Some("xsd:base64Binary");
+                                (v.text().unwrap_or_default().to_string(), base64)
+                            })
+                            .collect();
+                        (attr.attribute("name").unwrap().to_string(), values)
+                    })
+                    .collect();
+                (entry.attribute("dn").unwrap().to_string(), attrs)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parses_in_requested_order() {
+        let entries = vec![
+            EntryBuilder::new("cn=Tom & \"Jerry\",dc=example,dc=com")
+                .attr("cn", "Tom & Jerry")
+                .attr("sn", "<Cat>")
+                .attrs("mail", &["tom@example.com", "t@example.com"])
+                .build(),
+            EntryBuilder::new("cn=Bob,dc=example,dc=com")
+                .attr("cn", "Bob")
+                .build(),
+        ];
+
+        let parsed = parse(&to_string(&entries, &["sn", "mail", "cn"]));
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[0].0, "cn=Tom & \"Jerry\",dc=example,dc=com");
+        let names: Vec<&str> = parsed[0].1.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["sn", "mail", "cn"]);
+        assert_eq!(parsed[0].1[0].1, vec![("<Cat>".to_string(), false)]);
+        assert_eq!(parsed[0].1[1].1.len(), 2);
+        // Bob has only cn
+        assert_eq!(parsed[1].1.len(), 1);
+
+        // All attributes: alphabetical
+        let parsed = parse(&to_string(&entries[..1], &["*"]));
+        let names: Vec<&str> = parsed[0].1.iter().map(|(n, _)| n.as_str()).collect();
+        assert_eq!(names, vec!["cn", "mail", "sn"]);
+    }
+
+    #[test]
+    fn test_binary_values_are_base64() {
+        let entries = vec![EntryBuilder::new("cn=Bin,dc=example,dc=com")
+            .attr("cn", "Bin")
+            .attr("userCertificate;binary", "cert")
+            .attr("note", "bell\u{7}")
+            .binary("objectGUID", &[0xff, 0x00])
+            .build()];
+
+        let parsed = parse(&to_string(&entries, &["*"]));
+        let attrs = &parsed[0].1;
+        assert_eq!(
+            attrs[0],
+            ("cn".to_string(), vec![("Bin".to_string(), false)])
+        );
+        assert_eq!(attrs[1].1, vec![(base64_encode("bell\u{7}"), true)]);
+        assert_eq!(attrs[2].1, vec![("/wA=".to_string(), true)]);
+        assert_eq!(attrs[3].1, vec![(base64_encode("cert"), true)]);
+
+        // No entries is still a complete document
+        assert!(parse(&to_string(&[], &["*"])).is_empty());
+    }
+}
@@ -1,66 +1,137 @@
+use std::borrow::Borrow;
 use std::collections::BTreeMap;
+use std::io::Write;
 use std::path::Path;
 
-use crate::entry::LdapEntry;
+use crate::entry::{AttrValues, LdapEntry};
 use crate::error::CoreError;
 
-use super::requested_attrs;
-
-/// Filter entries to include only the requested attributes.
-fn filter_entries(entries: &[LdapEntry], attributes: &[String]) -> Vec<LdapEntry> {
-    if let Some(attrs) = requested_attrs(attributes) {
-        entries
-            .iter()
-            .map(|entry| {
-                let filtered: BTreeMap<String, Vec<String>> = attrs
-                    .iter()
-                    .filter_map(|a| entry.attributes.get(a).map(|v| (a.clone(), v.clone())))
-                    .collect();
-                LdapEntry::new(entry.dn.clone(), filtered)
-            })
-            .collect()
-    } else {
-        entries.to_vec()
+use super::AttrSelection;
+
+/// Keep only the selected attributes of an entry.
+fn filter_entry(entry: &LdapEntry, selection: &AttrSelection) -> LdapEntry {
+    let mut filtered = LdapEntry::new(entry.dn.clone(), BTreeMap::new());
+    for (attr, values) in selection.pick(entry) {
+        match values {
+            AttrValues::Text(v) => {
+                filtered.attributes.insert(attr.clone(), v.to_vec());
+            }
+            AttrValues::Binary(v) => {
+                filtered.binary_attributes.insert(attr.clone(), v.to_vec());
+            }
+        }
     }
+    filtered
 }
 
 /// Export entries to JSON format (array of entry objects).
-pub fn export(
-    entries: &[LdapEntry],
-    path: &Path,
-    attributes: &[String],
-) -> Result<usize, CoreError> {
-    let filtered = filter_entries(entries, attributes);
-    let json = serde_json::to_string_pretty(&filtered)
-        .map_err(|e| CoreError::ExportError(format!("JSON serialization failed: {}", e)))?;
-
-    std::fs::write(path, json)
-        .map_err(|e| CoreError::ExportError(format!("Failed to write file: {}", e)))?;
+pub fn export<I>(entries: I, path: &Path, attributes: &[String]) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+{
+    let file = std::fs::File::create(path)
+        .map_err(|e| CoreError::ExportError(format!("Failed to create file: {}", e)))?;
+    let writer = std::io::BufWriter::new(file);
 
-    Ok(entries.len())
+    write_to(entries, writer, attributes)
 }
 
 /// Serialize entries to a JSON string.
 pub fn to_string(entries: &[LdapEntry], attributes: &[String]) -> Result<String, CoreError> {
-    let filtered = filter_entries(entries, attributes);
-    serde_json::to_string_pretty(&filtered)
+    let mut buf = Vec::new();
+    write_to(entries, &mut buf, attributes)?;
+    String::from_utf8(buf)
         .map_err(|e| CoreError::ExportError(format!("JSON serialization failed: {}", e)))
 }
 
+/// Write entries as a pretty-printed JSON array to any writer, one entry
+/// at a time.
+pub fn write_to<I, W>(entries: I, mut writer: W, attributes: &[String]) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+    W: Write,
+{
+    let write_err = |e: std::io::Error| CoreError::ExportError(format!("Write failed: {}", e));
+    let selection = AttrSelection::parse(attributes);
+    let mut count = 0;
+
+    write!(writer, "[").map_err(write_err)?;
+    for entry in entries {
+        let json = serde_json::to_string_pretty(&filter_entry(entry.borrow(), &selection))
+            .map_err(|e| CoreError::ExportError(format!("JSON serialization failed: {}", e)))?;
+        let separator = if count > 0 { "," } else { "" };
+        // Indent the object as an array element
+        write!(writer, "{}\n  {}", separator, json.replace('\n', "\n  ")).map_err(write_err)?;
+        count += 1;
+    }
+    if count > 0 {
+        writeln!(writer).map_err(write_err)?;
+    }
+    write!(writer, "]").map_err(write_err)?;
+
+    writer
+        .flush()
+        .map_err(|e| CoreError::ExportError(format!("Flush failed: {}", e)))?;
+
+    Ok(count)
+}
+
+/// Export entries as newline-delimited JSON (NDJSON): one compact
+/// object per line.
+pub fn export_lines<I>(entries: I, path: &Path, attributes: &[String]) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+{
+    let file = std::fs::File::create(path)
+        .map_err(|e| CoreError::ExportError(format!("Failed to create file: {}", e)))?;
+    let writer = std::io::BufWriter::new(file);
+
+    write_lines_to(entries, writer, attributes)
+}
+
+/// Write entries as newline-delimited JSON to any writer, one entry at a
+/// time.
+pub fn write_lines_to<I, W>(
+    entries: I,
+    mut writer: W,
+    attributes: &[String],
+) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+    W: Write,
+{
+    let selection = AttrSelection::parse(attributes);
+    let mut count = 0;
+
+    for entry in entries {
+        serde_json::to_writer(&mut writer, &filter_entry(entry.borrow(), &selection))
+            .map_err(|e| CoreError::ExportError(format!("JSON serialization failed: {}", e)))?;
+        writeln!(writer).map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
+        count += 1;
+    }
+
+    writer
+        .flush()
+        .map_err(|e| CoreError::ExportError(format!("Flush failed: {}", e)))?;
+
+    Ok(count)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::BTreeMap;
+    use crate::testing::fixtures::EntryBuilder;
 
     #[test]
     fn test_export_json_roundtrip() {
-        let entries = vec![LdapEntry::new(
-            "cn=Test,dc=example,dc=com".to_string(),
-            BTreeMap::from([
-                ("cn".to_string(), vec!["Test".to_string()]),
-                ("sn".to_string(), vec!["User".to_string()]),
-            ]),
-        )];
+        let entries = vec![EntryBuilder::new("cn=Test,dc=example,dc=com")
+            .attr("cn", "Test")
+            .attr("sn", "User")
+            .build()];
 
         let star = vec!["*".to_string()];
         let json = to_string(&entries, &star).unwrap();
@@ -70,4 +141,86 @@ mod tests {
         assert_eq!(parsed.len(), 1);
         assert_eq!(parsed[0].dn, "cn=Test,dc=example,dc=com");
     }
+
+    #[test]
+    fn test_streamed_matches_serde() {
+        let entries = [
+            EntryBuilder::new("cn=Alice,dc=example,dc=com")
+                .attrs("cn", &["Alice", "Al"])
+                .attr("mail", "alice@example.com")
+                .build(),
+            EntryBuilder::new("cn=Bob,dc=example,dc=com")
+                .attr("cn", "Bob")
+                .build(),
+        ];
+        let star = vec!["*".to_string()];
+        for entries in [&entries[..], &entries[..1], &[]] {
+            let expected = serde_json::to_string_pretty(entries).unwrap();
+            assert_eq!(to_string(entries, &star).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_binary_values_wrapped() {
+        let entries = vec![EntryBuilder::new("cn=Test,dc=example,dc=com")
+            .attr("cn", "Test")
+            .binary("objectGUID", &[0xff, 0x00, 0x10])
+            .build()];
+
+        let json = to_string(&entries, &["cn".to_string(), "objectguid".to_string()]).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value[0]["attributes"]["objectguid"],
+            serde_json::json!([{ "_base64": "/wAQ" }])
+        );
+
+        let parsed: Vec<LdapEntry> =
+            serde_json::from_str(&to_string(&entries, &["*".to_string()]).unwrap()).unwrap();
+        assert_eq!(parsed, entries);
+    }
+
+    #[test]
+    fn test_json_lines_one_entry_per_line() {
+        let entries = vec![
+            EntryBuilder::new("cn=Alice,dc=example,dc=com")
+                .attr("cn", "Alice")
+                .attr("description", "two\nlines")
+                .attr("mail", "alice@example.com")
+                .build(),
+            EntryBuilder::new("cn=Bob,dc=example,dc=com")
+                .attr("cn", "Bob")
+                .binary("objectGUID", &[0xff])
+                .build(),
+            EntryBuilder::new("cn=Carol,dc=example,dc=com").build(),
+        ];
+
+        let mut buf = Vec::new();
+        let count = write_lines_to(&entries, &mut buf, &["*".to_string()]).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert!(output.ends_with('\n'));
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), count);
+        assert_eq!(count, entries.len());
+        for (line, entry) in lines.iter().zip(&entries) {
+            let parsed: LdapEntry = serde_json::from_str(line).unwrap();
+            assert_eq!(&parsed, entry);
+        }
+
+        // Same attribute selection as the array
+        let mut buf = Vec::new();
+        write_lines_to(&entries, &mut buf, &["MAIL".to_string()]).unwrap();
+        let first: serde_json::Value =
+            serde_json::from_str(String::from_utf8(buf).unwrap().lines().next().unwrap()).unwrap();
+        assert_eq!(
+            first["attributes"],
+            serde_json::json!({ "MAIL": ["alice@example.com"] })
+        );
+
+        let mut buf = Vec::new();
+        assert_eq!(
+            write_lines_to(&[] as &[LdapEntry], &mut buf, &[]).unwrap(),
+            0
+        );
+        assert!(buf.is_empty());
+    }
 }
@@ -1,71 +1,53 @@
+use std::borrow::Borrow;
 use std::io::Write;
 use std::path::Path;
 
-use crate::entry::LdapEntry;
+use crate::entry::{AttrValues, LdapEntry};
 use crate::error::CoreError;
 
-use super::requested_attrs;
+use super::AttrSelection;
 
 /// Export entries to LDIF format (RFC 2849).
-pub fn export(
-    entries: &[LdapEntry],
-    path: &Path,
-    attributes: &[String],
-) -> Result<usize, CoreError> {
+pub fn export<I>(entries: I, path: &Path, attributes: &[String]) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+{
     let file = std::fs::File::create(path)
         .map_err(|e| CoreError::ExportError(format!("Failed to create file: {}", e)))?;
-    let mut writer = std::io::BufWriter::new(file);
+    let writer = std::io::BufWriter::new(file);
 
-    write_ldif(&mut writer, entries, attributes)
+    write_to(entries, writer, attributes)
 }
 
 /// Write entries in LDIF format to any writer.
-pub fn write_ldif<W: Write>(
-    writer: &mut W,
-    entries: &[LdapEntry],
-    attributes: &[String],
-) -> Result<usize, CoreError> {
-    let filtered = requested_attrs(attributes);
+pub fn write_to<I, W>(entries: I, mut writer: W, attributes: &[String]) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+    W: Write,
+{
+    let selection = AttrSelection::parse(attributes);
     let mut count = 0;
 
     for entry in entries {
+        let entry = entry.borrow();
         if count > 0 {
             writeln!(writer).map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
         }
 
-        // DN line
-        if needs_base64(&entry.dn) {
-            writeln!(writer, "dn:: {}", base64_encode(&entry.dn))
-        } else {
-            writeln!(writer, "dn: {}", entry.dn)
-        }
-        .map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
-
-        // Attributes
-        if let Some(attrs) = filtered {
-            for attr in attrs {
-                if let Some(values) = entry.attributes.get(attr) {
-                    for value in values {
-                        if needs_base64(value) {
-                            writeln!(writer, "{}:: {}", attr, base64_encode(value))
-                        } else {
-                            writeln!(writer, "{}: {}", attr, value)
-                        }
-                        .map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
-                    }
-                }
-            }
-        } else {
-            for (attr, values) in &entry.attributes {
-                for value in values {
-                    if needs_base64(value) {
-                        writeln!(writer, "{}:: {}", attr, base64_encode(value))
-                    } else {
-                        writeln!(writer, "{}: {}", attr, value)
-                    }
-                    .map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
-                }
-            }
+        write_line(&mut writer, "dn", &entry.dn)
+            .map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
+        for (attr, values) in selection.pick(entry) {
+            let written = match values {
+                AttrValues::Text(values) => values
+                    .iter()
+                    .try_for_each(|value| write_line(&mut writer, attr, value)),
+                AttrValues::Binary(values) => values
+                    .iter()
+                    .try_for_each(|value| write_bytes(&mut writer, attr, value)),
+            };
+            written.map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
         }
 
         count += 1;
@@ -78,55 +60,90 @@ pub fn write_ldif<W: Write>(
     Ok(count)
 }
 
-/// Check if a value needs base64 encoding for LDIF.
+/// Longest line written; longer ones fold onto continuation lines
+/// starting with a space (RFC 2849).
+const LINE_WIDTH: usize = 76;
+
+/// Write `name: value`, or `name:: base64` when the value is not a safe
+/// LDIF string, folded at [`LINE_WIDTH`] columns.
+pub(super) fn write_line<W: Write>(writer: &mut W, name: &str, value: &str) -> std::io::Result<()> {
+    let line = if needs_base64(value) {
+        format!("{}:: {}", name, base64_encode(value))
+    } else {
+        format!("{}: {}", name, value)
+    };
+    write_folded(writer, &line)
+}
+
+/// Write `name:: base64` for a binary value; those are always base64.
+pub(super) fn write_bytes<W: Write>(
+    writer: &mut W,
+    name: &str,
+    value: &[u8],
+) -> std::io::Result<()> {
+    write_folded(writer, &format!("{}:: {}", name, base64_encode(value)))
+}
+
+/// Write a line, folded at [`LINE_WIDTH`] columns.
+fn write_folded<W: Write>(writer: &mut W, line: &str) -> std::io::Result<()> {
+    let mut rest = line;
+    let mut width = LINE_WIDTH;
+    let mut prefix = "";
+    while rest.len() > width {
+        let mut cut = width;
+        while !rest.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        writeln!(writer, "{}{}", prefix, &rest[..cut])?;
+        rest = &rest[cut..];
+        // The leading space of a continuation counts towards its width
+        prefix = " ";
+        width = LINE_WIDTH - 1;
+    }
+    writeln!(writer, "{}{}", prefix, rest)
+}
+
+/// Check if a value needs base64 encoding for LDIF: anything but a
+/// SAFE-STRING (RFC 2849), or one ending in a space.
 fn needs_base64(s: &str) -> bool {
     if s.is_empty() {
         return false;
     }
     let first = s.as_bytes()[0];
-    if first == b' ' || first == b':' || first == b'<' {
+    if first == b' ' || first == b':' || first == b'<' || s.ends_with(' ') {
         return true;
     }
     s.bytes()
-        .any(|b| b > 127 || (b < 32 && b != b'\n' && b != b'\r'))
+        .any(|b| b > 127 || b == 0 || b == b'\n' || b == b'\r')
 }
 
-fn base64_encode(s: &str) -> String {
+fn base64_encode(s: impl AsRef<[u8]>) -> String {
     use base64::Engine;
-    base64::engine::general_purpose::STANDARD.encode(s.as_bytes())
+    base64::engine::general_purpose::STANDARD.encode(s)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::BTreeMap;
+    use crate::testing::fixtures::EntryBuilder;
 
     #[test]
     fn test_export_ldif() {
         let entries = vec![
-            LdapEntry::new(
-                "cn=Alice,ou=Users,dc=example,dc=com".to_string(),
-                BTreeMap::from([
-                    ("cn".to_string(), vec!["Alice".to_string()]),
-                    ("sn".to_string(), vec!["Smith".to_string()]),
-                    (
-                        "objectClass".to_string(),
-                        vec!["top".to_string(), "person".to_string()],
-                    ),
-                ]),
-            ),
-            LdapEntry::new(
-                "cn=Bob,ou=Users,dc=example,dc=com".to_string(),
-                BTreeMap::from([
-                    ("cn".to_string(), vec!["Bob".to_string()]),
-                    ("sn".to_string(), vec!["Jones".to_string()]),
-                ]),
-            ),
+            EntryBuilder::new("cn=Alice,ou=Users,dc=example,dc=com")
+                .attr("cn", "Alice")
+                .attr("sn", "Smith")
+                .object_classes(&["top", "person"])
+                .build(),
+            EntryBuilder::new("cn=Bob,ou=Users,dc=example,dc=com")
+                .attr("cn", "Bob")
+                .attr("sn", "Jones")
+                .build(),
         ];
 
         let star = vec!["*".to_string()];
         let mut buf = Vec::new();
-        let count = write_ldif(&mut buf, &entries, &star).unwrap();
+        let count = write_to(&entries, &mut buf, &star).unwrap();
         assert_eq!(count, 2);
 
         let output = String::from_utf8(buf).unwrap();
@@ -142,5 +159,66 @@ mod tests {
         assert!(!needs_base64("hello"));
         assert!(needs_base64(" leading space"));
         assert!(needs_base64(":colon"));
+        assert!(needs_base64("trailing space "));
+        assert!(needs_base64("two\nlines"));
+        assert!(needs_base64("carriage\rreturn"));
+        assert!(needs_base64("nul\0"));
+        assert!(needs_base64("caf\u{e9}"));
+        assert!(!needs_base64("tab\tinside"));
+    }
+
+    #[test]
+    fn test_unsafe_values_roundtrip() {
+        // Folds fall after a space
+        let long = format!("{}end", "abcd ".repeat(40));
+        let entries = vec![EntryBuilder::new("cn=Jos\u{e9},dc=example,dc=com")
+            .attr("cn", "Jos\u{e9}")
+            .attr("description", " starts with a space")
+            .attr("postalAddress", "1 Main St\nSpringfield")
+            .attr("note", &long)
+            .build()];
+
+        let mut buf = Vec::new();
+        write_to(&entries, &mut buf, &["*".to_string()]).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert!(output.starts_with(&format!(
+            "dn:: {}\n",
+            base64_encode("cn=Jos\u{e9},dc=example,dc=com")
+        )));
+        assert!(output.contains(&format!(
+            "description:: {}\n",
+            base64_encode(" starts with a space")
+        )));
+        assert!(output.contains(&format!(
+            "postalAddress:: {}\n",
+            base64_encode("1 Main St\nSpringfield")
+        )));
+        assert!(
+            output.lines().all(|line| line.len() <= LINE_WIDTH),
+            "{}",
+            output
+        );
+        assert!(output.lines().any(|line| line.starts_with(' ')));
+
+        let reimported = crate::import::ldif::parse_ldif(&output).unwrap();
+        assert_eq!(reimported, entries);
+    }
+
+    #[test]
+    fn test_binary_values_roundtrip() {
+        let photo: Vec<u8> = (0..=255).collect();
+        let entries = vec![EntryBuilder::new("cn=Bin,dc=example,dc=com")
+            .attr("cn", "Bin")
+            .binary("jpegPhoto", &photo)
+            .build()];
+
+        let mut buf = Vec::new();
+        write_to(&entries, &mut buf, &["*".to_string()]).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert!(output.contains(&format!("jpegPhoto:: {}", &base64_encode(&photo)[..60])));
+        assert!(output.lines().all(|line| line.len() <= LINE_WIDTH));
+
+        let reimported = crate::import::ldif::parse_ldif(&output).unwrap();
+        assert_eq!(reimported, entries);
     }
 }
@@ -0,0 +1,212 @@
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::Path;
+
+use ldap3::Mod;
+
+use crate::error::CoreError;
+use crate::transaction::Change;
+
+use super::ldif::{write_bytes, write_line};
+
+/// Write change records to an LDIF changefile (RFC 2849) at `path`.
+pub fn export(changes: &[Change], path: &Path) -> Result<usize, CoreError> {
+    let file = std::fs::File::create(path)
+        .map_err(|e| CoreError::ExportError(format!("Failed to create file: {}", e)))?;
+    write_changes(changes, std::io::BufWriter::new(file))
+}
+
+/// Write change records (`changetype: add`, `modify`, `delete` or
+/// `modrdn`) to a changefile that `ldapmodify` or the LDIF import can
+/// apply later. Values of an attribute are written sorted.
+pub fn write_changes<W: Write>(changes: &[Change], mut writer: W) -> Result<usize, CoreError> {
+    let failed = |e: std::io::Error| CoreError::ExportError(format!("Write failed: {}", e));
+    for (i, change) in changes.iter().enumerate() {
+        if i > 0 {
+            writeln!(writer).map_err(failed)?;
+        }
+        write_change(&mut writer, change).map_err(failed)?;
+    }
+    writer
+        .flush()
+        .map_err(|e| CoreError::ExportError(format!("Flush failed: {}", e)))?;
+    Ok(changes.len())
+}
+
+fn write_change<W: Write>(writer: &mut W, change: &Change) -> std::io::Result<()> {
+    write_line(writer, "dn", change.dn())?;
+    match change {
+        Change::Add { attrs, binary, .. } => {
+            writeln!(writer, "changetype: add")?;
+            for (attr, values) in attrs {
+                for value in sorted(values) {
+                    write_line(writer, attr, value)?;
+                }
+            }
+            for (attr, values) in binary {
+                let mut values: Vec<_> = values.iter().collect();
+                values.sort();
+                for value in values {
+                    write_bytes(writer, attr, value)?;
+                }
+            }
+        }
+        Change::Modify { mods, .. } => {
+            writeln!(writer, "changetype: modify")?;
+            for m in mods {
+                let (op, attr, values) = match m {
+                    Mod::Add(attr, values) => ("add", attr, values),
+                    Mod::Delete(attr, values) => ("delete", attr, values),
+                    Mod::Replace(attr, values) => ("replace", attr, values),
+                    Mod::Increment(attr, value) => {
+                        writeln!(writer, "increment: {}", attr)?;
+                        write_line(writer, attr, value)?;
+                        writeln!(writer, "-")?;
+                        continue;
+                    }
+                };
+                writeln!(writer, "{}: {}", op, attr)?;
+                for value in sorted(values) {
+                    write_line(writer, attr, value)?;
+                }
+                writeln!(writer, "-")?;
+            }
+        }
+        Change::Delete { .. } => writeln!(writer, "changetype: delete")?,
+        Change::Rename {
+            new_rdn,
+            delete_old_rdn,
+            new_superior,
+            ..
+        } => {
+            writeln!(writer, "changetype: modrdn")?;
+            write_line(writer, "newrdn", new_rdn)?;
+            writeln!(writer, "deleteoldrdn: {}", u8::from(*delete_old_rdn))?;
+            if let Some(superior) = new_superior {
+                write_line(writer, "newsuperior", superior)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn sorted(values: &HashSet<String>) -> Vec<&String> {
+    let mut values: Vec<_> = values.iter().collect();
+    values.sort();
+    values
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bulk::BulkMod;
+    use crate::import::ldif::{parse_str, LdifRecord};
+    use crate::modify::replace_value_mods;
+    use crate::testing::fixtures::EntryBuilder;
+
+    /// Write `changes` and read them back through the LDIF import.
+    fn roundtrip(changes: &[Change]) -> (String, Vec<Change>) {
+        let mut buf = Vec::new();
+        assert_eq!(write_changes(changes, &mut buf).unwrap(), changes.len());
+        let output = String::from_utf8(buf).unwrap();
+        let reparsed = parse_str(&output)
+            .unwrap()
+            .into_iter()
+            .map(|record| match record {
+                LdifRecord::Change(change) => change,
+                other => panic!("not a change record: {:?}", other),
+            })
+            .collect();
+        (output, reparsed)
+    }
+
+    #[test]
+    fn test_changes_roundtrip() {
+        let changes = vec![
+            Change::modify(
+                "uid=alice,ou=People,dc=example,dc=com",
+                &[
+                    BulkMod::ReplaceAttribute {
+                        attr: "telephoneNumber".to_string(),
+                        value: "+1 555 0100".to_string(),
+                    },
+                    BulkMod::DeleteAttribute {
+                        attr: "pager".to_string(),
+                    },
+                ],
+            ),
+            Change::modify(
+                "cn=Staff,ou=Groups,dc=example,dc=com",
+                &[BulkMod::AddValue {
+                    attr: "member".to_string(),
+                    value: "uid=Jos\u{e9},ou=People,dc=example,dc=com".to_string(),
+                }],
+            ),
+            Change::Delete {
+                dn: "uid=old,ou=People,dc=example,dc=com".to_string(),
+            },
+        ];
+
+        let (output, reparsed) = roundtrip(&changes);
+        assert!(output.starts_with(
+            "dn: uid=alice,ou=People,dc=example,dc=com\nchangetype: modify\nreplace: telephoneNumber\ntelephoneNumber: +1 555 0100\n-\ndelete: pager\n-\n"
+        ));
+        assert_eq!(reparsed, changes);
+    }
+
+    #[test]
+    fn test_every_change_type_roundtrips() {
+        let entry = EntryBuilder::new("uid=bob,ou=People,dc=example,dc=com")
+            .attr("uid", "bob")
+            .attr("cn", "Bob Jones")
+            .attr("mail", "bob@example.com")
+            .attr("mail", "bjones@example.com")
+            .object_classes(&["top", "inetOrgPerson"])
+            .build();
+        let changes = vec![
+            Change::add(&entry),
+            Change::Modify {
+                dn: entry.dn.clone(),
+                mods: replace_value_mods("mail", "bob@example.com", "bob@example.org"),
+            },
+            Change::Rename {
+                dn: entry.dn.clone(),
+                new_rdn: "uid=robert".to_string(),
+                delete_old_rdn: true,
+                new_superior: Some("ou=Staff,dc=example,dc=com".to_string()),
+            },
+            Change::Rename {
+                dn: "uid=carol,ou=People,dc=example,dc=com".to_string(),
+                new_rdn: "uid=caroline".to_string(),
+                delete_old_rdn: false,
+                new_superior: None,
+            },
+            Change::Delete {
+                dn: "uid=robert,ou=Staff,dc=example,dc=com".to_string(),
+            },
+        ];
+
+        let (output, reparsed) = roundtrip(&changes);
+        assert!(output.contains(
+            "changetype: modify\ndelete: mail\nmail: bob@example.com\n-\nadd: mail\nmail: bob@example.org\n-\n"
+        ));
+        assert!(output.contains(
+            "changetype: modrdn\nnewrdn: uid=robert\ndeleteoldrdn: 1\nnewsuperior: ou=Staff,dc=example,dc=com\n"
+        ));
+        assert_eq!(reparsed, changes);
+    }
+
+    #[test]
+    fn test_export_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("pending.ldif");
+        let changes = vec![Change::Delete {
+            dn: "uid=old,ou=People,dc=example,dc=com".to_string(),
+        }];
+        assert_eq!(export(&changes, &path).unwrap(), 1);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "dn: uid=old,ou=People,dc=example,dc=com\nchangetype: delete\n"
+        );
+    }
+}
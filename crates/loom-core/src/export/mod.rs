@@ -1,42 +1,245 @@
 pub mod csv;
+pub mod dsml;
 pub mod json;
 pub mod ldif;
+pub mod ldif_changes;
+pub mod sqlite;
+pub mod subtree;
+pub mod timestamps;
 pub mod xlsx;
 
-use std::path::Path;
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
-use crate::entry::LdapEntry;
+use serde::{Deserialize, Serialize};
+
+use crate::dn::Dn;
+use crate::entry::{AttrValues, LdapEntry};
 use crate::error::CoreError;
 
+pub use csv::CsvOptions;
+pub use sqlite::SqliteOptions;
+
 /// Supported export formats.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ExportFormat {
     Ldif,
     Json,
+    /// One compact JSON object per line (NDJSON).
+    #[serde(rename = "jsonl")]
+    JsonLines,
     Csv,
     Xlsx,
+    Dsml,
+    Sqlite,
 }
 
 impl ExportFormat {
     /// Infer format from file extension.
     pub fn from_path(path: &Path) -> Option<Self> {
-        match path.extension()?.to_str()?.to_lowercase().as_str() {
+        Self::from_extension(path.extension()?.to_str()?)
+    }
+
+    fn from_extension(ext: &str) -> Option<Self> {
+        match ext.to_lowercase().as_str() {
             "ldif" | "ldf" => Some(Self::Ldif),
             "json" => Some(Self::Json),
+            "jsonl" | "ndjson" => Some(Self::JsonLines),
             "csv" => Some(Self::Csv),
             "xlsx" | "xls" => Some(Self::Xlsx),
+            "xml" | "dsml" => Some(Self::Dsml),
+            "db" | "sqlite" => Some(Self::Sqlite),
             _ => None,
         }
     }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Ldif => "LDIF",
+            Self::Json => "JSON",
+            Self::JsonLines => "JSON Lines",
+            Self::Csv => "CSV",
+            Self::Xlsx => "Excel",
+            Self::Dsml => "DSML",
+            Self::Sqlite => "SQLite",
+        }
+    }
+}
+
+/// A format by name or extension, ignoring case: `"ldif"`, `"json"`,
+/// `"jsonl"` or `"ndjson"`, `"csv"`, `"xlsx"` or `"excel"`, `"dsml"`,
+/// `"sqlite"`, `".xml"`, ...
+impl std::str::FromStr for ExportFormat {
+    type Err = CoreError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        let name = name.strip_prefix('.').unwrap_or(name);
+        if name.eq_ignore_ascii_case("excel") {
+            return Ok(Self::Xlsx);
+        }
+        Self::from_extension(name)
+            .ok_or_else(|| CoreError::ExportError(format!("Unknown export format \"{}\"", s)))
+    }
+}
+
+/// A named, persisted attribute selection offered in the export dialog.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportPreset {
+    pub name: String,
+    /// Attribute list as passed to an export, e.g. `["cn", "mail"]`.
+    pub attributes: Vec<String>,
+    /// Format chosen with the selection; the filename decides when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<ExportFormat>,
+}
+
+/// What an export wrote, for the summary shown afterwards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportReport {
+    pub format: ExportFormat,
+    pub path: PathBuf,
+    pub written: usize,
+    /// Entries handed to the exporter that did not end up in the file.
+    pub skipped: usize,
+    /// Size of the written file in bytes.
+    pub bytes: u64,
+    pub duration: Duration,
+    pub warnings: Vec<String>,
+}
+
+impl ExportReport {
+    /// One-line summary for the status bar and log.
+    pub fn summary(&self) -> String {
+        let mut msg = format!(
+            "Exported {} entries to {}",
+            self.written,
+            self.path.display()
+        );
+        if self.skipped > 0 {
+            msg.push_str(&format!(", {} skipped", self.skipped));
+        }
+        for warning in &self.warnings {
+            msg.push_str("; ");
+            msg.push_str(warning);
+        }
+        msg
+    }
 }
 
-/// If `attributes` contains only `"*"`, return `None` (meaning all attributes,
-/// alphabetical order). Otherwise return the explicit list.
-pub fn requested_attrs(attributes: &[String]) -> Option<&[String]> {
-    if attributes.len() == 1 && attributes[0] == "*" {
-        None
-    } else {
-        Some(attributes)
+/// Format a byte count for display, e.g. `"1.5 KiB"`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+/// Which attributes an export writes, read from its attribute list the
+/// way LDAP reads the attribute list of a search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttrSelection {
+    /// `["*"]`, or no list: every attribute the entries carry,
+    /// alphabetical.
+    All,
+    /// `["*", "+"]`, or `["+"]` for operational attributes alone: the
+    /// same, once the search has asked the server for them.
+    AllPlusOperational,
+    /// Exactly these attributes, in this order. With `"*"` or `"+"` in
+    /// the list, the entries' other attributes follow, alphabetical.
+    Explicit { names: Vec<String>, others: bool },
+}
+
+impl AttrSelection {
+    pub fn parse(attributes: &[String]) -> Self {
+        let operational = attributes.iter().any(|a| a == "+");
+        let names: Vec<String> = attributes
+            .iter()
+            .filter(|a| *a != "+" && *a != "*")
+            .cloned()
+            .collect();
+        let all = attributes.iter().any(|a| a == "*");
+        match (names.is_empty(), operational) {
+            (true, true) => Self::AllPlusOperational,
+            (true, false) => Self::All,
+            // Names alongside "*" still fix the order of those columns
+            (false, _) => Self::Explicit {
+                names,
+                others: operational || all,
+            },
+        }
+    }
+
+    /// The attributes always written, in order, when they do not depend
+    /// on the entries.
+    pub fn fixed(&self) -> Option<&[String]> {
+        match self {
+            Self::Explicit {
+                names,
+                others: false,
+            } => Some(names),
+            _ => None,
+        }
+    }
+
+    /// The selected attributes of `entry`, in export order. Listed names
+    /// match case-insensitively and keep the casing they were asked for.
+    pub fn pick<'a>(&'a self, entry: &'a LdapEntry) -> Vec<(&'a String, AttrValues<'a>)> {
+        match self {
+            Self::All | Self::AllPlusOperational => entry.all_values(),
+            Self::Explicit { names, others } => {
+                let mut picked: Vec<_> = names
+                    .iter()
+                    .filter_map(|name| entry.values_ci(name).map(|v| (name, v)))
+                    .collect();
+                if *others {
+                    picked.extend(
+                        entry
+                            .all_values()
+                            .into_iter()
+                            .filter(|(attr, _)| !self.lists(attr)),
+                    );
+                }
+                picked
+            }
+        }
+    }
+
+    /// Whether `attr` is among the listed names, ignoring case.
+    fn lists(&self, attr: &str) -> bool {
+        match self {
+            Self::Explicit { names, .. } => names.iter().any(|n| n.eq_ignore_ascii_case(attr)),
+            _ => false,
+        }
+    }
+
+    /// The columns after the DN for `entries`: the fixed attributes, or
+    /// the listed names then every other attribute, alphabetical.
+    pub fn columns<E: std::borrow::Borrow<LdapEntry>>(&self, entries: &[E]) -> Vec<String> {
+        if let Some(fixed) = self.fixed() {
+            return fixed.to_vec();
+        }
+        let names: &[String] = match self {
+            Self::Explicit { names, .. } => names,
+            _ => &[],
+        };
+        let rest: std::collections::BTreeSet<&String> = entries
+            .iter()
+            .flat_map(|entry| entry.borrow().attribute_names())
+            .filter(|attr| !self.lists(attr))
+            .collect();
+        names.iter().chain(rest).cloned().collect()
     }
 }
 
@@ -44,21 +247,420 @@ pub fn requested_attrs(attributes: &[String]) -> Option<&[String]> {
 ///
 /// `attributes` controls which attributes appear and in what order:
 /// - `["*"]` → all attributes, alphabetical order
+/// - `["*", "+"]` → operational attributes too, when the entries carry them
 /// - `["cn", "sn", "mail"]` → exactly those attributes, in that order
+///
+/// `options` lay out the rows of CSV and Excel files; other formats
+/// ignore them. An existing SQLite database is replaced, like any other
+/// file; use [`sqlite::export`] to fail instead.
 pub fn export_entries(
     entries: &[LdapEntry],
     path: &Path,
     attributes: &[String],
+    options: &CsvOptions,
 ) -> Result<usize, CoreError> {
-    let format = ExportFormat::from_path(path)
-        .ok_or_else(|| CoreError::ExportError("Unknown file extension".to_string()))?;
+    export_entries_iter(entries, path, attributes, options)
+}
 
-    match format {
+/// Export entries to a file as they arrive, like [`export_entries`],
+/// returning how many were written.
+///
+/// Each entry is written before the next is pulled, so a large result
+/// set need not be held in memory. The exception is CSV and Excel with
+/// all attributes (`["*"]`): their columns depend on every entry, so
+/// those are gathered first unless `options` use the long layout.
+pub fn export_entries_iter<I>(
+    entries: I,
+    path: &Path,
+    attributes: &[String],
+    options: &CsvOptions,
+) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+{
+    export_entries_as(entries, path, format_of(path)?, attributes, options)
+}
+
+/// Export entries to a file in `format`, whatever the path's extension,
+/// like [`export_entries_iter`]. For paths such as `/dev/stdout` or
+/// `export.bak` that say nothing of the format.
+pub fn export_entries_as<I>(
+    entries: I,
+    path: &Path,
+    format: ExportFormat,
+    attributes: &[String],
+    options: &CsvOptions,
+) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+{
+    export_with_warnings(entries, path, format, attributes, options).map(|(written, _)| written)
+}
+
+/// The format a path's extension names.
+fn format_of(path: &Path) -> Result<ExportFormat, CoreError> {
+    ExportFormat::from_path(path)
+        .ok_or_else(|| CoreError::ExportError("Unknown file extension".to_string()))
+}
+
+/// Export like [`export_entries_as`], also returning warnings about
+/// values that could not be written as they were.
+fn export_with_warnings<I>(
+    entries: I,
+    path: &Path,
+    format: ExportFormat,
+    attributes: &[String],
+    options: &CsvOptions,
+) -> Result<(usize, Vec<String>), CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+{
+    let written = match format {
         ExportFormat::Ldif => ldif::export(entries, path, attributes),
         ExportFormat::Json => json::export(entries, path, attributes),
-        ExportFormat::Csv => csv::export(entries, path, attributes),
-        ExportFormat::Xlsx => xlsx::export(entries, path, attributes),
+        ExportFormat::JsonLines => json::export_lines(entries, path, attributes),
+        ExportFormat::Csv => csv::export(entries, path, attributes, options),
+        ExportFormat::Xlsx => {
+            let report = xlsx::export(entries, path, attributes, options)?;
+            let mut warnings = Vec::new();
+            if report.truncated > 0 {
+                warnings.push(format!(
+                    "{} values longer than Excel's 32,767 character limit were truncated",
+                    report.truncated
+                ));
+            }
+            return Ok((report.written, warnings));
+        }
+        ExportFormat::Dsml => dsml::export(entries, path, attributes),
+        ExportFormat::Sqlite => {
+            sqlite::export(entries, path, attributes, &SqliteOptions::default())
+        }
+    }?;
+    Ok((written, Vec::new()))
+}
+
+/// Write entries in `format` to any writer, such as stdout or a buffer,
+/// returning how many were written. Like [`export_entries_iter`], each
+/// entry is written before the next is pulled, except that an Excel
+/// workbook or SQLite database is assembled in memory first.
+pub fn export_entries_to_writer<I, W>(
+    entries: I,
+    format: ExportFormat,
+    writer: W,
+    attributes: &[String],
+    options: &CsvOptions,
+) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+    W: std::io::Write,
+{
+    match format {
+        ExportFormat::Ldif => ldif::write_to(entries, writer, attributes),
+        ExportFormat::Json => json::write_to(entries, writer, attributes),
+        ExportFormat::JsonLines => json::write_lines_to(entries, writer, attributes),
+        ExportFormat::Csv => csv::write_to(entries, writer, attributes, options),
+        ExportFormat::Xlsx => {
+            xlsx::write_to(entries, writer, attributes, options).map(|r| r.written)
+        }
+        ExportFormat::Dsml => dsml::write_to(entries, writer, attributes),
+        ExportFormat::Sqlite => sqlite::write_to(entries, writer, attributes),
+    }
+}
+
+/// Export entries like [`export_entries`] and report what was written.
+pub fn export_with_report(
+    entries: &[LdapEntry],
+    path: &Path,
+    attributes: &[String],
+    options: &CsvOptions,
+) -> Result<ExportReport, CoreError> {
+    export_with_report_as(entries, path, format_of(path)?, attributes, options)
+}
+
+/// Export entries in `format`, whatever the path's extension, and report
+/// what was written.
+pub fn export_with_report_as(
+    entries: &[LdapEntry],
+    path: &Path,
+    format: ExportFormat,
+    attributes: &[String],
+    options: &CsvOptions,
+) -> Result<ExportReport, CoreError> {
+    export_with_progress(
+        entries,
+        path,
+        format,
+        attributes,
+        options,
+        &AtomicBool::new(false),
+        |_, _| {},
+    )
+}
+
+/// How many entries pass between progress reports.
+pub const PROGRESS_EVERY: usize = 1000;
+
+/// Export entries like [`export_entries`], calling `on_progress(done,
+/// total)` every [`PROGRESS_EVERY`] entries and once at the end.
+///
+/// The file is written beside `path` and moved over it once complete, so
+/// a failed export leaves an existing file as it was. Setting `cancel`
+/// stops the export before the next entry; the partial file is removed
+/// and the export fails with [`CoreError::Cancelled`].
+pub fn export_entries_with_progress<F>(
+    entries: &[LdapEntry],
+    path: &Path,
+    attributes: &[String],
+    options: &CsvOptions,
+    cancel: &AtomicBool,
+    on_progress: F,
+) -> Result<usize, CoreError>
+where
+    F: FnMut(usize, usize),
+{
+    export_with_progress(
+        entries,
+        path,
+        format_of(path)?,
+        attributes,
+        options,
+        cancel,
+        on_progress,
+    )
+    .map(|report| report.written)
+}
+
+/// Export entries in `format` with progress and cancellation, like
+/// [`export_entries_with_progress`], and report what was written. LDIF
+/// lists every parent ahead of its children, whatever order the entries
+/// came in.
+pub fn export_with_progress<F>(
+    entries: &[LdapEntry],
+    path: &Path,
+    format: ExportFormat,
+    attributes: &[String],
+    options: &CsvOptions,
+    cancel: &AtomicBool,
+    mut on_progress: F,
+) -> Result<ExportReport, CoreError>
+where
+    F: FnMut(usize, usize),
+{
+    let total = entries.len();
+    let mut done = 0;
+    let started = Instant::now();
+    let ordered = match format {
+        ExportFormat::Ldif => parents_first(entries),
+        _ => entries.iter().collect(),
+    };
+    let tracked = ordered
+        .into_iter()
+        .take_while(|_| !cancel.load(Ordering::Relaxed))
+        .inspect(|_| {
+            done += 1;
+            if done % PROGRESS_EVERY == 0 {
+                on_progress(done, total);
+            }
+        });
+    let staging = staging_path(path);
+    let target = staging.as_deref().unwrap_or(path);
+    let result = export_with_warnings(tracked, target, format, attributes, options);
+    let cancelled = done < total && cancel.load(Ordering::Relaxed);
+    let (written, warnings) = settle(path, staging.as_deref(), result, cancelled)?;
+    on_progress(done, total);
+    Ok(report(
+        format,
+        path,
+        written,
+        total.saturating_sub(written),
+        started,
+        warnings,
+    ))
+}
+
+/// Export entries in `format` as an iterator hands them over, for
+/// sources whose size is not known up front such as the pages of a
+/// search. `on_progress(done)` is called every [`PROGRESS_EVERY`]
+/// entries and once at the end. Entries are written in the order they
+/// come; staging and cancellation work as in [`export_with_progress`].
+pub fn export_stream<I, F>(
+    entries: I,
+    path: &Path,
+    format: ExportFormat,
+    attributes: &[String],
+    options: &CsvOptions,
+    cancel: &AtomicBool,
+    mut on_progress: F,
+) -> Result<ExportReport, CoreError>
+where
+    I: IntoIterator<Item = LdapEntry>,
+    F: FnMut(usize),
+{
+    let mut done = 0;
+    let started = Instant::now();
+    let tracked = entries
+        .into_iter()
+        .take_while(|_| !cancel.load(Ordering::Relaxed))
+        .inspect(|_| {
+            done += 1;
+            if done % PROGRESS_EVERY == 0 {
+                on_progress(done);
+            }
+        });
+    let staging = staging_path(path);
+    let target = staging.as_deref().unwrap_or(path);
+    let result = export_with_warnings(tracked, target, format, attributes, options);
+    let cancelled = cancel.load(Ordering::Relaxed);
+    let (written, warnings) = settle(path, staging.as_deref(), result, cancelled)?;
+    on_progress(done);
+    Ok(report(format, path, written, 0, started, warnings))
+}
+
+/// Write a table of text -- a header and rows, laid out the way a view
+/// shows them -- to a CSV or Excel file. Staged like the other exports,
+/// so a failed write leaves an existing file as it was.
+pub fn export_table(
+    header: &[String],
+    rows: &[Vec<String>],
+    path: &Path,
+    format: ExportFormat,
+) -> Result<ExportReport, CoreError> {
+    let started = Instant::now();
+    let staging = staging_path(path);
+    let target = staging.as_deref().unwrap_or(path);
+    let result = match format {
+        ExportFormat::Csv => std::fs::File::create(target)
+            .map_err(|e| CoreError::ExportError(format!("Failed to create file: {}", e)))
+            .and_then(|file| {
+                csv::write_table(
+                    header,
+                    rows,
+                    std::io::BufWriter::new(file),
+                    &CsvOptions::default(),
+                )
+            })
+            .map(|written| (written, Vec::new())),
+        ExportFormat::Xlsx => xlsx::export_table(header, rows, target).map(|report| {
+            let mut warnings = Vec::new();
+            if report.truncated > 0 {
+                warnings.push(format!(
+                    "{} values longer than Excel's 32,767 character limit were truncated",
+                    report.truncated
+                ));
+            }
+            (report.written, warnings)
+        }),
+        other => {
+            return Err(CoreError::ExportError(format!(
+                "A table exports to CSV or Excel, not {}",
+                other.label()
+            )))
+        }
+    };
+    let (written, warnings) = settle(path, staging.as_deref(), result, false)?;
+    Ok(report(format, path, written, 0, started, warnings))
+}
+
+/// Finish a staged export: move the staging file over `path`, or remove
+/// what was written when the export failed or was `cancelled`.
+fn settle(
+    path: &Path,
+    staging: Option<&Path>,
+    result: Result<(usize, Vec<String>), CoreError>,
+    cancelled: bool,
+) -> Result<(usize, Vec<String>), CoreError> {
+    if cancelled || result.is_err() {
+        if let Some(staging) = staging {
+            let _ = std::fs::remove_file(staging);
+        } else if cancelled {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+    if cancelled {
+        return Err(CoreError::Cancelled);
+    }
+    let written = result?;
+    if let Some(staging) = staging {
+        std::fs::rename(staging, path).map_err(|e| {
+            let _ = std::fs::remove_file(staging);
+            CoreError::ExportError(format!("Cannot write {}: {}", path.display(), e))
+        })?;
     }
+    Ok(written)
+}
+
+fn report(
+    format: ExportFormat,
+    path: &Path,
+    written: usize,
+    skipped: usize,
+    started: Instant,
+    mut warnings: Vec<String>,
+) -> ExportReport {
+    let duration = started.elapsed();
+    let bytes = std::fs::metadata(path).map(|m| m.len()).unwrap_or(0);
+    if written == 0 {
+        warnings.push("no entries matched".to_string());
+    }
+    ExportReport {
+        format,
+        path: path.to_path_buf(),
+        written,
+        skipped,
+        bytes,
+        duration,
+        warnings,
+    }
+}
+
+/// Entries with every parent ahead of its children, so an LDIF file can
+/// be imported in one pass. An entry listed before its parent moves to
+/// just after it; everything else keeps its place.
+fn parents_first(entries: &[LdapEntry]) -> Vec<&LdapEntry> {
+    let dns: Vec<Option<Dn>> = entries.iter().map(|e| Dn::parse(&e.dn).ok()).collect();
+    let index: HashMap<&Dn, usize> = dns
+        .iter()
+        .enumerate()
+        .filter_map(|(i, dn)| Some((dn.as_ref()?, i)))
+        .collect();
+    let mut written = vec![false; entries.len()];
+    let mut waiting: HashMap<usize, Vec<usize>> = HashMap::new();
+    let mut ordered = Vec::with_capacity(entries.len());
+    for (i, dn) in dns.iter().enumerate() {
+        let parent = dn
+            .as_ref()
+            .and_then(Dn::parent)
+            .and_then(|p| index.get(&p).copied());
+        if let Some(p) = parent.filter(|&p| !written[p]) {
+            waiting.entry(p).or_default().push(i);
+            continue;
+        }
+        let mut ready = vec![i];
+        while let Some(i) = ready.pop() {
+            written[i] = true;
+            ordered.push(&entries[i]);
+            if let Some(children) = waiting.remove(&i) {
+                ready.extend(children.into_iter().rev());
+            }
+        }
+    }
+    ordered
+}
+
+/// Where a file export is written before it replaces `path`: `path`
+/// with `.partial` appended. `None` for devices such as `/dev/stdout`,
+/// which are written in place.
+fn staging_path(path: &Path) -> Option<PathBuf> {
+    if path.exists() && !path.is_file() {
+        return None;
+    }
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    Some(PathBuf::from(partial))
 }
 
 #[cfg(test)]
@@ -91,10 +693,302 @@ mod tests {
             ExportFormat::from_path(Path::new("test.xls")),
             Some(ExportFormat::Xlsx)
         );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("test.xml")),
+            Some(ExportFormat::Dsml)
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("test.dsml")),
+            Some(ExportFormat::Dsml)
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("test.db")),
+            Some(ExportFormat::Sqlite)
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("test.sqlite")),
+            Some(ExportFormat::Sqlite)
+        );
         assert_eq!(ExportFormat::from_path(Path::new("test.txt")), None);
         assert_eq!(ExportFormat::from_path(Path::new("noext")), None);
     }
 
+    #[test]
+    fn test_export_with_report() {
+        use crate::testing::fixtures::person;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("people.json");
+        let entries = vec![person("dc=example,dc=com", "Ada", "Lovelace")];
+        let report =
+            export_with_report(&entries, &path, &["*".to_string()], &CsvOptions::default())
+                .unwrap();
+        assert_eq!(report.format, ExportFormat::Json);
+        assert_eq!(report.written, 1);
+        assert_eq!(report.skipped, 0);
+        assert_eq!(report.bytes, std::fs::metadata(&path).unwrap().len());
+        assert!(report.warnings.is_empty());
+
+        let empty =
+            export_with_report(&[], &path, &["*".to_string()], &CsvOptions::default()).unwrap();
+        assert_eq!(empty.warnings, vec!["no entries matched"]);
+        assert!(export_with_report(
+            &entries,
+            &dir.path().join("x.txt"),
+            &[],
+            &CsvOptions::default()
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_ldif_report_writes_parents_first() {
+        use crate::testing::fixtures::{domain, org_unit, person};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tree.ldif");
+        let people = "ou=Smith\\, Family,dc=example,dc=com";
+        let entries = vec![
+            person(people, "Ada", "Smith"),
+            org_unit("dc=example,dc=com", "Smith\\, Family"),
+            person(people, "Bob", "Smith"),
+            domain("dc=example,dc=com"),
+        ];
+        export_with_report(&entries, &path, &["cn".to_string()], &CsvOptions::default()).unwrap();
+        let dns: Vec<String> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .filter_map(|l| l.strip_prefix("dn: "))
+            .map(str::to_string)
+            .collect();
+        assert_eq!(
+            dns,
+            vec![
+                "dc=example,dc=com".to_string(),
+                people.to_string(),
+                format!("uid=ada.smith,{}", people),
+                format!("uid=bob.smith,{}", people),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_streamed_export_matches_slice() {
+        use crate::testing::fixtures::person;
+
+        let dir = tempfile::tempdir().unwrap();
+        let entries: Vec<LdapEntry> = ["Ada", "Grace", "Edsger"]
+            .iter()
+            .map(|name| person("dc=example,dc=com", name, "Tester"))
+            .collect();
+        let attrs = vec!["cn".to_string(), "sn".to_string()];
+        let options = CsvOptions::default();
+        for ext in ["ldif", "json", "csv", "xml"] {
+            let slice = dir.path().join(format!("slice.{}", ext));
+            let streamed = dir.path().join(format!("streamed.{}", ext));
+            assert_eq!(
+                export_entries(&entries, &slice, &attrs, &options).unwrap(),
+                3
+            );
+            let owned = entries.clone().into_iter();
+            assert_eq!(
+                export_entries_iter(owned, &streamed, &attrs, &options).unwrap(),
+                3
+            );
+            assert_eq!(
+                std::fs::read_to_string(&slice).unwrap(),
+                std::fs::read_to_string(&streamed).unwrap(),
+                "{}",
+                ext
+            );
+        }
+    }
+
+    #[test]
+    fn test_export_to_writer_matches_file() {
+        use crate::testing::fixtures::person;
+
+        let dir = tempfile::tempdir().unwrap();
+        let entries = vec![person("dc=example,dc=com", "Ada", "Lovelace")];
+        let star = vec!["*".to_string()];
+        let options = CsvOptions::default();
+        for format in [
+            ExportFormat::Ldif,
+            ExportFormat::Json,
+            ExportFormat::Csv,
+            ExportFormat::Dsml,
+        ] {
+            let path = dir
+                .path()
+                .join(format!("out.{}", format.label().to_lowercase()));
+            let path = match format {
+                ExportFormat::Dsml => path.with_extension("xml"),
+                _ => path,
+            };
+            export_entries(&entries, &path, &star, &options).unwrap();
+            let mut buf = Vec::new();
+            let count =
+                export_entries_to_writer(&entries, format, &mut buf, &star, &options).unwrap();
+            assert_eq!(count, 1);
+            assert_eq!(buf, std::fs::read(&path).unwrap(), "{:?}", format);
+        }
+    }
+
+    #[test]
+    fn test_attr_selection() {
+        use crate::testing::fixtures::EntryBuilder;
+
+        let list =
+            |attrs: &[&str]| -> Vec<String> { attrs.iter().map(|a| a.to_string()).collect() };
+        assert_eq!(AttrSelection::parse(&list(&["*"])), AttrSelection::All);
+        assert_eq!(AttrSelection::parse(&[]), AttrSelection::All);
+        assert_eq!(
+            AttrSelection::parse(&list(&["*", "+"])),
+            AttrSelection::AllPlusOperational
+        );
+        assert_eq!(
+            AttrSelection::parse(&list(&["+"])),
+            AttrSelection::AllPlusOperational
+        );
+        let explicit = AttrSelection::parse(&list(&["sn", "cn"]));
+        assert_eq!(explicit.fixed(), Some(&list(&["sn", "cn"])[..]));
+
+        let entry = EntryBuilder::new("cn=Ada,dc=example,dc=com")
+            .attr("cn", "Ada")
+            .attr("sn", "Lovelace")
+            .attr("modifyTimestamp", "20240101000000Z")
+            .attr("entryUUID", "0b9d")
+            .build();
+        let names = |selection: &AttrSelection| -> Vec<String> {
+            selection
+                .pick(&entry)
+                .into_iter()
+                .map(|(a, _)| a.clone())
+                .collect()
+        };
+        assert_eq!(names(&explicit), list(&["sn", "cn"]));
+        assert_eq!(
+            names(&AttrSelection::parse(&list(&["sn", "+"]))),
+            list(&["sn", "cn", "entryUUID", "modifyTimestamp"])
+        );
+        assert_eq!(
+            AttrSelection::parse(&list(&["sn", "+"])).columns(&[&entry]),
+            list(&["sn", "cn", "entryUUID", "modifyTimestamp"])
+        );
+
+        // Operational attributes the search returned reach the file
+        let mut buf = Vec::new();
+        csv::write_to(
+            [&entry],
+            &mut buf,
+            &list(&["*", "+"]),
+            &CsvOptions::default(),
+        )
+        .unwrap();
+        let header = String::from_utf8(buf)
+            .unwrap()
+            .lines()
+            .next()
+            .unwrap()
+            .to_string();
+        assert_eq!(header, "dn,cn,entryUUID,modifyTimestamp,sn");
+    }
+
+    #[test]
+    fn test_explicit_attributes_ignore_case() {
+        use crate::testing::fixtures::EntryBuilder;
+        use calamine::{open_workbook, Reader, Xlsx};
+
+        let dir = tempfile::tempdir().unwrap();
+        let entries = vec![EntryBuilder::new("cn=Ada,dc=example,dc=com")
+            .attr("cn", "Ada")
+            .attr("Mail", "ada@example.com")
+            .attr("sn", "Lovelace")
+            .build()];
+        let attrs = vec!["CN".to_string(), "mail".to_string()];
+        let options = CsvOptions::default();
+        let export = |ext: &str| {
+            let path = dir.path().join(format!("out.{}", ext));
+            assert_eq!(
+                export_entries(&entries, &path, &attrs, &options).unwrap(),
+                1
+            );
+            path
+        };
+
+        let ldif = std::fs::read_to_string(export("ldif")).unwrap();
+        assert!(
+            ldif.contains("CN: Ada\nmail: ada@example.com\n"),
+            "{}",
+            ldif
+        );
+        assert!(!ldif.contains("sn:"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(export("json")).unwrap()).unwrap();
+        assert_eq!(json[0]["attributes"]["CN"][0], "Ada");
+        assert_eq!(json[0]["attributes"]["mail"][0], "ada@example.com");
+
+        let csv = std::fs::read_to_string(export("csv")).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(lines[0], "dn,CN,mail");
+        assert_eq!(lines[1], "\"cn=Ada,dc=example,dc=com\",Ada,ada@example.com");
+
+        let dsml = std::fs::read_to_string(export("xml")).unwrap();
+        assert!(dsml.contains(r#"<attr name="CN">"#));
+        assert!(dsml.contains(r#"<attr name="mail">"#));
+
+        let mut workbook: Xlsx<_> = open_workbook(export("xlsx")).unwrap();
+        let range = workbook.worksheet_range("LDAP Entries").unwrap();
+        let rows: Vec<Vec<String>> = range
+            .rows()
+            .map(|r| r.iter().map(|c| c.to_string()).collect())
+            .collect();
+        assert_eq!(rows[0], vec!["dn", "CN", "mail"]);
+        assert_eq!(rows[1][1..], ["Ada", "ada@example.com"]);
+    }
+
+    #[test]
+    fn test_export_table_as_shown() {
+        let dir = tempfile::tempdir().unwrap();
+        let header = vec!["cn".to_string(), "mail".to_string()];
+        let rows = vec![
+            vec!["Bob".to_string(), "b@x; bob@x".to_string()],
+            vec!["Ada".to_string(), String::new()],
+        ];
+
+        let path = dir.path().join("people.csv");
+        let report = export_table(&header, &rows, &path, ExportFormat::Csv).unwrap();
+        assert_eq!(report.written, 2);
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "cn,mail\nBob,b@x; bob@x\nAda,\n"
+        );
+
+        let path = dir.path().join("people.xlsx");
+        export_table(&header, &rows, &path, ExportFormat::Xlsx).unwrap();
+        let mut workbook: calamine::Xlsx<_> = calamine::open_workbook(&path).unwrap();
+        let range = calamine::Reader::worksheet_range(&mut workbook, "LDAP Entries").unwrap();
+        let cells: Vec<Vec<String>> = range
+            .rows()
+            .map(|row| row.iter().map(|c| c.to_string()).collect())
+            .collect();
+        assert_eq!(cells[0], header);
+        assert_eq!(cells[1], rows[0]);
+        assert_eq!(cells[2], rows[1]);
+
+        let path = dir.path().join("people.ldif");
+        assert!(export_table(&header, &rows, &path, ExportFormat::Ldif).is_err());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+    }
+
     #[test]
     fn test_format_from_path_case_insensitive() {
         assert_eq!(
@@ -106,4 +1000,145 @@ mod tests {
             Some(ExportFormat::Json)
         );
     }
+
+    #[test]
+    fn test_format_from_str() {
+        for (name, format) in [
+            ("ldif", ExportFormat::Ldif),
+            ("JSON", ExportFormat::Json),
+            ("ndjson", ExportFormat::JsonLines),
+            (".jsonl", ExportFormat::JsonLines),
+            (".csv", ExportFormat::Csv),
+            ("Excel", ExportFormat::Xlsx),
+            ("xlsx", ExportFormat::Xlsx),
+            ("dsml", ExportFormat::Dsml),
+            ("sqlite", ExportFormat::Sqlite),
+        ] {
+            assert_eq!(name.parse::<ExportFormat>().unwrap(), format, "{}", name);
+        }
+        let err = "bak".parse::<ExportFormat>().unwrap_err();
+        assert!(err.to_string().contains("Unknown export format \"bak\""));
+    }
+
+    #[test]
+    fn test_export_as_ignores_extension() {
+        use crate::testing::fixtures::person;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("people.bak");
+        let entries = vec![person("ou=People,dc=example,dc=com", "Ada", "Lovelace")];
+        let star = vec!["*".to_string()];
+
+        assert!(export_entries(&entries, &path, &star, &CsvOptions::default()).is_err());
+        let written = export_entries_as(
+            &entries,
+            &path,
+            ExportFormat::Json,
+            &star,
+            &CsvOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(written, 1);
+        let parsed = crate::import::json::import(&path).unwrap();
+        assert_eq!(parsed, entries);
+
+        let report = export_with_report_as(
+            &entries,
+            &path,
+            ExportFormat::Ldif,
+            &star,
+            &CsvOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(report.format, ExportFormat::Ldif);
+        assert!(std::fs::read_to_string(&path).unwrap().starts_with("dn: "));
+    }
+
+    fn many(n: usize) -> Vec<LdapEntry> {
+        (0..n)
+            .map(|i| {
+                crate::testing::fixtures::EntryBuilder::new(format!("uid=u{},dc=example,dc=com", i))
+                    .attr("uid", format!("u{}", i))
+                    .build()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_export_progress() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("people.csv");
+        let entries = many(2500);
+        let mut ticks = Vec::new();
+        let written = export_entries_with_progress(
+            &entries,
+            &path,
+            &["uid".to_string()],
+            &CsvOptions::default(),
+            &AtomicBool::new(false),
+            |done, total| ticks.push((done, total)),
+        )
+        .unwrap();
+        assert_eq!(written, 2500);
+        assert_eq!(ticks, vec![(1000, 2500), (2000, 2500), (2500, 2500)]);
+    }
+
+    #[test]
+    fn test_cancelled_export_removes_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let entries = many(2500);
+        for ext in ["ldif", "csv", "xlsx", "sqlite"] {
+            let path = dir.path().join(format!("people.{}", ext));
+            let cancel = AtomicBool::new(false);
+            let result = export_entries_with_progress(
+                &entries,
+                &path,
+                &["*".to_string()],
+                &CsvOptions::default(),
+                &cancel,
+                |done, _| {
+                    if done == 1000 {
+                        cancel.store(true, Ordering::Relaxed);
+                    }
+                },
+            );
+            assert!(matches!(result, Err(CoreError::Cancelled)), "{}", ext);
+            assert!(!path.exists(), "{}", ext);
+        }
+    }
+
+    #[test]
+    fn test_cancelled_export_keeps_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("people.ldif");
+        std::fs::write(&path, "hand-curated").unwrap();
+        let entries = many(2500);
+        let cancel = AtomicBool::new(false);
+        let result = export_entries_with_progress(
+            &entries,
+            &path,
+            &["*".to_string()],
+            &CsvOptions::default(),
+            &cancel,
+            |done, _| {
+                if done == 1000 {
+                    cancel.store(true, Ordering::Relaxed);
+                }
+            },
+        );
+        assert!(matches!(result, Err(CoreError::Cancelled)));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "hand-curated");
+        assert!(!staging_path(&path).unwrap().exists());
+
+        let report = export_with_report(
+            &entries,
+            &path,
+            &["uid".to_string()],
+            &CsvOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(report.written, 2500);
+        assert!(std::fs::read_to_string(&path).unwrap().contains("uid: "));
+        assert!(!staging_path(&path).unwrap().exists());
+    }
 }
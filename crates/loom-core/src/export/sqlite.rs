@@ -0,0 +1,243 @@
+use std::borrow::Borrow;
+use std::io::Write;
+use std::path::Path;
+
+use rusqlite::types::Value;
+use rusqlite::{params, Connection, DatabaseName};
+
+use crate::entry::{AttrValues, LdapEntry};
+use crate::error::CoreError;
+
+use super::AttrSelection;
+
+/// What to do about an existing database file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SqliteOptions {
+    /// Replace an existing file rather than fail.
+    pub overwrite: bool,
+}
+
+impl Default for SqliteOptions {
+    /// Replace the file, like the other formats.
+    fn default() -> Self {
+        Self { overwrite: true }
+    }
+}
+
+const SCHEMA: &str = "
+    CREATE TABLE entries (
+        id INTEGER PRIMARY KEY,
+        dn TEXT NOT NULL
+    );
+    CREATE TABLE attributes (
+        entry_id INTEGER NOT NULL REFERENCES entries(id),
+        name TEXT NOT NULL,
+        value NOT NULL
+    );
+    CREATE INDEX attributes_entry_id ON attributes(entry_id);
+";
+
+fn sql_err(e: rusqlite::Error) -> CoreError {
+    CoreError::ExportError(format!("SQLite error: {}", e))
+}
+
+/// Export entries to a SQLite database with an `entries(id, dn)` table
+/// and an `attributes(entry_id, name, value)` table, one row per value.
+/// Binary values are stored as blobs.
+pub fn export<I>(
+    entries: I,
+    path: &Path,
+    attributes: &[String],
+    options: &SqliteOptions,
+) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+{
+    if path.exists() {
+        if !options.overwrite {
+            return Err(CoreError::ExportError(format!(
+                "{} already exists",
+                path.display()
+            )));
+        }
+        std::fs::remove_file(path)
+            .map_err(|e| CoreError::ExportError(format!("Failed to replace file: {}", e)))?;
+    }
+
+    let mut conn = Connection::open(path).map_err(sql_err)?;
+    insert(&mut conn, entries, attributes)
+}
+
+/// Write entries as a SQLite database to any writer. The database is
+/// assembled in memory before it is written.
+pub fn write_to<I, W>(entries: I, mut writer: W, attributes: &[String]) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+    W: Write,
+{
+    let mut conn = Connection::open_in_memory().map_err(sql_err)?;
+    let count = insert(&mut conn, entries, attributes)?;
+    let data = conn.serialize(DatabaseName::Main).map_err(sql_err)?;
+    writer
+        .write_all(&data)
+        .and_then(|()| writer.flush())
+        .map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
+
+    Ok(count)
+}
+
+/// Create the tables and insert the entries in a single transaction.
+fn insert<I>(conn: &mut Connection, entries: I, attributes: &[String]) -> Result<usize, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+{
+    let selection = AttrSelection::parse(attributes);
+    let tx = conn.transaction().map_err(sql_err)?;
+    tx.execute_batch(SCHEMA).map_err(sql_err)?;
+
+    let mut count = 0;
+    {
+        let mut insert_entry = tx
+            .prepare("INSERT INTO entries (dn) VALUES (?1)")
+            .map_err(sql_err)?;
+        let mut insert_value = tx
+            .prepare("INSERT INTO attributes (entry_id, name, value) VALUES (?1, ?2, ?3)")
+            .map_err(sql_err)?;
+
+        for entry in entries {
+            let entry = entry.borrow();
+            insert_entry.execute([&entry.dn]).map_err(sql_err)?;
+            let id = tx.last_insert_rowid();
+            for (attr, values) in selection.pick(entry) {
+                let values: Vec<Value> = match values {
+                    AttrValues::Text(v) => v.iter().cloned().map(Value::Text).collect(),
+                    AttrValues::Binary(v) => v.iter().cloned().map(Value::Blob).collect(),
+                };
+                for value in values {
+                    insert_value
+                        .execute(params![id, attr, value])
+                        .map_err(sql_err)?;
+                }
+            }
+            count += 1;
+        }
+    }
+
+    tx.commit().map_err(sql_err)?;
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures::EntryBuilder;
+
+    fn entries() -> Vec<LdapEntry> {
+        vec![
+            EntryBuilder::new("cn=Alice,ou=Users,dc=example,dc=com")
+                .attr("cn", "Alice")
+                .attrs("mail", &["alice@example.com", "al@example.com"])
+                .binary("objectGUID", &[0xff, 0x00])
+                .build(),
+            EntryBuilder::new("cn=Bob,ou=Users,dc=example,dc=com")
+                .attr("cn", "Bob")
+                .attr("sn", "Jones")
+                .build(),
+        ]
+    }
+
+    #[test]
+    fn test_query_exported_database() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.db");
+        let count = export(
+            entries(),
+            &path,
+            &["*".to_string()],
+            &SqliteOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(count, 2);
+
+        let conn = Connection::open(&path).unwrap();
+        let mail: Vec<String> = conn
+            .prepare(
+                "SELECT a.value FROM attributes a JOIN entries e ON e.id = a.entry_id \
+                 WHERE e.dn = ?1 AND a.name = 'mail' ORDER BY a.rowid",
+            )
+            .unwrap()
+            .query_map(["cn=Alice,ou=Users,dc=example,dc=com"], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(mail, vec!["alice@example.com", "al@example.com"]);
+
+        let guid: Vec<u8> = conn
+            .query_row(
+                "SELECT value FROM attributes WHERE name = 'objectGUID'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(guid, vec![0xff, 0x00]);
+
+        let dn: String = conn
+            .query_row(
+                "SELECT e.dn FROM entries e JOIN attributes a ON e.id = a.entry_id \
+                 WHERE a.name = 'sn' AND a.value = 'Jones'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(dn, "cn=Bob,ou=Users,dc=example,dc=com");
+    }
+
+    #[test]
+    fn test_selected_attributes_only() {
+        let mut buf = Vec::new();
+        write_to(entries(), &mut buf, &["CN".to_string()]).unwrap();
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.sqlite");
+        std::fs::write(&path, &buf).unwrap();
+        let conn = Connection::open(&path).unwrap();
+        let names: Vec<String> = conn
+            .prepare("SELECT DISTINCT name FROM attributes")
+            .unwrap()
+            .query_map([], |row| row.get(0))
+            .unwrap()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(names, vec!["CN"]);
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 2);
+    }
+
+    #[test]
+    fn test_existing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.db");
+        std::fs::write(&path, "not a database").unwrap();
+        let star = vec!["*".to_string()];
+
+        let keep = SqliteOptions { overwrite: false };
+        let err = export(entries(), &path, &star, &keep).unwrap_err();
+        assert!(err.to_string().contains("already exists"), "{}", err);
+        assert_eq!(std::fs::read(&path).unwrap(), b"not a database");
+
+        // Replacing it twice leaves one export's rows
+        for _ in 0..2 {
+            export(entries(), &path, &star, &SqliteOptions::default()).unwrap();
+        }
+        let conn = Connection::open(&path).unwrap();
+        let rows: i64 = conn
+            .query_row("SELECT COUNT(*) FROM entries", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(rows, 2);
+    }
+}
@@ -0,0 +1,304 @@
+//! Exporting a branch of the tree as a paged search walks it, so a
+//! subtree of any size is written with only a few pages in memory. The
+//! search and the writer run side by side: the search hands each page to
+//! a [`PageSender`], and the writer pulls entries from the matching
+//! [`PageReceiver`] like any other iterator. A full channel holds the
+//! search back until the writer catches up.
+
+use std::sync::mpsc::{self, Receiver, SyncSender};
+
+use serde::{Deserialize, Serialize};
+
+use crate::dn;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::ops::DirectoryOps;
+use crate::Scope;
+
+/// Pages the search may run ahead of the writer.
+pub const PAGES_IN_FLIGHT: usize = 4;
+
+/// How much of a branch an export takes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubtreeScope {
+    /// The entries directly below the base.
+    Children,
+    /// Everything below the base, however deep.
+    #[default]
+    Subtree,
+}
+
+impl SubtreeScope {
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Children => "Children only",
+            Self::Subtree => "Whole subtree",
+        }
+    }
+
+    /// The other choice, for a selector.
+    pub fn toggled(self) -> Self {
+        match self {
+            Self::Children => Self::Subtree,
+            Self::Subtree => Self::Children,
+        }
+    }
+
+    /// Whether `dn` belongs in an export of `base` with this scope,
+    /// `base` itself only when `include_base`.
+    pub fn includes(self, base: &str, dn: &str, include_base: bool) -> bool {
+        let base = dn::normalize(base);
+        let entry = dn::normalize(dn);
+        if entry == base {
+            return include_base;
+        }
+        match self {
+            Self::Children => dn::parent_dn(&entry).is_some_and(|p| dn::normalize(p) == base),
+            Self::Subtree => dn::is_ancestor(&entry, &base),
+        }
+    }
+}
+
+/// The search side of a page channel.
+#[derive(Debug, Clone)]
+pub struct PageSender(SyncSender<Vec<LdapEntry>>);
+
+impl PageSender {
+    /// Hand a page to the writer, waiting while [`PAGES_IN_FLIGHT`] are
+    /// queued. `false` once the writer has stopped reading.
+    pub fn send(&self, page: Vec<LdapEntry>) -> bool {
+        page.is_empty() || self.0.send(page).is_ok()
+    }
+}
+
+/// The writer side of a page channel: the entries of each page in turn,
+/// ending when every [`PageSender`] is gone.
+#[derive(Debug)]
+pub struct PageReceiver {
+    pages: Receiver<Vec<LdapEntry>>,
+    page: std::vec::IntoIter<LdapEntry>,
+}
+
+impl Iterator for PageReceiver {
+    type Item = LdapEntry;
+
+    fn next(&mut self) -> Option<LdapEntry> {
+        loop {
+            if let Some(entry) = self.page.next() {
+                return Some(entry);
+            }
+            self.page = self.pages.recv().ok()?.into_iter();
+        }
+    }
+}
+
+/// A channel carrying search pages to a writer on another thread.
+pub fn page_channel() -> (PageSender, PageReceiver) {
+    let (tx, rx) = mpsc::sync_channel(PAGES_IN_FLIGHT);
+    (
+        PageSender(tx),
+        PageReceiver {
+            pages: rx,
+            page: Vec::new().into_iter(),
+        },
+    )
+}
+
+/// Search the branch at `base` page by page, sending the entries `scope`
+/// and `include_base` take to `pages`. Returns how many were sent; stops
+/// early, without an error, when the writer stops reading.
+pub async fn search_subtree_pages(
+    ops: &mut dyn DirectoryOps,
+    base: &str,
+    scope: SubtreeScope,
+    include_base: bool,
+    attributes: &[String],
+    pages: &PageSender,
+) -> Result<usize, CoreError> {
+    let attrs: Vec<&str> = attributes.iter().map(String::as_str).collect();
+    let mut sent = 0;
+    if include_base && scope == SubtreeScope::Children {
+        let base_entry = ops
+            .search(base, Scope::Base, "(objectClass=*)", &attrs)
+            .await?;
+        sent += base_entry.len();
+        if !pages.send(base_entry) {
+            return Ok(sent);
+        }
+    }
+    let search_scope = match scope {
+        SubtreeScope::Children => Scope::OneLevel,
+        SubtreeScope::Subtree => Scope::Subtree,
+    };
+    let mut open = true;
+    ops.search_pages(
+        base,
+        search_scope,
+        "(objectClass=*)",
+        &attrs,
+        &mut |mut page| {
+            if !open {
+                return;
+            }
+            page.retain(|e| scope.includes(base, &e.dn, include_base));
+            sent += page.len();
+            open = pages.send(page);
+        },
+    )
+    .await?;
+    Ok(sent)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicBool;
+
+    use super::*;
+    use crate::export::{export_stream, CsvOptions, ExportFormat};
+    use crate::testing::fixtures::{domain, org_unit, person};
+    use crate::testing::MockDirectory;
+
+    const BASE: &str = "dc=example,dc=com";
+    const PEOPLE: &str = "ou=People,dc=example,dc=com";
+
+    fn directory() -> MockDirectory {
+        let mut entries = vec![domain(BASE), org_unit(BASE, "People")];
+        let staff = org_unit(PEOPLE, "Staff");
+        entries.push(person(&staff.dn, "Carol", "White"));
+        entries.push(staff);
+        for i in 0..25 {
+            entries.push(person(PEOPLE, &format!("User{}", i), "Test"));
+        }
+        MockDirectory::with_entries(entries)
+    }
+
+    async fn collect(scope: SubtreeScope, include_base: bool) -> Vec<String> {
+        let mut dir = directory();
+        let (tx, rx) = page_channel();
+        let reader = std::thread::spawn(move || rx.map(|e| e.dn).collect::<Vec<_>>());
+        let sent = search_subtree_pages(
+            &mut dir,
+            PEOPLE,
+            scope,
+            include_base,
+            &["*".to_string()],
+            &tx,
+        )
+        .await
+        .unwrap();
+        drop(tx);
+        let dns = reader.join().unwrap();
+        assert_eq!(sent, dns.len());
+        dns
+    }
+
+    #[test]
+    fn test_scope_includes() {
+        let carol = "uid=carol.white,ou=Staff,ou=People,dc=example,dc=com";
+        let staff = "ou=Staff,ou=People,dc=example,dc=com";
+        assert!(SubtreeScope::Subtree.includes(PEOPLE, carol, false));
+        assert!(!SubtreeScope::Children.includes(PEOPLE, carol, false));
+        assert!(SubtreeScope::Children.includes("OU=people,DC=example,DC=com", staff, false));
+        assert!(!SubtreeScope::Subtree.includes(PEOPLE, PEOPLE, false));
+        assert!(SubtreeScope::Children.includes(PEOPLE, PEOPLE, true));
+        assert!(!SubtreeScope::Subtree.includes(PEOPLE, BASE, true));
+    }
+
+    #[tokio::test]
+    async fn test_search_subtree_pages() {
+        let subtree = collect(SubtreeScope::Subtree, true).await;
+        assert_eq!(subtree.len(), 28);
+        assert_eq!(subtree[0], PEOPLE);
+
+        let below = collect(SubtreeScope::Subtree, false).await;
+        assert_eq!(below.len(), 27);
+        assert!(!below.iter().any(|dn| dn == PEOPLE));
+
+        let children = collect(SubtreeScope::Children, false).await;
+        assert_eq!(children.len(), 26);
+        assert!(children.iter().all(|dn| dn::parent_dn(dn) == Some(PEOPLE)));
+
+        let with_base = collect(SubtreeScope::Children, true).await;
+        assert_eq!(with_base.len(), 27);
+        assert_eq!(with_base[0], PEOPLE);
+    }
+
+    #[tokio::test]
+    async fn test_pages_stream_into_a_file() {
+        let mut dir = directory().with_page_size(5);
+        let file = tempfile::tempdir().unwrap();
+        let path = file.path().join("people.ldif");
+        let (tx, rx) = page_channel();
+        let target = path.clone();
+        let writer = std::thread::spawn(move || {
+            let mut ticks = Vec::new();
+            let report = export_stream(
+                rx,
+                &target,
+                ExportFormat::Ldif,
+                &["*".to_string()],
+                &CsvOptions::default(),
+                &AtomicBool::new(false),
+                |done| ticks.push(done),
+            );
+            (report, ticks)
+        });
+        search_subtree_pages(
+            &mut dir,
+            PEOPLE,
+            SubtreeScope::Subtree,
+            true,
+            &["*".to_string()],
+            &tx,
+        )
+        .await
+        .unwrap();
+        drop(tx);
+        let (report, ticks) = writer.join().unwrap();
+        let report = report.unwrap();
+        assert_eq!(report.written, 28);
+        assert_eq!(ticks, vec![28]);
+        let ldif = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(ldif.matches("\ndn: ").count() + 1, 28);
+        assert!(dir.pages_served() > 5);
+    }
+
+    #[tokio::test]
+    async fn test_cancelled_stream_removes_partial_file() {
+        let mut dir = directory().with_page_size(5);
+        let file = tempfile::tempdir().unwrap();
+        let path = file.path().join("people.csv");
+        let (tx, rx) = page_channel();
+        let target = path.clone();
+        let cancel = AtomicBool::new(true);
+        let writer = std::thread::spawn(move || {
+            export_stream(
+                rx,
+                &target,
+                ExportFormat::Csv,
+                &["cn".to_string()],
+                &CsvOptions::default(),
+                &cancel,
+                |_| {},
+            )
+        });
+        // The writer stops reading, so the search ends early without error
+        search_subtree_pages(
+            &mut dir,
+            PEOPLE,
+            SubtreeScope::Subtree,
+            true,
+            &["cn".to_string()],
+            &tx,
+        )
+        .await
+        .unwrap();
+        drop(tx);
+        assert!(matches!(writer.join().unwrap(), Err(CoreError::Cancelled)));
+        assert!(!path.exists());
+        let mut partial = path.into_os_string();
+        partial.push(".partial");
+        assert!(!std::path::Path::new(&partial).exists());
+    }
+}
@@ -0,0 +1,224 @@
+//! Per-export rendering of timestamp values in a chosen zone and pattern.
+//!
+//! Only attributes recognized as timestamps (GeneralizedTime or AD FILETIME)
+//! are rewritten; every other value passes through untouched.
+
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::entry::LdapEntry;
+use crate::schema::SchemaCache;
+use crate::time::{
+    is_filetime_never, parse_filetime, parse_generalized_time, timestamp_kind, TimestampKind,
+};
+use crate::timezone::Zone;
+
+/// A named, persisted set of value-formatting options for exports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportTemplate {
+    /// IANA name, `UTC`, `local` or `+HH:MM`.
+    #[serde(default = "default_timezone")]
+    pub timezone: String,
+    /// strftime-style pattern, e.g. `%d.%m.%Y %H:%M`.
+    #[serde(default = "default_timestamp_format")]
+    pub timestamp_format: String,
+}
+
+fn default_timezone() -> String {
+    "UTC".to_string()
+}
+fn default_timestamp_format() -> String {
+    "%Y-%m-%dT%H:%M:%SZ".to_string()
+}
+
+impl Default for ExportTemplate {
+    /// ISO 8601 in UTC.
+    fn default() -> Self {
+        Self {
+            timezone: default_timezone(),
+            timestamp_format: default_timestamp_format(),
+        }
+    }
+}
+
+impl ExportTemplate {
+    /// Validate the template and build its formatter.
+    pub fn formatter(&self) -> Result<TimestampFormat, String> {
+        TimestampFormat::new(&self.timezone, &self.timestamp_format)
+    }
+}
+
+/// A validated zone + pattern pair.
+#[derive(Debug, Clone)]
+pub struct TimestampFormat {
+    zone: Zone,
+    pattern: String,
+}
+
+impl TimestampFormat {
+    pub fn new(timezone: &str, pattern: &str) -> Result<Self, String> {
+        if pattern.trim().is_empty() {
+            return Err("Timestamp format is empty".to_string());
+        }
+        if StrftimeItems::new(pattern).any(|item| matches!(item, Item::Error)) {
+            return Err(format!("Invalid timestamp format '{}'", pattern));
+        }
+        Ok(Self {
+            zone: Zone::parse(timezone)?,
+            pattern: pattern.to_string(),
+        })
+    }
+
+    pub fn format(&self, utc: &DateTime<Utc>) -> String {
+        self.zone.to_local(utc).format(&self.pattern).to_string()
+    }
+}
+
+/// What happened to timestamp values during formatting.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct FormatReport {
+    pub formatted: usize,
+    /// Timestamp values that did not parse and were kept as-is.
+    pub unparseable: usize,
+}
+
+impl FormatReport {
+    /// Suffix for the export status message, e.g.
+    /// `"; 120 timestamps formatted, 2 unparseable kept as-is"`.
+    pub fn summary(&self) -> String {
+        let mut out = format!("; {} timestamps formatted", self.formatted);
+        if self.unparseable > 0 {
+            out.push_str(&format!(", {} unparseable kept as-is", self.unparseable));
+        }
+        out
+    }
+}
+
+/// Rewrite timestamp values of `entries` with `format`.
+/// FILETIME "never" sentinels are left alone and not counted as failures.
+pub fn format_timestamps(
+    entries: &[LdapEntry],
+    format: &TimestampFormat,
+    schema: Option<&SchemaCache>,
+) -> (Vec<LdapEntry>, FormatReport) {
+    let mut report = FormatReport::default();
+    let formatted = entries
+        .iter()
+        .map(|entry| {
+            let mut entry = entry.clone();
+            for (attr, values) in entry.attributes.iter_mut() {
+                let Some(kind) = timestamp_kind(attr, schema) else {
+                    continue;
+                };
+                for value in values.iter_mut() {
+                    let parsed = match kind {
+                        TimestampKind::GeneralizedTime => parse_generalized_time(value),
+                        TimestampKind::FileTime if is_filetime_never(value) => continue,
+                        TimestampKind::FileTime => parse_filetime(value),
+                    };
+                    match parsed {
+                        Some(utc) => {
+                            *value = format.format(&utc);
+                            report.formatted += 1;
+                        }
+                        None => report.unparseable += 1,
+                    }
+                }
+            }
+            entry
+        })
+        .collect();
+    (formatted, report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn entry(attrs: &[(&str, &str)]) -> LdapEntry {
+        let mut map: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (k, v) in attrs {
+            map.entry(k.to_string()).or_default().push(v.to_string());
+        }
+        LdapEntry::new("cn=test,dc=example,dc=com".to_string(), map)
+    }
+
+    fn berlin() -> TimestampFormat {
+        TimestampFormat::new("Europe/Berlin", "%d.%m.%Y %H:%M").unwrap()
+    }
+
+    #[test]
+    fn test_format_across_dst_boundary() {
+        let entries = vec![entry(&[
+            ("modifyTimestamp", "20240331005900Z"),
+            ("modifyTimestamp", "20240331010000Z"),
+            ("whenChanged", "20241027005900Z"),
+            ("whenCreated", "20241027010000.0Z"),
+        ])];
+        let (out, report) = format_timestamps(&entries, &berlin(), None);
+        let attrs = &out[0].attributes;
+        // 00:59 UTC is still CET (+1); 01:00 UTC is CEST (+2)
+        assert_eq!(
+            attrs["modifyTimestamp"],
+            vec!["31.03.2024 01:59", "31.03.2024 03:00"]
+        );
+        // In autumn 00:59 UTC is CEST (+2); 01:00 UTC is CET again (+1)
+        assert_eq!(attrs["whenChanged"], vec!["27.10.2024 02:59"]);
+        assert_eq!(attrs["whenCreated"], vec!["27.10.2024 02:00"]);
+        assert_eq!(report.formatted, 4);
+        assert_eq!(report.unparseable, 0);
+    }
+
+    #[test]
+    fn test_filetime_and_iso_utc() {
+        let format = ExportTemplate::default().formatter().unwrap();
+        let entries = vec![entry(&[
+            ("pwdLastSet", "133511778000000000"),
+            ("accountExpires", "9223372036854775807"),
+        ])];
+        let (out, report) = format_timestamps(&entries, &format, None);
+        assert_eq!(
+            out[0].attributes["pwdLastSet"],
+            vec!["2024-01-31T12:30:00Z"]
+        );
+        assert_eq!(
+            out[0].attributes["accountExpires"],
+            vec!["9223372036854775807"]
+        );
+        assert_eq!(report.formatted, 1);
+        assert_eq!(report.unparseable, 0);
+    }
+
+    #[test]
+    fn test_non_timestamps_pass_through() {
+        let entries = vec![entry(&[
+            ("uidNumber", "10001"),
+            ("employeeNumber", "20240131123000"),
+            ("cn", "20240131123000Z"),
+        ])];
+        let (out, report) = format_timestamps(&entries, &berlin(), None);
+        assert_eq!(out[0].attributes, entries[0].attributes);
+        assert_eq!(report, FormatReport::default());
+    }
+
+    #[test]
+    fn test_unparseable_kept_raw_and_counted() {
+        let entries = vec![entry(&[
+            ("modifyTimestamp", "not-a-time"),
+            ("pwdLastSet", "soon"),
+        ])];
+        let (out, report) = format_timestamps(&entries, &berlin(), None);
+        assert_eq!(out[0].attributes["modifyTimestamp"], vec!["not-a-time"]);
+        assert_eq!(report.unparseable, 2);
+        assert!(report.summary().contains("2 unparseable"));
+    }
+
+    #[test]
+    fn test_invalid_templates_rejected() {
+        assert!(TimestampFormat::new("UTC", "%Q").is_err());
+        assert!(TimestampFormat::new("UTC", "").is_err());
+        assert!(TimestampFormat::new("Mars/Olympus", "%Y").is_err());
+    }
+}
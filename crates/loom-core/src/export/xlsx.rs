@@ -1,85 +1,435 @@
-use std::collections::BTreeSet;
+use std::borrow::Borrow;
+use std::io::Write;
 use std::path::Path;
 
-use rust_xlsxwriter::{Format, Workbook};
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
 
 use crate::entry::LdapEntry;
 use crate::error::CoreError;
 
-use super::requested_attrs;
+use super::csv::{write_rows, CsvOptions};
 
-/// Export entries to Excel (.xlsx) format.
-pub fn export(
-    entries: &[LdapEntry],
+/// Most rows a worksheet holds, header included.
+const MAX_ROWS: u32 = 1_048_576;
+/// Most characters a cell holds; longer values are truncated.
+const MAX_CELL_CHARS: usize = 32_767;
+/// Widest a column is sized to, in characters.
+const MAX_COLUMN_WIDTH: usize = 80;
+
+/// What an Excel export wrote.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct XlsxReport {
+    pub written: usize,
+    /// Worksheets the rows were split across.
+    pub sheets: usize,
+    /// Values cut to Excel's cell limit of 32,767 characters.
+    pub truncated: usize,
+}
+
+/// Export entries to Excel (.xlsx) format, laid out like the CSV export.
+/// Nothing is written when there are no entries.
+///
+/// The header row is bold and frozen, and columns are sized to their
+/// content. Rows beyond Excel's limit of 1,048,576 per sheet continue on
+/// further sheets, each with the header.
+pub fn export<I>(
+    entries: I,
     path: &Path,
     attributes: &[String],
-) -> Result<usize, CoreError> {
-    if entries.is_empty() {
-        return Ok(0);
+    options: &CsvOptions,
+) -> Result<XlsxReport, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+{
+    let Some((mut workbook, report)) = build(entries, attributes, options, MAX_ROWS)? else {
+        return Ok(XlsxReport::default());
+    };
+    workbook
+        .save(path)
+        .map_err(|e| CoreError::ExportError(format!("Excel save failed: {}", e)))?;
+
+    Ok(report)
+}
+
+/// Write entries as an Excel workbook to any writer. The workbook is
+/// assembled in memory before it is written.
+pub fn write_to<I, W>(
+    entries: I,
+    mut writer: W,
+    attributes: &[String],
+    options: &CsvOptions,
+) -> Result<XlsxReport, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+    W: Write,
+{
+    let Some((mut workbook, report)) = build(entries, attributes, options, MAX_ROWS)? else {
+        return Ok(XlsxReport::default());
+    };
+    let buffer = workbook
+        .save_to_buffer()
+        .map_err(|e| CoreError::ExportError(format!("Excel save failed: {}", e)))?;
+    writer
+        .write_all(&buffer)
+        .and_then(|()| writer.flush())
+        .map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
+
+    Ok(report)
+}
+
+fn excel_err(e: XlsxError) -> CoreError {
+    CoreError::ExportError(format!("Excel error: {}", e))
+}
+
+/// The name of the `n`th worksheet, counting from 1.
+fn sheet_name(n: usize) -> String {
+    match n {
+        1 => "LDAP Entries".to_string(),
+        n => format!("LDAP Entries {}", n),
     }
+}
+
+/// Write a header and rows of text as they are to an Excel workbook,
+/// such as a table the way a view shows it, with the same header row,
+/// column sizing and sheet splitting as an export of entries.
+pub fn export_table(
+    header: &[String],
+    rows: &[Vec<String>],
+    path: &Path,
+) -> Result<XlsxReport, CoreError> {
+    let (mut workbook, report) = build_records(MAX_ROWS, |sink| {
+        sink(header)?;
+        for row in rows {
+            sink(row)?;
+        }
+        Ok(rows.len())
+    })?;
+    workbook
+        .save(path)
+        .map_err(|e| CoreError::ExportError(format!("Excel save failed: {}", e)))?;
+
+    Ok(report)
+}
 
+/// Lay entries out in a workbook, at most `max_rows` rows to a sheet, or
+/// `None` when there are none.
+fn build<I>(
+    entries: I,
+    attributes: &[String],
+    options: &CsvOptions,
+    max_rows: u32,
+) -> Result<Option<(Workbook, XlsxReport)>, CoreError>
+where
+    I: IntoIterator,
+    I::Item: Borrow<LdapEntry>,
+{
+    let mut entries = entries.into_iter().peekable();
+    if entries.peek().is_none() {
+        return Ok(None);
+    }
+    build_records(max_rows, |sink| {
+        write_rows(entries, attributes, options, sink)
+    })
+    .map(Some)
+}
+
+/// Lay the records `feed` hands to its sink out in a workbook, the first
+/// being the header, at most `max_rows` rows to a sheet. `feed` returns
+/// how many entries the records stand for.
+///
+/// Rows are flushed to a temporary file as they are written rather than
+/// held in memory until the workbook is saved.
+fn build_records<F>(max_rows: u32, feed: F) -> Result<(Workbook, XlsxReport), CoreError>
+where
+    F: FnOnce(&mut dyn FnMut(&[String]) -> Result<(), CoreError>) -> Result<usize, CoreError>,
+{
     let mut workbook = Workbook::new();
-    let worksheet = workbook.add_worksheet();
-
-    worksheet
-        .set_name("LDAP Entries")
-        .map_err(|e| CoreError::ExportError(format!("Excel error: {}", e)))?;
-
-    let attr_names: Vec<String> = if let Some(attrs) = requested_attrs(attributes) {
-        attrs.to_vec()
-    } else {
-        let mut all_attrs: BTreeSet<String> = BTreeSet::new();
-        for entry in entries {
-            for key in entry.attributes.keys() {
-                all_attrs.insert(key.clone());
-            }
+    let mut header: Vec<String> = Vec::new();
+    // Widest value of each column on the current sheet
+    let mut widths: Vec<usize> = Vec::new();
+    let mut report = XlsxReport::default();
+    let mut row: u32 = 0;
+
+    report.written = feed(&mut |record| {
+        // The first record is the header
+        let is_header = header.is_empty();
+        if is_header {
+            header = record.to_vec();
+        } else if row == max_rows {
+            finish_sheet(&mut workbook, report.sheets, &widths)?;
+        } else {
+            let sheet = workbook
+                .worksheet_from_index(report.sheets - 1)
+                .map_err(excel_err)?;
+            write_row(sheet, row, record, &mut widths, &mut report.truncated)?;
+            row += 1;
+            return Ok(());
         }
-        all_attrs.into_iter().collect()
-    };
 
-    let header_format = Format::new().set_bold();
+        report.sheets += 1;
+        widths = start_sheet(&mut workbook, report.sheets, &header)?;
+        row = 1;
+        if !is_header {
+            // The row that did not fit on the last sheet
+            let sheet = workbook
+                .worksheet_from_index(report.sheets - 1)
+                .map_err(excel_err)?;
+            write_row(sheet, row, record, &mut widths, &mut report.truncated)?;
+            row += 1;
+        }
+        Ok(())
+    })?;
+    finish_sheet(&mut workbook, report.sheets, &widths)?;
 
-    // Header row
-    worksheet
-        .write_string_with_format(0, 0, "dn", &header_format)
-        .map_err(|e| CoreError::ExportError(format!("Excel write error: {}", e)))?;
+    Ok((workbook, report))
+}
 
-    for (col, attr) in attr_names.iter().enumerate() {
-        worksheet
-            .write_string_with_format(0, (col + 1) as u16, attr, &header_format)
+/// Add the `n`th sheet with a bold, frozen header row, returning the
+/// widths of the header's columns.
+fn start_sheet(
+    workbook: &mut Workbook,
+    n: usize,
+    header: &[String],
+) -> Result<Vec<usize>, CoreError> {
+    let header_format = Format::new().set_bold();
+    let sheet = workbook.add_worksheet_with_constant_memory();
+    sheet.set_name(sheet_name(n)).map_err(excel_err)?;
+    sheet.set_freeze_panes(1, 0).map_err(excel_err)?;
+    for (col, name) in header.iter().enumerate() {
+        sheet
+            .write_string_with_format(0, col as u16, name, &header_format)
             .map_err(|e| CoreError::ExportError(format!("Excel write error: {}", e)))?;
     }
+    Ok(header.iter().map(|name| name.chars().count()).collect())
+}
 
-    // Data rows
-    for (row_idx, entry) in entries.iter().enumerate() {
-        let row = (row_idx + 1) as u32;
-
-        worksheet
-            .write_string(row, 0, &entry.dn)
+/// Write one row, widening `widths` to fit and counting values cut to
+/// the cell limit in `truncated`.
+fn write_row(
+    sheet: &mut Worksheet,
+    row: u32,
+    record: &[String],
+    widths: &mut [usize],
+    truncated: &mut usize,
+) -> Result<(), CoreError> {
+    for (col, value) in record.iter().enumerate() {
+        if value.is_empty() {
+            continue;
+        }
+        let mut chars = value.chars().count();
+        let value = if chars > MAX_CELL_CHARS {
+            *truncated += 1;
+            chars = MAX_CELL_CHARS;
+            value.chars().take(MAX_CELL_CHARS).collect()
+        } else {
+            value.clone()
+        };
+        widths[col] = widths[col].max(chars);
+        sheet
+            .write_string(row, col as u16, value)
             .map_err(|e| CoreError::ExportError(format!("Excel write error: {}", e)))?;
+    }
+    Ok(())
+}
 
-        for (col_idx, attr) in attr_names.iter().enumerate() {
-            let col = (col_idx + 1) as u16;
-            let value = entry
-                .attributes
-                .get(attr)
-                .map(|vals| vals.join("; "))
-                .unwrap_or_default();
-            if !value.is_empty() {
-                worksheet
-                    .write_string(row, col, &value)
-                    .map_err(|e| CoreError::ExportError(format!("Excel write error: {}", e)))?;
-            }
+/// Size the columns of the `n`th sheet to their widest values.
+fn finish_sheet(workbook: &mut Workbook, n: usize, widths: &[usize]) -> Result<(), CoreError> {
+    let sheet = workbook.worksheet_from_index(n - 1).map_err(excel_err)?;
+    for (col, width) in widths.iter().enumerate() {
+        sheet
+            .set_column_width(col as u16, (*width).min(MAX_COLUMN_WIDTH) as f64)
+            .map_err(excel_err)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::csv::Explode;
+    use crate::testing::fixtures::EntryBuilder;
+    use calamine::{open_workbook, Reader, Xlsx};
+
+    #[test]
+    fn test_exploded_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("groups.xlsx");
+        let entries = vec![EntryBuilder::new("uid=ada,dc=example,dc=com")
+            .attr("uid", "ada")
+            .attrs("memberOf", &["cn=a", "cn=b", "cn=c"])
+            .build()];
+        let options = CsvOptions {
+            explode: Explode::Attribute("memberOf".to_string()),
+            ..CsvOptions::default()
+        };
+        export(&entries, &path, &["*".to_string()], &options).unwrap();
+
+        let mut workbook: Xlsx<_> = open_workbook(&path).unwrap();
+        let range = workbook.worksheet_range("LDAP Entries").unwrap();
+        let rows: Vec<Vec<String>> = range
+            .rows()
+            .map(|r| r.iter().map(|c| c.to_string()).collect())
+            .collect();
+        assert_eq!(rows[0], vec!["dn", "memberOf", "uid"]);
+        assert_eq!(rows.len(), 4);
+        assert_eq!(rows[3], vec!["uid=ada,dc=example,dc=com", "cn=c", "ada"]);
+    }
+
+    #[test]
+    fn test_streamed_rows() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("people.xlsx");
+        let entries = (0..500).map(|i| {
+            EntryBuilder::new(format!("uid=u{},dc=example,dc=com", i))
+                .attr("uid", format!("u{}", i))
+                .build()
+        });
+        let attrs = vec!["uid".to_string(), "mail".to_string()];
+        let report = export(entries, &path, &attrs, &CsvOptions::default()).unwrap();
+        assert_eq!(report.written, 500);
+        assert_eq!(report.sheets, 1);
+
+        let mut workbook: Xlsx<_> = open_workbook(&path).unwrap();
+        let range = workbook.worksheet_range("LDAP Entries").unwrap();
+        assert_eq!(range.rows().count(), 501);
+        let last: Vec<String> = range
+            .rows()
+            .next_back()
+            .unwrap()
+            .iter()
+            .map(|c| c.to_string())
+            .collect();
+        assert_eq!(last, vec!["uid=u499,dc=example,dc=com", "u499", ""]);
+
+        // Nothing to write: no file
+        let empty = dir.path().join("empty.xlsx");
+        assert_eq!(
+            export(
+                Vec::<LdapEntry>::new(),
+                &empty,
+                &attrs,
+                &CsvOptions::default()
+            )
+            .unwrap(),
+            XlsxReport::default()
+        );
+        assert!(!empty.exists());
+    }
+
+    #[test]
+    fn test_write_to_buffer() {
+        let entries = vec![EntryBuilder::new("uid=ada,dc=example,dc=com")
+            .attr("uid", "ada")
+            .build()];
+        let mut buf = Vec::new();
+        let report = write_to(
+            &entries,
+            &mut buf,
+            &["*".to_string()],
+            &CsvOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(report.written, 1);
+
+        let mut workbook = Xlsx::new(std::io::Cursor::new(buf)).unwrap();
+        let range = workbook.worksheet_range("LDAP Entries").unwrap();
+        assert_eq!(range.rows().count(), 2);
+    }
+
+    /// The XML of a part of an xlsx file.
+    fn part(buf: &[u8], name: &str) -> String {
+        let mut zip = zip::ZipArchive::new(std::io::Cursor::new(buf)).unwrap();
+        std::io::read_to_string(zip.by_name(name).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_header_frozen_and_columns_sized() {
+        let entries = vec![EntryBuilder::new("uid=ada,dc=example,dc=com")
+            .attr("uid", "ada")
+            .attr("description", "x".repeat(200))
+            .build()];
+        let mut buf = Vec::new();
+        let attrs = vec!["uid".to_string(), "description".to_string()];
+        write_to(&entries, &mut buf, &attrs, &CsvOptions::default()).unwrap();
+
+        let sheet = part(&buf, "xl/worksheets/sheet1.xml");
+        assert!(sheet.contains(r#"ySplit="1""#), "{}", sheet);
+        assert!(sheet.contains(r#"state="frozen""#));
+        // dn is 25 characters, uid fits its header, description is capped
+        for (col, width) in [(1, 25), (2, 3), (3, 80)] {
+            let prefix = format!(r#"<col min="{col}" max="{col}" width="{width}."#);
+            assert!(sheet.contains(&prefix), "{} in {}", prefix, sheet);
         }
+
+        // The header cells share the one bold style
+        let styles = part(&buf, "xl/styles.xml");
+        assert!(styles.contains("<b/>"), "{}", styles);
+        assert!(sheet.contains(r#"<c r="A1" s="1""#));
+        assert!(!sheet.contains(r#"<c r="A2" s="1""#));
     }
 
-    worksheet
-        .set_column_width(0, 50)
-        .map_err(|e| CoreError::ExportError(format!("Excel error: {}", e)))?;
+    #[test]
+    fn test_rows_split_across_sheets() {
+        let entries = (0..7).map(|i| {
+            EntryBuilder::new(format!("uid=u{},dc=example,dc=com", i))
+                .attr("uid", format!("u{}", i))
+                .build()
+        });
+        let attrs = vec!["uid".to_string()];
+        // A header and three rows to a sheet
+        let (mut workbook, report) = build(entries, &attrs, &CsvOptions::default(), 4)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            report,
+            XlsxReport {
+                written: 7,
+                sheets: 3,
+                truncated: 0
+            }
+        );
 
-    workbook
-        .save(path)
-        .map_err(|e| CoreError::ExportError(format!("Excel save failed: {}", e)))?;
+        let buf = workbook.save_to_buffer().unwrap();
+        let mut workbook = Xlsx::new(std::io::Cursor::new(buf)).unwrap();
+        assert_eq!(
+            workbook.sheet_names(),
+            vec!["LDAP Entries", "LDAP Entries 2", "LDAP Entries 3"]
+        );
+        let uids: Vec<Vec<String>> = workbook
+            .worksheets()
+            .into_iter()
+            .map(|(_, range)| range.rows().map(|r| r[1].to_string()).collect())
+            .collect();
+        assert_eq!(
+            uids,
+            vec![
+                vec!["uid", "u0", "u1", "u2"],
+                vec!["uid", "u3", "u4", "u5"],
+                vec!["uid", "u6"],
+            ]
+        );
+    }
 
-    Ok(entries.len())
+    #[test]
+    fn test_long_values_truncated() {
+        let entries = vec![EntryBuilder::new("uid=ada,dc=example,dc=com")
+            .attr("description", "\u{e9}".repeat(MAX_CELL_CHARS + 10))
+            .build()];
+        let mut buf = Vec::new();
+        let report = write_to(
+            &entries,
+            &mut buf,
+            &["description".to_string()],
+            &CsvOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(report.truncated, 1);
+
+        let mut workbook = Xlsx::new(std::io::Cursor::new(buf)).unwrap();
+        let range = workbook.worksheet_range("LDAP Entries").unwrap();
+        let cell = range.get_value((1, 1)).unwrap().to_string();
+        assert_eq!(cell.chars().count(), MAX_CELL_CHARS);
+    }
 }
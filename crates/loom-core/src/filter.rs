@@ -1,3 +1,7 @@
+use crate::dn::{Dn, Rdn};
+use crate::entry::LdapEntry;
+use crate::util;
+
 /// The kind of filter context the cursor is in.
 #[derive(Debug, Clone, PartialEq)]
 pub enum FilterContext {
@@ -95,6 +99,22 @@ pub fn detect_attribute_context(input: &str) -> Option<String> {
     Some(partial.to_string())
 }
 
+/// Escape special characters for use as an LDAP filter value (RFC 4515).
+pub fn escape_filter_value(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '*' => out.push_str("\\2a"),
+            '(' => out.push_str("\\28"),
+            ')' => out.push_str("\\29"),
+            '\\' => out.push_str("\\5c"),
+            '\0' => out.push_str("\\00"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
 /// Validate that a string is a valid LDAP search filter per RFC 4515.
 ///
 /// ```text
@@ -108,138 +128,230 @@ pub fn detect_attribute_context(input: &str) -> Option<String> {
 /// filtertype = "=" / "~=" / ">=" / "<="
 /// ```
 pub fn validate_filter(filter: &str) -> Result<(), String> {
-    let filter = filter.trim();
-    if filter.is_empty() {
-        return Err("Filter cannot be empty".to_string());
+    validate(filter).map_err(|e| e.to_string())
+}
+
+/// A syntax error in a search filter, and where it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterError {
+    /// Byte offset into the filter as given, leading whitespace included.
+    pub position: usize,
+    pub message: String,
+}
+
+impl FilterError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
     }
+}
 
-    let bytes = filter.as_bytes();
-    let end = parse_filter(bytes, 0)?;
-    if end != bytes.len() {
-        Err(format!(
-            "Unexpected characters after filter at position {}",
-            end + 1
-        ))
-    } else {
-        Ok(())
+impl std::fmt::Display for FilterError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
     }
 }
 
+impl std::error::Error for FilterError {}
+
+/// Validate a search filter like [`validate_filter`], telling where the
+/// first error is so an editor can point at it.
+pub fn validate(filter: &str) -> Result<(), FilterError> {
+    Filter::parse(filter).map(|_| ())
+}
+
+const OPERATOR_EXPECTED: &str = "Expected comparison operator (=, ~=, >=, <=) after attribute name";
+
 /// Parse a single filter: "(" filtercomp ")"
-/// Returns the position after the closing ')'.
-fn parse_filter(input: &[u8], pos: usize) -> Result<usize, String> {
-    if pos >= input.len() {
-        return Err(format!("Expected '(' at position {}", pos + 1));
-    }
-    if input[pos] != b'(' {
-        return Err(format!("Expected '(' at position {}", pos + 1));
+/// Returns the filter and the position after the closing ')'.
+fn parse_filter(input: &[u8], pos: usize) -> Result<(Filter, usize), FilterError> {
+    if pos >= input.len() || input[pos] != b'(' {
+        return Err(FilterError::new(
+            pos,
+            format!("Expected '(' at position {}", pos + 1),
+        ));
     }
 
     let inner = pos + 1;
     if inner >= input.len() {
-        return Err(format!(
-            "Unexpected end of filter after '(' at position {}",
-            pos + 1
+        return Err(FilterError::new(
+            inner,
+            format!("Unexpected end of filter after '(' at position {}", pos + 1),
         ));
     }
 
-    let end = match input[inner] {
-        b'&' => parse_filter_list(input, inner + 1, '&')?,
-        b'|' => parse_filter_list(input, inner + 1, '|')?,
-        b'!' => parse_filter(input, inner + 1)?,
+    let (filter, end) = match input[inner] {
+        b'&' => {
+            let (filters, end) = parse_filter_list(input, inner + 1, '&')?;
+            (Filter::And(filters), end)
+        }
+        b'|' => {
+            let (filters, end) = parse_filter_list(input, inner + 1, '|')?;
+            (Filter::Or(filters), end)
+        }
+        b'!' => {
+            let (filter, end) = parse_filter(input, inner + 1)?;
+            (Filter::Not(Box::new(filter)), end)
+        }
         _ => parse_item(input, inner)?,
     };
 
-    if end >= input.len() {
-        return Err(format!("Expected ')' at position {}", end + 1));
-    }
-    if input[end] != b')' {
-        return Err(format!("Expected ')' at position {}", end + 1));
+    if end >= input.len() || input[end] != b')' {
+        return Err(FilterError::new(
+            end,
+            format!("Expected ')' at position {}", end + 1),
+        ));
     }
-    Ok(end + 1)
+    Ok((filter, end + 1))
 }
 
 /// Parse a filterlist: 1*filter
 /// The operator char is only used for error messages.
-fn parse_filter_list(input: &[u8], pos: usize, op: char) -> Result<usize, String> {
-    if pos >= input.len() || input[pos] != b'(' {
-        return Err(format!(
-            "Empty filter list in '{}' operator at position {}",
-            op,
-            pos + 1
-        ));
-    }
+fn parse_filter_list(
+    input: &[u8],
+    pos: usize,
+    op: char,
+) -> Result<(Vec<Filter>, usize), FilterError> {
+    let mut filters = Vec::new();
     let mut cur = pos;
-    let mut count = 0;
     while cur < input.len() && input[cur] == b'(' {
-        cur = parse_filter(input, cur)?;
-        count += 1;
-    }
-    if count == 0 {
-        return Err(format!(
-            "Empty filter list in '{}' operator at position {}",
-            op,
-            pos + 1
+        let (filter, end) = parse_filter(input, cur)?;
+        filters.push(filter);
+        cur = end;
+    }
+    if filters.is_empty() {
+        return Err(FilterError::new(
+            pos,
+            format!(
+                "Empty filter list in '{}' operator at position {}",
+                op,
+                pos + 1
+            ),
         ));
     }
-    Ok(cur)
+    Ok((filters, cur))
 }
 
-/// Parse a simple filter item: attr filtertype value
-/// Returns position after the value (just before the closing ')').
-fn parse_item(input: &[u8], pos: usize) -> Result<usize, String> {
-    // Parse attribute name: alphanumeric, hyphen, period, semicolon (for options like ;binary)
-    let attr_start = pos;
-    let mut cur = pos;
-    while cur < input.len()
-        && (input[cur].is_ascii_alphanumeric()
-            || input[cur] == b'-'
-            || input[cur] == b'.'
-            || input[cur] == b';')
-    {
-        cur += 1;
-    }
+/// Length of the run of attribute name characters at `pos`: alphanumeric,
+/// hyphen, period and semicolon (for options like ;binary).
+fn name_len(input: &[u8], pos: usize) -> usize {
+    input[pos..]
+        .iter()
+        .take_while(|&&c| c.is_ascii_alphanumeric() || c == b'-' || c == b'.' || c == b';')
+        .count()
+}
 
-    if cur == attr_start {
-        return Err(format!(
-            "Expected attribute name after '(' at position {}",
-            pos + 1
-        ));
-    }
+/// Parse a simple filter item: attr filtertype value, or an extensible
+/// match. Returns the filter and the position after the value (just
+/// before the closing ')').
+fn parse_item(input: &[u8], pos: usize) -> Result<(Filter, usize), FilterError> {
+    let mut cur = pos + name_len(input, pos);
+    let attr = String::from_utf8_lossy(&input[pos..cur]).into_owned();
 
-    if cur >= input.len() {
-        return Err(
-            "Expected comparison operator (=, ~=, >=, <=) after attribute name".to_string(),
-        );
+    if cur < input.len() && input[cur] == b':' {
+        return parse_extensible(input, pos, cur, attr);
+    }
+    if attr.is_empty() {
+        return Err(FilterError::new(
+            pos,
+            format!("Expected attribute name after '(' at position {}", pos + 1),
+        ));
     }
 
     // Parse filtertype: =, ~=, >=, <=
-    if cur + 1 < input.len() && input[cur + 1] == b'=' {
-        match input[cur] {
-            b'~' | b'>' | b'<' => {
-                cur += 2;
-            }
-            b'=' => {
-                // Just '=' — single char operator
-                cur += 1;
-            }
-            _ => {
-                return Err(
-                    "Expected comparison operator (=, ~=, >=, <=) after attribute name".to_string(),
-                );
+    let kind = match input.get(cur) {
+        Some(b'=') => b'=',
+        Some(&c @ (b'~' | b'>' | b'<')) if input.get(cur + 1) == Some(&b'=') => c,
+        _ => return Err(FilterError::new(cur, OPERATOR_EXPECTED)),
+    };
+    cur += if kind == b'=' { 1 } else { 2 };
+
+    let (raw, end) = scan_value(input, cur);
+    let filter = match kind {
+        b'~' => Filter::ApproxMatch(attr, unescape(raw)),
+        b'>' => Filter::GreaterOrEqual(attr, unescape(raw)),
+        b'<' => Filter::LessOrEqual(attr, unescape(raw)),
+        _ if raw == b"*" => Filter::Presence(attr),
+        _ => {
+            let parts = split_unescaped_stars(raw);
+            if parts.len() == 1 {
+                Filter::Equality(attr, unescape(raw))
+            } else {
+                let non_empty = |p: &[u8]| (!p.is_empty()).then(|| unescape(p));
+                Filter::Substring {
+                    attr,
+                    initial: non_empty(parts[0]),
+                    any: parts[1..parts.len() - 1]
+                        .iter()
+                        .filter_map(|p| non_empty(p))
+                        .collect(),
+                    last: non_empty(parts[parts.len() - 1]),
+                }
             }
         }
-    } else if cur < input.len() && input[cur] == b'=' {
-        cur += 1;
-    } else {
-        return Err(
-            "Expected comparison operator (=, ~=, >=, <=) after attribute name".to_string(),
-        );
+    };
+    Ok((filter, end))
+}
+
+/// Parse the rest of an extensible match from the first `:` at `cur`:
+///
+/// ```text
+/// extensible = ( attr [":dn"] [":" matchingrule] ":=" value )
+///            / ( [":dn"] ":" matchingrule ":=" value )
+/// ```
+fn parse_extensible(
+    input: &[u8],
+    start: usize,
+    mut cur: usize,
+    attr: String,
+) -> Result<(Filter, usize), FilterError> {
+    let dn_attributes = input[cur..].len() > 3
+        && input[cur + 1..cur + 3].eq_ignore_ascii_case(b"dn")
+        && input[cur + 3] == b':';
+    if dn_attributes {
+        cur += 3;
     }
+    let mut rule = None;
+    if input.get(cur + 1) != Some(&b'=') {
+        let len = name_len(input, cur + 1);
+        if len == 0 {
+            return Err(FilterError::new(
+                cur + 1,
+                format!("Expected matching rule after ':' at position {}", cur + 1),
+            ));
+        }
+        rule = Some(String::from_utf8_lossy(&input[cur + 1..cur + 1 + len]).into_owned());
+        cur += 1 + len;
+    }
+    if !input[cur..].starts_with(b":=") {
+        return Err(FilterError::new(
+            cur,
+            format!("Expected ':=' in extensible match at position {}", cur + 1),
+        ));
+    }
+    if attr.is_empty() && rule.is_none() {
+        return Err(FilterError::new(
+            start,
+            "Extensible match needs an attribute name or a matching rule",
+        ));
+    }
+
+    let (raw, end) = scan_value(input, cur + 2);
+    let filter = Filter::ExtensibleMatch {
+        attr: (!attr.is_empty()).then_some(attr),
+        rule,
+        dn_attributes,
+        value: unescape(raw),
+    };
+    Ok((filter, end))
+}
 
-    // Parse value: everything until the matching ')'
-    // Values can contain any character except unescaped ')' at our nesting level.
-    // We just scan until we find ')'.
+/// The raw value starting at `pos`: everything up to the next unescaped
+/// ')'. Returns the value and the position of that ')'.
+fn scan_value(input: &[u8], pos: usize) -> (&[u8], usize) {
+    let mut cur = pos;
     while cur < input.len() && input[cur] != b')' {
         if input[cur] == b'\\' && cur + 1 < input.len() {
             // Skip escaped character
@@ -248,8 +360,372 @@ fn parse_item(input: &[u8], pos: usize) -> Result<usize, String> {
             cur += 1;
         }
     }
+    (&input[pos..cur], cur)
+}
+
+/// A parsed search filter. Build one with [`Filter::parse`] or the
+/// constructors, send it to a server with its `Display` form, or evaluate
+/// it against entries already loaded with [`Filter::matches`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+    Equality(String, String),
+    Substring {
+        attr: String,
+        initial: Option<String>,
+        any: Vec<String>,
+        last: Option<String>,
+    },
+    Presence(String),
+    GreaterOrEqual(String, String),
+    LessOrEqual(String, String),
+    ApproxMatch(String, String),
+    /// `(attr:dn:rule:=value)`, where either the attribute or the rule
+    /// may be left out.
+    ExtensibleMatch {
+        attr: Option<String>,
+        rule: Option<String>,
+        dn_attributes: bool,
+        value: String,
+    },
+}
+
+/// Matching rules evaluated case-sensitively.
+const CASE_EXACT_RULES: [&str; 4] = [
+    "caseexactmatch",
+    "2.5.13.5",
+    "caseexactia5match",
+    "1.3.6.1.4.1.1466.109.114.1",
+];
+/// Active Directory's bitwise AND and OR matching rules.
+const BIT_AND_RULE: &str = "1.2.840.113556.1.4.803";
+const BIT_OR_RULE: &str = "1.2.840.113556.1.4.804";
+
+impl Filter {
+    /// Parse an RFC 4515 filter. Errors carry the byte offset where the
+    /// filter stops making sense, as [`validate`] reports them.
+    pub fn parse(filter: &str) -> Result<Self, FilterError> {
+        let leading = filter.len() - filter.trim_start().len();
+        let trimmed = filter.trim();
+        if trimmed.is_empty() {
+            return Err(FilterError::new(0, "Filter cannot be empty"));
+        }
+
+        let bytes = trimmed.as_bytes();
+        let result = parse_filter(bytes, 0).and_then(|(filter, end)| {
+            if end != bytes.len() {
+                Err(FilterError::new(
+                    end,
+                    format!("Unexpected characters after filter at position {}", end + 1),
+                ))
+            } else {
+                Ok(filter)
+            }
+        });
+        result.map_err(|mut e| {
+            e.position += leading;
+            e
+        })
+    }
+
+    /// `(attr=value)`
+    pub fn eq(attr: impl Into<String>, value: impl Into<String>) -> Self {
+        Filter::Equality(attr.into(), value.into())
+    }
+
+    /// `(attr=*)`
+    pub fn present(attr: impl Into<String>) -> Self {
+        Filter::Presence(attr.into())
+    }
+
+    /// `(attr=*value*)`
+    pub fn contains(attr: impl Into<String>, value: impl Into<String>) -> Self {
+        Filter::Substring {
+            attr: attr.into(),
+            initial: None,
+            any: vec![value.into()],
+            last: None,
+        }
+    }
+
+    /// `(attr=value*)`
+    pub fn starts_with(attr: impl Into<String>, value: impl Into<String>) -> Self {
+        Filter::Substring {
+            attr: attr.into(),
+            initial: Some(value.into()),
+            any: Vec::new(),
+            last: None,
+        }
+    }
+
+    /// `(attr>=value)`
+    pub fn ge(attr: impl Into<String>, value: impl Into<String>) -> Self {
+        Filter::GreaterOrEqual(attr.into(), value.into())
+    }
+
+    /// `(attr<=value)`
+    pub fn le(attr: impl Into<String>, value: impl Into<String>) -> Self {
+        Filter::LessOrEqual(attr.into(), value.into())
+    }
+
+    /// `(attr~=value)`
+    pub fn approx(attr: impl Into<String>, value: impl Into<String>) -> Self {
+        Filter::ApproxMatch(attr.into(), value.into())
+    }
+
+    /// `(&...)`
+    pub fn and(filters: impl IntoIterator<Item = Filter>) -> Self {
+        Filter::And(filters.into_iter().collect())
+    }
+
+    /// `(|...)`
+    pub fn or(filters: impl IntoIterator<Item = Filter>) -> Self {
+        Filter::Or(filters.into_iter().collect())
+    }
+
+    /// `(!filter)`
+    pub fn negate(filter: Filter) -> Self {
+        Filter::Not(Box::new(filter))
+    }
+
+    /// Whether `entry` matches. Attribute names and values compare
+    /// case-insensitively; `>=` and `<=` compare integers numerically and
+    /// anything else as lowercase text. `~=` is treated as equality, and an
+    /// extensible match as equality unless its rule is a case-exact or
+    /// Active Directory bitwise one.
+    pub fn matches(&self, entry: &LdapEntry) -> bool {
+        match self {
+            Filter::And(filters) => filters.iter().all(|f| f.matches(entry)),
+            Filter::Or(filters) => filters.iter().any(|f| f.matches(entry)),
+            Filter::Not(filter) => !filter.matches(entry),
+            Filter::Equality(attr, value) | Filter::ApproxMatch(attr, value) => {
+                let value = value.to_lowercase();
+                values_of(entry, attr).any(|v| v.to_lowercase() == value)
+            }
+            Filter::GreaterOrEqual(attr, value) => {
+                values_of(entry, attr).any(|v| compare_values(&v, value).is_ge())
+            }
+            Filter::LessOrEqual(attr, value) => {
+                values_of(entry, attr).any(|v| compare_values(&v, value).is_le())
+            }
+            Filter::Presence(attr) => values_of(entry, attr).next().is_some(),
+            Filter::Substring {
+                attr,
+                initial,
+                any,
+                last,
+            } => values_of(entry, attr)
+                .any(|v| substring_match(&v.to_lowercase(), initial, any, last)),
+            Filter::ExtensibleMatch {
+                attr,
+                rule,
+                dn_attributes,
+                value,
+            } => {
+                let mut values: Vec<String> = match attr {
+                    Some(attr) => values_of(entry, attr).collect(),
+                    None => entry
+                        .all_values()
+                        .into_iter()
+                        .flat_map(|(_, values)| values.to_text())
+                        .collect(),
+                };
+                if *dn_attributes {
+                    values.extend(
+                        dn_values(&entry.dn)
+                            .into_iter()
+                            .filter(|(name, _)| {
+                                attr.as_ref().map_or(true, |a| a.eq_ignore_ascii_case(name))
+                            })
+                            .map(|(_, v)| v),
+                    );
+                }
+                let rule = rule.as_deref().map(str::to_lowercase);
+                values
+                    .iter()
+                    .any(|v| extensible_match(rule.as_deref(), v, value))
+            }
+        }
+    }
+}
+
+/// RFC 4515 string form, with `*`, `(`, `)`, `\` and NUL in values
+/// escaped, so it parses back to the same filter.
+impl std::fmt::Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let list = |f: &mut std::fmt::Formatter<'_>, op: char, filters: &[Filter]| {
+            write!(f, "({}", op)?;
+            for filter in filters {
+                write!(f, "{}", filter)?;
+            }
+            f.write_str(")")
+        };
+        match self {
+            Filter::And(filters) => list(f, '&', filters),
+            Filter::Or(filters) => list(f, '|', filters),
+            Filter::Not(filter) => write!(f, "(!{})", filter),
+            Filter::Equality(attr, value) => {
+                write!(f, "({}={})", attr, escape_filter_value(value))
+            }
+            Filter::ApproxMatch(attr, value) => {
+                write!(f, "({}~={})", attr, escape_filter_value(value))
+            }
+            Filter::GreaterOrEqual(attr, value) => {
+                write!(f, "({}>={})", attr, escape_filter_value(value))
+            }
+            Filter::LessOrEqual(attr, value) => {
+                write!(f, "({}<={})", attr, escape_filter_value(value))
+            }
+            Filter::Presence(attr) => write!(f, "({}=*)", attr),
+            Filter::Substring {
+                attr,
+                initial,
+                any,
+                last,
+            } => {
+                write!(f, "({}=", attr)?;
+                if let Some(initial) = initial {
+                    f.write_str(&escape_filter_value(initial))?;
+                }
+                f.write_str("*")?;
+                for part in any {
+                    write!(f, "{}*", escape_filter_value(part))?;
+                }
+                if let Some(last) = last {
+                    f.write_str(&escape_filter_value(last))?;
+                }
+                f.write_str(")")
+            }
+            Filter::ExtensibleMatch {
+                attr,
+                rule,
+                dn_attributes,
+                value,
+            } => {
+                f.write_str("(")?;
+                if let Some(attr) = attr {
+                    f.write_str(attr)?;
+                }
+                if *dn_attributes {
+                    f.write_str(":dn")?;
+                }
+                if let Some(rule) = rule {
+                    write!(f, ":{}", rule)?;
+                }
+                write!(f, ":={})", escape_filter_value(value))
+            }
+        }
+    }
+}
 
-    Ok(cur)
+/// Text values of `attr` (case-insensitive), binary ones decoded lossily.
+fn values_of(entry: &LdapEntry, attr: &str) -> impl Iterator<Item = String> {
+    util::get_values_lossy(entry, attr).into_iter()
+}
+
+/// The `attr=value` pairs of every RDN in `dn`, unescaped.
+fn dn_values(dn: &str) -> Vec<(String, String)> {
+    let Ok(dn) = Dn::parse(dn) else {
+        return Vec::new();
+    };
+    dn.rdns()
+        .iter()
+        .flat_map(Rdn::avas)
+        .map(|ava| (ava.attr().to_string(), ava.value().to_string()))
+        .collect()
+}
+
+/// Evaluate one value against an extensible match with lowercase `rule`.
+fn extensible_match(rule: Option<&str>, value: &str, asserted: &str) -> bool {
+    let bits = || {
+        let value = value.trim().parse::<u64>().ok()?;
+        let mask = asserted.trim().parse::<u64>().ok()?;
+        Some((value, mask))
+    };
+    match rule {
+        Some(BIT_AND_RULE) => bits().is_some_and(|(v, mask)| v & mask == mask),
+        Some(BIT_OR_RULE) => bits().is_some_and(|(v, mask)| v & mask != 0),
+        Some(rule) if CASE_EXACT_RULES.contains(&rule) => value == asserted,
+        _ => value.to_lowercase() == asserted.to_lowercase(),
+    }
+}
+
+fn compare_values(value: &str, other: &str) -> std::cmp::Ordering {
+    match (value.trim().parse::<i64>(), other.trim().parse::<i64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => value.to_lowercase().cmp(&other.to_lowercase()),
+    }
+}
+
+fn substring_match(
+    value: &str,
+    initial: &Option<String>,
+    any: &[String],
+    last: &Option<String>,
+) -> bool {
+    let mut rest = value;
+    if let Some(initial) = initial {
+        let initial = initial.to_lowercase();
+        match rest.strip_prefix(initial.as_str()) {
+            Some(r) => rest = r,
+            None => return false,
+        }
+    }
+    for part in any {
+        let part = part.to_lowercase();
+        match rest.find(part.as_str()) {
+            Some(i) => rest = &rest[i + part.len()..],
+            None => return false,
+        }
+    }
+    match last {
+        Some(last) => rest.ends_with(last.to_lowercase().as_str()),
+        None => true,
+    }
+}
+
+fn split_unescaped_stars(raw: &[u8]) -> Vec<&[u8]> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut i = 0;
+    while i < raw.len() {
+        match raw[i] {
+            b'\\' => i += 2,
+            b'*' => {
+                parts.push(&raw[start..i]);
+                start = i + 1;
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    parts.push(&raw[start..]);
+    parts
+}
+
+/// Decode `\XX` hex escapes in a filter value.
+fn unescape(raw: &[u8]) -> String {
+    let mut out = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        if raw[i] == b'\\' {
+            let hex = raw.get(i + 1..i + 3).and_then(|h| {
+                std::str::from_utf8(h)
+                    .ok()
+                    .and_then(|h| u8::from_str_radix(h, 16).ok())
+            });
+            if let Some(byte) = hex {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(raw[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).into_owned()
 }
 
 #[cfg(test)]
@@ -367,6 +843,23 @@ mod tests {
         assert!(err.contains("Unexpected characters"), "got: {}", err);
     }
 
+    #[test]
+    fn test_error_positions() {
+        let position = |f: &str| validate(f).unwrap_err().position;
+        assert_eq!(position(""), 0);
+        assert_eq!(position("cn=admin"), 0);
+        assert_eq!(position("(cn=admin"), 9);
+        assert_eq!(position("(&(cn=a)(=b))"), 9);
+        assert_eq!(position("(cn)"), 3);
+        assert_eq!(position("(cn=test)garbage"), 9);
+        // Counted in the filter as typed, leading spaces included
+        assert_eq!(position("  (|)"), 4);
+        assert_eq!(
+            validate("(cn)").unwrap_err().to_string(),
+            validate_filter("(cn)").unwrap_err()
+        );
+    }
+
     // ---- detect_attribute_context tests ----
 
     #[test]
@@ -527,4 +1020,203 @@ mod tests {
             })
         );
     }
+
+    // ---- Filter evaluation ----
+
+    fn person() -> LdapEntry {
+        LdapEntry::new(
+            "uid=ada,ou=People,dc=example,dc=com".to_string(),
+            std::collections::BTreeMap::from([
+                (
+                    "objectClass".to_string(),
+                    vec!["top".to_string(), "inetOrgPerson".to_string()],
+                ),
+                ("cn".to_string(), vec!["Ada Lovelace".to_string()]),
+                ("mail".to_string(), vec!["ada@example.com".to_string()]),
+                ("uidNumber".to_string(), vec!["1815".to_string()]),
+                ("description".to_string(), vec!["a (b) c*".to_string()]),
+                ("userAccountControl".to_string(), vec!["514".to_string()]),
+            ]),
+        )
+    }
+
+    fn eval(filter: &str) -> bool {
+        Filter::parse(filter).unwrap().matches(&person())
+    }
+
+    #[test]
+    fn test_evaluate_items() {
+        assert!(eval("(objectclass=InetOrgPerson)"));
+        assert!(eval("(CN=ada lovelace)"));
+        assert!(!eval("(cn=Ada)"));
+        assert!(eval("(mail=*)"));
+        assert!(!eval("(telephoneNumber=*)"));
+        assert!(eval("(cn=ada*)"));
+        assert!(eval("(cn=*love*)"));
+        assert!(eval("(cn=*lace)"));
+        assert!(eval("(cn=A*a*L*e)"));
+        assert!(!eval("(cn=*ada)"));
+        assert!(eval("(uidNumber>=1000)"));
+        assert!(eval("(uidNumber<=1815)"));
+        // Numeric, not textual: "1815" < "900" as text
+        assert!(!eval("(uidNumber<=900)"));
+        assert!(eval("(cn~=ADA LOVELACE)"));
+        assert!(eval("(description=a \\28b\\29 c\\2a)"));
+        assert!(!eval("(description=a \\28b\\29 c*x)"));
+    }
+
+    #[test]
+    fn test_evaluate_combinations() {
+        // No uid attribute
+        assert!(!eval("(&(objectClass=inetOrg*)(uid=*)(!(uid=bob)))"));
+        assert!(eval(
+            "(&(objectClass=inetOrgPerson)(|(cn=Bob)(mail=ada@*)))"
+        ));
+        assert!(eval("(!(cn=Bob))"));
+        assert!(!eval("(|(cn=Bob)(cn=Carol))"));
+        assert_eq!(
+            Filter::parse("(|(cn=a)(!(sn=*b)))").unwrap(),
+            Filter::Or(vec![
+                Filter::Equality("cn".to_string(), "a".to_string()),
+                Filter::Not(Box::new(Filter::Substring {
+                    attr: "sn".to_string(),
+                    initial: None,
+                    any: Vec::new(),
+                    last: Some("b".to_string()),
+                })),
+            ])
+        );
+        assert!(Filter::parse("(cn=a").is_err());
+    }
+
+    #[test]
+    fn test_evaluate_extensible() {
+        // Disabled account: bit 2 of 512 | 2
+        assert!(eval("(userAccountControl:1.2.840.113556.1.4.803:=2)"));
+        assert!(!eval("(userAccountControl:1.2.840.113556.1.4.803:=18)"));
+        assert!(eval("(userAccountControl:1.2.840.113556.1.4.804:=18)"));
+        assert!(eval("(cn:=ADA LOVELACE)"));
+        assert!(!eval("(cn:caseExactMatch:=ADA LOVELACE)"));
+        assert!(eval("(cn:2.5.13.5:=Ada Lovelace)"));
+        // Any attribute, and values from the DN with :dn
+        assert!(eval("(:caseIgnoreMatch:=ada@example.com)"));
+        assert!(!eval("(uid:=ada)"));
+        assert!(eval("(uid:dn:=ADA)"));
+        assert!(eval("(ou:dn:caseExactMatch:=People)"));
+    }
+
+    #[test]
+    fn test_parse_variants() {
+        let parse = |f: &str| Filter::parse(f).unwrap();
+        assert_eq!(parse("(cn=*)"), Filter::present("cn"));
+        assert_eq!(parse("(cn~=ada)"), Filter::approx("cn", "ada"));
+        assert_eq!(parse("(uidNumber>=10)"), Filter::ge("uidNumber", "10"));
+        assert_eq!(parse("(uidNumber<=10)"), Filter::le("uidNumber", "10"));
+        assert_eq!(parse("(cn=*love*)"), Filter::contains("cn", "love"));
+        assert_eq!(parse("(cn=ada*)"), Filter::starts_with("cn", "ada"));
+        assert_eq!(
+            parse("(cn:dn:2.5.13.5:=Ada)"),
+            Filter::ExtensibleMatch {
+                attr: Some("cn".to_string()),
+                rule: Some("2.5.13.5".to_string()),
+                dn_attributes: true,
+                value: "Ada".to_string(),
+            }
+        );
+        assert_eq!(
+            parse("(:caseExactMatch:=x)"),
+            Filter::ExtensibleMatch {
+                attr: None,
+                rule: Some("caseExactMatch".to_string()),
+                dn_attributes: false,
+                value: "x".to_string(),
+            }
+        );
+        // An attribute name starting with "dn" is not the :dn flag
+        assert!(matches!(
+            parse("(cn:dnQualifierMatch:=x)"),
+            Filter::ExtensibleMatch {
+                dn_attributes: false,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn test_parse_escaped_values() {
+        assert_eq!(
+            Filter::parse(r"(cn=a\2ab\28\29\5c\00)").unwrap(),
+            Filter::eq("cn", "a*b()\\\0")
+        );
+        // Escaped stars are not wildcards
+        assert_eq!(
+            Filter::parse(r"(cn=\2a*x)").unwrap(),
+            Filter::Substring {
+                attr: "cn".to_string(),
+                initial: Some("*".to_string()),
+                any: Vec::new(),
+                last: Some("x".to_string()),
+            }
+        );
+        // UTF-8 written as escaped bytes
+        assert_eq!(
+            Filter::parse(r"(sn=Lu\c4\8di\c4\87)").unwrap(),
+            Filter::eq("sn", "Lučić")
+        );
+    }
+
+    #[test]
+    fn test_parse_extensible_errors() {
+        let position = |f: &str| Filter::parse(f).unwrap_err().position;
+        assert_eq!(position("(:=x)"), 1);
+        assert_eq!(position("(:dn:=x)"), 1);
+        assert_eq!(position("(cn:=x"), 6);
+        assert_eq!(position("(cn::=x)"), 4);
+        assert_eq!(position("(cn:rule=x)"), 8);
+    }
+
+    #[test]
+    fn test_display_escapes_and_round_trips() {
+        assert_eq!(
+            Filter::eq("description", "a (b) c*\\\0").to_string(),
+            r"(description=a \28b\29 c\2a\5c\00)"
+        );
+        let filter = Filter::and([
+            Filter::eq("objectClass", "person"),
+            Filter::or([
+                Filter::starts_with("cn", "A(1)"),
+                Filter::negate(Filter::present("mail")),
+            ]),
+            Filter::Substring {
+                attr: "sn".to_string(),
+                initial: Some("x".to_string()),
+                any: vec!["y*".to_string(), "z".to_string()],
+                last: Some("w".to_string()),
+            },
+            Filter::ExtensibleMatch {
+                attr: Some("ou".to_string()),
+                rule: None,
+                dn_attributes: true,
+                value: "Sales)".to_string(),
+            },
+        ]);
+        let text = filter.to_string();
+        assert_eq!(
+            text,
+            r"(&(objectClass=person)(|(cn=A\281\29*)(!(mail=*)))(sn=x*y\2a*z*w)(ou:dn:=Sales\29))"
+        );
+        assert_eq!(Filter::parse(&text).unwrap(), filter);
+
+        for text in [
+            "(cn~=a)",
+            "(uidNumber>=1)",
+            "(uidNumber<=9)",
+            "(cn=*)",
+            "(cn=*a*b*)",
+            "(:1.2.3:=v)",
+            "(!(&(a=1)(|(b=2)(c=3))))",
+        ] {
+            assert_eq!(Filter::parse(text).unwrap().to_string(), text);
+        }
+    }
 }
@@ -0,0 +1,215 @@
+//! Detecting entries that changed on the server since they were loaded.
+//!
+//! Entries are read together with `modifyTimestamp` and `entryCSN`. Before
+//! a destructive operation the entry is read again; if the markers moved,
+//! the operation is stopped and the differences are shown instead.
+
+use ldap3::Scope;
+
+use crate::account::ACCOUNT_STATE_ATTRS;
+use crate::diff::{diff_entries, AttributeChange};
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::ops::DirectoryOps;
+
+/// Operational attributes that change whenever an entry is modified.
+pub const CHANGE_MARKER_ATTRS: [&str; 2] = ["modifyTimestamp", "entryCSN"];
+
+/// The change markers of an entry at the time it was read.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeMarker {
+    pub modify_timestamp: Option<String>,
+    pub entry_csn: Option<String>,
+}
+
+impl ChangeMarker {
+    /// Whether the server reported any marker.
+    pub fn is_known(&self) -> bool {
+        self.modify_timestamp.is_some() || self.entry_csn.is_some()
+    }
+
+    /// Whether `self` (read now) shows a modification after `loaded`.
+    /// `entryCSN` is preferred: `modifyTimestamp` only has one-second
+    /// resolution. When neither copy carries a comparable marker this
+    /// cannot tell, and returns false.
+    pub fn changed_since(&self, loaded: &ChangeMarker) -> bool {
+        match (&self.entry_csn, &loaded.entry_csn) {
+            (Some(now), Some(then)) => return now != then,
+            (None, Some(_)) => return true,
+            _ => {}
+        }
+        match (&self.modify_timestamp, &loaded.modify_timestamp) {
+            (Some(now), Some(then)) => now != then,
+            (None, Some(_)) => true,
+            // First modification of an entry that had never been modified
+            (Some(_), None) => loaded.entry_csn.is_none(),
+            (None, None) => false,
+        }
+    }
+}
+
+/// An entry as read, with its change markers split off.
+#[derive(Debug, Clone)]
+pub struct TrackedEntry {
+    pub entry: LdapEntry,
+    pub marker: ChangeMarker,
+}
+
+impl TrackedEntry {
+    /// Remove the marker attributes from `entry`, keeping them aside, so
+    /// the entry displays as if only user attributes had been requested.
+    pub fn split(mut entry: LdapEntry) -> Self {
+        let mut take = |attr: &str| {
+            let key = entry
+                .attributes
+                .keys()
+                .find(|k| k.eq_ignore_ascii_case(attr))
+                .cloned()?;
+            entry
+                .attributes
+                .remove(&key)
+                .and_then(|v| v.into_iter().next())
+        };
+        let marker = ChangeMarker {
+            modify_timestamp: take("modifyTimestamp"),
+            entry_csn: take("entryCSN"),
+        };
+        Self { entry, marker }
+    }
+}
+
+/// Read `dn` with its change markers and account state split off, as
+/// entries are loaded for display. `None` when it does not exist.
+pub async fn read_tracked(
+    ops: &mut dyn DirectoryOps,
+    dn: &str,
+) -> Result<Option<TrackedEntry>, CoreError> {
+    let mut attrs = vec!["*"];
+    attrs.extend(CHANGE_MARKER_ATTRS);
+    attrs.extend(ACCOUNT_STATE_ATTRS);
+    let results = ops
+        .search(dn, Scope::Base, "(objectClass=*)", &attrs)
+        .await?;
+    Ok(results.into_iter().next().map(TrackedEntry::split))
+}
+
+/// Outcome of re-reading an entry before deleting it.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeleteCheck {
+    /// Unchanged since it was loaded (or it cannot be told); go ahead.
+    Proceed,
+    /// The entry no longer exists.
+    Gone,
+    /// Modified since it was loaded; the changes against the loaded copy.
+    Changed(Vec<AttributeChange>),
+    /// The root of a subtree delete has a different number of entries
+    /// directly below it than when the delete was confirmed.
+    ChildrenChanged { loaded: usize, now: usize },
+}
+
+/// Compare the copy loaded earlier with a fresh read of the same entry.
+/// With nothing loaded there is nothing to compare, so the delete proceeds.
+pub fn check_before_delete(
+    loaded: Option<&TrackedEntry>,
+    fresh: Option<&TrackedEntry>,
+) -> DeleteCheck {
+    let Some(fresh) = fresh else {
+        return DeleteCheck::Gone;
+    };
+    match loaded {
+        Some(loaded) if fresh.marker.changed_since(&loaded.marker) => {
+            DeleteCheck::Changed(diff_entries(&loaded.entry, &fresh.entry).changes())
+        }
+        _ => DeleteCheck::Proceed,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn read(desc: &str, csn: Option<&str>, ts: Option<&str>) -> TrackedEntry {
+        let mut attributes = BTreeMap::new();
+        attributes.insert("cn".to_string(), vec!["svc".to_string()]);
+        attributes.insert("description".to_string(), vec![desc.to_string()]);
+        if let Some(csn) = csn {
+            attributes.insert("entryCSN".to_string(), vec![csn.to_string()]);
+        }
+        if let Some(ts) = ts {
+            attributes.insert("modifytimestamp".to_string(), vec![ts.to_string()]);
+        }
+        TrackedEntry::split(LdapEntry::new(
+            "cn=svc,dc=example,dc=com".to_string(),
+            attributes,
+        ))
+    }
+
+    #[test]
+    fn test_split_removes_markers() {
+        let tracked = read(
+            "a",
+            Some("20260101000000.000000Z#000000#000#000000"),
+            Some("20260101000000Z"),
+        );
+        assert_eq!(tracked.entry.attributes.len(), 2);
+        assert_eq!(
+            tracked.marker.modify_timestamp.as_deref(),
+            Some("20260101000000Z")
+        );
+        assert!(tracked.marker.is_known());
+    }
+
+    #[test]
+    fn test_unchanged_entry_proceeds() {
+        let loaded = read("a", Some("csn-1"), Some("20260101000000Z"));
+        let fresh = read("a", Some("csn-1"), Some("20260101000000Z"));
+        assert_eq!(
+            check_before_delete(Some(&loaded), Some(&fresh)),
+            DeleteCheck::Proceed
+        );
+        // Nothing loaded, or a server without markers: cannot tell
+        assert_eq!(
+            check_before_delete(None, Some(&fresh)),
+            DeleteCheck::Proceed
+        );
+        let bare = read("a", None, None);
+        assert_eq!(
+            check_before_delete(Some(&bare), Some(&bare)),
+            DeleteCheck::Proceed
+        );
+    }
+
+    #[test]
+    fn test_modified_entry_is_blocked_with_diff() {
+        let loaded = read("Build server", Some("csn-1"), Some("20260101000000Z"));
+        // Same second, different CSN: only entryCSN catches it
+        let fresh = read("Payroll export", Some("csn-2"), Some("20260101000000Z"));
+        match check_before_delete(Some(&loaded), Some(&fresh)) {
+            DeleteCheck::Changed(changes) => {
+                assert_eq!(changes.len(), 1);
+                assert_eq!(changes[0].attr, "description");
+            }
+            other => panic!("expected Changed, got {:?}", other),
+        }
+
+        let loaded = read("a", None, Some("20260101000000Z"));
+        let fresh = read("a", None, Some("20260102000000Z"));
+        assert!(matches!(
+            check_before_delete(Some(&loaded), Some(&fresh)),
+            DeleteCheck::Changed(_)
+        ));
+        // Modified for the first time since it was created
+        let loaded = read("a", None, None);
+        assert!(matches!(
+            check_before_delete(Some(&loaded), Some(&fresh)),
+            DeleteCheck::Changed(_)
+        ));
+    }
+
+    #[test]
+    fn test_deleted_meanwhile() {
+        let loaded = read("a", Some("csn-1"), None);
+        assert_eq!(check_before_delete(Some(&loaded), None), DeleteCheck::Gone);
+    }
+}
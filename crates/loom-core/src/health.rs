@@ -0,0 +1,251 @@
+//! Connection health: noticing a dropped connection and getting it back.
+//!
+//! A [`HealthHandle`] is shared between an [`LdapConnection`] and whoever
+//! watches it, so the state can be read without locking the connection.
+//! While it is not [`ConnectionHealth::Connected`], requests fail at once
+//! with [`CoreError::ConnectionLost`] instead of waiting on a dead socket.
+//! A [`Reconnector`] opens the replacement connection without holding the
+//! old one, and [`Backoff`] spaces out the attempts.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use ldap3::Scope;
+use tracing::{debug, info};
+
+use crate::connection::{ConnectionSettings, LdapConnection};
+use crate::error::CoreError;
+use crate::tls::TrustStore;
+
+/// How long a health check may take before the connection counts as lost.
+const PING_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Whether a connection is usable.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConnectionHealth {
+    #[default]
+    Connected,
+    /// Lost; attempts to reconnect are under way.
+    Reconnecting,
+    /// Lost, and automatic reconnection gave up.
+    Disconnected,
+}
+
+impl ConnectionHealth {
+    /// Status bar symbol: `●`, `◌` or `✕`.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            ConnectionHealth::Connected => "\u{25cf}",
+            ConnectionHealth::Reconnecting => "\u{25cc}",
+            ConnectionHealth::Disconnected => "\u{2715}",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ConnectionHealth::Connected => "connected",
+            ConnectionHealth::Reconnecting => "reconnecting",
+            ConnectionHealth::Disconnected => "disconnected",
+        }
+    }
+
+    fn from_u8(value: u8) -> Self {
+        match value {
+            1 => ConnectionHealth::Reconnecting,
+            2 => ConnectionHealth::Disconnected,
+            _ => ConnectionHealth::Connected,
+        }
+    }
+
+    fn as_u8(self) -> u8 {
+        match self {
+            ConnectionHealth::Connected => 0,
+            ConnectionHealth::Reconnecting => 1,
+            ConnectionHealth::Disconnected => 2,
+        }
+    }
+}
+
+/// Shared, lock-free view of a connection's health.
+#[derive(Debug, Clone, Default)]
+pub struct HealthHandle(Arc<AtomicU8>);
+
+impl HealthHandle {
+    pub fn get(&self) -> ConnectionHealth {
+        ConnectionHealth::from_u8(self.0.load(Ordering::Relaxed))
+    }
+
+    pub fn set(&self, health: ConnectionHealth) {
+        self.0.store(health.as_u8(), Ordering::Relaxed);
+    }
+
+    /// Mark the connection lost, unless it already is. Returns whether
+    /// this call did, so only one watcher starts reconnecting.
+    pub fn mark_lost(&self) -> bool {
+        self.0
+            .compare_exchange(
+                ConnectionHealth::Connected.as_u8(),
+                ConnectionHealth::Reconnecting.as_u8(),
+                Ordering::Relaxed,
+                Ordering::Relaxed,
+            )
+            .is_ok()
+    }
+}
+
+/// Exponentially growing delays between reconnect attempts.
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    next: Duration,
+    max: Duration,
+}
+
+impl Backoff {
+    pub fn new(initial: Duration, max: Duration) -> Self {
+        Self { next: initial, max }
+    }
+
+    /// The delay before the next attempt; each is twice the last, up to `max`.
+    pub fn next_delay(&mut self) -> Duration {
+        let delay = self.next;
+        self.next = (self.next * 2).min(self.max);
+        delay
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(60))
+    }
+}
+
+/// Everything needed to open a replacement for a connection, taken while
+/// the connection is locked so the slow part can run without it.
+#[derive(Clone)]
+pub struct Reconnector {
+    settings: ConnectionSettings,
+    trust_store: Option<Arc<TrustStore>>,
+    credentials: Option<(String, String)>,
+}
+
+impl Reconnector {
+    /// Connect and authenticate the way the original connection did.
+    pub async fn open(&self) -> Result<LdapConnection, CoreError> {
+        let mut conn =
+            LdapConnection::connect(self.settings.clone(), self.trust_store.clone()).await?;
+        match &self.credentials {
+            Some((bind_dn, password)) if self.settings.auth.is_simple() => {
+                conn.simple_bind(bind_dn, password).await?
+            }
+            _ => conn.authenticate(None, "").await?,
+        }
+        Ok(conn)
+    }
+}
+
+impl LdapConnection {
+    /// This connection's health, to watch it without taking the lock.
+    pub fn health(&self) -> HealthHandle {
+        self.health.clone()
+    }
+
+    /// Fail fast with [`CoreError::ConnectionLost`] while the connection is
+    /// not usable.
+    pub fn check_health(&self) -> Result<(), CoreError> {
+        match self.health.get() {
+            ConnectionHealth::Connected => Ok(()),
+            _ => Err(CoreError::ConnectionLost),
+        }
+    }
+
+    /// Cheap liveness check: read the Root DSE's `objectClass` with no
+    /// attributes returned. Errors on timeout or a broken connection.
+    pub async fn ping(&mut self) -> Result<(), CoreError> {
+        let search = self
+            .ldap
+            .search("", Scope::Base, "(objectClass=*)", vec!["1.1"]);
+        match tokio::time::timeout(PING_TIMEOUT, search).await {
+            Err(_) => Err(CoreError::Timeout),
+            Ok(result) => {
+                result.map_err(CoreError::Ldap)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// What a background task needs to reopen this connection.
+    pub fn reconnector(&self) -> Reconnector {
+        Reconnector {
+            settings: self.settings.clone(),
+            trust_store: self.trust_store.clone(),
+            credentials: self.bind_credentials.clone(),
+        }
+    }
+
+    /// Take over the server connection of `fresh`, opened by a
+    /// [`Reconnector`], and mark this connection usable again.
+    pub fn replace_with(&mut self, fresh: LdapConnection) {
+        self.ldap = fresh.ldap;
+        // A transaction does not survive the connection it was opened on
+        self.txn_id = None;
+        self.health.set(ConnectionHealth::Connected);
+        info!(
+            "Reconnected to {}:{}",
+            self.settings.host, self.settings.port
+        );
+    }
+}
+
+/// Reopen a lost connection with `reconnector`, waiting between attempts
+/// as `backoff` says. Gives up after `max_attempts` and returns the last
+/// error.
+pub async fn reconnect_with_backoff(
+    reconnector: &Reconnector,
+    mut backoff: Backoff,
+    max_attempts: u32,
+) -> Result<LdapConnection, CoreError> {
+    let mut attempt = 1;
+    loop {
+        match reconnector.open().await {
+            Ok(conn) => return Ok(conn),
+            Err(e) if attempt >= max_attempts => return Err(e),
+            Err(e) => {
+                let delay = backoff.next_delay();
+                debug!(
+                    "Reconnect attempt {} failed ({}); retrying in {:?}",
+                    attempt, e, delay
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(5));
+        let delays: Vec<u64> = (0..5).map(|_| backoff.next_delay().as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 5, 5]);
+    }
+
+    #[test]
+    fn test_health_handle_is_shared() {
+        let health = HealthHandle::default();
+        let watcher = health.clone();
+        assert_eq!(watcher.get(), ConnectionHealth::Connected);
+
+        assert!(health.mark_lost());
+        assert!(!watcher.mark_lost());
+        assert_eq!(watcher.get(), ConnectionHealth::Reconnecting);
+
+        health.set(ConnectionHealth::Disconnected);
+        assert_eq!(watcher.get(), ConnectionHealth::Disconnected);
+        assert_eq!(watcher.get().symbol(), "\u{2715}");
+    }
+}
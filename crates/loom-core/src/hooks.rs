@@ -0,0 +1,326 @@
+use std::fmt;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+/// Maximum number of characters of hook output kept for the notification log.
+const MAX_OUTPUT_CHARS: usize = 500;
+
+/// Lifecycle events that can trigger a profile hook.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HookEvent {
+    Connect,
+    Disconnect,
+    Export,
+}
+
+impl HookEvent {
+    /// Config key for this event (e.g. `on_connect`).
+    pub fn key(&self) -> &'static str {
+        match self {
+            HookEvent::Connect => "on_connect",
+            HookEvent::Disconnect => "on_disconnect",
+            HookEvent::Export => "on_export",
+        }
+    }
+}
+
+impl fmt::Display for HookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.key())
+    }
+}
+
+/// A single hook command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HookCommand {
+    /// Shell command, executed via `sh -c`, or `cmd /C` on Windows.
+    pub command: String,
+    /// Only honoured for `on_connect`: a failing hook stops the connect.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub required: bool,
+}
+
+/// Optional per-profile commands run on connect, disconnect and export.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProfileHooks {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_connect: Option<HookCommand>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_disconnect: Option<HookCommand>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_export: Option<HookCommand>,
+    /// Timeout for each hook, in seconds.
+    #[serde(
+        default = "default_hook_timeout",
+        skip_serializing_if = "is_default_hook_timeout"
+    )]
+    pub timeout_secs: u64,
+    /// Set on profiles brought in via import; the user must approve the hooks
+    /// before they run for the first time.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub untrusted: bool,
+}
+
+fn is_false(v: &bool) -> bool {
+    !v
+}
+
+fn default_hook_timeout() -> u64 {
+    10
+}
+
+fn is_default_hook_timeout(v: &u64) -> bool {
+    *v == default_hook_timeout()
+}
+
+impl ProfileHooks {
+    /// True when no hook command is configured.
+    pub fn is_empty(&self) -> bool {
+        self.on_connect.is_none() && self.on_disconnect.is_none() && self.on_export.is_none()
+    }
+
+    /// The hook configured for the given event, if any.
+    pub fn get(&self, event: HookEvent) -> Option<&HookCommand> {
+        match event {
+            HookEvent::Connect => self.on_connect.as_ref(),
+            HookEvent::Disconnect => self.on_disconnect.as_ref(),
+            HookEvent::Export => self.on_export.as_ref(),
+        }
+    }
+}
+
+/// Everything needed to run one hook, detached from the profile it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookInvocation {
+    pub event: HookEvent,
+    pub profile: String,
+    pub command: HookCommand,
+    pub timeout_secs: u64,
+    /// Positional arguments, available to the command as `$1`, `$2`, ...
+    pub args: Vec<String>,
+    /// Extra environment variables (in addition to `LOOM_EVENT`/`LOOM_PROFILE`).
+    pub env: Vec<(String, String)>,
+}
+
+impl HookInvocation {
+    /// Build an invocation for `event` if the profile defines a hook for it.
+    pub fn new(hooks: &ProfileHooks, event: HookEvent, profile: &str) -> Option<Self> {
+        hooks.get(event).map(|command| Self {
+            event,
+            profile: profile.to_string(),
+            command: command.clone(),
+            timeout_secs: hooks.timeout_secs,
+            args: Vec::new(),
+            env: Vec::new(),
+        })
+    }
+
+    pub fn with_env(mut self, key: &str, value: &str) -> Self {
+        self.env.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Whether a failure of this hook should abort the connection.
+    pub fn is_required(&self) -> bool {
+        self.event == HookEvent::Connect && self.command.required
+    }
+}
+
+/// Result of running a hook.
+#[derive(Debug, Clone)]
+pub struct HookOutcome {
+    pub event: HookEvent,
+    pub profile: String,
+    pub success: bool,
+    /// Exit code, or None if the hook timed out or could not be started.
+    pub status: Option<i32>,
+    pub timed_out: bool,
+    /// Combined stdout/stderr, trimmed and truncated.
+    pub output: String,
+    pub required: bool,
+}
+
+impl HookOutcome {
+    /// One-line summary for the notification log.
+    pub fn summary(&self) -> String {
+        let state = if self.timed_out {
+            "timed out".to_string()
+        } else {
+            match self.status {
+                Some(0) => "succeeded".to_string(),
+                Some(code) => format!("failed (exit {})", code),
+                None if self.success => "succeeded".to_string(),
+                None => "failed".to_string(),
+            }
+        };
+        let mut msg = format!(
+            "Hook {} for profile '{}' {}",
+            self.event, self.profile, state
+        );
+        if !self.output.is_empty() {
+            msg.push_str(": ");
+            msg.push_str(&self.output);
+        }
+        msg
+    }
+}
+
+/// The system shell running `command`: `sh -c`, with `args` as `$1`,
+/// `$2`, ...
+#[cfg(not(windows))]
+fn shell(command: &str, args: &[String]) -> Command {
+    let mut cmd = Command::new("sh");
+    cmd.arg("-c").arg(command).arg("loom-hook").args(args);
+    cmd
+}
+
+/// The system shell running `command`: `cmd /C`, with `args` following
+/// the command, as `%1`, `%2`, ... to a batch file.
+#[cfg(windows)]
+fn shell(command: &str, args: &[String]) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command).args(args);
+    cmd
+}
+
+/// Run a hook command in the system shell, enforcing its timeout.
+/// Never returns an error: failures are reported in the outcome.
+pub async fn run_hook(invocation: &HookInvocation) -> HookOutcome {
+    let mut cmd = shell(&invocation.command.command, &invocation.args);
+    cmd.env("LOOM_EVENT", invocation.event.key())
+        .env("LOOM_PROFILE", &invocation.profile)
+        .envs(invocation.env.iter().map(|(k, v)| (k, v)))
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true);
+
+    let outcome =
+        |success: bool, status: Option<i32>, timed_out: bool, output: String| HookOutcome {
+            event: invocation.event,
+            profile: invocation.profile.clone(),
+            success,
+            status,
+            timed_out,
+            output,
+            required: invocation.is_required(),
+        };
+
+    let timeout = Duration::from_secs(invocation.timeout_secs.max(1));
+    match tokio::time::timeout(timeout, cmd.output()).await {
+        Ok(Ok(output)) => {
+            let mut text = String::from_utf8_lossy(&output.stdout).trim().to_string();
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            if !stderr.is_empty() {
+                if !text.is_empty() {
+                    text.push_str(" | ");
+                }
+                text.push_str(&stderr);
+            }
+            outcome(
+                output.status.success(),
+                output.status.code(),
+                false,
+                truncate_output(&text),
+            )
+        }
+        Ok(Err(e)) => outcome(false, None, false, format!("failed to start: {}", e)),
+        Err(_) => outcome(
+            false,
+            None,
+            true,
+            format!("no result after {}s", timeout.as_secs()),
+        ),
+    }
+}
+
+fn truncate_output(text: &str) -> String {
+    let flat = text.replace(['\n', '\r'], " ");
+    if flat.chars().count() > MAX_OUTPUT_CHARS {
+        let mut s: String = flat.chars().take(MAX_OUTPUT_CHARS).collect();
+        s.push_str("...");
+        s
+    } else {
+        flat
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invocation(command: &str, event: HookEvent) -> HookInvocation {
+        let hooks = ProfileHooks {
+            on_connect: Some(HookCommand {
+                command: command.to_string(),
+                required: true,
+            }),
+            on_export: Some(HookCommand {
+                command: command.to_string(),
+                required: true,
+            }),
+            timeout_secs: 2,
+            ..Default::default()
+        };
+        HookInvocation::new(&hooks, event, "Test").unwrap()
+    }
+
+    #[test]
+    fn test_parse_hooks_toml() {
+        let hooks: ProfileHooks = toml::from_str(
+            r#"
+on_connect = { command = "vpn-up", required = true }
+on_export = { command = "upload.sh" }
+"#,
+        )
+        .unwrap();
+        assert!(hooks.on_connect.as_ref().unwrap().required);
+        assert!(!hooks.on_export.as_ref().unwrap().required);
+        assert!(hooks.on_disconnect.is_none());
+        assert_eq!(hooks.timeout_secs, 10);
+        assert!(!hooks.untrusted);
+        assert!(!hooks.is_empty());
+    }
+
+    #[test]
+    fn test_required_only_applies_to_connect() {
+        assert!(invocation("true", HookEvent::Connect).is_required());
+        assert!(!invocation("true", HookEvent::Export).is_required());
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_success_with_args_and_env() {
+        let inv = invocation(
+            "echo \"$1 $2 $LOOM_EVENT $LOOM_PROFILE\"",
+            HookEvent::Export,
+        )
+        .with_args(vec!["/tmp/out.csv".to_string(), "csv".to_string()]);
+        let outcome = run_hook(&inv).await;
+        assert!(outcome.success);
+        assert_eq!(outcome.status, Some(0));
+        assert_eq!(outcome.output, "/tmp/out.csv csv on_export Test");
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_failure_reports_exit_code() {
+        let outcome = run_hook(&invocation("echo oops >&2; exit 3", HookEvent::Connect)).await;
+        assert!(!outcome.success);
+        assert_eq!(outcome.status, Some(3));
+        assert!(outcome.required);
+        assert!(outcome.summary().contains("failed (exit 3): oops"));
+    }
+
+    #[tokio::test]
+    async fn test_run_hook_timeout() {
+        let mut inv = invocation("sleep 5", HookEvent::Connect);
+        inv.timeout_secs = 1;
+        let outcome = run_hook(&inv).await;
+        assert!(!outcome.success);
+        assert!(outcome.timed_out);
+    }
+}
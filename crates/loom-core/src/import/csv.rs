@@ -3,11 +3,13 @@ use std::path::Path;
 
 use crate::entry::LdapEntry;
 use crate::error::CoreError;
+use crate::export::CsvOptions;
 
 /// Import entries from a CSV file.
 ///
 /// Expects first column to be "dn", remaining columns are attribute names.
-/// Multi-valued attributes should be separated by "; ".
+/// Multi-valued attributes should be separated by "; ", with a value
+/// containing it quoted as the CSV export does.
 pub fn import(path: &Path) -> Result<Vec<LdapEntry>, CoreError> {
     let content = std::fs::read_to_string(path)
         .map_err(|e| CoreError::ImportError(format!("Failed to read file: {}", e)))?;
@@ -16,7 +18,15 @@ pub fn import(path: &Path) -> Result<Vec<LdapEntry>, CoreError> {
 
 /// Parse CSV content string into entries.
 pub fn parse_csv(content: &str) -> Result<Vec<LdapEntry>, CoreError> {
-    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    parse_csv_with(content, &CsvOptions::default())
+}
+
+/// Parse CSV content written with `options`' delimiter and multi-value
+/// separator.
+pub fn parse_csv_with(content: &str, options: &CsvOptions) -> Result<Vec<LdapEntry>, CoreError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .delimiter(options.delimiter)
+        .from_reader(content.as_bytes());
 
     let headers: Vec<String> = reader
         .headers()
@@ -56,8 +66,7 @@ pub fn parse_csv(content: &str) -> Result<Vec<LdapEntry>, CoreError> {
             if let Some(value) = record.get(idx) {
                 let value = value.trim();
                 if !value.is_empty() {
-                    // Split multi-valued attributes on "; "
-                    let values: Vec<String> = value.split("; ").map(|s| s.to_string()).collect();
+                    let values = split_values(value, &options.multi_value_separator);
                     attributes.insert(header.clone(), values);
                 }
             }
@@ -69,6 +78,59 @@ pub fn parse_csv(content: &str) -> Result<Vec<LdapEntry>, CoreError> {
     Ok(entries)
 }
 
+/// Split a cell into the values [`join_values`] joined.
+///
+/// [`join_values`]: crate::export::csv::join_values
+fn split_values(cell: &str, separator: &str) -> Vec<String> {
+    if separator.is_empty() {
+        return vec![cell.to_string()];
+    }
+    let mut values = Vec::new();
+    let mut rest = cell;
+    loop {
+        // A quoted value runs to its closing quote; otherwise it is taken
+        // as written up to the separator
+        if let Some((value, after)) = rest.strip_prefix('"').and_then(unquote) {
+            if after.is_empty() {
+                values.push(value);
+                return values;
+            }
+            if let Some(next) = after.strip_prefix(separator) {
+                values.push(value);
+                rest = next;
+                continue;
+            }
+        }
+        match rest.split_once(separator) {
+            Some((value, next)) => {
+                values.push(value.to_string());
+                rest = next;
+            }
+            None => {
+                values.push(rest.to_string());
+                return values;
+            }
+        }
+    }
+}
+
+/// Read a quoted value up to its closing quote, `""` standing for a quote,
+/// and return it with what follows the closing quote.
+fn unquote(s: &str) -> Option<(String, &str)> {
+    let mut value = String::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if c != '"' {
+            value.push(c);
+        } else if chars.next_if(|&(_, c)| c == '"').is_some() {
+            value.push('"');
+        } else {
+            return Some((value, &s[i + 1..]));
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -102,7 +164,7 @@ mod tests {
 
         let star = vec!["*".to_string()];
         let mut buf = Vec::new();
-        crate::export::csv::write_csv(&mut buf, &entries, &star).unwrap();
+        crate::export::csv::write_to(&entries, &mut buf, &star, &CsvOptions::default()).unwrap();
         let csv_str = String::from_utf8(buf).unwrap();
 
         let reimported = parse_csv(&csv_str).unwrap();
@@ -110,4 +172,47 @@ mod tests {
         assert_eq!(reimported[0].dn, entries[0].dn);
         assert_eq!(reimported[0].first_value("cn"), Some("Test"));
     }
+
+    #[test]
+    fn test_separator_in_values_roundtrip() {
+        use crate::testing::fixtures::EntryBuilder;
+
+        // Values with commas, quotes and the separator itself
+        let groups: Vec<String> = (0..50)
+            .map(|i| format!("cn=\"Group, {}\"; x,ou=Groups,dc=example,dc=com", i))
+            .collect();
+        let refs: Vec<&str> = groups.iter().map(String::as_str).collect();
+        let entry = EntryBuilder::new("uid=ada,dc=example,dc=com")
+            .attr("uid", "ada")
+            .attrs("memberOf", &refs)
+            .attr("description", "\"quoted\" first")
+            .build();
+
+        for options in [
+            CsvOptions::default(),
+            CsvOptions {
+                multi_value_separator: "|".to_string(),
+                quote_all: true,
+                delimiter: b';',
+                ..CsvOptions::default()
+            },
+        ] {
+            let mut buf = Vec::new();
+            let star = vec!["*".to_string()];
+            crate::export::csv::write_to([&entry], &mut buf, &star, &options).unwrap();
+            let csv_str = String::from_utf8(buf).unwrap();
+
+            let reimported = parse_csv_with(&csv_str, &options).unwrap();
+            assert_eq!(reimported, vec![entry.clone()], "{}", csv_str);
+        }
+    }
+
+    #[test]
+    fn test_split_values() {
+        assert_eq!(split_values("a; b", "; "), vec!["a", "b"]);
+        assert_eq!(split_values("\"a; b\"; c", "; "), vec!["a; b", "c"]);
+        assert_eq!(split_values("\"say \"\"hi\"\"\"", "; "), vec!["say \"hi\""]);
+        // Not a quoted value after all: taken as written
+        assert_eq!(split_values("\"a\" b; c", "; "), vec!["\"a\" b", "c"]);
+    }
 }
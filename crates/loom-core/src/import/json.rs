@@ -16,6 +16,28 @@ pub fn parse_json(content: &str) -> Result<Vec<LdapEntry>, CoreError> {
         .map_err(|e| CoreError::ImportError(format!("JSON parse failed: {}", e)))
 }
 
+/// Import entries from a newline-delimited JSON file, one entry object
+/// per line.
+pub fn import_lines(path: &Path) -> Result<Vec<LdapEntry>, CoreError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| CoreError::ImportError(format!("Failed to read file: {}", e)))?;
+    parse_json_lines(&content)
+}
+
+/// Parse newline-delimited JSON into entries. Blank lines are skipped.
+pub fn parse_json_lines(content: &str) -> Result<Vec<LdapEntry>, CoreError> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line).map_err(|e| {
+                CoreError::ImportError(format!("JSON parse failed on line {}: {}", i + 1, e))
+            })
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -37,4 +59,21 @@ mod tests {
         assert_eq!(entries[0].dn, "cn=Test,dc=example,dc=com");
         assert_eq!(entries[0].first_value("cn"), Some("Test"));
     }
+
+    #[test]
+    fn test_parse_json_lines() {
+        let content = concat!(
+            r#"{"dn":"cn=A,dc=example,dc=com","attributes":{"cn":["A"]}}"#,
+            "\n\n",
+            r#"{"dn":"cn=B,dc=example,dc=com","attributes":{}}"#,
+            "\n",
+        );
+        let entries = parse_json_lines(content).unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].dn, "cn=B,dc=example,dc=com");
+
+        let err =
+            parse_json_lines(&format!("{}\n\n[", content.lines().next().unwrap())).unwrap_err();
+        assert!(err.to_string().contains("line 3"), "{}", err);
+    }
 }
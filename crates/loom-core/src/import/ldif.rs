@@ -1,116 +1,343 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, HashSet};
 use std::path::Path;
 
+use ldap3::Mod;
+
 use crate::entry::LdapEntry;
 use crate::error::CoreError;
+use crate::transaction::Change;
+
+/// One record of an LDIF file (RFC 2849).
+#[derive(Debug, Clone, PartialEq)]
+pub enum LdifRecord {
+    /// An entry as it stands, as the LDIF export writes them.
+    Content(LdapEntry),
+    /// A write to apply: `changetype: add`, `modify`, `delete` or
+    /// `modrdn`.
+    Change(Change),
+}
+
+impl LdifRecord {
+    pub fn dn(&self) -> &str {
+        match self {
+            LdifRecord::Content(entry) => &entry.dn,
+            LdifRecord::Change(change) => change.dn(),
+        }
+    }
+
+    /// The entry of a content record; change records have none.
+    pub fn into_entry(self) -> Option<LdapEntry> {
+        match self {
+            LdifRecord::Content(entry) => Some(entry),
+            LdifRecord::Change(_) => None,
+        }
+    }
+}
 
 /// Import entries from an LDIF file.
 pub fn import(path: &Path) -> Result<Vec<LdapEntry>, CoreError> {
-    let content = std::fs::read_to_string(path)
-        .map_err(|e| CoreError::ImportError(format!("Failed to read file: {}", e)))?;
+    let content = read(path)?;
     parse_ldif(&content)
 }
 
-/// Parse LDIF content string into entries.
+/// Parse LDIF content string into entries. Change records are an error:
+/// they describe writes, not entries.
 pub fn parse_ldif(content: &str) -> Result<Vec<LdapEntry>, CoreError> {
-    let mut entries = Vec::new();
-    let mut current_dn: Option<String> = None;
-    let mut current_attrs: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    parse_str(content)?
+        .into_iter()
+        .map(|record| {
+            let dn = record.dn().to_string();
+            record.into_entry().ok_or_else(|| {
+                CoreError::ImportError(format!("{}: change records cannot be read as entries", dn))
+            })
+        })
+        .collect()
+}
 
-    // Handle line folding (lines starting with a single space are continuations)
-    let unfolded = unfold_lines(content);
+/// Read the records of an LDIF file.
+pub fn parse_file(path: &Path) -> Result<Vec<LdifRecord>, CoreError> {
+    parse_str(&read(path)?)
+}
 
-    for line in unfolded.lines() {
-        let line = line.trim_end();
+/// Parse LDIF content into records, content and change records alike.
+///
+/// Folded lines, comments, base64 values (`attr:: dmFsdWU=`) and a
+/// leading `version: 1` are handled. URL values (`attr:< file:///...`)
+/// and controls are not supported and fail with the line they are on.
+pub fn parse_str(content: &str) -> Result<Vec<LdifRecord>, CoreError> {
+    let lines = unfold_lines(content);
+    let mut records = Vec::new();
+    let mut first = true;
 
-        // Empty line = end of entry
-        if line.is_empty() {
-            if let Some(dn) = current_dn.take() {
-                entries.push(LdapEntry::new(dn, std::mem::take(&mut current_attrs)));
+    for block in lines
+        .split(|(_, line)| line.is_empty())
+        .filter(|block| !block.is_empty())
+    {
+        let mut block = block;
+        if first {
+            first = false;
+            let (no, line) = &block[0];
+            let (attr, value) = parse_line(*no, line)?;
+            if attr.eq_ignore_ascii_case("version") {
+                if value != "1" {
+                    return Err(at(*no, format!("unsupported LDIF version {}", value)));
+                }
+                block = &block[1..];
+                if block.is_empty() {
+                    continue;
+                }
             }
-            continue;
         }
+        records.push(parse_record(block)?);
+    }
+
+    Ok(records)
+}
+
+fn read(path: &Path) -> Result<String, CoreError> {
+    std::fs::read_to_string(path)
+        .map_err(|e| CoreError::ImportError(format!("Failed to read file: {}", e)))
+}
 
-        // Skip comments
-        if line.starts_with('#') {
-            continue;
+/// An error on line `no` of the file.
+fn at(no: usize, message: String) -> CoreError {
+    CoreError::ImportError(format!("line {}: {}", no, message))
+}
+
+/// Parse one record, the lines between two blank lines.
+fn parse_record(lines: &[(usize, String)]) -> Result<LdifRecord, CoreError> {
+    let (no, line) = &lines[0];
+    let (attr, dn) = parse_line(*no, line)?;
+    if !attr.eq_ignore_ascii_case("dn") {
+        return Err(at(*no, format!("expected \"dn:\", found \"{}:\"", attr)));
+    }
+
+    let rest = &lines[1..];
+    let changetype = match rest.first() {
+        Some((no, line)) => {
+            let (attr, value) = parse_line(*no, line)?;
+            if attr.eq_ignore_ascii_case("control") {
+                return Err(at(*no, "controls are not supported".to_string()));
+            }
+            attr.eq_ignore_ascii_case("changetype")
+                .then(|| (*no, value.to_ascii_lowercase()))
         }
+        None => None,
+    };
+    let Some((no, changetype)) = changetype else {
+        return Ok(LdifRecord::Content(entry(dn, rest)?));
+    };
 
-        // Skip version line
-        if line.starts_with("version:") {
-            continue;
+    let body = &rest[1..];
+    let change = match changetype.as_str() {
+        "add" => Change::add(&entry(dn, body)?),
+        "delete" => match body.first() {
+            Some((no, _)) => return Err(at(*no, "a delete record takes no lines".to_string())),
+            None => Change::Delete { dn },
+        },
+        "modify" => Change::Modify {
+            dn,
+            mods: modifications(body)?,
+        },
+        "modrdn" | "moddn" => rename(dn, no, body)?,
+        other => return Err(at(no, format!("unknown changetype \"{}\"", other))),
+    };
+    Ok(LdifRecord::Change(change))
+}
+
+/// Collect the lines of a content record into an entry. An attribute
+/// with a base64 value that is not UTF-8 is binary as a whole.
+fn entry(dn: String, lines: &[(usize, String)]) -> Result<LdapEntry, CoreError> {
+    let mut values: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+    for (no, line) in lines {
+        let (attr, value) = parse_value(*no, line)?;
+        values.entry(attr).or_default().push(value);
+    }
+
+    let mut entry = LdapEntry::new(dn, BTreeMap::new());
+    for (attr, values) in values {
+        if values.iter().all(|v| matches!(v, Value::Text(_))) {
+            let text = values.into_iter().map(Value::into_text).collect();
+            entry.attributes.insert(attr, text);
+        } else {
+            let bytes = values.into_iter().map(Value::into_bytes).collect();
+            entry.binary_attributes.insert(attr, bytes);
         }
+    }
+    Ok(entry)
+}
 
-        // Parse attribute: value or attribute:: base64value
-        if let Some((attr, value)) = parse_ldif_line(line) {
-            if attr.eq_ignore_ascii_case("dn") {
-                // Start a new entry
-                if let Some(dn) = current_dn.take() {
-                    entries.push(LdapEntry::new(dn, std::mem::take(&mut current_attrs)));
-                }
-                current_dn = Some(value);
-            } else {
-                current_attrs.entry(attr).or_default().push(value);
+/// The `add:`, `delete:` and `replace:` groups of a modify record, each
+/// ended by a `-` line.
+fn modifications(lines: &[(usize, String)]) -> Result<Vec<Mod<String>>, CoreError> {
+    let mut mods = Vec::new();
+    let mut lines = lines.iter();
+
+    while let Some((no, line)) = lines.next() {
+        let (op, attr) = parse_line(*no, line)?;
+        let mut values = HashSet::new();
+        // The closing "-" is often left off the last group
+        for (no, line) in lines.by_ref().take_while(|(_, line)| line != "-") {
+            let (name, value) = parse_line(*no, line)?;
+            if !name.eq_ignore_ascii_case(&attr) {
+                return Err(at(
+                    *no,
+                    format!("expected a value of {}, found {}", attr, name),
+                ));
             }
+            values.insert(value);
         }
+        mods.push(match op.to_ascii_lowercase().as_str() {
+            "add" => Mod::Add(attr, values),
+            "delete" => Mod::Delete(attr, values),
+            "replace" => Mod::Replace(attr, values),
+            _ => return Err(at(*no, format!("unknown modification \"{}\"", op))),
+        });
     }
 
-    // Don't forget the last entry
-    if let Some(dn) = current_dn {
-        entries.push(LdapEntry::new(dn, current_attrs));
+    Ok(mods)
+}
+
+/// A modrdn record: `newrdn`, `deleteoldrdn` and an optional
+/// `newsuperior`.
+fn rename(dn: String, no: usize, lines: &[(usize, String)]) -> Result<Change, CoreError> {
+    let mut new_rdn = None;
+    let mut delete_old_rdn = None;
+    let mut new_superior = None;
+    for (no, line) in lines {
+        let (attr, value) = parse_line(*no, line)?;
+        match attr.to_ascii_lowercase().as_str() {
+            "newrdn" => new_rdn = Some(value),
+            "deleteoldrdn" => {
+                delete_old_rdn = Some(match value.as_str() {
+                    "0" => false,
+                    "1" => true,
+                    _ => {
+                        return Err(at(
+                            *no,
+                            format!("deleteoldrdn must be 0 or 1, not {}", value),
+                        ))
+                    }
+                })
+            }
+            "newsuperior" => new_superior = Some(value),
+            _ => {
+                return Err(at(
+                    *no,
+                    format!("unexpected \"{}:\" in a modrdn record", attr),
+                ))
+            }
+        }
     }
 
-    Ok(entries)
+    match (new_rdn, delete_old_rdn) {
+        (Some(new_rdn), Some(delete_old_rdn)) => Ok(Change::Rename {
+            dn,
+            new_rdn,
+            delete_old_rdn,
+            new_superior,
+        }),
+        _ => Err(at(
+            no,
+            "a modrdn record needs newrdn and deleteoldrdn".to_string(),
+        )),
+    }
 }
 
-/// Parse a single LDIF line into (attribute, value).
-fn parse_ldif_line(line: &str) -> Option<(String, String)> {
-    // Check for base64: "attr:: base64value"
-    if let Some(pos) = line.find(":: ") {
-        let attr = line[..pos].to_string();
-        let b64 = line[pos + 3..].trim();
-        let value = base64_decode(b64).unwrap_or_else(|| b64.to_string());
-        return Some((attr, value));
-    }
+/// A decoded value: text, or bytes that are not UTF-8.
+enum Value {
+    Text(String),
+    Binary(Vec<u8>),
+}
 
-    // Normal: "attr: value"
-    if let Some(pos) = line.find(": ") {
-        let attr = line[..pos].to_string();
-        let value = line[pos + 2..].to_string();
-        return Some((attr, value));
+impl Value {
+    /// The value as text, binary values as their base64.
+    fn into_text(self) -> String {
+        use base64::Engine;
+        match self {
+            Value::Text(s) => s,
+            Value::Binary(bytes) => base64::engine::general_purpose::STANDARD.encode(bytes),
+        }
     }
 
-    // "attr:" with empty value
-    if let Some(attr) = line.strip_suffix(':') {
-        if !attr.contains(' ') {
-            return Some((attr.to_string(), String::new()));
+    fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Value::Text(s) => s.into_bytes(),
+            Value::Binary(bytes) => bytes,
         }
     }
+}
 
-    None
+/// Parse a single LDIF line into (attribute, value): `attr: value`,
+/// `attr:: base64` or `attr:` for an empty value. Binary values that are
+/// not UTF-8 are kept as their base64 text.
+fn parse_line(no: usize, line: &str) -> Result<(String, String), CoreError> {
+    parse_value(no, line).map(|(attr, value)| (attr, value.into_text()))
 }
 
-/// Unfold LDIF continuation lines (lines starting with a single space).
-fn unfold_lines(content: &str) -> String {
-    let mut result = String::with_capacity(content.len());
-    for line in content.lines() {
-        if line.starts_with(' ') && !result.is_empty() && !result.ends_with('\n') {
-            // Continuation: append without the leading space
-            result.push_str(&line[1..]);
-        } else {
-            if !result.is_empty() {
-                result.push('\n');
+/// Parse a single LDIF line into (attribute, value), keeping base64
+/// values that are not UTF-8 as bytes.
+fn parse_value(no: usize, line: &str) -> Result<(String, Value), CoreError> {
+    let Some((attr, rest)) = line.split_once(':') else {
+        return Err(at(
+            no,
+            format!("expected \"attribute: value\", found \"{}\"", line),
+        ));
+    };
+    if attr.is_empty() || attr.contains(' ') {
+        return Err(at(no, format!("invalid attribute name \"{}\"", attr)));
+    }
+    let attr = attr.to_string();
+
+    if let Some(b64) = rest.strip_prefix(':') {
+        let b64 = b64.trim();
+        let value = base64_decode(b64)
+            .ok_or_else(|| at(no, format!("invalid base64 value of {}", attr)))?;
+        return Ok((attr, value));
+    }
+    if rest.starts_with('<') {
+        return Err(at(no, format!("URL value of {} is not supported", attr)));
+    }
+    Ok((attr, Value::Text(rest.trim_start_matches(' ').to_string())))
+}
+
+/// Unfold LDIF continuation lines (lines starting with a single space),
+/// dropping comments and keeping each line's number for errors.
+fn unfold_lines(content: &str) -> Vec<(usize, String)> {
+    let mut result: Vec<(usize, String)> = Vec::new();
+    // A comment's continuation lines belong to the comment
+    let mut in_comment = false;
+    for (i, line) in content.lines().enumerate() {
+        if let Some(rest) = line.strip_prefix(' ') {
+            if in_comment {
+                continue;
+            }
+            if let Some((_, last)) = result.last_mut().filter(|(_, l)| !l.trim().is_empty()) {
+                // Continuation: append without the leading space, keeping
+                // a space the fold fell after
+                last.push_str(rest);
+                continue;
             }
-            result.push_str(line);
         }
+        in_comment = line.starts_with('#');
+        if !in_comment {
+            result.push((i + 1, line.to_string()));
+        }
+    }
+    for (_, line) in &mut result {
+        line.truncate(line.trim_end().len());
     }
     result
 }
 
-fn base64_decode(s: &str) -> Option<String> {
+/// Decode base64 to text, or to bytes when they are not UTF-8.
+fn base64_decode(s: &str) -> Option<Value> {
     use base64::Engine;
     let bytes = base64::engine::general_purpose::STANDARD.decode(s).ok()?;
-    String::from_utf8(bytes).ok()
+    Some(match String::from_utf8(bytes) {
+        Ok(text) => Value::Text(text),
+        Err(e) => Value::Binary(e.into_bytes()),
+    })
 }
 
 #[cfg(test)]
@@ -148,9 +375,118 @@ sn: Jones
 
     #[test]
     fn test_unfold_lines() {
-        let input = "dn: cn=Very Long\n DN,dc=example,dc=com\ncn: Test\n";
+        let input = "dn: cn=Very Long\n DN,dc=example,dc=com\n# a comment\n  folded\ncn: Test\n";
         let unfolded = unfold_lines(input);
-        assert!(unfolded.contains("dn: cn=Very LongDN,dc=example,dc=com"));
+        assert_eq!(
+            unfolded,
+            vec![
+                (1, "dn: cn=Very LongDN,dc=example,dc=com".to_string()),
+                (5, "cn: Test".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_version_and_comments() {
+        let ldif = "version: 1\n# exported\ndn: cn=Test,dc=example,dc=com\ncn: Test\n";
+        let records = parse_str(ldif).unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].dn(), "cn=Test,dc=example,dc=com");
+
+        let err = parse_str("version: 2\n\ndn: cn=Test\n").unwrap_err();
+        assert!(err.to_string().contains("line 1"), "{}", err);
+    }
+
+    #[test]
+    fn test_change_records() {
+        let ldif = r#"version: 1
+
+dn: cn=New,dc=example,dc=com
+changetype: add
+cn: New
+objectClass: person
+
+dn: cn=Alice,dc=example,dc=com
+changetype: modify
+add: mail
+mail: alice@example.com
+mail: a@example.com
+-
+delete: description
+-
+replace: sn
+sn: Smith
+
+dn: cn=Old,dc=example,dc=com
+changetype: delete
+
+dn: cn=Bob,dc=example,dc=com
+changetype: modrdn
+newrdn: cn=Robert
+deleteoldrdn: 1
+newsuperior: ou=People,dc=example,dc=com
+"#;
+        let records = parse_str(ldif).unwrap();
+        assert_eq!(records.len(), 4);
+
+        let LdifRecord::Change(Change::Add { dn, attrs, .. }) = &records[0] else {
+            panic!("expected an add: {:?}", records[0]);
+        };
+        assert_eq!(dn, "cn=New,dc=example,dc=com");
+        assert!(attrs
+            .iter()
+            .any(|(a, v)| a == "objectClass" && v.contains("person")));
+
+        let set = |values: &[&str]| values.iter().map(|v| v.to_string()).collect();
+        assert_eq!(
+            records[1],
+            LdifRecord::Change(Change::Modify {
+                dn: "cn=Alice,dc=example,dc=com".to_string(),
+                mods: vec![
+                    Mod::Add(
+                        "mail".to_string(),
+                        set(&["alice@example.com", "a@example.com"])
+                    ),
+                    Mod::Delete("description".to_string(), HashSet::new()),
+                    Mod::Replace("sn".to_string(), set(&["Smith"])),
+                ],
+            })
+        );
+        assert_eq!(
+            records[2],
+            LdifRecord::Change(Change::Delete {
+                dn: "cn=Old,dc=example,dc=com".to_string()
+            })
+        );
+        assert_eq!(
+            records[3],
+            LdifRecord::Change(Change::Rename {
+                dn: "cn=Bob,dc=example,dc=com".to_string(),
+                new_rdn: "cn=Robert".to_string(),
+                delete_old_rdn: true,
+                new_superior: Some("ou=People,dc=example,dc=com".to_string()),
+            })
+        );
+
+        // Change records are not entries
+        assert!(parse_ldif(ldif).is_err());
+    }
+
+    #[test]
+    fn test_unsupported_lines_are_errors() {
+        let url = "dn: cn=Test,dc=example,dc=com\ncn: Test\njpegPhoto:< file:///tmp/photo.jpg\n";
+        let err = parse_str(url).unwrap_err().to_string();
+        assert!(err.contains("line 3") && err.contains("URL"), "{}", err);
+
+        for bad in [
+            "cn: Test\n",
+            "dn: cn=Test\nnot a value line\n",
+            "dn: cn=Test\ncn:: !!!\n",
+            "dn: cn=Test\nchangetype: frobnicate\n",
+            "dn: cn=Test\nchangetype: modrdn\nnewrdn: cn=Other\n",
+        ] {
+            assert!(parse_str(bad).is_err(), "{}", bad);
+        }
     }
 
     #[test]
@@ -165,7 +501,7 @@ sn: Jones
 
         let star = vec!["*".to_string()];
         let mut buf = Vec::new();
-        crate::export::ldif::write_ldif(&mut buf, &entries, &star).unwrap();
+        crate::export::ldif::write_to(&entries, &mut buf, &star).unwrap();
         let ldif_str = String::from_utf8(buf).unwrap();
 
         let reimported = parse_ldif(&ldif_str).unwrap();
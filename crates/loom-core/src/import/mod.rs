@@ -17,7 +17,14 @@ pub fn import_entries(path: &Path) -> Result<Vec<LdapEntry>, CoreError> {
     match format {
         ExportFormat::Ldif => ldif::import(path),
         ExportFormat::Json => json::import(path),
+        ExportFormat::JsonLines => json::import_lines(path),
         ExportFormat::Csv => csv::import(path),
         ExportFormat::Xlsx => xlsx::import(path),
+        ExportFormat::Dsml => Err(CoreError::ImportError(
+            "Importing DSML is not supported".to_string(),
+        )),
+        ExportFormat::Sqlite => Err(CoreError::ImportError(
+            "Importing SQLite is not supported".to_string(),
+        )),
     }
 }
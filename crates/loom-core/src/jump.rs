@@ -0,0 +1,338 @@
+//! Jumping to an entry by typing the first letters of its name.
+//!
+//! Matching ignores case. With the `collation` feature it also follows the
+//! collation rules of the user's locale (`LC_ALL`, `LC_COLLATE` or `LANG`),
+//! so `é` matches `e` and, in Swedish, `å` is a letter of its own.
+
+use std::cmp::Ordering;
+use std::time::{Duration, Instant};
+
+use crate::tree::{ChildPaging, PageDirection};
+
+/// Keys typed within this long of each other extend the prefix.
+pub const TYPING_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// Index strip bucket for names that do not start with a letter.
+pub const OTHER: char = '#';
+
+/// Buckets of the index strip, in order.
+pub const INDEX: [char; 27] = [
+    OTHER, 'A', 'B', 'C', 'D', 'E', 'F', 'G', 'H', 'I', 'J', 'K', 'L', 'M', 'N', 'O', 'P', 'Q',
+    'R', 'S', 'T', 'U', 'V', 'W', 'X', 'Y', 'Z',
+];
+
+/// What to look for after a key press.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Jump {
+    pub prefix: String,
+    /// Look past the current entry, so pressing one letter again steps
+    /// through the names starting with it.
+    pub advance: bool,
+}
+
+/// Characters typed so far in jump mode.
+#[derive(Debug, Default)]
+pub struct TypedPrefix {
+    typed: String,
+    last: Option<Instant>,
+}
+
+impl TypedPrefix {
+    /// Add a typed character. Within [`TYPING_TIMEOUT`] of the previous one
+    /// it narrows the prefix, unless it repeats the only letter typed so
+    /// far; after a pause it starts a new prefix.
+    pub fn push(&mut self, c: char, now: Instant) -> Jump {
+        let fresh = self
+            .last
+            .map_or(true, |last| now.duration_since(last) > TYPING_TIMEOUT);
+        if fresh {
+            self.typed.clear();
+        }
+        self.typed.push(c);
+        self.last = Some(now);
+
+        let mut buf = [0u8; 4];
+        let key = c.encode_utf8(&mut buf);
+        let repeated = self.typed.chars().all(|t| {
+            let mut buf = [0u8; 4];
+            compare(t.encode_utf8(&mut buf), key) == Ordering::Equal
+        });
+        if repeated {
+            Jump {
+                prefix: key.to_string(),
+                advance: true,
+            }
+        } else {
+            Jump {
+                prefix: self.typed.clone(),
+                advance: false,
+            }
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.typed.clear();
+        self.last = None;
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.typed
+    }
+}
+
+/// Index of the name to jump to: the first one from `current` on, wrapping
+/// around, that starts with the prefix. `current` itself only qualifies
+/// when the jump does not advance.
+pub fn find<S: AsRef<str>>(names: &[S], current: Option<usize>, jump: &Jump) -> Option<usize> {
+    let len = names.len();
+    let start = match current {
+        Some(i) if jump.advance => i + 1,
+        Some(i) => i,
+        None => 0,
+    };
+    (0..len)
+        .map(|k| (start + k) % len)
+        .find(|&i| starts_with(names[i].as_ref(), &jump.prefix))
+}
+
+/// Whether `name` starts with `prefix`, ignoring case.
+pub fn starts_with(name: &str, prefix: &str) -> bool {
+    let n = prefix.chars().count();
+    let head: String = name.chars().take(n).collect();
+    head.chars().count() == n && compare(&head, prefix) == Ordering::Equal
+}
+
+/// Compare two names ignoring case, by the locale's rules when the
+/// `collation` feature is enabled.
+pub fn compare(a: &str, b: &str) -> Ordering {
+    #[cfg(feature = "collation")]
+    {
+        collation::compare(a, b)
+    }
+    #[cfg(not(feature = "collation"))]
+    {
+        fold_compare(a, b)
+    }
+}
+
+fn fold_compare(a: &str, b: &str) -> Ordering {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .cmp(b.chars().flat_map(char::to_lowercase))
+}
+
+/// The index strip bucket a name files under.
+pub fn index_letter(name: &str) -> char {
+    let Some(first) = name.chars().next() else {
+        return OTHER;
+    };
+    let mut buf = [0u8; 4];
+    let first = first.encode_utf8(&mut buf);
+    INDEX[1..]
+        .iter()
+        .copied()
+        .find(|letter| {
+            let mut buf = [0u8; 4];
+            compare(first, letter.encode_utf8(&mut buf)) == Ordering::Equal
+        })
+        .unwrap_or(OTHER)
+}
+
+/// For a page of names sorted ascending, which way the names starting
+/// with `prefix` lie when none are on it; `None` when they would be here.
+pub fn page_toward(first: &str, last: &str, prefix: &str) -> Option<PageDirection> {
+    let head = |name: &str| -> String { name.chars().take(prefix.chars().count()).collect() };
+    if compare(prefix, &head(last)) == Ordering::Greater {
+        Some(PageDirection::Next)
+    } else if compare(prefix, &head(first)) == Ordering::Less {
+        Some(PageDirection::Previous)
+    } else {
+        None
+    }
+}
+
+/// Which neighbouring page of a paged container holds the names starting
+/// with `prefix`, given the names on the current one. Only answered when
+/// the listing is sorted ascending by `naming_attr` and that page exists.
+pub fn page_for<S: AsRef<str>>(
+    paging: &ChildPaging,
+    naming_attr: &str,
+    names: &[S],
+    prefix: &str,
+) -> Option<PageDirection> {
+    let sorted = !paging.query.sort_desc
+        && paging
+            .query
+            .sort_attr
+            .as_deref()
+            .is_some_and(|attr| attr.eq_ignore_ascii_case(naming_attr));
+    if !sorted {
+        return None;
+    }
+    let direction = page_toward(names.first()?.as_ref(), names.last()?.as_ref(), prefix)?;
+    let exists = match direction {
+        PageDirection::Next => paging.has_more,
+        PageDirection::Previous => paging.page > 0,
+    };
+    exists.then_some(direction)
+}
+
+#[cfg(feature = "collation")]
+mod collation {
+    use std::cmp::Ordering;
+
+    use icu_collator::{Collator, CollatorOptions, Strength};
+
+    thread_local! {
+        static COLLATOR: Option<Collator> = new_collator();
+    }
+
+    pub fn compare(a: &str, b: &str) -> Ordering {
+        COLLATOR.with(|collator| match collator {
+            Some(collator) => collator.compare(a, b),
+            None => super::fold_compare(a, b),
+        })
+    }
+
+    fn new_collator() -> Option<Collator> {
+        let mut options = CollatorOptions::new();
+        // Primary strength: base letters only, ignoring case and accents
+        options.strength = Some(Strength::Primary);
+        let locale = ["LC_ALL", "LC_COLLATE", "LANG"]
+            .iter()
+            .filter_map(|var| std::env::var(var).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| language_tag(&value))
+            .and_then(|tag| tag.parse().ok())
+            .unwrap_or_default();
+        Collator::try_new(&locale, options).ok()
+    }
+
+    /// `sv_SE.UTF-8@euro` -> `sv-SE`; `None` for the C locale.
+    pub(super) fn language_tag(posix: &str) -> Option<String> {
+        let name = posix.split(['.', '@']).next().unwrap_or_default();
+        match name {
+            "" | "C" | "POSIX" => None,
+            name => Some(name.replace('_', "-")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jump(prefix: &str, advance: bool) -> Jump {
+        Jump {
+            prefix: prefix.to_string(),
+            advance,
+        }
+    }
+
+    #[test]
+    fn test_typed_prefix_narrows_cycles_and_times_out() {
+        let start = Instant::now();
+        let mut typed = TypedPrefix::default();
+        assert_eq!(typed.push('s', start), jump("s", true));
+        assert_eq!(
+            typed.push('m', start + Duration::from_millis(200)),
+            jump("sm", false)
+        );
+
+        // After a pause the next key starts over
+        let later = start + Duration::from_secs(3);
+        assert_eq!(typed.push('s', later), jump("s", true));
+        // Repeating the letter, in either case, cycles its matches
+        assert_eq!(
+            typed.push('S', later + Duration::from_millis(100)),
+            jump("S", true)
+        );
+        assert_eq!(typed.as_str(), "sS");
+        typed.clear();
+        assert_eq!(typed.as_str(), "");
+    }
+
+    #[test]
+    fn test_find_wraps_and_advances() {
+        let names = ["alice", "Sam", "bob", "sally", "Steve"];
+        assert_eq!(find(&names, None, &jump("s", true)), Some(1));
+        assert_eq!(find(&names, Some(1), &jump("s", true)), Some(3));
+        assert_eq!(find(&names, Some(4), &jump("s", true)), Some(1));
+        // Narrowing keeps the current entry when it still matches
+        assert_eq!(find(&names, Some(3), &jump("sa", false)), Some(3));
+        assert_eq!(find(&names, Some(3), &jump("st", false)), Some(4));
+        assert_eq!(find(&names, Some(0), &jump("z", true)), None);
+        assert_eq!(find::<&str>(&[], None, &jump("a", true)), None);
+    }
+
+    #[test]
+    fn test_starts_with_ignores_case() {
+        assert!(starts_with("Smith", "sm"));
+        assert!(starts_with("smith", "SMI"));
+        assert!(!starts_with("Sm", "smi"));
+        assert!(!starts_with("Jones", "s"));
+    }
+
+    #[test]
+    fn test_index_letter() {
+        assert_eq!(index_letter("smith"), 'S');
+        assert_eq!(index_letter("Anna"), 'A');
+        assert_eq!(index_letter("42 Wallaby Way"), OTHER);
+        assert_eq!(index_letter(""), OTHER);
+    }
+
+    #[test]
+    fn test_page_toward() {
+        assert_eq!(
+            page_toward("adams", "jones", "s"),
+            Some(PageDirection::Next)
+        );
+        assert_eq!(
+            page_toward("miller", "zhang", "b"),
+            Some(PageDirection::Previous)
+        );
+        assert_eq!(page_toward("adams", "jones", "c"), None);
+        assert_eq!(page_toward("adams", "jones", "jo"), None);
+    }
+
+    #[test]
+    fn test_page_for_needs_name_order() {
+        let names = ["adams", "jones"];
+        let mut paging = ChildPaging::new(2);
+        paging.has_more = true;
+        // Unsorted listings give no hint where a name lies
+        assert_eq!(page_for(&paging, "cn", &names, "s"), None);
+
+        paging.query = crate::tree::ChildQuery::parse("sort=CN").unwrap();
+        assert_eq!(
+            page_for(&paging, "cn", &names, "s"),
+            Some(PageDirection::Next)
+        );
+        assert_eq!(page_for(&paging, "uid", &names, "s"), None);
+        // Already on the first page
+        assert_eq!(page_for(&paging, "cn", &names, "a"), None);
+        paging.has_more = false;
+        assert_eq!(page_for(&paging, "cn", &names, "s"), None);
+
+        paging.page = 1;
+        assert_eq!(page_for(&paging, "cn", &names, "a"), None);
+        paging.query = crate::tree::ChildQuery::parse("sort=cn").unwrap();
+        assert_eq!(
+            page_for(&paging, "cn", &names, "aa"),
+            Some(PageDirection::Previous)
+        );
+        paging.query.sort_desc = true;
+        assert_eq!(page_for(&paging, "cn", &names, "aa"), None);
+    }
+
+    #[cfg(feature = "collation")]
+    #[test]
+    fn test_collation_folds_accents() {
+        assert!(starts_with("Émile", "em"));
+        assert_eq!(
+            collation::language_tag("sv_SE.UTF-8@euro").as_deref(),
+            Some("sv-SE")
+        );
+        assert_eq!(collation::language_tag("C.UTF-8"), None);
+    }
+}
@@ -1,21 +1,58 @@
+pub mod account;
+pub mod activity;
+pub mod audit;
 pub mod auth;
+pub mod bookmark;
 pub mod bulk;
+pub mod changefile;
 pub mod config;
 pub mod connection;
+pub mod copy_template;
 pub mod credentials;
+pub mod decode;
+pub mod delete;
+pub mod diff;
 pub mod dn;
+pub mod doctor;
+pub mod duplicate;
 pub mod entry;
 pub mod error;
+pub mod errors;
 pub mod export;
 pub mod filter;
+pub mod freshness;
+pub mod health;
+pub mod hooks;
 pub mod import;
+pub mod jump;
+pub mod membership;
+pub mod merge;
+pub mod migrate;
 pub mod modify;
 pub mod offline;
+pub mod oids;
+pub mod opener;
+pub mod ops;
+pub mod password;
+pub mod profile_import;
+pub mod protect;
+pub mod redact;
+pub mod resolve;
 pub mod schema;
+#[cfg(feature = "scripting")]
+pub mod script;
 pub mod search;
 pub mod server_detect;
+pub mod snapshot;
+pub mod subtree_diff;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod time;
+pub mod timezone;
 pub mod tls;
+pub mod transaction;
 pub mod tree;
+pub mod undo;
 pub mod util;
 pub mod vault;
 
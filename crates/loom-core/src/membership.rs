@@ -0,0 +1,526 @@
+//! Which groups an entry belongs to: the groups its `memberOf` names and
+//! those listing it in `member` or `uniqueMember`, and optionally the
+//! groups those groups belong to in turn.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+
+use ldap3::Scope;
+
+use crate::dn::normalize;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::filter::{escape_filter_value, Filter};
+use crate::offline::OfflineDirectory;
+use crate::ops::{DirectoryOps, OpFuture};
+
+/// Active Directory's LDAP_MATCHING_RULE_IN_CHAIN: `member` matched
+/// through any number of nested groups, expanded by the server.
+pub const IN_CHAIN_RULE: &str = "1.2.840.113556.1.4.1941";
+
+/// Groups nested deeper than this are not followed unless asked.
+pub const DEFAULT_MAX_DEPTH: usize = 10;
+
+/// The one operation membership resolution needs, so it can run against
+/// a live server, an offline directory or a fake one in tests.
+pub trait GroupSearch: Send {
+    fn search<'a>(
+        &'a mut self,
+        base_dn: &'a str,
+        scope: Scope,
+        filter: &'a str,
+        attrs: &'a [&'a str],
+    ) -> OpFuture<'a, Vec<LdapEntry>>;
+}
+
+impl<T: DirectoryOps> GroupSearch for T {
+    fn search<'a>(
+        &'a mut self,
+        base_dn: &'a str,
+        scope: Scope,
+        filter: &'a str,
+        attrs: &'a [&'a str],
+    ) -> OpFuture<'a, Vec<LdapEntry>> {
+        DirectoryOps::search(self, base_dn, scope, filter, attrs)
+    }
+}
+
+/// Offline, filters are evaluated in memory; an in-chain match counts as
+/// plain equality there, so leave `in_chain` off.
+impl GroupSearch for OfflineDirectory {
+    fn search<'a>(
+        &'a mut self,
+        base_dn: &'a str,
+        scope: Scope,
+        filter: &'a str,
+        _attrs: &'a [&'a str],
+    ) -> OpFuture<'a, Vec<LdapEntry>> {
+        Box::pin(async move {
+            let filter = Filter::parse(filter).map_err(|e| CoreError::SearchFailed(e.message))?;
+            Ok(self.find(base_dn, scope, &filter))
+        })
+    }
+}
+
+/// How far to look for groups.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MembershipOptions {
+    /// Where to search for groups naming the entry.
+    pub base_dn: String,
+    /// Follow groups that are members of other groups.
+    pub nested: bool,
+    /// Longest chain of groups followed when nested.
+    pub max_depth: usize,
+    /// Let the server expand nested groups with [`IN_CHAIN_RULE`], which
+    /// only Active Directory understands.
+    pub in_chain: bool,
+}
+
+impl MembershipOptions {
+    pub fn new(base_dn: impl Into<String>) -> Self {
+        Self {
+            base_dn: base_dn.into(),
+            nested: false,
+            max_depth: DEFAULT_MAX_DEPTH,
+            in_chain: false,
+        }
+    }
+
+    pub fn with_nested(mut self, nested: bool) -> Self {
+        self.nested = nested;
+        self
+    }
+
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth.max(1);
+        self
+    }
+
+    pub fn with_in_chain(mut self, in_chain: bool) -> Self {
+        self.in_chain = in_chain;
+        self
+    }
+}
+
+/// One group the entry belongs to, and how.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupPath {
+    pub dn: String,
+    /// The groups from one the entry is a direct member of down to this
+    /// one, inclusive; the shortest such chain when there are several.
+    pub path: Vec<String>,
+}
+
+impl GroupPath {
+    /// 1 for a direct membership, more through nested groups.
+    pub fn depth(&self) -> usize {
+        self.path.len()
+    }
+
+    /// The groups the membership comes through, without this one.
+    pub fn via(&self) -> &[String] {
+        &self.path[..self.path.len() - 1]
+    }
+}
+
+/// The groups an entry belongs to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Membership {
+    pub member: String,
+    /// In tree order: each group follows the one its path goes through.
+    pub groups: Vec<GroupPath>,
+    /// Chains that lead back to a group already on them, ending with
+    /// the repeated group.
+    pub cycles: Vec<Vec<String>>,
+    /// Groups deeper than the depth limit were left out.
+    pub truncated: bool,
+    /// The server expanded nested groups itself.
+    pub server_expanded: bool,
+}
+
+impl Membership {
+    pub fn direct(&self) -> impl Iterator<Item = &GroupPath> {
+        self.groups.iter().filter(|g| g.depth() == 1)
+    }
+
+    /// The groups as entries for export: `depth` and the `via` groups in
+    /// order, outermost last.
+    pub fn to_entries(&self) -> Vec<LdapEntry> {
+        self.groups
+            .iter()
+            .map(|group| {
+                let mut attributes = BTreeMap::new();
+                attributes.insert("depth".to_string(), vec![group.depth().to_string()]);
+                if group.depth() > 1 {
+                    attributes.insert("via".to_string(), group.via().to_vec());
+                }
+                LdapEntry::new(group.dn.clone(), attributes)
+            })
+            .collect()
+    }
+}
+
+/// The groups `member_dn` belongs to: those its `memberOf` names and those
+/// under `options.base_dn` listing it in `member` or `uniqueMember`. With
+/// `nested`, the groups of those groups too, breadth first so each group
+/// is reached by its shortest chain, stopping at cycles and at
+/// `max_depth`.
+pub async fn resolve_membership(
+    source: &mut dyn GroupSearch,
+    member_dn: &str,
+    options: &MembershipOptions,
+) -> Result<Membership, CoreError> {
+    let mut membership = Membership {
+        member: member_dn.to_string(),
+        ..Default::default()
+    };
+    let member_key = normalize(member_dn);
+
+    // memberOf as read so far, by normalized DN
+    let mut member_of: HashMap<String, Vec<String>> = HashMap::new();
+    let own = source
+        .search(member_dn, Scope::Base, "(objectClass=*)", &["memberOf"])
+        .await?;
+    member_of.insert(member_key.clone(), values(own.first(), "memberOf"));
+
+    // Active Directory hands back every group the entry is in, each with
+    // its memberOf, which is the whole graph
+    if options.in_chain && options.nested {
+        let filter = format!(
+            "(member:{}:={})",
+            IN_CHAIN_RULE,
+            escape_filter_value(member_dn)
+        );
+        for group in source
+            .search(&options.base_dn, Scope::Subtree, &filter, &["memberOf"])
+            .await?
+        {
+            member_of.insert(normalize(&group.dn), values(Some(&group), "memberOf"));
+        }
+        membership.server_expanded = true;
+    }
+
+    let max_depth = if options.nested {
+        options.max_depth.max(1)
+    } else {
+        1
+    };
+    let mut seen: HashSet<String> = HashSet::from([member_key.clone()]);
+    let mut queue: VecDeque<(String, Vec<String>)> =
+        VecDeque::from([(member_dn.to_string(), vec![])]);
+    let mut found: Vec<GroupPath> = Vec::new();
+
+    while let Some((dn, path)) = queue.pop_front() {
+        let key = normalize(&dn);
+        let mut parents = member_of.get(&key).cloned().unwrap_or_default();
+        if !membership.server_expanded {
+            let filter = format!(
+                "(|(member={0})(uniqueMember={0}))",
+                escape_filter_value(&dn)
+            );
+            for group in source
+                .search(&options.base_dn, Scope::Subtree, &filter, &["memberOf"])
+                .await?
+            {
+                member_of
+                    .entry(normalize(&group.dn))
+                    .or_insert_with(|| values(Some(&group), "memberOf"));
+                parents.push(group.dn);
+            }
+        }
+
+        let mut listed = HashSet::new();
+        for parent in parents {
+            let parent_key = normalize(&parent);
+            if !listed.insert(parent_key.clone()) {
+                continue;
+            }
+            if parent_key == member_key || path.iter().any(|p| normalize(p) == parent_key) {
+                let mut cycle = path.clone();
+                cycle.push(parent);
+                membership.cycles.push(cycle);
+                continue;
+            }
+            if seen.contains(&parent_key) {
+                continue;
+            }
+            if path.len() == max_depth {
+                membership.truncated = true;
+                continue;
+            }
+            seen.insert(parent_key);
+            let mut parent_path = path.clone();
+            parent_path.push(parent.clone());
+            found.push(GroupPath {
+                dn: parent.clone(),
+                path: parent_path.clone(),
+            });
+            queue.push_back((parent, parent_path));
+        }
+        // Without nesting, only the entry's own groups are wanted
+        if !options.nested {
+            break;
+        }
+    }
+
+    found.sort_by_cached_key(|g| g.path.iter().map(|p| normalize(p)).collect::<Vec<_>>());
+    membership.groups = found;
+    Ok(membership)
+}
+
+fn values(entry: Option<&LdapEntry>, attr: &str) -> Vec<String> {
+    entry
+        .and_then(|e| e.values_ci(attr))
+        .map(|v| v.to_text())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures::{domain, group, org_unit, person, EntryBuilder};
+    use crate::testing::MockDirectory;
+
+    const BASE: &str = "dc=example,dc=com";
+    const USERS: &str = "ou=People,dc=example,dc=com";
+    const GROUPS: &str = "ou=Groups,dc=example,dc=com";
+
+    fn dn(cn: &str) -> String {
+        format!("cn={},{}", cn, GROUPS)
+    }
+
+    /// Ada is in Engineering directly and, through `uniqueMember`, in
+    /// Readers; Engineering is in Staff, Staff in Everyone, and Everyone
+    /// back in Staff.
+    fn directory() -> MockDirectory {
+        let ada = format!("cn=Ada Lovelace,{}", USERS);
+        MockDirectory::with_entries([
+            domain(BASE),
+            org_unit(BASE, "People"),
+            org_unit(BASE, "Groups"),
+            EntryBuilder::new(&ada)
+                .object_classes(&["inetOrgPerson"])
+                .attr("cn", "Ada Lovelace")
+                .attr("memberOf", dn("Engineering"))
+                .build(),
+            person(USERS, "Grace", "Hopper"),
+            group(GROUPS, "Engineering", &[&ada]),
+            group(GROUPS, "Staff", &[&dn("Engineering"), &dn("Everyone")]),
+            group(GROUPS, "Everyone", &[&dn("Staff")]),
+            EntryBuilder::new(dn("Readers"))
+                .object_classes(&["groupOfUniqueNames"])
+                .attr("cn", "Readers")
+                .attr("uniqueMember", &ada)
+                .build(),
+        ])
+    }
+
+    fn ada() -> String {
+        format!("cn=Ada Lovelace,{}", USERS)
+    }
+
+    fn paths(membership: &Membership) -> Vec<Vec<String>> {
+        membership.groups.iter().map(|g| g.path.clone()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_direct_groups_from_member_of_and_searches() {
+        let mut dir = directory();
+        let options = MembershipOptions::new(BASE);
+        let membership = resolve_membership(&mut dir, &ada(), &options)
+            .await
+            .unwrap();
+        // memberOf and member both name Engineering; it is listed once
+        assert_eq!(
+            paths(&membership),
+            vec![vec![dn("Engineering")], vec![dn("Readers")]]
+        );
+        assert!(membership.cycles.is_empty());
+        assert!(!membership.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_nested_groups_with_cycle() {
+        let mut dir = directory();
+        let options = MembershipOptions::new(BASE).with_nested(true);
+        let membership = resolve_membership(&mut dir, &ada(), &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            paths(&membership),
+            vec![
+                vec![dn("Engineering")],
+                vec![dn("Engineering"), dn("Staff")],
+                vec![dn("Engineering"), dn("Staff"), dn("Everyone")],
+                vec![dn("Readers")],
+            ]
+        );
+        assert_eq!(
+            membership.groups[2].via(),
+            &[dn("Engineering"), dn("Staff")]
+        );
+        assert_eq!(
+            membership.cycles,
+            vec![vec![
+                dn("Engineering"),
+                dn("Staff"),
+                dn("Everyone"),
+                dn("Staff")
+            ]]
+        );
+        assert!(!membership.truncated);
+        assert_eq!(membership.direct().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_depth_limit() {
+        let mut dir = directory();
+        let options = MembershipOptions::new(BASE)
+            .with_nested(true)
+            .with_max_depth(2);
+        let membership = resolve_membership(&mut dir, &ada(), &options)
+            .await
+            .unwrap();
+        assert_eq!(membership.groups.len(), 3);
+        assert!(membership.truncated);
+        assert!(membership.cycles.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_missing_entry_is_an_error() {
+        let mut dir = directory();
+        let options = MembershipOptions::new(BASE);
+        let result = resolve_membership(&mut dir, &format!("cn=Nobody,{}", USERS), &options).await;
+        assert!(result.is_err());
+    }
+
+    /// Answers in-chain filters the way Active Directory does and
+    /// remembers the filters it was sent.
+    struct InChainDirectory {
+        dir: MockDirectory,
+        filters: Vec<String>,
+    }
+
+    impl GroupSearch for InChainDirectory {
+        fn search<'a>(
+            &'a mut self,
+            base_dn: &'a str,
+            scope: Scope,
+            filter: &'a str,
+            attrs: &'a [&'a str],
+        ) -> OpFuture<'a, Vec<LdapEntry>> {
+            self.filters.push(filter.to_string());
+            let prefix = format!("(member:{}:=", IN_CHAIN_RULE);
+            let Some(member) = filter
+                .strip_prefix(&prefix)
+                .and_then(|f| f.strip_suffix(')'))
+            else {
+                return DirectoryOps::search(&mut self.dir, base_dn, scope, filter, attrs);
+            };
+            let mut inside = HashSet::from([normalize(member)]);
+            let mut groups: Vec<LdapEntry> = Vec::new();
+            loop {
+                let more: Vec<LdapEntry> = self
+                    .dir
+                    .entries()
+                    .iter()
+                    .filter(|e| !inside.contains(&normalize(&e.dn)))
+                    .filter(|e| {
+                        values(Some(e), "member")
+                            .iter()
+                            .any(|m| inside.contains(&normalize(m)))
+                    })
+                    .cloned()
+                    .collect();
+                if more.is_empty() {
+                    break;
+                }
+                inside.extend(more.iter().map(|e| normalize(&e.dn)));
+                groups.extend(more);
+            }
+            Box::pin(async move { Ok(groups) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_in_chain_lets_the_server_expand() {
+        let ada = ada();
+        // Active Directory keeps memberOf on groups as well
+        let mut dir = MockDirectory::with_entries([
+            domain(BASE),
+            org_unit(BASE, "People"),
+            org_unit(BASE, "Groups"),
+            EntryBuilder::new(&ada)
+                .object_classes(&["user"])
+                .attr("memberOf", dn("Engineering"))
+                .build(),
+            EntryBuilder::new(dn("Engineering"))
+                .object_classes(&["group"])
+                .attr("member", &ada)
+                .attr("memberOf", dn("Staff"))
+                .build(),
+            EntryBuilder::new(dn("Staff"))
+                .object_classes(&["group"])
+                .attr("member", dn("Engineering"))
+                .build(),
+        ]);
+        dir.insert(group(GROUPS, "Unrelated", &[&dn("Nobody")]));
+        let mut dir = InChainDirectory {
+            dir,
+            filters: Vec::new(),
+        };
+        let options = MembershipOptions::new(BASE)
+            .with_nested(true)
+            .with_in_chain(true);
+        let membership = resolve_membership(&mut dir, &ada, &options).await.unwrap();
+        assert!(membership.server_expanded);
+        assert_eq!(
+            paths(&membership),
+            vec![
+                vec![dn("Engineering")],
+                vec![dn("Engineering"), dn("Staff")]
+            ]
+        );
+        // One read of the entry and one search, however deep the nesting
+        assert_eq!(dir.filters.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_offline_directory() {
+        let mut dir = OfflineDirectory::load_embedded();
+        let options = MembershipOptions::new("dc=contoso,dc=com").with_nested(true);
+        let membership =
+            resolve_membership(&mut dir, "cn=Administrator,dc=contoso,dc=com", &options)
+                .await
+                .unwrap();
+        assert_eq!(
+            paths(&membership),
+            vec![vec![
+                "cn=Domain Admins,ou=Groups,dc=contoso,dc=com".to_string()
+            ]]
+        );
+    }
+
+    #[test]
+    fn test_to_entries() {
+        let membership = Membership {
+            member: ada(),
+            groups: vec![
+                GroupPath {
+                    dn: dn("Engineering"),
+                    path: vec![dn("Engineering")],
+                },
+                GroupPath {
+                    dn: dn("Staff"),
+                    path: vec![dn("Engineering"), dn("Staff")],
+                },
+            ],
+            ..Default::default()
+        };
+        let entries = membership.to_entries();
+        assert_eq!(entries[0].first_value("depth"), Some("1"));
+        assert!(entries[0].first_value("via").is_none());
+        assert_eq!(entries[1].dn, dn("Staff"));
+        assert_eq!(entries[1].first_value("depth"), Some("2"));
+        assert_eq!(entries[1].attributes["via"], vec![dn("Engineering")]);
+    }
+}
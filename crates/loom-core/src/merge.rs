@@ -0,0 +1,381 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::entry::LdapEntry;
+use crate::schema::SchemaCache;
+
+/// Options controlling how duplicate search results are merged.
+#[derive(Debug, Clone, Default)]
+pub struct MergeOptions {
+    /// Treat attribute values differing only in case (e.g. `TRUE`/`true`) as
+    /// the same value. Off by default so no value is silently collapsed.
+    pub case_insensitive_values: bool,
+}
+
+/// A search result after deduplication, with provenance.
+#[derive(Debug, Clone)]
+pub struct MergedEntry {
+    pub entry: LdapEntry,
+    /// Source connection the entry came from.
+    pub source: String,
+    /// How many result rows were folded into this entry (1 = no duplicate).
+    pub occurrences: usize,
+    /// Single-valued attributes that arrived with different values; both are kept.
+    pub conflicts: Vec<String>,
+}
+
+/// Outcome of a merge pass.
+#[derive(Debug, Clone, Default)]
+pub struct MergeReport {
+    pub entries: Vec<MergedEntry>,
+    /// Number of duplicate rows folded into an earlier entry.
+    pub duplicates_merged: usize,
+}
+
+impl MergeReport {
+    /// Total number of conflicting single-valued attributes across all entries.
+    pub fn conflict_count(&self) -> usize {
+        self.entries.iter().map(|e| e.conflicts.len()).sum()
+    }
+
+    /// Drop provenance and return the plain entries, in first-seen order.
+    pub fn into_entries(self) -> Vec<LdapEntry> {
+        self.entries.into_iter().map(|m| m.entry).collect()
+    }
+}
+
+/// Normalize a DN for duplicate detection; see [`crate::dn::normalize`].
+pub fn normalize_dn(dn: &str) -> String {
+    crate::dn::normalize(dn)
+}
+
+/// Merge search results that refer to the same entry.
+///
+/// Entries are keyed on (source, normalized DN). Attribute names are matched
+/// case-insensitively and values are unioned. When `schema` marks an
+/// attribute single-valued and the duplicates disagree, both values are
+/// kept and the attribute is recorded in `conflicts`.
+pub fn merge_entries(
+    results: Vec<(String, LdapEntry)>,
+    options: &MergeOptions,
+    schema: Option<&SchemaCache>,
+) -> MergeReport {
+    let mut report = MergeReport::default();
+    let mut index: HashMap<(String, String), usize> = HashMap::new();
+
+    for (source, entry) in results {
+        let key = (source.clone(), normalize_dn(&entry.dn));
+        match index.get(&key) {
+            Some(&i) => {
+                let merged = &mut report.entries[i];
+                merge_into(
+                    &mut merged.entry,
+                    &mut merged.conflicts,
+                    entry,
+                    options,
+                    schema,
+                );
+                merged.occurrences += 1;
+                report.duplicates_merged += 1;
+            }
+            None => {
+                index.insert(key, report.entries.len());
+                report.entries.push(MergedEntry {
+                    entry,
+                    source,
+                    occurrences: 1,
+                    conflicts: Vec::new(),
+                });
+            }
+        }
+    }
+
+    report
+}
+
+/// Convenience wrapper for results that all come from one connection.
+pub fn dedup_entries(
+    entries: Vec<LdapEntry>,
+    source: &str,
+    options: &MergeOptions,
+    schema: Option<&SchemaCache>,
+) -> MergeReport {
+    let results = entries
+        .into_iter()
+        .map(|e| (source.to_string(), e))
+        .collect();
+    merge_entries(results, options, schema)
+}
+
+/// Deduplication for results from one connection that arrive in batches.
+///
+/// Entries are appended to a caller-owned list; a duplicate DN is folded
+/// into the row already there, as [`dedup_entries`] does for a whole set.
+#[derive(Debug, Default)]
+pub struct DedupIndex {
+    index: HashMap<String, usize>,
+    conflicts: HashMap<usize, Vec<String>>,
+    /// Number of duplicate rows folded into an earlier entry.
+    pub duplicates_merged: usize,
+}
+
+impl DedupIndex {
+    /// Append `entry` to `entries`, or merge it into the earlier entry with
+    /// the same DN.
+    pub fn push(
+        &mut self,
+        entries: &mut Vec<LdapEntry>,
+        entry: LdapEntry,
+        options: &MergeOptions,
+        schema: Option<&SchemaCache>,
+    ) {
+        let key = normalize_dn(&entry.dn);
+        match self.index.get(&key) {
+            Some(&i) => {
+                let conflicts = self.conflicts.entry(i).or_default();
+                merge_into(&mut entries[i], conflicts, entry, options, schema);
+                self.duplicates_merged += 1;
+            }
+            None => {
+                self.index.insert(key, entries.len());
+                entries.push(entry);
+            }
+        }
+    }
+
+    /// Total number of conflicting single-valued attributes so far.
+    pub fn conflict_count(&self) -> usize {
+        self.conflicts.values().map(Vec::len).sum()
+    }
+}
+
+fn merge_into(
+    target: &mut LdapEntry,
+    conflicts: &mut Vec<String>,
+    incoming: LdapEntry,
+    options: &MergeOptions,
+    schema: Option<&SchemaCache>,
+) {
+    let mut grown = Vec::new();
+    for (attr, values) in incoming.attributes {
+        grown.extend(union(&mut target.attributes, attr, values, |a, b| {
+            if options.case_insensitive_values {
+                a.to_lowercase() == b.to_lowercase()
+            } else {
+                a == b
+            }
+        }));
+    }
+    for (attr, values) in incoming.binary_attributes {
+        grown.extend(union(
+            &mut target.binary_attributes,
+            attr,
+            values,
+            |a, b| a == b,
+        ));
+    }
+
+    for key in grown {
+        let single_valued = schema.is_some_and(|s| s.is_single_valued(&key));
+        if single_valued && !conflicts.iter().any(|c| c.eq_ignore_ascii_case(&key)) {
+            conflicts.push(key);
+        }
+    }
+}
+
+/// Add the `values` of `attr` missing from `attributes`, matching the name
+/// case-insensitively. Returns the existing attribute's name when it
+/// gained a value.
+fn union<V>(
+    attributes: &mut BTreeMap<String, Vec<V>>,
+    attr: String,
+    values: Vec<V>,
+    same: impl Fn(&V, &V) -> bool,
+) -> Option<String> {
+    let Some(key) = attributes
+        .keys()
+        .find(|k| k.eq_ignore_ascii_case(&attr))
+        .cloned()
+    else {
+        attributes.insert(attr, values);
+        return None;
+    };
+
+    let existing = attributes.get_mut(&key).unwrap();
+    let before = existing.len();
+    for value in values {
+        if !existing.iter().any(|v| same(v, &value)) {
+            existing.push(value);
+        }
+    }
+    (existing.len() > before).then_some(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{AttributeSyntax, AttributeTypeInfo};
+    use std::collections::BTreeMap;
+
+    fn entry(dn: &str, attrs: &[(&str, &[&str])]) -> LdapEntry {
+        LdapEntry::new(
+            dn.to_string(),
+            attrs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.iter().map(|s| s.to_string()).collect()))
+                .collect::<BTreeMap<_, _>>(),
+        )
+    }
+
+    fn schema_with_single(attr: &str) -> SchemaCache {
+        let mut schema = SchemaCache::new();
+        schema.attribute_types.insert(
+            attr.to_lowercase(),
+            AttributeTypeInfo {
+                oid: "1.2.3".to_string(),
+                names: vec![attr.to_string()],
+                description: None,
+                superior: None,
+                syntax: AttributeSyntax::DirectoryString,
+                single_value: true,
+                no_user_modification: false,
+            },
+        );
+        schema
+    }
+
+    #[test]
+    fn test_normalize_dn() {
+        assert_eq!(
+            normalize_dn("CN=Alice , OU=People,dc=Example"),
+            "cn=alice,ou=people,dc=example"
+        );
+    }
+
+    #[test]
+    fn test_exact_duplicates_collapse() {
+        let e = entry("cn=a,dc=x", &[("cn", &["a"]), ("mail", &["a@x"])]);
+        let report = dedup_entries(
+            vec![e.clone(), e.clone()],
+            "conn",
+            &MergeOptions::default(),
+            None,
+        );
+        assert_eq!(report.entries.len(), 1);
+        assert_eq!(report.duplicates_merged, 1);
+        assert_eq!(report.entries[0].occurrences, 2);
+        assert_eq!(report.entries[0].entry.attributes["mail"], vec!["a@x"]);
+        assert!(report.entries[0].conflicts.is_empty());
+    }
+
+    #[test]
+    fn test_attribute_subsets_are_unioned() {
+        let a = entry("cn=a,dc=x", &[("cn", &["a"]), ("member", &["cn=1"])]);
+        let b = entry("CN=A, DC=X", &[("Member", &["cn=2"]), ("sn", &["Smith"])]);
+        let report = dedup_entries(vec![a, b], "conn", &MergeOptions::default(), None);
+        assert_eq!(report.entries.len(), 1);
+        let merged = &report.entries[0].entry;
+        assert_eq!(merged.dn, "cn=a,dc=x");
+        assert_eq!(merged.attributes["member"], vec!["cn=1", "cn=2"]);
+        assert_eq!(merged.attributes["sn"], vec!["Smith"]);
+    }
+
+    #[test]
+    fn test_binary_values_are_unioned() {
+        let photo =
+            |bytes: &[u8]| BTreeMap::from([("jpegPhoto".to_string(), vec![bytes.to_vec()])]);
+        let a = entry("cn=a,dc=x", &[("cn", &["a"])]).with_binary_attributes(photo(&[0xff, 0xd8]));
+        let b = entry("cn=a,dc=x", &[("cn", &["a"])]).with_binary_attributes(photo(&[0x89, 0x50]));
+        let c = entry("cn=a,dc=x", &[]).with_binary_attributes(BTreeMap::from([(
+            "objectGUID".to_string(),
+            vec![vec![0; 16]],
+        )]));
+        let report = dedup_entries(vec![a, b, c], "conn", &MergeOptions::default(), None);
+        let merged = &report.entries[0].entry;
+        assert_eq!(
+            merged.binary_attributes["jpegPhoto"],
+            vec![vec![0xff, 0xd8], vec![0x89, 0x50]]
+        );
+        assert_eq!(merged.binary_attributes["objectGUID"], vec![vec![0; 16]]);
+    }
+
+    #[test]
+    fn test_single_valued_conflict_keeps_both() {
+        let schema = schema_with_single("displayName");
+        let a = entry("cn=a,dc=x", &[("displayName", &["Alice"])]);
+        let b = entry("cn=a,dc=x", &[("displayName", &["Alice B."])]);
+        let report = dedup_entries(vec![a, b], "conn", &MergeOptions::default(), Some(&schema));
+        let merged = &report.entries[0];
+        assert_eq!(
+            merged.entry.attributes["displayName"],
+            vec!["Alice", "Alice B."]
+        );
+        assert_eq!(merged.conflicts, vec!["displayName"]);
+        assert_eq!(report.conflict_count(), 1);
+    }
+
+    #[test]
+    fn test_value_case_sensitivity_is_configurable() {
+        let a = entry("cn=a,dc=x", &[("enabled", &["TRUE"])]);
+        let b = entry("cn=a,dc=x", &[("enabled", &["true"])]);
+
+        let strict = dedup_entries(
+            vec![a.clone(), b.clone()],
+            "conn",
+            &MergeOptions::default(),
+            None,
+        );
+        assert_eq!(strict.entries[0].entry.attributes["enabled"].len(), 2);
+
+        let relaxed = dedup_entries(
+            vec![a, b],
+            "conn",
+            &MergeOptions {
+                case_insensitive_values: true,
+            },
+            None,
+        );
+        assert_eq!(relaxed.entries[0].entry.attributes["enabled"], vec!["TRUE"]);
+    }
+
+    #[test]
+    fn test_same_dn_from_different_sources_stays_separate() {
+        let e = entry("cn=a,dc=x", &[("cn", &["a"])]);
+        let report = merge_entries(
+            vec![("one".to_string(), e.clone()), ("two".to_string(), e)],
+            &MergeOptions::default(),
+            None,
+        );
+        assert_eq!(report.entries.len(), 2);
+        assert_eq!(report.duplicates_merged, 0);
+        assert_eq!(report.entries[1].source, "two");
+    }
+
+    #[test]
+    fn test_dedup_index_matches_batch_dedup() {
+        let schema = schema_with_single("displayName");
+        let rows = vec![
+            entry("cn=a,dc=x", &[("displayName", &["Alice"])]),
+            entry("cn=b,dc=x", &[("cn", &["b"])]),
+            entry("CN=a, dc=x", &[("displayName", &["Alice B."])]),
+            entry("cn=a,dc=x", &[("displayName", &["Al"])]),
+        ];
+        let options = MergeOptions::default();
+        let mut index = DedupIndex::default();
+        let mut entries = Vec::new();
+        // Fed in two batches
+        for batch in rows.clone().chunks(2) {
+            for e in batch {
+                index.push(&mut entries, e.clone(), &options, Some(&schema));
+            }
+        }
+        let report = dedup_entries(rows, "conn", &options, Some(&schema));
+        assert_eq!(index.duplicates_merged, report.duplicates_merged);
+        assert_eq!(index.conflict_count(), report.conflict_count());
+        let expected = report.into_entries();
+        assert_eq!(entries.len(), expected.len());
+        for (got, want) in entries.iter().zip(&expected) {
+            assert_eq!(got.dn, want.dn);
+            assert_eq!(got.attributes, want.attributes);
+        }
+    }
+}
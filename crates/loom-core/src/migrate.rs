@@ -0,0 +1,330 @@
+//! Moving values from one attribute to another across many entries.
+//!
+//! Planning is done up front over the loaded entries so every change can
+//! be previewed before anything is sent to the server. Each planned
+//! change becomes a list of [`BulkMod`]s applied with
+//! [`LdapConnection::bulk_apply`](crate::connection::LdapConnection::bulk_apply).
+
+use crate::bulk::BulkMod;
+use crate::diff::{diff_entries, AttributeChange};
+use crate::entry::LdapEntry;
+
+/// What to do when the target attribute already has values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CollisionPolicy {
+    /// Keep the target's values and add the source values not already there.
+    Append,
+    /// Leave the entry untouched.
+    Skip,
+    /// Drop the target's values in favour of the source values.
+    Replace,
+}
+
+impl CollisionPolicy {
+    pub fn label(&self) -> &'static str {
+        match self {
+            CollisionPolicy::Append => "Append",
+            CollisionPolicy::Skip => "Skip entry",
+            CollisionPolicy::Replace => "Replace",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            CollisionPolicy::Append => CollisionPolicy::Skip,
+            CollisionPolicy::Skip => CollisionPolicy::Replace,
+            CollisionPolicy::Replace => CollisionPolicy::Append,
+        }
+    }
+}
+
+/// A request to move `source` values into `target`.
+#[derive(Debug, Clone)]
+pub struct AttributeMigration {
+    pub source: String,
+    pub target: String,
+    pub collision: CollisionPolicy,
+    /// Remove the source attribute once its values are copied.
+    pub delete_source: bool,
+}
+
+/// The planned change for one entry.
+#[derive(Debug, Clone)]
+pub struct MigrationStep {
+    pub dn: String,
+    /// Before/after differences, for the preview.
+    pub changes: Vec<AttributeChange>,
+    pub mods: Vec<BulkMod>,
+}
+
+/// Every entry's planned change, and the entries left alone with why.
+#[derive(Debug, Clone, Default)]
+pub struct MigrationPlan {
+    pub steps: Vec<MigrationStep>,
+    pub skipped: Vec<(String, String)>, // (dn, reason)
+}
+
+fn values<'a>(entry: &'a LdapEntry, attr: &str) -> &'a [String] {
+    entry
+        .attributes
+        .iter()
+        .find(|(k, _)| k.eq_ignore_ascii_case(attr))
+        .map(|(_, v)| v.as_slice())
+        .unwrap_or_default()
+}
+
+/// Plan moving `migration.source` into `migration.target` on each entry.
+/// Entries without source values, and entries the collision policy tells
+/// to skip, end up in `skipped`.
+pub fn plan_migration(
+    entries: &[LdapEntry],
+    migration: &AttributeMigration,
+) -> Result<MigrationPlan, String> {
+    let source = migration.source.trim();
+    let target = migration.target.trim();
+    if source.is_empty() || target.is_empty() {
+        return Err("Source and target attributes are required".to_string());
+    }
+    if source.eq_ignore_ascii_case(target) {
+        return Err("Source and target must be different attributes".to_string());
+    }
+
+    let mut plan = MigrationPlan::default();
+    for entry in entries {
+        let moving = values(entry, source);
+        let existing = values(entry, target);
+        if moving.is_empty() {
+            plan.skipped
+                .push((entry.dn.clone(), format!("no {} values", source)));
+            continue;
+        }
+
+        let mut mods = Vec::new();
+        let new_values: Vec<&String> = if existing.is_empty() {
+            moving.iter().collect()
+        } else {
+            match migration.collision {
+                CollisionPolicy::Skip => {
+                    plan.skipped
+                        .push((entry.dn.clone(), format!("{} already has values", target)));
+                    continue;
+                }
+                CollisionPolicy::Append => {
+                    moving.iter().filter(|v| !existing.contains(v)).collect()
+                }
+                CollisionPolicy::Replace => {
+                    mods.push(BulkMod::DeleteAttribute {
+                        attr: target.to_string(),
+                    });
+                    moving.iter().collect()
+                }
+            }
+        };
+        let mut added: Vec<&String> = Vec::new();
+        for value in new_values {
+            // Multi-valued sources may repeat a value
+            if !added.contains(&value) {
+                added.push(value);
+                mods.push(BulkMod::AddValue {
+                    attr: target.to_string(),
+                    value: value.clone(),
+                });
+            }
+        }
+        if migration.delete_source {
+            mods.push(BulkMod::DeleteAttribute {
+                attr: source.to_string(),
+            });
+        }
+
+        let after = apply_to_copy(entry, &mods);
+        let changes = diff_entries(entry, &after).changes();
+        if changes.is_empty() {
+            plan.skipped.push((
+                entry.dn.clone(),
+                format!("{} already holds every value", target),
+            ));
+            continue;
+        }
+        plan.steps.push(MigrationStep {
+            dn: entry.dn.clone(),
+            changes,
+            mods,
+        });
+    }
+    Ok(plan)
+}
+
+/// The entry as it would read after `mods`, for the preview.
+fn apply_to_copy(entry: &LdapEntry, mods: &[BulkMod]) -> LdapEntry {
+    let mut after = entry.clone();
+    let key = |after: &LdapEntry, attr: &str| {
+        after
+            .attributes
+            .keys()
+            .find(|k| k.eq_ignore_ascii_case(attr))
+            .cloned()
+    };
+    for m in mods {
+        match m {
+            BulkMod::DeleteAttribute { attr } => {
+                if let Some(k) = key(&after, attr) {
+                    after.attributes.remove(&k);
+                }
+            }
+            BulkMod::AddValue { attr, value } => {
+                let k = key(&after, attr).unwrap_or_else(|| attr.clone());
+                after.attributes.entry(k).or_default().push(value.clone());
+            }
+            BulkMod::ReplaceAttribute { attr, value } => {
+                let k = key(&after, attr).unwrap_or_else(|| attr.clone());
+                after.attributes.insert(k, vec![value.clone()]);
+            }
+            BulkMod::ReplaceValues { attr, values } => {
+                let k = key(&after, attr).unwrap_or_else(|| attr.clone());
+                after.attributes.insert(k, values.clone());
+            }
+            BulkMod::DeleteValue { attr, value } => {
+                if let Some(vals) = key(&after, attr).and_then(|k| after.attributes.get_mut(&k)) {
+                    vals.retain(|v| v != value);
+                }
+            }
+        }
+    }
+    after.attributes.retain(|_, v| !v.is_empty());
+    after
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn entry(dn: &str, attrs: &[(&str, &[&str])]) -> LdapEntry {
+        let attributes: BTreeMap<String, Vec<String>> = attrs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.iter().map(|s| s.to_string()).collect()))
+            .collect();
+        LdapEntry::new(dn.to_string(), attributes)
+    }
+
+    fn migration(collision: CollisionPolicy, delete_source: bool) -> AttributeMigration {
+        AttributeMigration {
+            source: "telephoneNumber".to_string(),
+            target: "mobile".to_string(),
+            collision,
+            delete_source,
+        }
+    }
+
+    fn entries() -> Vec<LdapEntry> {
+        vec![
+            // Target empty, multi-valued source
+            entry(
+                "uid=alice,dc=example,dc=com",
+                &[("telephoneNumber", &["555-0100", "555-0101"])],
+            ),
+            // Target already set, one value shared with the source
+            entry(
+                "uid=bob,dc=example,dc=com",
+                &[
+                    ("telephonenumber", &["555-0200", "555-0201"]),
+                    ("mobile", &["555-0200", "555-0299"]),
+                ],
+            ),
+            // Nothing to move
+            entry("uid=carol,dc=example,dc=com", &[("mobile", &["555-0300"])]),
+        ]
+    }
+
+    fn lines(step: &MigrationStep) -> Vec<String> {
+        step.changes.iter().map(|c| c.to_string()).collect()
+    }
+
+    #[test]
+    fn test_append_keeps_target_values() {
+        let plan = plan_migration(&entries(), &migration(CollisionPolicy::Append, true)).unwrap();
+        assert_eq!(plan.steps.len(), 2);
+        assert_eq!(
+            lines(&plan.steps[0]),
+            vec![
+                "+ mobile: \"555-0100\", \"555-0101\"",
+                "- telephoneNumber: \"555-0100\", \"555-0101\"",
+            ]
+        );
+        assert_eq!(
+            lines(&plan.steps[1]),
+            vec![
+                "+ mobile: \"555-0201\"",
+                "- telephonenumber: \"555-0200\", \"555-0201\"",
+            ]
+        );
+        assert_eq!(plan.steps[1].mods.len(), 2);
+        assert_eq!(
+            plan.skipped,
+            vec![(
+                "uid=carol,dc=example,dc=com".to_string(),
+                "no telephoneNumber values".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_skip_leaves_entries_with_target_values() {
+        let plan = plan_migration(&entries(), &migration(CollisionPolicy::Skip, false)).unwrap();
+        assert_eq!(plan.steps.len(), 1);
+        assert_eq!(plan.steps[0].dn, "uid=alice,dc=example,dc=com");
+        // The source stays when it is not deleted
+        assert_eq!(
+            lines(&plan.steps[0]),
+            vec!["+ mobile: \"555-0100\", \"555-0101\""]
+        );
+        assert_eq!(plan.skipped.len(), 2);
+        assert_eq!(plan.skipped[0].1, "mobile already has values");
+    }
+
+    #[test]
+    fn test_replace_drops_target_values() {
+        let plan = plan_migration(&entries(), &migration(CollisionPolicy::Replace, true)).unwrap();
+        let bob = &plan.steps[1];
+        assert_eq!(
+            lines(bob),
+            vec![
+                "~ mobile: \"555-0299\" -> \"555-0201\"",
+                "- telephonenumber: \"555-0200\", \"555-0201\"",
+            ]
+        );
+        assert!(matches!(
+            &bob.mods[0],
+            BulkMod::DeleteAttribute { attr } if attr == "mobile"
+        ));
+        assert_eq!(bob.mods.len(), 4);
+    }
+
+    #[test]
+    fn test_no_op_and_invalid_requests() {
+        let same = vec![entry(
+            "uid=dave,dc=example,dc=com",
+            &[("telephoneNumber", &["1", "1"]), ("mobile", &["1"])],
+        )];
+        let plan = plan_migration(&same, &migration(CollisionPolicy::Append, false)).unwrap();
+        assert!(plan.steps.is_empty());
+        assert_eq!(plan.skipped[0].1, "mobile already holds every value");
+
+        // Duplicate source values are only added once
+        let plan = plan_migration(&same, &migration(CollisionPolicy::Replace, false)).unwrap();
+        assert_eq!(plan.steps.len(), 0);
+        let dup = vec![entry(
+            "uid=erin,dc=example,dc=com",
+            &[("telephoneNumber", &["1", "1"])],
+        )];
+        let plan = plan_migration(&dup, &migration(CollisionPolicy::Append, false)).unwrap();
+        assert_eq!(plan.steps[0].mods.len(), 1);
+
+        let mut bad = migration(CollisionPolicy::Append, false);
+        bad.target = "TelephoneNumber".to_string();
+        assert!(plan_migration(&same, &bad).is_err());
+        bad.target = " ".to_string();
+        assert!(plan_migration(&same, &bad).is_err());
+    }
+}
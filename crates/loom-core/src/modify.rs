@@ -1,13 +1,34 @@
 use std::collections::HashSet;
 
-use ldap3::controls::RelaxRules;
-use ldap3::Mod;
+use ldap3::controls::{RawControl, RelaxRules, TxnSpec};
+use ldap3::{Ldap, Mod};
 use tracing::{debug, info};
 
+use crate::audit::{add_summary, modify_summary, rename_summary};
 use crate::connection::LdapConnection;
 use crate::error::CoreError;
+use crate::redact;
 
 impl LdapConnection {
+    /// The handle to send a write with, carrying the Relax Rules control
+    /// when enabled and the transaction control while a transaction is open.
+    /// Fails fast while the connection is lost.
+    fn writer(&mut self) -> Result<&mut Ldap, CoreError> {
+        self.check_health()?;
+        let mut controls: Vec<RawControl> = Vec::new();
+        if self.settings.relax_rules {
+            controls.push(RelaxRules.into());
+        }
+        if let Some(txn_id) = &self.txn_id {
+            controls.push(TxnSpec { txn_id }.into());
+        }
+        if controls.is_empty() {
+            Ok(&mut self.ldap)
+        } else {
+            Ok(self.ldap.with_controls(controls))
+        }
+    }
+
     /// Modify an entry's attributes.
     /// When `relax_rules` is enabled in connection settings, sends the
     /// Relax Rules control to bypass server-side schema violations from
@@ -22,56 +43,50 @@ impl LdapConnection {
             dn, self.settings.relax_rules
         );
         for m in &mods {
-            match m {
-                Mod::Add(attr, vals) => debug!("  mod ADD attr={} vals={:?}", attr, vals),
-                Mod::Delete(attr, vals) => debug!("  mod DELETE attr={} vals={:?}", attr, vals),
-                Mod::Replace(attr, vals) => debug!("  mod REPLACE attr={} vals={:?}", attr, vals),
-                Mod::Increment(attr, vals) => {
-                    debug!("  mod INCREMENT attr={} vals={:?}", attr, vals)
-                }
-            }
+            debug!("  mod {}", redact::modification(m));
         }
 
-        let result = if self.settings.relax_rules {
-            self.ldap
-                .with_controls(vec![RelaxRules.into()])
-                .modify(dn, mods)
-                .await
-                .map_err(CoreError::Ldap)?
-        } else {
-            self.ldap.modify(dn, mods).await.map_err(CoreError::Ldap)?
-        };
-
-        debug!("modify_entry result rc={} text={}", result.rc, result.text);
-
-        if result.rc != 0 {
-            return Err(CoreError::ModifyFailed(format!(
-                "Modify {} failed rc={}: {}",
-                dn, result.rc, result.text
-            )));
-        }
+        let summary = self.audit.as_ref().map(|_| modify_summary(&mods));
+        let result = self
+            .writer()?
+            .modify(dn, mods)
+            .await
+            .map_err(CoreError::Ldap)
+            .and_then(|result| {
+                debug!("modify_entry result rc={} text={}", result.rc, result.text);
+                if result.rc != 0 {
+                    return Err(CoreError::ModifyFailed(format!(
+                        "Modify {} failed rc={}: {}",
+                        dn, result.rc, result.text
+                    )));
+                }
+                Ok(())
+            });
+        self.audit_write(dn, || summary.unwrap_or_default(), &result);
+        result?;
 
         info!("Modified entry: {}", dn);
         Ok(())
     }
 
-    /// Replace a single attribute value.
+    /// Replace a single attribute value, leaving the attribute's other
+    /// values as they are. See [`replace_value_mods`].
     pub async fn replace_attribute_value(
         &mut self,
         dn: &str,
         attr: &str,
-        _old_value: &str,
+        old_value: &str,
         new_value: &str,
     ) -> Result<(), CoreError> {
         debug!(
-            "replace_attribute_value dn={} attr={} new_value={}",
-            dn, attr, new_value
+            "replace_attribute_value dn={} attr={} old_value={} new_value={}",
+            dn,
+            attr,
+            redact::value(attr, old_value),
+            redact::value(attr, new_value)
         );
-        let mods = vec![Mod::Replace(
-            attr.to_string(),
-            HashSet::from([new_value.to_string()]),
-        )];
-        self.modify_entry(dn, mods).await
+        self.modify_entry(dn, replace_value_mods(attr, old_value, new_value))
+            .await
     }
 
     /// Add a value to an attribute.
@@ -83,7 +98,9 @@ impl LdapConnection {
     ) -> Result<(), CoreError> {
         debug!(
             "add_attribute_value dn={} attr={} value={}",
-            dn, attr, value
+            dn,
+            attr,
+            redact::value(attr, value)
         );
         let mods = vec![Mod::Add(
             attr.to_string(),
@@ -119,7 +136,9 @@ impl LdapConnection {
     ) -> Result<(), CoreError> {
         debug!(
             "delete_attribute_value dn={} attr={} value={}",
-            dn, attr, value
+            dn,
+            attr,
+            redact::value(attr, value)
         );
         let mods = vec![Mod::Delete(
             attr.to_string(),
@@ -128,39 +147,57 @@ impl LdapConnection {
         self.modify_entry(dn, mods).await
     }
 
-    /// Add a new entry with the given DN and attributes.
+    /// Add a new entry with the given DN and attributes, text and binary.
     pub async fn add_entry(
         &mut self,
         dn: &str,
         attrs: Vec<(String, HashSet<String>)>,
+        binary: Vec<(String, HashSet<Vec<u8>>)>,
     ) -> Result<(), CoreError> {
         debug!(
             "add_entry dn={} relax_rules={}",
             dn, self.settings.relax_rules
         );
         for (attr, vals) in &attrs {
-            debug!("  attr={} vals={:?}", attr, vals);
+            debug!("  attr={} vals={}", attr, redact::values(attr, vals));
         }
-
-        let result = if self.settings.relax_rules {
-            self.ldap
-                .with_controls(vec![RelaxRules.into()])
-                .add(dn, attrs)
-                .await
-                .map_err(CoreError::Ldap)?
-        } else {
-            self.ldap.add(dn, attrs).await.map_err(CoreError::Ldap)?
-        };
-
-        debug!("add_entry result rc={} text={}", result.rc, result.text);
-
-        if result.rc != 0 {
-            return Err(CoreError::AddFailed(format!(
-                "Add {} failed rc={}: {}",
-                dn, result.rc, result.text
-            )));
+        for (attr, vals) in &binary {
+            debug!("  attr={} vals=[<binary>; {}]", attr, vals.len());
         }
 
+        let summary = self.audit.as_ref().map(|_| {
+            add_summary(
+                attrs
+                    .iter()
+                    .map(|(attr, _)| attr)
+                    .chain(binary.iter().map(|(attr, _)| attr)),
+            )
+        });
+        // ldap3 takes names and values of one type
+        let attrs: Vec<(Vec<u8>, HashSet<Vec<u8>>)> = attrs
+            .into_iter()
+            .map(|(attr, vals)| (attr, vals.into_iter().map(String::into_bytes).collect()))
+            .chain(binary)
+            .map(|(attr, vals)| (attr.into_bytes(), vals))
+            .collect();
+        let result = self
+            .writer()?
+            .add(dn, attrs)
+            .await
+            .map_err(CoreError::Ldap)
+            .and_then(|result| {
+                debug!("add_entry result rc={} text={}", result.rc, result.text);
+                if result.rc != 0 {
+                    return Err(CoreError::AddFailed(format!(
+                        "Add {} failed rc={}: {}",
+                        dn, result.rc, result.text
+                    )));
+                }
+                Ok(())
+            });
+        self.audit_write(dn, || summary.unwrap_or_default(), &result);
+        result?;
+
         info!("Added entry: {}", dn);
         Ok(())
     }
@@ -172,26 +209,111 @@ impl LdapConnection {
             dn, self.settings.relax_rules
         );
 
-        let result = if self.settings.relax_rules {
-            self.ldap
-                .with_controls(vec![RelaxRules.into()])
-                .delete(dn)
-                .await
-                .map_err(CoreError::Ldap)?
-        } else {
-            self.ldap.delete(dn).await.map_err(CoreError::Ldap)?
-        };
+        let result = self
+            .writer()?
+            .delete(dn)
+            .await
+            .map_err(CoreError::Ldap)
+            .and_then(|result| {
+                debug!("delete_entry result rc={} text={}", result.rc, result.text);
+                if result.rc != 0 {
+                    return Err(CoreError::DeleteFailed(format!(
+                        "Delete {} failed rc={}: {}",
+                        dn, result.rc, result.text
+                    )));
+                }
+                Ok(())
+            });
+        self.audit_write(dn, || "delete".to_string(), &result);
+        result?;
 
-        debug!("delete_entry result rc={} text={}", result.rc, result.text);
+        info!("Deleted entry: {}", dn);
+        Ok(())
+    }
 
-        if result.rc != 0 {
-            return Err(CoreError::DeleteFailed(format!(
-                "Delete {} failed rc={}: {}",
-                dn, result.rc, result.text
-            )));
-        }
+    /// Rename an entry: give it `new_rdn` and, with `new_superior`, move
+    /// it under a new parent.
+    pub async fn rename_entry(
+        &mut self,
+        dn: &str,
+        new_rdn: &str,
+        delete_old_rdn: bool,
+        new_superior: Option<&str>,
+    ) -> Result<(), CoreError> {
+        debug!(
+            "rename_entry dn={} new_rdn={} delete_old_rdn={} new_superior={:?}",
+            dn, new_rdn, delete_old_rdn, new_superior
+        );
 
-        info!("Deleted entry: {}", dn);
+        let result = self
+            .writer()?
+            .modifydn(dn, new_rdn, delete_old_rdn, new_superior)
+            .await
+            .map_err(CoreError::Ldap)
+            .and_then(|result| {
+                debug!("rename_entry result rc={} text={}", result.rc, result.text);
+                if result.rc != 0 {
+                    return Err(CoreError::ModifyFailed(format!(
+                        "Rename {} failed rc={}: {}",
+                        dn, result.rc, result.text
+                    )));
+                }
+                Ok(())
+            });
+        self.audit_write(
+            dn,
+            || rename_summary(new_rdn, delete_old_rdn, new_superior),
+            &result,
+        );
+        result?;
+
+        info!("Renamed entry: {} -> {}", dn, new_rdn);
         Ok(())
     }
 }
+
+/// The changes that turn one value of `attr` into another: delete the old
+/// value and add the new one in the same modify, so the other values of a
+/// multi-valued attribute survive. A value the entry no longer has fails
+/// the delete instead of being lost. Without an old value the attribute
+/// is replaced outright.
+pub fn replace_value_mods(attr: &str, old_value: &str, new_value: &str) -> Vec<Mod<String>> {
+    let new = HashSet::from([new_value.to_string()]);
+    if old_value.is_empty() {
+        return vec![Mod::Replace(attr.to_string(), new)];
+    }
+    vec![
+        Mod::Delete(attr.to_string(), HashSet::from([old_value.to_string()])),
+        Mod::Add(attr.to_string(), new),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_value_mods_keeps_other_values() {
+        let mods = replace_value_mods("mail", "old@example.com", "new@example.com");
+        assert_eq!(
+            mods,
+            vec![
+                Mod::Delete(
+                    "mail".to_string(),
+                    HashSet::from(["old@example.com".to_string()])
+                ),
+                Mod::Add(
+                    "mail".to_string(),
+                    HashSet::from(["new@example.com".to_string()])
+                ),
+            ]
+        );
+        assert_eq!(
+            replace_value_mods("description", "", "first"),
+            vec![Mod::Replace(
+                "description".to_string(),
+                HashSet::from(["first".to_string()])
+            )]
+        );
+    }
+}
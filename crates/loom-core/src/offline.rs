@@ -1,6 +1,9 @@
-use crate::dn;
+use ldap3::Scope;
+
+use crate::dn::{is_ancestor, normalize, parent_dn, Dn};
 use crate::entry::LdapEntry;
 use crate::error::CoreError;
+use crate::filter::Filter;
 use crate::import::ldif;
 use crate::schema::{
     AttributeSyntax, AttributeTypeInfo, ObjectClassInfo, ObjectClassKind, SchemaCache,
@@ -36,6 +39,16 @@ impl OfflineDirectory {
         })
     }
 
+    /// An offline directory over entries captured elsewhere, such as a
+    /// snapshot, with the schema that came with them.
+    pub fn new(entries: Vec<LdapEntry>, base_dn: String, schema: SchemaCache) -> Self {
+        Self {
+            entries,
+            base_dn,
+            schema,
+        }
+    }
+
     pub fn base_dn(&self) -> &str {
         &self.base_dn
     }
@@ -46,13 +59,16 @@ impl OfflineDirectory {
 
     /// Return immediate children of the given parent DN.
     pub fn children(&self, parent_dn: &str) -> Vec<TreeNode> {
-        let parent_lower = parent_dn.to_lowercase();
+        let Ok(parent) = Dn::parse(parent_dn) else {
+            return Vec::new();
+        };
         self.entries
             .iter()
             .filter(|e| {
-                dn::parent_dn(&e.dn)
-                    .map(|p| p.to_lowercase() == parent_lower)
-                    .unwrap_or(false)
+                Dn::parse(&e.dn)
+                    .ok()
+                    .and_then(|dn| dn.parent())
+                    .is_some_and(|p| p == parent)
             })
             .map(|e| TreeNode::new(e.dn.clone()))
             .collect()
@@ -131,6 +147,25 @@ impl OfflineDirectory {
             .cloned()
             .collect()
     }
+
+    /// Entries within `scope` of `base_dn` that match `filter`, evaluated
+    /// the way [`Filter::matches`] does.
+    pub fn find(&self, base_dn: &str, scope: Scope, filter: &Filter) -> Vec<LdapEntry> {
+        let base = normalize(base_dn);
+        self.entries
+            .iter()
+            .filter(|e| {
+                let dn = normalize(&e.dn);
+                let in_scope = match scope {
+                    Scope::Base => dn == base,
+                    Scope::OneLevel => parent_dn(&dn) == Some(base.as_str()),
+                    Scope::Subtree => dn == base || is_ancestor(&dn, &base),
+                };
+                in_scope && filter.matches(e)
+            })
+            .cloned()
+            .collect()
+    }
 }
 
 /// Extract the search value from a simple LDAP filter string.
@@ -338,6 +373,7 @@ fn build_example_schema() -> SchemaCache {
             oid: oid.to_string(),
             names: vec![name.to_string()],
             description: None,
+            superior: None,
             syntax,
             single_value,
             no_user_modification,
@@ -443,7 +479,7 @@ fn build_example_schema() -> SchemaCache {
             oid: oid.to_string(),
             names: vec![name.to_string()],
             description: None,
-            superior: superior.map(|s| s.to_string()),
+            superiors: superior.iter().map(|s| s.to_string()).collect(),
             kind,
             must: must.iter().map(|s| s.to_string()).collect(),
             may: may.iter().map(|s| s.to_string()).collect(),
@@ -581,4 +617,24 @@ mod tests {
         assert_eq!(extract_filter_value("Sarah"), "Sarah");
         assert_eq!(extract_filter_value("(objectClass=*)"), "");
     }
+
+    #[test]
+    fn test_find_evaluates_filter_in_scope() {
+        let dir = OfflineDirectory::load_embedded();
+        let filter = Filter::parse("(member=cn=Administrator,dc=contoso,dc=com)").unwrap();
+        let groups = dir.find("ou=Groups,dc=contoso,dc=com", Scope::Subtree, &filter);
+        assert!(groups
+            .iter()
+            .any(|g| g.dn == "cn=Domain Admins,ou=Groups,dc=contoso,dc=com"));
+        assert!(dir
+            .find("ou=Users,dc=contoso,dc=com", Scope::Subtree, &filter)
+            .is_empty());
+
+        let base = dir.find(
+            "cn=Domain Admins,ou=Groups,dc=contoso,dc=com",
+            Scope::Base,
+            &Filter::present("objectClass"),
+        );
+        assert_eq!(base.len(), 1);
+    }
 }
@@ -0,0 +1,290 @@
+//! Names for the OIDs a root DSE lists under `supportedControl`,
+//! `supportedExtension`, `supportedFeatures` and `supportedCapabilities`.
+
+/// Simple paged results control (RFC 2696).
+pub const PAGED_RESULTS: &str = "1.2.840.113556.1.4.319";
+/// Server-side sort request control (RFC 2891).
+pub const SERVER_SIDE_SORT: &str = "1.2.840.113556.1.4.473";
+/// Virtual list view request control.
+pub const VIRTUAL_LIST_VIEW: &str = "2.16.840.1.113730.3.4.9";
+/// Virtual list view response control.
+pub const VIRTUAL_LIST_VIEW_RESPONSE: &str = "2.16.840.1.113730.3.4.10";
+/// Password policy control (draft-behera-ldap-password-policy).
+pub const PASSWORD_POLICY: &str = "1.3.6.1.4.1.42.2.27.8.5.1";
+/// Password modify extended operation (RFC 3062).
+pub const PASSWORD_MODIFY: &str = "1.3.6.1.4.1.4203.1.11.1";
+/// "Who am I?" extended operation (RFC 4532).
+pub const WHOAMI: &str = "1.3.6.1.4.1.4203.1.11.3";
+
+/// What an OID in the root DSE stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OidKind {
+    Control,
+    Extension,
+    Feature,
+}
+
+/// Known OIDs, with what they are and a short name.
+const KNOWN: &[(&str, OidKind, &str)] = &[
+    // Controls
+    (PAGED_RESULTS, OidKind::Control, "Paged results"),
+    (SERVER_SIDE_SORT, OidKind::Control, "Server-side sort"),
+    (
+        "1.2.840.113556.1.4.474",
+        OidKind::Control,
+        "Server-side sort response",
+    ),
+    (
+        VIRTUAL_LIST_VIEW,
+        OidKind::Control,
+        "Virtual list view (VLV)",
+    ),
+    (
+        VIRTUAL_LIST_VIEW_RESPONSE,
+        OidKind::Control,
+        "Virtual list view response",
+    ),
+    (PASSWORD_POLICY, OidKind::Control, "Password policy"),
+    ("2.16.840.1.113730.3.4.2", OidKind::Control, "ManageDsaIT"),
+    (
+        "2.16.840.1.113730.3.4.3",
+        OidKind::Control,
+        "Persistent search",
+    ),
+    (
+        "2.16.840.1.113730.3.4.4",
+        OidKind::Control,
+        "Password expired",
+    ),
+    (
+        "2.16.840.1.113730.3.4.5",
+        OidKind::Control,
+        "Password expiring",
+    ),
+    (
+        "2.16.840.1.113730.3.4.7",
+        OidKind::Control,
+        "Entry change notification",
+    ),
+    (
+        "2.16.840.1.113730.3.4.12",
+        OidKind::Control,
+        "Proxied authorization (v1)",
+    ),
+    (
+        "2.16.840.1.113730.3.4.18",
+        OidKind::Control,
+        "Proxied authorization (v2)",
+    ),
+    (
+        "2.16.840.1.113730.3.4.16",
+        OidKind::Control,
+        "Authorization identity request",
+    ),
+    (
+        "2.16.840.1.113730.3.4.15",
+        OidKind::Control,
+        "Authorization identity response",
+    ),
+    ("1.3.6.1.1.12", OidKind::Control, "Assertion"),
+    ("1.3.6.1.1.13.1", OidKind::Control, "Pre-read"),
+    ("1.3.6.1.1.13.2", OidKind::Control, "Post-read"),
+    (
+        "1.3.6.1.1.21.2",
+        OidKind::Control,
+        "Transaction specification",
+    ),
+    ("1.3.6.1.1.22", OidKind::Control, "Don't use copy"),
+    (
+        "1.2.826.0.1.3344810.2.3",
+        OidKind::Control,
+        "Matched values",
+    ),
+    (
+        "1.3.6.1.4.1.4203.1.9.1.1",
+        OidKind::Control,
+        "Content synchronization (syncrepl)",
+    ),
+    ("1.3.6.1.4.1.4203.1.10.1", OidKind::Control, "Subentries"),
+    ("1.3.6.1.4.1.4203.1.10.2", OidKind::Control, "No-op"),
+    ("1.3.6.1.4.1.4203.666.5.12", OidKind::Control, "Relax rules"),
+    ("1.3.6.1.4.1.1466.29539.12", OidKind::Control, "Chaining"),
+    ("1.2.840.113556.1.4.417", OidKind::Control, "Show deleted"),
+    (
+        "1.2.840.113556.1.4.521",
+        OidKind::Control,
+        "Cross-domain move",
+    ),
+    (
+        "1.2.840.113556.1.4.528",
+        OidKind::Control,
+        "Change notification",
+    ),
+    ("1.2.840.113556.1.4.529", OidKind::Control, "Extended DN"),
+    ("1.2.840.113556.1.4.619", OidKind::Control, "Lazy commit"),
+    (
+        "1.2.840.113556.1.4.801",
+        OidKind::Control,
+        "Security descriptor flags",
+    ),
+    ("1.2.840.113556.1.4.805", OidKind::Control, "Tree delete"),
+    ("1.2.840.113556.1.4.841", OidKind::Control, "DirSync"),
+    ("1.2.840.113556.1.4.1339", OidKind::Control, "Domain scope"),
+    (
+        "1.2.840.113556.1.4.1340",
+        OidKind::Control,
+        "Search options",
+    ),
+    (
+        "1.2.840.113556.1.4.1413",
+        OidKind::Control,
+        "Permissive modify",
+    ),
+    (
+        "1.2.840.113556.1.4.1504",
+        OidKind::Control,
+        "Attribute scoped query",
+    ),
+    ("1.2.840.113556.1.4.1852", OidKind::Control, "Quota"),
+    ("1.2.840.113556.1.4.2064", OidKind::Control, "Show recycled"),
+    (
+        "1.2.840.113556.1.4.2065",
+        OidKind::Control,
+        "Show deactivated links",
+    ),
+    // Extended operations
+    ("1.3.6.1.4.1.1466.20037", OidKind::Extension, "StartTLS"),
+    (PASSWORD_MODIFY, OidKind::Extension, "Password modify"),
+    (WHOAMI, OidKind::Extension, "Who am I?"),
+    ("1.3.6.1.1.8", OidKind::Extension, "Cancel"),
+    (
+        crate::transaction::TXN_START_OID,
+        OidKind::Extension,
+        "Start transaction",
+    ),
+    ("1.3.6.1.1.21.3", OidKind::Extension, "End transaction"),
+    (
+        "1.3.6.1.4.1.1466.101.119.1",
+        OidKind::Extension,
+        "Dynamic refresh",
+    ),
+    (
+        "1.2.840.113556.1.4.1781",
+        OidKind::Extension,
+        "Fast concurrent bind",
+    ),
+    (
+        "1.2.840.113556.1.4.2212",
+        OidKind::Extension,
+        "Batch request",
+    ),
+    // Features and capabilities
+    ("1.3.6.1.1.14", OidKind::Feature, "Modify-increment"),
+    (
+        "1.3.6.1.4.1.4203.1.5.1",
+        OidKind::Feature,
+        "All operational attributes (+)",
+    ),
+    (
+        "1.3.6.1.4.1.4203.1.5.2",
+        OidKind::Feature,
+        "Attributes by objectClass (@)",
+    ),
+    (
+        "1.3.6.1.4.1.4203.1.5.3",
+        OidKind::Feature,
+        "Absolute true and false filters",
+    ),
+    ("1.3.6.1.4.1.4203.1.5.4", OidKind::Feature, "Language tags"),
+    (
+        "1.3.6.1.4.1.4203.1.5.5",
+        OidKind::Feature,
+        "Language ranges",
+    ),
+    (
+        "1.2.840.113556.1.4.800",
+        OidKind::Feature,
+        "Active Directory",
+    ),
+    (
+        "1.2.840.113556.1.4.1670",
+        OidKind::Feature,
+        "Active Directory (Windows Server 2003 or later)",
+    ),
+    (
+        "1.2.840.113556.1.4.1791",
+        OidKind::Feature,
+        "Active Directory LDAP integration",
+    ),
+    (
+        "1.2.840.113556.1.4.1935",
+        OidKind::Feature,
+        "Active Directory (Windows Server 2008 or later)",
+    ),
+    (
+        "1.2.840.113556.1.4.1851",
+        OidKind::Feature,
+        "Active Directory Lightweight Directory Services",
+    ),
+];
+
+/// The name of a well-known control, extended operation or feature.
+pub fn describe(oid: &str) -> Option<&'static str> {
+    lookup(oid).map(|(_, _, name)| *name)
+}
+
+/// Whether a well-known OID is a control, an extended operation or a
+/// feature.
+pub fn kind(oid: &str) -> Option<OidKind> {
+    lookup(oid).map(|(_, kind, _)| *kind)
+}
+
+fn lookup(oid: &str) -> Option<&'static (&'static str, OidKind, &'static str)> {
+    let oid = oid.trim();
+    KNOWN.iter().find(|(known, _, _)| *known == oid)
+}
+
+/// `Name (oid)` for a known OID, the OID itself otherwise.
+pub fn label(oid: &str) -> String {
+    match describe(oid) {
+        Some(name) => format!("{} ({})", name, oid),
+        None => oid.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_describe_known_oids() {
+        assert_eq!(describe(PAGED_RESULTS), Some("Paged results"));
+        assert_eq!(
+            describe("2.16.840.1.113730.3.4.9"),
+            Some("Virtual list view (VLV)")
+        );
+        assert_eq!(describe(" 1.3.6.1.4.1.4203.1.11.3 "), Some("Who am I?"));
+        assert_eq!(kind(WHOAMI), Some(OidKind::Extension));
+        assert_eq!(kind(PASSWORD_POLICY), Some(OidKind::Control));
+        assert_eq!(kind("1.3.6.1.1.14"), Some(OidKind::Feature));
+    }
+
+    #[test]
+    fn test_unknown_oids_stay_raw() {
+        assert_eq!(describe("1.2.3.4.5"), None);
+        assert_eq!(label("1.2.3.4.5"), "1.2.3.4.5");
+        assert_eq!(
+            label(SERVER_SIDE_SORT),
+            "Server-side sort (1.2.840.113556.1.4.473)"
+        );
+    }
+
+    #[test]
+    fn test_table_has_no_duplicates() {
+        let mut seen = HashSet::new();
+        for (oid, _, _) in KNOWN {
+            assert!(seen.insert(*oid), "{} listed twice", oid);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Opening files and their folders with the desktop's default handlers.
+//!
+//! Command construction is kept separate from running it so each
+//! platform's command line can be checked on any platform.
+
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Desktop conventions for opening files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Platform {
+    /// Linux and the BSDs, via `xdg-open`.
+    Linux,
+    MacOs,
+    Windows,
+}
+
+impl Platform {
+    pub fn current() -> Self {
+        if cfg!(target_os = "macos") {
+            Platform::MacOs
+        } else if cfg!(windows) {
+            Platform::Windows
+        } else {
+            Platform::Linux
+        }
+    }
+}
+
+/// A command line to launch, not yet run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpenCommand {
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl OpenCommand {
+    fn new(program: &str, args: Vec<String>) -> Self {
+        Self {
+            program: program.to_string(),
+            args,
+        }
+    }
+
+    /// Launch the command detached from the terminal. Only failure to
+    /// start is reported; the opener's own exit status is not awaited.
+    pub fn spawn(&self) -> std::io::Result<()> {
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()?;
+        // Reap the child so it does not linger as a zombie
+        std::thread::spawn(move || child.wait());
+        Ok(())
+    }
+}
+
+/// The command that opens `path` with its default application.
+pub fn open_command(platform: Platform, path: &Path) -> OpenCommand {
+    let path = path.display().to_string();
+    match platform {
+        Platform::Linux => OpenCommand::new("xdg-open", vec![path]),
+        Platform::MacOs => OpenCommand::new("open", vec![path]),
+        // `start` is a cmd builtin; its first quoted argument is the window title
+        Platform::Windows => OpenCommand::new(
+            "cmd",
+            vec!["/C".to_string(), "start".to_string(), String::new(), path],
+        ),
+    }
+}
+
+/// The command that shows `path` in the file manager, selected where the
+/// platform supports it. On Linux the containing folder is opened.
+pub fn reveal_command(platform: Platform, path: &Path) -> OpenCommand {
+    match platform {
+        Platform::Linux => {
+            let folder = match path.parent() {
+                Some(p) if !p.as_os_str().is_empty() => p,
+                _ => Path::new("."),
+            };
+            OpenCommand::new("xdg-open", vec![folder.display().to_string()])
+        }
+        Platform::MacOs => {
+            OpenCommand::new("open", vec!["-R".to_string(), path.display().to_string()])
+        }
+        Platform::Windows => {
+            OpenCommand::new("explorer", vec![format!("/select,{}", path.display())])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_command_per_platform() {
+        let path = Path::new("/home/ada/exports/people.csv");
+        assert_eq!(
+            open_command(Platform::Linux, path),
+            OpenCommand::new("xdg-open", vec!["/home/ada/exports/people.csv".to_string()])
+        );
+        assert_eq!(
+            open_command(Platform::MacOs, path),
+            OpenCommand::new("open", vec!["/home/ada/exports/people.csv".to_string()])
+        );
+        let windows = open_command(Platform::Windows, Path::new(r"C:\Users\Ada\people.xlsx"));
+        assert_eq!(windows.program, "cmd");
+        assert_eq!(
+            windows.args,
+            vec!["/C", "start", "", r"C:\Users\Ada\people.xlsx"]
+        );
+    }
+
+    #[test]
+    fn test_reveal_command_per_platform() {
+        let path = Path::new("/home/ada/exports/people.csv");
+        assert_eq!(
+            reveal_command(Platform::Linux, path).args,
+            vec!["/home/ada/exports"]
+        );
+        // A bare file name lives in the working directory
+        assert_eq!(
+            reveal_command(Platform::Linux, Path::new("people.csv")).args,
+            vec!["."]
+        );
+        assert_eq!(
+            reveal_command(Platform::MacOs, path),
+            OpenCommand::new(
+                "open",
+                vec!["-R".to_string(), "/home/ada/exports/people.csv".to_string()]
+            )
+        );
+        let windows = reveal_command(Platform::Windows, Path::new(r"C:\Users\Ada\people.xlsx"));
+        assert_eq!(windows.program, "explorer");
+        assert_eq!(windows.args, vec![r"/select,C:\Users\Ada\people.xlsx"]);
+    }
+}
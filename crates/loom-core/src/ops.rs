@@ -0,0 +1,153 @@
+//! The operations loom performs against a directory, as a trait so the
+//! same code can run against a live server or, in tests, against the
+//! in-memory directory in `testing`.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::pin::Pin;
+
+use ldap3::{Mod, Scope};
+
+use crate::connection::LdapConnection;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+
+/// Future returned by the [`DirectoryOps`] methods.
+pub type OpFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T, CoreError>> + Send + 'a>>;
+
+/// Callback receiving the pages of a paged search as they arrive.
+pub type PageSink<'a> = &'a mut (dyn FnMut(Vec<LdapEntry>) + Send);
+
+/// Search and update operations on a directory.
+pub trait DirectoryOps: Send {
+    fn search<'a>(
+        &'a mut self,
+        base_dn: &'a str,
+        scope: Scope,
+        filter: &'a str,
+        attrs: &'a [&'a str],
+    ) -> OpFuture<'a, Vec<LdapEntry>>;
+
+    /// Search page by page, handing each page to `on_page`. Returns the
+    /// total number of entries.
+    fn search_pages<'a>(
+        &'a mut self,
+        base_dn: &'a str,
+        scope: Scope,
+        filter: &'a str,
+        attrs: &'a [&'a str],
+        on_page: PageSink<'a>,
+    ) -> OpFuture<'a, usize>;
+
+    /// Add `dn` with its text attributes and, kept apart, its binary ones.
+    fn add_entry<'a>(
+        &'a mut self,
+        dn: &'a str,
+        attrs: Vec<(String, HashSet<String>)>,
+        binary: Vec<(String, HashSet<Vec<u8>>)>,
+    ) -> OpFuture<'a, ()>;
+
+    fn modify_entry<'a>(&'a mut self, dn: &'a str, mods: Vec<Mod<String>>) -> OpFuture<'a, ()>;
+
+    fn delete_entry<'a>(&'a mut self, dn: &'a str) -> OpFuture<'a, ()>;
+
+    /// Give `dn` a new RDN and, with `new_superior`, a new parent.
+    fn rename_entry<'a>(
+        &'a mut self,
+        dn: &'a str,
+        new_rdn: &'a str,
+        delete_old_rdn: bool,
+        new_superior: Option<&'a str>,
+    ) -> OpFuture<'a, ()>;
+
+    /// Start a transaction (RFC 5805). Writes belong to it until
+    /// [`end_transaction`](Self::end_transaction).
+    fn start_transaction(&mut self) -> OpFuture<'_, ()>;
+
+    /// Commit the open transaction, or abort it when `commit` is false.
+    fn end_transaction(&mut self, commit: bool) -> OpFuture<'_, ()>;
+}
+
+impl DirectoryOps for LdapConnection {
+    fn search<'a>(
+        &'a mut self,
+        base_dn: &'a str,
+        scope: Scope,
+        filter: &'a str,
+        attrs: &'a [&'a str],
+    ) -> OpFuture<'a, Vec<LdapEntry>> {
+        Box::pin(LdapConnection::search(self, base_dn, scope, filter, attrs))
+    }
+
+    fn search_pages<'a>(
+        &'a mut self,
+        base_dn: &'a str,
+        scope: Scope,
+        filter: &'a str,
+        attrs: &'a [&'a str],
+        on_page: PageSink<'a>,
+    ) -> OpFuture<'a, usize> {
+        Box::pin(LdapConnection::search_pages(
+            self, base_dn, scope, filter, attrs, on_page,
+        ))
+    }
+
+    fn add_entry<'a>(
+        &'a mut self,
+        dn: &'a str,
+        attrs: Vec<(String, HashSet<String>)>,
+        binary: Vec<(String, HashSet<Vec<u8>>)>,
+    ) -> OpFuture<'a, ()> {
+        Box::pin(LdapConnection::add_entry(self, dn, attrs, binary))
+    }
+
+    fn modify_entry<'a>(&'a mut self, dn: &'a str, mods: Vec<Mod<String>>) -> OpFuture<'a, ()> {
+        Box::pin(LdapConnection::modify_entry(self, dn, mods))
+    }
+
+    fn delete_entry<'a>(&'a mut self, dn: &'a str) -> OpFuture<'a, ()> {
+        Box::pin(LdapConnection::delete_entry(self, dn))
+    }
+
+    fn rename_entry<'a>(
+        &'a mut self,
+        dn: &'a str,
+        new_rdn: &'a str,
+        delete_old_rdn: bool,
+        new_superior: Option<&'a str>,
+    ) -> OpFuture<'a, ()> {
+        Box::pin(LdapConnection::rename_entry(
+            self,
+            dn,
+            new_rdn,
+            delete_old_rdn,
+            new_superior,
+        ))
+    }
+
+    fn start_transaction(&mut self) -> OpFuture<'_, ()> {
+        Box::pin(LdapConnection::start_transaction(self))
+    }
+
+    fn end_transaction(&mut self, commit: bool) -> OpFuture<'_, ()> {
+        Box::pin(LdapConnection::end_transaction(self, commit))
+    }
+}
+
+/// The attributes of `entry` in the form [`DirectoryOps::add_entry`] takes.
+pub fn add_attributes(entry: &LdapEntry) -> Vec<(String, HashSet<String>)> {
+    entry
+        .attributes
+        .iter()
+        .map(|(attr, values)| (attr.clone(), values.iter().cloned().collect()))
+        .collect()
+}
+
+/// The binary attributes of `entry` in the form [`DirectoryOps::add_entry`] takes.
+pub fn add_binary_attributes(entry: &LdapEntry) -> Vec<(String, HashSet<Vec<u8>>)> {
+    entry
+        .binary_attributes
+        .iter()
+        .map(|(attr, values)| (attr.clone(), values.iter().cloned().collect()))
+        .collect()
+}
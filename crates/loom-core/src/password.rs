@@ -0,0 +1,278 @@
+//! Setting a user's password.
+//!
+//! Servers that advertise the Password Modify extended operation (RFC 3062)
+//! get the new password through it, so they hash it by their own policy.
+//! Elsewhere `userPassword` is replaced outright, which some servers store
+//! as given. Either way the password policy request control goes along, and
+//! a refusal is explained from the policy response the server sends back.
+//! The password itself is never logged, and the audit log names only the
+//! attribute.
+
+use std::collections::HashSet;
+
+use ldap3::controls::{Control, RawControl};
+use ldap3::exop::PasswordModify;
+use ldap3::Mod;
+use tracing::{debug, info};
+
+use crate::connection::LdapConnection;
+use crate::error::CoreError;
+use crate::oids;
+use crate::redact;
+use crate::search::{ber_decode_integer, ber_read_tlv};
+use crate::server_detect::ServerType;
+
+/// How a password gets to the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordMethod {
+    /// The Password Modify extended operation.
+    Extended,
+    /// A plain replace of `userPassword`.
+    Replace,
+}
+
+impl PasswordMethod {
+    /// The extended operation where the server lists it, otherwise a replace.
+    pub fn for_server(supported_extensions: &[String]) -> Self {
+        if supported_extensions
+            .iter()
+            .any(|oid| oid == oids::PASSWORD_MODIFY)
+        {
+            PasswordMethod::Extended
+        } else {
+            PasswordMethod::Replace
+        }
+    }
+
+    /// What to tell the user before a password goes out this way.
+    pub fn warning(self) -> Option<&'static str> {
+        match self {
+            PasswordMethod::Extended => None,
+            PasswordMethod::Replace => Some(
+                "The server has no password modify operation; userPassword was replaced \
+                 and may be stored unhashed",
+            ),
+        }
+    }
+}
+
+/// The error a password policy response control reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyError {
+    PasswordExpired,
+    AccountLocked,
+    ChangeAfterReset,
+    PasswordModNotAllowed,
+    MustSupplyOldPassword,
+    InsufficientPasswordQuality,
+    PasswordTooShort,
+    PasswordTooYoung,
+    PasswordInHistory,
+}
+
+impl PolicyError {
+    fn from_code(code: i64) -> Option<Self> {
+        Some(match code {
+            0 => PolicyError::PasswordExpired,
+            1 => PolicyError::AccountLocked,
+            2 => PolicyError::ChangeAfterReset,
+            3 => PolicyError::PasswordModNotAllowed,
+            4 => PolicyError::MustSupplyOldPassword,
+            5 => PolicyError::InsufficientPasswordQuality,
+            6 => PolicyError::PasswordTooShort,
+            7 => PolicyError::PasswordTooYoung,
+            8 => PolicyError::PasswordInHistory,
+            _ => return None,
+        })
+    }
+
+    pub fn message(self) -> &'static str {
+        match self {
+            PolicyError::PasswordExpired => "the password has expired",
+            PolicyError::AccountLocked => "the account is locked",
+            PolicyError::ChangeAfterReset => "the password must be changed after a reset",
+            PolicyError::PasswordModNotAllowed => "the password may not be changed",
+            PolicyError::MustSupplyOldPassword => "the old password must be supplied",
+            PolicyError::InsufficientPasswordQuality => {
+                "the password does not meet the quality rules"
+            }
+            PolicyError::PasswordTooShort => "the password is too short",
+            PolicyError::PasswordTooYoung => "the password was changed too recently",
+            PolicyError::PasswordInHistory => "the password was used before",
+        }
+    }
+}
+
+/// Decode the error from a password policy response control's value:
+/// `SEQUENCE { warning [0] OPTIONAL, error [1] ENUMERATED OPTIONAL }`.
+pub fn decode_policy_response(val: &[u8]) -> Option<PolicyError> {
+    let (tag, mut body, _) = ber_read_tlv(val)?;
+    if tag != 0x30 {
+        return None;
+    }
+    while let Some((tag, value, rest)) = ber_read_tlv(body) {
+        if tag == 0x81 {
+            return PolicyError::from_code(ber_decode_integer(value));
+        }
+        body = rest;
+    }
+    None
+}
+
+/// The policy error among a result's response controls, if any.
+pub fn policy_error(ctrls: &[Control]) -> Option<PolicyError> {
+    ctrls
+        .iter()
+        .filter(|ctrl| ctrl.1.ctype == oids::PASSWORD_POLICY)
+        .find_map(|ctrl| ctrl.1.val.as_deref().and_then(decode_policy_response))
+}
+
+/// The change that makes the user pick a new password at their next
+/// login: `pwdLastSet` of 0 on Active Directory, `pwdReset` elsewhere.
+pub fn must_change_mods(server_type: &ServerType) -> Vec<Mod<String>> {
+    let (attr, value) = match server_type {
+        ServerType::ActiveDirectory => ("pwdLastSet", "0"),
+        _ => ("pwdReset", "TRUE"),
+    };
+    vec![Mod::Replace(
+        attr.to_string(),
+        HashSet::from([value.to_string()]),
+    )]
+}
+
+impl LdapConnection {
+    /// Set the password of `dn`. A refusal carries the password policy's
+    /// reason when the server gives one.
+    pub async fn set_password(
+        &mut self,
+        dn: &str,
+        password: &str,
+        method: PasswordMethod,
+    ) -> Result<(), CoreError> {
+        debug!(
+            "set_password dn={} method={:?} password={}",
+            dn,
+            method,
+            redact::secret(password)
+        );
+        self.check_health()?;
+        let ppolicy = RawControl {
+            ctype: oids::PASSWORD_POLICY.to_string(),
+            crit: false,
+            val: None,
+        };
+        let ldap = self.ldap.with_controls(vec![ppolicy]);
+        let res = match method {
+            PasswordMethod::Extended => {
+                let exop = PasswordModify {
+                    user_id: Some(dn),
+                    old_pass: None,
+                    new_pass: Some(password),
+                };
+                ldap.extended(exop).await.map(|exop| exop.1)
+            }
+            PasswordMethod::Replace => {
+                // Straight to the server: modify_entry logs the values
+                let mods = vec![Mod::Replace(
+                    "userPassword".to_string(),
+                    HashSet::from([password.to_string()]),
+                )];
+                ldap.modify(dn, mods).await
+            }
+        };
+        let result = res.map_err(CoreError::Ldap).and_then(|res| {
+            debug!("set_password result rc={} text={}", res.rc, res.text);
+            if res.rc != 0 {
+                let reason = match policy_error(&res.ctrls) {
+                    Some(policy) => policy.message().to_string(),
+                    None => format!("rc={}: {}", res.rc, res.text),
+                };
+                return Err(CoreError::PasswordFailed(format!("{}: {}", dn, reason)));
+            }
+            Ok(())
+        });
+        let operation = match method {
+            PasswordMethod::Extended => "set password: userPassword (password modify operation)",
+            PasswordMethod::Replace => "set password: replace userPassword",
+        };
+        self.audit_write(dn, || operation.to_string(), &result);
+        result?;
+        info!("Set password of {}", dn);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_method_follows_supported_extensions() {
+        let extensions = vec![oids::WHOAMI.to_string()];
+        assert_eq!(
+            PasswordMethod::for_server(&extensions),
+            PasswordMethod::Replace
+        );
+        assert!(PasswordMethod::Replace.warning().is_some());
+
+        let extensions = vec![oids::WHOAMI.to_string(), oids::PASSWORD_MODIFY.to_string()];
+        assert_eq!(
+            PasswordMethod::for_server(&extensions),
+            PasswordMethod::Extended
+        );
+        assert!(PasswordMethod::Extended.warning().is_none());
+    }
+
+    #[test]
+    fn test_decode_policy_response() {
+        // error: passwordInHistory
+        assert_eq!(
+            decode_policy_response(&[0x30, 0x03, 0x81, 0x01, 0x08]),
+            Some(PolicyError::PasswordInHistory)
+        );
+        // warning timeBeforeExpiration 300, then error: passwordTooShort
+        assert_eq!(
+            decode_policy_response(&[
+                0x30, 0x09, 0xa0, 0x04, 0x80, 0x02, 0x01, 0x2c, 0x81, 0x01, 0x06,
+            ]),
+            Some(PolicyError::PasswordTooShort)
+        );
+        // A warning alone is no error
+        assert_eq!(
+            decode_policy_response(&[0x30, 0x06, 0xa0, 0x04, 0x80, 0x02, 0x01, 0x2c]),
+            None
+        );
+        assert_eq!(decode_policy_response(&[0x30, 0x00]), None);
+        assert_eq!(decode_policy_response(&[0x04, 0x01, 0x00]), None);
+
+        let ctrls = vec![Control(
+            None,
+            RawControl {
+                ctype: oids::PASSWORD_POLICY.to_string(),
+                crit: false,
+                val: Some(vec![0x30, 0x03, 0x81, 0x01, 0x07]),
+            },
+        )];
+        assert_eq!(policy_error(&ctrls), Some(PolicyError::PasswordTooYoung));
+        assert_eq!(
+            PolicyError::PasswordTooYoung.message(),
+            "the password was changed too recently"
+        );
+    }
+
+    #[test]
+    fn test_must_change_mods_by_server() {
+        let value = |mods: Vec<Mod<String>>| match mods.into_iter().next() {
+            Some(Mod::Replace(attr, values)) => (attr, values.into_iter().next().unwrap()),
+            other => panic!("unexpected {:?}", other),
+        };
+        assert_eq!(
+            value(must_change_mods(&ServerType::ActiveDirectory)),
+            ("pwdLastSet".to_string(), "0".to_string())
+        );
+        assert_eq!(
+            value(must_change_mods(&ServerType::OpenLdap)),
+            ("pwdReset".to_string(), "TRUE".to_string())
+        );
+    }
+}
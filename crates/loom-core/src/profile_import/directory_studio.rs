@@ -0,0 +1,171 @@
+//! Apache Directory Studio connections (`connections.xml`, found under
+//! `.ApacheDirectoryStudio/.metadata/.plugins/org.apache.directory.studio.connection.core/`).
+//!
+//! Each `<connection>` element carries its settings as attributes, with
+//! browser options such as the base DN in `<extendedProperty>` children.
+
+use roxmltree::{Document, Node};
+
+use crate::connection::TlsMode;
+use crate::error::CoreError;
+
+use super::ForeignProfile;
+
+/// Parse a Directory Studio `connections.xml`.
+pub fn parse(content: &str) -> Result<Vec<ForeignProfile>, CoreError> {
+    let doc = Document::parse(content)
+        .map_err(|e| CoreError::ImportError(format!("Failed to parse XML: {}", e)))?;
+    let root = doc.root_element();
+    if root.tag_name().name() != "connections" {
+        return Err(CoreError::ImportError(format!(
+            "Expected <connections>, found <{}>",
+            root.tag_name().name()
+        )));
+    }
+    root.children()
+        .filter(|n| n.has_tag_name("connection"))
+        .map(parse_connection)
+        .collect()
+}
+
+fn parse_connection(node: Node) -> Result<ForeignProfile, CoreError> {
+    let attr = |name: &str| node.attribute(name).unwrap_or_default();
+    let host = attr("host").to_string();
+    if host.is_empty() {
+        return Err(CoreError::ImportError(format!(
+            "Connection '{}' has no host",
+            attr("name")
+        )));
+    }
+    let name = match attr("name") {
+        "" => host.clone(),
+        name => name.to_string(),
+    };
+
+    let mut warnings = Vec::new();
+    let tls_mode = match attr("encryptionMethod") {
+        "LDAPS" => TlsMode::Ldaps,
+        "START_TLS" => TlsMode::StartTls,
+        "NONE" | "" => TlsMode::None,
+        other => {
+            warnings.push(format!("Encryption method {} is not supported", other));
+            TlsMode::None
+        }
+    };
+    let port = match attr("port") {
+        "" if tls_mode == TlsMode::Ldaps => 636,
+        "" => 389,
+        port => port.parse().map_err(|_| {
+            CoreError::ImportError(format!("Connection '{}' has invalid port {}", name, port))
+        })?,
+    };
+
+    let mut profile = ForeignProfile::new(name, host, port, tls_mode);
+    let principal = attr("bindPrincipal");
+    match attr("authMethod") {
+        "SIMPLE" | "" => {
+            profile.bind_dn = Some(principal.to_string()).filter(|p| !p.is_empty());
+        }
+        "NONE" => {}
+        method => {
+            let mechanism = method.strip_prefix("SASL_").unwrap_or(method);
+            warnings.push(format!(
+                "{} authentication is not supported; imported as anonymous{}",
+                mechanism.replace('_', "-"),
+                if principal.is_empty() {
+                    String::new()
+                } else {
+                    format!(" (principal {})", principal)
+                }
+            ));
+        }
+    }
+    if !attr("bindPassword").is_empty() {
+        warnings.push("Saved password not imported; enter it when connecting".to_string());
+    }
+    profile.read_only = attr("readOnly") == "true";
+    profile.timeout_secs = attr("timeout")
+        .parse::<u64>()
+        .ok()
+        .filter(|ms| *ms > 0)
+        .map(|ms| ms.div_ceil(1000));
+
+    let property = |key: &str| {
+        node.descendants()
+            .find(|n| n.has_tag_name("extendedProperty") && n.attribute("key") == Some(key))
+            .and_then(|n| n.attribute("value"))
+    };
+    if property("ldapbrowser.fetchBaseDns") != Some("true") {
+        profile.base_dn = property("ldapbrowser.baseDn")
+            .filter(|b| !b.is_empty())
+            .map(str::to_string);
+    }
+    if property("ldapbrowser.pagedSearch") == Some("true") {
+        profile.page_size = property("ldapbrowser.pagedSearchSize").and_then(|s| s.parse().ok());
+    }
+    if matches!(
+        property("ldapbrowser.referralsHandlingMethod"),
+        Some("FOLLOW") | Some("FOLLOW_MANUALLY")
+    ) {
+        warnings.push("Referral following is not supported".to_string());
+    }
+
+    profile.warnings = warnings;
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_connection_attributes() {
+        let profiles = parse(
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<connections>
+  <connection id="1" name="Prod" host="ldap.example.com" port="10636"
+      encryptionMethod="LDAPS" authMethod="SIMPLE"
+      bindPrincipal="cn=admin,dc=example,dc=com" bindPassword=""
+      readOnly="true" timeout="2500">
+    <extendedProperties>
+      <extendedProperty key="ldapbrowser.baseDn" value="dc=example,dc=com"/>
+      <extendedProperty key="ldapbrowser.fetchBaseDns" value="false"/>
+    </extendedProperties>
+  </connection>
+</connections>"#,
+        )
+        .unwrap();
+        assert_eq!(profiles.len(), 1);
+        let p = &profiles[0];
+        assert_eq!(p.name, "Prod");
+        assert_eq!(p.port, 10636);
+        assert_eq!(p.tls_mode, TlsMode::Ldaps);
+        assert_eq!(p.bind_dn.as_deref(), Some("cn=admin,dc=example,dc=com"));
+        assert_eq!(p.base_dn.as_deref(), Some("dc=example,dc=com"));
+        assert_eq!(p.timeout_secs, Some(3));
+        assert!(p.read_only);
+        assert!(p.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_sasl_is_reported() {
+        let profiles = parse(
+            r#"<connections><connection name="Kerberos" host="kdc.example.com"
+                authMethod="SASL_GSSAPI" bindPrincipal="alice@EXAMPLE.COM"/></connections>"#,
+        )
+        .unwrap();
+        assert_eq!(profiles[0].port, 389);
+        assert_eq!(profiles[0].bind_dn, None);
+        assert_eq!(
+            profiles[0].warnings,
+            vec!["GSSAPI authentication is not supported; imported as anonymous (principal alice@EXAMPLE.COM)"]
+        );
+    }
+
+    #[test]
+    fn test_wrong_document() {
+        assert!(parse("<profiles/>").is_err());
+        assert!(parse("not xml").is_err());
+        assert!(parse(r#"<connections><connection name="x"/></connections>"#).is_err());
+    }
+}
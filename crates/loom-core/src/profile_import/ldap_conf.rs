@@ -0,0 +1,188 @@
+//! OpenLDAP client configuration (`ldap.conf`, `~/.ldaprc`).
+//!
+//! The file describes a single set of defaults, so it yields one profile.
+//! `URI` (or the deprecated `HOST`/`PORT`), `BASE`, `BINDDN`,
+//! `NETWORK_TIMEOUT`/`TIMEOUT`, `SASL_MECH` (`EXTERNAL` or `GSSAPI`) and the
+//! `TLS_CACERT`, `TLS_CERT`, `TLS_KEY` and `TLS_REQCERT` settings are
+//! mapped; everything else is reported.
+
+use crate::connection::{AuthMethod, TlsMode};
+use crate::error::CoreError;
+use crate::tls::TlsOptions;
+
+use super::url_list::parse_ldap_url;
+use super::ForeignProfile;
+
+/// Parse an `ldap.conf` into a single profile.
+pub fn parse(content: &str) -> Result<ForeignProfile, CoreError> {
+    let mut uris: Vec<String> = Vec::new();
+    let mut hosts: Vec<String> = Vec::new();
+    let mut port: Option<u16> = None;
+    let mut base_dn = None;
+    let mut bind_dn = None;
+    let mut network_timeout = None;
+    let mut timeout = None;
+    let mut tls_options = TlsOptions::default();
+    let mut auth = AuthMethod::default();
+    let mut warnings = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = match line.split_once(char::is_whitespace) {
+            Some((k, v)) => (k.to_uppercase(), v.trim()),
+            None => (line.to_uppercase(), ""),
+        };
+        match key.as_str() {
+            "URI" => uris.extend(value.split_whitespace().map(str::to_string)),
+            "HOST" => hosts.extend(value.split_whitespace().map(str::to_string)),
+            "PORT" => port = value.parse().ok(),
+            "BASE" => base_dn = Some(value.to_string()),
+            "BINDDN" => bind_dn = Some(value.to_string()),
+            "NETWORK_TIMEOUT" => network_timeout = value.parse::<u64>().ok(),
+            "TIMEOUT" => timeout = value.parse::<u64>().ok(),
+            "SASL_MECH" => match value.to_uppercase().as_str() {
+                "EXTERNAL" => auth = AuthMethod::SaslExternal,
+                "GSSAPI" => auth = AuthMethod::Gssapi,
+                _ => warnings.push(format!(
+                    "SASL_MECH {} is not supported; the profile uses a simple bind",
+                    value
+                )),
+            },
+            "TLS_CACERT" => tls_options.ca_cert = Some(value.into()),
+            "TLS_CERT" => tls_options.client_cert = Some(value.into()),
+            "TLS_KEY" => tls_options.client_key = Some(value.into()),
+            "TLS_CACERTDIR" => warnings.push(format!(
+                "{} is not imported; set a CA bundle file or approve the server certificate on first connect",
+                key
+            )),
+            // `allow` goes on with a bad certificate, like `verify = false`
+            "TLS_REQCERT" => {
+                tls_options.verify = !value.eq_ignore_ascii_case("never")
+                    && !value.eq_ignore_ascii_case("allow")
+            }
+            _ => warnings.push(format!("{} is ignored", key)),
+        }
+    }
+
+    let mut profile = if let Some(first) = uris.first() {
+        let url = parse_ldap_url(first)
+            .map_err(|e| CoreError::ImportError(format!("URI {}: {}", first, e)))?;
+        let mut profile = ForeignProfile::new(url.host.clone(), url.host, url.port, url.tls_mode);
+        if base_dn.is_none() {
+            base_dn = url.dn;
+        }
+        if bind_dn.is_none() {
+            bind_dn = url.bind_dn;
+        }
+        profile.warnings.extend(url.warnings);
+        if uris.len() > 1 {
+            profile.warnings.push(format!(
+                "Only the first URI is used; {} ignored",
+                uris[1..].join(" ")
+            ));
+        }
+        profile
+    } else if let Some(first) = hosts.first() {
+        let (host, host_port) = match first.rsplit_once(':') {
+            Some((h, p)) => (h.to_string(), p.parse().ok()),
+            None => (first.clone(), None),
+        };
+        let port = host_port.or(port).unwrap_or(389);
+        let mut profile = ForeignProfile::new(host.clone(), host, port, TlsMode::None);
+        if hosts.len() > 1 {
+            profile.warnings.push(format!(
+                "Only the first HOST is used; {} ignored",
+                hosts[1..].join(" ")
+            ));
+        }
+        profile
+    } else {
+        return Err(CoreError::ImportError(
+            "No URI or HOST found in ldap.conf".to_string(),
+        ));
+    };
+
+    profile.base_dn = base_dn.filter(|b| !b.is_empty());
+    profile.bind_dn = bind_dn.filter(|b| !b.is_empty());
+    profile.timeout_secs = network_timeout.or(timeout).filter(|t| *t > 0);
+    profile.tls_options = tls_options;
+    profile.auth = auth;
+    profile.warnings.extend(warnings);
+    Ok(profile)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_base_and_binddn() {
+        let profile = parse(
+            "# site defaults\nURI ldaps://ldap1.example.com ldaps://ldap2.example.com\nBASE dc=example,dc=com\nbinddn cn=reader,dc=example,dc=com\nNETWORK_TIMEOUT 5\nSIZELIMIT 100\n",
+        )
+        .unwrap();
+        assert_eq!(profile.name, "ldap1.example.com");
+        assert_eq!(profile.port, 636);
+        assert_eq!(profile.tls_mode, TlsMode::Ldaps);
+        assert_eq!(profile.base_dn.as_deref(), Some("dc=example,dc=com"));
+        assert_eq!(
+            profile.bind_dn.as_deref(),
+            Some("cn=reader,dc=example,dc=com")
+        );
+        assert_eq!(profile.timeout_secs, Some(5));
+        assert_eq!(
+            profile.warnings,
+            vec![
+                "Only the first URI is used; ldaps://ldap2.example.com ignored",
+                "SIZELIMIT is ignored",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deprecated_host_and_port() {
+        let profile = parse("HOST ldap.example.com\nPORT 10389\nTLS_REQCERT demand\n").unwrap();
+        assert_eq!(profile.host, "ldap.example.com");
+        assert_eq!(profile.port, 10389);
+        assert_eq!(profile.tls_mode, TlsMode::None);
+        assert!(profile.tls_options.verify);
+        assert!(profile.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_tls_settings() {
+        let profile = parse(
+            "URI ldaps://ldap.example.com\nTLS_CACERT /etc/ssl/ca.pem\nTLS_CERT /home/me/c.pem\nTLS_KEY /home/me/c.key\nTLS_REQCERT never\n",
+        )
+        .unwrap();
+        let tls = &profile.tls_options;
+        assert_eq!(tls.ca_cert, Some("/etc/ssl/ca.pem".into()));
+        assert_eq!(tls.client_cert, Some("/home/me/c.pem".into()));
+        assert_eq!(tls.client_key, Some("/home/me/c.key".into()));
+        assert!(!tls.verify);
+        assert!(profile.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_sasl_mech() {
+        let profile = parse("URI ldap://dc1.example.com\nSASL_MECH gssapi\n").unwrap();
+        assert_eq!(profile.auth, AuthMethod::Gssapi);
+        assert!(profile.warnings.is_empty());
+
+        let profile = parse("URI ldap://dc1.example.com\nSASL_MECH DIGEST-MD5\n").unwrap();
+        assert_eq!(profile.auth, AuthMethod::Simple);
+        assert_eq!(
+            profile.warnings,
+            vec!["SASL_MECH DIGEST-MD5 is not supported; the profile uses a simple bind"]
+        );
+    }
+
+    #[test]
+    fn test_missing_uri_is_an_error() {
+        assert!(parse("BASE dc=example,dc=com\n").is_err());
+        assert!(parse("URI ldapi:///\n").is_err());
+    }
+}
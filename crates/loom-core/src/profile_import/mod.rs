@@ -0,0 +1,223 @@
+//! Connection profiles from other LDAP tools.
+//!
+//! Supported sources are Apache Directory Studio's `connections.xml`,
+//! OpenLDAP's `ldap.conf` / `.ldaprc`, and plain lists of `ldap://` URLs.
+//! Each parser yields [`ForeignProfile`]s; settings a format has but loom
+//! cannot represent are kept as per-profile warnings instead of failing
+//! the import.
+
+pub mod directory_studio;
+pub mod ldap_conf;
+pub mod url_list;
+
+use std::path::{Path, PathBuf};
+
+use crate::connection::{AuthMethod, TlsMode};
+use crate::error::CoreError;
+use crate::tls::TlsOptions;
+
+/// A connection profile read from another tool's configuration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForeignProfile {
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub tls_mode: TlsMode,
+    pub bind_dn: Option<String>,
+    pub base_dn: Option<String>,
+    pub timeout_secs: Option<u64>,
+    pub page_size: Option<u32>,
+    pub read_only: bool,
+    pub tls_options: TlsOptions,
+    pub auth: AuthMethod,
+    /// Settings that could not be carried over.
+    pub warnings: Vec<String>,
+}
+
+impl ForeignProfile {
+    fn new(name: String, host: String, port: u16, tls_mode: TlsMode) -> Self {
+        Self {
+            name,
+            host,
+            port,
+            tls_mode,
+            bind_dn: None,
+            base_dn: None,
+            timeout_secs: None,
+            page_size: None,
+            read_only: false,
+            tls_options: TlsOptions::default(),
+            auth: AuthMethod::default(),
+            warnings: Vec::new(),
+        }
+    }
+}
+
+/// A configuration format of another LDAP tool.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForeignFormat {
+    DirectoryStudio,
+    LdapConf,
+    UrlList,
+}
+
+impl ForeignFormat {
+    pub const ALL: [ForeignFormat; 3] = [
+        ForeignFormat::DirectoryStudio,
+        ForeignFormat::LdapConf,
+        ForeignFormat::UrlList,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ForeignFormat::DirectoryStudio => "Apache Directory Studio (connections.xml)",
+            ForeignFormat::LdapConf => "OpenLDAP ldap.conf / .ldaprc",
+            ForeignFormat::UrlList => "LDAP URL list",
+        }
+    }
+
+    /// Guess the format from the file name, then the content.
+    /// `None` means the file looks like loom's own TOML (or is unknown).
+    pub fn detect(path: &Path, content: &str) -> Option<Self> {
+        let file_name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let extension = path
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        match extension.as_str() {
+            "toml" => return None,
+            "xml" => return Some(ForeignFormat::DirectoryStudio),
+            _ => {}
+        }
+        if matches!(file_name.as_str(), "ldap.conf" | ".ldaprc" | "ldaprc") {
+            return Some(ForeignFormat::LdapConf);
+        }
+
+        let trimmed = content.trim_start();
+        if trimmed.starts_with("<?xml") || trimmed.starts_with("<connections") {
+            return Some(ForeignFormat::DirectoryStudio);
+        }
+        let mut lines = content
+            .lines()
+            .map(str::trim)
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .peekable();
+        lines.peek()?;
+        let mut all_urls = true;
+        let mut conf_keys = false;
+        for line in lines {
+            let lower = line.to_lowercase();
+            if !(lower.starts_with("ldap://") || lower.starts_with("ldaps://")) {
+                all_urls = false;
+            }
+            let key = lower.split_whitespace().next().unwrap_or_default();
+            if matches!(key, "uri" | "base" | "binddn" | "host") {
+                conf_keys = true;
+            }
+        }
+        if all_urls {
+            Some(ForeignFormat::UrlList)
+        } else if conf_keys {
+            Some(ForeignFormat::LdapConf)
+        } else {
+            None
+        }
+    }
+
+    /// Parse `content` in this format.
+    pub fn parse(&self, content: &str) -> Result<Vec<ForeignProfile>, CoreError> {
+        let profiles = match self {
+            ForeignFormat::DirectoryStudio => directory_studio::parse(content)?,
+            ForeignFormat::LdapConf => vec![ldap_conf::parse(content)?],
+            ForeignFormat::UrlList => url_list::parse(content)?,
+        };
+        if profiles.is_empty() {
+            return Err(CoreError::ImportError(
+                "No connection profiles found in file".to_string(),
+            ));
+        }
+        Ok(profiles)
+    }
+}
+
+/// Where other tools keep their connections, relative to the home directory.
+const KNOWN_SOURCES: [(&str, ForeignFormat); 3] = [
+    (
+        ".ApacheDirectoryStudio/.metadata/.plugins/org.apache.directory.studio.connection.core/connections.xml",
+        ForeignFormat::DirectoryStudio,
+    ),
+    (".ldaprc", ForeignFormat::LdapConf),
+    ("ldaprc", ForeignFormat::LdapConf),
+];
+
+/// Configuration files of other LDAP tools present under `home`.
+pub fn find_sources(home: &Path) -> Vec<(ForeignFormat, PathBuf)> {
+    KNOWN_SOURCES
+        .iter()
+        .map(|(relative, format)| (*format, home.join(relative)))
+        .filter(|(_, path)| path.is_file())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_by_name_and_content() {
+        let detect = |name: &str, content: &str| ForeignFormat::detect(Path::new(name), content);
+        assert_eq!(detect("profiles.toml", "[[connections]]"), None);
+        assert_eq!(
+            detect("connections.xml", ""),
+            Some(ForeignFormat::DirectoryStudio)
+        );
+        assert_eq!(
+            detect("/home/me/.ldaprc", ""),
+            Some(ForeignFormat::LdapConf)
+        );
+        assert_eq!(
+            detect("export", "<?xml version=\"1.0\"?>\n<connections/>"),
+            Some(ForeignFormat::DirectoryStudio)
+        );
+        assert_eq!(
+            detect(
+                "servers",
+                "# prod\nldaps://a.example\nLDAP://b.example dev\n"
+            ),
+            Some(ForeignFormat::UrlList)
+        );
+        assert_eq!(
+            detect(
+                "site.conf",
+                "URI ldap://a.example\nBASE dc=example,dc=com\n"
+            ),
+            Some(ForeignFormat::LdapConf)
+        );
+        assert_eq!(detect("notes", "hello"), None);
+        assert_eq!(detect("empty", "\n# nothing\n"), None);
+    }
+
+    #[test]
+    fn test_find_sources() {
+        let home = tempfile::tempdir().unwrap();
+        assert!(find_sources(home.path()).is_empty());
+
+        std::fs::write(home.path().join(".ldaprc"), "URI ldap://a.example\n").unwrap();
+        let studio = home.path().join(KNOWN_SOURCES[0].0);
+        std::fs::create_dir_all(studio.parent().unwrap()).unwrap();
+        std::fs::write(&studio, "<connections/>").unwrap();
+
+        let found = find_sources(home.path());
+        assert_eq!(
+            found,
+            vec![
+                (ForeignFormat::DirectoryStudio, studio),
+                (ForeignFormat::LdapConf, home.path().join(".ldaprc")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,239 @@
+//! Lists of LDAP URLs (RFC 4516), one per line with an optional name:
+//!
+//! ```text
+//! # comment
+//! ldaps://dc1.example.com/dc=example,dc=com  Production
+//! ldap://test.example.com/??base?(objectClass=*)?bindname=cn=admin%2cdc=example%2cdc=com,StartTLS
+//! ```
+//!
+//! The DN sets the base DN and the `bindname` and `StartTLS` extensions are
+//! honoured. Spaces inside a URL must be written as `%20`, since the first
+//! whitespace separates the URL from the profile name.
+
+use crate::connection::TlsMode;
+use crate::error::CoreError;
+
+use super::ForeignProfile;
+
+const STARTTLS_OID: &str = "1.3.6.1.4.1.1466.20037";
+
+/// The parts of an LDAP URL that describe a connection.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LdapUrl {
+    pub tls_mode: TlsMode,
+    pub host: String,
+    pub port: u16,
+    pub dn: Option<String>,
+    pub bind_dn: Option<String>,
+    /// Parts of the URL a profile has no place for.
+    pub warnings: Vec<String>,
+}
+
+impl LdapUrl {
+    /// Profile name used when none is given: the host, plus the port when
+    /// it is not the scheme's default.
+    fn default_name(&self) -> String {
+        let default_port = if self.tls_mode == TlsMode::Ldaps {
+            636
+        } else {
+            389
+        };
+        if self.port == default_port {
+            self.host.clone()
+        } else if self.host.contains(':') {
+            format!("[{}]:{}", self.host, self.port)
+        } else {
+            format!("{}:{}", self.host, self.port)
+        }
+    }
+}
+
+/// Parse a list of LDAP URLs into profiles.
+pub fn parse(content: &str) -> Result<Vec<ForeignProfile>, CoreError> {
+    let mut profiles = Vec::new();
+    for (idx, line) in content.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (url, name) = match line.split_once(char::is_whitespace) {
+            Some((url, name)) => (url, name.trim()),
+            None => (line, ""),
+        };
+        let parsed = parse_ldap_url(url)
+            .map_err(|e| CoreError::ImportError(format!("line {}: {}", idx + 1, e)))?;
+        let name = if name.is_empty() {
+            parsed.default_name()
+        } else {
+            name.to_string()
+        };
+        let mut profile = ForeignProfile::new(name, parsed.host, parsed.port, parsed.tls_mode);
+        profile.base_dn = parsed.dn;
+        profile.bind_dn = parsed.bind_dn;
+        profile.warnings = parsed.warnings;
+        profiles.push(profile);
+    }
+    Ok(profiles)
+}
+
+/// Parse one `ldap://` or `ldaps://` URL.
+pub(crate) fn parse_ldap_url(url: &str) -> Result<LdapUrl, String> {
+    let lower = url.to_lowercase();
+    let (mut tls_mode, rest) = if lower.starts_with("ldaps://") {
+        (TlsMode::Ldaps, &url["ldaps://".len()..])
+    } else if lower.starts_with("ldap://") {
+        (TlsMode::None, &url["ldap://".len()..])
+    } else if lower.starts_with("ldapi://") {
+        return Err("ldapi:// (Unix socket) URLs are not supported".to_string());
+    } else {
+        return Err(format!("not an LDAP URL: {}", url));
+    };
+
+    let (hostport, path) = match rest.find(['/', '?']) {
+        Some(i) => (&rest[..i], rest[i..].trim_start_matches('/')),
+        None => (rest, ""),
+    };
+    let (host, port) = split_host_port(hostport)?;
+    if host.is_empty() {
+        return Err(format!("URL has no host: {}", url));
+    }
+    let port = match port {
+        Some(p) => p,
+        None if tls_mode == TlsMode::Ldaps => 636,
+        None => 389,
+    };
+
+    let mut parts = path.splitn(5, '?');
+    let dn = percent_decode(parts.next().unwrap_or_default())?;
+    let mut warnings = Vec::new();
+    let query: Vec<&str> = parts.by_ref().take(3).collect();
+    if query.iter().any(|q| !q.is_empty()) {
+        warnings.push("URL attributes, scope and filter are ignored".to_string());
+    }
+
+    let mut bind_dn = None;
+    let extensions = parts.next().unwrap_or_default();
+    for ext in extensions.split(',').filter(|e| !e.is_empty()) {
+        let (critical, ext) = match ext.strip_prefix('!') {
+            Some(e) => (true, e),
+            None => (false, ext),
+        };
+        let (name, value) = match ext.split_once('=') {
+            Some((n, v)) => (n, Some(percent_decode(v)?)),
+            None => (ext, None),
+        };
+        match name.to_lowercase().as_str() {
+            "bindname" | "x-bindname" => bind_dn = value.filter(|v| !v.is_empty()),
+            "starttls" | "x-starttls" | STARTTLS_OID => {
+                if tls_mode == TlsMode::Ldaps {
+                    warnings.push("StartTLS extension ignored on an ldaps:// URL".to_string());
+                } else {
+                    tls_mode = TlsMode::StartTls;
+                }
+            }
+            _ if critical => {
+                return Err(format!("unsupported critical extension {}", name));
+            }
+            _ => warnings.push(format!("URL extension {} ignored", name)),
+        }
+    }
+
+    Ok(LdapUrl {
+        tls_mode,
+        host,
+        port,
+        dn: Some(dn).filter(|d| !d.is_empty()),
+        bind_dn,
+        warnings,
+    })
+}
+
+/// Split `host[:port]`, including bracketed IPv6 literals.
+fn split_host_port(hostport: &str) -> Result<(String, Option<u16>), String> {
+    let (host, port) = if let Some(rest) = hostport.strip_prefix('[') {
+        let (host, after) = rest
+            .split_once(']')
+            .ok_or_else(|| format!("unterminated IPv6 address: {}", hostport))?;
+        (host, after.strip_prefix(':'))
+    } else {
+        match hostport.rsplit_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (hostport, None),
+        }
+    };
+    let port = match port {
+        Some(p) => Some(p.parse().map_err(|_| format!("invalid port: {}", p))?),
+        None => None,
+    };
+    Ok((percent_decode(host)?, port))
+}
+
+/// Decode `%XX` escapes.
+fn percent_decode(s: &str) -> Result<String, String> {
+    let bytes = s.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = s
+                .get(i + 1..i + 3)
+                .and_then(|h| u8::from_str_radix(h, 16).ok())
+                .ok_or_else(|| format!("invalid percent escape in {}", s))?;
+            out.push(hex);
+            i += 3;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8(out).map_err(|_| format!("invalid UTF-8 in {}", s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_parts() {
+        let url = parse_ldap_url(
+            "ldap://[::1]:1389/ou=People,dc=example,dc=com%20x??sub?(uid=*)?bindname=cn=admin%2cdc=example%2cdc=com,!StartTLS",
+        )
+        .unwrap();
+        assert_eq!(url.host, "::1");
+        assert_eq!(url.port, 1389);
+        assert_eq!(url.tls_mode, TlsMode::StartTls);
+        assert_eq!(url.dn.as_deref(), Some("ou=People,dc=example,dc=com x"));
+        assert_eq!(url.bind_dn.as_deref(), Some("cn=admin,dc=example,dc=com"));
+        assert_eq!(
+            url.warnings,
+            vec!["URL attributes, scope and filter are ignored"]
+        );
+    }
+
+    #[test]
+    fn test_default_ports_and_names() {
+        let profiles = parse("ldaps://dc1.example.com\nldap://dc2.example.com:10389/\n").unwrap();
+        assert_eq!(profiles[0].port, 636);
+        assert_eq!(profiles[0].name, "dc1.example.com");
+        assert_eq!(profiles[0].base_dn, None);
+        assert_eq!(profiles[1].name, "dc2.example.com:10389");
+        assert_eq!(profiles[1].tls_mode, TlsMode::None);
+    }
+
+    #[test]
+    fn test_rejected_urls() {
+        assert!(parse_ldap_url("ldapi://%2fvar%2frun%2fldapi").is_err());
+        assert!(parse_ldap_url("ldap:///dc=example").is_err());
+        assert!(parse_ldap_url("ldap://h:notaport").is_err());
+        assert!(parse_ldap_url("ldap://h/????!x-unknown").is_err());
+        assert!(parse_ldap_url("ldap://h/dc=%zz").is_err());
+        let url = parse_ldap_url("ldap://h/????x-unknown").unwrap();
+        assert_eq!(url.warnings, vec!["URL extension x-unknown ignored"]);
+
+        let err = parse("ldap://ok\nhttp://nope\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "import error: line 2: not an LDAP URL: http://nope"
+        );
+    }
+}
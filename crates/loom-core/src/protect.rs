@@ -0,0 +1,260 @@
+//! Protected DNs: entries that should not be changed by accident, such as
+//! the directory manager, `cn=config` or the AD configuration partition.
+//!
+//! Patterns are compared against normalized DNs (see [`crate::dn::normalize`]),
+//! so differences in case or spacing do not get around them:
+//!
+//! - `cn=admin,dc=example,dc=com` -- exactly that entry
+//! - `cn=admin,**` -- glob: `*` matches within one RDN, `**` across RDNs
+//! - `subtree:cn=config` -- the entry and everything below it; the base may
+//!   be a glob too, e.g. `subtree:cn=configuration,**`
+
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::dn::{normalize, parent_dn};
+use crate::server_detect::ServerType;
+
+/// How writes to protected entries are handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProtectionMode {
+    /// No protection.
+    Off,
+    /// Writes go ahead after a typed confirmation.
+    #[default]
+    Confirm,
+    /// Writes are refused.
+    Strict,
+}
+
+impl ProtectionMode {
+    pub fn is_default(&self) -> bool {
+        *self == ProtectionMode::default()
+    }
+}
+
+/// One protected-DN pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnPattern {
+    raw: String,
+    /// Normalized base pattern, without the `subtree:` prefix.
+    pattern: String,
+    subtree: bool,
+}
+
+impl DnPattern {
+    /// Parse a pattern; `None` when it is blank.
+    pub fn parse(raw: &str) -> Option<Self> {
+        let raw = raw.trim();
+        let (subtree, base) = match raw.strip_prefix("subtree:") {
+            Some(base) => (true, base.trim()),
+            None => (false, raw),
+        };
+        if base.is_empty() {
+            return None;
+        }
+        Some(Self {
+            raw: raw.to_string(),
+            pattern: normalize(base),
+            subtree,
+        })
+    }
+
+    pub fn matches(&self, dn: &str) -> bool {
+        let dn = normalize(dn);
+        if !self.subtree {
+            return glob_match(self.pattern.as_bytes(), dn.as_bytes());
+        }
+        // The entry itself or any of its ancestors, at RDN boundaries
+        let mut current = Some(dn.as_str());
+        while let Some(dn) = current {
+            if glob_match(self.pattern.as_bytes(), dn.as_bytes()) {
+                return true;
+            }
+            current = parent_dn(dn);
+        }
+        false
+    }
+}
+
+impl fmt::Display for DnPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.raw)
+    }
+}
+
+/// `*` matches any run of characters within one RDN, `**` any run at all.
+fn glob_match(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_match(rest, &text[i..])),
+        [b'*', rest @ ..] => {
+            let run = text.iter().position(|&c| c == b',').unwrap_or(text.len());
+            (0..=run).any(|i| glob_match(rest, &text[i..]))
+        }
+        [c, rest @ ..] => text.first() == Some(c) && glob_match(rest, &text[1..]),
+    }
+}
+
+/// Patterns protected by default for a server type.
+pub fn default_patterns(server: &ServerType) -> &'static [&'static str] {
+    match server {
+        ServerType::ActiveDirectory => &[
+            "subtree:cn=configuration,**",
+            "subtree:cn=system,**",
+            "subtree:ou=domain controllers,**",
+            "cn=administrator,cn=users,**",
+            "cn=krbtgt,cn=users,**",
+        ],
+        ServerType::OpenLdap => &[
+            "subtree:cn=config",
+            "subtree:cn=monitor",
+            "cn=admin,**",
+            "cn=manager,**",
+        ],
+        ServerType::Directory389 | ServerType::OpenDs => &[
+            "subtree:cn=config",
+            "subtree:cn=monitor",
+            "subtree:cn=schema",
+            "cn=directory manager",
+        ],
+        ServerType::EDirectory => &["cn=admin,**", "subtree:cn=security"],
+        ServerType::RadiantLogic | ServerType::Unknown(_) => &[
+            "subtree:cn=config",
+            "subtree:cn=schema",
+            "cn=admin,**",
+            "cn=manager,**",
+        ],
+    }
+}
+
+/// The protected entries of one connection.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProtectedDns {
+    pub mode: ProtectionMode,
+    patterns: Vec<DnPattern>,
+}
+
+impl ProtectedDns {
+    /// The server type's defaults plus the profile's own patterns.
+    pub fn for_server(server: &ServerType, patterns: &[String], mode: ProtectionMode) -> Self {
+        let patterns = default_patterns(server)
+            .iter()
+            .copied()
+            .chain(patterns.iter().map(String::as_str))
+            .filter_map(DnPattern::parse)
+            .collect();
+        Self { mode, patterns }
+    }
+
+    /// The pattern protecting `dn`, if any. Nothing is protected when the
+    /// mode is [`ProtectionMode::Off`].
+    pub fn check(&self, dn: &str) -> Option<&DnPattern> {
+        if self.mode == ProtectionMode::Off {
+            return None;
+        }
+        self.patterns.iter().find(|p| p.matches(dn))
+    }
+
+    pub fn is_protected(&self, dn: &str) -> bool {
+        self.check(dn).is_some()
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.mode == ProtectionMode::Strict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(raw: &str) -> DnPattern {
+        DnPattern::parse(raw).unwrap()
+    }
+
+    #[test]
+    fn test_exact_pattern_ignores_case_and_spacing() {
+        let p = pattern("cn=admin,dc=example,dc=com");
+        assert!(p.matches("CN=Admin, DC=Example, DC=com"));
+        assert!(p.matches("cn = admin,dc=example,dc=com"));
+        assert!(!p.matches("cn=admins,dc=example,dc=com"));
+        assert!(!p.matches("uid=x,cn=admin,dc=example,dc=com"));
+    }
+
+    #[test]
+    fn test_subtree_pattern() {
+        let p = pattern("subtree:cn=config");
+        assert!(p.matches("cn=config"));
+        assert!(p.matches("olcDatabase={1}mdb,cn=config"));
+        assert!(p.matches("olcOverlay={0}syncprov,olcDatabase={1}mdb, CN=Config"));
+        // Only whole RDNs count
+        assert!(!p.matches("cn=config2"));
+        assert!(!p.matches("cn=configuration,dc=example,dc=com"));
+        assert!(!p.matches("ou=x,dc=foocn=config"));
+        // Parent of the base is not covered
+        assert!(!pattern("subtree:ou=a,dc=example,dc=com").matches("dc=example,dc=com"));
+    }
+
+    #[test]
+    fn test_glob_patterns() {
+        let any_admin = pattern("cn=admin,**");
+        assert!(any_admin.matches("cn=admin,dc=example,dc=com"));
+        assert!(any_admin.matches("cn=Admin,o=acme"));
+        assert!(!any_admin.matches("uid=bob,cn=admin,dc=example,dc=com"));
+
+        // `*` stays inside one RDN
+        let one = pattern("cn=*,ou=service,dc=example,dc=com");
+        assert!(one.matches("cn=backup,ou=service,dc=example,dc=com"));
+        assert!(!one.matches("cn=a,cn=b,ou=service,dc=example,dc=com"));
+
+        let ad = pattern("subtree:cn=configuration,**");
+        assert!(ad.matches("CN=Configuration,DC=corp,DC=example,DC=com"));
+        assert!(ad.matches(
+            "CN=NTDS Settings,CN=DC1,CN=Servers,CN=Default-First-Site-Name,CN=Sites,CN=Configuration,DC=corp,DC=example,DC=com"
+        ));
+        assert!(!ad.matches("CN=Users,DC=corp,DC=example,DC=com"));
+    }
+
+    #[test]
+    fn test_blank_patterns_are_ignored() {
+        assert!(DnPattern::parse("  ").is_none());
+        assert!(DnPattern::parse("subtree: ").is_none());
+        assert_eq!(
+            pattern(" subtree:cn=config ").to_string(),
+            "subtree:cn=config"
+        );
+    }
+
+    #[test]
+    fn test_defaults_and_modes() {
+        let extra = vec!["subtree:ou=service,dc=example,dc=com".to_string()];
+        let protected =
+            ProtectedDns::for_server(&ServerType::OpenLdap, &extra, ProtectionMode::Confirm);
+        assert!(protected.is_protected("cn=admin,dc=example,dc=com"));
+        assert!(protected.is_protected("olcDatabase={0}config,cn=config"));
+        assert_eq!(
+            protected
+                .check("cn=backup,ou=service,dc=example,dc=com")
+                .unwrap()
+                .to_string(),
+            "subtree:ou=service,dc=example,dc=com"
+        );
+        assert!(!protected.is_protected("uid=alice,ou=people,dc=example,dc=com"));
+        assert!(!protected.is_strict());
+
+        let off = ProtectedDns::for_server(&ServerType::OpenLdap, &extra, ProtectionMode::Off);
+        assert!(!off.is_protected("cn=admin,dc=example,dc=com"));
+
+        let ad =
+            ProtectedDns::for_server(&ServerType::ActiveDirectory, &[], ProtectionMode::Strict);
+        assert!(ad.is_strict());
+        assert!(ad.is_protected("CN=krbtgt,CN=Users,DC=corp,DC=example,DC=com"));
+        assert!(!ad.is_protected("CN=Alice,CN=Users,DC=corp,DC=example,DC=com"));
+
+        // A default connection protects nothing
+        assert!(!ProtectedDns::default().is_protected("cn=config"));
+    }
+}
@@ -0,0 +1,101 @@
+//! Keeping secrets out of the log.
+//!
+//! Bind passwords and the values of password attributes are replaced
+//! where they are logged, before they are formatted, so no log level or
+//! target ever sees them.
+
+use std::collections::HashSet;
+
+use ldap3::Mod;
+
+/// What a secret is logged as.
+pub const REDACTED: &str = "<redacted>";
+
+/// Attributes that hold passwords; their values are never logged.
+const PASSWORD_ATTRS: &[&str] = &[
+    "userPassword",
+    "unicodePwd",
+    "authPassword",
+    "sambaNTPassword",
+    "sambaLMPassword",
+];
+
+/// Whether `attr` holds passwords.
+pub fn is_password_attr(attr: &str) -> bool {
+    PASSWORD_ATTRS.iter().any(|a| a.eq_ignore_ascii_case(attr))
+}
+
+/// A password as it may be logged: whether one was given, never what it is.
+pub fn secret(password: &str) -> &'static str {
+    if password.is_empty() {
+        "<empty>"
+    } else {
+        REDACTED
+    }
+}
+
+/// A value of `attr` as it may be logged.
+pub fn value<'a>(attr: &str, value: &'a str) -> &'a str {
+    if is_password_attr(attr) {
+        REDACTED
+    } else {
+        value
+    }
+}
+
+/// The values of `attr` as they may be logged: `{"a", "b"}`, or only how
+/// many there are, `[<redacted>; 2]`, for a password attribute.
+pub fn values(attr: &str, values: &HashSet<String>) -> String {
+    if is_password_attr(attr) {
+        format!("[{}; {}]", REDACTED, values.len())
+    } else {
+        format!("{:?}", values)
+    }
+}
+
+/// `REPLACE attr=title vals={"Engineer"}`, with password values redacted.
+pub fn modification(m: &Mod<String>) -> String {
+    match m {
+        Mod::Add(attr, vals) => format!("ADD attr={} vals={}", attr, values(attr, vals)),
+        Mod::Delete(attr, vals) => format!("DELETE attr={} vals={}", attr, values(attr, vals)),
+        Mod::Replace(attr, vals) => format!("REPLACE attr={} vals={}", attr, values(attr, vals)),
+        Mod::Increment(attr, val) => format!("INCREMENT attr={} by={}", attr, val),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_password_values_are_redacted() {
+        let mods = [
+            Mod::Replace(
+                "userPassword".to_string(),
+                HashSet::from(["hunter2".to_string()]),
+            ),
+            Mod::Add(
+                "UNICODEPWD".to_string(),
+                HashSet::from(["\"s3cret\"".to_string()]),
+            ),
+            Mod::Replace("title".to_string(), HashSet::from(["Engineer".to_string()])),
+        ];
+        let lines: Vec<String> = mods.iter().map(modification).collect();
+        assert_eq!(
+            lines,
+            [
+                "REPLACE attr=userPassword vals=[<redacted>; 1]",
+                "ADD attr=UNICODEPWD vals=[<redacted>; 1]",
+                "REPLACE attr=title vals={\"Engineer\"}",
+            ]
+        );
+        assert!(lines
+            .iter()
+            .all(|l| !l.contains("hunter2") && !l.contains("s3cret")));
+
+        assert_eq!(value("userPassword", "hunter2"), REDACTED);
+        assert_eq!(value("mail", "a@example.com"), "a@example.com");
+        assert_eq!(secret("hunter2"), REDACTED);
+        assert_eq!(secret(""), "<empty>");
+    }
+}
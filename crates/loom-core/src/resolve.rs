@@ -0,0 +1,127 @@
+//! Host name resolution ahead of connecting.
+//!
+//! Resolving separately from the TCP connect lets DNS failures be reported
+//! as such ("could not resolve ldap.prod.example: NXDOMAIN") instead of as a
+//! generic connection error, and lets tests substitute a [`Resolver`].
+
+use std::future::Future;
+use std::io;
+use std::net::SocketAddr;
+use std::pin::Pin;
+use std::time::Duration;
+
+use crate::error::CoreError;
+
+/// Future returned by [`Resolver::resolve`].
+pub type ResolveFuture<'a> = Pin<Box<dyn Future<Output = io::Result<Vec<SocketAddr>>> + Send + 'a>>;
+
+/// Looks up the socket addresses for a host.
+pub trait Resolver: Send + Sync {
+    fn resolve<'a>(&'a self, host: &'a str, port: u16) -> ResolveFuture<'a>;
+}
+
+/// The operating system resolver, run off the async executor.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemResolver;
+
+impl Resolver for SystemResolver {
+    fn resolve<'a>(&'a self, host: &'a str, port: u16) -> ResolveFuture<'a> {
+        Box::pin(async move { Ok(tokio::net::lookup_host((host, port)).await?.collect()) })
+    }
+}
+
+/// Resolve `host`, mapping failures to [`CoreError::ResolveFailed`].
+pub async fn resolve_host(
+    resolver: &dyn Resolver,
+    host: &str,
+    port: u16,
+    timeout: Duration,
+) -> Result<Vec<SocketAddr>, CoreError> {
+    let failed = |reason: String| CoreError::ResolveFailed {
+        host: host.to_string(),
+        reason,
+    };
+    match tokio::time::timeout(timeout, resolver.resolve(host, port)).await {
+        Err(_) => Err(failed("timed out".to_string())),
+        Ok(Err(e)) => Err(failed(describe_lookup_error(&e))),
+        Ok(Ok(addrs)) if addrs.is_empty() => Err(failed("no addresses".to_string())),
+        Ok(Ok(addrs)) => Ok(addrs),
+    }
+}
+
+/// Short reason for a failed lookup. getaddrinfo only reports free text,
+/// so the common platform messages are mapped to DNS terms.
+pub fn describe_lookup_error(err: &io::Error) -> String {
+    let msg = err.to_string();
+    let lower = msg.to_lowercase();
+    if lower.contains("not known")
+        || lower.contains("no such host")
+        || lower.contains("nodename nor servname")
+        || lower.contains("no address associated")
+    {
+        "NXDOMAIN".to_string()
+    } else if lower.contains("temporary failure") || lower.contains("try again") {
+        "temporary DNS failure".to_string()
+    } else {
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct FailingResolver(&'static str);
+
+    impl Resolver for FailingResolver {
+        fn resolve<'a>(&'a self, _host: &'a str, _port: u16) -> ResolveFuture<'a> {
+            let msg = self.0;
+            Box::pin(async move { Err(io::Error::other(msg)) })
+        }
+    }
+
+    struct SlowResolver;
+
+    impl Resolver for SlowResolver {
+        fn resolve<'a>(&'a self, _host: &'a str, _port: u16) -> ResolveFuture<'a> {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(Vec::new())
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_nxdomain_reported_as_dns_failure() {
+        let resolver =
+            FailingResolver("failed to lookup address information: Name or service not known");
+        let err = resolve_host(&resolver, "ldap.prod.example", 389, Duration::from_secs(1))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "could not resolve ldap.prod.example: NXDOMAIN"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_resolution_timeout() {
+        let err = resolve_host(
+            &SlowResolver,
+            "slow.example",
+            389,
+            Duration::from_millis(20),
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(err.to_string(), "could not resolve slow.example: timed out");
+    }
+
+    #[tokio::test]
+    async fn test_system_resolver_accepts_ip_literals() {
+        let addrs = resolve_host(&SystemResolver, "127.0.0.1", 389, Duration::from_secs(5))
+            .await
+            .unwrap();
+        assert_eq!(addrs, vec!["127.0.0.1:389".parse().unwrap()]);
+    }
+}
@@ -1,10 +1,12 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, VecDeque};
 
 use ldap3::{Scope, SearchEntry};
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
 use tracing::{debug, error, info, warn};
 
 use crate::connection::LdapConnection;
+use crate::dn::Dn;
+use crate::entry::LdapEntry;
 use crate::error::CoreError;
 use crate::util::find_values_ci;
 
@@ -24,29 +26,49 @@ pub enum AttributeSyntax {
 }
 
 /// An LDAP attribute type definition from the schema.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AttributeTypeInfo {
     pub oid: String,
     pub names: Vec<String>,
     pub description: Option<String>,
+    /// The attribute type this one is derived from (`SUP`).
+    #[serde(default)]
+    pub superior: Option<String>,
     pub syntax: AttributeSyntax,
     pub single_value: bool,
     pub no_user_modification: bool,
 }
 
 /// An LDAP object class definition.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObjectClassInfo {
     pub oid: String,
     pub names: Vec<String>,
     pub description: Option<String>,
-    pub superior: Option<String>,
+    /// Classes this one is derived from (`SUP`), usually just one.
+    #[serde(default, alias = "superior", deserialize_with = "one_or_many")]
+    pub superiors: Vec<String>,
     pub kind: ObjectClassKind,
     pub must: Vec<String>,
     pub may: Vec<String>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// Snapshots written before classes could have several superiors stored a
+/// single, possibly null, `superior`.
+fn one_or_many<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum OneOrMany {
+        One(Option<String>),
+        Many(Vec<String>),
+    }
+    Ok(match OneOrMany::deserialize(deserializer)? {
+        OneOrMany::One(one) => one.into_iter().collect(),
+        OneOrMany::Many(many) => many,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ObjectClassKind {
     Abstract,
     Structural,
@@ -54,7 +76,7 @@ pub enum ObjectClassKind {
 }
 
 /// Cached schema information for a connection.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SchemaCache {
     pub attribute_types: BTreeMap<String, AttributeTypeInfo>,
     pub object_classes: BTreeMap<String, ObjectClassInfo>,
@@ -93,8 +115,9 @@ impl SchemaCache {
     /// Filters out `no_user_modification` attributes.
     pub fn allowed_attributes(&self, object_classes: &[&str]) -> Vec<String> {
         let mut attrs = BTreeSet::new();
-        for oc_name in object_classes {
-            self.collect_oc_attrs(&oc_name.to_lowercase(), &mut attrs);
+        for oc in self.with_superiors(object_classes) {
+            attrs.extend(oc.must.iter().cloned());
+            attrs.extend(oc.may.iter().cloned());
         }
         // Filter out non-user-modifiable attributes
         attrs
@@ -107,20 +130,132 @@ impl SchemaCache {
             .collect()
     }
 
-    /// Recursively collect MUST + MAY attributes for an object class,
-    /// walking the superior chain.
-    fn collect_oc_attrs(&self, oc_lower: &str, attrs: &mut BTreeSet<String>) {
-        if let Some(oc) = self.object_classes.get(oc_lower) {
-            for a in &oc.must {
-                attrs.insert(a.clone());
+    /// MUST attributes of the given object classes and their superiors,
+    /// each once, in the order the classes declare them. `objectClass` is
+    /// left out, since it comes from the classes themselves.
+    pub fn required_attributes(&self, object_classes: &[&str]) -> Vec<String> {
+        let mut required: Vec<String> = Vec::new();
+        for oc in self.with_superiors(object_classes) {
+            for attr in &oc.must {
+                if !attr.eq_ignore_ascii_case("objectClass")
+                    && !required.iter().any(|r| self.same_attribute(r, attr))
+                {
+                    required.push(attr.clone());
+                }
             }
-            for a in &oc.may {
-                attrs.insert(a.clone());
+        }
+        required
+    }
+
+    /// User-modifiable attributes the given object classes allow but do
+    /// not require, sorted.
+    pub fn optional_attributes(&self, object_classes: &[&str]) -> Vec<String> {
+        let required = self.required_attributes(object_classes);
+        self.allowed_attributes(object_classes)
+            .into_iter()
+            .filter(|a| {
+                !a.eq_ignore_ascii_case("objectClass")
+                    && !required.iter().any(|r| self.same_attribute(r, a))
+            })
+            .collect()
+    }
+
+    /// Every object class once, whatever its aliases, sorted by name
+    /// ignoring case.
+    pub fn object_class_list(&self) -> Vec<&ObjectClassInfo> {
+        let mut seen_oids = BTreeSet::new();
+        let mut classes: Vec<&ObjectClassInfo> = self
+            .object_classes
+            .values()
+            .filter(|oc| seen_oids.insert(oc.oid.clone()))
+            .collect();
+        classes.sort_by_key(|oc| class_name(oc).to_lowercase());
+        classes
+    }
+
+    /// The named classes followed by their superiors, nearest first, each
+    /// once. Unknown names are skipped.
+    fn with_superiors(&self, object_classes: &[&str]) -> Vec<&ObjectClassInfo> {
+        let mut classes: Vec<&ObjectClassInfo> = Vec::new();
+        for name in object_classes {
+            let mut queue = VecDeque::from([name.to_lowercase()]);
+            while let Some(next) = queue.pop_front() {
+                let Some(oc) = self.object_classes.get(&next) else {
+                    continue;
+                };
+                // Already seen, which also ends cyclic SUP chains
+                if classes.iter().any(|c| c.oid == oc.oid) {
+                    continue;
+                }
+                classes.push(oc);
+                queue.extend(oc.superiors.iter().map(|s| s.to_lowercase()));
             }
-            if let Some(ref sup) = oc.superior {
-                self.collect_oc_attrs(&sup.to_lowercase(), attrs);
+        }
+        classes
+    }
+
+    /// Lookup an object class by name (case-insensitive).
+    pub fn get_object_class(&self, name: &str) -> Option<&ObjectClassInfo> {
+        self.object_classes.get(&name.to_lowercase())
+    }
+
+    /// Every attribute an object class requires or allows, including those
+    /// inherited through its superiors, each once with the class declaring
+    /// it. An attribute one class requires and another allows is required.
+    pub fn class_attributes(&self, class: &str) -> Vec<ClassAttribute> {
+        let mut attrs: Vec<ClassAttribute> = Vec::new();
+        for oc in self.with_superiors(&[class]) {
+            let declared = oc
+                .must
+                .iter()
+                .map(|a| (a, true))
+                .chain(oc.may.iter().map(|a| (a, false)));
+            for (name, required) in declared {
+                match attrs
+                    .iter_mut()
+                    .find(|a| self.same_attribute(&a.name, name))
+                {
+                    Some(seen) if required && !seen.required => {
+                        seen.required = true;
+                        seen.declared_by = class_name(oc);
+                    }
+                    Some(_) => {}
+                    None => attrs.push(ClassAttribute {
+                        name: name.clone(),
+                        required,
+                        declared_by: class_name(oc),
+                    }),
+                }
             }
         }
+        attrs
+    }
+
+    /// Object classes that name an attribute in their own MUST or MAY,
+    /// sorted by name.
+    pub fn classes_using(&self, attr: &str) -> Vec<&ObjectClassInfo> {
+        self.object_class_list()
+            .into_iter()
+            .filter(|oc| {
+                oc.must
+                    .iter()
+                    .chain(&oc.may)
+                    .any(|a| self.same_attribute(a, attr))
+            })
+            .collect()
+    }
+
+    /// Every attribute type once, whatever its aliases, sorted by name
+    /// ignoring case.
+    pub fn attribute_type_list(&self) -> Vec<&AttributeTypeInfo> {
+        let mut seen_oids = BTreeSet::new();
+        let mut types: Vec<&AttributeTypeInfo> = self
+            .attribute_types
+            .values()
+            .filter(|at| seen_oids.insert(at.oid.clone()))
+            .collect();
+        types.sort_by_key(|at| attribute_name(at).to_lowercase());
+        types
     }
 
     /// Return all attribute names in the schema, including aliases and
@@ -166,6 +301,170 @@ impl Default for SchemaCache {
     }
 }
 
+/// An attribute an object class requires or allows, see
+/// [`SchemaCache::class_attributes`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClassAttribute {
+    pub name: String,
+    pub required: bool,
+    /// The class, or superior, whose MUST or MAY lists it.
+    pub declared_by: String,
+}
+
+/// One section of an entry's attributes, grouped by the objectClass that
+/// declares them. `object_class` is `None` for the trailing section of
+/// attributes no class of the entry explains (operational or extension).
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeGroup {
+    pub object_class: Option<String>,
+    pub kind: Option<ObjectClassKind>,
+    pub attributes: Vec<GroupedAttribute>,
+}
+
+/// An attribute present on the entry, placed in its owning group.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GroupedAttribute {
+    /// Attribute name as it appears on the entry.
+    pub name: String,
+    /// Listed as MUST by any of the entry's classes.
+    pub required: bool,
+    /// Other classes of the entry that also declare this attribute.
+    pub also_in: Vec<String>,
+}
+
+impl SchemaCache {
+    /// Whether two attribute names refer to the same type (aliases included).
+    fn same_attribute(&self, a: &str, b: &str) -> bool {
+        if a.eq_ignore_ascii_case(b) {
+            return true;
+        }
+        match (self.get_attribute_type(a), self.get_attribute_type(b)) {
+            (Some(x), Some(y)) => x.oid == y.oid,
+            _ => false,
+        }
+    }
+
+    /// The entry's object classes plus their superiors, each once,
+    /// ordered structural (base classes first), then auxiliary, then abstract.
+    fn entry_classes(&self, entry: &LdapEntry) -> Vec<&ObjectClassInfo> {
+        let values = find_values_ci(&entry.attributes, "objectClass")
+            .cloned()
+            .unwrap_or_default();
+        let names: Vec<&str> = values.iter().map(String::as_str).collect();
+        let mut classes = self.with_superiors(&names);
+        classes.sort_by_key(|oc| {
+            let rank = match oc.kind {
+                ObjectClassKind::Structural => 0,
+                ObjectClassKind::Auxiliary => 1,
+                ObjectClassKind::Abstract => 2,
+            };
+            (rank, self.class_depth(oc))
+        });
+        classes
+    }
+
+    /// Length of the longest superior chain above a class, so base classes
+    /// sort first.
+    fn class_depth(&self, oc: &ObjectClassInfo) -> usize {
+        self.depth_below(oc, 0)
+    }
+
+    fn depth_below(&self, oc: &ObjectClassInfo, seen: usize) -> usize {
+        // Guard against cyclic SUP chains in broken schemas
+        if seen > 32 {
+            return 0;
+        }
+        oc.superiors
+            .iter()
+            .map(|sup| {
+                1 + self
+                    .object_classes
+                    .get(&sup.to_lowercase())
+                    .map_or(0, |s| self.depth_below(s, seen + 1))
+            })
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// Group the attributes present on `entry` by the objectClass declaring them.
+///
+/// Each attribute is listed once. When several of the entry's classes declare
+/// it, it goes to the first structural one (or the first auxiliary, then
+/// abstract, if no structural class does) and the others are recorded in
+/// `also_in`. Classes with no attribute present are omitted; attributes no
+/// class declares end up in the final `None` group.
+pub fn group_attributes(entry: &LdapEntry, schema: &SchemaCache) -> Vec<AttributeGroup> {
+    let classes = schema.entry_classes(entry);
+    let mut groups: Vec<AttributeGroup> = classes
+        .iter()
+        .map(|oc| AttributeGroup {
+            object_class: Some(class_name(oc)),
+            kind: Some(oc.kind.clone()),
+            attributes: Vec::new(),
+        })
+        .collect();
+    let mut other = Vec::new();
+
+    for (name, _) in entry.all_values() {
+        let declares = |list: &[String]| list.iter().any(|a| schema.same_attribute(a, name));
+        let claimants: Vec<usize> = classes
+            .iter()
+            .enumerate()
+            .filter(|(_, oc)| declares(&oc.must) || declares(&oc.may))
+            .map(|(i, _)| i)
+            .collect();
+        let Some((&owner, rest)) = claimants.split_first() else {
+            other.push(GroupedAttribute {
+                name: name.clone(),
+                required: false,
+                also_in: Vec::new(),
+            });
+            continue;
+        };
+        groups[owner].attributes.push(GroupedAttribute {
+            name: name.clone(),
+            required: claimants.iter().any(|&i| declares(&classes[i].must)),
+            also_in: rest.iter().map(|&i| class_name(classes[i])).collect(),
+        });
+    }
+
+    groups.retain(|g| !g.attributes.is_empty());
+    if !other.is_empty() {
+        groups.push(AttributeGroup {
+            object_class: None,
+            kind: None,
+            attributes: other,
+        });
+    }
+    groups
+}
+
+/// The entry a value of `attr` refers to, when it is a reference: the value
+/// parses as a DN, and the attribute has DN syntax in the schema or is
+/// named in `allowlist`.
+pub fn dn_reference(
+    attr: &str,
+    value: &str,
+    schema: Option<&SchemaCache>,
+    allowlist: &[String],
+) -> Option<Dn> {
+    let listed = allowlist.iter().any(|a| a.eq_ignore_ascii_case(attr));
+    let dn_syntax = schema.is_some_and(|s| matches!(s.attribute_syntax(attr), AttributeSyntax::Dn));
+    if !listed && !dn_syntax {
+        return None;
+    }
+    Dn::parse(value).ok().filter(|dn| !dn.is_root())
+}
+
+fn class_name(oc: &ObjectClassInfo) -> String {
+    oc.names.first().cloned().unwrap_or_else(|| oc.oid.clone())
+}
+
+fn attribute_name(at: &AttributeTypeInfo) -> &str {
+    at.names.first().unwrap_or(&at.oid)
+}
+
 impl LdapConnection {
     /// Discover and load the schema from the server.
     ///
@@ -239,6 +538,7 @@ impl LdapConnection {
             "try_load_schema_from: searching base={:?} scope=Base filter=(objectClass=*)",
             schema_dn
         );
+        self.check_health()?;
         let result = self
             .ldap
             .search(
@@ -367,149 +667,181 @@ impl LdapConnection {
     }
 }
 
-/// Parse an LDAP attributeType schema definition string.
-/// Format: ( OID NAME 'name' DESC 'desc' SYNTAX oid SINGLE-VALUE ... )
-fn parse_attribute_type(def: &str) -> Option<AttributeTypeInfo> {
-    let def = def.trim();
-    if !def.starts_with('(') || !def.ends_with(')') {
-        return None;
+/// A lexical token of an RFC 4512 schema definition.
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Open,
+    Close,
+    Dollar,
+    /// A `'quoted'` string, with `\27` and `\5C` escapes decoded.
+    Quoted(String),
+    /// A keyword, OID or descriptor.
+    Word(String),
+}
+
+fn tokenize(def: &str) -> Option<Vec<Token>> {
+    let mut tokens = Vec::new();
+    let mut chars = def.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | '$' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Dollar,
+                });
+            }
+            '\'' => {
+                chars.next();
+                let mut value = String::new();
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        '\\' => {
+                            let hex: String = chars.by_ref().take(2).collect();
+                            let byte = u8::from_str_radix(&hex, 16).ok()?;
+                            value.push(char::from(byte));
+                        }
+                        c => value.push(c),
+                    }
+                }
+                tokens.push(Token::Quoted(value));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || matches!(c, '(' | ')' | '$' | '\'') {
+                        break;
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(Token::Word(word));
+            }
+        }
+    }
+    Some(tokens)
+}
+
+/// Keywords that stand alone, without a value.
+const FLAGS: &[&str] = &[
+    "OBSOLETE",
+    "SINGLE-VALUE",
+    "COLLECTIVE",
+    "NO-USER-MODIFICATION",
+    "ABSTRACT",
+    "STRUCTURAL",
+    "AUXILIARY",
+];
+
+/// An RFC 4512 definition, `( numericoid KEYWORD value ... )`, split into
+/// its OID and keyword fields. A value is a single word or quoted string,
+/// or a parenthesised list of them, whose `$` separators are dropped.
+struct Definition {
+    oid: String,
+    fields: Vec<(String, Vec<String>)>,
+}
+
+impl Definition {
+    fn parse(def: &str) -> Option<Self> {
+        let tokens = tokenize(def)?;
+        let mut tokens = tokens.into_iter();
+        if tokens.next()? != Token::Open {
+            return None;
+        }
+        let Token::Word(oid) = tokens.next()? else {
+            return None;
+        };
+        let mut fields = Vec::new();
+        loop {
+            let keyword = match tokens.next()? {
+                Token::Close => break,
+                Token::Word(keyword) => keyword.to_ascii_uppercase(),
+                _ => return None,
+            };
+            let mut values = Vec::new();
+            if !FLAGS.contains(&keyword.as_str()) {
+                match tokens.next()? {
+                    Token::Word(v) | Token::Quoted(v) => values.push(v),
+                    Token::Open => loop {
+                        match tokens.next()? {
+                            Token::Close => break,
+                            Token::Dollar => {}
+                            Token::Word(v) | Token::Quoted(v) => values.push(v),
+                            Token::Open => return None,
+                        }
+                    },
+                    Token::Close | Token::Dollar => return None,
+                }
+            }
+            fields.push((keyword, values));
+        }
+        Some(Self { oid, fields })
     }
-    let inner = &def[1..def.len() - 1].trim();
 
-    let oid = inner.split_whitespace().next()?.to_string();
-    let names = parse_names(inner);
-    let description = parse_quoted_field(inner, "DESC");
-    let syntax_oid = parse_unquoted_field(inner, "SYNTAX");
-    let single_value = inner.contains("SINGLE-VALUE");
-    let no_user_modification = inner.contains("NO-USER-MODIFICATION");
+    fn values(&self, keyword: &str) -> &[String] {
+        self.fields
+            .iter()
+            .find(|(k, _)| k == keyword)
+            .map_or(&[], |(_, v)| v.as_slice())
+    }
+
+    fn value(&self, keyword: &str) -> Option<String> {
+        self.values(keyword).first().cloned()
+    }
 
-    let syntax = syntax_oid
+    fn has(&self, keyword: &str) -> bool {
+        self.fields.iter().any(|(k, _)| k == keyword)
+    }
+}
+
+/// Parse an RFC 4512 attributeType definition, e.g.
+/// `( 2.5.4.3 NAME ( 'cn' 'commonName' ) SUP name )`.
+pub fn parse_attribute_type(def: &str) -> Option<AttributeTypeInfo> {
+    let def = Definition::parse(def)?;
+    let syntax = def
+        .value("SYNTAX")
         .as_deref()
         .map(map_syntax_oid)
         .unwrap_or(AttributeSyntax::String);
-
     Some(AttributeTypeInfo {
-        oid,
-        names,
-        description,
+        names: def.values("NAME").to_vec(),
+        description: def.value("DESC"),
+        superior: def.value("SUP"),
         syntax,
-        single_value,
-        no_user_modification,
+        single_value: def.has("SINGLE-VALUE"),
+        no_user_modification: def.has("NO-USER-MODIFICATION"),
+        oid: def.oid,
     })
 }
 
-/// Parse an LDAP objectClass schema definition string.
-fn parse_object_class(def: &str) -> Option<ObjectClassInfo> {
-    let def = def.trim();
-    if !def.starts_with('(') || !def.ends_with(')') {
-        return None;
-    }
-    let inner = &def[1..def.len() - 1].trim();
-
-    let oid = inner.split_whitespace().next()?.to_string();
-    let names = parse_names(inner);
-    let description = parse_quoted_field(inner, "DESC");
-    let superior = parse_unquoted_field(inner, "SUP");
-
-    let kind = if inner.contains("ABSTRACT") {
+/// Parse an RFC 4512 objectClass definition, e.g.
+/// `( 2.5.6.6 NAME 'person' SUP top STRUCTURAL MUST ( sn $ cn ) )`.
+pub fn parse_object_class(def: &str) -> Option<ObjectClassInfo> {
+    let def = Definition::parse(def)?;
+    // STRUCTURAL is the default kind
+    let kind = if def.has("ABSTRACT") {
         ObjectClassKind::Abstract
-    } else if inner.contains("AUXILIARY") {
+    } else if def.has("AUXILIARY") {
         ObjectClassKind::Auxiliary
     } else {
         ObjectClassKind::Structural
     };
-
-    let must = parse_attr_list(inner, "MUST");
-    let may = parse_attr_list(inner, "MAY");
-
     Some(ObjectClassInfo {
-        oid,
-        names,
-        description,
-        superior,
+        names: def.values("NAME").to_vec(),
+        description: def.value("DESC"),
+        superiors: def.values("SUP").to_vec(),
         kind,
-        must,
-        may,
+        must: def.values("MUST").to_vec(),
+        may: def.values("MAY").to_vec(),
+        oid: def.oid,
     })
 }
 
-/// Parse NAME field — can be 'single' or ( 'multiple' 'names' ).
-fn parse_names(s: &str) -> Vec<String> {
-    if let Some(pos) = s.find("NAME") {
-        let rest = &s[pos + 4..].trim_start();
-        if rest.starts_with('(') {
-            // Multiple names: ( 'name1' 'name2' )
-            if let Some(end) = rest.find(')') {
-                let names_str = &rest[1..end];
-                return names_str
-                    .split('\'')
-                    .filter(|s| !s.trim().is_empty())
-                    .map(|s| s.to_string())
-                    .collect();
-            }
-        } else if let Some(rest) = rest.strip_prefix('\'') {
-            // Single name: 'name'
-            if let Some(end) = rest.find('\'') {
-                return vec![rest[..end].to_string()];
-            }
-        }
-    }
-    Vec::new()
-}
-
-/// Parse a single-quoted field value: KEYWORD 'value'.
-fn parse_quoted_field(s: &str, keyword: &str) -> Option<String> {
-    let pattern = format!("{} '", keyword);
-    if let Some(pos) = s.find(&pattern) {
-        let rest = &s[pos + pattern.len()..];
-        if let Some(end) = rest.find('\'') {
-            return Some(rest[..end].to_string());
-        }
-    }
-    None
-}
-
-/// Parse an unquoted field value: KEYWORD value (terminated by space or paren).
-fn parse_unquoted_field(s: &str, keyword: &str) -> Option<String> {
-    let pattern = format!("{} ", keyword);
-    if let Some(pos) = s.find(&pattern) {
-        let rest = &s[pos + pattern.len()..].trim_start();
-        // Take until next space, or strip leading/trailing braces
-        let val: String = rest
-            .split_whitespace()
-            .next()?
-            .trim_matches(['{', '}'])
-            .to_string();
-        if !val.is_empty() {
-            return Some(val);
-        }
-    }
-    None
-}
-
-/// Parse an attribute list: KEYWORD ( attr1 $ attr2 ) or KEYWORD attr.
-fn parse_attr_list(s: &str, keyword: &str) -> Vec<String> {
-    let pattern = format!("{} ", keyword);
-    if let Some(pos) = s.find(&pattern) {
-        let rest = &s[pos + pattern.len()..].trim_start();
-        if rest.starts_with('(') {
-            if let Some(end) = rest.find(')') {
-                return rest[1..end]
-                    .split('$')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect();
-            }
-        } else {
-            // Single attribute
-            if let Some(val) = rest.split_whitespace().next() {
-                return vec![val.to_string()];
-            }
-        }
-    }
-    Vec::new()
-}
-
 /// Map LDAP syntax OID to our AttributeSyntax enum.
 fn map_syntax_oid(oid: &str) -> AttributeSyntax {
     // Strip any length constraint like {128}
@@ -569,12 +901,66 @@ mod tests {
         let oc = parse_object_class(def).unwrap();
         assert_eq!(oc.oid, "2.5.6.6");
         assert_eq!(oc.names, vec!["person"]);
-        assert_eq!(oc.superior, Some("top".to_string()));
+        assert_eq!(oc.superiors, vec!["top"]);
         assert_eq!(oc.kind, ObjectClassKind::Structural);
         assert_eq!(oc.must, vec!["sn", "cn"]);
         assert_eq!(oc.may, vec!["userPassword", "telephoneNumber"]);
     }
 
+    #[test]
+    fn test_parse_keywords_inside_quotes_and_extensions() {
+        // Keywords in DESC are text, not fields
+        let def = "( 1.2.3.4 NAME 'flag' DESC 'NAME says SINGLE-VALUE, it\\27s not' EQUALITY booleanMatch SYNTAX 1.3.6.1.4.1.1466.115.121.1.7 X-ORIGIN ( 'RFC 4512' 'local' ) )";
+        let at = parse_attribute_type(def).unwrap();
+        assert_eq!(at.names, vec!["flag"]);
+        assert_eq!(
+            at.description.as_deref(),
+            Some("NAME says SINGLE-VALUE, it's not")
+        );
+        assert!(!at.single_value);
+        assert_eq!(at.syntax, AttributeSyntax::Boolean);
+
+        // Attribute SUP, flags and no spaces around the parentheses
+        let def = "(2.5.4.41 NAME('name')SUP x-base OBSOLETE NO-USER-MODIFICATION USAGE directoryOperation)";
+        let at = parse_attribute_type(def).unwrap();
+        assert_eq!(at.oid, "2.5.4.41");
+        assert_eq!(at.names, vec!["name"]);
+        assert_eq!(at.superior.as_deref(), Some("x-base"));
+        assert!(at.no_user_modification);
+
+        assert!(parse_attribute_type("( 1.2.3 NAME 'open' ").is_none());
+        assert!(parse_attribute_type("1.2.3 NAME 'bare'").is_none());
+    }
+
+    #[test]
+    fn test_parse_object_class_several_superiors() {
+        let def = "( 1.3.6.1.1.1.2.0 NAME 'posixAccount' SUP ( top $ account ) AUXILIARY MUST uid MAY ( gecos $ description ) )";
+        let oc = parse_object_class(def).unwrap();
+        assert_eq!(oc.superiors, vec!["top", "account"]);
+        assert_eq!(oc.kind, ObjectClassKind::Auxiliary);
+        assert_eq!(oc.must, vec!["uid"]);
+        assert_eq!(oc.may, vec!["gecos", "description"]);
+    }
+
+    #[test]
+    fn test_old_snapshots_with_single_superior() {
+        let oc: ObjectClassInfo = serde_json::from_str(
+            r#"{"oid":"2.5.6.6","names":["person"],"description":null,"superior":"top","kind":"Structural","must":[],"may":[]}"#,
+        )
+        .unwrap();
+        assert_eq!(oc.superiors, vec!["top"]);
+        let oc: ObjectClassInfo = serde_json::from_str(
+            r#"{"oid":"2.5.6.0","names":["top"],"description":null,"superior":null,"kind":"Abstract","must":[],"may":[]}"#,
+        )
+        .unwrap();
+        assert!(oc.superiors.is_empty());
+        let at: AttributeTypeInfo = serde_json::from_str(
+            r#"{"oid":"2.5.4.3","names":["cn"],"description":null,"syntax":"String","single_value":false,"no_user_modification":false}"#,
+        )
+        .unwrap();
+        assert_eq!(at.superior, None);
+    }
+
     #[test]
     fn test_map_syntax_oid() {
         assert_eq!(
@@ -599,7 +985,7 @@ mod tests {
             oid: "2.5.6.0".to_string(),
             names: vec!["top".to_string()],
             description: None,
-            superior: None,
+            superiors: Vec::new(),
             kind: ObjectClassKind::Abstract,
             must: vec!["objectClass".to_string()],
             may: vec![],
@@ -608,7 +994,7 @@ mod tests {
             oid: "2.5.6.6".to_string(),
             names: vec!["person".to_string()],
             description: None,
-            superior: Some("top".to_string()),
+            superiors: vec!["top".to_string()],
             kind: ObjectClassKind::Structural,
             must: vec!["sn".to_string(), "cn".to_string()],
             may: vec!["userPassword".to_string(), "telephoneNumber".to_string()],
@@ -617,7 +1003,7 @@ mod tests {
             oid: "2.16.840.1.113730.3.2.2".to_string(),
             names: vec!["inetOrgPerson".to_string()],
             description: None,
-            superior: Some("person".to_string()),
+            superiors: vec!["person".to_string()],
             kind: ObjectClassKind::Structural,
             must: vec![],
             may: vec!["mail".to_string(), "uid".to_string()],
@@ -643,6 +1029,7 @@ mod tests {
                 oid: oid.to_string(),
                 names: vec![name.to_string()],
                 description: None,
+                superior: None,
                 syntax: AttributeSyntax::String,
                 single_value: false,
                 no_user_modification: no_user_mod,
@@ -675,6 +1062,53 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_class_attributes_include_inherited() {
+        let schema = build_test_schema();
+        let attrs = schema.class_attributes("inetorgperson");
+        let summary: Vec<(&str, bool, &str)> = attrs
+            .iter()
+            .map(|a| (a.name.as_str(), a.required, a.declared_by.as_str()))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("mail", false, "inetOrgPerson"),
+                ("uid", false, "inetOrgPerson"),
+                ("sn", true, "person"),
+                ("cn", true, "person"),
+                ("userPassword", false, "person"),
+                ("telephoneNumber", false, "person"),
+                ("objectClass", true, "top"),
+            ]
+        );
+        assert!(schema.class_attributes("nope").is_empty());
+
+        let users: Vec<String> = schema
+            .classes_using("SN")
+            .iter()
+            .map(|oc| class_name(oc))
+            .collect();
+        assert_eq!(users, ["person"]);
+        assert_eq!(
+            schema
+                .attribute_type_list()
+                .iter()
+                .map(|at| attribute_name(at))
+                .collect::<Vec<_>>(),
+            [
+                "cn",
+                "createTimestamp",
+                "mail",
+                "objectClass",
+                "sn",
+                "telephoneNumber",
+                "uid",
+                "userPassword"
+            ]
+        );
+    }
+
     #[test]
     fn test_allowed_attributes_deduplicates() {
         let schema = build_test_schema();
@@ -684,6 +1118,32 @@ mod tests {
         assert_eq!(cn_count, 1, "cn should appear exactly once");
     }
 
+    #[test]
+    fn test_required_and_optional_attributes() {
+        let schema = build_test_schema();
+        // person's MUST via inetOrgPerson; top's objectClass is left out
+        assert_eq!(
+            schema.required_attributes(&["inetOrgPerson"]),
+            vec!["sn", "cn"]
+        );
+        assert_eq!(
+            schema.optional_attributes(&["inetOrgPerson"]),
+            vec!["mail", "telephoneNumber", "uid", "userPassword"]
+        );
+        assert_eq!(
+            schema.required_attributes(&["person", "inetOrgPerson", "unknown"]),
+            vec!["sn", "cn"]
+        );
+        assert!(schema.required_attributes(&["unknown"]).is_empty());
+
+        let names: Vec<String> = schema
+            .object_class_list()
+            .into_iter()
+            .map(class_name)
+            .collect();
+        assert_eq!(names, vec!["inetOrgPerson", "person", "top"]);
+    }
+
     #[test]
     fn test_all_user_attributes() {
         let schema = build_test_schema();
@@ -700,4 +1160,155 @@ mod tests {
             "no_user_modification attrs excluded"
         );
     }
+    fn posix_account_schema() -> SchemaCache {
+        let mut schema = build_test_schema();
+        schema.object_classes.insert(
+            "posixaccount".to_string(),
+            ObjectClassInfo {
+                oid: "1.3.6.1.1.1.2.0".to_string(),
+                names: vec!["posixAccount".to_string()],
+                description: None,
+                superiors: vec!["top".to_string()],
+                kind: ObjectClassKind::Auxiliary,
+                must: vec!["cn".to_string(), "uid".to_string(), "uidNumber".to_string()],
+                may: vec![],
+            },
+        );
+        for (oid, name) in [("1.3.6.1.1.1.1.0", "uidNumber"), ("2.5.4.4", "surname")] {
+            schema.attribute_types.insert(
+                name.to_lowercase(),
+                AttributeTypeInfo {
+                    oid: oid.to_string(),
+                    names: vec![name.to_string()],
+                    description: None,
+                    superior: None,
+                    syntax: AttributeSyntax::String,
+                    single_value: false,
+                    no_user_modification: false,
+                },
+            );
+        }
+        schema
+    }
+
+    fn entry_with(attrs: &[(&str, &[&str])]) -> LdapEntry {
+        let map = attrs
+            .iter()
+            .map(|(k, vs)| (k.to_string(), vs.iter().map(|v| v.to_string()).collect()))
+            .collect();
+        LdapEntry::new("uid=jdoe,ou=people,dc=example,dc=com".to_string(), map)
+    }
+
+    fn group_names(groups: &[AttributeGroup]) -> Vec<(Option<&str>, Vec<&str>)> {
+        groups
+            .iter()
+            .map(|g| {
+                (
+                    g.object_class.as_deref(),
+                    g.attributes.iter().map(|a| a.name.as_str()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_dn_reference() {
+        let mut schema = build_test_schema();
+        schema.attribute_types.insert(
+            "secretary".to_string(),
+            AttributeTypeInfo {
+                oid: "0.9.2342.19200300.100.1.21".to_string(),
+                names: vec!["secretary".to_string()],
+                description: None,
+                superior: None,
+                syntax: AttributeSyntax::Dn,
+                single_value: false,
+                no_user_modification: false,
+            },
+        );
+        let allowlist = vec!["Manager".to_string()];
+        let dn = "uid=boss,ou=people,dc=example,dc=com";
+
+        // Listed, or DN syntax in the schema
+        assert_eq!(
+            dn_reference("manager", dn, None, &allowlist),
+            Dn::parse(dn).ok()
+        );
+        assert!(dn_reference("secretary", dn, Some(&schema), &[]).is_some());
+        assert!(dn_reference("secretary", dn, None, &[]).is_none());
+        assert!(dn_reference("description", dn, Some(&schema), &allowlist).is_none());
+
+        // The value must be a DN
+        assert!(dn_reference("manager", "Jane Boss", None, &allowlist).is_none());
+        assert!(dn_reference("manager", "", None, &allowlist).is_none());
+        assert!(dn_reference("manager", "cn=a,,dc=com", None, &allowlist).is_none());
+    }
+
+    #[test]
+    fn test_group_attributes_by_object_class() {
+        let schema = posix_account_schema();
+        let entry = entry_with(&[
+            (
+                "objectClass",
+                &["top", "person", "inetOrgPerson", "posixAccount"],
+            ),
+            ("cn", &["John Doe"]),
+            ("surname", &["Doe"]),
+            ("mail", &["jdoe@example.com"]),
+            ("uid", &["jdoe"]),
+            ("uidNumber", &["1000"]),
+            ("createTimestamp", &["20240131123000Z"]),
+            ("x-custom", &["1"]),
+        ]);
+        let groups = group_attributes(&entry, &schema);
+        assert_eq!(
+            group_names(&groups),
+            vec![
+                (Some("person"), vec!["cn", "surname"]),
+                (Some("inetOrgPerson"), vec!["mail", "uid"]),
+                (Some("posixAccount"), vec!["uidNumber"]),
+                (Some("top"), vec!["objectClass"]),
+                (None, vec!["createTimestamp", "x-custom"]),
+            ]
+        );
+        assert_eq!(groups[2].kind, Some(ObjectClassKind::Auxiliary));
+        let sn = &groups[0].attributes[1];
+        assert!(sn.required, "surname is an alias of the MUST attribute sn");
+    }
+
+    #[test]
+    fn test_group_attributes_shared_attribute_goes_to_structural() {
+        let schema = posix_account_schema();
+        let entry = entry_with(&[
+            ("objectClass", &["posixAccount", "inetOrgPerson"]),
+            ("cn", &["John Doe"]),
+            ("uid", &["jdoe"]),
+        ]);
+        let groups = group_attributes(&entry, &schema);
+        let cn = &groups[0].attributes[0];
+        assert_eq!(groups[0].object_class.as_deref(), Some("person"));
+        assert_eq!(cn.name, "cn");
+        assert!(cn.required);
+        assert_eq!(cn.also_in, vec!["posixAccount"]);
+
+        // uid is only MAY in inetOrgPerson, but the auxiliary class requires it
+        let uid = &groups[1].attributes[0];
+        assert_eq!(groups[1].object_class.as_deref(), Some("inetOrgPerson"));
+        assert!(uid.required);
+        assert_eq!(uid.also_in, vec!["posixAccount"]);
+        assert!(groups
+            .iter()
+            .all(|g| g.object_class.as_deref() != Some("posixAccount")));
+    }
+
+    #[test]
+    fn test_group_attributes_unknown_class_falls_to_other() {
+        let schema = build_test_schema();
+        let entry = entry_with(&[("objectClass", &["mystery"]), ("cn", &["x"])]);
+        let groups = group_attributes(&entry, &schema);
+        assert_eq!(
+            group_names(&groups),
+            vec![(None, vec!["cn", "objectClass"])]
+        );
+    }
 }
@@ -0,0 +1,357 @@
+//! Per-entry scripts in [Rhai](https://rhai.rs), for values that config
+//! alone cannot express, e.g. deriving `mail` from `givenName` and `sn`.
+//!
+//! A script sees the entry as `entry`, read-only:
+//!
+//! - `entry.dn()` -- the DN
+//! - `entry.get("attr")` -- all values, as an array (empty when missing)
+//! - `entry.first("attr")` -- the first value, or `""`
+//!
+//! The script's value becomes the result: a string, an array of values,
+//! or `()` for none. Scripts cannot reach files, the network or the
+//! terminal: there is no module loading, `eval`, `print` or `debug`, and
+//! each run stops after a fixed number of operations or a time limit.
+
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, Dynamic, Engine, Scope, AST};
+
+use crate::bulk::BulkMod;
+use crate::entry::LdapEntry;
+
+/// Resource limits for one script run.
+#[derive(Debug, Clone, Copy)]
+pub struct ScriptLimits {
+    pub max_operations: u64,
+    pub max_time: Duration,
+}
+
+impl Default for ScriptLimits {
+    fn default() -> Self {
+        Self {
+            max_operations: 100_000,
+            max_time: Duration::from_millis(250),
+        }
+    }
+}
+
+/// The read-only entry handed to a script.
+#[derive(Debug, Clone)]
+struct ScriptEntry(Arc<LdapEntry>);
+
+impl ScriptEntry {
+    fn values(&self, attr: &str) -> Option<&Vec<String>> {
+        self.0
+            .attributes
+            .iter()
+            .find(|(k, _)| k.eq_ignore_ascii_case(attr))
+            .map(|(_, v)| v)
+    }
+}
+
+/// A compiled script, ready to run against many entries.
+pub struct EntryScript {
+    engine: Engine,
+    ast: AST,
+    limits: ScriptLimits,
+    /// When the current run must stop; read by the progress callback.
+    deadline: Arc<Mutex<Instant>>,
+}
+
+impl EntryScript {
+    /// Compile `source` with the default limits.
+    pub fn compile(source: &str) -> Result<Self, String> {
+        Self::compile_with_limits(source, ScriptLimits::default())
+    }
+
+    pub fn compile_with_limits(source: &str, limits: ScriptLimits) -> Result<Self, String> {
+        let deadline = Arc::new(Mutex::new(Instant::now()));
+        let mut engine = Engine::new();
+        engine
+            .set_module_resolver(DummyModuleResolver::new())
+            .disable_symbol("eval")
+            .on_print(|_| {})
+            .on_debug(|_, _, _| {})
+            .set_max_operations(limits.max_operations)
+            .set_max_call_levels(32)
+            .set_max_expr_depths(64, 32)
+            .set_max_string_size(64 * 1024)
+            .set_max_array_size(10_000)
+            .set_max_map_size(10_000);
+
+        let watch = deadline.clone();
+        engine.on_progress(move |_| {
+            let past = watch.lock().map(|d| Instant::now() >= *d).unwrap_or(true);
+            past.then(|| Dynamic::from("time limit exceeded"))
+        });
+
+        engine
+            .register_type_with_name::<ScriptEntry>("Entry")
+            .register_fn("dn", |e: &mut ScriptEntry| e.0.dn.clone())
+            .register_fn("get", |e: &mut ScriptEntry, attr: &str| -> Array {
+                e.values(attr)
+                    .map(|v| v.iter().cloned().map(Dynamic::from).collect())
+                    .unwrap_or_default()
+            })
+            .register_fn("first", |e: &mut ScriptEntry, attr: &str| {
+                e.values(attr)
+                    .and_then(|v| v.first().cloned())
+                    .unwrap_or_default()
+            });
+
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        Ok(Self {
+            engine,
+            ast,
+            limits,
+            deadline,
+        })
+    }
+
+    /// Run the script for one entry and return the values it produced.
+    pub fn eval(&self, entry: &LdapEntry) -> Result<Vec<String>, String> {
+        if let Ok(mut deadline) = self.deadline.lock() {
+            *deadline = Instant::now() + self.limits.max_time;
+        }
+        let mut scope = Scope::new();
+        scope.push_constant("entry", ScriptEntry(Arc::new(entry.clone())));
+        let result: Dynamic = self
+            .engine
+            .eval_ast_with_scope(&mut scope, &self.ast)
+            .map_err(|e| match *e {
+                rhai::EvalAltResult::ErrorTerminated(reason, _) => reason.to_string(),
+                other => other.to_string(),
+            })?;
+        to_values(result)
+    }
+}
+
+fn to_values(result: Dynamic) -> Result<Vec<String>, String> {
+    if result.is_unit() {
+        return Ok(Vec::new());
+    }
+    if result.is_array() {
+        let array = result.cast::<Array>();
+        return array
+            .into_iter()
+            .map(|v| scalar(v).ok_or_else(|| "array items must be strings".to_string()))
+            .collect();
+    }
+    let type_name = result.type_name().to_string();
+    scalar(result)
+        .map(|v| vec![v])
+        .ok_or_else(|| format!("script returned {}, expected a string or array", type_name))
+}
+
+fn scalar(value: Dynamic) -> Option<String> {
+    if value.is_string() || value.is_char() || value.is_int() || value.is_bool() {
+        Some(value.to_string())
+    } else {
+        None
+    }
+}
+
+/// How computed values are written to the attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScriptedOp {
+    /// The computed values become the attribute's values.
+    Replace,
+    Add,
+    Delete,
+}
+
+/// Modifications computed per entry, and the entries whose script failed.
+#[derive(Debug, Default)]
+pub struct ScriptedPlan {
+    pub changes: Vec<(String, Vec<BulkMod>)>,
+    pub errors: Vec<(String, String)>, // (dn, error_message)
+}
+
+/// Run `script` for each entry and turn its values into modifications of
+/// `attr`. A failing entry is recorded and the rest carry on. Entries for
+/// which the script yields nothing to add or delete are left out.
+pub fn plan_scripted_update(
+    entries: &[LdapEntry],
+    attr: &str,
+    op: ScriptedOp,
+    script: &EntryScript,
+) -> ScriptedPlan {
+    let mut plan = ScriptedPlan::default();
+    for entry in entries {
+        let values = match script.eval(entry) {
+            Ok(values) => values,
+            Err(e) => {
+                plan.errors.push((entry.dn.clone(), e));
+                continue;
+            }
+        };
+        let mods: Vec<BulkMod> = match op {
+            ScriptedOp::Replace => vec![BulkMod::ReplaceValues {
+                attr: attr.to_string(),
+                values,
+            }],
+            ScriptedOp::Add => values
+                .into_iter()
+                .map(|value| BulkMod::AddValue {
+                    attr: attr.to_string(),
+                    value,
+                })
+                .collect(),
+            ScriptedOp::Delete => values
+                .into_iter()
+                .map(|value| BulkMod::DeleteValue {
+                    attr: attr.to_string(),
+                    value,
+                })
+                .collect(),
+        };
+        if !mods.is_empty() {
+            plan.changes.push((entry.dn.clone(), mods));
+        }
+    }
+    plan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn person(given: &str, sn: &str) -> LdapEntry {
+        LdapEntry::new(
+            format!("uid={},ou=People,dc=example,dc=com", sn.to_lowercase()),
+            BTreeMap::from([
+                ("givenName".to_string(), vec![given.to_string()]),
+                ("sn".to_string(), vec![sn.to_string()]),
+                (
+                    "objectClass".to_string(),
+                    vec!["top".to_string(), "inetOrgPerson".to_string()],
+                ),
+            ]),
+        )
+    }
+
+    const MAIL_SCRIPT: &str = r#"
+        fn ascii(s) {
+            let out = s.to_lower();
+            for pair in [["ä", "ae"], ["ö", "oe"], ["ü", "ue"], ["ß", "ss"], ["é", "e"], [" ", "."]] {
+                out.replace(pair[0], pair[1]);
+            }
+            out
+        }
+        let given = entry.first("givenName");
+        let sn = entry.first("SN");
+        if given == "" || sn == "" {
+            throw "givenName and sn are required";
+        }
+        ascii(given) + "." + ascii(sn) + "@example.com"
+    "#;
+
+    #[test]
+    fn test_mail_derivation_with_transliteration() {
+        let script = EntryScript::compile(MAIL_SCRIPT).unwrap();
+        assert_eq!(
+            script
+                .eval(&person("Jürgen", "Müller-Lüdenscheidt"))
+                .unwrap(),
+            vec!["juergen.mueller-luedenscheidt@example.com"]
+        );
+        assert_eq!(
+            script.eval(&person("Renée", "Straße")).unwrap(),
+            vec!["renee.strasse@example.com"]
+        );
+        let err = script.eval(&person("", "Doe")).unwrap_err();
+        assert!(err.contains("givenName and sn are required"), "{}", err);
+    }
+
+    #[test]
+    fn test_entry_view_and_return_values() {
+        let entry = person("Ada", "Lovelace");
+        let eval = |src: &str| EntryScript::compile(src).unwrap().eval(&entry);
+        assert_eq!(
+            eval("entry.dn()").unwrap(),
+            vec!["uid=lovelace,ou=People,dc=example,dc=com"]
+        );
+        assert_eq!(
+            eval(r#"entry.get("objectclass")"#).unwrap(),
+            vec!["top", "inetOrgPerson"]
+        );
+        assert!(eval(r#"entry.get("mail")"#).unwrap().is_empty());
+        assert_eq!(eval(r#"entry.first("mail")"#).unwrap(), vec![""]);
+        assert!(eval("()").unwrap().is_empty());
+        assert_eq!(eval("42").unwrap(), vec!["42"]);
+        assert!(eval("#{ a: 1 }").is_err());
+        // The view is read-only
+        assert!(EntryScript::compile(r#"entry = 1; "x""#)
+            .and_then(|s| s.eval(&entry))
+            .is_err());
+    }
+
+    #[test]
+    fn test_sandbox_limits() {
+        let entry = person("Ada", "Lovelace");
+
+        let err = EntryScript::compile("loop {}")
+            .unwrap()
+            .eval(&entry)
+            .unwrap_err();
+        assert!(err.contains("operations"), "{}", err);
+
+        let slow = EntryScript::compile_with_limits(
+            "loop {}",
+            ScriptLimits {
+                max_operations: 0, // unlimited: only the clock stops it
+                max_time: Duration::from_millis(50),
+            },
+        )
+        .unwrap();
+        let started = Instant::now();
+        let err = slow.eval(&entry).unwrap_err();
+        assert!(err.contains("time limit"), "{}", err);
+        assert!(started.elapsed() < Duration::from_secs(5));
+
+        let err = EntryScript::compile(r#"let s = "x"; loop { s += s; }"#)
+            .unwrap()
+            .eval(&entry)
+            .unwrap_err();
+        assert!(err.to_lowercase().contains("length"), "{}", err);
+
+        // No way out to files, other scripts or the terminal
+        assert!(EntryScript::compile(r#"import "/etc/passwd" as m; 1"#)
+            .and_then(|s| s.eval(&entry))
+            .is_err());
+        assert!(EntryScript::compile(r#"eval("1")"#).is_err());
+        assert_eq!(
+            EntryScript::compile(r#"print("leak"); debug("leak"); "ok""#)
+                .unwrap()
+                .eval(&entry)
+                .unwrap(),
+            vec!["ok"]
+        );
+    }
+
+    #[test]
+    fn test_errors_do_not_abort_the_batch() {
+        let script = EntryScript::compile(MAIL_SCRIPT).unwrap();
+        let entries = vec![
+            person("Ada", "Lovelace"),
+            person("", "Nobody"),
+            person("Alan", "Turing"),
+        ];
+        let plan = plan_scripted_update(&entries, "mail", ScriptedOp::Replace, &script);
+        assert_eq!(plan.changes.len(), 2);
+        assert_eq!(plan.errors.len(), 1);
+        assert_eq!(plan.errors[0].0, "uid=nobody,ou=People,dc=example,dc=com");
+        assert!(matches!(
+            &plan.changes[1].1[0],
+            BulkMod::ReplaceValues { attr, values }
+                if attr == "mail" && values == &["alan.turing@example.com"]
+        ));
+
+        let none = EntryScript::compile("[]").unwrap();
+        let plan = plan_scripted_update(&entries, "mail", ScriptedOp::Add, &none);
+        assert!(plan.changes.is_empty() && plan.errors.is_empty());
+    }
+}
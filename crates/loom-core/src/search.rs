@@ -1,17 +1,265 @@
-use ldap3::{Scope, SearchEntry};
-use tracing::debug;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Instant;
 
-use crate::connection::LdapConnection;
+use ldap3::adapters::EntriesOnly;
+use ldap3::{Ldap, LdapResult, ResultEntry, Scope, SearchEntry, SearchResult};
+use tracing::{debug, info, warn};
+
+use crate::connection::{note_request, LdapConnection};
 use crate::entry::LdapEntry;
 use crate::error::CoreError;
+use crate::freshness::TrackedEntry;
+use crate::ops::OpFuture;
+use crate::tree::ChildQuery;
+
+/// OID of the server-side sort request control (RFC 2891).
+pub const SERVER_SIDE_SORT_OID: &str = crate::oids::SERVER_SIDE_SORT;
+
+/// One page of a one-level child listing.
+#[derive(Debug, Clone)]
+pub struct ChildPage {
+    pub entries: Vec<LdapEntry>,
+    /// Cookie for the following page; empty when this was the last one.
+    pub next_cookie: Vec<u8>,
+    /// How many children the server counted, when the page is a window of
+    /// a virtual list view rather than a paged result.
+    pub total: Option<u64>,
+}
+
+/// Sort key of a virtual list view over a listing that has none of its
+/// own; the control needs the results in some order.
+const VLV_SORT_ATTR: &str = "cn";
+
+/// How a paged search runs, beyond what it looks for.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PageOptions<'a> {
+    /// Attribute the server sorts by, and whether descending. Only send
+    /// it to servers that advertise server-side sort.
+    pub sort: Option<(&'a str, bool)>,
+    /// Once set, the search ends after the page in flight.
+    pub stop: Option<&'a AtomicBool>,
+}
 
 impl LdapConnection {
+    /// The paged results control for a request, or none when the server
+    /// does not advertise it; the whole result then comes in one page.
+    fn paged_controls(&self, page_size: u32, cookie: &[u8]) -> Vec<ldap3::controls::RawControl> {
+        if !self.paged_results {
+            return Vec::new();
+        }
+        vec![ldap3::controls::RawControl {
+            ctype: crate::oids::PAGED_RESULTS.to_string(),
+            crit: false,
+            val: Some(encode_paged_results_control(page_size, cookie)),
+        }]
+    }
+
     /// Search for immediate children of the given DN.
     pub async fn search_children(&mut self, parent_dn: &str) -> Result<Vec<LdapEntry>, CoreError> {
         self.search(parent_dn, Scope::OneLevel, "(objectClass=*)", &["*"])
             .await
     }
 
+    /// Fetch one page of the children of `parent_dn`.
+    ///
+    /// `cookie` resumes an earlier listing (empty for the first page). With
+    /// `server_sort`, the query's sort key is sent as a server-side sort
+    /// control; otherwise the page is sorted locally after it arrives.
+    pub async fn search_children_page(
+        &mut self,
+        parent_dn: &str,
+        query: &ChildQuery,
+        page_size: usize,
+        cookie: &[u8],
+        server_sort: bool,
+    ) -> Result<ChildPage, CoreError> {
+        let mut controls = self.paged_controls(page_size as u32, cookie);
+        if let (true, Some(attr)) = (server_sort, query.sort_attr.as_deref()) {
+            controls.push(ldap3::controls::RawControl {
+                ctype: SERVER_SIDE_SORT_OID.to_string(),
+                crit: false,
+                val: Some(encode_sort_control(attr, query.sort_desc)),
+            });
+        }
+
+        self.check_health()?;
+        let (entries, res) = send_search(
+            self.ldap.with_controls(controls),
+            parent_dn,
+            Scope::OneLevel,
+            query.ldap_filter(),
+            vec!["*"],
+        )
+        .await?;
+
+        let mut entries: Vec<LdapEntry> = entries
+            .into_iter()
+            .map(|e| LdapEntry::from_search_entry(SearchEntry::construct(e)))
+            .collect();
+        if !server_sort {
+            if let Some(ref attr) = query.sort_attr {
+                sort_entries(&mut entries, attr, query.sort_desc);
+            }
+        }
+
+        Ok(ChildPage {
+            entries,
+            next_cookie: extract_paged_results_cookie(&res),
+            total: None,
+        })
+    }
+
+    /// Fetch page `page` of a child listing.
+    ///
+    /// On servers with a virtual list view the page is asked for by its
+    /// offset. Otherwise the stored `cookie` is tried first. Servers drop
+    /// paged-results state when another search runs on the connection, so
+    /// if the cookie is rejected (or unknown) the listing is walked again
+    /// from the first page.
+    pub async fn search_children_page_at(
+        &mut self,
+        parent_dn: &str,
+        query: &ChildQuery,
+        page_size: usize,
+        page: usize,
+        cookie: Option<&[u8]>,
+        server_sort: bool,
+    ) -> Result<ChildPage, CoreError> {
+        if self.vlv {
+            match self
+                .search_children_window(parent_dn, query, page_size, page)
+                .await
+            {
+                Ok(window) => return Ok(window),
+                Err(e) if LdapConnection::is_connection_error(&e) => return Err(e),
+                Err(e) => {
+                    // Paging works everywhere; stop trying the view
+                    debug!("Virtual list view of '{}' failed: {}", parent_dn, e);
+                    self.vlv = false;
+                }
+            }
+        }
+
+        if let Some(cookie) = cookie {
+            match self
+                .search_children_page(parent_dn, query, page_size, cookie, server_sort)
+                .await
+            {
+                Ok(result) => return Ok(result),
+                Err(e) if page == 0 || LdapConnection::is_connection_error(&e) => return Err(e),
+                Err(e) => debug!("Stale paging cookie for '{}': {}", parent_dn, e),
+            }
+        }
+
+        let mut cookie = Vec::new();
+        for skipped in 0..page {
+            let result = self
+                .search_children_page(parent_dn, query, page_size, &cookie, server_sort)
+                .await?;
+            if result.next_cookie.is_empty() {
+                debug!("Child listing of '{}' ended at page {}", parent_dn, skipped);
+                return Ok(ChildPage {
+                    entries: Vec::new(),
+                    next_cookie: Vec::new(),
+                    total: None,
+                });
+            }
+            cookie = result.next_cookie;
+        }
+        self.search_children_page(parent_dn, query, page_size, &cookie, server_sort)
+            .await
+    }
+
+    /// Fetch page `page` of a child listing as a virtual list view window,
+    /// sorted on the server by the query's sort key (`cn` without one).
+    /// Only the entries of the window cross the wire, however deep into the
+    /// listing it is.
+    pub async fn search_children_window(
+        &mut self,
+        parent_dn: &str,
+        query: &ChildQuery,
+        page_size: usize,
+        page: usize,
+    ) -> Result<ChildPage, CoreError> {
+        let sort_attr = query.sort_attr.as_deref().unwrap_or(VLV_SORT_ATTR);
+        let page_size = page_size.max(1) as u32;
+        let offset = page as u32 * page_size + 1;
+        let controls = vec![
+            ldap3::controls::RawControl {
+                ctype: SERVER_SIDE_SORT_OID.to_string(),
+                crit: true,
+                val: Some(encode_sort_control(sort_attr, query.sort_desc)),
+            },
+            ldap3::controls::RawControl {
+                ctype: crate::oids::VIRTUAL_LIST_VIEW.to_string(),
+                crit: true,
+                val: Some(encode_vlv_request(0, page_size - 1, offset)),
+            },
+        ];
+
+        self.check_health()?;
+        let (entries, res) = send_search(
+            self.ldap.with_controls(controls),
+            parent_dn,
+            Scope::OneLevel,
+            query.ldap_filter(),
+            vec!["*"],
+        )
+        .await?;
+        let response = res
+            .ctrls
+            .iter()
+            .find(|c| c.1.ctype == crate::oids::VIRTUAL_LIST_VIEW_RESPONSE)
+            .and_then(|c| c.1.val.as_deref())
+            .and_then(parse_vlv_response)
+            .ok_or_else(|| CoreError::SearchFailed("no virtual list view response".to_string()))?;
+        if response.result != 0 {
+            return Err(CoreError::SearchFailed(format!(
+                "virtual list view failed (result {})",
+                response.result
+            )));
+        }
+
+        let entries: Vec<LdapEntry> = entries
+            .into_iter()
+            .map(|e| LdapEntry::from_search_entry(SearchEntry::construct(e)))
+            .collect();
+        debug!(
+            "VLV window of '{}' at {}: {} of {} entries",
+            parent_dn,
+            offset,
+            entries.len(),
+            response.content_count
+        );
+        Ok(ChildPage {
+            entries,
+            next_cookie: Vec::new(),
+            total: Some(response.content_count),
+        })
+    }
+
+    /// Approximate number of immediate children, from `numSubordinates`
+    /// (389-DS, OpenDJ, ApacheDS) or `msDS-Approx-Immed-Subordinates` (AD).
+    /// `None` when the server exposes neither.
+    pub async fn count_children_hint(&mut self, dn: &str) -> Option<u64> {
+        let attrs = ["numSubordinates", "msDS-Approx-Immed-Subordinates"];
+        self.check_health().ok()?;
+        let result = self
+            .ldap
+            .search(dn, Scope::Base, "(objectClass=*)", attrs.to_vec())
+            .await
+            .ok()?;
+        let (entries, _res) = result.success().ok()?;
+        let entry =
+            LdapEntry::from_search_entry(SearchEntry::construct(entries.into_iter().next()?));
+        entry
+            .attributes
+            .iter()
+            .find(|(name, _)| attrs.iter().any(|a| a.eq_ignore_ascii_case(name)))
+            .and_then(|(_, values)| values.first())
+            .and_then(|v| v.trim().parse().ok())
+    }
+
     /// Search for a single entry by exact DN.
     /// Requests only user attributes ("*"). Operational attributes are excluded
     /// to avoid displaying non-modifiable server-internal attributes.
@@ -22,6 +270,16 @@ impl LdapConnection {
         Ok(results.into_iter().next())
     }
 
+    /// Read an entry together with its change markers (`modifyTimestamp`,
+    /// `entryCSN`), split off so the entry holds only user attributes and
+    /// the operational ones telling whether an account can log in.
+    pub async fn search_entry_tracked(
+        &mut self,
+        dn: &str,
+    ) -> Result<Option<TrackedEntry>, CoreError> {
+        crate::freshness::read_tracked(self, dn).await
+    }
+
     /// Search a subtree with the given filter.
     pub async fn search_subtree(
         &mut self,
@@ -42,22 +300,17 @@ impl LdapConnection {
         attrs: &[&str],
         limit: usize,
     ) -> Result<Vec<LdapEntry>, CoreError> {
-        let controls = vec![ldap3::controls::RawControl {
-            ctype: "1.2.840.113556.1.4.319".to_string(),
-            crit: false,
-            val: Some(encode_paged_results_control(limit as u32, &[])),
-        }];
-
-        let result = self
-            .ldap
-            .with_controls(controls)
-            .search(base_dn, Scope::Subtree, filter, attrs.to_vec())
-            .await
-            .map_err(CoreError::Ldap)?;
+        let controls = self.paged_controls(limit as u32, &[]);
 
-        let (entries, _res) = result
-            .success()
-            .map_err(|e| CoreError::SearchFailed(e.to_string()))?;
+        self.check_health()?;
+        let (entries, _res) = send_search(
+            self.ldap.with_controls(controls),
+            base_dn,
+            Scope::Subtree,
+            filter,
+            attrs.to_vec(),
+        )
+        .await?;
 
         let entries: Vec<LdapEntry> = entries
             .into_iter()
@@ -81,48 +334,292 @@ impl LdapConnection {
         filter: &str,
         attrs: &[&str],
     ) -> Result<Vec<LdapEntry>, CoreError> {
-        let page_size = self.settings.page_size;
         let mut all_entries = Vec::new();
-        let mut cookie = Vec::new();
+        self.search_pages(base_dn, scope, filter, attrs, |page| {
+            all_entries.extend(page)
+        })
+        .await?;
+        Ok(all_entries)
+    }
 
-        loop {
-            let controls = vec![ldap3::controls::RawControl {
-                ctype: "1.2.840.113556.1.4.319".to_string(), // pagedResultsControl OID
-                crit: false,
-                val: Some(encode_paged_results_control(page_size, &cookie)),
-            }];
+    /// Perform a paged LDAP search, handing each page to `on_page` as it
+    /// arrives. Returns the total number of entries.
+    pub async fn search_pages<F>(
+        &mut self,
+        base_dn: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: &[&str],
+        on_page: F,
+    ) -> Result<usize, CoreError>
+    where
+        F: FnMut(Vec<LdapEntry>),
+    {
+        self.search_pages_with(
+            base_dn,
+            scope,
+            filter,
+            attrs,
+            PageOptions::default(),
+            on_page,
+        )
+        .await
+    }
 
-            let result = self
-                .ldap
-                .with_controls(controls)
-                .search(base_dn, scope, filter, attrs.to_vec())
-                .await
-                .map_err(CoreError::Ldap)?;
+    /// Like [`search_pages`](Self::search_pages), sorted on the server and
+    /// stoppable as `options` say. A stopped search ends after the page in
+    /// flight and the server is told to drop the rest of the result set.
+    pub async fn search_pages_with<F>(
+        &mut self,
+        base_dn: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: &[&str],
+        options: PageOptions<'_>,
+        on_page: F,
+    ) -> Result<usize, CoreError>
+    where
+        F: FnMut(Vec<LdapEntry>),
+    {
+        let page_size = self.settings.page_size;
+        let never = AtomicBool::new(false);
+        let mut request = PagedRequest {
+            conn: self,
+            base_dn,
+            scope,
+            filter,
+            attrs,
+            sort: options.sort,
+        };
+        let started = Instant::now();
+        let result = page_through(
+            &mut request,
+            page_size,
+            options.stop.unwrap_or(&never),
+            on_page,
+        )
+        .await;
+        let elapsed = started.elapsed().as_millis();
+        match &result {
+            // Entry reads come with every move in the tree; keep them quiet
+            Ok(total) if scope == Scope::Base => debug!(
+                "Read '{}' filter={}: {} entries in {} ms",
+                base_dn, filter, total, elapsed
+            ),
+            Ok(total) => info!(
+                "Search base='{}' scope={:?} filter={}: {} entries in {} ms",
+                base_dn, scope, filter, total, elapsed
+            ),
+            Err(e) => warn!(
+                "Search base='{}' scope={:?} filter={} failed after {} ms: {}",
+                base_dn, scope, filter, elapsed, e
+            ),
+        }
+        result
+    }
+}
 
-            let (entries, res) = result
-                .success()
-                .map_err(|e| CoreError::SearchFailed(e.to_string()))?;
+/// Send one search request and wait for all of its entries, logging what
+/// was asked, the result code and how long the server took.
+async fn send_search<S: AsRef<str> + Send + Sync>(
+    ldap: &mut Ldap,
+    base_dn: &str,
+    scope: Scope,
+    filter: &str,
+    attrs: Vec<S>,
+) -> Result<(Vec<ResultEntry>, LdapResult), CoreError> {
+    debug!(
+        "search base='{}' scope={:?} filter={} attrs={:?}",
+        base_dn,
+        scope,
+        filter,
+        attrs.iter().map(AsRef::as_ref).collect::<Vec<&str>>()
+    );
+    let started = Instant::now();
+    let mut stream = ldap
+        .streaming_search_with(EntriesOnly::new(), base_dn, scope, filter, attrs)
+        .await
+        .map_err(CoreError::Ldap)?;
+    // The stream runs on its own handle, so its id is only known here
+    note_request(stream.ldap_handle().last_id());
+    let mut entries = Vec::new();
+    while let Some(entry) = stream.next().await.map_err(CoreError::Ldap)? {
+        entries.push(entry);
+    }
+    let result = SearchResult(entries, stream.finish().await);
+    debug!(
+        "search result rc={} text={} entries={} in {} ms",
+        result.1.rc,
+        result.1.text,
+        result.0.len(),
+        started.elapsed().as_millis()
+    );
+    result
+        .success()
+        .map_err(|e| CoreError::SearchFailed(e.to_string()))
+}
 
-            let count = entries.len();
-            for entry in entries {
-                all_entries.push(LdapEntry::from_search_entry(SearchEntry::construct(entry)));
-            }
+/// Fetches one page of a paged search at a time.
+trait PageSource {
+    /// The entries of the page at `cookie` (empty for the first page) and
+    /// the cookie of the next one, empty after the last page.
+    fn fetch_page<'a>(
+        &'a mut self,
+        page_size: u32,
+        cookie: &'a [u8],
+    ) -> OpFuture<'a, (Vec<LdapEntry>, Vec<u8>)>;
+}
 
-            debug!(
-                "Paged search: got {} entries (total: {})",
-                count,
-                all_entries.len()
-            );
+/// One search, resumed page by page on a live connection.
+struct PagedRequest<'c> {
+    conn: &'c mut LdapConnection,
+    base_dn: &'c str,
+    scope: Scope,
+    filter: &'c str,
+    attrs: &'c [&'c str],
+    sort: Option<(&'c str, bool)>,
+}
 
-            // Extract the cookie from the response control
-            cookie = extract_paged_results_cookie(&res);
-            if cookie.is_empty() {
-                break;
+impl PageSource for PagedRequest<'_> {
+    fn fetch_page<'a>(
+        &'a mut self,
+        page_size: u32,
+        cookie: &'a [u8],
+    ) -> OpFuture<'a, (Vec<LdapEntry>, Vec<u8>)> {
+        Box::pin(async move {
+            let mut controls = self.conn.paged_controls(page_size, cookie);
+            if let Some((attr, descending)) = self.sort {
+                controls.push(ldap3::controls::RawControl {
+                    ctype: SERVER_SIDE_SORT_OID.to_string(),
+                    crit: false,
+                    val: Some(encode_sort_control(attr, descending)),
+                });
             }
+
+            self.conn.check_health()?;
+            let (entries, res) = send_search(
+                self.conn.ldap.with_controls(controls),
+                self.base_dn,
+                self.scope,
+                self.filter,
+                self.attrs.to_vec(),
+            )
+            .await?;
+            let entries = entries
+                .into_iter()
+                .map(|e| LdapEntry::from_search_entry(SearchEntry::construct(e)))
+                .collect();
+            Ok((entries, extract_paged_results_cookie(&res)))
+        })
+    }
+}
+
+/// Fetch pages until the cookie comes back empty or `stop` is set. A
+/// stopped search sends a page size of 0 with the last cookie, which
+/// RFC 2696 defines as abandoning the rest of the result set.
+async fn page_through<S, F>(
+    source: &mut S,
+    page_size: u32,
+    stop: &AtomicBool,
+    mut on_page: F,
+) -> Result<usize, CoreError>
+where
+    S: PageSource,
+    F: FnMut(Vec<LdapEntry>),
+{
+    let mut total = 0;
+    let mut cookie = Vec::new();
+
+    loop {
+        let (entries, next) = source.fetch_page(page_size, &cookie).await?;
+        let count = entries.len();
+        total += count;
+        on_page(entries);
+
+        debug!("Paged search: got {} entries (total: {})", count, total);
+
+        cookie = next;
+        if cookie.is_empty() {
+            break;
         }
+        if stop.load(Ordering::Relaxed) {
+            debug!("Paged search stopped after {} entries", total);
+            source.fetch_page(0, &cookie).await?;
+            break;
+        }
+    }
 
-        Ok(all_entries)
+    Ok(total)
+}
+
+/// Sort entries in place by the first value of `attr`, compared with
+/// [`natural_cmp`]. Entries without the attribute sort last.
+pub fn sort_entries(entries: &mut [LdapEntry], attr: &str, descending: bool) {
+    fn key<'a>(e: &'a LdapEntry, attr: &str) -> Option<&'a str> {
+        e.attributes
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(attr))
+            .and_then(|(_, values)| values.first())
+            .map(String::as_str)
     }
+    entries.sort_by(|a, b| match (key(a, attr), key(b, attr)) {
+        (Some(x), Some(y)) if descending => natural_cmp(y, x),
+        (Some(x), Some(y)) => natural_cmp(x, y),
+        (Some(_), None) => std::cmp::Ordering::Less,
+        (None, Some(_)) => std::cmp::Ordering::Greater,
+        (None, None) => std::cmp::Ordering::Equal,
+    });
+}
+
+/// Compare two values the way a person would sort them: ignoring case,
+/// and with runs of digits compared as numbers, so `host2` comes before
+/// `host10`. Values equal that way fall back to plain comparison.
+pub fn natural_cmp(a: &str, b: &str) -> std::cmp::Ordering {
+    use std::cmp::Ordering;
+
+    let (mut x, mut y) = (a.chars().peekable(), b.chars().peekable());
+    while let (Some(&cx), Some(&cy)) = (x.peek(), y.peek()) {
+        let ordering = if cx.is_ascii_digit() && cy.is_ascii_digit() {
+            let digits = |it: &mut std::iter::Peekable<std::str::Chars>| {
+                let mut run = String::new();
+                while let Some(c) = it.next_if(char::is_ascii_digit) {
+                    run.push(c);
+                }
+                run
+            };
+            let (nx, ny) = (digits(&mut x), digits(&mut y));
+            let (tx, ty) = (nx.trim_start_matches('0'), ny.trim_start_matches('0'));
+            tx.len().cmp(&ty.len()).then_with(|| tx.cmp(ty))
+        } else {
+            x.next();
+            y.next();
+            cx.to_lowercase().cmp(cy.to_lowercase())
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    x.peek()
+        .is_some()
+        .cmp(&y.peek().is_some())
+        .then_with(|| a.cmp(b))
+}
+
+/// Encode a server-side sort request control value (RFC 2891) with one key.
+fn encode_sort_control(attr: &str, descending: bool) -> Vec<u8> {
+    // SEQUENCE OF SEQUENCE { attributeType, [1] reverseOrder BOOLEAN }
+    let mut key = ber_encode_octet_string(attr.as_bytes());
+    if descending {
+        key.extend_from_slice(&[0x81, 0x01, 0xFF]);
+    }
+    let mut key_seq = vec![0x30];
+    ber_encode_length(&mut key_seq, key.len());
+    key_seq.extend_from_slice(&key);
+
+    let mut result = vec![0x30];
+    ber_encode_length(&mut result, key_seq.len());
+    result.extend_from_slice(&key_seq);
+    result
 }
 
 /// Encode a Simple Paged Results control value (RFC 2696).
@@ -142,10 +639,69 @@ fn encode_paged_results_control(page_size: u32, cookie: &[u8]) -> Vec<u8> {
     result
 }
 
+/// Encode a virtual list view request (draft-ietf-ldapext-ldapv3-vlv)
+/// for the entry at 1-based `offset` and the ones around it. A content
+/// count of 0 tells the server to take the offset as is.
+fn encode_vlv_request(before: u32, after: u32, offset: u32) -> Vec<u8> {
+    // byOffset [0] SEQUENCE { INTEGER offset, INTEGER contentCount }
+    let mut target = ber_encode_integer(offset as i64);
+    target.extend_from_slice(&ber_encode_integer(0));
+    let mut by_offset = vec![0xA0];
+    ber_encode_length(&mut by_offset, target.len());
+    by_offset.extend_from_slice(&target);
+
+    // SEQUENCE { INTEGER beforeCount, INTEGER afterCount, target }
+    let mut content = ber_encode_integer(before as i64);
+    content.extend_from_slice(&ber_encode_integer(after as i64));
+    content.extend_from_slice(&by_offset);
+
+    let mut result = vec![0x30];
+    ber_encode_length(&mut result, content.len());
+    result.extend_from_slice(&content);
+    result
+}
+
+/// What the server said about a virtual list view window.
+#[derive(Debug, PartialEq, Eq)]
+struct VlvResponse {
+    /// 1-based position of the target entry.
+    target_position: u64,
+    /// The server's count of the whole list.
+    content_count: u64,
+    /// LDAP result code for the view; 0 on success.
+    result: u32,
+}
+
+/// Parse a virtual list view response control value:
+/// SEQUENCE { INTEGER targetPosition, INTEGER contentCount, ENUMERATED result, ... }
+fn parse_vlv_response(data: &[u8]) -> Option<VlvResponse> {
+    let (tag, seq, _) = ber_read_tlv(data)?;
+    if tag != 0x30 {
+        return None;
+    }
+    let (tag, position, rest) = ber_read_tlv(seq)?;
+    if tag != 0x02 {
+        return None;
+    }
+    let (tag, count, rest) = ber_read_tlv(rest)?;
+    if tag != 0x02 {
+        return None;
+    }
+    let (tag, result, _) = ber_read_tlv(rest)?;
+    if tag != 0x0A {
+        return None;
+    }
+    Some(VlvResponse {
+        target_position: ber_decode_integer(position).max(0) as u64,
+        content_count: ber_decode_integer(count).max(0) as u64,
+        result: ber_decode_integer(result).max(0) as u32,
+    })
+}
+
 /// Extract the cookie from a paged results response control.
 fn extract_paged_results_cookie(res: &ldap3::LdapResult) -> Vec<u8> {
     for ctrl in &res.ctrls {
-        if ctrl.1.ctype == "1.2.840.113556.1.4.319" {
+        if ctrl.1.ctype == crate::oids::PAGED_RESULTS {
             if let Some(ref val) = ctrl.1.val {
                 return parse_paged_results_cookie(val);
             }
@@ -226,6 +782,21 @@ fn ber_encode_length(buf: &mut Vec<u8>, len: usize) {
     }
 }
 
+/// Split one tag-length-value element off the front of `data`, as
+/// (tag, value, rest).
+pub(crate) fn ber_read_tlv(data: &[u8]) -> Option<(u8, &[u8], &[u8])> {
+    let (&tag, rest) = data.split_first()?;
+    let (len, offset) = ber_decode_length(rest);
+    let value = rest.get(offset..offset + len)?;
+    Some((tag, value, &rest[offset + len..]))
+}
+
+pub(crate) fn ber_decode_integer(bytes: &[u8]) -> i64 {
+    let negative = bytes.first().is_some_and(|b| b & 0x80 != 0);
+    let start = if negative { -1 } else { 0 };
+    bytes.iter().fold(start, |acc, &b| (acc << 8) | b as i64)
+}
+
 fn ber_decode_length(data: &[u8]) -> (usize, usize) {
     if data.is_empty() {
         return (0, 0);
@@ -243,3 +814,167 @@ fn ber_decode_length(data: &[u8]) -> (usize, usize) {
         (len, 1 + num_bytes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn entry(dn: &str, sn: Option<&str>) -> LdapEntry {
+        let mut attrs = BTreeMap::new();
+        if let Some(sn) = sn {
+            attrs.insert("sn".to_string(), vec![sn.to_string()]);
+        }
+        LdapEntry::new(dn.to_string(), attrs)
+    }
+
+    #[test]
+    fn test_encode_sort_control() {
+        assert_eq!(
+            encode_sort_control("cn", false),
+            vec![0x30, 0x06, 0x30, 0x04, 0x04, 0x02, b'c', b'n']
+        );
+        assert_eq!(
+            encode_sort_control("cn", true),
+            vec![0x30, 0x09, 0x30, 0x07, 0x04, 0x02, b'c', b'n', 0x81, 0x01, 0xFF]
+        );
+    }
+
+    #[test]
+    fn test_paged_cookie_roundtrip() {
+        let encoded = encode_paged_results_control(1000, b"cookie");
+        assert_eq!(parse_paged_results_cookie(&encoded), b"cookie".to_vec());
+        let empty = encode_paged_results_control(1000, &[]);
+        assert!(parse_paged_results_cookie(&empty).is_empty());
+    }
+
+    #[test]
+    fn test_vlv_request_and_response() {
+        // Window of 3 entries starting at the 1001st
+        assert_eq!(
+            encode_vlv_request(0, 2, 1001),
+            vec![
+                0x30, 0x0F, 0x02, 0x01, 0x00, 0x02, 0x01, 0x02, 0xA0, 0x07, 0x02, 0x02, 0x03, 0xE9,
+                0x02, 0x01, 0x00
+            ]
+        );
+
+        // targetPosition 1001, contentCount 300000, success, with a context ID
+        let response = [
+            0x30, 0x10, 0x02, 0x02, 0x03, 0xE9, 0x02, 0x03, 0x04, 0x93, 0xE0, 0x0A, 0x01, 0x00,
+            0x04, 0x02, b'i', b'd',
+        ];
+        assert_eq!(
+            parse_vlv_response(&response),
+            Some(VlvResponse {
+                target_position: 1001,
+                content_count: 300_000,
+                result: 0,
+            })
+        );
+        assert_eq!(parse_vlv_response(&[0x30, 0x02, 0x02]), None);
+    }
+
+    #[test]
+    fn test_sort_entries_client_side() {
+        let mut entries = vec![
+            entry("cn=c", Some("beta")),
+            entry("cn=none", None),
+            entry("cn=a", Some("Alpha")),
+        ];
+        sort_entries(&mut entries, "SN", false);
+        let dns: Vec<&str> = entries.iter().map(|e| e.dn.as_str()).collect();
+        assert_eq!(dns, vec!["cn=a", "cn=c", "cn=none"]);
+
+        sort_entries(&mut entries, "sn", true);
+        let dns: Vec<&str> = entries.iter().map(|e| e.dn.as_str()).collect();
+        assert_eq!(dns, vec!["cn=c", "cn=a", "cn=none"]);
+    }
+
+    #[test]
+    fn test_natural_cmp() {
+        use std::cmp::Ordering;
+
+        let mut hosts = vec!["host10", "Host2", "host1", "host002b", "host", "HOST2"];
+        hosts.sort_by(|a, b| natural_cmp(a, b));
+        assert_eq!(
+            hosts,
+            vec!["host", "host1", "HOST2", "Host2", "host002b", "host10"]
+        );
+        assert_eq!(natural_cmp("9", "10"), Ordering::Less);
+        assert_eq!(natural_cmp("Ada", "ada"), Ordering::Less);
+        assert_eq!(natural_cmp("ada", "ada"), Ordering::Equal);
+    }
+
+    /// Serves three pages of two entries, recording the requests.
+    struct ThreePages {
+        requests: Vec<(u32, Vec<u8>)>,
+    }
+
+    impl PageSource for ThreePages {
+        fn fetch_page<'a>(
+            &'a mut self,
+            page_size: u32,
+            cookie: &'a [u8],
+        ) -> OpFuture<'a, (Vec<LdapEntry>, Vec<u8>)> {
+            self.requests.push((page_size, cookie.to_vec()));
+            let (page, next): (u8, &[u8]) = match cookie {
+                b"" => (1, b"page2"),
+                b"page2" => (2, b"page3"),
+                b"page3" => (3, b""),
+                _ => panic!("unexpected cookie {:?}", cookie),
+            };
+            let entries = if page_size == 0 {
+                Vec::new()
+            } else {
+                (0..2)
+                    .map(|i| entry(&format!("cn=p{}e{}", page, i), None))
+                    .collect()
+            };
+            Box::pin(async move { Ok((entries, next.to_vec())) })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_page_through_follows_cookies() {
+        let mut source = ThreePages {
+            requests: Vec::new(),
+        };
+        let mut pages = Vec::new();
+        let stop = AtomicBool::new(false);
+        let total = page_through(&mut source, 2, &stop, |page| pages.push(page.len()))
+            .await
+            .unwrap();
+
+        assert_eq!(total, 6);
+        assert_eq!(pages, vec![2, 2, 2]);
+        assert_eq!(
+            source.requests,
+            vec![
+                (2, Vec::new()),
+                (2, b"page2".to_vec()),
+                (2, b"page3".to_vec()),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_page_through_stops_after_current_page() {
+        let mut source = ThreePages {
+            requests: Vec::new(),
+        };
+        let stop = AtomicBool::new(false);
+        let total = page_through(&mut source, 2, &stop, |_| {
+            stop.store(true, Ordering::Relaxed)
+        })
+        .await
+        .unwrap();
+
+        // The first page is kept, then the result set is abandoned
+        assert_eq!(total, 2);
+        assert_eq!(
+            source.requests,
+            vec![(2, Vec::new()), (0, b"page2".to_vec())]
+        );
+    }
+}
@@ -7,6 +7,7 @@ use tracing::{debug, info};
 
 use crate::connection::LdapConnection;
 use crate::error::CoreError;
+use crate::oids;
 use crate::util::{get_first, get_values, has_attr};
 
 /// Known LDAP server types.
@@ -37,10 +38,69 @@ pub struct RootDse {
     pub vendor_version: Option<String>,
     pub supported_controls: Vec<String>,
     pub supported_extensions: Vec<String>,
+    /// `supportedFeatures`, and Active Directory's `supportedCapabilities`.
+    pub supported_features: Vec<String>,
+    pub supported_ldap_versions: Vec<String>,
+    pub supported_sasl_mechanisms: Vec<String>,
     pub server_type: ServerType,
     pub raw: BTreeMap<String, Vec<String>>,
 }
 
+impl RootDse {
+    /// Interpret the Root DSE's attributes, e.g. as saved in a snapshot.
+    pub fn from_attrs(attrs: BTreeMap<String, Vec<String>>) -> Self {
+        let naming_contexts = get_values(&attrs, "namingcontexts");
+        let vendor_name = get_first(&attrs, "vendorname");
+        let supported_controls = get_values(&attrs, "supportedcontrol");
+        let server_type = detect_server_type(&attrs, vendor_name.as_deref(), &supported_controls);
+        RootDse {
+            naming_contexts,
+            subschema_subentry: get_first(&attrs, "subschemasubentry"),
+            vendor_name,
+            vendor_version: get_first(&attrs, "vendorversion"),
+            supported_controls,
+            supported_extensions: get_values(&attrs, "supportedextension"),
+            supported_features: get_values(&attrs, "supportedfeatures")
+                .into_iter()
+                .chain(get_values(&attrs, "supportedcapabilities"))
+                .collect(),
+            supported_ldap_versions: get_values(&attrs, "supportedldapversion"),
+            supported_sasl_mechanisms: get_values(&attrs, "supportedsaslmechanisms"),
+            server_type,
+            raw: attrs,
+        }
+    }
+
+    /// The server advertises transactions (RFC 5805).
+    pub fn supports_transactions(&self) -> bool {
+        self.supports_extension(crate::transaction::TXN_START_OID)
+    }
+
+    pub fn supports_control(&self, oid: &str) -> bool {
+        self.supported_controls.iter().any(|c| c.trim() == oid)
+    }
+
+    pub fn supports_extension(&self, oid: &str) -> bool {
+        self.supported_extensions.iter().any(|e| e.trim() == oid)
+    }
+
+    /// The server advertises the paged results control (RFC 2696).
+    pub fn supports_paged_results(&self) -> bool {
+        self.supports_control(oids::PAGED_RESULTS)
+    }
+
+    /// The server advertises the server-side sort control (RFC 2891).
+    pub fn supports_server_side_sort(&self) -> bool {
+        self.supports_control(oids::SERVER_SIDE_SORT)
+    }
+
+    /// The server advertises the virtual list view control and the
+    /// server-side sort control it depends on.
+    pub fn supports_vlv(&self) -> bool {
+        self.supports_control(oids::VIRTUAL_LIST_VIEW) && self.supports_server_side_sort()
+    }
+}
+
 impl LdapConnection {
     /// Read the Root DSE and detect server type.
     pub async fn read_root_dse(&mut self) -> Result<RootDse, CoreError> {
@@ -60,6 +120,9 @@ impl LdapConnection {
                     "supportedControl",
                     "supportedExtension",
                     "supportedLDAPVersion",
+                    "supportedSASLMechanisms",
+                    "supportedFeatures",
+                    "supportedCapabilities",
                     "forestFunctionality",
                     "domainFunctionality",
                     "domainControllerFunctionality",
@@ -85,42 +148,33 @@ impl LdapConnection {
 
         let attrs: BTreeMap<String, Vec<String>> = entry.attrs.into_iter().collect();
 
-        let naming_contexts = get_values(&attrs, "namingcontexts");
-        let subschema_subentry = get_first(&attrs, "subschemasubentry");
-        let vendor_name = get_first(&attrs, "vendorname");
-        let vendor_version = get_first(&attrs, "vendorversion");
-        let supported_controls = get_values(&attrs, "supportedcontrol");
-        let supported_extensions = get_values(&attrs, "supportedextension");
-
         // Log all RootDSE attribute keys for troubleshooting non-standard servers
         let all_keys: Vec<&String> = attrs.keys().collect();
         debug!("RootDSE: all attribute keys returned: {:?}", all_keys);
+
+        let root_dse = RootDse::from_attrs(attrs);
         info!(
             "RootDSE: namingContexts={:?}, vendorName={:?}, vendorVersion={:?}",
-            naming_contexts, vendor_name, vendor_version
+            root_dse.naming_contexts, root_dse.vendor_name, root_dse.vendor_version
         );
+        info!("Detected server type: {}", root_dse.server_type);
 
-        let server_type = detect_server_type(&attrs, vendor_name.as_deref(), &supported_controls);
-        info!("Detected server type: {}", server_type);
+        // Without the control, a search returns everything at once
+        self.paged_results = root_dse.supports_paged_results();
+        if !self.paged_results {
+            info!("RootDSE: paged results control not advertised; searches are not paged");
+        }
+        self.vlv = root_dse.supports_vlv();
 
         // Auto-discover base DN if not set
         if self.base_dn.is_empty() {
-            if let Some(first_nc) = naming_contexts.first() {
+            if let Some(first_nc) = root_dse.naming_contexts.first() {
                 info!("Auto-discovered base DN: {}", first_nc);
                 self.base_dn = first_nc.clone();
             }
         }
 
-        Ok(RootDse {
-            naming_contexts,
-            subschema_subentry,
-            vendor_name,
-            vendor_version,
-            supported_controls,
-            supported_extensions,
-            server_type,
-            raw: attrs,
-        })
+        Ok(root_dse)
     }
 }
 
@@ -223,6 +277,32 @@ mod tests {
         assert_eq!(server, ServerType::OpenLdap);
     }
 
+    #[test]
+    fn test_root_dse_capabilities() {
+        let values = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let attrs = BTreeMap::from([
+            (
+                "supportedControl".to_string(),
+                values(&[oids::PAGED_RESULTS, oids::VIRTUAL_LIST_VIEW]),
+            ),
+            ("supportedLDAPVersion".to_string(), values(&["3"])),
+            (
+                "supportedSASLMechanisms".to_string(),
+                values(&["EXTERNAL", "GSSAPI"]),
+            ),
+            ("supportedFeatures".to_string(), values(&["1.3.6.1.1.14"])),
+        ]);
+        let dse = RootDse::from_attrs(attrs);
+        assert!(dse.supports_paged_results());
+        assert!(!dse.supports_server_side_sort());
+        // Virtual list view is no use without server-side sort
+        assert!(!dse.supports_vlv());
+        assert!(!dse.supports_transactions());
+        assert_eq!(dse.supported_ldap_versions, vec!["3"]);
+        assert_eq!(dse.supported_sasl_mechanisms, vec!["EXTERNAL", "GSSAPI"]);
+        assert_eq!(dse.supported_features, vec!["1.3.6.1.1.14"]);
+    }
+
     #[test]
     fn test_detect_unknown() {
         let attrs = BTreeMap::new();
@@ -0,0 +1,500 @@
+//! Read-only snapshots of a directory for offline handoff: every entry
+//! under a base DN, the schema and the Root DSE, packed with a manifest
+//! into one zip archive (`.loomz`) that offline mode opens later.
+
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use ldap3::Scope;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::connection::LdapConnection;
+use crate::error::CoreError;
+use crate::export::ldif;
+use crate::import::ldif::parse_ldif;
+use crate::offline::OfflineDirectory;
+use crate::ops::DirectoryOps;
+use crate::schema::SchemaCache;
+use crate::server_detect::RootDse;
+use crate::tls::sha256_fingerprint;
+
+/// Snapshot format written by this version. Archives with a higher
+/// version were made by a newer loom and are refused on open.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// File extension of snapshot archives.
+pub const EXTENSION: &str = "loomz";
+
+const MANIFEST: &str = "manifest.json";
+const ROOT_DSE: &str = "root_dse.json";
+const SCHEMA: &str = "schema.json";
+const ENTRIES: &str = "entries.ldif";
+
+/// What a snapshot holds and where it came from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    pub format_version: u32,
+    pub created: DateTime<Utc>,
+    /// Profile the snapshot was taken through.
+    pub profile: String,
+    pub host: String,
+    pub port: u16,
+    pub base_dn: String,
+    pub server_type: String,
+    /// Identifies the server; see [`server_fingerprint`].
+    pub server_fingerprint: String,
+    pub entries: usize,
+}
+
+/// Stage of a running [`create`], reported through its `progress` callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnapshotProgress {
+    RootDse,
+    Schema,
+    /// Entries written so far.
+    Entries(usize),
+    Finishing,
+}
+
+impl SnapshotProgress {
+    pub fn label(&self) -> String {
+        match self {
+            SnapshotProgress::RootDse => "Reading Root DSE".to_string(),
+            SnapshotProgress::Schema => "Reading schema".to_string(),
+            SnapshotProgress::Entries(n) => format!("{} entries written", n),
+            SnapshotProgress::Finishing => "Writing manifest".to_string(),
+        }
+    }
+}
+
+/// An opened snapshot, ready to browse through offline mode.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    pub manifest: Manifest,
+    pub root_dse: RootDse,
+    pub directory: OfflineDirectory,
+}
+
+/// Capture everything under `base` into a snapshot archive at `path`.
+///
+/// The archive is written next to `path` and moved into place only once
+/// complete. Dropping the returned future, e.g. by aborting its task,
+/// cancels the snapshot and removes the partial file.
+pub async fn create<F>(
+    connection: &mut LdapConnection,
+    profile: &str,
+    base: &str,
+    path: &Path,
+    mut progress: F,
+) -> Result<Manifest, CoreError>
+where
+    F: FnMut(SnapshotProgress) + Send,
+{
+    progress(SnapshotProgress::RootDse);
+    let root_dse = connection.read_root_dse().await?;
+
+    progress(SnapshotProgress::Schema);
+    let schema = match connection
+        .load_schema(root_dse.subschema_subentry.as_deref())
+        .await
+    {
+        Ok(schema) => schema,
+        Err(e) => {
+            warn!("Snapshot taken without schema: {}", e);
+            SchemaCache::new()
+        }
+    };
+
+    let host = connection.settings.host.clone();
+    let port = connection.settings.port;
+    let manifest = Manifest {
+        format_version: FORMAT_VERSION,
+        created: Utc::now(),
+        profile: profile.to_string(),
+        server_fingerprint: server_fingerprint(&host, port, &root_dse),
+        host,
+        port,
+        base_dn: base.to_string(),
+        server_type: root_dse.server_type.to_string(),
+        entries: 0,
+    };
+    capture(connection, manifest, &root_dse, &schema, path, progress).await
+}
+
+/// Write the snapshot archive: Root DSE and schema first, then the entries
+/// page by page, and the manifest with the final count last.
+async fn capture<F>(
+    ops: &mut dyn DirectoryOps,
+    mut manifest: Manifest,
+    root_dse: &RootDse,
+    schema: &SchemaCache,
+    path: &Path,
+    mut progress: F,
+) -> Result<Manifest, CoreError>
+where
+    F: FnMut(SnapshotProgress) + Send,
+{
+    let mut archive = PartialArchive::create(path)?;
+    archive.write_json(ROOT_DSE, &root_dse.raw)?;
+    archive.write_json(SCHEMA, schema)?;
+
+    archive.start_file(ENTRIES)?;
+    let all = ["*".to_string()];
+    let mut count = 0;
+    let mut failed = None;
+    let base = manifest.base_dn.clone();
+    ops.search_pages(
+        &base,
+        Scope::Subtree,
+        "(objectClass=*)",
+        &["*", "+"],
+        &mut |page| {
+            if failed.is_some() || page.is_empty() {
+                return;
+            }
+            let writer = archive.writer();
+            let written = if count > 0 {
+                writeln!(writer).map_err(write_error)
+            } else {
+                Ok(())
+            }
+            .and_then(|()| ldif::write_to(&page, &mut *writer, &all));
+            match written {
+                Ok(n) => {
+                    count += n;
+                    progress(SnapshotProgress::Entries(count));
+                }
+                Err(e) => failed = Some(e),
+            }
+        },
+    )
+    .await?;
+    if let Some(e) = failed {
+        return Err(e);
+    }
+
+    progress(SnapshotProgress::Finishing);
+    manifest.entries = count;
+    archive.write_json(MANIFEST, &manifest)?;
+    archive.finish()?;
+    Ok(manifest)
+}
+
+/// Open a snapshot archive for offline browsing.
+pub fn open(path: &Path) -> Result<Snapshot, CoreError> {
+    let file = File::open(path)
+        .map_err(|e| CoreError::SnapshotError(format!("Cannot open {}: {}", path.display(), e)))?;
+    let mut archive = ZipArchive::new(file).map_err(|e| {
+        CoreError::SnapshotError(format!("{} is not a loom snapshot: {}", path.display(), e))
+    })?;
+
+    // Check the version before anything else, as newer formats may not
+    // read as this one
+    let manifest: serde_json::Value = read_json(&mut archive, MANIFEST)?;
+    let version = manifest
+        .get("format_version")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(0);
+    check_version(version)?;
+    let manifest: Manifest = serde_json::from_value(manifest)
+        .map_err(|e| CoreError::SnapshotError(format!("Invalid {}: {}", MANIFEST, e)))?;
+
+    let root_dse = RootDse::from_attrs(read_json(&mut archive, ROOT_DSE)?);
+    let schema: SchemaCache = read_json(&mut archive, SCHEMA)?;
+    let entries = parse_ldif(&read_text(&mut archive, ENTRIES)?)?;
+    let directory = OfflineDirectory::new(entries, manifest.base_dn.clone(), schema);
+    Ok(Snapshot {
+        manifest,
+        root_dse,
+        directory,
+    })
+}
+
+fn check_version(version: u64) -> Result<(), CoreError> {
+    if version == 0 {
+        return Err(CoreError::SnapshotError(format!(
+            "{} has no format version",
+            MANIFEST
+        )));
+    }
+    if version > FORMAT_VERSION as u64 {
+        return Err(CoreError::SnapshotError(format!(
+            "This snapshot was made by a newer version of loom (format {}, this version reads up to {}). Update loom to open it.",
+            version, FORMAT_VERSION
+        )));
+    }
+    Ok(())
+}
+
+/// SHA-256 over the server's address and the Root DSE values that stay
+/// put across restarts, so two snapshots of one server can be matched.
+pub fn server_fingerprint(host: &str, port: u16, root_dse: &RootDse) -> String {
+    let mut identity = format!("{}:{}\n", host.to_lowercase(), port);
+    for key in [
+        "vendorname",
+        "vendorversion",
+        "namingcontexts",
+        "dsservicename",
+    ] {
+        let values = root_dse
+            .raw
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(key))
+            .map(|(_, values)| values.join("\n"))
+            .unwrap_or_default();
+        identity.push_str(&format!("{}={}\n", key, values));
+    }
+    sha256_fingerprint(identity.as_bytes())
+}
+
+fn write_error(e: impl std::fmt::Display) -> CoreError {
+    CoreError::SnapshotError(format!("Write failed: {}", e))
+}
+
+fn read_text(archive: &mut ZipArchive<File>, name: &str) -> Result<String, CoreError> {
+    let mut file = archive
+        .by_name(name)
+        .map_err(|e| CoreError::SnapshotError(format!("Missing {}: {}", name, e)))?;
+    let mut text = String::new();
+    file.read_to_string(&mut text)
+        .map_err(|e| CoreError::SnapshotError(format!("Cannot read {}: {}", name, e)))?;
+    Ok(text)
+}
+
+fn read_json<T: DeserializeOwned>(
+    archive: &mut ZipArchive<File>,
+    name: &str,
+) -> Result<T, CoreError> {
+    serde_json::from_str(&read_text(archive, name)?)
+        .map_err(|e| CoreError::SnapshotError(format!("Invalid {}: {}", name, e)))
+}
+
+/// An archive being written beside its final path. Removed when dropped
+/// before [`PartialArchive::finish`], so a cancelled or failed snapshot
+/// leaves nothing behind.
+struct PartialArchive {
+    zip: Option<ZipWriter<File>>,
+    partial: PathBuf,
+    path: PathBuf,
+}
+
+impl PartialArchive {
+    fn create(path: &Path) -> Result<Self, CoreError> {
+        let mut partial = path.as_os_str().to_owned();
+        partial.push(".partial");
+        let partial = PathBuf::from(partial);
+        let file = File::create(&partial).map_err(|e| {
+            CoreError::SnapshotError(format!("Cannot create {}: {}", partial.display(), e))
+        })?;
+        Ok(Self {
+            zip: Some(ZipWriter::new(file)),
+            partial,
+            path: path.to_path_buf(),
+        })
+    }
+
+    fn writer(&mut self) -> &mut ZipWriter<File> {
+        self.zip.as_mut().expect("archive is open until finished")
+    }
+
+    fn start_file(&mut self, name: &str) -> Result<(), CoreError> {
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(true);
+        self.writer().start_file(name, options).map_err(write_error)
+    }
+
+    fn write_json<T: Serialize + ?Sized>(
+        &mut self,
+        name: &str,
+        value: &T,
+    ) -> Result<(), CoreError> {
+        self.start_file(name)?;
+        serde_json::to_writer_pretty(self.writer(), value).map_err(write_error)
+    }
+
+    fn finish(mut self) -> Result<(), CoreError> {
+        if let Some(zip) = self.zip.take() {
+            zip.finish().map_err(write_error)?;
+        }
+        std::fs::rename(&self.partial, &self.path).map_err(|e| {
+            let _ = std::fs::remove_file(&self.partial);
+            CoreError::SnapshotError(format!("Cannot write {}: {}", self.path.display(), e))
+        })
+    }
+}
+
+impl Drop for PartialArchive {
+    fn drop(&mut self) {
+        if self.zip.take().is_some() {
+            let _ = std::fs::remove_file(&self.partial);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::testing::fixtures::{domain, person};
+    use crate::testing::MockDirectory;
+
+    fn root_dse() -> RootDse {
+        let mut attrs = BTreeMap::new();
+        attrs.insert("vendorName".to_string(), vec!["OpenLDAP".to_string()]);
+        attrs.insert(
+            "namingContexts".to_string(),
+            vec!["dc=example,dc=com".to_string()],
+        );
+        RootDse::from_attrs(attrs)
+    }
+
+    fn manifest() -> Manifest {
+        Manifest {
+            format_version: FORMAT_VERSION,
+            created: Utc::now(),
+            profile: "prod".to_string(),
+            host: "ldap.example.com".to_string(),
+            port: 636,
+            base_dn: "dc=example,dc=com".to_string(),
+            server_type: "OpenLDAP".to_string(),
+            server_fingerprint: server_fingerprint("ldap.example.com", 636, &root_dse()),
+            entries: 0,
+        }
+    }
+
+    fn directory() -> MockDirectory {
+        MockDirectory::with_entries([
+            domain("dc=example,dc=com"),
+            person("dc=example,dc=com", "Alice", "Smith"),
+            person("dc=example,dc=com", "Bob", "Jones").with_binary_attributes(BTreeMap::from([(
+                "jpegPhoto".to_string(),
+                vec![vec![0xff, 0xd8, 0xff]],
+            )])),
+        ])
+        .with_page_size(2)
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("prod.loomz");
+        let mut stages = Vec::new();
+        let written = capture(
+            &mut directory(),
+            manifest(),
+            &root_dse(),
+            &crate::offline::OfflineDirectory::load_embedded()
+                .schema()
+                .clone(),
+            &path,
+            |p| stages.push(p),
+        )
+        .await
+        .unwrap();
+        assert_eq!(written.entries, 3);
+        assert_eq!(
+            stages,
+            [
+                SnapshotProgress::Entries(2),
+                SnapshotProgress::Entries(3),
+                SnapshotProgress::Finishing,
+            ]
+        );
+        assert!(!dir.path().join("prod.loomz.partial").exists());
+
+        let snapshot = open(&path).unwrap();
+        assert_eq!(snapshot.manifest, written);
+        assert_eq!(snapshot.root_dse.vendor_name.as_deref(), Some("OpenLDAP"));
+        assert!(snapshot
+            .directory
+            .schema()
+            .get_attribute_type("cn")
+            .is_some());
+        assert_eq!(snapshot.directory.base_dn(), "dc=example,dc=com");
+        let bob = snapshot
+            .directory
+            .entry("uid=bob.jones,dc=example,dc=com")
+            .unwrap();
+        assert_eq!(
+            bob.binary_attributes["jpegPhoto"],
+            vec![vec![0xff, 0xd8, 0xff]]
+        );
+        assert_eq!(snapshot.directory.children("dc=example,dc=com").len(), 2);
+    }
+
+    #[test]
+    fn test_dropped_archive_is_removed() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("cancelled.loomz");
+        let mut archive = PartialArchive::create(&path).unwrap();
+        archive.write_json(ROOT_DSE, &root_dse().raw).unwrap();
+        assert!(dir.path().join("cancelled.loomz.partial").exists());
+        drop(archive);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_failed_search_leaves_nothing_behind() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("failed.loomz");
+        let mut directory = directory();
+        directory.fail_next(crate::testing::Operation::Search, 51, "busy");
+        let result = capture(
+            &mut directory,
+            manifest(),
+            &root_dse(),
+            &SchemaCache::new(),
+            &path,
+            |_| {},
+        )
+        .await;
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_newer_format_is_refused() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("future.loomz");
+        let mut archive = PartialArchive::create(&path).unwrap();
+        let mut future = serde_json::to_value(manifest()).unwrap();
+        future["format_version"] = (FORMAT_VERSION + 1).into();
+        future["shards"] = 4.into();
+        archive.write_json(MANIFEST, &future).unwrap();
+        archive.finish().unwrap();
+
+        let err = open(&path).unwrap_err().to_string();
+        assert!(err.contains("newer version of loom"), "{}", err);
+        assert!(err.contains(&format!("format {}", FORMAT_VERSION + 1)));
+    }
+
+    #[test]
+    fn test_not_a_snapshot() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.loomz");
+        std::fs::write(&path, "not a zip").unwrap();
+        let err = open(&path).unwrap_err().to_string();
+        assert!(err.contains("is not a loom snapshot"), "{}", err);
+    }
+
+    #[test]
+    fn test_server_fingerprint_ignores_host_case() {
+        let dse = root_dse();
+        assert_eq!(
+            server_fingerprint("LDAP.example.com", 636, &dse),
+            server_fingerprint("ldap.example.com", 636, &dse)
+        );
+        assert_ne!(
+            server_fingerprint("ldap.example.com", 389, &dse),
+            server_fingerprint("ldap.example.com", 636, &dse)
+        );
+    }
+}
@@ -0,0 +1,381 @@
+//! Comparing a subtree as two directories hold it, e.g. two replicas of
+//! one suffix: which entries only one side has, and how the entries both
+//! have differ.
+
+use std::collections::{BTreeMap, HashMap};
+use std::path::Path;
+
+use ldap3::Scope;
+
+use crate::diff::{diff_entries, EntryDiff};
+use crate::dn::normalize;
+use crate::duplicate::is_server_maintained;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::export::{export_entries_as, CsvOptions, ExportFormat};
+use crate::ops::DirectoryOps;
+use crate::schema::SchemaCache;
+
+/// Columns of an exported report, after the DN.
+const REPORT_COLUMNS: [&str; 4] = ["status", "attribute", "left", "right"];
+
+/// What to leave out of a subtree comparison.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubtreeCompareOptions {
+    /// Skip attributes the servers maintain, such as `entryCSN`, which
+    /// differ between replicas even when the data agrees.
+    pub skip_operational: bool,
+    /// Further attributes to skip, matched case-insensitively.
+    pub ignore: Vec<String>,
+}
+
+impl SubtreeCompareOptions {
+    /// Whether `attr` is left out of the comparison.
+    pub fn skips(&self, attr: &str, schema: Option<&SchemaCache>) -> bool {
+        self.ignore.iter().any(|a| a.eq_ignore_ascii_case(attr))
+            || (self.skip_operational && is_server_maintained(attr, schema))
+    }
+}
+
+/// Split an ignore list typed as `modifyTimestamp, entryCSN` or with
+/// spaces between the names.
+pub fn parse_ignore_list(text: &str) -> Vec<String> {
+    text.split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|a| !a.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Stage of a running [`compare_subtrees`], reported through its
+/// `progress` callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubtreeCompareProgress {
+    /// Entries read from the left directory so far.
+    Left(usize),
+    /// Entries read from the right directory so far, compared as they come.
+    Right(usize),
+}
+
+/// The outcome of [`compare_subtrees`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubtreeDiff {
+    pub base_dn: String,
+    /// DNs of entries only the left directory holds, sorted.
+    pub only_left: Vec<String>,
+    /// DNs of entries only the right directory holds, sorted.
+    pub only_right: Vec<String>,
+    /// Entries both hold whose attributes differ, sorted by DN.
+    pub differing: Vec<EntryDiff>,
+    /// Entries both directories hold, whether they differ or not.
+    pub matched: usize,
+}
+
+impl SubtreeDiff {
+    /// Whether both directories hold the same entries with the same values.
+    pub fn is_empty(&self) -> bool {
+        self.only_left.is_empty() && self.only_right.is_empty() && self.differing.is_empty()
+    }
+
+    /// E.g. `1200 matched, 3 differ, 2 only left, 0 only right`.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} matched, {} differ, {} only left, {} only right",
+            self.matched,
+            self.differing.len(),
+            self.only_left.len(),
+            self.only_right.len()
+        )
+    }
+
+    /// The report as rows an exporter can write: one per missing entry
+    /// and one per differing attribute, each with the entry's DN and the
+    /// [`REPORT_COLUMNS`].
+    pub fn rows(&self) -> Vec<LdapEntry> {
+        let row = |dn: &str, status: &str, attr: &str, left: &[String], right: &[String]| {
+            let values = [vec![status.to_string()], vec![attr.to_string()]];
+            let attributes: BTreeMap<String, Vec<String>> = REPORT_COLUMNS
+                .iter()
+                .map(|c| c.to_string())
+                .zip(values.into_iter().chain([left.to_vec(), right.to_vec()]))
+                .filter(|(_, v)| v.iter().any(|s| !s.is_empty()))
+                .collect();
+            LdapEntry::new(dn.to_string(), attributes)
+        };
+
+        let mut rows = Vec::new();
+        for dn in &self.only_left {
+            rows.push(row(dn, "only-left", "", &[], &[]));
+        }
+        for dn in &self.only_right {
+            rows.push(row(dn, "only-right", "", &[], &[]));
+        }
+        for diff in &self.differing {
+            for attr in diff.attributes.iter().filter(|a| a.change().is_some()) {
+                rows.push(row(
+                    &diff.left_dn,
+                    "differs",
+                    &attr.attr,
+                    &attr.left,
+                    &attr.right,
+                ));
+            }
+        }
+        rows.sort_by_key(|r| normalize(&r.dn));
+        rows
+    }
+
+    /// Write the report to `path` as CSV, JSON or JSON Lines, by its
+    /// extension. Returns the number of rows written.
+    pub fn export(&self, path: &Path) -> Result<usize, CoreError> {
+        let format = match ExportFormat::from_path(path) {
+            Some(f @ (ExportFormat::Csv | ExportFormat::Json | ExportFormat::JsonLines)) => f,
+            _ => {
+                return Err(CoreError::ExportError(
+                    "Save the comparison as .csv, .json or .jsonl".to_string(),
+                ))
+            }
+        };
+        let columns: Vec<String> = REPORT_COLUMNS.iter().map(|c| c.to_string()).collect();
+        export_entries_as(self.rows(), path, format, &columns, &CsvOptions::default())
+    }
+}
+
+/// Compare the subtree under `base_dn` as `left` and `right` hold it.
+///
+/// Both sides are read with paged searches, the left one first; the
+/// right one is compared page by page as it arrives, so only the left
+/// subtree is held in memory. Entries are matched by DN, ignoring case
+/// and spacing. Dropping the returned future, e.g. by aborting its task,
+/// stops the comparison.
+pub async fn compare_subtrees<F>(
+    left: &mut dyn DirectoryOps,
+    right: &mut dyn DirectoryOps,
+    base_dn: &str,
+    options: &SubtreeCompareOptions,
+    schema: Option<&SchemaCache>,
+    mut progress: F,
+) -> Result<SubtreeDiff, CoreError>
+where
+    F: FnMut(SubtreeCompareProgress) + Send,
+{
+    let attrs: &[&str] = if options.skip_operational {
+        &["*"]
+    } else {
+        &["*", "+"]
+    };
+
+    let mut pending: HashMap<String, LdapEntry> = HashMap::new();
+    left.search_pages(
+        base_dn,
+        Scope::Subtree,
+        "(objectClass=*)",
+        attrs,
+        &mut |page| {
+            for entry in page {
+                pending.insert(normalize(&entry.dn), entry);
+            }
+            progress(SubtreeCompareProgress::Left(pending.len()));
+        },
+    )
+    .await?;
+
+    let mut report = SubtreeDiff {
+        base_dn: base_dn.to_string(),
+        ..SubtreeDiff::default()
+    };
+    let mut read = 0;
+    right
+        .search_pages(
+            base_dn,
+            Scope::Subtree,
+            "(objectClass=*)",
+            attrs,
+            &mut |page| {
+                read += page.len();
+                for entry in page {
+                    let Some(theirs) = pending.remove(&normalize(&entry.dn)) else {
+                        report.only_right.push(entry.dn);
+                        continue;
+                    };
+                    report.matched += 1;
+                    let diff = diff_entries(&theirs, &entry).without(|a| options.skips(a, schema));
+                    if !diff.is_empty() {
+                        report.differing.push(diff);
+                    }
+                }
+                progress(SubtreeCompareProgress::Right(read));
+            },
+        )
+        .await?;
+
+    report.only_left = pending.into_values().map(|e| e.dn).collect();
+    report.only_left.sort_by_key(|dn| normalize(dn));
+    report.only_right.sort_by_key(|dn| normalize(dn));
+    report.differing.sort_by_key(|d| normalize(&d.left_dn));
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures::{domain, person, EntryBuilder};
+    use crate::testing::{MockDirectory, Operation};
+
+    const BASE: &str = "dc=example,dc=com";
+
+    fn replicas() -> (MockDirectory, MockDirectory) {
+        let stamped = |entry: LdapEntry, csn: &str| {
+            let mut entry = entry;
+            entry
+                .attributes
+                .insert("entryCSN".to_string(), vec![csn.to_string()]);
+            entry
+        };
+        let left = MockDirectory::with_entries([
+            domain(BASE),
+            stamped(person(BASE, "Alice", "Smith"), "1"),
+            person(BASE, "Bob", "Jones"),
+            person(BASE, "Carol", "White"),
+        ])
+        .with_page_size(2);
+        let mut alice = person(BASE, "Alice", "Smith");
+        alice
+            .attributes
+            .insert("mail".to_string(), vec!["alice@example.org".to_string()]);
+        let right = MockDirectory::with_entries([
+            domain(BASE),
+            stamped(alice, "2"),
+            // Same entry, DN spelled differently
+            EntryBuilder::new("UID=bob.jones, DC=example,DC=com")
+                .object_classes(&["top", "person", "organizationalPerson", "inetOrgPerson"])
+                .attr("uid", "bob.jones")
+                .attr("cn", "Bob Jones")
+                .attr("givenName", "Bob")
+                .attr("sn", "Jones")
+                .attr("mail", "bob.jones@example.com")
+                .build(),
+            person(BASE, "Dave", "Black"),
+        ])
+        .with_page_size(2);
+        (left, right)
+    }
+
+    #[tokio::test]
+    async fn test_compare_subtrees() {
+        let (mut left, mut right) = replicas();
+        let options = SubtreeCompareOptions {
+            skip_operational: true,
+            ignore: Vec::new(),
+        };
+        let mut stages = Vec::new();
+        let report = compare_subtrees(&mut left, &mut right, BASE, &options, None, |p| {
+            stages.push(p)
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(report.matched, 3);
+        assert_eq!(report.only_left, vec!["uid=carol.white,dc=example,dc=com"]);
+        assert_eq!(report.only_right, vec!["uid=dave.black,dc=example,dc=com"]);
+        // entryCSN differs too, but is server-maintained
+        assert_eq!(report.differing.len(), 1);
+        let changes = report.differing[0].changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].attr, "mail");
+        assert_eq!(
+            report.summary(),
+            "3 matched, 1 differ, 1 only left, 1 only right"
+        );
+        assert_eq!(stages.first(), Some(&SubtreeCompareProgress::Left(2)));
+        assert_eq!(stages.last(), Some(&SubtreeCompareProgress::Right(4)));
+    }
+
+    #[tokio::test]
+    async fn test_binary_values_are_compared() {
+        let with_photo = |bytes: &[u8]| {
+            MockDirectory::with_entries([
+                domain(BASE),
+                person(BASE, "Alice", "Smith").with_binary_attributes(
+                    [("jpegPhoto".to_string(), vec![bytes.to_vec()])].into(),
+                ),
+            ])
+        };
+        let options = SubtreeCompareOptions::default();
+        let report = compare_subtrees(
+            &mut with_photo(&[0xff, 0xd8]),
+            &mut with_photo(&[0x89, 0x50]),
+            BASE,
+            &options,
+            None,
+            |_| {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(report.differing.len(), 1);
+        assert_eq!(report.differing[0].changes()[0].attr, "jpegPhoto");
+    }
+
+    #[tokio::test]
+    async fn test_ignore_list_and_operational_switch() {
+        let (mut left, mut right) = replicas();
+        let options = SubtreeCompareOptions {
+            skip_operational: false,
+            ignore: parse_ignore_list("MAIL"),
+        };
+        let report = compare_subtrees(&mut left, &mut right, BASE, &options, None, |_| {})
+            .await
+            .unwrap();
+        let changes = report.differing[0].changes();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].attr, "entryCSN");
+
+        assert_eq!(
+            parse_ignore_list("modifyTimestamp, entryCSN  mail"),
+            vec!["modifyTimestamp", "entryCSN", "mail"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_search_failure_is_returned() {
+        let (mut left, mut right) = replicas();
+        right.fail_next(Operation::Search, 51, "busy");
+        let result = compare_subtrees(
+            &mut left,
+            &mut right,
+            BASE,
+            &SubtreeCompareOptions::default(),
+            None,
+            |_| {},
+        )
+        .await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_report_as_csv_and_json() {
+        let (mut left, mut right) = replicas();
+        let options = SubtreeCompareOptions {
+            skip_operational: true,
+            ignore: Vec::new(),
+        };
+        let report = compare_subtrees(&mut left, &mut right, BASE, &options, None, |_| {})
+            .await
+            .unwrap();
+        let dir = tempfile::tempdir().unwrap();
+
+        let csv = dir.path().join("replicas.csv");
+        assert_eq!(report.export(&csv).unwrap(), 3);
+        let text = std::fs::read_to_string(&csv).unwrap();
+        assert!(text.contains("only-left"));
+        assert!(text.contains("alice.smith@example.com"));
+        assert!(text.contains("alice@example.org"));
+
+        let json = dir.path().join("replicas.json");
+        assert_eq!(report.export(&json).unwrap(), 3);
+        let rows: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&json).unwrap()).unwrap();
+        assert_eq!(rows.as_array().unwrap().len(), 3);
+
+        assert!(report.export(&dir.path().join("replicas.ldif")).is_err());
+    }
+}
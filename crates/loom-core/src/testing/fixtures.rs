@@ -0,0 +1,152 @@
+//! Builders for the entry shapes tests need most: domains, OUs, people,
+//! groups, and trees of them in any size.
+
+use std::collections::BTreeMap;
+
+use crate::dn::rdn_display_name;
+use crate::entry::LdapEntry;
+
+/// Builds an entry one attribute value at a time.
+#[derive(Debug, Clone)]
+pub struct EntryBuilder {
+    dn: String,
+    attributes: BTreeMap<String, Vec<String>>,
+    binary_attributes: BTreeMap<String, Vec<Vec<u8>>>,
+}
+
+impl EntryBuilder {
+    pub fn new(dn: impl Into<String>) -> Self {
+        Self {
+            dn: dn.into(),
+            attributes: BTreeMap::new(),
+            binary_attributes: BTreeMap::new(),
+        }
+    }
+
+    /// Add a value; repeat the call for more values of the same attribute.
+    pub fn attr(mut self, attr: &str, value: impl Into<String>) -> Self {
+        self.attributes
+            .entry(attr.to_string())
+            .or_default()
+            .push(value.into());
+        self
+    }
+
+    pub fn attrs(self, attr: &str, values: &[&str]) -> Self {
+        values.iter().fold(self, |b, v| b.attr(attr, *v))
+    }
+
+    /// Add a binary value, kept apart from the text attributes.
+    pub fn binary(mut self, attr: &str, value: &[u8]) -> Self {
+        self.binary_attributes
+            .entry(attr.to_string())
+            .or_default()
+            .push(value.to_vec());
+        self
+    }
+
+    pub fn object_classes(self, classes: &[&str]) -> Self {
+        self.attrs("objectClass", classes)
+    }
+
+    pub fn build(self) -> LdapEntry {
+        LdapEntry::new(self.dn, self.attributes).with_binary_attributes(self.binary_attributes)
+    }
+}
+
+/// The domain entry at the top of a tree, e.g. `dc=example,dc=com`.
+pub fn domain(base_dn: &str) -> LdapEntry {
+    EntryBuilder::new(base_dn)
+        .object_classes(&["top", "domain"])
+        .attr("dc", rdn_display_name(base_dn))
+        .build()
+}
+
+pub fn org_unit(parent_dn: &str, ou: &str) -> LdapEntry {
+    EntryBuilder::new(format!("ou={},{}", ou, parent_dn))
+        .object_classes(&["top", "organizationalUnit"])
+        .attr("ou", ou)
+        .build()
+}
+
+/// An `inetOrgPerson` with uid `given.sn`, lowercased.
+pub fn person(parent_dn: &str, given: &str, sn: &str) -> LdapEntry {
+    let uid = format!("{}.{}", given, sn).to_lowercase();
+    EntryBuilder::new(format!("uid={},{}", uid, parent_dn))
+        .object_classes(&["top", "person", "organizationalPerson", "inetOrgPerson"])
+        .attr("uid", uid.as_str())
+        .attr("cn", format!("{} {}", given, sn))
+        .attr("givenName", given)
+        .attr("sn", sn)
+        .attr("mail", format!("{}@example.com", uid))
+        .build()
+}
+
+/// A `groupOfNames` with the given member DNs.
+pub fn group(parent_dn: &str, cn: &str, members: &[&str]) -> LdapEntry {
+    EntryBuilder::new(format!("cn={},{}", cn, parent_dn))
+        .object_classes(&["top", "groupOfNames"])
+        .attr("cn", cn)
+        .attrs("member", members)
+        .build()
+}
+
+/// A domain with organizational units of people and groups:
+/// `ou=Unit1` .. `ou=UnitN`, each with people `user1.unitN` .. and groups
+/// `cn=unitN-group1` .. that hold all of that unit's people.
+#[derive(Debug, Clone)]
+pub struct OuTree {
+    base_dn: String,
+    ous: usize,
+    people_per_ou: usize,
+    groups_per_ou: usize,
+}
+
+impl OuTree {
+    /// Two units of three people and one group each.
+    pub fn new(base_dn: &str) -> Self {
+        Self {
+            base_dn: base_dn.to_string(),
+            ous: 2,
+            people_per_ou: 3,
+            groups_per_ou: 1,
+        }
+    }
+
+    pub fn ous(mut self, count: usize) -> Self {
+        self.ous = count;
+        self
+    }
+
+    pub fn people_per_ou(mut self, count: usize) -> Self {
+        self.people_per_ou = count;
+        self
+    }
+
+    pub fn groups_per_ou(mut self, count: usize) -> Self {
+        self.groups_per_ou = count;
+        self
+    }
+
+    /// The entries, parents before their children.
+    pub fn build(&self) -> Vec<LdapEntry> {
+        let mut entries = vec![domain(&self.base_dn)];
+        for u in 1..=self.ous {
+            let unit = format!("Unit{}", u);
+            let ou = org_unit(&self.base_dn, &unit);
+            let ou_dn = ou.dn.clone();
+            entries.push(ou);
+
+            let people: Vec<LdapEntry> = (1..=self.people_per_ou)
+                .map(|p| person(&ou_dn, &format!("User{}", p), &unit))
+                .collect();
+            let members: Vec<&str> = people.iter().map(|p| p.dn.as_str()).collect();
+            let groups: Vec<LdapEntry> = (1..=self.groups_per_ou)
+                .map(|g| group(&ou_dn, &format!("unit{}-group{}", u, g), &members))
+                .collect();
+            entries.extend(people);
+            entries.extend(groups);
+        }
+        entries
+    }
+}
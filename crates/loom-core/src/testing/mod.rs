@@ -0,0 +1,957 @@
+//! Test support: an in-memory directory behind [`DirectoryOps`] and
+//! builders for the usual entry shapes.
+//!
+//! Available to loom-core's own tests, and to other crates' tests through
+//! the `testing` feature.
+
+pub mod fixtures;
+
+use std::collections::{BTreeMap, HashSet};
+use std::time::Duration;
+
+use ldap3::{Mod, Scope};
+
+use crate::audit::{AuditRecord, AuditSink};
+use crate::dn::{components, depth, normalize, parent_dn, rdn};
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::errors::result_code_name;
+use crate::filter::Filter;
+use crate::ops::{DirectoryOps, OpFuture, PageSink};
+
+const PROTOCOL_ERROR: u32 = 2;
+const ATTRIBUTE_OR_VALUE_EXISTS: u32 = 20;
+const INVALID_ATTRIBUTE_SYNTAX: u32 = 21;
+const NO_SUCH_ATTRIBUTE: u32 = 16;
+const NO_SUCH_OBJECT: u32 = 32;
+const INVALID_DN_SYNTAX: u32 = 34;
+const UNWILLING_TO_PERFORM: u32 = 53;
+const OBJECT_CLASS_VIOLATION: u32 = 65;
+const NOT_ALLOWED_ON_NON_LEAF: u32 = 66;
+const NOT_ALLOWED_ON_RDN: u32 = 67;
+const ENTRY_ALREADY_EXISTS: u32 = 68;
+const FILTER_ERROR: u32 = 87;
+
+/// The kinds of operation, for failure injection and the operation log.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Search,
+    Add,
+    Modify,
+    Delete,
+    Rename,
+    StartTransaction,
+    EndTransaction,
+}
+
+/// A result code and diagnostic the directory answers with.
+#[derive(Debug, Clone)]
+struct Refusal {
+    rc: u32,
+    text: String,
+}
+
+impl Refusal {
+    fn new(rc: u32, text: impl Into<String>) -> Self {
+        Self {
+            rc,
+            text: text.into(),
+        }
+    }
+
+    /// The error a live connection reports for the same result.
+    fn into_error(self, op: Operation, dn: &str) -> CoreError {
+        let (rc, text) = (self.rc, self.text);
+        match op {
+            Operation::Search => CoreError::SearchFailed(format!(
+                "rc={} ({}), dn: \"\", text: \"{}\"",
+                rc,
+                result_code_name(rc),
+                text
+            )),
+            Operation::Add => {
+                CoreError::AddFailed(format!("Add {} failed rc={}: {}", dn, rc, text))
+            }
+            Operation::Modify => {
+                CoreError::ModifyFailed(format!("Modify {} failed rc={}: {}", dn, rc, text))
+            }
+            Operation::Delete => {
+                CoreError::DeleteFailed(format!("Delete {} failed rc={}: {}", dn, rc, text))
+            }
+            Operation::Rename => {
+                CoreError::ModifyFailed(format!("Rename {} failed rc={}: {}", dn, rc, text))
+            }
+            Operation::StartTransaction => CoreError::TransactionFailed(format!(
+                "Start transaction failed rc={}: {}",
+                rc, text
+            )),
+            Operation::EndTransaction => {
+                CoreError::TransactionFailed(format!("End transaction failed rc={}: {}", rc, text))
+            }
+        }
+    }
+}
+
+/// An injected failure.
+#[derive(Debug, Clone)]
+struct Failure {
+    op: Operation,
+    /// Normalized DN the failure is limited to.
+    dn: Option<String>,
+    refusal: Refusal,
+    once: bool,
+}
+
+/// A directory held in memory, answering like a server would: searches
+/// are evaluated with [`Filter`], updates are checked and refused with the
+/// usual result codes, and results come back in pages.
+///
+/// Entries are kept in insertion order, which is the order searches
+/// return them in.
+#[derive(Debug, Clone)]
+pub struct MockDirectory {
+    entries: Vec<LdapEntry>,
+    page_size: usize,
+    latency: Duration,
+    failures: Vec<Failure>,
+    log: Vec<(Operation, String)>,
+    pages_served: usize,
+    transactions: bool,
+    /// Entries as they were when the open transaction started.
+    snapshot: Option<Vec<LdapEntry>>,
+}
+
+impl Default for MockDirectory {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            page_size: 500,
+            latency: Duration::ZERO,
+            failures: Vec::new(),
+            log: Vec::new(),
+            pages_served: 0,
+            transactions: false,
+            snapshot: None,
+        }
+    }
+}
+
+impl MockDirectory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A directory holding `entries`, loaded without any checks.
+    pub fn with_entries(entries: impl IntoIterator<Item = LdapEntry>) -> Self {
+        let mut directory = Self::new();
+        for entry in entries {
+            directory.insert(entry);
+        }
+        directory
+    }
+
+    /// Entries per page of a paged search.
+    pub fn with_page_size(mut self, page_size: usize) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Delay before every operation completes.
+    pub fn with_latency(mut self, latency: Duration) -> Self {
+        self.latency = latency;
+        self
+    }
+
+    /// Support transactions: writes inside one are applied as they come
+    /// and undone when it is aborted, or when committing it fails.
+    pub fn with_transactions(mut self) -> Self {
+        self.transactions = true;
+        self
+    }
+
+    pub fn in_transaction(&self) -> bool {
+        self.snapshot.is_some()
+    }
+
+    /// Store `entry`, replacing one with the same DN, without any checks.
+    pub fn insert(&mut self, entry: LdapEntry) {
+        match self.position(&entry.dn) {
+            Some(i) => self.entries[i] = entry,
+            None => self.entries.push(entry),
+        }
+    }
+
+    pub fn entry(&self, dn: &str) -> Option<&LdapEntry> {
+        self.position(dn).map(|i| &self.entries[i])
+    }
+
+    pub fn entries(&self) -> &[LdapEntry] {
+        &self.entries
+    }
+
+    /// Refuse the next `op` with result code `rc`.
+    pub fn fail_next(&mut self, op: Operation, rc: u32, text: &str) {
+        self.failures.push(Failure {
+            op,
+            dn: None,
+            refusal: Refusal::new(rc, text),
+            once: true,
+        });
+    }
+
+    /// Refuse every `op` on `dn` (the search base, for searches) with
+    /// result code `rc`, until [`clear_failures`](Self::clear_failures).
+    pub fn fail_on(&mut self, op: Operation, dn: &str, rc: u32, text: &str) {
+        self.failures.push(Failure {
+            op,
+            dn: Some(normalize(dn)),
+            refusal: Refusal::new(rc, text),
+            once: false,
+        });
+    }
+
+    pub fn clear_failures(&mut self) {
+        self.failures.clear();
+    }
+
+    /// Every operation requested so far, with its DN or search base.
+    pub fn log(&self) -> &[(Operation, String)] {
+        &self.log
+    }
+
+    /// Pages handed out by paged searches so far.
+    pub fn pages_served(&self) -> usize {
+        self.pages_served
+    }
+
+    fn position(&self, dn: &str) -> Option<usize> {
+        let dn = normalize(dn);
+        self.entries.iter().position(|e| normalize(&e.dn) == dn)
+    }
+
+    fn has_children(&self, dn: &str) -> bool {
+        let dn = normalize(dn);
+        self.entries
+            .iter()
+            .any(|e| parent_dn(&e.dn).is_some_and(|p| normalize(p) == dn))
+    }
+
+    async fn pause(&self) {
+        if !self.latency.is_zero() {
+            tokio::time::sleep(self.latency).await;
+        }
+    }
+
+    /// Log `op`, apply any injected failure, then run it.
+    fn run<T>(
+        &mut self,
+        op: Operation,
+        dn: &str,
+        f: impl FnOnce(&mut Self) -> Result<T, Refusal>,
+    ) -> Result<T, CoreError> {
+        self.log.push((op, dn.to_string()));
+        let target = normalize(dn);
+        let injected = self
+            .failures
+            .iter()
+            .position(|f| f.op == op && f.dn.as_ref().map_or(true, |d| *d == target));
+        let result = match injected {
+            Some(i) if self.failures[i].once => Err(self.failures.remove(i).refusal),
+            Some(i) => Err(self.failures[i].refusal.clone()),
+            None => f(self),
+        };
+        result.map_err(|refusal| refusal.into_error(op, dn))
+    }
+
+    fn start_transaction_now(&mut self) -> Result<(), Refusal> {
+        if !self.transactions {
+            return Err(Refusal::new(
+                PROTOCOL_ERROR,
+                "unsupported extended operation",
+            ));
+        }
+        if self.snapshot.is_some() {
+            return Err(Refusal::new(
+                UNWILLING_TO_PERFORM,
+                "a transaction is already open",
+            ));
+        }
+        self.snapshot = Some(self.entries.clone());
+        Ok(())
+    }
+
+    fn end_transaction_now(&mut self, commit: bool) -> Result<(), Refusal> {
+        let snapshot = self
+            .snapshot
+            .take()
+            .ok_or_else(|| Refusal::new(PROTOCOL_ERROR, "no transaction is open"))?;
+        if !commit {
+            self.entries = snapshot;
+        }
+        Ok(())
+    }
+
+    fn search_now(
+        &self,
+        base_dn: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: &[&str],
+    ) -> Result<Vec<LdapEntry>, Refusal> {
+        let filter =
+            Filter::parse(filter).map_err(|e| Refusal::new(FILTER_ERROR, e.to_string()))?;
+        if self.position(base_dn).is_none() {
+            return Err(Refusal::new(NO_SUCH_OBJECT, "no such object"));
+        }
+        let base = normalize(base_dn);
+        let suffix = format!(",{}", base);
+        let in_scope = |dn: &str| {
+            let dn = normalize(dn);
+            match scope {
+                Scope::Base => dn == base,
+                Scope::OneLevel => parent_dn(&dn) == Some(base.as_str()),
+                Scope::Subtree => dn == base || dn.ends_with(&suffix),
+            }
+        };
+        Ok(self
+            .entries
+            .iter()
+            .filter(|e| in_scope(&e.dn) && filter.matches(e))
+            .map(|e| select_attributes(e, attrs))
+            .collect())
+    }
+
+    fn add_now(
+        &mut self,
+        dn: &str,
+        attrs: Vec<(String, HashSet<String>)>,
+        binary: Vec<(String, HashSet<Vec<u8>>)>,
+    ) -> Result<(), Refusal> {
+        if !rdn(dn).contains('=') {
+            return Err(Refusal::new(INVALID_DN_SYNTAX, "invalid DN"));
+        }
+        if self.position(dn).is_some() {
+            return Err(Refusal::new(ENTRY_ALREADY_EXISTS, "entry already exists"));
+        }
+        // The first entry of an empty directory is its naming context
+        if let Some(parent) = parent_dn(dn) {
+            if !self.entries.is_empty() && self.position(parent).is_none() {
+                return Err(Refusal::new(NO_SUCH_OBJECT, "parent does not exist"));
+            }
+        }
+        let mut attributes = BTreeMap::new();
+        for (attr, values) in attrs {
+            let mut values: Vec<String> = values.into_iter().collect();
+            if values.is_empty() {
+                continue;
+            }
+            values.sort();
+            attributes.insert(attr, values);
+        }
+        let mut binary_attributes = BTreeMap::new();
+        for (attr, values) in binary {
+            let mut values: Vec<Vec<u8>> = values.into_iter().collect();
+            if values.is_empty() {
+                continue;
+            }
+            values.sort();
+            binary_attributes.insert(attr, values);
+        }
+        let entry =
+            LdapEntry::new(dn.to_string(), attributes).with_binary_attributes(binary_attributes);
+        if !has_value(&entry, "objectClass", None) {
+            return Err(Refusal::new(
+                OBJECT_CLASS_VIOLATION,
+                "no objectClass attribute",
+            ));
+        }
+        self.entries.push(entry);
+        Ok(())
+    }
+
+    fn modify_now(&mut self, dn: &str, mods: Vec<Mod<String>>) -> Result<(), Refusal> {
+        let i = self
+            .position(dn)
+            .ok_or_else(|| Refusal::new(NO_SUCH_OBJECT, "no such object"))?;
+        // Modifications apply all together or not at all
+        let mut entry = self.entries[i].clone();
+        for m in mods {
+            apply_mod(&mut entry, m)?;
+        }
+        let (rdn_attr, rdn_value) = split_rdn(rdn(&entry.dn));
+        if !has_value(&entry, rdn_attr, Some(rdn_value)) {
+            return Err(Refusal::new(
+                NOT_ALLOWED_ON_RDN,
+                format!("value of naming attribute '{}' is not present", rdn_attr),
+            ));
+        }
+        self.entries[i] = entry;
+        Ok(())
+    }
+
+    fn delete_now(&mut self, dn: &str) -> Result<(), Refusal> {
+        let i = self
+            .position(dn)
+            .ok_or_else(|| Refusal::new(NO_SUCH_OBJECT, "no such object"))?;
+        if self.has_children(dn) {
+            return Err(Refusal::new(
+                NOT_ALLOWED_ON_NON_LEAF,
+                "subordinate objects must be deleted first",
+            ));
+        }
+        self.entries.remove(i);
+        Ok(())
+    }
+
+    fn rename_now(
+        &mut self,
+        dn: &str,
+        new_rdn: &str,
+        delete_old_rdn: bool,
+        new_superior: Option<&str>,
+    ) -> Result<(), Refusal> {
+        let i = self
+            .position(dn)
+            .ok_or_else(|| Refusal::new(NO_SUCH_OBJECT, "no such object"))?;
+        if !new_rdn.contains('=') || new_rdn.contains(',') {
+            return Err(Refusal::new(INVALID_DN_SYNTAX, "invalid RDN"));
+        }
+        if let Some(superior) = new_superior {
+            if self.position(superior).is_none() {
+                return Err(Refusal::new(NO_SUCH_OBJECT, "new superior does not exist"));
+            }
+            let superior = normalize(superior);
+            let old = normalize(dn);
+            if superior == old || superior.ends_with(&format!(",{}", old)) {
+                return Err(Refusal::new(
+                    UNWILLING_TO_PERFORM,
+                    "cannot move an entry below itself",
+                ));
+            }
+        }
+        let parent = new_superior
+            .map(str::to_string)
+            .or_else(|| parent_dn(&self.entries[i].dn).map(str::to_string));
+        let new_dn = match parent {
+            Some(parent) => format!("{},{}", new_rdn, parent),
+            None => new_rdn.to_string(),
+        };
+        if normalize(&new_dn) != normalize(dn) && self.position(&new_dn).is_some() {
+            return Err(Refusal::new(ENTRY_ALREADY_EXISTS, "entry already exists"));
+        }
+
+        let old_dn = self.entries[i].dn.clone();
+        let entry = &mut self.entries[i];
+        let (old_attr, old_value) = split_rdn(rdn(&old_dn));
+        let (new_attr, new_value) = split_rdn(new_rdn);
+        if !has_value(entry, new_attr, Some(new_value)) {
+            let key = attribute_key(entry, new_attr);
+            entry
+                .attributes
+                .entry(key)
+                .or_default()
+                .push(new_value.to_string());
+        }
+        let same = old_attr.eq_ignore_ascii_case(new_attr)
+            && old_value.to_lowercase() == new_value.to_lowercase();
+        if delete_old_rdn && !same {
+            remove_value(entry, old_attr, old_value);
+        }
+        entry.dn = new_dn.clone();
+
+        // Descendants keep their RDNs below the new DN
+        let old_depth = depth(&old_dn);
+        let old_suffix = format!(",{}", normalize(&old_dn));
+        for entry in &mut self.entries {
+            if normalize(&entry.dn).ends_with(&old_suffix) {
+                let own = components(&entry.dn);
+                let keep = own.len() - old_depth;
+                entry.dn = format!("{},{}", own[..keep].join(","), new_dn);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// The attributes a search asked for; `*` or no list means all of them.
+fn select_attributes(entry: &LdapEntry, attrs: &[&str]) -> LdapEntry {
+    if attrs.is_empty() || attrs.contains(&"*") {
+        return entry.clone();
+    }
+    let wanted = |name: &String| attrs.iter().any(|a| a.eq_ignore_ascii_case(name));
+    let attributes = entry
+        .attributes
+        .iter()
+        .filter(|(name, _)| wanted(name))
+        .map(|(name, values)| (name.clone(), values.clone()))
+        .collect();
+    let binary = entry
+        .binary_attributes
+        .iter()
+        .filter(|(name, _)| wanted(name))
+        .map(|(name, values)| (name.clone(), values.clone()))
+        .collect();
+    LdapEntry::new(entry.dn.clone(), attributes).with_binary_attributes(binary)
+}
+
+fn split_rdn(rdn: &str) -> (&str, &str) {
+    rdn.split_once('=')
+        .map(|(a, v)| (a.trim(), v.trim()))
+        .unwrap_or((rdn, ""))
+}
+
+/// The entry's own spelling of `attr`, or `attr` when it has none.
+fn attribute_key(entry: &LdapEntry, attr: &str) -> String {
+    entry
+        .attributes
+        .keys()
+        .find(|k| k.eq_ignore_ascii_case(attr))
+        .cloned()
+        .unwrap_or_else(|| attr.to_string())
+}
+
+/// Whether the entry has `attr`, or with `value`, that value of it.
+fn has_value(entry: &LdapEntry, attr: &str, value: Option<&str>) -> bool {
+    let key = attribute_key(entry, attr);
+    entry
+        .attributes
+        .get(&key)
+        .is_some_and(|values| match value {
+            Some(value) => values
+                .iter()
+                .any(|v| v.to_lowercase() == value.to_lowercase()),
+            None => !values.is_empty(),
+        })
+}
+
+/// Remove one value; `false` when it was not there.
+fn remove_value(entry: &mut LdapEntry, attr: &str, value: &str) -> bool {
+    let key = attribute_key(entry, attr);
+    let Some(values) = entry.attributes.get_mut(&key) else {
+        return false;
+    };
+    let Some(i) = values
+        .iter()
+        .position(|v| v.to_lowercase() == value.to_lowercase())
+    else {
+        return false;
+    };
+    values.remove(i);
+    if values.is_empty() {
+        entry.attributes.remove(&key);
+    }
+    true
+}
+
+fn apply_mod(entry: &mut LdapEntry, m: Mod<String>) -> Result<(), Refusal> {
+    let sorted = |values: HashSet<String>| {
+        let mut values: Vec<String> = values.into_iter().collect();
+        values.sort();
+        values
+    };
+    match m {
+        Mod::Add(attr, values) => {
+            for value in sorted(values) {
+                if has_value(entry, &attr, Some(&value)) {
+                    return Err(Refusal::new(
+                        ATTRIBUTE_OR_VALUE_EXISTS,
+                        format!("{}: value already exists", attr),
+                    ));
+                }
+                let key = attribute_key(entry, &attr);
+                entry.attributes.entry(key).or_default().push(value);
+            }
+        }
+        Mod::Delete(attr, values) => {
+            if !has_value(entry, &attr, None) {
+                return Err(Refusal::new(
+                    NO_SUCH_ATTRIBUTE,
+                    format!("{}: no such attribute", attr),
+                ));
+            }
+            if values.is_empty() {
+                let key = attribute_key(entry, &attr);
+                entry.attributes.remove(&key);
+            }
+            for value in sorted(values) {
+                if !remove_value(entry, &attr, &value) {
+                    return Err(Refusal::new(
+                        NO_SUCH_ATTRIBUTE,
+                        format!("{}: no such value", attr),
+                    ));
+                }
+            }
+        }
+        Mod::Replace(attr, values) => {
+            let key = attribute_key(entry, &attr);
+            entry.attributes.remove(&key);
+            if !values.is_empty() {
+                entry.attributes.insert(key, sorted(values));
+            }
+        }
+        Mod::Increment(attr, by) => {
+            let invalid = || {
+                Refusal::new(
+                    INVALID_ATTRIBUTE_SYNTAX,
+                    format!("{}: value is not an integer", attr),
+                )
+            };
+            let by: i64 = by.trim().parse().map_err(|_| invalid())?;
+            let key = attribute_key(entry, &attr);
+            let Some(current) = entry.attributes.get_mut(&key) else {
+                return Err(Refusal::new(
+                    NO_SUCH_ATTRIBUTE,
+                    format!("{}: no such attribute", attr),
+                ));
+            };
+            for value in current.iter_mut() {
+                let n: i64 = value.trim().parse().map_err(|_| invalid())?;
+                *value = (n + by).to_string();
+            }
+        }
+    }
+    Ok(())
+}
+
+impl DirectoryOps for MockDirectory {
+    fn search<'a>(
+        &'a mut self,
+        base_dn: &'a str,
+        scope: Scope,
+        filter: &'a str,
+        attrs: &'a [&'a str],
+    ) -> OpFuture<'a, Vec<LdapEntry>> {
+        Box::pin(async move {
+            self.pause().await;
+            self.run(Operation::Search, base_dn, |d| {
+                d.search_now(base_dn, scope, filter, attrs)
+            })
+        })
+    }
+
+    fn search_pages<'a>(
+        &'a mut self,
+        base_dn: &'a str,
+        scope: Scope,
+        filter: &'a str,
+        attrs: &'a [&'a str],
+        on_page: PageSink<'a>,
+    ) -> OpFuture<'a, usize> {
+        Box::pin(async move {
+            self.pause().await;
+            let entries = self.run(Operation::Search, base_dn, |d| {
+                d.search_now(base_dn, scope, filter, attrs)
+            })?;
+            if entries.is_empty() {
+                self.pages_served += 1;
+                on_page(Vec::new());
+            }
+            for page in entries.chunks(self.page_size) {
+                self.pages_served += 1;
+                on_page(page.to_vec());
+            }
+            Ok(entries.len())
+        })
+    }
+
+    fn add_entry<'a>(
+        &'a mut self,
+        dn: &'a str,
+        attrs: Vec<(String, HashSet<String>)>,
+        binary: Vec<(String, HashSet<Vec<u8>>)>,
+    ) -> OpFuture<'a, ()> {
+        Box::pin(async move {
+            self.pause().await;
+            self.run(Operation::Add, dn, |d| d.add_now(dn, attrs, binary))
+        })
+    }
+
+    fn modify_entry<'a>(&'a mut self, dn: &'a str, mods: Vec<Mod<String>>) -> OpFuture<'a, ()> {
+        Box::pin(async move {
+            self.pause().await;
+            self.run(Operation::Modify, dn, |d| d.modify_now(dn, mods))
+        })
+    }
+
+    fn delete_entry<'a>(&'a mut self, dn: &'a str) -> OpFuture<'a, ()> {
+        Box::pin(async move {
+            self.pause().await;
+            self.run(Operation::Delete, dn, |d| d.delete_now(dn))
+        })
+    }
+
+    fn rename_entry<'a>(
+        &'a mut self,
+        dn: &'a str,
+        new_rdn: &'a str,
+        delete_old_rdn: bool,
+        new_superior: Option<&'a str>,
+    ) -> OpFuture<'a, ()> {
+        Box::pin(async move {
+            self.pause().await;
+            self.run(Operation::Rename, dn, |d| {
+                d.rename_now(dn, new_rdn, delete_old_rdn, new_superior)
+            })
+        })
+    }
+
+    fn start_transaction(&mut self) -> OpFuture<'_, ()> {
+        Box::pin(async move {
+            self.pause().await;
+            self.run(Operation::StartTransaction, "", |d| {
+                d.start_transaction_now()
+            })
+        })
+    }
+
+    fn end_transaction(&mut self, commit: bool) -> OpFuture<'_, ()> {
+        Box::pin(async move {
+            self.pause().await;
+            let result = self.run(Operation::EndTransaction, "", |d| {
+                d.end_transaction_now(commit)
+            });
+            // A transaction that fails to commit is abandoned whole
+            if result.is_err() {
+                if let Some(snapshot) = self.snapshot.take() {
+                    self.entries = snapshot;
+                }
+            }
+            result
+        })
+    }
+}
+
+/// Audit sink that keeps its records in memory for a test to look at.
+#[derive(Default)]
+pub struct MemoryAudit {
+    records: std::sync::Mutex<Vec<AuditRecord>>,
+}
+
+impl MemoryAudit {
+    pub fn records(&self) -> Vec<AuditRecord> {
+        self.records.lock().unwrap().clone()
+    }
+}
+
+impl AuditSink for MemoryAudit {
+    fn record(&self, record: &AuditRecord) -> std::io::Result<()> {
+        self.records.lock().unwrap().push(record.clone());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::{org_unit, person, OuTree};
+    use super::*;
+    use crate::errors::parse_error_message;
+
+    const BASE: &str = "dc=example,dc=com";
+
+    fn directory() -> MockDirectory {
+        MockDirectory::with_entries(OuTree::new(BASE).build())
+    }
+
+    fn code(err: CoreError) -> Option<u32> {
+        parse_error_message(&err.to_string()).code
+    }
+
+    #[tokio::test]
+    async fn test_search_scope_filter_and_attributes() {
+        let mut dir = directory();
+        let people = dir
+            .search(BASE, Scope::Subtree, "(objectClass=inetOrgPerson)", &["*"])
+            .await
+            .unwrap();
+        assert_eq!(people.len(), 6);
+
+        let units = dir
+            .search(BASE, Scope::OneLevel, "(objectClass=*)", &["ou"])
+            .await
+            .unwrap();
+        assert_eq!(units.len(), 2);
+        assert_eq!(units[0].attributes.len(), 1);
+
+        let found = dir
+            .search(
+                "OU=Unit2, DC=Example,DC=com",
+                Scope::Subtree,
+                "(&(sn=unit2)(|(givenName=User1)(mail=user3*)))",
+                &["cn"],
+            )
+            .await
+            .unwrap();
+        let names: Vec<_> = found.iter().map(|e| e.first_value("cn").unwrap()).collect();
+        assert_eq!(names, vec!["User1 Unit2", "User3 Unit2"]);
+
+        let err = dir
+            .search("ou=Missing,dc=example,dc=com", Scope::Base, "(cn=*)", &[])
+            .await
+            .unwrap_err();
+        assert_eq!(code(err), Some(NO_SUCH_OBJECT));
+        let err = dir
+            .search(BASE, Scope::Base, "(cn=*", &[])
+            .await
+            .unwrap_err();
+        assert_eq!(code(err), Some(FILTER_ERROR));
+    }
+
+    #[tokio::test]
+    async fn test_paged_search() {
+        let mut dir =
+            MockDirectory::with_entries(OuTree::new(BASE).ous(3).people_per_ou(4).build())
+                .with_page_size(5);
+        let mut pages = Vec::new();
+        let total = dir
+            .search_pages(BASE, Scope::Subtree, "(uid=*)", &["uid"], &mut |page| {
+                pages.push(page.len())
+            })
+            .await
+            .unwrap();
+        assert_eq!(total, 12);
+        assert_eq!(pages, vec![5, 5, 2]);
+        assert_eq!(dir.pages_served(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_updates_and_result_codes() {
+        let mut dir = directory();
+        let ada = person("ou=Unit1,dc=example,dc=com", "Ada", "Lovelace");
+        let attrs = crate::ops::add_attributes(&ada);
+        dir.add_entry(&ada.dn, attrs.clone(), Vec::new())
+            .await
+            .unwrap();
+        let err = dir
+            .add_entry(&ada.dn, attrs.clone(), Vec::new())
+            .await
+            .unwrap_err();
+        assert_eq!(code(err), Some(ENTRY_ALREADY_EXISTS));
+        let orphan = person("ou=Nowhere,dc=example,dc=com", "Alan", "Turing");
+        let err = dir
+            .add_entry(&orphan.dn, crate::ops::add_attributes(&orphan), Vec::new())
+            .await
+            .unwrap_err();
+        assert_eq!(code(err), Some(NO_SUCH_OBJECT));
+
+        let set = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<HashSet<_>>();
+        dir.modify_entry(
+            &ada.dn,
+            vec![
+                Mod::Add("mail".to_string(), set(&["ada@analytical.org"])),
+                Mod::Replace("title".to_string(), set(&["Countess"])),
+            ],
+        )
+        .await
+        .unwrap();
+        let stored = dir.entry(&ada.dn).unwrap();
+        assert_eq!(stored.attributes["mail"].len(), 2);
+        assert_eq!(stored.first_value("title"), Some("Countess"));
+
+        // A failing modification leaves the entry untouched
+        let err = dir
+            .modify_entry(
+                &ada.dn,
+                vec![
+                    Mod::Replace("title".to_string(), set(&["Engineer"])),
+                    Mod::Add("MAIL".to_string(), set(&["ADA.Lovelace@example.com"])),
+                ],
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(code(err), Some(ATTRIBUTE_OR_VALUE_EXISTS));
+        assert_eq!(
+            dir.entry(&ada.dn).unwrap().first_value("title"),
+            Some("Countess")
+        );
+
+        let err = dir
+            .modify_entry(&ada.dn, vec![Mod::Delete("pager".to_string(), set(&[]))])
+            .await
+            .unwrap_err();
+        assert_eq!(code(err), Some(NO_SUCH_ATTRIBUTE));
+        let err = dir
+            .modify_entry(&ada.dn, vec![Mod::Delete("uid".to_string(), set(&[]))])
+            .await
+            .unwrap_err();
+        assert_eq!(code(err), Some(NOT_ALLOWED_ON_RDN));
+
+        let err = dir
+            .delete_entry("ou=Unit1,dc=example,dc=com")
+            .await
+            .unwrap_err();
+        assert_eq!(code(err), Some(NOT_ALLOWED_ON_NON_LEAF));
+        dir.delete_entry(&ada.dn).await.unwrap();
+        let err = dir.delete_entry(&ada.dn).await.unwrap_err();
+        assert!(matches!(err, CoreError::DeleteFailed(_)));
+        assert_eq!(code(err), Some(NO_SUCH_OBJECT));
+    }
+
+    #[tokio::test]
+    async fn test_rename_moves_descendants() {
+        let mut dir = directory();
+        dir.insert(org_unit(BASE, "Archive"));
+        dir.rename_entry(
+            "ou=Unit1,dc=example,dc=com",
+            "ou=Retired",
+            true,
+            Some("ou=Archive,dc=example,dc=com"),
+        )
+        .await
+        .unwrap();
+        let moved = dir
+            .entry("ou=Retired,ou=Archive,dc=example,dc=com")
+            .unwrap();
+        assert_eq!(moved.attributes["ou"], vec!["Retired"]);
+        assert!(dir
+            .entry("uid=user2.unit1,ou=Retired,ou=Archive,dc=example,dc=com")
+            .is_some());
+        assert!(dir.entry("ou=Unit1,dc=example,dc=com").is_none());
+
+        let err = dir
+            .rename_entry("ou=Unit2,dc=example,dc=com", "ou=Archive", true, None)
+            .await
+            .unwrap_err();
+        assert_eq!(code(err), Some(ENTRY_ALREADY_EXISTS));
+        let err = dir
+            .rename_entry(
+                "ou=Archive,dc=example,dc=com",
+                "ou=Loop",
+                true,
+                Some("ou=Retired,ou=Archive,dc=example,dc=com"),
+            )
+            .await
+            .unwrap_err();
+        assert_eq!(code(err), Some(UNWILLING_TO_PERFORM));
+    }
+
+    #[tokio::test]
+    async fn test_failure_injection_and_latency() {
+        let mut dir = directory().with_latency(Duration::from_millis(20));
+        let dn = "uid=user1.unit1,ou=Unit1,dc=example,dc=com";
+        dir.fail_next(Operation::Delete, 50, "insufficient access");
+        let err = dir.delete_entry(dn).await.unwrap_err();
+        assert_eq!(code(err), Some(50));
+        dir.delete_entry(dn).await.unwrap();
+
+        dir.fail_on(Operation::Search, "ou=Unit2,dc=example,dc=com", 51, "busy");
+        for _ in 0..2 {
+            assert!(dir
+                .search("OU=Unit2,dc=example,dc=com", Scope::Base, "(ou=*)", &[])
+                .await
+                .is_err());
+        }
+        assert!(dir.search(BASE, Scope::Base, "(dc=*)", &[]).await.is_ok());
+        dir.clear_failures();
+        assert!(dir
+            .search("ou=Unit2,dc=example,dc=com", Scope::Base, "(ou=*)", &[])
+            .await
+            .is_ok());
+
+        assert_eq!(dir.log().len(), 6);
+        assert_eq!(dir.log()[0], (Operation::Delete, dn.to_string()));
+
+        let started = std::time::Instant::now();
+        dir.search(BASE, Scope::Base, "(dc=*)", &[]).await.unwrap();
+        assert!(started.elapsed() >= Duration::from_millis(20));
+    }
+}
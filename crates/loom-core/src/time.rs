@@ -0,0 +1,332 @@
+//! Parsers for the timestamp encodings found in directory attributes:
+//! LDAP GeneralizedTime (RFC 4517) and Active Directory FILETIME integers.
+
+use chrono::{DateTime, Duration, NaiveDate, SecondsFormat, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::schema::{AttributeSyntax, SchemaCache};
+
+/// AD attributes stored as FILETIME (100ns intervals since 1601-01-01 UTC).
+/// Their schema syntax is Large Integer, which is also used for plain counters,
+/// so they are recognized by name.
+pub const FILETIME_ATTRIBUTES: &[&str] = &[
+    "accountExpires",
+    "badPasswordTime",
+    "lastLogoff",
+    "lastLogon",
+    "lastLogonTimestamp",
+    "lockoutTime",
+    "msDS-LastSuccessfulInteractiveLogonTime",
+    "msDS-LastFailedInteractiveLogonTime",
+    "msDS-UserPasswordExpiryTimeComputed",
+    "pwdLastSet",
+];
+
+/// Common GeneralizedTime attributes, used when no schema is loaded.
+const GENERALIZED_TIME_ATTRIBUTES: &[&str] = &[
+    "createTimestamp",
+    "modifyTimestamp",
+    "whenCreated",
+    "whenChanged",
+    "pwdChangedTime",
+    "pwdAccountLockedTime",
+    "pwdFailureTime",
+    "dSCorePropagationData",
+    "authTimestamp",
+    "krbLastPwdChange",
+    "krbPasswordExpiration",
+    "krbLastSuccessfulAuth",
+    "krbLastFailedAuth",
+];
+
+/// How an attribute's values encode a point in time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampKind {
+    GeneralizedTime,
+    FileTime,
+}
+
+/// Classify an attribute as a timestamp, using the schema when available.
+pub fn timestamp_kind(attr: &str, schema: Option<&SchemaCache>) -> Option<TimestampKind> {
+    if FILETIME_ATTRIBUTES
+        .iter()
+        .any(|a| a.eq_ignore_ascii_case(attr))
+    {
+        return Some(TimestampKind::FileTime);
+    }
+    let by_schema = schema
+        .and_then(|s| s.get_attribute_type(attr))
+        .map(|at| at.syntax == AttributeSyntax::GeneralizedTime);
+    match by_schema {
+        Some(true) => Some(TimestampKind::GeneralizedTime),
+        Some(false) => None,
+        None => GENERALIZED_TIME_ATTRIBUTES
+            .iter()
+            .any(|a| a.eq_ignore_ascii_case(attr))
+            .then_some(TimestampKind::GeneralizedTime),
+    }
+}
+
+/// Parse a GeneralizedTime value such as `20240131123000Z`,
+/// `20240131123000.0Z` or `202401311230+0100`.
+/// Values without a zone designator are rejected, since their zone is unknown.
+pub fn parse_generalized_time(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    let digits = value.bytes().take_while(u8::is_ascii_digit).count();
+    if !matches!(digits, 10 | 12 | 14) {
+        return None;
+    }
+    let num = |range: std::ops::Range<usize>| -> Option<u32> { value.get(range)?.parse().ok() };
+    let year = num(0..4)? as i32;
+    let date = NaiveDate::from_ymd_opt(year, num(4..6)?, num(6..8)?)?;
+    let hour = num(8..10)?;
+    let minute = if digits >= 12 { num(10..12)? } else { 0 };
+    let second = if digits == 14 { num(12..14)? } else { 0 };
+    let mut rest = &value[digits..];
+
+    // Optional fraction of the last given unit
+    let mut fraction: f64 = 0.0;
+    if let Some(frac) = rest.strip_prefix('.').or_else(|| rest.strip_prefix(',')) {
+        let len = frac.bytes().take_while(u8::is_ascii_digit).count();
+        if len == 0 {
+            return None;
+        }
+        fraction = format!("0.{}", &frac[..len]).parse().ok()?;
+        rest = &frac[len..];
+    }
+    let unit_secs = match digits {
+        10 => 3600.0,
+        12 => 60.0,
+        _ => 1.0,
+    };
+
+    let offset_secs: i64 = match rest {
+        "Z" => 0,
+        _ => {
+            let sign = match rest.as_bytes().first()? {
+                b'+' => 1,
+                b'-' => -1,
+                _ => return None,
+            };
+            let tz = &rest[1..];
+            if !matches!(tz.len(), 2 | 4) || !tz.bytes().all(|b| b.is_ascii_digit()) {
+                return None;
+            }
+            let hours: i64 = tz[..2].parse().ok()?;
+            let minutes: i64 = if tz.len() == 4 {
+                tz[2..].parse().ok()?
+            } else {
+                0
+            };
+            sign * (hours * 3600 + minutes * 60)
+        }
+    };
+
+    let local = date.and_hms_opt(hour, minute, second)?;
+    let nanos = (fraction * unit_secs * 1e9).round() as i64;
+    let utc = Utc.from_utc_datetime(&local) - Duration::seconds(offset_secs)
+        + Duration::nanoseconds(nanos);
+    Some(utc)
+}
+
+/// Parse an AD FILETIME value. The "never" sentinels (0 and `i64::MAX`)
+/// are not points in time and yield `None`; see [`is_filetime_never`].
+pub fn parse_filetime(value: &str) -> Option<DateTime<Utc>> {
+    let ticks: i64 = value.trim().parse().ok()?;
+    if ticks <= 0 || ticks == i64::MAX {
+        return None;
+    }
+    const EPOCH_DIFF_SECS: i64 = 11_644_473_600;
+    let secs = ticks / 10_000_000 - EPOCH_DIFF_SECS;
+    let nanos = (ticks % 10_000_000) * 100;
+    DateTime::from_timestamp(secs, nanos as u32)
+}
+
+/// Whether a FILETIME value is one of the "never"/"not set" sentinels.
+pub fn is_filetime_never(value: &str) -> bool {
+    matches!(value.trim().parse::<i64>(), Ok(0) | Ok(i64::MAX))
+}
+
+/// How timestamp values are shown.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TimeDisplay {
+    /// As stored, e.g. `20240312084512Z`.
+    Raw,
+    /// ISO 8601 in UTC, e.g. `2024-03-12T08:45:12Z`.
+    Iso,
+    /// How long ago, e.g. `3 days ago`.
+    Relative,
+    /// `2024-03-12T08:45:12Z (3 days ago)`.
+    #[default]
+    Both,
+}
+
+impl TimeDisplay {
+    pub fn is_default(&self) -> bool {
+        *self == TimeDisplay::default()
+    }
+}
+
+/// A timestamp value of `kind` shown as `display` says, relative to `now`.
+/// FILETIME sentinels read `never`; values that do not parse are returned
+/// unchanged.
+pub fn display_timestamp(
+    value: &str,
+    kind: TimestampKind,
+    display: TimeDisplay,
+    now: DateTime<Utc>,
+) -> String {
+    if display == TimeDisplay::Raw {
+        return value.to_string();
+    }
+    if kind == TimestampKind::FileTime && is_filetime_never(value) {
+        return "never".to_string();
+    }
+    let parsed = match kind {
+        TimestampKind::GeneralizedTime => parse_generalized_time(value),
+        TimestampKind::FileTime => parse_filetime(value),
+    };
+    let Some(at) = parsed else {
+        return value.to_string();
+    };
+    let iso = at.to_rfc3339_opts(SecondsFormat::AutoSi, true);
+    match display {
+        TimeDisplay::Raw => value.to_string(),
+        TimeDisplay::Iso => iso,
+        TimeDisplay::Relative => relative_phrase(at, now),
+        TimeDisplay::Both => format!("{} ({})", iso, relative_phrase(at, now)),
+    }
+}
+
+/// `just now`, `5 minutes ago`, `in 3 days`, `2 years ago`: the distance
+/// from `now` in its largest whole unit.
+pub fn relative_phrase(at: DateTime<Utc>, now: DateTime<Utc>) -> String {
+    let secs = (now - at).num_seconds();
+    let distance = secs.unsigned_abs();
+    if distance < 60 {
+        return "just now".to_string();
+    }
+    const UNITS: &[(u64, &str)] = &[
+        (365 * 86_400, "year"),
+        (30 * 86_400, "month"),
+        (86_400, "day"),
+        (3_600, "hour"),
+        (60, "minute"),
+    ];
+    let (count, unit) = UNITS
+        .iter()
+        .find(|(size, _)| distance >= *size)
+        .map(|(size, unit)| (distance / size, *unit))
+        .unwrap_or((distance / 60, "minute"));
+    let plural = if count == 1 { "" } else { "s" };
+    if secs >= 0 {
+        format!("{} {}{} ago", count, unit, plural)
+    } else {
+        format!("in {} {}{}", count, unit, plural)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_generalized_time_forms() {
+        let expected = Utc.with_ymd_and_hms(2024, 1, 31, 12, 30, 0).unwrap();
+        assert_eq!(parse_generalized_time("20240131123000Z"), Some(expected));
+        assert_eq!(parse_generalized_time("20240131123000.0Z"), Some(expected));
+        assert_eq!(parse_generalized_time("202401311230Z"), Some(expected));
+        assert_eq!(
+            parse_generalized_time("20240131133000+0100"),
+            Some(expected)
+        );
+        assert_eq!(
+            parse_generalized_time("2024013112.5Z"),
+            Some(expected),
+            "fraction of an hour"
+        );
+    }
+
+    #[test]
+    fn test_parse_generalized_time_rejects_garbage() {
+        assert_eq!(parse_generalized_time("20240131123000"), None);
+        assert_eq!(parse_generalized_time("20241331123000Z"), None);
+        assert_eq!(parse_generalized_time("yesterday"), None);
+        assert_eq!(parse_generalized_time("20240131123000+1"), None);
+    }
+
+    #[test]
+    fn test_relative_phrase() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap();
+        let ago = |secs| relative_phrase(now - Duration::seconds(secs), now);
+        assert_eq!(ago(30), "just now");
+        assert_eq!(ago(-30), "just now");
+        assert_eq!(ago(60), "1 minute ago");
+        assert_eq!(ago(5 * 3600 + 59), "5 hours ago");
+        assert_eq!(ago(3 * 86_400), "3 days ago");
+        assert_eq!(ago(-2 * 86_400), "in 2 days");
+        assert_eq!(ago(45 * 86_400), "1 month ago");
+        assert_eq!(ago(800 * 86_400), "2 years ago");
+    }
+
+    #[test]
+    fn test_display_timestamp() {
+        let now = Utc.with_ymd_and_hms(2024, 3, 15, 8, 45, 12).unwrap();
+        let show =
+            |value, display| display_timestamp(value, TimestampKind::GeneralizedTime, display, now);
+        assert_eq!(show("20240312084512Z", TimeDisplay::Raw), "20240312084512Z");
+        assert_eq!(
+            show("20240312084512Z", TimeDisplay::Iso),
+            "2024-03-12T08:45:12Z"
+        );
+        assert_eq!(show("20240312084512Z", TimeDisplay::Relative), "3 days ago");
+        assert_eq!(
+            show("20240312094511.5+0100", TimeDisplay::Both),
+            "2024-03-12T08:45:11.500Z (3 days ago)"
+        );
+        // Malformed values are shown as they are
+        assert_eq!(show("2024-03-12", TimeDisplay::Both), "2024-03-12");
+        assert_eq!(
+            display_timestamp("0", TimestampKind::FileTime, TimeDisplay::Both, now),
+            "never"
+        );
+        assert_eq!(
+            display_timestamp(
+                "133511778000000000",
+                TimestampKind::FileTime,
+                TimeDisplay::Iso,
+                now
+            ),
+            "2024-01-31T12:30:00Z"
+        );
+    }
+
+    #[test]
+    fn test_parse_filetime() {
+        // 2024-01-31 12:30:00 UTC
+        assert_eq!(
+            parse_filetime("133511778000000000"),
+            Some(Utc.with_ymd_and_hms(2024, 1, 31, 12, 30, 0).unwrap())
+        );
+        assert_eq!(parse_filetime("0"), None);
+        assert_eq!(parse_filetime("9223372036854775807"), None);
+        assert!(is_filetime_never("9223372036854775807"));
+        assert!(!is_filetime_never("133511778000000000"));
+        assert_eq!(parse_filetime("abc"), None);
+    }
+
+    #[test]
+    fn test_timestamp_kind_without_schema() {
+        assert_eq!(
+            timestamp_kind("PWDLASTSET", None),
+            Some(TimestampKind::FileTime)
+        );
+        assert_eq!(
+            timestamp_kind("whenCreated", None),
+            Some(TimestampKind::GeneralizedTime)
+        );
+        assert_eq!(timestamp_kind("uidNumber", None), None);
+        assert_eq!(timestamp_kind("employeeNumber", None), None);
+    }
+}
@@ -0,0 +1,122 @@
+//! Time zones for rendering timestamps: UTC, the local zone, fixed offsets,
+//! and IANA names from the tz database bundled by `chrono-tz`.
+
+use chrono::{DateTime, FixedOffset, Local, Offset, Utc};
+use chrono_tz::Tz;
+
+/// A time zone that can map any UTC instant to its local offset.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Zone {
+    Utc,
+    /// The zone of the machine running loom.
+    Local,
+    Fixed(FixedOffset),
+    Iana(Tz),
+}
+
+impl Zone {
+    /// Parse a zone specification: `UTC`, `local`, a fixed offset such as
+    /// `+01:00`, or an IANA name such as `Europe/Berlin`.
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+        if spec.is_empty() || ["utc", "z", "gmt"].contains(&spec.to_lowercase().as_str()) {
+            return Ok(Zone::Utc);
+        }
+        if spec.eq_ignore_ascii_case("local") {
+            return Ok(Zone::Local);
+        }
+        if spec.starts_with('+') || spec.starts_with('-') {
+            return parse_fixed_offset(spec)
+                .map(Zone::Fixed)
+                .ok_or_else(|| format!("Invalid UTC offset '{}'", spec));
+        }
+        spec.parse::<Tz>().map(Zone::Iana).map_err(|_| {
+            format!(
+                "Unknown time zone '{}' (use an IANA name, UTC, local or +HH:MM)",
+                spec
+            )
+        })
+    }
+
+    /// The UTC offset in effect at `utc`.
+    pub fn offset_at(&self, utc: &DateTime<Utc>) -> FixedOffset {
+        let secs = match self {
+            Zone::Utc => 0,
+            Zone::Local => utc.with_timezone(&Local).offset().fix().local_minus_utc(),
+            Zone::Fixed(offset) => offset.local_minus_utc(),
+            Zone::Iana(tz) => utc.with_timezone(tz).offset().fix().local_minus_utc(),
+        };
+        FixedOffset::east_opt(secs).unwrap_or_else(|| Utc.fix())
+    }
+
+    /// Convert a UTC instant to local time in this zone.
+    pub fn to_local(&self, utc: &DateTime<Utc>) -> DateTime<FixedOffset> {
+        utc.with_timezone(&self.offset_at(utc))
+    }
+}
+
+/// Parse `+HH`, `+HHMM` or `+HH:MM` (and the `-` forms).
+fn parse_fixed_offset(spec: &str) -> Option<FixedOffset> {
+    let sign = if spec.starts_with('-') { -1 } else { 1 };
+    let digits: String = spec[1..].chars().filter(|c| *c != ':').collect();
+    if !matches!(digits.len(), 2 | 4) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = digits[..2].parse().ok()?;
+    let minutes: i32 = if digits.len() == 4 {
+        digits[2..].parse().ok()?
+    } else {
+        0
+    };
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn offset_hours(zone: &Zone, y: i32, mo: u32, d: u32, h: u32, mi: u32) -> f64 {
+        let utc = Utc.with_ymd_and_hms(y, mo, d, h, mi, 0).unwrap();
+        zone.offset_at(&utc).local_minus_utc() as f64 / 3600.0
+    }
+
+    #[test]
+    fn test_parse_simple_zones() {
+        assert_eq!(Zone::parse("UTC").unwrap(), Zone::Utc);
+        assert_eq!(Zone::parse("").unwrap(), Zone::Utc);
+        assert_eq!(Zone::parse("local").unwrap(), Zone::Local);
+        assert_eq!(
+            Zone::parse("+05:30").unwrap(),
+            Zone::Fixed(FixedOffset::east_opt(19800).unwrap())
+        );
+        assert_eq!(
+            Zone::parse("-0800").unwrap(),
+            Zone::Fixed(FixedOffset::west_opt(8 * 3600).unwrap())
+        );
+        assert!(Zone::parse("+5:3").is_err());
+        assert!(Zone::parse("Not/A_Zone").is_err());
+        assert!(Zone::parse("../etc/passwd").is_err());
+        assert!(Zone::parse("CET-1CEST,M3.5.0,M10.5.0/3").is_err());
+    }
+
+    #[test]
+    fn test_iana_spring_forward_and_fall_back() {
+        // Europe switches at 01:00 UTC on the last Sundays of March and October
+        let zone = Zone::parse("Europe/Berlin").unwrap();
+        assert_eq!(offset_hours(&zone, 2024, 3, 31, 0, 59), 1.0);
+        assert_eq!(offset_hours(&zone, 2024, 3, 31, 1, 0), 2.0);
+        assert_eq!(offset_hours(&zone, 2024, 10, 27, 0, 59), 2.0);
+        assert_eq!(offset_hours(&zone, 2024, 10, 27, 1, 0), 1.0);
+        assert_eq!(offset_hours(&zone, 1995, 7, 1, 0, 0), 2.0);
+    }
+
+    #[test]
+    fn test_iana_other_zones() {
+        let zone = Zone::parse("Australia/Sydney").unwrap();
+        assert_eq!(offset_hours(&zone, 2024, 1, 15, 0, 0), 11.0);
+        assert_eq!(offset_hours(&zone, 2024, 7, 15, 0, 0), 10.0);
+        let zone = Zone::parse("Asia/Kolkata").unwrap();
+        assert_eq!(offset_hours(&zone, 2024, 6, 1, 0, 0), 5.5);
+    }
+}
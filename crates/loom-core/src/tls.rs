@@ -1,15 +1,73 @@
 use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, RwLock};
 
 use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
 use rustls::client::WebPkiServerVerifier;
-use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
 use rustls::{
-    ClientConfig, DigitallySignedStruct, Error as TlsError, RootCertStore, SignatureScheme,
+    CertificateError, ClientConfig, DigitallySignedStruct, Error as TlsError, RootCertStore,
+    SignatureScheme,
 };
 use sha2::{Digest, Sha256};
 
+use crate::error::CoreError;
+
+/// Certificate settings of a profile, on top of its TLS mode.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct TlsOptions {
+    /// PEM bundle of CA certificates trusted besides the system's.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_cert: Option<PathBuf>,
+    /// PEM certificate chain presented to the server (mutual TLS).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_cert: Option<PathBuf>,
+    /// PEM private key for `client_cert`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_key: Option<PathBuf>,
+    /// Check the server certificate. Off accepts any certificate, which
+    /// is only meant for lab servers.
+    #[serde(default = "default_verify", skip_serializing_if = "is_verify")]
+    pub verify: bool,
+}
+
+fn default_verify() -> bool {
+    true
+}
+
+fn is_verify(v: &bool) -> bool {
+    *v
+}
+
+impl Default for TlsOptions {
+    fn default() -> Self {
+        Self {
+            ca_cert: None,
+            client_cert: None,
+            client_key: None,
+            verify: true,
+        }
+    }
+}
+
+impl TlsOptions {
+    /// Rewrite every path with `f`, e.g. to expand `~`.
+    pub fn map_paths(&mut self, f: impl Fn(&Path) -> PathBuf) {
+        for path in [
+            &mut self.ca_cert,
+            &mut self.client_cert,
+            &mut self.client_key,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *path = f(path);
+        }
+    }
+}
+
 /// Information about a server certificate, extracted for display to the user.
 #[derive(Debug, Clone)]
 pub struct CertificateInfo {
@@ -20,6 +78,9 @@ pub struct CertificateInfo {
     pub not_before: String,
     pub not_after: String,
     pub fingerprint_sha256: String,
+    /// Why verification failed, e.g. "unknown CA"; empty when it was not
+    /// checked.
+    pub problem: String,
 }
 
 impl fmt::Display for CertificateInfo {
@@ -141,9 +202,52 @@ pub fn parse_cert_info(der: &[u8], host: &str, port: u16) -> CertificateInfo {
         not_before,
         not_after,
         fingerprint_sha256: fingerprint,
+        problem: String::new(),
     }
 }
 
+/// Why a server certificate was rejected, in words.
+pub fn describe_cert_error(err: &TlsError, host: &str) -> String {
+    let TlsError::InvalidCertificate(err) = err else {
+        return err.to_string();
+    };
+    match err {
+        CertificateError::Expired | CertificateError::ExpiredContext { .. } => {
+            "certificate expired".to_string()
+        }
+        CertificateError::NotValidYet | CertificateError::NotValidYetContext { .. } => {
+            "certificate not valid yet".to_string()
+        }
+        CertificateError::UnknownIssuer => "unknown CA".to_string(),
+        CertificateError::NotValidForNameContext { presented, .. } if !presented.is_empty() => {
+            format!(
+                "hostname mismatch: issued for {}, not {}",
+                presented.join(", "),
+                host
+            )
+        }
+        CertificateError::NotValidForName | CertificateError::NotValidForNameContext { .. } => {
+            format!("hostname mismatch: not issued for {}", host)
+        }
+        CertificateError::Revoked => "certificate revoked".to_string(),
+        CertificateError::BadSignature => "bad signature".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn load_certs(path: &Path) -> Result<Vec<CertificateDer<'static>>, CoreError> {
+    let certs = CertificateDer::pem_file_iter(path)
+        .and_then(|certs| certs.collect::<Result<Vec<_>, _>>())
+        .map_err(|e| CoreError::TlsConfig(format!("{}: {}", path.display(), e)))?;
+    if certs.is_empty() {
+        return Err(CoreError::TlsConfig(format!(
+            "{}: no certificates found",
+            path.display()
+        )));
+    }
+    Ok(certs)
+}
+
 /// Load the system's native root certificate store.
 fn load_native_root_store() -> RootCertStore {
     let mut store = RootCertStore::empty();
@@ -164,6 +268,7 @@ pub struct CertCaptureVerifier {
     webpki_verifier: Arc<WebPkiServerVerifier>,
     host: String,
     port: u16,
+    verify: bool,
 }
 
 impl CertCaptureVerifier {
@@ -173,8 +278,18 @@ impl CertCaptureVerifier {
         host: &str,
         port: u16,
     ) -> Self {
-        let root_store = load_native_root_store();
-        let webpki_verifier = WebPkiServerVerifier::builder(Arc::new(root_store))
+        Self::with_roots(trust_store, captured, host, port, load_native_root_store())
+    }
+
+    /// Verify against `roots` instead of the system's certificates.
+    pub fn with_roots(
+        trust_store: Arc<TrustStore>,
+        captured: Arc<Mutex<Option<CertificateInfo>>>,
+        host: &str,
+        port: u16,
+        roots: RootCertStore,
+    ) -> Self {
+        let webpki_verifier = WebPkiServerVerifier::builder(Arc::new(roots))
             .build()
             .expect("failed to build webpki verifier");
 
@@ -184,8 +299,15 @@ impl CertCaptureVerifier {
             webpki_verifier,
             host: host.to_string(),
             port,
+            verify: true,
         }
     }
+
+    /// Accept any server certificate when `verify` is false.
+    pub fn with_verify(mut self, verify: bool) -> Self {
+        self.verify = verify;
+        self
+    }
 }
 
 impl ServerCertVerifier for CertCaptureVerifier {
@@ -200,7 +322,7 @@ impl ServerCertVerifier for CertCaptureVerifier {
         let fingerprint = sha256_fingerprint(end_entity.as_ref());
 
         // Check trust store first
-        if self.trust_store.is_trusted(&fingerprint) {
+        if !self.verify || self.trust_store.is_trusted(&fingerprint) {
             return Ok(ServerCertVerified::assertion());
         }
 
@@ -215,7 +337,8 @@ impl ServerCertVerifier for CertCaptureVerifier {
             Ok(verified) => Ok(verified),
             Err(err) => {
                 // Capture the certificate info for the UI
-                let cert_info = parse_cert_info(end_entity.as_ref(), &self.host, self.port);
+                let mut cert_info = parse_cert_info(end_entity.as_ref(), &self.host, self.port);
+                cert_info.problem = describe_cert_error(&err, &self.host);
                 if let Ok(mut slot) = self.captured.lock() {
                     *slot = Some(cert_info);
                 }
@@ -249,19 +372,47 @@ impl ServerCertVerifier for CertCaptureVerifier {
     }
 }
 
-/// Build a rustls `ClientConfig` that uses our `CertCaptureVerifier`.
+/// Build a rustls `ClientConfig` that uses our `CertCaptureVerifier`,
+/// with the extra CA and client certificate from `options`.
 pub fn build_client_config(
     trust_store: Arc<TrustStore>,
     captured: Arc<Mutex<Option<CertificateInfo>>>,
     host: &str,
     port: u16,
-) -> Arc<ClientConfig> {
-    let verifier = CertCaptureVerifier::new(trust_store, captured, host, port);
-    let config = ClientConfig::builder()
+    options: &TlsOptions,
+) -> Result<Arc<ClientConfig>, CoreError> {
+    let mut roots = load_native_root_store();
+    if let Some(path) = &options.ca_cert {
+        let (added, _) = roots.add_parsable_certificates(load_certs(path)?);
+        if added == 0 {
+            return Err(CoreError::TlsConfig(format!(
+                "{}: no usable CA certificates",
+                path.display()
+            )));
+        }
+    }
+    let verifier = CertCaptureVerifier::with_roots(trust_store, captured, host, port, roots)
+        .with_verify(options.verify);
+    let builder = ClientConfig::builder()
         .dangerous()
-        .with_custom_certificate_verifier(Arc::new(verifier))
-        .with_no_client_auth();
-    Arc::new(config)
+        .with_custom_certificate_verifier(Arc::new(verifier));
+    let config = match (&options.client_cert, &options.client_key) {
+        (Some(cert), Some(key)) => {
+            let chain = load_certs(cert)?;
+            let key = PrivateKeyDer::from_pem_file(key)
+                .map_err(|e| CoreError::TlsConfig(format!("{}: {}", key.display(), e)))?;
+            builder
+                .with_client_auth_cert(chain, key)
+                .map_err(|e| CoreError::TlsConfig(format!("client certificate: {}", e)))?
+        }
+        (None, None) => builder.with_no_client_auth(),
+        _ => {
+            return Err(CoreError::TlsConfig(
+                "client_cert and client_key must be set together".to_string(),
+            ))
+        }
+    };
+    Ok(Arc::new(config))
 }
 
 #[cfg(test)]
@@ -281,6 +432,68 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_describe_cert_error() {
+        let err = |e| TlsError::InvalidCertificate(e);
+        assert_eq!(
+            describe_cert_error(&err(CertificateError::Expired), "ldap"),
+            "certificate expired"
+        );
+        assert_eq!(
+            describe_cert_error(&err(CertificateError::UnknownIssuer), "ldap"),
+            "unknown CA"
+        );
+        let mismatch = CertificateError::NotValidForNameContext {
+            expected: ServerName::try_from("ldap.example.com").unwrap(),
+            presented: vec!["DnsName(\"other.example.com\")".to_string()],
+        };
+        assert!(describe_cert_error(&err(mismatch), "ldap.example.com")
+            .starts_with("hostname mismatch: issued for"));
+    }
+
+    #[test]
+    fn test_client_config_checks_tls_options() {
+        // Binaries install the provider at startup; another test may
+        // already have
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let store = Arc::new(TrustStore::from_config(&[]));
+        let build = |options: &TlsOptions| {
+            build_client_config(
+                store.clone(),
+                Arc::new(Mutex::new(None)),
+                "ldap.example.com",
+                636,
+                options,
+            )
+        };
+        assert!(build(&TlsOptions::default()).is_ok());
+
+        let half = TlsOptions {
+            client_cert: Some("client.pem".into()),
+            ..Default::default()
+        };
+        assert!(build(&half)
+            .unwrap_err()
+            .to_string()
+            .contains("must be set together"));
+
+        let missing = TlsOptions {
+            ca_cert: Some("/nonexistent/ca.pem".into()),
+            ..Default::default()
+        };
+        assert!(build(&missing)
+            .unwrap_err()
+            .to_string()
+            .contains("/nonexistent/ca.pem"));
+
+        let mut options = TlsOptions {
+            ca_cert: Some("ca.pem".into()),
+            ..Default::default()
+        };
+        options.map_paths(|p| Path::new("/etc/ssl").join(p));
+        assert_eq!(options.ca_cert, Some("/etc/ssl/ca.pem".into()));
+    }
+
     #[test]
     fn test_trust_store_session() {
         let store = TrustStore::from_config(&[]);
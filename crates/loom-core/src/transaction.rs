@@ -0,0 +1,510 @@
+//! Applying a batch of changes atomically where the server allows it.
+//!
+//! Servers that advertise the transaction extended operation (RFC 5805,
+//! e.g. OpenLDAP with `olcTxnEnabled`) get the batch wrapped in a start
+//! and end transaction, with the transaction control on every write; the
+//! first failure aborts it and nothing is kept. Elsewhere the changes go
+//! out one by one and each is reported on its own.
+
+use std::collections::HashSet;
+use std::fmt;
+
+use ldap3::exop::{EndTxn, StartTxn};
+use ldap3::Mod;
+use tracing::{debug, info, warn};
+
+use crate::bulk::{build_ldap_mods, BulkMod};
+use crate::connection::LdapConnection;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::ops::{add_attributes, add_binary_attributes, DirectoryOps};
+
+/// OID of the Start Transaction extended operation, as listed in the
+/// root DSE's `supportedExtension`.
+pub const TXN_START_OID: &str = "1.3.6.1.1.21.1";
+
+impl LdapConnection {
+    /// Start a transaction; writes carry its control until it ends.
+    pub async fn start_transaction(&mut self) -> Result<(), CoreError> {
+        if self.txn_id.is_some() {
+            return Err(CoreError::TransactionFailed(
+                "a transaction is already open".to_string(),
+            ));
+        }
+        self.check_health()?;
+        let result = self
+            .ldap
+            .extended(StartTxn)
+            .await
+            .map_err(CoreError::Ldap)?;
+        let (exop, res) = (result.0, result.1);
+        debug!("start_transaction result rc={} text={}", res.rc, res.text);
+        if res.rc != 0 {
+            return Err(CoreError::TransactionFailed(format!(
+                "Start transaction failed rc={}: {}",
+                res.rc, res.text
+            )));
+        }
+        let txn_id = exop
+            .val
+            .and_then(|v| String::from_utf8(v).ok())
+            .ok_or_else(|| {
+                CoreError::TransactionFailed("server sent no transaction identifier".to_string())
+            })?;
+        info!("Started transaction {}", txn_id);
+        self.txn_id = Some(txn_id);
+        Ok(())
+    }
+
+    /// Commit the open transaction, or abort it when `commit` is false.
+    pub async fn end_transaction(&mut self, commit: bool) -> Result<(), CoreError> {
+        let txn_id = self
+            .txn_id
+            .take()
+            .ok_or_else(|| CoreError::TransactionFailed("no transaction is open".to_string()))?;
+        let result = self
+            .ldap
+            .extended(EndTxn {
+                txn_id: &txn_id,
+                commit,
+            })
+            .await
+            .map_err(CoreError::Ldap)?;
+        let res = result.1;
+        debug!(
+            "end_transaction commit={} result rc={} text={}",
+            commit, res.rc, res.text
+        );
+        if res.rc != 0 {
+            return Err(CoreError::TransactionFailed(format!(
+                "End transaction failed rc={}: {}",
+                res.rc, res.text
+            )));
+        }
+        info!(
+            "{} transaction {}",
+            if commit { "Committed" } else { "Aborted" },
+            txn_id
+        );
+        Ok(())
+    }
+}
+
+/// One write in a batch.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Change {
+    Add {
+        dn: String,
+        attrs: Vec<(String, HashSet<String>)>,
+        binary: Vec<(String, HashSet<Vec<u8>>)>,
+    },
+    Modify {
+        dn: String,
+        mods: Vec<Mod<String>>,
+    },
+    Delete {
+        dn: String,
+    },
+    Rename {
+        dn: String,
+        new_rdn: String,
+        delete_old_rdn: bool,
+        new_superior: Option<String>,
+    },
+}
+
+impl Change {
+    pub fn add(entry: &LdapEntry) -> Self {
+        Change::Add {
+            dn: entry.dn.clone(),
+            attrs: add_attributes(entry),
+            binary: add_binary_attributes(entry),
+        }
+    }
+
+    pub fn modify(dn: &str, modifications: &[BulkMod]) -> Self {
+        Change::Modify {
+            dn: dn.to_string(),
+            mods: build_ldap_mods(modifications),
+        }
+    }
+
+    pub fn dn(&self) -> &str {
+        match self {
+            Change::Add { dn, .. }
+            | Change::Modify { dn, .. }
+            | Change::Delete { dn }
+            | Change::Rename { dn, .. } => dn,
+        }
+    }
+
+    /// What the change does, without the DN: `add`, `delete`,
+    /// `modify mail, pager` or `modrdn to uid=bob`.
+    pub fn summary(&self) -> String {
+        match self {
+            Change::Add { .. } => "add".to_string(),
+            Change::Delete { .. } => "delete".to_string(),
+            Change::Modify { mods, .. } => {
+                let mut attrs: Vec<&str> = Vec::new();
+                for m in mods {
+                    let (Mod::Add(attr, _)
+                    | Mod::Delete(attr, _)
+                    | Mod::Replace(attr, _)
+                    | Mod::Increment(attr, _)) = m;
+                    if !attrs.contains(&attr.as_str()) {
+                        attrs.push(attr);
+                    }
+                }
+                format!("modify {}", attrs.join(", "))
+            }
+            Change::Rename {
+                new_rdn,
+                new_superior,
+                ..
+            } => match new_superior {
+                Some(superior) => format!("modrdn to {},{}", new_rdn, superior),
+                None => format!("modrdn to {}", new_rdn),
+            },
+        }
+    }
+
+    pub(crate) async fn apply(&self, ops: &mut dyn DirectoryOps) -> Result<(), CoreError> {
+        match self {
+            Change::Add { dn, attrs, binary } => {
+                ops.add_entry(dn, attrs.clone(), binary.clone()).await
+            }
+            Change::Modify { dn, mods } => ops.modify_entry(dn, mods.clone()).await,
+            Change::Delete { dn } => ops.delete_entry(dn).await,
+            Change::Rename {
+                dn,
+                new_rdn,
+                delete_old_rdn,
+                new_superior,
+            } => {
+                ops.rename_entry(dn, new_rdn, *delete_old_rdn, new_superior.as_deref())
+                    .await
+            }
+        }
+    }
+}
+
+/// What became of one change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChangeOutcome {
+    Applied,
+    Failed(String),
+    /// Accepted, then undone when the transaction was aborted.
+    RolledBack,
+    /// Not sent, because the transaction had already failed.
+    NotAttempted,
+}
+
+/// The outcome of [`apply_batch`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchReport {
+    /// Per change, in order: its DN and outcome.
+    pub outcomes: Vec<(String, ChangeOutcome)>,
+    /// The batch ran inside a transaction.
+    pub atomic: bool,
+    /// The transaction was aborted and none of the batch was kept.
+    pub rolled_back: bool,
+    /// Why the batch was not atomic, or how the transaction ended badly.
+    pub note: Option<String>,
+}
+
+impl BatchReport {
+    pub fn applied(&self) -> usize {
+        self.count(|o| *o == ChangeOutcome::Applied)
+    }
+
+    pub fn failed(&self) -> usize {
+        self.count(|o| matches!(o, ChangeOutcome::Failed(_)))
+    }
+
+    /// `(dn, error)` for every change that failed.
+    pub fn errors(&self) -> Vec<(String, String)> {
+        self.outcomes
+            .iter()
+            .filter_map(|(dn, o)| match o {
+                ChangeOutcome::Failed(e) => Some((dn.clone(), e.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn count(&self, f: impl Fn(&ChangeOutcome) -> bool) -> usize {
+        self.outcomes.iter().filter(|(_, o)| f(o)).count()
+    }
+}
+
+impl fmt::Display for BatchReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.rolled_back {
+            f.write_str("Transaction rolled back, nothing was changed")?;
+            if self.failed() > 0 {
+                write!(f, " ({} failed)", self.failed())?;
+            }
+        } else {
+            write!(f, "{} applied, {} failed", self.applied(), self.failed())?;
+            if self.atomic {
+                f.write_str(" in one transaction")?;
+            }
+        }
+        if let Some(note) = &self.note {
+            write!(f, "; {}", note)?;
+        }
+        Ok(())
+    }
+}
+
+/// Apply `changes` in order. With `transactions` (the server advertises
+/// [`TXN_START_OID`]), they run in one transaction that is aborted on the
+/// first failure. Otherwise, or when the transaction cannot be started,
+/// each change is applied on its own and failures do not stop the rest.
+pub async fn apply_batch(
+    ops: &mut dyn DirectoryOps,
+    changes: &[Change],
+    transactions: bool,
+) -> BatchReport {
+    if !transactions {
+        let mut report = apply_each(ops, changes).await;
+        report.note =
+            Some("the server does not support transactions, so this was not atomic".to_string());
+        return report;
+    }
+    if let Err(e) = ops.start_transaction().await {
+        warn!("Could not start a transaction, applying one by one: {}", e);
+        let mut report = apply_each(ops, changes).await;
+        report.note = Some(format!(
+            "no transaction could be started ({}), so this was not atomic",
+            e
+        ));
+        return report;
+    }
+
+    let mut report = BatchReport {
+        atomic: true,
+        ..Default::default()
+    };
+    for (i, change) in changes.iter().enumerate() {
+        if let Err(e) = change.apply(ops).await {
+            debug!("Transaction aborted at {}: {}", change.dn(), e);
+            for outcome in &mut report.outcomes {
+                outcome.1 = ChangeOutcome::RolledBack;
+            }
+            report.outcomes.push((
+                change.dn().to_string(),
+                ChangeOutcome::Failed(e.to_string()),
+            ));
+            report.outcomes.extend(
+                changes[i + 1..]
+                    .iter()
+                    .map(|c| (c.dn().to_string(), ChangeOutcome::NotAttempted)),
+            );
+            report.rolled_back = true;
+            if let Err(e) = ops.end_transaction(false).await {
+                report.note = Some(format!("aborting the transaction failed: {}", e));
+            }
+            return report;
+        }
+        report
+            .outcomes
+            .push((change.dn().to_string(), ChangeOutcome::Applied));
+    }
+
+    // The server may still refuse the whole batch when committing it
+    if let Err(e) = ops.end_transaction(true).await {
+        warn!("Transaction commit failed: {}", e);
+        for outcome in &mut report.outcomes {
+            outcome.1 = ChangeOutcome::RolledBack;
+        }
+        report.rolled_back = true;
+        report.note = Some(format!("commit failed: {}", e));
+        return report;
+    }
+    info!("Transaction committed: {} changes", changes.len());
+    report
+}
+
+async fn apply_each(ops: &mut dyn DirectoryOps, changes: &[Change]) -> BatchReport {
+    let mut report = BatchReport::default();
+    for change in changes {
+        let outcome = match change.apply(ops).await {
+            Ok(()) => ChangeOutcome::Applied,
+            Err(e) => {
+                debug!("Change to {} failed: {}", change.dn(), e);
+                ChangeOutcome::Failed(e.to_string())
+            }
+        };
+        report.outcomes.push((change.dn().to_string(), outcome));
+    }
+    report
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures::{domain, group, org_unit, person};
+    use crate::testing::{MockDirectory, Operation};
+
+    const BASE: &str = "dc=example,dc=com";
+
+    /// Create a user and add them to three groups.
+    fn onboarding() -> (LdapEntry, Vec<Change>) {
+        let user = person(&format!("ou=people,{}", BASE), "Grace", "Hopper");
+        let mut changes = vec![Change::add(&user)];
+        for g in ["admins", "staff", "vpn"] {
+            changes.push(Change::modify(
+                &format!("cn={},ou=groups,{}", g, BASE),
+                &[BulkMod::AddValue {
+                    attr: "member".to_string(),
+                    value: user.dn.clone(),
+                }],
+            ));
+        }
+        (user, changes)
+    }
+
+    #[test]
+    fn test_change_summary() {
+        let (user, changes) = onboarding();
+        assert_eq!(changes[0].summary(), "add");
+        assert_eq!(changes[1].summary(), "modify member");
+        let edit = Change::Modify {
+            dn: user.dn.clone(),
+            mods: crate::modify::replace_value_mods("mail", "old@example.com", "new@example.com"),
+        };
+        assert_eq!(edit.summary(), "modify mail");
+        let rename = Change::Rename {
+            dn: user.dn,
+            new_rdn: "cn=Grace Murray Hopper".to_string(),
+            delete_old_rdn: true,
+            new_superior: Some(format!("ou=groups,{}", BASE)),
+        };
+        assert_eq!(
+            rename.summary(),
+            "modrdn to cn=Grace Murray Hopper,ou=groups,dc=example,dc=com"
+        );
+    }
+
+    fn directory() -> MockDirectory {
+        let admin = format!("cn=admin,{}", BASE);
+        let mut entries = vec![
+            domain(BASE),
+            org_unit(BASE, "people"),
+            org_unit(BASE, "groups"),
+        ];
+        for g in ["admins", "staff", "vpn"] {
+            entries.push(group(&format!("ou=groups,{}", BASE), g, &[&admin]));
+        }
+        MockDirectory::with_entries(entries)
+    }
+
+    fn members(dir: &MockDirectory, g: &str) -> usize {
+        dir.entry(&format!("cn={},ou=groups,{}", g, BASE))
+            .unwrap()
+            .attributes["member"]
+            .len()
+    }
+
+    #[tokio::test]
+    async fn test_batch_commits_in_one_transaction() {
+        let mut dir = directory().with_transactions();
+        let (user, changes) = onboarding();
+
+        let report = apply_batch(&mut dir, &changes, true).await;
+        assert!(report.atomic && !report.rolled_back, "{:?}", report);
+        assert_eq!(report.applied(), 4);
+        assert_eq!(report.to_string(), "4 applied, 0 failed in one transaction");
+        assert!(dir.entry(&user.dn).is_some());
+        assert_eq!(members(&dir, "vpn"), 2);
+
+        let ops: Vec<Operation> = dir.log().iter().map(|(op, _)| *op).collect();
+        assert_eq!(ops.first(), Some(&Operation::StartTransaction));
+        assert_eq!(ops.last(), Some(&Operation::EndTransaction));
+        assert!(!dir.in_transaction());
+    }
+
+    #[tokio::test]
+    async fn test_mid_batch_failure_rolls_everything_back() {
+        let mut dir = directory().with_transactions();
+        let (user, changes) = onboarding();
+        dir.fail_on(
+            Operation::Modify,
+            &format!("cn=staff,ou=groups,{}", BASE),
+            50,
+            "insufficient access",
+        );
+
+        let report = apply_batch(&mut dir, &changes, true).await;
+        assert!(report.rolled_back);
+        assert_eq!(
+            report
+                .outcomes
+                .iter()
+                .map(|(_, o)| o.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                ChangeOutcome::RolledBack,
+                ChangeOutcome::RolledBack,
+                ChangeOutcome::Failed(
+                    "modify failed: Modify cn=staff,ou=groups,dc=example,dc=com failed rc=50: insufficient access"
+                        .to_string()
+                ),
+                ChangeOutcome::NotAttempted,
+            ]
+        );
+        assert_eq!(report.errors().len(), 1);
+        // Neither the user nor the first membership was kept
+        assert!(dir.entry(&user.dn).is_none());
+        assert_eq!(members(&dir, "admins"), 1);
+        assert!(report.to_string().starts_with("Transaction rolled back"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_commit_keeps_nothing() {
+        let mut dir = directory().with_transactions();
+        let (user, changes) = onboarding();
+        dir.fail_next(Operation::EndTransaction, 51, "busy");
+
+        let report = apply_batch(&mut dir, &changes, true).await;
+        assert!(report.rolled_back);
+        assert!(report.note.as_deref().unwrap().contains("busy"));
+        assert!(dir.entry(&user.dn).is_none());
+        assert!(!dir.in_transaction());
+    }
+
+    #[tokio::test]
+    async fn test_falls_back_to_one_by_one() {
+        let (user, changes) = onboarding();
+
+        // Not advertised: no transaction is attempted
+        let mut dir = directory();
+        dir.fail_on(
+            Operation::Modify,
+            &format!("cn=staff,ou=groups,{}", BASE),
+            50,
+            "insufficient access",
+        );
+        let report = apply_batch(&mut dir, &changes, false).await;
+        assert!(!report.atomic && !report.rolled_back);
+        assert_eq!((report.applied(), report.failed()), (3, 1));
+        assert!(dir.entry(&user.dn).is_some());
+        assert!(report.to_string().contains("not atomic"), "{}", report);
+        assert!(!dir
+            .log()
+            .iter()
+            .any(|(op, _)| *op == Operation::StartTransaction));
+
+        // Advertised but refused: the note says why
+        let mut dir = directory();
+        let report = apply_batch(&mut dir, &changes, true).await;
+        assert!(!report.atomic);
+        assert_eq!(report.applied(), 4);
+        assert!(report
+            .note
+            .as_deref()
+            .unwrap()
+            .contains("no transaction could be started"));
+    }
+}
@@ -1,4 +1,19 @@
-use crate::dn;
+use crate::dn::{self, Dn};
+
+/// Where a node's child listing stands.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum LoadState {
+    /// Children were never asked for, or were dropped on collapse.
+    #[default]
+    NotLoaded,
+    /// A listing is in flight. Children from an earlier listing stay
+    /// visible until it lands.
+    Loading,
+    /// The last listing succeeded; the children may be empty.
+    Loaded,
+    /// The last listing failed with this error.
+    Failed(String),
+}
 
 /// A node in the directory tree.
 #[derive(Debug, Clone)]
@@ -7,6 +22,9 @@ pub struct TreeNode {
     pub display_name: String,
     pub children: Option<Vec<TreeNode>>,
     pub has_children_hint: bool,
+    /// Set when the children are listed a page at a time.
+    pub paging: Option<ChildPaging>,
+    pub load: LoadState,
 }
 
 impl TreeNode {
@@ -17,6 +35,8 @@ impl TreeNode {
             display_name,
             children: None,
             has_children_hint: true,
+            paging: None,
+            load: LoadState::NotLoaded,
         }
     }
 
@@ -30,18 +50,273 @@ impl TreeNode {
         self.children.as_ref().is_some_and(|c| !c.is_empty())
     }
 
+    /// Whether the last listing succeeded and found no children.
+    pub fn is_empty_container(&self) -> bool {
+        self.load == LoadState::Loaded && self.children.as_ref().is_some_and(|c| c.is_empty())
+    }
+
     /// Set the children of this node.
     pub fn set_children(&mut self, children: Vec<TreeNode>) {
         self.has_children_hint = !children.is_empty();
         self.children = Some(children);
+        self.load = LoadState::Loaded;
     }
 
     /// Collapse this node (remove children from memory).
     pub fn collapse(&mut self) {
         self.children = None;
+        self.load = LoadState::NotLoaded;
+    }
+
+    /// Tree label, with the page indicator for paged containers.
+    pub fn label(&self) -> String {
+        match (&self.paging, &self.children) {
+            (Some(paging), Some(children)) => paging.label(&self.display_name, children.len()),
+            _ => self.display_name.clone(),
+        }
+    }
+}
+
+/// Narrowing applied to a paged container's children.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChildQuery {
+    /// Extra LDAP filter; all children when `None`.
+    pub filter: Option<String>,
+    /// Attribute to sort by.
+    pub sort_attr: Option<String>,
+    pub sort_desc: bool,
+}
+
+impl ChildQuery {
+    /// Parse `[filter] [sort=attr | sort=-attr]`, e.g. `(sn=a*) sort=-cn`.
+    pub fn parse(input: &str) -> Result<Self, String> {
+        let mut query = ChildQuery::default();
+        let mut filter_parts = Vec::new();
+        for word in input.split_whitespace() {
+            if let Some(attr) = word.strip_prefix("sort=") {
+                let (attr, desc) = match attr.strip_prefix('-') {
+                    Some(a) => (a, true),
+                    None => (attr, false),
+                };
+                if attr.is_empty() {
+                    return Err("sort= needs an attribute name".to_string());
+                }
+                query.sort_attr = Some(attr.to_string());
+                query.sort_desc = desc;
+            } else {
+                filter_parts.push(word);
+            }
+        }
+        let filter = filter_parts.join(" ");
+        if !filter.is_empty() {
+            crate::filter::validate_filter(&filter)?;
+            query.filter = Some(filter);
+        }
+        Ok(query)
+    }
+
+    /// The filter to send for a one-level search.
+    pub fn ldap_filter(&self) -> &str {
+        self.filter.as_deref().unwrap_or("(objectClass=*)")
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.filter.is_none() && self.sort_attr.is_none()
+    }
+}
+
+impl std::fmt::Display for ChildQuery {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(ref filter) = self.filter {
+            parts.push(filter.clone());
+        }
+        if let Some(ref attr) = self.sort_attr {
+            let dir = if self.sort_desc { "-" } else { "" };
+            parts.push(format!("sort={}{}", dir, attr));
+        }
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// Page state for a container whose children are listed a page at a time.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChildPaging {
+    pub page_size: usize,
+    /// Zero-based index of the page currently materialized.
+    pub page: usize,
+    /// Whether the server reported more children after this page.
+    pub has_more: bool,
+    /// Approximate child count (numSubordinates) when the server provides it.
+    pub total_hint: Option<u64>,
+    pub query: ChildQuery,
+    /// Paged-results cookies; `cookies[i]` resumes the listing at page `i`.
+    pub cookies: Vec<Vec<u8>>,
+}
+
+impl ChildPaging {
+    pub fn new(page_size: usize) -> Self {
+        Self {
+            page_size: page_size.max(1),
+            page: 0,
+            has_more: false,
+            total_hint: None,
+            query: ChildQuery::default(),
+            cookies: vec![Vec::new()],
+        }
+    }
+
+    /// Whether a page other than the first can be shown, so the container
+    /// needs page markers or an indicator at all.
+    pub fn is_needed(&self) -> bool {
+        self.page > 0 || self.has_more || !self.query.is_empty()
+    }
+
+    /// Cookie that resumes the listing at `page`, if known.
+    pub fn cookie_for(&self, page: usize) -> Option<&[u8]> {
+        self.cookies.get(page).map(|c| c.as_slice())
+    }
+
+    /// Record a loaded page and the cookie for the page after it.
+    pub fn record_page(&mut self, page: usize, next_cookie: Vec<u8>) {
+        self.page = page;
+        self.has_more = !next_cookie.is_empty();
+        self.cookies.truncate(page + 1);
+        if self.has_more {
+            self.cookies.push(next_cookie);
+        }
+    }
+
+    /// Record a page read as a virtual list view window of `shown`
+    /// entries out of the server's count of `total`.
+    pub fn record_window(&mut self, page: usize, shown: usize, total: u64) {
+        self.page = page;
+        self.total_hint = Some(total);
+        self.has_more = ((page * self.page_size + shown) as u64) < total;
+    }
+
+    /// Start over at the first page, keeping the page size and query.
+    pub fn reset(&mut self) {
+        self.page = 0;
+        self.has_more = false;
+        self.total_hint = None;
+        self.cookies = vec![Vec::new()];
+    }
+
+    /// Take page `page` out of a fully loaded child list (offline directories).
+    pub fn slice(&mut self, children: Vec<TreeNode>, page: usize) -> Vec<TreeNode> {
+        let last_page = children.len().saturating_sub(1) / self.page_size;
+        self.page = page.min(last_page);
+        self.total_hint = Some(children.len() as u64);
+        let start = self.page * self.page_size;
+        self.has_more = children.len() > start + self.page_size;
+        children
+            .into_iter()
+            .skip(start)
+            .take(self.page_size)
+            .collect()
+    }
+
+    /// Label such as `People (showing 1,001-2,000 of ~200,000)`.
+    pub fn label(&self, name: &str, shown: usize) -> String {
+        let first = self.page * self.page_size;
+        let range = if self.page == 0 {
+            format_count(shown as u64)
+        } else if shown == 0 {
+            "none".to_string()
+        } else {
+            format!(
+                "{}-{}",
+                format_count(first as u64 + 1),
+                format_count((first + shown) as u64)
+            )
+        };
+        let total = match self.total_hint {
+            Some(total) if self.query.filter.is_none() => {
+                format!(" of ~{}", format_count(total))
+            }
+            _ if self.has_more => "+".to_string(),
+            _ => String::new(),
+        };
+        let narrowed = if self.query.is_empty() {
+            String::new()
+        } else {
+            format!(", {}", self.query)
+        };
+        format!("{} (showing {}{}{})", name, range, total, narrowed)
     }
 }
 
+/// Which way a page marker moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageDirection {
+    Previous,
+    Next,
+}
+
+impl PageDirection {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PageDirection::Previous => "[previous page]",
+            PageDirection::Next => "[next page]",
+        }
+    }
+}
+
+const PAGE_MARKER_PREFIX: &str = "\u{1}page:";
+
+/// Tree identifier of a `[next page]`/`[previous page]` pseudo-node.
+/// Never a valid DN, so it cannot collide with a real entry.
+pub fn page_marker_id(parent_dn: &str, direction: PageDirection) -> String {
+    let dir = match direction {
+        PageDirection::Previous => "prev",
+        PageDirection::Next => "next",
+    };
+    format!("{}{}:{}", PAGE_MARKER_PREFIX, dir, parent_dn)
+}
+
+/// Decode a page marker identifier into its direction and parent DN.
+pub fn parse_page_marker(id: &str) -> Option<(PageDirection, &str)> {
+    let rest = id.strip_prefix(PAGE_MARKER_PREFIX)?;
+    if let Some(dn) = rest.strip_prefix("prev:") {
+        Some((PageDirection::Previous, dn))
+    } else {
+        rest.strip_prefix("next:")
+            .map(|dn| (PageDirection::Next, dn))
+    }
+}
+
+const STATUS_MARKER_PREFIX: &str = "\u{1}status:";
+
+/// Tree identifier of the `(empty)`/`loading`/error pseudo-node shown
+/// under a container.
+pub fn status_marker_id(parent_dn: &str) -> String {
+    format!("{}{}", STATUS_MARKER_PREFIX, parent_dn)
+}
+
+/// Decode a status marker identifier into its parent DN.
+pub fn parse_status_marker(id: &str) -> Option<&str> {
+    id.strip_prefix(STATUS_MARKER_PREFIX)
+}
+
+/// Whether a tree identifier is a pseudo-node rather than an entry DN.
+pub fn is_marker(id: &str) -> bool {
+    parse_page_marker(id).is_some() || parse_status_marker(id).is_some()
+}
+
+/// Format a count with thousands separators (1000 -> "1,000").
+pub fn format_count(n: u64) -> String {
+    let digits = n.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i) % 3 == 0 {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
 /// The full directory tree, lazily loaded.
 #[derive(Debug)]
 pub struct DirectoryTree {
@@ -55,18 +330,36 @@ impl DirectoryTree {
         Self { root_dn, root }
     }
 
+    /// Find a node by DN.
+    pub fn find_node(&self, target_dn: &str) -> Option<&TreeNode> {
+        fn find<'a>(node: &'a TreeNode, target: &Target) -> Option<&'a TreeNode> {
+            match target.locate(node) {
+                Place::Here => Some(node),
+                Place::Below => node
+                    .children
+                    .iter()
+                    .flatten()
+                    .find_map(|child| find(child, target)),
+                Place::Elsewhere => None,
+            }
+        }
+        find(&self.root, &Target::new(target_dn))
+    }
+
     /// Find a mutable reference to a node by DN.
     pub fn find_node_mut(&mut self, target_dn: &str) -> Option<&mut TreeNode> {
-        Self::find_in_node(&mut self.root, target_dn)
+        Self::find_in_node(&mut self.root, &Target::new(target_dn))
     }
 
-    fn find_in_node<'a>(node: &'a mut TreeNode, target_dn: &str) -> Option<&'a mut TreeNode> {
-        if node.dn.eq_ignore_ascii_case(target_dn) {
-            return Some(node);
+    fn find_in_node<'a>(node: &'a mut TreeNode, target: &Target) -> Option<&'a mut TreeNode> {
+        match target.locate(node) {
+            Place::Here => return Some(node),
+            Place::Below => {}
+            Place::Elsewhere => return None,
         }
         if let Some(ref mut children) = node.children {
             for child in children.iter_mut() {
-                if let Some(found) = Self::find_in_node(child, target_dn) {
+                if let Some(found) = Self::find_in_node(child, target) {
                     return Some(found);
                 }
             }
@@ -80,6 +373,186 @@ impl DirectoryTree {
             node.set_children(children);
         }
     }
+
+    /// Add a newly created entry under `parent_dn`. It is listed at the
+    /// end when the children are loaded; otherwise the parent is only
+    /// marked as having some, and the entry shows up once it is expanded.
+    pub fn insert_child(&mut self, parent_dn: &str, child: TreeNode) {
+        if let Some(node) = self.find_node_mut(parent_dn) {
+            node.has_children_hint = true;
+            if let Some(children) = node.children.as_mut() {
+                let key = dn::normalize(&child.dn);
+                if !children.iter().any(|c| dn::normalize(&c.dn) == key) {
+                    children.push(child);
+                }
+            }
+        }
+    }
+
+    /// Drop a deleted entry and everything under it. A parent left with
+    /// no children stops showing as a container. The root stays.
+    pub fn remove(&mut self, target_dn: &str) -> bool {
+        self.take(target_dn).is_some()
+    }
+
+    /// Show a renamed or moved entry under its new DN. It leaves its old
+    /// parent and joins `new_dn`'s parent if that one's children are
+    /// listed; its own children are listed again when it is expanded, as
+    /// their DNs changed with it. Other nodes keep their state.
+    pub fn move_node(&mut self, old_dn: &str, new_dn: &str) {
+        let mut node = self
+            .take(old_dn)
+            .unwrap_or_else(|| TreeNode::new(old_dn.to_string()));
+        let moved = TreeNode::new(new_dn.to_string());
+        node.children = None;
+        node.paging = None;
+        node.load = LoadState::NotLoaded;
+        node.dn = moved.dn;
+        node.display_name = moved.display_name;
+        if let Some(parent) = dn::parent_dn(new_dn) {
+            self.insert_child(parent, node);
+        }
+    }
+
+    /// Detach `target_dn` from its parent's loaded children.
+    fn take(&mut self, target_dn: &str) -> Option<TreeNode> {
+        let parent_dn = dn::parent_dn(target_dn)?.to_string();
+        let parent = self.find_node_mut(&parent_dn)?;
+        let children = parent.children.as_mut()?;
+        let key = dn::normalize(target_dn);
+        let index = children.iter().position(|c| dn::normalize(&c.dn) == key)?;
+        let node = children.remove(index);
+        if children.is_empty() && parent.paging.is_none() {
+            parent.has_children_hint = false;
+        }
+        Some(node)
+    }
+
+    /// Replace a container's children with one page of them.
+    /// The paging state is dropped when the whole listing fit on one page.
+    pub fn insert_page(&mut self, parent_dn: &str, children: Vec<TreeNode>, paging: ChildPaging) {
+        if let Some(node) = self.find_node_mut(parent_dn) {
+            node.set_children(children);
+            if paging.is_needed() {
+                node.has_children_hint = true;
+                node.paging = Some(paging);
+            } else {
+                node.paging = None;
+            }
+        }
+    }
+
+    /// Record that a listing of `dn`'s children has started.
+    pub fn mark_loading(&mut self, dn: &str) {
+        if let Some(node) = self.find_node_mut(dn) {
+            node.load = LoadState::Loading;
+        }
+    }
+
+    /// Record that listing `dn`'s children failed. Children from an
+    /// earlier listing are kept.
+    pub fn mark_failed(&mut self, dn: &str, error: String) {
+        if let Some(node) = self.find_node_mut(dn) {
+            node.load = LoadState::Failed(error);
+        }
+    }
+
+    /// Follow the loaded nodes from the root toward `target_dn`.
+    pub fn reach(&self, target_dn: &str) -> Reach {
+        let target = Target::new(target_dn);
+        let mut path = Vec::new();
+        if target.dn.is_none() || matches!(target.locate(&self.root), Place::Elsewhere) {
+            return Reach::Unlisted(path);
+        }
+        let mut node = &self.root;
+        loop {
+            if matches!(target.locate(node), Place::Here) {
+                return Reach::Listed(path);
+            }
+            let Some(children) = node.children.as_ref() else {
+                return Reach::Unloaded {
+                    path,
+                    parent_dn: node.dn.clone(),
+                };
+            };
+            match children
+                .iter()
+                .find(|c| !matches!(target.locate(c), Place::Elsewhere))
+            {
+                Some(child) => {
+                    path.push(child.dn.clone());
+                    node = child;
+                }
+                None => return Reach::Unlisted(path),
+            }
+        }
+    }
+
+    /// Number of entries loaded so far, the root included.
+    pub fn loaded_count(&self) -> usize {
+        fn count(node: &TreeNode) -> usize {
+            1 + node.children.iter().flatten().map(count).sum::<usize>()
+        }
+        count(&self.root)
+    }
+}
+
+/// How far the loaded tree reaches toward an entry. Paths hold the DNs of
+/// the nodes from below the root down, as the tree panel identifies them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reach {
+    /// The entry is listed at the end of the path.
+    Listed(Vec<String>),
+    /// The children of `parent_dn`, the last node on the path or the root,
+    /// have not been listed yet.
+    Unloaded {
+        path: Vec<String>,
+        parent_dn: String,
+    },
+    /// The last node's children are listed without the next step toward
+    /// the entry: it is on another page, or was added since.
+    Unlisted(Vec<String>),
+}
+
+/// The DN a tree search is after, parsed once so each node can be
+/// placed relative to it.
+struct Target<'a> {
+    raw: &'a str,
+    dn: Option<Dn>,
+}
+
+/// Where a search target lies relative to a node.
+enum Place {
+    Here,
+    Below,
+    Elsewhere,
+}
+
+impl<'a> Target<'a> {
+    fn new(raw: &'a str) -> Self {
+        Self {
+            raw,
+            dn: Dn::parse(raw).ok(),
+        }
+    }
+
+    /// Only subtrees the target lies in are searched. When either DN
+    /// does not parse, the node is compared as text and searched through.
+    fn locate(&self, node: &TreeNode) -> Place {
+        if node.dn.eq_ignore_ascii_case(self.raw) {
+            return Place::Here;
+        }
+        let (Some(target), Ok(dn)) = (&self.dn, Dn::parse(&node.dn)) else {
+            return Place::Below;
+        };
+        if *target == dn {
+            Place::Here
+        } else if target.is_descendant_of(&dn) {
+            Place::Below
+        } else {
+            Place::Elsewhere
+        }
+    }
 }
 
 #[cfg(test)]
@@ -158,12 +631,153 @@ mod tests {
         assert_eq!(found.unwrap().display_name, "Groups");
     }
 
+    #[test]
+    fn test_insert_child() {
+        let mut tree = DirectoryTree::new("dc=example,dc=com".to_string());
+        tree.insert_children(
+            "dc=example,dc=com",
+            vec![TreeNode::new("ou=People,dc=example,dc=com".to_string())],
+        );
+        tree.insert_child(
+            "dc=example,dc=com",
+            TreeNode::new("ou=Groups,dc=example,dc=com".to_string()),
+        );
+        // Twice is still once
+        tree.insert_child(
+            "dc=example,dc=com",
+            TreeNode::new("OU=Groups,dc=example,dc=com".to_string()),
+        );
+        assert_eq!(tree.root.children.as_ref().unwrap().len(), 2);
+
+        // An empty container that was never expanded only learns it has some
+        let people = "ou=People,dc=example,dc=com";
+        tree.find_node_mut(people).unwrap().has_children_hint = false;
+        tree.insert_child(people, TreeNode::new(format!("uid=new,{}", people)));
+        let node = tree.find_node(people).unwrap();
+        assert!(node.has_children_hint);
+        assert!(node.children.is_none());
+    }
+
+    #[test]
+    fn test_remove() {
+        let mut tree = DirectoryTree::new("dc=example,dc=com".to_string());
+        let people = "ou=People,dc=example,dc=com";
+        tree.insert_children("dc=example,dc=com", vec![TreeNode::new(people.to_string())]);
+        tree.insert_children(people, vec![TreeNode::new(format!("uid=a,{}", people))]);
+
+        assert!(tree.remove("UID=a,ou=people,dc=example,dc=com"));
+        assert!(!tree.find_node(people).unwrap().has_children_hint);
+        assert!(!tree.remove("uid=a,ou=People,dc=example,dc=com"));
+
+        assert!(tree.remove(people));
+        assert!(tree.find_node(people).is_none());
+        assert!(!tree.remove("dc=example,dc=com"));
+    }
+
+    #[test]
+    fn test_move_node() {
+        let mut tree = DirectoryTree::new("dc=example,dc=com".to_string());
+        let people = "ou=People,dc=example,dc=com";
+        let staff = "ou=Staff,dc=example,dc=com";
+        tree.insert_children(
+            "dc=example,dc=com",
+            vec![
+                TreeNode::new(people.to_string()),
+                TreeNode::new(staff.to_string()),
+            ],
+        );
+        tree.insert_children(people, vec![TreeNode::new(format!("ou=Old,{}", people))]);
+        tree.insert_children(
+            &format!("ou=Old,{}", people),
+            vec![TreeNode::new(format!("uid=a,ou=Old,{}", people))],
+        );
+        tree.insert_children(staff, Vec::new());
+
+        tree.move_node(&format!("ou=Old,{}", people), &format!("ou=New,{}", staff));
+        assert!(!tree.find_node(people).unwrap().has_children_hint);
+        let moved = tree.find_node(&format!("ou=New,{}", staff)).unwrap();
+        assert_eq!(moved.display_name, "New");
+        // Its children had the old DNs, so they are listed again
+        assert!(moved.children.is_none());
+        assert!(moved.has_children_hint);
+        assert!(tree.find_node(staff).unwrap().has_children_hint);
+    }
+
     #[test]
     fn test_directory_tree_find_not_found() {
         let mut tree = DirectoryTree::new("dc=example,dc=com".to_string());
         assert!(tree.find_node_mut("cn=missing,dc=example,dc=com").is_none());
     }
 
+    #[test]
+    fn test_load_state_empty_then_refresh() {
+        let mut tree = DirectoryTree::new("dc=example,dc=com".to_string());
+        let ou = "ou=Empty,dc=example,dc=com";
+        tree.insert_children("dc=example,dc=com", vec![TreeNode::new(ou.to_string())]);
+        assert_eq!(tree.find_node(ou).unwrap().load, LoadState::NotLoaded);
+        assert!(!tree.find_node(ou).unwrap().is_empty_container());
+
+        tree.mark_loading(ou);
+        assert_eq!(tree.find_node(ou).unwrap().load, LoadState::Loading);
+        assert!(!tree.find_node(ou).unwrap().is_empty_container());
+
+        tree.insert_children(ou, vec![]);
+        assert!(tree.find_node(ou).unwrap().is_empty_container());
+
+        // A refresh keeps the old listing on screen until the new one lands
+        tree.mark_loading(ou);
+        let node = tree.find_node(ou).unwrap();
+        assert_eq!(node.load, LoadState::Loading);
+        assert_eq!(node.children.as_ref().map(Vec::len), Some(0));
+
+        tree.insert_children(ou, vec![TreeNode::new(format!("cn=a,{}", ou))]);
+        let node = tree.find_node(ou).unwrap();
+        assert_eq!(node.load, LoadState::Loaded);
+        assert!(!node.is_empty_container());
+    }
+
+    #[test]
+    fn test_load_state_failure_and_retry_after_reconnect() {
+        let mut tree = DirectoryTree::new("dc=example,dc=com".to_string());
+        let root = "dc=example,dc=com";
+        tree.insert_children(root, vec![TreeNode::new("ou=A,dc=example,dc=com".into())]);
+
+        // Refresh fails (e.g. the reconnect failed): error shown, old children kept
+        tree.mark_loading(root);
+        tree.mark_failed(root, "connection refused".to_string());
+        let node = tree.find_node(root).unwrap();
+        assert_eq!(
+            node.load,
+            LoadState::Failed("connection refused".to_string())
+        );
+        assert_eq!(node.children.as_ref().map(Vec::len), Some(1));
+        assert!(!node.is_empty_container());
+
+        // Retrying once the connection is back clears the error
+        tree.mark_loading(root);
+        tree.insert_children(root, vec![]);
+        let node = tree.find_node(root).unwrap();
+        assert_eq!(node.load, LoadState::Loaded);
+        assert!(node.is_empty_container());
+
+        // Collapsing forgets the listing entirely
+        tree.find_node_mut(root).unwrap().collapse();
+        assert_eq!(tree.find_node(root).unwrap().load, LoadState::NotLoaded);
+    }
+
+    #[test]
+    fn test_status_marker_roundtrip() {
+        let id = status_marker_id("ou=People,dc=example,dc=com");
+        assert_eq!(
+            parse_status_marker(&id),
+            Some("ou=People,dc=example,dc=com")
+        );
+        assert!(is_marker(&id));
+        assert!(is_marker(&page_marker_id("dc=x", PageDirection::Next)));
+        assert!(!is_marker("ou=People,dc=example,dc=com"));
+        assert!(parse_page_marker(&id).is_none());
+    }
+
     #[test]
     fn test_directory_tree_case_insensitive_find() {
         let mut tree = DirectoryTree::new("DC=example,DC=com".to_string());
@@ -194,4 +808,204 @@ mod tests {
         assert!(found.is_some());
         assert_eq!(found.unwrap().display_name, "Alice");
     }
+
+    #[test]
+    fn test_directory_tree_loaded_count() {
+        let mut tree = DirectoryTree::new("dc=example,dc=com".to_string());
+        assert_eq!(tree.loaded_count(), 1);
+        tree.insert_children(
+            "dc=example,dc=com",
+            vec![
+                TreeNode::new("ou=Users,dc=example,dc=com".to_string()),
+                TreeNode::new("ou=Groups,dc=example,dc=com".to_string()),
+            ],
+        );
+        tree.insert_children(
+            "ou=Users,dc=example,dc=com",
+            vec![TreeNode::new(
+                "cn=Alice,ou=Users,dc=example,dc=com".to_string(),
+            )],
+        );
+        assert_eq!(tree.loaded_count(), 4);
+
+        // Collapsing forgets the children
+        tree.find_node_mut("ou=Users,dc=example,dc=com")
+            .unwrap()
+            .collapse();
+        assert_eq!(tree.loaded_count(), 3);
+    }
+
+    #[test]
+    fn test_child_paging_cookies_and_reset() {
+        let mut paging = ChildPaging::new(1000);
+        assert!(!paging.is_needed());
+        paging.record_page(0, b"c1".to_vec());
+        assert!(paging.has_more);
+        assert!(paging.is_needed());
+        paging.record_page(1, b"c2".to_vec());
+        assert_eq!(paging.cookie_for(2), Some(&b"c2"[..]));
+
+        // Going back drops cookies beyond the page after the current one
+        paging.record_page(0, b"c1".to_vec());
+        assert_eq!(paging.cookies.len(), 2);
+        assert!(paging.cookie_for(2).is_none());
+
+        paging.record_page(1, Vec::new());
+        assert!(!paging.has_more);
+        paging.reset();
+        assert_eq!(paging.page, 0);
+        assert_eq!(paging.cookies, vec![Vec::<u8>::new()]);
+    }
+
+    #[test]
+    fn test_child_paging_window() {
+        let mut paging = ChildPaging::new(1000);
+        paging.record_window(299, 1000, 300_000);
+        assert_eq!(paging.page, 299);
+        assert!(!paging.has_more);
+        assert_eq!(paging.total_hint, Some(300_000));
+        paging.record_window(5, 1000, 300_000);
+        assert!(paging.has_more);
+        assert_eq!(
+            paging.label("Users", 1000),
+            "Users (showing 5,001-6,000 of ~300,000)"
+        );
+    }
+
+    #[test]
+    fn test_child_paging_slice() {
+        let children: Vec<TreeNode> = (0..5)
+            .map(|i| TreeNode::new(format!("cn=u{},dc=example,dc=com", i)))
+            .collect();
+        let mut paging = ChildPaging::new(2);
+        let page = paging.slice(children.clone(), 1);
+        assert_eq!(page.len(), 2);
+        assert_eq!(page[0].display_name, "u2");
+        assert!(paging.has_more);
+        assert_eq!(paging.total_hint, Some(5));
+
+        // Past the end clamps to the last page
+        let page = paging.slice(children, 9);
+        assert_eq!(paging.page, 2);
+        assert_eq!(page.len(), 1);
+        assert!(!paging.has_more);
+    }
+
+    #[test]
+    fn test_child_paging_label() {
+        let mut paging = ChildPaging::new(1000);
+        paging.total_hint = Some(200_000);
+        paging.has_more = true;
+        assert_eq!(
+            paging.label("People", 1000),
+            "People (showing 1,000 of ~200,000)"
+        );
+        paging.page = 1;
+        assert_eq!(
+            paging.label("People", 1000),
+            "People (showing 1,001-2,000 of ~200,000)"
+        );
+        paging.total_hint = None;
+        paging.query = ChildQuery::parse("(sn=a*) sort=-cn").unwrap();
+        assert_eq!(
+            paging.label("People", 10),
+            "People (showing 1,001-1,010+, (sn=a*) sort=-cn)"
+        );
+    }
+
+    #[test]
+    fn test_child_query_parse() {
+        assert!(ChildQuery::parse("").unwrap().is_empty());
+        let q = ChildQuery::parse("sort=sn").unwrap();
+        assert_eq!(q.sort_attr.as_deref(), Some("sn"));
+        assert!(!q.sort_desc);
+        assert_eq!(q.ldap_filter(), "(objectClass=*)");
+        let q = ChildQuery::parse("(&(objectClass=person)(sn=a*)) sort=-cn").unwrap();
+        assert_eq!(q.filter.as_deref(), Some("(&(objectClass=person)(sn=a*))"));
+        assert!(q.sort_desc);
+        assert!(ChildQuery::parse("sort=").is_err());
+        assert!(ChildQuery::parse("(sn=a*").is_err());
+    }
+
+    #[test]
+    fn test_page_marker_roundtrip() {
+        let id = page_marker_id("ou=People,dc=example,dc=com", PageDirection::Next);
+        assert_eq!(
+            parse_page_marker(&id),
+            Some((PageDirection::Next, "ou=People,dc=example,dc=com"))
+        );
+        assert!(parse_page_marker("ou=People,dc=example,dc=com").is_none());
+    }
+
+    #[test]
+    fn test_insert_page_drops_paging_for_small_containers() {
+        let mut tree = DirectoryTree::new("dc=example,dc=com".to_string());
+        let mut paging = ChildPaging::new(2);
+        paging.record_page(0, b"more".to_vec());
+        tree.insert_page(
+            "dc=example,dc=com",
+            vec![TreeNode::new("ou=A,dc=example,dc=com".to_string())],
+            paging,
+        );
+        assert!(tree.root.paging.is_some());
+        assert!(tree.root.label().starts_with("example (showing 1+"));
+
+        tree.insert_page("dc=example,dc=com", vec![], ChildPaging::new(2));
+        assert!(tree.root.paging.is_none());
+    }
+
+    #[test]
+    fn test_reach() {
+        let mut tree = DirectoryTree::new("dc=example,dc=com".to_string());
+        let target = "uid=ada,ou=People,dc=example,dc=com";
+        assert_eq!(
+            tree.reach(target),
+            Reach::Unloaded {
+                path: vec![],
+                parent_dn: "dc=example,dc=com".to_string()
+            }
+        );
+
+        tree.insert_children(
+            "dc=example,dc=com",
+            vec![
+                TreeNode::new("ou=Groups,dc=example,dc=com".to_string()),
+                TreeNode::new("ou=People,dc=example,dc=com".to_string()),
+            ],
+        );
+        let people = "ou=People,dc=example,dc=com".to_string();
+        assert_eq!(
+            tree.reach(target),
+            Reach::Unloaded {
+                path: vec![people.clone()],
+                parent_dn: people.clone()
+            }
+        );
+
+        tree.insert_children(
+            &people,
+            vec![TreeNode::new(
+                "uid=ada,ou=People,dc=example,dc=com".to_string(),
+            )],
+        );
+        assert_eq!(
+            tree.reach("UID=Ada, OU=people, DC=example, DC=com"),
+            Reach::Listed(vec![people.clone(), target.to_string()])
+        );
+        assert_eq!(
+            tree.reach("uid=bob,ou=People,dc=example,dc=com"),
+            Reach::Unlisted(vec![people])
+        );
+        assert_eq!(tree.reach("dc=example,dc=com"), Reach::Listed(vec![]));
+        assert_eq!(tree.reach("dc=other"), Reach::Unlisted(vec![]));
+        assert_eq!(tree.reach("not a dn"), Reach::Unlisted(vec![]));
+    }
+
+    #[test]
+    fn test_format_count() {
+        assert_eq!(format_count(0), "0");
+        assert_eq!(format_count(999), "999");
+        assert_eq!(format_count(1000), "1,000");
+        assert_eq!(format_count(1234567), "1,234,567");
+    }
 }
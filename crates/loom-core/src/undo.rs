@@ -0,0 +1,570 @@
+//! Undoing writes. Before an entry is modified, deleted or renamed it is
+//! read as it stands, and from that before-image the change that puts it
+//! back is worked out: a re-add for a delete, the opposite modifications
+//! for a modify, a rename back for a rename. Steps wait on a bounded
+//! stack, newest on top, until they are replayed or pushed off the end.
+
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::fmt;
+
+use ldap3::Mod;
+
+use crate::dn::{self, DnError, Rdn};
+use crate::duplicate::is_server_maintained;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::export::ldif_changes::write_changes;
+use crate::ops::DirectoryOps;
+use crate::transaction::Change;
+
+/// How many steps are kept unless configured otherwise.
+pub const DEFAULT_UNDO_DEPTH: usize = 20;
+
+/// Most entries a subtree delete reads ahead to be able to undo it;
+/// bigger subtrees are deleted without an undo step.
+pub const SUBTREE_UNDO_LIMIT: usize = 1000;
+
+/// The change that undoes `change` on an entry that looked like `before`.
+/// An add needs no before-image: it is undone by deleting the entry.
+pub fn invert_change(before: &LdapEntry, change: &Change) -> Option<Change> {
+    match change {
+        Change::Add { dn, .. } => Some(Change::Delete { dn: dn.clone() }),
+        Change::Modify { mods, .. } => invert_modify(before, mods),
+        Change::Delete { .. } => Some(invert_delete(before)),
+        Change::Rename {
+            new_rdn,
+            new_superior,
+            ..
+        } => invert_rename(before, new_rdn, new_superior.as_deref()).ok(),
+    }
+}
+
+/// The change that brings back an entry about to be deleted: an add of
+/// its text and binary attributes, leaving out those the server sets itself.
+pub fn invert_delete(before: &LdapEntry) -> Change {
+    Change::Add {
+        dn: before.dn.clone(),
+        attrs: before
+            .attributes
+            .iter()
+            .filter(|(attr, _)| !is_server_maintained(attr, None))
+            .map(|(attr, values)| (attr.clone(), values.iter().cloned().collect()))
+            .collect(),
+        binary: before
+            .binary_attributes
+            .iter()
+            .filter(|(attr, _)| !is_server_maintained(attr, None))
+            .map(|(attr, values)| (attr.clone(), values.iter().cloned().collect()))
+            .collect(),
+    }
+}
+
+/// The adds that bring back the `deleted` entries of a subtree read as
+/// `before`, parents ahead of their children.
+pub fn invert_subtree_delete(before: &[LdapEntry], deleted: &[String]) -> Vec<Change> {
+    let deleted: HashSet<String> = deleted.iter().map(|d| dn::normalize(d)).collect();
+    let mut entries: Vec<&LdapEntry> = before
+        .iter()
+        .filter(|e| deleted.contains(&dn::normalize(&e.dn)))
+        .collect();
+    entries.sort_by_key(|e| dn::depth(&e.dn));
+    entries.into_iter().map(invert_delete).collect()
+}
+
+/// The modify that undoes `mods` on an entry that looked like `before`.
+/// Only what the modify changes is put back: values it adds are deleted,
+/// values it deletes are added, and a replaced attribute gets its old
+/// values again. `None` when the modify would change nothing.
+pub fn invert_modify(before: &LdapEntry, mods: &[Mod<String>]) -> Option<Change> {
+    // Attribute values as each modification leaves them, by lowercase name
+    let mut state: BTreeMap<String, Vec<String>> = before
+        .attributes
+        .iter()
+        .map(|(attr, values)| (attr.to_lowercase(), values.clone()))
+        .collect();
+    let mut inverse = Vec::new();
+    for m in mods {
+        match m {
+            Mod::Add(attr, values) => {
+                let current = state.entry(attr.to_lowercase()).or_default();
+                let added: HashSet<String> = values
+                    .iter()
+                    .filter(|v| !current.contains(v))
+                    .cloned()
+                    .collect();
+                current.extend(added.iter().cloned());
+                if !added.is_empty() {
+                    inverse.push(Mod::Delete(attr.clone(), added));
+                }
+            }
+            Mod::Delete(attr, values) => {
+                let current = state.entry(attr.to_lowercase()).or_default();
+                let removed: HashSet<String> = if values.is_empty() {
+                    current.drain(..).collect()
+                } else {
+                    let removed = current.iter().filter(|v| values.contains(*v)).cloned();
+                    let removed: HashSet<String> = removed.collect();
+                    current.retain(|v| !removed.contains(v));
+                    removed
+                };
+                if !removed.is_empty() {
+                    inverse.push(Mod::Add(attr.clone(), removed));
+                }
+            }
+            Mod::Replace(attr, values) => {
+                let current = state.entry(attr.to_lowercase()).or_default();
+                let old = std::mem::replace(current, values.iter().cloned().collect());
+                inverse.push(Mod::Replace(attr.clone(), old.into_iter().collect()));
+            }
+            Mod::Increment(attr, by) => {
+                let negated = match by.strip_prefix('-') {
+                    Some(positive) => positive.to_string(),
+                    None => format!("-{}", by),
+                };
+                inverse.push(Mod::Increment(attr.clone(), negated));
+            }
+        }
+    }
+    if inverse.is_empty() {
+        return None;
+    }
+    inverse.reverse();
+    Some(Change::Modify {
+        dn: before.dn.clone(),
+        mods: inverse,
+    })
+}
+
+/// The rename that moves an entry that looked like `before` back after a
+/// ModifyDN to `new_rdn` (and `new_superior`). The new RDN's value is
+/// dropped on the way back only if the entry did not have it before.
+pub fn invert_rename(
+    before: &LdapEntry,
+    new_rdn: &str,
+    new_superior: Option<&str>,
+) -> Result<Change, DnError> {
+    let new_dn = dn::renamed(&before.dn, new_rdn, new_superior)?;
+    let added_value = Rdn::parse(new_rdn.trim())?.avas().iter().any(|ava| {
+        !before
+            .attributes
+            .iter()
+            .filter(|(attr, _)| attr.eq_ignore_ascii_case(ava.attr()))
+            .flat_map(|(_, values)| values)
+            .any(|v| v.eq_ignore_ascii_case(ava.value()))
+    });
+    let moved = new_superior.is_some_and(|s| !s.trim().is_empty());
+    Ok(Change::Rename {
+        dn: new_dn,
+        new_rdn: dn::rdn(&before.dn).to_string(),
+        delete_old_rdn: added_value,
+        new_superior: moved.then(|| dn::parent_dn(&before.dn).unwrap_or_default().to_string()),
+    })
+}
+
+/// One undoable write: what it was, and the changes that reverse it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UndoStep {
+    /// What was done, e.g. `Delete uid=alice,ou=People,dc=example,dc=com`.
+    pub description: String,
+    pub changes: Vec<Change>,
+}
+
+impl UndoStep {
+    pub fn new(description: impl Into<String>, changes: Vec<Change>) -> Self {
+        Self {
+            description: description.into(),
+            changes,
+        }
+    }
+
+    /// The changes an undo sends, as LDIF change records.
+    pub fn preview(&self) -> String {
+        let mut ldif = Vec::new();
+        match write_changes(&self.changes, &mut ldif) {
+            Ok(_) => String::from_utf8_lossy(&ldif).into_owned(),
+            Err(e) => e.to_string(),
+        }
+    }
+}
+
+/// Why an undo stopped part way.
+#[derive(Debug)]
+pub struct UndoError {
+    /// Changes that went through before the failure.
+    pub applied: usize,
+    pub total: usize,
+    pub dn: String,
+    pub error: CoreError,
+}
+
+impl fmt::Display for UndoError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Undo stopped at {} after {} of {} changes: {}",
+            self.dn, self.applied, self.total, self.error
+        )
+    }
+}
+
+/// Send the changes of `step` in order, stopping at the first refusal
+/// since later ones (children of a re-added entry) depend on it.
+pub async fn replay(ops: &mut dyn DirectoryOps, step: &UndoStep) -> Result<(), UndoError> {
+    for (applied, change) in step.changes.iter().enumerate() {
+        if let Err(error) = change.apply(ops).await {
+            return Err(UndoError {
+                applied,
+                total: step.changes.len(),
+                dn: change.dn().to_string(),
+                error,
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Undo steps, newest last, holding at most `depth` of them.
+#[derive(Debug, Clone)]
+pub struct UndoStack {
+    depth: usize,
+    steps: VecDeque<UndoStep>,
+}
+
+impl Default for UndoStack {
+    fn default() -> Self {
+        Self::new(DEFAULT_UNDO_DEPTH)
+    }
+}
+
+impl UndoStack {
+    /// A stack keeping `depth` steps; 0 keeps none.
+    pub fn new(depth: usize) -> Self {
+        Self {
+            depth,
+            steps: VecDeque::new(),
+        }
+    }
+
+    /// Push a step, dropping the oldest once the stack is full.
+    pub fn push(&mut self, step: UndoStep) {
+        if self.depth == 0 {
+            return;
+        }
+        while self.steps.len() >= self.depth {
+            self.steps.pop_front();
+        }
+        self.steps.push_back(step);
+    }
+
+    /// The step an undo would replay.
+    pub fn last(&self) -> Option<&UndoStep> {
+        self.steps.back()
+    }
+
+    pub fn pop(&mut self) -> Option<UndoStep> {
+        self.steps.pop_back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.steps.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::fixtures::{domain, org_unit, person, EntryBuilder};
+    use crate::testing::MockDirectory;
+
+    const BASE: &str = "dc=example,dc=com";
+    const PEOPLE: &str = "ou=People,dc=example,dc=com";
+
+    fn alice() -> LdapEntry {
+        person(PEOPLE, "Alice", "Smith")
+    }
+
+    fn directory() -> MockDirectory {
+        MockDirectory::with_entries([domain(BASE), org_unit(BASE, "People"), alice()])
+    }
+
+    /// Attribute values in a fixed order; adds hand values over as sets.
+    fn sorted(entry: Option<&LdapEntry>) -> Option<BTreeMap<String, Vec<String>>> {
+        entry.map(|e| {
+            e.attributes
+                .iter()
+                .map(|(attr, values)| {
+                    let mut values = values.clone();
+                    values.sort();
+                    (attr.clone(), values)
+                })
+                .collect()
+        })
+    }
+
+    fn set(values: &[&str]) -> HashSet<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_modify_round_trip() {
+        let before = EntryBuilder::new(format!("uid=bob,{}", PEOPLE))
+            .object_classes(&["top", "inetOrgPerson"])
+            .attr("uid", "bob")
+            .attr("cn", "Bob")
+            .attr("sn", "Jones")
+            .attrs("mail", &["bob@example.com", "b.jones@example.com"])
+            .attr("description", "old")
+            .build();
+        let mut dir = MockDirectory::with_entries([domain(BASE), before.clone()]);
+        let mods = vec![
+            Mod::Delete("mail".to_string(), set(&["bob@example.com"])),
+            Mod::Add("mail".to_string(), set(&["bob@example.org"])),
+            Mod::Replace("description".to_string(), set(&["new"])),
+            Mod::Add("telephoneNumber".to_string(), set(&["+1 555 0100"])),
+            Mod::Delete("sn".to_string(), HashSet::new()),
+            Mod::Add("sn".to_string(), set(&["Smith"])),
+        ];
+        let inverse = invert_modify(&before, &mods).unwrap();
+        dir.modify_entry(&before.dn, mods).await.unwrap();
+        assert_ne!(dir.entry(&before.dn), Some(&before));
+
+        replay(&mut dir, &UndoStep::new("Modify", vec![inverse]))
+            .await
+            .unwrap();
+        assert_eq!(sorted(dir.entry(&before.dn)), sorted(Some(&before)));
+    }
+
+    #[test]
+    fn test_invert_change() {
+        let before = alice();
+        assert_eq!(
+            invert_change(
+                &before,
+                &Change::Delete {
+                    dn: before.dn.clone()
+                }
+            ),
+            Some(invert_delete(&before))
+        );
+        assert_eq!(
+            invert_change(&before, &Change::add(&before)),
+            Some(Change::Delete {
+                dn: before.dn.clone()
+            })
+        );
+        let rename = Change::Rename {
+            dn: before.dn.clone(),
+            new_rdn: "uid=asmith".to_string(),
+            delete_old_rdn: true,
+            new_superior: None,
+        };
+        assert_eq!(
+            invert_change(&before, &rename),
+            invert_rename(&before, "uid=asmith", None).ok()
+        );
+    }
+
+    #[test]
+    fn test_invert_modify_only_undoes_changes() {
+        let before = alice();
+        // Adding a value the entry has already changes nothing
+        let cn = before.attributes["cn"][0].clone();
+        assert_eq!(
+            invert_modify(&before, &[Mod::Add("CN".to_string(), set(&[&cn]))]),
+            None
+        );
+        assert_eq!(
+            invert_modify(
+                &before,
+                &[Mod::Increment("uidNumber".to_string(), "5".to_string())]
+            ),
+            Some(Change::Modify {
+                dn: before.dn.clone(),
+                mods: vec![Mod::Increment("uidNumber".to_string(), "-5".to_string())],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delete_round_trip() {
+        let before = alice().with_binary_attributes(BTreeMap::from([(
+            "jpegPhoto".to_string(),
+            vec![vec![0xff, 0xd8, 0xff]],
+        )]));
+        let mut dir =
+            MockDirectory::with_entries([domain(BASE), org_unit(BASE, "People"), before.clone()]);
+        let inverse = invert_delete(&before);
+        dir.delete_entry(&before.dn).await.unwrap();
+        assert!(dir.entry(&before.dn).is_none());
+
+        replay(&mut dir, &UndoStep::new("Delete", vec![inverse]))
+            .await
+            .unwrap();
+        assert_eq!(sorted(dir.entry(&before.dn)), sorted(Some(&before)));
+        assert_eq!(
+            dir.entry(&before.dn).unwrap().binary_attributes,
+            before.binary_attributes
+        );
+    }
+
+    #[test]
+    fn test_invert_delete_leaves_out_server_attributes() {
+        let before = EntryBuilder::new(format!("cn=x,{}", BASE))
+            .attr("cn", "x")
+            .attr("objectClass", "device")
+            .attr("entryUUID", "5a6b")
+            .attr("createTimestamp", "20240101000000Z")
+            .build()
+            .with_binary_attributes(BTreeMap::from([
+                ("objectGUID".to_string(), vec![vec![0; 16]]),
+                ("userCertificate".to_string(), vec![vec![0x30, 0x82]]),
+            ]));
+        let Change::Add { attrs, binary, .. } = invert_delete(&before) else {
+            panic!("expected an add");
+        };
+        let names: Vec<&str> = attrs.iter().map(|(a, _)| a.as_str()).collect();
+        assert_eq!(names, vec!["cn", "objectClass"]);
+        let names: Vec<&str> = binary.iter().map(|(a, _)| a.as_str()).collect();
+        assert_eq!(names, vec!["userCertificate"]);
+    }
+
+    #[tokio::test]
+    async fn test_subtree_delete_round_trip() {
+        let people = org_unit(BASE, "People");
+        let entries = vec![people.clone(), alice(), person(PEOPLE, "Bob", "Jones")];
+        let mut dir = MockDirectory::with_entries(
+            std::iter::once(domain(BASE)).chain(entries.iter().cloned()),
+        );
+        let deleted: Vec<String> = [&entries[2], &entries[1], &entries[0]]
+            .iter()
+            .map(|e| e.dn.clone())
+            .collect();
+        for d in &deleted {
+            dir.delete_entry(d).await.unwrap();
+        }
+
+        let changes = invert_subtree_delete(&entries, &deleted);
+        assert_eq!(changes[0].dn(), PEOPLE);
+        replay(&mut dir, &UndoStep::new("Delete subtree", changes))
+            .await
+            .unwrap();
+        for entry in &entries {
+            assert_eq!(sorted(dir.entry(&entry.dn)), sorted(Some(entry)));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rename_round_trip() {
+        let before = alice();
+        let mut dir = MockDirectory::with_entries([
+            domain(BASE),
+            org_unit(BASE, "People"),
+            org_unit(BASE, "Staff"),
+            before.clone(),
+        ]);
+        let staff = "ou=Staff,dc=example,dc=com";
+        let inverse = invert_rename(&before, "uid=asmith", Some(staff)).unwrap();
+        assert_eq!(
+            inverse,
+            Change::Rename {
+                dn: format!("uid=asmith,{}", staff),
+                new_rdn: before.rdn().to_string(),
+                delete_old_rdn: true,
+                new_superior: Some(PEOPLE.to_string()),
+            }
+        );
+        dir.rename_entry(&before.dn, "uid=asmith", true, Some(staff))
+            .await
+            .unwrap();
+
+        replay(&mut dir, &UndoStep::new("Rename", vec![inverse]))
+            .await
+            .unwrap();
+        assert_eq!(dir.entry(&before.dn), Some(&before));
+        assert!(dir.entry(&format!("uid=asmith,{}", staff)).is_none());
+    }
+
+    #[test]
+    fn test_invert_rename_keeps_a_value_the_entry_had() {
+        let before = alice();
+        let cn = before.attributes["cn"][0].clone();
+        let Change::Rename {
+            delete_old_rdn,
+            new_superior,
+            ..
+        } = invert_rename(&before, &format!("cn={}", cn), None).unwrap()
+        else {
+            panic!("expected a rename");
+        };
+        assert!(!delete_old_rdn);
+        assert_eq!(new_superior, None);
+        assert!(invert_rename(&before, "", None).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_replay_stops_at_first_failure() {
+        let mut dir = directory();
+        let step = UndoStep::new(
+            "Delete subtree",
+            vec![
+                invert_delete(&org_unit(BASE, "Staff")),
+                invert_delete(&org_unit(BASE, "People")),
+                invert_delete(&person("ou=Staff,dc=example,dc=com", "Carol", "White")),
+            ],
+        );
+        let error = replay(&mut dir, &step).await.unwrap_err();
+        assert_eq!(error.applied, 1);
+        assert_eq!(error.dn, PEOPLE);
+        assert!(error
+            .to_string()
+            .starts_with(&format!("Undo stopped at {} after 1 of 3 changes", PEOPLE)));
+        assert!(dir
+            .entry("cn=Carol White,ou=Staff,dc=example,dc=com")
+            .is_none());
+    }
+
+    #[test]
+    fn test_stack_is_bounded() {
+        let mut stack = UndoStack::new(3);
+        for i in 0..5 {
+            stack.push(UndoStep::new(format!("step {}", i), Vec::new()));
+        }
+        assert_eq!(stack.len(), 3);
+        assert_eq!(stack.last().unwrap().description, "step 4");
+        assert_eq!(stack.pop().unwrap().description, "step 4");
+        assert_eq!(stack.pop().unwrap().description, "step 3");
+        assert_eq!(stack.pop().unwrap().description, "step 2");
+        assert!(stack.pop().is_none());
+
+        let mut off = UndoStack::new(0);
+        off.push(UndoStep::new("ignored", Vec::new()));
+        assert!(off.is_empty());
+        assert_eq!(UndoStack::default().depth, DEFAULT_UNDO_DEPTH);
+    }
+
+    #[test]
+    fn test_preview_is_ldif() {
+        let step = UndoStep::new(
+            "Modify",
+            vec![Change::Modify {
+                dn: alice().dn,
+                mods: vec![Mod::Replace("description".to_string(), set(&["old"]))],
+            }],
+        );
+        assert_eq!(
+            step.preview(),
+            format!(
+                "dn: {}\nchangetype: modify\nreplace: description\ndescription: old\n-\n",
+                alice().dn
+            )
+        );
+    }
+}
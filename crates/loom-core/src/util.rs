@@ -1,5 +1,7 @@
 use std::collections::BTreeMap;
 
+use crate::entry::{AttrValues, LdapEntry};
+
 /// Get all values for an attribute (case-insensitive key lookup).
 pub fn get_values(attrs: &BTreeMap<String, Vec<String>>, key: &str) -> Vec<String> {
     let key_lower = key.to_lowercase();
@@ -27,11 +29,53 @@ pub fn find_values_ci<'a>(
     attrs: &'a BTreeMap<String, Vec<String>>,
     key: &str,
 ) -> Option<&'a Vec<String>> {
+    find_ci(attrs, key)
+}
+
+/// Find binary values for an attribute (case-insensitive), returning a
+/// reference.
+pub fn find_bytes_ci<'a>(
+    binary: &'a BTreeMap<String, Vec<Vec<u8>>>,
+    key: &str,
+) -> Option<&'a Vec<Vec<u8>>> {
+    find_ci(binary, key)
+}
+
+fn find_ci<'a, V>(map: &'a BTreeMap<String, V>, key: &str) -> Option<&'a V> {
     let key_lower = key.to_lowercase();
-    for (k, v) in attrs {
+    for (k, v) in map {
         if k.to_lowercase() == key_lower {
             return Some(v);
         }
     }
     None
 }
+
+/// Get all binary values for an attribute (case-insensitive key lookup).
+pub fn get_bytes(binary: &BTreeMap<String, Vec<Vec<u8>>>, key: &str) -> Vec<Vec<u8>> {
+    find_bytes_ci(binary, key).cloned().unwrap_or_default()
+}
+
+/// Get the first binary value for an attribute (case-insensitive).
+pub fn get_first_bytes(binary: &BTreeMap<String, Vec<Vec<u8>>>, key: &str) -> Option<Vec<u8>> {
+    get_bytes(binary, key).into_iter().next()
+}
+
+/// Get all values of an attribute of either kind as text
+/// (case-insensitive), binary ones decoded lossily.
+pub fn get_values_lossy(entry: &LdapEntry, key: &str) -> Vec<String> {
+    match entry.values_ci(key) {
+        Some(AttrValues::Text(values)) => values.to_vec(),
+        Some(AttrValues::Binary(values)) => values
+            .iter()
+            .map(|v| String::from_utf8_lossy(v).into_owned())
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+/// Get the first value of an attribute of either kind as text
+/// (case-insensitive), decoded lossily if binary.
+pub fn get_first_lossy(entry: &LdapEntry, key: &str) -> Option<String> {
+    get_values_lossy(entry, key).into_iter().next()
+}
@@ -235,6 +235,49 @@ impl Vault {
     }
 }
 
+/// Encrypt `plaintext` with a key derived from `passphrase`, like the
+/// vault, for data kept outside it. The result starts with the salt and
+/// nonce [`unseal`] needs.
+pub fn seal(plaintext: &[u8], passphrase: &str) -> Result<Vec<u8>, CoreError> {
+    let mut salt = [0u8; SALT_LEN];
+    rand::thread_rng().fill_bytes(&mut salt);
+    let mut nonce_bytes = [0u8; NONCE_LEN];
+    rand::thread_rng().fill_bytes(&mut nonce_bytes);
+
+    let mut key = derive_key(passphrase, &salt)?;
+    let cipher = ChaCha20Poly1305::new_from_slice(&key)
+        .map_err(|e| CoreError::VaultError(format!("Cipher init failed: {}", e)));
+    key.zeroize();
+    let ciphertext = cipher?
+        .encrypt(Nonce::from_slice(&nonce_bytes), plaintext)
+        .map_err(|e| CoreError::VaultError(format!("Encryption failed: {}", e)))?;
+
+    let mut sealed = Vec::with_capacity(SALT_LEN + NONCE_LEN + ciphertext.len());
+    sealed.extend_from_slice(&salt);
+    sealed.extend_from_slice(&nonce_bytes);
+    sealed.extend_from_slice(&ciphertext);
+    Ok(sealed)
+}
+
+/// Decrypt data written by [`seal`].
+pub fn unseal(sealed: &[u8], passphrase: &str) -> Result<Vec<u8>, CoreError> {
+    if sealed.len() < SALT_LEN + NONCE_LEN {
+        return Err(CoreError::VaultError(
+            "Encrypted data is truncated".to_string(),
+        ));
+    }
+    let (salt, rest) = sealed.split_at(SALT_LEN);
+    let (nonce_bytes, ciphertext) = rest.split_at(NONCE_LEN);
+
+    let mut key = derive_key(passphrase, salt)?;
+    let cipher = ChaCha20Poly1305::new_from_slice(&key)
+        .map_err(|e| CoreError::VaultError(format!("Cipher init failed: {}", e)));
+    key.zeroize();
+    cipher?
+        .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+        .map_err(|_| CoreError::VaultError("Wrong passphrase or corrupted data".to_string()))
+}
+
 /// Derive a 256-bit key from a password and salt using Argon2id.
 fn derive_key(password: &str, salt: &[u8]) -> Result<Vec<u8>, CoreError> {
     let params = argon2::Params::new(ARGON2_M_COST, ARGON2_T_COST, ARGON2_P_COST, Some(KEY_LEN))
@@ -413,4 +456,15 @@ mod tests {
         assert!(result.is_err());
         assert!(result.unwrap_err().to_string().contains("too small"));
     }
+
+    #[test]
+    fn test_seal_roundtrip() {
+        let sealed = seal(b"secret profiles", "passphrase").unwrap();
+        assert!(!String::from_utf8_lossy(&sealed).contains("secret"));
+        assert_eq!(unseal(&sealed, "passphrase").unwrap(), b"secret profiles");
+
+        let err = unseal(&sealed, "wrong").unwrap_err();
+        assert!(err.to_string().contains("Wrong passphrase"), "{}", err);
+        assert!(unseal(&sealed[..10], "passphrase").is_err());
+    }
 }
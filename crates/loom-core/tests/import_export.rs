@@ -67,7 +67,7 @@ fn test_ldif_roundtrip_with_fixtures() {
 
     let star = vec!["*".to_string()];
     let mut buf = Vec::new();
-    export::ldif::write_ldif(&mut buf, &entries, &star).unwrap();
+    export::ldif::write_to(&entries, &mut buf, &star).unwrap();
     let ldif_str = String::from_utf8(buf).unwrap();
 
     let reimported = import::ldif::parse_ldif(&ldif_str).unwrap();
@@ -108,7 +108,7 @@ fn test_csv_roundtrip_with_fixtures() {
 
     let star = vec!["*".to_string()];
     let mut buf = Vec::new();
-    export::csv::write_csv(&mut buf, &entries, &star).unwrap();
+    export::csv::write_to(&entries, &mut buf, &star, &Default::default()).unwrap();
     let csv_str = String::from_utf8(buf).unwrap();
 
     let reimported = import::csv::parse_csv(&csv_str).unwrap();
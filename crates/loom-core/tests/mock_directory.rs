@@ -0,0 +1,77 @@
+use loom_core::diff::diff_entries;
+use loom_core::export;
+use loom_core::import;
+use loom_core::ops::{add_attributes, add_binary_attributes, DirectoryOps};
+use loom_core::testing::fixtures::OuTree;
+use loom_core::testing::MockDirectory;
+use loom_core::Scope;
+
+const BASE: &str = "dc=example,dc=com";
+
+/// Search one directory, export to a file, import the file into an empty
+/// directory, and check the two hold the same entries.
+async fn roundtrip(file_name: &str) {
+    let mut source = MockDirectory::with_entries(OuTree::new(BASE).ous(3).people_per_ou(5).build())
+        .with_page_size(4);
+    let mut found = Vec::new();
+    source
+        .search_pages(
+            BASE,
+            Scope::Subtree,
+            "(objectClass=*)",
+            &["*"],
+            &mut |page| found.extend(page),
+        )
+        .await
+        .unwrap();
+    assert_eq!(found.len(), 1 + 3 * (1 + 5 + 1));
+    assert!(source.pages_served() > 1);
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join(file_name);
+    let report =
+        export::export_with_report(&found, &path, &["*".to_string()], &Default::default()).unwrap();
+    assert_eq!(report.written, found.len());
+
+    let imported = match export::ExportFormat::from_path(&path).unwrap() {
+        export::ExportFormat::Ldif => import::ldif::import(&path).unwrap(),
+        export::ExportFormat::Json => import::json::import(&path).unwrap(),
+        other => panic!("no roundtrip for {:?}", other),
+    };
+    let mut target = MockDirectory::new();
+    for entry in &imported {
+        target
+            .add_entry(
+                &entry.dn,
+                add_attributes(entry),
+                add_binary_attributes(entry),
+            )
+            .await
+            .unwrap();
+    }
+
+    let copied = target
+        .search(BASE, Scope::Subtree, "(objectClass=*)", &["*"])
+        .await
+        .unwrap();
+    assert_eq!(copied.len(), found.len());
+    for (before, after) in found.iter().zip(&copied) {
+        assert_eq!(before.dn, after.dn);
+        assert!(
+            diff_entries(before, after).is_empty(),
+            "{} differs: {:?}",
+            before.dn,
+            diff_entries(before, after)
+        );
+    }
+}
+
+#[tokio::test]
+async fn test_search_export_import_ldif_roundtrip() {
+    roundtrip("directory.ldif").await;
+}
+
+#[tokio::test]
+async fn test_search_export_import_json_roundtrip() {
+    roundtrip("directory.json").await;
+}
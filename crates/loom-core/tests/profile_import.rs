@@ -0,0 +1,92 @@
+use std::path::{Path, PathBuf};
+
+use loom_core::connection::{AuthMethod, TlsMode};
+use loom_core::profile_import::ForeignFormat;
+
+fn fixture(name: &str) -> (PathBuf, String) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("../../tests/fixtures")
+        .join(name);
+    let content = std::fs::read_to_string(&path).unwrap();
+    (path, content)
+}
+
+#[test]
+fn test_import_directory_studio_fixture() {
+    let (path, content) = fixture("connections.xml");
+    let format = ForeignFormat::detect(&path, &content).unwrap();
+    assert_eq!(format, ForeignFormat::DirectoryStudio);
+    let profiles = format.parse(&content).unwrap();
+    assert_eq!(profiles.len(), 3);
+
+    let prod = &profiles[0];
+    assert_eq!(prod.name, "Production");
+    assert_eq!((prod.host.as_str(), prod.port), ("ldap.example.com", 636));
+    assert_eq!(prod.tls_mode, TlsMode::Ldaps);
+    assert_eq!(prod.base_dn.as_deref(), Some("dc=example,dc=com"));
+    assert_eq!(prod.page_size, Some(250));
+    assert_eq!(prod.timeout_secs, Some(30));
+    assert!(prod.read_only);
+    assert!(prod.warnings.is_empty());
+
+    let staging = &profiles[1];
+    assert_eq!(staging.tls_mode, TlsMode::StartTls);
+    assert_eq!(staging.base_dn, None);
+    assert_eq!(staging.bind_dn, None);
+    assert_eq!(staging.warnings.len(), 3);
+    assert!(staging.warnings[0].starts_with("DIGEST-MD5 authentication"));
+
+    let public = &profiles[2];
+    assert_eq!(public.tls_mode, TlsMode::None);
+    assert_eq!(public.bind_dn, None);
+    assert_eq!(public.timeout_secs, None);
+}
+
+#[test]
+fn test_import_ldaprc_fixture() {
+    let (path, content) = fixture("ldaprc");
+    let format = ForeignFormat::detect(&path, &content).unwrap();
+    assert_eq!(format, ForeignFormat::LdapConf);
+    let profiles = format.parse(&content).unwrap();
+    assert_eq!(profiles.len(), 1);
+
+    let p = &profiles[0];
+    assert_eq!(p.name, "ldap.example.com");
+    assert_eq!(p.tls_mode, TlsMode::Ldaps);
+    assert_eq!(
+        p.bind_dn.as_deref(),
+        Some("cn=reader,ou=Services,dc=example,dc=com")
+    );
+    assert_eq!(p.timeout_secs, Some(10));
+    assert_eq!(
+        p.tls_options.ca_cert.as_deref(),
+        Some(Path::new("/etc/ssl/certs/example-ca.pem"))
+    );
+    assert!(!p.tls_options.verify);
+    assert_eq!(p.auth, AuthMethod::Gssapi);
+    // Second URI and DEREF
+    assert_eq!(p.warnings.len(), 2);
+}
+
+#[test]
+fn test_import_url_list_fixture() {
+    let (path, content) = fixture("servers.txt");
+    let format = ForeignFormat::detect(&path, &content).unwrap();
+    assert_eq!(format, ForeignFormat::UrlList);
+    let profiles = format.parse(&content).unwrap();
+    assert_eq!(profiles.len(), 3);
+
+    assert_eq!(profiles[0].name, "Domain controller 1");
+    assert_eq!(profiles[0].tls_mode, TlsMode::Ldaps);
+    assert_eq!(profiles[0].base_dn.as_deref(), Some("dc=example,dc=com"));
+
+    assert_eq!(profiles[1].tls_mode, TlsMode::StartTls);
+    assert_eq!(
+        profiles[1].bind_dn.as_deref(),
+        Some("cn=svc,ou=Services,dc=example,dc=com")
+    );
+
+    assert_eq!(profiles[2].name, "[2001:db8::10]:10389");
+    assert_eq!(profiles[2].base_dn.as_deref(), Some("o=test"));
+    assert_eq!(profiles[2].warnings.len(), 1);
+}
@@ -1111,6 +1111,12 @@ pub fn run() -> Result<(), slint::PlatformError> {
                         .map(|s| s.trim().to_string())
                         .filter(|s| !s.is_empty())
                         .collect(),
+                    hooks: Default::default(),
+                    protected_dns: Vec::new(),
+                    auth: Default::default(),
+                    tls_options: Default::default(),
+                    protection: Default::default(),
+                    result_columns: Vec::new(),
                 };
 
                 let profile_name = profile.name.clone();
@@ -1326,7 +1332,8 @@ pub fn run() -> Result<(), slint::PlatformError> {
                 match entries {
                     Ok(entries) => {
                         let path = std::path::Path::new(&file_path);
-                        match loom_core::export::export_entries(&entries, path, &[]) {
+                        let options = loom_core::export::CsvOptions::default();
+                        match loom_core::export::export_entries(&entries, path, &[], &options) {
                             Ok(count) => {
                                 info!("Exported {} entries to {}", count, &file_path);
                                 if let Some(win) = weak.upgrade() {
@@ -1762,11 +1769,10 @@ fn spawn_connect(
         };
 
         // Bind
-        let bind_result = if let (Some(ref dn), Some(ref pw)) = (&bind_dn, &password) {
-            conn.simple_bind(dn, pw).await
-        } else {
-            conn.anonymous_bind().await
-        };
+        let dn = bind_dn.as_deref().filter(|_| password.is_some());
+        let bind_result = conn
+            .authenticate(dn, password.as_deref().unwrap_or_default())
+            .await;
 
         if let Err(e) = bind_result {
             error!("Bind failed: {}", e);
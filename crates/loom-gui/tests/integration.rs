@@ -43,6 +43,12 @@ fn test_connection_profile_construction() {
         read_only: false,
         offline: false,
         labels: vec![],
+        hooks: Default::default(),
+        protected_dns: Vec::new(),
+        auth: Default::default(),
+        tls_options: Default::default(),
+        protection: Default::default(),
+        result_columns: Vec::new(),
     };
 
     assert_eq!(profile.name, "Test Server");
@@ -69,6 +75,12 @@ fn test_connection_profile_to_settings() {
         read_only: false,
         offline: false,
         labels: vec![],
+        hooks: Default::default(),
+        protected_dns: Vec::new(),
+        auth: Default::default(),
+        tls_options: Default::default(),
+        protection: Default::default(),
+        result_columns: Vec::new(),
     };
 
     let settings = profile.to_connection_settings();
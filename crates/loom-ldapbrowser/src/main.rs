@@ -1,13 +1,16 @@
 use std::path::PathBuf;
 
 use anyhow::Result;
-use clap::Parser;
+use clap::{Parser, Subcommand};
 use tracing::{info, warn};
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter};
 
 use loom_core::vault::Vault;
 use loom_tui::app::App;
-use loom_tui::config::AppConfig;
+use loom_tui::config::{AppConfig, GeneralConfig};
+use loom_tui::logging::{self, LogBuffer, LogWriter, Logging};
 
 #[derive(Parser, Debug)]
 #[command(
@@ -43,6 +46,27 @@ struct Cli {
     /// Path to vault file (default: ~/.config/loom-ldapbrowser/vault.dat)
     #[arg(long)]
     vault: Option<String>,
+
+    /// Open a snapshot (.loomz) read-only instead of connecting
+    #[arg(long)]
+    open: Option<PathBuf>,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Check the environment and print a report for bug reports
+    Doctor {
+        /// Write the report to this file instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Include hosts, DNs and profile names (redacted by default)
+        #[arg(long)]
+        no_redact: bool,
+    },
 }
 
 #[tokio::main]
@@ -53,24 +77,60 @@ async fn main() -> Result<()> {
 
     let cli = Cli::parse();
 
-    // Initialize logging to ./logs/ directory at debug level
-    let log_dir = std::path::PathBuf::from("./logs");
-    std::fs::create_dir_all(&log_dir)?;
-    let log_file = std::fs::File::create(log_dir.join("loom-ldapbrowser.log"))?;
+    if let Some(Command::Doctor { output, no_redact }) = cli.command {
+        return run_doctor(output, !no_redact);
+    }
+
+    // Read the snapshot before taking over the terminal so errors stay visible
+    let snapshot = match cli.open {
+        Some(path) => match loom_core::snapshot::open(&path) {
+            Ok(snapshot) => Some(snapshot),
+            Err(e) => {
+                eprintln!("Cannot open {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
 
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::from_default_env().add_directive("loom_ldapbrowser=debug".parse()?),
+    // Log to the buffer the log viewer reads from the start, and to the
+    // log file once the config says where and at which level
+    let log_buffer = LogBuffer::new(GeneralConfig::default().log_viewer_lines);
+    let log_writer = LogWriter::new(log_buffer.clone());
+    let rust_log = std::env::var_os("RUST_LOG").is_some();
+    let (filter, filter_handle) = reload::Layer::new(
+        EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("warn,loom=info")),
+    );
+    tracing_subscriber::registry()
+        .with(filter)
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(log_writer.clone())
+                .with_ansi(false),
         )
-        .with_writer(log_file)
-        .with_ansi(false)
         .init();
 
+    // Load config; a legacy config is rewritten only once the user agrees
+    let (mut config, migration) = AppConfig::load_migrated();
+
+    // RUST_LOG, when set, keeps the filter whatever the config says
+    let filter = (!rust_log).then(|| -> logging::FilterReload {
+        Box::new(move |directives| {
+            filter_handle
+                .reload(EnvFilter::new(directives))
+                .map_err(|e| e.to_string())
+        })
+    });
+    let logging = Logging {
+        buffer: log_buffer,
+        writer: log_writer,
+        filter,
+    };
+    if let Err(e) = logging.apply(&config.general) {
+        warn!("{}", e);
+    }
     info!("loom-ldapbrowser starting");
 
-    // Load config
-    let mut config = AppConfig::load();
-
     // Apply CLI overrides
     if let Some(host) = cli.host {
         // Create/override first connection from CLI args
@@ -90,6 +150,12 @@ async fn main() -> Result<()> {
             read_only: false,
             offline: false,
             labels: vec![],
+            hooks: Default::default(),
+            protected_dns: Vec::new(),
+            auth: Default::default(),
+            tls_options: Default::default(),
+            protection: Default::default(),
+            result_columns: Vec::new(),
         };
         config.connections.insert(0, profile);
     }
@@ -99,13 +165,38 @@ async fn main() -> Result<()> {
 
     // Create and run the app
     let mut app = App::new(config, vault);
-    app.connect_first_profile().await;
+    app.set_logging(logging);
+    app.set_config_migration(migration);
+    app.watch_config();
+    match snapshot {
+        Some(snapshot) => app.open_snapshot(snapshot),
+        None => app.connect_first_profile().await,
+    }
     app.run().await?;
 
     info!("loom-ldapbrowser exiting");
     Ok(())
 }
 
+/// Run the environment checks and print or save the report.
+/// Exits with status 1 if any check failed.
+fn run_doctor(output: Option<PathBuf>, redact: bool) -> Result<()> {
+    let config = AppConfig::load();
+    let report = loom_tui::doctor::run_checks(&config);
+    let text = report.to_text(redact);
+    match output {
+        Some(path) => {
+            std::fs::write(&path, &text)?;
+            println!("Wrote report to {}", path.display());
+        }
+        None => print!("{}", text),
+    }
+    if report.worst() == loom_core::doctor::CheckStatus::Fail {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
 /// Attempt to open the vault if vault_enabled is set in config.
 /// Returns None if vault is not enabled, doesn't exist yet, or can't be opened.
 fn load_vault(
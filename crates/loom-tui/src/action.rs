@@ -1,12 +1,39 @@
+use std::path::PathBuf;
+
+use loom_core::account::AccountAction;
+use loom_core::activity::OpId;
+use loom_core::bookmark::{Bookmark, BookmarkTarget};
+use loom_core::bulk::{BatchChange, BulkMod};
+use loom_core::changefile::{ApplyOptions, ApplyReport};
+use loom_core::delete::{EntriesDeletion, SubtreeDeletion};
+use loom_core::diff::{AttributeChange, EntryDiff};
+use loom_core::duplicate::SubtreeCopy;
 use loom_core::entry::LdapEntry;
+use loom_core::export::subtree::SubtreeScope;
+use loom_core::export::timestamps::ExportTemplate;
+use loom_core::export::{CsvOptions, ExportFormat, ExportReport};
+use loom_core::freshness::{ChangeMarker, DeleteCheck, TrackedEntry};
+use loom_core::health::ConnectionHealth;
+use loom_core::hooks::HookOutcome;
+use loom_core::jump::Jump;
+use loom_core::membership::Membership;
+use loom_core::password::PasswordMethod;
 use loom_core::schema::SchemaCache;
 use loom_core::server_detect::ServerType;
+use loom_core::snapshot::{Manifest, SnapshotProgress};
+use loom_core::subtree_diff::{SubtreeCompareOptions, SubtreeCompareProgress, SubtreeDiff};
 use loom_core::tls::CertificateInfo;
-use loom_core::tree::TreeNode;
+use loom_core::transaction::Change;
+use loom_core::tree::{ChildPaging, PageDirection, TreeNode};
+use loom_core::undo::UndoStep;
+use loom_core::Scope;
 
 use crate::components::attribute_editor::EditResult;
 use crate::components::bulk_update_dialog::BulkOp;
-use crate::config::ConnectionProfile;
+use crate::components::export_dialog::{ExportRequest, ExportSource};
+use crate::components::quick_switcher::JumpTarget;
+use crate::config::{ConnectionProfile, ProfileImport};
+use crate::connecting::{ConnectFailure, ConnectedServer};
 
 /// Unique identifier for a connection tab.
 pub type ConnectionId = usize;
@@ -16,6 +43,7 @@ pub type ConnectionId = usize;
 pub enum ActiveLayout {
     Browser,
     Profiles,
+    Schema,
 }
 
 /// Where the context menu was invoked from, carrying the relevant state.
@@ -60,29 +88,87 @@ pub enum Action {
     ConnectAdHoc(ConnectionProfile, String), // profile + password (never saved)
     PromptCredentials(ConnectionProfile),    // show credential prompt for profile
     ConnectWithCredentials(ConnectionProfile, String), // retry with user-provided credentials
+    /// A background connection attempt finished (attempt id, outcome).
+    ConnectFinished(u64, Result<Box<ConnectedServer>, ConnectFailure>),
+    /// Abandon every connection attempt still running.
+    CancelConnect,
     Connected(ConnectionId, String, ServerType),
     Disconnected(ConnectionId),
     ConnectionError(String),
     SaveCurrentConnection,
 
     // Tree Navigation
+    /// Open a container, listing its children unless they are cached.
     TreeExpand(String),
     TreeCollapse(String),
     TreeSelect(String),
-    TreeChildrenLoaded(ConnectionId, String, Vec<TreeNode>),
+    /// List a container's children one level down, keeping its current
+    /// page; also how `R` refreshes children gone stale.
+    LoadChildren(String),
+    /// One page of a container's children, with the container's page state.
+    TreePageLoaded(ConnectionId, String, Vec<TreeNode>, Box<ChildPaging>),
+    /// Listing a container's children failed: connection, DN, error.
+    TreeLoadFailed(ConnectionId, String, String),
+    /// Move a paged container to its next or previous page.
+    TreePage(String, PageDirection),
+    /// Open the `:children` filter/sort prompt for a container.
+    TreeChildQueryPrompt(String),
+    /// Move to the next sibling whose name starts with the typed letters.
+    TreeJump(Jump),
     TreeUp,
     TreeDown,
     TreeToggle,
 
     // Entry Detail
-    EntryLoaded(ConnectionId, LdapEntry),
+    EntryLoaded(ConnectionId, LdapEntry, ChangeMarker),
     EntryRefresh,
+    /// Go to the entry a DN-valued attribute names, remembering the way back.
+    FollowDn(String),
+    /// Return to the entry shown before the last link was followed.
+    NavigateBack,
+    /// The entry reached by a link or going back has loaded; select `usize`
+    /// in the detail pane.
+    LinkArrived(ConnectionId, String, usize),
+    /// A followed link names an entry that does not exist.
+    LinkBroken(String),
+    /// List the groups an entry belongs to.
+    ShowGroups(String),
+    /// Look the groups up again, nested ones up to a depth or only direct.
+    ResolveGroups {
+        dn: String,
+        nested: bool,
+        max_depth: usize,
+    },
+    GroupsResolved(ConnectionId, Box<Membership>),
+    GroupsFailed(String),
+    /// Export the groups the membership view lists.
+    ExportGroups,
 
     // Search
     SearchExecute(String),
-    SearchResults(ConnectionId, Vec<LdapEntry>),
+    CommandFocusInput,
+    CommandExecute(String), // ':' command, without the colon
+    SearchResults(ConnectionId, u64, Vec<LdapEntry>), // one batch of search `u64`
+    SearchComplete(ConnectionId, u64),
+    SearchFailed(ConnectionId, u64, String),
+    /// Stop the running search once the page in flight arrives.
+    SearchStop,
+    /// Sort the search results by an attribute, descending if true.
+    SearchSort(String, bool),
+    /// Open the column picker with the results table's columns.
+    ShowResultColumns(Vec<String>),
+    /// Show these columns in the results table and keep them in the profile.
+    SaveResultColumns(Vec<String>),
     SearchClear,
     SearchFocusInput,
+    ShowSearchForm,
+    /// Search submitted from the search form.
+    Search {
+        base: String,
+        scope: Scope,
+        filter: String,
+        attrs: Vec<String>,
+    },
 
     // Live Search (debounced preview while typing)
     LiveSearchRequest {
@@ -118,13 +204,117 @@ pub enum Action {
 
     // Export / Import
     ShowExportDialog,
+    /// Open the export dialog to walk the branch at this DN.
+    ShowSubtreeExport(String),
     ExportExecute {
         base_dn: String,
         path: String,
         filter: String,
         attributes: Vec<String>,
+        /// Timestamp formatting; `None` leaves values raw.
+        timestamps: Option<ExportTemplate>,
+        /// Write GUIDs, SIDs, FILETIMEs and account flags readably.
+        decode: bool,
+        /// Row layout of CSV and Excel files.
+        csv: CsvOptions,
+        /// Overrides the format the path's extension implies.
+        format: Option<ExportFormat>,
+        source: ExportSource,
+        /// How much of the branch a subtree export takes.
+        scope: SubtreeScope,
+        /// Whether a subtree export starts with the base entry itself.
+        include_base: bool,
+    },
+    /// Entries written so far by the running export, of `total`.
+    ExportProgress {
+        done: usize,
+        total: usize,
+    },
+    /// Stop the running export and remove its partial file.
+    ExportCancel,
+    /// Save the export dialog's attribute selection as a named preset;
+    /// `replace` overwrites one of the same name.
+    SaveExportPreset {
+        name: String,
+        attributes: Vec<String>,
+        format: Option<ExportFormat>,
+        replace: bool,
+    },
+    /// A written export; `message` goes to the status bar and log.
+    ExportComplete {
+        report: ExportReport,
+        message: String,
+    },
+    /// An export that failed, with the choices needed to retry it.
+    ExportFailed {
+        error: String,
+        request: ExportRequest,
+    },
+    /// Reopen the export dialog with an earlier export's choices.
+    RetryExport(ExportRequest),
+    /// Open a file with the platform's default application.
+    OpenPath(String),
+    /// Show a file in the platform's file manager.
+    RevealPath(String),
+    ExportWritten {
+        conn_id: ConnectionId,
+        path: String,
+    },
+
+    // Snapshot
+    ShowSnapshotDialog,
+    SnapshotExecute {
+        base_dn: String,
+        path: String,
     },
-    ExportComplete(String), // success message
+    SnapshotProgress(SnapshotProgress),
+    /// Abort the running snapshot; its partial file is removed.
+    SnapshotCancel,
+    SnapshotComplete {
+        path: String,
+        manifest: Manifest,
+    },
+    SnapshotFailed(String),
+
+    // Subtree compare
+    ShowSubtreeCompareDialog,
+    /// Compare `base_dn` on the active connection with `other`.
+    SubtreeCompareExecute {
+        base_dn: String,
+        other: ConnectionId,
+        options: SubtreeCompareOptions,
+    },
+    SubtreeCompareProgress(SubtreeCompareProgress),
+    SubtreeCompareCancel,
+    SubtreeCompareComplete(Box<SubtreeDiff>),
+    SubtreeCompareFailed(String),
+    /// Open the compare view on two copies of an entry.
+    ShowEntryDiff(Box<EntryDiff>),
+
+    // Offline edits
+    /// Record writes on the active connection instead of sending them.
+    ToggleOfflineEdits,
+    /// Add changes to a connection's pending changes.
+    QueueChanges {
+        conn_id: ConnectionId,
+        changes: Vec<Change>,
+    },
+    ShowPendingChanges,
+    /// The pending changes as reordered or trimmed in the panel.
+    PendingChangesEdited(Vec<Change>),
+
+    // Apply LDIF
+    /// Open the apply dialog, with the file filled in if given.
+    ShowApplyLdifDialog(Option<String>),
+    /// Apply the records of a changefile to the active connection.
+    ApplyLdifExecute {
+        changes: Vec<Change>,
+        options: ApplyOptions,
+    },
+    /// Records done and the total.
+    ApplyLdifProgress(usize, usize),
+    ApplyLdifCancel,
+    ApplyLdifComplete(Box<ApplyReport>),
 
     // Bulk Update
     ShowBulkUpdateDialog,
@@ -133,8 +323,43 @@ pub enum Action {
         attribute: String,
         value: String,
         op: BulkOp,
+        /// `value` is a script computing the values for each entry.
+        script: bool,
+    },
+    /// Report of a bulk update, with the (dn, error) of each failure.
+    BulkUpdateComplete {
+        message: String,
+        errors: Vec<(String, String)>,
+    },
+
+    // Batch modify
+    /// Open the batch modify dialog for these entries.
+    ShowBatchModifyDialog(Vec<String>),
+    /// Apply `change` to each entry, or write it to `changefile` as LDIF
+    /// instead when one is given.
+    BatchModifyExecute {
+        dns: Vec<String>,
+        change: BatchChange,
+        changefile: Option<PathBuf>,
+    },
+    /// How a batch modify went, with the (dn, error) of each failure.
+    BatchModifyComplete {
+        succeeded: usize,
+        errors: Vec<(String, String)>,
+    },
+
+    // Attribute migration
+    /// Apply a reviewed migration: modifications per DN, and how many
+    /// entries the plan skipped.
+    MigrateAttributeExecute {
+        changes: Vec<(String, Vec<BulkMod>)>,
+        skipped: usize,
+    },
+    /// Report of an applied migration, with the (dn, error) of each failure.
+    MigrateAttributeComplete {
+        message: String,
+        errors: Vec<(String, String)>,
     },
-    BulkUpdateComplete(String), // result message
 
     // Create / Delete Entry
     ShowCreateEntryDialog(String), // parent DN
@@ -144,18 +369,168 @@ pub enum Action {
     },
     EntryCreated(String), // new entry DN
     DeleteEntry(String),  // DN to delete
+    /// The user typed the confirmation for a write to a protected entry;
+    /// `action` is the write to run again.
+    ProtectedWriteConfirmed {
+        dn: String,
+        action: Box<Action>,
+    },
     EntryDeleted(String), // DN that was deleted
+    /// A delete was stopped because the entry changed since it was loaded:
+    /// DN, the fresh copy, and its differences from the loaded one.
+    DeleteBlocked(String, Box<TrackedEntry>, Vec<AttributeChange>),
+    /// Ask before deleting an entry, offering its subtree if it has children.
+    ShowDeleteDialog(String),
+    /// Delete an entry and everything below it, children first.
+    DeleteSubtree(String),
+    SubtreeDeleteProgress {
+        done: usize,
+        total: usize,
+    },
+    SubtreeDeleteCancel,
+    SubtreeDeleted {
+        base: String,
+        result: SubtreeDeletion,
+    },
+    SubtreeDeleteFailed(String),
+    /// A subtree delete was stopped because its root, or the number of
+    /// entries directly below it, changed since it was confirmed: DN, the
+    /// fresh copy of the root, and what changed.
+    SubtreeDeleteBlocked(String, Box<TrackedEntry>, DeleteCheck),
+    /// Ask before deleting the selected search results.
+    ConfirmDeleteEntries(Vec<String>),
+    /// Delete each of these entries, children before parents.
+    DeleteEntries(Vec<String>),
+    EntriesDeleted(EntriesDeletion),
+    /// Open the rename / move dialog for an entry.
+    ShowRenameDialog(String),
+    /// ModifyDN: give `dn` a new RDN and, with `new_superior`, a new parent.
+    RenameEntry {
+        dn: String,
+        new_rdn: String,
+        delete_old_rdn: bool,
+        new_superior: Option<String>,
+    },
+    EntryRenamed {
+        old_dn: String,
+        new_dn: String,
+    },
+    /// Ask for a new password for an entry.
+    ShowSetPassword(String),
+    /// Set the password of `dn`, optionally making its user change it at
+    /// their next login.
+    SetPassword {
+        dn: String,
+        password: String,
+        must_change: bool,
+    },
+    /// Ask before enabling, disabling or unlocking an account, showing the
+    /// attributes that will change.
+    ShowAccountAction(String, AccountAction),
+    /// Send the modify that carries out an account action.
+    ChangeAccount {
+        action: AccountAction,
+        change: Change,
+    },
+    AccountChanged {
+        dn: String,
+        action: AccountAction,
+    },
+    /// A password went through, sent by `method`.
+    PasswordSet {
+        dn: String,
+        method: PasswordMethod,
+        must_change: bool,
+    },
+    /// A write went through: keep the step that takes it back.
+    PushUndo(ConnectionId, UndoStep),
+    /// Ask before undoing the active tab's last change, showing what
+    /// will be sent.
+    ShowUndo,
+    /// Undo the active tab's last change.
+    UndoExecute,
+    /// An undo went through; the step it replayed.
+    UndoComplete(ConnectionId, UndoStep),
+    /// Open the create-entry wizard prefilled from `source`, for a copy
+    /// of it under `parent`, or beside it when `None`.
+    ShowCopyEntryDialog {
+        source: String,
+        parent: Option<String>,
+    },
+    /// Open the copy-subtree dialog for the branch at `source`, targeting
+    /// `parent`, or the branch's own parent when `None`.
+    ShowCopySubtreeDialog {
+        source: String,
+        parent: Option<String>,
+    },
+    /// Remember an entry to paste copies of.
+    YankEntry(String),
+    /// Mark an entry for comparison, or compare it with the marked one.
+    CompareEntry(String),
+    /// Paste a copy of the yanked entry under this parent.
+    PasteEntry(String),
+    /// Copy the yanked entry's subtree under this parent.
+    PasteSubtree(String),
+    /// Copy the branch at `source` to `target`, or with a `changefile`
+    /// only write the adds it would make there.
+    CopySubtree {
+        source: String,
+        target: String,
+        changefile: Option<PathBuf>,
+    },
+    /// The branch at `source` as read, to copy to `target`.
+    SubtreeRead {
+        source: String,
+        target: String,
+        entries: Vec<LdapEntry>,
+        changefile: Option<PathBuf>,
+    },
+    SubtreeCopyProgress {
+        done: usize,
+        total: usize,
+    },
+    SubtreeCopyCancel,
+    SubtreeCopied {
+        target: String,
+        result: SubtreeCopy,
+    },
+    SubtreeCopyFailed(String),
 
     // Schema
     ShowSchemaViewer,
+    /// Open the Schema layout at an attribute type, or object class, by name.
+    ShowSchemaDefinition(String),
 
     // Help / About
     ShowHelp,
     ShowAbout,
+    ShowErrorDetails,
+    /// Show one attribute value full screen; `binary` values start in hex.
+    ShowValueViewer {
+        attr: String,
+        value: Vec<u8>,
+        binary: bool,
+    },
+    ShowDoctor,
+    /// Root DSE of a connection, the active tab's when `None`.
+    ShowServerInfo(Option<ConnectionId>),
     SchemaLoaded(ConnectionId, Box<SchemaCache>),
 
     // Log Panel
     ToggleLogPanel,
+    /// Open the log viewer, tailing the application log.
+    ShowLogViewer,
+    /// Open the log of messages posted to the status bar.
+    ShowMessages,
+
+    // Theme
+    /// Read the configured theme again, e.g. after editing its file.
+    ReloadTheme,
+
+    // Profile hooks
+    /// Trust the hooks of the profile whose hook is waiting for approval.
+    ApproveHooks,
+    HookFinished(Box<HookOutcome>),
 
     // Popup / Modal
     ShowConfirm(String, Box<Action>),
@@ -169,20 +544,70 @@ pub enum Action {
 
     // Layout switching
     SwitchLayout(ActiveLayout),
+    ResizePanes(i16), // move the tree divider by this many percent
+    ResetLayout,
+
+    // Quick switcher
+    ShowQuickSwitcher,
+    /// List the active connection's outstanding operations.
+    ShowActivity,
+    /// Cancel one operation of the active connection.
+    CancelOperation(OpId),
+    CancelAllOperations,
+    QuickSwitch(JumpTarget),
+
+    // Bookmarks
+    ShowBookmarks,
+    /// Ask for a label, then bookmark the target under the active tab's profile.
+    PromptBookmark(BookmarkTarget),
+    AddBookmark(Bookmark),
+    /// Remove a bookmark: (profile, name).
+    RemoveBookmark(String, String),
+    OpenBookmark(Bookmark),
+    /// Select an entry in a tab's tree, listing its ancestors' children
+    /// first where needed.
+    RevealInTree(ConnectionId, String),
 
     // Profiles Manager
-    ConnMgrSelect(usize),                       // select saved profile by index
-    ConnMgrNew,                                 // start creating new profile
-    ConnMgrSave(usize, Box<ConnectionProfile>), // save edited profile at index
-    ConnMgrCreate(Box<ConnectionProfile>),      // create new profile
-    ConnMgrDelete(usize),                       // delete saved profile by index
-    ConnMgrDuplicate(usize),                    // duplicate saved profile by index
-    ConnMgrConnect(usize),                      // connect from connections manager
-    ConnMgrExport,                              // open export profiles dialog
-    ConnMgrImport,                              // open import profiles dialog
-    ConnMgrImportExecute(Vec<ConnectionProfile>), // commit selected imported profiles
-    ConnMgrSelectFolder(String),                // folder path selected in tree
-    ConnMgrSaveFolderDesc(String, String),      // (folder path, new description)
+    ConnMgrSelect(usize),                        // select saved profile by index
+    ConnMgrNew,                                  // start creating new profile
+    ConnMgrSave(usize, Box<ConnectionProfile>),  // save edited profile at index
+    ConnMgrCreate(Box<ConnectionProfile>),       // create new profile
+    ConnMgrDelete(usize),                        // delete saved profile by index
+    ConnMgrDuplicate(usize),                     // duplicate saved profile by index
+    ConnMgrConnect(usize),                       // connect from connections manager
+    ConnMgrTest(Box<ConnectionProfile>, String), // bind with form values, saving nothing
+    ConnMgrTestFinished(Result<String, String>), // outcome of ConnMgrTest
+    ConnectionHealthChanged(ConnectionId, ConnectionHealth), // from the health monitor
+    Reconnect(Option<ConnectionId>),             // reconnect a tab now; None for the active tab
+    /// The profile's name was typed: let writes through on this tab of a
+    /// read-only profile until it reconnects.
+    AllowWrites(ConnectionId),
+    ConnMgrExport, // open export profiles dialog
+    ConnMgrExportExecute {
+        profiles: Vec<ConnectionProfile>,
+        path: PathBuf,
+        /// Write the profiles' stored passwords too.
+        include_passwords: bool,
+        /// Encrypt the file with this passphrase.
+        passphrase: Option<String>,
+    },
+    ConnMgrImport,                            // open import profiles dialog
+    ConnMgrImportExecute(Vec<ProfileImport>), // commit selected imported profiles
+    ConnMgrAdoptMigration,                    // rewrite a migrated legacy config
+    ConnMgrSelectFolder(String),              // folder path selected in tree
+    ConnMgrSaveFolderDesc(String, String),    // (folder path, new description)
+
+    // Setup wizard
+    ShowSetup,
+    /// Save the wizard's profile and connect to it.
+    SetupTestConnection(Box<ConnectionProfile>),
+    /// Save the wizard's profile without connecting.
+    SetupSaveProfile(Box<ConnectionProfile>),
+    /// Import profiles from another tool's configuration file.
+    SetupImport(PathBuf),
+    /// The wizard ended; holds the theme picked, if any.
+    SetupFinished(Option<String>),
 
     // Certificate Trust
     ShowCertTrustDialog {
@@ -200,7 +625,11 @@ pub enum Action {
 
     // Context Menu
     ShowContextMenu(ContextMenuSource),
+    /// Open the "Copy As" submenu for the DN/value of a context menu source.
+    ShowCopyAsMenu(ContextMenuSource),
     CopyToClipboard(String),
+    /// Copy a loaded entry to the clipboard as LDIF.
+    CopyEntryLdif(String),
 
     // Vault
     VaultStorePassword(String, String), // profile name + password
@@ -211,6 +640,32 @@ pub enum Action {
     None,
 }
 
+impl Action {
+    /// Whether this action sends a write to the directory. Writing LDIF to
+    /// a changefile, or a dry run, is not one.
+    pub fn is_write(&self) -> bool {
+        match self {
+            Action::SaveAttribute(_)
+            | Action::DeleteAttributeValue(..)
+            | Action::AddMultipleValues { .. }
+            | Action::BulkUpdateExecute { .. }
+            | Action::MigrateAttributeExecute { .. }
+            | Action::CreateEntry { .. }
+            | Action::DeleteEntry(_)
+            | Action::DeleteSubtree(_)
+            | Action::DeleteEntries(_)
+            | Action::RenameEntry { .. }
+            | Action::SetPassword { .. }
+            | Action::ChangeAccount { .. }
+            | Action::UndoExecute => true,
+            Action::ApplyLdifExecute { options, .. } => !options.dry_run,
+            Action::BatchModifyExecute { changefile, .. }
+            | Action::CopySubtree { changefile, .. } => changefile.is_none(),
+            _ => false,
+        }
+    }
+}
+
 /// Which panel is focused.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum FocusTarget {
@@ -219,4 +674,6 @@ pub enum FocusTarget {
     CommandPanel,
     ConnectionsTree,
     ConnectionForm,
+    SchemaList,
+    SchemaDefinition,
 }
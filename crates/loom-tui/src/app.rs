@@ -1,59 +1,155 @@
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crossterm::event::{KeyCode, KeyModifiers, MouseEventKind};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEventKind};
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use tokio::sync::Mutex;
-use tracing::{debug, error, info};
-
-use loom_core::bulk::BulkMod;
-use loom_core::connection::LdapConnection;
+use tracing::{debug, error, info, warn};
+
+use loom_core::account::{account_change, AccountAction};
+use loom_core::activity::{ActivityRegistry, OpId};
+use loom_core::audit::{AuditFile, Auditor, Rotation};
+use loom_core::bookmark::{Bookmark, BookmarkTarget};
+use loom_core::bulk::{BatchChange, BulkMod};
+use loom_core::changefile::{apply_changefile, ApplyOptions};
+use loom_core::connection::{LdapConnection, RequestSlot};
+use loom_core::copy_template::{effective_templates, CopyContext};
 use loom_core::credentials::{CredentialMethod, CredentialProvider};
+use loom_core::decode::decode_entry;
+use loom_core::delete::{delete_entries, delete_subtree, deletion_order, recheck_subtree_root};
+use loom_core::diff::AttributeChange;
+use loom_core::duplicate::{copy_entries, copyable_attributes, plan_subtree_copy, read_subtree};
+use loom_core::entry::LdapEntry;
 use loom_core::error::CoreError;
+use loom_core::export::subtree::{page_channel, search_subtree_pages};
+use loom_core::export::timestamps::{format_timestamps, FormatReport, TimestampFormat};
+use loom_core::export::{export_stream, export_table, ExportFormat, ExportReport};
+use loom_core::freshness::{check_before_delete, DeleteCheck, TrackedEntry};
+use loom_core::health::{ConnectionHealth, HealthHandle, Reconnector};
+use loom_core::hooks::{HookEvent, HookInvocation};
+use loom_core::jump::{self, Jump};
+use loom_core::membership::{resolve_membership, Membership, MembershipOptions};
+use loom_core::merge::{normalize_dn, MergeOptions};
 use loom_core::offline::OfflineDirectory;
+use loom_core::opener::{open_command, reveal_command, Platform};
+use loom_core::password::{must_change_mods, PasswordMethod};
+use loom_core::profile_import::find_sources;
+use loom_core::protect::{ProtectedDns, ProtectionMode};
+use loom_core::resolve::{Resolver, SystemResolver};
 use loom_core::schema::{AttributeSyntax, SchemaCache};
+#[cfg(feature = "scripting")]
+use loom_core::script::{plan_scripted_update, EntryScript, ScriptedOp};
+use loom_core::search::PageOptions;
+use loom_core::server_detect::{RootDse, ServerType};
+use loom_core::snapshot::{self, Snapshot};
+use loom_core::subtree_diff::{compare_subtrees, SubtreeCompareOptions};
 use loom_core::tls::{TrustStore, TrustedCertEntry};
-use loom_core::tree::{DirectoryTree, TreeNode};
+use loom_core::transaction::{apply_batch, Change, ChangeOutcome};
+use loom_core::tree::{
+    ChildPaging, ChildQuery, DirectoryTree, LoadState, PageDirection, Reach, TreeNode,
+};
+use loom_core::undo::{
+    self, invert_change, invert_delete, invert_rename, invert_subtree_delete, UndoStack, UndoStep,
+    SUBTREE_UNDO_LIMIT,
+};
 use loom_core::vault::Vault;
+use loom_core::Scope;
 
 use crate::action::{Action, ActiveLayout, ConnectionId, ContextMenuSource, FocusTarget};
+use crate::clipboard::{self, Clipboard, Copied};
+use crate::command_line::{self, ColonCommand};
 use crate::component::Component;
 use crate::components::about_popup::AboutPopup;
+use crate::components::activity_popup::ActivityPopup;
+use crate::components::apply_ldif_dialog::ApplyLdifDialog;
+use crate::components::apply_results_view::ApplyResultsView;
 use crate::components::attribute_editor::{AttributeEditor, EditOp, EditResult};
 use crate::components::attribute_picker::AttributePicker;
-use crate::components::bulk_update_dialog::BulkUpdateDialog;
+use crate::components::batch_modify_dialog::BatchModifyDialog;
+use crate::components::batch_summary_popup::BatchSummaryPopup;
+use crate::components::bookmarks_popup::BookmarksPopup;
+use crate::components::bulk_update_dialog::{BulkOp, BulkUpdateDialog};
 use crate::components::cert_trust_dialog::CertTrustDialog;
+use crate::components::columns_dialog::ColumnsDialog;
 use crate::components::command_panel::CommandPanel;
+use crate::components::compare_view::CompareView;
 use crate::components::confirm_dialog::ConfirmDialog;
 use crate::components::connect_dialog::ConnectDialog;
 use crate::components::connection_form::ConnectionForm;
 use crate::components::connections_tree::{ActiveConnInfo, ConnectionsTree};
 use crate::components::context_menu::ContextMenu;
+use crate::components::copy_subtree_dialog::CopySubtreeDialog;
 use crate::components::create_entry_dialog::CreateEntryDialog;
 use crate::components::credential_prompt::CredentialPromptDialog;
+use crate::components::delete_dialog::{Children, DeleteDialog};
 use crate::components::detail_panel::DetailPanel;
-use crate::components::export_dialog::ExportDialog;
+use crate::components::doctor_popup::DoctorPopup;
+use crate::components::error_details_popup::ErrorDetailsPopup;
+use crate::components::export_dialog::{ExportDialog, ExportRequest, ExportSource};
+use crate::components::export_summary_popup::ExportSummaryPopup;
 use crate::components::help_popup::HelpPopup;
-use crate::components::layout_bar::LayoutBar;
+use crate::components::layout_bar::{BarTarget, LayoutBar, TabState};
 use crate::components::log_panel::LogPanel;
+use crate::components::log_viewer::LogViewer;
+use crate::components::membership_popup::MembershipPopup;
+use crate::components::messages_popup::MessagesPopup;
+use crate::components::migrate_dialog::MigrateAttributeDialog;
 use crate::components::new_connection_dialog::NewConnectionDialog;
+use crate::components::password_dialog::PasswordDialog;
+use crate::components::pending_changes_popup::PendingChangesPopup;
 use crate::components::profile_export_dialog::ProfileExportDialog;
 use crate::components::profile_import_dialog::ProfileImportDialog;
+use crate::components::protected_write_dialog::ProtectedWriteDialog;
+use crate::components::quick_switcher::{
+    JumpTarget, JumpTargetProvider, QuickSwitcher, RecentEntries,
+};
+use crate::components::rename_dialog::RenameDialog;
 use crate::components::schema_viewer::SchemaViewer;
-use crate::components::search_dialog::SearchDialog;
-use crate::components::status_bar::StatusBar;
+use crate::components::search_dialog::{SearchDialog, SearchStatus};
+use crate::components::search_form_dialog::SearchFormDialog;
+use crate::components::server_info_popup::ServerInfoPopup;
+use crate::components::setup_wizard::SetupWizard;
+use crate::components::snapshot_dialog::SnapshotDialog;
+use crate::components::status_bar::{Severity, StatusBar};
+use crate::components::subtree_compare_dialog::SubtreeCompareDialog;
+use crate::components::subtree_diff_view::SubtreeDiffView;
 use crate::components::tab_bar::TabBar;
 use crate::components::tree_panel::TreePanel;
+use crate::components::value_viewer::ValueViewer;
 use crate::components::vault_password_dialog::VaultPasswordDialog;
-use crate::config::{AppConfig, ConnectionProfile};
+use crate::components::write_override_dialog::WriteOverrideDialog;
+use crate::config::{
+    AppConfig, ConnectionProfile, GeneralConfig, LayoutConfig, MigrationReport, PaneRatios,
+    BACKUP_FILE, DEFAULT_RESULT_COLUMNS,
+};
+use crate::connecting::{
+    open_connection, recover, spawn_health_monitor, ConnectAttempts, ConnectFailure, ConnectOrigin,
+    ConnectedServer,
+};
 use crate::event::{self, AppEvent};
 use crate::focus::FocusManager;
-use crate::keymap::Keymap;
-use crate::theme::Theme;
+use crate::ingest::ResultIngest;
+use crate::keymap::{KeyContext, Keymap};
+use crate::logging::{self, LogBuffer, Logging};
+use crate::panes::{self, ScreenLayout};
+use crate::paths::{expand_path, write_replacing};
+use crate::settings::{self, Changes, ConfigWatch, Overrides, Takes};
+use crate::theme::{Styles, Theme};
 use crate::tui;
+use crate::widgets::width::truncate_to_width;
+
+/// Poll timeout while search results are queued, so one batch is moved
+/// into the results table per frame.
+const INGEST_FRAME: Duration = Duration::from_millis(16);
+
+/// Entries remembered for going back along followed links.
+const NAV_HISTORY_LIMIT: usize = 50;
 
 /// Which divider the user is dragging.
 #[derive(Debug, Clone, Copy)]
@@ -62,6 +158,59 @@ enum DragTarget {
     Tree,
 }
 
+/// What a search looks for, kept so the results can be fetched again in
+/// another order.
+#[derive(Clone)]
+struct SearchRequest {
+    base_dn: String,
+    scope: Scope,
+    filter: String,
+    attrs: Vec<String>,
+    /// Attribute the server sorts by, and whether descending.
+    sort: Option<(String, bool)>,
+}
+
+/// A jump waiting for another page of a paged container to load.
+struct PendingJump {
+    conn_id: ConnectionId,
+    parent_dn: String,
+    /// DNs from the top of the tree down to the container.
+    parent_path: Vec<String>,
+    jump: Jump,
+    /// Page direction taken so far; turning back means no entry matches.
+    direction: PageDirection,
+}
+
+/// An entry being revealed in the tree, waiting for `parent_dn`'s
+/// children to load.
+struct PendingReveal {
+    conn_id: ConnectionId,
+    parent_dn: String,
+    dn: String,
+    /// Move focus to the tree once the entry is selected.
+    focus_tree: bool,
+}
+
+/// A hook of an imported profile, waiting for the user to allow it.
+struct HookApproval {
+    invocation: HookInvocation,
+    /// The connect held back until its `on_connect` hook may run.
+    connect: Option<HeldConnect>,
+}
+
+struct HeldConnect {
+    profile: ConnectionProfile,
+    password: String,
+    origin: ConnectOrigin,
+}
+
+/// An entry left by following a link, and the row that was selected.
+struct NavStop {
+    conn_id: ConnectionId,
+    dn: String,
+    row: usize,
+}
+
 /// Backend for a connection tab — either live LDAP or offline/example.
 enum TabBackend {
     Live(Arc<Mutex<LdapConnection>>),
@@ -72,18 +221,109 @@ enum TabBackend {
 struct ConnectionTab {
     id: ConnectionId,
     label: String,
+    /// Profile the tab was opened from; bookmarks are kept under it.
+    profile: String,
     host: String,
-    server_type: String,
     subschema_dn: Option<String>,
+    /// Writes are refused: the backend takes none, as with the example
+    /// directory, or the profile is read-only and not overridden.
     read_only: bool,
+    /// The profile is marked read-only: writes are refused as they are
+    /// dispatched, whatever the server would allow.
+    profile_read_only: bool,
+    /// `:set write on` lifted the profile's read-only flag for this
+    /// session; a reconnect puts it back.
+    write_override: bool,
+    /// Who the connection is bound as, when the server says.
+    identity: Option<String>,
+    /// Server advertises the server-side sort control.
+    server_side_sort: bool,
+    /// Server advertises transactions, so batches can be applied atomically.
+    transactions: bool,
+    /// What the server said about itself; a snapshot keeps the one it
+    /// was taken with.
+    root_dse: Option<RootDse>,
     backend: TabBackend,
     directory_tree: DirectoryTree,
     schema: Option<SchemaCache>,
+    /// Entries as last loaded into the detail panel, keyed by normalized
+    /// DN, so a delete can tell whether the server copy changed since.
+    entry_cache: HashMap<String, TrackedEntry>,
+    /// Entries that need confirmation, or are refused, before a write.
+    protected: ProtectedDns,
+    /// Background operations running against this connection.
+    ops: ActivityRegistry,
+    /// Whether the connection is up; always connected when offline.
+    health: HealthHandle,
+    /// Reopens a live connection after it drops.
+    reconnector: Option<Reconnector>,
+    /// Writes are queued in `pending_changes` instead of being sent.
+    offline_edits: bool,
+    /// Writes recorded while offline edits were on, in order.
+    pending_changes: Vec<Change>,
+    /// Steps that take back the latest writes; gone with the tab.
+    undo: UndoStack,
+}
+
+impl ConnectionTab {
+    /// Spawn `task` as an operation listed under `label` until it ends.
+    /// Cancelling it aborts the task and, on a live connection, asks the
+    /// server to abandon the search the task was waiting on.
+    fn spawn_op<F>(&self, label: impl Into<String>, task: F) -> OpId
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        let op = self.ops.begin(label);
+        let id = op.id();
+        let request = RequestSlot::default();
+        let slot = request.clone();
+        let handle = tokio::spawn(async move {
+            let _op = op;
+            slot.scope(task).await;
+        })
+        .abort_handle();
+        let connection = match &self.backend {
+            TabBackend::Live(connection) => Some(connection.clone()),
+            TabBackend::Offline(_) => None,
+        };
+        self.ops.on_cancel(
+            id,
+            Box::new(move || {
+                handle.abort();
+                if let Some(connection) = connection {
+                    let msgid = request.msgid();
+                    tokio::spawn(async move {
+                        if let Err(e) = connection.lock().await.abandon(msgid).await {
+                            debug!("Abandon after cancel failed: {}", e);
+                        }
+                    });
+                }
+            }),
+        );
+        id
+    }
+
+    /// Writes are refused because the profile is read-only.
+    fn writes_locked(&self) -> bool {
+        self.profile_read_only && !self.write_override
+    }
+
+    /// Lift the profile's read-only flag for this session, or put it back.
+    fn set_write_override(&mut self, on: bool) {
+        self.write_override = on;
+        self.read_only = matches!(self.backend, TabBackend::Offline(_)) || self.writes_locked();
+    }
 }
 
 /// The main application.
 pub struct App {
     config: AppConfig,
+    // Where the config is saved to and read back from
+    config_path: Option<PathBuf>,
+    // Settings changed with `:set` for this session only
+    overrides: Overrides,
+    // Notices the config file being edited outside loom
+    config_watch: Option<ConfigWatch>,
     should_quit: bool,
     next_conn_id: ConnectionId,
 
@@ -93,6 +333,16 @@ pub struct App {
     // Certificate trust
     trust_store: Arc<TrustStore>,
 
+    // Where writes are recorded, when `audit_log` is set
+    audit: Option<Arc<AuditFile>>,
+
+    // The running log, once main has set it up
+    logging: Option<Logging>,
+
+    // Connection attempts running in the background
+    connect_attempts: ConnectAttempts,
+    resolver: Arc<dyn Resolver>,
+
     // Layout state
     active_layout: ActiveLayout,
 
@@ -132,13 +382,52 @@ pub struct App {
     attribute_picker: AttributePicker,
     export_dialog: ExportDialog,
     bulk_update_dialog: BulkUpdateDialog,
+    batch_modify_dialog: BatchModifyDialog,
+    batch_summary_popup: BatchSummaryPopup,
+    migrate_dialog: MigrateAttributeDialog,
     create_entry_dialog: CreateEntryDialog,
     schema_viewer: SchemaViewer,
     help_popup: HelpPopup,
     about_popup: AboutPopup,
+    error_details_popup: ErrorDetailsPopup,
+    value_viewer: ValueViewer,
+    membership_popup: MembershipPopup,
+    messages_popup: MessagesPopup,
+    columns_dialog: ColumnsDialog,
+    password_dialog: PasswordDialog,
+    compare_view: CompareView,
+    doctor_popup: DoctorPopup,
+    server_info_popup: ServerInfoPopup,
+    export_summary_popup: ExportSummaryPopup,
+    protected_write_dialog: ProtectedWriteDialog,
+    write_override_dialog: WriteOverrideDialog,
     log_panel: LogPanel,
+    log_viewer: LogViewer,
     profile_export_dialog: ProfileExportDialog,
     profile_import_dialog: ProfileImportDialog,
+    search_form_dialog: SearchFormDialog,
+    snapshot_dialog: SnapshotDialog,
+    subtree_compare_dialog: SubtreeCompareDialog,
+    subtree_diff_view: SubtreeDiffView,
+    pending_changes_popup: PendingChangesPopup,
+    apply_ldif_dialog: ApplyLdifDialog,
+    apply_results_view: ApplyResultsView,
+    delete_dialog: DeleteDialog,
+    rename_dialog: RenameDialog,
+    copy_subtree_dialog: CopySubtreeDialog,
+    activity_popup: ActivityPopup,
+    setup_wizard: SetupWizard,
+    quick_switcher: QuickSwitcher,
+    bookmarks_popup: BookmarksPopup,
+
+    // Normalized DN of a protected entry whose next write was confirmed
+    protected_write_allowed: Option<String>,
+
+    // Entries recently shown in the detail panel, for the quick switcher
+    recent_entries: RecentEntries,
+
+    // Most recent error, for the error details popup
+    last_error: Option<String>,
 
     // Ad-hoc connection tracking (for save-to-config)
     last_adhoc_profile: Option<ConnectionProfile>,
@@ -150,16 +439,73 @@ pub struct App {
     layout_bar_area: Option<Rect>,
     conn_tree_area: Option<Rect>,
     conn_form_area: Option<Rect>,
+    schema_list_area: Option<Rect>,
+    schema_definition_area: Option<Rect>,
 
-    // Resizable panel splits (percentages, 10..=90)
-    tree_split_pct: u16, // left panel width as % of content area
+    // Divider being dragged; split ratios live in config.layout
     drag_target: Option<DragTarget>,
 
     // Vim-style 'g' prefix state for gt/gT tab switching
     pending_g: bool,
 
-    // First-launch: after help popup closes, show the connect dialog
-    show_connect_after_help: bool,
+    // Profile saved by the setup wizard, replaced when it is saved again
+    setup_profile: Option<usize>,
+
+    // Hooks of imported profiles waiting to be allowed, asked about one at
+    // a time, and whether the confirm dialog is asking about the first
+    hook_approvals: VecDeque<HookApproval>,
+    asking_hook: bool,
+
+    // Legacy config loaded at startup, to offer rewriting before connecting
+    pending_migration: Option<MigrationReport>,
+    /// Legacy password and the profile it belongs to, moved into the
+    /// keychain if the user agrees to rewrite the config.
+    migrated_password: Option<(String, String)>,
+
+    // Snapshot being written, cancelled through its tab's operations
+    snapshot_task: Option<(ConnectionId, OpId)>,
+    // Running subtree comparison: the tab it runs on, its operation and
+    // the other connection's label
+    subtree_compare_task: Option<(ConnectionId, OpId, String)>,
+    // Changefile being applied, cancelled through its tab's operations
+    apply_ldif_task: Option<(ConnectionId, OpId)>,
+
+    // Subtree being deleted and its base DN, cancelled through its tab's
+    // operations
+    subtree_delete_task: Option<(ConnectionId, OpId, String)>,
+
+    // Branch being copied and its new DN, cancelled through its tab's
+    // operations
+    subtree_copy_task: Option<(ConnectionId, OpId, String)>,
+
+    // Entry yanked with `yy`, to paste copies of
+    yanked_entry: Option<(ConnectionId, String)>,
+    // Entry marked with `C`, to compare the next one with
+    compare_mark: Option<LdapEntry>,
+    clipboard: Clipboard,
+
+    // Export being written, cancelled through its tab's operations
+    export_task: Option<(ConnectionId, OpId)>,
+
+    // Tree jump that loaded another page and resumes when it arrives
+    pending_jump: Option<PendingJump>,
+    // Entry being revealed in the tree as its ancestors load
+    pending_reveal: Option<PendingReveal>,
+    // Entries left by following links, newest last
+    nav_history: Vec<NavStop>,
+    // Where a link being followed was followed from, until it loads
+    pending_follow: Option<NavStop>,
+    // Bookmark opened on a profile that is still connecting
+    pending_bookmark: Option<Bookmark>,
+
+    // Search results waiting to be moved into the results table
+    result_ingest: ResultIngest,
+    // Tab the current search results came from
+    results_conn: Option<ConnectionId>,
+    // Stop flag of the latest search, with its tab and generation
+    search_stop: Option<(ConnectionId, u64, Arc<AtomicBool>)>,
+    // The latest search, to run again sorted on the server
+    last_search: Option<(ConnectionId, SearchRequest)>,
 
     // Async communication
     action_tx: tokio::sync::mpsc::UnboundedSender<Action>,
@@ -168,20 +514,49 @@ pub struct App {
 
 impl App {
     pub fn new(config: AppConfig, vault: Option<Vault>) -> Self {
-        let theme = Theme::load(&config.general.theme);
+        let (theme, theme_problem) = match Styles::named(&config.general.theme) {
+            Ok(styles) => (Theme::new(styles), None),
+            Err(e) => (Theme::default(), Some(e)),
+        };
         let keymap = Keymap::from_config(&config.keybindings);
         let status_bar = StatusBar::new(theme.clone(), &keymap);
+        let search_dialog =
+            SearchDialog::new(theme.clone(), &keymap).with_index_strip(config.general.index_strip);
         let (action_tx, action_rx) = tokio::sync::mpsc::unbounded_channel();
         let autocomplete_enabled = config.general.autocomplete;
         let live_search_enabled = config.general.live_search;
+        let index_strip = config.general.index_strip;
+        let confirm_discard = config.general.confirm_discard;
+        let tree_panel = TreePanel::new(theme.clone())
+            .with_index_strip(index_strip)
+            .with_keys(&keymap);
+        let detail_panel = DetailPanel::new(theme.clone())
+            .with_layout(config.detail.clone())
+            .with_keys(&keymap);
+        let connections_tree = ConnectionsTree::new(theme.clone()).with_keys(&keymap);
+        let confirm_dialog = ConfirmDialog::new(theme.clone()).with_keys(&keymap);
+        let log_panel = LogPanel::new(theme.clone()).with_keys(&keymap);
         let trust_store = Arc::new(TrustStore::from_config(&config.trusted_certificates));
+        let audit = audit_file(&config.general);
+        let log_viewer = LogViewer::new(
+            theme.clone(),
+            LogBuffer::new(config.general.log_viewer_lines),
+            logging::log_path(&config.general),
+        );
 
-        Self {
+        let mut app = Self {
             config,
+            config_path: AppConfig::path(),
+            overrides: Overrides::default(),
+            config_watch: None,
             should_quit: false,
             next_conn_id: 0,
             vault,
             trust_store,
+            audit,
+            logging: None,
+            connect_attempts: ConnectAttempts::default(),
+            resolver: Arc::new(SystemResolver),
             active_layout: ActiveLayout::Profiles,
             tabs: Vec::new(),
             active_tab_id: None,
@@ -189,8 +564,8 @@ impl App {
             theme: theme.clone(),
             layout_bar: LayoutBar::new(theme.clone()),
             tab_bar: TabBar::new(theme.clone()),
-            tree_panel: TreePanel::new(theme.clone()),
-            detail_panel: DetailPanel::new(theme.clone()),
+            tree_panel,
+            detail_panel,
             command_panel: CommandPanel::new(
                 theme.clone(),
                 autocomplete_enabled,
@@ -198,27 +573,66 @@ impl App {
             ),
             status_bar,
             focus: FocusManager::new(),
-            connections_tree: ConnectionsTree::new(theme.clone()),
-            connection_form: ConnectionForm::new(theme.clone()),
+            connections_tree,
+            connection_form: ConnectionForm::new(theme.clone())
+                .with_confirm_discard(confirm_discard),
             context_menu: ContextMenu::new(theme.clone()),
-            confirm_dialog: ConfirmDialog::new(theme.clone()),
+            confirm_dialog,
             cert_trust_dialog: CertTrustDialog::new(theme.clone()),
             connect_dialog: ConnectDialog::new(theme.clone()),
-            new_connection_dialog: NewConnectionDialog::new(theme.clone()),
+            new_connection_dialog: NewConnectionDialog::new(theme.clone())
+                .with_confirm_discard(confirm_discard),
             credential_prompt: CredentialPromptDialog::new(theme.clone()),
             vault_password_dialog: VaultPasswordDialog::new(theme.clone()),
-            search_dialog: SearchDialog::new(theme.clone()),
+            search_dialog,
             attribute_editor: AttributeEditor::new(theme.clone()),
             attribute_picker: AttributePicker::new(theme.clone()),
             export_dialog: ExportDialog::new(theme.clone()),
-            bulk_update_dialog: BulkUpdateDialog::new(theme.clone()),
-            create_entry_dialog: CreateEntryDialog::new(theme.clone()),
+            bulk_update_dialog: BulkUpdateDialog::new(theme.clone())
+                .with_confirm_discard(confirm_discard),
+            batch_modify_dialog: BatchModifyDialog::new(theme.clone())
+                .with_confirm_discard(confirm_discard),
+            batch_summary_popup: BatchSummaryPopup::new(theme.clone()),
+            migrate_dialog: MigrateAttributeDialog::new(theme.clone()),
+            create_entry_dialog: CreateEntryDialog::new(theme.clone())
+                .with_confirm_discard(confirm_discard),
             schema_viewer: SchemaViewer::new(theme.clone()),
             help_popup: HelpPopup::new(theme.clone()),
             about_popup: AboutPopup::new(theme.clone()),
-            log_panel: LogPanel::new(theme.clone()),
+            error_details_popup: ErrorDetailsPopup::new(theme.clone()),
+            value_viewer: ValueViewer::new(theme.clone()),
+            membership_popup: MembershipPopup::new(theme.clone()),
+            messages_popup: MessagesPopup::new(theme.clone()),
+            columns_dialog: ColumnsDialog::new(theme.clone()),
+            password_dialog: PasswordDialog::new(theme.clone()),
+            compare_view: CompareView::new(theme.clone()),
+            doctor_popup: DoctorPopup::new(theme.clone()),
+            server_info_popup: ServerInfoPopup::new(theme.clone()),
+            export_summary_popup: ExportSummaryPopup::new(theme.clone()),
+            protected_write_dialog: ProtectedWriteDialog::new(theme.clone()),
+            write_override_dialog: WriteOverrideDialog::new(theme.clone()),
+            log_panel,
+            log_viewer,
             profile_export_dialog: ProfileExportDialog::new(theme.clone()),
-            profile_import_dialog: ProfileImportDialog::new(theme),
+            profile_import_dialog: ProfileImportDialog::new(theme.clone())
+                .with_confirm_discard(confirm_discard),
+            search_form_dialog: SearchFormDialog::new(theme.clone()),
+            snapshot_dialog: SnapshotDialog::new(theme.clone()),
+            subtree_compare_dialog: SubtreeCompareDialog::new(theme.clone()),
+            subtree_diff_view: SubtreeDiffView::new(theme.clone()),
+            pending_changes_popup: PendingChangesPopup::new(theme.clone()),
+            apply_ldif_dialog: ApplyLdifDialog::new(theme.clone()),
+            apply_results_view: ApplyResultsView::new(theme.clone()),
+            delete_dialog: DeleteDialog::new(theme.clone()),
+            rename_dialog: RenameDialog::new(theme.clone()),
+            copy_subtree_dialog: CopySubtreeDialog::new(theme.clone()),
+            activity_popup: ActivityPopup::new(theme.clone()),
+            setup_wizard: SetupWizard::new(theme.clone()),
+            quick_switcher: QuickSwitcher::new(theme.clone()),
+            bookmarks_popup: BookmarksPopup::new(theme),
+            protected_write_allowed: None,
+            recent_entries: RecentEntries::default(),
+            last_error: None,
             last_adhoc_profile: None,
             tree_area: None,
             detail_area: None,
@@ -226,13 +640,47 @@ impl App {
             layout_bar_area: None,
             conn_tree_area: None,
             conn_form_area: None,
-            tree_split_pct: 25,
+            schema_list_area: None,
+            schema_definition_area: None,
             drag_target: None,
             pending_g: false,
-            show_connect_after_help: false,
+            setup_profile: None,
+            hook_approvals: VecDeque::new(),
+            asking_hook: false,
+            pending_migration: None,
+            migrated_password: None,
+            snapshot_task: None,
+            subtree_compare_task: None,
+            apply_ldif_task: None,
+            subtree_delete_task: None,
+            subtree_copy_task: None,
+            yanked_entry: None,
+            compare_mark: None,
+            clipboard: Clipboard::default(),
+            export_task: None,
+            pending_jump: None,
+            pending_reveal: None,
+            nav_history: Vec::new(),
+            pending_follow: None,
+            pending_bookmark: None,
+            result_ingest: ResultIngest::default(),
+            results_conn: None,
+            search_stop: None,
+            last_search: None,
             action_tx,
             action_rx,
+        };
+        app.command_panel
+            .set_command_history(app.config.command_history.clone());
+        for problem in app.keymap.problems().to_vec() {
+            app.push_error(format!("[keybindings] {}", problem));
+        }
+        if let Some(problem) = theme_problem {
+            let msg = format!("Theme {}; using the dark theme", problem);
+            app.log_panel.push_error(msg.clone());
+            app.status_bar.set_warning(msg);
         }
+        app
     }
 
     fn allocate_conn_id(&mut self) -> ConnectionId {
@@ -254,35 +702,45 @@ impl App {
 
     fn push_message(&mut self, msg: String) {
         self.command_panel.push_message(msg.clone());
+        self.status_bar.log(Severity::Info, msg.clone());
         self.log_panel.push_info(msg);
     }
 
     fn push_error(&mut self, msg: String) {
+        self.last_error = Some(msg.clone());
         self.command_panel.push_error(msg.clone());
+        self.status_bar.log(Severity::Error, msg.clone());
         self.log_panel.push_error(msg);
     }
 
+    /// Show the active tab's profile, identity and base DN in the status bar.
+    fn sync_status_context(&mut self) {
+        match self.active_tab() {
+            Some(tab) => {
+                let (label, identity) = (tab.label.clone(), tab.identity.clone());
+                let base_dn = tab.directory_tree.root_dn.clone();
+                self.status_bar
+                    .set_connected(&label, identity.as_deref(), &base_dn);
+            }
+            None => self.status_bar.set_disconnected(),
+        }
+    }
+
     /// Connect to the first configured connection profile.
     /// Auth errors are handled gracefully by showing a credential prompt.
-    /// On first launch (no config file), shows help first, then the connect dialog.
+    /// On first launch (no config file), runs the setup wizard instead.
     pub async fn connect_first_profile(&mut self) {
         if self.config.first_launch {
-            self.help_popup.show(&self.keymap);
-            self.show_connect_after_help = true;
+            self.start_setup();
+            return;
+        }
+        if let Some(report) = self.pending_migration.take() {
+            self.offer_config_migration(&report);
             return;
         }
         if !self.config.connections.is_empty() {
             let profile = self.config.connections[0].clone();
-            match self.connect_profile(&profile).await {
-                Ok(()) => {}
-                Err(e) if is_auth_error(&e) => {
-                    self.push_error(format!("Authentication failed: {}", e));
-                    self.credential_prompt.show(profile);
-                }
-                Err(e) => {
-                    self.push_error(format!("Connection failed: {}", e));
-                }
-            }
+            self.connect_profile(profile);
         } else {
             self.status_bar.set_message(format!(
                 "No profiles configured. Press {} or add profiles to ~/.config/loom-ldapbrowser/config.toml",
@@ -291,136 +749,537 @@ impl App {
         }
     }
 
-    async fn connect_profile(&mut self, profile: &ConnectionProfile) -> anyhow::Result<()> {
+    /// Show the running log in the log viewer, and point its file and
+    /// filter wherever a reloaded config says.
+    pub fn set_logging(&mut self, logging: Logging) {
+        self.log_viewer.set_buffer(logging.buffer.clone());
+        self.logging = Some(logging);
+    }
+
+    /// Read the config file again whenever something other than loom
+    /// writes it.
+    pub fn watch_config(&mut self) {
+        self.config_watch = self.config_path.clone().map(ConfigWatch::new);
+    }
+
+    /// Note a migration done by [`AppConfig::load_migrated`]; the user is
+    /// asked whether to rewrite the file before the first profile connects.
+    pub fn set_config_migration(&mut self, report: MigrationReport) {
+        if !report.is_empty() {
+            self.pending_migration = Some(report);
+        }
+    }
+
+    fn offer_config_migration(&mut self, report: &MigrationReport) {
+        for note in &report.notes {
+            self.log_panel.push_info(note.clone());
+        }
+        self.migrated_password = report.profile.clone().zip(report.password.clone());
+        let change = match &report.profile {
+            Some(name) => format!(
+                "Your config still uses the old single-connection settings; they were loaded as profile '{}'.",
+                name
+            ),
+            None => format!(
+                "Your config has old settings that are no longer used ({}).",
+                report.legacy_keys.join(", ")
+            ),
+        };
+        let password = if self.migrated_password.is_some() {
+            " The saved password is moved to the OS keychain."
+        } else {
+            ""
+        };
+        self.confirm_dialog.show(
+            format!(
+                "{} Rewrite config.toml in the new format? The current file is kept as {}.{}",
+                change, BACKUP_FILE, password
+            ),
+            Action::ConnMgrAdoptMigration,
+        );
+    }
+
+    /// Open the setup wizard, creating the config directory first.
+    fn start_setup(&mut self) {
+        let config_note = match dirs::config_dir().map(|d| d.join("loom-ldapbrowser")) {
+            Some(dir) => match std::fs::create_dir_all(&dir) {
+                Ok(()) => format!("Settings are saved in {}.", dir.display()),
+                Err(e) => format!("Could not create {}: {}", dir.display(), e),
+            },
+            None => {
+                "Cannot determine the config directory; settings will not be saved.".to_string()
+            }
+        };
+        let sources = dirs::home_dir()
+            .map(|home| find_sources(&home))
+            .unwrap_or_default();
+        self.setup_profile = None;
+        self.setup_wizard
+            .show(config_note, sources, &self.config.general.theme);
+    }
+
+    /// Add the wizard's profile to the config, or replace the one it saved
+    /// earlier, and write the config.
+    fn save_setup_profile(&mut self, profile: ConnectionProfile) {
+        match self
+            .setup_profile
+            .filter(|&i| i < self.config.connections.len())
+        {
+            Some(i) => self.config.connections[i] = profile,
+            None => {
+                self.config.connections.push(profile);
+                self.setup_profile = Some(self.config.connections.len() - 1);
+            }
+        }
+        if let Err(e) = self.save_config() {
+            self.push_error(format!("Failed to save config: {}", e));
+        }
+    }
+
+    fn connect_profile(&mut self, profile: ConnectionProfile) {
         if profile.offline {
             self.connect_offline();
-            return Ok(());
+            return;
         }
-        let password = if profile.bind_dn.is_some() {
-            match resolve_password(profile, &self.vault) {
+        let password = if profile.bind_dn.is_some() && profile.auth.is_simple() {
+            match resolve_password(&profile, &self.vault) {
                 Ok(password) if !password.is_empty() => password,
                 _ => {
                     // No password available — need interactive prompt
-                    self.credential_prompt.show(profile.clone());
-                    return Ok(());
+                    self.credential_prompt.show(profile);
+                    return;
                 }
             }
         } else {
             String::new()
         };
 
-        match self.connect_with_password(profile, &password).await {
-            Ok(()) => Ok(()),
-            Err(e) if extract_cert_trust_error(&e).is_some() => {
-                let info = extract_cert_trust_error(&e).unwrap();
-                let _ = self.action_tx.send(Action::ShowCertTrustDialog {
-                    cert_info: Box::new(info),
-                    profile: Box::new(profile.clone()),
-                    password,
-                });
-                Ok(())
-            }
-            Err(e) => Err(e),
-        }
+        self.start_connect(profile, password, ConnectOrigin::Profile);
     }
 
     fn connect_offline(&mut self) {
-        let offline = OfflineDirectory::load_embedded();
+        self.open_offline(
+            OfflineDirectory::load_embedded(),
+            "Example Directory".to_string(),
+            example_profile().name,
+            "contoso.example",
+            ServerType::ActiveDirectory,
+        );
+        self.push_message("Connected to example directory (read-only)".to_string());
+    }
+
+    /// Browse a snapshot read-only, with the schema it was taken with.
+    pub fn open_snapshot(&mut self, snapshot: Snapshot) {
+        let manifest = snapshot.manifest;
+        let label = format!("{} (snapshot)", manifest.profile);
+        self.open_offline(
+            snapshot.directory,
+            label,
+            manifest.profile.clone(),
+            &manifest.host,
+            snapshot.root_dse.server_type.clone(),
+        );
+        if let Some(tab) = self.tabs.last_mut() {
+            tab.root_dse = Some(snapshot.root_dse);
+        }
+        self.push_message(format!(
+            "Opened snapshot of {} from {}: {} entries (read-only)",
+            manifest.base_dn,
+            manifest.created.format("%Y-%m-%d %H:%M UTC"),
+            manifest.entries
+        ));
+    }
+
+    fn open_offline(
+        &mut self,
+        offline: OfflineDirectory,
+        label: String,
+        profile: String,
+        host: &str,
+        server: ServerType,
+    ) {
         let base_dn = offline.base_dn().to_string();
         let schema = offline.schema().clone();
         let conn_id = self.allocate_conn_id();
 
         let tab = ConnectionTab {
             id: conn_id,
-            label: "Example Directory".to_string(),
-            host: "contoso.example".to_string(),
-            server_type: "Active Directory (Example)".to_string(),
+            label: label.clone(),
+            profile: profile.clone(),
+            host: host.to_string(),
             subschema_dn: None,
             read_only: true,
+            profile_read_only: false,
+            write_override: false,
+            identity: None,
+            server_side_sort: false,
+            transactions: false,
+            root_dse: None,
             backend: TabBackend::Offline(offline),
             directory_tree: DirectoryTree::new(base_dn.clone()),
             schema: Some(schema),
+            entry_cache: HashMap::new(),
+            protected: ProtectedDns::for_server(&server, &[], ProtectionMode::Confirm),
+            ops: ActivityRegistry::default(),
+            health: HealthHandle::default(),
+            reconnector: None,
+            offline_edits: false,
+            pending_changes: Vec::new(),
+            undo: UndoStack::new(self.config.general.undo_depth),
         };
 
         self.tabs.push(tab);
-        self.tab_bar
-            .add_tab(conn_id, "Example Directory".to_string());
+        self.tab_bar.add_tab(conn_id, label);
         self.active_tab_id = Some(conn_id);
         self.active_layout = ActiveLayout::Browser;
         self.layout_bar.active = ActiveLayout::Browser;
         self.focus.set_layout(ActiveLayout::Browser);
         self.spawn_load_children(conn_id, base_dn);
-        self.push_message("Connected to example directory (read-only)".to_string());
-        self.status_bar
-            .set_connected("contoso.example", "Active Directory (Example)");
+        self.sync_status_context();
+        self.resume_pending_bookmark(&profile);
     }
 
-    async fn connect_with_password(
+    /// Start connecting in the background; the outcome arrives as
+    /// `Action::ConnectFinished`. A second request for a profile that is
+    /// already connecting is ignored. The profile's `on_connect` hook runs
+    /// before the bind, so a failing required hook stops the connect.
+    fn start_connect(
         &mut self,
-        profile: &ConnectionProfile,
-        password: &str,
-    ) -> anyhow::Result<()> {
-        self.push_message(format!("Connecting to {}...", profile.host));
+        profile: ConnectionProfile,
+        password: String,
+        origin: ConnectOrigin,
+    ) {
+        let hook = match self.connect_hook(&profile) {
+            Some(invocation) if self.hooks_untrusted(&profile.name) => invocation,
+            hook => return self.begin_connect(profile, password, origin, hook),
+        };
+        let waiting = self
+            .hook_approvals
+            .iter()
+            .filter_map(|a| a.connect.as_ref())
+            .any(|c| c.profile.name == profile.name);
+        if waiting {
+            self.push_message(format!("Already connecting to {}", profile.host));
+            return;
+        }
+        self.hook_approvals.push_back(HookApproval {
+            invocation: hook,
+            connect: Some(HeldConnect {
+                profile,
+                password,
+                origin,
+            }),
+        });
+        self.ask_hook_approval();
+    }
 
-        let settings = profile.to_connection_settings();
-        let mut conn = LdapConnection::connect(settings, Some(self.trust_store.clone())).await?;
+    /// The `on_connect` hook to run before binding with `profile`.
+    fn connect_hook(&self, profile: &ConnectionProfile) -> Option<HookInvocation> {
+        let invocation = HookInvocation::new(&profile.hooks, HookEvent::Connect, &profile.name)?;
+        if !self.config.general.hooks_enabled {
+            debug!(
+                "Hooks disabled; skipping {} for profile '{}'",
+                invocation.event, invocation.profile
+            );
+            return None;
+        }
+        Some(
+            invocation
+                .with_env("LOOM_HOST", &profile.host)
+                .with_env("LOOM_PORT", &profile.port.to_string())
+                .with_env("LOOM_BASE_DN", profile.base_dn.as_deref().unwrap_or("")),
+        )
+    }
 
-        // Bind with credential resolution
-        if let Some(ref bind_dn) = profile.bind_dn {
-            conn.simple_bind(bind_dn, password).await?;
-        } else {
-            conn.anonymous_bind().await?;
+    fn begin_connect(
+        &mut self,
+        profile: ConnectionProfile,
+        password: String,
+        origin: ConnectOrigin,
+        hook: Option<HookInvocation>,
+    ) {
+        let host = profile.host.clone();
+        let Some(attempt) = self
+            .connect_attempts
+            .begin(profile.clone(), password.clone(), origin)
+        else {
+            self.push_message(format!("Already connecting to {}", host));
+            return;
+        };
+        self.push_message(format!("Connecting to {}...", host));
+
+        let tx = self.action_tx.clone();
+        let trust_store = self.trust_store.clone();
+        let resolver = self.resolver.clone();
+        let audit = self.audit.clone();
+        if let Some(invocation) = &hook {
+            self.log_panel.push_debug(format!(
+                "Running hook {} for profile '{}'",
+                invocation.event, invocation.profile
+            ));
         }
+        let task = tokio::spawn(async move {
+            if let Some(invocation) = hook {
+                let outcome = loom_core::hooks::run_hook(&invocation).await;
+                let stop = outcome.required && !outcome.success;
+                let _ = tx.send(Action::HookFinished(Box::new(outcome)));
+                if stop {
+                    let _ = tx.send(Action::ConnectFinished(
+                        attempt,
+                        Err(ConnectFailure::Other(
+                            "required on_connect hook failed".to_string(),
+                        )),
+                    ));
+                    return;
+                }
+            }
+            let result = open_connection(&profile, &password, trust_store, resolver.as_ref())
+                .await
+                .map(Box::new);
+            if let (Ok(server), Some(audit)) = (&result, audit) {
+                server.connection.lock().await.audit = Some(Auditor::new(
+                    audit,
+                    profile.name.clone(),
+                    server.identity.clone(),
+                ));
+            }
+            let _ = tx.send(Action::ConnectFinished(attempt, result));
+        });
+        self.connect_attempts.attach(attempt, task.abort_handle());
+    }
 
-        // Read RootDSE to detect server type and auto-discover base DN
-        let (server_type_str, subschema_dn) = match conn.read_root_dse().await {
-            Ok(root_dse) => {
-                let st = root_dse.server_type.to_string();
-                debug!(
-                    "RootDSE: server_type={}, subschema_subentry={:?}, naming_contexts={:?}, vendor={:?}",
-                    st,
-                    root_dse.subschema_subentry,
-                    root_dse.naming_contexts,
-                    root_dse.vendor_name,
-                );
-                // Log all raw RootDSE attribute keys for troubleshooting
-                let raw_keys: Vec<&String> = root_dse.raw.keys().collect();
-                debug!("RootDSE raw attribute keys: {:?}", raw_keys);
-                self.push_message(format!("Server type: {}", st));
-                (st, root_dse.subschema_subentry)
+    /// Bind with a profile from the connection form and report back to
+    /// the form; nothing is saved and no tab is opened.
+    fn test_profile(&mut self, profile: ConnectionProfile, password: String) {
+        let password = if password.is_empty() {
+            resolve_password(&profile, &self.vault).unwrap_or_default()
+        } else {
+            password
+        };
+        let tx = self.action_tx.clone();
+        let trust_store = self.trust_store.clone();
+        let resolver = self.resolver.clone();
+        tokio::spawn(async move {
+            let result =
+                match open_connection(&profile, &password, trust_store, resolver.as_ref()).await {
+                    Ok(server) => {
+                        let _ = server.connection.lock().await.disconnect().await;
+                        let as_suffix = server
+                            .identity
+                            .map(|id| format!(" as {}", id))
+                            .unwrap_or_default();
+                        Ok(format!(
+                            "Connected: {}{} (base: {})",
+                            server.server_type, as_suffix, server.base_dn
+                        ))
+                    }
+                    Err(ConnectFailure::Auth(e)) => Err(format!("Authentication failed: {}", e)),
+                    Err(ConnectFailure::CertUntrusted(info)) => Err(format!(
+                        "Certificate {} rejected: {}; connect once to review it",
+                        info.subject, info.problem
+                    )),
+                    Err(ConnectFailure::Other(e)) => Err(format!("Connection failed: {}", e)),
+                };
+            let _ = tx.send(Action::ConnMgrTestFinished(result));
+        });
+    }
+
+    /// Report a change in a tab's connection health. Operations still
+    /// running when the connection is lost are cancelled, since they
+    /// would wait on a dead socket.
+    fn health_changed(&mut self, id: ConnectionId, health: ConnectionHealth) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == id) else {
+            return;
+        };
+        let host = tab.host.clone();
+        match health {
+            ConnectionHealth::Reconnecting => {
+                let cancelled = tab.ops.cancel_all();
+                let mut msg = format!("Connection to {} lost, reconnecting", host);
+                if cancelled > 0 {
+                    msg.push_str(&format!("; {} operation(s) cancelled", cancelled));
+                }
+                self.log_panel.push_error(msg.clone());
+                self.status_bar.set_error(msg);
             }
-            Err(e) => {
-                debug!("RootDSE read failed (non-fatal): {}", e);
-                ("LDAP".to_string(), None)
+            ConnectionHealth::Connected => {
+                let mut msg = format!("Reconnected to {}", host);
+                // `:set write on` lasts until the connection is reopened
+                if let Some(tab) = self
+                    .tabs
+                    .iter_mut()
+                    .find(|t| t.id == id)
+                    .filter(|t| t.write_override)
+                {
+                    tab.set_write_override(false);
+                    msg.push_str(&format!("; {} is read-only again", tab.profile));
+                }
+                self.log_panel.push_info(msg.clone());
+                self.status_bar.set_message(msg);
+            }
+            ConnectionHealth::Disconnected => {
+                let msg = format!(
+                    "Could not reconnect to {}; press Ctrl-R in Profiles to try again",
+                    host
+                );
+                self.log_panel.push_error(msg.clone());
+                self.status_bar.set_error(msg);
             }
+        }
+    }
+
+    /// Reopen tab `id`'s connection (the active tab's for `None`) now,
+    /// whether or not it was found to be down.
+    fn reconnect_tab(&mut self, id: Option<ConnectionId>) {
+        let Some(tab) = id
+            .or(self.active_tab_id)
+            .and_then(|id| self.tabs.iter().find(|t| t.id == id))
+        else {
+            self.status_bar
+                .set_message("No connection to reconnect".to_string());
+            return;
+        };
+        let (TabBackend::Live(connection), Some(reconnector)) = (&tab.backend, &tab.reconnector)
+        else {
+            let msg = format!("{} is offline; nothing to reconnect", tab.label);
+            self.status_bar.set_message(msg);
+            return;
         };
-        debug!("connect_with_password: subschema_dn={:?}", subschema_dn);
+        if tab.health.get() == ConnectionHealth::Reconnecting {
+            let msg = format!("Already reconnecting to {}", tab.host);
+            self.status_bar.set_message(msg);
+            return;
+        }
+        tab.health.set(ConnectionHealth::Reconnecting);
+        let id = tab.id;
+        let connection = Arc::downgrade(connection);
+        let health = tab.health.clone();
+        let reconnector = reconnector.clone();
+        let tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            recover(id, connection, &health, &reconnector, &tx).await;
+        });
+    }
+
+    /// Keep a password that worked in the vault or keychain, whichever
+    /// the profile uses.
+    fn remember_password(&mut self, profile: &ConnectionProfile, password: &str) {
+        match profile.credential_method {
+            CredentialMethod::Vault => {
+                if let Some(ref mut vault) = self.vault {
+                    let _ = vault.set_password(&profile.name, password);
+                }
+            }
+            CredentialMethod::Keychain => {
+                if let Err(e) = CredentialProvider::store_in_keychain(&profile.name, password) {
+                    self.push_error(format!("Password not stored in the keychain: {}", e));
+                }
+            }
+            CredentialMethod::Prompt | CredentialMethod::Command => {}
+        }
+    }
+
+    /// Store a password from a legacy config in the keychain and switch
+    /// its profile to the keychain method.
+    fn move_password_to_keychain(&mut self, name: &str, password: &str) {
+        match CredentialProvider::store_in_keychain(name, password) {
+            Ok(()) => {
+                if let Some(profile) = self.config.connections.iter_mut().find(|p| p.name == name) {
+                    profile.credential_method = CredentialMethod::Keychain;
+                }
+                self.push_message(format!(
+                    "Moved the saved password for '{}' to the OS keychain",
+                    name
+                ));
+            }
+            Err(e) => self.push_error(format!(
+                "The saved password could not be moved to the keychain and was dropped: {}",
+                e
+            )),
+        }
+    }
+
+    fn profile_names(&self) -> Vec<String> {
+        self.config
+            .connections
+            .iter()
+            .map(|p| p.name.clone())
+            .collect()
+    }
+
+    /// Open a tab for a connection that came back from `start_connect`.
+    fn finish_connect(&mut self, profile: &ConnectionProfile, server: ConnectedServer) {
+        self.push_message(format!("Server type: {}", server.server_type));
+        let ConnectedServer {
+            connection,
+            base_dn,
+            subschema_dn,
+            server_side_sort,
+            transactions,
+            root_dse,
+            protected,
+            identity,
+            health,
+            reconnector,
+            ..
+        } = server;
+        debug!("finish_connect: subschema_dn={:?}", subschema_dn);
 
         let conn_id = self.allocate_conn_id();
-        let base_dn = conn.base_dn.clone();
         let label = profile.name.clone();
         let host = profile.host.clone();
 
         let read_only = profile.read_only;
         let ro_suffix = if read_only { " (read-only)" } else { "" };
-        let conn_msg = format!("Connected to {} (base: {}){}", host, base_dn, ro_suffix);
+        let as_suffix = identity
+            .as_ref()
+            .map(|id| format!(" as {}", id))
+            .unwrap_or_default();
+        let conn_msg = format!(
+            "Connected to {}{} (base: {}){}",
+            host, as_suffix, base_dn, ro_suffix
+        );
         self.status_bar.set_message(conn_msg.clone());
         self.log_panel.push_info(conn_msg);
-        self.status_bar.set_connected(&host, &server_type_str);
 
-        let connection = Arc::new(Mutex::new(conn));
         let directory_tree = DirectoryTree::new(base_dn.clone());
 
+        let check_secs = self.config.general.health_check_secs;
+        if check_secs > 0 {
+            spawn_health_monitor(
+                conn_id,
+                &connection,
+                health.clone(),
+                reconnector.clone(),
+                Duration::from_secs(check_secs),
+                self.action_tx.clone(),
+            );
+        }
+
         let tab = ConnectionTab {
             id: conn_id,
             label: label.clone(),
+            profile: profile.name.clone(),
             host,
-            server_type: server_type_str,
             subschema_dn,
             read_only,
+            profile_read_only: read_only,
+            write_override: false,
+            identity,
+            server_side_sort,
+            transactions,
+            root_dse,
             backend: TabBackend::Live(connection),
             directory_tree,
             schema: None,
+            entry_cache: HashMap::new(),
+            protected,
+            ops: ActivityRegistry::default(),
+            health,
+            reconnector: Some(reconnector),
+            offline_edits: false,
+            pending_changes: Vec::new(),
+            undo: UndoStack::new(self.config.general.undo_depth),
         };
 
         self.tabs.push(tab);
@@ -429,92 +1288,719 @@ impl App {
         self.active_layout = ActiveLayout::Browser;
         self.layout_bar.active = ActiveLayout::Browser;
         self.focus.set_layout(ActiveLayout::Browser);
+        self.sync_status_context();
 
         // Load root children
-        self.spawn_load_children(conn_id, base_dn);
+        self.spawn_load_children(conn_id, base_dn.clone());
 
         // Auto-load schema so attribute picker is ready
         self.spawn_load_schema(conn_id);
+        self.resume_pending_bookmark(&profile.name);
+    }
 
-        Ok(())
+    /// The search results columns of the profile tab `id` was opened from.
+    fn result_columns(&self, id: ConnectionId) -> Vec<String> {
+        let profile = self.tabs.iter().find(|t| t.id == id).map(|t| &t.profile);
+        match profile.and_then(|name| self.config.connections.iter().find(|p| &p.name == name)) {
+            Some(profile) => profile.result_columns(),
+            None => DEFAULT_RESULT_COLUMNS.map(String::from).to_vec(),
+        }
     }
 
-    fn spawn_load_children(&self, conn_id: ConnectionId, dn: String) {
-        let tab = self.tabs.iter().find(|t| t.id == conn_id);
-        if let Some(tab) = tab {
-            let tx = self.action_tx.clone();
+    /// Build the hook invocation for `event` from the named profile, if it defines one.
+    fn profile_hook(&self, profile_name: &str, event: HookEvent) -> Option<HookInvocation> {
+        let profile = self
+            .config
+            .connections
+            .iter()
+            .find(|p| p.name == profile_name)?;
+        HookInvocation::new(&profile.hooks, event, &profile.name)
+    }
 
-            match &tab.backend {
-                TabBackend::Offline(dir) => {
-                    let nodes = dir.children(&dn);
-                    let _ = tx.send(Action::TreeChildrenLoaded(conn_id, dn, nodes));
-                }
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
-                    tokio::spawn(async move {
-                        let mut conn = connection.lock().await;
-                        let result = match conn.search_children(&dn).await {
-                            Ok(entries) => Ok(entries),
-                            Err(e) if LdapConnection::is_connection_error(&e) => {
-                                let _ =
-                                    tx.send(Action::StatusMessage("Reconnecting...".to_string()));
-                                if conn.reconnect().await.is_ok() {
-                                    conn.search_children(&dn).await
-                                } else {
-                                    Err(e)
-                                }
-                            }
-                            Err(e) => Err(e),
-                        };
+    /// Run a profile hook in the background, honouring the global kill-switch.
+    /// Hooks that came from an imported profile need a one-time confirmation.
+    fn trigger_hook(&mut self, invocation: HookInvocation) {
+        if !self.config.general.hooks_enabled {
+            debug!(
+                "Hooks disabled; skipping {} for profile '{}'",
+                invocation.event, invocation.profile
+            );
+            return;
+        }
 
-                        match result {
-                            Ok(entries) => {
-                                info!("Loaded {} child objects under '{}'", entries.len(), dn);
-                                let nodes: Vec<TreeNode> = entries
-                                    .iter()
-                                    .map(|e| TreeNode::new(e.dn.clone()))
-                                    .collect();
-                                let _ = tx.send(Action::TreeChildrenLoaded(conn_id, dn, nodes));
-                            }
-                            Err(e) => {
-                                error!("Failed to load children of '{}': {}", dn, e);
-                                let _ = tx.send(Action::ErrorMessage(format!(
-                                    "Failed to load {}: {}",
-                                    dn, e
-                                )));
-                            }
+        if self.hooks_untrusted(&invocation.profile) {
+            self.hook_approvals.push_back(HookApproval {
+                invocation,
+                connect: None,
+            });
+            self.ask_hook_approval();
+            return;
+        }
+
+        self.spawn_hook(invocation);
+    }
+
+    /// Whether the named profile was imported with hooks not yet allowed.
+    fn hooks_untrusted(&self, profile: &str) -> bool {
+        self.config
+            .connections
+            .iter()
+            .any(|p| p.name == profile && p.hooks.untrusted)
+    }
+
+    /// Ask about the first hook waiting for approval once the confirm
+    /// dialog is free. A question closed without approving declines it.
+    fn ask_hook_approval(&mut self) {
+        if self.asking_hook {
+            let asking = self.confirm_dialog.visible
+                && matches!(
+                    self.confirm_dialog.on_confirm.as_deref(),
+                    Some(Action::ApproveHooks)
+                );
+            if asking {
+                return;
+            }
+            self.asking_hook = false;
+            if let Some(declined) = self.hook_approvals.pop_front() {
+                let invocation = declined.invocation;
+                match declined.connect {
+                    Some(held) if invocation.is_required() => self.push_error(format!(
+                        "Not connecting to {}: required hook {} was not allowed",
+                        held.profile.host, invocation.event
+                    )),
+                    held => {
+                        self.push_message(format!(
+                            "Hook {} for profile '{}' not run: not allowed",
+                            invocation.event, invocation.profile
+                        ));
+                        if let Some(held) = held {
+                            self.begin_connect(held.profile, held.password, held.origin, None);
                         }
-                    });
+                    }
                 }
             }
         }
+        if self.confirm_dialog.visible {
+            return;
+        }
+        let Some(next) = self.hook_approvals.front() else {
+            return;
+        };
+        let msg = format!(
+            "Profile '{}' was imported with hooks. Allow it to run {}: `{}`?",
+            next.invocation.profile, next.invocation.event, next.invocation.command.command
+        );
+        self.confirm_dialog.show(msg, Action::ApproveHooks);
+        self.asking_hook = true;
     }
 
-    fn spawn_load_entry(&self, conn_id: ConnectionId, dn: String) {
-        let tab = self.tabs.iter().find(|t| t.id == conn_id);
-        if let Some(tab) = tab {
-            let tx = self.action_tx.clone();
+    fn spawn_hook(&mut self, invocation: HookInvocation) {
+        self.log_panel.push_debug(format!(
+            "Running hook {} for profile '{}'",
+            invocation.event, invocation.profile
+        ));
+        let tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let outcome = loom_core::hooks::run_hook(&invocation).await;
+            let _ = tx.send(Action::HookFinished(Box::new(outcome)));
+        });
+    }
 
-            match &tab.backend {
-                TabBackend::Offline(dir) => match dir.entry(&dn) {
-                    Some(entry) => {
-                        let _ = tx.send(Action::EntryLoaded(conn_id, entry));
-                    }
+    /// Carry out a `:` command, mostly by sending the action its key would.
+    fn run_command(&mut self, command: ColonCommand) {
+        let action = match command {
+            ColonCommand::Doctor => Action::ShowDoctor,
+            ColonCommand::Setup => Action::ShowSetup,
+            ColonCommand::Messages => Action::ShowMessages,
+            ColonCommand::LayoutReset => Action::ResetLayout,
+            ColonCommand::Apply(path) => Action::ShowApplyLdifDialog(path),
+            ColonCommand::Search(filter) => Action::SearchExecute(filter),
+            ColonCommand::Children(args) => {
+                self.apply_child_query(&args);
+                return;
+            }
+            ColonCommand::Migrate => {
+                if self.active_tab_id.is_none() {
+                    self.push_error("No active connection".to_string());
+                } else if !self.search_dialog.has_results() {
+                    self.push_error(
+                        "Run a search first; :migrate works on its results".to_string(),
+                    );
+                } else {
+                    self.migrate_dialog.show(self.search_dialog.results.clone());
+                }
+                return;
+            }
+            ColonCommand::Connect(name) => {
+                let names = self.connectable_profiles();
+                match names.iter().position(|n| n.eq_ignore_ascii_case(&name)) {
+                    Some(index) => Action::ConnectByIndex(index),
                     None => {
-                        let _ = tx.send(Action::ErrorMessage(format!("Entry not found: {}", dn)));
+                        let suggestion =
+                            command_line::did_you_mean(&name, names.iter().map(String::as_str))
+                                .map(|n| format!("; did you mean '{}'?", n))
+                                .unwrap_or_default();
+                        self.push_error(format!("No profile named '{}'{}", name, suggestion));
+                        return;
                     }
-                },
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
-                    tokio::spawn(async move {
-                        let mut conn = connection.lock().await;
-                        let result = match conn.search_entry(&dn).await {
-                            Ok(entry) => Ok(entry),
-                            Err(e) if LdapConnection::is_connection_error(&e) => {
-                                let _ =
+                }
+            }
+            ColonCommand::Delete(dn) => match dn.or_else(|| self.tree_panel.selected_entry_dn()) {
+                Some(dn) if self.active_tab_id.is_some() => Action::ShowDeleteDialog(dn),
+                Some(_) => {
+                    self.push_error("No active connection".to_string());
+                    return;
+                }
+                None => {
+                    self.push_error("Select an entry in the tree first".to_string());
+                    return;
+                }
+            },
+            ColonCommand::Export { path, attributes } => {
+                let Some(tab) = self.active_tab() else {
+                    self.push_error("No active connection".to_string());
+                    return;
+                };
+                Action::ExportExecute {
+                    base_dn: tab.directory_tree.root_dn.clone(),
+                    path,
+                    filter: "(objectClass=*)".to_string(),
+                    attributes,
+                    timestamps: None,
+                    decode: false,
+                    csv: Default::default(),
+                    format: None,
+                    source: ExportSource::Search,
+                    scope: Default::default(),
+                    include_base: true,
+                }
+            }
+            ColonCommand::Base(dn) => {
+                self.set_base(dn);
+                return;
+            }
+            ColonCommand::Set { key, value } => {
+                self.apply_setting(&key, &value);
+                return;
+            }
+            ColonCommand::SetSave => {
+                self.save_settings();
+                return;
+            }
+            ColonCommand::ReloadConfig => {
+                self.reload_config();
+                return;
+            }
+        };
+        let _ = self.action_tx.send(action);
+    }
+
+    /// Names of the profiles `:connect` knows, in the order `ConnectByIndex`
+    /// counts them.
+    fn connectable_profiles(&self) -> Vec<String> {
+        let mut names = self.profile_names();
+        names.push(example_profile().name);
+        names
+    }
+
+    /// Re-root the active tab's tree at `dn`; searches start there too.
+    fn set_base(&mut self, dn: String) {
+        let Some(tab) = self.active_tab_mut() else {
+            self.push_error("No active connection".to_string());
+            return;
+        };
+        let id = tab.id;
+        tab.directory_tree = DirectoryTree::new(dn.clone());
+        self.tree_panel.tree_state = tui_tree_widget::TreeState::default();
+        self.detail_panel.clear();
+        self.sync_status_context();
+        self.push_message(format!("Base DN is now {}", dn));
+        self.spawn_load_children(id, dn);
+    }
+
+    /// Change a setting for the active connection until it is closed.
+    fn apply_setting(&mut self, key: &str, value: &str) {
+        match key {
+            "page_size" => {
+                let Some(size) = value.parse::<u32>().ok().filter(|n| *n > 0) else {
+                    self.push_error(format!(
+                        "page_size must be a positive number, not '{}'",
+                        value
+                    ));
+                    return;
+                };
+                let Some(TabBackend::Live(connection)) = self.active_tab().map(|t| &t.backend)
+                else {
+                    self.push_error("page_size needs a live connection".to_string());
+                    return;
+                };
+                let connection = connection.clone();
+                tokio::spawn(async move {
+                    connection.lock().await.settings.page_size = size;
+                });
+                self.push_message(format!("Searches now ask for {} entries per page", size));
+            }
+            "write" => self.set_write(value),
+            _ if settings::SETTINGS.iter().any(|s| s.name == key) => {
+                let old = self.config.clone();
+                match self.overrides.set(&mut self.config, key, value) {
+                    Ok(setting) => {
+                        self.apply_config_changes(&old);
+                        let takes = match setting.takes {
+                            Takes::Now => "",
+                            Takes::NewConnections => " on new connections",
+                            Takes::Restart => " after a restart",
+                        };
+                        self.push_message(format!(
+                            "{} is {} for this session{}; :set-save keeps it",
+                            key, value, takes
+                        ));
+                    }
+                    Err(e) => self.push_error(e),
+                }
+            }
+            _ => {
+                let suggestion = command_line::did_you_mean(key, command_line::setting_names())
+                    .map(|s| format!("; did you mean {}?", s))
+                    .unwrap_or_default();
+                self.push_error(format!("Unknown setting '{}'{}", key, suggestion));
+            }
+        }
+    }
+
+    /// `:set-save`: keep the settings changed with `:set`, writing them
+    /// to the config file.
+    fn save_settings(&mut self) {
+        if self.overrides.is_empty() {
+            self.push_message("No settings changed with :set to save".to_string());
+            return;
+        }
+        let names = self.overrides.keep();
+        match self.save_config() {
+            Ok(()) => self.push_message(format!("Saved {}", names.join(", "))),
+            Err(e) => self.push_error(format!("Failed to save config: {}", e)),
+        }
+    }
+
+    /// Write the config, with the settings changed by `:set` as the file
+    /// had them. Edits made to the file since it was last read are read
+    /// first rather than written over.
+    fn save_config(&mut self) -> Result<(), String> {
+        let path = self
+            .config_path
+            .clone()
+            .ok_or_else(|| "Cannot determine config directory".to_string())?;
+        if self.config_watch.as_mut().is_some_and(|w| w.changed()) {
+            self.reload_config();
+        }
+        self.overrides.persisted(&self.config).save_to(&path)?;
+        if let Some(watch) = &mut self.config_watch {
+            watch.seen();
+        }
+        Ok(())
+    }
+
+    /// Read the config file again and apply what changed, keeping the
+    /// `:set` overrides on top. A file that does not parse changes nothing.
+    fn reload_config(&mut self) {
+        let Some(path) = self.config_path.clone() else {
+            self.push_error("Cannot determine config directory".to_string());
+            return;
+        };
+        // Once is enough to hear about a file that does not parse
+        if let Some(watch) = &mut self.config_watch {
+            watch.seen();
+        }
+        let mut config = match AppConfig::read(&path) {
+            Ok((config, _)) => config,
+            Err(e) => {
+                self.push_error(format!("Config not reloaded: {}", e));
+                return;
+            }
+        };
+        // The histories are newer in memory than in the file
+        config.first_launch = self.config.first_launch;
+        config.search_history = std::mem::take(&mut self.config.search_history);
+        config.command_history = std::mem::take(&mut self.config.command_history);
+        self.overrides.reapply(&mut config);
+        let old = std::mem::replace(&mut self.config, config);
+        let changes = self.apply_config_changes(&old);
+        if changes.is_empty() {
+            self.push_message("Config reloaded; nothing changed".to_string());
+        } else {
+            self.push_message(format!("Config reloaded: {}", changes.summary()));
+        }
+    }
+
+    /// Bring the running app in line with `self.config`, changed from
+    /// `old`. Settings read where they are used need nothing here; those
+    /// copied into components at startup are handed over again.
+    fn apply_config_changes(&mut self, old: &AppConfig) -> Changes {
+        let changes = Changes::between(old, &self.config);
+        if changes.contains("theme") {
+            match Styles::named(&self.config.general.theme) {
+                Ok(styles) => self.theme.set(styles),
+                Err(e) => self.push_error(format!("Theme {}; keeping the current theme", e)),
+            }
+        }
+        if changes.contains("keybindings") {
+            self.keymap = Keymap::from_config(&self.config.keybindings);
+            for problem in self.keymap.problems().to_vec() {
+                self.push_error(format!("[keybindings] {}", problem));
+            }
+            self.status_bar.set_keys(&self.keymap);
+            self.tree_panel.set_keys(&self.keymap);
+            self.detail_panel.set_keys(&self.keymap);
+            self.connections_tree.set_keys(&self.keymap);
+            self.search_dialog.set_keys(&self.keymap);
+            self.confirm_dialog.set_keys(&self.keymap);
+            self.log_panel.set_keys(&self.keymap);
+        }
+        let general = &self.config.general;
+        self.command_panel
+            .set_assists(general.autocomplete, general.live_search);
+        self.tree_panel.set_index_strip(general.index_strip);
+        self.search_dialog.set_index_strip(general.index_strip);
+        self.detail_panel.set_layout(self.config.detail.clone());
+        self.log_viewer.set_path(logging::log_path(general));
+        if changes
+            .new_connections
+            .iter()
+            .any(|s| s.starts_with("audit_"))
+        {
+            self.audit = audit_file(general);
+        }
+        let logged = match &self.logging {
+            Some(logging) => logging.apply(general),
+            None => Ok(()),
+        };
+        if let Err(e) = logged {
+            self.push_error(format!("[log] {}", e));
+        }
+        changes
+    }
+
+    /// `:set write on|off`: lift a read-only profile's flag on the active
+    /// tab, once its name is typed, or put it back.
+    fn set_write(&mut self, value: &str) {
+        let Some(tab) = self.active_tab() else {
+            self.push_error("No active connection".to_string());
+            return;
+        };
+        let (id, profile) = (tab.id, tab.profile.clone());
+        match value {
+            "on" | "off" if !tab.profile_read_only => {
+                self.push_message(format!("{} is not read-only", profile))
+            }
+            "on" if tab.write_override => {
+                self.push_message(format!("Writes are already allowed on {}", profile))
+            }
+            "on" => self.write_override_dialog.show(id, profile),
+            "off" => {
+                if let Some(tab) = self.active_tab_mut() {
+                    tab.set_write_override(false);
+                }
+                self.push_message(format!("{} is read-only again", profile));
+            }
+            _ => self.push_error(format!("write must be on or off, not '{}'", value)),
+        }
+    }
+
+    /// Apply a `:children [filter] [sort=attr]` query to the selected container
+    /// and reload it from the first page.
+    fn apply_child_query(&mut self, args: &str) {
+        let Some(dn) = self.tree_panel.selected_entry_dn() else {
+            self.push_error("Select a container in the tree first".to_string());
+            return;
+        };
+        let Some(tab) = self.active_tab() else {
+            self.push_error("No active connection".to_string());
+            return;
+        };
+        let query = match ChildQuery::parse(args) {
+            Ok(query) => query,
+            Err(e) => {
+                self.push_error(format!("Invalid children query: {}", e));
+                return;
+            }
+        };
+        if matches!(tab.backend, TabBackend::Offline(_)) && !query.is_empty() {
+            self.push_error("Filtering children needs a live connection".to_string());
+            return;
+        }
+        let id = tab.id;
+        let mut paging = tab
+            .directory_tree
+            .find_node(&dn)
+            .and_then(|n| n.paging.clone())
+            .unwrap_or_else(|| ChildPaging::new(self.config.general.child_page_size));
+        paging.reset();
+        paging.query = query;
+        self.spawn_load_child_page(id, dn, paging, 0);
+    }
+
+    /// Run the on_disconnect hook for a tab that is being closed.
+    fn trigger_disconnect_hook(&mut self, id: ConnectionId) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == id) else {
+            return;
+        };
+        if !matches!(tab.backend, TabBackend::Live(_)) {
+            return;
+        }
+        if let Some(invocation) = self.profile_hook(&tab.label, HookEvent::Disconnect) {
+            let invocation = invocation
+                .with_env("LOOM_HOST", &tab.host)
+                .with_env("LOOM_BASE_DN", &tab.directory_tree.root_dn);
+            self.trigger_hook(invocation);
+        }
+    }
+
+    /// Load a container's children, keeping its current page if it is paged.
+    fn spawn_load_children(&mut self, conn_id: ConnectionId, dn: String) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let (paging, page) = match tab
+            .directory_tree
+            .find_node(&dn)
+            .and_then(|n| n.paging.clone())
+        {
+            Some(paging) => {
+                let page = paging.page;
+                (paging, page)
+            }
+            None => (ChildPaging::new(self.config.general.child_page_size), 0),
+        };
+        self.spawn_load_child_page(conn_id, dn, paging, page);
+    }
+
+    /// Select the first sibling after `current` under the last DN of
+    /// `parent_path` whose name starts with the jump's prefix. A paged
+    /// container sorted by name loads the page the name would be on, and
+    /// the jump resumes once it arrives; `paged` is the way it went.
+    fn jump_among(
+        &mut self,
+        parent_path: Vec<String>,
+        current: Option<String>,
+        jump: Jump,
+        paged: Option<PageDirection>,
+    ) {
+        let Some(tab) = self.active_tab() else {
+            return;
+        };
+        let conn_id = tab.id;
+        let parent = match parent_path.last() {
+            Some(dn) => tab.directory_tree.find_node(dn),
+            None => Some(&tab.directory_tree.root),
+        };
+        let Some(parent) = parent else {
+            return;
+        };
+        let children = parent.children.as_deref().unwrap_or_default();
+        let names: Vec<&str> = children.iter().map(|c| c.display_name.as_str()).collect();
+        let current = current.and_then(|dn| children.iter().position(|c| c.dn == dn));
+
+        if let Some(i) = jump::find(&names, current, &jump) {
+            let dn = children[i].dn.clone();
+            let mut path = parent_path;
+            path.push(dn.clone());
+            self.tree_panel.tree_state.select(path);
+            self.status_bar
+                .set_message(format!("Jump: {}", jump.prefix));
+            self.spawn_load_entry(conn_id, dn);
+            return;
+        }
+
+        // Only a server-sorted listing says which page a name is on
+        let naming_attr = children
+            .first()
+            .and_then(|c| loom_core::dn::rdn(&c.dn).split('=').next())
+            .unwrap_or_default()
+            .trim();
+        let direction = parent
+            .paging
+            .as_ref()
+            .filter(|_| tab.server_side_sort && matches!(tab.backend, TabBackend::Live(_)))
+            .and_then(|paging| jump::page_for(paging, naming_attr, &names, &jump.prefix))
+            .filter(|direction| paged.map_or(true, |p| p == *direction));
+        match direction {
+            Some(direction) => {
+                let parent_dn = parent.dn.clone();
+                self.pending_jump = Some(PendingJump {
+                    conn_id,
+                    parent_dn: parent_dn.clone(),
+                    parent_path,
+                    jump,
+                    direction,
+                });
+                let _ = self.action_tx.send(Action::TreePage(parent_dn, direction));
+            }
+            None => self
+                .status_bar
+                .set_message(format!("No entry starting with '{}'", jump.prefix)),
+        }
+    }
+
+    /// Fetch one page of a container's children and send it as `TreePageLoaded`.
+    fn spawn_load_child_page(
+        &mut self,
+        conn_id: ConnectionId,
+        dn: String,
+        mut paging: ChildPaging,
+        page: usize,
+    ) {
+        let tab = self.tabs.iter_mut().find(|t| t.id == conn_id);
+        if let Some(tab) = tab {
+            tab.directory_tree.mark_loading(&dn);
+            let tx = self.action_tx.clone();
+
+            match &tab.backend {
+                TabBackend::Offline(dir) => {
+                    let nodes = paging.slice(dir.children(&dn), page);
+                    let _ = tx.send(Action::TreePageLoaded(conn_id, dn, nodes, Box::new(paging)));
+                }
+                TabBackend::Live(connection) => {
+                    let connection = connection.clone();
+                    let server_sort = tab.server_side_sort;
+                    let cancel_tx = tx.clone();
+                    let cancelled_dn = dn.clone();
+                    let op = tab.spawn_op(
+                        format!(
+                            "List children of {} (page {})",
+                            loom_core::dn::rdn_display_name(&dn),
+                            page + 1
+                        ),
+                        async move {
+                            let mut conn = connection.lock().await;
+                            let cookie = paging.cookie_for(page).map(|c| c.to_vec());
+                            let result = match conn
+                                .search_children_page_at(
+                                    &dn,
+                                    &paging.query,
+                                    paging.page_size,
+                                    page,
+                                    cookie.as_deref(),
+                                    server_sort,
+                                )
+                                .await
+                            {
+                                Ok(result) => Ok(result),
+                                Err(e) if LdapConnection::is_connection_error(&e) => {
+                                    let _ = tx
+                                        .send(Action::StatusMessage("Reconnecting...".to_string()));
+                                    if conn.reconnect().await.is_ok() {
+                                        // Paging cookies do not survive a reconnect
+                                        conn.search_children_page_at(
+                                            &dn,
+                                            &paging.query,
+                                            paging.page_size,
+                                            page,
+                                            None,
+                                            server_sort,
+                                        )
+                                        .await
+                                    } else {
+                                        Err(e)
+                                    }
+                                }
+                                Err(e) => Err(e),
+                            };
+
+                            match result {
+                                Ok(result) => {
+                                    info!(
+                                        "Loaded {} child objects under '{}' (page {})",
+                                        result.entries.len(),
+                                        dn,
+                                        page + 1
+                                    );
+                                    match result.total {
+                                        Some(total) => {
+                                            paging.record_window(page, result.entries.len(), total)
+                                        }
+                                        None => paging.record_page(page, result.next_cookie),
+                                    }
+                                    if paging.is_needed() && paging.total_hint.is_none() {
+                                        paging.total_hint = conn.count_children_hint(&dn).await;
+                                    }
+                                    let nodes: Vec<TreeNode> = result
+                                        .entries
+                                        .iter()
+                                        .map(|e| TreeNode::new(e.dn.clone()))
+                                        .collect();
+                                    let _ = tx.send(Action::TreePageLoaded(
+                                        conn_id,
+                                        dn,
+                                        nodes,
+                                        Box::new(paging),
+                                    ));
+                                }
+                                Err(e) => {
+                                    error!("Failed to load children of '{}': {}", dn, e);
+                                    let _ =
+                                        tx.send(Action::TreeLoadFailed(conn_id, dn, e.to_string()));
+                                }
+                            }
+                        },
+                    );
+                    tab.ops.on_cancel(
+                        op,
+                        Box::new(move || {
+                            let _ = cancel_tx.send(Action::TreeLoadFailed(
+                                conn_id,
+                                cancelled_dn,
+                                "cancelled".to_string(),
+                            ));
+                        }),
+                    );
+                }
+            }
+        }
+    }
+
+    fn spawn_load_entry(&self, conn_id: ConnectionId, dn: String) {
+        let missing = Action::ErrorMessage(format!("Entry not found: {}", dn));
+        self.spawn_read_entry(conn_id, dn, missing, None);
+    }
+
+    /// Load an entry into the detail panel, then send `then`. When there
+    /// is no such entry, `missing` is sent instead and `then` dropped.
+    fn spawn_read_entry(
+        &self,
+        conn_id: ConnectionId,
+        dn: String,
+        missing: Action,
+        then: Option<Action>,
+    ) {
+        let tab = self.tabs.iter().find(|t| t.id == conn_id);
+        if let Some(tab) = tab {
+            let tx = self.action_tx.clone();
+
+            match &tab.backend {
+                TabBackend::Offline(dir) => match dir.entry(&dn) {
+                    Some(entry) => {
+                        let tracked = TrackedEntry::split(entry);
+                        let _ =
+                            tx.send(Action::EntryLoaded(conn_id, tracked.entry, tracked.marker));
+                        if let Some(then) = then {
+                            let _ = tx.send(then);
+                        }
+                    }
+                    None => {
+                        let _ = tx.send(missing);
+                    }
+                },
+                TabBackend::Live(connection) => {
+                    let connection = connection.clone();
+                    tab.spawn_op(format!("Read {}", dn), async move {
+                        let mut conn = connection.lock().await;
+                        let result = match conn.search_entry_tracked(&dn).await {
+                            Ok(entry) => Ok(entry),
+                            Err(e) if LdapConnection::is_connection_error(&e) => {
+                                let _ =
                                     tx.send(Action::StatusMessage("Reconnecting...".to_string()));
                                 if conn.reconnect().await.is_ok() {
-                                    conn.search_entry(&dn).await
+                                    conn.search_entry_tracked(&dn).await
                                 } else {
                                     Err(e)
                                 }
@@ -523,12 +2009,18 @@ impl App {
                         };
 
                         match result {
-                            Ok(Some(entry)) => {
-                                let _ = tx.send(Action::EntryLoaded(conn_id, entry));
+                            Ok(Some(tracked)) => {
+                                let _ = tx.send(Action::EntryLoaded(
+                                    conn_id,
+                                    tracked.entry,
+                                    tracked.marker,
+                                ));
+                                if let Some(then) = then {
+                                    let _ = tx.send(then);
+                                }
                             }
                             Ok(None) => {
-                                let _ = tx
-                                    .send(Action::ErrorMessage(format!("Entry not found: {}", dn)));
+                                let _ = tx.send(missing);
                             }
                             Err(e) => {
                                 let _ = tx.send(Action::ErrorMessage(format!(
@@ -543,28 +2035,65 @@ impl App {
         }
     }
 
-    fn spawn_search(&self, conn_id: ConnectionId, filter: String) {
+    /// Run a search, sending each page of results as a batch for search
+    /// `generation`, then `SearchComplete`.
+    fn spawn_search(&mut self, conn_id: ConnectionId, generation: u64, request: SearchRequest) {
+        let stop = Arc::new(AtomicBool::new(false));
+        self.search_stop = Some((conn_id, generation, stop.clone()));
+        self.last_search = Some((conn_id, request.clone()));
+        let SearchRequest {
+            base_dn,
+            scope,
+            filter,
+            attrs,
+            sort,
+        } = request;
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
-            let base_dn = tab.directory_tree.root_dn.clone();
             let tx = self.action_tx.clone();
 
             match &tab.backend {
                 TabBackend::Offline(dir) => {
-                    let entries = dir.search(&base_dn, &filter);
-                    let _ = tx.send(Action::SearchResults(conn_id, entries));
+                    let mut entries = dir.search(&base_dn, &filter);
+                    entries.retain(|e| match scope {
+                        Scope::Base => e.dn.eq_ignore_ascii_case(&base_dn),
+                        Scope::OneLevel => loom_core::dn::parent_dn(&e.dn)
+                            .is_some_and(|parent| parent.eq_ignore_ascii_case(&base_dn)),
+                        Scope::Subtree => true,
+                    });
+                    let _ = tx.send(Action::SearchResults(conn_id, generation, entries));
+                    let _ = tx.send(Action::SearchComplete(conn_id, generation));
                 }
                 TabBackend::Live(connection) => {
                     let connection = connection.clone();
-                    tokio::spawn(async move {
+                    let op = tab.spawn_op(format!("Search {}", filter), async move {
                         let mut conn = connection.lock().await;
-                        let result = match conn.search_subtree(&base_dn, &filter, &["*"]).await {
-                            Ok(entries) => Ok(entries),
-                            Err(e) if LdapConnection::is_connection_error(&e) => {
+                        let attrs: Vec<&str> = attrs.iter().map(String::as_str).collect();
+                        let options = PageOptions {
+                            sort: sort.as_ref().map(|(attr, desc)| (attr.as_str(), *desc)),
+                            stop: Some(&stop),
+                        };
+                        let mut pages = 0;
+                        let send_page = |page: Vec<LdapEntry>| {
+                            let _ = tx.send(Action::SearchResults(conn_id, generation, page));
+                        };
+                        let first = conn
+                            .search_pages_with(&base_dn, scope, &filter, &attrs, options, |page| {
+                                pages += 1;
+                                send_page(page)
+                            })
+                            .await;
+                        let result = match first {
+                            Ok(n) => Ok(n),
+                            // Retry only if nothing was delivered yet
+                            Err(e) if pages == 0 && LdapConnection::is_connection_error(&e) => {
                                 let _ =
                                     tx.send(Action::StatusMessage("Reconnecting...".to_string()));
                                 if conn.reconnect().await.is_ok() {
-                                    conn.search_subtree(&base_dn, &filter, &["*"]).await
+                                    conn.search_pages_with(
+                                        &base_dn, scope, &filter, &attrs, options, send_page,
+                                    )
+                                    .await
                                 } else {
                                     Err(e)
                                 }
@@ -572,18 +2101,92 @@ impl App {
                             Err(e) => Err(e),
                         };
 
-                        match result {
-                            Ok(entries) => {
-                                let _ = tx.send(Action::SearchResults(conn_id, entries));
-                            }
-                            Err(e) => {
-                                let _ =
-                                    tx.send(Action::ErrorMessage(format!("Search failed: {}", e)));
-                            }
+                        if let Err(e) = result {
+                            let _ =
+                                tx.send(Action::SearchFailed(conn_id, generation, e.to_string()));
                         }
+                        let _ = tx.send(Action::SearchComplete(conn_id, generation));
                     });
+                    let cancel_tx = self.action_tx.clone();
+                    tab.ops.on_cancel(
+                        op,
+                        Box::new(move || {
+                            let _ = cancel_tx.send(Action::SearchFailed(
+                                conn_id,
+                                generation,
+                                "Cancelled".to_string(),
+                            ));
+                        }),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Move the next batch of queued search results into the results
+    /// table, at most `ingest_entries_per_tick` per frame. Returns how many
+    /// entries were moved.
+    fn ingest_results(&mut self) -> usize {
+        let Some(conn_id) = self.result_ingest.conn_id() else {
+            return 0;
+        };
+        if self.active_tab_id != Some(conn_id) {
+            self.result_ingest.cancel();
+            return 0;
+        }
+        let options = MergeOptions {
+            case_insensitive_values: self.config.general.dedup_ignore_value_case,
+        };
+        let schema = self
+            .tabs
+            .iter()
+            .find(|t| t.id == conn_id)
+            .and_then(|t| t.schema.as_ref());
+        let first_batch = self.search_dialog.results.is_empty();
+        let taken = self.result_ingest.drain_into(
+            &mut self.search_dialog.results,
+            self.config.general.ingest_entries_per_tick.max(1),
+            &options,
+            schema,
+        );
+        if taken > 0 {
+            self.search_dialog.results_appended(first_batch);
+        }
+
+        let shown = self.search_dialog.results.len();
+        if self.result_ingest.take_completed() {
+            let mut summary = self.result_ingest.summary(shown);
+            if let Some((_, _, stop)) = self.search_stop.take() {
+                if stop.load(Ordering::Relaxed) {
+                    summary = format!("Search stopped. {}", summary);
+                }
+            }
+            self.status_bar.set_message(summary);
+            self.search_dialog.complete();
+            if shown == 0 {
+                self.search_dialog.visible = true;
+            }
+        } else if let Some(line) = self.result_ingest.status_line(shown) {
+            self.status_bar.set_message(line);
+        }
+        taken
+    }
+
+    fn handle_app_event(&mut self, app_event: AppEvent) {
+        match app_event {
+            AppEvent::Key(key) => self.handle_key_event(key),
+            AppEvent::Mouse(mouse) => {
+                let action = self.handle_mouse(mouse);
+                if !matches!(action, Action::None) {
+                    let _ = self.action_tx.send(action);
                 }
             }
+            AppEvent::Resize(w, h) => {
+                let _ = self.action_tx.send(Action::Resize(w, h));
+            }
+            AppEvent::Tick => {
+                let _ = self.action_tx.send(Action::Tick);
+            }
         }
     }
 
@@ -606,12 +2209,15 @@ impl App {
                 }
                 TabBackend::Live(connection) => {
                     let connection = connection.clone();
-                    tokio::spawn(async move {
+                    tab.spawn_op(format!("Modify {}", result.dn), async move {
                         debug!(
                             "spawn_save_attribute: dn={} op={:?} new_value={}",
                             result.dn, result.op, result.new_value
                         );
                         let mut conn = connection.lock().await;
+                        let before = read_before_image(&mut conn, &result.dn).await;
+                        let inverse =
+                            before.and_then(|before| invert_change(&before, &result.change()));
                         let modify_result = match &result.op {
                             EditOp::Replace { attr, old_value } => {
                                 conn.replace_attribute_value(
@@ -633,6 +2239,13 @@ impl App {
 
                         match modify_result {
                             Ok(()) => {
+                                if let Some(inverse) = inverse {
+                                    let step = UndoStep::new(
+                                        format!("Modify {}", result.dn),
+                                        vec![inverse],
+                                    );
+                                    let _ = tx.send(Action::PushUndo(conn_id, step));
+                                }
                                 let _ = tx.send(Action::AttributeSaved(result.dn));
                             }
                             Err(e) => {
@@ -668,7 +2281,7 @@ impl App {
                         "spawn_load_schema: conn_id={}, subschema_dn={:?}",
                         conn_id, subschema_dn
                     );
-                    tokio::spawn(async move {
+                    tab.spawn_op("Load schema", async move {
                         let mut conn = connection.lock().await;
                         match conn.load_schema(subschema_dn.as_deref()).await {
                             Ok(schema) => {
@@ -699,108 +2312,594 @@ impl App {
         }
     }
 
-    /// Expand a user-provided file path:
-    /// - Replace leading `~` with the user's home directory
-    /// - Create parent directories if they don't exist
-    fn expand_export_path(raw: &str) -> Result<PathBuf, String> {
-        let expanded = if raw.starts_with("~/") || raw.starts_with("~\\") {
-            if let Some(home) = dirs::home_dir() {
-                home.join(&raw[2..])
-            } else {
-                return Err("Could not determine home directory".to_string());
-            }
-        } else if raw == "~" {
-            return Err("Filename is required, not just '~'".to_string());
-        } else {
-            PathBuf::from(raw)
+    /// Write a snapshot of `base_dn` in the background, reporting to the snapshot dialog.
+    fn spawn_snapshot(&mut self, conn_id: ConnectionId, base_dn: String, path: String) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
         };
-
-        if let Some(parent) = expanded.parent() {
-            if !parent.as_os_str().is_empty() && !parent.exists() {
-                std::fs::create_dir_all(parent).map_err(|e| {
-                    format!("Failed to create directory {}: {}", parent.display(), e)
-                })?;
+        let TabBackend::Live(ref connection) = tab.backend else {
+            self.snapshot_dialog.hide();
+            self.push_error("Snapshots need a live connection".to_string());
+            return;
+        };
+        let filepath = match Self::expand_export_path(&path) {
+            Ok(p) => p,
+            Err(e) => {
+                self.snapshot_dialog.hide();
+                self.push_error(format!("Snapshot failed: {}", e));
+                return;
             }
-        }
-
-        Ok(expanded)
+        };
+        let connection = connection.clone();
+        let profile = tab.label.clone();
+        let tx = self.action_tx.clone();
+        let op = tab.spawn_op(format!("Snapshot of {}", base_dn), async move {
+            let mut conn = connection.lock().await;
+            let progress_tx = tx.clone();
+            let result = snapshot::create(&mut conn, &profile, &base_dn, &filepath, |p| {
+                let _ = progress_tx.send(Action::SnapshotProgress(p));
+            })
+            .await;
+            let _ = tx.send(match result {
+                Ok(manifest) => Action::SnapshotComplete {
+                    path: filepath.display().to_string(),
+                    manifest,
+                },
+                Err(e) => Action::SnapshotFailed(format!("Snapshot failed: {}", e)),
+            });
+        });
+        // Cancelled from the operations list: close the dialog too
+        let cancel_tx = self.action_tx.clone();
+        tab.ops.on_cancel(
+            op,
+            Box::new(move || {
+                let _ = cancel_tx.send(Action::SnapshotCancel);
+            }),
+        );
+        self.snapshot_task = Some((conn_id, op));
     }
 
-    fn spawn_export(
-        &self,
+    /// Compare `base_dn` on `conn_id` with the same subtree on `other` in
+    /// the background, reporting progress to the compare dialog. Both
+    /// connections stay locked until the comparison ends.
+    fn spawn_subtree_compare(
+        &mut self,
         conn_id: ConnectionId,
-        path: String,
+        other: ConnectionId,
         base_dn: String,
-        filter: String,
-        attributes: Vec<String>,
+        options: SubtreeCompareOptions,
     ) {
-        let tab = self.tabs.iter().find(|t| t.id == conn_id);
-        if let Some(tab) = tab {
-            let tx = self.action_tx.clone();
+        let find = |id| self.tabs.iter().find(|t| t.id == id);
+        let (Some(tab), Some(other_tab)) = (find(conn_id), find(other)) else {
+            self.subtree_compare_dialog.hide();
+            self.push_error("The other connection was closed".to_string());
+            return;
+        };
+        let (TabBackend::Live(left), TabBackend::Live(right)) = (&tab.backend, &other_tab.backend)
+        else {
+            self.subtree_compare_dialog.hide();
+            self.push_error("Comparing subtrees needs two live connections".to_string());
+            return;
+        };
+        let (left, right) = (left.clone(), right.clone());
+        let other_label = other_tab.label.clone();
+        let schema = tab.schema.clone();
+        let tx = self.action_tx.clone();
+        let op = tab.spawn_op(
+            format!("Compare {} with {}", base_dn, other_label),
+            async move {
+                let mut left = left.lock().await;
+                let mut right = right.lock().await;
+                let progress_tx = tx.clone();
+                let result = compare_subtrees(
+                    &mut *left,
+                    &mut *right,
+                    &base_dn,
+                    &options,
+                    schema.as_ref(),
+                    |p| {
+                        let _ = progress_tx.send(Action::SubtreeCompareProgress(p));
+                    },
+                )
+                .await;
+                let _ = tx.send(match result {
+                    Ok(report) => Action::SubtreeCompareComplete(Box::new(report)),
+                    Err(e) => {
+                        Action::SubtreeCompareFailed(format!("Subtree comparison failed: {}", e))
+                    }
+                });
+            },
+        );
+        // Cancelled from the operations list: close the dialog too
+        let cancel_tx = self.action_tx.clone();
+        tab.ops.on_cancel(
+            op,
+            Box::new(move || {
+                let _ = cancel_tx.send(Action::SubtreeCompareCancel);
+            }),
+        );
+        self.subtree_compare_task = Some((conn_id, op, other_label));
+    }
 
-            let filepath = match Self::expand_export_path(&path) {
-                Ok(p) => p,
-                Err(e) => {
-                    let _ = tx.send(Action::ErrorMessage(format!("Export failed: {}", e)));
-                    return;
+    /// Apply a changefile's records to `conn_id` in the background,
+    /// reporting progress to the apply dialog. A read-only connection
+    /// only takes a dry run.
+    fn spawn_apply_ldif(
+        &mut self,
+        conn_id: ConnectionId,
+        changes: Vec<Change>,
+        options: ApplyOptions,
+    ) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let refusal = match tab.backend {
+            TabBackend::Offline(_) => Some("Applying a changefile needs a live connection"),
+            _ if tab.read_only && !options.dry_run => {
+                Some("Connection is read-only; only a dry run is possible")
+            }
+            TabBackend::Live(_) => None,
+        };
+        if let Some(refusal) = refusal {
+            self.apply_ldif_dialog.hide();
+            self.push_error(refusal.to_string());
+            return;
+        }
+        let TabBackend::Live(ref connection) = tab.backend else {
+            return;
+        };
+        let connection = connection.clone();
+        let protected = tab.protected.clone();
+        let tx = self.action_tx.clone();
+        let label = if options.dry_run {
+            format!("Dry run of {} LDIF records", changes.len())
+        } else {
+            format!("Apply {} LDIF records", changes.len())
+        };
+        let op = tab.spawn_op(label, async move {
+            let mut conn = connection.lock().await;
+            let progress_tx = tx.clone();
+            let report =
+                apply_changefile(&mut *conn, &changes, options, &protected, |done, total| {
+                    let _ = progress_tx.send(Action::ApplyLdifProgress(done, total));
+                })
+                .await;
+            let _ = tx.send(Action::ApplyLdifComplete(Box::new(report)));
+        });
+        // Cancelled from the operations list: close the dialog too
+        let cancel_tx = self.action_tx.clone();
+        tab.ops.on_cancel(
+            op,
+            Box::new(move || {
+                let _ = cancel_tx.send(Action::ApplyLdifCancel);
+            }),
+        );
+        self.apply_ldif_task = Some((conn_id, op));
+    }
+
+    /// Expand a user-provided file path:
+    /// - Expand `~`, `~user` and environment variables via [`expand_path`]
+    /// - Refuse a bare `~`, or a `~` whose home directory cannot be found
+    /// - Create parent directories if they don't exist
+    fn expand_export_path(raw: &str) -> Result<PathBuf, String> {
+        if raw == "~" {
+            return Err("Filename is required, not just '~'".to_string());
+        }
+        let expanded = expand_path(raw);
+        if raw.starts_with('~') && expanded.as_os_str() == raw {
+            return Err(format!("Could not find the home directory in {}", raw));
+        }
+
+        if let Some(parent) = expanded.parent() {
+            if !parent.as_os_str().is_empty() && !parent.exists() {
+                std::fs::create_dir_all(parent).map_err(|e| {
+                    format!("Failed to create directory {}: {}", parent.display(), e)
+                })?;
+            }
+        }
+
+        Ok(expanded)
+    }
+
+    /// Write an export in the background, reporting progress to the export
+    /// dialog. Cancelling stops the write and removes the partial file.
+    fn spawn_export(&mut self, conn_id: ConnectionId, request: ExportRequest) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        let fail = |tx: &tokio::sync::mpsc::UnboundedSender<Action>,
+                    error: String,
+                    request: ExportRequest| {
+            let _ = tx.send(Action::ExportFailed { error, request });
+        };
+
+        let filepath = match Self::expand_export_path(&request.path) {
+            Ok(p) => p,
+            Err(e) => {
+                fail(&tx, format!("Export failed: {}", e), request);
+                return;
+            }
+        };
+        let display_path = filepath.display().to_string();
+        let formatter = match request
+            .timestamps
+            .as_ref()
+            .map(|t| t.formatter())
+            .transpose()
+        {
+            Ok(f) => f,
+            Err(e) => {
+                fail(&tx, format!("Export failed: {}", e), request);
+                return;
+            }
+        };
+        let Some(format) = request
+            .format
+            .or_else(|| ExportFormat::from_path(&filepath))
+        else {
+            fail(
+                &tx,
+                "Export failed: Unknown file extension".to_string(),
+                request,
+            );
+            return;
+        };
+        match request.source {
+            ExportSource::Subtree => {
+                return self.spawn_subtree_export(conn_id, request, filepath, format, formatter)
+            }
+            ExportSource::Table => {
+                return self.spawn_table_export(conn_id, request, filepath, format)
+            }
+            _ => {}
+        }
+        let schema = tab.schema.clone();
+        let attributes = request.attributes.clone();
+        let csv_options = request.csv.clone();
+        let decode = request.decode;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let write_cancel = cancel.clone();
+        let progress_tx = tx.clone();
+
+        // Apply timestamp formatting and decoding, then write the file
+        let write = move |entries: Vec<LdapEntry>| -> Result<Action, CoreError> {
+            let (entries, format_report) = match formatter {
+                Some(ref f) => {
+                    let (formatted, report) = format_timestamps(&entries, f, schema.as_ref());
+                    (formatted, Some(report))
                 }
+                None => (entries, None),
+            };
+            // After the timestamps, which need FILETIMEs as integers
+            let entries = if decode {
+                entries.iter().map(decode_entry).collect()
+            } else {
+                entries
             };
-            let display_path = filepath.display().to_string();
+            let mut report = loom_core::export::export_with_progress(
+                &entries,
+                &filepath,
+                format,
+                &attributes,
+                &csv_options,
+                &write_cancel,
+                |done, total| {
+                    let _ = progress_tx.send(Action::ExportProgress { done, total });
+                },
+            )?;
+            let message = export_message(&mut report, format_report);
+            Ok(Action::ExportComplete { report, message })
+        };
 
-            match &tab.backend {
-                TabBackend::Offline(dir) => {
-                    let entries = dir.search(&base_dn, &filter);
-                    match loom_core::export::export_entries(&entries, &filepath, &attributes) {
-                        Ok(count) => {
-                            let _ = tx.send(Action::ExportComplete(format!(
-                                "Exported {} entries to {}",
-                                count, display_path
-                            )));
-                        }
+        // Loaded results and offline entries are at hand; a live tab
+        // searches for them first
+        let (entries, connection) = match (request.source, &tab.backend) {
+            (ExportSource::FilteredResults, _) => (self.search_dialog.filtered_results(), None),
+            (ExportSource::AllResults, _) => (self.search_dialog.results.clone(), None),
+            (ExportSource::SelectedResults, _) => (self.search_dialog.selected_results(), None),
+            (ExportSource::Groups, _) => (
+                self.membership_popup
+                    .membership()
+                    .map(Membership::to_entries)
+                    .unwrap_or_default(),
+                None,
+            ),
+            (
+                ExportSource::Search | ExportSource::Subtree | ExportSource::Table,
+                TabBackend::Offline(dir),
+            ) => (dir.search(&request.base_dn, &request.filter), None),
+            (
+                ExportSource::Search | ExportSource::Subtree | ExportSource::Table,
+                TabBackend::Live(connection),
+            ) => (Vec::new(), Some(connection.clone())),
+        };
+        let op = tab.spawn_op(format!("Export to {}", request.path), async move {
+            let live = connection.is_some();
+            let entries = match connection {
+                None => entries,
+                Some(connection) => {
+                    let mut conn = connection.lock().await;
+                    let attr_refs: Vec<&str> =
+                        request.attributes.iter().map(|s| s.as_str()).collect();
+                    let result = conn
+                        .search_subtree(&request.base_dn, &request.filter, &attr_refs)
+                        .await;
+                    match result {
+                        Ok(entries) => entries,
                         Err(e) => {
-                            let _ = tx.send(Action::ErrorMessage(format!("Export failed: {}", e)));
+                            fail(&tx, format!("Export search failed: {}", e), request);
+                            return;
                         }
                     }
                 }
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
-                    tokio::spawn(async move {
-                        let mut conn = connection.lock().await;
-                        let attr_refs: Vec<&str> = attributes.iter().map(|s| s.as_str()).collect();
-                        match conn.search_subtree(&base_dn, &filter, &attr_refs).await {
-                            Ok(entries) => {
-                                match loom_core::export::export_entries(
-                                    &entries,
-                                    &filepath,
-                                    &attributes,
-                                ) {
-                                    Ok(count) => {
-                                        let _ = tx.send(Action::ExportComplete(format!(
-                                            "Exported {} entries to {}",
-                                            count, display_path
-                                        )));
-                                    }
-                                    Err(e) => {
-                                        let _ = tx.send(Action::ErrorMessage(format!(
-                                            "Export failed: {}",
-                                            e
-                                        )));
-                                    }
-                                }
-                            }
-                            Err(e) => {
-                                let _ = tx.send(Action::ErrorMessage(format!(
-                                    "Export search failed: {}",
-                                    e
-                                )));
-                            }
+            };
+            match tokio::task::spawn_blocking(move || write(entries)).await {
+                Ok(Ok(done)) => {
+                    let _ = tx.send(done);
+                    if live {
+                        let _ = tx.send(Action::ExportWritten {
+                            conn_id,
+                            path: display_path,
+                        });
+                    }
+                }
+                Ok(Err(CoreError::Cancelled)) => {}
+                Ok(Err(e)) => fail(&tx, format!("Export failed: {}", e), request),
+                Err(e) => fail(&tx, format!("Export failed: {}", e), request),
+            }
+        });
+        // Aborting the task leaves the blocking write running, so it also
+        // watches a flag. Cancelled from the operations list: close the
+        // dialog too
+        let cancel_tx = self.action_tx.clone();
+        tab.ops.on_cancel(
+            op,
+            Box::new(move || {
+                cancel.store(true, Ordering::Relaxed);
+                let _ = cancel_tx.send(Action::ExportCancel);
+            }),
+        );
+        self.export_task = Some((conn_id, op));
+    }
+
+    /// Write the search results table as it is shown -- its columns, in
+    /// its order, narrowed by the quick filter -- to a CSV or Excel file.
+    fn spawn_table_export(
+        &mut self,
+        conn_id: ConnectionId,
+        request: ExportRequest,
+        filepath: PathBuf,
+        format: ExportFormat,
+    ) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        let (header, rows) = self.search_dialog.table();
+        let op = tab.spawn_op(format!("Export to {}", request.path), async move {
+            let write = move || export_table(&header, &rows, &filepath, format);
+            let error = match tokio::task::spawn_blocking(write).await {
+                Ok(Ok(mut report)) => {
+                    let message = export_message(&mut report, None);
+                    let _ = tx.send(Action::ExportComplete { report, message });
+                    return;
+                }
+                Ok(Err(e)) => e.to_string(),
+                Err(e) => e.to_string(),
+            };
+            let error = format!("Export failed: {}", error);
+            let _ = tx.send(Action::ExportFailed { error, request });
+        });
+        self.export_task = Some((conn_id, op));
+    }
+
+    /// Stream the branch at the request's base DN to the file. A live tab
+    /// walks it with paged searches while the writer drains each page, so
+    /// only a few pages are held at once; there is no total to report.
+    fn spawn_subtree_export(
+        &mut self,
+        conn_id: ConnectionId,
+        request: ExportRequest,
+        filepath: PathBuf,
+        format: ExportFormat,
+        formatter: Option<TimestampFormat>,
+    ) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        let fail = |tx: &tokio::sync::mpsc::UnboundedSender<Action>,
+                    error: String,
+                    request: ExportRequest| {
+            let _ = tx.send(Action::ExportFailed { error, request });
+        };
+        let display_path = filepath.display().to_string();
+        let schema = tab.schema.clone();
+        let attributes = request.attributes.clone();
+        let csv_options = request.csv.clone();
+        let decode = request.decode;
+        let cancel = Arc::new(AtomicBool::new(false));
+        let write_cancel = cancel.clone();
+        // Set once the whole branch is sent; pages ending before then
+        // mean the walk failed or was aborted
+        let walked = Arc::new(AtomicBool::new(false));
+        let write_walked = walked.clone();
+        let progress_tx = tx.clone();
+        let (pages, entries) = page_channel();
+
+        let write = move || -> Result<Action, CoreError> {
+            let mut timestamps = formatter.as_ref().map(|_| FormatReport::default());
+            let entries = entries
+                .chain(std::iter::from_fn(|| {
+                    if !write_walked.load(Ordering::Relaxed) {
+                        write_cancel.store(true, Ordering::Relaxed);
+                    }
+                    None
+                }))
+                .map(|entry| {
+                    let entry = match (&formatter, timestamps.as_mut()) {
+                        (Some(f), Some(total)) => {
+                            let (mut formatted, report) =
+                                format_timestamps(std::slice::from_ref(&entry), f, schema.as_ref());
+                            total.formatted += report.formatted;
+                            total.unparseable += report.unparseable;
+                            formatted.pop().unwrap_or(entry)
                         }
-                    });
+                        _ => entry,
+                    };
+                    if decode {
+                        decode_entry(&entry)
+                    } else {
+                        entry
+                    }
+                });
+            let mut report = export_stream(
+                entries,
+                &filepath,
+                format,
+                &attributes,
+                &csv_options,
+                &write_cancel,
+                |done| {
+                    let _ = progress_tx.send(Action::ExportProgress { done, total: 0 });
+                },
+            )?;
+            let message = export_message(&mut report, timestamps);
+            Ok(Action::ExportComplete { report, message })
+        };
+
+        // Offline entries are at hand and go as a single page
+        let connection = match &tab.backend {
+            TabBackend::Offline(dir) => {
+                let branch: Vec<LdapEntry> = dir
+                    .search(&request.base_dn, "(objectClass=*)")
+                    .into_iter()
+                    .filter(|e| {
+                        request
+                            .scope
+                            .includes(&request.base_dn, &e.dn, request.include_base)
+                    })
+                    .collect();
+                pages.send(branch);
+                None
+            }
+            TabBackend::Live(connection) => Some(connection.clone()),
+        };
+        let op = tab.spawn_op(format!("Export to {}", request.path), async move {
+            let writer = tokio::task::spawn_blocking(write);
+            let live = connection.is_some();
+            if let Some(connection) = connection {
+                let result = search_subtree_pages(
+                    &mut *connection.lock().await,
+                    &request.base_dn,
+                    request.scope,
+                    request.include_base,
+                    &request.attributes,
+                    &pages,
+                )
+                .await;
+                if let Err(e) = result {
+                    // The writer sees the walk end early and removes the file
+                    drop(pages);
+                    let _ = writer.await;
+                    fail(&tx, format!("Export search failed: {}", e), request);
+                    return;
+                }
+            }
+            walked.store(true, Ordering::Relaxed);
+            drop(pages);
+            match writer.await {
+                Ok(Ok(done)) => {
+                    let _ = tx.send(done);
+                    if live {
+                        let _ = tx.send(Action::ExportWritten {
+                            conn_id,
+                            path: display_path,
+                        });
+                    }
                 }
+                Ok(Err(CoreError::Cancelled)) => {}
+                Ok(Err(e)) => fail(&tx, format!("Export failed: {}", e), request),
+                Err(e) => fail(&tx, format!("Export failed: {}", e), request),
+            }
+        });
+        let cancel_tx = self.action_tx.clone();
+        tab.ops.on_cancel(
+            op,
+            Box::new(move || {
+                cancel.store(true, Ordering::Relaxed);
+                let _ = cancel_tx.send(Action::ExportCancel);
+            }),
+        );
+        self.export_task = Some((conn_id, op));
+    }
+
+    /// Look up the groups `dn` belongs to in the background, for the
+    /// membership popup. Active Directory expands nested groups itself.
+    fn spawn_membership(&self, conn_id: ConnectionId, dn: String, nested: bool, max_depth: usize) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let active_directory = tab
+            .root_dse
+            .as_ref()
+            .is_some_and(|dse| dse.server_type == ServerType::ActiveDirectory);
+        let options = MembershipOptions::new(tab.directory_tree.root_dn.clone())
+            .with_nested(nested)
+            .with_max_depth(max_depth)
+            .with_in_chain(active_directory);
+        let label = format!("Groups of {}", loom_core::dn::rdn_display_name(&dn));
+        let tx = self.action_tx.clone();
+        let send = move |result: Result<Membership, CoreError>| {
+            let _ = tx.send(match result {
+                Ok(membership) => Action::GroupsResolved(conn_id, Box::new(membership)),
+                Err(e) => Action::GroupsFailed(format!("Looking up groups failed: {}", e)),
+            });
+        };
+        match &tab.backend {
+            TabBackend::Offline(dir) => {
+                let mut dir = dir.clone();
+                tab.spawn_op(label, async move {
+                    send(resolve_membership(&mut dir, &dn, &options).await);
+                });
+            }
+            TabBackend::Live(connection) => {
+                let connection = connection.clone();
+                tab.spawn_op(label, async move {
+                    let mut conn = connection.lock().await;
+                    send(resolve_membership(&mut *conn, &dn, &options).await);
+                });
             }
         }
     }
 
+    /// Let the export dialog take the selected or quick-filtered search
+    /// results instead of searching, while some are picked out.
+    fn offer_results_to_export(&mut self) {
+        let selected = self.search_dialog.selection_count();
+        if selected > 0 {
+            self.export_dialog.offer_selection(
+                selected,
+                self.search_dialog.row_count(),
+                self.search_dialog.results.len(),
+            );
+        } else if self.search_dialog.is_filtered() {
+            self.export_dialog.offer_results(
+                self.search_dialog.row_count(),
+                self.search_dialog.results.len(),
+            );
+        }
+    }
+
+    /// Let the export dialog take the groups the membership popup lists.
+    fn offer_groups_to_export(&mut self) {
+        if let Some(membership) = self.membership_popup.membership() {
+            self.export_dialog
+                .offer_groups(&membership.member, membership.groups.len());
+        }
+    }
+
     fn spawn_bulk_update(
         &self,
         conn_id: ConnectionId,
@@ -825,15 +2924,18 @@ impl App {
                 }
                 TabBackend::Live(connection) => {
                     let connection = connection.clone();
-                    tokio::spawn(async move {
+                    tab.spawn_op(format!("Bulk update {}", filter), async move {
                         let mut conn = connection.lock().await;
                         match conn.bulk_update(&filter, &modifications).await {
                             Ok(result) => {
-                                let msg = format!(
+                                let message = format!(
                                     "Bulk update: {} succeeded, {} failed out of {}",
                                     result.succeeded, result.failed, result.total
                                 );
-                                let _ = tx.send(Action::BulkUpdateComplete(msg));
+                                let _ = tx.send(Action::BulkUpdateComplete {
+                                    message,
+                                    errors: result.errors,
+                                });
                             }
                             Err(e) => {
                                 let _ = tx.send(Action::ErrorMessage(format!(
@@ -848,11 +2950,67 @@ impl App {
         }
     }
 
-    fn spawn_create_entry(
+    /// Compile a bulk update script and run it against the filter's entries.
+    fn start_scripted_bulk_update(
+        &mut self,
+        conn_id: ConnectionId,
+        filter: String,
+        attribute: String,
+        source: String,
+        op: BulkOp,
+    ) {
+        #[cfg(feature = "scripting")]
+        {
+            let script = match EntryScript::compile(&source) {
+                Ok(script) => script,
+                Err(e) => {
+                    self.push_error(format!("Script error: {}", e));
+                    return;
+                }
+            };
+            let op = match op {
+                BulkOp::Replace => ScriptedOp::Replace,
+                BulkOp::Add => ScriptedOp::Add,
+                BulkOp::Delete => ScriptedOp::Delete,
+            };
+            if let Some(base) = self
+                .active_tab()
+                .filter(|t| t.offline_edits)
+                .map(|t| t.directory_tree.root_dn.clone())
+            {
+                let tx = self.action_tx.clone();
+                self.spawn_plan_changes(conn_id, base, filter, &["*"], move |entries| {
+                    let plan = plan_scripted_update(&entries, &attribute, op, &script);
+                    for (dn, error) in plan.errors {
+                        let _ = tx.send(Action::ErrorMessage(format!("{}: {}", dn, error)));
+                    }
+                    Ok(plan
+                        .changes
+                        .iter()
+                        .map(|(dn, mods)| Change::modify(dn, mods))
+                        .collect())
+                });
+                return;
+            }
+            self.push_message(format!("Executing scripted bulk update: {}...", filter));
+            self.spawn_scripted_bulk_update(conn_id, filter, attribute, op, script);
+        }
+        #[cfg(not(feature = "scripting"))]
+        {
+            let _ = (conn_id, filter, attribute, source, op);
+            self.push_error("Scripting support is not compiled in".to_string());
+        }
+    }
+
+    /// Bulk update where a script computes each entry's values.
+    #[cfg(feature = "scripting")]
+    fn spawn_scripted_bulk_update(
         &self,
         conn_id: ConnectionId,
-        dn: String,
-        attributes: Vec<(String, Vec<String>)>,
+        filter: String,
+        attribute: String,
+        op: ScriptedOp,
+        script: EntryScript,
     ) {
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
@@ -872,21 +3030,28 @@ impl App {
                 }
                 TabBackend::Live(connection) => {
                     let connection = connection.clone();
-                    tokio::spawn(async move {
+                    tab.spawn_op(format!("Bulk update {}", filter), async move {
                         let mut conn = connection.lock().await;
-                        // Convert Vec<String> -> HashSet<String> for ldap3
-                        let attrs: Vec<(String, std::collections::HashSet<String>)> = attributes
-                            .into_iter()
-                            .map(|(k, v)| (k, v.into_iter().collect()))
-                            .collect();
-
-                        match conn.add_entry(&dn, attrs).await {
-                            Ok(()) => {
-                                let _ = tx.send(Action::EntryCreated(dn));
+                        match conn
+                            .bulk_update_scripted(&filter, &attribute, op, &script)
+                            .await
+                        {
+                            Ok(result) => {
+                                let mut message = format!(
+                                    "Scripted bulk update: {} succeeded, {} failed out of {}",
+                                    result.succeeded, result.failed, result.total
+                                );
+                                if result.failed > 0 {
+                                    message.push_str(" (failures are in the log)");
+                                }
+                                let _ = tx.send(Action::BulkUpdateComplete {
+                                    message,
+                                    errors: result.errors,
+                                });
                             }
                             Err(e) => {
                                 let _ = tx.send(Action::ErrorMessage(format!(
-                                    "Failed to create entry: {}",
+                                    "Bulk update failed: {}",
                                     e
                                 )));
                             }
@@ -897,7 +3062,8 @@ impl App {
         }
     }
 
-    fn spawn_delete_entry(&self, conn_id: ConnectionId, dn: String) {
+    /// Apply one change to each selected entry and report every outcome.
+    fn spawn_batch_modify(&self, conn_id: ConnectionId, dns: Vec<String>, change: BatchChange) {
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
             if tab.read_only {
@@ -916,123 +3082,121 @@ impl App {
                 }
                 TabBackend::Live(connection) => {
                     let connection = connection.clone();
-                    tokio::spawn(async move {
+                    tab.spawn_op(format!("Batch modify {} entries", dns.len()), async move {
                         let mut conn = connection.lock().await;
-                        match conn.delete_entry(&dn).await {
-                            Ok(()) => {
-                                let _ = tx.send(Action::EntryDeleted(dn));
-                            }
-                            Err(e) => {
-                                let _ = tx.send(Action::ErrorMessage(format!(
-                                    "Failed to delete entry: {}",
-                                    e
-                                )));
-                            }
-                        }
+                        let result = conn.batch_modify(&dns, &change).await;
+                        let _ = tx.send(Action::BatchModifyComplete {
+                            succeeded: result.succeeded,
+                            errors: result.errors,
+                        });
                     });
                 }
             }
         }
     }
 
-    fn spawn_dn_search(
+    /// Apply per-entry modifications planned ahead (e.g. an attribute
+    /// migration) and report the outcome.
+    fn spawn_bulk_apply(
         &self,
         conn_id: ConnectionId,
-        generation: u64,
-        query: String,
-        base_dn: String,
+        changes: Vec<(String, Vec<BulkMod>)>,
+        skipped: usize,
     ) {
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
+            if tab.read_only {
+                let _ = self
+                    .action_tx
+                    .send(Action::ErrorMessage("Connection is read-only".to_string()));
+                return;
+            }
             let tx = self.action_tx.clone();
+            let transactions = tab.transactions;
 
             match &tab.backend {
-                TabBackend::Offline(dir) => {
-                    let entries = dir.search_limited(&base_dn, &query, 50);
-                    let _ = tx.send(Action::DnSearchResults {
-                        generation,
-                        entries,
-                    });
+                TabBackend::Offline(_) => {
+                    let _ = tx.send(Action::ErrorMessage(
+                        "Example directory is read-only".to_string(),
+                    ));
                 }
                 TabBackend::Live(connection) => {
                     let connection = connection.clone();
-                    tokio::spawn(async move {
-                        let mut conn = connection.lock().await;
-                        let result = match conn
-                            .search_limited(&base_dn, &query, &["cn", "uid", "sn"], 50)
-                            .await
-                        {
-                            Ok(entries) => Ok(entries),
-                            Err(e) if LdapConnection::is_connection_error(&e) => {
-                                if conn.reconnect().await.is_ok() {
-                                    conn.search_limited(&base_dn, &query, &["cn", "uid", "sn"], 50)
-                                        .await
-                                } else {
-                                    Err(e)
+                    tab.spawn_op(
+                        format!("Apply changes to {} entries", changes.len()),
+                        async move {
+                            let mut conn = connection.lock().await;
+                            // Protected entries stay out of the batch
+                            let mut errors = Vec::new();
+                            let mut batch = Vec::new();
+                            for (dn, mods) in &changes {
+                                match conn.protected.check(dn) {
+                                    Some(pattern) => errors.push((
+                                        dn.clone(),
+                                        format!("protected by '{}', skipped", pattern),
+                                    )),
+                                    None => batch.push(Change::modify(dn, mods)),
                                 }
                             }
-                            Err(e) => Err(e),
-                        };
-
-                        match result {
-                            Ok(entries) => {
-                                let _ = tx.send(Action::DnSearchResults {
-                                    generation,
-                                    entries,
-                                });
-                            }
-                            Err(e) => {
-                                // Silently log — no error popup spam during live search
-                                debug!("DN search failed: {}", e);
+                            let report = apply_batch(&mut *conn, &batch, transactions).await;
+                            let mut message = format!(
+                                "Attribute migration ({} skipped): {}",
+                                skipped + errors.len(),
+                                report
+                            );
+                            errors.extend(report.errors());
+                            if !errors.is_empty() {
+                                message.push_str(" (failures are in the log)");
                             }
-                        }
-                    });
+                            let _ = tx.send(Action::MigrateAttributeComplete { message, errors });
+                        },
+                    );
                 }
             }
         }
     }
 
-    fn spawn_live_search(&self, conn_id: ConnectionId, generation: u64, filter: String) {
+    fn spawn_create_entry(
+        &self,
+        conn_id: ConnectionId,
+        dn: String,
+        attributes: Vec<(String, Vec<String>)>,
+    ) {
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
-            let base_dn = tab.directory_tree.root_dn.clone();
+            if tab.read_only {
+                let _ = self
+                    .action_tx
+                    .send(Action::ErrorMessage("Connection is read-only".to_string()));
+                return;
+            }
             let tx = self.action_tx.clone();
 
             match &tab.backend {
-                TabBackend::Offline(dir) => {
-                    let mut entries = dir.search(&base_dn, &filter);
-                    entries.truncate(50);
-                    let _ = tx.send(Action::LiveSearchResults {
-                        generation,
-                        entries,
-                    });
+                TabBackend::Offline(_) => {
+                    let _ = tx.send(Action::ErrorMessage(
+                        "Example directory is read-only".to_string(),
+                    ));
                 }
                 TabBackend::Live(connection) => {
                     let connection = connection.clone();
-                    tokio::spawn(async move {
+                    tab.spawn_op(format!("Add {}", dn), async move {
                         let mut conn = connection.lock().await;
-                        let result = match conn.search_limited(&base_dn, &filter, &["*"], 50).await
-                        {
-                            Ok(entries) => Ok(entries),
-                            Err(e) if LdapConnection::is_connection_error(&e) => {
-                                if conn.reconnect().await.is_ok() {
-                                    conn.search_limited(&base_dn, &filter, &["*"], 50).await
-                                } else {
-                                    Err(e)
-                                }
-                            }
-                            Err(e) => Err(e),
-                        };
+                        // Convert Vec<String> -> HashSet<String> for ldap3
+                        let attrs: Vec<(String, std::collections::HashSet<String>)> = attributes
+                            .into_iter()
+                            .map(|(k, v)| (k, v.into_iter().collect()))
+                            .collect();
 
-                        match result {
-                            Ok(entries) => {
-                                let _ = tx.send(Action::LiveSearchResults {
-                                    generation,
-                                    entries,
-                                });
+                        match conn.add_entry(&dn, attrs, Vec::new()).await {
+                            Ok(()) => {
+                                let _ = tx.send(Action::EntryCreated(dn));
                             }
                             Err(e) => {
-                                debug!("Live search failed: {}", e);
+                                let _ = tx.send(Action::ErrorMessage(format!(
+                                    "Failed to create entry: {}",
+                                    e
+                                )));
                             }
                         }
                     });
@@ -1041,13 +3205,9 @@ impl App {
         }
     }
 
-    fn spawn_add_multiple_values(
-        &self,
-        conn_id: ConnectionId,
-        dn: String,
-        attr: String,
-        values: Vec<String>,
-    ) {
+    /// Delete an entry after re-reading it: if it changed since `loaded`
+    /// was read, the delete is abandoned with `DeleteBlocked`.
+    fn spawn_delete_entry(&self, conn_id: ConnectionId, dn: String, loaded: Option<TrackedEntry>) {
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
             if tab.read_only {
@@ -1066,15 +3226,53 @@ impl App {
                 }
                 TabBackend::Live(connection) => {
                     let connection = connection.clone();
-                    tokio::spawn(async move {
+                    tab.spawn_op(format!("Delete {}", dn), async move {
                         let mut conn = connection.lock().await;
-                        match conn.add_attribute_values(&dn, &attr, values).await {
+                        let fresh = match conn.search_entry_tracked(&dn).await {
+                            Ok(fresh) => fresh,
+                            Err(e) => {
+                                let _ = tx.send(Action::ErrorMessage(format!(
+                                    "Failed to re-read {} before deleting: {}",
+                                    dn, e
+                                )));
+                                return;
+                            }
+                        };
+                        match check_before_delete(loaded.as_ref(), fresh.as_ref()) {
+                            // Children are only counted for a subtree delete
+                            DeleteCheck::Proceed | DeleteCheck::ChildrenChanged { .. } => {}
+                            DeleteCheck::Gone => {
+                                let _ = tx.send(Action::ErrorMessage(format!(
+                                    "Entry no longer exists: {}",
+                                    dn
+                                )));
+                                return;
+                            }
+                            DeleteCheck::Changed(changes) => {
+                                if let Some(fresh) = fresh {
+                                    let _ = tx.send(Action::DeleteBlocked(
+                                        dn,
+                                        Box::new(fresh),
+                                        changes,
+                                    ));
+                                }
+                                return;
+                            }
+                        }
+                        match conn.delete_entry(&dn).await {
                             Ok(()) => {
-                                let _ = tx.send(Action::AttributeSaved(dn));
+                                if let Some(fresh) = fresh {
+                                    let step = UndoStep::new(
+                                        format!("Delete {}", dn),
+                                        vec![invert_delete(&fresh.entry)],
+                                    );
+                                    let _ = tx.send(Action::PushUndo(conn_id, step));
+                                }
+                                let _ = tx.send(Action::EntryDeleted(dn));
                             }
                             Err(e) => {
                                 let _ = tx.send(Action::ErrorMessage(format!(
-                                    "Failed to add values: {}",
+                                    "Failed to delete entry: {}",
                                     e
                                 )));
                             }
@@ -1085,1238 +3283,4097 @@ impl App {
         }
     }
 
-    /// Look up whether an attribute has DN syntax and whether it's multi-valued,
-    /// using the active tab's schema cache.
-    fn lookup_attr_schema(&self, attr: &str) -> (bool, bool) {
-        if let Some(tab) = self.active_tab() {
-            if let Some(ref schema) = tab.schema {
-                let is_dn = matches!(schema.attribute_syntax(attr), AttributeSyntax::Dn);
-                let multi_valued = !schema.is_single_valued(attr);
-                return (is_dn, multi_valued);
+    /// Delete hand-picked entries in the background, skipping protected
+    /// ones, and report every outcome.
+    fn spawn_delete_entries(&mut self, conn_id: ConnectionId, dns: Vec<String>) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let refusal = match tab.backend {
+            _ if tab.read_only => Some("Connection is read-only"),
+            TabBackend::Offline(_) => Some("Example directory is read-only"),
+            TabBackend::Live(_) => None,
+        };
+        if let Some(refusal) = refusal {
+            self.push_error(refusal.to_string());
+            return;
+        }
+        let TabBackend::Live(ref connection) = tab.backend else {
+            return;
+        };
+        let connection = connection.clone();
+        let protected = tab.protected.clone();
+        let tx = self.action_tx.clone();
+        tab.spawn_op(format!("Delete {} entries", dns.len()), async move {
+            let mut conn = connection.lock().await;
+            let result = delete_entries(&mut *conn, dns, &protected).await;
+            let _ = tx.send(Action::EntriesDeleted(result));
+        });
+    }
+
+    /// Delete `dn` and everything below it in the background, reporting
+    /// progress to the delete dialog. Stops at the first failure. The root
+    /// is read again first: if it or the number of entries directly below
+    /// it changed since `loaded` and `loaded_children`, the delete is
+    /// abandoned with `SubtreeDeleteBlocked`.
+    fn spawn_delete_subtree(
+        &mut self,
+        conn_id: ConnectionId,
+        dn: String,
+        loaded: Option<TrackedEntry>,
+        loaded_children: Option<usize>,
+    ) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let refusal = match tab.backend {
+            _ if tab.read_only => Some("Connection is read-only"),
+            TabBackend::Offline(_) => Some("Example directory is read-only"),
+            TabBackend::Live(_) => None,
+        };
+        if let Some(refusal) = refusal {
+            self.delete_dialog.hide();
+            self.push_error(refusal.to_string());
+            return;
+        }
+        let TabBackend::Live(ref connection) = tab.backend else {
+            return;
+        };
+        let connection = connection.clone();
+        let protected = tab.protected.clone();
+        let tx = self.action_tx.clone();
+        let base = dn.clone();
+        let op = tab.spawn_op(format!("Delete subtree {}", dn), async move {
+            let mut conn = connection.lock().await;
+            let recheck =
+                recheck_subtree_root(&mut *conn, &base, loaded.as_ref(), loaded_children).await;
+            match recheck {
+                Ok((DeleteCheck::Proceed, _)) => {}
+                Ok((DeleteCheck::Gone, _)) => {
+                    let _ = tx.send(Action::SubtreeDeleteFailed(format!(
+                        "Entry no longer exists: {}",
+                        base
+                    )));
+                    return;
+                }
+                Ok((check, Some(fresh))) => {
+                    let _ = tx.send(Action::SubtreeDeleteBlocked(base, Box::new(fresh), check));
+                    return;
+                }
+                Ok((_, None)) => return,
+                Err(e) => {
+                    let _ = tx.send(Action::SubtreeDeleteFailed(format!(
+                        "Failed to re-read {} before deleting: {}",
+                        base, e
+                    )));
+                    return;
+                }
+            }
+            // The whole subtree is read first so the delete can be undone;
+            // a subtree too big to hold is deleted with a warning instead
+            let before = match read_subtree(&mut *conn, &base).await {
+                Ok(entries) if entries.len() <= SUBTREE_UNDO_LIMIT => Some(entries),
+                Ok(entries) => {
+                    let _ = tx.send(Action::ErrorMessage(format!(
+                        "{} holds {} entries, more than {} can be undone: this delete cannot be undone",
+                        base,
+                        entries.len(),
+                        SUBTREE_UNDO_LIMIT
+                    )));
+                    None
+                }
+                Err(e) => {
+                    let _ = tx.send(Action::ErrorMessage(format!(
+                        "Cannot read {} for undo, this delete cannot be undone: {}",
+                        base, e
+                    )));
+                    None
+                }
+            };
+            let progress_tx = tx.clone();
+            let result = delete_subtree(&mut *conn, &base, &protected, |done, total| {
+                let _ = progress_tx.send(Action::SubtreeDeleteProgress { done, total });
+            })
+            .await;
+            if let (Some(before), Ok(result)) = (&before, &result) {
+                let changes = invert_subtree_delete(before, &result.deleted);
+                if !changes.is_empty() {
+                    let step = UndoStep::new(format!("Delete subtree {}", base), changes);
+                    let _ = tx.send(Action::PushUndo(conn_id, step));
+                }
             }
+            let _ = tx.send(match result {
+                Ok(result) => Action::SubtreeDeleted { base, result },
+                Err(e) => Action::SubtreeDeleteFailed(format!("Subtree delete failed: {}", e)),
+            });
+        });
+        // Cancelled from the operations list: close the dialog too
+        let cancel_tx = self.action_tx.clone();
+        tab.ops.on_cancel(
+            op,
+            Box::new(move || {
+                let _ = cancel_tx.send(Action::SubtreeDeleteCancel);
+            }),
+        );
+        self.subtree_delete_task = Some((conn_id, op, dn.clone()));
+        // A delete confirmed through the protected-entry prompt arrives
+        // with the dialog closed
+        if !self.delete_dialog.visible {
+            self.delete_dialog.show(dn, Children::Some);
         }
-        (false, true) // default: not DN, multi-valued
+        self.delete_dialog.set_progress(0, 0);
     }
 
-    /// Check if any popup/dialog is currently visible.
-    fn popup_active(&self) -> bool {
-        self.context_menu.visible
-            || self.confirm_dialog.visible
-            || self.cert_trust_dialog.visible
-            || self.connect_dialog.visible
-            || self.new_connection_dialog.visible
-            || self.credential_prompt.visible
-            || self.search_dialog.visible
-            || self.attribute_editor.visible
-            || self.attribute_picker.visible
-            || self.export_dialog.visible
-            || self.bulk_update_dialog.visible
-            || self.create_entry_dialog.visible
-            || self.schema_viewer.visible
-            || self.help_popup.visible
-            || self.about_popup.visible
-            || self.log_panel.visible
-            || self.profile_export_dialog.visible
-            || self.profile_import_dialog.visible
+    /// Read the branch at `source` for a copy to `target`: at once from
+    /// an example directory, in the background from a live one.
+    fn spawn_read_subtree(
+        &mut self,
+        conn_id: ConnectionId,
+        source: String,
+        target: String,
+        changefile: Option<PathBuf>,
+    ) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        let connection = match tab.backend {
+            TabBackend::Offline(ref dir) => {
+                let entries = dir.search(&source, "(objectClass=*)");
+                let _ = tx.send(Action::SubtreeRead {
+                    source,
+                    target,
+                    entries,
+                    changefile,
+                });
+                return;
+            }
+            TabBackend::Live(ref connection) => connection.clone(),
+        };
+        let label = format!("Read subtree {}", source);
+        let new_dn = target.clone();
+        let op = tab.spawn_op(label, async move {
+            let mut conn = connection.lock().await;
+            let _ = tx.send(match read_subtree(&mut *conn, &source).await {
+                Ok(entries) => Action::SubtreeRead {
+                    source,
+                    target,
+                    entries,
+                    changefile,
+                },
+                Err(e) => Action::SubtreeCopyFailed(format!("Reading {} failed: {}", source, e)),
+            });
+        });
+        let cancel_tx = self.action_tx.clone();
+        tab.ops.on_cancel(
+            op,
+            Box::new(move || {
+                let _ = cancel_tx.send(Action::SubtreeCopyCancel);
+            }),
+        );
+        self.subtree_copy_task = Some((conn_id, op, new_dn));
     }
 
-    /// Check if any popup, dialog, or text-input mode is active.
-    fn any_popup_or_input_active(&self) -> bool {
-        self.context_menu.visible
-            || self.attribute_editor.visible
-            || self.attribute_picker.visible
-            || self.confirm_dialog.visible
-            || self.cert_trust_dialog.visible
-            || self.connect_dialog.visible
-            || self.new_connection_dialog.visible
-            || self.credential_prompt.visible
-            || self.search_dialog.visible
-            || self.export_dialog.visible
-            || self.bulk_update_dialog.visible
-            || self.create_entry_dialog.visible
-            || self.schema_viewer.visible
-            || self.help_popup.visible
-            || self.about_popup.visible
-            || self.log_panel.visible
-            || self.profile_export_dialog.visible
-            || self.profile_import_dialog.visible
-            || self.command_panel.input_active
-            || (self.connection_form.is_editing()
-                && self.active_layout == ActiveLayout::Profiles
-                && self.focus.current() == FocusTarget::ConnectionForm)
+    /// Add the entries of a subtree copy in the background, reporting
+    /// progress to the copy dialog. Stops at the first failure.
+    fn spawn_copy_entries(&mut self, conn_id: ConnectionId, target: String, plan: Vec<LdapEntry>) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let TabBackend::Live(ref connection) = tab.backend else {
+            return;
+        };
+        let connection = connection.clone();
+        let tx = self.action_tx.clone();
+        let new_dn = target.clone();
+        let op = tab.spawn_op(format!("Copy subtree to {}", target), async move {
+            let mut conn = connection.lock().await;
+            let progress_tx = tx.clone();
+            let result = copy_entries(&mut *conn, &plan, |done, total| {
+                let _ = progress_tx.send(Action::SubtreeCopyProgress { done, total });
+            })
+            .await;
+            let _ = tx.send(Action::SubtreeCopied { target, result });
+        });
+        let cancel_tx = self.action_tx.clone();
+        tab.ops.on_cancel(
+            op,
+            Box::new(move || {
+                let _ = cancel_tx.send(Action::SubtreeCopyCancel);
+            }),
+        );
+        self.subtree_copy_task = Some((conn_id, op, new_dn));
     }
 
-    /// Dismiss every open popup/dialog.
-    fn dismiss_all_popups(&mut self) {
-        self.context_menu.hide();
-        self.confirm_dialog.hide();
-        self.cert_trust_dialog.hide();
-        self.connect_dialog.hide();
-        self.new_connection_dialog.hide();
-        self.credential_prompt.hide();
-        self.search_dialog.hide();
-        self.command_panel.soft_deactivate();
-        self.attribute_editor.hide();
-        self.attribute_picker.hide();
-        self.export_dialog.hide();
-        self.bulk_update_dialog.hide();
-        self.create_entry_dialog.hide();
-        self.schema_viewer.hide();
-        self.help_popup.hide();
-        self.about_popup.hide();
-        self.log_panel.hide();
-        self.profile_export_dialog.hide();
-        self.profile_import_dialog.hide();
+    /// The entry `dn` as last loaded, or from the example directory.
+    fn loaded_entry(&self, dn: &str) -> Option<LdapEntry> {
+        let tab = self.active_tab()?;
+        if let Some(tracked) = tab.entry_cache.get(&normalize_dn(dn)) {
+            return Some(tracked.entry.clone());
+        }
+        match tab.backend {
+            TabBackend::Offline(ref dir) => dir.entry(dn),
+            TabBackend::Live(_) => None,
+        }
     }
 
-    /// Main event loop.
-    pub async fn run(&mut self) -> anyhow::Result<()> {
-        tui::install_panic_hook();
-        let mut terminal = tui::init()?;
+    /// Mark `dn` for comparison, or open the compare view against the
+    /// entry marked before, which may be on another connection.
+    fn compare_entry(&mut self, dn: &str) {
+        let Some(entry) = self.loaded_entry(dn) else {
+            self.push_error(format!("Select {} first so its attributes are loaded", dn));
+            return;
+        };
+        let Some(marked) = self.compare_mark.take() else {
+            self.status_bar.set_message(format!(
+                "Marked {} for compare; select another entry and press C",
+                loom_core::dn::rdn_display_name(dn)
+            ));
+            self.compare_mark = Some(entry);
+            return;
+        };
+        if normalize_dn(&marked.dn) == normalize_dn(&entry.dn) {
+            self.status_bar
+                .set_message("Compare mark cleared".to_string());
+            return;
+        }
+        let schema = self.active_tab().and_then(|t| t.schema.clone());
+        self.compare_view
+            .show(loom_core::diff::diff_entries(&marked, &entry), schema);
+    }
 
-        let tick_rate = Duration::from_millis(self.config.general.tick_rate_ms);
+    /// The entry yanked on the active connection.
+    fn yanked_on_active_tab(&mut self) -> Option<String> {
+        let yanked = self
+            .yanked_entry
+            .as_ref()
+            .filter(|(id, _)| Some(*id) == self.active_tab_id)
+            .map(|(_, dn)| dn.clone());
+        if yanked.is_none() {
+            self.push_error("Nothing yanked on this connection; press yy on an entry".to_string());
+        }
+        yanked
+    }
 
-        loop {
-            // Render
-            terminal.draw(|frame| self.render(frame))?;
+    /// What the active tab's tree knows about the children of `dn`.
+    /// How many children `dn` was shown with, when they were all listed.
+    fn listed_children(&self, dn: &str) -> Option<usize> {
+        let node = self.active_tab()?.directory_tree.find_node(dn)?;
+        match node.children {
+            Some(ref c) if node.paging.is_none() => Some(c.len()),
+            _ => None,
+        }
+    }
 
-            // Poll for events
-            if let Some(app_event) = event::poll_event(tick_rate) {
-                match app_event {
-                    AppEvent::Key(key) => {
-                        // Clear pending 'g' if a popup/input became active
-                        if self.pending_g && self.any_popup_or_input_active() {
-                            self.pending_g = false;
-                        }
+    /// Show and cache the fresh copy of an entry whose delete was stopped;
+    /// a confirmed retry is checked against it.
+    fn keep_fresh_copy(&mut self, dn: &str, fresh: TrackedEntry) {
+        if self
+            .detail_panel
+            .entry
+            .as_ref()
+            .is_some_and(|e| e.dn.eq_ignore_ascii_case(dn))
+        {
+            let schema = self.active_tab().and_then(|t| t.schema.clone());
+            self.detail_panel
+                .set_entry(fresh.entry.clone(), schema.as_ref());
+        }
+        if let Some(tab) = self.active_tab_mut() {
+            tab.entry_cache.insert(normalize_dn(dn), fresh);
+        }
+    }
 
-                        // Search binding activates search from any non-input context,
-                        // but yields to dialogs/popups/editors that capture keystrokes.
-                        let action = if self.pending_g {
-                            // Resolve vim-style gt/gT tab switching
-                            self.pending_g = false;
-                            match key.code {
-                                KeyCode::Char('t') => Action::NextTab,
-                                KeyCode::Char('T') => Action::PrevTab,
-                                _ => Action::None,
-                            }
-                        } else if !self.any_popup_or_input_active()
-                            && matches!(
-                                self.keymap.resolve_global_only(&key),
-                                Action::SearchFocusInput
-                            )
-                        {
-                            self.dismiss_all_popups();
-                            if self.active_layout != ActiveLayout::Browser {
-                                let _ = self
-                                    .action_tx
-                                    .send(Action::SwitchLayout(ActiveLayout::Browser));
-                            }
-                            Action::SearchFocusInput
-                        // Popups intercept keys first
-                        } else if self.context_menu.visible {
-                            self.context_menu.handle_key_event(key)
-                        } else if self.attribute_editor.visible {
-                            self.attribute_editor.handle_key_event(key)
-                        } else if self.attribute_picker.visible {
-                            self.attribute_picker.handle_key_event(key)
-                        } else if self.confirm_dialog.visible {
-                            self.confirm_dialog.handle_key_event(key)
-                        } else if self.cert_trust_dialog.visible {
-                            self.cert_trust_dialog.handle_key_event(key)
-                        } else if self.connect_dialog.visible {
-                            self.connect_dialog.handle_key_event(key)
-                        } else if self.new_connection_dialog.visible {
-                            self.new_connection_dialog.handle_key_event(key)
-                        } else if self.credential_prompt.visible {
-                            self.credential_prompt.handle_key_event(key)
-                        } else if self.vault_password_dialog.visible {
-                            self.vault_password_dialog.handle_key_event(key)
-                        } else if self.search_dialog.visible {
-                            // Search popup is open — route keys based on input state
-                            if matches!(
-                                self.keymap.resolve_global_only(&key),
-                                Action::SearchFocusInput
-                            ) {
-                                // F9 toggles popup closed
-                                self.search_dialog.hide();
-                                self.command_panel.soft_deactivate();
-                                Action::None
-                            } else if self.command_panel.input_active {
-                                // Input is active — route to command panel
-                                self.command_panel.handle_input_key(key)
-                            } else {
-                                // Input not active — navigate results or edit filter
-                                match key.code {
-                                    KeyCode::Char('/') => {
-                                        // Reactivate input editing
-                                        self.command_panel.resume_input();
-                                        Action::None
-                                    }
-                                    KeyCode::Up
-                                    | KeyCode::Down
-                                    | KeyCode::Enter
-                                    | KeyCode::Esc
-                                    | KeyCode::Char('j')
-                                    | KeyCode::Char('k')
-                                    | KeyCode::Char('q') => {
-                                        let a = self.search_dialog.handle_key_event(key);
-                                        if matches!(&a, Action::TreeSelect(_)) {
-                                            self.command_panel.soft_deactivate();
-                                            let _ = self
-                                                .action_tx
-                                                .send(Action::FocusPanel(FocusTarget::DetailPanel));
-                                        }
-                                        a
-                                    }
-                                    KeyCode::Char(c) if !c.is_control() => {
-                                        // Start editing with this character
-                                        self.command_panel.resume_input();
-                                        self.command_panel.handle_input_key(key)
-                                    }
-                                    _ => Action::None,
-                                }
-                            }
-                        } else if self.export_dialog.visible {
-                            self.export_dialog.handle_key_event(key)
-                        } else if self.bulk_update_dialog.visible {
-                            self.bulk_update_dialog.handle_key_event(key)
-                        } else if self.profile_export_dialog.visible {
-                            self.profile_export_dialog
-                                .handle_key_event(key, &self.config.connections)
-                        } else if self.profile_import_dialog.visible {
-                            self.profile_import_dialog.handle_key_event(key)
-                        } else if self.create_entry_dialog.visible {
-                            self.create_entry_dialog.handle_key_event(key)
-                        } else if self.schema_viewer.visible {
-                            self.schema_viewer.handle_key_event(key)
-                        } else if self.help_popup.visible {
-                            let a = self.help_popup.handle_key_event(key);
-                            if matches!(a, Action::ClosePopup) && self.show_connect_after_help {
-                                self.show_connect_after_help = false;
-                                // Ask about vault setup, then show connect dialog
-                                let _ = self.action_tx.send(Action::ShowConfirm(
-                                    "Use an encrypted vault for password storage?".to_string(),
-                                    Box::new(Action::VaultSetupPrompt),
-                                ));
-                                let _ = self.action_tx.send(Action::ShowConnectDialog);
-                            }
-                            a
-                        } else if self.about_popup.visible {
-                            self.about_popup.handle_key_event(key)
-                        } else if self.log_panel.visible {
-                            self.log_panel.handle_key_event(key)
-                        } else if self.command_panel.input_active
-                            && self.active_layout == ActiveLayout::Browser
-                        {
-                            self.command_panel.handle_input_key(key)
-                        } else if self.connection_form.is_editing()
-                            && self.active_layout == ActiveLayout::Profiles
-                            && self.focus.current() == FocusTarget::ConnectionForm
-                        {
-                            // Connection form in edit/create mode captures all keys
-                            self.connection_form.handle_key_event(key)
-                        } else if self.active_layout == ActiveLayout::Profiles {
-                            // Connections layout: route to connections panels first
-                            let panel_action = match self.focus.current() {
-                                FocusTarget::ConnectionsTree => {
-                                    self.connections_tree.handle_key_event(key)
-                                }
-                                FocusTarget::ConnectionForm => {
-                                    self.connection_form.handle_key_event(key)
-                                }
-                                _ => Action::None,
-                            };
-                            if matches!(panel_action, Action::None) {
-                                self.keymap.resolve(key, self.focus.current())
-                            } else {
-                                panel_action
-                            }
-                        } else {
-                            // Browser layout: intercept '/' to open search popup
-                            if matches!(key.code, KeyCode::Char('/'))
-                                && !self.any_popup_or_input_active()
-                            {
-                                Action::SearchFocusInput
-                            } else {
-                                // Try panel-specific handler first, fall back to global keymap
-                                let panel_action = match self.focus.current() {
-                                    FocusTarget::TreePanel => self.tree_panel.handle_key_event(key),
-                                    FocusTarget::DetailPanel => {
-                                        self.detail_panel.handle_key_event(key)
-                                    }
-                                    _ => Action::None,
-                                };
-                                if matches!(panel_action, Action::None) {
-                                    self.keymap.resolve(key, self.focus.current())
-                                } else {
-                                    panel_action
-                                }
-                            }
-                        };
-                        // Initiate vim 'g' prefix when g produces no action
-                        if matches!(action, Action::None)
-                            && matches!(key.code, KeyCode::Char('g'))
-                            && key.modifiers == KeyModifiers::NONE
-                            && !self.any_popup_or_input_active()
-                        {
-                            self.pending_g = true;
-                        } else {
-                            let _ = self.action_tx.send(action);
-                        }
-                    }
-                    AppEvent::Mouse(mouse) => {
-                        let action = self.handle_mouse(mouse);
-                        if !matches!(action, Action::None) {
-                            let _ = self.action_tx.send(action);
-                        }
-                    }
-                    AppEvent::Resize(w, h) => {
-                        let _ = self.action_tx.send(Action::Resize(w, h));
+    fn children_of(&self, dn: &str) -> Option<Children> {
+        let tab = self.active_tab()?;
+        Some(match tab.directory_tree.find_node(dn) {
+            Some(node) => match node.children {
+                Some(ref c) if c.is_empty() && node.paging.is_none() => Children::None,
+                Some(_) => Children::Some,
+                None if node.has_children_hint => Children::Unknown,
+                None => Children::None,
+            },
+            None => Children::Unknown,
+        })
+    }
+
+    /// Rename `dn`, moving it under `new_superior` when given. The
+    /// server's refusal, such as for a subtree move, is shown as it came.
+    fn spawn_rename_entry(
+        &self,
+        conn_id: ConnectionId,
+        dn: String,
+        new_rdn: String,
+        delete_old_rdn: bool,
+        new_superior: Option<String>,
+    ) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        if tab.read_only {
+            let _ = tx.send(Action::ErrorMessage("Connection is read-only".to_string()));
+            return;
+        }
+        let TabBackend::Live(ref connection) = tab.backend else {
+            let _ = tx.send(Action::ErrorMessage(
+                "Example directory is read-only".to_string(),
+            ));
+            return;
+        };
+        let new_dn = match loom_core::dn::renamed(&dn, &new_rdn, new_superior.as_deref()) {
+            Ok(new_dn) => new_dn,
+            Err(e) => {
+                let _ = tx.send(Action::ErrorMessage(format!("Cannot rename {}: {}", dn, e)));
+                return;
+            }
+        };
+        let connection = connection.clone();
+        tab.spawn_op(format!("Rename {}", dn), async move {
+            let mut conn = connection.lock().await;
+            let before = read_before_image(&mut conn, &dn).await;
+            match conn
+                .rename_entry(&dn, &new_rdn, delete_old_rdn, new_superior.as_deref())
+                .await
+            {
+                Ok(()) => {
+                    let inverse = before.and_then(|before| {
+                        invert_rename(&before, &new_rdn, new_superior.as_deref()).ok()
+                    });
+                    if let Some(inverse) = inverse {
+                        let step = UndoStep::new(format!("Rename {}", dn), vec![inverse]);
+                        let _ = tx.send(Action::PushUndo(conn_id, step));
                     }
-                    AppEvent::Tick => {
-                        let _ = self.action_tx.send(Action::Tick);
+                    let _ = tx.send(Action::EntryRenamed { old_dn: dn, new_dn });
+                }
+                Err(e) => {
+                    let _ = tx.send(Action::ErrorMessage(format!(
+                        "Failed to rename entry: {}",
+                        e
+                    )));
+                }
+            }
+        });
+    }
+
+    /// Enable, disable or unlock an account in the background. The
+    /// before-image includes the account state, so the change can be undone.
+    fn spawn_account_change(&self, conn_id: ConnectionId, action: AccountAction, change: Change) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        if tab.read_only {
+            let _ = tx.send(Action::ErrorMessage("Connection is read-only".to_string()));
+            return;
+        }
+        let TabBackend::Live(ref connection) = tab.backend else {
+            let _ = tx.send(Action::ErrorMessage(
+                "Example directory is read-only".to_string(),
+            ));
+            return;
+        };
+        let Change::Modify { dn, mods } = change.clone() else {
+            return;
+        };
+        let connection = connection.clone();
+        tab.spawn_op(format!("{} account {}", action.title(), dn), async move {
+            let mut conn = connection.lock().await;
+            let before = conn.search_entry_tracked(&dn).await.ok().flatten();
+            let inverse = before.and_then(|before| invert_change(&before.entry, &change));
+            match conn.modify_entry(&dn, mods).await {
+                Ok(()) => {
+                    if let Some(inverse) = inverse {
+                        let step = UndoStep::new(
+                            format!("{} account {}", action.title(), dn),
+                            vec![inverse],
+                        );
+                        let _ = tx.send(Action::PushUndo(conn_id, step));
                     }
+                    let _ = tx.send(Action::AccountChanged { dn, action });
+                }
+                Err(e) => {
+                    let _ = tx.send(Action::ErrorMessage(format!(
+                        "Failed to {} account: {}",
+                        action.verb(),
+                        e
+                    )));
                 }
             }
+        });
+    }
 
-            // Drain action queue
-            while let Ok(action) = self.action_rx.try_recv() {
-                self.process_action(action).await;
+    /// Set the password of `dn` in the background, then require a change
+    /// at next login when asked. Only the DN is ever reported back.
+    fn spawn_set_password(
+        &self,
+        conn_id: ConnectionId,
+        dn: String,
+        password: String,
+        must_change: bool,
+    ) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        if tab.read_only {
+            let _ = tx.send(Action::ErrorMessage("Connection is read-only".to_string()));
+            return;
+        }
+        let TabBackend::Live(ref connection) = tab.backend else {
+            let _ = tx.send(Action::ErrorMessage(
+                "Example directory is read-only".to_string(),
+            ));
+            return;
+        };
+        let (extensions, server_type) = match &tab.root_dse {
+            Some(dse) => (dse.supported_extensions.clone(), dse.server_type.clone()),
+            None => (Vec::new(), ServerType::Unknown(String::new())),
+        };
+        let method = PasswordMethod::for_server(&extensions);
+        let connection = connection.clone();
+        tab.spawn_op(format!("Set password of {}", dn), async move {
+            let mut conn = connection.lock().await;
+            if let Err(e) = conn.set_password(&dn, &password, method).await {
+                let _ = tx.send(Action::ErrorMessage(format!(
+                    "Failed to set password: {}",
+                    e
+                )));
+                return;
+            }
+            if must_change {
+                if let Err(e) = conn.modify_entry(&dn, must_change_mods(&server_type)).await {
+                    let _ = tx.send(Action::ErrorMessage(format!(
+                        "Password set, but a change at next login could not be required: {}",
+                        e
+                    )));
+                    return;
+                }
             }
+            let _ = tx.send(Action::PasswordSet {
+                dn,
+                method,
+                must_change,
+            });
+        });
+    }
 
-            if self.should_quit {
-                break;
+    /// Replay the active tab's last undo step in the background. The step
+    /// leaves the stack even when the server refuses part of it, since
+    /// what went through can no longer be taken back the same way.
+    fn spawn_undo(&mut self) {
+        let Some(tab) = self.active_tab_mut() else {
+            return;
+        };
+        let TabBackend::Live(ref connection) = tab.backend else {
+            return;
+        };
+        let connection = connection.clone();
+        let Some(step) = tab.undo.pop() else {
+            self.status_bar.set_message("Nothing to undo".to_string());
+            return;
+        };
+        let tab = self.active_tab().expect("active tab");
+        let conn_id = tab.id;
+        let tx = self.action_tx.clone();
+        tab.spawn_op(format!("Undo {}", step.description), async move {
+            let mut conn = connection.lock().await;
+            match undo::replay(&mut *conn, &step).await {
+                Ok(()) => {
+                    let _ = tx.send(Action::UndoComplete(conn_id, step));
+                }
+                Err(e) => {
+                    let _ = tx.send(Action::ErrorMessage(e.to_string()));
+                }
             }
-        }
+        });
+    }
 
-        tui::restore()?;
-        Ok(())
+    /// Refuse to delete the active tab's base DN, which would take the
+    /// whole tree with it. Returns `true` when `dn` is the base.
+    fn refuse_base_delete(&mut self, dn: &str) -> bool {
+        let Some(tab) = self.active_tab() else {
+            return false;
+        };
+        if loom_core::dn::normalize(dn) != loom_core::dn::normalize(&tab.directory_tree.root_dn) {
+            return false;
+        }
+        self.delete_dialog.hide();
+        let msg = format!("{} is the connection's base DN and cannot be deleted", dn);
+        self.status_bar.set_message(msg.clone());
+        self.push_error(msg);
+        true
     }
 
-    fn handle_mouse(&mut self, mouse: crossterm::event::MouseEvent) -> Action {
-        // Popups block mouse events; also clear any drag
-        if self.popup_active() {
-            self.drag_target = None;
-            return Action::None;
+    /// Drop deleted entries from the active tab: tree nodes, cached
+    /// copies, recent entries, and the detail panel if it shows one.
+    fn forget_deleted(&mut self, dns: &[String]) {
+        let showing = self
+            .detail_panel
+            .entry
+            .as_ref()
+            .map(|e| normalize_dn(&e.dn));
+        if showing
+            .as_ref()
+            .is_some_and(|shown| dns.iter().any(|d| normalize_dn(d) == *shown))
+        {
+            self.detail_panel.clear();
+        }
+        let Some(id) = self.active_tab_id else {
+            return;
+        };
+        for dn in dns {
+            self.recent_entries.remove(id, dn);
+        }
+        if let Some(tab) = self.active_tab_mut() {
+            for dn in dns {
+                tab.entry_cache.remove(&normalize_dn(dn));
+                tab.directory_tree.remove(dn);
+            }
         }
+    }
 
-        match mouse.kind {
-            MouseEventKind::Down(crossterm::event::MouseButton::Left) => {
-                // Check if click is on a panel divider (start drag)
-                if let Some(target) = self.divider_hit(mouse.column, mouse.row) {
-                    self.drag_target = Some(target);
-                    return Action::None;
-                }
+    fn spawn_dn_search(
+        &self,
+        conn_id: ConnectionId,
+        generation: u64,
+        query: String,
+        base_dn: String,
+    ) {
+        let tab = self.tabs.iter().find(|t| t.id == conn_id);
+        if let Some(tab) = tab {
+            let tx = self.action_tx.clone();
 
-                let pos = Rect::new(mouse.column, mouse.row, 1, 1);
+            match &tab.backend {
+                TabBackend::Offline(dir) => {
+                    let entries = dir.search_limited(&base_dn, &query, 50);
+                    let _ = tx.send(Action::DnSearchResults {
+                        generation,
+                        entries,
+                    });
+                }
+                TabBackend::Live(connection) => {
+                    let connection = connection.clone();
+                    tab.spawn_op(format!("Find DN {}", query), async move {
+                        let mut conn = connection.lock().await;
+                        let result = match conn
+                            .search_limited(&base_dn, &query, &["cn", "uid", "sn"], 50)
+                            .await
+                        {
+                            Ok(entries) => Ok(entries),
+                            Err(e) if LdapConnection::is_connection_error(&e) => {
+                                if conn.reconnect().await.is_ok() {
+                                    conn.search_limited(&base_dn, &query, &["cn", "uid", "sn"], 50)
+                                        .await
+                                } else {
+                                    Err(e)
+                                }
+                            }
+                            Err(e) => Err(e),
+                        };
 
-                // Check layout bar clicks (unified tab strip)
-                if let Some(bar) = self.layout_bar_area {
-                    if bar.intersects(pos) {
-                        for &(start, end, ref target) in &self.layout_bar.hit_regions {
-                            if mouse.column >= start && mouse.column < end {
-                                return match target {
-                                    None => Action::SwitchLayout(ActiveLayout::Profiles),
-                                    Some(id) => Action::SwitchTab(*id),
-                                };
+                        match result {
+                            Ok(entries) => {
+                                let _ = tx.send(Action::DnSearchResults {
+                                    generation,
+                                    entries,
+                                });
+                            }
+                            Err(e) => {
+                                // Silently log — no error popup spam during live search
+                                debug!("DN search failed: {}", e);
                             }
                         }
-                    }
+                    });
                 }
+            }
+        }
+    }
 
-                // Check connections layout panels
-                if self.active_layout == ActiveLayout::Profiles {
-                    if let Some(ct) = self.conn_tree_area {
-                        if ct.intersects(pos) {
-                            return Action::FocusPanel(FocusTarget::ConnectionsTree);
-                        }
-                    }
-                    if let Some(cf) = self.conn_form_area {
-                        if cf.intersects(pos) {
-                            return Action::FocusPanel(FocusTarget::ConnectionForm);
-                        }
-                    }
-                    return Action::None;
+    fn spawn_live_search(&self, conn_id: ConnectionId, generation: u64, filter: String) {
+        let tab = self.tabs.iter().find(|t| t.id == conn_id);
+        if let Some(tab) = tab {
+            let base_dn = tab.directory_tree.root_dn.clone();
+            let tx = self.action_tx.clone();
+
+            match &tab.backend {
+                TabBackend::Offline(dir) => {
+                    let mut entries = dir.search(&base_dn, &filter);
+                    entries.truncate(50);
+                    let _ = tx.send(Action::LiveSearchResults {
+                        generation,
+                        entries,
+                    });
                 }
+                TabBackend::Live(connection) => {
+                    let connection = connection.clone();
+                    tab.spawn_op(format!("Search {}", filter), async move {
+                        let mut conn = connection.lock().await;
+                        let result = match conn.search_limited(&base_dn, &filter, &["*"], 50).await
+                        {
+                            Ok(entries) => Ok(entries),
+                            Err(e) if LdapConnection::is_connection_error(&e) => {
+                                if conn.reconnect().await.is_ok() {
+                                    conn.search_limited(&base_dn, &filter, &["*"], 50).await
+                                } else {
+                                    Err(e)
+                                }
+                            }
+                            Err(e) => Err(e),
+                        };
 
-                // Browser layout panels
-                if let Some(tree) = self.tree_area {
-                    if tree.intersects(pos) {
-                        return Action::FocusPanel(FocusTarget::TreePanel);
-                    }
+                        match result {
+                            Ok(entries) => {
+                                let _ = tx.send(Action::LiveSearchResults {
+                                    generation,
+                                    entries,
+                                });
+                            }
+                            Err(e) => {
+                                debug!("Live search failed: {}", e);
+                            }
+                        }
+                    });
                 }
-                if let Some(detail) = self.detail_area {
-                    if detail.intersects(pos) {
-                        return Action::FocusPanel(FocusTarget::DetailPanel);
-                    }
+            }
+        }
+    }
+
+    fn spawn_add_multiple_values(
+        &self,
+        conn_id: ConnectionId,
+        dn: String,
+        attr: String,
+        values: Vec<String>,
+    ) {
+        let tab = self.tabs.iter().find(|t| t.id == conn_id);
+        if let Some(tab) = tab {
+            if tab.read_only {
+                let _ = self
+                    .action_tx
+                    .send(Action::ErrorMessage("Connection is read-only".to_string()));
+                return;
+            }
+            let tx = self.action_tx.clone();
+
+            match &tab.backend {
+                TabBackend::Offline(_) => {
+                    let _ = tx.send(Action::ErrorMessage(
+                        "Example directory is read-only".to_string(),
+                    ));
+                }
+                TabBackend::Live(connection) => {
+                    let connection = connection.clone();
+                    tab.spawn_op(format!("Modify {}", dn), async move {
+                        let mut conn = connection.lock().await;
+                        let before = read_before_image(&mut conn, &dn).await;
+                        let added: Vec<BulkMod> = values
+                            .iter()
+                            .map(|value| BulkMod::AddValue {
+                                attr: attr.clone(),
+                                value: value.clone(),
+                            })
+                            .collect();
+                        let inverse = before.and_then(|before| {
+                            invert_change(&before, &Change::modify(&dn, &added))
+                        });
+                        match conn.add_attribute_values(&dn, &attr, values).await {
+                            Ok(()) => {
+                                if let Some(inverse) = inverse {
+                                    let step =
+                                        UndoStep::new(format!("Modify {}", dn), vec![inverse]);
+                                    let _ = tx.send(Action::PushUndo(conn_id, step));
+                                }
+                                let _ = tx.send(Action::AttributeSaved(dn));
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::ErrorMessage(format!(
+                                    "Failed to add values: {}",
+                                    e
+                                )));
+                            }
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    /// Look up whether an attribute has DN syntax and whether it's multi-valued,
+    /// using the active tab's schema cache.
+    fn lookup_attr_schema(&self, attr: &str) -> (bool, bool) {
+        if let Some(tab) = self.active_tab() {
+            if let Some(ref schema) = tab.schema {
+                let is_dn = matches!(schema.attribute_syntax(attr), AttributeSyntax::Dn);
+                let multi_valued = !schema.is_single_valued(attr);
+                return (is_dn, multi_valued);
+            }
+        }
+        (false, true) // default: not DN, multi-valued
+    }
+
+    /// Check if any popup/dialog is currently visible.
+    fn popup_active(&self) -> bool {
+        self.context_menu.visible
+            || self.confirm_dialog.visible
+            || self.cert_trust_dialog.visible
+            || self.connect_dialog.visible
+            || self.new_connection_dialog.visible
+            || self.credential_prompt.visible
+            || self.search_dialog.visible
+            || self.attribute_editor.visible
+            || self.attribute_picker.visible
+            || self.export_dialog.visible
+            || self.bulk_update_dialog.visible
+            || self.batch_modify_dialog.visible
+            || self.batch_summary_popup.visible
+            || self.migrate_dialog.visible
+            || self.create_entry_dialog.visible
+            || self.help_popup.visible
+            || self.about_popup.visible
+            || self.error_details_popup.visible
+            || self.value_viewer.visible
+            || self.membership_popup.visible
+            || self.messages_popup.visible
+            || self.columns_dialog.visible
+            || self.password_dialog.visible
+            || self.compare_view.visible
+            || self.doctor_popup.visible
+            || self.server_info_popup.visible
+            || self.export_summary_popup.visible
+            || self.protected_write_dialog.visible
+            || self.write_override_dialog.visible
+            || self.log_panel.visible
+            || self.log_viewer.visible
+            || self.profile_export_dialog.visible
+            || self.profile_import_dialog.visible
+            || self.search_form_dialog.visible
+            || self.snapshot_dialog.visible
+            || self.subtree_compare_dialog.visible
+            || self.subtree_diff_view.visible
+            || self.pending_changes_popup.visible
+            || self.apply_ldif_dialog.visible
+            || self.apply_results_view.visible
+            || self.delete_dialog.visible
+            || self.rename_dialog.visible
+            || self.copy_subtree_dialog.visible
+            || self.activity_popup.visible
+            || self.setup_wizard.visible
+            || self.quick_switcher.visible
+            || self.bookmarks_popup.visible
+    }
+
+    /// Check if any popup, dialog, or text-input mode is active.
+    fn any_popup_or_input_active(&self) -> bool {
+        self.context_menu.visible
+            || self.attribute_editor.visible
+            || self.attribute_picker.visible
+            || self.confirm_dialog.visible
+            || self.cert_trust_dialog.visible
+            || self.connect_dialog.visible
+            || self.new_connection_dialog.visible
+            || self.credential_prompt.visible
+            || self.search_dialog.visible
+            || self.export_dialog.visible
+            || self.bulk_update_dialog.visible
+            || self.batch_modify_dialog.visible
+            || self.batch_summary_popup.visible
+            || self.migrate_dialog.visible
+            || self.create_entry_dialog.visible
+            || self.help_popup.visible
+            || self.about_popup.visible
+            || self.error_details_popup.visible
+            || self.value_viewer.visible
+            || self.membership_popup.visible
+            || self.messages_popup.visible
+            || self.columns_dialog.visible
+            || self.password_dialog.visible
+            || self.compare_view.visible
+            || self.doctor_popup.visible
+            || self.server_info_popup.visible
+            || self.export_summary_popup.visible
+            || self.protected_write_dialog.visible
+            || self.write_override_dialog.visible
+            || self.log_panel.visible
+            || self.log_viewer.visible
+            || self.profile_export_dialog.visible
+            || self.profile_import_dialog.visible
+            || self.search_form_dialog.visible
+            || self.snapshot_dialog.visible
+            || self.subtree_compare_dialog.visible
+            || self.subtree_diff_view.visible
+            || self.pending_changes_popup.visible
+            || self.apply_ldif_dialog.visible
+            || self.apply_results_view.visible
+            || self.delete_dialog.visible
+            || self.rename_dialog.visible
+            || self.copy_subtree_dialog.visible
+            || self.activity_popup.visible
+            || self.setup_wizard.visible
+            || self.quick_switcher.visible
+            || self.bookmarks_popup.visible
+            || self.command_panel.input_active
+            || (self.tree_panel.is_jumping() && self.focus.current() == FocusTarget::TreePanel)
+            || (self.detail_panel.is_searching()
+                && self.focus.current() == FocusTarget::DetailPanel)
+            || (self.schema_viewer.is_filtering() && self.active_layout == ActiveLayout::Schema)
+            || (self.connection_form.is_editing()
+                && self.active_layout == ActiveLayout::Profiles
+                && self.focus.current() == FocusTarget::ConnectionForm)
+    }
+
+    /// Dismiss every open popup/dialog.
+    fn dismiss_all_popups(&mut self) {
+        self.context_menu.hide();
+        self.confirm_dialog.hide();
+        self.cert_trust_dialog.hide();
+        self.connect_dialog.hide();
+        self.new_connection_dialog.hide();
+        self.credential_prompt.hide();
+        self.search_dialog.hide();
+        self.command_panel.soft_deactivate();
+        self.attribute_editor.hide();
+        self.attribute_picker.hide();
+        self.export_dialog.hide();
+        self.bulk_update_dialog.hide();
+        self.batch_modify_dialog.hide();
+        self.batch_summary_popup.hide();
+        self.migrate_dialog.hide();
+        self.create_entry_dialog.hide();
+        self.help_popup.hide();
+        self.about_popup.hide();
+        self.error_details_popup.hide();
+        self.value_viewer.hide();
+        self.membership_popup.hide();
+        self.messages_popup.hide();
+        self.columns_dialog.hide();
+        self.password_dialog.hide();
+        self.compare_view.hide();
+        self.doctor_popup.hide();
+        self.server_info_popup.hide();
+        self.export_summary_popup.hide();
+        self.protected_write_dialog.hide();
+        self.write_override_dialog.hide();
+        self.log_panel.hide();
+        self.log_viewer.hide();
+        self.profile_export_dialog.hide();
+        self.profile_import_dialog.hide();
+        self.search_form_dialog.hide();
+        self.snapshot_dialog.hide();
+        self.subtree_compare_dialog.hide();
+        self.subtree_diff_view.hide();
+        self.pending_changes_popup.hide();
+        self.apply_ldif_dialog.hide();
+        self.apply_results_view.hide();
+        self.delete_dialog.hide();
+        self.rename_dialog.hide();
+        self.copy_subtree_dialog.hide();
+        self.activity_popup.hide();
+        self.quick_switcher.hide();
+        self.bookmarks_popup.hide();
+    }
+
+    /// Main event loop.
+    pub async fn run(&mut self) -> anyhow::Result<()> {
+        tui::install_panic_hook();
+        let mut terminal = tui::init()?;
+
+        let tick_rate = Duration::from_millis(self.config.general.tick_rate_ms);
+
+        loop {
+            // Render
+            terminal.draw(|frame| self.render(frame))?;
+
+            // Poll for events; while results are queued, only wait one frame
+            let timeout = if self.result_ingest.backlog() > 0 {
+                INGEST_FRAME.min(tick_rate)
+            } else {
+                tick_rate
+            };
+            if let Some(app_event) = event::poll_event(timeout) {
+                self.handle_app_event(app_event);
+            }
+            // Input first: handle everything the terminal already has queued
+            while let Some(app_event) = event::poll_ready() {
+                self.handle_app_event(app_event);
+            }
+
+            // Drain action queue
+            while let Ok(action) = self.action_rx.try_recv() {
+                self.process_action(action).await;
+            }
+
+            self.ingest_results();
+
+            if self.should_quit {
+                break;
+            }
+        }
+
+        tui::restore()?;
+        Ok(())
+    }
+
+    /// Route a key press to the focused popup or panel and queue the resulting action.
+    fn handle_key_event(&mut self, key: KeyEvent) {
+        // Clear pending 'g' if a popup/input became active
+        if self.pending_g && self.any_popup_or_input_active() {
+            self.pending_g = false;
+        }
+
+        // Search binding activates search from any non-input context,
+        // but yields to dialogs/popups/editors that capture keystrokes.
+        let action = if self.pending_g {
+            // Resolve vim-style gt/gT tab switching
+            self.pending_g = false;
+            match key.code {
+                KeyCode::Char('t') => Action::NextTab,
+                KeyCode::Char('T') => Action::PrevTab,
+                _ => Action::None,
+            }
+        } else if !self.any_popup_or_input_active()
+            && matches!(
+                self.keymap.resolve_global_only(&key),
+                Action::SearchFocusInput
+            )
+        {
+            self.dismiss_all_popups();
+            if self.active_layout != ActiveLayout::Browser {
+                let _ = self
+                    .action_tx
+                    .send(Action::SwitchLayout(ActiveLayout::Browser));
+            }
+            Action::SearchFocusInput
+        // Popups intercept keys first
+        } else if self.protected_write_dialog.visible {
+            self.protected_write_dialog.handle_key_event(key)
+        } else if self.write_override_dialog.visible {
+            self.write_override_dialog.handle_key_event(key)
+        } else if self.context_menu.visible {
+            self.context_menu.handle_key_event(key)
+        } else if self.quick_switcher.visible {
+            self.quick_switcher.handle_key_event(key)
+        } else if self.bookmarks_popup.visible {
+            self.bookmarks_popup.handle_key_event(key)
+        } else if self.password_dialog.visible {
+            self.password_dialog.handle_key_event(key)
+        } else if self.attribute_editor.visible {
+            self.attribute_editor.handle_key_event(key)
+        } else if self.attribute_picker.visible {
+            self.attribute_picker.handle_key_event(key)
+        } else if self.confirm_dialog.visible {
+            self.confirm_dialog.handle_key_event(key)
+        } else if self.cert_trust_dialog.visible {
+            self.cert_trust_dialog.handle_key_event(key)
+        } else if self.connect_dialog.visible {
+            self.connect_dialog.handle_key_event(key)
+        } else if self.new_connection_dialog.visible {
+            self.new_connection_dialog.handle_key_event(key)
+        } else if self.credential_prompt.visible {
+            self.credential_prompt.handle_key_event(key)
+        } else if self.vault_password_dialog.visible {
+            self.vault_password_dialog.handle_key_event(key)
+        } else if self.batch_modify_dialog.visible {
+            // Opened over the search results
+            self.batch_modify_dialog.handle_key_event(key)
+        } else if self.export_dialog.visible {
+            // Also opened over the search results
+            self.export_dialog.handle_key_event(key)
+        } else if self.columns_dialog.visible {
+            self.columns_dialog.handle_key_event(key)
+        } else if self.batch_summary_popup.visible {
+            self.batch_summary_popup.handle_key_event(key)
+        } else if self.search_dialog.visible {
+            // Search popup is open — route keys based on input state
+            if matches!(
+                self.keymap.resolve_global_only(&key),
+                Action::SearchFocusInput
+            ) {
+                // F9 toggles popup closed
+                self.search_dialog.hide();
+                self.command_panel.soft_deactivate();
+                Action::None
+            } else if self.command_panel.input_active {
+                // Input is active — route to command panel
+                self.command_panel.handle_input_key(key)
+            } else {
+                // Input not active — navigate results, jump or edit filter
+                let to_results = self.search_dialog.is_jumping()
+                    || self.search_dialog.is_quick_filtering()
+                    || key.code == KeyCode::Esc
+                    || self.keymap.command(KeyContext::Results, &key).is_some();
+                match key.code {
+                    _ if to_results => {
+                        let a = self.search_dialog.handle_key_event(key);
+                        if matches!(&a, Action::TreeSelect(_)) {
+                            self.command_panel.soft_deactivate();
+                            let _ = self
+                                .action_tx
+                                .send(Action::FocusPanel(FocusTarget::DetailPanel));
+                        }
+                        a
+                    }
+                    KeyCode::Char('/') => {
+                        // Reactivate input editing
+                        self.command_panel.resume_input();
+                        Action::None
+                    }
+                    KeyCode::Char(c) if !c.is_control() => {
+                        // Start editing with this character
+                        self.command_panel.resume_input();
+                        self.command_panel.handle_input_key(key)
+                    }
+                    _ => Action::None,
+                }
+            }
+        } else if self.bulk_update_dialog.visible {
+            self.bulk_update_dialog.handle_key_event(key)
+        } else if self.migrate_dialog.visible {
+            self.migrate_dialog.handle_key_event(key)
+        } else if self.profile_export_dialog.visible {
+            self.profile_export_dialog.handle_key_event(key)
+        } else if self.profile_import_dialog.visible {
+            self.profile_import_dialog.handle_key_event(key)
+        } else if self.setup_wizard.visible {
+            self.setup_wizard.handle_key_event(key)
+        } else if self.search_form_dialog.visible {
+            self.search_form_dialog.handle_key_event(key)
+        } else if self.snapshot_dialog.visible {
+            self.snapshot_dialog.handle_key_event(key)
+        } else if self.subtree_compare_dialog.visible {
+            self.subtree_compare_dialog.handle_key_event(key)
+        } else if self.apply_ldif_dialog.visible {
+            self.apply_ldif_dialog.handle_key_event(key)
+        } else if self.delete_dialog.visible {
+            self.delete_dialog.handle_key_event(key)
+        } else if self.rename_dialog.visible {
+            self.rename_dialog.handle_key_event(key)
+        } else if self.copy_subtree_dialog.visible {
+            self.copy_subtree_dialog.handle_key_event(key)
+        } else if self.activity_popup.visible {
+            self.activity_popup.handle_key_event(key)
+        } else if self.create_entry_dialog.visible {
+            self.create_entry_dialog.handle_key_event(key)
+        } else if self.help_popup.visible {
+            self.help_popup.handle_key_event(key)
+        } else if self.about_popup.visible {
+            self.about_popup.handle_key_event(key)
+        } else if self.error_details_popup.visible {
+            self.error_details_popup.handle_key_event(key)
+        } else if self.value_viewer.visible {
+            self.value_viewer.handle_key_event(key)
+        } else if self.membership_popup.visible {
+            self.membership_popup.handle_key_event(key)
+        } else if self.messages_popup.visible {
+            self.messages_popup.handle_key_event(key)
+        } else if self.compare_view.visible {
+            self.compare_view.handle_key_event(key)
+        } else if self.subtree_diff_view.visible {
+            self.subtree_diff_view.handle_key_event(key)
+        } else if self.pending_changes_popup.visible {
+            self.pending_changes_popup.handle_key_event(key)
+        } else if self.apply_results_view.visible {
+            self.apply_results_view.handle_key_event(key)
+        } else if self.doctor_popup.visible {
+            self.doctor_popup.handle_key_event(key)
+        } else if self.server_info_popup.visible {
+            self.server_info_popup.handle_key_event(key)
+        } else if self.export_summary_popup.visible {
+            self.export_summary_popup.handle_key_event(key)
+        } else if self.log_panel.visible {
+            self.log_panel.handle_key_event(key)
+        } else if self.log_viewer.visible {
+            self.log_viewer.handle_key_event(key)
+        } else if self.command_panel.input_active && self.active_layout == ActiveLayout::Browser {
+            self.command_panel.handle_input_key(key)
+        } else if self.connection_form.is_editing()
+            && self.active_layout == ActiveLayout::Profiles
+            && self.focus.current() == FocusTarget::ConnectionForm
+        {
+            // Connection form in edit/create mode captures all keys
+            self.connection_form.handle_key_event(key)
+        } else if key.code == KeyCode::Esc && !self.connect_attempts.is_empty() {
+            Action::CancelConnect
+        } else if self.active_layout == ActiveLayout::Schema {
+            let panel_action = match self.focus.current() {
+                FocusTarget::SchemaDefinition => self.schema_viewer.handle_definition_key(key),
+                _ => self.schema_viewer.handle_list_key(key),
+            };
+            if matches!(panel_action, Action::None) && !self.schema_viewer.is_filtering() {
+                self.keymap
+                    .resolve(self.focus.current().into(), key)
+                    .unwrap_or(Action::None)
+            } else {
+                panel_action
+            }
+        } else if self.active_layout == ActiveLayout::Profiles {
+            // Connections layout: route to connections panels first
+            let panel_action = match self.focus.current() {
+                FocusTarget::ConnectionsTree => self.connections_tree.handle_key_event(key),
+                FocusTarget::ConnectionForm => self.connection_form.handle_key_event(key),
+                _ => Action::None,
+            };
+            if matches!(panel_action, Action::None) {
+                self.keymap
+                    .resolve(self.focus.current().into(), key)
+                    .unwrap_or(Action::None)
+            } else {
+                panel_action
+            }
+        } else {
+            // Browser layout: the search form and command input keys work
+            // from either panel, unless the focused one binds the key itself
+            // ('/' in the detail pane searches the entry)
+            let focused = KeyContext::from(self.focus.current());
+            let browser_action = self
+                .keymap
+                .command(KeyContext::Browser, &key)
+                .filter(|_| {
+                    !self.any_popup_or_input_active()
+                        && self.keymap.command(focused, &key).is_none()
+                })
+                .and_then(|command| command.action(KeyContext::Browser));
+            if let Some(action) = browser_action {
+                action
+            } else {
+                // Try panel-specific handler first, fall back to global keymap
+                let panel_action = match self.focus.current() {
+                    FocusTarget::TreePanel => self.tree_panel.handle_key_event(key),
+                    FocusTarget::DetailPanel => self.detail_panel.handle_key_event(key),
+                    _ => Action::None,
+                };
+                if !matches!(panel_action, Action::None) {
+                    panel_action
+                } else {
+                    match self.keymap.resolve(focused, key) {
+                        Some(action) => action,
+                        // Esc nothing else wants cancels the newest operation
+                        None if key.code == KeyCode::Esc => self
+                            .active_tab()
+                            .and_then(|t| t.ops.newest_running())
+                            .map_or(Action::None, |op| Action::CancelOperation(op.id)),
+                        None => Action::None,
+                    }
+                }
+            }
+        };
+        // Initiate vim 'g' prefix when g produces no action
+        if matches!(action, Action::None)
+            && matches!(key.code, KeyCode::Char('g'))
+            && key.modifiers == KeyModifiers::NONE
+            && !self.any_popup_or_input_active()
+        {
+            self.pending_g = true;
+        } else {
+            let _ = self.action_tx.send(action);
+        }
+    }
+
+    fn handle_mouse(&mut self, mouse: crossterm::event::MouseEvent) -> Action {
+        // Popups block mouse events; also clear any drag
+        if self.popup_active() {
+            self.drag_target = None;
+            return Action::None;
+        }
+
+        match mouse.kind {
+            MouseEventKind::Down(crossterm::event::MouseButton::Left) => {
+                // Check if click is on a panel divider (start drag)
+                if let Some(target) = self.divider_hit(mouse.column, mouse.row) {
+                    self.drag_target = Some(target);
+                    return Action::None;
+                }
+
+                let pos = Rect::new(mouse.column, mouse.row, 1, 1);
+
+                // Check layout bar clicks (unified tab strip)
+                if let Some(bar) = self.layout_bar_area {
+                    if bar.intersects(pos) {
+                        for &(start, end, ref target) in &self.layout_bar.hit_regions {
+                            if mouse.column >= start && mouse.column < end {
+                                return match target {
+                                    BarTarget::Layout(ActiveLayout::Schema) => {
+                                        Action::ShowSchemaViewer
+                                    }
+                                    BarTarget::Layout(layout) => Action::SwitchLayout(*layout),
+                                    BarTarget::Tab(id) => Action::SwitchTab(*id),
+                                };
+                            }
+                        }
+                    }
+                }
+
+                // Check connections layout panels
+                if self.active_layout == ActiveLayout::Profiles {
+                    if let Some(ct) = self.conn_tree_area {
+                        if ct.intersects(pos) {
+                            return Action::FocusPanel(FocusTarget::ConnectionsTree);
+                        }
+                    }
+                    if let Some(cf) = self.conn_form_area {
+                        if cf.intersects(pos) {
+                            return Action::FocusPanel(FocusTarget::ConnectionForm);
+                        }
+                    }
+                    return Action::None;
+                }
+                if self.active_layout == ActiveLayout::Schema {
+                    if let Some(list) = self.schema_list_area {
+                        if list.intersects(pos) {
+                            return Action::FocusPanel(FocusTarget::SchemaList);
+                        }
+                    }
+                    if let Some(definition) = self.schema_definition_area {
+                        if definition.intersects(pos) {
+                            return Action::FocusPanel(FocusTarget::SchemaDefinition);
+                        }
+                    }
+                    return Action::None;
+                }
+
+                // Browser layout panels
+                if let Some(tree) = self.tree_area {
+                    if tree.intersects(pos) {
+                        return Action::FocusPanel(FocusTarget::TreePanel);
+                    }
+                }
+                if let Some(detail) = self.detail_area {
+                    if detail.intersects(pos) {
+                        return Action::FocusPanel(FocusTarget::DetailPanel);
+                    }
+                }
+                Action::None
+            }
+            MouseEventKind::Drag(crossterm::event::MouseButton::Left) => {
+                if let Some(target) = self.drag_target {
+                    self.apply_drag(target, mouse.column, mouse.row);
+                }
+                Action::None
+            }
+            MouseEventKind::Down(crossterm::event::MouseButton::Right) => {
+                if self.active_layout == ActiveLayout::Profiles {
+                    let pos = Rect::new(mouse.column, mouse.row, 1, 1);
+                    if let Some(ct) = self.conn_tree_area {
+                        if ct.intersects(pos) {
+                            self.context_menu
+                                .show_for_profiles(self.connection_form.profile_index);
+                            self.context_menu.set_anchor(mouse.column, mouse.row);
+                            return Action::Render;
+                        }
+                    }
+                    if let Some(cf) = self.conn_form_area {
+                        if cf.intersects(pos) {
+                            self.context_menu
+                                .show_for_profiles(self.connection_form.profile_index);
+                            self.context_menu.set_anchor(mouse.column, mouse.row);
+                            return Action::Render;
+                        }
+                    }
+                }
+                if self.active_layout == ActiveLayout::Browser {
+                    let pos = Rect::new(mouse.column, mouse.row, 1, 1);
+                    if let Some(tree) = self.tree_area {
+                        if tree.intersects(pos) {
+                            if let Some(dn) = self.tree_panel.selected_entry_dn() {
+                                self.context_menu.show_for_tree(&dn);
+                                self.context_menu.set_anchor(mouse.column, mouse.row);
+                                return Action::Render;
+                            }
+                        }
+                    }
+                    if let Some(detail) = self.detail_area {
+                        if detail.intersects(pos) && !self.detail_panel.selected_is_binary() {
+                            if let (Some(entry), Some((attr, val))) = (
+                                &self.detail_panel.entry,
+                                self.detail_panel.selected_attr_value(),
+                            ) {
+                                let dn = entry.dn.clone();
+                                let attr = attr.to_string();
+                                let val = val.to_string();
+                                self.context_menu.show_for_detail(&dn, &attr, &val);
+                                self.context_menu.set_anchor(mouse.column, mouse.row);
+                                return Action::Render;
+                            }
+                        }
+                    }
+                }
+                Action::None
+            }
+            MouseEventKind::Up(_) => {
+                if self.drag_target.take().is_some() {
+                    self.save_layout();
+                }
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Check if a mouse position is on (or within 1 cell of) a panel divider.
+    fn divider_hit(&self, col: u16, row: u16) -> Option<DragTarget> {
+        match self.active_layout {
+            ActiveLayout::Browser => {
+                // Vertical divider: right edge of tree panel
+                if let Some(tree) = self.tree_area {
+                    let divider_col = tree.x + tree.width;
+                    if col.abs_diff(divider_col) <= 1 && row >= tree.y && row < tree.y + tree.height
+                    {
+                        return Some(DragTarget::Tree);
+                    }
+                }
+            }
+            ActiveLayout::Profiles => {
+                // Vertical divider: right edge of profiles tree
+                if let Some(ct) = self.conn_tree_area {
+                    let divider_col = ct.x + ct.width;
+                    if col.abs_diff(divider_col) <= 1 && row >= ct.y && row < ct.y + ct.height {
+                        return Some(DragTarget::Tree);
+                    }
+                }
+            }
+            ActiveLayout::Schema => {
+                if let Some(list) = self.schema_list_area {
+                    let divider_col = list.x + list.width;
+                    if col.abs_diff(divider_col) <= 1 && row >= list.y && row < list.y + list.height
+                    {
+                        return Some(DragTarget::Tree);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Update split percentages based on the current drag position.
+    fn apply_drag(&mut self, target: DragTarget, col: u16, _row: u16) {
+        match target {
+            DragTarget::Tree => {
+                if let Some((left, total)) = self.pane_extent() {
+                    let offset = col.saturating_sub(left.x);
+                    self.pane_ratios_mut().tree_percent = panes::percent_at(total, offset);
+                }
+            }
+        }
+    }
+
+    /// Split ratios of the layout on screen.
+    fn pane_ratios_mut(&mut self) -> &mut PaneRatios {
+        match self.active_layout {
+            ActiveLayout::Browser => &mut self.config.layout.browser,
+            ActiveLayout::Profiles => &mut self.config.layout.profiles,
+            ActiveLayout::Schema => &mut self.config.layout.schema,
+        }
+    }
+
+    /// The left pane and the width both panes shared in the last frame.
+    fn pane_extent(&self) -> Option<(Rect, u16)> {
+        let (left, right) = match self.active_layout {
+            ActiveLayout::Browser => (self.tree_area?, self.detail_area?),
+            ActiveLayout::Profiles => (self.conn_tree_area?, self.conn_form_area?),
+            ActiveLayout::Schema => (self.schema_list_area?, self.schema_definition_area?),
+        };
+        Some((left, left.width + right.width))
+    }
+
+    fn save_layout(&mut self) {
+        if let Err(e) = self.save_config() {
+            self.push_error(format!("Failed to save layout: {}", e));
+        }
+    }
+
+    async fn process_action(&mut self, action: Action) {
+        // Recorded edits only ever go to a changefile
+        if action.is_write()
+            && !self.recording_edits()
+            && self.active_tab().is_some_and(ConnectionTab::writes_locked)
+        {
+            self.push_error("profile is read-only".to_string());
+            return;
+        }
+        match action {
+            Action::Quit => {
+                self.should_quit = true;
+            }
+            Action::FocusNext => {
+                self.focus.next();
+            }
+            Action::FocusPrev => {
+                self.focus.prev();
+            }
+            Action::FocusPanel(target) => {
+                self.focus.set(target);
+            }
+
+            // Tab management — cycle through Profiles → conn1 → conn2 → … → Profiles
+            Action::NextTab => {
+                let tab_count = self.tab_bar.tabs.len();
+                if tab_count > 0 {
+                    if self.active_layout == ActiveLayout::Profiles {
+                        let first_id = self.tab_bar.tabs[0].id;
+                        self.switch_to_tab(first_id);
+                        self.active_layout = ActiveLayout::Browser;
+                        self.layout_bar.active = ActiveLayout::Browser;
+                        self.focus.set_layout(ActiveLayout::Browser);
+                    } else {
+                        let current_idx = self
+                            .tab_bar
+                            .tabs
+                            .iter()
+                            .position(|t| Some(t.id) == self.active_tab_id)
+                            .unwrap_or(0);
+                        if current_idx + 1 < tab_count {
+                            let next_id = self.tab_bar.tabs[current_idx + 1].id;
+                            self.switch_to_tab(next_id);
+                        } else {
+                            self.active_layout = ActiveLayout::Profiles;
+                            self.layout_bar.active = ActiveLayout::Profiles;
+                            self.focus.set_layout(ActiveLayout::Profiles);
+                        }
+                    }
+                }
+            }
+            Action::PrevTab => {
+                let tab_count = self.tab_bar.tabs.len();
+                if tab_count > 0 {
+                    if self.active_layout == ActiveLayout::Profiles {
+                        let last_id = self.tab_bar.tabs[tab_count - 1].id;
+                        self.switch_to_tab(last_id);
+                        self.active_layout = ActiveLayout::Browser;
+                        self.layout_bar.active = ActiveLayout::Browser;
+                        self.focus.set_layout(ActiveLayout::Browser);
+                    } else {
+                        let current_idx = self
+                            .tab_bar
+                            .tabs
+                            .iter()
+                            .position(|t| Some(t.id) == self.active_tab_id)
+                            .unwrap_or(0);
+                        if current_idx > 0 {
+                            let prev_id = self.tab_bar.tabs[current_idx - 1].id;
+                            self.switch_to_tab(prev_id);
+                        } else {
+                            self.active_layout = ActiveLayout::Profiles;
+                            self.layout_bar.active = ActiveLayout::Profiles;
+                            self.focus.set_layout(ActiveLayout::Profiles);
+                        }
+                    }
+                }
+            }
+            Action::SwitchTab(id) => {
+                self.switch_to_tab(id);
+                // A connection tab always opens the Browser layout
+                if self.active_layout != ActiveLayout::Browser {
+                    self.active_layout = ActiveLayout::Browser;
+                    self.layout_bar.active = ActiveLayout::Browser;
+                    self.focus.set_layout(ActiveLayout::Browser);
+                }
+            }
+            Action::ShowConnectDialog => {
+                let mut profiles = self.config.connections.clone();
+                profiles.push(example_profile());
+                self.connect_dialog.show(profiles);
+            }
+            Action::ShowNewConnectionForm => {
+                self.connect_dialog.hide();
+                self.new_connection_dialog.show();
+            }
+            Action::ConnectByIndex(idx) => {
+                // The example profile is appended after saved profiles
+                let profile = if idx == self.config.connections.len() {
+                    Some(example_profile())
+                } else {
+                    self.config.connections.get(idx).cloned()
+                };
+                if let Some(profile) = profile {
+                    self.connect_profile(profile);
+                }
+            }
+            Action::ConnectAdHoc(profile, password) => {
+                self.start_connect(profile, password, ConnectOrigin::AdHoc);
+            }
+            Action::PromptCredentials(profile) => {
+                self.credential_prompt.show(profile);
+            }
+            Action::ConnectWithCredentials(profile, password) => {
+                self.start_connect(profile, password, ConnectOrigin::Credentials);
+            }
+            Action::ConnectFinished(attempt, result) => {
+                let Some(pending) = self.connect_attempts.finish(attempt) else {
+                    debug!("Dropping result of cancelled connect attempt {}", attempt);
+                    return;
+                };
+                let profile = pending.profile;
+                let password = pending.password;
+                if self.setup_wizard.is_testing(&profile.name) {
+                    match &result {
+                        Ok(server) => self.setup_wizard.test_finished(Ok(server.base_dn.clone())),
+                        Err(ConnectFailure::Auth(e)) => self
+                            .setup_wizard
+                            .test_finished(Err(format!("authentication failed: {}", e))),
+                        Err(ConnectFailure::Other(e)) => {
+                            self.setup_wizard.test_finished(Err(e.clone()))
+                        }
+                        // Still waiting on the certificate decision
+                        Err(ConnectFailure::CertUntrusted(_)) => {}
+                    }
+                }
+                match result {
+                    Ok(server) => {
+                        self.finish_connect(&profile, *server);
+                        match pending.origin {
+                            ConnectOrigin::Profile => {}
+                            ConnectOrigin::AdHoc => {
+                                self.last_adhoc_profile = Some(profile);
+                                let tip_msg = format!(
+                                    "Tip: Press {} to save this connection to config",
+                                    self.keymap.hint("save_connection"),
+                                );
+                                self.status_bar.set_message(tip_msg.clone());
+                                self.log_panel.push_info(tip_msg);
+                            }
+                            ConnectOrigin::Credentials => {
+                                let auth_msg = format!(
+                                    "Authenticated as {}",
+                                    profile.bind_dn.as_deref().unwrap_or("anonymous")
+                                );
+                                self.status_bar.set_message(auth_msg.clone());
+                                self.log_panel.push_info(auth_msg);
+                                // Auto-store in vault or keychain if the profile uses it
+                                if !password.is_empty() {
+                                    self.remember_password(&profile, &password);
+                                }
+                            }
+                        }
+                    }
+                    Err(ConnectFailure::CertUntrusted(cert_info)) => {
+                        self.push_message(format!(
+                            "Certificate {} for {} rejected: {}",
+                            cert_info.subject, cert_info.host, cert_info.problem
+                        ));
+                        let _ = self.action_tx.send(Action::ShowCertTrustDialog {
+                            cert_info,
+                            profile: Box::new(profile),
+                            password,
+                        });
+                    }
+                    Err(ConnectFailure::Auth(e)) => {
+                        self.push_error(format!("Authentication failed: {}", e));
+                        self.credential_prompt.show(profile);
+                    }
+                    Err(ConnectFailure::Other(e)) => {
+                        self.push_error(format!("Connection failed: {}", e));
+                    }
+                }
+            }
+            Action::CancelConnect => {
+                self.setup_wizard
+                    .test_finished(Err("Cancelled".to_string()));
+                let hosts = self.connect_attempts.cancel_all();
+                if !hosts.is_empty() {
+                    let msg = format!("Cancelled connecting to {}", hosts.join(", "));
+                    self.status_bar.set_message(msg.clone());
+                    self.log_panel.push_info(msg);
+                }
+            }
+            Action::SaveCurrentConnection => {
+                if let Some(profile) = self.last_adhoc_profile.take() {
+                    match AppConfig::append_connection(&profile) {
+                        Ok(()) => {
+                            let save_msg = format!("Saved connection '{}' to config", profile.name);
+                            self.status_bar.set_message(save_msg.clone());
+                            self.log_panel.push_info(save_msg);
+                            self.config.connections.push(profile);
+                        }
+                        Err(e) => {
+                            self.push_error(format!("Failed to save connection: {}", e));
+                        }
+                    }
+                } else {
+                    self.push_message("No ad-hoc connection to save".to_string());
+                }
+            }
+            // Layout switching
+            Action::SwitchLayout(ActiveLayout::Schema) if self.active_tab_id.is_none() => {
+                self.push_error("No active connection".to_string());
+            }
+            Action::SwitchLayout(layout) => {
+                self.active_layout = layout;
+                self.layout_bar.active = layout;
+                self.focus.set_layout(layout);
+            }
+
+            Action::ResizePanes(delta) => {
+                let total = self.pane_extent().map_or(0, |(_, total)| total);
+                let ratios = self.pane_ratios_mut();
+                let percent = panes::resize(ratios.tree_percent, delta, total);
+                if percent != ratios.tree_percent {
+                    ratios.tree_percent = percent;
+                    self.save_layout();
+                }
+            }
+            Action::ResetLayout => {
+                self.config.layout = LayoutConfig::default();
+                self.save_layout();
+                self.push_message("Pane sizes reset".to_string());
+            }
+
+            // Quick switcher
+            Action::ShowQuickSwitcher => {
+                let providers: [&dyn JumpTargetProvider; 5] = [
+                    &self.layout_bar,
+                    &self.tab_bar,
+                    &self.recent_entries,
+                    &self.search_dialog,
+                    &self.log_panel,
+                ];
+                let targets = providers.iter().flat_map(|p| p.jump_targets()).collect();
+                self.quick_switcher.show(targets);
+            }
+            Action::QuickSwitch(target) => self.jump_to(target),
+
+            // Bookmarks
+            Action::ShowBookmarks => self.bookmarks_popup.show(self.config.bookmarks()),
+            Action::PromptBookmark(target) => match self.active_tab() {
+                Some(tab) => {
+                    let profile = tab.profile.clone();
+                    self.bookmarks_popup.prompt(profile, target);
+                }
+                None => self
+                    .status_bar
+                    .set_error("No active connection".to_string()),
+            },
+            Action::AddBookmark(bookmark) => {
+                let name = bookmark.name.clone();
+                match self.config.add_bookmark(bookmark) {
+                    Ok(()) => {
+                        if let Err(e) = self.save_config() {
+                            self.push_error(format!("Failed to save config: {}", e));
+                        } else {
+                            let _ = self
+                                .action_tx
+                                .send(Action::StatusMessage(format!("Bookmarked '{}'", name)));
+                        }
+                    }
+                    Err(e) => self.push_error(e),
+                }
+            }
+            Action::RemoveBookmark(profile, name) => {
+                if !self.config.remove_bookmark(&profile, &name) {
+                    return;
+                }
+                match self.save_config() {
+                    Ok(()) => {
+                        self.push_message(format!("Removed bookmark '{}' of {}", name, profile))
+                    }
+                    Err(e) => self.push_error(format!("Failed to save config: {}", e)),
+                }
+            }
+            Action::OpenBookmark(bookmark) => self.open_bookmark(bookmark),
+            Action::RevealInTree(conn_id, dn) => self.reveal_in_tree(conn_id, dn, true),
+
+            // Outstanding operations
+            Action::ShowActivity => match self.active_tab() {
+                Some(tab) => {
+                    let ops = tab.ops.list(Instant::now());
+                    let label = tab.label.clone();
+                    self.activity_popup.show(&label, ops);
+                }
+                None => self
+                    .status_bar
+                    .set_message("No connection to show operations for".to_string()),
+            },
+            Action::CancelOperation(op) => {
+                if let Some(tab) = self.active_tab() {
+                    let label = tab
+                        .ops
+                        .list(Instant::now())
+                        .into_iter()
+                        .find(|o| o.id == op)
+                        .map(|o| o.label);
+                    if tab.ops.cancel(op) {
+                        let ops = tab.ops.list(Instant::now());
+                        self.activity_popup.set_ops(ops);
+                        let msg = format!("Cancelled: {}", label.unwrap_or_default());
+                        self.status_bar.set_message(msg.clone());
+                        self.log_panel.push_info(msg);
+                    }
+                }
+            }
+            Action::CancelAllOperations => {
+                if let Some(tab) = self.active_tab() {
+                    let cancelled = tab.ops.cancel_all();
+                    let ops = tab.ops.list(Instant::now());
+                    self.activity_popup.set_ops(ops);
+                    let msg = format!("Cancelled {} operations", cancelled);
+                    self.status_bar.set_message(msg.clone());
+                    self.log_panel.push_info(msg);
+                }
+            }
+
+            // Connections Manager
+            Action::ConnMgrSelect(idx) => {
+                self.connection_form.set_profile_names(self.profile_names());
+                if idx == self.config.connections.len() {
+                    // Example profile — show it in view mode
+                    let profile = example_profile();
+                    self.connection_form.view_profile(idx, &profile);
+                } else if let Some(profile) = self.config.connections.get(idx) {
+                    self.connection_form.view_profile(idx, profile);
+                }
+            }
+            Action::ConnMgrNew => {
+                self.connection_form.set_profile_names(self.profile_names());
+                self.connection_form.new_profile();
+                self.focus.set(FocusTarget::ConnectionForm);
+            }
+            Action::ConnMgrSave(idx, profile) => {
+                if idx >= self.config.connections.len() {
+                    self.push_error("Cannot edit example profile".to_string());
+                } else {
+                    let typed_password = self.connection_form.password().to_string();
+                    // Rename vault entry if profile name changed
+                    let old_name = self.config.connections[idx].name.clone();
+                    let new_name = profile.name.clone();
+                    let is_vault_method =
+                        matches!(profile.credential_method, CredentialMethod::Vault);
+                    let uses_keychain =
+                        matches!(profile.credential_method, CredentialMethod::Keychain);
+
+                    // Hooks, protected DNs and result columns are not
+                    // editable in the form; keep the existing ones
+                    let mut profile = *profile;
+                    let existing = &self.config.connections[idx];
+                    profile.hooks = existing.hooks.clone();
+                    profile.protected_dns = existing.protected_dns.clone();
+                    profile.protection = existing.protection;
+                    profile.result_columns = existing.result_columns.clone();
+                    self.config.update_connection(idx, profile);
+                    if let Err(e) = self.save_config() {
+                        self.push_error(format!("Failed to save config: {}", e));
+                    } else {
+                        self.status_bar.set_message("Profile saved".to_string());
+                        self.log_panel.push_info("Profile saved".to_string());
+                    }
+
+                    // Handle vault and keychain rename
+                    if old_name != new_name {
+                        if let Some(ref mut vault) = self.vault {
+                            let _ = vault.rename_profile(&old_name, &new_name);
+                        }
+                        if uses_keychain {
+                            if let Err(e) =
+                                CredentialProvider::rename_in_keychain(&old_name, &new_name)
+                            {
+                                self.push_error(format!("Keychain password not renamed: {}", e));
+                            }
+                        }
+                    }
+                    if uses_keychain && !typed_password.is_empty() {
+                        let profile = self.config.connections[idx].clone();
+                        self.remember_password(&profile, &typed_password);
+                    }
+
+                    // If using Vault method, store the typed password or
+                    // prompt for one
+                    if is_vault_method {
+                        if let Some(ref mut vault) = self.vault {
+                            if !typed_password.is_empty() {
+                                let _ = vault.set_password(&new_name, &typed_password);
+                            } else if vault.get_password(&new_name).is_none() {
+                                self.vault_password_dialog.show_store_password(&new_name);
+                            }
+                        }
+                    }
+
+                    if let Some(updated) = self.config.connections.get(idx) {
+                        self.connection_form.view_profile(idx, updated);
+                    }
+                }
+            }
+            Action::ConnMgrCreate(profile) => {
+                let is_vault_method = matches!(profile.credential_method, CredentialMethod::Vault);
+                let profile_name = profile.name.clone();
+                let typed_password = self.connection_form.password().to_string();
+
+                self.config.connections.push(*profile);
+                let new_idx = self.config.connections.len() - 1;
+                if let Err(e) = self.save_config() {
+                    self.push_error(format!("Failed to save config: {}", e));
+                } else {
+                    self.push_message("Profile created".to_string());
+                }
+                if let Some(created) = self.config.connections.get(new_idx) {
+                    self.connection_form.view_profile(new_idx, created);
+                }
+
+                let uses_keychain = self
+                    .config
+                    .connections
+                    .get(new_idx)
+                    .filter(|p| matches!(p.credential_method, CredentialMethod::Keychain))
+                    .cloned();
+                if let Some(profile) = uses_keychain.filter(|_| !typed_password.is_empty()) {
+                    self.remember_password(&profile, &typed_password);
+                }
+
+                // If using Vault method, store the typed password or
+                // prompt for one
+                if is_vault_method {
+                    if let Some(ref mut vault) = self.vault {
+                        if typed_password.is_empty() {
+                            self.vault_password_dialog
+                                .show_store_password(&profile_name);
+                        } else {
+                            let _ = vault.set_password(&profile_name, &typed_password);
+                        }
+                    }
+                }
+            }
+            Action::ConnMgrTest(profile, password) => self.test_profile(*profile, password),
+            Action::ConnMgrTestFinished(result) => self.connection_form.test_finished(result),
+            Action::ConnectionHealthChanged(id, health) => self.health_changed(id, health),
+            Action::Reconnect(id) => self.reconnect_tab(id),
+            Action::AllowWrites(id) => {
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == id) {
+                    tab.set_write_override(true);
+                    let msg = format!("Writes allowed on {} until it reconnects", tab.profile);
+                    self.log_panel.push_info(msg.clone());
+                    self.status_bar.set_warning(msg);
+                }
+            }
+            Action::ConnMgrDelete(idx) => {
+                if idx >= self.config.connections.len() {
+                    self.push_error("Cannot delete example profile".to_string());
+                } else {
+                    let profile_name = self.config.connections[idx].name.clone();
+                    if matches!(
+                        self.config.connections[idx].credential_method,
+                        CredentialMethod::Keychain
+                    ) {
+                        let _ = CredentialProvider::delete_from_keychain(&profile_name);
+                    }
+                    self.config.delete_connection(idx);
+                    if let Err(e) = self.save_config() {
+                        self.push_error(format!("Failed to save config: {}", e));
+                    } else {
+                        self.push_message("Profile deleted".to_string());
+                    }
+                    // Remove from vault
+                    if let Some(ref mut vault) = self.vault {
+                        let _ = vault.remove_password(&profile_name);
+                    }
+                    self.connection_form.clear();
+                }
+            }
+            Action::ConnMgrDuplicate(idx) => {
+                if let Some(profile) = self.config.connections.get(idx).cloned() {
+                    let mut dup = profile;
+                    dup.name = format!("{}-copy", dup.name);
+                    self.config.connections.push(dup);
+                    let new_idx = self.config.connections.len() - 1;
+                    if let Err(e) = self.save_config() {
+                        self.push_error(format!("Failed to save config: {}", e));
+                    } else {
+                        self.push_message("Profile duplicated".to_string());
+                    }
+                    if let Some(created) = self.config.connections.get(new_idx) {
+                        self.connection_form.view_profile(new_idx, created);
+                    }
+                }
+            }
+            Action::ConnMgrConnect(idx) => {
+                let profile = if idx == self.config.connections.len() {
+                    Some(example_profile())
+                } else {
+                    self.config.connections.get(idx).cloned()
+                };
+                if let Some(profile) = profile {
+                    // Switches to the Browser layout once connected
+                    self.connect_profile(profile);
+                }
+            }
+
+            Action::ConnMgrExport => {
+                if self.config.connections.is_empty() {
+                    self.push_error("No profiles to export".to_string());
+                } else {
+                    self.profile_export_dialog.show(&self.config.connections);
+                }
+            }
+            Action::ConnMgrExportExecute {
+                profiles,
+                path,
+                include_passwords,
+                passphrase,
+            } => {
+                let mut passwords = BTreeMap::new();
+                if include_passwords {
+                    for profile in &profiles {
+                        let password = match profile.credential_method {
+                            CredentialMethod::Vault => self
+                                .vault
+                                .as_ref()
+                                .and_then(|v| v.get_password(&profile.name))
+                                .map(str::to_string),
+                            // Keychain secrets stay in the keychain
+                            CredentialMethod::Prompt
+                            | CredentialMethod::Command
+                            | CredentialMethod::Keychain => None,
+                        };
+                        if let Some(password) = password {
+                            passwords.insert(profile.name.clone(), password);
+                        }
+                    }
+                }
+                let content = match AppConfig::export_profiles_with_passwords(
+                    &profiles,
+                    &passwords,
+                    passphrase.as_deref(),
+                ) {
+                    Ok(c) => c,
+                    Err(e) => {
+                        self.push_error(e);
+                        return;
+                    }
+                };
+                if let Err(e) = write_replacing(&path, content.as_bytes()) {
+                    self.push_error(format!("Failed to write {}: {}", path.display(), e));
+                } else if passwords.is_empty() {
+                    self.push_message(format!(
+                        "Exported {} profile(s) to {}",
+                        profiles.len(),
+                        path.display()
+                    ));
+                } else {
+                    self.push_message(format!(
+                        "Exported {} profile(s) with {} password(s) to {}{}",
+                        profiles.len(),
+                        passwords.len(),
+                        path.display(),
+                        if passphrase.is_some() {
+                            " (encrypted)"
+                        } else {
+                            ""
+                        }
+                    ));
+                }
+            }
+            Action::ConnMgrImport => {
+                self.profile_import_dialog.show(&self.config.connections);
+            }
+            Action::ConnMgrImportExecute(imports) => {
+                let report = self.config.merge_imported(imports);
+                if let Err(e) = self.save_config() {
+                    self.push_error(format!("Failed to save config: {}", e));
+                } else {
+                    self.push_message(format!("Imported {} profile(s)", report.changed));
+                }
+                let mut unstored = 0;
+                for (name, password) in &report.passwords {
+                    let method = self
+                        .config
+                        .connections
+                        .iter()
+                        .find(|p| &p.name == name)
+                        .map(|p| p.credential_method.clone());
+                    let stored = match (method, self.vault.as_mut()) {
+                        (Some(CredentialMethod::Vault), Some(vault)) => {
+                            vault.set_password(name, password).is_ok()
+                        }
+                        (Some(CredentialMethod::Keychain), _) => {
+                            CredentialProvider::store_in_keychain(name, password).is_ok()
+                        }
+                        _ => false,
+                    };
+                    if !stored {
+                        unstored += 1;
+                    }
+                }
+                if unstored > 0 {
+                    self.push_error(format!(
+                        "{} imported password(s) were not stored; use the vault or keychain method",
+                        unstored
+                    ));
+                }
+                // Refresh the form if a profile was being viewed
+                if let Some(idx) = self.config.connections.len().checked_sub(1) {
+                    self.connection_form
+                        .view_profile(idx, &self.config.connections[idx].clone());
+                }
+            }
+
+            // Setup wizard
+            Action::ShowSetup => self.start_setup(),
+            Action::SetupSaveProfile(profile) => self.save_setup_profile(*profile),
+            Action::SetupTestConnection(profile) => {
+                self.save_setup_profile((*profile).clone());
+                self.connect_profile(*profile);
+            }
+            Action::SetupImport(path) => {
+                let action = self
+                    .profile_import_dialog
+                    .show_file(&self.config.connections, &path);
+                if !matches!(action, Action::None) {
+                    let _ = self.action_tx.send(action);
+                }
+            }
+            Action::SetupFinished(theme) => {
+                if let Some(theme) = theme {
+                    if let Some(styles) = Styles::builtin(&theme) {
+                        self.theme.set(styles);
+                    }
+                    self.config.general.theme = theme;
+                }
+                let msg = "Setup complete".to_string();
+                // Written even when every step was skipped, so setup does
+                // not come back on the next start
+                if let Err(e) = self.save_config() {
+                    self.push_error(format!("Failed to save config: {}", e));
+                }
+                self.config.first_launch = false;
+                self.status_bar.set_message(msg.clone());
+                self.log_panel.push_info(msg);
+                if self.tabs.is_empty() {
+                    match self.config.connections.first().cloned() {
+                        Some(profile) => self.connect_profile(profile),
+                        None => self.status_bar.set_message(format!(
+                            "No profiles configured. Press {} to connect, or run :setup again",
+                            self.keymap.hint("show_connect_dialog"),
+                        )),
+                    }
+                }
+            }
+
+            Action::ConnMgrAdoptMigration => {
+                if let Some((name, password)) = self.migrated_password.take() {
+                    self.move_password_to_keychain(&name, &password);
+                }
+                match self.save_config() {
+                    Ok(()) => self.push_message(format!(
+                        "Config migrated; the old file was kept as {}",
+                        BACKUP_FILE
+                    )),
+                    Err(e) => self.push_error(format!("Failed to save config: {}", e)),
+                }
+                // The startup connection waited for the answer
+                if let Some(profile) = self.config.connections.first().cloned() {
+                    self.connect_profile(profile);
+                }
+            }
+
+            Action::ConnMgrSelectFolder(path) => {
+                let desc = self
+                    .config
+                    .folder_description(&path)
+                    .unwrap_or_default()
+                    .to_string();
+                self.connection_form.view_folder(&path, &desc);
+            }
+            Action::ConnMgrSaveFolderDesc(path, description) => {
+                // Update or insert the folder config
+                if let Some(existing) = self.config.folders.iter_mut().find(|f| f.path == path) {
+                    existing.description = description.clone();
+                } else {
+                    self.config.folders.push(crate::config::FolderConfig {
+                        path: path.clone(),
+                        description: description.clone(),
+                    });
+                }
+                if let Err(e) = self.save_config() {
+                    self.push_error(format!("Failed to save config: {}", e));
+                } else {
+                    self.push_message("Folder description saved".to_string());
+                }
+                self.connection_form.view_folder(&path, &description);
+            }
+
+            // Certificate Trust
+            Action::ShowCertTrustDialog {
+                cert_info,
+                profile,
+                password,
+            } => {
+                self.cert_trust_dialog.show(*cert_info, *profile, password);
+            }
+            Action::TrustCertAndConnect {
+                cert_info,
+                fingerprint,
+                always,
+                profile,
+                password,
+            } => {
+                if always {
+                    let entry = TrustedCertEntry {
+                        host: cert_info.host.clone(),
+                        port: cert_info.port,
+                        fingerprint_sha256: fingerprint.clone(),
+                        subject: cert_info.subject.clone(),
+                    };
+                    self.trust_store.trust_always(entry);
+                    // Persist to config
+                    self.config.trusted_certificates = self.trust_store.to_config_entries();
+                    if let Err(e) = self.save_config() {
+                        self.push_error(format!("Failed to save config: {}", e));
+                    }
+                    self.push_message("Certificate trusted permanently".to_string());
+                } else {
+                    self.trust_store.trust_session(fingerprint);
+                    self.push_message("Certificate trusted for this session".to_string());
+                }
+                // Retry the connection
+                self.start_connect(*profile, password, ConnectOrigin::Profile);
+            }
+
+            Action::CloseCurrentTab => {
+                if let Some(id) = self.active_tab_id {
+                    self.trigger_disconnect_hook(id);
+                    self.tabs.retain(|t| t.id != id);
+                    self.tab_bar.remove_tab(id);
+                    self.active_tab_id = self.tab_bar.active_tab;
+                    self.detail_panel.clear();
+                    self.sync_status_context();
+                    if self.active_tab_id.is_none() {
+                        self.active_layout = ActiveLayout::Profiles;
+                        self.layout_bar.active = ActiveLayout::Profiles;
+                        self.focus.set_layout(ActiveLayout::Profiles);
+                    }
+                }
+            }
+            Action::CloseTab(id) => {
+                self.trigger_disconnect_hook(id);
+                self.tabs.retain(|t| t.id != id);
+                self.recent_entries.remove_tab(id);
+                self.tab_bar.remove_tab(id);
+                if self.active_tab_id == Some(id) {
+                    self.active_tab_id = self.tab_bar.active_tab;
+                    self.detail_panel.clear();
+                    self.sync_status_context();
+                    if self.active_tab_id.is_none() {
+                        self.active_layout = ActiveLayout::Profiles;
+                        self.layout_bar.active = ActiveLayout::Profiles;
+                        self.focus.set_layout(ActiveLayout::Profiles);
+                    }
+                }
+            }
+
+            // Tree
+            Action::TreeExpand(dn) => {
+                if let Some((direction, parent)) = loom_core::tree::parse_page_marker(&dn) {
+                    let _ = self
+                        .action_tx
+                        .send(Action::TreePage(parent.to_string(), direction));
+                } else if !dn.is_empty() && !loom_core::tree::is_marker(&dn) {
+                    // Children listed before are shown as they were; `R`
+                    // lists them again
+                    let load = self
+                        .active_tab()
+                        .and_then(|t| t.directory_tree.find_node(&dn))
+                        .map(|n| n.load.clone());
+                    if !matches!(load, Some(LoadState::Loaded | LoadState::Loading)) {
+                        let _ = self.action_tx.send(Action::LoadChildren(dn.clone()));
+                    }
+                    if let Some(id) = self.active_tab_id {
+                        self.spawn_load_entry(id, dn);
+                    }
+                }
+            }
+            Action::LoadChildren(dn) => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_load_children(id, dn);
+                }
+            }
+            Action::TreeCollapse(_dn) => {}
+            Action::TreeSelect(dn) => {
+                if !dn.is_empty() && !loom_core::tree::is_marker(&dn) {
+                    if let Some(id) = self.active_tab_id {
+                        self.spawn_load_entry(id, dn);
+                    }
+                }
+            }
+            Action::TreePageLoaded(conn_id, parent_dn, nodes, paging) => {
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
+                    let name = loom_core::dn::rdn_display_name(&parent_dn).to_string();
+                    let loaded_msg = if paging.is_needed() {
+                        format!("Loaded {}", paging.label(&name, nodes.len()))
+                    } else {
+                        format!("Loaded children of {}", name)
+                    };
+                    tab.directory_tree.insert_page(&parent_dn, nodes, *paging);
+                    self.status_bar.set_message(loaded_msg.clone());
+                    self.log_panel.push_info(loaded_msg);
+                }
+                let resumes = self.pending_jump.as_ref().is_some_and(|p| {
+                    p.conn_id == conn_id
+                        && Some(conn_id) == self.active_tab_id
+                        && p.parent_dn == parent_dn
+                });
+                if let Some(pending) = self.pending_jump.take().filter(|_| resumes) {
+                    let jump = Jump {
+                        prefix: pending.jump.prefix,
+                        advance: false,
+                    };
+                    self.jump_among(pending.parent_path, None, jump, Some(pending.direction));
+                }
+                let reveals = self.pending_reveal.as_ref().is_some_and(|p| {
+                    p.conn_id == conn_id && normalize_dn(&p.parent_dn) == normalize_dn(&parent_dn)
+                });
+                if let Some(pending) = self.pending_reveal.take().filter(|_| reveals) {
+                    self.reveal_in_tree(pending.conn_id, pending.dn, pending.focus_tree);
+                }
+            }
+            Action::TreeLoadFailed(conn_id, dn, error) => {
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
+                    tab.directory_tree.mark_failed(&dn, error.clone());
+                }
+                self.pending_jump = None;
+                self.pending_reveal = None;
+                self.push_error(format!("Failed to load {}: {}", dn, error));
+            }
+            Action::TreePage(parent_dn, direction) => {
+                let Some(id) = self.active_tab_id else {
+                    return;
+                };
+                let paging = self
+                    .active_tab()
+                    .and_then(|t| t.directory_tree.find_node(&parent_dn))
+                    .and_then(|n| n.paging.clone());
+                if let Some(paging) = paging {
+                    let page = match direction {
+                        PageDirection::Next if paging.has_more => paging.page + 1,
+                        PageDirection::Previous => paging.page.saturating_sub(1),
+                        PageDirection::Next => paging.page,
+                    };
+                    self.status_bar.set_message(format!(
+                        "Loading page {} of {}...",
+                        page + 1,
+                        loom_core::dn::rdn_display_name(&parent_dn)
+                    ));
+                    self.spawn_load_child_page(id, parent_dn, paging, page);
+                }
+            }
+            Action::TreeJump(jump) => {
+                let selected = self.tree_panel.tree_state.selected().to_vec();
+                let (current, parent_path) = match selected.split_last() {
+                    Some((last, path)) => (Some(last.clone()), path.to_vec()),
+                    None => (None, Vec::new()),
+                };
+                self.pending_jump = None;
+                self.jump_among(parent_path, current, jump, None);
+            }
+            Action::TreeChildQueryPrompt(dn) => {
+                let current = self
+                    .active_tab()
+                    .and_then(|t| t.directory_tree.find_node(&dn))
+                    .and_then(|n| n.paging.as_ref())
+                    .map(|p| p.query.to_string())
+                    .unwrap_or_default();
+                self.command_panel
+                    .activate_command_with(&format!("children {}", current));
+                self.status_bar.set_message(format!(
+                    "Filter/sort children of {}: [filter] [sort=attr|sort=-attr], empty to clear",
+                    loom_core::dn::rdn_display_name(&dn)
+                ));
+            }
+            Action::EntryLoaded(conn_id, entry, marker) => {
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
+                    self.recent_entries.push(conn_id, &tab.label, &entry.dn);
+                    tab.entry_cache.insert(
+                        normalize_dn(&entry.dn),
+                        TrackedEntry {
+                            entry: entry.clone(),
+                            marker,
+                        },
+                    );
+                }
+                let schema = self.active_tab().and_then(|t| t.schema.clone());
+                let protected_by = self
+                    .active_tab()
+                    .and_then(|t| t.protected.check(&entry.dn))
+                    .map(|p| p.to_string());
+                self.detail_panel.set_entry(entry, schema.as_ref());
+                self.detail_panel.set_protected(protected_by);
+            }
+            Action::FollowDn(dn) => {
+                let (Some(id), Some(from)) = (self.active_tab_id, self.detail_panel.entry.as_ref())
+                else {
+                    return;
+                };
+                self.pending_follow = Some(NavStop {
+                    conn_id: id,
+                    dn: from.dn.clone(),
+                    row: self.detail_panel.selected_row().unwrap_or(0),
+                });
+                let arrived = Action::LinkArrived(id, dn.clone(), 0);
+                self.spawn_read_entry(id, dn.clone(), Action::LinkBroken(dn), Some(arrived));
+            }
+            Action::NavigateBack => {
+                let Some(stop) = self.nav_history.pop() else {
+                    self.status_bar
+                        .set_message("No earlier entry to go back to".to_string());
+                    return;
+                };
+                if !self.tabs.iter().any(|t| t.id == stop.conn_id) {
+                    self.status_bar
+                        .set_message("That connection tab is no longer open".to_string());
+                    return;
+                }
+                if self.active_tab_id != Some(stop.conn_id) {
+                    self.switch_to_tab(stop.conn_id);
+                }
+                let missing = Action::ErrorMessage(format!("{} no longer exists", stop.dn));
+                let arrived = Action::LinkArrived(stop.conn_id, stop.dn.clone(), stop.row);
+                self.spawn_read_entry(stop.conn_id, stop.dn, missing, Some(arrived));
+            }
+            Action::LinkArrived(conn_id, dn, row) => {
+                if let Some(stop) = self.pending_follow.take() {
+                    self.nav_history.push(stop);
+                    if self.nav_history.len() > NAV_HISTORY_LIMIT {
+                        self.nav_history.remove(0);
+                    }
+                }
+                self.detail_panel.select_row(row);
+                let root = self
+                    .tabs
+                    .iter()
+                    .find(|t| t.id == conn_id)
+                    .map(|t| t.directory_tree.root_dn.clone())
+                    .unwrap_or_default();
+                if normalize_dn(&dn) == normalize_dn(&root)
+                    || loom_core::dn::is_ancestor(&dn, &root)
+                {
+                    self.reveal_in_tree(conn_id, dn, false);
+                } else {
+                    self.status_bar.set_message(format!(
+                        "{} is outside {}; shown in the details only",
+                        dn, root
+                    ));
+                }
+            }
+            Action::LinkBroken(dn) => {
+                self.pending_follow = None;
+                let msg = format!("{} does not exist", dn);
+                self.log_panel.push_error(msg.clone());
+                self.detail_panel.set_link_error(msg);
+            }
+            Action::ShowGroups(dn) => {
+                if self.active_tab_id.is_none() {
+                    self.push_error("No active connection".to_string());
+                    return;
+                }
+                self.membership_popup.show(&dn);
+                let _ = self.action_tx.send(self.membership_popup.request());
+            }
+            Action::ResolveGroups {
+                dn,
+                nested,
+                max_depth,
+            } => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_membership(id, dn, nested, max_depth);
+                }
+            }
+            // A lookup from another tab has nothing left to show in
+            Action::GroupsResolved(conn_id, membership) if self.active_tab_id == Some(conn_id) => {
+                self.membership_popup.set_membership(*membership);
+            }
+            Action::GroupsFailed(error) => {
+                self.log_panel.push_error(error.clone());
+                self.membership_popup.set_error(error);
+            }
+            Action::EntryRefresh => {
+                if let (Some(id), Some(ref entry)) = (self.active_tab_id, &self.detail_panel.entry)
+                {
+                    let dn = entry.dn.clone();
+                    // Refreshing a paged container starts its listing over
+                    let paging = self
+                        .active_tab()
+                        .and_then(|t| t.directory_tree.find_node(&dn))
+                        .and_then(|n| n.paging.clone());
+                    if let Some(mut paging) = paging {
+                        paging.reset();
+                        self.spawn_load_child_page(id, dn.clone(), paging, 0);
+                    }
+                    self.spawn_load_entry(id, dn);
+                }
+            }
+
+            // Search
+            Action::SearchExecute(filter) => {
+                if let Err(e) = loom_core::filter::validate_filter(&filter) {
+                    self.status_bar.set_error(format!("Invalid filter: {}", e));
+                    // Re-activate input so user can fix the filter
+                    self.command_panel.resume_input();
+                    self.command_panel.input_buffer = filter;
+                    self.command_panel.cursor_pos = self.command_panel.input_buffer.len();
+                } else if let Some(id) = self.active_tab_id {
+                    self.status_bar
+                        .set_message(format!("Searching: {}...", filter));
+                    let base_dn = self
+                        .active_tab()
+                        .map(|t| t.directory_tree.root_dn.clone())
+                        .unwrap_or_default();
+                    self.search_dialog.set_columns(self.result_columns(id));
+                    self.search_dialog.clear_results(filter.clone(), &base_dn);
+                    let generation = self.result_ingest.start(id);
+                    self.results_conn = Some(id);
+                    self.spawn_search(
+                        id,
+                        generation,
+                        SearchRequest {
+                            base_dn,
+                            scope: Scope::Subtree,
+                            filter,
+                            attrs: vec!["*".to_string()],
+                            sort: None,
+                        },
+                    );
+                } else {
+                    self.status_bar
+                        .set_error("No active connection".to_string());
+                }
+            }
+            Action::ShowSearchForm => {
+                if let Some(tab) = self.active_tab() {
+                    let base_dn = self
+                        .tree_panel
+                        .selected_entry_dn()
+                        .unwrap_or_else(|| tab.directory_tree.root_dn.clone());
+                    self.dismiss_all_popups();
+                    self.search_form_dialog
+                        .show(&base_dn, &self.config.search_history);
+                } else {
+                    self.push_error("No active connection".to_string());
+                }
+            }
+            Action::Search {
+                base,
+                scope,
+                filter,
+                attrs,
+            } => {
+                if let Some(id) = self.active_tab_id {
+                    self.config.push_search_history(&filter);
+                    if let Err(e) = self.save_config() {
+                        self.push_error(format!("Failed to save config: {}", e));
+                    }
+                    self.status_bar
+                        .set_message(format!("Searching: {}...", filter));
+                    self.search_dialog.set_columns(self.result_columns(id));
+                    self.search_dialog.clear_results(filter.clone(), &base);
+                    self.search_dialog.visible = true;
+                    self.command_panel.soft_deactivate();
+                    let generation = self.result_ingest.start(id);
+                    self.results_conn = Some(id);
+                    self.spawn_search(
+                        id,
+                        generation,
+                        SearchRequest {
+                            base_dn: base,
+                            scope,
+                            filter,
+                            attrs,
+                            sort: None,
+                        },
+                    );
+                } else {
+                    self.status_bar
+                        .set_error("No active connection".to_string());
                 }
-                Action::None
             }
-            MouseEventKind::Drag(crossterm::event::MouseButton::Left) => {
-                if let Some(target) = self.drag_target {
-                    self.apply_drag(target, mouse.column, mouse.row);
+            Action::SearchResults(conn_id, generation, entries) => {
+                // Only queued here; ingest_results moves them into the table
+                self.result_ingest.accept(conn_id, generation, entries);
+            }
+            Action::SearchFailed(conn_id, generation, error) => {
+                if self.result_ingest.is_current(conn_id, generation) {
+                    self.search_dialog.fail(error.clone());
                 }
-                Action::None
+                self.push_error(format!("Search failed: {}", error));
             }
-            MouseEventKind::Down(crossterm::event::MouseButton::Right) => {
-                if self.active_layout == ActiveLayout::Profiles {
-                    let pos = Rect::new(mouse.column, mouse.row, 1, 1);
-                    if let Some(ct) = self.conn_tree_area {
-                        if ct.intersects(pos) {
-                            self.context_menu
-                                .show_for_profiles(self.connection_form.profile_index);
-                            self.context_menu.set_anchor(mouse.column, mouse.row);
-                            return Action::Render;
-                        }
-                    }
-                    if let Some(cf) = self.conn_form_area {
-                        if cf.intersects(pos) {
-                            self.context_menu
-                                .show_for_profiles(self.connection_form.profile_index);
-                            self.context_menu.set_anchor(mouse.column, mouse.row);
-                            return Action::Render;
-                        }
+            Action::SearchSort(attr, descending) => {
+                let server_sort = self.last_search.as_ref().and_then(|(id, request)| {
+                    self.tabs
+                        .iter()
+                        .find(|t| t.id == *id)
+                        .filter(|t| {
+                            t.server_side_sort
+                                && matches!(t.backend, TabBackend::Live(_))
+                                && self.results_conn == Some(*id)
+                                && !attr.eq_ignore_ascii_case("dn")
+                        })
+                        .map(|_| (*id, request.clone()))
+                });
+                let order = if descending { ", descending" } else { "" };
+                if let Some((id, mut request)) = server_sort {
+                    // The server sorts the whole result set, not just what has arrived
+                    if let Some((_, _, stop)) = &self.search_stop {
+                        stop.store(true, Ordering::Relaxed);
                     }
+                    self.search_dialog
+                        .clear_results(request.filter.clone(), &request.base_dn);
+                    self.search_dialog.set_sort(&attr, descending);
+                    request.sort = Some((attr.clone(), descending));
+                    let generation = self.result_ingest.start(id);
+                    self.spawn_search(id, generation, request);
+                    self.status_bar
+                        .set_message(format!("Sorting by {} on the server{}...", attr, order));
+                } else if self.search_dialog.status == SearchStatus::Running {
+                    self.status_bar.set_message(
+                        "Results are still arriving; sort once they are in".to_string(),
+                    );
+                } else {
+                    self.search_dialog.set_sort(&attr, descending);
+                    self.search_dialog.sort_loaded();
+                    self.status_bar.set_message(format!(
+                        "Sorted {} results by {}{}",
+                        self.search_dialog.results.len(),
+                        attr,
+                        order
+                    ));
                 }
-                if self.active_layout == ActiveLayout::Browser {
-                    let pos = Rect::new(mouse.column, mouse.row, 1, 1);
-                    if let Some(tree) = self.tree_area {
-                        if tree.intersects(pos) {
-                            if let Some(dn) = self.tree_panel.selected_dn().cloned() {
-                                self.context_menu.show_for_tree(&dn);
-                                self.context_menu.set_anchor(mouse.column, mouse.row);
-                                return Action::Render;
-                            }
+            }
+            Action::ShowResultColumns(columns) => self.columns_dialog.show(columns),
+            Action::SaveResultColumns(columns) => {
+                self.search_dialog.set_columns(columns.clone());
+                let profile = self
+                    .results_conn
+                    .and_then(|id| self.tabs.iter().find(|t| t.id == id))
+                    .map(|t| t.profile.clone());
+                let saved = profile
+                    .as_deref()
+                    .and_then(|name| self.config.connections.iter_mut().find(|p| p.name == name));
+                match saved {
+                    Some(profile) => {
+                        // The default columns are not written out
+                        profile.result_columns = if columns == DEFAULT_RESULT_COLUMNS {
+                            Vec::new()
+                        } else {
+                            columns
+                        };
+                        if let Err(e) = self.save_config() {
+                            self.push_error(format!("Failed to save config: {}", e));
+                        } else {
+                            self.status_bar
+                                .set_message("Result columns saved to the profile".to_string());
                         }
                     }
-                    if let Some(detail) = self.detail_area {
-                        if detail.intersects(pos) {
-                            if let (Some(entry), Some((attr, val))) = (
-                                &self.detail_panel.entry,
-                                self.detail_panel.selected_attr_value(),
-                            ) {
-                                let dn = entry.dn.clone();
-                                let attr = attr.to_string();
-                                let val = val.to_string();
-                                self.context_menu.show_for_detail(&dn, &attr, &val);
-                                self.context_menu.set_anchor(mouse.column, mouse.row);
-                                return Action::Render;
-                            }
-                        }
+                    None => self.status_bar.set_message(
+                        "Result columns set for this session; the connection has no saved profile"
+                            .to_string(),
+                    ),
+                }
+            }
+            Action::SearchStop => {
+                if let Some((conn_id, generation, stop)) = &self.search_stop {
+                    if self.result_ingest.is_current(*conn_id, *generation) {
+                        stop.store(true, Ordering::Relaxed);
+                        self.status_bar
+                            .set_message("Stopping the search after this page...".to_string());
                     }
                 }
-                Action::None
             }
-            MouseEventKind::Up(_) => {
-                self.drag_target = None;
-                Action::None
+            Action::SearchComplete(conn_id, generation) => {
+                self.result_ingest.finish(conn_id, generation);
             }
-            _ => Action::None,
-        }
-    }
-
-    /// Check if a mouse position is on (or within 1 cell of) a panel divider.
-    fn divider_hit(&self, col: u16, row: u16) -> Option<DragTarget> {
-        match self.active_layout {
-            ActiveLayout::Browser => {
-                // Vertical divider: right edge of tree panel
-                if let Some(tree) = self.tree_area {
-                    let divider_col = tree.x + tree.width;
-                    if col.abs_diff(divider_col) <= 1 && row >= tree.y && row < tree.y + tree.height
-                    {
-                        return Some(DragTarget::Tree);
-                    }
+            Action::CommandFocusInput => {
+                self.dismiss_all_popups();
+                self.search_dialog.visible = true;
+                self.command_panel
+                    .set_profile_names(self.connectable_profiles());
+                self.command_panel.activate_command();
+            }
+            Action::CommandExecute(line) => {
+                self.search_dialog.hide();
+                // Run first, so `:reload-config` reads edits before the
+                // history is saved over them
+                match command_line::parse(&line) {
+                    Ok(command) => self.run_command(command),
+                    Err(e) => self.push_error(e.to_string()),
+                }
+                self.config.push_command_history(&line);
+                self.command_panel
+                    .set_command_history(self.config.command_history.clone());
+                if let Err(e) = self.save_config() {
+                    self.push_error(format!("Failed to save config: {}", e));
                 }
             }
-            ActiveLayout::Profiles => {
-                // Vertical divider: right edge of profiles tree
-                if let Some(ct) = self.conn_tree_area {
-                    let divider_col = ct.x + ct.width;
-                    if col.abs_diff(divider_col) <= 1 && row >= ct.y && row < ct.y + ct.height {
-                        return Some(DragTarget::Tree);
-                    }
+            Action::SearchFocusInput => {
+                self.dismiss_all_popups();
+                self.search_dialog.visible = true;
+                if self.search_dialog.has_results() {
+                    // Results exist — open in navigation mode (press / to edit filter)
+                    self.command_panel.soft_deactivate();
+                } else if self.command_panel.input_buffer.is_empty() {
+                    self.command_panel.activate_input();
+                } else {
+                    self.command_panel.resume_input();
                 }
             }
-        }
-        None
-    }
 
-    /// Update split percentages based on the current drag position.
-    fn apply_drag(&mut self, target: DragTarget, col: u16, _row: u16) {
-        // We need a reference area to compute the percentage from pixel position.
-        match target {
-            DragTarget::Tree => {
-                if let (Some(tree), Some(detail)) = (self.tree_area, self.detail_area) {
-                    let total_w = (tree.width + detail.width) as u32;
-                    if total_w == 0 {
-                        return;
+            // Live Search (debounced preview)
+            Action::LiveSearchRequest { generation, filter } => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_live_search(id, generation, filter);
+                }
+            }
+            Action::LiveSearchResults {
+                generation,
+                entries,
+            } => {
+                if self.command_panel.receive_live_results(generation) {
+                    self.result_ingest.cancel();
+                    // Feed live results directly into the search dialog table
+                    let filter = self.command_panel.input_buffer.clone();
+                    let base_dn = self
+                        .active_tab()
+                        .map(|t| t.directory_tree.root_dn.clone())
+                        .unwrap_or_default();
+                    if let Some(id) = self.active_tab_id {
+                        self.search_dialog.set_columns(self.result_columns(id));
                     }
-                    let offset = col.saturating_sub(tree.x) as u32;
-                    let pct = ((offset * 100) / total_w) as u16;
-                    self.tree_split_pct = pct.clamp(10, 90);
+                    self.search_dialog.show_results(filter, &base_dn, entries);
                 }
             }
-        }
-    }
 
-    async fn process_action(&mut self, action: Action) {
-        match action {
-            Action::Quit => {
-                self.should_quit = true;
-            }
-            Action::FocusNext => {
-                self.focus.next();
-            }
-            Action::FocusPrev => {
-                self.focus.prev();
+            // Attribute editing
+            Action::EditAttribute(dn, attr, value) => {
+                let (is_dn, multi_valued) = self.lookup_attr_schema(&attr);
+                self.attribute_editor
+                    .edit_value_with_options(dn, attr, value, is_dn, multi_valued);
             }
-            Action::FocusPanel(target) => {
-                self.focus.set(target);
+            Action::AddAttribute(dn, attr) => {
+                let (is_dn, multi_valued) = self.lookup_attr_schema(&attr);
+                self.attribute_editor
+                    .add_value_with_options(dn, attr, is_dn, multi_valued);
             }
+            Action::ShowAddAttribute(dn) => {
+                // Build candidate list from schema
+                let candidates = if let Some(tab) = self.active_tab() {
+                    if let Some(ref schema) = tab.schema {
+                        let present: std::collections::HashSet<String> = self
+                            .detail_panel
+                            .entry
+                            .as_ref()
+                            .map(|e| e.attributes.keys().map(|k| k.to_lowercase()).collect())
+                            .unwrap_or_default();
 
-            // Tab management — cycle through Profiles → conn1 → conn2 → … → Profiles
-            Action::NextTab => {
-                let tab_count = self.tab_bar.tabs.len();
-                if tab_count > 0 {
-                    if self.active_layout == ActiveLayout::Profiles {
-                        let first_id = self.tab_bar.tabs[0].id;
-                        self.switch_to_tab(first_id);
-                        self.active_layout = ActiveLayout::Browser;
-                        self.layout_bar.active = ActiveLayout::Browser;
-                        self.focus.set_layout(ActiveLayout::Browser);
-                    } else {
-                        let current_idx = self
-                            .tab_bar
-                            .tabs
-                            .iter()
-                            .position(|t| Some(t.id) == self.active_tab_id)
-                            .unwrap_or(0);
-                        if current_idx + 1 < tab_count {
-                            let next_id = self.tab_bar.tabs[current_idx + 1].id;
-                            self.switch_to_tab(next_id);
+                        let ocs: Vec<&str> = self
+                            .detail_panel
+                            .entry
+                            .as_ref()
+                            .map(|e| e.object_classes())
+                            .unwrap_or_default();
+
+                        let attr_names = if !ocs.is_empty() {
+                            schema.allowed_attributes(&ocs)
                         } else {
-                            self.active_layout = ActiveLayout::Profiles;
-                            self.layout_bar.active = ActiveLayout::Profiles;
-                            self.focus.set_layout(ActiveLayout::Profiles);
-                        }
+                            schema.all_user_attributes()
+                        };
+
+                        attr_names
+                            .into_iter()
+                            .filter(|name| !present.contains(&name.to_lowercase()))
+                            .map(|name| {
+                                let syntax_label = schema
+                                    .get_attribute_type(&name)
+                                    .map(|at| format!("{:?}", at.syntax))
+                                    .unwrap_or_default();
+                                (name, syntax_label)
+                            })
+                            .collect()
+                    } else {
+                        Vec::new()
                     }
-                }
+                } else {
+                    Vec::new()
+                };
+
+                self.attribute_picker.show(dn, candidates);
             }
-            Action::PrevTab => {
-                let tab_count = self.tab_bar.tabs.len();
-                if tab_count > 0 {
-                    if self.active_layout == ActiveLayout::Profiles {
-                        let last_id = self.tab_bar.tabs[tab_count - 1].id;
-                        self.switch_to_tab(last_id);
-                        self.active_layout = ActiveLayout::Browser;
-                        self.layout_bar.active = ActiveLayout::Browser;
-                        self.focus.set_layout(ActiveLayout::Browser);
+            Action::DeleteAttributeValue(dn, attr, value) => {
+                let retry = Action::DeleteAttributeValue(dn.clone(), attr.clone(), value.clone());
+                if !self.guard_write(&dn, retry) {
+                    return;
+                }
+                let result = EditResult {
+                    dn,
+                    op: EditOp::Delete { attr, value },
+                    new_value: String::new(),
+                };
+                if let Some(id) = self.active_tab_id {
+                    if self.recording_edits() {
+                        self.queue_changes(id, vec![result.change()]);
                     } else {
-                        let current_idx = self
-                            .tab_bar
-                            .tabs
-                            .iter()
-                            .position(|t| Some(t.id) == self.active_tab_id)
-                            .unwrap_or(0);
-                        if current_idx > 0 {
-                            let prev_id = self.tab_bar.tabs[current_idx - 1].id;
-                            self.switch_to_tab(prev_id);
-                        } else {
-                            self.active_layout = ActiveLayout::Profiles;
-                            self.layout_bar.active = ActiveLayout::Profiles;
-                            self.focus.set_layout(ActiveLayout::Profiles);
-                        }
+                        self.spawn_save_attribute(id, result);
+                    }
+                }
+            }
+            Action::SaveAttribute(result) => {
+                let dn = result.dn.clone();
+                if !self.guard_write(&dn, Action::SaveAttribute(result.clone())) {
+                    return;
+                }
+                if let Some(id) = self.active_tab_id {
+                    if self.recording_edits() {
+                        self.queue_changes(id, vec![result.change()]);
+                    } else {
+                        self.spawn_save_attribute(id, result);
                     }
                 }
             }
-            Action::SwitchTab(id) => {
-                self.switch_to_tab(id);
-                // If switching from Connections layout, go to Browser
-                if self.active_layout == ActiveLayout::Profiles {
-                    self.active_layout = ActiveLayout::Browser;
-                    self.layout_bar.active = ActiveLayout::Browser;
-                    self.focus.set_layout(ActiveLayout::Browser);
+            Action::AttributeSaved(dn) => {
+                let saved_msg =
+                    format!("Saved changes to {}", loom_core::dn::rdn_display_name(&dn));
+                self.status_bar.set_message(saved_msg.clone());
+                self.log_panel.push_info(saved_msg);
+                // Refresh the entry
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_load_entry(id, dn);
                 }
             }
-            Action::ShowConnectDialog => {
-                let mut profiles = self.config.connections.clone();
-                profiles.push(example_profile());
-                self.connect_dialog.show(profiles);
-            }
-            Action::ShowNewConnectionForm => {
-                self.connect_dialog.hide();
-                self.new_connection_dialog.show();
+
+            // Export
+            Action::ShowExportDialog => {
+                if self.export_task.is_some() {
+                    self.push_error("An export is already running".to_string());
+                } else if let Some(tab) = self.active_tab() {
+                    let base_dn = self
+                        .tree_panel
+                        .selected_entry_dn()
+                        .unwrap_or_else(|| tab.directory_tree.root_dn.clone());
+                    self.export_dialog
+                        .set_templates(&self.config.export_templates);
+                    self.export_dialog.set_presets(self.config.export_presets());
+                    self.export_dialog.show(&base_dn);
+                    self.offer_results_to_export();
+                } else {
+                    self.push_error("No active connection".to_string());
+                }
             }
-            Action::ConnectByIndex(idx) => {
-                // The example profile is appended after saved profiles
-                let profile = if idx == self.config.connections.len() {
-                    Some(example_profile())
+            Action::ShowSubtreeExport(dn) => {
+                if self.export_task.is_some() {
+                    self.push_error("An export is already running".to_string());
+                } else if self.active_tab().is_some() {
+                    self.export_dialog
+                        .set_templates(&self.config.export_templates);
+                    self.export_dialog.set_presets(self.config.export_presets());
+                    self.export_dialog.show_subtree(&dn);
                 } else {
-                    self.config.connections.get(idx).cloned()
-                };
-                if let Some(profile) = profile {
-                    match self.connect_profile(&profile).await {
-                        Ok(()) => {}
-                        Err(e) if is_auth_error(&e) => {
-                            self.push_error(format!("Authentication failed: {}", e));
-                            self.credential_prompt.show(profile);
-                        }
-                        Err(e) => {
-                            self.push_error(format!("Connection failed: {}", e));
-                        }
-                    }
+                    self.push_error("No active connection".to_string());
                 }
             }
-            Action::ConnectAdHoc(profile, password) => {
-                let profile_clone = profile.clone();
-                match self.connect_with_password(&profile, &password).await {
-                    Ok(()) => {
-                        self.last_adhoc_profile = Some(profile_clone);
-                        let tip_msg = format!(
-                            "Tip: Press {} to save this connection to config",
-                            self.keymap.hint("save_connection"),
-                        );
-                        self.status_bar.set_message(tip_msg.clone());
-                        self.log_panel.push_info(tip_msg);
-                    }
-                    Err(e) if extract_cert_trust_error(&e).is_some() => {
-                        let info = extract_cert_trust_error(&e).unwrap();
-                        let _ = self.action_tx.send(Action::ShowCertTrustDialog {
-                            cert_info: Box::new(info),
-                            profile: Box::new(profile_clone),
-                            password,
-                        });
-                    }
-                    Err(e) if is_auth_error(&e) => {
-                        self.push_error(format!("Authentication failed: {}", e));
-                        self.credential_prompt.show(profile_clone);
-                    }
-                    Err(e) => {
-                        self.push_error(format!("Connection failed: {}", e));
+            Action::ExportGroups => {
+                if self.export_task.is_some() {
+                    self.push_error("An export is already running".to_string());
+                } else if let Some(membership) = self.membership_popup.membership() {
+                    let member = membership.member.clone();
+                    self.membership_popup.hide();
+                    self.export_dialog
+                        .set_templates(&self.config.export_templates);
+                    self.export_dialog.set_presets(self.config.export_presets());
+                    self.export_dialog.show(&member);
+                    self.offer_groups_to_export();
+                }
+            }
+            Action::ExportExecute {
+                base_dn,
+                path,
+                filter,
+                attributes,
+                timestamps,
+                decode,
+                csv,
+                format,
+                source,
+                scope,
+                include_base,
+            } => {
+                if self.export_task.is_some() {
+                    self.export_dialog.hide();
+                    self.push_error("An export is already running".to_string());
+                } else if let Some(id) = self.active_tab_id {
+                    if source == ExportSource::Subtree {
+                        self.push_message(format!(
+                            "Exporting {} of {} to {}...",
+                            scope.label().to_lowercase(),
+                            base_dn,
+                            path
+                        ));
+                    } else {
+                        self.push_message(format!("Exporting to {} (filter: {})...", path, filter));
                     }
+                    let request = ExportRequest {
+                        base_dn,
+                        path,
+                        filter,
+                        attributes,
+                        timestamps,
+                        decode,
+                        csv,
+                        format,
+                        source,
+                        scope,
+                        include_base,
+                    };
+                    self.spawn_export(id, request);
+                } else {
+                    self.export_dialog.hide();
                 }
             }
-            Action::PromptCredentials(profile) => {
-                self.credential_prompt.show(profile);
+            Action::ExportProgress { done, total } => {
+                self.export_dialog.set_progress(done, total);
             }
-            Action::ConnectWithCredentials(profile, password) => {
-                let profile_clone = profile.clone();
-                match self.connect_with_password(&profile, &password).await {
+            Action::ExportCancel => {
+                if let Some((conn_id, op)) = self.export_task.take() {
+                    if let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) {
+                        tab.ops.cancel(op);
+                    }
+                    self.push_message("Export cancelled".to_string());
+                }
+                self.export_dialog.hide();
+            }
+            Action::SaveExportPreset {
+                name,
+                attributes,
+                format,
+                replace,
+            } => {
+                match self
+                    .config
+                    .save_export_preset(&name, attributes, format, replace)
+                {
                     Ok(()) => {
-                        let auth_msg = format!(
-                            "Authenticated as {}",
-                            profile.bind_dn.as_deref().unwrap_or("anonymous")
-                        );
-                        self.status_bar.set_message(auth_msg.clone());
-                        self.log_panel.push_info(auth_msg);
-                        // Auto-store in vault if using Vault method
-                        if matches!(profile.credential_method, CredentialMethod::Vault) {
-                            if let Some(ref mut vault) = self.vault {
-                                if !password.is_empty() {
-                                    let _ = vault.set_password(&profile.name, &password);
-                                }
-                            }
+                        self.export_dialog.set_presets(self.config.export_presets());
+                        if let Err(e) = self.save_config() {
+                            self.push_error(format!("Failed to save config: {}", e));
+                        } else {
+                            self.push_message(format!("Saved export preset '{}'", name));
                         }
                     }
-                    Err(e) if extract_cert_trust_error(&e).is_some() => {
-                        let info = extract_cert_trust_error(&e).unwrap();
-                        let _ = self.action_tx.send(Action::ShowCertTrustDialog {
-                            cert_info: Box::new(info),
-                            profile: Box::new(profile_clone),
-                            password,
-                        });
-                    }
-                    Err(e) if is_auth_error(&e) => {
-                        self.push_error(format!("Authentication failed: {}", e));
-                        self.credential_prompt.show(profile_clone);
-                    }
-                    Err(e) => {
-                        self.push_error(format!("Connection failed: {}", e));
-                    }
+                    Err(e) => self.push_error(e),
                 }
             }
-            Action::SaveCurrentConnection => {
-                if let Some(profile) = self.last_adhoc_profile.take() {
-                    match AppConfig::append_connection(&profile) {
-                        Ok(()) => {
-                            let save_msg = format!("Saved connection '{}' to config", profile.name);
-                            self.status_bar.set_message(save_msg.clone());
-                            self.log_panel.push_info(save_msg);
-                            self.config.connections.push(profile);
-                        }
-                        Err(e) => {
-                            self.push_error(format!("Failed to save connection: {}", e));
-                        }
+            Action::ExportComplete { report, message } => {
+                self.export_task = None;
+                self.export_dialog.hide();
+                self.status_bar.set_message(message.clone());
+                self.log_panel.push_info(message);
+                self.export_summary_popup.show_report(report);
+            }
+            Action::ExportFailed { error, request } => {
+                self.export_task = None;
+                self.export_dialog.hide();
+                error!("{}", error);
+                self.last_error = Some(error.clone());
+                self.log_panel.push_error(error.clone());
+                self.status_bar.set_error(error.clone());
+                self.export_summary_popup.show_failure(error, request);
+            }
+            Action::RetryExport(request) => {
+                if self.active_tab_id.is_some() {
+                    self.export_dialog
+                        .set_templates(&self.config.export_templates);
+                    self.export_dialog.set_presets(self.config.export_presets());
+                    self.export_dialog.show_request(&request);
+                    match request.source {
+                        ExportSource::Search | ExportSource::Subtree => {}
+                        ExportSource::Groups => self.offer_groups_to_export(),
+                        _ => self.offer_results_to_export(),
                     }
                 } else {
-                    self.push_message("No ad-hoc connection to save".to_string());
+                    self.push_error("No active connection".to_string());
                 }
             }
-            // Layout switching
-            Action::SwitchLayout(layout) => {
-                self.active_layout = layout;
-                self.layout_bar.active = layout;
-                self.focus.set_layout(layout);
-            }
-
-            // Connections Manager
-            Action::ConnMgrSelect(idx) => {
-                if idx == self.config.connections.len() {
-                    // Example profile — show it in view mode
-                    let profile = example_profile();
-                    self.connection_form.view_profile(idx, &profile);
-                } else if let Some(profile) = self.config.connections.get(idx) {
-                    self.connection_form.view_profile(idx, profile);
+            Action::OpenPath(path) => {
+                let command = open_command(Platform::current(), Path::new(&path));
+                if let Err(e) = command.spawn() {
+                    self.push_error(format!("Could not run {}: {}", command.program, e));
                 }
             }
-            Action::ConnMgrNew => {
-                self.connection_form.new_profile();
-                self.focus.set(FocusTarget::ConnectionForm);
+            Action::RevealPath(path) => {
+                let command = reveal_command(Platform::current(), Path::new(&path));
+                if let Err(e) = command.spawn() {
+                    self.push_error(format!("Could not run {}: {}", command.program, e));
+                }
             }
-            Action::ConnMgrSave(idx, profile) => {
-                if idx >= self.config.connections.len() {
-                    self.push_error("Cannot edit example profile".to_string());
-                } else {
-                    // Rename vault entry if profile name changed
-                    let old_name = self.config.connections[idx].name.clone();
-                    let new_name = profile.name.clone();
-                    let is_vault_method =
-                        matches!(profile.credential_method, CredentialMethod::Vault);
-
-                    self.config.update_connection(idx, *profile);
-                    if let Err(e) = self.config.save() {
-                        self.push_error(format!("Failed to save config: {}", e));
-                    } else {
-                        self.status_bar.set_message("Profile saved".to_string());
-                        self.log_panel.push_info("Profile saved".to_string());
-                    }
-
-                    // Handle vault rename
-                    if old_name != new_name {
-                        if let Some(ref mut vault) = self.vault {
-                            let _ = vault.rename_profile(&old_name, &new_name);
-                        }
-                    }
-
-                    // If using Vault method, prompt to store password
-                    if is_vault_method {
-                        if let Some(ref vault) = self.vault {
-                            if vault.get_password(&new_name).is_none() {
-                                self.vault_password_dialog.show_store_password(&new_name);
-                            }
-                        }
-                    }
-
-                    if let Some(updated) = self.config.connections.get(idx) {
-                        self.connection_form.view_profile(idx, updated);
-                    }
+            Action::ExportWritten { conn_id, path } => {
+                let label = self
+                    .tabs
+                    .iter()
+                    .find(|t| t.id == conn_id)
+                    .map(|t| t.label.clone());
+                if let Some(invocation) =
+                    label.and_then(|l| self.profile_hook(&l, HookEvent::Export))
+                {
+                    let format = std::path::Path::new(&path)
+                        .extension()
+                        .map(|e| e.to_string_lossy().to_lowercase())
+                        .unwrap_or_default();
+                    let invocation = invocation
+                        .with_args(vec![path.clone(), format.clone()])
+                        .with_env("LOOM_EXPORT_PATH", &path)
+                        .with_env("LOOM_EXPORT_FORMAT", &format);
+                    self.trigger_hook(invocation);
                 }
             }
-            Action::ConnMgrCreate(profile) => {
-                let is_vault_method = matches!(profile.credential_method, CredentialMethod::Vault);
-                let profile_name = profile.name.clone();
 
-                self.config.connections.push(*profile);
-                let new_idx = self.config.connections.len() - 1;
-                if let Err(e) = self.config.save() {
-                    self.push_error(format!("Failed to save config: {}", e));
+            // Snapshot
+            Action::ShowSnapshotDialog => {
+                if self.snapshot_task.is_some() {
+                    self.push_error("A snapshot is already being written".to_string());
+                } else if let Some(tab) = self.active_tab() {
+                    let base_dn = self
+                        .tree_panel
+                        .selected_entry_dn()
+                        .unwrap_or_else(|| tab.directory_tree.root_dn.clone());
+                    let label = tab.label.clone();
+                    self.snapshot_dialog.show(&base_dn, &label);
                 } else {
-                    self.push_message("Profile created".to_string());
-                }
-                if let Some(created) = self.config.connections.get(new_idx) {
-                    self.connection_form.view_profile(new_idx, created);
+                    self.push_error("No active connection".to_string());
                 }
-
-                // If using Vault method, prompt to store password
-                if is_vault_method && self.vault.is_some() {
-                    self.vault_password_dialog
-                        .show_store_password(&profile_name);
+            }
+            Action::SnapshotExecute { base_dn, path } => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_snapshot(id, base_dn, path);
                 }
             }
-            Action::ConnMgrDelete(idx) => {
-                if idx >= self.config.connections.len() {
-                    self.push_error("Cannot delete example profile".to_string());
-                } else {
-                    let profile_name = self.config.connections[idx].name.clone();
-                    self.config.delete_connection(idx);
-                    if let Err(e) = self.config.save() {
-                        self.push_error(format!("Failed to save config: {}", e));
-                    } else {
-                        self.push_message("Profile deleted".to_string());
-                    }
-                    // Remove from vault
-                    if let Some(ref mut vault) = self.vault {
-                        let _ = vault.remove_password(&profile_name);
+            Action::SnapshotProgress(progress) => {
+                self.snapshot_dialog.set_progress(progress);
+            }
+            Action::SnapshotCancel => {
+                if let Some((conn_id, op)) = self.snapshot_task.take() {
+                    if let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) {
+                        tab.ops.cancel(op);
                     }
-                    self.connection_form.clear();
+                    self.push_message("Snapshot cancelled".to_string());
                 }
+                self.snapshot_dialog.hide();
             }
-            Action::ConnMgrDuplicate(idx) => {
-                if let Some(profile) = self.config.connections.get(idx).cloned() {
-                    let mut dup = profile;
-                    dup.name = format!("{}-copy", dup.name);
-                    self.config.connections.push(dup);
-                    let new_idx = self.config.connections.len() - 1;
-                    if let Err(e) = self.config.save() {
-                        self.push_error(format!("Failed to save config: {}", e));
+            Action::SnapshotComplete { path, manifest } => {
+                self.snapshot_task = None;
+                self.snapshot_dialog.hide();
+                self.push_message(format!(
+                    "Snapshot of {} written to {} ({} entries)",
+                    manifest.base_dn, path, manifest.entries
+                ));
+            }
+            Action::SnapshotFailed(error) => {
+                self.snapshot_task = None;
+                self.snapshot_dialog.hide();
+                self.push_error(error);
+            }
+
+            // Subtree compare
+            Action::ShowSubtreeCompareDialog => {
+                if self.subtree_compare_task.is_some() {
+                    self.push_error("A subtree comparison is already running".to_string());
+                } else if let Some(tab) = self.active_tab() {
+                    let base_dn = self
+                        .tree_panel
+                        .selected_entry_dn()
+                        .unwrap_or_else(|| tab.directory_tree.root_dn.clone());
+                    let label = tab.label.clone();
+                    let targets: Vec<(ConnectionId, String)> = self
+                        .tabs
+                        .iter()
+                        .filter(|t| t.id != tab.id && matches!(t.backend, TabBackend::Live(_)))
+                        .map(|t| (t.id, t.label.clone()))
+                        .collect();
+                    if targets.is_empty() {
+                        self.push_error(
+                            "Open the other directory on a second connection to compare with"
+                                .to_string(),
+                        );
                     } else {
-                        self.push_message("Profile duplicated".to_string());
+                        self.subtree_compare_dialog.show(&base_dn, &label, targets);
                     }
-                    if let Some(created) = self.config.connections.get(new_idx) {
-                        self.connection_form.view_profile(new_idx, created);
+                } else {
+                    self.push_error("No active connection".to_string());
+                }
+            }
+            Action::SubtreeCompareExecute {
+                base_dn,
+                other,
+                options,
+            } => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_subtree_compare(id, other, base_dn, options);
+                }
+            }
+            Action::SubtreeCompareProgress(progress) => {
+                self.subtree_compare_dialog.set_progress(progress);
+            }
+            Action::SubtreeCompareCancel => {
+                if let Some((conn_id, op, _)) = self.subtree_compare_task.take() {
+                    if let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) {
+                        tab.ops.cancel(op);
                     }
+                    self.push_message("Subtree comparison cancelled".to_string());
                 }
+                self.subtree_compare_dialog.hide();
             }
-            Action::ConnMgrConnect(idx) => {
-                let profile = if idx == self.config.connections.len() {
-                    Some(example_profile())
+            Action::SubtreeCompareComplete(report) => {
+                let Some((conn_id, _, other)) = self.subtree_compare_task.take() else {
+                    return;
+                };
+                self.subtree_compare_dialog.hide();
+                let label = self
+                    .tabs
+                    .iter()
+                    .find(|t| t.id == conn_id)
+                    .map(|t| t.label.clone())
+                    .unwrap_or_default();
+                self.push_message(format!(
+                    "Compared {} with {}: {}",
+                    report.base_dn,
+                    other,
+                    report.summary()
+                ));
+                self.subtree_diff_view.show(*report, &label, &other);
+            }
+            Action::SubtreeCompareFailed(error) => {
+                self.subtree_compare_task = None;
+                self.subtree_compare_dialog.hide();
+                self.push_error(error);
+            }
+            Action::ShowEntryDiff(diff) => {
+                let schema = self.active_tab().and_then(|t| t.schema.clone());
+                self.compare_view.show(*diff, schema);
+            }
+
+            // Offline edits
+            Action::ToggleOfflineEdits => {
+                let Some(tab) = self.active_tab_mut() else {
+                    self.push_error("No active connection".to_string());
+                    return;
+                };
+                tab.offline_edits = !tab.offline_edits;
+                let msg = if tab.offline_edits {
+                    format!("Offline edits on: writes to {} are queued", tab.label)
                 } else {
-                    self.config.connections.get(idx).cloned()
+                    format!(
+                        "Offline edits off: writes to {} go to the server; {} change(s) still pending",
+                        tab.label,
+                        tab.pending_changes.len()
+                    )
                 };
-                if let Some(profile) = profile {
-                    match self.connect_profile(&profile).await {
-                        Ok(()) => {
-                            // Switch to Browser layout on successful connect
-                            self.active_layout = ActiveLayout::Browser;
-                            self.layout_bar.active = ActiveLayout::Browser;
-                            self.focus.set_layout(ActiveLayout::Browser);
-                        }
-                        Err(e) if is_auth_error(&e) => {
-                            self.push_error(format!("Authentication failed: {}", e));
-                            self.credential_prompt.show(profile);
-                        }
-                        Err(e) => {
-                            self.push_error(format!("Connection failed: {}", e));
-                        }
-                    }
+                self.status_bar.pending = tab.offline_edits.then_some(tab.pending_changes.len());
+                self.status_bar.set_message(msg.clone());
+                self.log_panel.push_info(msg);
+            }
+            Action::QueueChanges { conn_id, changes } => {
+                self.queue_changes(conn_id, changes);
+            }
+            Action::ShowPendingChanges => match self.active_tab() {
+                Some(tab) => {
+                    let (changes, recording) = (tab.pending_changes.clone(), tab.offline_edits);
+                    self.pending_changes_popup.show(changes, recording);
+                }
+                None => self.push_error("No active connection".to_string()),
+            },
+            Action::PendingChangesEdited(changes) => {
+                if let Some(tab) = self.active_tab_mut() {
+                    tab.pending_changes = changes;
                 }
             }
 
-            Action::ConnMgrExport => {
-                if self.config.connections.is_empty() {
-                    self.push_error("No profiles to export".to_string());
+            // Apply LDIF
+            Action::ShowApplyLdifDialog(path) => {
+                if self.apply_ldif_task.is_some() {
+                    self.push_error("A changefile is already being applied".to_string());
+                } else if self.active_tab_id.is_some() {
+                    self.apply_ldif_dialog.show(path);
                 } else {
-                    self.profile_export_dialog.show(&self.config.connections);
+                    self.push_error("No active connection".to_string());
                 }
             }
-            Action::ConnMgrImport => {
-                self.profile_import_dialog.show();
+            Action::ApplyLdifExecute { changes, options } => {
+                let Some(id) = self.active_tab_id else {
+                    return;
+                };
+                if self.recording_edits() && !options.dry_run {
+                    self.apply_ldif_dialog.hide();
+                    self.queue_changes(id, changes);
+                    return;
+                }
+                self.spawn_apply_ldif(id, changes, options);
+            }
+            Action::ApplyLdifProgress(done, total) => {
+                self.apply_ldif_dialog.set_progress(done, total);
             }
-            Action::ConnMgrImportExecute(profiles) => {
-                let count = profiles.len();
-                for p in profiles {
-                    self.config.connections.push(p);
+            Action::ApplyLdifCancel => {
+                if let Some((conn_id, op)) = self.apply_ldif_task.take() {
+                    if let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) {
+                        tab.ops.cancel(op);
+                    }
+                    self.push_message(
+                        "Applying the changefile was cancelled; records already applied stay"
+                            .to_string(),
+                    );
                 }
-                if let Err(e) = self.config.save() {
-                    self.push_error(format!("Failed to save config: {}", e));
-                } else {
-                    self.push_message(format!("Imported {} profile(s)", count));
+                self.apply_ldif_dialog.hide();
+            }
+            Action::ApplyLdifComplete(report) => {
+                if self.apply_ldif_task.take().is_none() {
+                    return;
                 }
-                // Refresh the form if a profile was being viewed
-                if let Some(idx) = self.config.connections.len().checked_sub(1) {
-                    self.connection_form
-                        .view_profile(idx, &self.config.connections[idx].clone());
+                self.apply_ldif_dialog.hide();
+                for record in &report.records {
+                    if let ChangeOutcome::Failed(error) = &record.outcome {
+                        self.log_panel
+                            .push_error(format!("{}: {}", record.dn, error));
+                    }
                 }
+                let verb = if report.dry_run {
+                    "Dry run of changefile"
+                } else {
+                    "Applied changefile"
+                };
+                let msg = format!("{}: {}", verb, report.summary());
+                self.status_bar.set_message(msg.clone());
+                self.log_panel.push_info(msg);
+                self.apply_results_view.show(*report);
             }
 
-            Action::ConnMgrSelectFolder(path) => {
-                let desc = self
-                    .config
-                    .folder_description(&path)
-                    .unwrap_or_default()
-                    .to_string();
-                self.connection_form.view_folder(&path, &desc);
-            }
-            Action::ConnMgrSaveFolderDesc(path, description) => {
-                // Update or insert the folder config
-                if let Some(existing) = self.config.folders.iter_mut().find(|f| f.path == path) {
-                    existing.description = description.clone();
-                } else {
-                    self.config.folders.push(crate::config::FolderConfig {
-                        path: path.clone(),
-                        description: description.clone(),
-                    });
-                }
-                if let Err(e) = self.config.save() {
-                    self.push_error(format!("Failed to save config: {}", e));
+            // Bulk Update
+            Action::ShowBulkUpdateDialog => {
+                if self.active_tab_id.is_some() {
+                    self.bulk_update_dialog.show();
                 } else {
-                    self.push_message("Folder description saved".to_string());
+                    self.push_error("No active connection".to_string());
                 }
-                self.connection_form.view_folder(&path, &description);
             }
-
-            // Certificate Trust
-            Action::ShowCertTrustDialog {
-                cert_info,
-                profile,
-                password,
+            Action::BulkUpdateExecute {
+                filter,
+                attribute,
+                value,
+                op,
+                script: true,
             } => {
-                self.cert_trust_dialog.show(*cert_info, *profile, password);
+                if let Some(id) = self.active_tab_id {
+                    self.start_scripted_bulk_update(id, filter, attribute, value, op);
+                }
             }
-            Action::TrustCertAndConnect {
-                cert_info,
-                fingerprint,
-                always,
-                profile,
-                password,
+            Action::BulkUpdateExecute {
+                filter,
+                attribute,
+                value,
+                op,
+                script: false,
             } => {
-                if always {
-                    let entry = TrustedCertEntry {
-                        host: cert_info.host.clone(),
-                        port: cert_info.port,
-                        fingerprint_sha256: fingerprint.clone(),
-                        subject: cert_info.subject.clone(),
+                if let Some(id) = self.active_tab_id {
+                    let modification = match op {
+                        BulkOp::Replace => BulkMod::ReplaceAttribute {
+                            attr: attribute,
+                            value,
+                        },
+                        BulkOp::Add => BulkMod::AddValue {
+                            attr: attribute,
+                            value,
+                        },
+                        BulkOp::Delete => {
+                            if value.is_empty() {
+                                BulkMod::DeleteAttribute { attr: attribute }
+                            } else {
+                                BulkMod::DeleteValue {
+                                    attr: attribute,
+                                    value,
+                                }
+                            }
+                        }
                     };
-                    self.trust_store.trust_always(entry);
-                    // Persist to config
-                    self.config.trusted_certificates = self.trust_store.to_config_entries();
-                    if let Err(e) = self.config.save() {
-                        self.push_error(format!("Failed to save config: {}", e));
+                    if let Some(base) = self
+                        .active_tab()
+                        .filter(|t| t.offline_edits)
+                        .map(|t| t.directory_tree.root_dn.clone())
+                    {
+                        self.spawn_plan_changes(id, base, filter, &["1.1"], move |entries| {
+                            Ok(entries
+                                .iter()
+                                .map(|e| Change::modify(&e.dn, std::slice::from_ref(&modification)))
+                                .collect())
+                        });
+                        return;
                     }
-                    self.push_message("Certificate trusted permanently".to_string());
+                    self.push_message(format!("Executing bulk update: {}...", filter));
+                    self.spawn_bulk_update(id, filter, vec![modification]);
+                }
+            }
+            Action::BulkUpdateComplete { message, errors } => {
+                for (dn, error) in errors {
+                    self.log_panel.push_error(format!("{}: {}", dn, error));
+                }
+                self.status_bar.set_message(message.clone());
+                self.log_panel.push_info(message);
+            }
+
+            // Batch modify
+            Action::ShowBatchModifyDialog(dns) => {
+                if self.active_tab_id.is_some() {
+                    self.batch_modify_dialog.show(dns);
                 } else {
-                    self.trust_store.trust_session(fingerprint);
-                    self.push_message("Certificate trusted for this session".to_string());
+                    self.push_error("No active connection".to_string());
                 }
-                // Retry the connection
-                match self.connect_with_password(&profile, &password).await {
-                    Ok(()) => {}
-                    Err(e) if is_auth_error(&e) => {
-                        self.push_error(format!("Authentication failed: {}", e));
-                        self.credential_prompt.show(*profile);
-                    }
-                    Err(e) => {
-                        self.push_error(format!("Connection failed: {}", e));
+            }
+            Action::BatchModifyExecute {
+                dns,
+                change,
+                changefile: Some(path),
+            } => {
+                let mut buf = Vec::new();
+                let written =
+                    loom_core::export::ldif_changes::write_changes(&change.changes(&dns), &mut buf)
+                        .and_then(|count| {
+                            match path.parent() {
+                                Some(dir) if !dir.as_os_str().is_empty() => {
+                                    std::fs::create_dir_all(dir)
+                                }
+                                _ => Ok(()),
+                            }
+                            .and_then(|()| write_replacing(&path, &buf))
+                            .map(|_| count)
+                            .map_err(|e| CoreError::ExportError(e.to_string()))
+                        });
+                match written {
+                    Ok(count) => self.push_message(format!(
+                        "Dry run: wrote {} changes to {}",
+                        count,
+                        path.display()
+                    )),
+                    Err(e) => self.push_error(format!("Failed to write {}: {}", path.display(), e)),
+                }
+            }
+            Action::BatchModifyExecute {
+                dns,
+                change,
+                changefile: None,
+            } => {
+                if let Some(id) = self.active_tab_id {
+                    if self.recording_edits() {
+                        self.queue_changes(id, change.changes(&dns));
+                        return;
                     }
+                    self.push_message(format!("Modifying {} entries...", dns.len()));
+                    self.spawn_batch_modify(id, dns, change);
                 }
             }
+            Action::BatchModifyComplete { succeeded, errors } => {
+                for (dn, error) in &errors {
+                    self.log_panel.push_error(format!("{}: {}", dn, error));
+                }
+                self.batch_summary_popup.show(succeeded, errors);
+                let message = format!("Batch modify: {}", self.batch_summary_popup.headline());
+                self.status_bar.set_message(message.clone());
+                self.log_panel.push_info(message);
+            }
 
-            Action::CloseCurrentTab => {
+            // Attribute migration
+            Action::MigrateAttributeExecute { changes, skipped } => {
                 if let Some(id) = self.active_tab_id {
-                    self.tabs.retain(|t| t.id != id);
-                    self.tab_bar.remove_tab(id);
-                    self.active_tab_id = self.tab_bar.active_tab;
-                    self.detail_panel.clear();
-                    if self.active_tab_id.is_none() {
-                        self.status_bar.set_disconnected();
-                        self.active_layout = ActiveLayout::Profiles;
-                        self.layout_bar.active = ActiveLayout::Profiles;
-                        self.focus.set_layout(ActiveLayout::Profiles);
+                    if self.recording_edits() {
+                        let changes = changes
+                            .iter()
+                            .map(|(dn, mods)| Change::modify(dn, mods))
+                            .collect();
+                        self.queue_changes(id, changes);
+                        return;
                     }
+                    self.push_message(format!(
+                        "Migrating attribute on {} entries...",
+                        changes.len()
+                    ));
+                    self.spawn_bulk_apply(id, changes, skipped);
                 }
             }
-            Action::CloseTab(id) => {
-                self.tabs.retain(|t| t.id != id);
-                self.tab_bar.remove_tab(id);
-                if self.active_tab_id == Some(id) {
-                    self.active_tab_id = self.tab_bar.active_tab;
-                    self.detail_panel.clear();
-                    if self.active_tab_id.is_none() {
-                        self.status_bar.set_disconnected();
-                        self.active_layout = ActiveLayout::Profiles;
-                        self.layout_bar.active = ActiveLayout::Profiles;
-                        self.focus.set_layout(ActiveLayout::Profiles);
-                    }
+            Action::MigrateAttributeComplete { message, errors } => {
+                for (dn, error) in errors {
+                    self.log_panel.push_error(format!("{}: {}", dn, error));
                 }
+                self.status_bar.set_message(message.clone());
+                self.log_panel.push_info(message);
             }
 
-            // Tree
-            Action::TreeExpand(dn) => {
-                if !dn.is_empty() {
-                    if let Some(id) = self.active_tab_id {
-                        self.spawn_load_children(id, dn.clone());
-                        self.spawn_load_entry(id, dn);
-                    }
+            // Create / Delete Entry
+            Action::ShowCreateEntryDialog(parent_dn) => {
+                if let Some(id) = self.active_tab_id {
+                    let schema = self
+                        .tabs
+                        .iter()
+                        .find(|t| t.id == id)
+                        .and_then(|t| t.schema.as_ref());
+                    self.create_entry_dialog.show(parent_dn, schema);
+                } else {
+                    self.push_error("No active connection".to_string());
                 }
             }
-            Action::TreeCollapse(_dn) => {}
-            Action::TreeSelect(dn) => {
-                if !dn.is_empty() {
-                    if let Some(id) = self.active_tab_id {
-                        self.spawn_load_entry(id, dn);
+            Action::CreateEntry { dn, attributes } => {
+                let retry = Action::CreateEntry {
+                    dn: dn.clone(),
+                    attributes: attributes.clone(),
+                };
+                if !self.guard_write(&dn, retry) {
+                    return;
+                }
+                if let Some(id) = self.active_tab_id {
+                    if self.recording_edits() {
+                        let attrs = attributes
+                            .into_iter()
+                            .map(|(attr, values)| (attr, values.into_iter().collect()))
+                            .collect();
+                        self.queue_changes(
+                            id,
+                            vec![Change::Add {
+                                dn,
+                                attrs,
+                                binary: Vec::new(),
+                            }],
+                        );
+                        return;
                     }
+                    self.push_message(format!("Creating entry: {}...", dn));
+                    self.spawn_create_entry(id, dn, attributes);
                 }
             }
-            Action::TreeChildrenLoaded(conn_id, parent_dn, nodes) => {
-                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
-                    tab.directory_tree.insert_children(&parent_dn, nodes);
-                    let loaded_msg = format!(
-                        "Loaded children of {}",
-                        loom_core::dn::rdn_display_name(&parent_dn)
-                    );
-                    self.status_bar.set_message(loaded_msg.clone());
-                    self.log_panel.push_info(loaded_msg);
+            Action::EntryCreated(dn) => {
+                let created_msg =
+                    format!("Created entry: {}", loom_core::dn::rdn_display_name(&dn));
+                self.status_bar.set_message(created_msg.clone());
+                self.log_panel.push_info(created_msg);
+                // Show it under its parent without listing the siblings again
+                if let Some(parent) = loom_core::dn::parent_dn(&dn) {
+                    let parent = parent.to_string();
+                    if let Some(tab) = self.active_tab_mut() {
+                        tab.directory_tree
+                            .insert_child(&parent, TreeNode::new(dn.clone()));
+                    }
                 }
             }
-            Action::EntryLoaded(_conn_id, entry) => {
-                let schema = self.active_tab().and_then(|t| t.schema.clone());
-                self.detail_panel.set_entry(entry, schema.as_ref());
-            }
-            Action::EntryRefresh => {
-                if let (Some(id), Some(ref entry)) = (self.active_tab_id, &self.detail_panel.entry)
-                {
-                    self.spawn_load_entry(id, entry.dn.clone());
+            Action::ShowDeleteDialog(dn) => {
+                if self.refuse_base_delete(&dn) {
+                    return;
+                }
+                if self.subtree_delete_task.is_some() {
+                    self.push_error("A subtree is already being deleted".to_string());
+                } else if let Some(children) = self.children_of(&dn) {
+                    self.delete_dialog.show(dn, children);
                 }
             }
-
-            // Search
-            Action::SearchExecute(filter) => {
-                if let Err(e) = loom_core::filter::validate_filter(&filter) {
-                    self.status_bar.set_error(format!("Invalid filter: {}", e));
-                    // Re-activate input so user can fix the filter
-                    self.command_panel.resume_input();
-                    self.command_panel.input_buffer = filter;
-                    self.command_panel.cursor_pos = self.command_panel.input_buffer.len();
-                } else if let Some(id) = self.active_tab_id {
-                    self.status_bar
-                        .set_message(format!("Searching: {}...", filter));
-                    self.search_dialog.filter = filter.clone();
-                    self.spawn_search(id, filter);
-                } else {
-                    self.status_bar
-                        .set_error("No active connection".to_string());
+            Action::DeleteSubtree(dn) => {
+                if self.refuse_base_delete(&dn) {
+                    return;
+                }
+                if !self.guard_write(&dn, Action::DeleteSubtree(dn.clone())) {
+                    self.delete_dialog.hide();
+                    return;
+                }
+                if let Some(id) = self.active_tab_id {
+                    if self.recording_edits() {
+                        self.delete_dialog.hide();
+                        self.queue_subtree_delete(id, dn);
+                        return;
+                    }
+                    self.push_message(format!("Deleting subtree: {}...", dn));
+                    let loaded = self
+                        .active_tab()
+                        .and_then(|t| t.entry_cache.get(&normalize_dn(&dn)))
+                        .cloned();
+                    let loaded_children = self.listed_children(&dn);
+                    self.spawn_delete_subtree(id, dn, loaded, loaded_children);
                 }
             }
-            Action::SearchResults(conn_id, entries) => {
-                if self.active_tab_id == Some(conn_id) {
-                    let count = entries.len();
-                    self.status_bar
-                        .set_message(format!("Found {} entries", count));
-                    // Store results in search dialog (keep popup visible)
-                    let filter = self.search_dialog.filter.clone();
-                    self.search_dialog.show_results(filter, entries);
+            Action::SubtreeDeleteProgress { done, total } => {
+                self.delete_dialog.set_progress(done, total);
+            }
+            Action::SubtreeDeleteCancel => {
+                if let Some((conn_id, op, base)) = self.subtree_delete_task.take() {
+                    if let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) {
+                        tab.ops.cancel(op);
+                    }
+                    self.push_message(
+                        "Subtree delete stopped; entries already deleted stay deleted".to_string(),
+                    );
+                    // How far it got is unknown, so list the subtree's parent again
+                    if let Some(parent) = loom_core::dn::parent_dn(&base) {
+                        self.spawn_load_children(conn_id, parent.to_string());
+                    }
                 }
+                self.delete_dialog.hide();
             }
-            Action::SearchFocusInput => {
-                self.dismiss_all_popups();
-                self.search_dialog.visible = true;
-                if self.search_dialog.has_results() {
-                    // Results exist — open in navigation mode (press / to edit filter)
-                    self.command_panel.soft_deactivate();
-                } else if self.command_panel.input_buffer.is_empty() {
-                    self.command_panel.activate_input();
-                } else {
-                    self.command_panel.resume_input();
+            Action::SubtreeDeleted { base, result } => {
+                self.subtree_delete_task = None;
+                self.delete_dialog.hide();
+                self.forget_deleted(&result.deleted);
+                match result.failure {
+                    None => {
+                        let msg = format!(
+                            "Deleted {} of {} entries under {}",
+                            result.deleted.len(),
+                            result.total,
+                            base
+                        );
+                        self.status_bar.set_message(msg.clone());
+                        self.log_panel.push_info(msg);
+                    }
+                    Some((dn, error)) => {
+                        let msg = format!(
+                            "Subtree delete stopped at {}: {}; {} of {} entries deleted",
+                            dn,
+                            error,
+                            result.deleted.len(),
+                            result.total
+                        );
+                        self.status_bar.set_message(msg.clone());
+                        self.push_error(msg);
+                    }
                 }
             }
-
-            // Live Search (debounced preview)
-            Action::LiveSearchRequest { generation, filter } => {
+            Action::SubtreeDeleteFailed(error) => {
+                self.subtree_delete_task = None;
+                self.delete_dialog.hide();
+                self.push_error(error);
+            }
+            Action::SubtreeDeleteBlocked(dn, fresh, check) => {
+                self.subtree_delete_task = None;
+                self.delete_dialog.hide();
+                let mut msg = match &check {
+                    DeleteCheck::ChildrenChanged { loaded, now } => format!(
+                        "{} had {} entries directly below it when the delete was confirmed; it now has {}.\n",
+                        dn, loaded, now
+                    ),
+                    DeleteCheck::Changed(changes) => changed_since_loaded(&dn, changes),
+                    DeleteCheck::Proceed | DeleteCheck::Gone => String::new(),
+                };
+                msg.push_str("Delete the subtree anyway?");
+                self.push_error(format!("Delete of subtree {} stopped: it changed", dn));
+                self.keep_fresh_copy(&dn, *fresh);
+                // List the children again so a retry counts what is there now
                 if let Some(id) = self.active_tab_id {
-                    self.spawn_live_search(id, generation, filter);
+                    self.spawn_load_children(id, dn.clone());
                 }
+                self.confirm_dialog.show(msg, Action::DeleteSubtree(dn));
             }
-            Action::LiveSearchResults {
-                generation,
-                entries,
-            } => {
-                if self.command_panel.receive_live_results(generation) {
-                    // Feed live results directly into the search dialog table
-                    let filter = self.command_panel.input_buffer.clone();
-                    self.search_dialog.filter = filter;
-                    self.search_dialog.results = entries;
-                    self.search_dialog.reset_selection();
+            Action::ConfirmDeleteEntries(dns) => {
+                let mut msg = match dns.as_slice() {
+                    [dn] => format!("Delete {}?\n", dn),
+                    _ => format!("Delete these {} entries?\n", dns.len()),
+                };
+                for dn in dns.iter().take(5).filter(|_| dns.len() > 1) {
+                    msg.push_str(&format!("  {}\n", dn));
                 }
+                if dns.len() > 5 {
+                    msg.push_str(&format!("  \u{2026}and {} more\n", dns.len() - 5));
+                }
+                msg.push_str("Entries with children are left in place.");
+                self.confirm_dialog.show(msg, Action::DeleteEntries(dns));
             }
-
-            // Attribute editing
-            Action::EditAttribute(dn, attr, value) => {
-                let (is_dn, multi_valued) = self.lookup_attr_schema(&attr);
-                self.attribute_editor
-                    .edit_value_with_options(dn, attr, value, is_dn, multi_valued);
+            Action::DeleteEntries(dns) => {
+                if dns.iter().any(|dn| self.refuse_base_delete(dn)) {
+                    return;
+                }
+                if let Some(id) = self.active_tab_id {
+                    if self.recording_edits() {
+                        let (dns, protected): (Vec<String>, Vec<String>) =
+                            dns.into_iter().partition(|dn| {
+                                !self
+                                    .active_tab()
+                                    .is_some_and(|t| t.protected.check(dn).is_some())
+                            });
+                        if !protected.is_empty() {
+                            self.push_error(format!(
+                                "Left {} protected entries out of the queue",
+                                protected.len()
+                            ));
+                        }
+                        let changes = deletion_order(dns)
+                            .into_iter()
+                            .map(|dn| Change::Delete { dn })
+                            .collect();
+                        self.queue_changes(id, changes);
+                        return;
+                    }
+                    self.push_message(format!("Deleting {} entries...", dns.len()));
+                    self.spawn_delete_entries(id, dns);
+                }
             }
-            Action::AddAttribute(dn, attr) => {
-                let (is_dn, multi_valued) = self.lookup_attr_schema(&attr);
-                self.attribute_editor
-                    .add_value_with_options(dn, attr, is_dn, multi_valued);
+            Action::EntriesDeleted(result) => {
+                for (dn, error) in &result.errors {
+                    self.log_panel.push_error(format!("{}: {}", dn, error));
+                }
+                self.forget_deleted(&result.deleted);
+                self.search_dialog.forget_results(&result.deleted);
+                if let Some(id) = self.active_tab_id {
+                    let parents: BTreeSet<String> = result
+                        .deleted
+                        .iter()
+                        .filter_map(|dn| loom_core::dn::parent_dn(dn))
+                        .map(str::to_string)
+                        .collect();
+                    for parent in parents {
+                        self.spawn_load_children(id, parent);
+                    }
+                }
+                self.batch_summary_popup.show_titled(
+                    "Delete Entries",
+                    result.deleted.len(),
+                    result.errors,
+                );
+                let message = format!("Delete: {}", self.batch_summary_popup.headline());
+                self.status_bar.set_message(message.clone());
+                self.log_panel.push_info(message);
             }
-            Action::ShowAddAttribute(dn) => {
-                // Build candidate list from schema
-                let candidates = if let Some(tab) = self.active_tab() {
-                    if let Some(ref schema) = tab.schema {
-                        let present: std::collections::HashSet<String> = self
-                            .detail_panel
-                            .entry
-                            .as_ref()
-                            .map(|e| e.attributes.keys().map(|k| k.to_lowercase()).collect())
-                            .unwrap_or_default();
-
-                        let ocs: Vec<&str> = self
-                            .detail_panel
-                            .entry
-                            .as_ref()
-                            .map(|e| e.object_classes())
-                            .unwrap_or_default();
-
-                        let attr_names = if !ocs.is_empty() {
-                            schema.allowed_attributes(&ocs)
-                        } else {
-                            schema.all_user_attributes()
+            Action::ShowRenameDialog(dn) => {
+                let is_base = self.active_tab().is_some_and(|t| {
+                    loom_core::dn::normalize(&dn)
+                        == loom_core::dn::normalize(&t.directory_tree.root_dn)
+                });
+                if is_base {
+                    self.push_error(format!(
+                        "{} is the connection's base DN and cannot be renamed",
+                        dn
+                    ));
+                } else if let Some(children) = self.children_of(&dn) {
+                    self.rename_dialog.show(dn, children);
+                }
+            }
+            Action::RenameEntry {
+                dn,
+                new_rdn,
+                delete_old_rdn,
+                new_superior,
+            } => {
+                let retry = Action::RenameEntry {
+                    dn: dn.clone(),
+                    new_rdn: new_rdn.clone(),
+                    delete_old_rdn,
+                    new_superior: new_superior.clone(),
+                };
+                if !self.guard_write(&dn, retry) {
+                    return;
+                }
+                if let Some(id) = self.active_tab_id {
+                    if self.recording_edits() {
+                        let change = Change::Rename {
+                            dn,
+                            new_rdn,
+                            delete_old_rdn,
+                            new_superior,
                         };
-
-                        attr_names
-                            .into_iter()
-                            .filter(|name| !present.contains(&name.to_lowercase()))
-                            .map(|name| {
-                                let syntax_label = schema
-                                    .get_attribute_type(&name)
-                                    .map(|at| format!("{:?}", at.syntax))
-                                    .unwrap_or_default();
-                                (name, syntax_label)
-                            })
-                            .collect()
-                    } else {
-                        Vec::new()
+                        self.queue_changes(id, vec![change]);
+                        return;
                     }
-                } else {
-                    Vec::new()
-                };
-
-                self.attribute_picker.show(dn, candidates);
+                    self.push_message(format!("Renaming entry: {}...", dn));
+                    self.spawn_rename_entry(id, dn, new_rdn, delete_old_rdn, new_superior);
+                }
             }
-            Action::DeleteAttributeValue(dn, attr, value) => {
-                let result = EditResult {
-                    dn,
-                    op: EditOp::Delete { attr, value },
-                    new_value: String::new(),
+            Action::ShowAccountAction(dn, action) => {
+                let Some(tab) = self.active_tab() else {
+                    self.push_error("No active connection".to_string());
+                    return;
                 };
-                if let Some(id) = self.active_tab_id {
-                    self.spawn_save_attribute(id, result);
+                // The shown entry, or a cached one, carries the account state
+                let key = normalize_dn(&dn);
+                let entry = self
+                    .detail_panel
+                    .entry
+                    .as_ref()
+                    .filter(|e| normalize_dn(&e.dn) == key)
+                    .or_else(|| tab.entry_cache.get(&key).map(|t| &t.entry))
+                    .cloned();
+                let Some(entry) = entry else {
+                    self.push_error(format!("Open {} to see its account first", dn));
+                    return;
+                };
+                let server_type = tab
+                    .root_dse
+                    .as_ref()
+                    .map_or(ServerType::Unknown(String::new()), |dse| {
+                        dse.server_type.clone()
+                    });
+                match account_change(&entry, &server_type, action) {
+                    Ok(change) => {
+                        let msg = format!(
+                            "{} the account {}?\n{}",
+                            action.title(),
+                            dn,
+                            change.lines.join("\n")
+                        );
+                        let change = Change::Modify {
+                            dn,
+                            mods: change.mods,
+                        };
+                        self.confirm_dialog
+                            .show(msg, Action::ChangeAccount { action, change });
+                    }
+                    Err(e) => self.push_error(e),
                 }
             }
-            Action::SaveAttribute(result) => {
+            Action::ChangeAccount { action, change } => {
+                let dn = change.dn().to_string();
+                let retry = Action::ChangeAccount {
+                    action,
+                    change: change.clone(),
+                };
+                if !self.guard_write(&dn, retry) {
+                    return;
+                }
                 if let Some(id) = self.active_tab_id {
-                    self.spawn_save_attribute(id, result);
+                    if self.recording_edits() {
+                        self.queue_changes(id, vec![change]);
+                        return;
+                    }
+                    self.spawn_account_change(id, action, change);
                 }
             }
-            Action::AttributeSaved(dn) => {
-                let saved_msg =
-                    format!("Saved changes to {}", loom_core::dn::rdn_display_name(&dn));
-                self.status_bar.set_message(saved_msg.clone());
-                self.log_panel.push_info(saved_msg);
-                // Refresh the entry
+            Action::AccountChanged { dn, action } => {
+                let msg = format!(
+                    "{} account {}",
+                    action.done(),
+                    loom_core::dn::rdn_display_name(&dn)
+                );
+                self.status_bar.set_message(msg.clone());
+                self.log_panel.push_info(msg);
                 if let Some(id) = self.active_tab_id {
                     self.spawn_load_entry(id, dn);
                 }
             }
-
-            // Export
-            Action::ShowExportDialog => {
-                if let Some(tab) = self.active_tab() {
-                    let base_dn = self
-                        .tree_panel
-                        .selected_dn()
-                        .cloned()
-                        .unwrap_or_else(|| tab.directory_tree.root_dn.clone());
-                    self.export_dialog.show(&base_dn);
-                } else {
+            Action::ShowSetPassword(dn) => {
+                let Some(tab) = self.active_tab() else {
                     self.push_error("No active connection".to_string());
-                }
+                    return;
+                };
+                let extensions = tab
+                    .root_dse
+                    .as_ref()
+                    .map(|dse| dse.supported_extensions.as_slice())
+                    .unwrap_or_default();
+                let method = PasswordMethod::for_server(extensions);
+                self.password_dialog.show(dn, method);
             }
-            Action::ExportExecute {
-                base_dn,
-                path,
-                filter,
-                attributes,
+            Action::SetPassword {
+                dn,
+                password,
+                must_change,
             } => {
+                let retry = Action::SetPassword {
+                    dn: dn.clone(),
+                    password: password.clone(),
+                    must_change,
+                };
+                if !self.guard_write(&dn, retry) {
+                    return;
+                }
+                // A queued change would keep the password on disk
+                if self.recording_edits() {
+                    self.push_error(
+                        "Passwords are not recorded as edits; stop recording to set one"
+                            .to_string(),
+                    );
+                    return;
+                }
                 if let Some(id) = self.active_tab_id {
-                    self.push_message(format!("Exporting to {} (filter: {})...", path, filter));
-                    self.spawn_export(id, path, base_dn, filter, attributes);
+                    self.spawn_set_password(id, dn, password, must_change);
+                }
+            }
+            Action::PasswordSet {
+                dn,
+                method,
+                must_change,
+            } => {
+                let mut msg = format!("Set password of {}", dn);
+                if must_change {
+                    msg.push_str("; it must be changed at next login");
+                }
+                if let Some(tab) = self.active_tab_mut() {
+                    tab.entry_cache.remove(&normalize_dn(&dn));
+                }
+                self.push_message(msg);
+                if let Some(warning) = method.warning() {
+                    self.status_bar.set_warning(warning.to_string());
+                    self.log_panel.push_info(warning.to_string());
                 }
             }
-            Action::ExportComplete(msg) => {
+            Action::EntryRenamed { old_dn, new_dn } => {
+                let msg = format!("Renamed {} to {}", old_dn, new_dn);
                 self.status_bar.set_message(msg.clone());
                 self.log_panel.push_info(msg);
+                let showing = self
+                    .detail_panel
+                    .entry
+                    .as_ref()
+                    .is_some_and(|e| normalize_dn(&e.dn) == normalize_dn(&old_dn));
+                if let Some(id) = self.active_tab_id {
+                    self.recent_entries.remove(id, &old_dn);
+                }
+                let mut path = None;
+                if let Some(tab) = self.active_tab_mut() {
+                    tab.entry_cache.remove(&normalize_dn(&old_dn));
+                    tab.directory_tree.move_node(&old_dn, &new_dn);
+                    path = tree_path(&tab.directory_tree, &new_dn);
+                }
+                if let Some(path) = path {
+                    self.tree_panel.tree_state.select(path);
+                }
+                if showing {
+                    let _ = self.action_tx.send(Action::TreeSelect(new_dn));
+                }
             }
-
-            // Bulk Update
-            Action::ShowBulkUpdateDialog => {
-                if self.active_tab_id.is_some() {
-                    self.bulk_update_dialog.show();
-                } else {
-                    self.push_error("No active connection".to_string());
+            Action::PushUndo(id, step) => {
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == id) {
+                    tab.undo.push(step);
                 }
             }
-            Action::BulkUpdateExecute {
-                filter,
-                attribute,
-                value,
-                op,
-            } => {
-                if let Some(id) = self.active_tab_id {
-                    use crate::components::bulk_update_dialog::BulkOp;
-                    let modification = match op {
-                        BulkOp::Replace => BulkMod::ReplaceAttribute {
-                            attr: attribute,
-                            value,
-                        },
-                        BulkOp::Add => BulkMod::AddValue {
-                            attr: attribute,
-                            value,
-                        },
-                        BulkOp::Delete => {
-                            if value.is_empty() {
-                                BulkMod::DeleteAttribute { attr: attribute }
-                            } else {
-                                BulkMod::DeleteValue {
-                                    attr: attribute,
-                                    value,
-                                }
-                            }
+            Action::ShowUndo => {
+                let preview = self.active_tab().map(|tab| {
+                    tab.undo
+                        .last()
+                        .map(|step| (step.description.clone(), step.preview()))
+                });
+                match preview {
+                    None => self.push_error("No active connection".to_string()),
+                    Some(None) => self.status_bar.set_message("Nothing to undo".to_string()),
+                    Some(Some((description, preview))) => {
+                        const PREVIEW_LINES: usize = 30;
+                        let lines: Vec<&str> = preview.lines().collect();
+                        let mut msg = format!("Undo {}? This sends:\n\n", description);
+                        for line in lines.iter().take(PREVIEW_LINES) {
+                            msg.push_str(line);
+                            msg.push('\n');
                         }
-                    };
-                    self.push_message(format!("Executing bulk update: {}...", filter));
-                    self.spawn_bulk_update(id, filter, vec![modification]);
+                        if lines.len() > PREVIEW_LINES {
+                            msg.push_str(&format!(
+                                "... and {} more lines\n",
+                                lines.len() - PREVIEW_LINES
+                            ));
+                        }
+                        self.confirm_dialog.show(msg, Action::UndoExecute);
+                    }
                 }
             }
-            Action::BulkUpdateComplete(msg) => {
+            Action::UndoExecute => self.spawn_undo(),
+            Action::UndoComplete(id, step) => {
+                let msg = format!("Undid {}", step.description);
                 self.status_bar.set_message(msg.clone());
                 self.log_panel.push_info(msg);
+                // Reload what the undo touched: containers it put entries
+                // back into, entries it renamed back, the entry on show
+                let showing = self
+                    .detail_panel
+                    .entry
+                    .as_ref()
+                    .map(|e| normalize_dn(&e.dn));
+                let mut containers = BTreeSet::new();
+                let mut reselect = None;
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == id) {
+                    for change in &step.changes {
+                        tab.entry_cache.remove(&normalize_dn(change.dn()));
+                        match change {
+                            Change::Rename {
+                                dn,
+                                new_rdn,
+                                new_superior,
+                                ..
+                            } => {
+                                let Ok(restored) =
+                                    loom_core::dn::renamed(dn, new_rdn, new_superior.as_deref())
+                                else {
+                                    continue;
+                                };
+                                tab.directory_tree.move_node(dn, &restored);
+                                if showing.as_deref() == Some(normalize_dn(dn).as_str()) {
+                                    reselect = Some(restored);
+                                }
+                            }
+                            Change::Modify { dn, .. } => {
+                                if showing.as_deref() == Some(normalize_dn(dn).as_str()) {
+                                    reselect = Some(dn.clone());
+                                }
+                            }
+                            Change::Add { dn, .. } | Change::Delete { dn } => {
+                                if let Some(parent) = loom_core::dn::parent_dn(dn) {
+                                    containers.insert(parent.to_string());
+                                }
+                            }
+                        }
+                    }
+                }
+                for parent in containers {
+                    self.spawn_load_children(id, parent);
+                }
+                if let Some(dn) = reselect {
+                    let _ = self.action_tx.send(Action::TreeSelect(dn));
+                }
             }
-
-            // Create / Delete Entry
-            Action::ShowCreateEntryDialog(parent_dn) => {
-                if self.active_tab_id.is_some() {
-                    self.create_entry_dialog.show(parent_dn);
+            Action::YankEntry(dn) => {
+                if let Some(id) = self.active_tab_id {
+                    self.status_bar.set_message(format!(
+                        "Yanked {}: p pastes a copy under the selected entry, P its subtree",
+                        loom_core::dn::rdn_display_name(&dn)
+                    ));
+                    self.yanked_entry = Some((id, dn));
+                }
+            }
+            Action::CompareEntry(dn) => self.compare_entry(&dn),
+            Action::PasteEntry(parent) => {
+                if let Some(source) = self.yanked_on_active_tab() {
+                    let _ = self.action_tx.send(Action::ShowCopyEntryDialog {
+                        source,
+                        parent: Some(parent),
+                    });
+                }
+            }
+            Action::PasteSubtree(parent) => {
+                if let Some(source) = self.yanked_on_active_tab() {
+                    let _ = self.action_tx.send(Action::ShowCopySubtreeDialog {
+                        source,
+                        parent: Some(parent),
+                    });
+                }
+            }
+            Action::ShowCopyEntryDialog { source, parent } => {
+                let Some(parent) =
+                    parent.or_else(|| loom_core::dn::parent_dn(&source).map(str::to_string))
+                else {
+                    self.push_error(format!("{} has no parent to copy it under", source));
+                    return;
+                };
+                let Some(entry) = self.loaded_entry(&source) else {
+                    self.push_error(format!(
+                        "Select {} first so its attributes are loaded",
+                        source
+                    ));
+                    return;
+                };
+                let schema = self.active_tab().and_then(|t| t.schema.clone());
+                let template = copyable_attributes(&entry, schema.as_ref());
+                let (rdn_attr, rdn_value) = loom_core::dn::Dn::parse(&source)
+                    .ok()
+                    .and_then(|d| {
+                        d.rdn()
+                            .and_then(|r| r.avas().first())
+                            .map(|a| (a.attr().to_string(), a.value().to_string()))
+                    })
+                    .unwrap_or_default();
+                self.create_entry_dialog.show_copy(
+                    parent,
+                    schema.as_ref(),
+                    &template,
+                    (&rdn_attr, &rdn_value),
+                );
+                self.status_bar.set_message(format!(
+                    "Copy of {}: give it a new {}",
+                    loom_core::dn::rdn_display_name(&source),
+                    rdn_attr
+                ));
+            }
+            Action::ShowCopySubtreeDialog { source, parent } => {
+                let Some(parent) =
+                    parent.or_else(|| loom_core::dn::parent_dn(&source).map(str::to_string))
+                else {
+                    self.push_error(format!("{} has no parent to copy it under", source));
+                    return;
+                };
+                if self.subtree_copy_task.is_some() {
+                    self.push_error("A subtree is already being copied".to_string());
+                } else if self.active_tab_id.is_some() {
+                    self.copy_subtree_dialog.show(source, parent);
                 } else {
                     self.push_error("No active connection".to_string());
                 }
             }
-            Action::CreateEntry { dn, attributes } => {
-                if let Some(id) = self.active_tab_id {
-                    self.push_message(format!("Creating entry: {}...", dn));
-                    self.spawn_create_entry(id, dn, attributes);
+            Action::CopySubtree {
+                source,
+                target,
+                changefile,
+            } => {
+                let Some(id) = self.active_tab_id else {
+                    return;
+                };
+                if self.subtree_copy_task.is_some() {
+                    self.copy_subtree_dialog.hide();
+                    self.push_error("A subtree is already being copied".to_string());
+                    return;
+                }
+                // A dry run only reads, so it runs anywhere, as does a
+                // copy queued as offline edits
+                if changefile.is_none() && !self.recording_edits() {
+                    let refusal = self.active_tab().and_then(|tab| match tab.backend {
+                        _ if tab.read_only => Some("Connection is read-only"),
+                        TabBackend::Offline(_) => Some("Example directory is read-only"),
+                        TabBackend::Live(_) => None,
+                    });
+                    if let Some(refusal) = refusal {
+                        self.copy_subtree_dialog.hide();
+                        self.push_error(refusal.to_string());
+                        return;
+                    }
+                    let retry = Action::CopySubtree {
+                        source: source.clone(),
+                        target: target.clone(),
+                        changefile: None,
+                    };
+                    if !self.guard_write(&target, retry) {
+                        self.copy_subtree_dialog.hide();
+                        return;
+                    }
+                }
+                self.push_message(format!("Copying {} to {}...", source, target));
+                self.spawn_read_subtree(id, source, target, changefile);
+            }
+            Action::SubtreeRead {
+                source,
+                target,
+                entries,
+                changefile,
+            } => {
+                let conn_id = self
+                    .subtree_copy_task
+                    .take()
+                    .map(|(id, _, _)| id)
+                    .or(self.active_tab_id);
+                let Some(conn_id) = conn_id else {
+                    return;
+                };
+                let schema = self
+                    .tabs
+                    .iter()
+                    .find(|t| t.id == conn_id)
+                    .and_then(|t| t.schema.as_ref());
+                let plan = match plan_subtree_copy(&entries, &source, &target, schema) {
+                    Ok(plan) if plan.is_empty() => Err(format!("{} was not found", source)),
+                    Ok(plan) => Ok(plan),
+                    Err(e) => Err(format!("Cannot copy {}: {}", source, e)),
+                };
+                let plan = match plan {
+                    Ok(plan) => plan,
+                    Err(error) => {
+                        self.copy_subtree_dialog.hide();
+                        self.push_error(error);
+                        return;
+                    }
+                };
+                let recording = self.tabs.iter().any(|t| t.id == conn_id && t.offline_edits);
+                let Some(path) = changefile else {
+                    if recording {
+                        self.copy_subtree_dialog.hide();
+                        self.queue_changes(conn_id, plan.iter().map(Change::add).collect());
+                    } else if self.tabs.iter().any(|t| t.id == conn_id && t.read_only) {
+                        // `:set write off` or a reconnect while the branch was read
+                        self.copy_subtree_dialog.hide();
+                        self.push_error("Connection is read-only".to_string());
+                    } else {
+                        self.spawn_copy_entries(conn_id, target, plan);
+                    }
+                    return;
+                };
+                let changes: Vec<Change> = plan.iter().map(Change::add).collect();
+                let mut buf = Vec::new();
+                let written = loom_core::export::ldif_changes::write_changes(&changes, &mut buf)
+                    .and_then(|count| {
+                        write_replacing(&path, &buf)
+                            .map(|_| count)
+                            .map_err(|e| CoreError::ExportError(e.to_string()))
+                    });
+                match written {
+                    Ok(count) => {
+                        let msg = format!("Dry run: wrote {} adds to {}", count, path.display());
+                        self.status_bar.set_message(msg.clone());
+                        self.push_message(msg);
+                    }
+                    Err(e) => self.push_error(format!("Dry run failed: {}", e)),
+                }
+            }
+            Action::SubtreeCopyProgress { done, total } => {
+                self.copy_subtree_dialog.set_progress(done, total);
+            }
+            Action::SubtreeCopyCancel => {
+                if let Some((conn_id, op, target)) = self.subtree_copy_task.take() {
+                    if let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) {
+                        tab.ops.cancel(op);
+                    }
+                    self.push_message(
+                        "Subtree copy stopped; entries already added stay".to_string(),
+                    );
+                    if let Some(parent) = loom_core::dn::parent_dn(&target) {
+                        self.spawn_load_children(conn_id, parent.to_string());
+                    }
                 }
+                self.copy_subtree_dialog.hide();
             }
-            Action::EntryCreated(dn) => {
-                let created_msg =
-                    format!("Created entry: {}", loom_core::dn::rdn_display_name(&dn));
-                self.status_bar.set_message(created_msg.clone());
-                self.log_panel.push_info(created_msg);
-                // Refresh parent's children in the tree
-                if let Some(id) = self.active_tab_id {
-                    if let Some(parent) = loom_core::dn::parent_dn(&dn) {
-                        self.spawn_load_children(id, parent.to_string());
+            Action::SubtreeCopied { target, result } => {
+                self.subtree_copy_task = None;
+                self.copy_subtree_dialog.hide();
+                let added_top = result
+                    .added
+                    .first()
+                    .is_some_and(|dn| normalize_dn(dn) == normalize_dn(&target));
+                if let (true, Some(parent)) = (added_top, loom_core::dn::parent_dn(&target)) {
+                    let parent = parent.to_string();
+                    if let Some(tab) = self.active_tab_mut() {
+                        tab.directory_tree
+                            .insert_child(&parent, TreeNode::new(target.clone()));
+                    }
+                }
+                match result.failure {
+                    None => {
+                        let msg = format!(
+                            "Copied {} of {} entries to {}",
+                            result.added.len(),
+                            result.total,
+                            target
+                        );
+                        self.status_bar.set_message(msg.clone());
+                        self.log_panel.push_info(msg);
+                    }
+                    Some((dn, error)) => {
+                        let msg = format!(
+                            "Subtree copy stopped at {}: {}; {} of {} entries added",
+                            dn,
+                            error,
+                            result.added.len(),
+                            result.total
+                        );
+                        self.status_bar.set_message(msg.clone());
+                        self.push_error(msg);
                     }
                 }
             }
+            Action::SubtreeCopyFailed(error) => {
+                self.subtree_copy_task = None;
+                self.copy_subtree_dialog.hide();
+                self.push_error(error);
+            }
             Action::DeleteEntry(dn) => {
+                if self.refuse_base_delete(&dn) {
+                    return;
+                }
+                if !self.guard_write(&dn, Action::DeleteEntry(dn.clone())) {
+                    return;
+                }
                 if let Some(id) = self.active_tab_id {
+                    if self.recording_edits() {
+                        self.queue_changes(id, vec![Change::Delete { dn }]);
+                        return;
+                    }
                     self.push_message(format!("Deleting entry: {}...", dn));
-                    self.spawn_delete_entry(id, dn);
+                    let loaded = self
+                        .active_tab()
+                        .and_then(|t| t.entry_cache.get(&normalize_dn(&dn)))
+                        .cloned();
+                    self.spawn_delete_entry(id, dn, loaded);
                 }
             }
+            Action::DeleteBlocked(dn, fresh, changes) => {
+                let mut msg = changed_since_loaded(&dn, &changes);
+                msg.push_str("Delete it anyway?");
+                self.push_error(format!("Delete of {} stopped: entry changed", dn));
+                self.keep_fresh_copy(&dn, *fresh);
+                self.confirm_dialog.show(msg, Action::DeleteEntry(dn));
+            }
             Action::EntryDeleted(dn) => {
                 let deleted_msg =
                     format!("Deleted entry: {}", loom_core::dn::rdn_display_name(&dn));
                 self.status_bar.set_message(deleted_msg.clone());
                 self.log_panel.push_info(deleted_msg);
-                // Clear detail panel if showing the deleted entry
-                if let Some(ref entry) = self.detail_panel.entry {
-                    if entry.dn == dn {
-                        self.detail_panel.clear();
-                    }
-                }
+                self.forget_deleted(std::slice::from_ref(&dn));
                 // Refresh parent's children in the tree
                 if let Some(id) = self.active_tab_id {
                     if let Some(parent) = loom_core::dn::parent_dn(&dn) {
@@ -2326,28 +7383,32 @@ impl App {
             }
 
             // Schema
-            Action::ShowSchemaViewer => {
-                let schema_and_id = self.active_tab().map(|tab| {
-                    if let Some(ref schema) = tab.schema {
-                        (schema.clone(), tab.id)
-                    } else {
-                        (SchemaCache::new(), tab.id)
-                    }
-                });
-                match schema_and_id {
-                    Some((schema, _id)) if !schema.attribute_types.is_empty() => {
-                        self.schema_viewer.show(&schema);
-                    }
-                    Some((_, id)) => {
+            Action::ShowSchemaViewer => match self.active_tab() {
+                Some(tab) => {
+                    let id = tab.id;
+                    let loaded = tab
+                        .schema
+                        .as_ref()
+                        .is_some_and(|s| !s.attribute_types.is_empty());
+                    if !loaded {
                         self.push_message("Loading schema...".to_string());
-                        // Mark viewer as pending so SchemaLoaded knows to show it
-                        self.schema_viewer.visible = true;
                         self.spawn_load_schema(id);
                     }
-                    None => {
-                        self.push_error("No active connection".to_string());
-                    }
+                    let _ = self
+                        .action_tx
+                        .send(Action::SwitchLayout(ActiveLayout::Schema));
+                }
+                None => {
+                    self.push_error("No active connection".to_string());
+                }
+            },
+            Action::ShowSchemaDefinition(name) => {
+                self.sync_schema_viewer();
+                if self.active_tab_id.is_some() && !self.schema_viewer.reveal(&name) {
+                    self.status_bar
+                        .set_message(format!("'{}' is not in the schema", name));
                 }
+                let _ = self.action_tx.send(Action::ShowSchemaViewer);
             }
             Action::SchemaLoaded(conn_id, schema) => {
                 debug!(
@@ -2357,8 +7418,6 @@ impl App {
                     schema.object_classes.len(),
                     self.active_tab_id,
                 );
-                // Only show schema viewer if it was already visible (user triggered ShowSchemaViewer)
-                let viewer_was_open = self.schema_viewer.visible;
                 let schema_empty = schema.attribute_types.is_empty();
                 if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
                     if !schema_empty {
@@ -2373,8 +7432,8 @@ impl App {
                         debug!("SchemaLoaded: schema is empty for conn_id={}, will use fallback attributes", conn_id);
                     }
                     tab.schema = Some(*schema.clone());
-                    if viewer_was_open && !schema_empty {
-                        self.schema_viewer.show(&schema);
+                    if self.schema_viewer.source() == Some(conn_id) {
+                        self.schema_viewer.invalidate();
                     }
                 } else {
                     debug!("SchemaLoaded: no tab found for conn_id={}", conn_id);
@@ -2412,11 +7471,113 @@ impl App {
             Action::ShowAbout => {
                 self.about_popup.show();
             }
+            Action::ShowValueViewer {
+                attr,
+                value,
+                binary,
+            } => self.value_viewer.show(attr, value, binary),
+            Action::ShowErrorDetails => match self.last_error.clone() {
+                Some(msg) => self.error_details_popup.show(msg),
+                None => self
+                    .status_bar
+                    .set_message("No errors to explain".to_string()),
+            },
+            Action::ShowDoctor => {
+                let report = crate::doctor::run_checks(&self.config);
+                self.doctor_popup.show(report);
+            }
+            Action::ShowServerInfo(id) => {
+                let tab = match id {
+                    Some(id) => self.tabs.iter().find(|t| t.id == id),
+                    None => self.active_tab(),
+                };
+                match tab {
+                    Some(tab) => {
+                        let label = format!("{} ({})", tab.label, tab.host);
+                        let root_dse = tab.root_dse.clone();
+                        self.server_info_popup.show(&label, root_dse);
+                    }
+                    None => self
+                        .status_bar
+                        .set_message("No connection to show server info for".to_string()),
+                }
+            }
 
             // Log Panel
             Action::ToggleLogPanel => {
                 self.log_panel.toggle();
             }
+            Action::ShowLogViewer => {
+                self.log_viewer.show();
+            }
+            Action::ShowMessages => {
+                let messages = self.status_bar.messages().iter().cloned().collect();
+                let audit = self
+                    .audit
+                    .as_ref()
+                    .map(|log| format!("{} ({})", log.path().display(), log.format().label()));
+                self.messages_popup.show(messages, audit);
+            }
+
+            Action::ReloadTheme => {
+                let name = self.config.general.theme.clone();
+                match Styles::named(&name) {
+                    Ok(styles) => {
+                        self.theme.set(styles);
+                        self.push_message(format!("Reloaded theme '{}'", name));
+                    }
+                    Err(e) => self.push_error(format!("Theme {}; keeping the current theme", e)),
+                }
+            }
+
+            // Profile hooks
+            Action::ApproveHooks => {
+                self.asking_hook = false;
+                let Some(approved) = self.hook_approvals.pop_front() else {
+                    return;
+                };
+                let profile = approved.invocation.profile.clone();
+                for p in self
+                    .config
+                    .connections
+                    .iter_mut()
+                    .filter(|p| p.name == profile)
+                {
+                    p.hooks.untrusted = false;
+                }
+                if let Err(e) = self.save_config() {
+                    self.push_error(format!("Failed to save config: {}", e));
+                }
+                self.log_panel
+                    .push_info(format!("Hooks approved for profile '{}'", profile));
+                // Other hooks of the profile waiting behind it are trusted now too
+                let (trusted, waiting) = std::mem::take(&mut self.hook_approvals)
+                    .into_iter()
+                    .partition(|a| a.invocation.profile == profile);
+                self.hook_approvals = waiting;
+                for approval in std::iter::once(approved).chain::<VecDeque<_>>(trusted) {
+                    match approval.connect {
+                        Some(held) => self.begin_connect(
+                            held.profile,
+                            held.password,
+                            held.origin,
+                            Some(approval.invocation),
+                        ),
+                        None => self.spawn_hook(approval.invocation),
+                    }
+                }
+                self.ask_hook_approval();
+            }
+            Action::HookFinished(outcome) => {
+                let summary = outcome.summary();
+                if outcome.success {
+                    info!("{}", summary);
+                    self.log_panel.push_info(summary);
+                } else {
+                    error!("{}", summary);
+                    self.push_error(summary);
+                }
+            }
 
             // Popups
             Action::ShowConfirm(msg, on_confirm) => {
@@ -2433,12 +7594,29 @@ impl App {
                 self.attribute_picker.hide();
                 self.export_dialog.hide();
                 self.bulk_update_dialog.hide();
+                self.batch_modify_dialog.hide();
+                self.batch_summary_popup.hide();
+                self.migrate_dialog.hide();
                 self.create_entry_dialog.hide();
-                self.schema_viewer.hide();
                 self.help_popup.hide();
                 self.log_panel.hide();
                 self.profile_export_dialog.hide();
                 self.profile_import_dialog.hide();
+                self.search_form_dialog.hide();
+                self.snapshot_dialog.hide();
+                self.subtree_compare_dialog.hide();
+                self.pending_changes_popup.hide();
+                self.apply_ldif_dialog.hide();
+                self.apply_results_view.hide();
+                self.delete_dialog.hide();
+                self.rename_dialog.hide();
+                self.copy_subtree_dialog.hide();
+                self.activity_popup.hide();
+                self.bookmarks_popup.hide();
+                self.server_info_popup.hide();
+                self.value_viewer.hide();
+                self.membership_popup.hide();
+                self.messages_popup.hide();
             }
 
             // Status
@@ -2448,6 +7626,7 @@ impl App {
             }
             Action::ErrorMessage(msg) => {
                 error!("{}", msg);
+                self.last_error = Some(msg.clone());
                 self.log_panel.push_error(msg.clone());
                 self.status_bar.set_error(msg);
             }
@@ -2469,12 +7648,73 @@ impl App {
                 self.attribute_editor.receive_results(generation, entries);
             }
             Action::AddMultipleValues { dn, attr, values } => {
+                let retry = Action::AddMultipleValues {
+                    dn: dn.clone(),
+                    attr: attr.clone(),
+                    values: values.clone(),
+                };
+                if !self.guard_write(&dn, retry) {
+                    return;
+                }
                 if let Some(id) = self.active_tab_id {
+                    if self.recording_edits() {
+                        let mods: Vec<BulkMod> = values
+                            .into_iter()
+                            .map(|value| BulkMod::AddValue {
+                                attr: attr.clone(),
+                                value,
+                            })
+                            .collect();
+                        self.queue_changes(id, vec![Change::modify(&dn, &mods)]);
+                        return;
+                    }
                     self.spawn_add_multiple_values(id, dn, attr, values);
                 }
             }
 
             Action::Tick => {
+                self.layout_bar.tick();
+                self.tree_panel.tick();
+                self.status_bar.tick();
+                self.status_bar.running = self.active_tab().map_or(0, |t| t.ops.running());
+                self.status_bar.current = self
+                    .active_tab()
+                    .and_then(|t| t.ops.newest_running())
+                    .map(|op| {
+                        format!(
+                            "{} {}",
+                            truncate_to_width(&op.label, 40),
+                            op.describe(Instant::now())
+                        )
+                    });
+                self.status_bar.pending = self
+                    .active_tab()
+                    .filter(|t| t.offline_edits)
+                    .map(|t| t.pending_changes.len());
+                self.status_bar.health = self
+                    .active_tab()
+                    .filter(|t| t.reconnector.is_some())
+                    .map(|t| t.health.get());
+                self.status_bar.read_only = self.active_tab().is_some_and(|t| t.writes_locked());
+                self.status_bar.selected = self.search_dialog.selection_count();
+                self.status_bar.connecting = self.connect_attempts.status_line();
+                self.sync_status_context();
+                if self.activity_popup.visible {
+                    let ops = self
+                        .active_tab()
+                        .map(|t| t.ops.list(Instant::now()))
+                        .unwrap_or_default();
+                    self.activity_popup.set_ops(ops);
+                }
+                if self.log_viewer.visible {
+                    self.log_viewer.refresh();
+                }
+                if !self.hook_approvals.is_empty() {
+                    self.ask_hook_approval();
+                }
+                if self.config_watch.as_mut().is_some_and(|w| w.changed()) {
+                    self.reload_config();
+                }
                 // Dispatch tick to attribute editor for debounced DN search
                 if self.attribute_editor.visible {
                     let base_dn = self
@@ -2507,30 +7747,58 @@ impl App {
                     self.context_menu.show_for_detail(dn, attr_name, attr_value);
                 }
             },
-            Action::CopyToClipboard(text) => match arboard::Clipboard::new() {
-                Ok(mut clipboard) => match clipboard.set_text(&text) {
-                    Ok(_) => {
-                        let preview = if text.len() > 40 {
-                            format!("{}...", &text[..40])
-                        } else {
-                            text.clone()
-                        };
-                        let _ = self
-                            .action_tx
-                            .send(Action::StatusMessage(format!("Copied: {}", preview)));
+            Action::ShowCopyAsMenu(source) => {
+                let (value, dn) = match &source {
+                    ContextMenuSource::Tree { dn } => (dn.as_str(), dn.as_str()),
+                    ContextMenuSource::Detail { dn, attr_value, .. } => {
+                        (attr_value.as_str(), dn.as_str())
                     }
-                    Err(e) => {
-                        let _ = self
-                            .action_tx
-                            .send(Action::ErrorMessage(format!("Clipboard error: {}", e)));
+                };
+                let (host, base) = self
+                    .active_tab()
+                    .map(|t| (t.host.clone(), t.directory_tree.root_dn.clone()))
+                    .unwrap_or_default();
+                let ctx = CopyContext {
+                    value,
+                    dn,
+                    host: &host,
+                    base: &base,
+                };
+                let entries = effective_templates(&self.config.copy_templates)
+                    .into_iter()
+                    .map(|(name, template)| {
+                        let rendered = template.render(&ctx);
+                        (name, rendered)
+                    })
+                    .collect();
+                self.context_menu.show_copy_as(entries);
+            }
+            Action::CopyToClipboard(text) => {
+                let reply = match self.clipboard.copy(&text) {
+                    Ok(Copied::System) => {
+                        Action::StatusMessage(format!("Copied: {}", clipboard::preview(&text)))
+                    }
+                    Ok(Copied::Terminal) => Action::StatusMessage(format!(
+                        "Copied to the terminal clipboard: {}",
+                        clipboard::preview(&text)
+                    )),
+                    Err(e) => Action::ErrorMessage(e),
+                };
+                let _ = self.action_tx.send(reply);
+            }
+            Action::CopyEntryLdif(dn) => match self.loaded_entry(&dn) {
+                Some(entry) => {
+                    let mut buf = Vec::new();
+                    match loom_core::export::ldif::write_to(std::iter::once(&entry), &mut buf, &[])
+                    {
+                        Ok(_) => {
+                            let text = String::from_utf8_lossy(&buf).into_owned();
+                            let _ = self.action_tx.send(Action::CopyToClipboard(text));
+                        }
+                        Err(e) => self.push_error(format!("Failed to write LDIF: {}", e)),
                     }
-                },
-                Err(e) => {
-                    let _ = self.action_tx.send(Action::ErrorMessage(format!(
-                        "Clipboard unavailable: {}",
-                        e
-                    )));
                 }
+                None => self.push_error(format!("{} is not loaded yet", dn)),
             },
 
             // Vault actions
@@ -2544,7 +7812,7 @@ impl App {
                     Ok(vault) => {
                         self.vault = Some(vault);
                         self.config.general.vault_enabled = true;
-                        if let Err(e) = self.config.save() {
+                        if let Err(e) = self.save_config() {
                             self.push_error(format!("Failed to save config: {}", e));
                         }
                         self.push_message("Vault created successfully".to_string());
@@ -2568,19 +7836,316 @@ impl App {
                     self.push_error("No vault loaded".to_string());
                 }
             }
+            Action::ProtectedWriteConfirmed { dn, action } => {
+                self.protected_write_allowed = Some(loom_core::dn::normalize(&dn));
+                let _ = self.action_tx.send(*action);
+            }
             Action::Render | Action::Resize(_, _) | Action::None => {}
             _ => {}
         }
     }
 
+    /// Whether writes on the active connection are queued rather than sent.
+    fn recording_edits(&self) -> bool {
+        self.active_tab().is_some_and(|t| t.offline_edits)
+    }
+
+    /// Add `changes` to the pending changes of connection `conn_id`.
+    fn queue_changes(&mut self, conn_id: ConnectionId, changes: Vec<Change>) {
+        let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let queued = changes.len();
+        tab.pending_changes.extend(changes);
+        let msg = format!(
+            "Queued {} change(s); {} pending",
+            queued,
+            tab.pending_changes.len()
+        );
+        self.status_bar.set_message(msg.clone());
+        self.log_panel.push_info(msg);
+    }
+
+    /// Queue deletes of `dn` and everything below it, children first. A
+    /// protected entry below `dn` keeps the whole subtree out of the queue.
+    fn queue_subtree_delete(&self, conn_id: ConnectionId, dn: String) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let protected = tab.protected.clone();
+        let base_key = loom_core::dn::normalize(&dn);
+        let filter = "(objectClass=*)".to_string();
+        self.spawn_plan_changes(conn_id, dn, filter, &["1.1"], move |entries| {
+            let dns: Vec<String> = entries.into_iter().map(|e| e.dn).collect();
+            if let Some((target, pattern)) = dns
+                .iter()
+                .filter(|d| loom_core::dn::normalize(d) != base_key)
+                .find_map(|d| protected.check(d).map(|p| (d, p)))
+            {
+                return Err(format!(
+                    "{} is protected by '{}'; nothing was queued",
+                    target, pattern
+                ));
+            }
+            Ok(deletion_order(dns)
+                .into_iter()
+                .map(|dn| Change::Delete { dn })
+                .collect())
+        });
+    }
+
+    /// Find the entries below `base` matching `filter`, then queue the
+    /// changes `plan` makes of them. Only reads go to the server, so this
+    /// runs on read-only and example connections too.
+    fn spawn_plan_changes<F>(
+        &self,
+        conn_id: ConnectionId,
+        base: String,
+        filter: String,
+        attrs: &'static [&'static str],
+        plan: F,
+    ) where
+        F: FnOnce(Vec<LdapEntry>) -> Result<Vec<Change>, String> + Send + 'static,
+    {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        let queue = move |entries: Vec<LdapEntry>| {
+            let _ = tx.send(match plan(entries) {
+                Ok(changes) => Action::QueueChanges { conn_id, changes },
+                Err(e) => Action::ErrorMessage(e),
+            });
+        };
+        let connection = match tab.backend {
+            TabBackend::Offline(ref dir) => {
+                queue(dir.search(&base, &filter));
+                return;
+            }
+            TabBackend::Live(ref connection) => connection.clone(),
+        };
+        let error_tx = self.action_tx.clone();
+        tab.spawn_op(format!("Plan changes for {}", filter), async move {
+            let mut conn = connection.lock().await;
+            match conn.search_subtree(&base, &filter, attrs).await {
+                Ok(entries) => queue(entries),
+                Err(e) => {
+                    let _ = error_tx.send(Action::ErrorMessage(format!(
+                        "Failed to list entries for {}: {}",
+                        filter, e
+                    )));
+                }
+            }
+        });
+    }
+
+    /// Check a write to `dn` against the active tab's protected entries.
+    /// Returns `true` when it may go ahead; otherwise it is refused (strict
+    /// mode) or `retry` waits behind a typed confirmation.
+    fn guard_write(&mut self, dn: &str, retry: Action) -> bool {
+        let Some(tab) = self.active_tab() else {
+            return true;
+        };
+        let Some(pattern) = tab.protected.check(dn).map(|p| p.to_string()) else {
+            return true;
+        };
+        if tab.protected.is_strict() {
+            self.push_error(format!(
+                "{} is protected by '{}'; strict mode refuses writes",
+                dn, pattern
+            ));
+            return false;
+        }
+        // A confirmation covers the one write it was given for
+        if self
+            .protected_write_allowed
+            .take_if(|allowed| *allowed == loom_core::dn::normalize(dn))
+            .is_some()
+        {
+            return true;
+        }
+        self.protected_write_dialog
+            .show(dn.to_string(), pattern, retry);
+        false
+    }
+
+    /// Go to a place picked in the quick switcher. Targets can go stale
+    /// while the switcher is open, so each one is checked first.
+    /// Open a bookmark on its profile's tab, connecting first when none
+    /// is open. A DN is read before it is revealed, so one that is gone
+    /// is reported rather than leaving the tree and details untouched.
+    fn open_bookmark(&mut self, bookmark: Bookmark) {
+        let tab_id = self
+            .tabs
+            .iter()
+            .find(|t| t.profile == bookmark.profile)
+            .map(|t| t.id);
+        let Some(id) = tab_id else {
+            let profile = self
+                .config
+                .connections
+                .iter()
+                .find(|p| p.name == bookmark.profile)
+                .cloned()
+                .or_else(|| Some(example_profile()).filter(|p| p.name == bookmark.profile));
+            match profile {
+                Some(profile) => {
+                    self.status_bar
+                        .set_message(format!("Connecting to {}...", profile.name));
+                    self.pending_bookmark = Some(bookmark);
+                    self.connect_profile(profile);
+                }
+                None => self.push_error(format!(
+                    "Bookmark '{}': profile '{}' no longer exists",
+                    bookmark.name, bookmark.profile
+                )),
+            }
+            return;
+        };
+        if self.active_tab_id != Some(id) {
+            self.switch_to_tab(id);
+        }
+        self.active_layout = ActiveLayout::Browser;
+        self.layout_bar.active = ActiveLayout::Browser;
+        self.focus.set_layout(ActiveLayout::Browser);
+        match bookmark.target {
+            BookmarkTarget::Dn(dn) => {
+                let missing = Action::ErrorMessage(format!(
+                    "Bookmark '{}': {} no longer exists",
+                    bookmark.name, dn
+                ));
+                let reveal = Action::RevealInTree(id, dn.clone());
+                self.spawn_read_entry(id, dn, missing, Some(reveal));
+            }
+            BookmarkTarget::Search(search) => {
+                let _ = self.action_tx.send(Action::Search {
+                    attrs: search.attrs(),
+                    base: search.base,
+                    scope: search.scope.into(),
+                    filter: search.filter,
+                });
+            }
+        }
+    }
+
+    /// Open a bookmark that waited for its profile's tab to open.
+    fn resume_pending_bookmark(&mut self, profile: &str) {
+        if self
+            .pending_bookmark
+            .as_ref()
+            .is_some_and(|b| b.profile == profile)
+        {
+            if let Some(bookmark) = self.pending_bookmark.take() {
+                let _ = self.action_tx.send(Action::OpenBookmark(bookmark));
+            }
+        }
+    }
+
+    /// Select `dn` in a tab's tree with its ancestors opened, and focus the
+    /// tree if `focus_tree`. A container whose children were never listed
+    /// is loaded first, and the reveal resumes when they arrive.
+    fn reveal_in_tree(&mut self, conn_id: ConnectionId, dn: String, focus_tree: bool) {
+        self.pending_reveal = None;
+        if self.active_tab_id != Some(conn_id) {
+            return;
+        }
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        match tab.directory_tree.reach(&dn) {
+            Reach::Listed(path) => {
+                self.select_tree_path(path);
+                if focus_tree {
+                    self.focus.set(FocusTarget::TreePanel);
+                }
+            }
+            Reach::Unloaded { path, parent_dn } => {
+                let loading = tab
+                    .directory_tree
+                    .find_node(&parent_dn)
+                    .is_some_and(|n| matches!(n.load, LoadState::Loading));
+                self.select_tree_path(path);
+                self.pending_reveal = Some(PendingReveal {
+                    conn_id,
+                    parent_dn: parent_dn.clone(),
+                    dn,
+                    focus_tree,
+                });
+                if !loading {
+                    self.spawn_load_children(conn_id, parent_dn);
+                }
+            }
+            Reach::Unlisted(path) => {
+                let under = path
+                    .last()
+                    .map_or(tab.directory_tree.root_dn.as_str(), |p| p.as_str());
+                let msg = format!(
+                    "{} is not listed under {}; it may be on another page",
+                    loom_core::dn::rdn_display_name(&dn),
+                    loom_core::dn::rdn_display_name(under)
+                );
+                self.select_tree_path(path);
+                self.status_bar.set_message(msg);
+            }
+        }
+    }
+
+    /// Select the last DN of `path` in the tree, opening the nodes above it.
+    fn select_tree_path(&mut self, path: Vec<String>) {
+        for depth in 1..path.len() {
+            self.tree_panel.tree_state.open(path[..depth].to_vec());
+        }
+        if !path.is_empty() {
+            self.tree_panel.tree_state.select(path);
+        }
+    }
+
+    fn jump_to(&mut self, target: JumpTarget) {
+        let has_tab = |id| self.tabs.iter().any(|t| t.id == id);
+        match target {
+            JumpTarget::Layout(ActiveLayout::Schema) => {
+                let _ = self.action_tx.send(Action::ShowSchemaViewer);
+            }
+            JumpTarget::Layout(layout) => {
+                let _ = self.action_tx.send(Action::SwitchLayout(layout));
+            }
+            JumpTarget::Tab(id) if has_tab(id) => {
+                let _ = self.action_tx.send(Action::SwitchTab(id));
+            }
+            JumpTarget::Entry(id, dn) if has_tab(id) => {
+                let _ = self.action_tx.send(Action::SwitchTab(id));
+                let _ = self.action_tx.send(Action::TreeSelect(dn));
+                let _ = self
+                    .action_tx
+                    .send(Action::FocusPanel(FocusTarget::DetailPanel));
+            }
+            JumpTarget::Tab(_) | JumpTarget::Entry(..) => {
+                self.status_bar
+                    .set_message("That connection tab is no longer open".to_string());
+            }
+            JumpTarget::SearchResults if self.search_dialog.has_results() => {
+                let _ = self.action_tx.send(Action::SearchFocusInput);
+            }
+            JumpTarget::SearchResults => {
+                self.status_bar
+                    .set_message("Those search results are gone".to_string());
+            }
+            JumpTarget::LogPanel => {
+                if !self.log_panel.visible {
+                    self.log_panel.toggle();
+                }
+            }
+        }
+    }
+
     fn switch_to_tab(&mut self, id: ConnectionId) {
         self.active_tab_id = Some(id);
         self.tab_bar.set_active(id);
         self.detail_panel.clear();
         self.tree_panel.tree_state = tui_tree_widget::TreeState::default();
 
+        self.sync_status_context();
         if let Some(tab) = self.tabs.iter().find(|t| t.id == id) {
-            self.status_bar.set_connected(&tab.host, &tab.server_type);
             if let Some(schema) = &tab.schema {
                 self.command_panel
                     .set_attribute_names(schema.all_attribute_names());
@@ -2592,45 +8157,67 @@ impl App {
         }
     }
 
+    /// Point the Schema layout at the active connection's schema.
+    fn sync_schema_viewer(&mut self) {
+        if let Some(tab) = self.tabs.iter().find(|t| Some(t.id) == self.active_tab_id) {
+            self.schema_viewer.sync(tab.id, tab.schema.as_ref());
+        }
+    }
+
+    /// What the layout bar shows for each connection tab, in tab order.
+    fn tab_states(&self) -> Vec<TabState> {
+        // Edits open in a dialog have not been written yet
+        let editing = self.attribute_editor.visible
+            || self.create_entry_dialog.visible
+            || self.bulk_update_dialog.visible
+            || self.batch_modify_dialog.visible
+            || self.migrate_dialog.visible;
+        self.tab_bar
+            .tabs
+            .iter()
+            .filter_map(|entry| {
+                let tab = self.tabs.iter().find(|t| t.id == entry.id)?;
+                let results = (self.results_conn == Some(tab.id)
+                    && self.search_dialog.has_results())
+                .then_some(self.search_dialog.results.len());
+                Some(TabState {
+                    id: tab.id,
+                    label: entry.label.clone(),
+                    entries: tab.directory_tree.loaded_count(),
+                    results,
+                    busy: tab.ops.is_busy(),
+                    dirty: editing && self.active_tab_id == Some(tab.id),
+                })
+            })
+            .collect()
+    }
+
     fn render(&mut self, frame: &mut ratatui::Frame) {
         let full = frame.area();
 
-        // Vertical: layout_bar (1) | content area | status bar (1)
-        let outer = Layout::vertical([
-            Constraint::Length(1), // layout bar (includes tabs in Browser mode)
-            Constraint::Min(3),    // content
-            Constraint::Length(1), // status bar
-        ])
-        .split(full);
-
-        let layout_bar_area = outer[0];
-        let content_area = outer[1];
-        let status_area = outer[2];
+        // Vertical: layout_bar (1) | left pane, right pane | status bar (1)
+        let ratios = match self.active_layout {
+            ActiveLayout::Browser => self.config.layout.browser,
+            ActiveLayout::Profiles => self.config.layout.profiles,
+            ActiveLayout::Schema => self.config.layout.schema,
+        };
+        let screen = ScreenLayout::compute(full, ratios);
+        let layout_bar_area = screen.layout_bar;
+        let status_area = screen.status;
 
         self.layout_bar_area = Some(layout_bar_area);
 
         // Render layout bar (includes tab bar in Browser mode)
-        self.layout_bar.render(
-            frame,
-            layout_bar_area,
-            &self.tab_bar.tabs,
-            self.tab_bar.active_tab,
-        );
+        let tab_states = self.tab_states();
+        self.layout_bar
+            .render(frame, layout_bar_area, &tab_states, self.tab_bar.active_tab);
 
         match self.active_layout {
             ActiveLayout::Browser => {
                 self.tab_area = Some(layout_bar_area);
 
-                // Horizontal: tree | detail (full content area, no command panel)
-                let tp = self.tree_split_pct;
-                let horizontal = Layout::horizontal([
-                    Constraint::Percentage(tp),
-                    Constraint::Percentage(100 - tp),
-                ])
-                .split(content_area);
-
-                let tree_area = horizontal[0];
-                let detail_area = horizontal[1];
+                let tree_area = screen.left;
+                let detail_area = screen.right;
 
                 // Store areas for mouse hit-testing
                 self.tree_area = Some(tree_area);
@@ -2639,7 +8226,9 @@ impl App {
                 // Render tree panel
                 let tree_focused = self.focus.is_focused(FocusTarget::TreePanel);
                 if let Some(tab) = self.active_tab() {
-                    let items = TreePanel::build_tree_items(&tab.directory_tree.root);
+                    let items = self
+                        .tree_panel
+                        .build_tree_items(&tab.directory_tree.root, &tab.protected);
                     self.tree_panel.render_with_items(
                         frame,
                         tree_area,
@@ -2659,16 +8248,8 @@ impl App {
                 );
             }
             ActiveLayout::Profiles => {
-                // Horizontal: profiles tree | connection form (full content area)
-                let tp = self.tree_split_pct;
-                let horizontal = Layout::horizontal([
-                    Constraint::Percentage(tp),
-                    Constraint::Percentage(100 - tp),
-                ])
-                .split(content_area);
-
-                let conn_tree_area = horizontal[0];
-                let conn_form_area = horizontal[1];
+                let conn_tree_area = screen.left;
+                let conn_form_area = screen.right;
 
                 self.conn_tree_area = Some(conn_tree_area);
                 self.conn_form_area = Some(conn_form_area);
@@ -2680,6 +8261,8 @@ impl App {
                     .map(|t| ActiveConnInfo {
                         id: t.id,
                         label: t.label.clone(),
+                        health: t.health.get(),
+                        read_only: t.writes_locked(),
                     })
                     .collect();
 
@@ -2687,9 +8270,11 @@ impl App {
                 let tree_focused = self.focus.is_focused(FocusTarget::ConnectionsTree);
                 let mut conn_profiles = self.config.connections.clone();
                 conn_profiles.push(example_profile());
-                let items = self
-                    .connections_tree
-                    .build_tree_items(&conn_profiles, &active_conns);
+                let items = self.connections_tree.build_tree_items(
+                    &conn_profiles,
+                    &active_conns,
+                    &self.connect_attempts,
+                );
                 self.connections_tree.render_with_items(
                     frame,
                     conn_tree_area,
@@ -2704,14 +8289,41 @@ impl App {
                     self.focus.is_focused(FocusTarget::ConnectionForm),
                 );
             }
+            ActiveLayout::Schema => {
+                self.schema_list_area = Some(screen.left);
+                self.schema_definition_area = Some(screen.right);
+
+                self.sync_schema_viewer();
+                self.schema_viewer.render_list(
+                    frame,
+                    screen.left,
+                    self.focus.is_focused(FocusTarget::SchemaList),
+                );
+                self.schema_viewer.render_definition(
+                    frame,
+                    screen.right,
+                    self.focus.is_focused(FocusTarget::SchemaDefinition),
+                );
+            }
         }
 
         // Status bar
         self.status_bar.render(frame, status_area, false);
 
         // Render popups on top (order matters: last rendered is on top)
-        if self.confirm_dialog.visible {
-            self.confirm_dialog.render(frame, full);
+        // The setup wizard opens the other dialogs over itself
+        if self.setup_wizard.visible {
+            self.setup_wizard.render(frame, full);
+        }
+        // The export summary asks for confirmation before opening some files
+        if self.export_summary_popup.visible {
+            self.export_summary_popup.render(frame, full);
+        }
+        if self.protected_write_dialog.visible {
+            self.protected_write_dialog.render(frame, full);
+        }
+        if self.write_override_dialog.visible {
+            self.write_override_dialog.render(frame, full);
         }
         if self.cert_trust_dialog.visible {
             self.cert_trust_dialog.render(frame, full);
@@ -2739,15 +8351,15 @@ impl App {
             frame.render_widget(Clear, popup_area);
 
             let title = format!(
-                " Search: {} ({} results) ",
+                " Search: {} ({}) ",
                 self.search_dialog.filter,
-                self.search_dialog.results.len()
+                self.search_dialog.count_line()
             );
             let block = Block::default()
                 .title(title)
                 .borders(Borders::ALL)
-                .border_style(self.theme.popup_border)
-                .title_style(self.theme.popup_title);
+                .border_style(self.theme.popup_border())
+                .title_style(self.theme.popup_title());
             let inner = block.inner(popup_area);
             frame.render_widget(block, popup_area);
 
@@ -2774,12 +8386,16 @@ impl App {
             // Separator line
             let sep = Line::from(Span::styled(
                 "\u{2500}".repeat(layout[1].width as usize),
-                self.theme.popup_border,
+                self.theme.popup_border(),
             ));
             frame.render_widget(Paragraph::new(sep), layout[1]);
 
             self.command_panel.render_input_only(frame, layout[2]);
         }
+        // Also asks about deleting selected search results
+        if self.confirm_dialog.visible {
+            self.confirm_dialog.render(frame, full);
+        }
         if self.attribute_editor.visible {
             self.attribute_editor.render(frame, full);
         }
@@ -2789,39 +8405,129 @@ impl App {
         if self.export_dialog.visible {
             self.export_dialog.render(frame, full);
         }
+        if self.columns_dialog.visible {
+            self.columns_dialog.render(frame, full);
+        }
+        if self.password_dialog.visible {
+            self.password_dialog.render(frame, full);
+        }
         if self.bulk_update_dialog.visible {
             self.bulk_update_dialog.render(frame, full);
         }
+        if self.batch_modify_dialog.visible {
+            self.batch_modify_dialog.render(frame, full);
+        }
+        if self.batch_summary_popup.visible {
+            self.batch_summary_popup.render(frame, full);
+        }
+        if self.migrate_dialog.visible {
+            self.migrate_dialog.render(frame, full);
+        }
         if self.profile_export_dialog.visible {
             self.profile_export_dialog.render(frame, full);
         }
         if self.profile_import_dialog.visible {
             self.profile_import_dialog.render(frame, full);
         }
+        if self.search_form_dialog.visible {
+            self.search_form_dialog.render(frame, full);
+        }
+        if self.snapshot_dialog.visible {
+            self.snapshot_dialog.render(frame, full);
+        }
+        if self.subtree_compare_dialog.visible {
+            self.subtree_compare_dialog.render(frame, full);
+        }
+        if self.apply_ldif_dialog.visible {
+            self.apply_ldif_dialog.render(frame, full);
+        }
+        if self.delete_dialog.visible {
+            self.delete_dialog.render(frame, full);
+        }
+        if self.rename_dialog.visible {
+            self.rename_dialog.render(frame, full);
+        }
+        if self.copy_subtree_dialog.visible {
+            self.copy_subtree_dialog.render(frame, full);
+        }
+        if self.activity_popup.visible {
+            self.activity_popup.render(frame, full);
+        }
         if self.create_entry_dialog.visible {
             self.create_entry_dialog.render(frame, full);
         }
-        if self.schema_viewer.visible {
-            self.schema_viewer.render(frame, full);
+        if self.subtree_diff_view.visible {
+            self.subtree_diff_view.render(frame, full);
+        }
+        if self.pending_changes_popup.visible {
+            self.pending_changes_popup.render(frame, full);
+        }
+        if self.apply_results_view.visible {
+            self.apply_results_view.render(frame, full);
+        }
+        if self.compare_view.visible {
+            self.compare_view.render(frame, full);
         }
         if self.help_popup.visible {
             self.help_popup.render(frame, full);
         }
-        if self.about_popup.visible {
-            self.about_popup.render(frame, full);
+        if self.about_popup.visible {
+            self.about_popup.render(frame, full);
+        }
+        if self.error_details_popup.visible {
+            self.error_details_popup.render(frame, full);
+        }
+        if self.value_viewer.visible {
+            self.value_viewer.render(frame, full);
+        }
+        if self.membership_popup.visible {
+            self.membership_popup.render(frame, full);
+        }
+        if self.messages_popup.visible {
+            self.messages_popup.render(frame, full);
+        }
+        if self.doctor_popup.visible {
+            self.doctor_popup.render(frame, full);
+        }
+        if self.server_info_popup.visible {
+            self.server_info_popup.render(frame, full);
         }
         if self.log_panel.visible {
             self.log_panel.render(frame, full);
         }
+        if self.log_viewer.visible {
+            self.log_viewer.render(frame, full);
+        }
         if self.context_menu.visible {
             self.context_menu.render(frame, full);
         }
+        if self.quick_switcher.visible {
+            self.quick_switcher.render(frame, full);
+        }
+        if self.bookmarks_popup.visible {
+            self.bookmarks_popup.render(frame, full);
+        }
     }
 }
 
 /// Resolve password from the connection profile's credential method.
 /// Returns empty string for Prompt method when LOOM_PASSWORD is not set,
 /// which signals the caller to show an interactive credential prompt.
+/// The audit log `general` asks for, if any.
+fn audit_file(general: &GeneralConfig) -> Option<Arc<AuditFile>> {
+    general.audit_log.as_deref().map(|path| {
+        let rotation = Rotation {
+            max_bytes: general.audit_max_mb.saturating_mul(1024 * 1024),
+            keep: general.audit_keep,
+        };
+        Arc::new(AuditFile::new(
+            expand_path(path),
+            general.audit_format,
+            rotation,
+        ))
+    })
+}
+
 fn resolve_password(profile: &ConnectionProfile, vault: &Option<Vault>) -> anyhow::Result<String> {
     match profile.credential_method {
         CredentialMethod::Prompt => Ok(std::env::var("LOOM_PASSWORD").unwrap_or_default()),
@@ -2844,26 +8550,6 @@ fn resolve_password(profile: &ConnectionProfile, vault: &Option<Vault>) -> anyho
     }
 }
 
-/// Check if an error is an LDAP authentication/bind failure (rc=49 etc.).
-fn is_auth_error(err: &anyhow::Error) -> bool {
-    let msg = err.to_string().to_lowercase();
-    msg.contains("bind failed")
-        || msg.contains("rc=49")
-        || msg.contains("invalid credentials")
-        || msg.contains("password must be provided")
-}
-
-/// Extract CertificateInfo if the error is a certificate trust error.
-fn extract_cert_trust_error(err: &anyhow::Error) -> Option<loom_core::tls::CertificateInfo> {
-    err.downcast_ref::<CoreError>().and_then(|ce| {
-        if let CoreError::CertificateNotTrusted(info) = ce {
-            Some(info.as_ref().clone())
-        } else {
-            None
-        }
-    })
-}
-
 /// Return the built-in example directory profile.
 fn example_profile() -> ConnectionProfile {
     ConnectionProfile {
@@ -2882,5 +8568,2124 @@ fn example_profile() -> ConnectionProfile {
         read_only: false,
         offline: true,
         labels: vec![],
+        hooks: Default::default(),
+        protected_dns: Vec::new(),
+        auth: Default::default(),
+        tls_options: Default::default(),
+        protection: Default::default(),
+        result_columns: Vec::new(),
+    }
+}
+
+/// Read `dn` as it stands before a write, for the step that undoes it.
+/// When it cannot be read the write still goes ahead, without one.
+async fn read_before_image(conn: &mut LdapConnection, dn: &str) -> Option<LdapEntry> {
+    match conn.search_entry(dn).await {
+        Ok(entry) => entry,
+        Err(e) => {
+            warn!(
+                "Cannot read {} before writing; it cannot be undone: {}",
+                dn, e
+            );
+            None
+        }
+    }
+}
+
+/// The status line for a finished export, noting any timestamp
+/// formatting; values that did not parse become a warning in `report`.
+fn export_message(report: &mut ExportReport, timestamps: Option<FormatReport>) -> String {
+    let mut message = report.summary();
+    if let Some(timestamps) = timestamps {
+        message.push_str(&timestamps.summary());
+        if timestamps.unparseable > 0 {
+            report.warnings.push(format!(
+                "{} timestamp values did not parse and were kept as-is",
+                timestamps.unparseable
+            ));
+        }
+    }
+    message
+}
+
+/// The start of the question after a delete was stopped because `dn`
+/// changed: one line per changed attribute.
+fn changed_since_loaded(dn: &str, changes: &[AttributeChange]) -> String {
+    let mut msg = format!(
+        "{} was modified since it was loaded; it has been refreshed.\n",
+        dn
+    );
+    for change in changes {
+        msg.push_str(&format!("  {}\n", change));
+    }
+    if changes.is_empty() {
+        msg.push_str("  (only operational attributes changed)\n");
+    }
+    msg
+}
+
+/// The ids leading from the tree's root to `dn`, for selecting it; `None`
+/// when it is not listed.
+fn tree_path(tree: &DirectoryTree, dn: &str) -> Option<Vec<String>> {
+    let root = loom_core::dn::normalize(&tree.root_dn);
+    let mut path = Vec::new();
+    let mut current = dn;
+    loop {
+        path.push(tree.find_node(current)?.dn.clone());
+        if loom_core::dn::normalize(current) == root {
+            break;
+        }
+        current = loom_core::dn::parent_dn(current)?;
+    }
+    path.reverse();
+    Some(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::KeyChords;
+    use crate::logging::LogWriter;
+    use loom_core::delete::SubtreeDeletion;
+    use loom_core::export::subtree::SubtreeScope;
+    use loom_core::hooks::{HookCommand, ProfileHooks};
+    use loom_core::resolve::ResolveFuture;
+    use loom_core::subtree_diff::SubtreeDiff;
+    use loom_core::time::TimeDisplay;
+
+    /// Resolver whose lookups outlive the test.
+    struct SleepingResolver;
+
+    impl Resolver for SleepingResolver {
+        fn resolve<'a>(&'a self, _host: &'a str, _port: u16) -> ResolveFuture<'a> {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_secs(60)).await;
+                Ok(Vec::new())
+            })
+        }
+    }
+
+    fn press(app: &mut App, code: KeyCode) {
+        app.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    async fn drain(app: &mut App) {
+        while let Ok(action) = app.action_rx.try_recv() {
+            app.process_action(action).await;
+        }
+    }
+
+    /// A config past the first-run wizard.
+    fn test_config() -> AppConfig {
+        AppConfig {
+            first_launch: false,
+            ..Default::default()
+        }
+    }
+
+    /// An app past the first-run wizard with the offline directory open.
+    async fn offline_app() -> (App, ConnectionId) {
+        offline_app_with(test_config()).await
+    }
+
+    /// [`offline_app`] with `config` in place of the default.
+    async fn offline_app_with(config: AppConfig) -> (App, ConnectionId) {
+        let mut app = App::new(config, None);
+        app.connect_offline();
+        drain(&mut app).await;
+        let id = app.active_tab_id.unwrap();
+        (app, id)
+    }
+
+    /// Process actions until a background export has finished.
+    async fn finish_export(app: &mut App) {
+        while app.export_task.is_some() {
+            let action = tokio::time::timeout(Duration::from_secs(10), app.action_rx.recv())
+                .await
+                .expect("export finished")
+                .unwrap();
+            app.process_action(action).await;
+        }
+        drain(app).await;
+    }
+
+    #[tokio::test]
+    async fn test_keys_processed_while_resolving() {
+        let mut app = App::new(test_config(), None);
+        app.resolver = Arc::new(SleepingResolver);
+        let profile = ConnectionProfile {
+            name: "prod".to_string(),
+            host: "ldap.prod.example".to_string(),
+            offline: false,
+            ..example_profile()
+        };
+
+        let started = std::time::Instant::now();
+        app.process_action(Action::ConnectAdHoc(profile.clone(), String::new()))
+            .await;
+        // A second request for the same profile coalesces into the first
+        app.process_action(Action::ConnectAdHoc(profile, String::new()))
+            .await;
+        assert_eq!(app.connect_attempts.len(), 1);
+
+        // The help popup opens and closes while the lookup is still sleeping
+        press(&mut app, KeyCode::F(5));
+        drain(&mut app).await;
+        assert!(app.help_popup.visible);
+        press(&mut app, KeyCode::Esc);
+        drain(&mut app).await;
+        assert!(!app.help_popup.visible);
+        assert_eq!(app.connect_attempts.len(), 1);
+
+        // With no popup open, Esc abandons the attempt
+        press(&mut app, KeyCode::Esc);
+        drain(&mut app).await;
+        assert!(app.connect_attempts.is_empty());
+        assert!(started.elapsed() < Duration::from_secs(5));
+        assert!(app.tabs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_result_ingestion_is_chunked() {
+        const TOTAL: usize = 100_000;
+        let config = test_config();
+        let budget = config.general.ingest_entries_per_tick;
+        let mut app = App::new(config, None);
+        app.active_tab_id = Some(7);
+        let generation = app.result_ingest.start(7);
+
+        // The worker streams pages of 1,000 through the action channel
+        let entry = |i: usize| {
+            LdapEntry::new(
+                format!("uid=user{:06},ou=People,dc=example,dc=com", i),
+                [("uid".to_string(), vec![format!("user{:06}", i)])].into(),
+            )
+        };
+        for start in (0..TOTAL).step_by(1000) {
+            let page = (start..start + 1000).map(entry).collect();
+            let _ = app
+                .action_tx
+                .send(Action::SearchResults(7, generation, page));
+        }
+        let _ = app.action_tx.send(Action::SearchComplete(7, generation));
+        drain(&mut app).await;
+        assert_eq!(app.result_ingest.backlog(), TOTAL);
+        assert!(app.search_dialog.results.is_empty());
+
+        let mut ticks = 0;
+        while app.result_ingest.conn_id().is_some() {
+            // A key press between frames is handled before the next batch
+            press(&mut app, KeyCode::Down);
+            let tick = std::time::Instant::now();
+            let taken = app.ingest_results();
+            assert!(taken <= budget);
+            assert!(tick.elapsed() < Duration::from_millis(250));
+            ticks += 1;
+            if app.result_ingest.conn_id().is_some() {
+                assert!(app
+                    .status_bar
+                    .latest_text()
+                    .is_some_and(|m| m.contains("queued")));
+            }
+        }
+        assert_eq!(ticks, TOTAL.div_ceil(budget));
+        assert!(app.search_dialog.visible);
+
+        let results = &app.search_dialog.results;
+        assert_eq!(results.len(), TOTAL);
+        assert!(results.iter().enumerate().all(|(i, e)| e.dn == entry(i).dn));
+        assert_eq!(app.status_bar.latest_text(), Some("Found 100000 entries"));
+    }
+
+    #[tokio::test]
+    async fn test_empty_states_for_search_and_tree() {
+        let (mut app, id) = offline_app().await;
+        let base_dn = OfflineDirectory::load_embedded().base_dn().to_string();
+
+        // A search that matches nothing says so, with the filter and base
+        app.process_action(Action::SearchExecute("(cn=nobody)".to_string()))
+            .await;
+        drain(&mut app).await;
+        app.ingest_results();
+        assert_eq!(app.search_dialog.status, SearchStatus::Done);
+        let message: String = app
+            .search_dialog
+            .empty_message()
+            .spans
+            .iter()
+            .map(|s| s.content.as_ref())
+            .collect();
+        assert!(message.starts_with(&format!("No entries matched (cn=nobody) under {}", base_dn)));
+        assert!(message.contains("F9 to close"));
+
+        // A leaf listed once is an empty container, not an unloaded one
+        let leaf = {
+            let tab = app.active_tab().unwrap();
+            let first = &tab.directory_tree.root.children.as_ref().unwrap()[0];
+            first.dn.clone()
+        };
+        let tab = app.tabs.iter_mut().find(|t| t.id == id).unwrap();
+        tab.directory_tree.insert_children(&leaf, vec![]);
+        let label = |app: &App| {
+            let tab = app.active_tab().unwrap();
+            let node = tab.directory_tree.find_node(&leaf).unwrap().clone();
+            let items = app.tree_panel.build_tree_items(&node, &tab.protected);
+            items.last().map(|i| format!("{:?}", i))
+        };
+        assert!(label(&app).unwrap().contains("(empty)"));
+
+        // A failed refresh shows the error; the retry clears it
+        app.process_action(Action::TreeLoadFailed(id, leaf.clone(), "Busy".to_string()))
+            .await;
+        assert!(label(&app).unwrap().contains("(failed: Busy)"));
+        app.process_action(Action::TreeExpand(leaf.clone())).await;
+        drain(&mut app).await;
+        let tab = app.active_tab().unwrap();
+        assert_eq!(
+            tab.directory_tree.find_node(&leaf).unwrap().load,
+            LoadState::Loaded
+        );
+    }
+
+    #[tokio::test]
+    async fn test_expanding_reuses_cached_children() {
+        let (mut app, _) = offline_app().await;
+        let offline = OfflineDirectory::load_embedded();
+        let ou = {
+            let tab = app.active_tab().unwrap();
+            let top = tab.directory_tree.root.children.as_ref().unwrap();
+            let container = top.iter().find(|n| !offline.children(&n.dn).is_empty());
+            container.unwrap().dn.clone()
+        };
+        let expected: Vec<String> = offline.children(&ou).into_iter().map(|n| n.dn).collect();
+        let child_dns = |app: &App| -> Vec<String> {
+            let tab = app.active_tab().unwrap();
+            let node = tab.directory_tree.find_node(&ou).unwrap();
+            node.children
+                .iter()
+                .flatten()
+                .map(|c| c.dn.clone())
+                .collect()
+        };
+        // Runs the queued actions, counting the listings the backend served
+        async fn listings(app: &mut App) -> usize {
+            let mut served = 0;
+            while let Ok(action) = app.action_rx.try_recv() {
+                served += usize::from(matches!(action, Action::TreePageLoaded(..)));
+                app.process_action(action).await;
+            }
+            served
+        }
+
+        // The first expansion lists the children one level down
+        app.process_action(Action::TreeExpand(ou.clone())).await;
+        assert_eq!(listings(&mut app).await, 1);
+        assert_eq!(child_dns(&app), expected);
+
+        // Expanding again shows the cached children without a new listing
+        app.process_action(Action::TreeExpand(ou.clone())).await;
+        assert_eq!(listings(&mut app).await, 0);
+        assert_eq!(child_dns(&app), expected);
+
+        // R on the node lists them again
+        let path = vec![
+            app.active_tab().unwrap().directory_tree.root_dn.clone(),
+            ou.clone(),
+        ];
+        app.tree_panel.tree_state.select(path);
+        let action = app
+            .tree_panel
+            .handle_key_event(KeyEvent::new(KeyCode::Char('R'), KeyModifiers::SHIFT));
+        assert!(matches!(&action, Action::LoadChildren(dn) if *dn == ou));
+        app.process_action(action).await;
+        assert_eq!(listings(&mut app).await, 1);
+        assert_eq!(child_dns(&app), expected);
+
+        // While a listing is in flight the children stay, followed by a
+        // status row for the spinner
+        app.active_tab_mut()
+            .unwrap()
+            .directory_tree
+            .mark_loading(&ou);
+        assert_eq!(child_dns(&app), expected);
+        let tab = app.active_tab().unwrap();
+        let node = tab.directory_tree.find_node(&ou).unwrap().clone();
+        let items = app.tree_panel.build_tree_items(&node, &tab.protected);
+        let ids: Vec<&String> = items.iter().map(|item| item.identifier()).collect();
+        let status = loom_core::tree::status_marker_id(&ou);
+        assert_eq!(ids.len(), expected.len() + 1);
+        assert_eq!(ids.last(), Some(&&status));
+    }
+
+    #[tokio::test]
+    async fn test_tree_jump_by_letter() {
+        let (mut app, _) = offline_app().await;
+        app.process_action(Action::FocusPanel(FocusTarget::TreePanel))
+            .await;
+        let children: Vec<(String, String)> = app
+            .active_tab()
+            .unwrap()
+            .directory_tree
+            .root
+            .children
+            .as_ref()
+            .unwrap()
+            .iter()
+            .map(|c| (c.dn.clone(), c.display_name.clone()))
+            .collect();
+        let (target_dn, target_name) = children.last().unwrap().clone();
+
+        // Typing the name quickly narrows to it, whatever shares its letter
+        press(&mut app, KeyCode::Char('\''));
+        assert!(app.tree_panel.is_jumping());
+        for c in target_name.to_lowercase().chars() {
+            press(&mut app, KeyCode::Char(c));
+            drain(&mut app).await;
+        }
+        assert_eq!(app.tree_panel.selected_dn(), Some(&target_dn));
+        assert_eq!(
+            app.detail_panel.entry.as_ref().map(|e| e.dn.as_str()),
+            Some(target_dn.as_str())
+        );
+
+        // A prefix nothing starts with leaves the selection alone
+        press(&mut app, KeyCode::Char('\u{e9}'));
+        drain(&mut app).await;
+        assert_eq!(app.tree_panel.selected_dn(), Some(&target_dn));
+        assert!(app
+            .status_bar
+            .latest_text()
+            .is_some_and(|m| m.starts_with("No entry starting with")));
+
+        // Esc leaves jump mode; letters are commands again
+        press(&mut app, KeyCode::Esc);
+        drain(&mut app).await;
+        assert!(!app.tree_panel.is_jumping());
+    }
+
+    #[tokio::test]
+    async fn test_quick_switcher_jumps_and_handles_stale_targets() {
+        let (mut app, id) = offline_app().await;
+        let ctrl_p = KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL);
+        let type_query = |app: &mut App, query: &str| {
+            for c in query.chars() {
+                press(app, KeyCode::Char(c));
+            }
+        };
+
+        let base_dn = OfflineDirectory::load_embedded().base_dn().to_string();
+        app.process_action(Action::TreeSelect(base_dn.clone()))
+            .await;
+        drain(&mut app).await;
+        app.process_action(Action::SwitchLayout(ActiveLayout::Profiles))
+            .await;
+
+        // A recently viewed entry brings back its tab and the entry
+        app.detail_panel.clear();
+        app.handle_key_event(ctrl_p);
+        drain(&mut app).await;
+        assert!(app.quick_switcher.visible);
+        type_query(&mut app, "recent");
+        press(&mut app, KeyCode::Enter);
+        drain(&mut app).await;
+        assert!(!app.quick_switcher.visible);
+        assert_eq!(app.active_layout, ActiveLayout::Browser);
+        assert_eq!(app.active_tab_id, Some(id));
+        assert_eq!(
+            app.detail_panel.entry.as_ref().map(|e| e.dn.as_str()),
+            Some(base_dn.as_str())
+        );
+
+        // The tab closes while the switcher still lists it
+        app.handle_key_event(ctrl_p);
+        drain(&mut app).await;
+        type_query(&mut app, "tab");
+        app.process_action(Action::CloseTab(id)).await;
+        press(&mut app, KeyCode::Enter);
+        drain(&mut app).await;
+        assert!(app.tabs.is_empty());
+        assert_eq!(
+            app.status_bar.latest_text(),
+            Some("That connection tab is no longer open")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_subtree_export_streams_the_branch() {
+        let (mut app, _) = offline_app().await;
+        let it = "ou=IT,ou=Corporate,dc=contoso,dc=com";
+        app.process_action(Action::ShowSubtreeExport(it.to_string()))
+            .await;
+        assert!(app.export_dialog.visible);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("it.ldif");
+        let export = |scope, include_base| Action::ExportExecute {
+            base_dn: it.to_string(),
+            path: path.display().to_string(),
+            filter: String::new(),
+            attributes: vec!["*".to_string()],
+            timestamps: None,
+            decode: false,
+            csv: Default::default(),
+            format: None,
+            source: ExportSource::Subtree,
+            scope,
+            include_base,
+        };
+        app.process_action(export(SubtreeScope::Subtree, true))
+            .await;
+        finish_export(&mut app).await;
+        assert!(app.last_error.is_none());
+        // Unfolded, for the long DNs
+        let ldif = std::fs::read_to_string(&path).unwrap().replace("\n ", "");
+        let dns: Vec<&str> = ldif
+            .lines()
+            .filter_map(|l| l.strip_prefix("dn: "))
+            .collect();
+        assert!(dns.contains(&it));
+        assert!(dns.contains(&"cn=Lisa Park,ou=Development,ou=IT,ou=Corporate,dc=contoso,dc=com"));
+        assert!(dns
+            .iter()
+            .all(|dn| SubtreeScope::Subtree.includes(it, dn, true)));
+        assert!(app
+            .status_bar
+            .latest_text()
+            .unwrap()
+            .starts_with(&format!("Exported {} entries", dns.len())));
+
+        // Without the base, the rest of the branch is still written
+        app.export_summary_popup.hide();
+        app.process_action(export(SubtreeScope::Subtree, false))
+            .await;
+        finish_export(&mut app).await;
+        let ldif = std::fs::read_to_string(&path).unwrap().replace("\n ", "");
+        assert!(!ldif.contains(&format!("dn: {}\n", it)));
+        assert_eq!(ldif.matches("dn: ").count(), dns.len() - 1);
+    }
+
+    #[tokio::test]
+    async fn test_export_summary_and_retry() {
+        let (mut app, _) = offline_app().await;
+        let base_dn = OfflineDirectory::load_embedded().base_dn().to_string();
+        let path = std::env::temp_dir().join(format!("loom-export-{}.ldif", std::process::id()));
+        let export = |path: String| Action::ExportExecute {
+            base_dn: base_dn.clone(),
+            path,
+            filter: "(objectClass=*)".to_string(),
+            attributes: vec!["*".to_string()],
+            timestamps: None,
+            decode: false,
+            csv: Default::default(),
+            format: None,
+            source: Default::default(),
+            scope: Default::default(),
+            include_base: true,
+        };
+
+        app.process_action(export(path.display().to_string())).await;
+        finish_export(&mut app).await;
+        let _ = std::fs::remove_file(&path);
+        assert!(app.export_summary_popup.visible);
+        assert!(!app.export_dialog.visible);
+        press(&mut app, KeyCode::Esc);
+        drain(&mut app).await;
+        assert!(!app.export_summary_popup.visible);
+
+        // A failed export can be retried from the dialog, filled in
+        app.process_action(export("people.unknown".to_string()))
+            .await;
+        drain(&mut app).await;
+        assert!(app.export_summary_popup.visible);
+        press(&mut app, KeyCode::Char('r'));
+        drain(&mut app).await;
+        assert!(!app.export_summary_popup.visible);
+        assert!(app.export_dialog.visible);
+    }
+
+    #[tokio::test]
+    async fn test_copy_entry_as_ldif() {
+        let (mut app, _) = offline_app().await;
+        let base_dn = OfflineDirectory::load_embedded().base_dn().to_string();
+        let groups = format!("ou=Groups,{}", base_dn);
+
+        app.tree_panel
+            .tree_state
+            .select(vec![base_dn.clone(), groups.clone()]);
+        press(&mut app, KeyCode::Char('Y'));
+        let Ok(Action::CopyEntryLdif(dn)) = app.action_rx.try_recv() else {
+            panic!("expected an LDIF copy");
+        };
+        app.process_action(Action::CopyEntryLdif(dn)).await;
+        match app.action_rx.try_recv() {
+            Ok(Action::CopyToClipboard(text)) => {
+                assert!(text.starts_with(&format!("dn: {}\n", groups)));
+                assert!(text.contains("objectClass: organizationalUnit\n"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        app.process_action(Action::CopyEntryLdif(format!("cn=nobody,{}", base_dn)))
+            .await;
+        assert!(app
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.ends_with("is not loaded yet")));
+    }
+
+    #[tokio::test]
+    async fn test_open_bookmarks() {
+        use loom_core::bookmark::{SavedSearch, SearchScope};
+
+        let (mut app, _) = offline_app().await;
+        let profile = example_profile().name;
+        let dev = "ou=Development,ou=IT,ou=Corporate,dc=contoso,dc=com";
+        let lisa = format!("cn=Lisa Park,{}", dev);
+
+        // `m` in the tree bookmarks under the tab's profile
+        app.tree_panel
+            .tree_state
+            .select(vec!["ou=Corporate,dc=contoso,dc=com".to_string()]);
+        press(&mut app, KeyCode::Char('m'));
+        let Ok(Action::PromptBookmark(target)) = app.action_rx.try_recv() else {
+            panic!("expected a bookmark prompt");
+        };
+        app.process_action(Action::PromptBookmark(target)).await;
+        assert!(app.bookmarks_popup.visible);
+        app.bookmarks_popup.hide();
+
+        // A DN three levels down is revealed, its ancestors listed on the way
+        app.process_action(Action::OpenBookmark(Bookmark::new(
+            "lisa",
+            profile.clone(),
+            BookmarkTarget::Dn(lisa.clone()),
+        )))
+        .await;
+        drain(&mut app).await;
+        assert_eq!(
+            app.tree_panel.tree_state.selected(),
+            [
+                "ou=Corporate,dc=contoso,dc=com",
+                "ou=IT,ou=Corporate,dc=contoso,dc=com",
+                dev,
+                lisa.as_str()
+            ]
+        );
+        assert_eq!(
+            app.detail_panel.entry.as_ref().map(|e| e.dn.as_str()),
+            Some(lisa.as_str())
+        );
+        assert!(app.pending_reveal.is_none());
+
+        // A DN that is gone is reported and leaves the tree as it was
+        app.process_action(Action::OpenBookmark(Bookmark::new(
+            "gone",
+            profile.clone(),
+            BookmarkTarget::Dn(format!("cn=Nobody,{}", dev)),
+        )))
+        .await;
+        drain(&mut app).await;
+        assert_eq!(
+            app.last_error.as_deref(),
+            Some(format!("Bookmark 'gone': cn=Nobody,{} no longer exists", dev).as_str())
+        );
+        assert_eq!(app.tree_panel.selected_dn(), Some(&lisa));
+
+        // A saved search runs again
+        app.process_action(Action::OpenBookmark(Bookmark::new(
+            "developers",
+            profile,
+            BookmarkTarget::Search(SavedSearch {
+                base: dev.to_string(),
+                scope: SearchScope::One,
+                filter: "(objectClass=*)".to_string(),
+                attributes: Vec::new(),
+            }),
+        )))
+        .await;
+        drain(&mut app).await;
+        app.ingest_results();
+        assert!(app.search_dialog.visible);
+        assert!(app.search_dialog.row_count() > 0);
+
+        app.process_action(Action::OpenBookmark(Bookmark::new(
+            "elsewhere",
+            "No such profile",
+            BookmarkTarget::Dn(lisa),
+        )))
+        .await;
+        assert!(app
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.contains("profile 'No such profile' no longer exists")));
+    }
+
+    #[tokio::test]
+    async fn test_selected_results_delete_and_export() {
+        let (mut app, _) = offline_app().await;
+        app.process_action(Action::SearchExecute("(uid=*)".to_string()))
+            .await;
+        drain(&mut app).await;
+        app.ingest_results();
+        app.command_panel.soft_deactivate();
+        assert!(app.search_dialog.row_count() > 2);
+
+        // The loose example search lists the base first
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Down);
+        press(&mut app, KeyCode::Char(' '));
+        drain(&mut app).await;
+        assert_eq!(app.status_bar.latest_text(), Some("2 selected"));
+
+        // The confirmation shows over the results; the read-only example
+        // directory refuses the delete
+        press(&mut app, KeyCode::Char('d'));
+        drain(&mut app).await;
+        assert!(app.confirm_dialog.visible);
+        assert!(app
+            .confirm_dialog
+            .message
+            .starts_with("Delete these 2 entries?"));
+        press(&mut app, KeyCode::Char('y'));
+        drain(&mut app).await;
+        assert_eq!(app.last_error.as_deref(), Some("Connection is read-only"));
+        assert!(app.search_dialog.visible);
+
+        // Export opens over the results and can take just the selection
+        press(&mut app, KeyCode::Char('e'));
+        drain(&mut app).await;
+        assert!(app.export_dialog.visible);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("selected.ldif");
+        app.process_action(Action::ExportExecute {
+            base_dn: String::new(),
+            path: path.display().to_string(),
+            filter: String::new(),
+            attributes: vec!["*".to_string()],
+            timestamps: None,
+            decode: false,
+            csv: Default::default(),
+            format: None,
+            source: ExportSource::SelectedResults,
+            scope: Default::default(),
+            include_base: true,
+        })
+        .await;
+        finish_export(&mut app).await;
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(written.matches("dn: ").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_account_actions_confirm_the_exact_change() {
+        let (mut app, _) = offline_app().await;
+        let dn = "cn=Lisa Park,ou=Development,ou=IT,ou=Corporate,dc=contoso,dc=com";
+        let user = LdapEntry::new(
+            dn.to_string(),
+            [
+                ("objectClass".to_string(), vec!["user".to_string()]),
+                ("userAccountControl".to_string(), vec!["512".to_string()]),
+            ]
+            .into(),
+        );
+        app.detail_panel.set_entry(user, None);
+
+        // The example directory names no server flavor
+        app.process_action(Action::ShowAccountAction(
+            dn.to_string(),
+            AccountAction::Disable,
+        ))
+        .await;
+        assert!(app
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.starts_with("Don't know how to disable an account")));
+
+        app.active_tab_mut().unwrap().root_dse = Some(RootDse::from_attrs(
+            [("domainFunctionality".to_string(), vec!["7".to_string()])].into(),
+        ));
+        app.process_action(Action::ShowAccountAction(
+            dn.to_string(),
+            AccountAction::Unlock,
+        ))
+        .await;
+        assert_eq!(app.last_error.as_deref(), Some("The account is not locked"));
+        app.process_action(Action::ShowAccountAction(
+            dn.to_string(),
+            AccountAction::Disable,
+        ))
+        .await;
+        assert!(app.confirm_dialog.visible);
+        assert!(app
+            .confirm_dialog
+            .message
+            .ends_with("userAccountControl: 512 → 514"));
+
+        press(&mut app, KeyCode::Char('y'));
+        drain(&mut app).await;
+        assert_eq!(app.last_error.as_deref(), Some("Connection is read-only"));
+
+        // Recorded like any other modify
+        app.process_action(Action::ToggleOfflineEdits).await;
+        app.process_action(Action::ShowAccountAction(
+            dn.to_string(),
+            AccountAction::Disable,
+        ))
+        .await;
+        press(&mut app, KeyCode::Char('y'));
+        drain(&mut app).await;
+        let pending = &app.active_tab().unwrap().pending_changes;
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].summary(), "modify userAccountControl");
+    }
+
+    #[tokio::test]
+    async fn test_read_only_profile_refuses_every_write() {
+        let (mut app, _) = offline_app().await;
+        let tab = app.active_tab_mut().unwrap();
+        tab.profile_read_only = true;
+        let (id, profile) = (tab.id, tab.profile.clone());
+
+        let dn = "cn=Lisa Park,ou=Development,ou=IT,ou=Corporate,dc=contoso,dc=com";
+        let value = |attr: &str| BulkMod::DeleteValue {
+            attr: attr.to_string(),
+            value: "x".to_string(),
+        };
+        let writes = vec![
+            Action::SaveAttribute(EditResult {
+                dn: dn.to_string(),
+                op: EditOp::Add {
+                    attr: "mail".to_string(),
+                },
+                new_value: "lisa@contoso.com".to_string(),
+            }),
+            Action::DeleteAttributeValue(dn.to_string(), "mail".to_string(), "x".to_string()),
+            Action::AddMultipleValues {
+                dn: dn.to_string(),
+                attr: "mail".to_string(),
+                values: vec!["a@contoso.com".to_string()],
+            },
+            Action::ApplyLdifExecute {
+                changes: vec![Change::Delete { dn: dn.to_string() }],
+                options: ApplyOptions::default(),
+            },
+            Action::BulkUpdateExecute {
+                filter: "(objectClass=user)".to_string(),
+                attribute: "title".to_string(),
+                value: "Engineer".to_string(),
+                op: BulkOp::Replace,
+                script: false,
+            },
+            Action::BatchModifyExecute {
+                dns: vec![dn.to_string()],
+                change: BatchChange::Entry(value("mail")),
+                changefile: None,
+            },
+            Action::MigrateAttributeExecute {
+                changes: vec![(dn.to_string(), vec![value("mail")])],
+                skipped: 0,
+            },
+            Action::CreateEntry {
+                dn: "cn=New,dc=contoso,dc=com".to_string(),
+                attributes: vec![("objectClass".to_string(), vec!["person".to_string()])],
+            },
+            Action::DeleteEntry(dn.to_string()),
+            Action::DeleteSubtree("ou=IT,ou=Corporate,dc=contoso,dc=com".to_string()),
+            Action::DeleteEntries(vec![dn.to_string()]),
+            Action::RenameEntry {
+                dn: dn.to_string(),
+                new_rdn: "cn=Lisa Stone".to_string(),
+                delete_old_rdn: true,
+                new_superior: None,
+            },
+            Action::SetPassword {
+                dn: dn.to_string(),
+                password: "Tr0ub4dor&3".to_string(),
+                must_change: false,
+            },
+            Action::ChangeAccount {
+                action: AccountAction::Disable,
+                change: Change::modify(dn, &[value("userAccountControl")]),
+            },
+            Action::UndoExecute,
+            Action::CopySubtree {
+                source: "ou=IT,ou=Corporate,dc=contoso,dc=com".to_string(),
+                target: "dc=contoso,dc=com".to_string(),
+                changefile: None,
+            },
+        ];
+        for action in writes {
+            assert!(action.is_write(), "{:?}", action);
+            app.last_error = None;
+            app.process_action(action.clone()).await;
+            drain(&mut app).await;
+            assert_eq!(
+                app.last_error.as_deref(),
+                Some("profile is read-only"),
+                "{:?}",
+                action
+            );
+            assert!(!app.protected_write_dialog.visible);
+        }
+        // Writing LDIF to a file, or a dry run, sends nothing
+        assert!(!Action::BatchModifyExecute {
+            dns: vec![dn.to_string()],
+            change: BatchChange::Entry(value("mail")),
+            changefile: Some(PathBuf::from("/tmp/batch.ldif")),
+        }
+        .is_write());
+        assert!(!Action::ApplyLdifExecute {
+            changes: Vec::new(),
+            options: ApplyOptions {
+                dry_run: true,
+                ..Default::default()
+            },
+        }
+        .is_write());
+
+        // Offline edits are still recorded, since they are not sent
+        app.process_action(Action::ToggleOfflineEdits).await;
+        app.process_action(Action::DeleteEntry(dn.to_string()))
+            .await;
+        assert_eq!(app.active_tab().unwrap().pending_changes.len(), 1);
+        app.process_action(Action::ToggleOfflineEdits).await;
+
+        // `:set write on` wants the profile's name typed
+        app.process_action(Action::CommandExecute("set write on".to_string()))
+            .await;
+        assert!(app.write_override_dialog.visible);
+        press(&mut app, KeyCode::Enter);
+        drain(&mut app).await;
+        assert!(app.write_override_dialog.visible);
+        for c in profile.chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        drain(&mut app).await;
+        assert!(!app.write_override_dialog.visible);
+        app.last_error = None;
+        app.process_action(Action::SetPassword {
+            dn: dn.to_string(),
+            password: "Tr0ub4dor&3".to_string(),
+            must_change: false,
+        })
+        .await;
+        drain(&mut app).await;
+        // Past the profile's flag, down to what the example directory allows
+        assert_eq!(app.last_error.as_deref(), Some("Connection is read-only"));
+
+        // and lasts until the connection is reopened
+        app.health_changed(id, ConnectionHealth::Connected);
+        assert!(app.active_tab().unwrap().writes_locked());
+
+        // A copy whose branch was read before the tab locked again is not sent
+        let it = "ou=IT,ou=Corporate,dc=contoso,dc=com";
+        app.last_error = None;
+        app.process_action(Action::SubtreeRead {
+            source: it.to_string(),
+            target: format!("ou=IT Copy,{}", OfflineDirectory::load_embedded().base_dn()),
+            entries: OfflineDirectory::load_embedded().search(it, "(objectClass=*)"),
+            changefile: None,
+        })
+        .await;
+        assert_eq!(app.last_error.as_deref(), Some("Connection is read-only"));
+        assert!(!app.copy_subtree_dialog.visible);
+    }
+
+    #[tokio::test]
+    async fn test_set_password_keeps_what_is_typed_out_of_messages() {
+        let (mut app, _) = offline_app().await;
+        let dn = "cn=Lisa Park,ou=Development,ou=IT,ou=Corporate,dc=contoso,dc=com";
+        app.process_action(Action::ShowSetPassword(dn.to_string()))
+            .await;
+        assert!(app.password_dialog.visible);
+
+        for field in ["Tr0ub4dor&3", "Tr0ub4dor&3"] {
+            for c in field.chars() {
+                press(&mut app, KeyCode::Char(c));
+            }
+            press(&mut app, KeyCode::Tab);
+        }
+        press(&mut app, KeyCode::Char(' '));
+        press(&mut app, KeyCode::Enter);
+        drain(&mut app).await;
+        assert!(!app.password_dialog.visible);
+        assert_eq!(app.last_error.as_deref(), Some("Connection is read-only"));
+        assert!(app
+            .status_bar
+            .messages()
+            .iter()
+            .all(|m| !m.text.contains("Tr0ub4dor")));
+
+        // Never queued, so never written to a change file
+        app.process_action(Action::ToggleOfflineEdits).await;
+        app.process_action(Action::SetPassword {
+            dn: dn.to_string(),
+            password: "Tr0ub4dor&3".to_string(),
+            must_change: false,
+        })
+        .await;
+        assert!(app.active_tab().unwrap().pending_changes.is_empty());
+        assert!(app
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.starts_with("Passwords are not recorded")));
+    }
+
+    #[tokio::test]
+    async fn test_result_columns_are_kept_and_exported_as_shown() {
+        let mut config = test_config();
+        config.connections.push(ConnectionProfile {
+            result_columns: vec!["cn".to_string(), "title".to_string()],
+            ..example_profile()
+        });
+        let (mut app, _) = offline_app_with(config).await;
+        app.process_action(Action::SearchExecute("(uid=*)".to_string()))
+            .await;
+        drain(&mut app).await;
+        app.ingest_results();
+        app.command_panel.soft_deactivate();
+        assert_eq!(app.search_dialog.columns(), ["cn", "title"]);
+
+        // The column picker opens over the results and saves to the profile
+        press(&mut app, KeyCode::Char('c'));
+        drain(&mut app).await;
+        assert!(app.columns_dialog.visible);
+        assert!(app.search_dialog.visible);
+        press(&mut app, KeyCode::Char('a'));
+        for c in "mail".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        press(&mut app, KeyCode::Enter);
+        drain(&mut app).await;
+        assert!(!app.columns_dialog.visible);
+        assert_eq!(app.search_dialog.columns(), ["cn", "mail", "title"]);
+        assert_eq!(
+            app.config.connections[0].result_columns,
+            ["cn", "mail", "title"]
+        );
+
+        // s sorts by the column under the cursor
+        press(&mut app, KeyCode::Char('s'));
+        drain(&mut app).await;
+        assert!(app
+            .status_bar
+            .latest_text()
+            .is_some_and(|t| t.starts_with("Sorted") && t.ends_with("by cn")));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("people.csv");
+        app.process_action(Action::ExportExecute {
+            base_dn: String::new(),
+            path: path.display().to_string(),
+            filter: String::new(),
+            attributes: vec!["*".to_string()],
+            timestamps: None,
+            decode: false,
+            csv: Default::default(),
+            format: None,
+            source: ExportSource::Table,
+            scope: Default::default(),
+            include_base: true,
+        })
+        .await;
+        finish_export(&mut app).await;
+        let written = std::fs::read_to_string(&path).unwrap();
+        let (_, rows) = app.search_dialog.table();
+        let mut lines = written.lines();
+        assert_eq!(lines.next(), Some("cn,mail,title"));
+        assert!(lines.next().is_some_and(|l| l.starts_with(&rows[0][0])));
+        assert_eq!(written.lines().count(), rows.len() + 1);
+    }
+
+    #[tokio::test]
+    async fn test_batch_modify_dry_run_and_summary() {
+        let (mut app, _) = offline_app().await;
+        app.process_action(Action::SearchExecute("(uid=*)".to_string()))
+            .await;
+        drain(&mut app).await;
+        app.ingest_results();
+        app.command_panel.soft_deactivate();
+        let found = app.search_dialog.row_count();
+        assert!(found > 0);
+
+        // `a` selects every result and `m` offers them to the batch
+        // dialog, over the search
+        press(&mut app, KeyCode::Char('a'));
+        press(&mut app, KeyCode::Char('m'));
+        drain(&mut app).await;
+        assert!(app.batch_modify_dialog.visible);
+        assert!(app.search_dialog.visible);
+        for c in "description".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Tab);
+        for c in "reviewed".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+
+        // A dry run writes a changefile, even offline
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("changes.ldif");
+        press(&mut app, KeyCode::F(3));
+        press(&mut app, KeyCode::Tab);
+        let default = crate::paths::state_dir().join("batch-changes.ldif");
+        for _ in 0..default.display().to_string().chars().count() {
+            press(&mut app, KeyCode::Backspace);
+        }
+        for c in path.display().to_string().chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        drain(&mut app).await;
+        assert!(!app.batch_modify_dialog.visible);
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(written.matches("changetype: modify").count(), found);
+        assert!(written.contains("add: description\ndescription: reviewed\n-\n"));
+
+        // The outcome lists the failures
+        app.process_action(Action::BatchModifyComplete {
+            succeeded: 42,
+            errors: vec![(
+                "uid=carol,dc=example,dc=com".to_string(),
+                "insufficientAccessRights".to_string(),
+            )],
+        })
+        .await;
+        assert!(app.batch_summary_popup.visible);
+        assert_eq!(
+            app.status_bar.latest_text(),
+            Some("Batch modify: 42 succeeded, 1 failed")
+        );
+        press(&mut app, KeyCode::Esc);
+        drain(&mut app).await;
+        assert!(!app.batch_summary_popup.visible);
+    }
+
+    #[tokio::test]
+    async fn test_created_entry_joins_the_tree() {
+        let (mut app, _) = offline_app().await;
+        let base_dn = OfflineDirectory::load_embedded().base_dn().to_string();
+
+        // The wizard offers the connection's object classes
+        app.process_action(Action::ShowCreateEntryDialog(base_dn.clone()))
+            .await;
+        assert!(app.create_entry_dialog.visible);
+        press(&mut app, KeyCode::Esc);
+        drain(&mut app).await;
+        assert!(!app.create_entry_dialog.visible);
+
+        let dn = format!("ou=Contractors,{}", base_dn);
+        app.process_action(Action::EntryCreated(dn.clone())).await;
+        let tab = app.active_tab().unwrap();
+        let root = tab.directory_tree.find_node(&base_dn).unwrap();
+        assert_eq!(root.children.as_ref().unwrap().last().unwrap().dn, dn);
+        assert!(tab.directory_tree.find_node(&dn).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_schema_layout_opens_at_attribute() {
+        let (mut app, _) = offline_app().await;
+
+        app.process_action(Action::ShowSchemaDefinition("MAIL".to_string()))
+            .await;
+        drain(&mut app).await;
+        assert_eq!(app.active_layout, ActiveLayout::Schema);
+        assert_eq!(app.focus.current(), FocusTarget::SchemaList);
+        assert_eq!(app.schema_viewer.source(), app.active_tab_id);
+        assert!(app
+            .status_bar
+            .latest_text()
+            .map_or(true, |m| !m.contains("not in the schema")));
+
+        app.process_action(Action::ShowSchemaDefinition("noSuchAttr".to_string()))
+            .await;
+        assert!(app
+            .status_bar
+            .latest_text()
+            .is_some_and(|m| m.contains("not in the schema")));
+
+        drain(&mut app).await;
+
+        // Esc goes back to the browser
+        press(&mut app, KeyCode::Esc);
+        drain(&mut app).await;
+        assert_eq!(app.active_layout, ActiveLayout::Browser);
+        assert_eq!(app.focus.current(), FocusTarget::TreePanel);
+    }
+
+    #[tokio::test]
+    async fn test_protected_entry_needs_typed_confirmation() {
+        let (mut app, _) = offline_app().await;
+        let base_dn = OfflineDirectory::load_embedded().base_dn().to_string();
+        let krbtgt = format!("CN=krbtgt,CN=Users,{}", base_dn);
+
+        app.process_action(Action::DeleteEntry(krbtgt.clone()))
+            .await;
+        assert!(app.protected_write_dialog.visible);
+        press(&mut app, KeyCode::Enter);
+        drain(&mut app).await;
+        assert!(app.protected_write_dialog.visible);
+
+        for c in "cn=krbtgt".chars() {
+            press(&mut app, KeyCode::Char(c));
+        }
+        press(&mut app, KeyCode::Enter);
+        drain(&mut app).await;
+        assert!(!app.protected_write_dialog.visible);
+        // The confirmation was used up by the write it was given for
+        assert!(app.protected_write_allowed.is_none());
+
+        // Strict mode refuses without asking
+        app.active_tab_mut().unwrap().protected.mode = ProtectionMode::Strict;
+        app.process_action(Action::DeleteEntry(krbtgt)).await;
+        assert!(!app.protected_write_dialog.visible);
+
+        // Ordinary entries are not affected
+        let alice = format!("CN=Alice,CN=Users,{}", base_dn);
+        assert!(app.guard_write(&alice, Action::DeleteEntry(alice.clone())));
+    }
+
+    #[tokio::test]
+    async fn test_delete_dialog_and_subtree_result() {
+        let (mut app, _) = offline_app().await;
+        let base_dn = OfflineDirectory::load_embedded().base_dn().to_string();
+        let users = format!("CN=Users,{}", base_dn);
+
+        // The base DN is refused outright
+        app.process_action(Action::ShowDeleteDialog(base_dn.clone()))
+            .await;
+        assert!(!app.delete_dialog.visible);
+        assert!(app
+            .status_bar
+            .latest_text()
+            .is_some_and(|m| m.contains("base DN")));
+
+        // dd on an entry asks first
+        app.tree_panel
+            .tree_state
+            .select(vec![base_dn.clone(), users.clone()]);
+        press(&mut app, KeyCode::Char('d'));
+        drain(&mut app).await;
+        assert!(!app.delete_dialog.visible);
+        press(&mut app, KeyCode::Char('d'));
+        drain(&mut app).await;
+        assert!(app.delete_dialog.visible);
+
+        // The example directory is read-only
+        press(&mut app, KeyCode::Char('s'));
+        drain(&mut app).await;
+        assert!(!app.delete_dialog.visible);
+        assert!(app
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.contains("read-only")));
+
+        // What a finished run deleted leaves the tree
+        let result = SubtreeDeletion {
+            deleted: vec![users.clone()],
+            total: 1,
+            failure: None,
+        };
+        app.process_action(Action::SubtreeDeleted {
+            base: users.clone(),
+            result,
+        })
+        .await;
+        let tree = &app.active_tab().unwrap().directory_tree;
+        assert!(tree.find_node(&users).is_none());
+        assert!(app
+            .status_bar
+            .latest_text()
+            .is_some_and(|m| m.starts_with("Deleted 1 of 1 entries")));
+
+        // A subtree that grew since the delete was confirmed is asked about again
+        let fresh = TrackedEntry::split(LdapEntry::new(users.clone(), BTreeMap::new()));
+        app.process_action(Action::SubtreeDeleteBlocked(
+            users.clone(),
+            Box::new(fresh),
+            DeleteCheck::ChildrenChanged { loaded: 2, now: 3 },
+        ))
+        .await;
+        assert!(app.subtree_delete_task.is_none());
+        assert!(app.confirm_dialog.message.contains("it now has 3"));
+        assert!(matches!(
+            app.confirm_dialog.on_confirm.as_deref(),
+            Some(Action::DeleteSubtree(dn)) if *dn == users
+        ));
+        assert!(app
+            .active_tab()
+            .unwrap()
+            .entry_cache
+            .contains_key(&normalize_dn(&users)));
+    }
+
+    #[tokio::test]
+    async fn test_rename_dialog_and_moved_node() {
+        let (mut app, _) = offline_app().await;
+        let base_dn = OfflineDirectory::load_embedded().base_dn().to_string();
+        let users = format!("CN=Users,{}", base_dn);
+
+        app.tree_panel
+            .tree_state
+            .select(vec![base_dn.clone(), users.clone()]);
+        press(&mut app, KeyCode::Char('r'));
+        drain(&mut app).await;
+        assert!(app.rename_dialog.visible);
+        press(&mut app, KeyCode::Char('2'));
+        press(&mut app, KeyCode::Enter);
+        drain(&mut app).await;
+        assert!(!app.rename_dialog.visible);
+        assert!(app
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.contains("read-only")));
+
+        // A rename the server accepted moves the node and selects it
+        let renamed = format!("CN=Users2,{}", base_dn);
+        app.process_action(Action::EntryRenamed {
+            old_dn: users.clone(),
+            new_dn: renamed.clone(),
+        })
+        .await;
+        let tree = &app.active_tab().unwrap().directory_tree;
+        assert!(tree.find_node(&users).is_none());
+        assert!(tree.find_node(&renamed).is_some());
+        assert_eq!(app.tree_panel.selected_dn(), Some(&renamed));
+    }
+
+    #[tokio::test]
+    async fn test_clone_entry_and_subtree_dry_run() {
+        let (mut app, _) = offline_app().await;
+        let base_dn = OfflineDirectory::load_embedded().base_dn().to_string();
+        let groups = format!("ou=Groups,{}", base_dn);
+
+        app.tree_panel
+            .tree_state
+            .select(vec![base_dn.clone(), groups.clone()]);
+        press(&mut app, KeyCode::Char('c'));
+        drain(&mut app).await;
+        assert!(app.create_entry_dialog.visible);
+        app.process_action(Action::ClosePopup).await;
+
+        // Nothing to paste until an entry is yanked
+        press(&mut app, KeyCode::Char('P'));
+        drain(&mut app).await;
+        assert!(!app.copy_subtree_dialog.visible);
+        press(&mut app, KeyCode::Char('y'));
+        press(&mut app, KeyCode::Char('y'));
+        drain(&mut app).await;
+        press(&mut app, KeyCode::Char('P'));
+        drain(&mut app).await;
+        assert!(app.copy_subtree_dialog.visible);
+        app.process_action(Action::ClosePopup).await;
+
+        // Writing to the example directory is refused, a dry run is not
+        let target = format!("ou=Groups Copy,{}", base_dn);
+        app.process_action(Action::CopySubtree {
+            source: groups.clone(),
+            target: target.clone(),
+            changefile: None,
+        })
+        .await;
+        drain(&mut app).await;
+        assert!(app
+            .last_error
+            .as_deref()
+            .is_some_and(|e| e.contains("read-only")));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("copy.ldif");
+        app.process_action(Action::CopySubtree {
+            source: groups.clone(),
+            target: target.clone(),
+            changefile: Some(path.clone()),
+        })
+        .await;
+        drain(&mut app).await;
+        let ldif = std::fs::read_to_string(&path).unwrap();
+        assert!(ldif.contains(&format!("dn: {}", target)), "{}", ldif);
+        assert!(ldif.contains("changetype: add"));
+        assert!(!ldif.contains(&format!("dn: {}\n", groups)));
+    }
+
+    #[tokio::test]
+    async fn test_tab_states_for_layout_bar() {
+        let (mut app, _) = offline_app().await;
+
+        let states = app.tab_states();
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[0].label, "Example Directory");
+        assert!(states[0].entries > 1, "root children are loaded on connect");
+        assert_eq!(states[0].results, None);
+        assert!(!states[0].busy && !states[0].dirty);
+
+        // The spinner shows while any operation holds a guard
+        let tab = app.active_tab().unwrap();
+        let first = tab.ops.begin("Search");
+        let second = tab.ops.begin("Read");
+        drop(first);
+        assert!(app.tab_states()[0].busy);
+        drop(second);
+        assert!(!app.tab_states()[0].busy);
+
+        app.create_entry_dialog.visible = true;
+        assert!(app.tab_states()[0].dirty);
+    }
+
+    #[tokio::test]
+    async fn test_cancel_outstanding_operation() {
+        let (mut app, _) = offline_app().await;
+
+        let tab = app.active_tab().unwrap();
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel::<()>();
+        let op = tab.spawn_op("Search (cn=*)", async move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            let _ = done_tx.send(());
+        });
+        app.process_action(Action::Tick).await;
+        assert_eq!(app.status_bar.running, 1);
+
+        app.process_action(Action::ShowActivity).await;
+        assert!(app.activity_popup.visible);
+        app.process_action(Action::CancelOperation(op)).await;
+        assert_eq!(
+            app.status_bar.latest_text(),
+            Some("Cancelled: Search (cn=*)")
+        );
+        // The task was aborted, so it never completes
+        assert!(done_rx.await.is_err());
+        app.process_action(Action::Tick).await;
+        assert_eq!(app.status_bar.running, 0);
+        let ops = app.active_tab().unwrap().ops.list(Instant::now());
+        assert!(ops[0]
+            .describe(Instant::now())
+            .starts_with("cancelled after"));
+    }
+
+    #[tokio::test]
+    async fn test_esc_cancels_newest_operation() {
+        let (mut app, _) = offline_app().await;
+
+        let tab = app.active_tab().unwrap();
+        let older = tab.spawn_op("Read", std::future::pending());
+        let newer = tab.spawn_op("Search (cn=*)", std::future::pending());
+        app.process_action(Action::Tick).await;
+        assert!(app
+            .status_bar
+            .current
+            .as_deref()
+            .unwrap()
+            .starts_with("Search (cn=*) "));
+
+        press(&mut app, KeyCode::Esc);
+        drain(&mut app).await;
+        assert_eq!(
+            app.status_bar.latest_text(),
+            Some("Cancelled: Search (cn=*)")
+        );
+        let ops = app.active_tab().unwrap().ops.clone();
+        assert_eq!(ops.newest_running().map(|op| op.id), Some(older));
+        assert_ne!(older, newer);
+
+        // The next Esc cancels the older one
+        press(&mut app, KeyCode::Esc);
+        drain(&mut app).await;
+        app.process_action(Action::Tick).await;
+        assert_eq!(app.status_bar.running, 0);
+        assert!(app.status_bar.current.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_slash_searches_focused_detail_pane() {
+        let (mut app, _) = offline_app().await;
+
+        let mut attrs = std::collections::BTreeMap::new();
+        attrs.insert("cn".to_string(), vec!["jdoe".to_string()]);
+        let entry = LdapEntry::new("cn=jdoe,dc=example,dc=com".to_string(), attrs);
+        app.detail_panel.set_entry(entry, None);
+        app.focus.set(FocusTarget::DetailPanel);
+
+        // '/' searches the entry and 's' is typed into the search
+        press(&mut app, KeyCode::Char('/'));
+        press(&mut app, KeyCode::Char('s'));
+        drain(&mut app).await;
+        assert!(app.detail_panel.is_searching());
+        assert!(!app.search_form_dialog.visible);
+
+        // Elsewhere '/' still opens the search form
+        press(&mut app, KeyCode::Enter);
+        app.focus.set(FocusTarget::TreePanel);
+        press(&mut app, KeyCode::Char('/'));
+        drain(&mut app).await;
+        assert!(app.search_form_dialog.visible);
+    }
+
+    #[tokio::test]
+    async fn test_follow_dn_links_and_go_back() {
+        let (mut app, _) = offline_app().await;
+
+        let group = "cn=Domain Admins,ou=Groups,dc=contoso,dc=com";
+        let member = "cn=James Wilson,ou=Infrastructure,ou=IT,ou=Corporate,dc=contoso,dc=com";
+        let shown = |app: &App| app.detail_panel.entry.as_ref().map(|e| e.dn.clone());
+        app.process_action(Action::TreeSelect(group.to_string()))
+            .await;
+        drain(&mut app).await;
+        app.detail_panel.select_row(3);
+
+        app.process_action(Action::FollowDn(member.to_string()))
+            .await;
+        drain(&mut app).await;
+        assert_eq!(shown(&app).as_deref(), Some(member));
+        assert_eq!(
+            app.tree_panel
+                .tree_state
+                .selected()
+                .last()
+                .map(String::as_str),
+            Some(member)
+        );
+        assert_eq!(app.nav_history.len(), 1);
+
+        // A broken link leaves the entry and history as they were
+        app.process_action(Action::FollowDn("cn=Nobody,dc=contoso,dc=com".into()))
+            .await;
+        drain(&mut app).await;
+        assert_eq!(shown(&app).as_deref(), Some(member));
+        assert_eq!(app.nav_history.len(), 1);
+        assert!(app.pending_follow.is_none());
+
+        // Back returns to the group, on the row that was selected
+        app.process_action(Action::NavigateBack).await;
+        drain(&mut app).await;
+        assert_eq!(shown(&app).as_deref(), Some(group));
+        assert_eq!(app.detail_panel.selected_row(), Some(3));
+        app.process_action(Action::NavigateBack).await;
+        assert_eq!(
+            app.status_bar.latest_text(),
+            Some("No earlier entry to go back to")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_keys_from_config() {
+        let mut config = test_config();
+        config.keybindings.commands = [
+            ("search_form", KeyChords::One("ctrl-f".to_string())),
+            ("teleport", KeyChords::One("t".to_string())),
+        ]
+        .into_iter()
+        .map(|(name, chords)| (name.to_string(), chords))
+        .collect();
+        let (mut app, _) = offline_app_with(config).await;
+        assert_eq!(
+            app.last_error.as_deref(),
+            Some("[keybindings] Unknown command 'teleport'")
+        );
+
+        app.focus.set(FocusTarget::TreePanel);
+        press(&mut app, KeyCode::Char('/'));
+        drain(&mut app).await;
+        assert!(!app.search_form_dialog.visible);
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::CONTROL));
+        drain(&mut app).await;
+        assert!(app.search_form_dialog.visible);
+    }
+
+    #[tokio::test]
+    async fn test_groups_of_an_entry_and_export() {
+        let (mut app, _) = offline_app().await;
+
+        let admin = "cn=Administrator,dc=contoso,dc=com";
+        app.process_action(Action::TreeSelect(admin.to_string()))
+            .await;
+        drain(&mut app).await;
+        app.focus.set(FocusTarget::DetailPanel);
+        press(&mut app, KeyCode::Char('G'));
+        while app.membership_popup.membership().is_none() {
+            let action = tokio::time::timeout(Duration::from_secs(10), app.action_rx.recv())
+                .await
+                .expect("groups looked up")
+                .unwrap();
+            app.process_action(action).await;
+        }
+        assert!(app.membership_popup.visible);
+        let groups: Vec<&str> = app
+            .membership_popup
+            .membership()
+            .unwrap()
+            .groups
+            .iter()
+            .map(|g| g.dn.as_str())
+            .collect();
+        assert_eq!(groups, vec!["cn=Domain Admins,ou=Groups,dc=contoso,dc=com"]);
+
+        // `e` hands the groups to the export dialog instead of a search
+        press(&mut app, KeyCode::Char('e'));
+        drain(&mut app).await;
+        assert!(app.export_dialog.visible);
+        assert!(!app.membership_popup.visible);
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("groups.csv");
+        app.process_action(Action::ExportExecute {
+            base_dn: admin.to_string(),
+            path: path.display().to_string(),
+            filter: String::new(),
+            attributes: vec!["depth".to_string(), "via".to_string()],
+            timestamps: None,
+            decode: false,
+            csv: Default::default(),
+            format: None,
+            source: ExportSource::Groups,
+            scope: Default::default(),
+            include_base: true,
+        })
+        .await;
+        finish_export(&mut app).await;
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.contains("cn=Domain Admins,ou=Groups,dc=contoso,dc=com"));
+    }
+
+    #[tokio::test]
+    async fn test_lost_connection_cancels_operations() {
+        let (mut app, _) = offline_app().await;
+
+        // The example directory has nothing to reconnect, and no indicator
+        app.process_action(Action::Reconnect(None)).await;
+        assert_eq!(
+            app.status_bar.latest_text(),
+            Some("Example Directory is offline; nothing to reconnect")
+        );
+        app.process_action(Action::Tick).await;
+        assert_eq!(app.status_bar.health, None);
+
+        let tab = app.active_tab().unwrap();
+        let id = tab.id;
+        let (done_tx, done_rx) = tokio::sync::oneshot::channel::<()>();
+        tab.spawn_op("Search (cn=*)", async move {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            let _ = done_tx.send(());
+        });
+        app.process_action(Action::ConnectionHealthChanged(
+            id,
+            ConnectionHealth::Reconnecting,
+        ))
+        .await;
+        assert_eq!(
+            app.status_bar.latest_text(),
+            Some("Connection to contoso.example lost, reconnecting; 1 operation(s) cancelled")
+        );
+        assert_eq!(
+            app.status_bar.latest().map(|m| m.severity),
+            Some(Severity::Error)
+        );
+        assert!(done_rx.await.is_err());
+
+        app.process_action(Action::ConnectionHealthChanged(
+            id,
+            ConnectionHealth::Connected,
+        ))
+        .await;
+        assert_eq!(
+            app.status_bar.latest_text(),
+            Some("Reconnected to contoso.example")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_compare_marked_entry_with_selected() {
+        let (mut app, _) = offline_app().await;
+
+        let admin = "cn=Administrator,dc=contoso,dc=com".to_string();
+        let groups = "ou=Groups,dc=contoso,dc=com".to_string();
+        app.process_action(Action::CompareEntry(admin.clone()))
+            .await;
+        assert_eq!(
+            app.status_bar.latest_text(),
+            Some("Marked Administrator for compare; select another entry and press C")
+        );
+        assert!(!app.compare_view.visible);
+
+        app.process_action(Action::CompareEntry(groups)).await;
+        assert!(app.compare_view.visible);
+        assert!(app.compare_mark.is_none());
+        let shown = app.compare_view.shown();
+        assert_eq!(shown.left_dn, admin);
+        assert!(!shown.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_offline_edits_queue_writes() {
+        let (mut app, _) = offline_app().await;
+
+        let it = "ou=IT,ou=Corporate,dc=contoso,dc=com".to_string();
+        let corporate = "ou=Corporate,dc=contoso,dc=com".to_string();
+        app.process_action(Action::ToggleOfflineEdits).await;
+        assert_eq!(app.status_bar.pending, Some(0));
+
+        // The example directory is read-only, but queueing needs no writes
+        app.process_action(Action::RenameEntry {
+            dn: it.clone(),
+            new_rdn: "ou=Technology".to_string(),
+            delete_old_rdn: true,
+            new_superior: None,
+        })
+        .await;
+        app.process_action(Action::DeleteSubtree(corporate.clone()))
+            .await;
+        drain(&mut app).await;
+        let tab = app.active_tab().unwrap();
+        let pending: Vec<(&str, String)> = tab
+            .pending_changes
+            .iter()
+            .map(|c| (c.dn(), c.summary()))
+            .collect();
+        assert_eq!(
+            pending[0],
+            (it.as_str(), "modrdn to ou=Technology".to_string())
+        );
+        // Children go before their parents
+        assert_eq!(
+            pending.last().unwrap(),
+            &(corporate.as_str(), "delete".to_string())
+        );
+        assert!(pending.len() > 3);
+
+        app.process_action(Action::ShowPendingChanges).await;
+        assert!(app.pending_changes_popup.visible);
+        let kept = app.active_tab().unwrap().pending_changes[..1].to_vec();
+        app.process_action(Action::PendingChangesEdited(kept)).await;
+        assert_eq!(app.active_tab().unwrap().pending_changes.len(), 1);
+
+        // Switched off, writes go to the directory again
+        app.process_action(Action::ToggleOfflineEdits).await;
+        app.process_action(Action::DeleteEntry(it)).await;
+        drain(&mut app).await;
+        assert_eq!(app.active_tab().unwrap().pending_changes.len(), 1);
+        assert_eq!(
+            app.status_bar.latest_text(),
+            Some("Connection is read-only")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_command_line_commands() {
+        let (mut app, _) = offline_app().await;
+
+        let run = |line: &str| Action::CommandExecute(line.to_string());
+        app.process_action(run("exprot out.csv cn")).await;
+        assert_eq!(
+            app.last_error.as_deref(),
+            Some("Unknown command :exprot; did you mean :export?")
+        );
+        app.process_action(run("connect example directory (contso)"))
+            .await;
+        assert_eq!(
+            app.last_error.as_deref(),
+            Some(
+                "No profile named 'example directory (contso)'; \
+                 did you mean 'Example Directory (Contoso)'?"
+            )
+        );
+        app.process_action(run("set page_sise 10")).await;
+        assert_eq!(
+            app.last_error.as_deref(),
+            Some("Unknown setting 'page_sise'; did you mean page_size?")
+        );
+
+        let it = "ou=IT,ou=Corporate,dc=contoso,dc=com";
+        app.process_action(run(&format!("base {}", it))).await;
+        drain(&mut app).await;
+        let tab = app.active_tab().unwrap();
+        assert_eq!(tab.directory_tree.root_dn, it);
+        assert!(tab.directory_tree.root.is_loaded());
+        assert_eq!(app.status_bar.base_dn.as_deref(), Some(it));
+
+        assert_eq!(app.config.command_history[0], format!("base {}", it));
+        assert_eq!(app.config.command_history[1], "set page_sise 10");
+    }
+
+    #[tokio::test]
+    async fn test_command_line_edits_non_ascii_dn() {
+        let (mut app, _) = offline_app().await;
+        let type_text = |app: &mut App, text: &str| {
+            for c in text.chars() {
+                press(app, KeyCode::Char(c));
+            }
+        };
+
+        press(&mut app, KeyCode::Char(':'));
+        drain(&mut app).await;
+        assert!(app.command_panel.is_command());
+        type_text(&mut app, "base ou=Zü");
+        press(&mut app, KeyCode::Backspace);
+        assert_eq!(app.command_panel.input_buffer, ":base ou=Z");
+        type_text(&mut app, "ürich");
+        for _ in 0..5 {
+            press(&mut app, KeyCode::Left);
+        }
+        press(&mut app, KeyCode::Right);
+        press(&mut app, KeyCode::Left);
+        press(&mut app, KeyCode::Delete);
+        assert_eq!(app.command_panel.input_buffer, ":base ou=Zrich");
+        type_text(&mut app, "ö");
+        press(&mut app, KeyCode::End);
+        type_text(&mut app, ",dc=contoso,dc=com");
+        assert_eq!(
+            app.command_panel.input_buffer,
+            ":base ou=Zörich,dc=contoso,dc=com"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_undo_confirms_the_last_step() {
+        let mut config = test_config();
+        config.general.undo_depth = 1;
+        let (mut app, _) = offline_app_with(config).await;
+
+        press(&mut app, KeyCode::Char('u'));
+        drain(&mut app).await;
+        assert_eq!(app.status_bar.latest_text(), Some("Nothing to undo"));
+        assert!(!app.confirm_dialog.visible);
+
+        let id = app.active_tab_id.unwrap();
+        let dn = "cn=John Smith,ou=Users,ou=Corporate,dc=contoso,dc=com";
+        for title in ["Engineer", "Manager"] {
+            let restore = Change::modify(
+                dn,
+                &[BulkMod::ReplaceAttribute {
+                    attr: "title".to_string(),
+                    value: title.to_string(),
+                }],
+            );
+            let step = UndoStep::new(format!("Modify {} ({})", dn, title), vec![restore]);
+            app.process_action(Action::PushUndo(id, step)).await;
+        }
+        // Only the newest step fits
+        assert_eq!(app.active_tab().unwrap().undo.len(), 1);
+
+        press(&mut app, KeyCode::Char('u'));
+        drain(&mut app).await;
+        assert!(app.confirm_dialog.visible);
+        assert!(app
+            .confirm_dialog
+            .message
+            .starts_with(&format!("Undo Modify {} (Manager)? This sends:", dn)));
+        assert!(app
+            .confirm_dialog
+            .message
+            .contains("changetype: modify\nreplace: title\ntitle: Manager\n"));
+        press(&mut app, KeyCode::Esc);
+        drain(&mut app).await;
+        assert_eq!(app.active_tab().unwrap().undo.len(), 1);
+
+        let step = app.active_tab_mut().unwrap().undo.pop().unwrap();
+        app.process_action(Action::UndoComplete(id, step)).await;
+        assert_eq!(
+            app.status_bar.latest_text(),
+            Some(format!("Undid Modify {} (Manager)", dn).as_str())
+        );
+    }
+
+    #[tokio::test]
+    async fn test_apply_ldif_command_and_refusals() {
+        let (mut app, _) = offline_app().await;
+
+        app.process_action(Action::CommandExecute("apply changes.ldif".to_string()))
+            .await;
+        drain(&mut app).await;
+        assert!(app.apply_ldif_dialog.visible);
+
+        let changes = vec![Change::Delete {
+            dn: "ou=IT,ou=Corporate,dc=contoso,dc=com".to_string(),
+        }];
+        let options = ApplyOptions {
+            stop_on_error: true,
+            dry_run: false,
+        };
+        app.process_action(Action::ApplyLdifExecute {
+            changes: changes.clone(),
+            options,
+        })
+        .await;
+        assert!(!app.apply_ldif_dialog.visible);
+        assert_eq!(
+            app.last_error.as_deref(),
+            Some("Applying a changefile needs a live connection")
+        );
+
+        // Recording offline edits, the records join the pending changes
+        app.process_action(Action::ToggleOfflineEdits).await;
+        app.process_action(Action::ApplyLdifExecute { changes, options })
+            .await;
+        assert_eq!(app.active_tab().unwrap().pending_changes.len(), 1);
+        assert!(app.apply_ldif_task.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_search_sort_falls_back_to_loaded_results() {
+        let (mut app, _) = offline_app().await;
+
+        app.process_action(Action::SearchExecute("(mail=*)".to_string()))
+            .await;
+        drain(&mut app).await;
+        app.ingest_results();
+        assert!(app.search_dialog.results.len() > 1);
+
+        // The example directory cannot sort on a server, so the table does
+        app.process_action(Action::SearchSort("mail".to_string(), true))
+            .await;
+        let mails: Vec<String> = app
+            .search_dialog
+            .results
+            .iter()
+            .map(|e| e.first_value("mail").unwrap_or_default().to_lowercase())
+            .collect();
+        let mut sorted = mails.clone();
+        sorted.sort_by(|a, b| b.cmp(a));
+        assert_eq!(mails, sorted);
+        assert!(app
+            .status_bar
+            .latest_text()
+            .is_some_and(|m| m.starts_with("Sorted") && m.ends_with("by mail, descending")));
+    }
+
+    #[tokio::test]
+    async fn test_server_info_for_active_tab() {
+        let mut app = App::new(test_config(), None);
+        app.process_action(Action::ShowServerInfo(None)).await;
+        assert!(!app.server_info_popup.visible);
+
+        let (mut app, _) = offline_app().await;
+        app.process_action(Action::ShowServerInfo(None)).await;
+        assert!(app.server_info_popup.visible);
+        app.process_action(Action::ClosePopup).await;
+        assert!(!app.server_info_popup.visible);
+    }
+
+    #[tokio::test]
+    async fn test_subtree_compare_needs_second_connection() {
+        let (mut app, _) = offline_app().await;
+
+        app.process_action(Action::ShowSubtreeCompareDialog).await;
+        assert!(!app.subtree_compare_dialog.visible);
+        assert_eq!(
+            app.last_error.as_deref(),
+            Some("Open the other directory on a second connection to compare with")
+        );
+
+        // A finished comparison opens its report, and Enter on a differing
+        // entry its side-by-side view
+        let id = app.active_tab_id.unwrap();
+        app.subtree_compare_task = Some((id, 0, "Replica".to_string()));
+        let entry = |mail: &str| {
+            let attributes = BTreeMap::from([("mail".to_string(), vec![mail.to_string()])]);
+            LdapEntry::new("uid=alice,dc=contoso,dc=com".to_string(), attributes)
+        };
+        let report = SubtreeDiff {
+            base_dn: "dc=contoso,dc=com".to_string(),
+            differing: vec![loom_core::diff::diff_entries(
+                &entry("alice@contoso.com"),
+                &entry("alice@example.com"),
+            )],
+            matched: 1,
+            ..SubtreeDiff::default()
+        };
+        app.process_action(Action::SubtreeCompareComplete(Box::new(report)))
+            .await;
+        assert!(app.subtree_compare_task.is_none());
+        assert!(app.subtree_diff_view.visible);
+        let action = app
+            .subtree_diff_view
+            .handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        app.process_action(action).await;
+        assert!(app.compare_view.visible);
+    }
+
+    #[tokio::test]
+    async fn test_legacy_config_prompts_before_connecting() {
+        let content = r#"
+host = "ldap.example.com"
+bind_dn = "cn=admin,dc=example,dc=com"
+password = "secret"
+"#;
+        let (config, report) = AppConfig::migrate(toml::from_str(content).unwrap()).unwrap();
+        let mut app = App::new(config, None);
+        app.set_config_migration(report);
+        app.connect_first_profile().await;
+
+        // Asked once, and nothing connects (or prompts for a password) yet
+        assert!(app.confirm_dialog.visible);
+        assert!(!app.credential_prompt.visible);
+        assert!(app.pending_migration.is_none());
+
+        // Declining keeps the migrated profile for this session
+        press(&mut app, KeyCode::Esc);
+        drain(&mut app).await;
+        assert!(!app.confirm_dialog.visible);
+        assert_eq!(app.config.connections[0].name, "default");
+    }
+
+    #[tokio::test]
+    async fn test_untrusted_hooks_wait_for_the_confirm_dialog() {
+        let dir = tempfile::tempdir().unwrap();
+        let hooks = ProfileHooks {
+            on_export: Some(HookCommand {
+                command: "true".to_string(),
+                required: false,
+            }),
+            untrusted: true,
+            ..Default::default()
+        };
+        let mut config = test_config();
+        config.connections.push(ConnectionProfile {
+            name: "imported".to_string(),
+            hooks: hooks.clone(),
+            ..example_profile()
+        });
+        let mut app = App::new(config, None);
+        app.config_path = Some(dir.path().join("config.toml"));
+
+        // Asked about only once the dialog already open is answered
+        app.confirm_dialog
+            .show("Discard the changes?".to_string(), Action::None);
+        let invocation = HookInvocation::new(&hooks, HookEvent::Export, "imported").unwrap();
+        app.trigger_hook(invocation.clone());
+        app.trigger_hook(invocation);
+        assert_eq!(app.hook_approvals.len(), 2);
+        assert_eq!(app.confirm_dialog.message, "Discard the changes?");
+
+        press(&mut app, KeyCode::Esc);
+        drain(&mut app).await;
+        app.process_action(Action::Tick).await;
+        assert!(app.confirm_dialog.message.contains("run on_export: `true`"));
+
+        // Allowing one runs both, since the profile is trusted from then on
+        press(&mut app, KeyCode::Char('y'));
+        drain(&mut app).await;
+        assert!(app.hook_approvals.is_empty());
+        assert!(!app.confirm_dialog.visible);
+        assert!(!app.hooks_untrusted("imported"));
+    }
+
+    #[tokio::test]
+    async fn test_failing_required_connect_hook_stops_the_connect() {
+        let mut app = App::new(test_config(), None);
+        let profile = ConnectionProfile {
+            name: "vpn".to_string(),
+            hooks: ProfileHooks {
+                on_connect: Some(HookCommand {
+                    command: "exit 3".to_string(),
+                    required: true,
+                }),
+                ..Default::default()
+            },
+            ..example_profile()
+        };
+        app.start_connect(profile, String::new(), ConnectOrigin::Profile);
+        while !app.connect_attempts.is_empty() {
+            let action = tokio::time::timeout(Duration::from_secs(10), app.action_rx.recv())
+                .await
+                .expect("connect finished")
+                .unwrap();
+            app.process_action(action).await;
+        }
+        assert!(app.tabs.is_empty());
+        assert_eq!(
+            app.last_error.as_deref(),
+            Some("Connection failed: required on_connect hook failed")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ctrl_l_opens_log_viewer() {
+        let mut app = App::new(test_config(), None);
+        let buffer = LogBuffer::new(10);
+        buffer.push(
+            tracing::Level::INFO,
+            "loom-ldapbrowser starting".to_string(),
+        );
+        app.set_logging(Logging {
+            writer: LogWriter::new(buffer.clone()),
+            buffer,
+            filter: None,
+        });
+
+        app.handle_key_event(KeyEvent::new(KeyCode::Char('l'), KeyModifiers::CONTROL));
+        drain(&mut app).await;
+        assert!(app.log_viewer.visible);
+        assert!(app.any_popup_or_input_active());
+
+        press(&mut app, KeyCode::Esc);
+        drain(&mut app).await;
+        assert!(!app.log_viewer.visible);
+    }
+
+    #[tokio::test]
+    async fn test_set_lasts_the_session_and_edits_to_the_file_apply_live() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut app = App::new(test_config(), None);
+        app.config_path = Some(path.clone());
+        app.watch_config();
+
+        // Applied at once, but saved as the file had it
+        app.process_action(Action::CommandExecute("set time_display iso".to_string()))
+            .await;
+        assert_eq!(app.config.detail.time_display, TimeDisplay::Iso);
+        app.save_config().unwrap();
+        let (saved, _) = AppConfig::read(&path).unwrap();
+        assert_eq!(saved.detail.time_display, TimeDisplay::Both);
+
+        // An edit made elsewhere is read on the next tick, under the override
+        let mut edited = saved.clone();
+        edited.general.theme = "nord".to_string();
+        edited.general.autocomplete = false;
+        edited.save_to(&path).unwrap();
+        app.process_action(Action::Tick).await;
+        assert_eq!(app.config.general.theme, "nord");
+        assert!(!app.config.general.autocomplete);
+        assert_eq!(app.config.detail.time_display, TimeDisplay::Iso);
+        assert!(app.last_error.is_none());
+
+        app.process_action(Action::CommandExecute("set-save".to_string()))
+            .await;
+        let (saved, _) = AppConfig::read(&path).unwrap();
+        assert_eq!(saved.detail.time_display, TimeDisplay::Iso);
+        assert_eq!(saved.general.theme, "nord");
+
+        // The command reads the edit before its history is saved
+        let mut edited = saved.clone();
+        edited.general.theme = "light".to_string();
+        edited.save_to(&path).unwrap();
+        app.process_action(Action::CommandExecute("reload-config".to_string()))
+            .await;
+        assert_eq!(app.config.general.theme, "light");
+        let (saved, _) = AppConfig::read(&path).unwrap();
+        assert_eq!(saved.general.theme, "light");
+        assert_eq!(saved.command_history[0], "reload-config");
+
+        // A file that does not parse changes nothing
+        std::fs::write(&path, "[general\n").unwrap();
+        app.process_action(Action::CommandExecute("reload-config".to_string()))
+            .await;
+        assert!(app
+            .last_error
+            .as_deref()
+            .unwrap()
+            .starts_with("Config not reloaded"));
+        assert_eq!(app.config.general.theme, "light");
+
+        app.process_action(Action::CommandExecute(
+            "set autocomplete perhaps".to_string(),
+        ))
+        .await;
+        assert!(app
+            .last_error
+            .as_deref()
+            .unwrap()
+            .starts_with("autocomplete cannot be 'perhaps'"));
     }
 }
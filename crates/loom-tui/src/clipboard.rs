@@ -0,0 +1,123 @@
+//! Copying text out of loom: to the system clipboard when one can be
+//! reached, otherwise with an OSC 52 escape sequence that asks the
+//! terminal to set its own clipboard. The fallback is what makes copying
+//! work over SSH, where the remote host has no clipboard of its own but
+//! the terminal on the user's machine does.
+
+use std::io::{self, IsTerminal, Write};
+
+use base64::Engine;
+
+/// Largest OSC 52 payload sent, base64-encoded. Terminals drop or cut
+/// longer sequences, some well below this.
+pub const MAX_OSC52_BYTES: usize = 100_000;
+
+/// Where copied text went.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Copied {
+    /// The system clipboard.
+    System,
+    /// The terminal, through OSC 52.
+    Terminal,
+}
+
+/// The clipboard behind every copy action. The system clipboard is opened
+/// on first use and kept open: on X11 and Wayland the copied text is only
+/// served while the handle lives.
+#[derive(Default)]
+pub struct Clipboard {
+    system: Option<arboard::Clipboard>,
+}
+
+impl Clipboard {
+    /// Copy `text`, falling back to OSC 52 on stdout when the system
+    /// clipboard cannot be used.
+    pub fn copy(&mut self, text: &str) -> Result<Copied, String> {
+        let system_error = match self.copy_to_system(text) {
+            Ok(()) => return Ok(Copied::System),
+            Err(e) => e,
+        };
+        let mut stdout = io::stdout();
+        if !stdout.is_terminal() {
+            return Err(format!("Clipboard unavailable: {}", system_error));
+        }
+        write_osc52(&mut stdout, text)
+            .map(|_| Copied::Terminal)
+            .map_err(|e| format!("Clipboard unavailable: {}; {}", system_error, e))
+    }
+
+    fn copy_to_system(&mut self, text: &str) -> Result<(), String> {
+        if self.system.is_none() {
+            self.system = Some(arboard::Clipboard::new().map_err(|e| e.to_string())?);
+        }
+        let Some(system) = self.system.as_mut() else {
+            return Err("no system clipboard".to_string());
+        };
+        let result = system.set_text(text).map_err(|e| e.to_string());
+        if result.is_err() {
+            // Open it afresh next time, e.g. after the display went away
+            self.system = None;
+        }
+        result
+    }
+}
+
+/// The OSC 52 sequence that sets the terminal's clipboard to `text`.
+pub fn osc52(text: &str) -> Result<String, String> {
+    let encoded = base64::engine::general_purpose::STANDARD.encode(text);
+    if encoded.len() > MAX_OSC52_BYTES {
+        return Err(format!(
+            "{} bytes is too much for the terminal clipboard",
+            text.len()
+        ));
+    }
+    Ok(format!("\x1b]52;c;{}\x07", encoded))
+}
+
+fn write_osc52<W: Write>(out: &mut W, text: &str) -> Result<(), String> {
+    let sequence = osc52(text)?;
+    out.write_all(sequence.as_bytes())
+        .and_then(|_| out.flush())
+        .map_err(|e| format!("terminal copy failed: {}", e))
+}
+
+/// What a status message says about copied text: its first line, cut
+/// short, and how many more lines there were.
+pub fn preview(text: &str) -> String {
+    const WIDTH: usize = 40;
+    let mut lines = text.lines();
+    let first = lines.next().unwrap_or("");
+    let mut shown: String = first.chars().take(WIDTH).collect();
+    if first.chars().count() > WIDTH {
+        shown.push('\u{2026}');
+    }
+    match lines.count() {
+        0 => shown,
+        1 => format!("{} (+1 line)", shown),
+        more => format!("{} (+{} lines)", shown, more),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_osc52_sequence_and_limit() {
+        assert_eq!(osc52("hi").unwrap(), "\x1b]52;c;aGk=\x07");
+        let mut out = Vec::new();
+        write_osc52(&mut out, "uid=ada").unwrap();
+        assert_eq!(out, b"\x1b]52;c;dWlkPWFkYQ==\x07");
+
+        let big = "x".repeat(MAX_OSC52_BYTES);
+        assert!(osc52(&big).unwrap_err().contains("too much"));
+    }
+
+    #[test]
+    fn test_preview_counts_lines_and_cuts_on_characters() {
+        assert_eq!(preview("cn=a"), "cn=a");
+        assert_eq!(preview("a\nb\nc"), "a (+2 lines)");
+        let long = "é".repeat(50);
+        assert_eq!(preview(&long), format!("{}\u{2026}", "é".repeat(40)));
+    }
+}
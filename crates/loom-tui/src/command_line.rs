@@ -0,0 +1,515 @@
+//! The `:` command line: parsing a typed command into a [`ColonCommand`],
+//! completing command names and their arguments, and suggesting the
+//! closest name when a word is not recognised.
+
+use std::fmt;
+
+/// A command the `:` line accepts, with the usage shown when it is typed
+/// wrong and a one-line summary.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub usage: &'static str,
+    pub summary: &'static str,
+}
+
+/// Every `:` command, in the order completion offers them.
+pub const COMMANDS: &[CommandSpec] = &[
+    CommandSpec {
+        name: "apply",
+        usage: ":apply [path]",
+        summary: "Apply an LDIF changefile",
+    },
+    CommandSpec {
+        name: "base",
+        usage: ":base <dn>",
+        summary: "Browse and search from another base DN",
+    },
+    CommandSpec {
+        name: "children",
+        usage: ":children [filter] [sort=attr]",
+        summary: "Filter or sort the selected container",
+    },
+    CommandSpec {
+        name: "connect",
+        usage: ":connect <profile>",
+        summary: "Connect to a saved profile",
+    },
+    CommandSpec {
+        name: "delete",
+        usage: ":delete [dn]",
+        summary: "Delete the selected entry",
+    },
+    CommandSpec {
+        name: "doctor",
+        usage: ":doctor",
+        summary: "Check the setup and connections",
+    },
+    CommandSpec {
+        name: "export",
+        usage: ":export <path> [attr,attr...]",
+        summary: "Export the subtree under the base DN",
+    },
+    CommandSpec {
+        name: "layout",
+        usage: ":layout reset",
+        summary: "Restore the default pane sizes",
+    },
+    CommandSpec {
+        name: "messages",
+        usage: ":messages",
+        summary: "Show earlier status messages",
+    },
+    CommandSpec {
+        name: "migrate",
+        usage: ":migrate",
+        summary: "Move values between attributes of the search results",
+    },
+    CommandSpec {
+        name: "reload-config",
+        usage: ":reload-config",
+        summary: "Read the config file again and apply what changed",
+    },
+    CommandSpec {
+        name: "search",
+        usage: ":search <filter>",
+        summary: "Search from the base DN",
+    },
+    CommandSpec {
+        name: "set",
+        usage: ":set <setting> <value>",
+        summary: "Change a setting for this session",
+    },
+    CommandSpec {
+        name: "set-save",
+        usage: ":set-save",
+        summary: "Write the settings changed with :set to the config file",
+    },
+    CommandSpec {
+        name: "setup",
+        usage: ":setup",
+        summary: "Run the setup wizard",
+    },
+];
+
+/// Settings `:set` changes for the active connection.
+pub const SETTINGS: &[&str] = &["page_size", "write"];
+
+/// Everything `:set` changes: the connection's settings, then the
+/// config file's.
+pub fn setting_names() -> impl Iterator<Item = &'static str> {
+    SETTINGS
+        .iter()
+        .copied()
+        .chain(crate::settings::SETTINGS.iter().map(|s| s.name))
+}
+
+/// A parsed `:` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ColonCommand {
+    Apply(Option<String>),
+    Base(String),
+    /// The arguments as typed, parsed as a child query by the tree.
+    Children(String),
+    Connect(String),
+    /// The entry to delete; the selected one when `None`.
+    Delete(Option<String>),
+    Doctor,
+    Export {
+        path: String,
+        attributes: Vec<String>,
+    },
+    LayoutReset,
+    Messages,
+    Migrate,
+    ReloadConfig,
+    Search(String),
+    Set {
+        key: String,
+        value: String,
+    },
+    SetSave,
+    Setup,
+}
+
+/// Why a line did not parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandError {
+    Empty,
+    Unknown {
+        name: String,
+        suggestion: Option<&'static str>,
+    },
+    /// Arguments missing, or given to a command that takes none.
+    Usage(&'static CommandSpec),
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::Empty => write!(f, "No command given"),
+            CommandError::Unknown {
+                name,
+                suggestion: Some(suggestion),
+            } => write!(
+                f,
+                "Unknown command :{}; did you mean :{}?",
+                name, suggestion
+            ),
+            CommandError::Unknown { name, .. } => write!(f, "Unknown command :{}", name),
+            CommandError::Usage(spec) => write!(f, "Usage: {}", spec.usage),
+        }
+    }
+}
+
+fn spec(name: &str) -> &'static CommandSpec {
+    COMMANDS
+        .iter()
+        .find(|c| c.name == name)
+        .expect("command is listed")
+}
+
+/// Parse a command line, with or without its leading `:`.
+pub fn parse(line: &str) -> Result<ColonCommand, CommandError> {
+    let line = line.trim();
+    let line = line.strip_prefix(':').unwrap_or(line).trim_start();
+    let (name, rest) = match line.split_once(char::is_whitespace) {
+        Some((name, rest)) => (name, rest.trim()),
+        None => (line, ""),
+    };
+    if name.is_empty() {
+        return Err(CommandError::Empty);
+    }
+    let name = name.to_ascii_lowercase();
+    let usage = || CommandError::Usage(spec(&name));
+    let no_arguments = |command: ColonCommand| {
+        if rest.is_empty() {
+            Ok(command)
+        } else {
+            Err(usage())
+        }
+    };
+
+    match name.as_str() {
+        "apply" => Ok(ColonCommand::Apply(
+            (!rest.is_empty()).then(|| unquote(rest).to_string()),
+        )),
+        "base" if !rest.is_empty() => Ok(ColonCommand::Base(rest.to_string())),
+        "children" => Ok(ColonCommand::Children(rest.to_string())),
+        "connect" if !rest.is_empty() => Ok(ColonCommand::Connect(unquote(rest).to_string())),
+        "delete" => Ok(ColonCommand::Delete(
+            (!rest.is_empty()).then(|| rest.to_string()),
+        )),
+        "doctor" => no_arguments(ColonCommand::Doctor),
+        "export" => {
+            let mut words = split_words(rest).into_iter();
+            let path = words.next().ok_or_else(usage)?;
+            let mut attributes: Vec<String> = words
+                .flat_map(|word| {
+                    word.split(',')
+                        .map(str::trim)
+                        .filter(|a| !a.is_empty())
+                        .map(str::to_string)
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            if attributes.is_empty() {
+                attributes.push("*".to_string());
+            }
+            Ok(ColonCommand::Export { path, attributes })
+        }
+        "layout" if rest.eq_ignore_ascii_case("reset") => Ok(ColonCommand::LayoutReset),
+        "messages" => no_arguments(ColonCommand::Messages),
+        "migrate" => no_arguments(ColonCommand::Migrate),
+        "reload-config" => no_arguments(ColonCommand::ReloadConfig),
+        "search" if !rest.is_empty() => Ok(ColonCommand::Search(rest.to_string())),
+        "set" => match rest.split_once(char::is_whitespace) {
+            Some((key, value)) if !value.trim().is_empty() => Ok(ColonCommand::Set {
+                key: key.to_ascii_lowercase(),
+                value: unquote(value.trim()).to_string(),
+            }),
+            _ => Err(usage()),
+        },
+        "set-save" => no_arguments(ColonCommand::SetSave),
+        "setup" => no_arguments(ColonCommand::Setup),
+        _ if COMMANDS.iter().any(|c| c.name == name) => Err(usage()),
+        _ => Err(CommandError::Unknown {
+            suggestion: did_you_mean(&name, COMMANDS.iter().map(|c| c.name)),
+            name,
+        }),
+    }
+}
+
+/// Strip one pair of matching quotes around `text`.
+fn unquote(text: &str) -> &str {
+    for quote in ['"', '\''] {
+        if let Some(inner) = text.strip_prefix(quote).and_then(|t| t.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    text
+}
+
+/// Split on whitespace, keeping quoted words (for paths with spaces) whole.
+fn split_words(text: &str) -> Vec<String> {
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quote = None;
+    let mut started = false;
+    for c in text.chars() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => word.push(c),
+            (None, '"' | '\'') => {
+                quote = Some(c);
+                started = true;
+            }
+            (None, c) if c.is_whitespace() => {
+                if started {
+                    words.push(std::mem::take(&mut word));
+                    started = false;
+                }
+            }
+            (None, c) => {
+                word.push(c);
+                started = true;
+            }
+        }
+    }
+    if started {
+        words.push(word);
+    }
+    words
+}
+
+/// The candidate closest to `word`, if it is close enough to be the one
+/// meant: a few letters off, or `word` cut short.
+pub fn did_you_mean<'a>(
+    word: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Option<&'a str> {
+    let word = word.to_lowercase();
+    let allowed = (word.chars().count() / 3).clamp(1, 3);
+    candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let lower = candidate.to_lowercase();
+            let distance = if lower.starts_with(&word) {
+                0
+            } else {
+                edit_distance(&word, &lower)
+            };
+            (distance <= allowed).then_some((distance, candidate))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two strings, by characters.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Completions for the word being typed: where it starts in the line,
+/// what could replace it, and what to type after one is accepted.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Completion {
+    pub start: usize,
+    pub candidates: Vec<String>,
+    pub suffix: &'static str,
+}
+
+/// Complete the end of `line` (the text before the cursor, without the
+/// leading `:`): command names, profile names for `:connect`, attribute
+/// names after the path of `:export`, and settings for `:set`.
+pub fn complete(line: &str, profiles: &[String], attributes: &[String]) -> Option<Completion> {
+    let Some(name_end) = line.find(char::is_whitespace) else {
+        let names = COMMANDS.iter().map(|c| c.name);
+        return completion(0, line, names, " ");
+    };
+    let name = line[..name_end].to_ascii_lowercase();
+    let args_start = name_end + (line[name_end..].len() - line[name_end..].trim_start().len());
+    let args = &line[args_start..];
+
+    match name.as_str() {
+        "connect" => completion(args_start, args, profiles.iter().map(String::as_str), ""),
+        "layout" => completion(args_start, args, ["reset"], ""),
+        "set" if !args.contains(char::is_whitespace) => {
+            completion(args_start, args, setting_names(), " ")
+        }
+        // Attributes follow the path, separated by commas or spaces
+        "export" if args.contains(char::is_whitespace) => {
+            let start = args_start
+                + args
+                    .rfind(|c: char| c == ',' || c.is_whitespace())
+                    .map_or(0, |i| i + 1);
+            let partial = &line[start..];
+            completion(start, partial, attributes.iter().map(String::as_str), "")
+        }
+        _ => None,
+    }
+}
+
+fn completion<'a>(
+    start: usize,
+    partial: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+    suffix: &'static str,
+) -> Option<Completion> {
+    let partial = partial.to_lowercase();
+    let mut candidates: Vec<String> = candidates
+        .into_iter()
+        .filter(|c| c.to_lowercase().starts_with(&partial))
+        .map(str::to_string)
+        .collect();
+    candidates.sort_by_key(|c| c.to_lowercase());
+    candidates.dedup();
+    candidates.truncate(50);
+    (!candidates.is_empty()).then_some(Completion {
+        start,
+        candidates,
+        suffix,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            parse(":export /tmp/out.csv cn,mail"),
+            Ok(ColonCommand::Export {
+                path: "/tmp/out.csv".to_string(),
+                attributes: vec!["cn".to_string(), "mail".to_string()],
+            })
+        );
+        assert_eq!(
+            parse("export \"/tmp/my file.ldif\" cn, mail sn"),
+            Ok(ColonCommand::Export {
+                path: "/tmp/my file.ldif".to_string(),
+                attributes: vec!["cn".to_string(), "mail".to_string(), "sn".to_string()],
+            })
+        );
+        assert_eq!(
+            parse(":export out.csv"),
+            Ok(ColonCommand::Export {
+                path: "out.csv".to_string(),
+                attributes: vec!["*".to_string()],
+            })
+        );
+        assert_eq!(
+            parse(":search (objectClass=person)"),
+            Ok(ColonCommand::Search("(objectClass=person)".to_string()))
+        );
+        assert_eq!(
+            parse(":base ou=People, dc=example,dc=com"),
+            Ok(ColonCommand::Base(
+                "ou=People, dc=example,dc=com".to_string()
+            ))
+        );
+        assert_eq!(
+            parse(":connect 'prod ldap'"),
+            Ok(ColonCommand::Connect("prod ldap".to_string()))
+        );
+        assert_eq!(parse(":delete"), Ok(ColonCommand::Delete(None)));
+        assert_eq!(
+            parse(":set page_size 1000"),
+            Ok(ColonCommand::Set {
+                key: "page_size".to_string(),
+                value: "1000".to_string(),
+            })
+        );
+        assert_eq!(parse(":set-save"), Ok(ColonCommand::SetSave));
+        assert_eq!(parse(":reload-config"), Ok(ColonCommand::ReloadConfig));
+        assert_eq!(parse("  :LAYOUT reset "), Ok(ColonCommand::LayoutReset));
+        assert_eq!(
+            parse(":children (sn=a*) sort=-cn"),
+            Ok(ColonCommand::Children("(sn=a*) sort=-cn".to_string()))
+        );
+        assert_eq!(parse(":apply"), Ok(ColonCommand::Apply(None)));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(parse(":"), Err(CommandError::Empty));
+        assert_eq!(parse(":search"), Err(CommandError::Usage(spec("search"))));
+        assert_eq!(
+            parse(":set page_size"),
+            Err(CommandError::Usage(spec("set")))
+        );
+        assert_eq!(
+            parse(":doctor now"),
+            Err(CommandError::Usage(spec("doctor")))
+        );
+        assert_eq!(parse(":layout"), Err(CommandError::Usage(spec("layout"))));
+        assert_eq!(
+            parse(":exprot out.csv").unwrap_err().to_string(),
+            "Unknown command :exprot; did you mean :export?"
+        );
+        assert_eq!(
+            parse(":frobnicate").unwrap_err().to_string(),
+            "Unknown command :frobnicate"
+        );
+        assert_eq!(
+            parse(":search").unwrap_err().to_string(),
+            "Usage: :search <filter>"
+        );
+    }
+
+    #[test]
+    fn test_did_you_mean() {
+        let names = ["connect", "children", "search", "set", "setup"];
+        assert_eq!(did_you_mean("conect", names), Some("connect"));
+        assert_eq!(did_you_mean("serach", names), Some("search"));
+        assert_eq!(did_you_mean("chil", names), Some("children"));
+        assert_eq!(did_you_mean("stup", names), Some("setup"));
+        assert_eq!(did_you_mean("xyz", names), None);
+        assert_eq!(did_you_mean("PROD", ["prod-ldap"]), Some("prod-ldap"));
+    }
+
+    #[test]
+    fn test_complete() {
+        let profiles = vec![
+            "prod-ldap".to_string(),
+            "Prod AD".to_string(),
+            "test".to_string(),
+        ];
+        let attributes = vec!["cn".to_string(), "mail".to_string(), "manager".to_string()];
+
+        let names = complete("se", &profiles, &attributes).unwrap();
+        assert_eq!(names.start, 0);
+        assert_eq!(names.candidates, vec!["search", "set", "set-save", "setup"]);
+        assert_eq!(names.suffix, " ");
+
+        let connect = complete("connect pro", &profiles, &attributes).unwrap();
+        assert_eq!(connect.start, 8);
+        assert_eq!(connect.candidates, vec!["Prod AD", "prod-ldap"]);
+
+        assert!(complete("export /tmp/out", &profiles, &attributes).is_none());
+        let export = complete("export /tmp/out.csv cn,ma", &profiles, &attributes).unwrap();
+        assert_eq!(export.start, 23);
+        assert_eq!(export.candidates, vec!["mail", "manager"]);
+
+        let set = complete("set pa", &profiles, &attributes).unwrap();
+        assert_eq!(set.candidates, vec!["page_size"]);
+        assert!(complete("set page_size 1", &profiles, &attributes).is_none());
+        assert!(complete("zz", &profiles, &attributes).is_none());
+    }
+}
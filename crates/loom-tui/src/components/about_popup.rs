@@ -50,8 +50,8 @@ impl AboutPopup {
         let block = Block::default()
             .title(" About ")
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -60,33 +60,39 @@ impl AboutPopup {
 
         let lines = vec![
             Line::from(""),
-            Line::from(Span::styled("  Loom LDAP Browser", self.theme.popup_title)),
-            Line::from(Span::styled(format!("  v{}", version), self.theme.dimmed)),
+            Line::from(Span::styled(
+                "  Loom LDAP Browser",
+                self.theme.popup_title(),
+            )),
+            Line::from(Span::styled(format!("  v{}", version), self.theme.dimmed())),
             Line::from(""),
             Line::from(Span::styled(
                 "  A terminal-based LDAP browser",
-                self.theme.normal,
+                self.theme.normal(),
             )),
             Line::from(""),
             Line::from(vec![
-                Span::styled("  Author:  ", self.theme.dimmed),
-                Span::styled("Jared Jennings", self.theme.normal),
+                Span::styled("  Author:  ", self.theme.dimmed()),
+                Span::styled("Jared Jennings", self.theme.normal()),
             ]),
             Line::from(vec![
-                Span::styled("  License: ", self.theme.dimmed),
-                Span::styled("GPL-3.0", self.theme.normal),
+                Span::styled("  License: ", self.theme.dimmed()),
+                Span::styled("GPL-3.0", self.theme.normal()),
             ]),
             Line::from(vec![
-                Span::styled("  Repo:    ", self.theme.dimmed),
-                Span::styled("github.com/trepidity/loom-ldapbrowser", self.theme.normal),
+                Span::styled("  Repo:    ", self.theme.dimmed()),
+                Span::styled("github.com/trepidity/loom-ldapbrowser", self.theme.normal()),
             ]),
             Line::from(""),
             Line::from(Span::styled(
                 "  Built with Rust, ratatui, and ldap3",
-                self.theme.dimmed,
+                self.theme.dimmed(),
             )),
             Line::from(""),
-            Line::from(Span::styled("  Press any key to close", self.theme.dimmed)),
+            Line::from(Span::styled(
+                "  Press any key to close",
+                self.theme.dimmed(),
+            )),
         ];
 
         frame.render_widget(Paragraph::new(lines), inner);
@@ -0,0 +1,191 @@
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use loom_core::activity::{OpEnd, OpId, Operation};
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Popup listing the operations outstanding on the active connection,
+/// with recently ended ones, so a stuck one can be cancelled.
+pub struct ActivityPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    ops: Vec<Operation>,
+    /// Selected operation, kept by id as the list refreshes.
+    selected: Option<OpId>,
+}
+
+impl ActivityPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Operations", theme.clone()).with_size(70, 50),
+            theme,
+            ops: Vec::new(),
+            selected: None,
+        }
+    }
+
+    pub fn show(&mut self, label: &str, ops: Vec<Operation>) {
+        self.popup =
+            Popup::new(format!("Operations: {}", label), self.theme.clone()).with_size(70, 50);
+        self.selected = None;
+        self.set_ops(ops);
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// Replace the list, keeping the selection on the same operation
+    /// while it is still listed.
+    pub fn set_ops(&mut self, ops: Vec<Operation>) {
+        self.ops = ops;
+        if !self
+            .selected
+            .is_some_and(|id| self.ops.iter().any(|op| op.id == id))
+        {
+            self.selected = self.ops.first().map(|op| op.id);
+        }
+    }
+
+    fn selected_index(&self) -> Option<usize> {
+        self.selected
+            .and_then(|id| self.ops.iter().position(|op| op.id == id))
+    }
+
+    fn select_index(&mut self, index: usize) {
+        if let Some(op) = self.ops.get(index) {
+            self.selected = Some(op.id);
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if let Some(i) = self.selected_index() {
+                    self.select_index(i + 1);
+                }
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                if let Some(i) = self.selected_index() {
+                    self.select_index(i.saturating_sub(1));
+                }
+                Action::None
+            }
+            KeyCode::Char('x') | KeyCode::Delete => {
+                match self
+                    .selected_index()
+                    .map(|i| &self.ops[i])
+                    .filter(|op| op.is_running())
+                {
+                    Some(op) => Action::CancelOperation(op.id),
+                    None => Action::None,
+                }
+            }
+            KeyCode::Char('X') => Action::CancelAllOperations,
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let now = Instant::now();
+        let lines: Vec<Line> = if self.ops.is_empty() {
+            vec![Line::from(Span::styled(
+                "Nothing running on this connection",
+                self.theme.dimmed(),
+            ))]
+        } else {
+            self.ops
+                .iter()
+                .map(|op| {
+                    let mut style = match op.ended {
+                        None => self.theme.normal(),
+                        Some((_, OpEnd::Finished)) => self.theme.dimmed(),
+                        Some((_, OpEnd::Cancelled)) => self.theme.warning(),
+                    };
+                    if self.selected == Some(op.id) {
+                        style = self.theme.selected().add_modifier(Modifier::BOLD);
+                    }
+                    Line::from(vec![
+                        Span::styled(format!(" {:>22} ", op.describe(now)), style),
+                        Span::styled(op.label.clone(), style),
+                    ])
+                })
+                .collect()
+        };
+        let scroll = self.selected_index().map_or(0, |i| {
+            i.saturating_sub(layout[0].height.saturating_sub(1) as usize)
+        });
+        frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), layout[0]);
+
+        let hints = Line::from(Span::styled(
+            "x:cancel  X:cancel all  \u{2191}/\u{2193}:select  Esc:close",
+            self.theme.dimmed(),
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use loom_core::activity::ActivityRegistry;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_cancel_selected_and_keep_selection() {
+        let registry = ActivityRegistry::default();
+        let search = registry.begin("Search (cn=*)");
+        let modify = registry.begin("Modify cn=alice");
+        let mut popup = ActivityPopup::new(Theme::load("dark"));
+        popup.show("prod", registry.list(Instant::now()));
+
+        popup.handle_key_event(key(KeyCode::Down));
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Char('x'))),
+            Action::CancelOperation(id) if id == modify.id()
+        ));
+
+        // The search finishes; the selection stays on the modify
+        drop(search);
+        popup.set_ops(registry.list(Instant::now()));
+        assert_eq!(popup.selected, Some(modify.id()));
+
+        // Ended operations cannot be cancelled again
+        drop(modify);
+        popup.set_ops(registry.list(Instant::now()));
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Char('x'))),
+            Action::None
+        ));
+    }
+}
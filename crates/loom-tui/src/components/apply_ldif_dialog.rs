@@ -0,0 +1,310 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use loom_core::changefile::{read_changefile, ApplyOptions, ChangefilePreview};
+use loom_core::transaction::Change;
+
+use crate::action::Action;
+use crate::components::path_input::PathInput;
+use crate::components::popup::Popup;
+use crate::paths::expand_path;
+use crate::theme::Theme;
+
+/// Apply an LDIF changefile to the active connection: pick the file and
+/// what to do on a failure, review what it holds, then follow the
+/// progress until the results are in.
+pub struct ApplyLdifDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    path: PathInput,
+    pub options: ApplyOptions,
+    /// Set once the file is read; the preview is showing.
+    changes: Option<Vec<Change>>,
+    /// Records done and the total while the run is going.
+    progress: Option<(usize, usize)>,
+}
+
+impl ApplyLdifDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Apply LDIF", theme.clone()).with_size(70, 50),
+            theme,
+            path: PathInput::default(),
+            options: ApplyOptions {
+                stop_on_error: true,
+                dry_run: false,
+            },
+            changes: None,
+            progress: None,
+        }
+    }
+
+    /// Open the form, with `path` filled in if given. The options are
+    /// kept from the last run.
+    pub fn show(&mut self, path: Option<String>) {
+        if let Some(path) = path {
+            self.path.set(path);
+        }
+        self.changes = None;
+        self.progress = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.changes = None;
+        self.progress = None;
+        self.popup.hide();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.progress.is_some()
+    }
+
+    pub fn set_progress(&mut self, done: usize, total: usize) {
+        if self.visible {
+            self.progress = Some((done, total));
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if self.is_running() {
+            return match key.code {
+                KeyCode::Esc => Action::ApplyLdifCancel,
+                _ => Action::None,
+            };
+        }
+        if self.changes.is_some() {
+            return self.handle_preview_key(key);
+        }
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::F(2) => {
+                self.options.stop_on_error = !self.options.stop_on_error;
+                Action::None
+            }
+            KeyCode::F(3) => {
+                self.options.dry_run = !self.options.dry_run;
+                Action::None
+            }
+            KeyCode::Enter => {
+                let raw = self.path.value().trim();
+                if raw.is_empty() {
+                    return Action::ErrorMessage("Filename is required".to_string());
+                }
+                let path = expand_path(raw);
+                match read_changefile(&path) {
+                    Ok(changes) if changes.is_empty() => {
+                        Action::ErrorMessage(format!("{} holds no records", path.display()))
+                    }
+                    Ok(changes) => {
+                        self.changes = Some(changes);
+                        Action::None
+                    }
+                    Err(e) => {
+                        Action::ErrorMessage(format!("Failed to read {}: {}", path.display(), e))
+                    }
+                }
+            }
+            _ => {
+                self.path.handle_key(key);
+                Action::None
+            }
+        }
+    }
+
+    fn handle_preview_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            // Back to the form to adjust the choices
+            KeyCode::Esc => {
+                self.changes = None;
+                Action::None
+            }
+            KeyCode::F(2) => {
+                self.options.stop_on_error = !self.options.stop_on_error;
+                Action::None
+            }
+            KeyCode::F(3) => {
+                self.options.dry_run = !self.options.dry_run;
+                Action::None
+            }
+            KeyCode::Enter => {
+                let Some(changes) = self.changes.clone() else {
+                    return Action::None;
+                };
+                self.progress = Some((0, changes.len()));
+                Action::ApplyLdifExecute {
+                    changes,
+                    options: self.options,
+                }
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let mut lines = vec![
+            Line::from(vec![
+                Span::styled("File: ", self.theme.header()),
+                Span::styled(self.path.value().to_string(), self.theme.normal()),
+                if self.changes.is_none() {
+                    Span::styled("_", self.theme.command_prompt())
+                } else {
+                    Span::raw("")
+                },
+            ]),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("On error: ", self.theme.header()),
+                Span::styled(
+                    if self.options.stop_on_error {
+                        "Stop at the first failure"
+                    } else {
+                        "Continue with the next record"
+                    },
+                    self.theme.success(),
+                ),
+                Span::styled("  (F2 to toggle)", self.theme.dimmed()),
+            ]),
+            Line::from(vec![
+                Span::styled("Dry run: ", self.theme.header()),
+                Span::styled(
+                    if self.options.dry_run {
+                        "Yes, only check the target DNs"
+                    } else {
+                        "No, write the changes"
+                    },
+                    self.theme.success(),
+                ),
+                Span::styled("  (F3 to toggle)", self.theme.dimmed()),
+            ]),
+            Line::from(""),
+        ];
+        let hint = match (&self.changes, self.progress) {
+            (_, Some((done, total))) => {
+                let verb = if self.options.dry_run {
+                    "Checked"
+                } else {
+                    "Applied"
+                };
+                lines.push(Line::from(Span::styled(
+                    format!("{} {} of {} records", verb, done, total),
+                    self.theme.normal(),
+                )));
+                "Esc:cancel"
+            }
+            (Some(changes), None) => {
+                lines.extend(self.preview_lines(changes));
+                "F2:on error  F3:dry run  Enter:apply  Esc:back"
+            }
+            (None, None) => "Tab:complete path  F2:on error  F3:dry run  Enter:preview  Esc:cancel",
+        };
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(hint, self.theme.dimmed()))),
+            layout[1],
+        );
+    }
+
+    fn preview_lines(&self, changes: &[Change]) -> Vec<Line<'static>> {
+        let preview = ChangefilePreview::of(changes);
+        let mut lines = vec![Line::from(Span::styled(
+            format!(
+                "{} records: {} add, {} modify, {} delete, {} modrdn",
+                preview.total(),
+                preview.adds,
+                preview.modifies,
+                preview.deletes,
+                preview.renames
+            ),
+            self.theme.header(),
+        ))];
+        for dn in &preview.first_dns {
+            lines.push(Line::from(Span::styled(
+                format!("  {}", dn),
+                self.theme.normal(),
+            )));
+        }
+        if preview.total() > preview.first_dns.len() {
+            lines.push(Line::from(Span::styled(
+                format!("  and {} more", preview.total() - preview.first_dns.len()),
+                self.theme.dimmed(),
+            )));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_preview_then_apply_with_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("changes.ldif");
+        std::fs::write(
+            &path,
+            "dn: uid=a,dc=example,dc=com\nchangetype: delete\n\n\
+             dn: uid=b,dc=example,dc=com\nchangetype: delete\n",
+        )
+        .unwrap();
+        let mut dialog = ApplyLdifDialog::new(Theme::load("dark"));
+        dialog.show(Some(path.display().to_string()));
+
+        // Continue past failures, dry run
+        dialog.handle_key_event(key(KeyCode::F(2)));
+        dialog.handle_key_event(key(KeyCode::F(3)));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::None
+        ));
+        assert_eq!(dialog.changes.as_ref().map(Vec::len), Some(2));
+
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::ApplyLdifExecute { changes, options } => {
+                assert_eq!(changes.len(), 2);
+                assert!(!options.stop_on_error);
+                assert!(options.dry_run);
+            }
+            other => panic!("expected ApplyLdifExecute, got {:?}", other),
+        }
+        assert!(dialog.is_running());
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Esc)),
+            Action::ApplyLdifCancel
+        ));
+    }
+
+    #[test]
+    fn test_unreadable_file_stays_on_form() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut dialog = ApplyLdifDialog::new(Theme::load("dark"));
+        dialog.show(Some(dir.path().join("missing.ldif").display().to_string()));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(ref m) if m.starts_with("Failed to read")
+        ));
+        assert!(dialog.changes.is_none());
+    }
+}
@@ -0,0 +1,306 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use loom_core::changefile::{ApplyReport, RecordResult};
+use loom_core::transaction::ChangeOutcome;
+
+use crate::action::Action;
+use crate::components::path_input::PathInput;
+use crate::components::popup::Popup;
+use crate::paths::expand_path;
+use crate::theme::Theme;
+
+/// Results of applying a changefile, one line per record with the server
+/// error for failures. `f` shows only the failures, `s` saves the report
+/// as CSV or JSON.
+pub struct ApplyResultsView {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    report: ApplyReport,
+    /// Indexes into the report's records that are showing.
+    rows: Vec<usize>,
+    failures_only: bool,
+    selected: usize,
+    /// File name being typed after `s`.
+    saving: Option<PathInput>,
+}
+
+impl ApplyResultsView {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Apply Results", theme.clone()).with_size(100, 100),
+            theme,
+            report: ApplyReport::default(),
+            rows: Vec::new(),
+            failures_only: false,
+            selected: 0,
+            saving: None,
+        }
+    }
+
+    pub fn show(&mut self, report: ApplyReport) {
+        self.report = report;
+        self.failures_only = false;
+        self.filter();
+        self.saving = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+        self.saving = None;
+    }
+
+    fn filter(&mut self) {
+        self.rows = self
+            .report
+            .records
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| !self.failures_only || matches!(r.outcome, ChangeOutcome::Failed(_)))
+            .map(|(i, _)| i)
+            .collect();
+        self.selected = 0;
+    }
+
+    fn selected_record(&self) -> Option<&RecordResult> {
+        self.rows
+            .get(self.selected)
+            .map(|&i| &self.report.records[i])
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if let Some(input) = self.saving.as_mut() {
+            return match key.code {
+                KeyCode::Esc => {
+                    self.saving = None;
+                    Action::None
+                }
+                KeyCode::Enter => self.save(),
+                _ => {
+                    input.handle_key(key);
+                    Action::None
+                }
+            };
+        }
+        let last = self.rows.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(last);
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::PageDown => {
+                self.selected = (self.selected + 10).min(last);
+                Action::None
+            }
+            KeyCode::PageUp => {
+                self.selected = self.selected.saturating_sub(10);
+                Action::None
+            }
+            KeyCode::Char('g') | KeyCode::Home => {
+                self.selected = 0;
+                Action::None
+            }
+            KeyCode::Char('G') | KeyCode::End => {
+                self.selected = last;
+                Action::None
+            }
+            KeyCode::Char('f') => {
+                self.failures_only = !self.failures_only;
+                self.filter();
+                Action::None
+            }
+            KeyCode::Char('c') | KeyCode::Char('y') => match self.selected_record() {
+                Some(record) => Action::CopyToClipboard(record.dn.clone()),
+                None => Action::None,
+            },
+            KeyCode::Char('s') if !self.report.records.is_empty() => {
+                self.saving = Some(PathInput::new("apply-report.csv"));
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Write the report to the typed file.
+    fn save(&mut self) -> Action {
+        let Some(input) = self.saving.as_ref() else {
+            return Action::None;
+        };
+        if input.value().trim().is_empty() {
+            return Action::ErrorMessage("Filename is required".to_string());
+        }
+        let path = expand_path(input.value().trim());
+        match self.report.export(&path) {
+            Ok(rows) => {
+                self.saving = None;
+                Action::StatusMessage(format!("Saved {} report rows to {}", rows, path.display()))
+            }
+            Err(e) => Action::ErrorMessage(format!("Failed to write {}: {}", path.display(), e)),
+        }
+    }
+
+    fn row_item(&self, record: &RecordResult) -> ListItem<'static> {
+        let (mark, style) = match record.outcome {
+            ChangeOutcome::Applied => ("\u{2713} ", self.theme.success()),
+            ChangeOutcome::Failed(_) => ("\u{2717} ", self.theme.error()),
+            ChangeOutcome::RolledBack | ChangeOutcome::NotAttempted => ("- ", self.theme.dimmed()),
+        };
+        let mut spans = vec![
+            Span::styled(mark, style),
+            Span::styled(record.dn.clone(), self.theme.normal()),
+            Span::styled(format!("  {}", record.change), self.theme.dimmed()),
+        ];
+        match &record.outcome {
+            ChangeOutcome::Failed(e) => {
+                spans.push(Span::styled(format!("  {}", e), self.theme.error()))
+            }
+            ChangeOutcome::NotAttempted => {
+                spans.push(Span::styled("  not attempted", self.theme.dimmed()))
+            }
+            _ => {}
+        }
+        ListItem::new(Line::from(spans))
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        let headline_style = if self.report.failed() == 0 {
+            self.theme.success()
+        } else {
+            self.theme.warning()
+        };
+        let mut headline = vec![Span::styled(self.report.summary(), headline_style)];
+        if self.report.dry_run {
+            headline.push(Span::styled("  (dry run)", self.theme.dimmed()));
+        }
+        if self.failures_only {
+            headline.push(Span::styled("  showing failures", self.theme.dimmed()));
+        }
+        frame.render_widget(Paragraph::new(Line::from(headline)), layout[0]);
+
+        if self.rows.is_empty() {
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(
+                    "No records failed.",
+                    self.theme.success(),
+                ))),
+                layout[1],
+            );
+        } else {
+            let items: Vec<ListItem> = self
+                .rows
+                .iter()
+                .map(|&i| self.row_item(&self.report.records[i]))
+                .collect();
+            let mut state = ListState::default().with_selected(Some(self.selected));
+            frame.render_stateful_widget(
+                List::new(items).highlight_style(self.theme.selected()),
+                layout[1],
+                &mut state,
+            );
+        }
+
+        let hint = match &self.saving {
+            Some(input) => Line::from(vec![
+                Span::styled("Save to: ", self.theme.header()),
+                Span::styled(input.value(), self.theme.normal()),
+                Span::styled("_", self.theme.command_prompt()),
+                Span::styled(
+                    "  .csv, .json or .jsonl  Enter:save  Esc:cancel",
+                    self.theme.dimmed(),
+                ),
+            ]),
+            None => Line::from(Span::styled(
+                "\u{2191}/\u{2193}:select  f:failures only  c:copy DN  s:save report  Esc:close",
+                self.theme.dimmed(),
+            )),
+        };
+        frame.render_widget(Paragraph::new(hint), layout[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    fn report() -> ApplyReport {
+        let record = |uid: &str, outcome| RecordResult {
+            dn: format!("uid={},dc=example,dc=com", uid),
+            change: "delete".to_string(),
+            outcome,
+        };
+        ApplyReport {
+            records: vec![
+                record("a", ChangeOutcome::Applied),
+                record("b", ChangeOutcome::Failed("no such object".to_string())),
+                record("c", ChangeOutcome::NotAttempted),
+            ],
+            dry_run: false,
+        }
+    }
+
+    #[test]
+    fn test_failures_only_and_copy() {
+        let mut view = ApplyResultsView::new(Theme::load("dark"));
+        view.show(report());
+        assert_eq!(view.rows.len(), 3);
+
+        view.handle_key_event(key(KeyCode::Char('f')));
+        assert_eq!(view.rows, vec![1]);
+        assert!(matches!(
+            view.handle_key_event(key(KeyCode::Char('c'))),
+            Action::CopyToClipboard(ref dn) if dn == "uid=b,dc=example,dc=com"
+        ));
+    }
+
+    #[test]
+    fn test_saves_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut view = ApplyResultsView::new(Theme::load("dark"));
+        view.show(report());
+
+        view.handle_key_event(key(KeyCode::Char('s')));
+        let path = dir.path().join("report.csv");
+        view.saving
+            .as_mut()
+            .unwrap()
+            .set(path.display().to_string());
+        assert!(matches!(
+            view.handle_key_event(key(KeyCode::Enter)),
+            Action::StatusMessage(ref m) if m.starts_with("Saved 3 report rows")
+        ));
+        let csv = std::fs::read_to_string(&path).unwrap();
+        assert!(csv.contains("failed,no such object"));
+    }
+}
@@ -6,7 +6,11 @@ use ratatui::Frame;
 
 use crate::action::Action;
 use crate::theme::Theme;
+use loom_core::bulk::BulkMod;
 use loom_core::entry::LdapEntry;
+use loom_core::filter::escape_filter_value as ldap_escape;
+use loom_core::modify::replace_value_mods;
+use loom_core::transaction::Change;
 
 /// Edit mode for an attribute value.
 #[derive(Debug, Clone)]
@@ -24,6 +28,29 @@ pub struct EditResult {
     pub new_value: String,
 }
 
+impl EditResult {
+    /// The modify request the edit sends, for queueing it instead.
+    pub fn change(&self) -> Change {
+        let modification = match &self.op {
+            EditOp::Replace { attr, old_value } => {
+                return Change::Modify {
+                    dn: self.dn.clone(),
+                    mods: replace_value_mods(attr, old_value, &self.new_value),
+                };
+            }
+            EditOp::Add { attr } => BulkMod::AddValue {
+                attr: attr.clone(),
+                value: self.new_value.clone(),
+            },
+            EditOp::Delete { attr, value } => BulkMod::DeleteValue {
+                attr: attr.clone(),
+                value: value.clone(),
+            },
+        };
+        Change::modify(&self.dn, &[modification])
+    }
+}
+
 /// Which part of the editor has focus.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum EditorFocus {
@@ -448,8 +475,8 @@ impl AttributeEditor {
         let block = Block::default()
             .title(title)
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -463,8 +490,8 @@ impl AttributeEditor {
 
         // DN line
         let dn_line = Line::from(vec![
-            Span::styled("DN: ", self.theme.header),
-            Span::styled(&self.dn, self.theme.dimmed),
+            Span::styled("DN: ", self.theme.header()),
+            Span::styled(&self.dn, self.theme.dimmed()),
         ]);
         frame.render_widget(Paragraph::new(dn_line), layout[0]);
 
@@ -474,7 +501,7 @@ impl AttributeEditor {
         // Hint
         let hint = Line::from(Span::styled(
             "Enter: save  Esc: cancel  Ctrl+Space: DN search",
-            self.theme.dimmed,
+            self.theme.dimmed(),
         ));
         frame.render_widget(Paragraph::new(hint), layout[2]);
     }
@@ -495,8 +522,8 @@ impl AttributeEditor {
         let block = Block::default()
             .title(title)
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -511,8 +538,8 @@ impl AttributeEditor {
 
         // DN line
         let dn_line = Line::from(vec![
-            Span::styled("DN: ", self.theme.header),
-            Span::styled(&self.dn, self.theme.dimmed),
+            Span::styled("DN: ", self.theme.header()),
+            Span::styled(&self.dn, self.theme.dimmed()),
         ]);
         frame.render_widget(Paragraph::new(dn_line), layout[0]);
 
@@ -524,7 +551,7 @@ impl AttributeEditor {
         self.render_input_line(frame, input_parts[0]);
 
         if self.searching {
-            let spinner = Span::styled("Searching...", self.theme.dimmed);
+            let spinner = Span::styled("Searching...", self.theme.dimmed());
             frame.render_widget(Paragraph::new(Line::from(spinner)), input_parts[1]);
         }
 
@@ -538,7 +565,7 @@ impl AttributeEditor {
             } else {
                 "No results"
             };
-            let empty = Paragraph::new(Line::from(Span::styled(empty_msg, self.theme.dimmed)));
+            let empty = Paragraph::new(Line::from(Span::styled(empty_msg, self.theme.dimmed())));
             frame.render_widget(empty, results_area);
         } else {
             let items: Vec<ListItem> = self
@@ -569,11 +596,11 @@ impl AttributeEditor {
                     let style = if Some(idx) == self.result_state.selected()
                         && self.focus == EditorFocus::Results
                     {
-                        self.theme.selected
+                        self.theme.selected()
                     } else if is_selected {
-                        self.theme.header
+                        self.theme.header()
                     } else {
-                        self.theme.normal
+                        self.theme.normal()
                     };
 
                     ListItem::new(Line::from(Span::styled(
@@ -598,19 +625,19 @@ impl AttributeEditor {
         } else {
             "Enter: save  Tab: results  Esc: cancel".to_string()
         };
-        let hint = Line::from(Span::styled(hint_text, self.theme.dimmed));
+        let hint = Line::from(Span::styled(hint_text, self.theme.dimmed()));
         frame.render_widget(Paragraph::new(hint), layout[3]);
     }
 
     fn render_input_line(&self, frame: &mut Frame, area: Rect) {
         let (before_cursor, after_cursor) = self.input_buffer.split_at(self.cursor_pos);
         let cursor_style = if self.focus == EditorFocus::Input {
-            self.theme.selected
+            self.theme.selected()
         } else {
-            self.theme.normal
+            self.theme.normal()
         };
         let input_line = Line::from(vec![
-            Span::styled(before_cursor, self.theme.normal),
+            Span::styled(before_cursor, self.theme.normal()),
             Span::styled(
                 if after_cursor.is_empty() {
                     "_"
@@ -625,7 +652,7 @@ impl AttributeEditor {
                 } else {
                     ""
                 },
-                self.theme.normal,
+                self.theme.normal(),
             ),
         ]);
         frame.render_widget(Paragraph::new(input_line), area);
@@ -677,22 +704,6 @@ pub fn build_dn_search_filter(input: &str) -> String {
     )
 }
 
-/// Escape special characters for LDAP filter values per RFC 4515.
-fn ldap_escape(input: &str) -> String {
-    let mut out = String::with_capacity(input.len());
-    for c in input.chars() {
-        match c {
-            '*' => out.push_str("\\2a"),
-            '(' => out.push_str("\\28"),
-            ')' => out.push_str("\\29"),
-            '\\' => out.push_str("\\5c"),
-            '\0' => out.push_str("\\00"),
-            _ => out.push(c),
-        }
-    }
-    out
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -210,8 +210,8 @@ impl AttributePicker {
         let block = Block::default()
             .title(" Add Attribute ")
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -231,27 +231,27 @@ impl AttributePicker {
         // Input line with cursor
         let (before, after) = self.input.split_at(self.cursor);
         let cursor_style = if self.focus == PickerFocus::Input {
-            self.theme.selected
+            self.theme.selected()
         } else {
-            self.theme.normal
+            self.theme.normal()
         };
         let input_line = Line::from(vec![
-            Span::styled("> ", self.theme.header),
-            Span::styled(before, self.theme.normal),
+            Span::styled("> ", self.theme.header()),
+            Span::styled(before, self.theme.normal()),
             Span::styled(
                 if after.is_empty() { "_" } else { &after[..1] },
                 cursor_style,
             ),
             Span::styled(
                 if after.len() > 1 { &after[1..] } else { "" },
-                self.theme.normal,
+                self.theme.normal(),
             ),
         ]);
         frame.render_widget(Paragraph::new(input_line), layout[0]);
 
         // Match count / freeform hint
         let count_line = if self.all_items.is_empty() {
-            Line::from(Span::styled("Type an attribute name", self.theme.dimmed))
+            Line::from(Span::styled("Type an attribute name", self.theme.dimmed()))
         } else {
             Line::from(Span::styled(
                 format!(
@@ -259,7 +259,7 @@ impl AttributePicker {
                     self.filtered.len(),
                     self.all_items.len()
                 ),
-                self.theme.dimmed,
+                self.theme.dimmed(),
             ))
         };
         frame.render_widget(Paragraph::new(count_line), layout[1]);
@@ -287,14 +287,14 @@ impl AttributePicker {
                 let is_highlighted =
                     actual_idx == self.selected_idx && self.focus == PickerFocus::List;
                 let style = if is_highlighted {
-                    self.theme.selected
+                    self.theme.selected()
                 } else {
-                    self.theme.normal
+                    self.theme.normal()
                 };
                 let syntax_style = if is_highlighted {
-                    self.theme.selected
+                    self.theme.selected()
                 } else {
-                    self.theme.dimmed
+                    self.theme.dimmed()
                 };
                 ListItem::new(Line::from(vec![
                     Span::styled(name, style),
@@ -311,7 +311,10 @@ impl AttributePicker {
         frame.render_stateful_widget(list, list_area, &mut list_state);
 
         // Hints
-        let hint = Line::from(Span::styled("Enter:Select  Esc:Cancel", self.theme.dimmed));
+        let hint = Line::from(Span::styled(
+            "Enter:Select  Esc:Cancel",
+            self.theme.dimmed(),
+        ));
         frame.render_widget(Paragraph::new(hint), layout[3]);
     }
 }
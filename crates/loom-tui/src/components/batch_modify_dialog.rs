@@ -0,0 +1,508 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::path_input::PathInput;
+use crate::components::popup::{DiscardGuard, Dismiss, OverwriteGuard, Popup};
+use crate::paths::{expand_path, state_dir};
+use crate::theme::Theme;
+use loom_core::bulk::{BatchChange, BulkMod};
+
+/// Which field is currently being edited.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BatchField {
+    Attribute,
+    Value,
+    Changefile,
+}
+
+/// What the batch does to each selected entry.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BatchOp {
+    AddValue,
+    ReplaceAll,
+    DeleteValue,
+    DeleteAttribute,
+    /// Add the entry's DN to a group's member attribute.
+    AddToGroup,
+}
+
+impl BatchOp {
+    fn label(&self) -> &'static str {
+        match self {
+            BatchOp::AddValue => "Add value",
+            BatchOp::ReplaceAll => "Replace all values",
+            BatchOp::DeleteValue => "Delete value",
+            BatchOp::DeleteAttribute => "Delete attribute",
+            BatchOp::AddToGroup => "Add to group",
+        }
+    }
+
+    fn next(&self) -> Self {
+        match self {
+            BatchOp::AddValue => BatchOp::ReplaceAll,
+            BatchOp::ReplaceAll => BatchOp::DeleteValue,
+            BatchOp::DeleteValue => BatchOp::DeleteAttribute,
+            BatchOp::DeleteAttribute => BatchOp::AddToGroup,
+            BatchOp::AddToGroup => BatchOp::AddValue,
+        }
+    }
+
+    fn takes_value(&self) -> bool {
+        *self != BatchOp::DeleteAttribute
+    }
+}
+
+/// Dialog applying one modification to every selected search result, or
+/// writing it to an LDIF changefile as a dry run.
+pub struct BatchModifyDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    active_field: BatchField,
+    /// The entries the batch applies to.
+    dns: Vec<String>,
+    pub attribute: String,
+    pub value: String,
+    pub op: BatchOp,
+    /// Write the changes to `changefile` instead of the server.
+    pub dry_run: bool,
+    changefile: PathInput,
+    guard: DiscardGuard,
+    overwrite: OverwriteGuard,
+}
+
+impl BatchModifyDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Batch Modify", theme.clone()).with_size(60, 50),
+            theme,
+            active_field: BatchField::Attribute,
+            dns: Vec::new(),
+            attribute: String::new(),
+            value: String::new(),
+            op: BatchOp::AddValue,
+            dry_run: false,
+            changefile: PathInput::default(),
+            guard: DiscardGuard::new(true),
+            overwrite: OverwriteGuard::default(),
+        }
+    }
+
+    /// Ask before Esc throws away what was typed.
+    pub fn with_confirm_discard(mut self, enabled: bool) -> Self {
+        self.guard = DiscardGuard::new(enabled);
+        self
+    }
+
+    fn is_dirty(&self) -> bool {
+        !self.attribute.is_empty() || !self.value.is_empty()
+    }
+
+    /// Open for the entries in `dns`.
+    pub fn show(&mut self, dns: Vec<String>) {
+        self.dns = dns;
+        self.attribute.clear();
+        self.value.clear();
+        self.op = BatchOp::AddValue;
+        self.dry_run = false;
+        self.changefile
+            .set(state_dir().join("batch-changes.ldif").display().to_string());
+        self.active_field = BatchField::Attribute;
+        self.guard.reset();
+        self.overwrite.reset();
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// The fields Tab moves through: the value only when the operation
+    /// takes one, the changefile only for a dry run.
+    fn fields(&self) -> Vec<BatchField> {
+        let mut fields = vec![BatchField::Attribute];
+        if self.op.takes_value() {
+            fields.push(BatchField::Value);
+        }
+        if self.dry_run {
+            fields.push(BatchField::Changefile);
+        }
+        fields
+    }
+
+    fn move_field(&mut self, forward: bool) {
+        let fields = self.fields();
+        let i = fields
+            .iter()
+            .position(|f| *f == self.active_field)
+            .unwrap_or(0);
+        let next = if forward {
+            (i + 1) % fields.len()
+        } else {
+            (i + fields.len() - 1) % fields.len()
+        };
+        self.active_field = fields[next];
+    }
+
+    /// Keep the active field one that is shown.
+    fn settle_field(&mut self) {
+        if !self.fields().contains(&self.active_field) {
+            self.active_field = BatchField::Attribute;
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if self.overwrite.is_asking() {
+            if self.overwrite.confirm(&key) {
+                return self.submit(true);
+            }
+            return Action::None;
+        }
+        match self.guard.check(&key, true, true, self.is_dirty()) {
+            Dismiss::Close => {
+                self.hide();
+                return Action::ClosePopup;
+            }
+            Dismiss::Stay | Dismiss::Back => return Action::None,
+            Dismiss::Pass => {}
+        }
+        match key.code {
+            KeyCode::Tab
+                if self.active_field == BatchField::Changefile && self.changefile.complete() =>
+            {
+                Action::None
+            }
+            KeyCode::Tab => {
+                self.move_field(true);
+                Action::None
+            }
+            KeyCode::BackTab => {
+                self.move_field(false);
+                Action::None
+            }
+            KeyCode::F(2) => {
+                let was_group = self.op == BatchOp::AddToGroup;
+                self.op = self.op.next();
+                // Group membership usually lives in `member`
+                if self.op == BatchOp::AddToGroup && self.attribute.is_empty() {
+                    self.attribute = "member".to_string();
+                } else if was_group && self.attribute == "member" {
+                    self.attribute.clear();
+                }
+                self.settle_field();
+                Action::None
+            }
+            KeyCode::F(3) => {
+                self.dry_run = !self.dry_run;
+                self.settle_field();
+                Action::None
+            }
+            KeyCode::Enter => self.submit(false),
+            _ => {
+                match self.active_field {
+                    BatchField::Attribute => edit(&mut self.attribute, key),
+                    BatchField::Value => edit(&mut self.value, key),
+                    BatchField::Changefile => {
+                        self.changefile.handle_key(key);
+                    }
+                }
+                Action::None
+            }
+        }
+    }
+
+    /// Check the fields and hand the batch to the app, once `confirmed`
+    /// if a dry run would replace a file.
+    fn submit(&mut self, confirmed: bool) -> Action {
+        let attr = self.attribute.trim().to_string();
+        if attr.is_empty() {
+            return Action::ErrorMessage("Attribute is required".to_string());
+        }
+        let value = self.value.clone();
+        if self.op.takes_value() && value.is_empty() {
+            return Action::ErrorMessage(match self.op {
+                BatchOp::AddToGroup => "Group DN is required".to_string(),
+                _ => "Value is required".to_string(),
+            });
+        }
+        let change = match self.op {
+            BatchOp::AddValue => BatchChange::Entry(BulkMod::AddValue { attr, value }),
+            BatchOp::ReplaceAll => BatchChange::Entry(BulkMod::ReplaceAttribute { attr, value }),
+            BatchOp::DeleteValue => BatchChange::Entry(BulkMod::DeleteValue { attr, value }),
+            BatchOp::DeleteAttribute => BatchChange::Entry(BulkMod::DeleteAttribute { attr }),
+            BatchOp::AddToGroup => BatchChange::AddToGroup {
+                group_dn: value.trim().to_string(),
+                attr,
+            },
+        };
+
+        let changefile: Option<PathBuf> = if self.dry_run {
+            if self.changefile.value().trim().is_empty() {
+                return Action::ErrorMessage("Changefile is required".to_string());
+            }
+            let path = expand_path(self.changefile.value().trim());
+            if !confirmed && self.overwrite.needs_confirm(&path) {
+                return Action::None;
+            }
+            Some(path)
+        } else {
+            None
+        };
+
+        self.hide();
+        Action::BatchModifyExecute {
+            dns: std::mem::take(&mut self.dns),
+            change,
+            changefile,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+
+        // Layout: summary (4) | attribute (2) | value (2) | changefile (2) | hints (flex)
+        let layout = Layout::vertical([
+            Constraint::Length(4),
+            Constraint::Length(2),
+            Constraint::Length(2),
+            Constraint::Length(2),
+            Constraint::Min(1),
+        ])
+        .split(inner);
+
+        let entries = if self.dns.len() == 1 {
+            "1 entry".to_string()
+        } else {
+            format!("{} entries", self.dns.len())
+        };
+        let summary = vec![
+            Line::from(vec![
+                Span::styled("Applies to: ", self.theme.header()),
+                Span::styled(entries, self.theme.normal()),
+            ]),
+            Line::from(vec![
+                Span::styled("Operation: ", self.theme.header()),
+                Span::styled(self.op.label(), self.theme.success()),
+                Span::styled("  (F2 to cycle)", self.theme.dimmed()),
+            ]),
+            Line::from(vec![
+                Span::styled("Dry run: ", self.theme.header()),
+                Span::styled(
+                    if self.dry_run {
+                        "write an LDIF changefile"
+                    } else {
+                        "off, modify the server"
+                    },
+                    self.theme.success(),
+                ),
+                Span::styled("  (F3 to toggle)", self.theme.dimmed()),
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(summary), layout[0]);
+
+        let attr_label = if self.op == BatchOp::AddToGroup {
+            "Group member attribute"
+        } else {
+            "Attribute"
+        };
+        self.render_field(
+            frame,
+            layout[1],
+            attr_label,
+            &self.attribute,
+            BatchField::Attribute,
+        );
+        if self.op.takes_value() {
+            let value_label = if self.op == BatchOp::AddToGroup {
+                "Group DN"
+            } else {
+                "Value"
+            };
+            self.render_field(
+                frame,
+                layout[2],
+                value_label,
+                &self.value,
+                BatchField::Value,
+            );
+        }
+        if self.dry_run {
+            self.render_field(
+                frame,
+                layout[3],
+                "Changefile",
+                self.changefile.value(),
+                BatchField::Changefile,
+            );
+        }
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "Tab:next field  F2:operation  F3:dry run  Enter:apply  Esc:cancel",
+            self.theme.dimmed(),
+        )));
+        frame.render_widget(hints, layout[4]);
+        self.overwrite.render(frame, inner, &self.theme);
+        self.guard.render(frame, inner, &self.theme);
+    }
+
+    fn render_field(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        label: &str,
+        value: &str,
+        field: BatchField,
+    ) {
+        let is_active = self.active_field == field;
+        let label_style = if is_active {
+            self.theme.header()
+        } else {
+            self.theme.dimmed()
+        };
+        let value_style = if is_active {
+            self.theme.normal()
+        } else {
+            self.theme.dimmed()
+        };
+
+        let lines = vec![
+            Line::from(Span::styled(format!("{}:", label), label_style)),
+            Line::from(vec![
+                Span::styled(value, value_style),
+                if is_active {
+                    Span::styled("_", self.theme.command_prompt())
+                } else {
+                    Span::raw("")
+                },
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
+
+fn edit(buf: &mut String, key: KeyEvent) {
+    match key.code {
+        KeyCode::Backspace => {
+            buf.pop();
+        }
+        KeyCode::Char(c) => buf.push(c),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    fn type_text(dialog: &mut BatchModifyDialog, text: &str) {
+        for c in text.chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    fn dns() -> Vec<String> {
+        vec![
+            "uid=alice,ou=People,dc=example,dc=com".to_string(),
+            "uid=bob,ou=People,dc=example,dc=com".to_string(),
+        ]
+    }
+
+    #[test]
+    fn test_replace_all_values() {
+        let mut dialog = BatchModifyDialog::new(Theme::load("dark"));
+        dialog.show(dns());
+        dialog.handle_key_event(key(KeyCode::F(2)));
+        assert_eq!(dialog.op, BatchOp::ReplaceAll);
+        type_text(&mut dialog, "telephoneNumber");
+        dialog.handle_key_event(key(KeyCode::Tab));
+        type_text(&mut dialog, "+1 555 0100");
+
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::BatchModifyExecute {
+                dns: selected,
+                change: BatchChange::Entry(BulkMod::ReplaceAttribute { attr, value }),
+                changefile: None,
+            } => {
+                assert_eq!(selected, dns());
+                assert_eq!(attr, "telephoneNumber");
+                assert_eq!(value, "+1 555 0100");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(!dialog.visible);
+    }
+
+    #[test]
+    fn test_add_to_group_dry_run() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut dialog = BatchModifyDialog::new(Theme::load("dark"));
+        dialog.show(dns());
+        for _ in 0..4 {
+            dialog.handle_key_event(key(KeyCode::F(2)));
+        }
+        assert_eq!(dialog.op, BatchOp::AddToGroup);
+        assert_eq!(dialog.attribute, "member");
+
+        // The value is required
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(ref m) if m.contains("Group DN")
+        ));
+        dialog.handle_key_event(key(KeyCode::Tab));
+        type_text(&mut dialog, "cn=Staff,ou=Groups,dc=example,dc=com");
+        dialog.handle_key_event(key(KeyCode::F(3)));
+        dialog.handle_key_event(key(KeyCode::Tab));
+        assert_eq!(dialog.active_field, BatchField::Changefile);
+        let path = dir.path().join("staff.ldif");
+        dialog.changefile.set(path.display().to_string());
+
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::BatchModifyExecute {
+                change: BatchChange::AddToGroup { group_dn, attr },
+                changefile: Some(file),
+                ..
+            } => {
+                assert_eq!(group_dn, "cn=Staff,ou=Groups,dc=example,dc=com");
+                assert_eq!(attr, "member");
+                assert_eq!(file, path);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_delete_attribute_skips_value() {
+        let mut dialog = BatchModifyDialog::new(Theme::load("dark"));
+        dialog.show(dns());
+        for _ in 0..3 {
+            dialog.handle_key_event(key(KeyCode::F(2)));
+        }
+        type_text(&mut dialog, "pager");
+        dialog.handle_key_event(key(KeyCode::Tab));
+        assert_eq!(dialog.active_field, BatchField::Attribute);
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::BatchModifyExecute {
+                change: BatchChange::Entry(BulkMod::DeleteAttribute { .. }),
+                ..
+            }
+        ));
+    }
+}
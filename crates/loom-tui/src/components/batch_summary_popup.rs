@@ -0,0 +1,253 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::path_input::PathInput;
+use crate::components::popup::Popup;
+use crate::paths::{expand_path, write_replacing};
+use crate::theme::Theme;
+
+/// Popup shown after a batch modify: how many entries succeeded and which
+/// failed with what error, with a shortcut to save the failures to a text
+/// file.
+pub struct BatchSummaryPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    succeeded: usize,
+    /// `(dn, error)` of each failed entry.
+    failures: Vec<(String, String)>,
+    /// First failure shown.
+    scroll: usize,
+    /// File name being typed after `s`.
+    saving: Option<PathInput>,
+}
+
+impl BatchSummaryPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Batch Modify", theme.clone()).with_size(70, 60),
+            theme,
+            succeeded: 0,
+            failures: Vec::new(),
+            scroll: 0,
+            saving: None,
+        }
+    }
+
+    pub fn show(&mut self, succeeded: usize, failures: Vec<(String, String)>) {
+        self.show_titled("Batch Modify", succeeded, failures);
+    }
+
+    /// Show the outcome of another multi-entry write, e.g. a delete.
+    pub fn show_titled(&mut self, title: &str, succeeded: usize, failures: Vec<(String, String)>) {
+        self.popup.title = title.to_string();
+        self.succeeded = succeeded;
+        self.failures = failures;
+        self.scroll = 0;
+        self.saving = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+        self.failures.clear();
+        self.saving = None;
+    }
+
+    /// E.g. `42 succeeded, 3 failed`.
+    pub fn headline(&self) -> String {
+        format!(
+            "{} succeeded, {} failed",
+            self.succeeded,
+            self.failures.len()
+        )
+    }
+
+    /// The failures as text, one `dn: error` per line.
+    fn failures_text(&self) -> String {
+        self.failures
+            .iter()
+            .map(|(dn, error)| format!("{}: {}\n", dn, error))
+            .collect()
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if let Some(input) = self.saving.as_mut() {
+            return match key.code {
+                KeyCode::Esc => {
+                    self.saving = None;
+                    Action::None
+                }
+                KeyCode::Enter => self.save(),
+                _ => {
+                    input.handle_key(key);
+                    Action::None
+                }
+            };
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll = self.scroll.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.scroll + 1 < self.failures.len() {
+                    self.scroll += 1;
+                }
+                Action::None
+            }
+            KeyCode::Char('s') if !self.failures.is_empty() => {
+                self.saving = Some(PathInput::new("batch-failures.txt"));
+                Action::None
+            }
+            KeyCode::Char('c') | KeyCode::Char('y') if !self.failures.is_empty() => {
+                Action::CopyToClipboard(self.failures_text())
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Write the failures to the typed file.
+    fn save(&mut self) -> Action {
+        let Some(input) = self.saving.as_ref() else {
+            return Action::None;
+        };
+        if input.value().trim().is_empty() {
+            return Action::ErrorMessage("Filename is required".to_string());
+        }
+        let path = expand_path(input.value().trim());
+        match write_replacing(&path, self.failures_text().as_bytes()) {
+            Ok(()) => {
+                self.saving = None;
+                Action::StatusMessage(format!(
+                    "Saved {} failures to {}",
+                    self.failures.len(),
+                    path.display()
+                ))
+            }
+            Err(e) => Action::ErrorMessage(format!("Failed to write {}: {}", path.display(), e)),
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let headline_style = if self.failures.is_empty() {
+            self.theme.success()
+        } else {
+            self.theme.warning()
+        };
+        let mut lines = vec![
+            Line::from(Span::styled(self.headline(), headline_style)),
+            Line::from(""),
+        ];
+        if !self.failures.is_empty() {
+            lines.push(Line::from(Span::styled("Failures", self.theme.header())));
+            for (dn, error) in self.failures.iter().skip(self.scroll) {
+                lines.push(Line::from(Span::styled(
+                    format!("  {}", dn),
+                    self.theme.normal(),
+                )));
+                lines.push(Line::from(Span::styled(
+                    format!("    {}", error),
+                    self.theme.error(),
+                )));
+            }
+        }
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), layout[0]);
+
+        let hint = match &self.saving {
+            Some(input) => Line::from(vec![
+                Span::styled("Save to: ", self.theme.header()),
+                Span::styled(input.value(), self.theme.normal()),
+                Span::styled("_", self.theme.command_prompt()),
+                Span::styled("  Enter:save  Esc:cancel", self.theme.dimmed()),
+            ]),
+            None if self.failures.is_empty() => {
+                Line::from(Span::styled("Esc:close", self.theme.dimmed()))
+            }
+            None => Line::from(Span::styled(
+                "\u{2191}/\u{2193}:scroll  s:save failures  c:copy  Esc:close",
+                self.theme.dimmed(),
+            )),
+        };
+        frame.render_widget(Paragraph::new(hint), layout[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_saves_failures_to_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut popup = BatchSummaryPopup::new(Theme::load("dark"));
+        popup.show(
+            42,
+            vec![
+                (
+                    "uid=carol,dc=example,dc=com".to_string(),
+                    "insufficientAccessRights".to_string(),
+                ),
+                (
+                    "uid=dave,dc=example,dc=com".to_string(),
+                    "noSuchObject".to_string(),
+                ),
+            ],
+        );
+        assert_eq!(popup.headline(), "42 succeeded, 2 failed");
+
+        popup.handle_key_event(key(KeyCode::Char('s')));
+        let path = dir.path().join("failures.txt");
+        popup
+            .saving
+            .as_mut()
+            .unwrap()
+            .set(path.display().to_string());
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Enter)),
+            Action::StatusMessage(ref m) if m.starts_with("Saved 2 failures")
+        ));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "uid=carol,dc=example,dc=com: insufficientAccessRights\nuid=dave,dc=example,dc=com: noSuchObject\n"
+        );
+
+        // Typing a name does not close the popup, Esc afterwards does
+        assert!(popup.visible);
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Esc)),
+            Action::ClosePopup
+        ));
+    }
+
+    #[test]
+    fn test_nothing_to_save_without_failures() {
+        let mut popup = BatchSummaryPopup::new(Theme::load("dark"));
+        popup.show(3, Vec::new());
+        assert_eq!(popup.headline(), "3 succeeded, 0 failed");
+        popup.handle_key_event(key(KeyCode::Char('s')));
+        assert!(popup.saving.is_none());
+    }
+}
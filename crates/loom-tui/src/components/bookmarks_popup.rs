@@ -0,0 +1,443 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use loom_core::bookmark::{Bookmark, BookmarkTarget};
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+use crate::widgets::fuzzy_input::{FuzzyFilter, FuzzyMatch};
+
+/// What the popup is showing.
+enum Mode {
+    /// Every saved bookmark, filtered by what is typed.
+    List,
+    /// Asking for the label of a new bookmark.
+    Naming {
+        profile: String,
+        target: BookmarkTarget,
+    },
+}
+
+/// Saved DNs and searches across all profiles, with a fuzzy filter. It
+/// also asks for the label when something is bookmarked.
+pub struct BookmarksPopup {
+    pub visible: bool,
+    mode: Mode,
+    popup: Popup,
+    input: String,
+    bookmarks: Vec<Bookmark>,
+    /// "profile name target" per bookmark, matched against the input.
+    haystacks: Vec<String>,
+    filtered: Vec<FuzzyMatch>,
+    selected_idx: usize,
+    fuzzy: FuzzyFilter,
+    theme: Theme,
+}
+
+impl BookmarksPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            mode: Mode::List,
+            popup: Popup::new("Bookmark", theme.clone()).with_size(60, 25),
+            input: String::new(),
+            bookmarks: Vec::new(),
+            haystacks: Vec::new(),
+            filtered: Vec::new(),
+            selected_idx: 0,
+            fuzzy: FuzzyFilter::new(),
+            theme,
+        }
+    }
+
+    /// List `bookmarks`, in the order given.
+    pub fn show(&mut self, bookmarks: Vec<Bookmark>) {
+        self.mode = Mode::List;
+        self.input.clear();
+        self.set_bookmarks(bookmarks);
+        self.visible = true;
+    }
+
+    /// Ask for a label for `target`, saved under `profile`.
+    pub fn prompt(&mut self, profile: String, target: BookmarkTarget) {
+        self.input = match &target {
+            BookmarkTarget::Dn(dn) => loom_core::dn::rdn_display_name(dn).to_string(),
+            BookmarkTarget::Search(search) => search.filter.clone(),
+        };
+        self.mode = Mode::Naming { profile, target };
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.mode = Mode::List;
+        self.popup.hide();
+        self.bookmarks.clear();
+        self.haystacks.clear();
+        self.filtered.clear();
+    }
+
+    fn set_bookmarks(&mut self, bookmarks: Vec<Bookmark>) {
+        self.haystacks = bookmarks
+            .iter()
+            .map(|b| format!("{} {} {}", b.profile, b.name, b.target.summary()))
+            .collect();
+        self.bookmarks = bookmarks;
+        self.refilter();
+    }
+
+    fn refilter(&mut self) {
+        self.filtered = self.fuzzy.filter(&self.input, &self.haystacks);
+        self.selected_idx = self.selected_idx.min(self.filtered.len().saturating_sub(1));
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if !self.visible {
+            return Action::None;
+        }
+        if key.code == KeyCode::Esc {
+            self.hide();
+            return Action::ClosePopup;
+        }
+        match self.mode {
+            Mode::List => self.handle_list_key(key),
+            Mode::Naming { .. } => self.handle_naming_key(key),
+        }
+    }
+
+    fn handle_list_key(&mut self, key: KeyEvent) -> Action {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Enter => match self.filtered.get(self.selected_idx) {
+                Some(fm) => {
+                    let bookmark = self.bookmarks[fm.index].clone();
+                    self.hide();
+                    Action::OpenBookmark(bookmark)
+                }
+                None => Action::None,
+            },
+            KeyCode::Delete => match self.filtered.get(self.selected_idx) {
+                Some(fm) => {
+                    let mut bookmarks = std::mem::take(&mut self.bookmarks);
+                    let removed = bookmarks.remove(fm.index);
+                    self.set_bookmarks(bookmarks);
+                    Action::RemoveBookmark(removed.profile, removed.name)
+                }
+                None => Action::None,
+            },
+            KeyCode::Up => {
+                self.selected_idx = self.selected_idx.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Char('p') if ctrl => {
+                self.selected_idx = self.selected_idx.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down => {
+                self.select_next();
+                Action::None
+            }
+            KeyCode::Char('n') if ctrl => {
+                self.select_next();
+                Action::None
+            }
+            KeyCode::Backspace => {
+                if self.input.pop().is_some() {
+                    self.selected_idx = 0;
+                    self.refilter();
+                }
+                Action::None
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.input.push(c);
+                self.selected_idx = 0;
+                self.refilter();
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn handle_naming_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Enter => {
+                let name = self.input.trim().to_string();
+                if name.is_empty() {
+                    return Action::ErrorMessage("Bookmark name is required".to_string());
+                }
+                let Mode::Naming { profile, target } =
+                    std::mem::replace(&mut self.mode, Mode::List)
+                else {
+                    return Action::None;
+                };
+                self.hide();
+                Action::AddBookmark(Bookmark::new(name, profile, target))
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                Action::None
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.input.push(c);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn select_next(&mut self) {
+        if self.selected_idx + 1 < self.filtered.len() {
+            self.selected_idx += 1;
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+        match &self.mode {
+            Mode::List => self.render_list(frame, full),
+            Mode::Naming { profile, target } => self.render_naming(frame, full, profile, target),
+        }
+    }
+
+    fn render_naming(&self, frame: &mut Frame, full: Rect, profile: &str, target: &BookmarkTarget) {
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([
+            Constraint::Length(2), // What is bookmarked
+            Constraint::Length(2), // Name
+            Constraint::Min(0),
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        let kind = match target {
+            BookmarkTarget::Dn(_) => "Entry",
+            BookmarkTarget::Search(_) => "Search",
+        };
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(Span::styled(
+                    format!("{} on {}:", kind, profile),
+                    self.theme.dimmed(),
+                )),
+                Line::from(Span::styled(target.summary(), self.theme.normal())),
+            ]),
+            layout[0],
+        );
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(Span::styled("Name:", self.theme.header())),
+                Line::from(vec![
+                    Span::styled(self.input.as_str(), self.theme.normal()),
+                    Span::styled("_", self.theme.command_prompt()),
+                ]),
+            ]),
+            layout[1],
+        );
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                "Enter:save  Esc:cancel",
+                self.theme.dimmed(),
+            ))),
+            layout[3],
+        );
+    }
+
+    fn render_list(&self, frame: &mut Frame, full: Rect) {
+        let popup_width = (full.width as u32 * 60 / 100).clamp(40, 100) as u16;
+        let popup_height = (full.height as u32 * 50 / 100).clamp(10, 30) as u16;
+
+        let x = full.x + (full.width.saturating_sub(popup_width)) / 2;
+        let y = full.y + (full.height.saturating_sub(popup_height)) / 4;
+        let area = Rect::new(x, y, popup_width, popup_height).intersection(full);
+
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(" Bookmarks ")
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if inner.height < 4 {
+            return;
+        }
+
+        let layout = Layout::vertical([
+            Constraint::Length(1), // input
+            Constraint::Length(1), // match count
+            Constraint::Min(1),    // list
+            Constraint::Length(1), // hints
+        ])
+        .split(inner);
+
+        let input_line = Line::from(vec![
+            Span::styled("> ", self.theme.header()),
+            Span::styled(self.input.as_str(), self.theme.normal()),
+            Span::styled("_", self.theme.selected()),
+        ]);
+        frame.render_widget(Paragraph::new(input_line), layout[0]);
+
+        let count = if self.bookmarks.is_empty() {
+            "No bookmarks yet: press m on an entry, or F2 in the search form".to_string()
+        } else {
+            format!("{}/{} bookmarks", self.filtered.len(), self.bookmarks.len())
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(count, self.theme.dimmed()))),
+            layout[1],
+        );
+
+        let list_area = layout[2];
+        let visible_count = list_area.height as usize;
+        let scroll_offset = if self.selected_idx >= visible_count {
+            self.selected_idx - visible_count + 1
+        } else {
+            0
+        };
+
+        let items: Vec<ListItem> = self
+            .filtered
+            .iter()
+            .skip(scroll_offset)
+            .take(visible_count)
+            .enumerate()
+            .map(|(display_idx, fm)| {
+                let bookmark = &self.bookmarks[fm.index];
+                let highlighted = display_idx + scroll_offset == self.selected_idx;
+                let (style, dim) = if highlighted {
+                    (self.theme.selected(), self.theme.selected())
+                } else {
+                    (self.theme.normal(), self.theme.dimmed())
+                };
+                let badge = match bookmark.target {
+                    BookmarkTarget::Dn(_) => "dn",
+                    BookmarkTarget::Search(_) => "search",
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:<8}", badge), dim),
+                    Span::styled(bookmark.name.clone(), style),
+                    Span::styled(
+                        format!("  {}  {}", bookmark.profile, bookmark.target.summary()),
+                        dim,
+                    ),
+                ]))
+            })
+            .collect();
+
+        let mut list_state = ListState::default();
+        if !self.filtered.is_empty() {
+            list_state.select(Some(self.selected_idx - scroll_offset));
+        }
+        frame.render_stateful_widget(List::new(items), list_area, &mut list_state);
+
+        let hint = Line::from(Span::styled(
+            "Enter:Open  Del:Remove  Up/Down:Select  Esc:Close",
+            self.theme.dimmed(),
+        ));
+        frame.render_widget(Paragraph::new(hint), layout[3]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use loom_core::bookmark::{SavedSearch, SearchScope};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn type_text(popup: &mut BookmarksPopup, text: &str) {
+        for c in text.chars() {
+            popup.handle_key_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    fn bookmarks() -> Vec<Bookmark> {
+        vec![
+            Bookmark::new(
+                "admins",
+                "prod",
+                BookmarkTarget::Dn("cn=admins,ou=groups,dc=example,dc=com".to_string()),
+            ),
+            Bookmark::new(
+                "locked",
+                "staging",
+                BookmarkTarget::Search(SavedSearch {
+                    base: "ou=people,dc=example,dc=com".to_string(),
+                    scope: SearchScope::Sub,
+                    filter: "(pwdAccountLockedTime=*)".to_string(),
+                    attributes: Vec::new(),
+                }),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_filter_across_profiles_and_open() {
+        let mut popup = BookmarksPopup::new(Theme::load("dark"));
+        popup.show(bookmarks());
+        assert_eq!(popup.filtered.len(), 2);
+
+        type_text(&mut popup, "staging");
+        assert_eq!(popup.filtered.len(), 1);
+        match popup.handle_key_event(key(KeyCode::Enter)) {
+            Action::OpenBookmark(b) => {
+                assert_eq!(b.name, "locked");
+                assert_eq!(b.profile, "staging");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(!popup.visible);
+    }
+
+    #[test]
+    fn test_delete_removes_the_selected_bookmark() {
+        let mut popup = BookmarksPopup::new(Theme::load("dark"));
+        popup.show(bookmarks());
+        popup.handle_key_event(key(KeyCode::Down));
+        match popup.handle_key_event(key(KeyCode::Delete)) {
+            Action::RemoveBookmark(profile, name) => {
+                assert_eq!((profile.as_str(), name.as_str()), ("staging", "locked"))
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert_eq!(popup.bookmarks.len(), 1);
+        assert_eq!(popup.selected_idx, 0);
+        assert!(popup.visible);
+    }
+
+    #[test]
+    fn test_naming_suggests_the_rdn_value() {
+        let mut popup = BookmarksPopup::new(Theme::load("dark"));
+        let target = BookmarkTarget::Dn("cn=admins,ou=groups,dc=example,dc=com".to_string());
+        popup.prompt("prod".to_string(), target.clone());
+        assert_eq!(popup.input, "admins");
+
+        for _ in 0.."admins".len() {
+            popup.handle_key_event(key(KeyCode::Backspace));
+        }
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+        type_text(&mut popup, "Admins group");
+        match popup.handle_key_event(key(KeyCode::Enter)) {
+            Action::AddBookmark(b) => {
+                assert_eq!(b, Bookmark::new("Admins group", "prod", target));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(!popup.visible);
+    }
+}
@@ -5,7 +5,7 @@ use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use ratatui::Frame;
 
 use crate::action::Action;
-use crate::components::popup::Popup;
+use crate::components::popup::{DiscardGuard, Dismiss, Popup};
 use crate::theme::Theme;
 
 /// Which field is currently being edited.
@@ -52,6 +52,9 @@ pub struct BulkUpdateDialog {
     pub attribute: String,
     pub value: String,
     pub op: BulkOp,
+    /// The value field holds a script run per entry instead of a literal.
+    pub value_is_script: bool,
+    guard: DiscardGuard,
 }
 
 impl BulkUpdateDialog {
@@ -65,15 +68,29 @@ impl BulkUpdateDialog {
             attribute: String::new(),
             value: String::new(),
             op: BulkOp::Replace,
+            value_is_script: false,
+            guard: DiscardGuard::new(true),
         }
     }
 
+    /// Ask before Esc throws away what was typed.
+    pub fn with_confirm_discard(mut self, enabled: bool) -> Self {
+        self.guard = DiscardGuard::new(enabled);
+        self
+    }
+
+    fn is_dirty(&self) -> bool {
+        !self.filter.is_empty() || !self.attribute.is_empty() || !self.value.is_empty()
+    }
+
     pub fn show(&mut self) {
         self.filter.clear();
         self.attribute.clear();
         self.value.clear();
         self.op = BulkOp::Replace;
+        self.value_is_script = false;
         self.active_field = BulkField::Filter;
+        self.guard.reset();
         self.visible = true;
         self.popup.show();
     }
@@ -84,11 +101,15 @@ impl BulkUpdateDialog {
     }
 
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
-        match key.code {
-            KeyCode::Esc => {
+        match self.guard.check(&key, true, true, self.is_dirty()) {
+            Dismiss::Close => {
                 self.hide();
-                Action::ClosePopup
+                return Action::ClosePopup;
             }
+            Dismiss::Stay | Dismiss::Back => return Action::None,
+            Dismiss::Pass => {}
+        }
+        match key.code {
             KeyCode::Tab => {
                 self.active_field = match self.active_field {
                     BulkField::Filter => BulkField::Attribute,
@@ -109,6 +130,10 @@ impl BulkUpdateDialog {
                 self.op = self.op.next();
                 Action::None
             }
+            KeyCode::F(3) => {
+                self.value_is_script = !self.value_is_script;
+                Action::None
+            }
             KeyCode::Enter => {
                 if self.filter.is_empty() || self.attribute.is_empty() {
                     return Action::ErrorMessage("Filter and attribute are required".to_string());
@@ -117,12 +142,17 @@ impl BulkUpdateDialog {
                 let attr = self.attribute.clone();
                 let value = self.value.clone();
                 let op = self.op;
+                let script = self.value_is_script;
+                if script && value.trim().is_empty() {
+                    return Action::ErrorMessage("Script is empty".to_string());
+                }
                 self.hide();
                 Action::BulkUpdateExecute {
                     filter,
                     attribute: attr,
                     value,
                     op,
+                    script,
                 }
             }
             KeyCode::Backspace => {
@@ -156,15 +186,15 @@ impl BulkUpdateDialog {
         let block = Block::default()
             .title(" Bulk Update ")
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
 
-        // Layout: operation (2) | filter (2) | attribute (2) | value (2) | hints (flex)
+        // Layout: operation (3) | filter (2) | attribute (2) | value (2) | hints (flex)
         let layout = Layout::vertical([
-            Constraint::Length(2),
+            Constraint::Length(3),
             Constraint::Length(2),
             Constraint::Length(2),
             Constraint::Length(2),
@@ -175,9 +205,21 @@ impl BulkUpdateDialog {
         // Operation
         let op_line = vec![
             Line::from(vec![
-                Span::styled("Operation: ", self.theme.header),
-                Span::styled(self.op.label(), self.theme.success),
-                Span::styled("  (F2 to cycle)", self.theme.dimmed),
+                Span::styled("Operation: ", self.theme.header()),
+                Span::styled(self.op.label(), self.theme.success()),
+                Span::styled("  (F2 to cycle)", self.theme.dimmed()),
+            ]),
+            Line::from(vec![
+                Span::styled("Value is: ", self.theme.header()),
+                Span::styled(
+                    if self.value_is_script {
+                        "Script"
+                    } else {
+                        "Literal"
+                    },
+                    self.theme.success(),
+                ),
+                Span::styled("  (F3 to toggle)", self.theme.dimmed()),
             ]),
             Line::from(Span::raw("")),
         ];
@@ -196,14 +238,20 @@ impl BulkUpdateDialog {
         );
 
         // Value field
-        self.render_field(frame, layout[3], "Value", &self.value, BulkField::Value);
+        let value_label = if self.value_is_script {
+            "Script (rhai)"
+        } else {
+            "Value"
+        };
+        self.render_field(frame, layout[3], value_label, &self.value, BulkField::Value);
 
         // Hints
         let hints = Paragraph::new(Line::from(Span::styled(
-            "Tab:next field  F2:operation  Enter:execute  Esc:cancel",
-            self.theme.dimmed,
+            "Tab:next field  F2:operation  F3:script  Enter:execute  Esc:cancel",
+            self.theme.dimmed(),
         )));
         frame.render_widget(hints, layout[4]);
+        self.guard.render(frame, inner, &self.theme);
     }
 
     fn render_field(
@@ -216,14 +264,14 @@ impl BulkUpdateDialog {
     ) {
         let is_active = self.active_field == field;
         let label_style = if is_active {
-            self.theme.header
+            self.theme.header()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
         let value_style = if is_active {
-            self.theme.normal
+            self.theme.normal()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
 
         let lines = vec![
@@ -231,7 +279,7 @@ impl BulkUpdateDialog {
             Line::from(vec![
                 Span::styled(value, value_style),
                 if is_active {
-                    Span::styled("_", self.theme.command_prompt)
+                    Span::styled("_", self.theme.command_prompt())
                 } else {
                     Span::raw("")
                 },
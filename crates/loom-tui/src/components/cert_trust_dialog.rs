@@ -126,7 +126,7 @@ impl CertTrustDialog {
 
         // Size the popup
         let popup_width = 56u16.min(full.width.saturating_sub(4));
-        let popup_height = 16u16.min(full.height.saturating_sub(2));
+        let popup_height = 17u16.min(full.height.saturating_sub(2));
 
         let x = full.x + (full.width.saturating_sub(popup_width)) / 2;
         let y = full.y + (full.height.saturating_sub(popup_height)) / 2;
@@ -137,8 +137,8 @@ impl CertTrustDialog {
         let block = Block::default()
             .title(" Untrusted Certificate ")
             .borders(Borders::ALL)
-            .border_style(self.theme.warning)
-            .title_style(self.theme.warning);
+            .border_style(self.theme.warning())
+            .title_style(self.theme.warning());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -161,42 +161,46 @@ impl CertTrustDialog {
         let mut lines = vec![
             Line::from(Span::styled(
                 "The server presented a certificate that is",
-                self.theme.normal,
+                self.theme.normal(),
             )),
             Line::from(Span::styled(
                 "not trusted by your system's certificate store.",
-                self.theme.normal,
+                self.theme.normal(),
             )),
             Line::from(""),
             Line::from(vec![
-                Span::styled("  Host:    ", self.theme.dimmed),
-                Span::styled(host_port, self.theme.normal),
+                Span::styled("  Host:    ", self.theme.dimmed()),
+                Span::styled(host_port, self.theme.normal()),
             ]),
             Line::from(vec![
-                Span::styled("  Subject: ", self.theme.dimmed),
-                Span::styled(&info.subject, self.theme.normal),
+                Span::styled("  Problem: ", self.theme.dimmed()),
+                Span::styled(&info.problem, self.theme.warning()),
             ]),
             Line::from(vec![
-                Span::styled("  Issuer:  ", self.theme.dimmed),
-                Span::styled(&info.issuer, self.theme.normal),
+                Span::styled("  Subject: ", self.theme.dimmed()),
+                Span::styled(&info.subject, self.theme.normal()),
             ]),
             Line::from(vec![
-                Span::styled("  Valid:   ", self.theme.dimmed),
+                Span::styled("  Issuer:  ", self.theme.dimmed()),
+                Span::styled(&info.issuer, self.theme.normal()),
+            ]),
+            Line::from(vec![
+                Span::styled("  Valid:   ", self.theme.dimmed()),
                 Span::styled(
                     format!("{} to {}", info.not_before, info.not_after),
-                    self.theme.normal,
+                    self.theme.normal(),
                 ),
             ]),
             Line::from(vec![
-                Span::styled("  SHA-256: ", self.theme.dimmed),
-                Span::styled(fp_line1, self.theme.normal),
+                Span::styled("  SHA-256: ", self.theme.dimmed()),
+                Span::styled(fp_line1, self.theme.normal()),
             ]),
         ];
 
         if let Some(fp2) = fp_line2 {
             lines.push(Line::from(vec![
-                Span::styled("           ", self.theme.dimmed),
-                Span::styled(fp2, self.theme.normal),
+                Span::styled("           ", self.theme.dimmed()),
+                Span::styled(fp2, self.theme.normal()),
             ]));
         }
 
@@ -207,19 +211,19 @@ impl CertTrustDialog {
 
         // Buttons
         let always_style = if self.selected == 0 {
-            self.theme.selected
+            self.theme.selected()
         } else {
-            self.theme.normal
+            self.theme.normal()
         };
         let session_style = if self.selected == 1 {
-            self.theme.selected
+            self.theme.selected()
         } else {
-            self.theme.normal
+            self.theme.normal()
         };
         let reject_style = if self.selected == 2 {
-            self.theme.selected
+            self.theme.selected()
         } else {
-            self.theme.normal
+            self.theme.normal()
         };
 
         let buttons = Line::from(vec![
@@ -0,0 +1,271 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Picks the attributes the search results table shows: add, remove and
+/// reorder them, then Enter keeps the list.
+pub struct ColumnsDialog {
+    pub visible: bool,
+    popup: Popup,
+    columns: Vec<String>,
+    selected: usize,
+    /// The name of a column being added, while it is typed.
+    adding: Option<String>,
+    theme: Theme,
+}
+
+impl ColumnsDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Result Columns", theme.clone()).with_size(40, 50),
+            columns: Vec::new(),
+            selected: 0,
+            adding: None,
+            theme,
+        }
+    }
+
+    pub fn show(&mut self, columns: Vec<String>) {
+        self.columns = columns;
+        self.selected = 0;
+        self.adding = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.adding = None;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if !self.visible {
+            return Action::None;
+        }
+        if self.adding.is_some() {
+            return self.handle_adding_key(key);
+        }
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        match key.code {
+            // Back to the results, which stay open
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::None
+            }
+            KeyCode::Enter => {
+                if self.columns.is_empty() {
+                    return Action::ErrorMessage("Keep at least one column".to_string());
+                }
+                let columns = std::mem::take(&mut self.columns);
+                self.hide();
+                Action::SaveResultColumns(columns)
+            }
+            KeyCode::Up if shift => self.move_selected(-1),
+            KeyCode::Down if shift => self.move_selected(1),
+            KeyCode::Char('K') => self.move_selected(-1),
+            KeyCode::Char('J') => self.move_selected(1),
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < self.columns.len() {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            KeyCode::Char('a') | KeyCode::Insert => {
+                self.adding = Some(String::new());
+                Action::None
+            }
+            KeyCode::Char('d') | KeyCode::Delete if !self.columns.is_empty() => {
+                self.columns.remove(self.selected);
+                self.selected = self.selected.min(self.columns.len().saturating_sub(1));
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Keys while the name of a new column is typed.
+    fn handle_adding_key(&mut self, key: KeyEvent) -> Action {
+        let Some(name) = self.adding.as_mut() else {
+            return Action::None;
+        };
+        match key.code {
+            KeyCode::Esc => self.adding = None,
+            KeyCode::Enter => {
+                let name = name.trim().to_string();
+                if name.is_empty() {
+                    return Action::ErrorMessage("Type an attribute name".to_string());
+                }
+                if self.columns.iter().any(|c| c.eq_ignore_ascii_case(&name)) {
+                    return Action::ErrorMessage(format!("{} is already a column", name));
+                }
+                let at = if self.columns.is_empty() {
+                    0
+                } else {
+                    self.selected + 1
+                };
+                self.columns.insert(at, name);
+                self.selected = at;
+                self.adding = None;
+            }
+            KeyCode::Backspace => {
+                name.pop();
+            }
+            // Attribute names have no spaces
+            KeyCode::Char(c)
+                if !c.is_whitespace() && !key.modifiers.contains(KeyModifiers::CONTROL) =>
+            {
+                name.push(c);
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    /// Swap the selected column with its neighbour `by` places along.
+    fn move_selected(&mut self, by: isize) -> Action {
+        let Some(to) = self.selected.checked_add_signed(by) else {
+            return Action::None;
+        };
+        if to < self.columns.len() {
+            self.columns.swap(self.selected, to);
+            self.selected = to;
+        }
+        Action::None
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(1), // New column
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        let items: Vec<ListItem> = if self.columns.is_empty() {
+            vec![ListItem::new(Span::styled(
+                "No columns: press a to add one",
+                self.theme.dimmed(),
+            ))]
+        } else {
+            self.columns
+                .iter()
+                .enumerate()
+                .map(|(i, column)| {
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!("{:>2}  ", i + 1), self.theme.dimmed()),
+                        Span::styled(column.clone(), self.theme.normal()),
+                    ]))
+                })
+                .collect()
+        };
+        let mut state = ListState::default();
+        if !self.columns.is_empty() && self.adding.is_none() {
+            state.select(Some(self.selected));
+        }
+        frame.render_stateful_widget(
+            List::new(items).highlight_style(self.theme.selected()),
+            layout[0],
+            &mut state,
+        );
+
+        if let Some(name) = &self.adding {
+            frame.render_widget(
+                Paragraph::new(Line::from(vec![
+                    Span::styled("Add: ", self.theme.header()),
+                    Span::styled(name.as_str(), self.theme.normal()),
+                    Span::styled("_", self.theme.command_prompt()),
+                ])),
+                layout[1],
+            );
+        }
+
+        let hints = if self.adding.is_some() {
+            "Enter:add  Esc:back"
+        } else {
+            "a:add  d:remove  J/K:move  Enter:save  Esc:cancel"
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(hints, self.theme.dimmed()))),
+            layout[2],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_add_remove_and_reorder_columns() {
+        let mut dialog = ColumnsDialog::new(Theme::load("dark"));
+        dialog.show(vec!["cn".to_string(), "mail".to_string()]);
+
+        // Added after the selected column
+        dialog.handle_key_event(key(KeyCode::Char('a')));
+        for c in "uid".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        dialog.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(dialog.columns, ["cn", "uid", "mail"]);
+        assert_eq!(dialog.selected, 1);
+
+        dialog.handle_key_event(key(KeyCode::Char('a')));
+        dialog.handle_key_event(key(KeyCode::Char('C')));
+        dialog.handle_key_event(key(KeyCode::Char('N')));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(msg) if msg == "CN is already a column"
+        ));
+        dialog.handle_key_event(key(KeyCode::Esc));
+        assert!(dialog.visible);
+
+        dialog.handle_key_event(key(KeyCode::Char('J')));
+        assert_eq!(dialog.columns, ["cn", "mail", "uid"]);
+        dialog.handle_key_event(KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT));
+        dialog.handle_key_event(KeyEvent::new(KeyCode::Up, KeyModifiers::SHIFT));
+        assert_eq!(dialog.columns, ["uid", "cn", "mail"]);
+
+        dialog.handle_key_event(key(KeyCode::Down));
+        dialog.handle_key_event(key(KeyCode::Delete));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::SaveResultColumns(columns) if columns == ["uid", "mail"]
+        ));
+        assert!(!dialog.visible);
+    }
+
+    #[test]
+    fn test_keeps_at_least_one_column() {
+        let mut dialog = ColumnsDialog::new(Theme::load("dark"));
+        dialog.show(vec!["cn".to_string()]);
+        dialog.handle_key_event(key(KeyCode::Char('d')));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+        assert!(dialog.visible);
+        dialog.handle_key_event(key(KeyCode::Esc));
+        assert!(!dialog.visible);
+    }
+}
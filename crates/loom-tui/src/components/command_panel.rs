@@ -12,6 +12,7 @@ use loom_core::filter::{detect_filter_context, validate_filter, FilterContext};
 use loom_core::schema::SchemaCache;
 
 use crate::action::Action;
+use crate::command_line;
 use crate::component::Component;
 use crate::theme::Theme;
 use crate::widgets::fuzzy_input::{FuzzyFilter, FuzzyMatch};
@@ -71,6 +72,8 @@ enum CompletionKind {
     Attributes,
     Values,
     Templates,
+    /// Command names and their arguments on the `:` line.
+    Command,
 }
 
 /// The bottom-right panel: command input and status messages.
@@ -100,6 +103,18 @@ pub struct CommandPanel {
     completion_selected: usize,
     completion_kind: CompletionKind,
     value_items: Vec<String>,
+    /// Where the word a command completion replaces starts, and what
+    /// follows an accepted one.
+    command_start: usize,
+    command_suffix: &'static str,
+
+    // `:` command line state
+    profile_names: Vec<String>,
+    command_history: Vec<String>,
+    /// Position in `command_history` while Up/Down walk it, and the line
+    /// being typed before they started.
+    history_pos: Option<usize>,
+    history_draft: String,
 
     // Live search debounce state
     search_generation: u64,
@@ -136,6 +151,12 @@ impl CommandPanel {
             completion_selected: 0,
             completion_kind: CompletionKind::Attributes,
             value_items: Vec::new(),
+            command_start: 0,
+            command_suffix: "",
+            profile_names: Vec::new(),
+            command_history: Vec::new(),
+            history_pos: None,
+            history_draft: String::new(),
             search_generation: 0,
             search_dirty: false,
             last_search_text: String::new(),
@@ -145,6 +166,15 @@ impl CommandPanel {
         }
     }
 
+    /// Turn attribute completion and live search on or off.
+    pub fn set_assists(&mut self, autocomplete_enabled: bool, live_search_enabled: bool) {
+        self.autocomplete_enabled = autocomplete_enabled;
+        self.live_search_enabled = live_search_enabled;
+        if !autocomplete_enabled {
+            self.completion_visible = false;
+        }
+    }
+
     pub fn push_message(&mut self, text: String) {
         self.messages.push(StatusMessage {
             text,
@@ -173,9 +203,29 @@ impl CommandPanel {
         self.search_dirty = false;
         self.last_search_text.clear();
         self.live_searching = false;
+        self.history_pos = None;
         self.hide_completions();
     }
 
+    /// Activate input in command mode, pre-filled with ':'.
+    pub fn activate_command(&mut self) {
+        self.activate_input();
+        self.input_buffer.push(':');
+        self.cursor_pos = 1;
+    }
+
+    /// Activate command mode pre-filled with `:<text>`.
+    pub fn activate_command_with(&mut self, text: &str) {
+        self.activate_command();
+        self.input_buffer.push_str(text);
+        self.cursor_pos = self.input_buffer.len();
+    }
+
+    /// True when the input holds a ':' command rather than a filter.
+    pub fn is_command(&self) -> bool {
+        self.input_buffer.trim_start().starts_with(':')
+    }
+
     pub fn deactivate_input(&mut self) {
         self.input_active = false;
         self.input_buffer.clear();
@@ -215,6 +265,39 @@ impl CommandPanel {
         self.attribute_names = names;
     }
 
+    /// Set the profile names `:connect` completes.
+    pub fn set_profile_names(&mut self, names: Vec<String>) {
+        self.profile_names = names;
+    }
+
+    /// Set the `:` lines Up and Down recall, most recent first.
+    pub fn set_command_history(&mut self, history: Vec<String>) {
+        self.command_history = history;
+        self.history_pos = None;
+    }
+
+    /// Replace the command line with an older (`older`) or newer entry of
+    /// the history; going past the newest restores what was being typed.
+    fn recall_history(&mut self, older: bool) {
+        let pos = match (self.history_pos, older) {
+            (None, true) if !self.command_history.is_empty() => {
+                self.history_draft = self.input_buffer.clone();
+                Some(0)
+            }
+            (None, _) => return,
+            (Some(pos), true) => Some((pos + 1).min(self.command_history.len() - 1)),
+            (Some(0), false) => None,
+            (Some(pos), false) => Some(pos - 1),
+        };
+        self.history_pos = pos;
+        self.input_buffer = match pos {
+            Some(pos) => format!(":{}", self.command_history[pos]),
+            None => std::mem::take(&mut self.history_draft),
+        };
+        self.cursor_pos = self.input_buffer.len();
+        self.hide_completions();
+    }
+
     /// Set the schema cache for value suggestions.
     pub fn set_schema(&mut self, schema: Option<SchemaCache>) {
         debug!(
@@ -251,6 +334,10 @@ impl CommandPanel {
     }
 
     fn update_completions(&mut self) {
+        if self.is_command() {
+            self.update_command_completions();
+            return;
+        }
         if !self.autocomplete_enabled {
             debug!("update_completions: autocomplete disabled, hiding");
             self.hide_completions();
@@ -385,6 +472,34 @@ impl CommandPanel {
         }
     }
 
+    fn update_command_completions(&mut self) {
+        if !self.autocomplete_enabled {
+            self.hide_completions();
+            return;
+        }
+        let text_to_cursor = &self.input_buffer[..self.cursor_pos];
+        let colon = text_to_cursor.find(':').map_or(0, |i| i + 1);
+        let line = &text_to_cursor[colon..];
+        // Nothing to offer until a name is started, nor for a finished word
+        let completion = command_line::complete(line, &self.profile_names, &self.attribute_names)
+            .filter(|c| !line.is_empty() && c.candidates != [&line[c.start..]]);
+        let Some(completion) = completion else {
+            self.hide_completions();
+            return;
+        };
+        self.completion_kind = CompletionKind::Command;
+        self.command_start = colon + completion.start;
+        self.command_suffix = completion.suffix;
+        self.value_items = completion.candidates;
+        self.completions = (0..self.value_items.len())
+            .map(|index| FuzzyMatch { index, score: 0 })
+            .collect();
+        self.completion_visible = true;
+        if self.completion_selected >= self.completions.len() {
+            self.completion_selected = 0;
+        }
+    }
+
     fn accept_completion(&mut self) {
         if !self.completion_visible || self.completions.is_empty() {
             debug!(
@@ -459,6 +574,15 @@ impl CommandPanel {
                 self.input_buffer = template;
                 self.cursor_pos = self.input_buffer.len();
             }
+            CompletionKind::Command => {
+                let word = self.value_items[selected.index].clone();
+                let suffix = self.input_buffer[self.cursor_pos..].to_string();
+                self.input_buffer.truncate(self.command_start);
+                self.input_buffer.push_str(&word);
+                self.input_buffer.push_str(self.command_suffix);
+                self.cursor_pos = self.input_buffer.len();
+                self.input_buffer.push_str(&suffix);
+            }
         }
 
         self.hide_completions();
@@ -582,6 +706,15 @@ impl CommandPanel {
 
         // Input mode: capture text
         match key.code {
+            KeyCode::Enter if self.is_command() => {
+                let command = self.input_buffer.trim().trim_start_matches(':').to_string();
+                self.deactivate_input();
+                if command.is_empty() {
+                    Action::None
+                } else {
+                    Action::CommandExecute(command)
+                }
+            }
             KeyCode::Enter => {
                 let query = normalize_filter(&self.input_buffer);
                 self.soft_deactivate();
@@ -597,7 +730,7 @@ impl CommandPanel {
             }
             KeyCode::Backspace => {
                 if self.cursor_pos > 0 {
-                    self.cursor_pos -= 1;
+                    self.cursor_pos -= self.prev_char_len();
                     let removed = self.input_buffer.remove(self.cursor_pos);
                     // If we deleted a '(' and the char now at cursor_pos is ')',
                     // remove the matching ')' too.
@@ -649,15 +782,22 @@ impl CommandPanel {
                 Action::None
             }
             KeyCode::Left => {
-                if self.cursor_pos > 0 {
-                    self.cursor_pos -= 1;
-                }
+                self.cursor_pos -= self.prev_char_len();
                 Action::None
             }
             KeyCode::Right => {
-                if self.cursor_pos < self.input_buffer.len() {
-                    self.cursor_pos += 1;
-                }
+                self.cursor_pos += self.input_buffer[self.cursor_pos..]
+                    .chars()
+                    .next()
+                    .map_or(0, char::len_utf8);
+                Action::None
+            }
+            KeyCode::Up if self.is_command() => {
+                self.recall_history(true);
+                Action::None
+            }
+            KeyCode::Down if self.is_command() => {
+                self.recall_history(false);
                 Action::None
             }
             KeyCode::Up => {
@@ -676,6 +816,12 @@ impl CommandPanel {
                 self.cursor_pos = self.input_buffer.len();
                 Action::None
             }
+            KeyCode::Char(c) if self.is_command() => {
+                self.input_buffer.insert(self.cursor_pos, c);
+                self.cursor_pos += c.len_utf8();
+                self.update_completions();
+                Action::None
+            }
             KeyCode::Char(c) => {
                 if c == '(' && self.autocomplete_enabled {
                     // Auto-insert matching parentheses
@@ -684,7 +830,7 @@ impl CommandPanel {
                     self.cursor_pos += 1; // position between ( and )
                 } else {
                     self.input_buffer.insert(self.cursor_pos, c);
-                    self.cursor_pos += 1;
+                    self.cursor_pos += c.len_utf8();
 
                     // Auto-wrap bare attr= in parentheses:
                     // If user typed '=' and there are no parens yet, wrap in (...)
@@ -747,6 +893,15 @@ fn normalize_filter(filter: &str) -> String {
 }
 
 impl CommandPanel {
+    /// Byte length of the character before the cursor, so the cursor
+    /// stays on a character boundary.
+    fn prev_char_len(&self) -> usize {
+        self.input_buffer[..self.cursor_pos]
+            .chars()
+            .next_back()
+            .map_or(0, char::len_utf8)
+    }
+
     /// Format the input buffer for multi-line display when it contains boolean operators.
     /// Returns `(formatted_lines, cursor_row, cursor_col)`.
     /// For simple filters, returns a single line with direct cursor mapping.
@@ -904,7 +1059,7 @@ impl CommandPanel {
         let block = Block::default()
             .title(format!(" {} ", title))
             .borders(Borders::ALL)
-            .border_style(self.theme.border);
+            .border_style(self.theme.border());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -917,9 +1072,9 @@ impl CommandPanel {
             .iter()
             .map(|msg| {
                 let style = if msg.is_error {
-                    self.theme.error
+                    self.theme.error()
                 } else {
-                    self.theme.normal
+                    self.theme.normal()
                 };
                 Line::from(Span::styled(&msg.text, style))
             })
@@ -958,6 +1113,7 @@ impl CommandPanel {
             CompletionKind::Attributes => "Attributes",
             CompletionKind::Values => "Values",
             CompletionKind::Templates => "Templates",
+            CompletionKind::Command => "Completions",
         };
         let title = if total > max_visible {
             format!(" {} ({}/{}) ", kind_label, visible_count, total)
@@ -968,7 +1124,7 @@ impl CommandPanel {
         let block = Block::default()
             .title(title)
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border);
+            .border_style(self.theme.popup_border());
 
         let inner = block.inner(popup_area);
         frame.render_widget(block, popup_area);
@@ -983,7 +1139,9 @@ impl CommandPanel {
         // Choose the correct source list based on completion kind
         let source_list: &[String] = match self.completion_kind {
             CompletionKind::Attributes => &self.attribute_names,
-            CompletionKind::Values | CompletionKind::Templates => &self.value_items,
+            CompletionKind::Values | CompletionKind::Templates | CompletionKind::Command => {
+                &self.value_items
+            }
         };
 
         let items: Vec<ListItem> = self.completions[scroll_offset..]
@@ -994,9 +1152,9 @@ impl CommandPanel {
                 let name = &source_list[m.index];
                 let actual_idx = scroll_offset + i;
                 let style = if actual_idx == self.completion_selected {
-                    self.theme.selected
+                    self.theme.selected()
                 } else {
-                    self.theme.normal
+                    self.theme.normal()
                 };
                 ListItem::new(Span::styled(name.as_str(), style))
             })
@@ -1034,16 +1192,16 @@ impl CommandPanel {
         let block = Block::default()
             .title(title)
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border);
+            .border_style(self.theme.popup_border());
 
         let inner = block.inner(popup_area);
         frame.render_widget(block, popup_area);
 
         let header = Row::new(vec![
-            Cell::from(Span::styled("DN", self.theme.header)),
-            Cell::from(Span::styled("sAMAccountName", self.theme.header)),
-            Cell::from(Span::styled("Display Name", self.theme.header)),
-            Cell::from(Span::styled("Mail", self.theme.header)),
+            Cell::from(Span::styled("DN", self.theme.header())),
+            Cell::from(Span::styled("sAMAccountName", self.theme.header())),
+            Cell::from(Span::styled("Display Name", self.theme.header())),
+            Cell::from(Span::styled("Mail", self.theme.header())),
         ]);
 
         let rows: Vec<Row> = self
@@ -1052,18 +1210,18 @@ impl CommandPanel {
             .take(max_rows)
             .map(|entry| {
                 Row::new(vec![
-                    Cell::from(Span::styled(&entry.dn, self.theme.normal)),
+                    Cell::from(Span::styled(&entry.dn, self.theme.normal())),
                     Cell::from(Span::styled(
                         entry.first_value("sAMAccountName").unwrap_or(""),
-                        self.theme.normal,
+                        self.theme.normal(),
                     )),
                     Cell::from(Span::styled(
                         entry.first_value("displayName").unwrap_or(""),
-                        self.theme.normal,
+                        self.theme.normal(),
                     )),
                     Cell::from(Span::styled(
                         entry.first_value("mail").unwrap_or(""),
-                        self.theme.normal,
+                        self.theme.normal(),
                     )),
                 ])
             })
@@ -1076,7 +1234,7 @@ impl CommandPanel {
             Constraint::Percentage(25),
         ];
 
-        let table = Table::new(rows, widths).header(header.style(self.theme.header));
+        let table = Table::new(rows, widths).header(header.style(self.theme.header()));
 
         frame.render_widget(table, inner);
     }
@@ -1114,15 +1272,15 @@ impl CommandPanel {
                 };
 
                 let mut spans = vec![
-                    Span::styled("/ ", self.theme.command_prompt),
-                    Span::styled(before_cursor.to_string(), self.theme.normal),
-                    Span::styled(at_cursor.to_string(), self.theme.command_prompt),
+                    Span::styled("/ ", self.theme.command_prompt()),
+                    Span::styled(before_cursor.to_string(), self.theme.normal()),
+                    Span::styled(at_cursor.to_string(), self.theme.command_prompt()),
                 ];
                 if !after_cursor.is_empty() {
-                    spans.push(Span::styled(after_cursor.to_string(), self.theme.normal));
+                    spans.push(Span::styled(after_cursor.to_string(), self.theme.normal()));
                 }
                 if self.live_searching {
-                    spans.push(Span::styled(" ...", self.theme.dimmed));
+                    spans.push(Span::styled(" ...", self.theme.dimmed()));
                 }
                 let input_line = Line::from(spans);
                 frame.render_widget(Paragraph::new(input_line), area);
@@ -1148,18 +1306,18 @@ impl CommandPanel {
                             };
 
                             let mut spans = vec![
-                                Span::styled(prefix, self.theme.command_prompt),
-                                Span::styled(before.to_string(), self.theme.normal),
-                                Span::styled(at.to_string(), self.theme.command_prompt),
+                                Span::styled(prefix, self.theme.command_prompt()),
+                                Span::styled(before.to_string(), self.theme.normal()),
+                                Span::styled(at.to_string(), self.theme.command_prompt()),
                             ];
                             if !after.is_empty() {
-                                spans.push(Span::styled(after.to_string(), self.theme.normal));
+                                spans.push(Span::styled(after.to_string(), self.theme.normal()));
                             }
                             Line::from(spans)
                         } else {
                             Line::from(vec![
-                                Span::styled(prefix, self.theme.command_prompt),
-                                Span::styled(line_text.clone(), self.theme.normal),
+                                Span::styled(prefix, self.theme.command_prompt()),
+                                Span::styled(line_text.clone(), self.theme.normal()),
                             ])
                         }
                     })
@@ -1169,12 +1327,12 @@ impl CommandPanel {
         } else {
             // Not active — show filter text as dimmed, or hint
             if self.input_buffer.is_empty() {
-                let hint = Line::from(Span::styled("Press / to edit filter", self.theme.dimmed));
+                let hint = Line::from(Span::styled("Press / to edit filter", self.theme.dimmed()));
                 frame.render_widget(Paragraph::new(hint), area);
             } else {
                 let line = Line::from(vec![
-                    Span::styled("/ ", self.theme.command_prompt),
-                    Span::styled(&self.input_buffer, self.theme.dimmed),
+                    Span::styled("/ ", self.theme.command_prompt()),
+                    Span::styled(&self.input_buffer, self.theme.dimmed()),
                 ]);
                 frame.render_widget(Paragraph::new(line), area);
             }
@@ -1190,9 +1348,9 @@ impl CommandPanel {
 impl Component for CommandPanel {
     fn render(&self, frame: &mut Frame, area: Rect, focused: bool) {
         let border_style = if focused {
-            self.theme.border_focused
+            self.theme.border_focused()
         } else {
-            self.theme.border
+            self.theme.border()
         };
 
         let mut block = Block::default()
@@ -1227,9 +1385,9 @@ impl Component for CommandPanel {
             .iter()
             .map(|msg| {
                 let style = if msg.is_error {
-                    self.theme.error
+                    self.theme.error()
                 } else {
-                    self.theme.normal
+                    self.theme.normal()
                 };
                 Line::from(Span::styled(&msg.text, style))
             })
@@ -1255,15 +1413,15 @@ impl Component for CommandPanel {
                 };
 
                 let mut spans = vec![
-                    Span::styled("/ ", self.theme.command_prompt),
-                    Span::styled(before_cursor.to_string(), self.theme.normal),
-                    Span::styled(at_cursor.to_string(), self.theme.command_prompt),
+                    Span::styled("/ ", self.theme.command_prompt()),
+                    Span::styled(before_cursor.to_string(), self.theme.normal()),
+                    Span::styled(at_cursor.to_string(), self.theme.command_prompt()),
                 ];
                 if !after_cursor.is_empty() {
-                    spans.push(Span::styled(after_cursor.to_string(), self.theme.normal));
+                    spans.push(Span::styled(after_cursor.to_string(), self.theme.normal()));
                 }
                 if self.live_searching {
-                    spans.push(Span::styled(" ...", self.theme.dimmed));
+                    spans.push(Span::styled(" ...", self.theme.dimmed()));
                 }
                 let input_line = Line::from(spans);
                 frame.render_widget(Paragraph::new(input_line), layout[1]);
@@ -1291,18 +1449,18 @@ impl Component for CommandPanel {
                             };
 
                             let mut spans = vec![
-                                Span::styled(prefix, self.theme.command_prompt),
-                                Span::styled(before.to_string(), self.theme.normal),
-                                Span::styled(at.to_string(), self.theme.command_prompt),
+                                Span::styled(prefix, self.theme.command_prompt()),
+                                Span::styled(before.to_string(), self.theme.normal()),
+                                Span::styled(at.to_string(), self.theme.command_prompt()),
                             ];
                             if !after.is_empty() {
-                                spans.push(Span::styled(after.to_string(), self.theme.normal));
+                                spans.push(Span::styled(after.to_string(), self.theme.normal()));
                             }
                             Line::from(spans)
                         } else {
                             Line::from(vec![
-                                Span::styled(prefix, self.theme.command_prompt),
-                                Span::styled(line_text.clone(), self.theme.normal),
+                                Span::styled(prefix, self.theme.command_prompt()),
+                                Span::styled(line_text.clone(), self.theme.normal()),
                             ])
                         }
                     })
@@ -1310,7 +1468,7 @@ impl Component for CommandPanel {
                 frame.render_widget(Paragraph::new(display_lines), layout[1]);
             }
         } else if focused {
-            let input_line = Line::from(Span::styled("Press / to search", self.theme.dimmed));
+            let input_line = Line::from(Span::styled("Press / to search", self.theme.dimmed()));
             frame.render_widget(Paragraph::new(input_line), layout[1]);
         }
 
@@ -0,0 +1,405 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use loom_core::diff::{AttributeDiff, DiffKind, EntryDiff};
+use loom_core::duplicate::is_server_maintained;
+use loom_core::schema::SchemaCache;
+
+use crate::action::Action;
+use crate::components::path_input::PathInput;
+use crate::components::popup::Popup;
+use crate::paths::{expand_path, write_replacing};
+use crate::theme::Theme;
+use crate::widgets::width::{display_width, truncate_to_width};
+
+/// Width of the attribute name column.
+const NAME_WIDTH: usize = 24;
+
+/// Full-screen side-by-side comparison of two entries. Each attribute
+/// takes a row, values held by both entries line up, values only on the
+/// left show in red and only on the right in green.
+pub struct CompareView {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    diff: EntryDiff,
+    schema: Option<SchemaCache>,
+    /// Leave out server-maintained attributes such as `modifyTimestamp`.
+    hide_operational: bool,
+    /// First line shown.
+    scroll: usize,
+    /// File name being typed after `s`.
+    saving: Option<PathInput>,
+}
+
+impl CompareView {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Compare Entries", theme.clone()).with_size(100, 100),
+            theme,
+            diff: EntryDiff {
+                left_dn: String::new(),
+                right_dn: String::new(),
+                attributes: Vec::new(),
+            },
+            schema: None,
+            hide_operational: true,
+            scroll: 0,
+            saving: None,
+        }
+    }
+
+    pub fn show(&mut self, diff: EntryDiff, schema: Option<SchemaCache>) {
+        self.diff = diff;
+        self.schema = schema;
+        self.scroll = 0;
+        self.saving = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+        self.saving = None;
+    }
+
+    /// The diff as currently shown, without operational attributes while
+    /// they are hidden.
+    pub fn shown(&self) -> EntryDiff {
+        if self.hide_operational {
+            self.diff
+                .without(|attr| is_server_maintained(attr, self.schema.as_ref()))
+        } else {
+            self.diff.clone()
+        }
+    }
+
+    /// E.g. `2 changed, 1 only left, 0 only right`.
+    pub fn summary(&self) -> String {
+        let shown = self.shown();
+        format!(
+            "{} changed, {} only left, {} only right",
+            shown.of_kind(DiffKind::Changed).count(),
+            shown.of_kind(DiffKind::Removed).count(),
+            shown.of_kind(DiffKind::Added).count()
+        )
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if let Some(input) = self.saving.as_mut() {
+            return match key.code {
+                KeyCode::Esc => {
+                    self.saving = None;
+                    Action::None
+                }
+                KeyCode::Enter => self.save(),
+                _ => {
+                    input.handle_key(key);
+                    Action::None
+                }
+            };
+        }
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll = self.scroll.saturating_add(1);
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll = self.scroll.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::PageDown => {
+                self.scroll = self.scroll.saturating_add(10);
+                Action::None
+            }
+            KeyCode::PageUp => {
+                self.scroll = self.scroll.saturating_sub(10);
+                Action::None
+            }
+            KeyCode::Char('g') | KeyCode::Home => {
+                self.scroll = 0;
+                Action::None
+            }
+            KeyCode::Char('o') => {
+                self.hide_operational = !self.hide_operational;
+                self.scroll = 0;
+                Action::None
+            }
+            KeyCode::Char('s') => {
+                self.saving = Some(PathInput::new("entry.diff"));
+                Action::None
+            }
+            KeyCode::Char('c') | KeyCode::Char('y') => {
+                Action::CopyToClipboard(self.shown().to_unified())
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Write the shown diff to the typed file as unified-diff-style text.
+    fn save(&mut self) -> Action {
+        let Some(input) = self.saving.as_ref() else {
+            return Action::None;
+        };
+        if input.value().trim().is_empty() {
+            return Action::ErrorMessage("Filename is required".to_string());
+        }
+        let path = expand_path(input.value().trim());
+        match write_replacing(&path, self.shown().to_unified().as_bytes()) {
+            Ok(()) => {
+                self.saving = None;
+                Action::StatusMessage(format!("Saved diff to {}", path.display()))
+            }
+            Err(e) => Action::ErrorMessage(format!("Failed to write {}: {}", path.display(), e)),
+        }
+    }
+
+    /// One line per value. Values both entries hold come first and line
+    /// up; an attribute whose values match shows only its first value
+    /// when it has several.
+    fn attribute_lines<'a>(&self, attr: &'a AttributeDiff, side: usize) -> Vec<Line<'static>> {
+        let cell = |text: &str, style: Style| {
+            let text = truncate_to_width(text, side);
+            let pad = side.saturating_sub(display_width(&text));
+            vec![Span::styled(text, style), Span::raw(" ".repeat(pad))]
+        };
+
+        let common = attr.common();
+        let same = attr.kind() == DiffKind::Same;
+        // One side's values: the shared ones, then its own in `own`
+        let side_values = |only: Vec<&'a String>, own: Style| -> Vec<(&'a String, Style)> {
+            let shared = common.iter().take(if same { 1 } else { common.len() });
+            shared
+                .map(|v| (*v, self.theme.normal()))
+                .chain(only.into_iter().map(|v| (v, own)))
+                .collect()
+        };
+        let left = side_values(attr.only_left(), self.theme.error());
+        let right = side_values(attr.only_right(), self.theme.success());
+
+        let name_style = match attr.kind() {
+            DiffKind::Same => self.theme.dimmed(),
+            DiffKind::Removed => self.theme.error(),
+            DiffKind::Added => self.theme.success(),
+            DiffKind::Changed => self.theme.warning(),
+        };
+        let rows = left.len().max(right.len()).max(1);
+        (0..rows)
+            .map(|i| {
+                let name = if i == 0 { attr.attr.as_str() } else { "" };
+                let mut spans = vec![Span::styled(
+                    format!(
+                        "{:<width$}",
+                        truncate_to_width(name, NAME_WIDTH - 1),
+                        width = NAME_WIDTH
+                    ),
+                    name_style,
+                )];
+                let blank = (&String::new(), self.theme.normal());
+                let (value, style) = left.get(i).copied().unwrap_or(blank);
+                spans.extend(cell(value, style));
+                spans.push(Span::styled(" \u{2502} ", self.theme.dimmed()));
+                let (value, style) = right.get(i).copied().unwrap_or(blank);
+                spans.extend(cell(value, style));
+                if i == 0 && attr.kind() == DiffKind::Same && common.len() > 1 {
+                    spans.push(Span::styled(
+                        format!(" ({} values)", common.len()),
+                        self.theme.dimmed(),
+                    ));
+                }
+                Line::from(spans)
+            })
+            .collect()
+    }
+
+    fn build_lines(&self, width: usize) -> Vec<Line<'static>> {
+        // Name column, the two sides, the divider and room for a count
+        let side = width.saturating_sub(NAME_WIDTH + 3 + 12) / 2;
+        let shown = self.shown();
+        let mut lines = Vec::new();
+        for attr in &shown.attributes {
+            lines.extend(self.attribute_lines(attr, side));
+        }
+        if lines.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "Nothing to compare.",
+                self.theme.dimmed(),
+            )));
+        }
+        lines
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+        let width = inner.width as usize;
+        let side = width.saturating_sub(NAME_WIDTH + 3 + 12) / 2;
+
+        let heading = |dn: &str| {
+            let text = truncate_to_width(dn, side);
+            let pad = side.saturating_sub(display_width(&text));
+            format!("{}{}", text, " ".repeat(pad))
+        };
+        let header = vec![
+            Line::from(vec![
+                Span::styled(format!("{:<NAME_WIDTH$}", "Attribute"), self.theme.header()),
+                Span::styled(heading(&self.diff.left_dn), self.theme.error()),
+                Span::styled(" \u{2502} ", self.theme.dimmed()),
+                Span::styled(heading(&self.diff.right_dn), self.theme.success()),
+            ]),
+            Line::from(Span::styled(self.summary(), self.theme.dimmed())),
+        ];
+        frame.render_widget(Paragraph::new(header), layout[0]);
+
+        let lines = self.build_lines(width);
+        let scroll = self.scroll.min(lines.len().saturating_sub(1));
+        frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), layout[1]);
+
+        let hint =
+            match &self.saving {
+                Some(input) => Line::from(vec![
+                    Span::styled("Save to: ", self.theme.header()),
+                    Span::styled(input.value(), self.theme.normal()),
+                    Span::styled("_", self.theme.command_prompt()),
+                    Span::styled("  Enter:save  Esc:cancel", self.theme.dimmed()),
+                ]),
+                None => Line::from(Span::styled(
+                    format!(
+                    "\u{2191}/\u{2193}:scroll  o:{} operational  s:save diff  c:copy  Esc:close",
+                    if self.hide_operational { "show" } else { "hide" }
+                ),
+                    self.theme.dimmed(),
+                )),
+            };
+        frame.render_widget(Paragraph::new(hint), layout[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use loom_core::diff::diff_entries;
+    use loom_core::entry::LdapEntry;
+
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    fn entry(dn: &str, attrs: &[(&str, &[&str])]) -> LdapEntry {
+        let attributes: BTreeMap<String, Vec<String>> = attrs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.iter().map(|s| s.to_string()).collect()))
+            .collect();
+        LdapEntry::new(dn.to_string(), attributes)
+    }
+
+    fn view() -> CompareView {
+        let left = entry(
+            "uid=alice,dc=example,dc=com",
+            &[
+                ("cn", &["Alice"]),
+                ("description", &["Staff", "Admins"]),
+                ("modifyTimestamp", &["20260101000000Z"]),
+            ],
+        );
+        let right = entry(
+            "uid=bob,dc=example,dc=com",
+            &[
+                ("cn", &["Alice"]),
+                ("description", &["Staff", "Operations"]),
+                ("mail", &["bob@example.com"]),
+                ("modifyTimestamp", &["20260202000000Z"]),
+            ],
+        );
+        let mut view = CompareView::new(Theme::load("dark"));
+        view.show(diff_entries(&left, &right), None);
+        view
+    }
+
+    #[test]
+    fn test_operational_attributes_toggle() {
+        let mut view = view();
+        assert!(view
+            .shown()
+            .attributes
+            .iter()
+            .all(|a| a.attr != "modifyTimestamp"));
+        assert_eq!(view.summary(), "1 changed, 0 only left, 1 only right");
+
+        view.handle_key_event(key(KeyCode::Char('o')));
+        assert_eq!(view.summary(), "2 changed, 0 only left, 1 only right");
+    }
+
+    #[test]
+    fn test_values_line_up_row_by_row() {
+        let view = view();
+        let lines = view.build_lines(100);
+        let text: Vec<String> = lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        // cn, description expanded to its shared and differing value, mail
+        assert_eq!(text.len(), 4);
+        assert!(text[1].starts_with("description") && text[1].contains("Staff"));
+        assert!(text[2].contains("Admins") && text[2].contains("Operations"));
+        assert!(text[3].starts_with("mail"));
+
+        let admins = lines[2]
+            .spans
+            .iter()
+            .find(|s| s.content.starts_with("Admins"))
+            .unwrap();
+        assert_eq!(admins.style, view.theme.error());
+        let ops = lines[2]
+            .spans
+            .iter()
+            .find(|s| s.content.starts_with("Operations"))
+            .unwrap();
+        assert_eq!(ops.style, view.theme.success());
+    }
+
+    #[test]
+    fn test_saves_unified_diff() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut view = view();
+        view.handle_key_event(key(KeyCode::Char('s')));
+        let path = dir.path().join("alice-bob.diff");
+        view.saving
+            .as_mut()
+            .unwrap()
+            .set(path.display().to_string());
+        assert!(matches!(
+            view.handle_key_event(key(KeyCode::Enter)),
+            Action::StatusMessage(ref m) if m.starts_with("Saved diff")
+        ));
+        let text = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            text.starts_with("--- uid=alice,dc=example,dc=com\n+++ uid=bob,dc=example,dc=com\n")
+        );
+        assert!(text.contains("-description: Admins\n"));
+        assert!(text.contains("+description: Operations\n"));
+        assert!(!text.contains("modifyTimestamp"));
+    }
+}
@@ -5,6 +5,7 @@ use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
 use ratatui::Frame;
 
 use crate::action::Action;
+use crate::keymap::{Command, ContextKeys, KeyContext, Keymap};
 use crate::theme::Theme;
 
 /// A confirmation dialog: "Are you sure?" with Yes/No buttons.
@@ -14,6 +15,7 @@ pub struct ConfirmDialog {
     pub on_confirm: Option<Box<Action>>,
     selected: usize, // 0 = Yes, 1 = No
     theme: Theme,
+    keys: ContextKeys,
 }
 
 impl ConfirmDialog {
@@ -24,9 +26,20 @@ impl ConfirmDialog {
             on_confirm: None,
             selected: 1, // Default to No for safety
             theme,
+            keys: Keymap::default().context(KeyContext::Confirm),
         }
     }
 
+    /// Use the confirm keys of `keymap` instead of the defaults.
+    pub fn with_keys(mut self, keymap: &Keymap) -> Self {
+        self.set_keys(keymap);
+        self
+    }
+
+    pub fn set_keys(&mut self, keymap: &Keymap) {
+        self.keys = keymap.context(KeyContext::Confirm);
+    }
+
     pub fn show(&mut self, message: String, on_confirm: Action) {
         self.message = message;
         self.on_confirm = Some(Box::new(on_confirm));
@@ -44,6 +57,17 @@ impl ConfirmDialog {
             return Action::None;
         }
 
+        match self.keys.command(&key) {
+            Some(Command::Yes) => {
+                self.visible = false;
+                return self.on_confirm.take().map(|a| *a).unwrap_or(Action::None);
+            }
+            Some(Command::No) => {
+                self.hide();
+                return Action::ClosePopup;
+            }
+            _ => {}
+        }
         match key.code {
             KeyCode::Left | KeyCode::Char('h') => {
                 self.selected = 0;
@@ -53,14 +77,6 @@ impl ConfirmDialog {
                 self.selected = 1;
                 Action::None
             }
-            KeyCode::Char('y') | KeyCode::Char('Y') => {
-                self.visible = false;
-                self.on_confirm.take().map(|a| *a).unwrap_or(Action::None)
-            }
-            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
-                self.hide();
-                Action::ClosePopup
-            }
             KeyCode::Enter => {
                 if self.selected == 0 {
                     self.visible = false;
@@ -79,9 +95,14 @@ impl ConfirmDialog {
             return;
         }
 
-        // Center a 50x10 popup
-        let popup_width = (full.width as u32 * 50 / 100).min(60) as u16;
-        let popup_height = 8u16.min(full.height);
+        // Center a 50x10 popup, taller and wider for multi-line messages
+        let lines = self.message.lines().count() as u16;
+        let widest = self.message.lines().map(|l| l.len()).max().unwrap_or(0) as u16;
+        let popup_width = (full.width as u32 * 50 / 100)
+            .min(60)
+            .max(widest.saturating_add(4).min(full.width * 9 / 10) as u32)
+            as u16;
+        let popup_height = lines.saturating_add(5).max(8).min(full.height);
 
         let x = full.x + (full.width.saturating_sub(popup_width)) / 2;
         let y = full.y + (full.height.saturating_sub(popup_height)) / 2;
@@ -92,8 +113,8 @@ impl ConfirmDialog {
         let block = Block::default()
             .title(" Confirm ")
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -103,20 +124,20 @@ impl ConfirmDialog {
 
         // Message
         let msg = Paragraph::new(self.message.as_str())
-            .style(self.theme.normal)
+            .style(self.theme.normal())
             .wrap(Wrap { trim: true });
         frame.render_widget(msg, layout[0]);
 
         // Buttons
         let yes_style = if self.selected == 0 {
-            self.theme.selected
+            self.theme.selected()
         } else {
-            self.theme.normal
+            self.theme.normal()
         };
         let no_style = if self.selected == 1 {
-            self.theme.selected
+            self.theme.selected()
         } else {
-            self.theme.normal
+            self.theme.normal()
         };
 
         let buttons = Line::from(vec![
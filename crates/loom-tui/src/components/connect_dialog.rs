@@ -98,8 +98,8 @@ impl ConnectDialog {
         let block = Block::default()
             .title(" Connect ")
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -108,12 +108,12 @@ impl ConnectDialog {
         let layout = Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(inner);
 
         let hint = Line::from(vec![
-            Span::styled("  \u{2191}/\u{2193}", self.theme.header),
-            Span::styled(": navigate  ", self.theme.dimmed),
-            Span::styled("Enter", self.theme.header),
-            Span::styled(": connect  ", self.theme.dimmed),
-            Span::styled("Esc", self.theme.header),
-            Span::styled(": cancel", self.theme.dimmed),
+            Span::styled("  \u{2191}/\u{2193}", self.theme.header()),
+            Span::styled(": navigate  ", self.theme.dimmed()),
+            Span::styled("Enter", self.theme.header()),
+            Span::styled(": connect  ", self.theme.dimmed()),
+            Span::styled("Esc", self.theme.header()),
+            Span::styled(": cancel", self.theme.dimmed()),
         ]);
         frame.render_widget(Paragraph::new(hint), layout[0]);
 
@@ -122,22 +122,22 @@ impl ConnectDialog {
 
         items.push(ListItem::new(Line::from(Span::styled(
             "+ New Connection...",
-            self.theme.success,
+            self.theme.success(),
         ))));
 
         for p in &self.profiles {
             let mut spans = vec![
-                Span::styled(&p.name, self.theme.header),
-                Span::styled(format!("  {}:{}", p.host, p.port), self.theme.dimmed),
+                Span::styled(&p.name, self.theme.header()),
+                Span::styled(format!("  {}:{}", p.host, p.port), self.theme.dimmed()),
             ];
             if p.offline {
-                spans.push(Span::styled("  [offline]", self.theme.dimmed));
+                spans.push(Span::styled("  [offline]", self.theme.dimmed()));
             }
             items.push(ListItem::new(Line::from(spans)));
         }
 
         let list =
-            List::new(items).highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
+            List::new(items).highlight_style(self.theme.selected().add_modifier(Modifier::BOLD));
 
         frame.render_stateful_widget(list, layout[1], &mut self.list_state.clone());
     }
@@ -4,10 +4,12 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
 use ratatui::Frame;
 
-use loom_core::connection::TlsMode;
+use loom_core::connection::{AuthMethod, TlsMode};
 use loom_core::credentials::CredentialMethod;
+use loom_core::tls::TlsOptions;
 
 use crate::action::Action;
+use crate::components::popup::{DiscardGuard, Dismiss};
 use crate::config::ConnectionProfile;
 use crate::theme::Theme;
 
@@ -25,10 +27,16 @@ enum Field {
     Name,
     Host,
     Port,
+    Auth,
     BindDn,
+    Password,
     BaseDn,
     Folder,
     TlsMode,
+    CaCert,
+    ClientCert,
+    ClientKey,
+    Verify,
     CredentialMethod,
     PasswordCommand,
     PageSize,
@@ -38,15 +46,43 @@ enum Field {
 }
 
 impl Field {
+    const ALL: [Field; 19] = [
+        Field::Name,
+        Field::Host,
+        Field::Port,
+        Field::Auth,
+        Field::BindDn,
+        Field::Password,
+        Field::BaseDn,
+        Field::Folder,
+        Field::TlsMode,
+        Field::CaCert,
+        Field::ClientCert,
+        Field::ClientKey,
+        Field::Verify,
+        Field::CredentialMethod,
+        Field::PasswordCommand,
+        Field::PageSize,
+        Field::Timeout,
+        Field::RelaxRules,
+        Field::ReadOnly,
+    ];
+
     fn next(self) -> Self {
         match self {
             Field::Name => Field::Host,
             Field::Host => Field::Port,
-            Field::Port => Field::BindDn,
-            Field::BindDn => Field::BaseDn,
+            Field::Port => Field::Auth,
+            Field::Auth => Field::BindDn,
+            Field::BindDn => Field::Password,
+            Field::Password => Field::BaseDn,
             Field::BaseDn => Field::Folder,
             Field::Folder => Field::TlsMode,
-            Field::TlsMode => Field::CredentialMethod,
+            Field::TlsMode => Field::CaCert,
+            Field::CaCert => Field::ClientCert,
+            Field::ClientCert => Field::ClientKey,
+            Field::ClientKey => Field::Verify,
+            Field::Verify => Field::CredentialMethod,
             Field::CredentialMethod => Field::PasswordCommand,
             Field::PasswordCommand => Field::PageSize,
             Field::PageSize => Field::Timeout,
@@ -61,11 +97,17 @@ impl Field {
             Field::Name => Field::ReadOnly,
             Field::Host => Field::Name,
             Field::Port => Field::Host,
-            Field::BindDn => Field::Port,
-            Field::BaseDn => Field::BindDn,
+            Field::Auth => Field::Port,
+            Field::BindDn => Field::Auth,
+            Field::Password => Field::BindDn,
+            Field::BaseDn => Field::Password,
             Field::Folder => Field::BaseDn,
             Field::TlsMode => Field::Folder,
-            Field::CredentialMethod => Field::TlsMode,
+            Field::CaCert => Field::TlsMode,
+            Field::ClientCert => Field::CaCert,
+            Field::ClientKey => Field::ClientCert,
+            Field::Verify => Field::ClientKey,
+            Field::CredentialMethod => Field::Verify,
             Field::PasswordCommand => Field::CredentialMethod,
             Field::PageSize => Field::PasswordCommand,
             Field::Timeout => Field::PageSize,
@@ -75,6 +117,45 @@ impl Field {
     }
 }
 
+/// A path field as entered; empty means unset.
+fn optional_path(value: &str) -> Option<std::path::PathBuf> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.into())
+}
+
+/// A group of fields edited on its own, one page of the setup wizard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormSection {
+    Server,
+    Security,
+    Auth,
+    BaseDn,
+}
+
+impl FormSection {
+    fn fields(self) -> &'static [Field] {
+        match self {
+            FormSection::Server => &[Field::Name, Field::Host, Field::Port],
+            FormSection::Security => &[Field::TlsMode],
+            FormSection::Auth => &[
+                Field::Auth,
+                Field::BindDn,
+                Field::CredentialMethod,
+                Field::PasswordCommand,
+            ],
+            FormSection::BaseDn => &[Field::BaseDn],
+        }
+    }
+}
+
+/// Outcome of the last Ctrl-T connection test.
+#[derive(Debug, Clone, PartialEq)]
+enum TestStatus {
+    Running,
+    Passed(String),
+    Failed(String),
+}
+
 /// Right panel in Connections layout: view/edit/create form for ConnectionProfile.
 pub struct ConnectionForm {
     mode: FormMode,
@@ -87,10 +168,18 @@ pub struct ConnectionForm {
     name: String,
     host: String,
     port: String,
+    auth: AuthMethod,
     bind_dn: String,
+    /// Used by the connection test, and kept in the vault or keychain on
+    /// save when the profile uses one; never written to the config file.
+    password: String,
     base_dn: String,
     folder: String,
     tls_mode: TlsMode,
+    ca_cert: String,
+    client_cert: String,
+    client_key: String,
+    verify: bool,
     credential_method: CredentialMethod,
     password_command: String,
     page_size: String,
@@ -101,6 +190,19 @@ pub struct ConnectionForm {
     // Folder view/edit fields
     folder_path: String,
     folder_description: String,
+
+    /// When set, only these fields are shown and Tab cycles among them.
+    section: Option<FormSection>,
+
+    /// Names of all saved profiles, to keep new names unique.
+    profile_names: Vec<String>,
+    /// Fields the user has left; their errors are shown from then on.
+    touched: Vec<Field>,
+    test_status: Option<TestStatus>,
+
+    /// Field values when editing started, to tell whether any changed.
+    initial: Vec<String>,
+    guard: DiscardGuard,
 }
 
 impl ConnectionForm {
@@ -145,10 +247,16 @@ impl ConnectionForm {
             name: String::new(),
             host: String::new(),
             port: "389".to_string(),
+            auth: AuthMethod::Simple,
             bind_dn: String::new(),
+            password: String::new(),
             base_dn: String::new(),
             folder: String::new(),
             tls_mode: TlsMode::Auto,
+            ca_cert: String::new(),
+            client_cert: String::new(),
+            client_key: String::new(),
+            verify: true,
             credential_method: CredentialMethod::Prompt,
             password_command: String::new(),
             page_size: "500".to_string(),
@@ -157,9 +265,192 @@ impl ConnectionForm {
             read_only: false,
             folder_path: String::new(),
             folder_description: String::new(),
+            section: None,
+            profile_names: Vec::new(),
+            touched: Vec::new(),
+            test_status: None,
+            initial: Vec::new(),
+            guard: DiscardGuard::new(true),
         }
     }
 
+    /// Ask before Esc throws away unsaved edits.
+    pub fn with_confirm_discard(mut self, enabled: bool) -> Self {
+        self.guard = DiscardGuard::new(enabled);
+        self
+    }
+
+    /// Every field as displayed.
+    fn values(&self) -> Vec<String> {
+        Field::ALL
+            .iter()
+            .map(|&field| self.field_display(field).1)
+            .collect()
+    }
+
+    fn is_dirty(&self) -> bool {
+        self.values() != self.initial
+    }
+
+    fn start_editing(&mut self) {
+        self.initial = self.values();
+        self.touched.clear();
+        self.test_status = None;
+        self.guard.reset();
+    }
+
+    /// Names of the saved profiles; a new or renamed profile may not
+    /// take one of them.
+    pub fn set_profile_names(&mut self, names: Vec<String>) {
+        self.profile_names = names;
+    }
+
+    /// The password typed for a connection test.
+    pub fn password(&self) -> &str {
+        &self.password
+    }
+
+    /// What is wrong with `field` as entered, if anything.
+    fn field_error(&self, field: Field) -> Option<String> {
+        match field {
+            Field::Name => {
+                let name = match self.name.trim() {
+                    "" => format!("{}:{}", self.host.trim(), self.port),
+                    name => name.to_string(),
+                };
+                let taken =
+                    self.profile_names.iter().enumerate().any(|(i, n)| {
+                        Some(i) != self.profile_index && n.eq_ignore_ascii_case(&name)
+                    });
+                taken.then(|| format!("A profile named '{}' already exists", name))
+            }
+            Field::Host if self.host.trim().is_empty() => Some("Host is required".to_string()),
+            Field::Port => match self.port.parse::<u16>() {
+                Ok(port) if port > 0 => None,
+                _ => Some("Port must be a valid number (1-65535)".to_string()),
+            },
+            Field::PageSize if self.page_size.parse::<u32>().is_err() => {
+                Some("Page size must be a valid number".to_string())
+            }
+            Field::Timeout if self.timeout.parse::<u64>().is_err() => {
+                Some("Timeout must be a valid number".to_string())
+            }
+            Field::Auth => self.auth.available().err(),
+            Field::ClientKey
+                if self.client_cert.trim().is_empty() != self.client_key.trim().is_empty() =>
+            {
+                Some("Client cert and key must be set together".to_string())
+            }
+            _ => None,
+        }
+    }
+
+    /// The first invalid field and its error.
+    fn first_error(&self) -> Option<(Field, String)> {
+        Field::ALL
+            .iter()
+            .find_map(|&field| self.field_error(field).map(|e| (field, e)))
+    }
+
+    /// Whether every field is valid, so the form can be saved.
+    pub fn is_valid(&self) -> bool {
+        self.first_error().is_none()
+    }
+
+    /// Show every error and move to the first one.
+    fn reject(&mut self) -> Option<Action> {
+        let (field, msg) = self.first_error()?;
+        self.touched = Field::ALL.to_vec();
+        self.active_field = field;
+        Some(Action::ErrorMessage(msg))
+    }
+
+    /// Bind with the values entered, without saving them.
+    fn test_connection(&mut self) -> Action {
+        self.try_parse_host_url();
+        if let Some(action) = self.reject() {
+            return action;
+        }
+        match self.to_profile() {
+            Ok(profile) => {
+                self.test_status = Some(TestStatus::Running);
+                Action::ConnMgrTest(Box::new(profile), self.password.clone())
+            }
+            Err(msg) => Action::ErrorMessage(msg),
+        }
+    }
+
+    /// Show how the connection test went.
+    pub fn test_finished(&mut self, result: Result<String, String>) {
+        if self.test_status.is_none() || !self.is_editing() {
+            return;
+        }
+        self.test_status = Some(match result {
+            Ok(msg) => TestStatus::Passed(msg),
+            Err(msg) => TestStatus::Failed(msg),
+        });
+    }
+
+    /// Leave the active field for `next`, marking it as visited.
+    fn move_to(&mut self, next: Field) {
+        if self.active_field == Field::Host {
+            self.try_parse_host_url();
+        }
+        if !self.touched.contains(&self.active_field) {
+            self.touched.push(self.active_field);
+        }
+        self.active_field = next;
+    }
+
+    /// Edit only the fields of `section`, starting at its first field.
+    pub fn set_section(&mut self, section: Option<FormSection>) {
+        self.section = section;
+        if let Some(section) = section {
+            self.active_field = section.fields()[0];
+        }
+    }
+
+    pub fn port(&self) -> Option<u16> {
+        self.port.parse().ok()
+    }
+
+    pub fn tls_mode(&self) -> &TlsMode {
+        &self.tls_mode
+    }
+
+    pub fn set_tls_mode(&mut self, tls_mode: TlsMode) {
+        self.tls_mode = tls_mode;
+    }
+
+    /// The profile as entered, after expanding an `ldap://` URL in the
+    /// host field.
+    pub fn profile(&mut self) -> Result<ConnectionProfile, String> {
+        self.try_parse_host_url();
+        self.to_profile()
+    }
+
+    /// The field after (or before) the active one, within the section.
+    fn step_field(&self, forward: bool) -> Field {
+        let Some(section) = self.section else {
+            return if forward {
+                self.active_field.next()
+            } else {
+                self.active_field.prev()
+            };
+        };
+        let fields = section.fields();
+        let i = fields
+            .iter()
+            .position(|f| *f == self.active_field)
+            .unwrap_or(0);
+        let next = if forward {
+            (i + 1) % fields.len()
+        } else {
+            (i + fields.len() - 1) % fields.len()
+        };
+        fields[next]
+    }
+
     /// Show folder details in the right panel.
     pub fn view_folder(&mut self, path: &str, description: &str) {
         self.mode = FormMode::FolderView;
@@ -180,6 +471,7 @@ impl ConnectionForm {
         if self.profile_index.is_some() {
             self.mode = FormMode::Edit;
             self.active_field = Field::Name;
+            self.start_editing();
         }
     }
 
@@ -191,16 +483,23 @@ impl ConnectionForm {
         self.name.clear();
         self.host.clear();
         self.port = "389".to_string();
+        self.auth = AuthMethod::Simple;
         self.bind_dn.clear();
+        self.password.clear();
         self.base_dn.clear();
         self.folder.clear();
         self.tls_mode = TlsMode::Auto;
+        self.ca_cert.clear();
+        self.client_cert.clear();
+        self.client_key.clear();
+        self.verify = true;
         self.credential_method = CredentialMethod::Prompt;
         self.password_command.clear();
         self.page_size = "500".to_string();
         self.timeout = "30".to_string();
         self.relax_rules = false;
         self.read_only = false;
+        self.start_editing();
     }
 
     /// Clear the form (no profile selected).
@@ -210,9 +509,15 @@ impl ConnectionForm {
         self.name.clear();
         self.host.clear();
         self.port.clear();
+        self.auth = AuthMethod::Simple;
         self.bind_dn.clear();
+        self.password.clear();
         self.base_dn.clear();
         self.folder.clear();
+        self.ca_cert.clear();
+        self.client_cert.clear();
+        self.client_key.clear();
+        self.verify = true;
         self.password_command.clear();
         self.page_size.clear();
         self.timeout.clear();
@@ -224,10 +529,21 @@ impl ConnectionForm {
         self.name = profile.name.clone();
         self.host = profile.host.clone();
         self.port = profile.port.to_string();
+        self.auth = profile.auth;
         self.bind_dn = profile.bind_dn.clone().unwrap_or_default();
+        self.password.clear();
         self.base_dn = profile.base_dn.clone().unwrap_or_default();
         self.folder = profile.folder.clone().unwrap_or_default();
         self.tls_mode = profile.tls_mode.clone();
+        let path = |p: &Option<std::path::PathBuf>| {
+            p.as_ref()
+                .map(|p| p.display().to_string())
+                .unwrap_or_default()
+        };
+        self.ca_cert = path(&profile.tls_options.ca_cert);
+        self.client_cert = path(&profile.tls_options.client_cert);
+        self.client_key = path(&profile.tls_options.client_key);
+        self.verify = profile.tls_options.verify;
         self.credential_method = profile.credential_method.clone();
         self.password_command = profile.password_command.clone().unwrap_or_default();
         self.page_size = profile.page_size.to_string();
@@ -243,7 +559,9 @@ impl ConnectionForm {
         let port: u16 = self
             .port
             .parse()
-            .map_err(|_| "Port must be a valid number (1-65535)".to_string())?;
+            .ok()
+            .filter(|port| *port > 0)
+            .ok_or_else(|| "Port must be a valid number (1-65535)".to_string())?;
         let page_size: u32 = self
             .page_size
             .parse()
@@ -264,6 +582,12 @@ impl ConnectionForm {
             host: self.host.trim().to_string(),
             port,
             tls_mode: self.tls_mode.clone(),
+            tls_options: TlsOptions {
+                ca_cert: optional_path(&self.ca_cert),
+                client_cert: optional_path(&self.client_cert),
+                client_key: optional_path(&self.client_key),
+                verify: self.verify,
+            },
             bind_dn: if self.bind_dn.trim().is_empty() {
                 None
             } else {
@@ -291,11 +615,19 @@ impl ConnectionForm {
             read_only: self.read_only,
             offline: false,
             labels: vec![],
+            hooks: Default::default(),
+            protected_dns: Vec::new(),
+            auth: self.auth,
+            protection: Default::default(),
+            result_columns: Vec::new(),
         })
     }
 
     fn submit(&mut self) -> Action {
         self.try_parse_host_url();
+        if let Some(action) = self.reject() {
+            return action;
+        }
         match self.to_profile() {
             Ok(profile) => match self.mode {
                 FormMode::Edit => {
@@ -318,13 +650,22 @@ impl ConnectionForm {
             Field::Host => Some(&mut self.host),
             Field::Port => Some(&mut self.port),
             Field::BindDn => Some(&mut self.bind_dn),
+            Field::Password => Some(&mut self.password),
             Field::BaseDn => Some(&mut self.base_dn),
             Field::Folder => Some(&mut self.folder),
+            Field::CaCert => Some(&mut self.ca_cert),
+            Field::ClientCert => Some(&mut self.client_cert),
+            Field::ClientKey => Some(&mut self.client_key),
             Field::PasswordCommand => Some(&mut self.password_command),
             Field::PageSize => Some(&mut self.page_size),
             Field::Timeout => Some(&mut self.timeout),
             // These are cycled with special keys, not typed
-            Field::TlsMode | Field::CredentialMethod | Field::RelaxRules | Field::ReadOnly => None,
+            Field::Auth
+            | Field::TlsMode
+            | Field::Verify
+            | Field::CredentialMethod
+            | Field::RelaxRules
+            | Field::ReadOnly => None,
         }
     }
 
@@ -346,6 +687,9 @@ impl ConnectionForm {
     }
 
     fn handle_view_key(&mut self, key: KeyEvent) -> Action {
+        if key.modifiers == KeyModifiers::CONTROL && key.code == KeyCode::Char('r') {
+            return Action::Reconnect(None);
+        }
         match key.code {
             KeyCode::Char('n') => Action::ConnMgrNew,
             KeyCode::Char('e') if self.profile_index.is_some() => {
@@ -419,30 +763,33 @@ impl ConnectionForm {
     }
 
     fn handle_edit_key(&mut self, key: KeyEvent) -> Action {
+        // The setup wizard handles leaving its sections itself
+        if self.section.is_none() {
+            match self.guard.check(&key, true, true, self.is_dirty()) {
+                Dismiss::Close => {
+                    self.cancel_edit();
+                    return Action::None;
+                }
+                Dismiss::Stay | Dismiss::Back => return Action::None,
+                Dismiss::Pass => {}
+            }
+        }
         match (key.modifiers, key.code) {
             (KeyModifiers::NONE, KeyCode::Esc) => {
-                // Cancel: revert to view or clear
-                if self.mode == FormMode::Edit {
-                    self.mode = FormMode::View;
-                } else {
-                    self.clear();
-                }
+                self.cancel_edit();
                 Action::None
             }
             (KeyModifiers::NONE, KeyCode::Tab) | (KeyModifiers::NONE, KeyCode::Down) => {
-                if self.active_field == Field::Host {
-                    self.try_parse_host_url();
-                }
-                self.active_field = self.active_field.next();
+                self.move_to(self.step_field(true));
                 Action::None
             }
             (KeyModifiers::SHIFT, KeyCode::BackTab) | (KeyModifiers::NONE, KeyCode::Up) => {
-                if self.active_field == Field::Host {
-                    self.try_parse_host_url();
-                }
-                self.active_field = self.active_field.prev();
+                self.move_to(self.step_field(false));
                 Action::None
             }
+            (KeyModifiers::CONTROL, KeyCode::Char('t')) if self.section.is_none() => {
+                self.test_connection()
+            }
             (KeyModifiers::NONE, KeyCode::F(2)) => {
                 // Cycle TLS mode
                 self.tls_mode = self.tls_mode.next();
@@ -464,6 +811,10 @@ impl ConnectionForm {
             (KeyModifiers::NONE, KeyCode::Enter) => {
                 // Enter on toggle fields acts like a cycle
                 match self.active_field {
+                    Field::Auth => {
+                        self.auth = self.auth.next();
+                        Action::None
+                    }
                     Field::TlsMode => {
                         self.tls_mode = self.tls_mode.next();
                         Action::None
@@ -477,6 +828,10 @@ impl ConnectionForm {
                         };
                         Action::None
                     }
+                    Field::Verify => {
+                        self.verify = !self.verify;
+                        Action::None
+                    }
                     Field::RelaxRules => {
                         self.relax_rules = !self.relax_rules;
                         Action::None
@@ -504,6 +859,14 @@ impl ConnectionForm {
                     return Action::None;
                 }
                 // Toggle fields: space toggles
+                if self.active_field == Field::Auth {
+                    self.auth = self.auth.next();
+                    return Action::None;
+                }
+                if self.active_field == Field::Verify {
+                    self.verify = !self.verify;
+                    return Action::None;
+                }
                 if self.active_field == Field::RelaxRules {
                     self.relax_rules = !self.relax_rules;
                     return Action::None;
@@ -521,11 +884,20 @@ impl ConnectionForm {
         }
     }
 
+    /// Cancel: revert to view or clear.
+    fn cancel_edit(&mut self) {
+        if self.mode == FormMode::Edit {
+            self.mode = FormMode::View;
+        } else {
+            self.clear();
+        }
+    }
+
     pub fn render(&self, frame: &mut Frame, area: Rect, focused: bool) {
         let border_style = if focused {
-            self.theme.border_focused
+            self.theme.border_focused()
         } else {
-            self.theme.border
+            self.theme.border()
         };
 
         // Folder view/edit modes have their own render path
@@ -554,152 +926,124 @@ impl ConnectionForm {
 
         if self.profile_index.is_none() && self.mode == FormMode::View {
             let empty = Paragraph::new("Select a profile or press 'n' to create one")
-                .style(self.theme.dimmed);
+                .style(self.theme.dimmed());
             frame.render_widget(empty, inner);
             return;
         }
 
         let editable = self.mode != FormMode::View;
 
-        // Layout: 13 fields at 2 lines each + hints
-        let layout = Layout::vertical([
-            Constraint::Length(2), // Name
-            Constraint::Length(2), // Host
-            Constraint::Length(2), // Port
-            Constraint::Length(2), // Bind DN
-            Constraint::Length(2), // Base DN
-            Constraint::Length(2), // Folder
-            Constraint::Length(2), // TLS Mode
-            Constraint::Length(2), // Credential Method
-            Constraint::Length(2), // Password Command
-            Constraint::Length(2), // Page Size
-            Constraint::Length(2), // Timeout
-            Constraint::Length(2), // Relax Rules
-            Constraint::Length(2), // Read Only
-            Constraint::Min(1),    // Hints
-        ])
+        // Layout: every field at 2 lines each, test result + hints
+        let layout = Layout::vertical(
+            Field::ALL
+                .iter()
+                .map(|_| Constraint::Length(2))
+                .chain([Constraint::Length(1), Constraint::Min(1)]),
+        )
         .split(inner);
+        for (i, &field) in Field::ALL.iter().enumerate() {
+            let (label, value) = self.field_display(field);
+            self.render_field(frame, layout[i], label, &value, field, editable);
+        }
+        let test_area = layout[Field::ALL.len()];
+        let hints_area = layout[Field::ALL.len() + 1];
 
-        self.render_field(frame, layout[0], "Name", &self.name, Field::Name, editable);
-        self.render_field(frame, layout[1], "Host", &self.host, Field::Host, editable);
-        self.render_field(frame, layout[2], "Port", &self.port, Field::Port, editable);
-        self.render_field(
-            frame,
-            layout[3],
-            "Bind DN",
-            &self.bind_dn,
-            Field::BindDn,
-            editable,
-        );
-        self.render_field(
-            frame,
-            layout[4],
-            "Base DN",
-            &self.base_dn,
-            Field::BaseDn,
-            editable,
-        );
-        self.render_field(
-            frame,
-            layout[5],
-            "Folder",
-            &self.folder,
-            Field::Folder,
-            editable,
-        );
-
-        // TLS Mode (special: shows label, not a text buffer)
-        self.render_field(
-            frame,
-            layout[6],
-            "TLS Mode",
-            self.tls_mode.label(),
-            Field::TlsMode,
-            editable,
-        );
-
-        // Credential Method
-        let cred_label = match self.credential_method {
-            CredentialMethod::Prompt => "Prompt",
-            CredentialMethod::Command => "Command",
-            CredentialMethod::Keychain => "Keychain",
-            CredentialMethod::Vault => "Vault",
-        };
-        self.render_field(
-            frame,
-            layout[7],
-            "Credential",
-            cred_label,
-            Field::CredentialMethod,
-            editable,
-        );
-
-        self.render_field(
-            frame,
-            layout[8],
-            "Password Cmd",
-            &self.password_command,
-            Field::PasswordCommand,
-            editable,
-        );
-        self.render_field(
-            frame,
-            layout[9],
-            "Page Size",
-            &self.page_size,
-            Field::PageSize,
-            editable,
-        );
-        self.render_field(
-            frame,
-            layout[10],
-            "Timeout (s)",
-            &self.timeout,
-            Field::Timeout,
-            editable,
-        );
-
-        // Relax Rules (boolean toggle)
-        let relax_str = if self.relax_rules { "Yes" } else { "No" };
-        self.render_field(
-            frame,
-            layout[11],
-            "Relax Rules",
-            relax_str,
-            Field::RelaxRules,
-            editable,
-        );
-
-        // Read Only (boolean toggle)
-        let read_only_str = if self.read_only { "Yes" } else { "No" };
-        self.render_field(
-            frame,
-            layout[12],
-            "Read Only",
-            read_only_str,
-            Field::ReadOnly,
-            editable,
-        );
+        if let Some(status) = &self.test_status {
+            let line = match status {
+                TestStatus::Running => Span::styled("Testing connection...", self.theme.dimmed()),
+                TestStatus::Passed(msg) => {
+                    Span::styled(format!("\u{2713} {}", msg), self.theme.success())
+                }
+                TestStatus::Failed(msg) => {
+                    Span::styled(format!("\u{2717} {}", msg), self.theme.error())
+                }
+            };
+            frame.render_widget(Paragraph::new(Line::from(line)), test_area);
+        }
 
         // Hints
         let hints_text = match self.mode {
             FormMode::View => "e:Edit  c:Connect  d:Delete  x:Export  i:Import",
-            FormMode::Edit => "Tab/\u{2191}\u{2193}:fields  F2:TLS  F3:Cred  F10:Save  Esc:Cancel",
-            FormMode::Create => {
-                "Tab/\u{2191}\u{2193}:fields  F2:TLS  F3:Cred  F10:Save  Esc:Cancel"
+            FormMode::Edit | FormMode::Create if self.is_valid() => {
+                "Tab/\u{2191}\u{2193}:fields  F2:TLS  F3:Cred  ^T:Test  F10:Save  Esc:Cancel"
+            }
+            FormMode::Edit | FormMode::Create => {
+                "Tab/\u{2191}\u{2193}:fields  F2:TLS  F3:Cred  Esc:Cancel  (fix errors to save)"
             }
             // FolderView/FolderEdit are handled in render_folder and never reach here
             FormMode::FolderView | FormMode::FolderEdit => unreachable!(),
         };
-        let hints = Paragraph::new(Line::from(Span::styled(hints_text, self.theme.dimmed)));
-        frame.render_widget(hints, layout[13]);
+        let hints = Paragraph::new(Line::from(Span::styled(hints_text, self.theme.dimmed())));
+        frame.render_widget(hints, hints_area);
+        self.guard.render(frame, inner, &self.theme);
+    }
+
+    /// Draw only the fields of the current section, without a border.
+    pub fn render_section(&self, frame: &mut Frame, area: Rect) {
+        let Some(section) = self.section else {
+            return;
+        };
+        let fields = section.fields();
+        let layout = Layout::vertical(
+            fields
+                .iter()
+                .map(|_| Constraint::Length(2))
+                .chain([Constraint::Min(0)]),
+        )
+        .split(area);
+        for (i, &field) in fields.iter().enumerate() {
+            let (label, value) = self.field_display(field);
+            self.render_field(frame, layout[i], label, &value, field, true);
+        }
+    }
+
+    fn field_display(&self, field: Field) -> (&'static str, String) {
+        match field {
+            Field::Name => ("Name", self.name.clone()),
+            Field::Host => ("Host", self.host.clone()),
+            Field::Port => ("Port", self.port.clone()),
+            Field::Auth => ("Auth", self.auth.label().to_string()),
+            Field::BindDn => ("Bind DN", self.bind_dn.clone()),
+            Field::Password => ("Password", "\u{2022}".repeat(self.password.chars().count())),
+            Field::BaseDn => ("Base DN", self.base_dn.clone()),
+            Field::Folder => ("Folder", self.folder.clone()),
+            Field::TlsMode => ("TLS Mode", self.tls_mode.label().to_string()),
+            Field::CaCert => ("CA Cert", self.ca_cert.clone()),
+            Field::ClientCert => ("Client Cert", self.client_cert.clone()),
+            Field::ClientKey => ("Client Key", self.client_key.clone()),
+            Field::Verify => (
+                "Verify Cert",
+                if self.verify { "Yes" } else { "No" }.to_string(),
+            ),
+            Field::CredentialMethod => (
+                "Credential",
+                match self.credential_method {
+                    CredentialMethod::Prompt => "Prompt",
+                    CredentialMethod::Command => "Command",
+                    CredentialMethod::Keychain => "Keychain",
+                    CredentialMethod::Vault => "Vault",
+                }
+                .to_string(),
+            ),
+            Field::PasswordCommand => ("Password Cmd", self.password_command.clone()),
+            Field::PageSize => ("Page Size", self.page_size.clone()),
+            Field::Timeout => ("Timeout (s)", self.timeout.clone()),
+            Field::RelaxRules => (
+                "Relax Rules",
+                if self.relax_rules { "Yes" } else { "No" }.to_string(),
+            ),
+            Field::ReadOnly => (
+                "Read Only",
+                if self.read_only { "Yes" } else { "No" }.to_string(),
+            ),
+        }
     }
 
     fn render_folder(&self, frame: &mut Frame, area: Rect, focused: bool) {
         let border_style = if focused {
-            self.theme.border_focused
+            self.theme.border_focused()
         } else {
-            self.theme.border
+            self.theme.border()
         };
 
         let title = match self.mode {
@@ -729,8 +1073,8 @@ impl ConnectionForm {
 
         // Folder path (always read-only)
         let path_lines = vec![
-            Line::from(Span::styled("Folder:", self.theme.dimmed)),
-            Line::from(Span::styled(&self.folder_path, self.theme.normal)),
+            Line::from(Span::styled("Folder:", self.theme.dimmed())),
+            Line::from(Span::styled(&self.folder_path, self.theme.normal())),
         ];
         frame.render_widget(Paragraph::new(path_lines), layout[0]);
 
@@ -741,16 +1085,16 @@ impl ConnectionForm {
             &self.folder_description
         };
         let desc_label_style = if editing {
-            self.theme.header
+            self.theme.header()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
         let desc_lines = vec![
             Line::from(Span::styled("Description:", desc_label_style)),
             Line::from(vec![
-                Span::styled(desc_display, self.theme.normal),
+                Span::styled(desc_display, self.theme.normal()),
                 if editing {
-                    Span::styled("_", self.theme.command_prompt)
+                    Span::styled("_", self.theme.command_prompt())
                 } else {
                     Span::raw("")
                 },
@@ -764,7 +1108,7 @@ impl ConnectionForm {
         } else {
             "e:Edit Description"
         };
-        let hints = Paragraph::new(Line::from(Span::styled(hints_text, self.theme.dimmed)));
+        let hints = Paragraph::new(Line::from(Span::styled(hints_text, self.theme.dimmed())));
         frame.render_widget(hints, layout[2]);
     }
 
@@ -779,18 +1123,27 @@ impl ConnectionForm {
     ) {
         let is_active = editable && self.active_field == field;
         let label_style = if is_active {
-            self.theme.header
+            self.theme.header()
+        } else {
+            self.theme.dimmed()
+        };
+        let value_style = self.theme.normal();
+        let error = if editable && self.touched.contains(&field) {
+            self.field_error(field)
         } else {
-            self.theme.dimmed
+            None
         };
-        let value_style = self.theme.normal;
 
+        let mut label_line = vec![Span::styled(format!("{}:", label), label_style)];
+        if let Some(error) = error {
+            label_line.push(Span::styled(format!(" {}", error), self.theme.error()));
+        }
         let lines = vec![
-            Line::from(Span::styled(format!("{}:", label), label_style)),
+            Line::from(label_line),
             Line::from(vec![
                 Span::styled(value, value_style),
                 if is_active {
-                    Span::styled("_", self.theme.command_prompt)
+                    Span::styled("_", self.theme.command_prompt())
                 } else {
                     Span::raw("")
                 },
@@ -799,3 +1152,207 @@ impl ConnectionForm {
         frame.render_widget(Paragraph::new(lines), area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn profile() -> ConnectionProfile {
+        let mut form = ConnectionForm::new(Theme::load("dark"));
+        form.new_profile();
+        form.handle_key_event(key(KeyCode::Tab));
+        for c in "ldap.example.com".chars() {
+            form.handle_key_event(key(KeyCode::Char(c)));
+        }
+        form.profile().unwrap()
+    }
+
+    #[test]
+    fn test_esc_asks_before_dropping_edits() {
+        let mut form = ConnectionForm::new(Theme::load("dark"));
+        form.view_profile(0, &profile());
+
+        // Nothing changed: Esc leaves edit mode straight away
+        form.edit_profile();
+        form.handle_key_event(key(KeyCode::Esc));
+        assert!(!form.is_editing());
+
+        form.edit_profile();
+        form.handle_key_event(key(KeyCode::Char('x')));
+        form.handle_key_event(key(KeyCode::Esc));
+        assert!(form.is_editing() && form.guard.is_asking());
+        form.handle_key_event(key(KeyCode::Char('n')));
+        assert!(form.is_editing());
+        assert_eq!(form.name, "ldap.example.com:389x");
+
+        // Undoing the change makes it clean again
+        form.handle_key_event(key(KeyCode::Backspace));
+        form.handle_key_event(key(KeyCode::Esc));
+        assert!(!form.is_editing());
+
+        form.edit_profile();
+        form.handle_key_event(key(KeyCode::F(2)));
+        form.handle_key_event(key(KeyCode::Esc));
+        form.handle_key_event(key(KeyCode::Char('y')));
+        assert!(!form.is_editing());
+        assert_eq!(form.mode, FormMode::View);
+    }
+
+    #[test]
+    fn test_new_profile_discard_can_be_turned_off() {
+        let mut form = ConnectionForm::new(Theme::load("dark")).with_confirm_discard(false);
+        form.new_profile();
+        form.handle_key_event(key(KeyCode::Char('a')));
+        form.handle_key_event(key(KeyCode::Esc));
+        assert!(!form.is_editing());
+        assert!(form.name.is_empty());
+    }
+
+    fn type_text(form: &mut ConnectionForm, text: &str) {
+        for c in text.chars() {
+            form.handle_key_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_fields_are_checked_once_left_and_block_saving() {
+        let mut form = ConnectionForm::new(Theme::load("dark"));
+        form.set_profile_names(vec!["Prod".to_string()]);
+        form.new_profile();
+        type_text(&mut form, "prod");
+        assert!(!form.touched.contains(&Field::Name));
+        form.handle_key_event(key(KeyCode::Tab));
+        assert!(form.touched.contains(&Field::Name));
+        assert_eq!(
+            form.field_error(Field::Name).unwrap(),
+            "A profile named 'prod' already exists"
+        );
+
+        // Host left empty, port out of range
+        form.handle_key_event(key(KeyCode::Tab));
+        form.port = "0".to_string();
+        assert!(!form.is_valid());
+        let Action::ErrorMessage(msg) = form.handle_key_event(key(KeyCode::F(10))) else {
+            panic!("saving an invalid form should fail");
+        };
+        assert!(msg.contains("already exists"));
+        assert_eq!(form.active_field, Field::Name);
+        assert_eq!(form.touched, Field::ALL);
+        assert_eq!(form.field_error(Field::Host).unwrap(), "Host is required");
+        assert!(form.field_error(Field::Port).unwrap().contains("1-65535"));
+
+        form.name = "staging".to_string();
+        form.host = "ldap.example.com".to_string();
+        form.port = "389".to_string();
+        assert!(matches!(
+            form.handle_key_event(key(KeyCode::F(10))),
+            Action::ConnMgrCreate(p) if p.name == "staging"
+        ));
+    }
+
+    #[test]
+    fn test_editing_keeps_its_own_name() {
+        let mut form = ConnectionForm::new(Theme::load("dark"));
+        let mut prod = profile();
+        prod.name = "Prod".to_string();
+        form.set_profile_names(vec!["Prod".to_string(), "Test".to_string()]);
+        form.view_profile(0, &prod);
+        form.edit_profile();
+        assert!(form.is_valid());
+        form.name = "test".to_string();
+        assert!(form.field_error(Field::Name).is_some());
+    }
+
+    #[test]
+    fn test_ctrl_t_tests_without_saving() {
+        let mut form = ConnectionForm::new(Theme::load("dark"));
+        form.new_profile();
+        form.handle_key_event(key(KeyCode::Tab));
+        type_text(&mut form, "ldaps://ldap.example.com");
+        // Auth, bind DN, then the masked password
+        for _ in 0..4 {
+            form.handle_key_event(key(KeyCode::Tab));
+        }
+        type_text(&mut form, "s3cret");
+        assert_eq!(form.field_display(Field::Password).1, "\u{2022}".repeat(6));
+
+        let ctrl_t = KeyEvent::new(KeyCode::Char('t'), KeyModifiers::CONTROL);
+        let Action::ConnMgrTest(tested, password) = form.handle_key_event(ctrl_t) else {
+            panic!("Ctrl-T should test the connection");
+        };
+        assert_eq!(tested.host, "ldap.example.com");
+        assert_eq!(tested.port, 636);
+        assert_eq!(password, "s3cret");
+        assert!(form.is_editing());
+        assert_eq!(form.test_status, Some(TestStatus::Running));
+
+        form.test_finished(Err("Connection failed: refused".to_string()));
+        assert_eq!(
+            form.test_status,
+            Some(TestStatus::Failed("Connection failed: refused".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_tls_options_round_trip_through_the_form() {
+        let mut form = ConnectionForm::new(Theme::load("dark"));
+        let mut original = profile();
+        original.tls_options = TlsOptions {
+            ca_cert: Some("/etc/ssl/corp-ca.pem".into()),
+            client_cert: Some("client.pem".into()),
+            client_key: Some("client.key".into()),
+            verify: false,
+        };
+        form.view_profile(0, &original);
+        form.edit_profile();
+        assert_eq!(form.to_profile().unwrap().tls_options, original.tls_options);
+
+        // A client cert needs its key
+        form.active_field = Field::ClientKey;
+        form.client_key.clear();
+        let Action::ErrorMessage(msg) = form.handle_key_event(key(KeyCode::F(10))) else {
+            panic!("a client cert without a key should not save");
+        };
+        assert!(msg.contains("set together"));
+
+        form.client_cert.clear();
+        form.active_field = Field::Verify;
+        form.handle_key_event(key(KeyCode::Char(' ')));
+        let Action::ConnMgrSave(_, saved) = form.handle_key_event(key(KeyCode::F(10))) else {
+            panic!("expected the profile to save");
+        };
+        assert_eq!(saved.tls_options.client_cert, None);
+        assert!(saved.tls_options.verify);
+    }
+
+    #[test]
+    fn test_auth_method_cycles_and_saves() {
+        let mut form = ConnectionForm::new(Theme::load("dark"));
+        form.view_profile(0, &profile());
+        form.edit_profile();
+        form.active_field = Field::Port;
+        form.handle_key_event(key(KeyCode::Tab));
+        assert_eq!(form.active_field, Field::Auth);
+
+        form.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(form.auth, AuthMethod::SaslExternal);
+        form.handle_key_event(key(KeyCode::Char(' ')));
+        assert_eq!(form.field_display(Field::Auth).1, "GSSAPI (Kerberos)");
+        if cfg!(feature = "gssapi") {
+            let Action::ConnMgrSave(_, saved) = form.handle_key_event(key(KeyCode::F(10))) else {
+                panic!("expected the profile to save");
+            };
+            assert_eq!(saved.auth, AuthMethod::Gssapi);
+            form.view_profile(0, &saved);
+            assert_eq!(form.auth, AuthMethod::Gssapi);
+        } else {
+            // Refused while the profile is edited, not when it binds
+            assert!(form.field_error(Field::Auth).unwrap().contains("GSSAPI"));
+            assert!(!form.is_valid());
+        }
+    }
+}
@@ -1,21 +1,31 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::KeyEvent;
 use ratatui::layout::Rect;
 use ratatui::style::Modifier;
 use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
 use ratatui::Frame;
 use tui_tree_widget::{Tree, TreeItem, TreeState};
 
+use loom_core::health::ConnectionHealth;
+
 use crate::action::{Action, ConnectionId};
 use crate::config::ConnectionProfile;
+use crate::connecting::ConnectAttempts;
+use crate::keymap::{Command, ContextKeys, KeyContext, Keymap};
 use crate::theme::Theme;
 
 use std::collections::BTreeMap;
 
+/// Badge on read-only profiles, and on the status bar while one is active.
+pub const READ_ONLY_MARKER: &str = "\u{1F512}";
+
 /// Info about an active connection for display in the tree.
 #[derive(Debug, Clone)]
 pub struct ActiveConnInfo {
     pub id: ConnectionId,
     pub label: String,
+    pub health: ConnectionHealth,
+    /// Writes are refused: the profile is read-only.
+    pub read_only: bool,
 }
 
 /// Left panel in Connections layout: folder tree of saved profiles + active connections.
@@ -28,6 +38,7 @@ pub struct ConnectionsTree {
     active_keys: Vec<(String, ConnectionId)>,
     /// Maps tree item keys like "folder:Production" to folder paths
     folder_keys: Vec<(String, String)>,
+    keys: ContextKeys,
 }
 
 impl ConnectionsTree {
@@ -38,9 +49,20 @@ impl ConnectionsTree {
             profile_keys: Vec::new(),
             active_keys: Vec::new(),
             folder_keys: Vec::new(),
+            keys: Keymap::default().context(KeyContext::Connections),
         }
     }
 
+    /// Use the profiles tree keys of `keymap` instead of the defaults.
+    pub fn with_keys(mut self, keymap: &Keymap) -> Self {
+        self.set_keys(keymap);
+        self
+    }
+
+    pub fn set_keys(&mut self, keymap: &Keymap) {
+        self.keys = keymap.context(KeyContext::Connections);
+    }
+
     /// Get the currently selected key from the tree state.
     fn selected_key(&self) -> Option<&String> {
         self.tree_state.selected().last()
@@ -65,7 +87,6 @@ impl ConnectionsTree {
     }
 
     /// Get the connection id for the currently selected item, if it's an active connection.
-    #[allow(dead_code)]
     fn selected_active_id(&self) -> Option<ConnectionId> {
         let key = self.selected_key()?;
         self.active_keys
@@ -75,31 +96,36 @@ impl ConnectionsTree {
     }
 
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
-        match key.code {
-            KeyCode::Up | KeyCode::Char('k') => {
+        let Some(command) = self.keys.command(&key) else {
+            return Action::None;
+        };
+        match command {
+            // The selected active connection, else the active tab
+            Command::Reconnect => Action::Reconnect(self.selected_active_id()),
+            Command::Up => {
                 self.tree_state.key_up();
                 self.on_selection_changed()
             }
-            KeyCode::Down | KeyCode::Char('j') => {
+            Command::Down => {
                 self.tree_state.key_down();
                 self.on_selection_changed()
             }
-            KeyCode::Right | KeyCode::Char('l') => {
+            Command::Expand => {
                 self.tree_state.toggle_selected();
                 self.on_selection_changed()
             }
-            KeyCode::Left | KeyCode::Char('h') => {
+            Command::Collapse => {
                 self.tree_state.key_left();
                 Action::None
             }
-            KeyCode::Char('c') => {
+            Command::Connect => {
                 if let Some(idx) = self.selected_profile_index() {
                     Action::ConnMgrConnect(idx)
                 } else {
                     Action::None
                 }
             }
-            KeyCode::Char('d') | KeyCode::Delete => {
+            Command::Delete => {
                 if let Some(idx) = self.selected_profile_index() {
                     Action::ShowConfirm(
                         "Delete this connection profile?".to_string(),
@@ -109,7 +135,7 @@ impl ConnectionsTree {
                     Action::None
                 }
             }
-            KeyCode::Enter => {
+            Command::Open => {
                 if self.selected_key().map(|k| k.as_str()) == Some("action:new") {
                     Action::ConnMgrNew
                 } else if let Some(idx) = self.selected_profile_index() {
@@ -118,23 +144,27 @@ impl ConnectionsTree {
                     Action::None
                 }
             }
-            KeyCode::Char('n') => Action::ConnMgrNew,
-            KeyCode::Char('e') => {
+            Command::Create => Action::ConnMgrNew,
+            Command::Edit => {
                 if let Some(idx) = self.selected_profile_index() {
                     Action::ConnMgrSelect(idx)
                 } else {
                     Action::None
                 }
             }
-            KeyCode::Char('u') => {
+            Command::Duplicate => {
                 if let Some(idx) = self.selected_profile_index() {
                     Action::ConnMgrDuplicate(idx)
                 } else {
                     Action::None
                 }
             }
-            KeyCode::Char('x') => Action::ConnMgrExport,
-            KeyCode::Char('i') => Action::ConnMgrImport,
+            Command::Export => Action::ConnMgrExport,
+            // Server info for an active connection, import for anything else
+            Command::ServerInfo => match self.selected_active_id() {
+                Some(id) => Action::ShowServerInfo(Some(id)),
+                None => Action::ConnMgrImport,
+            },
             _ => Action::None,
         }
     }
@@ -154,7 +184,20 @@ impl ConnectionsTree {
         &mut self,
         profiles: &[ConnectionProfile],
         active: &[ActiveConnInfo],
+        connecting: &ConnectAttempts,
     ) -> Vec<TreeItem<'static, String>> {
+        // Profiles being connected to show a spinner and elapsed time
+        let profile_label = |profile: &ConnectionProfile| {
+            let mut label = profile.name.clone();
+            if profile.read_only {
+                label.push_str(&format!(" {}", READ_ONLY_MARKER));
+            }
+            if let Some(progress) = connecting.progress(profile) {
+                label.push_str(&format!(" {}", progress));
+            }
+            label
+        };
+
         self.profile_keys.clear();
         self.active_keys.clear();
         self.folder_keys.clear();
@@ -167,7 +210,11 @@ impl ConnectionsTree {
             for info in active {
                 let key = format!("active:{}", info.id);
                 self.active_keys.push((key.clone(), info.id));
-                let item = TreeItem::new_leaf(key, format!("* {}", info.label));
+                let mut label = format!("{} {}", info.health.symbol(), info.label);
+                if info.read_only {
+                    label.push_str(&format!(" {}", READ_ONLY_MARKER));
+                }
+                let item = TreeItem::new_leaf(key, label);
                 active_children.push(item);
             }
             let active_section = TreeItem::new(
@@ -207,7 +254,7 @@ impl ConnectionsTree {
             for (idx, profile) in profiles_in_folder {
                 let key = format!("profile:{}", idx);
                 self.profile_keys.push((key.clone(), *idx));
-                let item = TreeItem::new_leaf(key, profile.name.clone());
+                let item = TreeItem::new_leaf(key, profile_label(profile));
                 children.push(item);
             }
 
@@ -245,7 +292,7 @@ impl ConnectionsTree {
         for (idx, profile) in &ungrouped {
             let key = format!("profile:{}", idx);
             self.profile_keys.push((key.clone(), *idx));
-            let item = TreeItem::new_leaf(key, profile.name.clone());
+            let item = TreeItem::new_leaf(key, profile_label(profile));
             top_items.push(item);
         }
 
@@ -266,9 +313,9 @@ impl ConnectionsTree {
         items: &[TreeItem<'_, String>],
     ) {
         let border_style = if focused {
-            self.theme.border_focused
+            self.theme.border_focused()
         } else {
-            self.theme.border
+            self.theme.border()
         };
 
         let mut block = Block::default()
@@ -282,16 +329,16 @@ impl ConnectionsTree {
         let tree_widget = Tree::new(items)
             .expect("tree widget")
             .block(block)
-            .highlight_style(self.theme.tree_node_selected.add_modifier(Modifier::BOLD));
+            .highlight_style(self.theme.tree_node_selected().add_modifier(Modifier::BOLD));
 
         frame.render_stateful_widget(tree_widget, area, &mut self.tree_state);
     }
 
     pub fn render_empty(&self, frame: &mut Frame, area: Rect, focused: bool) {
         let border_style = if focused {
-            self.theme.border_focused
+            self.theme.border_focused()
         } else {
-            self.theme.border
+            self.theme.border()
         };
 
         let mut block = Block::default()
@@ -303,7 +350,7 @@ impl ConnectionsTree {
         }
 
         let empty = Paragraph::new("No profiles configured")
-            .style(self.theme.dimmed)
+            .style(self.theme.dimmed())
             .block(block);
         frame.render_widget(empty, area);
     }
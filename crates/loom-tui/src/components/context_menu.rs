@@ -1,11 +1,12 @@
 use crossterm::event::{KeyCode, KeyEvent};
+use loom_core::account::AccountAction;
 use ratatui::layout::Rect;
 use ratatui::style::Modifier;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph};
 use ratatui::Frame;
 
-use crate::action::Action;
+use crate::action::{Action, ContextMenuSource};
 use crate::theme::Theme;
 
 /// A single item in the context menu.
@@ -41,31 +42,84 @@ impl ContextMenu {
         self.items = vec![
             MenuItem {
                 label: "Copy DN".into(),
-                hint: String::new(),
+                hint: "y".into(),
                 action: Action::CopyToClipboard(dn.to_string()),
             },
+            MenuItem {
+                label: "Copy As...".into(),
+                hint: ">".into(),
+                action: Action::ShowCopyAsMenu(ContextMenuSource::Tree { dn: dn.to_string() }),
+            },
             MenuItem {
                 label: "Create Child Entry".into(),
                 hint: "a".into(),
                 action: Action::ShowCreateEntryDialog(dn.to_string()),
             },
+            MenuItem {
+                label: "Clone Entry".into(),
+                hint: "c".into(),
+                action: Action::ShowCopyEntryDialog {
+                    source: dn.to_string(),
+                    parent: None,
+                },
+            },
+            MenuItem {
+                label: "Clone Subtree".into(),
+                hint: String::new(),
+                action: Action::ShowCopySubtreeDialog {
+                    source: dn.to_string(),
+                    parent: None,
+                },
+            },
             MenuItem {
                 label: "Export Subtree".into(),
-                hint: "F4".into(),
-                action: Action::ShowExportDialog,
+                hint: "E".into(),
+                action: Action::ShowSubtreeExport(dn.to_string()),
+            },
+            MenuItem {
+                label: "Snapshot Subtree".into(),
+                hint: String::new(),
+                action: Action::ShowSnapshotDialog,
+            },
+            MenuItem {
+                label: "Compare Subtree...".into(),
+                hint: String::new(),
+                action: Action::ShowSubtreeCompareDialog,
             },
             MenuItem {
                 label: "Refresh".into(),
                 hint: "r".into(),
                 action: Action::EntryRefresh,
             },
+            MenuItem {
+                label: "Rename / Move".into(),
+                hint: "r".into(),
+                action: Action::ShowRenameDialog(dn.to_string()),
+            },
+            MenuItem {
+                label: "Set Password...".into(),
+                hint: String::new(),
+                action: Action::ShowSetPassword(dn.to_string()),
+            },
             MenuItem {
                 label: "Delete Entry".into(),
-                hint: "d".into(),
-                action: Action::ShowConfirm(
-                    format!("Delete entry?\n{}", dn),
-                    Box::new(Action::DeleteEntry(dn.to_string())),
-                ),
+                hint: "dd".into(),
+                action: Action::ShowDeleteDialog(dn.to_string()),
+            },
+            MenuItem {
+                label: "Enable Account".into(),
+                hint: String::new(),
+                action: Action::ShowAccountAction(dn.to_string(), AccountAction::Enable),
+            },
+            MenuItem {
+                label: "Disable Account".into(),
+                hint: String::new(),
+                action: Action::ShowAccountAction(dn.to_string(), AccountAction::Disable),
+            },
+            MenuItem {
+                label: "Unlock Account".into(),
+                hint: String::new(),
+                action: Action::ShowAccountAction(dn.to_string(), AccountAction::Unlock),
             },
         ];
         self.selected = 0;
@@ -91,6 +145,15 @@ impl ContextMenu {
                 hint: String::new(),
                 action: Action::CopyToClipboard(dn.to_string()),
             },
+            MenuItem {
+                label: "Copy As...".into(),
+                hint: ">".into(),
+                action: Action::ShowCopyAsMenu(ContextMenuSource::Detail {
+                    dn: dn.to_string(),
+                    attr_name: attr_name.to_string(),
+                    attr_value: attr_value.to_string(),
+                }),
+            },
             MenuItem {
                 label: "Edit Value".into(),
                 hint: "e".into(),
@@ -149,6 +212,25 @@ impl ContextMenu {
         self.visible = true;
     }
 
+    /// Show the "Copy As" submenu: one item per template with its rendered
+    /// text, or the rendering error for a broken template.
+    pub fn show_copy_as(&mut self, entries: Vec<(String, Result<String, String>)>) {
+        self.items = entries
+            .into_iter()
+            .map(|(name, rendered)| MenuItem {
+                action: match rendered {
+                    Ok(text) => Action::CopyToClipboard(text),
+                    Err(e) => Action::ErrorMessage(format!("Copy template '{}': {}", name, e)),
+                },
+                label: name,
+                hint: String::new(),
+            })
+            .collect();
+        self.selected = 0;
+        self.anchor = None;
+        self.visible = true;
+    }
+
     pub fn hide(&mut self) {
         self.visible = false;
         self.items.clear();
@@ -246,7 +328,7 @@ impl ContextMenu {
 
         let block = Block::default()
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
+            .border_style(self.theme.popup_border())
             .border_type(BorderType::Rounded);
 
         let inner = block.inner(area);
@@ -261,9 +343,9 @@ impl ContextMenu {
 
             let is_selected = i == self.selected;
             let style = if is_selected {
-                self.theme.selected.add_modifier(Modifier::BOLD)
+                self.theme.selected().add_modifier(Modifier::BOLD)
             } else {
-                self.theme.normal
+                self.theme.normal()
             };
 
             if inner.width < 4 {
@@ -289,7 +371,7 @@ impl ContextMenu {
                     if is_selected {
                         style
                     } else {
-                        self.theme.dimmed
+                        self.theme.dimmed()
                     },
                 ),
             ]);
@@ -318,10 +400,18 @@ mod tests {
         menu.show_for_tree("dc=example,dc=com");
         assert!(menu.visible);
         assert_eq!(menu.selected, 0);
-        assert_eq!(menu.item_count(), 5);
+        assert_eq!(menu.item_count(), 15);
         assert_eq!(menu.items[0].label, "Copy DN");
-        assert_eq!(menu.items[1].label, "Create Child Entry");
-        assert_eq!(menu.items[4].label, "Delete Entry");
+        assert_eq!(menu.items[1].label, "Copy As...");
+        assert_eq!(menu.items[2].label, "Create Child Entry");
+        assert_eq!(menu.items[3].label, "Clone Entry");
+        assert_eq!(menu.items[4].label, "Clone Subtree");
+        assert_eq!(menu.items[6].label, "Snapshot Subtree");
+        assert_eq!(menu.items[7].label, "Compare Subtree...");
+        assert_eq!(menu.items[9].label, "Rename / Move");
+        assert_eq!(menu.items[10].label, "Set Password...");
+        assert_eq!(menu.items[11].label, "Delete Entry");
+        assert_eq!(menu.items[14].label, "Unlock Account");
     }
 
     #[test]
@@ -330,11 +420,12 @@ mod tests {
         menu.show_for_detail("dc=example,dc=com", "cn", "Test User");
         assert!(menu.visible);
         assert_eq!(menu.selected, 0);
-        assert_eq!(menu.item_count(), 6);
+        assert_eq!(menu.item_count(), 7);
         assert_eq!(menu.items[0].label, "Copy Attribute Name");
         assert_eq!(menu.items[1].label, "Copy Attribute Value");
         assert_eq!(menu.items[2].label, "Copy DN");
-        assert_eq!(menu.items[3].label, "Edit Value");
+        assert_eq!(menu.items[3].label, "Copy As...");
+        assert_eq!(menu.items[4].label, "Edit Value");
     }
 
     #[test]
@@ -408,6 +499,7 @@ mod tests {
     fn test_space_selects_item() {
         let mut menu = make_menu();
         menu.show_for_tree("dc=example,dc=com");
+        menu.handle_key_event(key(KeyCode::Down)); // select "Copy As..."
         menu.handle_key_event(key(KeyCode::Down)); // select "Create Child Entry"
         let action = menu.handle_key_event(key(KeyCode::Char(' ')));
         assert!(matches!(action, Action::ShowCreateEntryDialog(_)));
@@ -436,15 +528,21 @@ mod tests {
     fn test_first_letter_jump() {
         let mut menu = make_menu();
         menu.show_for_tree("dc=example,dc=com");
-        // 'e' should jump to "Export Subtree" (index 2)
+        // 'e' should jump to "Export Subtree" (index 5)
         menu.handle_key_event(key(KeyCode::Char('e')));
-        assert_eq!(menu.selected, 2);
-        // 'r' should jump to "Refresh" (index 3)
+        assert_eq!(menu.selected, 5);
+        // 's' should jump to "Snapshot Subtree" (index 6)
+        menu.handle_key_event(key(KeyCode::Char('s')));
+        assert_eq!(menu.selected, 6);
+        // 'r' should jump to "Refresh" (index 8)
         menu.handle_key_event(key(KeyCode::Char('r')));
-        assert_eq!(menu.selected, 3);
-        // 'd' should jump to "Delete Entry" (index 4)
+        assert_eq!(menu.selected, 8);
+        // 'd' should jump to "Delete Entry" (index 11)
         menu.handle_key_event(key(KeyCode::Char('d')));
-        assert_eq!(menu.selected, 4);
+        assert_eq!(menu.selected, 11);
+        // 'u' should jump to "Unlock Account" (index 14)
+        menu.handle_key_event(key(KeyCode::Char('u')));
+        assert_eq!(menu.selected, 14);
     }
 
     #[test]
@@ -456,6 +554,39 @@ mod tests {
         assert_eq!(menu.anchor, Some((10, 20)));
     }
 
+    #[test]
+    fn test_copy_as_submenu() {
+        let mut menu = make_menu();
+        menu.show_for_tree("cn=a,dc=example,dc=com");
+        menu.handle_key_event(key(KeyCode::Down));
+        let action = menu.handle_key_event(key(KeyCode::Enter));
+        assert!(matches!(
+            action,
+            Action::ShowCopyAsMenu(ContextMenuSource::Tree { .. })
+        ));
+
+        menu.show_copy_as(vec![
+            ("filter_member".to_string(), Ok("(member=cn=a)".to_string())),
+            (
+                "broken".to_string(),
+                Err("Unknown placeholder {x}".to_string()),
+            ),
+        ]);
+        assert_eq!(menu.item_count(), 2);
+        assert!(matches!(
+            menu.handle_key_event(key(KeyCode::Enter)),
+            Action::CopyToClipboard(ref text) if text == "(member=cn=a)"
+        ));
+        menu.show_copy_as(vec![(
+            "broken".to_string(),
+            Err("Unknown placeholder {x}".to_string()),
+        )]);
+        assert!(matches!(
+            menu.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+    }
+
     #[test]
     fn test_handle_key_when_not_visible() {
         let mut menu = make_menu();
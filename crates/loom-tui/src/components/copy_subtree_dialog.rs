@@ -0,0 +1,428 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Gauge, Paragraph};
+use ratatui::Frame;
+
+use loom_core::dn;
+use loom_core::tree::format_count;
+
+use crate::action::Action;
+use crate::components::path_input::PathInput;
+use crate::components::popup::{OverwriteGuard, Popup};
+use crate::paths::expand_path;
+use crate::theme::Theme;
+
+/// Which field is being edited.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CopyField {
+    NewParent,
+    Rdn,
+    Changefile,
+}
+
+/// Dialog copying a branch under a new parent, or writing the adds it
+/// would make to an LDIF changefile as a dry run, and the progress of
+/// the copy once it runs.
+pub struct CopySubtreeDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    source: String,
+    active_field: CopyField,
+    new_parent: String,
+    rdn: String,
+    /// Write the adds to `changefile` instead of the server.
+    pub dry_run: bool,
+    changefile: PathInput,
+    overwrite: OverwriteGuard,
+    /// Why the last submit was refused.
+    error: Option<String>,
+    /// Entries added and planned while a copy runs.
+    progress: Option<(usize, usize)>,
+}
+
+impl CopySubtreeDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Copy Subtree", theme.clone()).with_size(60, 45),
+            theme,
+            source: String::new(),
+            active_field: CopyField::NewParent,
+            new_parent: String::new(),
+            rdn: String::new(),
+            dry_run: false,
+            changefile: PathInput::default(),
+            overwrite: OverwriteGuard::default(),
+            error: None,
+            progress: None,
+        }
+    }
+
+    /// Open to copy the branch at `source` under `new_parent`.
+    pub fn show(&mut self, source: String, new_parent: String) {
+        self.rdn = dn::rdn(&source).to_string();
+        self.source = source;
+        self.new_parent = new_parent;
+        self.active_field = CopyField::NewParent;
+        self.dry_run = false;
+        self.changefile.set("subtree-copy.ldif");
+        self.overwrite.reset();
+        self.error = None;
+        self.progress = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.progress = None;
+        self.popup.hide();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.progress.is_some()
+    }
+
+    pub fn set_progress(&mut self, done: usize, total: usize) {
+        if self.visible {
+            self.progress = Some((done, total));
+        }
+    }
+
+    fn fields(&self) -> &'static [CopyField] {
+        if self.dry_run {
+            &[CopyField::NewParent, CopyField::Rdn, CopyField::Changefile]
+        } else {
+            &[CopyField::NewParent, CopyField::Rdn]
+        }
+    }
+
+    fn move_field(&mut self, forward: bool) {
+        let fields = self.fields();
+        let i = fields
+            .iter()
+            .position(|f| *f == self.active_field)
+            .unwrap_or(0);
+        let next = if forward {
+            (i + 1) % fields.len()
+        } else {
+            (i + fields.len() - 1) % fields.len()
+        };
+        self.active_field = fields[next];
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if self.is_running() {
+            return match key.code {
+                KeyCode::Esc => Action::SubtreeCopyCancel,
+                _ => Action::None,
+            };
+        }
+        if self.overwrite.is_asking() {
+            if self.overwrite.confirm(&key) {
+                return self.submit(true);
+            }
+            return Action::None;
+        }
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Tab
+                if self.active_field == CopyField::Changefile && self.changefile.complete() =>
+            {
+                Action::None
+            }
+            KeyCode::Tab | KeyCode::Down => {
+                self.move_field(true);
+                Action::None
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                self.move_field(false);
+                Action::None
+            }
+            KeyCode::F(3) => {
+                self.dry_run = !self.dry_run;
+                if !self.fields().contains(&self.active_field) {
+                    self.active_field = CopyField::NewParent;
+                }
+                Action::None
+            }
+            KeyCode::Enter => self.submit(false),
+            _ => {
+                match self.active_field {
+                    CopyField::NewParent => edit(&mut self.new_parent, key),
+                    CopyField::Rdn => edit(&mut self.rdn, key),
+                    CopyField::Changefile => {
+                        self.changefile.handle_key(key);
+                    }
+                }
+                self.error = None;
+                Action::None
+            }
+        }
+    }
+
+    /// Check the target and hand the copy to the app, once `confirmed` if
+    /// a dry run would replace a file.
+    fn submit(&mut self, confirmed: bool) -> Action {
+        if self.new_parent.trim().is_empty() {
+            self.error = Some("New parent DN is required".to_string());
+            return Action::None;
+        }
+        let target = match dn::renamed(&self.source, &self.rdn, Some(&self.new_parent)) {
+            Ok(target) => target,
+            Err(e) => {
+                self.error = Some(e.message);
+                return Action::None;
+            }
+        };
+        if dn::normalize(&target) == dn::normalize(&self.source) {
+            self.error = Some("The copy needs a new parent or RDN".to_string());
+            return Action::None;
+        }
+
+        let changefile = if self.dry_run {
+            if self.changefile.value().trim().is_empty() {
+                self.error = Some("Changefile is required".to_string());
+                return Action::None;
+            }
+            let path = expand_path(self.changefile.value().trim());
+            if !confirmed && self.overwrite.needs_confirm(&path) {
+                return Action::None;
+            }
+            self.hide();
+            Some(path)
+        } else {
+            self.progress = Some((0, 0));
+            None
+        };
+        Action::CopySubtree {
+            source: self.source.clone(),
+            target,
+            changefile,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+        let inner = self.popup.render_frame(frame, full);
+        // Layout: source + dry run (3) | parent (2) | rdn (2) | changefile (2) | note (flex) | bottom (1)
+        let layout = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Length(2),
+            Constraint::Length(2),
+            Constraint::Length(2),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(Span::styled("Copy the branch at:", self.theme.dimmed())),
+                Line::from(Span::styled(self.source.as_str(), self.theme.normal())),
+                Line::from(vec![
+                    Span::styled("Dry run: ", self.theme.header()),
+                    Span::styled(
+                        if self.dry_run {
+                            "write the adds to an LDIF changefile"
+                        } else {
+                            "off, add to the server"
+                        },
+                        self.theme.success(),
+                    ),
+                    Span::styled("  (F3 to toggle)", self.theme.dimmed()),
+                ]),
+            ]),
+            layout[0],
+        );
+        self.render_field(
+            frame,
+            layout[1],
+            "New parent DN",
+            &self.new_parent,
+            CopyField::NewParent,
+        );
+        self.render_field(frame, layout[2], "New RDN", &self.rdn, CopyField::Rdn);
+        if self.dry_run {
+            self.render_field(
+                frame,
+                layout[3],
+                "Changefile",
+                self.changefile.value(),
+                CopyField::Changefile,
+            );
+        }
+        if let Some(error) = &self.error {
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(error.as_str(), self.theme.error()))),
+                layout[4],
+            );
+        }
+
+        if let Some((done, total)) = self.progress {
+            let ratio = if total == 0 {
+                0.0
+            } else {
+                done as f64 / total as f64
+            };
+            let label = if total == 0 {
+                "Reading the subtree\u{2026}".to_string()
+            } else {
+                format!(
+                    "Added {} / {}  (Esc:stop)",
+                    format_count(done as u64),
+                    format_count(total as u64)
+                )
+            };
+            let gauge = Gauge::default()
+                .gauge_style(self.theme.selected())
+                .ratio(ratio.min(1.0))
+                .label(label);
+            frame.render_widget(gauge, layout[5]);
+            return;
+        }
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                "Tab:next field  F3:dry run  Enter:copy  Esc:cancel",
+                self.theme.dimmed(),
+            ))),
+            layout[5],
+        );
+        self.overwrite.render(frame, inner, &self.theme);
+    }
+
+    fn render_field(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        label: &str,
+        value: &str,
+        field: CopyField,
+    ) {
+        let is_active = self.active_field == field;
+        let (label_style, value_style) = if is_active {
+            (self.theme.header(), self.theme.normal())
+        } else {
+            (self.theme.dimmed(), self.theme.dimmed())
+        };
+        let mut value_line = vec![Span::styled(value.to_string(), value_style)];
+        if is_active {
+            value_line.push(Span::styled("_", self.theme.command_prompt()));
+        }
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(Span::styled(format!("{}:", label), label_style)),
+                Line::from(value_line),
+            ]),
+            area,
+        );
+    }
+}
+
+fn edit(buf: &mut String, key: KeyEvent) {
+    match key.code {
+        KeyCode::Backspace => {
+            buf.pop();
+        }
+        KeyCode::Char(c) => buf.push(c),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    fn type_text(dialog: &mut CopySubtreeDialog, text: &str) {
+        for c in text.chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_copy_runs_until_cancelled() {
+        let mut dialog = CopySubtreeDialog::new(Theme::load("dark"));
+        dialog.show(
+            "ou=People,dc=example,dc=com".to_string(),
+            "dc=example,dc=com".to_string(),
+        );
+        // Same parent and RDN is no copy
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::None
+        ));
+        assert!(dialog.error.is_some());
+
+        dialog.handle_key_event(key(KeyCode::Tab));
+        for _ in 0.."People".len() {
+            dialog.handle_key_event(key(KeyCode::Backspace));
+        }
+        type_text(&mut dialog, "Staff");
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::CopySubtree {
+                source,
+                target,
+                changefile: None,
+            } => {
+                assert_eq!(source, "ou=People,dc=example,dc=com");
+                assert_eq!(target, "ou=Staff,dc=example,dc=com");
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(dialog.is_running());
+        dialog.set_progress(2, 5);
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Esc)),
+            Action::SubtreeCopyCancel
+        ));
+    }
+
+    #[test]
+    fn test_dry_run_and_own_subtree() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut dialog = CopySubtreeDialog::new(Theme::load("dark"));
+        dialog.show(
+            "ou=People,dc=example,dc=com".to_string(),
+            "ou=People,dc=example,dc=com".to_string(),
+        );
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::None
+        ));
+        assert!(dialog.error.is_some());
+
+        for _ in 0.."ou=People,dc=example,dc=com".len() {
+            dialog.handle_key_event(key(KeyCode::Backspace));
+        }
+        assert!(dialog.error.is_none());
+        type_text(&mut dialog, "o=copy,dc=example,dc=com");
+        dialog.handle_key_event(key(KeyCode::F(3)));
+        dialog.handle_key_event(key(KeyCode::BackTab));
+        assert_eq!(dialog.active_field, CopyField::Changefile);
+        let path = dir.path().join("copy.ldif");
+        dialog.changefile.set(path.display().to_string());
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::CopySubtree {
+                target,
+                changefile: Some(file),
+                ..
+            } => {
+                assert_eq!(target, "ou=People,o=copy,dc=example,dc=com");
+                assert_eq!(file, path);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(!dialog.visible);
+    }
+}
@@ -1,165 +1,532 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use std::collections::BTreeMap;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::widgets::Paragraph;
 use ratatui::Frame;
 
 use crate::action::Action;
-use crate::components::popup::Popup;
+use crate::components::popup::{DiscardGuard, Dismiss, Popup};
 use crate::theme::Theme;
+use crate::widgets::fuzzy_input::FuzzyFilter;
+use loom_core::dn::escape_value;
+use loom_core::schema::{ObjectClassKind, SchemaCache};
+
+/// Attributes tried, in order, as the RDN of a new entry.
+const NAMING_ATTRIBUTES: &[&str] = &["cn", "uid", "ou", "o", "dc", "l"];
 
-/// Which field is currently active.
+/// Where the wizard is.
 #[derive(Debug, Clone, Copy, PartialEq)]
-enum CreateField {
-    Rdn,
-    ObjectClasses,
+enum Phase {
+    /// Picking the object classes.
+    Classes,
+    /// Filling in attribute values.
     Attributes,
+    /// Picking an optional attribute to add.
+    AddOptional,
+}
+
+/// A list narrowed by what is typed, with a cursor.
+#[derive(Debug, Default)]
+struct Picker {
+    names: Vec<String>,
+    filter: String,
+    /// Indices into `names` matching the filter, best first.
+    shown: Vec<usize>,
+    cursor: usize,
+}
+
+impl Picker {
+    fn new(names: Vec<String>, fuzzy: &mut FuzzyFilter) -> Self {
+        let mut picker = Self {
+            names,
+            ..Self::default()
+        };
+        picker.refilter(fuzzy);
+        picker
+    }
+
+    fn refilter(&mut self, fuzzy: &mut FuzzyFilter) {
+        self.shown = fuzzy
+            .filter(&self.filter, &self.names)
+            .into_iter()
+            .map(|m| m.index)
+            .collect();
+        self.cursor = 0;
+    }
+
+    fn current(&self) -> Option<&str> {
+        self.shown.get(self.cursor).map(|&i| self.names[i].as_str())
+    }
+
+    /// Handle moving and typing; returns false for other keys.
+    fn handle_key(&mut self, key: KeyEvent, fuzzy: &mut FuzzyFilter) -> bool {
+        match key.code {
+            KeyCode::Up => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Down => {
+                if self.cursor + 1 < self.shown.len() {
+                    self.cursor += 1;
+                }
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.refilter(fuzzy);
+            }
+            KeyCode::Char(c) if c != ' ' && !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.filter.push(c);
+                self.refilter(fuzzy);
+            }
+            _ => return false,
+        }
+        true
+    }
 }
 
-/// Dialog for creating a new LDAP entry under a selected parent DN.
+/// An attribute being filled in.
+#[derive(Debug, Clone, PartialEq)]
+struct Field {
+    attr: String,
+    value: String,
+    /// A MUST attribute of the chosen classes.
+    required: bool,
+}
+
+/// Wizard for creating a child entry: pick object classes from the
+/// schema, then fill in their required attributes and any optional ones.
 pub struct CreateEntryDialog {
     pub visible: bool,
     popup: Popup,
     theme: Theme,
-    active_field: CreateField,
+    phase: Phase,
     parent_dn: String,
-    rdn: String,
-    object_classes: String,
-    extra_attributes: String,
+    schema: SchemaCache,
+    fuzzy: FuzzyFilter,
+    classes: Picker,
+    /// Chosen object classes, in the order picked.
+    selected: Vec<String>,
+    fields: Vec<Field>,
+    field_cursor: usize,
+    optional: Picker,
+    /// The attribute whose value names the entry.
+    rdn_attr: Option<String>,
+    guard: DiscardGuard,
 }
 
 impl CreateEntryDialog {
     pub fn new(theme: Theme) -> Self {
         Self {
             visible: false,
-            popup: Popup::new("Create Entry", theme.clone()).with_size(60, 50),
+            popup: Popup::new("Create Entry", theme.clone()).with_size(65, 70),
             theme,
-            active_field: CreateField::Rdn,
+            phase: Phase::Classes,
             parent_dn: String::new(),
-            rdn: String::new(),
-            object_classes: String::new(),
-            extra_attributes: String::new(),
+            schema: SchemaCache::new(),
+            fuzzy: FuzzyFilter::new(),
+            classes: Picker::default(),
+            selected: Vec::new(),
+            fields: Vec::new(),
+            field_cursor: 0,
+            optional: Picker::default(),
+            rdn_attr: None,
+            guard: DiscardGuard::new(true),
         }
     }
 
-    pub fn show(&mut self, parent_dn: String) {
+    /// Ask before Esc throws away what was typed.
+    pub fn with_confirm_discard(mut self, enabled: bool) -> Self {
+        self.guard = DiscardGuard::new(enabled);
+        self
+    }
+
+    fn is_dirty(&self) -> bool {
+        !self.selected.is_empty() || self.fields.iter().any(|f| !f.value.is_empty())
+    }
+
+    /// Open for a child of `parent_dn`, offering the classes in `schema`.
+    /// Without a schema, class and attribute names are typed in full.
+    pub fn show(&mut self, parent_dn: String, schema: Option<&SchemaCache>) {
         self.parent_dn = parent_dn;
-        self.rdn.clear();
-        self.object_classes.clear();
-        self.extra_attributes.clear();
-        self.active_field = CreateField::Rdn;
+        self.schema = schema.cloned().unwrap_or_default();
+        let names = self
+            .schema
+            .object_class_list()
+            .into_iter()
+            .filter(|oc| oc.kind != ObjectClassKind::Abstract)
+            .filter_map(|oc| oc.names.first().cloned())
+            .collect();
+        self.classes = Picker::new(names, &mut self.fuzzy);
+        self.selected.clear();
+        self.fields.clear();
+        self.field_cursor = 0;
+        self.rdn_attr = None;
+        self.phase = Phase::Classes;
+        self.guard.reset();
         self.visible = true;
         self.popup.show();
     }
 
+    /// Open prefilled from `template`, an existing entry's attributes, for
+    /// a copy of it under `parent_dn`. The value naming the original by
+    /// `rdn_attr` is left empty for the copy's own.
+    pub fn show_copy(
+        &mut self,
+        parent_dn: String,
+        schema: Option<&SchemaCache>,
+        template: &BTreeMap<String, Vec<String>>,
+        rdn: (&str, &str),
+    ) {
+        self.show(parent_dn, schema);
+        let (rdn_attr, rdn_value) = rdn;
+        let mut classes = Vec::new();
+        let mut fields = Vec::new();
+        for (attr, values) in template {
+            if attr.eq_ignore_ascii_case("objectClass") {
+                classes.extend(values.iter().cloned());
+                continue;
+            }
+            let naming = attr.eq_ignore_ascii_case(rdn_attr);
+            let mut values: Vec<String> = values
+                .iter()
+                .filter(|v| !(naming && v.eq_ignore_ascii_case(rdn_value)))
+                .cloned()
+                .collect();
+            // The naming value comes first, as the one the DN is built from
+            if naming {
+                values.insert(0, String::new());
+            }
+            fields.extend(values.into_iter().map(|value| Field {
+                attr: attr.clone(),
+                value,
+                required: false,
+            }));
+        }
+        if !fields.iter().any(|f| f.attr.eq_ignore_ascii_case(rdn_attr)) {
+            fields.insert(
+                0,
+                Field {
+                    attr: rdn_attr.to_string(),
+                    value: String::new(),
+                    required: false,
+                },
+            );
+        }
+        let class_names: Vec<&str> = classes.iter().map(String::as_str).collect();
+        let required = self.schema.required_attributes(&class_names);
+        for attr in &required {
+            match fields
+                .iter_mut()
+                .find(|f| f.attr.eq_ignore_ascii_case(attr))
+            {
+                Some(field) => field.required = true,
+                None => fields.push(Field {
+                    attr: attr.clone(),
+                    value: String::new(),
+                    required: true,
+                }),
+            }
+        }
+        self.selected = classes;
+        self.field_cursor = fields
+            .iter()
+            .position(|f| f.attr.eq_ignore_ascii_case(rdn_attr))
+            .unwrap_or(0);
+        self.rdn_attr = fields
+            .iter()
+            .find(|f| f.attr.eq_ignore_ascii_case(rdn_attr))
+            .map(|f| f.attr.clone());
+        self.fields = fields;
+        self.phase = Phase::Attributes;
+    }
+
     pub fn hide(&mut self) {
         self.visible = false;
         self.popup.hide();
     }
 
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
-        match key.code {
-            KeyCode::Esc => {
+        let first_phase = self.phase == Phase::Classes;
+        match self.guard.check(&key, first_phase, true, self.is_dirty()) {
+            Dismiss::Close => {
                 self.hide();
-                Action::ClosePopup
+                return Action::ClosePopup;
             }
-            KeyCode::Tab => {
-                self.active_field = match self.active_field {
-                    CreateField::Rdn => CreateField::ObjectClasses,
-                    CreateField::ObjectClasses => CreateField::Attributes,
-                    CreateField::Attributes => CreateField::Rdn,
+            Dismiss::Back => {
+                self.phase = match self.phase {
+                    Phase::AddOptional => Phase::Attributes,
+                    _ => Phase::Classes,
                 };
-                Action::None
-            }
-            KeyCode::BackTab => {
-                self.active_field = match self.active_field {
-                    CreateField::Rdn => CreateField::Attributes,
-                    CreateField::ObjectClasses => CreateField::Rdn,
-                    CreateField::Attributes => CreateField::ObjectClasses,
-                };
-                Action::None
+                return Action::None;
             }
-            KeyCode::Enter => self.submit(),
-            KeyCode::Backspace => {
-                self.active_text_buffer_mut().pop();
+            Dismiss::Stay => return Action::None,
+            Dismiss::Pass => {}
+        }
+        match self.phase {
+            Phase::Classes => self.handle_classes_key(key),
+            Phase::Attributes => self.handle_attributes_key(key),
+            Phase::AddOptional => self.handle_optional_key(key),
+        }
+    }
+
+    fn handle_classes_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Char(' ') => {
+                if let Some(name) = self.classes.current().map(str::to_string) {
+                    self.toggle_class(name);
+                }
                 Action::None
             }
-            KeyCode::Char(c) => {
-                self.active_text_buffer_mut().push(c);
+            KeyCode::Enter => self.choose_classes(),
+            _ => {
+                self.classes.handle_key(key, &mut self.fuzzy);
                 Action::None
             }
-            _ => Action::None,
         }
     }
 
-    fn active_text_buffer_mut(&mut self) -> &mut String {
-        match self.active_field {
-            CreateField::Rdn => &mut self.rdn,
-            CreateField::ObjectClasses => &mut self.object_classes,
-            CreateField::Attributes => &mut self.extra_attributes,
+    fn toggle_class(&mut self, name: String) {
+        match self
+            .selected
+            .iter()
+            .position(|s| s.eq_ignore_ascii_case(&name))
+        {
+            Some(i) => {
+                self.selected.remove(i);
+            }
+            None => self.selected.push(name),
         }
     }
 
-    fn submit(&mut self) -> Action {
-        let rdn = self.rdn.trim();
-        if rdn.is_empty() {
-            return Action::ErrorMessage("RDN is required (e.g. cn=NewUser)".to_string());
+    /// Move on to the attributes of the chosen classes. With none chosen,
+    /// the class under the cursor is taken, or the typed names when the
+    /// schema offers none.
+    fn choose_classes(&mut self) -> Action {
+        if self.selected.is_empty() {
+            match self.classes.current() {
+                Some(name) => self.selected.push(name.to_string()),
+                None => self.selected = split_names(&self.classes.filter),
+            }
+        }
+        if self.selected.is_empty() {
+            return Action::ErrorMessage("Pick at least one objectClass".to_string());
         }
-        if !rdn.contains('=') {
-            return Action::ErrorMessage("RDN must contain '=' (e.g. cn=NewUser)".to_string());
+        let known: Vec<_> = self
+            .selected
+            .iter()
+            .filter_map(|name| self.schema.object_classes.get(&name.to_lowercase()))
+            .collect();
+        if known.len() == self.selected.len()
+            && !known
+                .iter()
+                .any(|oc| oc.kind == ObjectClassKind::Structural)
+        {
+            return Action::ErrorMessage("Pick a structural objectClass for the entry".to_string());
         }
 
-        let oc_input = self.object_classes.trim();
-        if oc_input.is_empty() {
-            return Action::ErrorMessage("At least one objectClass is required".to_string());
+        let classes: Vec<&str> = self.selected.iter().map(String::as_str).collect();
+        let required = self.schema.required_attributes(&classes);
+        // Keep what was typed for attributes that are still wanted
+        let old = std::mem::take(&mut self.fields);
+        let value_of = |attr: &str| {
+            old.iter()
+                .find(|f| f.attr.eq_ignore_ascii_case(attr))
+                .map(|f| f.value.clone())
+                .unwrap_or_default()
+        };
+        self.fields = required
+            .iter()
+            .map(|attr| Field {
+                attr: attr.clone(),
+                value: value_of(attr),
+                required: true,
+            })
+            .collect();
+        for field in old.iter().filter(|f| !f.required && !f.value.is_empty()) {
+            if !self.has_field(&field.attr) {
+                self.fields.push(field.clone());
+            }
+        }
+        self.field_cursor = 0;
+        if self
+            .rdn_attr
+            .as_deref()
+            .map_or(true, |attr| !self.has_field(attr))
+        {
+            self.rdn_attr = NAMING_ATTRIBUTES
+                .iter()
+                .find(|name| self.has_field(name))
+                .map(|name| self.field(name).attr.clone())
+                .or_else(|| self.fields.first().map(|f| f.attr.clone()));
         }
+        self.phase = Phase::Attributes;
+        Action::None
+    }
 
-        let full_dn = format!("{},{}", rdn, self.parent_dn);
+    fn has_field(&self, attr: &str) -> bool {
+        self.fields
+            .iter()
+            .any(|f| f.attr.eq_ignore_ascii_case(attr))
+    }
 
-        let mut attributes: Vec<(String, Vec<String>)> = Vec::new();
+    fn field(&self, attr: &str) -> &Field {
+        self.fields
+            .iter()
+            .find(|f| f.attr.eq_ignore_ascii_case(attr))
+            .expect("field exists")
+    }
 
-        // Add objectClass values
-        let oc_values: Vec<String> = oc_input
-            .split(',')
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty())
-            .collect();
-        attributes.push(("objectClass".to_string(), oc_values));
-
-        // Add the RDN attribute (e.g. cn=NewUser -> cn: NewUser)
-        if let Some(eq_pos) = rdn.find('=') {
-            let rdn_attr = rdn[..eq_pos].trim().to_string();
-            let rdn_val = rdn[eq_pos + 1..].trim().to_string();
-            if !rdn_val.is_empty() {
-                attributes.push((rdn_attr, vec![rdn_val]));
+    fn handle_attributes_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Up | KeyCode::BackTab => {
+                self.field_cursor = self.field_cursor.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Tab if self.field_cursor + 1 < self.fields.len() => {
+                self.field_cursor += 1;
             }
+            KeyCode::F(2) => {
+                let classes: Vec<&str> = self.selected.iter().map(String::as_str).collect();
+                let mut names = self.schema.optional_attributes(&classes);
+                if names.is_empty() {
+                    names = self.schema.all_user_attributes();
+                }
+                names.retain(|name| !self.has_field(name));
+                self.optional = Picker::new(names, &mut self.fuzzy);
+                self.phase = Phase::AddOptional;
+            }
+            KeyCode::F(3) => self.cycle_rdn(),
+            // Only optional attributes can be dropped again
+            KeyCode::Delete
+                if self
+                    .fields
+                    .get(self.field_cursor)
+                    .is_some_and(|f| !f.required) =>
+            {
+                let removed = self.fields.remove(self.field_cursor);
+                if self.rdn_attr.as_deref() == Some(removed.attr.as_str()) {
+                    self.rdn_attr = None;
+                }
+                self.field_cursor = self.field_cursor.min(self.fields.len().saturating_sub(1));
+            }
+            KeyCode::Enter => return self.submit(),
+            KeyCode::Backspace => {
+                if let Some(field) = self.fields.get_mut(self.field_cursor) {
+                    field.value.pop();
+                }
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(field) = self.fields.get_mut(self.field_cursor) {
+                    field.value.push(c);
+                }
+            }
+            _ => {}
         }
+        Action::None
+    }
 
-        // Parse additional attributes (comma-separated attr=value pairs)
-        let extra = self.extra_attributes.trim();
-        if !extra.is_empty() {
-            for pair in extra.split(',') {
-                let pair = pair.trim();
-                if let Some(eq_pos) = pair.find('=') {
-                    let attr = pair[..eq_pos].trim().to_string();
-                    let val = pair[eq_pos + 1..].trim().to_string();
-                    if !attr.is_empty() && !val.is_empty() {
-                        // Check if we already have this attribute, and merge values
-                        if let Some(existing) = attributes.iter_mut().find(|(a, _)| a == &attr) {
-                            existing.1.push(val);
-                        } else {
-                            attributes.push((attr, vec![val]));
-                        }
-                    }
-                }
+    /// Name the entry by the next filled attribute.
+    fn cycle_rdn(&mut self) {
+        let filled: Vec<&Field> = self
+            .fields
+            .iter()
+            .filter(|f| !f.value.trim().is_empty())
+            .collect();
+        if filled.is_empty() {
+            return;
+        }
+        let next = self
+            .rdn_attr
+            .as_ref()
+            .and_then(|attr| filled.iter().position(|f| &f.attr == attr))
+            .map_or(0, |i| (i + 1) % filled.len());
+        self.rdn_attr = Some(filled[next].attr.clone());
+    }
+
+    fn handle_optional_key(&mut self, key: KeyEvent) -> Action {
+        if key.code != KeyCode::Enter {
+            self.optional.handle_key(key, &mut self.fuzzy);
+            return Action::None;
+        }
+        let attr = match self.optional.current() {
+            Some(name) => name.to_string(),
+            // Not in the schema, so take the name as typed
+            None => self.optional.filter.trim().to_string(),
+        };
+        if attr.is_empty() || attr.contains(char::is_whitespace) {
+            return Action::None;
+        }
+        if !self.has_field(&attr) {
+            self.fields.push(Field {
+                attr: attr.clone(),
+                value: String::new(),
+                required: false,
+            });
+        }
+        self.field_cursor = self
+            .fields
+            .iter()
+            .position(|f| f.attr.eq_ignore_ascii_case(&attr))
+            .unwrap_or(0);
+        if self.rdn_attr.is_none() {
+            self.rdn_attr = Some(attr);
+        }
+        self.phase = Phase::Attributes;
+        Action::None
+    }
+
+    /// Required attributes still empty.
+    fn missing(&self) -> Vec<&str> {
+        self.fields
+            .iter()
+            .filter(|f| f.required && f.value.trim().is_empty())
+            .map(|f| f.attr.as_str())
+            .collect()
+    }
+
+    /// The DN the entry gets, once its RDN attribute has a value.
+    fn full_dn(&self) -> Option<String> {
+        let attr = self.rdn_attr.as_deref()?;
+        let value = self.field(attr).value.trim();
+        if value.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "{}={},{}",
+            attr,
+            escape_value(value),
+            self.parent_dn
+        ))
+    }
+
+    fn submit(&mut self) -> Action {
+        let missing = self.missing();
+        if !missing.is_empty() {
+            return Action::ErrorMessage(format!("Required: {}", missing.join(", ")));
+        }
+        let Some(dn) = self.full_dn() else {
+            return Action::ErrorMessage(
+                "Fill in an attribute to name the entry by (F3 picks it)".to_string(),
+            );
+        };
+
+        let mut attributes: Vec<(String, Vec<String>)> =
+            vec![("objectClass".to_string(), self.selected.clone())];
+        for field in &self.fields {
+            let value = field.value.trim();
+            if value.is_empty() {
+                continue;
+            }
+            // A copied entry has a field per value
+            match attributes
+                .iter_mut()
+                .find(|(attr, _)| attr.eq_ignore_ascii_case(&field.attr))
+            {
+                Some((_, values)) => values.push(value.to_string()),
+                None => attributes.push((field.attr.clone(), vec![value.to_string()])),
             }
         }
 
         self.hide();
-        Action::CreateEntry {
-            dn: full_dn,
-            attributes,
-        }
+        Action::CreateEntry { dn, attributes }
     }
 
     pub fn render(&self, frame: &mut Frame, full: Rect) {
@@ -167,115 +534,469 @@ impl CreateEntryDialog {
             return;
         }
 
-        let area = self.popup.centered_area(full);
-        frame.render_widget(Clear, area);
-
-        let block = Block::default()
-            .title(" Create Entry ")
-            .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+        let inner = self.popup.render_frame(frame, full);
 
-        let inner = block.inner(area);
-        frame.render_widget(block, area);
-
-        // Layout: parent_dn(2) | rdn(2) | objectClasses(2) | extra_attrs(2) | preview(2) | hints(1)
+        // Layout: parent (2) | body (flex) | status (1) | hints (1)
         let layout = Layout::vertical([
-            Constraint::Length(2), // Parent DN (read-only)
-            Constraint::Length(2), // RDN
-            Constraint::Length(2), // Object Classes
-            Constraint::Length(2), // Additional Attributes
-            Constraint::Length(2), // Preview full DN
-            Constraint::Min(1),    // Hints
+            Constraint::Length(2),
+            Constraint::Min(3),
+            Constraint::Length(1),
+            Constraint::Length(1),
         ])
         .split(inner);
 
-        // Parent DN (read-only)
-        let parent_lines = vec![
-            Line::from(Span::styled("Parent DN:", self.theme.dimmed)),
-            Line::from(Span::styled(self.parent_dn.as_str(), self.theme.normal)),
-        ];
-        frame.render_widget(Paragraph::new(parent_lines), layout[0]);
-
-        // RDN field
-        self.render_text_field(
-            frame,
-            layout[1],
-            "RDN (e.g. cn=NewUser)",
-            &self.rdn,
-            CreateField::Rdn,
-        );
-
-        // Object Classes field
-        self.render_text_field(
-            frame,
-            layout[2],
-            "Object Classes (comma-separated)",
-            &self.object_classes,
-            CreateField::ObjectClasses,
+        let (label, dn) = match self.phase {
+            Phase::Classes => ("Parent DN:", self.parent_dn.clone()),
+            _ => (
+                "Full DN:",
+                self.full_dn()
+                    .unwrap_or_else(|| format!("...,{}", self.parent_dn)),
+            ),
+        };
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(Span::styled(label, self.theme.dimmed())),
+                Line::from(Span::styled(dn, self.theme.header())),
+            ]),
+            layout[0],
         );
 
-        // Additional Attributes field
-        self.render_text_field(
-            frame,
+        let (body, status, hints) = match self.phase {
+            Phase::Classes => (
+                self.classes_lines(layout[1].height as usize),
+                self.selected_line(),
+                "Type to filter  \u{2191}/\u{2193}:move  Space:select  Enter:next  Esc:cancel",
+            ),
+            Phase::Attributes => (
+                self.field_lines(layout[1].height as usize),
+                self.missing_line(),
+                "\u{2191}/\u{2193}:field  F2:add attribute  F3:RDN  Del:remove  Enter:create  Esc:back",
+            ),
+            Phase::AddOptional => (
+                self.optional_lines(layout[1].height as usize),
+                self.missing_line(),
+                "Type to filter  \u{2191}/\u{2193}:move  Enter:add  Esc:back",
+            ),
+        };
+        frame.render_widget(Paragraph::new(body), layout[1]);
+        frame.render_widget(Paragraph::new(status), layout[2]);
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(hints, self.theme.dimmed()))),
             layout[3],
-            "Extra Attributes (attr=val, ...)",
-            &self.extra_attributes,
-            CreateField::Attributes,
         );
+        self.guard.render(frame, inner, &self.theme);
+    }
 
-        // Preview full DN
-        let rdn = self.rdn.trim();
-        let preview = if rdn.is_empty() {
-            "...".to_string()
-        } else {
-            format!("{},{}", rdn, self.parent_dn)
-        };
-        let preview_lines = vec![
-            Line::from(Span::styled("Full DN:", self.theme.dimmed)),
-            Line::from(Span::styled(preview, self.theme.header)),
-        ];
-        frame.render_widget(Paragraph::new(preview_lines), layout[4]);
-
-        // Hints
-        let hints = Paragraph::new(Line::from(Span::styled(
-            "Tab:next field  Enter:create  Esc:cancel",
-            self.theme.dimmed,
-        )));
-        frame.render_widget(hints, layout[5]);
-    }
-
-    fn render_text_field(
-        &self,
-        frame: &mut Frame,
-        area: Rect,
-        label: &str,
-        value: &str,
-        field: CreateField,
-    ) {
-        let is_active = self.active_field == field;
-        let label_style = if is_active {
-            self.theme.header
+    fn filter_line(&self, label: &str, filter: &str) -> Line<'static> {
+        Line::from(vec![
+            Span::styled(format!("{}: ", label), self.theme.header()),
+            Span::styled(filter.to_string(), self.theme.normal()),
+            Span::styled("_", self.theme.command_prompt()),
+        ])
+    }
+
+    fn classes_lines(&self, height: usize) -> Vec<Line<'static>> {
+        let mut lines = vec![self.filter_line("Object classes", &self.classes.filter)];
+        if self.classes.names.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "No schema loaded: type class names separated by commas",
+                self.theme.dimmed(),
+            )));
+            return lines;
+        }
+        let rows = height.saturating_sub(1).max(1);
+        let offset = scroll_offset(self.classes.cursor, rows);
+        for (row, &i) in self
+            .classes
+            .shown
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(rows)
+        {
+            let name = &self.classes.names[i];
+            let checked = self.selected.iter().any(|s| s.eq_ignore_ascii_case(name));
+            let kind = match self
+                .schema
+                .object_classes
+                .get(&name.to_lowercase())
+                .map(|oc| &oc.kind)
+            {
+                Some(ObjectClassKind::Auxiliary) => "auxiliary",
+                _ => "structural",
+            };
+            let style = if row == self.classes.cursor {
+                self.theme.selected()
+            } else {
+                self.theme.normal()
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{} {}", if checked { "[x]" } else { "[ ]" }, name),
+                    style,
+                ),
+                Span::styled(format!("  {}", kind), self.theme.dimmed()),
+            ]));
+        }
+        lines
+    }
+
+    fn field_lines(&self, height: usize) -> Vec<Line<'static>> {
+        let offset = scroll_offset(self.field_cursor, height.max(1));
+        if self.fields.is_empty() {
+            return vec![Line::from(Span::styled(
+                "No required attributes: press F2 to add some",
+                self.theme.dimmed(),
+            ))];
+        }
+        self.fields
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(height.max(1))
+            .map(|(i, field)| {
+                let active = i == self.field_cursor;
+                let mut spans = vec![
+                    Span::styled(
+                        if field.required { "* " } else { "  " },
+                        self.theme.warning(),
+                    ),
+                    Span::styled(
+                        format!("{}: ", field.attr),
+                        if active {
+                            self.theme.header()
+                        } else {
+                            self.theme.dimmed()
+                        },
+                    ),
+                    Span::styled(field.value.clone(), self.theme.normal()),
+                ];
+                if active {
+                    spans.push(Span::styled("_", self.theme.command_prompt()));
+                }
+                if self.rdn_attr.as_deref() == Some(field.attr.as_str()) {
+                    spans.push(Span::styled("  (RDN)", self.theme.success()));
+                }
+                Line::from(spans)
+            })
+            .collect()
+    }
+
+    fn optional_lines(&self, height: usize) -> Vec<Line<'static>> {
+        let mut lines = vec![self.filter_line("Add attribute", &self.optional.filter)];
+        let rows = height.saturating_sub(1).max(1);
+        let offset = scroll_offset(self.optional.cursor, rows);
+        for (row, &i) in self
+            .optional
+            .shown
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(rows)
+        {
+            let style = if row == self.optional.cursor {
+                self.theme.selected()
+            } else {
+                self.theme.normal()
+            };
+            lines.push(Line::from(Span::styled(
+                self.optional.names[i].clone(),
+                style,
+            )));
+        }
+        lines
+    }
+
+    fn selected_line(&self) -> Line<'static> {
+        if self.selected.is_empty() {
+            Line::from(Span::styled("Selected: none", self.theme.dimmed()))
         } else {
-            self.theme.dimmed
-        };
-        let value_style = if is_active {
-            self.theme.normal
+            Line::from(vec![
+                Span::styled("Selected: ", self.theme.header()),
+                Span::styled(self.selected.join(", "), self.theme.success()),
+            ])
+        }
+    }
+
+    fn missing_line(&self) -> Line<'static> {
+        let missing = self.missing();
+        if missing.is_empty() {
+            Line::from(Span::styled(
+                "All required attributes are filled",
+                self.theme.success(),
+            ))
         } else {
-            self.theme.dimmed
-        };
+            Line::from(Span::styled(
+                format!("Required: {}", missing.join(", ")),
+                self.theme.warning(),
+            ))
+        }
+    }
+}
 
-        let lines = vec![
-            Line::from(Span::styled(format!("{}:", label), label_style)),
-            Line::from(vec![
-                Span::styled(value, value_style),
-                if is_active {
-                    Span::styled("_", self.theme.command_prompt)
-                } else {
-                    Span::raw("")
+/// First row to draw so that `cursor` is visible in `rows` rows.
+fn scroll_offset(cursor: usize, rows: usize) -> usize {
+    (cursor + 1).saturating_sub(rows)
+}
+
+/// Names typed by hand, separated by commas or spaces.
+fn split_names(text: &str) -> Vec<String> {
+    text.split([',', ' '])
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use loom_core::schema::{AttributeSyntax, AttributeTypeInfo, ObjectClassInfo};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn type_text(dialog: &mut CreateEntryDialog, text: &str) {
+        for c in text.chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    fn schema() -> SchemaCache {
+        let mut schema = SchemaCache::new();
+        let class =
+            |oid: &str, name: &str, sup: Option<&str>, kind, must: &[&str], may: &[&str]| {
+                ObjectClassInfo {
+                    oid: oid.to_string(),
+                    names: vec![name.to_string()],
+                    description: None,
+                    superiors: sup.into_iter().map(str::to_string).collect(),
+                    kind,
+                    must: must.iter().map(|s| s.to_string()).collect(),
+                    may: may.iter().map(|s| s.to_string()).collect(),
+                }
+            };
+        for oc in [
+            class(
+                "2.5.6.0",
+                "top",
+                None,
+                ObjectClassKind::Abstract,
+                &["objectClass"],
+                &[],
+            ),
+            class(
+                "2.5.6.6",
+                "person",
+                Some("top"),
+                ObjectClassKind::Structural,
+                &["sn", "cn"],
+                &["telephoneNumber"],
+            ),
+            class(
+                "2.16.840.1.113730.3.2.2",
+                "inetOrgPerson",
+                Some("person"),
+                ObjectClassKind::Structural,
+                &[],
+                &["mail", "uid"],
+            ),
+            class(
+                "1.3.6.1.1.1.2.0",
+                "posixAccount",
+                Some("top"),
+                ObjectClassKind::Auxiliary,
+                &["uidNumber"],
+                &[],
+            ),
+        ] {
+            schema.object_classes.insert(oc.names[0].to_lowercase(), oc);
+        }
+        for (oid, name) in [
+            ("2.5.4.4", "sn"),
+            ("2.5.4.3", "cn"),
+            ("2.5.4.20", "telephoneNumber"),
+            ("0.9.2342.19200300.100.1.3", "mail"),
+            ("0.9.2342.19200300.100.1.1", "uid"),
+            ("1.3.6.1.1.1.1.0", "uidNumber"),
+        ] {
+            schema.attribute_types.insert(
+                name.to_lowercase(),
+                AttributeTypeInfo {
+                    oid: oid.to_string(),
+                    names: vec![name.to_string()],
+                    description: None,
+                    superior: None,
+                    syntax: AttributeSyntax::String,
+                    single_value: false,
+                    no_user_modification: false,
                 },
-            ]),
-        ];
-        frame.render_widget(Paragraph::new(lines), area);
+            );
+        }
+        schema
+    }
+
+    fn dialog() -> CreateEntryDialog {
+        let mut dialog = CreateEntryDialog::new(Theme::load("dark"));
+        dialog.show("ou=People,dc=example,dc=com".to_string(), Some(&schema()));
+        dialog
+    }
+
+    #[test]
+    fn test_required_attributes_gate_submit() {
+        let mut dialog = dialog();
+        // Abstract classes are not offered
+        assert!(!dialog.classes.names.contains(&"top".to_string()));
+        type_text(&mut dialog, "inetorg");
+        assert_eq!(dialog.classes.current(), Some("inetOrgPerson"));
+        dialog.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(dialog.phase, Phase::Attributes);
+        let attrs: Vec<_> = dialog.fields.iter().map(|f| f.attr.as_str()).collect();
+        assert_eq!(attrs, vec!["sn", "cn"]);
+        assert_eq!(dialog.rdn_attr.as_deref(), Some("cn"));
+
+        type_text(&mut dialog, "Doe");
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(ref m) if m == "Required: cn"
+        ));
+        dialog.handle_key_event(key(KeyCode::Down));
+        type_text(&mut dialog, "Doe, Jane");
+
+        // An optional attribute is added on demand
+        dialog.handle_key_event(key(KeyCode::F(2)));
+        type_text(&mut dialog, "mail");
+        dialog.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(dialog.phase, Phase::Attributes);
+        type_text(&mut dialog, "jane@example.com");
+
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::CreateEntry { dn, attributes } => {
+                assert_eq!(dn, "cn=Doe\\, Jane,ou=People,dc=example,dc=com");
+                assert_eq!(
+                    attributes,
+                    vec![
+                        ("objectClass".to_string(), vec!["inetOrgPerson".to_string()]),
+                        ("sn".to_string(), vec!["Doe".to_string()]),
+                        ("cn".to_string(), vec!["Doe, Jane".to_string()]),
+                        ("mail".to_string(), vec!["jane@example.com".to_string()]),
+                    ]
+                );
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(!dialog.visible);
+    }
+
+    #[test]
+    fn test_several_classes_and_rdn_choice() {
+        let mut dialog = dialog();
+        type_text(&mut dialog, "posix");
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+        // An auxiliary class alone is not enough
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(ref m) if m.contains("structural")
+        ));
+        for _ in 0.."posix".len() {
+            dialog.handle_key_event(key(KeyCode::Backspace));
+        }
+        type_text(&mut dialog, "person");
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+        dialog.handle_key_event(key(KeyCode::Enter));
+        let attrs: Vec<_> = dialog.fields.iter().map(|f| f.attr.as_str()).collect();
+        assert_eq!(attrs, vec!["uidNumber", "sn", "cn"]);
+
+        type_text(&mut dialog, "1000");
+        dialog.handle_key_event(key(KeyCode::Down));
+        type_text(&mut dialog, "Doe");
+        dialog.handle_key_event(key(KeyCode::Down));
+        type_text(&mut dialog, "Jane");
+        // F3 moves the RDN through the filled attributes
+        dialog.handle_key_event(key(KeyCode::F(3)));
+        assert_eq!(dialog.rdn_attr.as_deref(), Some("uidNumber"));
+        dialog.handle_key_event(key(KeyCode::F(3)));
+        assert_eq!(dialog.rdn_attr.as_deref(), Some("sn"));
+
+        // Going back keeps what was typed
+        dialog.handle_key_event(key(KeyCode::Esc));
+        assert_eq!(dialog.phase, Phase::Classes);
+        dialog.handle_key_event(key(KeyCode::Enter));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::CreateEntry { ref dn, .. } if dn == "sn=Doe,ou=People,dc=example,dc=com"
+        ));
+    }
+
+    #[test]
+    fn test_copy_prefills_all_but_the_rdn_value() {
+        let mut dialog = CreateEntryDialog::new(Theme::load("dark"));
+        let template = BTreeMap::from([
+            (
+                "objectClass".to_string(),
+                vec!["top".to_string(), "person".to_string()],
+            ),
+            (
+                "cn".to_string(),
+                vec!["Jane Doe".to_string(), "Jane".to_string()],
+            ),
+            ("sn".to_string(), vec!["Doe".to_string()]),
+        ]);
+        dialog.show_copy(
+            "ou=People,dc=example,dc=com".to_string(),
+            Some(&schema()),
+            &template,
+            ("cn", "Jane Doe"),
+        );
+        assert_eq!(dialog.phase, Phase::Attributes);
+        assert_eq!(dialog.rdn_attr.as_deref(), Some("cn"));
+        assert_eq!(dialog.fields[dialog.field_cursor].value, "");
+
+        // The copy needs its own name
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+        type_text(&mut dialog, "John Doe");
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::CreateEntry { dn, attributes } => {
+                assert_eq!(dn, "cn=John Doe,ou=People,dc=example,dc=com");
+                assert_eq!(
+                    attributes,
+                    vec![
+                        (
+                            "objectClass".to_string(),
+                            vec!["top".to_string(), "person".to_string()]
+                        ),
+                        (
+                            "cn".to_string(),
+                            vec!["John Doe".to_string(), "Jane".to_string()]
+                        ),
+                        ("sn".to_string(), vec!["Doe".to_string()]),
+                    ]
+                );
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_without_schema_names_are_typed() {
+        let mut dialog = CreateEntryDialog::new(Theme::load("dark"));
+        dialog.show("dc=example,dc=com".to_string(), None);
+        type_text(&mut dialog, "organizationalUnit");
+        dialog.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(dialog.phase, Phase::Attributes);
+        assert!(dialog.fields.is_empty());
+
+        dialog.handle_key_event(key(KeyCode::F(2)));
+        type_text(&mut dialog, "ou");
+        dialog.handle_key_event(key(KeyCode::Enter));
+        type_text(&mut dialog, "Groups");
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::CreateEntry { ref dn, .. } if dn == "ou=Groups,dc=example,dc=com"
+        ));
     }
 }
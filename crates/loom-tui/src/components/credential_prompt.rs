@@ -122,8 +122,8 @@ impl CredentialPromptDialog {
         let block = Block::default()
             .title(" Credentials Required ")
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -146,8 +146,8 @@ impl CredentialPromptDialog {
         // Host info
         let info_line = vec![
             Line::from(vec![
-                Span::styled("Server: ", self.theme.dimmed),
-                Span::styled(host_info, self.theme.normal),
+                Span::styled("Server: ", self.theme.dimmed()),
+                Span::styled(host_info, self.theme.normal()),
             ]),
             Line::from(Span::raw("")),
         ];
@@ -176,7 +176,7 @@ impl CredentialPromptDialog {
         // Hints
         let hints = Paragraph::new(Line::from(Span::styled(
             "Tab:switch field  Enter:connect  Esc:cancel",
-            self.theme.dimmed,
+            self.theme.dimmed(),
         )));
         frame.render_widget(hints, layout[3]);
     }
@@ -192,14 +192,14 @@ impl CredentialPromptDialog {
     ) {
         let is_active = self.active_field == field;
         let label_style = if is_active {
-            self.theme.header
+            self.theme.header()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
         let value_style = if is_active {
-            self.theme.normal
+            self.theme.normal()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
 
         let display_value = if masked && !value.is_empty() {
@@ -213,7 +213,7 @@ impl CredentialPromptDialog {
             Line::from(vec![
                 Span::styled(display_value, value_style),
                 if is_active {
-                    Span::styled("_", self.theme.command_prompt)
+                    Span::styled("_", self.theme.command_prompt())
                 } else {
                     Span::raw("")
                 },
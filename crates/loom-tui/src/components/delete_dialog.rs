@@ -0,0 +1,283 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Gauge, Paragraph, Wrap};
+use ratatui::Frame;
+
+use loom_core::tree::format_count;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// The choices offered; the subtree only when the entry has children.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Choice {
+    Entry,
+    Subtree,
+    Cancel,
+}
+
+/// What is known about the children of the entry to delete.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Children {
+    None,
+    Some,
+    /// Not listed yet, so the subtree is offered in case.
+    Unknown,
+}
+
+/// Confirmation for deleting an entry, which can also take its whole
+/// subtree, and the progress of a subtree delete once it runs.
+pub struct DeleteDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    dn: String,
+    children: Children,
+    selected: Choice,
+    /// Entries deleted and found while a subtree delete runs.
+    progress: Option<(usize, usize)>,
+}
+
+impl DeleteDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Delete", theme.clone()).with_size(60, 30),
+            theme,
+            dn: String::new(),
+            children: Children::None,
+            selected: Choice::Cancel,
+            progress: None,
+        }
+    }
+
+    pub fn show(&mut self, dn: String, children: Children) {
+        self.dn = dn;
+        self.children = children;
+        self.selected = Choice::Cancel;
+        self.progress = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.progress = None;
+        self.popup.hide();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.progress.is_some()
+    }
+
+    pub fn set_progress(&mut self, done: usize, total: usize) {
+        if self.visible {
+            self.progress = Some((done, total));
+        }
+    }
+
+    fn offers_subtree(&self) -> bool {
+        self.children != Children::None
+    }
+
+    fn choices(&self) -> &'static [Choice] {
+        if self.offers_subtree() {
+            &[Choice::Entry, Choice::Subtree, Choice::Cancel]
+        } else {
+            &[Choice::Entry, Choice::Cancel]
+        }
+    }
+
+    fn choose(&mut self, choice: Choice) -> Action {
+        match choice {
+            Choice::Entry => {
+                self.hide();
+                Action::DeleteEntry(self.dn.clone())
+            }
+            Choice::Subtree => {
+                self.progress = Some((0, 0));
+                Action::DeleteSubtree(self.dn.clone())
+            }
+            Choice::Cancel => {
+                self.hide();
+                Action::ClosePopup
+            }
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if self.is_running() {
+            return match key.code {
+                KeyCode::Esc => Action::SubtreeDeleteCancel,
+                _ => Action::None,
+            };
+        }
+        let choices = self.choices();
+        let at = choices
+            .iter()
+            .position(|c| *c == self.selected)
+            .unwrap_or(0);
+        match key.code {
+            KeyCode::Left | KeyCode::Char('h') | KeyCode::BackTab => {
+                self.selected = choices[at.saturating_sub(1)];
+                Action::None
+            }
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Tab => {
+                self.selected = choices[(at + 1).min(choices.len() - 1)];
+                Action::None
+            }
+            KeyCode::Char('y') | KeyCode::Char('Y') => self.choose(Choice::Entry),
+            KeyCode::Char('s') | KeyCode::Char('S') if self.offers_subtree() => {
+                self.choose(Choice::Subtree)
+            }
+            KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => self.choose(Choice::Cancel),
+            KeyCode::Enter => self.choose(self.selected),
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([
+            Constraint::Min(1),    // Question
+            Constraint::Length(1), // Buttons or progress
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        let mut lines = vec![
+            Line::from(Span::styled("Delete this entry?", self.theme.header())),
+            Line::from(Span::styled(self.dn.as_str(), self.theme.normal())),
+        ];
+        let note = match self.children {
+            Children::None => None,
+            Children::Some => Some(
+                "It has child entries, so the server will refuse to delete it alone. \
+                 Deleting the subtree removes them, deepest first, then the entry.",
+            ),
+            Children::Unknown => Some(
+                "Its children have not been listed. If it has any, delete the \
+                 subtree to remove them first.",
+            ),
+        };
+        if let Some(note) = note {
+            lines.push(Line::default());
+            lines.push(Line::from(Span::styled(note, self.theme.warning())));
+        }
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: true }), layout[0]);
+
+        if let Some((done, total)) = self.progress {
+            let ratio = if total == 0 {
+                0.0
+            } else {
+                done as f64 / total as f64
+            };
+            let label = if total == 0 {
+                "Listing the subtree\u{2026}".to_string()
+            } else {
+                format!(
+                    "Deleted {} / {}",
+                    format_count(done as u64),
+                    format_count(total as u64)
+                )
+            };
+            let gauge = Gauge::default()
+                .gauge_style(self.theme.selected())
+                .ratio(ratio.min(1.0))
+                .label(label);
+            frame.render_widget(gauge, layout[1]);
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(
+                    "Esc:stop (entries already deleted stay deleted)",
+                    self.theme.dimmed(),
+                ))),
+                layout[2],
+            );
+            return;
+        }
+
+        let mut buttons = vec![Span::raw("  ")];
+        for choice in self.choices() {
+            let label = match choice {
+                Choice::Entry => " [Y]es, this entry ",
+                Choice::Subtree => " Whole [s]ubtree ",
+                Choice::Cancel => " [N]o ",
+            };
+            let style = if *choice == self.selected {
+                self.theme.selected()
+            } else {
+                self.theme.normal()
+            };
+            buttons.push(Span::styled(label, style));
+            buttons.push(Span::raw("   "));
+        }
+        frame.render_widget(Paragraph::new(Line::from(buttons)), layout[1]);
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                "Left/Right:choose  Enter:confirm  Esc:cancel",
+                self.theme.dimmed(),
+            ))),
+            layout[2],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_defaults_to_cancel() {
+        let mut dialog = DeleteDialog::new(Theme::load("dark"));
+        dialog.show("uid=a,dc=example,dc=com".to_string(), Children::None);
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ClosePopup
+        ));
+        assert!(!dialog.visible);
+
+        // No subtree to offer for a leaf
+        dialog.show("uid=a,dc=example,dc=com".to_string(), Children::None);
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('s'))),
+            Action::None
+        ));
+        dialog.handle_key_event(key(KeyCode::Left));
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::DeleteEntry(dn) => assert_eq!(dn, "uid=a,dc=example,dc=com"),
+            other => panic!("expected DeleteEntry, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_subtree_runs_until_cancelled() {
+        let mut dialog = DeleteDialog::new(Theme::load("dark"));
+        dialog.show("ou=people,dc=example,dc=com".to_string(), Children::Some);
+        dialog.handle_key_event(key(KeyCode::Left));
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::DeleteSubtree(dn) => assert_eq!(dn, "ou=people,dc=example,dc=com"),
+            other => panic!("expected DeleteSubtree, got {:?}", other),
+        }
+        assert!(dialog.is_running());
+        dialog.set_progress(3, 10);
+        assert_eq!(dialog.progress, Some((3, 10)));
+
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('n'))),
+            Action::None
+        ));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Esc)),
+            Action::SubtreeDeleteCancel
+        ));
+    }
+}
@@ -1,15 +1,26 @@
-use crossterm::event::{KeyCode, KeyEvent};
-use ratatui::layout::{Constraint, Rect};
-use ratatui::style::Modifier;
+use std::collections::HashSet;
+
+use chrono::{DateTime, Utc};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Alignment, Constraint, Rect};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span, Text};
 use ratatui::widgets::{Block, BorderType, Borders, Cell, Row, Table, TableState};
 use ratatui::Frame;
 
 use crate::action::{Action, ContextMenuSource};
 use crate::component::Component;
+use crate::components::tree_panel::PROTECTED_MARKER;
+use crate::config::DetailConfig;
+use crate::keymap::{Command, ContextKeys, KeyContext, Keymap};
 use crate::theme::Theme;
-use loom_core::entry::LdapEntry;
-use loom_core::schema::SchemaCache;
+use crate::widgets::width::{display_width, take_width};
+use loom_core::account::{account_status, AccountAction, AccountStatus};
+use loom_core::bookmark::BookmarkTarget;
+use loom_core::decode::{decode_bytes, decode_text, to_hex};
+use loom_core::entry::{AttrValues, LdapEntry};
+use loom_core::schema::{dn_reference, group_attributes, ObjectClassKind, SchemaCache};
+use loom_core::time::{display_timestamp, timestamp_kind, TimeDisplay};
 
 /// Whether an attribute is user-editable or operational/system.
 #[derive(Clone, Copy, PartialEq, Eq)]
@@ -23,11 +34,100 @@ struct AttrRow {
     attr_name: String,
     /// Original value (for editing, copying, deleting).
     raw_value: String,
-    /// Sanitized value for display (control chars replaced).
+    /// Value for display: decoded or raw, control chars replaced.
     display_value: String,
+    /// A binary value; `raw_value` holds it base64-encoded.
+    binary: bool,
+    /// Position of the value among the attribute's values.
+    index: usize,
     /// True for first value of an attribute (displays the attribute name).
     is_first: bool,
     kind: AttrKind,
+    /// Listed as MUST by one of the entry's object classes (grouped view).
+    required: bool,
+    /// Other object classes that also declare this attribute (grouped view).
+    also_in: Vec<String>,
+    /// Set on objectClass section header rows, which carry no value.
+    section: Option<SectionHeader>,
+    /// Set on the row that folds or unfolds a long attribute.
+    fold: Option<Fold>,
+    /// The value names an entry, which Enter goes to.
+    link: bool,
+}
+
+/// The row after the shown values of a long multi-valued attribute.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fold {
+    /// This many values are hidden; Enter shows them.
+    More(usize),
+    /// Every value is shown; Enter hides all but the first few again.
+    Less,
+}
+
+/// Values of an attribute shown before the rest are folded away.
+const FOLD_AFTER: usize = 5;
+
+/// Header line of a collapsible objectClass section in the grouped view.
+struct SectionHeader {
+    /// Collapse-state key: the lowercased class name, or empty for "other".
+    key: String,
+    title: String,
+    count: usize,
+    collapsed: bool,
+}
+
+/// How the detail pane splits its width between names and values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ColumnLayout {
+    /// Names in a column this many cells wide, values beside them.
+    Columns { name_width: u16 },
+    /// Names on their own line with the values indented below.
+    Compact,
+}
+
+/// Heading of the name column, which is never made narrower than this.
+const NAME_HEADING: &str = "Attribute";
+
+/// Indent of values under their name in the compact layout.
+const COMPACT_INDENT: &str = "  ";
+
+/// Pick the layout for a pane `pane_width` cells wide (inside the border)
+/// showing names of the given display widths.
+fn column_layout(
+    name_widths: impl Iterator<Item = usize>,
+    pane_width: u16,
+    config: &DetailConfig,
+) -> ColumnLayout {
+    if pane_width < config.compact_below {
+        return ColumnLayout::Compact;
+    }
+    let widest = name_widths.max().unwrap_or(0).max(NAME_HEADING.len());
+    let cap = (pane_width as usize * config.name_column_max_percent.min(100) as usize / 100).max(1);
+    ColumnLayout::Columns {
+        name_width: widest.min(cap) as u16,
+    }
+}
+
+/// Cut `spans` to `width` columns, ending in `…` when anything was dropped.
+fn fit_spans(spans: Vec<Span<'_>>, width: usize) -> Vec<Span<'_>> {
+    let total: usize = spans.iter().map(|s| display_width(&s.content)).sum();
+    if total <= width {
+        return spans;
+    }
+    let mut out = Vec::new();
+    let mut used = 0;
+    for span in spans {
+        let w = display_width(&span.content);
+        if used + w < width {
+            used += w;
+            out.push(span);
+            continue;
+        }
+        let cut = format!("{}\u{2026}", take_width(&span.content, width - used - 1));
+        out.push(Span::styled(cut, span.style));
+        break;
+    }
+    out
 }
 
 /// The top-right panel: entry detail viewer.
@@ -35,6 +135,28 @@ pub struct DetailPanel {
     pub entry: Option<LdapEntry>,
     pub table_state: TableState,
     rows: Vec<AttrRow>,
+    schema: Option<SchemaCache>,
+    /// Group attributes by objectClass when a schema is available.
+    grouped: bool,
+    /// Sections collapsed by the user; kept across entries.
+    collapsed: HashSet<String>,
+    /// Show values as stored, binary ones in hex, rather than decoded.
+    raw: bool,
+    /// Long attributes unfolded by the user, lowercased; reset when another
+    /// entry is shown.
+    expanded: HashSet<String>,
+    /// The attribute search term, lowercased, while a search is on.
+    search: Option<String>,
+    /// The search term is being typed.
+    search_typing: bool,
+    /// The pattern protecting the shown entry, if any.
+    protected_by: Option<String>,
+    /// Why the last link followed from this entry led nowhere.
+    link_error: Option<String>,
+    /// Whether the shown entry can log in, when it is an account.
+    account: Option<AccountStatus>,
+    layout: DetailConfig,
+    keys: ContextKeys,
     theme: Theme,
     area: Option<Rect>,
 }
@@ -45,20 +167,321 @@ impl DetailPanel {
             entry: None,
             table_state: TableState::default(),
             rows: Vec::new(),
+            schema: None,
+            grouped: true,
+            collapsed: HashSet::new(),
+            raw: false,
+            expanded: HashSet::new(),
+            search: None,
+            search_typing: false,
+            protected_by: None,
+            link_error: None,
+            account: None,
+            layout: DetailConfig::default(),
+            keys: Keymap::default().context(KeyContext::Detail),
             theme,
             area: None,
         }
     }
 
+    pub fn with_layout(mut self, layout: DetailConfig) -> Self {
+        self.layout = layout;
+        self
+    }
+
+    pub fn set_layout(&mut self, layout: DetailConfig) {
+        self.layout = layout;
+    }
+
+    /// Use the detail keys of `keymap` instead of the defaults.
+    pub fn with_keys(mut self, keymap: &Keymap) -> Self {
+        self.set_keys(keymap);
+        self
+    }
+
+    pub fn set_keys(&mut self, keymap: &Keymap) {
+        self.keys = keymap.context(KeyContext::Detail);
+    }
+
     pub fn set_entry(&mut self, entry: LdapEntry, schema: Option<&SchemaCache>) {
-        self.rows = build_rows(&entry, schema);
+        // Folds and the search belong to the entry; a refresh keeps them
+        if self.entry.as_ref().map(|e| &e.dn) != Some(&entry.dn) {
+            self.reset_view();
+        }
+        self.schema = schema.cloned();
+        self.account = account_status(&entry, Utc::now());
+        self.entry = Some(entry);
+        self.link_error = None;
+        self.rebuild_rows();
         self.table_state
             .select(if self.rows.is_empty() { None } else { Some(0) });
-        self.entry = Some(entry);
+    }
+
+    /// Report, under the entry, that a link from it leads nowhere.
+    pub fn set_link_error(&mut self, message: String) {
+        self.link_error = Some(message);
+    }
+
+    /// The selected row, for returning to it later.
+    pub fn selected_row(&self) -> Option<usize> {
+        self.table_state.selected()
+    }
+
+    /// Select a row remembered with `selected_row`, the last one if the
+    /// entry has fewer rows now.
+    pub fn select_row(&mut self, row: usize) {
+        if !self.rows.is_empty() {
+            self.table_state.select(Some(row.min(self.rows.len() - 1)));
+        }
+    }
+
+    /// Mark the shown entry as protected by `pattern`.
+    pub fn set_protected(&mut self, pattern: Option<String>) {
+        self.protected_by = pattern;
+    }
+
+    fn rebuild_rows(&mut self) {
+        let Some(ref entry) = self.entry else {
+            self.rows.clear();
+            return;
+        };
+        let view = ValueView {
+            raw: self.raw,
+            time_display: self.layout.time_display,
+            schema: self.schema.as_ref(),
+            now: Utc::now(),
+            expanded: &self.expanded,
+            search: self.search.as_deref().filter(|t| !t.is_empty()),
+            links: &self.layout.link_attributes,
+        };
+        self.rows = match self.schema {
+            Some(ref schema) if self.grouped => {
+                build_grouped_rows(entry, schema, &self.collapsed, &view)
+            }
+            _ => build_rows(entry, &view),
+        };
+        if let Some(i) = self.table_state.selected() {
+            let max = self.rows.len().saturating_sub(1);
+            self.table_state.select(Some(i.min(max)));
+        }
+    }
+
+    /// Switch between the objectClass-grouped and flat alphabetical views.
+    fn toggle_grouping(&mut self) -> Action {
+        self.grouped = !self.grouped;
+        self.rebuild_rows();
+        if self.schema.is_none() {
+            return Action::StatusMessage("Grouping needs the schema, showing flat view".into());
+        }
+        Action::StatusMessage(
+            if self.grouped {
+                "Detail view: grouped by objectClass"
+            } else {
+                "Detail view: flat"
+            }
+            .into(),
+        )
+    }
+
+    /// Switch between decoded values and the values as stored.
+    fn toggle_raw(&mut self) -> Action {
+        self.raw = !self.raw;
+        self.rebuild_rows();
+        Action::StatusMessage(
+            if self.raw {
+                "Detail view: raw values, binary in hex"
+            } else {
+                "Detail view: decoded values"
+            }
+            .into(),
+        )
+    }
+
+    /// Collapse or expand the section whose header is selected.
+    fn toggle_section(&mut self) -> bool {
+        let Some(key) = self
+            .table_state
+            .selected()
+            .and_then(|i| self.rows.get(i))
+            .and_then(|r| r.section.as_ref())
+            .map(|h| h.key.clone())
+        else {
+            return false;
+        };
+        if !self.collapsed.remove(&key) {
+            self.collapsed.insert(key);
+        }
+        self.rebuild_rows();
+        true
+    }
+
+    /// Unfold or fold the long attribute whose fold row is selected.
+    fn toggle_fold(&mut self) -> bool {
+        let Some((name, fold)) = self
+            .table_state
+            .selected()
+            .and_then(|i| self.rows.get(i))
+            .and_then(|r| Some((r.attr_name.to_lowercase(), r.fold?)))
+        else {
+            return false;
+        };
+        match fold {
+            Fold::More(_) => self.expanded.insert(name.clone()),
+            Fold::Less => self.expanded.remove(&name),
+        };
+        self.rebuild_rows();
+        // Stay on the attribute's fold row, which moved
+        let row = self
+            .rows
+            .iter()
+            .position(|r| r.fold.is_some() && r.attr_name.to_lowercase() == name);
+        if row.is_some() {
+            self.table_state.select(row);
+        }
+        true
+    }
+
+    /// Whether a row matches the search term.
+    fn row_matches(&self, row: &AttrRow) -> bool {
+        let Some(term) = self.search.as_deref().filter(|t| !t.is_empty()) else {
+            return false;
+        };
+        row.section.is_none()
+            && row.fold.is_none()
+            && (row.attr_name.to_lowercase().contains(term)
+                || row.display_value.to_lowercase().contains(term))
+    }
+
+    /// Rows matching the search term, in order.
+    fn matches(&self) -> Vec<usize> {
+        (0..self.rows.len())
+            .filter(|&i| self.row_matches(&self.rows[i]))
+            .collect()
+    }
+
+    /// Select the next match after the selected row, or the previous one
+    /// before it, wrapping around. `from_here` accepts the selected row.
+    fn jump_to_match(&mut self, forward: bool, from_here: bool) -> Action {
+        let term = self.search.clone().unwrap_or_default();
+        let matches = self.matches();
+        if matches.is_empty() {
+            return Action::StatusMessage(format!("No attribute or value matches '{}'", term));
+        }
+        let current = self.table_state.selected().unwrap_or(0);
+        let next = if forward {
+            matches
+                .iter()
+                .copied()
+                .find(|&i| i > current || (from_here && i == current))
+                .unwrap_or(matches[0])
+        } else {
+            matches
+                .iter()
+                .rev()
+                .copied()
+                .find(|&i| i < current)
+                .unwrap_or(matches[matches.len() - 1])
+        };
+        self.table_state.select(Some(next));
+        let n = matches.iter().position(|&i| i == next).unwrap_or(0) + 1;
+        Action::StatusMessage(format!("Match {} of {} for '{}'", n, matches.len(), term))
+    }
+
+    /// Keys while the search term is typed: it narrows as you type, Enter
+    /// keeps it for `n`/`N`, Esc drops it.
+    fn handle_search_key(&mut self, key: KeyEvent) -> Option<Action> {
+        match key.code {
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.search
+                    .get_or_insert_with(String::new)
+                    .extend(c.to_lowercase());
+            }
+            KeyCode::Backspace => {
+                self.search.get_or_insert_with(String::new).pop();
+            }
+            KeyCode::Enter => {
+                self.search_typing = false;
+                if self.search.as_deref().unwrap_or_default().is_empty() {
+                    self.search = None;
+                    return Some(Action::Render);
+                }
+                let count = self.matches().len();
+                return Some(Action::StatusMessage(format!(
+                    "{} matches; n/N to move between them, Esc to clear",
+                    count
+                )));
+            }
+            KeyCode::Esc => {
+                self.search = None;
+                self.search_typing = false;
+                self.rebuild_rows();
+                return Some(Action::Render);
+            }
+            // Anything else ends typing and does its usual job
+            _ => {
+                self.search_typing = false;
+                return None;
+            }
+        }
+        // Folded values that match are shown while searching
+        self.rebuild_rows();
+        if self.search.as_deref().is_some_and(|t| !t.is_empty()) {
+            self.jump_to_match(true, true);
+        }
+        Some(Action::Render)
+    }
+
+    /// Whether typed keys go to the attribute search.
+    pub fn is_searching(&self) -> bool {
+        self.search_typing
+    }
+
+    /// Open the selected value full screen.
+    fn view_value(&self) -> Action {
+        let Some(row) = self.table_state.selected().and_then(|i| self.rows.get(i)) else {
+            return Action::None;
+        };
+        if row.section.is_some() || row.fold.is_some() {
+            return Action::None;
+        }
+        let value = if row.binary {
+            let Some(bytes) =
+                self.entry
+                    .as_ref()
+                    .and_then(|e| match e.values_ci(&row.attr_name)? {
+                        AttrValues::Binary(values) => values.get(row.index).cloned(),
+                        AttrValues::Text(_) => None,
+                    })
+            else {
+                return Action::None;
+            };
+            bytes
+        } else {
+            row.raw_value.clone().into_bytes()
+        };
+        Action::ShowValueViewer {
+            attr: row.attr_name.clone(),
+            value,
+            binary: row.binary,
+        }
+    }
+
+    fn reset_view(&mut self) {
+        self.expanded.clear();
+        self.search = None;
+        self.search_typing = false;
     }
 
     pub fn clear(&mut self) {
+        self.reset_view();
         self.entry = None;
+        self.protected_by = None;
+        self.link_error = None;
+        self.account = None;
         self.rows.clear();
         self.table_state.select(None);
     }
@@ -67,100 +490,223 @@ impl DetailPanel {
     pub fn selected_attr_value(&self) -> Option<(&str, &str)> {
         let idx = self.table_state.selected()?;
         let row = self.rows.get(idx)?;
+        if row.section.is_some() || row.fold.is_some() {
+            return None;
+        }
         Some((&row.attr_name, &row.raw_value))
     }
 
+    /// Whether the selected row is a binary value, which the value
+    /// actions cannot change.
+    pub fn selected_is_binary(&self) -> bool {
+        let row = self.table_state.selected().and_then(|i| self.rows.get(i));
+        row.is_some_and(|r| r.binary)
+    }
+
+    /// The refusal for changing the selected value, if it is binary.
+    fn binary_refusal(&self, attr: &str) -> Option<Action> {
+        self.selected_is_binary().then(|| {
+            Action::StatusMessage(format!("'{}' is binary and cannot be edited here", attr))
+        })
+    }
+
+    /// Whether the selected row is an attribute the server maintains
+    /// (NO-USER-MODIFICATION in the schema).
+    fn selected_is_operational(&self) -> bool {
+        let row = self.table_state.selected().and_then(|i| self.rows.get(i));
+        row.is_some_and(|r| r.kind == AttrKind::Operational)
+    }
+
+    /// `action` on the selected attribute, behind a confirmation when the
+    /// server maintains that attribute.
+    fn warn_if_operational(&self, attr: &str, verb: &str, action: Action) -> Action {
+        if !self.selected_is_operational() {
+            return action;
+        }
+        let msg = format!("{} '{}'?\n{}", verb, attr, operational_note(attr));
+        Action::ShowConfirm(msg, Box::new(action))
+    }
+
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
-        match key.code {
-            KeyCode::Up | KeyCode::Char('k') => {
+        if self.search_typing {
+            if let Some(action) = self.handle_search_key(key) {
+                return action;
+            }
+        }
+        if key.code == KeyCode::Esc && self.search.is_some() {
+            self.search = None;
+            self.rebuild_rows();
+            return Action::Render;
+        }
+        let Some(command) = self.keys.command(&key) else {
+            return Action::None;
+        };
+        match command {
+            Command::Find => {
+                self.search = Some(String::new());
+                self.search_typing = true;
+                Action::Render
+            }
+            Command::NextMatch if self.search.is_some() => self.jump_to_match(true, false),
+            Command::PrevMatch if self.search.is_some() => self.jump_to_match(false, false),
+            Command::Up => {
                 let i = self.table_state.selected().unwrap_or(0);
                 if i > 0 {
                     self.table_state.select(Some(i - 1));
                 }
                 Action::None
             }
-            KeyCode::Down | KeyCode::Char('j') => {
+            Command::Down => {
                 let i = self.table_state.selected().unwrap_or(0);
                 if i + 1 < self.rows.len() {
                     self.table_state.select(Some(i + 1));
                 }
                 Action::None
             }
-            KeyCode::PageUp => {
+            Command::PageUp => {
                 let i = self.table_state.selected().unwrap_or(0);
                 self.table_state.select(Some(i.saturating_sub(10)));
                 Action::None
             }
-            KeyCode::PageDown => {
+            Command::PageDown => {
                 let i = self.table_state.selected().unwrap_or(0);
                 let max = self.rows.len().saturating_sub(1);
                 self.table_state.select(Some((i + 10).min(max)));
                 Action::None
             }
-            KeyCode::Char('e') => {
+            Command::Edit => {
                 // Edit the selected attribute value
                 if let (Some(entry), Some((attr, val))) = (&self.entry, self.selected_attr_value())
                 {
-                    return Action::EditAttribute(
-                        entry.dn.clone(),
-                        attr.to_string(),
-                        val.to_string(),
-                    );
+                    if let Some(refusal) = self.binary_refusal(attr) {
+                        return refusal;
+                    }
+                    let edit =
+                        Action::EditAttribute(entry.dn.clone(), attr.to_string(), val.to_string());
+                    return self.warn_if_operational(attr, "Edit", edit);
                 }
                 Action::None
             }
-            KeyCode::Enter => {
-                // Jump to the selected value as a DN
-                if let Some((_attr, val)) = self.selected_attr_value() {
-                    return Action::TreeSelect(val.to_string());
+            Command::Open => {
+                if self.toggle_section() || self.toggle_fold() {
+                    return Action::Render;
+                }
+                // Go to the entry the selected value names
+                match self.table_state.selected().and_then(|i| self.rows.get(i)) {
+                    Some(row) if row.link => Action::FollowDn(row.raw_value.clone()),
+                    Some(row) if row.section.is_none() => Action::StatusMessage(format!(
+                        "'{}' does not name an entry; e edits it",
+                        row.attr_name
+                    )),
+                    _ => Action::None,
                 }
-                Action::None
             }
-            KeyCode::Char('a') => {
+            Command::AddAttribute => {
                 // Open attribute picker to add a new attribute
                 if let Some(entry) = &self.entry {
                     return Action::ShowAddAttribute(entry.dn.clone());
                 }
                 Action::None
             }
-            KeyCode::Char('+') => {
+            Command::AddValue => {
                 // Add value to selected attribute (reuses existing attribute editor)
                 if let (Some(entry), Some((attr, _val))) = (&self.entry, self.selected_attr_value())
                 {
-                    let row = self.rows.get(self.table_state.selected().unwrap_or(0));
-                    if row.map(|r| r.kind) != Some(AttrKind::Operational) {
-                        return Action::AddAttribute(entry.dn.clone(), attr.to_string());
+                    if let Some(refusal) = self.binary_refusal(attr) {
+                        return refusal;
                     }
+                    let add = Action::AddAttribute(entry.dn.clone(), attr.to_string());
+                    return self.warn_if_operational(attr, "Add a value to", add);
                 }
                 Action::None
             }
-            KeyCode::Char('d') | KeyCode::Delete => {
+            Command::Delete => {
                 // Delete selected attribute value (with confirmation)
                 if let (Some(entry), Some((attr, val))) = (&self.entry, self.selected_attr_value())
                 {
-                    let row = self.rows.get(self.table_state.selected().unwrap_or(0));
-                    if row.map(|r| r.kind) != Some(AttrKind::Operational) {
-                        let msg = format!(
-                            "Delete value '{}' from '{}'?",
-                            flatten_for_message(val),
-                            attr
-                        );
-                        return Action::ShowConfirm(
-                            msg,
-                            Box::new(Action::DeleteAttributeValue(
-                                entry.dn.clone(),
-                                attr.to_string(),
-                                val.to_string(),
-                            )),
-                        );
+                    if let Some(refusal) = self.binary_refusal(attr) {
+                        return refusal;
                     }
+                    let mut msg = format!(
+                        "Delete value '{}' from '{}'?",
+                        flatten_for_message(val),
+                        attr
+                    );
+                    if self.selected_is_operational() {
+                        msg.push_str(&format!("\n{}", operational_note(attr)));
+                    }
+                    return Action::ShowConfirm(
+                        msg,
+                        Box::new(Action::DeleteAttributeValue(
+                            entry.dn.clone(),
+                            attr.to_string(),
+                            val.to_string(),
+                        )),
+                    );
                 }
                 Action::None
             }
-            KeyCode::Char('r') => Action::EntryRefresh,
-            KeyCode::Char(' ') => {
+            Command::Schema => match self.selected_attr_value() {
+                // An objectClass value names a class
+                Some((attr, val)) if attr.eq_ignore_ascii_case("objectClass") => {
+                    Action::ShowSchemaDefinition(val.to_string())
+                }
+                Some((attr, _)) => Action::ShowSchemaDefinition(attr.to_string()),
+                None => Action::None,
+            },
+            Command::CopyValue => match (&self.entry, self.selected_attr_value()) {
+                // Every value of a multi-valued attribute, one per line
+                (Some(entry), Some((attr, _))) => match entry.values_ci(attr) {
+                    Some(values) => Action::CopyToClipboard(values.to_text().join("\n")),
+                    None => Action::None,
+                },
+                _ => Action::None,
+            },
+            Command::CopyLdif => match &self.entry {
+                Some(entry) => Action::CopyEntryLdif(entry.dn.clone()),
+                None => Action::None,
+            },
+            Command::Bookmark => match &self.entry {
+                Some(entry) => Action::PromptBookmark(BookmarkTarget::Dn(entry.dn.clone())),
+                None => Action::None,
+            },
+            Command::Groups => match &self.entry {
+                Some(entry) => Action::ShowGroups(entry.dn.clone()),
+                None => Action::None,
+            },
+            Command::ToggleAccount => match (&self.entry, self.account) {
+                (Some(entry), Some(account)) => {
+                    let action = if account.disabled {
+                        AccountAction::Enable
+                    } else {
+                        AccountAction::Disable
+                    };
+                    Action::ShowAccountAction(entry.dn.clone(), action)
+                }
+                (Some(entry), None) => {
+                    Action::ErrorMessage(format!("{} is not an account", entry.dn))
+                }
+                _ => Action::None,
+            },
+            Command::UnlockAccount => match &self.entry {
+                Some(entry) => Action::ShowAccountAction(entry.dn.clone(), AccountAction::Unlock),
+                None => Action::None,
+            },
+            Command::SetPassword => match &self.entry {
+                Some(entry) => Action::ShowSetPassword(entry.dn.clone()),
+                None => Action::None,
+            },
+            Command::Bookmarks => Action::ShowBookmarks,
+            Command::Refresh => Action::EntryRefresh,
+            Command::ViewValue => self.view_value(),
+            Command::GroupByClass => self.toggle_grouping(),
+            Command::ToggleRaw => self.toggle_raw(),
+            Command::ContextMenu => {
                 if let (Some(entry), Some((attr, val))) = (&self.entry, self.selected_attr_value())
                 {
+                    if let Some(refusal) = self.binary_refusal(attr) {
+                        return refusal;
+                    }
                     Action::ShowContextMenu(ContextMenuSource::Detail {
                         dn: entry.dn.clone(),
                         attr_name: attr.to_string(),
@@ -175,71 +721,215 @@ impl DetailPanel {
     }
 }
 
+impl DetailPanel {
+    fn value_style(&self, kind: AttrKind) -> Style {
+        match kind {
+            AttrKind::Operational => self.theme.attr_operational(),
+            AttrKind::Normal => self.theme.normal(),
+        }
+    }
+
+    /// Name column contents of a row: the name with its required marker
+    /// and the other classes declaring it. Empty for repeated values.
+    fn name_spans<'a>(&self, r: &'a AttrRow) -> Vec<Span<'a>> {
+        let mut spans = Vec::new();
+        if !r.is_first {
+            return spans;
+        }
+        let attr_style = match r.kind {
+            AttrKind::Operational => self.theme.attr_operational(),
+            AttrKind::Normal => self.theme.header(),
+        };
+        spans.push(Span::styled(r.attr_name.as_str(), attr_style));
+        if r.required {
+            spans.push(Span::styled("*", self.theme.warning()));
+        }
+        if !r.also_in.is_empty() {
+            spans.push(Span::styled(
+                format!(" (also {})", r.also_in.join(", ")),
+                self.theme.dimmed(),
+            ));
+        }
+        spans
+    }
+
+    fn aligned<'a>(&self, line: Line<'a>) -> Line<'a> {
+        if self.layout.align_names_right {
+            line.alignment(Alignment::Right)
+        } else {
+            line
+        }
+    }
+
+    /// Block title; shows the search term and match position while searching.
+    fn title(&self) -> String {
+        let Some(term) = self.search.as_deref() else {
+            return " Details ".to_string();
+        };
+        let cursor = if self.search_typing { "_" } else { "" };
+        let matches = self.matches();
+        let position = self
+            .table_state
+            .selected()
+            .and_then(|sel| matches.iter().position(|&i| i == sel))
+            .map_or_else(|| "-".to_string(), |i| (i + 1).to_string());
+        format!(
+            " Details  /{}{} ({}/{}) ",
+            term,
+            cursor,
+            position,
+            matches.len()
+        )
+    }
+
+    fn render_row<'a>(&self, r: &'a AttrRow, layout: ColumnLayout) -> Row<'a> {
+        if let Some(ref section) = r.section {
+            let marker = if section.collapsed {
+                "\u{25b8}"
+            } else {
+                "\u{25be}"
+            };
+            let title = Span::styled(
+                format!("{} {}", marker, section.title),
+                self.theme.header().add_modifier(Modifier::UNDERLINED),
+            );
+            let count = Span::styled(format!("{} attributes", section.count), self.theme.dimmed());
+            return match layout {
+                ColumnLayout::Columns { .. } => {
+                    Row::new(vec![Cell::from(title), Cell::from(count)])
+                }
+                ColumnLayout::Compact => Row::new(vec![Cell::from(Line::from(vec![
+                    title,
+                    "  ".into(),
+                    count,
+                ]))]),
+            };
+        }
+
+        let mut value_style = if r.fold.is_some() {
+            self.theme.dimmed()
+        } else if self.row_matches(r) {
+            self.theme.marked()
+        } else {
+            self.value_style(r.kind)
+        };
+        if r.link {
+            value_style = value_style.add_modifier(Modifier::UNDERLINED);
+        }
+        match layout {
+            ColumnLayout::Columns { name_width } => {
+                let name = fit_spans(self.name_spans(r), name_width as usize);
+                let value_lines: Vec<Line> = r
+                    .display_value
+                    .split('\n')
+                    .map(|line| Line::from(Span::styled(line, value_style)))
+                    .collect();
+                let height = value_lines.len() as u16;
+                Row::new(vec![
+                    Cell::from(self.aligned(Line::from(name))),
+                    Cell::from(Text::from(value_lines)),
+                ])
+                .height(height)
+            }
+            ColumnLayout::Compact => {
+                let mut lines = Vec::new();
+                if r.is_first {
+                    lines.push(Line::from(self.name_spans(r)));
+                }
+                lines.extend(r.display_value.split('\n').map(|line| {
+                    Line::from(vec![
+                        Span::raw(COMPACT_INDENT),
+                        Span::styled(line, value_style),
+                    ])
+                }));
+                let height = lines.len() as u16;
+                Row::new(vec![Cell::from(Text::from(lines))]).height(height)
+            }
+        }
+    }
+}
+
 impl Component for DetailPanel {
     fn render(&self, frame: &mut Frame, area: Rect, focused: bool) {
         let border_style = if focused {
-            self.theme.border_focused
+            self.theme.border_focused()
         } else {
-            self.theme.border
+            self.theme.border()
         };
 
         let mut block = Block::default()
-            .title(" Details ")
+            .title(self.title())
             .borders(Borders::ALL)
             .border_style(border_style);
         if focused {
             block = block.border_type(BorderType::Double);
         }
+        if let Some(ref message) = self.link_error {
+            block = block.title_bottom(Line::styled(format!(" {} ", message), self.theme.error()));
+        }
+        if let Some(account) = self.account {
+            let style = if account.is_enabled() {
+                self.theme.dimmed()
+            } else {
+                self.theme.warning()
+            };
+            block = block.title(
+                Line::styled(format!(" Account: {} ", account.summary()), style).right_aligned(),
+            );
+        }
 
         if let Some(ref entry) = self.entry {
             // Build header with DN
-            let dn_line = Line::from(vec![
-                Span::styled("DN: ", self.theme.header),
-                Span::styled(&entry.dn, self.theme.normal),
-            ]);
+            let mut dn_spans = vec![
+                Span::styled("DN: ", self.theme.header()),
+                Span::styled(entry.dn.as_str(), self.theme.normal()),
+            ];
+            if let Some(ref pattern) = self.protected_by {
+                dn_spans.push(Span::styled(
+                    format!("  {} protected by {}", PROTECTED_MARKER, pattern),
+                    self.theme.warning(),
+                ));
+            }
+            let dn_line = Line::from(dn_spans);
 
-            // Build attribute rows
+            // Recomputed every frame, so resizes and collapsed sections are
+            // picked up without extra bookkeeping
+            let layout = column_layout(
+                self.rows
+                    .iter()
+                    .filter(|r| r.section.is_none() && r.is_first)
+                    .map(|r| {
+                        self.name_spans(r)
+                            .iter()
+                            .map(|s| display_width(&s.content))
+                            .sum()
+                    }),
+                area.width.saturating_sub(2),
+                &self.layout,
+            );
             let rows: Vec<Row> = self
                 .rows
                 .iter()
-                .map(|r| {
-                    let attr_style = match r.kind {
-                        AttrKind::Operational => self.theme.attr_operational,
-                        AttrKind::Normal => self.theme.header,
-                    };
-                    let value_style = match r.kind {
-                        AttrKind::Operational => self.theme.attr_operational,
-                        AttrKind::Normal => self.theme.normal,
-                    };
-                    let attr_display = if r.is_first { r.attr_name.as_str() } else { "" };
-
-                    let value_lines: Vec<Line> = r
-                        .display_value
-                        .split('\n')
-                        .map(|line| Line::from(Span::styled(line.to_string(), value_style)))
-                        .collect();
-                    let height = value_lines.len() as u16;
-
-                    Row::new(vec![
-                        Cell::from(Span::styled(attr_display, attr_style)),
-                        Cell::from(Text::from(value_lines)),
-                    ])
-                    .height(height)
-                })
+                .map(|r| self.render_row(r, layout))
                 .collect();
 
-            let widths = [Constraint::Percentage(30), Constraint::Percentage(70)];
+            let (widths, header) = match layout {
+                ColumnLayout::Columns { name_width } => (
+                    vec![Constraint::Length(name_width), Constraint::Fill(1)],
+                    vec![
+                        Cell::from(self.aligned(Line::from(NAME_HEADING))),
+                        Cell::from("Value"),
+                    ],
+                ),
+                ColumnLayout::Compact => {
+                    (vec![Constraint::Fill(1)], vec![Cell::from(NAME_HEADING)])
+                }
+            };
 
             let table = Table::new(rows, widths)
-                .header(
-                    Row::new(vec![
-                        Cell::from(Span::styled("Attribute", self.theme.header)),
-                        Cell::from(Span::styled("Value", self.theme.header)),
-                    ])
-                    .style(self.theme.header),
-                )
+                .header(Row::new(header).style(self.theme.header()))
                 .block(block)
-                .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
+                .highlight_style(self.theme.selected().add_modifier(Modifier::BOLD));
 
             frame.render_stateful_widget(table, area, &mut self.table_state.clone());
 
@@ -261,7 +951,7 @@ impl Component for DetailPanel {
             }
         } else {
             let empty = ratatui::widgets::Paragraph::new("Select an entry from the tree")
-                .style(self.theme.dimmed)
+                .style(self.theme.dimmed())
                 .block(block);
             frame.render_widget(empty, area);
         }
@@ -302,28 +992,758 @@ fn flatten_for_message(value: &str) -> String {
     out
 }
 
-fn build_rows(entry: &LdapEntry, schema: Option<&SchemaCache>) -> Vec<AttrRow> {
+/// Warning shown before changing an attribute the server maintains.
+fn operational_note(attr: &str) -> String {
+    format!(
+        "'{}' is an operational attribute maintained by the server; most servers refuse the change.",
+        attr
+    )
+}
+
+fn attr_kind(name: &str, schema: Option<&SchemaCache>) -> AttrKind {
+    schema
+        .and_then(|s| s.get_attribute_type(name))
+        .map(|at| {
+            if at.no_user_modification {
+                AttrKind::Operational
+            } else {
+                AttrKind::Normal
+            }
+        })
+        .unwrap_or(AttrKind::Normal)
+}
+
+/// How stored values become the text shown.
+struct ValueView<'a> {
+    /// Values as stored, binary ones in hex.
+    raw: bool,
+    time_display: TimeDisplay,
+    schema: Option<&'a SchemaCache>,
+    /// What relative timestamps are relative to.
+    now: DateTime<Utc>,
+    /// Long attributes unfolded by the user, lowercased.
+    expanded: &'a HashSet<String>,
+    /// Lowercased search term; matching values are never folded away.
+    search: Option<&'a str>,
+    /// Attributes holding DNs whatever the schema says.
+    links: &'a [String],
+}
+
+impl ValueView<'_> {
+    /// Timestamps as configured, other values decoded where loom knows the
+    /// encoding. Anything that does not parse is shown as stored.
+    fn text(&self, name: &str, value: &str) -> String {
+        if self.raw {
+            return value.to_string();
+        }
+        if let Some(kind) = timestamp_kind(name, self.schema) {
+            return display_timestamp(value, kind, self.time_display, self.now);
+        }
+        decode_text(name, value).unwrap_or_else(|| value.to_string())
+    }
+
+    /// Decoded, or summarized when the encoding is unknown.
+    fn bytes(&self, name: &str, value: &[u8]) -> String {
+        if self.raw {
+            return to_hex(value);
+        }
+        decode_bytes(name, value)
+            .unwrap_or_else(|| format!("(binary, {} bytes; x shows hex)", value.len()))
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        self.search
+            .is_some_and(|term| text.to_lowercase().contains(term))
+    }
+}
+
+fn push_value_rows(
+    rows: &mut Vec<AttrRow>,
+    name: &str,
+    values: AttrValues<'_>,
+    kind: AttrKind,
+    required: bool,
+    also_in: &[String],
+    view: &ValueView<'_>,
+) {
+    let shown: Vec<String> = match values {
+        AttrValues::Text(values) => values.iter().map(|v| view.text(name, v)).collect(),
+        AttrValues::Binary(values) => values.iter().map(|v| view.bytes(name, v)).collect(),
+    };
+    let binary = matches!(values, AttrValues::Binary(_));
+    let count = shown.len();
+    let unfolded = view.expanded.contains(&name.to_lowercase());
+    let folded =
+        count > FOLD_AFTER && !unfolded && !shown[FOLD_AFTER..].iter().any(|v| view.matches(v));
+    let row =
+        |raw_value: String, display_value: String, index: usize, fold: Option<Fold>| AttrRow {
+            attr_name: name.to_string(),
+            link: !binary
+                && fold.is_none()
+                && dn_reference(name, &raw_value, view.schema, view.links).is_some(),
+            raw_value,
+            display_value,
+            binary,
+            index,
+            is_first: index == 0,
+            kind,
+            required,
+            also_in: also_in.to_vec(),
+            section: None,
+            fold,
+        };
+    let texts = values.to_text();
+    for (i, (val, shown)) in texts.into_iter().zip(shown).enumerate() {
+        if folded && i == FOLD_AFTER {
+            break;
+        }
+        rows.push(row(val, sanitize_for_display(&shown), i, None));
+    }
+    if folded {
+        let more = count - FOLD_AFTER;
+        rows.push(row(
+            String::new(),
+            format!("\u{2026}and {} more", more),
+            count,
+            Some(Fold::More(more)),
+        ));
+    } else if count > FOLD_AFTER && unfolded {
+        rows.push(row(
+            String::new(),
+            "show fewer".to_string(),
+            count,
+            Some(Fold::Less),
+        ));
+    }
+}
+
+fn build_rows(entry: &LdapEntry, view: &ValueView<'_>) -> Vec<AttrRow> {
     let mut rows = Vec::new();
-    for (name, values) in &entry.attributes {
-        let kind = schema
-            .and_then(|s| s.get_attribute_type(name))
-            .map(|at| {
-                if at.no_user_modification {
-                    AttrKind::Operational
-                } else {
-                    AttrKind::Normal
-                }
-            })
-            .unwrap_or(AttrKind::Normal);
-        for (i, val) in values.iter().enumerate() {
-            rows.push(AttrRow {
-                attr_name: name.clone(),
-                raw_value: val.clone(),
-                display_value: sanitize_for_display(val),
-                is_first: i == 0,
-                kind,
-            });
+    for (name, values) in entry.all_values() {
+        let kind = attr_kind(name, view.schema);
+        push_value_rows(&mut rows, name, values, kind, false, &[], view);
+    }
+    rows
+}
+
+/// Rows for the grouped view: a header per objectClass section, followed by
+/// its attributes unless the section is collapsed.
+fn build_grouped_rows(
+    entry: &LdapEntry,
+    schema: &SchemaCache,
+    collapsed: &HashSet<String>,
+    view: &ValueView<'_>,
+) -> Vec<AttrRow> {
+    let mut rows = Vec::new();
+    for group in group_attributes(entry, schema) {
+        let key = group
+            .object_class
+            .as_deref()
+            .unwrap_or_default()
+            .to_lowercase();
+        let title = match (&group.object_class, &group.kind) {
+            (Some(name), Some(kind)) => format!("{} ({})", name, kind_label(kind)),
+            _ => "other / extension".to_string(),
+        };
+        let is_collapsed = collapsed.contains(&key);
+        rows.push(AttrRow {
+            attr_name: String::new(),
+            raw_value: String::new(),
+            display_value: String::new(),
+            binary: false,
+            index: 0,
+            is_first: true,
+            kind: AttrKind::Normal,
+            required: false,
+            also_in: Vec::new(),
+            fold: None,
+            link: false,
+            section: Some(SectionHeader {
+                key,
+                title,
+                count: group.attributes.len(),
+                collapsed: is_collapsed,
+            }),
+        });
+        if is_collapsed {
+            continue;
+        }
+        for attr in &group.attributes {
+            if let Some(values) = entry.values_ci(&attr.name) {
+                push_value_rows(
+                    &mut rows,
+                    &attr.name,
+                    values,
+                    attr_kind(&attr.name, Some(schema)),
+                    attr.required,
+                    &attr.also_in,
+                    view,
+                );
+            }
         }
     }
     rows
 }
+
+fn kind_label(kind: &ObjectClassKind) -> &'static str {
+    match kind {
+        ObjectClassKind::Structural => "structural",
+        ObjectClassKind::Auxiliary => "auxiliary",
+        ObjectClassKind::Abstract => "abstract",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::*;
+
+    const AD_NAME: &str = "msDS-UserPasswordExpiryTimeComputed";
+
+    fn detail(layout: DetailConfig, attrs: &[(&str, &str)]) -> DetailPanel {
+        let mut attributes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for (name, value) in attrs {
+            attributes
+                .entry(name.to_string())
+                .or_default()
+                .push(value.to_string());
+        }
+        let mut panel = DetailPanel::new(Theme::load("dark")).with_layout(layout);
+        let entry = LdapEntry::new("cn=jdoe,dc=example,dc=com".to_string(), attributes);
+        panel.set_entry(entry, None);
+        panel
+    }
+
+    /// The attribute rows as drawn in a pane `width` cells wide, without
+    /// the border and trailing blanks.
+    fn render(panel: &DetailPanel, width: u16) -> Vec<String> {
+        render_rows(panel, width, 8)
+    }
+
+    fn render_rows(panel: &DetailPanel, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| panel.render(frame, frame.area(), false))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        // Row 1 holds the DN, drawn over the table header
+        (2..height - 1)
+            .map(|y| {
+                let line: String = (1..width - 1).map(|x| buffer[(x, y)].symbol()).collect();
+                line.trim_end().to_string()
+            })
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn test_name_column_fits_widest_visible_name() {
+        let panel = detail(
+            DetailConfig::default(),
+            &[("cn", "jdoe"), ("mail", "jdoe@example.com"), ("sn", "Doe")],
+        );
+        // "Attribute" is the floor for the column width
+        assert_eq!(
+            render(&panel, 60),
+            [
+                "cn        jdoe",
+                "mail      jdoe@example.com",
+                "sn        Doe"
+            ]
+        );
+
+        // The FILETIME value is shown as ISO 8601
+        let iso = DetailConfig {
+            time_display: TimeDisplay::Iso,
+            ..DetailConfig::default()
+        };
+        let panel = detail(iso, &[("cn", "jdoe"), (AD_NAME, "133475904000000000")]);
+        assert_eq!(
+            render(&panel, 120),
+            [
+                "cn                                  jdoe".to_string(),
+                format!("{} 2023-12-21T00:00:00Z", AD_NAME),
+            ]
+        );
+        // At 80 columns the 40% cap (31 cells) cuts the AD name
+        assert_eq!(
+            render(&panel, 80),
+            [
+                "cn                              jdoe",
+                "msDS-UserPasswordExpiryTimeCom\u{2026} 2023-12-21T00:00:00Z",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_right_aligned_names() {
+        let layout = DetailConfig {
+            align_names_right: true,
+            ..DetailConfig::default()
+        };
+        let panel = detail(layout, &[("cn", "jdoe"), ("mail", "jdoe@example.com")]);
+        assert_eq!(
+            render(&panel, 60),
+            ["       cn jdoe", "     mail jdoe@example.com"]
+        );
+    }
+
+    #[test]
+    fn test_compact_layout_below_threshold() {
+        let panel = detail(
+            DetailConfig::default(),
+            &[
+                ("cn", "jdoe"),
+                ("mail", "jdoe@example.com"),
+                ("mail", "j@example.com"),
+            ],
+        );
+        assert_eq!(
+            render(&panel, 40),
+            [
+                "cn",
+                "  jdoe",
+                "mail",
+                "  jdoe@example.com",
+                "  j@example.com"
+            ]
+        );
+        // The threshold is configurable; 0 turns compact mode off
+        let layout = DetailConfig {
+            compact_below: 0,
+            ..DetailConfig::default()
+        };
+        let panel = detail(layout, &[("cn", "jdoe")]);
+        // The percentage cap wins over the heading floor in narrow panes
+        assert_eq!(render(&panel, 20), ["cn      jdoe"]);
+    }
+
+    #[test]
+    fn test_pathological_attribute_name() {
+        let long = "x".repeat(200);
+        let panel = detail(
+            DetailConfig::default(),
+            &[(long.as_str(), "v"), ("cn", "jdoe")],
+        );
+        for width in [60u16, 80, 200] {
+            let cap = (width as usize - 2) * 40 / 100;
+            let rows = render(&panel, width);
+            assert_eq!(rows[0], format!("cn{} jdoe", " ".repeat(cap - 2)));
+            assert_eq!(rows[1], format!("{}\u{2026} v", "x".repeat(cap - 1)));
+        }
+    }
+
+    #[test]
+    fn test_layout_math_uses_display_width() {
+        let config = DetailConfig::default();
+        let widths = ["cn", "東京本社"].map(display_width);
+        assert_eq!(
+            column_layout(widths.into_iter(), 100, &config),
+            ColumnLayout::Columns { name_width: 9 }
+        );
+        let widths = ["部署名称の正式な表記"].map(display_width);
+        assert_eq!(
+            column_layout(widths.into_iter(), 100, &config),
+            ColumnLayout::Columns { name_width: 20 }
+        );
+        assert_eq!(
+            column_layout(widths.into_iter(), 30, &config),
+            ColumnLayout::Compact
+        );
+
+        // Wide characters are never split when the name is cut
+        let spans = fit_spans(vec![Span::raw("東京本社"), Span::raw("*")], 6);
+        let text: String = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(text, "東京\u{2026}");
+        let spans = fit_spans(vec![Span::raw("東京"), Span::raw("*")], 5);
+        assert_eq!(spans.len(), 2);
+    }
+
+    #[test]
+    fn test_schema_definition_of_selected_row() {
+        let mut panel = detail(
+            DetailConfig::default(),
+            &[
+                ("objectClass", "inetOrgPerson"),
+                ("mail", "jdoe@example.com"),
+            ],
+        );
+        let mut open = |name: &str| {
+            let i = panel.rows.iter().position(|r| r.attr_name == name);
+            panel.table_state.select(i);
+            panel.handle_key_event(KeyEvent::from(KeyCode::Char('S')))
+        };
+        assert!(matches!(open("mail"), Action::ShowSchemaDefinition(n) if n == "mail"));
+        // objectClass rows open the class
+        assert!(matches!(
+            open("objectClass"),
+            Action::ShowSchemaDefinition(n) if n == "inetOrgPerson"
+        ));
+    }
+
+    /// An entry whose `member` has eight values and one other attribute.
+    fn group() -> DetailPanel {
+        let members: Vec<String> = (1..=8).map(|i| format!("uid=user{}", i)).collect();
+        let mut attrs = vec![("cn", "admins")];
+        attrs.extend(members.iter().map(|m| ("member", m.as_str())));
+        detail(DetailConfig::default(), &attrs)
+    }
+
+    #[test]
+    fn test_long_attributes_fold() {
+        let mut panel = group();
+        assert_eq!(
+            render_rows(&panel, 60, 12),
+            vec![
+                "cn        admins",
+                "member    uid=user1",
+                "          uid=user2",
+                "          uid=user3",
+                "          uid=user4",
+                "          uid=user5",
+                "          \u{2026}and 3 more",
+            ]
+        );
+
+        // Enter on the fold row shows the rest, and again folds them
+        panel.table_state.select(Some(6));
+        assert!(matches!(
+            panel.handle_key_event(KeyEvent::from(KeyCode::Enter)),
+            Action::Render
+        ));
+        assert_eq!(panel.rows.len(), 10);
+        assert_eq!(panel.table_state.selected(), Some(9));
+        assert_eq!(panel.rows[9].display_value, "show fewer");
+        assert_eq!(panel.selected_attr_value(), None);
+        panel.handle_key_event(KeyEvent::from(KeyCode::Enter));
+        assert_eq!(panel.rows.len(), 7);
+        assert_eq!(panel.table_state.selected(), Some(6));
+
+        // A new entry starts folded again
+        panel.table_state.select(Some(6));
+        panel.handle_key_event(KeyEvent::from(KeyCode::Enter));
+        let entry = panel.entry.clone().unwrap();
+        let mut other = entry.clone();
+        other.dn = "cn=others,dc=example,dc=com".to_string();
+        panel.set_entry(other, None);
+        assert_eq!(panel.rows.len(), 7);
+    }
+
+    #[test]
+    fn test_search_names_and_values() {
+        let mut panel = group();
+        let key = |c| KeyEvent::from(KeyCode::Char(c));
+        assert!(matches!(panel.handle_key_event(key('/')), Action::Render));
+        assert!(panel.is_searching());
+        for c in "USER7".chars() {
+            panel.handle_key_event(key(c));
+        }
+        // The folded match is shown and selected
+        assert_eq!(panel.table_state.selected(), Some(7));
+        assert_eq!(panel.rows[7].display_value, "uid=user7");
+        assert_eq!(panel.title(), " Details  /user7_ (1/1) ");
+
+        panel.handle_key_event(KeyEvent::from(KeyCode::Backspace));
+        panel.handle_key_event(KeyEvent::from(KeyCode::Enter));
+        assert!(!panel.is_searching());
+        assert_eq!(panel.title(), " Details  /user (7/8) ");
+
+        // n and N step through the matches and wrap around
+        panel.handle_key_event(key('n'));
+        assert_eq!(panel.table_state.selected(), Some(8));
+        panel.handle_key_event(key('n'));
+        assert_eq!(panel.table_state.selected(), Some(1));
+        assert!(matches!(
+            panel.handle_key_event(key('N')),
+            Action::StatusMessage(msg) if msg == "Match 8 of 8 for 'user'"
+        ));
+
+        // Esc clears the search and folds the attribute again
+        panel.handle_key_event(KeyEvent::from(KeyCode::Esc));
+        assert_eq!(panel.title(), " Details ");
+        assert_eq!(panel.rows.len(), 7);
+        // Without a search, n is left to the keymap (create child entry)
+        assert!(matches!(panel.handle_key_event(key('n')), Action::None));
+    }
+
+    #[test]
+    fn test_dn_values_are_links() {
+        let mut panel = detail(
+            DetailConfig::default(),
+            &[
+                ("cn", "admins"),
+                ("description", "cn=not,dc=a,dc=link"),
+                ("member", "uid=jdoe,dc=example,dc=com"),
+                ("member", "not a dn"),
+            ],
+        );
+        let enter = || KeyEvent::from(KeyCode::Enter);
+        let links: Vec<bool> = panel.rows.iter().map(|r| r.link).collect();
+        assert_eq!(links, vec![false, false, true, false]);
+
+        panel.table_state.select(Some(2));
+        assert!(matches!(
+            panel.handle_key_event(enter()),
+            Action::FollowDn(dn) if dn == "uid=jdoe,dc=example,dc=com"
+        ));
+        panel.table_state.select(Some(1));
+        assert!(matches!(
+            panel.handle_key_event(enter()),
+            Action::StatusMessage(_)
+        ));
+
+        // A broken link is reported in the pane until another entry loads
+        panel.set_link_error("uid=gone,dc=example,dc=com does not exist".into());
+        let mut terminal = Terminal::new(TestBackend::new(60, 8)).unwrap();
+        terminal
+            .draw(|frame| panel.render(frame, frame.area(), false))
+            .unwrap();
+        let bottom: String = (0..60)
+            .map(|x| terminal.backend().buffer()[(x, 7)].symbol())
+            .collect();
+        assert!(bottom.contains(" uid=gone,dc=example,dc=com does not exist "));
+        let entry = panel.entry.clone().unwrap();
+        panel.set_entry(entry, None);
+        assert!(panel.link_error.is_none());
+    }
+
+    #[test]
+    fn test_view_value() {
+        let mut panel = group();
+        panel.table_state.select(Some(2));
+        assert!(matches!(
+            panel.handle_key_event(KeyEvent::from(KeyCode::Char('v'))),
+            Action::ShowValueViewer { attr, value, binary: false }
+                if attr == "member" && value == b"uid=user2"
+        ));
+        // Fold rows have no value to show
+        panel.table_state.select(Some(6));
+        assert!(matches!(
+            panel.handle_key_event(KeyEvent::from(KeyCode::Char('v'))),
+            Action::None
+        ));
+    }
+
+    #[test]
+    fn test_copy_values_and_entry() {
+        let mut panel = detail(
+            DetailConfig::default(),
+            &[
+                ("cn", "jdoe"),
+                ("mail", "jdoe@example.com"),
+                ("mail", "john.doe@example.com"),
+            ],
+        );
+        let key = |c| KeyEvent::from(KeyCode::Char(c));
+        // Either row of a multi-valued attribute copies all its values
+        let last = panel.rows.iter().rposition(|r| r.attr_name == "mail");
+        panel.table_state.select(last);
+        assert!(matches!(
+            panel.handle_key_event(key('y')),
+            Action::CopyToClipboard(text) if text == "jdoe@example.com\njohn.doe@example.com"
+        ));
+        panel.table_state.select(Some(0));
+        assert!(matches!(
+            panel.handle_key_event(key('y')),
+            Action::CopyToClipboard(text) if text == "jdoe"
+        ));
+        assert!(matches!(
+            panel.handle_key_event(key('Y')),
+            Action::CopyEntryLdif(dn) if dn == "cn=jdoe,dc=example,dc=com"
+        ));
+    }
+
+    #[test]
+    fn test_timestamps_follow_time_display() {
+        let attrs = [
+            ("modifyTimestamp", "20240312084512Z"),
+            ("createTimestamp", "yesterday"),
+        ];
+        let shown = |panel: &DetailPanel| -> Vec<String> {
+            panel.rows.iter().map(|r| r.display_value.clone()).collect()
+        };
+        let mut panel = detail(DetailConfig::default(), &attrs);
+        let both = shown(&panel);
+        // Malformed values are shown as they are
+        assert_eq!(both[0], "yesterday");
+        assert!(both[1].starts_with("2024-03-12T08:45:12Z ("));
+        assert!(both[1].ends_with(" ago)"));
+
+        let relative = DetailConfig {
+            time_display: TimeDisplay::Relative,
+            ..DetailConfig::default()
+        };
+        let panel_relative = detail(relative, &attrs);
+        assert!(shown(&panel_relative)[1].ends_with(" ago"));
+        assert!(!shown(&panel_relative)[1].contains("2024"));
+
+        // x shows the stored value, and editing starts from it
+        panel.handle_key_event(KeyEvent::from(KeyCode::Char('x')));
+        assert_eq!(shown(&panel)[1], "20240312084512Z");
+        panel.table_state.select(Some(1));
+        assert_eq!(
+            panel.selected_attr_value(),
+            Some(("modifyTimestamp", "20240312084512Z"))
+        );
+    }
+
+    #[test]
+    fn test_decoded_values_and_raw_toggle() {
+        let guid: Vec<u8> = (1..=16).collect();
+        let entry = LdapEntry::new(
+            "cn=jdoe,dc=example,dc=com".to_string(),
+            BTreeMap::from([("userAccountControl".to_string(), vec!["514".to_string()])]),
+        )
+        .with_binary_attributes(BTreeMap::from([
+            ("objectGUID".to_string(), vec![guid]),
+            ("jpegPhoto".to_string(), vec![vec![0xff, 0xd8]]),
+        ]));
+        let mut panel = DetailPanel::new(Theme::load("dark"));
+        panel.set_entry(entry, None);
+        let shown = |panel: &DetailPanel| -> Vec<(String, String)> {
+            panel
+                .rows
+                .iter()
+                .map(|r| (r.attr_name.clone(), r.display_value.clone()))
+                .collect()
+        };
+        let pair = |a: &str, v: &str| (a.to_string(), v.to_string());
+        assert_eq!(
+            shown(&panel),
+            vec![
+                pair("jpegPhoto", "(binary, 2 bytes; x shows hex)"),
+                pair("objectGUID", "04030201-0605-0807-090a-0b0c0d0e0f10"),
+                pair("userAccountControl", "DISABLED, NORMAL_ACCOUNT"),
+            ]
+        );
+        // Editing keeps the stored value
+        panel.table_state.select(Some(2));
+        assert!(matches!(
+            panel.handle_key_event(KeyEvent::from(KeyCode::Char('e'))),
+            Action::EditAttribute(_, _, value) if value == "514"
+        ));
+
+        panel.handle_key_event(KeyEvent::from(KeyCode::Char('x')));
+        assert_eq!(shown(&panel)[0], pair("jpegPhoto", "ff d8"));
+        assert_eq!(shown(&panel)[2], pair("userAccountControl", "514"));
+
+        // Binary values cannot be edited as text
+        panel.table_state.select(Some(1));
+        assert!(matches!(
+            panel.handle_key_event(KeyEvent::from(KeyCode::Char('e'))),
+            Action::StatusMessage(msg) if msg.contains("binary")
+        ));
+    }
+
+    #[test]
+    fn test_account_status_and_actions() {
+        let mut panel = detail(
+            DetailConfig::default(),
+            &[
+                ("objectClass", "inetOrgPerson"),
+                ("pwdAccountLockedTime", loom_core::account::PERMANENT_LOCK),
+            ],
+        );
+        let mut terminal = Terminal::new(TestBackend::new(60, 6)).unwrap();
+        terminal
+            .draw(|frame| panel.render(frame, frame.area(), false))
+            .unwrap();
+        let top: String = (0..60)
+            .map(|x| terminal.backend().buffer()[(x, 0)].symbol())
+            .collect();
+        assert!(top.contains(" Account: disabled "), "{}", top);
+
+        let press = |panel: &mut DetailPanel, c: char| {
+            panel.handle_key_event(KeyEvent::from(KeyCode::Char(c)))
+        };
+        assert!(matches!(
+            press(&mut panel, 'A'),
+            Action::ShowAccountAction(_, AccountAction::Enable)
+        ));
+        assert!(matches!(
+            press(&mut panel, 'U'),
+            Action::ShowAccountAction(_, AccountAction::Unlock)
+        ));
+
+        let entry = LdapEntry::new(
+            "cn=jdoe,dc=example,dc=com".to_string(),
+            [("objectClass".to_string(), vec!["person".to_string()])].into(),
+        );
+        panel.set_entry(entry, None);
+        assert!(matches!(
+            press(&mut panel, 'A'),
+            Action::ShowAccountAction(_, AccountAction::Disable)
+        ));
+        // Not an account: no status, and nothing to toggle
+        let ou = LdapEntry::new(
+            "ou=people,dc=example,dc=com".to_string(),
+            [(
+                "objectClass".to_string(),
+                vec!["organizationalUnit".to_string()],
+            )]
+            .into(),
+        );
+        panel.set_entry(ou, None);
+        assert!(panel.account.is_none());
+        assert!(matches!(press(&mut panel, 'A'), Action::ErrorMessage(_)));
+    }
+
+    #[test]
+    fn test_operational_attributes_warn_before_changes() {
+        use loom_core::schema::{AttributeSyntax, AttributeTypeInfo};
+
+        let mut schema = SchemaCache::new();
+        schema.attribute_types.insert(
+            "modifytimestamp".to_string(),
+            AttributeTypeInfo {
+                oid: "2.5.18.2".to_string(),
+                names: vec!["modifyTimestamp".to_string()],
+                description: None,
+                superior: None,
+                syntax: AttributeSyntax::GeneralizedTime,
+                single_value: true,
+                no_user_modification: true,
+            },
+        );
+        let mut panel = detail(
+            DetailConfig::default(),
+            &[("cn", "jdoe"), ("modifyTimestamp", "20240101000000Z")],
+        );
+        let entry = panel.entry.clone().unwrap();
+        panel.set_entry(entry, Some(&schema));
+        let select = |panel: &mut DetailPanel, name: &str| {
+            let i = panel
+                .rows
+                .iter()
+                .position(|r| r.attr_name == name && r.section.is_none());
+            panel.table_state.select(i);
+        };
+        let press = |panel: &mut DetailPanel, c: char| {
+            panel.handle_key_event(KeyEvent::from(KeyCode::Char(c)))
+        };
+
+        select(&mut panel, "cn");
+        assert!(
+            matches!(press(&mut panel, 'e'), Action::EditAttribute(_, attr, _) if attr == "cn")
+        );
+
+        select(&mut panel, "modifyTimestamp");
+        for c in ['e', '+', 'd'] {
+            match press(&mut panel, c) {
+                Action::ShowConfirm(msg, _) => {
+                    assert!(msg.contains("operational attribute"), "{}", c)
+                }
+                other => panic!("{}: {:?}", c, other),
+            }
+        }
+        let Action::ShowConfirm(_, edit) = press(&mut panel, 'e') else {
+            unreachable!()
+        };
+        assert!(matches!(*edit, Action::EditAttribute(_, attr, value)
+            if attr == "modifyTimestamp" && value == "20240101000000Z"));
+    }
+}
@@ -0,0 +1,111 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use loom_core::doctor::{CheckStatus, DoctorReport};
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// File name used when saving the report from the popup.
+const REPORT_FILE: &str = "loom-doctor-report.txt";
+
+/// Popup showing the environment self-check report (`:doctor`).
+pub struct DoctorPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    report: DoctorReport,
+    scroll: u16,
+}
+
+impl DoctorPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Doctor", theme.clone()).with_size(80, 70),
+            theme,
+            report: DoctorReport::new(),
+            scroll: 0,
+        }
+    }
+
+    pub fn show(&mut self, report: DoctorReport) {
+        self.report = report;
+        self.scroll = 0;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Char('c') | KeyCode::Char('y') => {
+                Action::CopyToClipboard(self.report.to_text(true))
+            }
+            KeyCode::Char('s') => match std::fs::write(REPORT_FILE, self.report.to_text(true)) {
+                Ok(()) => {
+                    Action::StatusMessage(format!("Saved redacted report to {}", REPORT_FILE))
+                }
+                Err(e) => Action::ErrorMessage(format!("Failed to save report: {}", e)),
+            },
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll = self.scroll.saturating_add(1);
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll = self.scroll.saturating_sub(1);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let mut lines = Vec::new();
+        for check in &self.report.checks {
+            let style = match check.status {
+                CheckStatus::Pass => self.theme.success(),
+                CheckStatus::Warn => self.theme.warning(),
+                CheckStatus::Fail => self.theme.error(),
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("[{}] ", check.status.label()), style),
+                Span::styled(format!("{}: ", check.name), self.theme.header()),
+                Span::styled(check.detail.clone(), self.theme.normal()),
+            ]));
+            if let Some(ref hint) = check.hint {
+                lines.push(Line::from(Span::styled(
+                    format!("       {}", hint),
+                    self.theme.dimmed(),
+                )));
+            }
+        }
+
+        frame.render_widget(Paragraph::new(lines).scroll((self.scroll, 0)), layout[0]);
+
+        let hints = Line::from(Span::styled(
+            "c:copy report  s:save report  \u{2191}/\u{2193}:scroll  Esc:close",
+            self.theme.dimmed(),
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[1]);
+    }
+}
@@ -0,0 +1,152 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+
+use loom_core::errors::{explain, format_report, parse_error_message, result_code_name};
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Popup explaining the most recent error: result code, matched DN,
+/// diagnostic message, and curated remedies.
+pub struct ErrorDetailsPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    message: String,
+    scroll: u16,
+}
+
+impl ErrorDetailsPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Error Details", theme.clone()).with_size(70, 60),
+            theme,
+            message: String::new(),
+            scroll: 0,
+        }
+    }
+
+    pub fn show(&mut self, message: String) {
+        self.message = message;
+        self.scroll = 0;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Char('c') | KeyCode::Char('y') => {
+                Action::CopyToClipboard(format_report(&self.message))
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll = self.scroll.saturating_add(1);
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll = self.scroll.saturating_sub(1);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn build_lines(&self) -> Vec<Line<'static>> {
+        let details = parse_error_message(&self.message);
+        let mut lines = vec![
+            Line::from(Span::styled("Error", self.theme.header())),
+            Line::from(Span::styled(self.message.clone(), self.theme.error())),
+            Line::from(""),
+        ];
+
+        let field = |label: &str, value: String| {
+            Line::from(vec![
+                Span::styled(format!("{:<14}", label), self.theme.dimmed()),
+                Span::styled(value, self.theme.normal()),
+            ])
+        };
+
+        let Some(code) = details.code else {
+            lines.push(Line::from(Span::styled(
+                "No LDAP result code found in this error.",
+                self.theme.dimmed(),
+            )));
+            return lines;
+        };
+
+        let explanation = explain(code, &details.diagnostic);
+        lines.push(field(
+            "Result code:",
+            format!("{} ({})", code, result_code_name(code)),
+        ));
+        if let Some(dn) = details.matched_dn {
+            lines.push(field("Matched DN:", dn));
+        }
+        if !details.diagnostic.is_empty() {
+            lines.push(field("Diagnostic:", details.diagnostic.clone()));
+        }
+        if let Some(data) = explanation.ad_data_code {
+            lines.push(field("AD data code:", data));
+        }
+        if let Some(win32) = explanation.ad_error_code {
+            lines.push(field("AD error:", win32));
+        }
+        if let Some(dsid) = explanation.dsid {
+            lines.push(field("DSID:", dsid));
+        }
+
+        lines.push(Line::from(""));
+        lines.push(Line::from(Span::styled(
+            "What it means",
+            self.theme.header(),
+        )));
+        lines.push(Line::from(Span::styled(
+            explanation.summary,
+            self.theme.normal(),
+        )));
+        if !explanation.remedies.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Try", self.theme.header())));
+            for remedy in explanation.remedies {
+                lines.push(Line::from(Span::styled(
+                    format!("  - {}", remedy),
+                    self.theme.normal(),
+                )));
+            }
+        }
+        lines
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let content = Paragraph::new(self.build_lines())
+            .wrap(Wrap { trim: false })
+            .scroll((self.scroll, 0));
+        frame.render_widget(content, layout[0]);
+
+        let hints = Line::from(Span::styled(
+            "c:copy full details  \u{2191}/\u{2193}:scroll  Esc:close",
+            self.theme.dimmed(),
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[1]);
+    }
+}
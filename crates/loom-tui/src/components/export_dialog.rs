@@ -1,30 +1,102 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::Modifier;
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::widgets::{Block, Borders, Clear, Gauge, Paragraph};
 use ratatui::Frame;
 
+use std::collections::BTreeMap;
+
+use loom_core::export::csv::Explode;
+use loom_core::export::subtree::SubtreeScope;
+use loom_core::export::timestamps::ExportTemplate;
+use loom_core::export::{CsvOptions, ExportFormat, ExportPreset};
+use loom_core::tree::format_count;
+
 use crate::action::Action;
-use crate::components::popup::Popup;
+use crate::components::path_input::PathInput;
+use crate::components::popup::{OverwriteGuard, Popup};
+use crate::paths::expand_path;
 use crate::theme::Theme;
 
-/// Export format options.
+/// Export format options, after the first choice of detecting the
+/// format from the filename.
 const FORMATS: &[(&str, &str)] = &[
     ("LDIF", ".ldif"),
     ("JSON", ".json"),
     ("CSV", ".csv"),
     ("Excel", ".xlsx"),
+    ("DSML", ".xml"),
+    ("SQLite", ".db"),
+    ("JSON Lines", ".jsonl"),
 ];
 
+/// Row layouts for CSV and Excel, in the order they cycle.
+const ROW_LAYOUTS: &[&str] = &[
+    "One per entry",
+    "One per value of",
+    "One per value (dn, attribute, value)",
+];
+
+/// Where an export takes its entries from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ExportSource {
+    /// Search the directory with the base DN and filter.
+    #[default]
+    Search,
+    /// The loaded search results the quick filter lets through.
+    FilteredResults,
+    /// The results table as shown: its columns and order, narrowed by
+    /// the quick filter. CSV or Excel only.
+    Table,
+    /// Every loaded search result.
+    AllResults,
+    /// The search results picked out in the results list.
+    SelectedResults,
+    /// The groups the membership view lists.
+    Groups,
+    /// The branch at the base DN, walked page by page straight to the
+    /// file.
+    Subtree,
+}
+
 /// Which field is currently active.
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum ExportField {
+    Source,
     BaseDn,
     Filter,
+    Scope,
+    IncludeBase,
     Attributes,
+    Operational,
     Format,
+    Timestamps,
+    Decode,
+    Rows,
+    Separator,
     Filename,
+    Preset,
+}
+
+/// The choices of one export, kept so a failed export can be retried.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExportRequest {
+    pub base_dn: String,
+    pub path: String,
+    pub filter: String,
+    pub attributes: Vec<String>,
+    pub timestamps: Option<ExportTemplate>,
+    /// Write GUIDs, SIDs, FILETIMEs and account flags readably.
+    pub decode: bool,
+    pub csv: CsvOptions,
+    /// Overrides the format the path's extension implies.
+    pub format: Option<ExportFormat>,
+    pub source: ExportSource,
+    /// How much of the branch a subtree export takes.
+    pub scope: SubtreeScope,
+    /// Whether a subtree export starts with the base entry itself.
+    pub include_base: bool,
 }
 
 /// Dialog for exporting entries to a file.
@@ -33,25 +105,185 @@ pub struct ExportDialog {
     popup: Popup,
     theme: Theme,
     active_field: ExportField,
+    /// Quick-filtered and total search results on offer; `None` when
+    /// there are none, and only a search can be exported.
+    loaded: Option<(usize, usize)>,
+    /// Search results picked out in the results list, offered first
+    /// when there are any.
+    selected: usize,
+    /// Whose group memberships are on offer, and how many; exported
+    /// instead of a search when set.
+    groups: Option<(String, usize)>,
+    /// Opened on a tree node, offering its branch before a search.
+    subtree: bool,
+    source: ExportSource,
+    /// Children only or the whole branch, for a subtree export.
+    scope: SubtreeScope,
+    /// Write the base entry ahead of the branch.
+    include_base: bool,
+    /// 0 = detect from the filename, otherwise `FORMATS[idx - 1]`.
     format_idx: usize,
     base_dn: String,
     filter: String,
     attributes: String,
-    filename: String,
+    /// Ask for operational attributes too (`+`).
+    operational: bool,
+    filename: PathInput,
+    /// Timestamp formatting choices: built-in ISO 8601 UTC, then configured templates.
+    templates: Vec<(String, ExportTemplate)>,
+    /// 0 = leave values raw, otherwise `templates[idx - 1]`.
+    template_idx: usize,
+    /// Write GUIDs, SIDs, FILETIMEs and account flags readably.
+    decode: bool,
+    /// Index into `ROW_LAYOUTS`.
+    rows_idx: usize,
+    /// Attribute whose values get a row each, for the second layout.
+    explode_attr: String,
+    /// Joins the values of a multi-valued attribute in one cell.
+    separator: String,
+    /// Entries written and the total of the running export; `None` while
+    /// editing.
+    progress: Option<(usize, usize)>,
+    /// Saved attribute selections from the config.
+    presets: Vec<ExportPreset>,
+    /// 0 = none picked, otherwise `presets[idx - 1]`.
+    preset_idx: usize,
+    /// Name being typed to save the current selection as a preset.
+    preset_name: Option<String>,
+    /// The typed name is taken; Enter again replaces that preset.
+    confirm_replace: bool,
+    /// Asks before replacing an existing file.
+    overwrite: OverwriteGuard,
 }
 
 impl ExportDialog {
     pub fn new(theme: Theme) -> Self {
         Self {
             visible: false,
-            popup: Popup::new("Export Entries", theme.clone()).with_size(60, 55),
+            popup: Popup::new("Export Entries", theme.clone()).with_size(60, 60),
             theme,
             active_field: ExportField::BaseDn,
+            loaded: None,
+            selected: 0,
+            groups: None,
+            subtree: false,
+            source: ExportSource::Search,
+            scope: SubtreeScope::default(),
+            include_base: true,
             format_idx: 0,
             base_dn: String::new(),
             filter: String::new(),
             attributes: String::new(),
-            filename: String::new(),
+            operational: false,
+            filename: PathInput::default(),
+            templates: vec![("ISO 8601 UTC".to_string(), ExportTemplate::default())],
+            template_idx: 0,
+            decode: false,
+            rows_idx: 0,
+            explode_attr: String::new(),
+            separator: CsvOptions::default().multi_value_separator,
+            progress: None,
+            presets: Vec::new(),
+            preset_idx: 0,
+            preset_name: None,
+            confirm_replace: false,
+            overwrite: OverwriteGuard::default(),
+        }
+    }
+
+    /// Set the configured export templates offered for timestamp formatting.
+    pub fn set_templates(&mut self, templates: &BTreeMap<String, ExportTemplate>) {
+        let selected = self.selected_template().map(|(name, _)| name.clone());
+        self.templates = vec![("ISO 8601 UTC".to_string(), ExportTemplate::default())];
+        self.templates
+            .extend(templates.iter().map(|(n, t)| (n.clone(), t.clone())));
+        // Keep the previous choice if it still exists
+        self.template_idx = selected
+            .and_then(|name| self.templates.iter().position(|(n, _)| *n == name))
+            .map_or(0, |i| i + 1);
+    }
+
+    /// Set the saved attribute selections offered as presets.
+    pub fn set_presets(&mut self, presets: &[ExportPreset]) {
+        let selected = self.selected_preset().map(|p| p.name.clone());
+        self.presets = presets.to_vec();
+        self.preset_idx = selected
+            .and_then(|name| self.presets.iter().position(|p| p.name == name))
+            .map_or(0, |i| i + 1);
+    }
+
+    fn selected_preset(&self) -> Option<&ExportPreset> {
+        self.preset_idx
+            .checked_sub(1)
+            .and_then(|i| self.presets.get(i))
+    }
+
+    fn preset_label(&self) -> String {
+        match self.selected_preset() {
+            Some(preset) => {
+                let format = preset.format.map_or("Auto", |f| f.label());
+                format!(
+                    "{}: {} ({})",
+                    preset.name,
+                    preset.attributes.join(", "),
+                    format
+                )
+            }
+            None if self.presets.is_empty() => "None saved".to_string(),
+            None => "None".to_string(),
+        }
+    }
+
+    /// Fill in the picked preset's attributes and format.
+    fn apply_preset(&mut self) {
+        let Some(preset) = self.selected_preset().cloned() else {
+            return;
+        };
+        self.set_attribute_list(&preset.attributes);
+        self.format_idx = Self::format_index(preset.format);
+        self.update_filename_ext();
+    }
+
+    fn selected_template(&self) -> Option<&(String, ExportTemplate)> {
+        self.template_idx
+            .checked_sub(1)
+            .and_then(|i| self.templates.get(i))
+    }
+
+    fn template_label(&self) -> String {
+        match self.selected_template() {
+            Some((name, t)) => format!("{} ({}, {})", name, t.timezone, t.timestamp_format),
+            None => "Raw (unchanged)".to_string(),
+        }
+    }
+
+    /// The format chosen over the filename's, if any.
+    fn chosen_format(&self) -> Option<ExportFormat> {
+        let (_, ext) = FORMATS.get(self.format_idx.checked_sub(1)?)?;
+        ext.parse().ok()
+    }
+
+    /// The format the export will be written in.
+    fn format(&self) -> Option<ExportFormat> {
+        self.chosen_format()
+            .or_else(|| ExportFormat::from_path(std::path::Path::new(self.filename.value().trim())))
+    }
+
+    /// Whether the selected format is a table (CSV or Excel).
+    fn tabular(&self) -> bool {
+        matches!(self.format(), Some(ExportFormat::Csv | ExportFormat::Xlsx))
+    }
+
+    fn csv_options(&self) -> CsvOptions {
+        let explode = match self.rows_idx {
+            1 => Explode::Attribute(self.explode_attr.trim().to_string()),
+            2 => Explode::Long,
+            _ => Explode::None,
+        };
+        CsvOptions {
+            explode,
+            multi_value_separator: self.separator.clone(),
+            ..CsvOptions::default()
         }
     }
 
@@ -59,46 +291,350 @@ impl ExportDialog {
         self.base_dn = base_dn.to_string();
         self.filter = "(objectClass=*)".to_string();
         self.attributes = "*".to_string();
+        self.operational = false;
+        self.decode = false;
+        self.rows_idx = 0;
+        self.explode_attr.clear();
+        self.separator = CsvOptions::default().multi_value_separator;
         self.format_idx = 0;
-        self.filename = format!("export{}", FORMATS[0].1);
+        self.filename.set(format!("export{}", FORMATS[0].1));
         self.active_field = ExportField::BaseDn;
+        self.loaded = None;
+        self.selected = 0;
+        self.groups = None;
+        self.subtree = false;
+        self.source = ExportSource::Search;
+        self.scope = SubtreeScope::default();
+        self.include_base = true;
+        self.progress = None;
+        self.preset_idx = 0;
+        self.preset_name = None;
+        self.confirm_replace = false;
+        self.overwrite.reset();
         self.visible = true;
         self.popup.show();
     }
 
+    /// Open the dialog filled in with an earlier export's choices.
+    pub fn show_request(&mut self, request: &ExportRequest) {
+        self.show(&request.base_dn);
+        self.filter = request.filter.clone();
+        self.set_attribute_list(&request.attributes);
+        self.filename.set(request.path.clone());
+        self.format_idx = Self::format_index(request.format);
+        self.template_idx = request
+            .timestamps
+            .as_ref()
+            .and_then(|t| self.templates.iter().position(|(_, known)| known == t))
+            .map_or(0, |i| i + 1);
+        self.decode = request.decode;
+        (self.rows_idx, self.explode_attr) = match &request.csv.explode {
+            Explode::None => (0, String::new()),
+            Explode::Attribute(attr) => (1, attr.clone()),
+            Explode::Long => (2, String::new()),
+        };
+        self.separator = request.csv.multi_value_separator.clone();
+        self.source = request.source;
+        self.subtree = request.source == ExportSource::Subtree;
+        self.scope = request.scope;
+        self.include_base = request.include_base;
+    }
+
+    /// Open the dialog to export the branch at `dn`, named after it.
+    pub fn show_subtree(&mut self, dn: &str) {
+        self.show(dn);
+        let name = loom_core::dn::rdn_display_name(dn)
+            .to_lowercase()
+            .replace(char::is_whitespace, "-");
+        if !name.is_empty() {
+            self.filename.set(format!("{}{}", name, FORMATS[0].1));
+        }
+        self.subtree = true;
+        self.source = ExportSource::Subtree;
+        self.active_field = ExportField::Scope;
+    }
+
+    /// Whether the branch is exported rather than searched.
+    fn walks_subtree(&self) -> bool {
+        self.source == ExportSource::Subtree
+    }
+
+    /// Offer the quick-filtered search results, `filtered` of `total`, as
+    /// what to export instead of a search, and pick them unless another
+    /// source is already chosen.
+    pub fn offer_results(&mut self, filtered: usize, total: usize) {
+        self.loaded = Some((filtered, total));
+        if matches!(
+            self.source,
+            ExportSource::Search | ExportSource::SelectedResults
+        ) {
+            self.source = ExportSource::FilteredResults;
+        }
+        self.active_field = ExportField::Source;
+    }
+
+    /// Offer the `selected` search results alongside the quick-filtered
+    /// and loaded ones, and pick them.
+    pub fn offer_selection(&mut self, selected: usize, filtered: usize, total: usize) {
+        self.offer_results(filtered, total);
+        self.selected = selected;
+        self.source = ExportSource::SelectedResults;
+    }
+
+    /// Offer the `count` groups `member` belongs to, as the membership
+    /// view lists them, and pick them.
+    pub fn offer_groups(&mut self, member: &str, count: usize) {
+        self.groups = Some((member.to_string(), count));
+        self.source = ExportSource::Groups;
+        self.active_field = ExportField::Source;
+    }
+
+    /// Whether there is anything besides a search to export.
+    fn has_sources(&self) -> bool {
+        self.loaded.is_some() || self.groups.is_some() || self.subtree
+    }
+
+    /// The sources to cycle through, in order.
+    fn sources(&self) -> &'static [ExportSource] {
+        if self.subtree {
+            &[ExportSource::Subtree, ExportSource::Search]
+        } else if self.groups.is_some() {
+            &[ExportSource::Groups, ExportSource::Search]
+        } else if self.selected > 0 {
+            &[
+                ExportSource::SelectedResults,
+                ExportSource::FilteredResults,
+                ExportSource::Table,
+                ExportSource::AllResults,
+                ExportSource::Search,
+            ]
+        } else {
+            &[
+                ExportSource::FilteredResults,
+                ExportSource::Table,
+                ExportSource::AllResults,
+                ExportSource::Search,
+            ]
+        }
+    }
+
+    fn cycle_source(&mut self, forward: bool) {
+        let sources = self.sources();
+        let i = sources.iter().position(|s| *s == self.source).unwrap_or(0);
+        let next = if forward {
+            (i + 1) % sources.len()
+        } else {
+            (i + sources.len() - 1) % sources.len()
+        };
+        self.source = sources[next];
+    }
+
+    fn source_label(&self) -> String {
+        let (filtered, total) = self.loaded.unwrap_or_default();
+        match self.source {
+            ExportSource::Search => "Search with the base DN and filter below".to_string(),
+            ExportSource::FilteredResults => {
+                format!("Quick-filtered results ({})", format_count(filtered as u64))
+            }
+            ExportSource::Table => {
+                format!("Results table as shown ({})", format_count(filtered as u64))
+            }
+            ExportSource::AllResults => {
+                format!("All loaded results ({})", format_count(total as u64))
+            }
+            ExportSource::SelectedResults => {
+                format!("Selected results ({})", format_count(self.selected as u64))
+            }
+            ExportSource::Groups => {
+                let (member, count) = self.groups.clone().unwrap_or_default();
+                format!(
+                    "Groups of {} ({})",
+                    loom_core::dn::rdn_display_name(&member),
+                    format_count(count as u64)
+                )
+            }
+            ExportSource::Subtree => format!(
+                "Subtree of {}",
+                loom_core::dn::rdn_display_name(self.base_dn.trim())
+            ),
+        }
+    }
+
+    /// Show an attribute list in the Attributes field, with `+` as the
+    /// operational checkbox.
+    fn set_attribute_list(&mut self, attributes: &[String]) {
+        let listed: Vec<&str> = attributes
+            .iter()
+            .map(String::as_str)
+            .filter(|a| *a != "+")
+            .collect();
+        self.operational = listed.len() < attributes.len();
+        self.attributes = if listed.is_empty() {
+            "*".to_string()
+        } else {
+            listed.join(", ")
+        };
+    }
+
+    /// The attribute list the fields describe: comma or space separated,
+    /// or `*` for all, with `+` for the checkbox.
+    fn attribute_list(&self) -> Vec<String> {
+        let attributes = self.attributes.trim();
+        let mut attrs: Vec<String> = if attributes.is_empty() || attributes == "*" {
+            vec!["*".to_string()]
+        } else {
+            attributes
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty())
+                .collect()
+        };
+        if self.operational && !attrs.iter().any(|a| a == "+") {
+            attrs.push("+".to_string());
+        }
+        attrs
+    }
+
+    /// The `format_idx` choosing `format`; 0 for Auto.
+    fn format_index(format: Option<ExportFormat>) -> usize {
+        format
+            .and_then(|f| {
+                FORMATS
+                    .iter()
+                    .position(|(_, ext)| ext.parse::<ExportFormat>().ok() == Some(f))
+            })
+            .map_or(0, |i| i + 1)
+    }
+
     pub fn hide(&mut self) {
         self.visible = false;
+        self.progress = None;
         self.popup.hide();
     }
 
+    pub fn is_running(&self) -> bool {
+        self.progress.is_some()
+    }
+
+    pub fn set_progress(&mut self, done: usize, total: usize) {
+        if self.visible {
+            self.progress = Some((done, total));
+        }
+    }
+
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if self.is_running() {
+            return match key.code {
+                KeyCode::Esc => Action::ExportCancel,
+                _ => Action::None,
+            };
+        }
+        if self.overwrite.is_asking() {
+            if self.overwrite.confirm(&key) {
+                return self.submit(true);
+            }
+            self.active_field = ExportField::Filename;
+            return Action::None;
+        }
+        if self.preset_name.is_some() {
+            return self.handle_preset_name_key(key);
+        }
+        if key.code == KeyCode::Char('s') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            // Start from the picked preset's name, to update it
+            self.preset_name = Some(
+                self.selected_preset()
+                    .map(|p| p.name.clone())
+                    .unwrap_or_default(),
+            );
+            self.confirm_replace = false;
+            self.active_field = ExportField::Preset;
+            return Action::None;
+        }
         match key.code {
             KeyCode::Esc => {
                 self.hide();
                 Action::ClosePopup
             }
+            KeyCode::Tab
+                if self.active_field == ExportField::Filename && self.filename.complete() =>
+            {
+                Action::None
+            }
             KeyCode::Tab => {
                 self.active_field = match self.active_field {
+                    ExportField::Source => ExportField::BaseDn,
+                    ExportField::BaseDn if self.walks_subtree() => ExportField::Scope,
                     ExportField::BaseDn => ExportField::Filter,
-                    ExportField::Filter => ExportField::Attributes,
-                    ExportField::Attributes => ExportField::Format,
-                    ExportField::Format => ExportField::Filename,
-                    ExportField::Filename => ExportField::BaseDn,
+                    ExportField::Scope => ExportField::IncludeBase,
+                    ExportField::Filter | ExportField::IncludeBase => ExportField::Attributes,
+                    ExportField::Attributes => ExportField::Operational,
+                    ExportField::Operational => ExportField::Format,
+                    ExportField::Format => ExportField::Timestamps,
+                    ExportField::Timestamps => ExportField::Decode,
+                    ExportField::Decode if self.tabular() => ExportField::Rows,
+                    ExportField::Decode | ExportField::Separator => ExportField::Filename,
+                    ExportField::Rows => ExportField::Separator,
+                    ExportField::Filename => ExportField::Preset,
+                    ExportField::Preset if self.has_sources() => ExportField::Source,
+                    ExportField::Preset => ExportField::BaseDn,
                 };
                 Action::None
             }
             KeyCode::BackTab => {
                 self.active_field = match self.active_field {
-                    ExportField::BaseDn => ExportField::Filename,
-                    ExportField::Filter => ExportField::BaseDn,
+                    ExportField::Source => ExportField::Preset,
+                    ExportField::BaseDn if self.has_sources() => ExportField::Source,
+                    ExportField::BaseDn => ExportField::Preset,
+                    ExportField::Filter | ExportField::Scope => ExportField::BaseDn,
+                    ExportField::IncludeBase => ExportField::Scope,
+                    ExportField::Attributes if self.walks_subtree() => ExportField::IncludeBase,
                     ExportField::Attributes => ExportField::Filter,
-                    ExportField::Format => ExportField::Attributes,
-                    ExportField::Filename => ExportField::Format,
+                    ExportField::Operational => ExportField::Attributes,
+                    ExportField::Format => ExportField::Operational,
+                    ExportField::Timestamps => ExportField::Format,
+                    ExportField::Decode => ExportField::Timestamps,
+                    ExportField::Rows => ExportField::Decode,
+                    ExportField::Separator => ExportField::Rows,
+                    ExportField::Filename if self.tabular() => ExportField::Separator,
+                    ExportField::Filename => ExportField::Decode,
+                    ExportField::Preset => ExportField::Filename,
                 };
                 Action::None
             }
+            KeyCode::Left | KeyCode::Char('h') if self.active_field == ExportField::Source => {
+                self.cycle_source(false);
+                Action::None
+            }
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ')
+                if self.active_field == ExportField::Source =>
+            {
+                self.cycle_source(true);
+                Action::None
+            }
+            KeyCode::Left
+            | KeyCode::Right
+            | KeyCode::Char('h')
+            | KeyCode::Char('l')
+            | KeyCode::Char(' ')
+                if self.active_field == ExportField::Scope =>
+            {
+                self.scope = self.scope.toggled();
+                Action::None
+            }
+            KeyCode::Char(' ') if self.active_field == ExportField::IncludeBase => {
+                self.include_base = !self.include_base;
+                Action::None
+            }
+            KeyCode::Char(' ') if self.active_field == ExportField::Operational => {
+                self.operational = !self.operational;
+                Action::None
+            }
+            KeyCode::Char(' ') if self.active_field == ExportField::Decode => {
+                self.decode = !self.decode;
+                Action::None
+            }
             KeyCode::F(2) if self.active_field == ExportField::Format => {
-                self.format_idx = (self.format_idx + 1) % FORMATS.len();
+                self.format_idx = (self.format_idx + 1) % (FORMATS.len() + 1);
                 self.update_filename_ext();
                 Action::None
             }
@@ -110,13 +646,57 @@ impl ExportDialog {
                 Action::None
             }
             KeyCode::Down | KeyCode::Char('j') if self.active_field == ExportField::Format => {
-                if self.format_idx + 1 < FORMATS.len() {
+                if self.format_idx < FORMATS.len() {
                     self.format_idx += 1;
                     self.update_filename_ext();
                 }
                 Action::None
             }
-            KeyCode::Enter => self.submit(),
+            KeyCode::Left | KeyCode::Up | KeyCode::Char('k') | KeyCode::Char('h')
+                if self.active_field == ExportField::Timestamps =>
+            {
+                let count = self.templates.len() + 1;
+                self.template_idx = (self.template_idx + count - 1) % count;
+                Action::None
+            }
+            KeyCode::Right
+            | KeyCode::Down
+            | KeyCode::Char('j')
+            | KeyCode::Char('l')
+            | KeyCode::Char(' ')
+                if self.active_field == ExportField::Timestamps =>
+            {
+                self.template_idx = (self.template_idx + 1) % (self.templates.len() + 1);
+                Action::None
+            }
+            KeyCode::Left | KeyCode::Up | KeyCode::Char('k') | KeyCode::Char('h')
+                if self.active_field == ExportField::Preset =>
+            {
+                let count = self.presets.len() + 1;
+                self.preset_idx = (self.preset_idx + count - 1) % count;
+                self.apply_preset();
+                Action::None
+            }
+            KeyCode::Right
+            | KeyCode::Down
+            | KeyCode::Char('j')
+            | KeyCode::Char('l')
+            | KeyCode::Char(' ')
+                if self.active_field == ExportField::Preset =>
+            {
+                self.preset_idx = (self.preset_idx + 1) % (self.presets.len() + 1);
+                self.apply_preset();
+                Action::None
+            }
+            KeyCode::Left if self.active_field == ExportField::Rows => {
+                self.rows_idx = (self.rows_idx + ROW_LAYOUTS.len() - 1) % ROW_LAYOUTS.len();
+                Action::None
+            }
+            KeyCode::Right if self.active_field == ExportField::Rows => {
+                self.rows_idx = (self.rows_idx + 1) % ROW_LAYOUTS.len();
+                Action::None
+            }
+            KeyCode::Enter => self.submit(false),
             KeyCode::Backspace => {
                 if let Some(buf) = self.active_text_buffer_mut() {
                     buf.pop();
@@ -133,64 +713,181 @@ impl ExportDialog {
         }
     }
 
-    fn submit(&mut self) -> Action {
-        if self.base_dn.trim().is_empty() {
+    /// Keys while typing a name to save the selection as a preset.
+    fn handle_preset_name_key(&mut self, key: KeyEvent) -> Action {
+        let Some(name) = self.preset_name.as_mut() else {
+            return Action::None;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.preset_name = None;
+                self.confirm_replace = false;
+            }
+            KeyCode::Enter => return self.save_preset(),
+            KeyCode::Backspace => {
+                name.pop();
+                self.confirm_replace = false;
+            }
+            KeyCode::Char(c) => {
+                name.push(c);
+                self.confirm_replace = false;
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    /// Save the current attributes and format under the typed name,
+    /// asking first when a preset of that name exists.
+    fn save_preset(&mut self) -> Action {
+        let name = self.preset_name.as_deref().unwrap_or_default().trim();
+        if name.is_empty() {
+            return Action::ErrorMessage("Preset name is required".to_string());
+        }
+        let name = name.to_string();
+        let existing = self
+            .presets
+            .iter()
+            .position(|p| p.name.eq_ignore_ascii_case(&name));
+        if existing.is_some() && !self.confirm_replace {
+            self.confirm_replace = true;
+            return Action::None;
+        }
+
+        let preset = ExportPreset {
+            name: name.clone(),
+            attributes: self.attribute_list(),
+            format: self.chosen_format(),
+        };
+        // Pick it now; the saved list replaces this one once stored
+        match existing {
+            Some(i) => self.presets[i] = preset.clone(),
+            None => self.presets.push(preset.clone()),
+        }
+        self.preset_idx = existing.unwrap_or(self.presets.len() - 1) + 1;
+        self.preset_name = None;
+        self.confirm_replace = false;
+        Action::SaveExportPreset {
+            name,
+            attributes: preset.attributes,
+            format: preset.format,
+            replace: existing.is_some(),
+        }
+    }
+
+    /// Start the export, once `confirmed` if the file exists.
+    fn submit(&mut self, confirmed: bool) -> Action {
+        let search = self.source == ExportSource::Search;
+        if (search || self.walks_subtree()) && self.base_dn.trim().is_empty() {
             return Action::ErrorMessage("Base DN is required".to_string());
         }
-        if self.filter.trim().is_empty() {
+        if search && self.filter.trim().is_empty() {
             return Action::ErrorMessage("Search filter is required".to_string());
         }
-        if self.filename.trim().is_empty() {
+        if self.filename.value().trim().is_empty() {
             return Action::ErrorMessage("Filename is required".to_string());
         }
 
         let base_dn = self.base_dn.trim().to_string();
-        let mut path = self.filename.trim().to_string();
+        let mut path = self.filename.value().trim().to_string();
         let filter = self.filter.trim().to_string();
 
-        // Append the format extension if the filename has none
+        let format = self.chosen_format();
+        if self.format().is_none() {
+            return Action::ErrorMessage(
+                "Choose a format, or a filename ending in .ldif, .json, .csv, ...".to_string(),
+            );
+        }
+        if self.source == ExportSource::Table && !self.tabular() {
+            return Action::ErrorMessage("The results table exports to CSV or Excel".to_string());
+        }
+        // Append the chosen format's extension if the filename has none
         if std::path::Path::new(&path).extension().is_none() {
-            path.push_str(FORMATS[self.format_idx].1);
+            if let Some((_, ext)) = self.format_idx.checked_sub(1).map(|i| FORMATS[i]) {
+                path.push_str(ext);
+            }
         }
-        let attributes = self.attributes.trim().to_string();
+        let attrs = self.attribute_list();
 
-        // Parse attributes: comma or space separated, or "*" for all
-        let attrs: Vec<String> = if attributes.is_empty() || attributes == "*" {
-            vec!["*".to_string()]
+        let csv = if self.tabular() {
+            self.csv_options()
         } else {
-            attributes
-                .split(|c: char| c == ',' || c.is_whitespace())
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect()
+            CsvOptions::default()
         };
+        if csv.explode == Explode::Attribute(String::new()) {
+            return Action::ErrorMessage("Attribute to explode is required".to_string());
+        }
+        if csv.multi_value_separator.is_empty() {
+            return Action::ErrorMessage("Value separator is required".to_string());
+        }
 
-        self.hide();
+        let timestamps = self.selected_template().map(|(_, t)| t.clone());
+        if let Some(ref template) = timestamps {
+            if let Err(e) = template.formatter() {
+                return Action::ErrorMessage(format!("Timestamp format: {}", e));
+            }
+        }
+
+        if !confirmed && self.overwrite.needs_confirm(&expand_path(&path)) {
+            return Action::None;
+        }
+
+        let decode = self.decode;
+
+        // Stay open to show progress until the export is done
+        self.progress = Some((0, 0));
         Action::ExportExecute {
             base_dn,
             path,
             filter,
             attributes: attrs,
+            timestamps,
+            decode,
+            csv,
+            format,
+            source: self.source,
+            scope: self.scope,
+            include_base: self.include_base,
         }
     }
 
-    /// Returns mutable reference to the active text field, or None for Format.
+    /// Returns mutable reference to the active text field, or None for the
+    /// selectors.
     fn active_text_buffer_mut(&mut self) -> Option<&mut String> {
         match self.active_field {
+            ExportField::Rows if self.rows_idx == 1 => Some(&mut self.explode_attr),
             ExportField::BaseDn => Some(&mut self.base_dn),
             ExportField::Filter => Some(&mut self.filter),
             ExportField::Attributes => Some(&mut self.attributes),
-            ExportField::Filename => Some(&mut self.filename),
-            ExportField::Format => None,
+            ExportField::Separator => Some(&mut self.separator),
+            ExportField::Filename => Some(self.filename.value_mut()),
+            ExportField::Source
+            | ExportField::Scope
+            | ExportField::IncludeBase
+            | ExportField::Operational
+            | ExportField::Format
+            | ExportField::Timestamps
+            | ExportField::Decode
+            | ExportField::Rows
+            | ExportField::Preset => None,
         }
     }
 
+    /// Give the filename the chosen format's extension, unless it has
+    /// one no format claims (`.bak`), which the choice overrides.
     fn update_filename_ext(&mut self) {
-        let ext = FORMATS[self.format_idx].1;
-        if let Some(dot_pos) = self.filename.rfind('.') {
-            self.filename.truncate(dot_pos);
+        let Some((_, ext)) = self.format_idx.checked_sub(1).map(|i| FORMATS[i]) else {
+            return;
+        };
+        let path = std::path::Path::new(self.filename.value());
+        if path.extension().is_some() && ExportFormat::from_path(path).is_none() {
+            return;
         }
-        self.filename.push_str(ext);
+        let filename = self.filename.value_mut();
+        if let Some(dot_pos) = filename.rfind('.') {
+            filename.truncate(dot_pos);
+        }
+        filename.push_str(ext);
     }
 
     pub fn render(&self, frame: &mut Frame, full: Rect) {
@@ -204,22 +901,53 @@ impl ExportDialog {
         let block = Block::default()
             .title(" Export Entries ")
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
 
-        // Layout: base_dn(2) | filter(2) | attributes(2) | format(formats+1) | filename(2) | hints(1)
+        // Loaded search results or groups as an alternative to searching, on top
+        let fields = if self.has_sources() {
+            let [source, rest] =
+                Layout::vertical([Constraint::Length(2), Constraint::Min(1)]).areas(inner);
+            let (label_style, value_style) = if self.active_field == ExportField::Source {
+                (
+                    self.theme.header(),
+                    self.theme.selected().add_modifier(Modifier::BOLD),
+                )
+            } else {
+                (self.theme.dimmed(), self.theme.dimmed())
+            };
+            let lines = vec![
+                Line::from(Span::styled("Entries:", label_style)),
+                Line::from(Span::styled(
+                    format!("< {} >", self.source_label()),
+                    value_style,
+                )),
+            ];
+            frame.render_widget(Paragraph::new(lines), source);
+            rest
+        } else {
+            inner
+        };
+
+        // Layout: base_dn(2) | filter(2) | attributes(2) | operational(1) | format(formats+2) | timestamps(2) | decode(1) | rows(2) | separator(2) | filename(2) | preset(2) | hints(1)
         let layout = Layout::vertical([
             Constraint::Length(2),                        // Base DN
             Constraint::Length(2),                        // Filter
             Constraint::Length(2),                        // Attributes
-            Constraint::Length(FORMATS.len() as u16 + 1), // Format
+            Constraint::Length(1),                        // Operational
+            Constraint::Length(FORMATS.len() as u16 + 2), // Format
+            Constraint::Length(2),                        // Timestamps
+            Constraint::Length(1),                        // Decode
+            Constraint::Length(2),                        // Rows
+            Constraint::Length(2),                        // Separator
             Constraint::Length(2),                        // Filename
+            Constraint::Length(2),                        // Preset
             Constraint::Min(1),                           // Hints
         ])
-        .split(inner);
+        .split(fields);
 
         // Base DN field
         self.render_text_field(
@@ -230,14 +958,18 @@ impl ExportDialog {
             ExportField::BaseDn,
         );
 
-        // Filter field
-        self.render_text_field(
-            frame,
-            layout[1],
-            "Search Filter",
-            &self.filter,
-            ExportField::Filter,
-        );
+        // Filter field, or how much of the branch to walk
+        if self.walks_subtree() {
+            self.render_scope(frame, layout[1]);
+        } else {
+            self.render_text_field(
+                frame,
+                layout[1],
+                "Search Filter",
+                &self.filter,
+                ExportField::Filter,
+            );
+        }
 
         // Attributes field
         self.render_text_field(
@@ -248,49 +980,261 @@ impl ExportDialog {
             ExportField::Attributes,
         );
 
+        // Operational attributes checkbox
+        let op_style = if self.active_field == ExportField::Operational {
+            self.theme.selected().add_modifier(Modifier::BOLD)
+        } else {
+            self.theme.dimmed()
+        };
+        let marker = if self.operational { "[x] " } else { "[ ] " };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                format!("{}Include operational attributes (+)", marker),
+                op_style,
+            ))),
+            layout[3],
+        );
+
         // Format selector
         let format_active = self.active_field == ExportField::Format;
         let format_label_style = if format_active {
-            self.theme.header
+            self.theme.header()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
         let mut format_lines = vec![Line::from(Span::styled("Format:", format_label_style))];
-        for (i, (name, ext)) in FORMATS.iter().enumerate() {
+        let detected = ExportFormat::from_path(std::path::Path::new(self.filename.value().trim()))
+            .map_or("unknown", |f| f.label());
+        let auto = format!("Auto from filename: {}", detected);
+        let choices = std::iter::once((auto.as_str(), None))
+            .chain(FORMATS.iter().map(|(name, ext)| (*name, Some(*ext))));
+        for (i, (name, ext)) in choices.enumerate() {
             let marker = if i == self.format_idx { "> " } else { "  " };
             let style = if i == self.format_idx && format_active {
-                self.theme.selected.add_modifier(Modifier::BOLD)
+                self.theme.selected().add_modifier(Modifier::BOLD)
             } else if i == self.format_idx {
-                self.theme.normal.add_modifier(Modifier::BOLD)
+                self.theme.normal().add_modifier(Modifier::BOLD)
             } else if format_active {
-                self.theme.normal
+                self.theme.normal()
             } else {
-                self.theme.dimmed
+                self.theme.dimmed()
             };
-            format_lines.push(Line::from(Span::styled(
-                format!("{}{} ({})", marker, name, ext),
-                style,
-            )));
+            let text = match ext {
+                Some(ext) => format!("{}{} ({})", marker, name, ext),
+                None => format!("{}{}", marker, name),
+            };
+            format_lines.push(Line::from(Span::styled(text, style)));
+        }
+        frame.render_widget(Paragraph::new(format_lines), layout[4]);
+
+        // Timestamp formatting selector
+        let ts_active = self.active_field == ExportField::Timestamps;
+        let (ts_label_style, ts_value_style) = if ts_active {
+            (
+                self.theme.header(),
+                self.theme.selected().add_modifier(Modifier::BOLD),
+            )
+        } else {
+            (self.theme.dimmed(), self.theme.dimmed())
+        };
+        let ts_lines = vec![
+            Line::from(Span::styled("Timestamps:", ts_label_style)),
+            Line::from(Span::styled(
+                format!("< {} >", self.template_label()),
+                ts_value_style,
+            )),
+        ];
+        frame.render_widget(Paragraph::new(ts_lines), layout[5]);
+
+        // Decoded values checkbox
+        let decode_style = if self.active_field == ExportField::Decode {
+            self.theme.selected().add_modifier(Modifier::BOLD)
+        } else {
+            self.theme.dimmed()
+        };
+        let marker = if self.decode { "[x] " } else { "[ ] " };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                format!("{}Decode GUIDs, SIDs, FILETIMEs and account flags", marker),
+                decode_style,
+            ))),
+            layout[6],
+        );
+
+        // Row layout selector, for CSV and Excel
+        let rows_active = self.active_field == ExportField::Rows;
+        let (rows_label_style, rows_value_style) = if rows_active {
+            (
+                self.theme.header(),
+                self.theme.selected().add_modifier(Modifier::BOLD),
+            )
+        } else {
+            (self.theme.dimmed(), self.theme.dimmed())
+        };
+        let rows_value = if !self.tabular() {
+            "CSV and Excel only".to_string()
+        } else if self.rows_idx == 1 {
+            let cursor = if rows_active { "_" } else { "" };
+            format!("< {}: {}{} >", ROW_LAYOUTS[1], self.explode_attr, cursor)
+        } else {
+            format!("< {} >", ROW_LAYOUTS[self.rows_idx])
+        };
+        let rows_lines = vec![
+            Line::from(Span::styled("Rows:", rows_label_style)),
+            Line::from(Span::styled(rows_value, rows_value_style)),
+        ];
+        frame.render_widget(Paragraph::new(rows_lines), layout[7]);
+
+        // Multi-value separator, for CSV and Excel
+        if self.tabular() {
+            self.render_text_field(
+                frame,
+                layout[8],
+                "Values Joined By",
+                &self.separator,
+                ExportField::Separator,
+            );
+        } else {
+            let lines = vec![
+                Line::from(Span::styled("Values Joined By:", self.theme.dimmed())),
+                Line::from(Span::styled("CSV and Excel only", self.theme.dimmed())),
+            ];
+            frame.render_widget(Paragraph::new(lines), layout[8]);
         }
-        frame.render_widget(Paragraph::new(format_lines), layout[3]);
 
         // Filename field
         self.render_text_field(
             frame,
-            layout[4],
+            layout[9],
             "Filename",
-            &self.filename,
+            self.filename.value(),
             ExportField::Filename,
         );
 
+        // Saved attribute selections, or the name to save one as
+        let preset_active = self.active_field == ExportField::Preset;
+        let (preset_label_style, preset_value_style) = if preset_active {
+            (
+                self.theme.header(),
+                self.theme.selected().add_modifier(Modifier::BOLD),
+            )
+        } else {
+            (self.theme.dimmed(), self.theme.dimmed())
+        };
+        let preset_lines = match &self.preset_name {
+            Some(name) => vec![
+                Line::from(Span::styled("Save Preset As:", self.theme.header())),
+                Line::from(vec![
+                    Span::styled(name.as_str(), self.theme.normal()),
+                    Span::styled("_", self.theme.command_prompt()),
+                ]),
+            ],
+            None => vec![
+                Line::from(Span::styled("Preset:", preset_label_style)),
+                Line::from(Span::styled(
+                    format!("< {} >", self.preset_label()),
+                    preset_value_style,
+                )),
+            ],
+        };
+        frame.render_widget(Paragraph::new(preset_lines), layout[10]);
+
+        if let Some((done, total)) = self.progress {
+            let bottom =
+                Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(layout[11]);
+            let ratio = if total == 0 {
+                0.0
+            } else {
+                done as f64 / total as f64
+            };
+            // A streamed branch has no total until the walk ends
+            let label = if self.walks_subtree() {
+                format!("{} entries written", format_count(done as u64))
+            } else {
+                format!(
+                    "Exported {} / {}",
+                    format_count(done as u64),
+                    format_count(total as u64)
+                )
+            };
+            let gauge = Gauge::default()
+                .gauge_style(self.theme.selected())
+                .ratio(ratio.min(1.0))
+                .label(label);
+            frame.render_widget(gauge, bottom[0]);
+            let hints = Paragraph::new(Line::from(Span::styled(
+                "Esc:cancel and remove the partial file",
+                self.theme.dimmed(),
+            )));
+            frame.render_widget(hints, bottom[1]);
+            return;
+        }
+
         // Hints
-        let hint_text = if format_active {
+        let replace_hint;
+        let hint_text = if self.confirm_replace {
+            replace_hint = format!(
+                "'{}' exists  Enter:replace  Esc:back",
+                self.preset_name.as_deref().unwrap_or_default().trim()
+            );
+            replace_hint.as_str()
+        } else if self.preset_name.is_some() {
+            "type a name  Enter:save  Esc:back"
+        } else if preset_active {
+            "Tab:next  \u{2190}/\u{2192}:pick  Ctrl+S:save selection  Enter:export  Esc:cancel"
+        } else if matches!(self.active_field, ExportField::Source | ExportField::Scope) {
+            "Tab:next  \u{2190}/\u{2192}:choose  Enter:export  Esc:cancel"
+        } else if matches!(
+            self.active_field,
+            ExportField::Operational | ExportField::Decode | ExportField::IncludeBase
+        ) {
+            "Tab:next  Space:toggle  Enter:export  Esc:cancel"
+        } else if format_active {
             "Tab:next  \u{2191}/\u{2193}:select  F2:cycle  Enter:export  Esc:cancel"
+        } else if ts_active {
+            "Tab:next  \u{2190}/\u{2192}:cycle  Enter:export  Esc:cancel"
+        } else if rows_active && self.rows_idx == 1 {
+            "Tab:next  \u{2190}/\u{2192}:cycle  type the attribute  Enter:export  Esc:cancel"
+        } else if rows_active {
+            "Tab:next  \u{2190}/\u{2192}:cycle  Enter:export  Esc:cancel"
+        } else if self.active_field == ExportField::Filename {
+            "Tab:complete or next  Enter:export  Esc:cancel"
         } else {
             "Tab:next  Enter:export  Esc:cancel"
         };
-        let hints = Paragraph::new(Line::from(Span::styled(hint_text, self.theme.dimmed)));
-        frame.render_widget(hints, layout[5]);
+        let hints = Paragraph::new(Line::from(Span::styled(hint_text, self.theme.dimmed())));
+        frame.render_widget(hints, layout[11]);
+        self.overwrite.render(frame, inner, &self.theme);
+    }
+
+    /// The scope selector and the base entry checkbox, for a subtree.
+    fn render_scope(&self, frame: &mut Frame, area: Rect) {
+        let scope_active = self.active_field == ExportField::Scope;
+        let (label_style, value_style) = if scope_active {
+            (
+                self.theme.header(),
+                self.theme.selected().add_modifier(Modifier::BOLD),
+            )
+        } else {
+            (self.theme.dimmed(), self.theme.dimmed())
+        };
+        let base_style = if self.active_field == ExportField::IncludeBase {
+            self.theme.selected().add_modifier(Modifier::BOLD)
+        } else {
+            self.theme.dimmed()
+        };
+        let marker = if self.include_base { "[x] " } else { "[ ] " };
+        let lines = vec![
+            Line::from(vec![
+                Span::styled("Scope: ", label_style),
+                Span::styled(format!("< {} >", self.scope.label()), value_style),
+            ]),
+            Line::from(Span::styled(
+                format!("{}Include the base entry", marker),
+                base_style,
+            )),
+        ];
+        frame.render_widget(Paragraph::new(lines), area);
     }
 
     fn render_text_field(
@@ -303,14 +1247,14 @@ impl ExportDialog {
     ) {
         let is_active = self.active_field == field;
         let label_style = if is_active {
-            self.theme.header
+            self.theme.header()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
         let value_style = if is_active {
-            self.theme.normal
+            self.theme.normal()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
 
         let lines = vec![
@@ -318,7 +1262,7 @@ impl ExportDialog {
             Line::from(vec![
                 Span::styled(value, value_style),
                 if is_active {
-                    Span::styled("_", self.theme.command_prompt)
+                    Span::styled("_", self.theme.command_prompt())
                 } else {
                     Span::raw("")
                 },
@@ -327,3 +1271,377 @@ impl ExportDialog {
         frame.render_widget(Paragraph::new(lines), area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_operational_checkbox_adds_plus() {
+        let mut dialog = ExportDialog::new(Theme::load("dark"));
+        dialog.show("dc=example,dc=com");
+        for _ in 0..3 {
+            dialog.handle_key_event(key(KeyCode::Tab));
+        }
+        assert_eq!(dialog.active_field, ExportField::Operational);
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+
+        let Action::ExportExecute {
+            base_dn,
+            path,
+            filter,
+            attributes,
+            timestamps,
+            decode,
+            csv,
+            format,
+            source,
+            scope,
+            include_base,
+        } = dialog.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected an export");
+        };
+        assert_eq!(attributes, vec!["*", "+"]);
+        assert_eq!(source, ExportSource::Search);
+
+        // Retrying keeps the box ticked without showing "+" in the list
+        let request = ExportRequest {
+            base_dn,
+            path,
+            filter,
+            attributes,
+            timestamps,
+            decode,
+            csv,
+            format,
+            source,
+            scope,
+            include_base,
+        };
+        dialog.show_request(&request);
+        assert!(dialog.operational);
+        assert_eq!(dialog.attributes, "*");
+    }
+
+    #[test]
+    fn test_decode_checkbox() {
+        let mut dialog = ExportDialog::new(Theme::load("dark"));
+        dialog.show("dc=example,dc=com");
+        for _ in 0..6 {
+            dialog.handle_key_event(key(KeyCode::Tab));
+        }
+        assert_eq!(dialog.active_field, ExportField::Decode);
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ExportExecute { decode: true, .. }
+        ));
+        // Not carried over to the next export
+        dialog.show("dc=example,dc=com");
+        assert!(!dialog.decode);
+    }
+
+    #[test]
+    fn test_chosen_format_overrides_filename() {
+        let mut dialog = ExportDialog::new(Theme::load("dark"));
+        dialog.show("dc=example,dc=com");
+        assert_eq!(dialog.format(), Some(ExportFormat::Ldif));
+
+        // Auto cannot tell the format of a .bak file
+        dialog.filename.set("people.bak");
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+
+        dialog.show("dc=example,dc=com");
+        dialog.filename.set("people.bak");
+        for _ in 0..4 {
+            dialog.handle_key_event(key(KeyCode::Tab));
+        }
+        assert_eq!(dialog.active_field, ExportField::Format);
+        for _ in 0..3 {
+            dialog.handle_key_event(key(KeyCode::Down));
+        }
+        // The choice leaves an extension no format claims alone
+        assert_eq!(dialog.filename.value(), "people.bak");
+        assert!(dialog.tabular());
+
+        let Action::ExportExecute { path, format, .. } =
+            dialog.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected an export");
+        };
+        assert_eq!(path, "people.bak");
+        assert_eq!(format, Some(ExportFormat::Csv));
+
+        // Choosing a format renames a known extension, and a retry keeps it
+        dialog.show("dc=example,dc=com");
+        dialog.active_field = ExportField::Format;
+        dialog.handle_key_event(key(KeyCode::Down));
+        dialog.handle_key_event(key(KeyCode::Down));
+        assert_eq!(dialog.filename.value(), "export.json");
+        dialog.show_request(&ExportRequest {
+            base_dn: "dc=example,dc=com".to_string(),
+            path: "people.bak".to_string(),
+            filter: "(objectClass=*)".to_string(),
+            attributes: vec!["*".to_string()],
+            timestamps: None,
+            decode: false,
+            csv: CsvOptions::default(),
+            format: Some(ExportFormat::Dsml),
+            source: ExportSource::Search,
+            scope: SubtreeScope::Subtree,
+            include_base: true,
+        });
+        assert_eq!(dialog.chosen_format(), Some(ExportFormat::Dsml));
+    }
+
+    #[test]
+    fn test_offered_results_replace_the_search() {
+        let mut dialog = ExportDialog::new(Theme::load("dark"));
+        dialog.show("dc=example,dc=com");
+        dialog.offer_results(34, 2193);
+        assert_eq!(dialog.active_field, ExportField::Source);
+        assert_eq!(dialog.source_label(), "Quick-filtered results (34)");
+        // Base DN and filter are not needed for loaded results
+        dialog.base_dn.clear();
+        let Action::ExportExecute { source, .. } = dialog.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected an export");
+        };
+        assert_eq!(source, ExportSource::FilteredResults);
+
+        dialog.show("dc=example,dc=com");
+        dialog.offer_results(34, 2193);
+        dialog.handle_key_event(key(KeyCode::Right));
+        assert_eq!(dialog.source_label(), "Results table as shown (34)");
+        // The table is written as a sheet, so not as LDIF
+        dialog
+            .filename
+            .value_mut()
+            .replace_range(.., "results.ldif");
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(msg) if msg.contains("CSV or Excel")
+        ));
+        dialog.handle_key_event(key(KeyCode::Right));
+        assert_eq!(dialog.source_label(), "All loaded results (2,193)");
+        dialog.handle_key_event(key(KeyCode::Right));
+        assert_eq!(dialog.source, ExportSource::Search);
+        // Tab order wraps through the Entries choice
+        dialog.handle_key_event(key(KeyCode::BackTab));
+        assert_eq!(dialog.active_field, ExportField::Preset);
+        dialog.handle_key_event(key(KeyCode::Tab));
+        assert_eq!(dialog.active_field, ExportField::Source);
+
+        // Without loaded results there is nothing to choose
+        dialog.show("dc=example,dc=com");
+        dialog.handle_key_event(key(KeyCode::BackTab));
+        assert_eq!(dialog.active_field, ExportField::Preset);
+        dialog.handle_key_event(key(KeyCode::Tab));
+        assert_eq!(dialog.active_field, ExportField::BaseDn);
+    }
+
+    #[test]
+    fn test_offered_groups() {
+        let mut dialog = ExportDialog::new(Theme::load("dark"));
+        dialog.show("cn=Ada,ou=People,dc=example,dc=com");
+        dialog.offer_groups("cn=Ada,ou=People,dc=example,dc=com", 12);
+        assert_eq!(dialog.active_field, ExportField::Source);
+        assert_eq!(dialog.source_label(), "Groups of Ada (12)");
+        dialog.handle_key_event(key(KeyCode::Right));
+        assert_eq!(dialog.source, ExportSource::Search);
+        dialog.handle_key_event(key(KeyCode::Right));
+        let Action::ExportExecute { source, .. } = dialog.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected an export");
+        };
+        assert_eq!(source, ExportSource::Groups);
+
+        // Reopened for a search, the groups are gone
+        dialog.show("dc=example,dc=com");
+        assert_eq!(dialog.source, ExportSource::Search);
+        dialog.handle_key_event(key(KeyCode::BackTab));
+        assert_eq!(dialog.active_field, ExportField::Preset);
+    }
+
+    #[test]
+    fn test_subtree_export_from_the_tree() {
+        let mut dialog = ExportDialog::new(Theme::load("dark"));
+        dialog.show_subtree("ou=Service Accounts,dc=example,dc=com");
+        assert_eq!(dialog.active_field, ExportField::Scope);
+        assert_eq!(dialog.filename.value(), "service-accounts.ldif");
+        assert_eq!(dialog.source_label(), "Subtree of Service Accounts");
+        dialog.handle_key_event(key(KeyCode::Right));
+        assert_eq!(dialog.scope, SubtreeScope::Children);
+        dialog.handle_key_event(key(KeyCode::Tab));
+        assert_eq!(dialog.active_field, ExportField::IncludeBase);
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+        dialog.handle_key_event(key(KeyCode::Tab));
+        assert_eq!(dialog.active_field, ExportField::Attributes);
+        // The filter is not asked for when walking the branch
+        dialog.filter.clear();
+        let Action::ExportExecute {
+            source,
+            scope,
+            include_base,
+            attributes,
+            ..
+        } = dialog.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected an export");
+        };
+        assert_eq!(source, ExportSource::Subtree);
+        assert_eq!(scope, SubtreeScope::Children);
+        assert!(!include_base);
+        assert_eq!(attributes, vec!["*"]);
+
+        // A search of the same base is the other choice
+        dialog.show_subtree("ou=People,dc=example,dc=com");
+        dialog.active_field = ExportField::Source;
+        dialog.handle_key_event(key(KeyCode::Right));
+        assert_eq!(dialog.source, ExportSource::Search);
+        dialog.handle_key_event(key(KeyCode::Tab));
+        dialog.handle_key_event(key(KeyCode::Tab));
+        assert_eq!(dialog.active_field, ExportField::Filter);
+    }
+
+    #[test]
+    fn test_offered_selection_comes_first() {
+        let mut dialog = ExportDialog::new(Theme::load("dark"));
+        dialog.show("dc=example,dc=com");
+        dialog.offer_selection(5, 34, 2193);
+        assert_eq!(dialog.source_label(), "Selected results (5)");
+        dialog.handle_key_event(key(KeyCode::Left));
+        assert_eq!(dialog.source, ExportSource::Search);
+        dialog.handle_key_event(key(KeyCode::Right));
+        assert_eq!(dialog.source, ExportSource::SelectedResults);
+        dialog.handle_key_event(key(KeyCode::Right));
+        assert_eq!(dialog.source, ExportSource::FilteredResults);
+        dialog.handle_key_event(key(KeyCode::Right));
+        assert_eq!(dialog.source_label(), "Results table as shown (34)");
+
+        // A retried export of a selection that is gone takes the filtered results
+        dialog.show("dc=example,dc=com");
+        dialog.source = ExportSource::SelectedResults;
+        dialog.offer_results(34, 2193);
+        assert_eq!(dialog.source, ExportSource::FilteredResults);
+        dialog.handle_key_event(key(KeyCode::Left));
+        assert_eq!(dialog.source, ExportSource::Search);
+    }
+
+    #[test]
+    fn test_running_export_shows_progress_and_cancels() {
+        let mut dialog = ExportDialog::new(Theme::load("dark"));
+        dialog.show("dc=example,dc=com");
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ExportExecute { .. }
+        ));
+        assert!(dialog.visible);
+        assert!(dialog.is_running());
+
+        dialog.set_progress(1000, 2500);
+        assert_eq!(dialog.progress, Some((1000, 2500)));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Esc)),
+            Action::ExportCancel
+        ));
+
+        dialog.hide();
+        dialog.set_progress(2000, 2500);
+        assert!(!dialog.is_running());
+    }
+
+    #[test]
+    fn test_presets_fill_in_and_save() {
+        let mut dialog = ExportDialog::new(Theme::load("dark"));
+        dialog.show("dc=example,dc=com");
+        dialog.set_presets(&[ExportPreset {
+            name: "people".to_string(),
+            attributes: vec!["cn".to_string(), "mail".to_string(), "+".to_string()],
+            format: Some(ExportFormat::Csv),
+        }]);
+        dialog.active_field = ExportField::Preset;
+        dialog.handle_key_event(key(KeyCode::Right));
+        assert_eq!(dialog.attributes, "cn, mail");
+        assert!(dialog.operational);
+        assert_eq!(dialog.filename.value(), "export.csv");
+
+        // Edit the picked preset and save it under its own name
+        dialog.attributes.push_str(", title");
+        let ctrl_s = KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL);
+        dialog.handle_key_event(ctrl_s);
+        assert_eq!(dialog.preset_name.as_deref(), Some("people"));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::None
+        ));
+        assert!(dialog.confirm_replace);
+        let Action::SaveExportPreset {
+            name,
+            attributes,
+            format,
+            replace,
+        } = dialog.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected a preset to save");
+        };
+        assert_eq!(name, "people");
+        assert_eq!(attributes, vec!["cn", "mail", "title", "+"]);
+        assert_eq!(format, Some(ExportFormat::Csv));
+        assert!(replace);
+        assert!(dialog.preset_name.is_none());
+        assert_eq!(dialog.presets.len(), 1);
+
+        // A new name saves a second preset; Esc while naming keeps the dialog
+        dialog.handle_key_event(ctrl_s);
+        for _ in 0.."people".len() {
+            dialog.handle_key_event(key(KeyCode::Backspace));
+        }
+        dialog.handle_key_event(key(KeyCode::Esc));
+        assert!(dialog.visible);
+        assert!(dialog.preset_name.is_none());
+        dialog.handle_key_event(ctrl_s);
+        dialog.preset_name = Some("mail only".to_string());
+        let Action::SaveExportPreset { replace, .. } = dialog.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected a preset to save");
+        };
+        assert!(!replace);
+        assert_eq!(dialog.selected_preset().unwrap().name, "mail only");
+    }
+
+    #[test]
+    fn test_existing_file_asks_before_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("people.ldif");
+        std::fs::write(&path, "").unwrap();
+        let mut dialog = ExportDialog::new(Theme::load("dark"));
+        dialog.show("dc=example,dc=com");
+        dialog.filename.set(path.display().to_string());
+
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::None
+        ));
+        assert!(!dialog.is_running());
+        dialog.handle_key_event(key(KeyCode::Esc));
+        assert_eq!(dialog.active_field, ExportField::Filename);
+        assert!(dialog.visible);
+
+        dialog.handle_key_event(key(KeyCode::Enter));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('y'))),
+            Action::ExportExecute { .. }
+        ));
+        assert!(dialog.is_running());
+    }
+}
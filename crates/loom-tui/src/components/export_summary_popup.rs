@@ -0,0 +1,264 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+
+use loom_core::errors::{parse_error_message, result_code_name};
+use loom_core::export::{format_size, ExportFormat, ExportReport};
+
+use crate::action::Action;
+use crate::components::export_dialog::{ExportRequest, ExportSource};
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// How the last export went.
+enum Outcome {
+    Written(ExportReport),
+    Failed {
+        error: String,
+        request: ExportRequest,
+    },
+}
+
+/// Popup shown after an export: what was written and where, with
+/// shortcuts to open the file or its folder, or to retry a failed export.
+pub struct ExportSummaryPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    outcome: Option<Outcome>,
+}
+
+impl ExportSummaryPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Export Summary", theme.clone()).with_size(70, 50),
+            theme,
+            outcome: None,
+        }
+    }
+
+    pub fn show_report(&mut self, report: ExportReport) {
+        self.outcome = Some(Outcome::Written(report));
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn show_failure(&mut self, error: String, request: ExportRequest) {
+        self.outcome = Some(Outcome::Failed { error, request });
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+        self.outcome = None;
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match (key.code, &self.outcome) {
+            (KeyCode::Esc | KeyCode::Char('q') | KeyCode::Enter, _) => {
+                self.hide();
+                Action::ClosePopup
+            }
+            (KeyCode::Char('c') | KeyCode::Char('y'), Some(Outcome::Written(report))) => {
+                Action::CopyToClipboard(report.path.display().to_string())
+            }
+            (KeyCode::Char('o'), Some(Outcome::Written(report))) => {
+                let path = report.path.display().to_string();
+                // Spreadsheet applications are slow to start and may lock the file
+                if matches!(report.format, ExportFormat::Csv | ExportFormat::Xlsx) {
+                    Action::ShowConfirm(
+                        format!(
+                            "Open {} in the default spreadsheet application?",
+                            report.path.display()
+                        ),
+                        Box::new(Action::OpenPath(path)),
+                    )
+                } else {
+                    Action::OpenPath(path)
+                }
+            }
+            (KeyCode::Char('f'), Some(Outcome::Written(report))) => {
+                Action::RevealPath(report.path.display().to_string())
+            }
+            (KeyCode::Char('c') | KeyCode::Char('y'), Some(Outcome::Failed { error, .. })) => {
+                Action::CopyToClipboard(error.clone())
+            }
+            (KeyCode::Char('r'), Some(Outcome::Failed { request, .. })) => {
+                let request = request.clone();
+                self.hide();
+                Action::RetryExport(request)
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+        let Some(outcome) = &self.outcome else {
+            return;
+        };
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let (lines, hint) = match outcome {
+            Outcome::Written(report) => (
+                self.report_lines(report),
+                "o:open  f:show folder  c:copy path  Esc:close",
+            ),
+            Outcome::Failed { error, request } => (
+                self.failure_lines(error, request),
+                "r:retry  c:copy error  Esc:close",
+            ),
+        };
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), layout[0]);
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(hint, self.theme.dimmed()))),
+            layout[1],
+        );
+    }
+
+    fn field(&self, label: &str, value: String) -> Line<'static> {
+        Line::from(vec![
+            Span::styled(format!("{:<10}", label), self.theme.header()),
+            Span::styled(value, self.theme.normal()),
+        ])
+    }
+
+    fn report_lines(&self, report: &ExportReport) -> Vec<Line<'static>> {
+        let mut lines = vec![
+            Line::from(Span::styled("Export complete", self.theme.success())),
+            Line::from(""),
+            self.field("Format", report.format.label().to_string()),
+            self.field("Path", report.path.display().to_string()),
+            self.field(
+                "Entries",
+                format!("{} written, {} skipped", report.written, report.skipped),
+            ),
+            self.field("Size", format_size(report.bytes)),
+            self.field("Duration", format!("{:.2}s", report.duration.as_secs_f64())),
+        ];
+        if !report.warnings.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Warnings", self.theme.warning())));
+            for warning in &report.warnings {
+                lines.push(Line::from(Span::styled(
+                    format!("  {}", warning),
+                    self.theme.warning(),
+                )));
+            }
+        }
+        lines
+    }
+
+    fn failure_lines(&self, error: &str, request: &ExportRequest) -> Vec<Line<'static>> {
+        let details = parse_error_message(error);
+        let mut lines = vec![
+            Line::from(Span::styled("Export failed", self.theme.error())),
+            Line::from(""),
+            self.field("Path", request.path.clone()),
+            self.field("Base DN", request.base_dn.clone()),
+            if request.source == ExportSource::Subtree {
+                self.field("Scope", request.scope.label().to_string())
+            } else {
+                self.field("Filter", request.filter.clone())
+            },
+        ];
+        if let Some(code) = details.code {
+            lines.push(self.field("Result", format!("{} ({})", result_code_name(code), code)));
+        }
+        if let Some(dn) = details.matched_dn {
+            lines.push(self.field("Matched", dn));
+        }
+        lines.push(self.field("Error", details.diagnostic));
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+    use std::time::Duration;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    fn report(path: &str, format: ExportFormat) -> ExportReport {
+        ExportReport {
+            format,
+            path: PathBuf::from(path),
+            written: 3,
+            skipped: 0,
+            bytes: 2048,
+            duration: Duration::from_millis(40),
+            warnings: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_open_confirms_only_for_spreadsheets() {
+        let mut popup = ExportSummaryPopup::new(Theme::load("dark"));
+        popup.show_report(report("/tmp/people.ldif", ExportFormat::Ldif));
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Char('o'))),
+            Action::OpenPath(ref p) if p == "/tmp/people.ldif"
+        ));
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Char('f'))),
+            Action::RevealPath(_)
+        ));
+        // The retry key does nothing after a successful export
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Char('r'))),
+            Action::None
+        ));
+
+        popup.show_report(report("/tmp/people.xlsx", ExportFormat::Xlsx));
+        match popup.handle_key_event(key(KeyCode::Char('o'))) {
+            Action::ShowConfirm(_, on_confirm) => {
+                assert!(matches!(*on_confirm, Action::OpenPath(ref p) if p == "/tmp/people.xlsx"))
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_failure_offers_retry_with_the_same_request() {
+        let request = ExportRequest {
+            base_dn: "dc=example,dc=com".to_string(),
+            path: "people.csv".to_string(),
+            filter: "(objectClass=person)".to_string(),
+            attributes: vec!["cn".to_string(), "mail".to_string()],
+            timestamps: None,
+            decode: false,
+            csv: Default::default(),
+            format: None,
+            source: Default::default(),
+            scope: Default::default(),
+            include_base: true,
+        };
+        let mut popup = ExportSummaryPopup::new(Theme::load("dark"));
+        popup.show_failure(
+            "Export failed: permission denied".to_string(),
+            request.clone(),
+        );
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Char('o'))),
+            Action::None
+        ));
+        match popup.handle_key_event(key(KeyCode::Char('r'))) {
+            Action::RetryExport(retry) => assert_eq!(retry, request),
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(!popup.visible);
+    }
+}
@@ -6,7 +6,7 @@ use ratatui::Frame;
 
 use crate::action::Action;
 use crate::components::popup::Popup;
-use crate::keymap::Keymap;
+use crate::keymap::{KeyContext, Keymap};
 use crate::theme::Theme;
 
 struct HelpSection {
@@ -107,8 +107,8 @@ impl HelpPopup {
         let block = Block::default()
             .title(" Help ")
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -121,14 +121,14 @@ impl HelpPopup {
             // Section header
             lines.push(Line::from(Span::styled(
                 section.title.clone(),
-                self.theme.popup_title,
+                self.theme.popup_title(),
             )));
             // Entries
             for (key, desc) in &section.entries {
                 let padded_key = format!("  {:<16}", key);
                 lines.push(Line::from(vec![
-                    Span::styled(padded_key, self.theme.header),
-                    Span::styled(desc.clone(), self.theme.normal),
+                    Span::styled(padded_key, self.theme.header()),
+                    Span::styled(desc.clone(), self.theme.normal()),
                 ]));
             }
             // Blank line between sections (except last)
@@ -149,117 +149,39 @@ impl HelpPopup {
         // Hints
         let hints = Line::from(Span::styled(
             "\u{2191}/\u{2193}:scroll  Home/End  PgUp/PgDn  q:close",
-            self.theme.dimmed,
+            self.theme.dimmed(),
         ));
         frame.render_widget(Paragraph::new(hints), layout[1]);
     }
 }
 
 fn build_sections(keymap: &Keymap) -> Vec<HelpSection> {
-    vec![
-        HelpSection {
-            title: "GLOBAL SHORTCUTS (configurable)".to_string(),
-            entries: vec![
-                (
-                    keymap.hint("switch_to_profiles").to_string(),
-                    "Profiles".to_string(),
-                ),
-                (
-                    keymap.hint("show_connect_dialog").to_string(),
-                    "Connect dialog".to_string(),
-                ),
-                (
-                    keymap.hint("show_export_dialog").to_string(),
-                    "Export dialog".to_string(),
-                ),
-                (
-                    format!("{}/?", keymap.hint("show_help")),
-                    "Help".to_string(),
-                ),
-                (
-                    keymap.hint("show_schema_viewer").to_string(),
-                    "Schema viewer".to_string(),
-                ),
-                ("F3".to_string(), "About".to_string()),
-                (
-                    keymap.hint("toggle_log_panel").to_string(),
-                    "Log panel".to_string(),
-                ),
-                (
-                    keymap.hint("show_bulk_update").to_string(),
-                    "Bulk update".to_string(),
-                ),
-                (
-                    keymap.hint("search").to_string(),
-                    "Focus search input".to_string(),
-                ),
-                (
-                    keymap.hint("save_connection").to_string(),
-                    "Save connection".to_string(),
-                ),
-                (
-                    keymap.hint("focus_next").to_string(),
-                    "Next panel".to_string(),
-                ),
-                (
-                    keymap.hint("focus_prev").to_string(),
-                    "Previous panel".to_string(),
-                ),
-                (
-                    format!("{}/gt", keymap.hint("next_tab")),
-                    "Next tab".to_string(),
-                ),
-                (
-                    format!("{}/gT", keymap.hint("prev_tab")),
-                    "Previous tab".to_string(),
-                ),
-                (
-                    keymap.hint("close_tab").to_string(),
-                    "Close tab".to_string(),
-                ),
-                (keymap.hint("quit").to_string(), "Quit".to_string()),
-                (
-                    keymap.hint("force_quit").to_string(),
-                    "Force quit".to_string(),
-                ),
-            ],
-        },
-        HelpSection {
-            title: "TREE PANEL".to_string(),
-            entries: vec![
-                (
-                    "j/k \u{2191}/\u{2193}".to_string(),
-                    "Navigate up/down".to_string(),
-                ),
-                (
-                    "l/\u{2192}/Enter".to_string(),
-                    "Expand / toggle node".to_string(),
-                ),
-                ("h/\u{2190}".to_string(), "Collapse node".to_string()),
-                ("a".to_string(), "Create child entry".to_string()),
-                ("d/Delete".to_string(), "Delete entry".to_string()),
-                ("Space".to_string(), "Context menu".to_string()),
-            ],
-        },
-        HelpSection {
-            title: "DETAIL PANEL".to_string(),
-            entries: vec![
-                (
-                    "j/k \u{2191}/\u{2193}".to_string(),
-                    "Navigate attributes".to_string(),
-                ),
-                ("e/Enter".to_string(), "Edit attribute value".to_string()),
-                ("a".to_string(), "Add new attribute".to_string()),
-                ("+".to_string(), "Add value to attribute".to_string()),
-                ("d/Delete".to_string(), "Delete attribute value".to_string()),
-                ("n".to_string(), "Create child entry".to_string()),
-                ("x".to_string(), "Delete entry".to_string()),
-                ("r".to_string(), "Refresh entry".to_string()),
-                ("Space".to_string(), "Context menu".to_string()),
-            ],
-        },
+    let mut sections = Vec::new();
+    if !keymap.problems().is_empty() {
+        sections.push(HelpSection {
+            title: "[keybindings] PROBLEMS".to_string(),
+            entries: keymap
+                .problems()
+                .iter()
+                .map(|problem| ("!".to_string(), problem.clone()))
+                .collect(),
+        });
+    }
+    // The effective keys of each context, then the fixed keys of the
+    // popups and forms in its group
+    let configurable = |context: KeyContext| HelpSection {
+        title: format!("{} (configurable)", context.title()),
+        entries: keymap.help(context),
+    };
+    sections.extend([
+        configurable(KeyContext::Global),
+        configurable(KeyContext::Browser),
+        configurable(KeyContext::Tree),
+        configurable(KeyContext::Detail),
+    ]);
+    sections.extend([
         HelpSection {
-            title: "CONTEXT MENU".to_string(),
+            title: "BROWSER: CONTEXT MENU".to_string(),
             entries: vec![
                 (
                     "Space/Right-click".to_string(),
@@ -274,53 +196,48 @@ fn build_sections(keymap: &Keymap) -> Vec<HelpSection> {
             ],
         },
         HelpSection {
-            title: "COMMAND / SEARCH".to_string(),
+            title: "BROWSER: COMMAND / SEARCH".to_string(),
             entries: vec![
-                ("/ or :".to_string(), "Activate search input".to_string()),
                 ("Enter".to_string(), "Execute search filter".to_string()),
                 ("Esc".to_string(), "Cancel / deactivate input".to_string()),
             ],
         },
         HelpSection {
-            title: "PROFILES TREE".to_string(),
+            title: "BROWSER: SEARCH FORM".to_string(),
             entries: vec![
+                ("Tab/S-Tab".to_string(), "Next / previous field".to_string()),
                 (
-                    "j/k \u{2191}/\u{2193}".to_string(),
-                    "Navigate profiles".to_string(),
+                    "h/l \u{2190}/\u{2192}".to_string(),
+                    "Cycle scope".to_string(),
                 ),
-                ("l/\u{2192}".to_string(), "Expand folder / view".to_string()),
-                ("h/\u{2190}".to_string(), "Collapse folder".to_string()),
-                ("e".to_string(), "Edit / view profile".to_string()),
-                ("c".to_string(), "Connect to profile".to_string()),
-                ("n".to_string(), "New profile".to_string()),
-                ("d/Delete".to_string(), "Delete profile".to_string()),
+                (
+                    "\u{2191}/\u{2193}".to_string(),
+                    "Filter history".to_string(),
+                ),
+                ("Enter".to_string(), "Search".to_string()),
+                ("F2".to_string(), "Bookmark search".to_string()),
+                ("Esc".to_string(), "Cancel".to_string()),
             ],
         },
+    ]);
+    sections.extend([
+        configurable(KeyContext::Connections),
         HelpSection {
-            title: "CONNECTION FORM".to_string(),
+            title: "CONNECTIONS: CONNECTION FORM".to_string(),
             entries: vec![
                 ("Tab/S-Tab".to_string(), "Next / previous field".to_string()),
                 ("e".to_string(), "Enter edit mode (view)".to_string()),
                 ("c".to_string(), "Connect (view mode)".to_string()),
                 ("F2".to_string(), "Cycle TLS mode (edit)".to_string()),
                 ("F3".to_string(), "Cycle credential method".to_string()),
+                ("C-t".to_string(), "Test connection (edit)".to_string()),
                 ("F10/C-Enter".to_string(), "Save profile (edit)".to_string()),
                 ("Esc".to_string(), "Cancel editing".to_string()),
             ],
         },
+        configurable(KeyContext::Results),
         HelpSection {
-            title: "SEARCH RESULTS".to_string(),
-            entries: vec![
-                (
-                    "j/k \u{2191}/\u{2193}".to_string(),
-                    "Navigate results".to_string(),
-                ),
-                ("Enter".to_string(), "Go to selected entry".to_string()),
-                ("Esc/q".to_string(), "Close".to_string()),
-            ],
-        },
-        HelpSection {
-            title: "EXPORT DIALOG".to_string(),
+            title: "DIALOGS: EXPORT DIALOG".to_string(),
             entries: vec![
                 ("Tab/S-Tab".to_string(), "Next / previous field".to_string()),
                 ("F2".to_string(), "Cycle export format".to_string()),
@@ -329,50 +246,69 @@ fn build_sections(keymap: &Keymap) -> Vec<HelpSection> {
             ],
         },
         HelpSection {
-            title: "BULK UPDATE DIALOG".to_string(),
+            title: "DIALOGS: EXPORT SUMMARY".to_string(),
+            entries: vec![
+                ("o".to_string(), "Open the file".to_string()),
+                ("f".to_string(), "Show the file in its folder".to_string()),
+                ("c/y".to_string(), "Copy path or error".to_string()),
+                ("r".to_string(), "Retry a failed export".to_string()),
+                ("Esc".to_string(), "Close".to_string()),
+            ],
+        },
+        HelpSection {
+            title: "DIALOGS: BULK UPDATE DIALOG".to_string(),
             entries: vec![
                 ("Tab/S-Tab".to_string(), "Next / previous field".to_string()),
                 ("F2".to_string(), "Cycle operation type".to_string()),
+                (
+                    "F3".to_string(),
+                    "Toggle literal value / script".to_string(),
+                ),
                 ("Enter".to_string(), "Execute bulk update".to_string()),
                 ("Esc".to_string(), "Cancel".to_string()),
             ],
         },
         HelpSection {
-            title: "CONFIRM DIALOG".to_string(),
+            title: "DIALOGS: BATCH MODIFY DIALOG".to_string(),
             entries: vec![
-                ("y".to_string(), "Confirm (Yes)".to_string()),
-                ("n/Esc".to_string(), "Cancel (No)".to_string()),
+                ("Tab/S-Tab".to_string(), "Next / previous field".to_string()),
+                ("F2".to_string(), "Cycle operation".to_string()),
                 (
-                    "h/l \u{2190}/\u{2192}".to_string(),
-                    "Select Yes / No".to_string(),
+                    "F3".to_string(),
+                    "Toggle dry run (LDIF changefile)".to_string(),
                 ),
-                ("Enter".to_string(), "Execute selection".to_string()),
+                ("Enter".to_string(), "Apply to every entry".to_string()),
+                ("Esc".to_string(), "Cancel".to_string()),
             ],
         },
+        configurable(KeyContext::Confirm),
+        configurable(KeyContext::LogPanel),
         HelpSection {
             title: "SCHEMA VIEWER".to_string(),
             entries: vec![
-                ("j/k \u{2191}/\u{2193}".to_string(), "Scroll".to_string()),
-                ("Tab".to_string(), "Switch Attributes/Classes".to_string()),
+                ("j/k \u{2191}/\u{2193}".to_string(), "Move".to_string()),
+                (
+                    "o/a \u{2190}/\u{2192}".to_string(),
+                    "Classes / attributes".to_string(),
+                ),
                 ("/".to_string(), "Filter".to_string()),
-                ("Esc/q".to_string(), "Close".to_string()),
-            ],
-        },
-        HelpSection {
-            title: "LOG PANEL".to_string(),
-            entries: vec![
-                ("j/k \u{2191}/\u{2193}".to_string(), "Scroll".to_string()),
-                ("g/G Home/End".to_string(), "Top / bottom".to_string()),
-                ("Esc/q".to_string(), "Close".to_string()),
+                ("Tab".to_string(), "List / definition".to_string()),
+                ("Enter".to_string(), "Open definition or link".to_string()),
+                (
+                    "Backspace".to_string(),
+                    "Back to previous definition".to_string(),
+                ),
+                ("Esc/q".to_string(), "Back to browser".to_string()),
             ],
         },
-    ]
+    ]);
+    sections
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::KeybindingConfig;
+    use crate::config::{KeyChords, KeybindingConfig};
 
     fn make_popup() -> HelpPopup {
         HelpPopup::new(Theme::load("dark"))
@@ -482,14 +418,17 @@ mod tests {
         popup.show(&keymap());
         let titles: Vec<&str> = popup.sections.iter().map(|s| s.title.as_str()).collect();
         assert!(titles.contains(&"GLOBAL SHORTCUTS (configurable)"));
-        assert!(titles.contains(&"TREE PANEL"));
-        assert!(titles.contains(&"DETAIL PANEL"));
-        assert!(titles.contains(&"COMMAND / SEARCH"));
-        assert!(titles.contains(&"PROFILES TREE"));
-        assert!(titles.contains(&"CONNECTION FORM"));
-        assert!(titles.contains(&"EXPORT DIALOG"));
+        assert!(titles.contains(&"BROWSER (configurable)"));
+        assert!(titles.contains(&"BROWSER: TREE PANEL (configurable)"));
+        assert!(titles.contains(&"BROWSER: DETAIL PANEL (configurable)"));
+        assert!(titles.contains(&"BROWSER: COMMAND / SEARCH"));
+        assert!(titles.contains(&"CONNECTIONS: PROFILES TREE (configurable)"));
+        assert!(titles.contains(&"CONNECTIONS: CONNECTION FORM"));
+        assert!(titles.contains(&"DIALOGS: SEARCH RESULTS (configurable)"));
+        assert!(titles.contains(&"DIALOGS: EXPORT DIALOG"));
         assert!(titles.contains(&"SCHEMA VIEWER"));
-        assert!(titles.contains(&"LOG PANEL"));
+        assert!(titles.contains(&"DIALOGS: CONFIRM DIALOG (configurable)"));
+        assert!(titles.contains(&"DIALOGS: LOG PANEL (configurable)"));
         assert!(popup.total_lines > 0);
     }
 
@@ -502,7 +441,9 @@ mod tests {
         // Check that F-key bindings are present
         assert!(keys.contains(&"F1"));
         assert!(keys.contains(&"F4"));
-        assert!(keys.contains(&"F5/?"));
+        assert!(keys.contains(&"F5"));
+        assert!(keys.contains(&"?"));
+        assert!(keys.contains(&"F3"));
         assert!(keys.contains(&"F6"));
         assert!(keys.contains(&"F7"));
         assert!(keys.contains(&"F8"));
@@ -526,4 +467,46 @@ mod tests {
         popup.handle_key_event(KeyEvent::from(KeyCode::PageUp));
         assert_eq!(popup.scroll_offset, total.saturating_sub(20));
     }
+
+    #[test]
+    fn test_sections_show_configured_keys_and_problems() {
+        let config = KeybindingConfig {
+            commands: [
+                ("rename", KeyChords::One("F2".to_string())),
+                ("yes", KeyChords::One("j".to_string())),
+            ]
+            .into_iter()
+            .map(|(name, chords)| (name.to_string(), chords))
+            .collect(),
+            ..Default::default()
+        };
+        let mut popup = make_popup();
+        popup.show(&Keymap::from_config(&config));
+
+        let section = |title: &str| {
+            popup
+                .sections
+                .iter()
+                .find(|s| s.title == title)
+                .unwrap()
+                .entries
+                .clone()
+        };
+        assert_eq!(
+            section("[keybindings] PROBLEMS"),
+            vec![(
+                "!".to_string(),
+                "Key 'F2' for rename is already bound to show_connect_dialog in the tree panel; using 'r'"
+                    .to_string()
+            )]
+        );
+        assert!(section("BROWSER: TREE PANEL (configurable)")
+            .contains(&("r".to_string(), "Rename / move entry".to_string())));
+        assert!(section("DIALOGS: CONFIRM DIALOG (configurable)")
+            .contains(&("j".to_string(), "Confirm (Yes)".to_string())));
+        assert!(section("BROWSER: TREE PANEL (configurable)").contains(&(
+            "l/Right/Enter".to_string(),
+            "Expand / toggle node".to_string()
+        )));
+    }
 }
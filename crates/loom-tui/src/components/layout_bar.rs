@@ -4,97 +4,316 @@ use ratatui::widgets::Paragraph;
 use ratatui::Frame;
 
 use crate::action::{ActiveLayout, ConnectionId};
-use crate::components::tab_bar::TabEntry;
+use crate::components::quick_switcher::{
+    JumpTarget, JumpTargetProvider, SwitchTarget, TargetCategory,
+};
 use crate::theme::Theme;
+use crate::widgets::spinner::Spinner;
+use crate::widgets::width::{display_width, truncate_to_width};
+use loom_core::tree::format_count;
 
-/// Unified tab bar: `[Profiles] | [conn1] conn2`
+/// What the layout bar shows for one connection tab.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TabState {
+    pub id: ConnectionId,
+    /// Profile name.
+    pub label: String,
+    /// Entries loaded into the tree.
+    pub entries: usize,
+    /// Result count of the last search, when it ran on this tab.
+    pub results: Option<usize>,
+    /// A background operation is running on the connection.
+    pub busy: bool,
+    /// Changes not yet written to the server.
+    pub dirty: bool,
+}
+
+impl TabState {
+    fn badge(&self) -> String {
+        match self.results {
+            Some(n) => format!("{} found", format_count(n as u64)),
+            None => format_count(self.entries as u64),
+        }
+    }
+}
+
+/// How much of each tab fits: badges go first, then names are shortened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Detail {
+    Full,
+    NoBadges,
+    /// Names cut to this many columns.
+    Truncated(usize),
+}
+
+/// Spans for the connection tabs, fitted into `width` columns, each with
+/// its tab's id so the caller can record where it was drawn. Every tab is
+/// followed by one column of spacing, which is not part of its spans.
+pub fn tab_spans(
+    tabs: &[TabState],
+    active: Option<ConnectionId>,
+    width: usize,
+    spinner: &str,
+    theme: &Theme,
+) -> Vec<(ConnectionId, Vec<Span<'static>>)> {
+    let render = |detail| {
+        tabs.iter()
+            .map(|tab| {
+                let spans = one_tab(tab, active == Some(tab.id), detail, spinner, theme);
+                (tab.id, spans)
+            })
+            .collect::<Vec<_>>()
+    };
+    let fits = |tabs: &[(ConnectionId, Vec<Span<'static>>)]| {
+        let used: usize = tabs
+            .iter()
+            .map(|(_, spans)| spans.iter().map(Span::width).sum::<usize>() + 1)
+            .sum();
+        used <= width
+    };
+
+    let longest = tabs
+        .iter()
+        .map(|t| display_width(&t.label))
+        .max()
+        .unwrap_or(0);
+    let mut fallback = None;
+    for detail in [Detail::Full, Detail::NoBadges]
+        .into_iter()
+        .chain((1..longest).rev().map(Detail::Truncated))
+    {
+        let rendered = render(detail);
+        if fits(&rendered) {
+            return rendered;
+        }
+        fallback = Some(rendered);
+    }
+    // Too many tabs to fit at all: the paragraph clips the rest
+    fallback.unwrap_or_else(|| render(Detail::NoBadges))
+}
+
+fn one_tab(
+    tab: &TabState,
+    is_active: bool,
+    detail: Detail,
+    spinner: &str,
+    theme: &Theme,
+) -> Vec<Span<'static>> {
+    let (style, open, close) = if is_active {
+        (
+            theme.tab_active(),
+            Span::styled("[", theme.tab_active()),
+            Span::styled("]", theme.tab_active()),
+        )
+    } else {
+        (
+            theme.tab_inactive(),
+            Span::styled(" ", theme.status_bar()),
+            Span::styled(" ", theme.status_bar()),
+        )
+    };
+    let name = match detail {
+        Detail::Truncated(max) => truncate_to_width(&tab.label, max),
+        Detail::Full | Detail::NoBadges => tab.label.clone(),
+    };
+
+    let mut spans = vec![open, Span::styled(name, style)];
+    if tab.dirty {
+        spans.push(Span::styled("*", theme.warning()));
+    }
+    if detail == Detail::Full {
+        spans.push(Span::styled(format!(" {}", tab.badge()), theme.dimmed()));
+    }
+    if tab.busy {
+        spans.push(Span::styled(format!(" {}", spinner), theme.warning()));
+    }
+    spans.push(close);
+    spans
+}
+
+/// What a click on the layout bar opens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BarTarget {
+    Layout(ActiveLayout),
+    Tab(ConnectionId),
+}
+
+/// Unified tab bar: `[Profiles] Schema | [conn1] conn2`
 ///
-/// Profiles is always the first tab. Connection tabs follow after a separator.
+/// Profiles is always the first tab, followed by Schema once a connection
+/// is open. Connection tabs follow after a separator, each with its entry
+/// or result count, a spinner while the connection is busy, and `*` when it
+/// has unsaved changes.
 pub struct LayoutBar {
     pub active: ActiveLayout,
     theme: Theme,
+    spinner: Spinner,
     /// Hit regions populated during render: (x_start, x_end_exclusive, target).
-    /// `None` = Profiles tab, `Some(id)` = connection tab.
-    pub hit_regions: Vec<(u16, u16, Option<ConnectionId>)>,
+    pub hit_regions: Vec<(u16, u16, BarTarget)>,
 }
 
 impl LayoutBar {
     pub fn new(theme: Theme) -> Self {
         Self {
             active: ActiveLayout::Profiles,
+            spinner: Spinner::new(theme.warning()),
             theme,
             hit_regions: Vec::new(),
         }
     }
 
+    /// Advance the busy spinner one frame.
+    pub fn tick(&mut self) {
+        self.spinner.tick();
+    }
+
     pub fn render(
         &mut self,
         frame: &mut Frame,
         area: Rect,
-        tabs: &[TabEntry],
+        tabs: &[TabState],
         active_tab: Option<ConnectionId>,
     ) {
         self.hit_regions.clear();
 
-        let profiles_active = self.active == ActiveLayout::Profiles;
-        let profiles_style = if profiles_active {
-            self.theme.tab_active
-        } else {
-            self.theme.tab_inactive
-        };
-
-        let mut spans = vec![Span::styled(" ", self.theme.status_bar)];
+        let mut spans = vec![Span::styled(" ", self.theme.status_bar())];
         let mut x = area.x + 1; // after leading space
 
-        // Profiles tab
-        let profiles_label = if profiles_active {
-            "[Profiles]"
-        } else {
-            " Profiles "
-        };
-        spans.push(Span::styled(profiles_label, profiles_style));
-        let profiles_end = x + profiles_label.len() as u16;
-        self.hit_regions.push((x, profiles_end, None));
-        x = profiles_end;
+        // Layout tabs; the schema shown is the active connection's
+        let mut layouts = vec![("Profiles", ActiveLayout::Profiles)];
+        if !tabs.is_empty() {
+            layouts.push(("Schema", ActiveLayout::Schema));
+        }
+        for (name, layout) in layouts {
+            let (label, style) = if self.active == layout {
+                (format!("[{}]", name), self.theme.tab_active())
+            } else {
+                (format!(" {} ", name), self.theme.tab_inactive())
+            };
+            let end = x + label.len() as u16;
+            self.hit_regions.push((x, end, BarTarget::Layout(layout)));
+            spans.push(Span::styled(label, style));
+            x = end;
+        }
 
         // Connection tabs after separator
         if !tabs.is_empty() {
-            spans.push(Span::styled(" \u{2502} ", self.theme.dimmed));
+            spans.push(Span::styled(" \u{2502} ", self.theme.dimmed()));
             x += 3;
 
-            for tab in tabs {
-                let is_active = self.active == ActiveLayout::Browser && active_tab == Some(tab.id);
-                let style = if is_active {
-                    self.theme.tab_active
-                } else {
-                    self.theme.tab_inactive
-                };
-
+            let active = match self.active {
+                ActiveLayout::Browser => active_tab,
+                ActiveLayout::Profiles | ActiveLayout::Schema => None,
+            };
+            let available = (area.x + area.width).saturating_sub(x) as usize;
+            for (id, tab) in tab_spans(tabs, active, available, self.spinner.frame(), &self.theme) {
                 let tab_start = x;
-                if is_active {
-                    spans.push(Span::styled("[", style));
-                    spans.push(Span::styled(&tab.label, style));
-                    spans.push(Span::styled("]", style));
-                } else {
-                    spans.push(Span::styled(" ", self.theme.status_bar));
-                    spans.push(Span::styled(&tab.label, style));
-                    spans.push(Span::styled(" ", self.theme.status_bar));
-                }
-                x += tab.label.len() as u16 + 2;
-                self.hit_regions.push((tab_start, x, Some(tab.id)));
-
-                spans.push(Span::styled(" ", self.theme.status_bar));
+                x += tab.iter().map(Span::width).sum::<usize>() as u16;
+                self.hit_regions.push((tab_start, x, BarTarget::Tab(id)));
+                spans.extend(tab);
+
+                spans.push(Span::styled(" ", self.theme.status_bar()));
                 x += 1;
             }
         }
 
         // Pad remaining width
-        let content_len: usize = spans.iter().map(|s| s.content.len()).sum();
+        let content_len: usize = spans.iter().map(Span::width).sum();
         let padding = " ".repeat(area.width as usize - content_len.min(area.width as usize));
-        spans.push(Span::styled(padding, self.theme.status_bar));
+        spans.push(Span::styled(padding, self.theme.status_bar()));
 
         let line = Line::from(spans);
         let bar = Paragraph::new(line);
         frame.render_widget(bar, area);
     }
 }
+
+impl JumpTargetProvider for LayoutBar {
+    /// The Browser layout is reached through its connection tabs.
+    fn jump_targets(&self) -> Vec<SwitchTarget> {
+        vec![
+            SwitchTarget::new(
+                TargetCategory::Layout,
+                "Profiles",
+                JumpTarget::Layout(ActiveLayout::Profiles),
+            ),
+            SwitchTarget::new(
+                TargetCategory::Layout,
+                "Schema",
+                JumpTarget::Layout(ActiveLayout::Schema),
+            ),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tab(id: ConnectionId, label: &str, entries: usize) -> TabState {
+        TabState {
+            id,
+            label: label.to_string(),
+            entries,
+            results: None,
+            busy: false,
+            dirty: false,
+        }
+    }
+
+    fn text(tabs: &[(ConnectionId, Vec<Span<'static>>)]) -> String {
+        tabs.iter()
+            .map(|(_, spans)| spans.iter().map(|s| s.content.as_ref()).collect::<String>())
+            .collect::<Vec<_>>()
+            .join("|")
+    }
+
+    #[test]
+    fn test_badges_spinner_and_dirty_marker() {
+        let theme = Theme::load("dark");
+        let mut prod = tab(1, "prod", 1204);
+        prod.busy = true;
+        let mut staging = tab(2, "staging", 12);
+        staging.results = Some(42);
+        staging.dirty = true;
+
+        let spans = tab_spans(&[prod, staging], Some(1), 80, "\u{280b}", &theme);
+        assert_eq!(text(&spans), "[prod 1,204 \u{280b}]| staging* 42 found ");
+        assert_eq!(spans[0].0, 1);
+        assert_eq!(spans[1].0, 2);
+    }
+
+    #[test]
+    fn test_narrow_bar_drops_badges_then_truncates_names() {
+        let theme = Theme::load("dark");
+        let mut busy = tab(2, "development", 15000);
+        busy.busy = true;
+        let tabs = vec![tab(1, "production", 1204), busy];
+
+        // Full: "[production 1,204]" + 1 and " development 15,000 ⠋ " + 1
+        let full = tab_spans(&tabs, Some(1), 42, "\u{280b}", &theme);
+        assert_eq!(
+            text(&full),
+            "[production 1,204]| development 15,000 \u{280b} "
+        );
+
+        // Badges go first; the spinner stays
+        let no_badges = tab_spans(&tabs, Some(1), 30, "\u{280b}", &theme);
+        assert_eq!(text(&no_badges), "[production]| development \u{280b} ");
+
+        // Then names shrink, width-aware
+        let truncated = tab_spans(&tabs, Some(1), 20, "\u{280b}", &theme);
+        assert_eq!(text(&truncated), "[produ\u{2026}]| devel\u{2026} \u{280b} ");
+        let used: usize = truncated
+            .iter()
+            .map(|(_, s)| s.iter().map(Span::width).sum::<usize>() + 1)
+            .sum();
+        assert!(used <= 20);
+
+        // Every tab keeps its id, so a busy tab is still clickable
+        assert_eq!(
+            truncated.iter().map(|(id, _)| *id).collect::<Vec<_>>(),
+            [1, 2]
+        );
+    }
+}
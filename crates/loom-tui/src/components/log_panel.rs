@@ -1,4 +1,4 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::KeyEvent;
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
@@ -6,6 +6,10 @@ use ratatui::Frame;
 
 use crate::action::Action;
 use crate::components::popup::Popup;
+use crate::components::quick_switcher::{
+    JumpTarget, JumpTargetProvider, SwitchTarget, TargetCategory,
+};
+use crate::keymap::{Command, ContextKeys, KeyContext, Keymap};
 use crate::theme::Theme;
 
 /// A toggleable in-TUI log viewer.
@@ -16,6 +20,7 @@ pub struct LogPanel {
     theme: Theme,
     messages: Vec<LogEntry>,
     scroll_offset: usize,
+    keys: ContextKeys,
 }
 
 struct LogEntry {
@@ -38,9 +43,20 @@ impl LogPanel {
             theme,
             messages: Vec::new(),
             scroll_offset: 0,
+            keys: Keymap::default().context(KeyContext::LogPanel),
         }
     }
 
+    /// Use the log panel keys of `keymap` instead of the defaults.
+    pub fn with_keys(mut self, keymap: &Keymap) -> Self {
+        self.set_keys(keymap);
+        self
+    }
+
+    pub fn set_keys(&mut self, keymap: &Keymap) {
+        self.keys = keymap.context(KeyContext::LogPanel);
+    }
+
     pub fn toggle(&mut self) {
         self.visible = !self.visible;
         if self.visible {
@@ -88,26 +104,26 @@ impl LogPanel {
     }
 
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
-        match key.code {
-            KeyCode::Esc | KeyCode::Char('q') => {
+        match self.keys.command(&key) {
+            Some(Command::Close) => {
                 self.hide();
                 Action::ClosePopup
             }
-            KeyCode::Up | KeyCode::Char('k') => {
+            Some(Command::Up) => {
                 self.scroll_offset = self.scroll_offset.saturating_sub(1);
                 Action::None
             }
-            KeyCode::Down | KeyCode::Char('j') => {
+            Some(Command::Down) => {
                 if self.scroll_offset + 1 < self.messages.len() {
                     self.scroll_offset += 1;
                 }
                 Action::None
             }
-            KeyCode::Home | KeyCode::Char('g') => {
+            Some(Command::Top) => {
                 self.scroll_offset = 0;
                 Action::None
             }
-            KeyCode::End | KeyCode::Char('G') => {
+            Some(Command::Bottom) => {
                 self.scroll_offset = self.messages.len().saturating_sub(1);
                 Action::None
             }
@@ -126,8 +142,8 @@ impl LogPanel {
         let block = Block::default()
             .title(format!(" Logs ({}) ", self.messages.len()))
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -146,9 +162,9 @@ impl LogPanel {
             .iter()
             .map(|entry| {
                 let (prefix, style) = match entry.level {
-                    LogLevel::Info => ("[INFO] ", self.theme.normal),
-                    LogLevel::Error => ("[ERR]  ", self.theme.error),
-                    LogLevel::Debug => ("[DBG]  ", self.theme.dimmed),
+                    LogLevel::Info => ("[INFO] ", self.theme.normal()),
+                    LogLevel::Error => ("[ERR]  ", self.theme.error()),
+                    LogLevel::Debug => ("[DBG]  ", self.theme.dimmed()),
                 };
                 Line::from(vec![
                     Span::styled(prefix, style),
@@ -162,8 +178,20 @@ impl LogPanel {
         // Hints
         let hints = Line::from(Span::styled(
             "\u{2191}/\u{2193}:scroll  g/G:top/bottom  q:close",
-            self.theme.dimmed,
+            self.theme.dimmed(),
         ));
         frame.render_widget(Paragraph::new(hints), layout[1]);
     }
 }
+
+impl JumpTargetProvider for LogPanel {
+    fn jump_targets(&self) -> Vec<SwitchTarget> {
+        if self.visible {
+            return Vec::new();
+        }
+        vec![
+            SwitchTarget::new(TargetCategory::Dialog, "Log", JumpTarget::LogPanel)
+                .with_detail(format!("{} messages", self.messages.len())),
+        ]
+    }
+}
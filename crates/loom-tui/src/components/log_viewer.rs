@@ -0,0 +1,226 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+use tracing::Level;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::logging::{LogBuffer, LogLine};
+use crate::theme::Theme;
+
+/// Levels `l` steps through, from the least to the most verbose.
+const LEVELS: [Level; 5] = [
+    Level::ERROR,
+    Level::WARN,
+    Level::INFO,
+    Level::DEBUG,
+    Level::TRACE,
+];
+
+/// Popup tailing the application log: the most recent lines written to
+/// the log file, filtered by level, following new lines as they come
+/// unless scrolled back.
+pub struct LogViewer {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    buffer: LogBuffer,
+    path: PathBuf,
+    /// Least severe level shown.
+    level: Level,
+    lines: Vec<LogLine>,
+    /// Lines scrolled back from the newest; 0 follows the log.
+    from_bottom: usize,
+}
+
+impl LogViewer {
+    pub fn new(theme: Theme, buffer: LogBuffer, path: PathBuf) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Log", theme.clone()).with_size(90, 70),
+            theme,
+            buffer,
+            path,
+            level: Level::TRACE,
+            lines: Vec::new(),
+            from_bottom: 0,
+        }
+    }
+
+    /// Read from `buffer` instead, the one the log is written to.
+    pub fn set_buffer(&mut self, buffer: LogBuffer) {
+        self.buffer = buffer;
+        self.refresh();
+    }
+
+    /// Name `path` as the file the log goes to.
+    pub fn set_path(&mut self, path: PathBuf) {
+        self.path = path;
+    }
+
+    pub fn show(&mut self) {
+        self.from_bottom = 0;
+        self.refresh();
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// Pick up lines logged since the last look, keeping a scrolled-back
+    /// view where it was.
+    pub fn refresh(&mut self) {
+        let before = self.lines.len();
+        self.lines = self.buffer.lines(self.level);
+        if self.from_bottom > 0 {
+            self.from_bottom += self.lines.len().saturating_sub(before);
+            self.clamp_scroll();
+        }
+    }
+
+    fn clamp_scroll(&mut self) {
+        self.from_bottom = self.from_bottom.min(self.lines.len().saturating_sub(1));
+    }
+
+    fn cycle_level(&mut self) {
+        let next = LEVELS
+            .iter()
+            .position(|l| *l == self.level)
+            .map_or(0, |i| (i + 1) % LEVELS.len());
+        self.level = LEVELS[next];
+        self.from_bottom = 0;
+        self.refresh();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                return Action::ClosePopup;
+            }
+            KeyCode::Char('l') => self.cycle_level(),
+            KeyCode::Up | KeyCode::Char('k') => self.from_bottom += 1,
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.from_bottom = self.from_bottom.saturating_sub(1)
+            }
+            KeyCode::PageUp => self.from_bottom += 10,
+            KeyCode::PageDown => self.from_bottom = self.from_bottom.saturating_sub(10),
+            KeyCode::Home | KeyCode::Char('g') => self.from_bottom = self.lines.len(),
+            KeyCode::End | KeyCode::Char('G') => self.from_bottom = 0,
+            _ => {}
+        }
+        self.clamp_scroll();
+        Action::None
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        let header = Line::from(vec![
+            Span::styled(self.path.display().to_string(), self.theme.dimmed()),
+            Span::styled(
+                format!("  {} and above, {} lines", self.level, self.lines.len()),
+                self.theme.normal(),
+            ),
+        ]);
+        frame.render_widget(Paragraph::new(header), layout[0]);
+
+        let height = layout[1].height as usize;
+        let end = self.lines.len().saturating_sub(self.from_bottom);
+        let start = end.saturating_sub(height);
+        let lines: Vec<Line> = if self.lines.is_empty() {
+            vec![Line::from(Span::styled(
+                "Nothing logged at this level yet",
+                self.theme.dimmed(),
+            ))]
+        } else {
+            self.lines[start..end]
+                .iter()
+                .map(|line| {
+                    let style = match line.level {
+                        Level::ERROR => self.theme.error(),
+                        Level::WARN => self.theme.warning(),
+                        Level::INFO => self.theme.normal(),
+                        _ => self.theme.dimmed(),
+                    };
+                    Line::from(Span::styled(line.text.clone(), style))
+                })
+                .collect()
+        };
+        frame.render_widget(Paragraph::new(lines), layout[1]);
+
+        let follow = if self.from_bottom == 0 {
+            "following"
+        } else {
+            "G:follow"
+        };
+        let hints = Line::from(Span::styled(
+            format!(
+                "l:level  \u{2191}/\u{2193}:scroll  g:top  {}  Esc:close",
+                follow
+            ),
+            self.theme.dimmed(),
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_level_filter_and_follow() {
+        let buffer = LogBuffer::new(100);
+        buffer.push(Level::DEBUG, "search base='dc=example,dc=com'".to_string());
+        buffer.push(Level::WARN, "search failed".to_string());
+        let mut viewer = LogViewer::new(
+            Theme::load("dark"),
+            buffer.clone(),
+            PathBuf::from("loom.log"),
+        );
+        viewer.show();
+        assert_eq!(viewer.lines.len(), 2);
+
+        // TRACE wraps around to ERROR, then WARN
+        viewer.handle_key_event(key(KeyCode::Char('l')));
+        assert_eq!(viewer.level, Level::ERROR);
+        assert!(viewer.lines.is_empty());
+        viewer.handle_key_event(key(KeyCode::Char('l')));
+        assert_eq!(viewer.lines.len(), 1);
+        assert_eq!(viewer.lines[0].text, "search failed");
+
+        // Scrolled back, the view stays put as lines come in
+        buffer.push(Level::ERROR, "bind failed".to_string());
+        viewer.refresh();
+        assert_eq!(viewer.from_bottom, 0);
+        viewer.handle_key_event(key(KeyCode::Up));
+        buffer.push(Level::ERROR, "bind failed again".to_string());
+        viewer.refresh();
+        assert_eq!(viewer.from_bottom, 2);
+        viewer.handle_key_event(key(KeyCode::Char('G')));
+        assert_eq!(viewer.from_bottom, 0);
+    }
+}
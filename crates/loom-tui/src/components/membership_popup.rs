@@ -0,0 +1,388 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use loom_core::dn::rdn_display_name;
+use loom_core::membership::{Membership, DEFAULT_MAX_DEPTH};
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Deepest nesting `+` goes to.
+const DEPTH_CEILING: usize = 50;
+
+/// Popup listing the groups an entry belongs to as a tree, each group
+/// under the one its membership comes through.
+pub struct MembershipPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    member: String,
+    nested: bool,
+    max_depth: usize,
+    /// `None` while the lookup runs.
+    membership: Option<Membership>,
+    error: Option<String>,
+    selected: usize,
+}
+
+impl MembershipPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Groups", theme.clone()).with_size(70, 60),
+            theme,
+            member: String::new(),
+            nested: true,
+            max_depth: DEFAULT_MAX_DEPTH,
+            membership: None,
+            error: None,
+            selected: 0,
+        }
+    }
+
+    /// Open on `member` while its groups are looked up. Nesting and the
+    /// depth limit carry over from the last time.
+    pub fn show(&mut self, member: &str) {
+        self.popup.title = format!("Groups of {}", rdn_display_name(member));
+        self.member = member.to_string();
+        self.loading();
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    fn loading(&mut self) {
+        self.membership = None;
+        self.error = None;
+        self.selected = 0;
+    }
+
+    /// The lookup for what the popup shows.
+    pub fn request(&self) -> Action {
+        Action::ResolveGroups {
+            dn: self.member.clone(),
+            nested: self.nested,
+            max_depth: self.max_depth,
+        }
+    }
+
+    /// Show the groups looked up, unless they belong to another entry.
+    pub fn set_membership(&mut self, membership: Membership) {
+        if membership.member == self.member {
+            self.membership = Some(membership);
+        }
+    }
+
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+    }
+
+    pub fn membership(&self) -> Option<&Membership> {
+        self.membership.as_ref()
+    }
+
+    fn group_count(&self) -> usize {
+        self.membership.as_ref().map_or(0, |m| m.groups.len())
+    }
+
+    fn selected_dn(&self) -> Option<&str> {
+        self.membership
+            .as_ref()
+            .and_then(|m| m.groups.get(self.selected))
+            .map(|g| g.dn.as_str())
+    }
+
+    /// Look again with new settings.
+    fn reload(&mut self) -> Action {
+        self.loading();
+        self.request()
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < self.group_count() {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Enter => match self.selected_dn().map(str::to_string) {
+                Some(dn) => {
+                    self.hide();
+                    Action::FollowDn(dn)
+                }
+                None => Action::None,
+            },
+            KeyCode::Char('y') | KeyCode::Char('c') => match self.selected_dn() {
+                Some(dn) => Action::CopyToClipboard(dn.to_string()),
+                None => Action::None,
+            },
+            KeyCode::Char('n') => {
+                self.nested = !self.nested;
+                self.reload()
+            }
+            KeyCode::Char('+') if self.nested && self.max_depth < DEPTH_CEILING => {
+                self.max_depth += 1;
+                self.reload()
+            }
+            KeyCode::Char('-') if self.nested && self.max_depth > 1 => {
+                self.max_depth -= 1;
+                self.reload()
+            }
+            KeyCode::Char('e') if self.group_count() > 0 => Action::ExportGroups,
+            _ => Action::None,
+        }
+    }
+
+    fn summary(&self, membership: &Membership) -> String {
+        let mut summary = format!(
+            "{} groups, {} direct",
+            membership.groups.len(),
+            membership.direct().count()
+        );
+        if !self.nested {
+            summary.push_str("; nested groups not followed");
+        } else if membership.server_expanded {
+            summary.push_str("; nesting expanded by the server");
+        } else {
+            summary.push_str(&format!("; nested up to {} deep", self.max_depth));
+        }
+        summary
+    }
+
+    fn lines(&self) -> Vec<Line<'_>> {
+        if let Some(error) = &self.error {
+            return vec![Line::from(Span::styled(error.clone(), self.theme.error()))];
+        }
+        let Some(membership) = &self.membership else {
+            return vec![Line::from(Span::styled(
+                "Looking up groups...",
+                self.theme.dimmed(),
+            ))];
+        };
+
+        let mut lines = vec![
+            Line::from(Span::styled(self.summary(membership), self.theme.header())),
+            Line::default(),
+        ];
+        if membership.groups.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "Not a member of any group",
+                self.theme.dimmed(),
+            )));
+        }
+        for (i, group) in membership.groups.iter().enumerate() {
+            let (name, dn) = if i == self.selected {
+                let style = self.theme.selected().add_modifier(Modifier::BOLD);
+                (style, style)
+            } else {
+                (self.theme.normal(), self.theme.dimmed())
+            };
+            lines.push(Line::from(vec![
+                Span::raw("  ".repeat(group.depth())),
+                Span::styled(rdn_display_name(&group.dn), name),
+                Span::styled(format!("  {}", group.dn), dn),
+            ]));
+        }
+
+        if !membership.cycles.is_empty() || membership.truncated {
+            lines.push(Line::default());
+        }
+        for cycle in &membership.cycles {
+            let names: Vec<_> = cycle.iter().map(|dn| rdn_display_name(dn)).collect();
+            lines.push(Line::from(Span::styled(
+                format!("Cycle: {}", names.join(" \u{2192} ")),
+                self.theme.warning(),
+            )));
+        }
+        if membership.truncated {
+            lines.push(Line::from(Span::styled(
+                format!(
+                    "Groups nested deeper than {} are not shown; + looks deeper",
+                    self.max_depth
+                ),
+                self.theme.warning(),
+            )));
+        }
+        lines
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        // Keep the selected group in view below the summary
+        let scroll =
+            (self.selected + 2).saturating_sub(layout[0].height.saturating_sub(1) as usize);
+        frame.render_widget(
+            Paragraph::new(self.lines()).scroll((scroll as u16, 0)),
+            layout[0],
+        );
+
+        let mode = if self.nested { "on" } else { "off" };
+        let depth = if self.nested {
+            format!("+/-:depth ({})  ", self.max_depth)
+        } else {
+            String::new()
+        };
+        let hints = Line::from(Span::styled(
+            format!(
+                "Enter:go to  n:nested ({})  {}e:export  y:copy DN  Esc:close",
+                mode, depth
+            ),
+            self.theme.dimmed(),
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use loom_core::membership::GroupPath;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    const ADA: &str = "cn=Ada,ou=People,dc=example,dc=com";
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn group(path: &[&str]) -> GroupPath {
+        let path: Vec<String> = path
+            .iter()
+            .map(|cn| format!("cn={},ou=Groups,dc=example,dc=com", cn))
+            .collect();
+        GroupPath {
+            dn: path.last().unwrap().clone(),
+            path,
+        }
+    }
+
+    fn render(popup: &MembershipPopup, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| popup.render(frame, frame.area()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| {
+                let line: String = (0..width).map(|x| buffer[(x, y)].symbol()).collect();
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_tree_with_cycle_and_truncation() {
+        let mut popup = MembershipPopup::new(Theme::load("dark"));
+        popup.show(ADA);
+        assert!(render(&popup, 100, 30)
+            .iter()
+            .any(|l| l.contains("Looking up groups...")));
+
+        popup.set_membership(Membership {
+            member: ADA.to_string(),
+            groups: vec![
+                group(&["Engineering"]),
+                group(&["Engineering", "Staff"]),
+                group(&["Readers"]),
+            ],
+            cycles: vec![group(&["Engineering", "Staff", "Engineering"]).path],
+            truncated: true,
+            server_expanded: false,
+        });
+        let screen = render(&popup, 100, 30).join("\n");
+        assert!(screen.contains("3 groups, 2 direct; nested up to 10 deep"));
+        assert!(screen.contains("  Engineering  cn=Engineering,ou=Groups,dc=example,dc=com"));
+        assert!(screen.contains("    Staff  cn=Staff,ou=Groups,dc=example,dc=com"));
+        assert!(screen.contains("Cycle: Engineering \u{2192} Staff \u{2192} Engineering"));
+        assert!(screen.contains("Groups nested deeper than 10 are not shown"));
+
+        // A lookup for another entry that finishes late is dropped
+        popup.set_membership(Membership {
+            member: "cn=Grace,ou=People,dc=example,dc=com".to_string(),
+            ..Default::default()
+        });
+        assert_eq!(popup.group_count(), 3);
+    }
+
+    #[test]
+    fn test_keys() {
+        let mut popup = MembershipPopup::new(Theme::load("dark"));
+        popup.show(ADA);
+        // Nothing to export or follow while loading
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Char('e'))),
+            Action::None
+        ));
+        popup.set_membership(Membership {
+            member: ADA.to_string(),
+            groups: vec![group(&["Engineering"]), group(&["Engineering", "Staff"])],
+            ..Default::default()
+        });
+        popup.handle_key_event(key(KeyCode::Down));
+        popup.handle_key_event(key(KeyCode::Down));
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Char('y'))),
+            Action::CopyToClipboard(dn) if dn == "cn=Staff,ou=Groups,dc=example,dc=com"
+        ));
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Char('e'))),
+            Action::ExportGroups
+        ));
+
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Char('-'))),
+            Action::ResolveGroups {
+                nested: true,
+                max_depth: 9,
+                ..
+            }
+        ));
+        assert!(popup.membership().is_none());
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Char('n'))),
+            Action::ResolveGroups { ref dn, nested: false, .. } if dn == ADA
+        ));
+        // The depth only matters for nested groups
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Char('+'))),
+            Action::None
+        ));
+
+        popup.set_membership(Membership {
+            member: ADA.to_string(),
+            groups: vec![group(&["Engineering"])],
+            ..Default::default()
+        });
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Enter)),
+            Action::FollowDn(dn) if dn == "cn=Engineering,ou=Groups,dc=example,dc=com"
+        ));
+        assert!(!popup.visible);
+    }
+}
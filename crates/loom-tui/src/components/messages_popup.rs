@@ -0,0 +1,195 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::components::status_bar::{LoggedMessage, Severity};
+use crate::theme::Theme;
+
+/// Popup listing the messages posted to the status bar, newest last, so
+/// one that was replaced before it could be read is still there. When
+/// writes are audited, the first line says where to.
+pub struct MessagesPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    messages: Vec<LoggedMessage>,
+    /// The audit log file and its format, when one is kept.
+    audit: Option<String>,
+    /// Lines scrolled up from the newest message.
+    scroll_back: usize,
+}
+
+impl MessagesPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Messages", theme.clone()).with_size(80, 60),
+            theme,
+            messages: Vec::new(),
+            audit: None,
+            scroll_back: 0,
+        }
+    }
+
+    pub fn show(&mut self, messages: Vec<LoggedMessage>, audit: Option<String>) {
+        self.messages = messages;
+        self.audit = audit;
+        self.scroll_back = 0;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        let last = self.messages.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll_back = (self.scroll_back + 1).min(last);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll_back = self.scroll_back.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::PageUp => {
+                self.scroll_back = (self.scroll_back + 10).min(last);
+                Action::None
+            }
+            KeyCode::PageDown => {
+                self.scroll_back = self.scroll_back.saturating_sub(10);
+                Action::None
+            }
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.scroll_back = last;
+                Action::None
+            }
+            KeyCode::End | KeyCode::Char('G') => {
+                self.scroll_back = 0;
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let audit_height = u16::from(self.audit.is_some());
+        let layout = Layout::vertical([
+            Constraint::Length(audit_height),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        if let Some(audit) = &self.audit {
+            frame.render_widget(
+                Paragraph::new(Line::from(vec![
+                    Span::styled("Audit log: ", self.theme.header()),
+                    Span::styled(audit.as_str(), self.theme.normal()),
+                ])),
+                layout[0],
+            );
+        }
+
+        let lines: Vec<Line> = if self.messages.is_empty() {
+            vec![Line::from(Span::styled(
+                "No messages yet",
+                self.theme.dimmed(),
+            ))]
+        } else {
+            let end = self.messages.len() - self.scroll_back;
+            let start = end.saturating_sub(layout[1].height as usize);
+            self.messages[start..end]
+                .iter()
+                .map(|message| {
+                    let (label, style) = match message.severity {
+                        Severity::Info => ("info ", self.theme.normal()),
+                        Severity::Warning => ("warn ", self.theme.warning()),
+                        Severity::Error => ("error", self.theme.error()),
+                    };
+                    Line::from(vec![
+                        Span::styled(
+                            format!("{} ", message.at.format("%H:%M:%S")),
+                            self.theme.dimmed(),
+                        ),
+                        Span::styled(format!("{} ", label), style),
+                        Span::styled(message.text.clone(), style),
+                    ])
+                })
+                .collect()
+        };
+        frame.render_widget(Paragraph::new(lines), layout[1]);
+
+        let hints = Line::from(Span::styled(
+            format!(
+                "{} messages  \u{2191}/\u{2193}:scroll  g/G:oldest/newest  Esc:close",
+                self.messages.len()
+            ),
+            self.theme.dimmed(),
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Local;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn render(popup: &MessagesPopup, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| popup.render(frame, frame.area()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (0..height)
+            .map(|y| (0..width).map(|x| buffer[(x, y)].symbol()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_audit_log_shown_when_active() {
+        let mut popup = MessagesPopup::new(Theme::load("dark"));
+        let messages = vec![LoggedMessage {
+            at: Local::now(),
+            severity: Severity::Info,
+            text: "Modified cn=Ada".to_string(),
+        }];
+        popup.show(messages.clone(), None);
+        let screen = render(&popup, 100, 30);
+        assert!(!screen.iter().any(|line| line.contains("Audit log")));
+
+        popup.show(
+            messages,
+            Some("/var/log/loom/audit.log (jsonl)".to_string()),
+        );
+        let screen = render(&popup, 100, 30);
+        let audit = screen
+            .iter()
+            .position(|line| line.contains("Audit log: /var/log/loom/audit.log (jsonl)"))
+            .unwrap();
+        let message = screen
+            .iter()
+            .position(|line| line.contains("Modified cn=Ada"))
+            .unwrap();
+        assert!(audit < message);
+    }
+}
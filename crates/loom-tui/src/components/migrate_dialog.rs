@@ -0,0 +1,360 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use loom_core::entry::LdapEntry;
+use loom_core::migrate::{plan_migration, AttributeMigration, CollisionPolicy, MigrationPlan};
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Which field is currently being edited.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MigrateField {
+    Source,
+    Target,
+}
+
+/// Guided move of values from one attribute to another over the loaded
+/// search results: pick the attributes and policy, review, then apply.
+pub struct MigrateAttributeDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    entries: Vec<LdapEntry>,
+    active_field: MigrateField,
+    pub source: String,
+    pub target: String,
+    pub collision: CollisionPolicy,
+    pub delete_source: bool,
+    /// Set once the form is submitted; the preview is showing.
+    plan: Option<MigrationPlan>,
+    scroll: u16,
+}
+
+impl MigrateAttributeDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Migrate Attribute", theme.clone()).with_size(70, 60),
+            theme,
+            entries: Vec::new(),
+            active_field: MigrateField::Source,
+            source: String::new(),
+            target: String::new(),
+            collision: CollisionPolicy::Append,
+            delete_source: true,
+            plan: None,
+            scroll: 0,
+        }
+    }
+
+    /// Open the form for the given entries.
+    pub fn show(&mut self, entries: Vec<LdapEntry>) {
+        self.entries = entries;
+        self.source.clear();
+        self.target.clear();
+        self.collision = CollisionPolicy::Append;
+        self.delete_source = true;
+        self.active_field = MigrateField::Source;
+        self.plan = None;
+        self.scroll = 0;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+        self.entries.clear();
+        self.plan = None;
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if self.plan.is_some() {
+            self.handle_preview_key(key)
+        } else {
+            self.handle_form_key(key)
+        }
+    }
+
+    fn handle_form_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.active_field = match self.active_field {
+                    MigrateField::Source => MigrateField::Target,
+                    MigrateField::Target => MigrateField::Source,
+                };
+                Action::None
+            }
+            KeyCode::F(2) => {
+                self.collision = self.collision.next();
+                Action::None
+            }
+            KeyCode::F(3) => {
+                self.delete_source = !self.delete_source;
+                Action::None
+            }
+            KeyCode::Enter => {
+                let migration = AttributeMigration {
+                    source: self.source.clone(),
+                    target: self.target.clone(),
+                    collision: self.collision,
+                    delete_source: self.delete_source,
+                };
+                match plan_migration(&self.entries, &migration) {
+                    Ok(plan) => {
+                        self.plan = Some(plan);
+                        self.scroll = 0;
+                        Action::None
+                    }
+                    Err(e) => Action::ErrorMessage(e),
+                }
+            }
+            KeyCode::Backspace => {
+                self.active_buffer_mut().pop();
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                self.active_buffer_mut().push(c);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn handle_preview_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            // Back to the form to adjust the choices
+            KeyCode::Esc => {
+                self.plan = None;
+                self.scroll = 0;
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll = self.scroll.saturating_add(1);
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll = self.scroll.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::PageDown => {
+                self.scroll = self.scroll.saturating_add(10);
+                Action::None
+            }
+            KeyCode::PageUp => {
+                self.scroll = self.scroll.saturating_sub(10);
+                Action::None
+            }
+            KeyCode::Enter => {
+                let Some(plan) = self.plan.take() else {
+                    return Action::None;
+                };
+                if plan.steps.is_empty() {
+                    self.plan = Some(plan);
+                    return Action::ErrorMessage("No entries would change".to_string());
+                }
+                let changes = plan
+                    .steps
+                    .into_iter()
+                    .map(|step| (step.dn, step.mods))
+                    .collect();
+                let skipped = plan.skipped.len();
+                self.hide();
+                Action::MigrateAttributeExecute { changes, skipped }
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn active_buffer_mut(&mut self) -> &mut String {
+        match self.active_field {
+            MigrateField::Source => &mut self.source,
+            MigrateField::Target => &mut self.target,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let (lines, hint) = match &self.plan {
+            None => (
+                self.form_lines(),
+                "Tab:next field  F2:collision  F3:delete source  Enter:preview  Esc:cancel",
+            ),
+            Some(plan) => (
+                self.preview_lines(plan),
+                "\u{2191}/\u{2193}:scroll  Enter:apply  Esc:back",
+            ),
+        };
+        frame.render_widget(Paragraph::new(lines).scroll((self.scroll, 0)), layout[0]);
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(hint, self.theme.dimmed()))),
+            layout[1],
+        );
+    }
+
+    fn form_lines(&self) -> Vec<Line<'static>> {
+        let mut lines = vec![
+            Line::from(Span::styled(
+                format!("{} entries from the search results", self.entries.len()),
+                self.theme.dimmed(),
+            )),
+            Line::from(""),
+        ];
+        for (label, value, field) in [
+            ("Source attribute", &self.source, MigrateField::Source),
+            ("Target attribute", &self.target, MigrateField::Target),
+        ] {
+            let is_active = self.active_field == field;
+            let (label_style, value_style) = if is_active {
+                (self.theme.header(), self.theme.normal())
+            } else {
+                (self.theme.dimmed(), self.theme.dimmed())
+            };
+            lines.push(Line::from(Span::styled(format!("{}:", label), label_style)));
+            lines.push(Line::from(vec![
+                Span::styled(value.clone(), value_style),
+                if is_active {
+                    Span::styled("_", self.theme.command_prompt())
+                } else {
+                    Span::raw("")
+                },
+            ]));
+            lines.push(Line::from(""));
+        }
+        lines.push(Line::from(vec![
+            Span::styled("When the target has values: ", self.theme.header()),
+            Span::styled(self.collision.label(), self.theme.success()),
+            Span::styled("  (F2 to cycle)", self.theme.dimmed()),
+        ]));
+        lines.push(Line::from(vec![
+            Span::styled("Delete source afterwards: ", self.theme.header()),
+            Span::styled(
+                if self.delete_source { "Yes" } else { "No" },
+                self.theme.success(),
+            ),
+            Span::styled("  (F3 to toggle)", self.theme.dimmed()),
+        ]));
+        lines
+    }
+
+    fn preview_lines(&self, plan: &MigrationPlan) -> Vec<Line<'static>> {
+        let mut lines = vec![
+            Line::from(Span::styled(
+                format!(
+                    "{} entries will change, {} skipped",
+                    plan.steps.len(),
+                    plan.skipped.len()
+                ),
+                self.theme.header(),
+            )),
+            Line::from(""),
+        ];
+        for step in &plan.steps {
+            lines.push(Line::from(Span::styled(
+                step.dn.clone(),
+                self.theme.normal(),
+            )));
+            for change in &step.changes {
+                lines.push(Line::from(Span::styled(
+                    format!("  {}", change),
+                    self.theme.success(),
+                )));
+            }
+        }
+        if !plan.skipped.is_empty() {
+            lines.push(Line::from(""));
+            lines.push(Line::from(Span::styled("Skipped", self.theme.header())));
+            for (dn, reason) in &plan.skipped {
+                lines.push(Line::from(Span::styled(
+                    format!("  {}: {}", dn, reason),
+                    self.theme.dimmed(),
+                )));
+            }
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    fn type_text(dialog: &mut MigrateAttributeDialog, text: &str) {
+        for c in text.chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_form_preview_apply() {
+        let entries = vec![
+            LdapEntry::new(
+                "uid=a,dc=example,dc=com".to_string(),
+                BTreeMap::from([("description".to_string(), vec!["Engineer".to_string()])]),
+            ),
+            LdapEntry::new("uid=b,dc=example,dc=com".to_string(), BTreeMap::new()),
+        ];
+        let mut dialog = MigrateAttributeDialog::new(Theme::load("dark"));
+        dialog.show(entries);
+        type_text(&mut dialog, "description");
+        dialog.handle_key_event(key(KeyCode::Tab));
+        type_text(&mut dialog, "title");
+
+        // Enter previews; Esc goes back to the form, not out of the dialog
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::None
+        ));
+        assert_eq!(dialog.plan.as_ref().unwrap().steps.len(), 1);
+        dialog.handle_key_event(key(KeyCode::Esc));
+        assert!(dialog.visible && dialog.plan.is_none());
+
+        dialog.handle_key_event(key(KeyCode::F(3)));
+        dialog.handle_key_event(key(KeyCode::Enter));
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::MigrateAttributeExecute { changes, skipped } => {
+                assert_eq!(skipped, 1);
+                assert_eq!(changes.len(), 1);
+                assert_eq!(changes[0].0, "uid=a,dc=example,dc=com");
+                // Source kept: only the add of the title value
+                assert_eq!(changes[0].1.len(), 1);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(!dialog.visible);
+    }
+
+    #[test]
+    fn test_same_attribute_is_rejected() {
+        let mut dialog = MigrateAttributeDialog::new(Theme::load("dark"));
+        dialog.show(Vec::new());
+        type_text(&mut dialog, "cn");
+        dialog.handle_key_event(key(KeyCode::Tab));
+        type_text(&mut dialog, "CN");
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+        assert!(dialog.plan.is_none());
+    }
+}
@@ -1,28 +1,59 @@
 pub mod about_popup;
+pub mod activity_popup;
+pub mod apply_ldif_dialog;
+pub mod apply_results_view;
 pub mod attribute_editor;
 pub mod attribute_picker;
+pub mod batch_modify_dialog;
+pub mod batch_summary_popup;
+pub mod bookmarks_popup;
 pub mod bulk_update_dialog;
 pub mod cert_trust_dialog;
+pub mod columns_dialog;
 pub mod command_panel;
+pub mod compare_view;
 pub mod confirm_dialog;
 pub mod connect_dialog;
 pub mod connection_form;
 pub mod connections_tree;
 pub mod context_menu;
+pub mod copy_subtree_dialog;
 pub mod create_entry_dialog;
 pub mod credential_prompt;
+pub mod delete_dialog;
 pub mod detail_panel;
+pub mod doctor_popup;
+pub mod error_details_popup;
 pub mod export_dialog;
+pub mod export_summary_popup;
 pub mod help_popup;
 pub mod layout_bar;
 pub mod log_panel;
+pub mod log_viewer;
+pub mod membership_popup;
+pub mod messages_popup;
+pub mod migrate_dialog;
 pub mod new_connection_dialog;
+pub mod password_dialog;
+pub mod path_input;
+pub mod pending_changes_popup;
 pub mod popup;
 pub mod profile_export_dialog;
 pub mod profile_import_dialog;
+pub mod protected_write_dialog;
+pub mod quick_switcher;
+pub mod rename_dialog;
 pub mod schema_viewer;
 pub mod search_dialog;
+pub mod search_form_dialog;
+pub mod server_info_popup;
+pub mod setup_wizard;
+pub mod snapshot_dialog;
 pub mod status_bar;
+pub mod subtree_compare_dialog;
+pub mod subtree_diff_view;
 pub mod tab_bar;
 pub mod tree_panel;
+pub mod value_viewer;
 pub mod vault_password_dialog;
+pub mod write_override_dialog;
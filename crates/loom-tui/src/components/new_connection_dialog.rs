@@ -8,7 +8,7 @@ use loom_core::connection::TlsMode;
 use loom_core::credentials::CredentialMethod;
 
 use crate::action::Action;
-use crate::components::popup::Popup;
+use crate::components::popup::{DiscardGuard, Dismiss, Popup};
 use crate::config::ConnectionProfile;
 use crate::theme::Theme;
 
@@ -60,6 +60,7 @@ pub struct NewConnectionDialog {
     base_dn: String,
     password: String,
     tls_mode: TlsMode,
+    guard: DiscardGuard,
 }
 
 impl NewConnectionDialog {
@@ -106,9 +107,27 @@ impl NewConnectionDialog {
             base_dn: String::new(),
             password: String::new(),
             tls_mode: TlsMode::Auto,
+            guard: DiscardGuard::new(true),
         }
     }
 
+    /// Ask before Esc throws away what was typed.
+    pub fn with_confirm_discard(mut self, enabled: bool) -> Self {
+        self.guard = DiscardGuard::new(enabled);
+        self
+    }
+
+    /// Anything was entered since the dialog opened.
+    fn is_dirty(&self) -> bool {
+        !self.name.is_empty()
+            || !self.host.is_empty()
+            || self.port != "389"
+            || !self.bind_dn.is_empty()
+            || !self.base_dn.is_empty()
+            || !self.password.is_empty()
+            || self.tls_mode != TlsMode::Auto
+    }
+
     pub fn show(&mut self) {
         self.name.clear();
         self.host.clear();
@@ -118,6 +137,7 @@ impl NewConnectionDialog {
         self.password.clear();
         self.tls_mode = TlsMode::Auto;
         self.active_field = Field::Host;
+        self.guard.reset();
         self.visible = true;
         self.popup.show();
     }
@@ -128,11 +148,15 @@ impl NewConnectionDialog {
     }
 
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
-        match key.code {
-            KeyCode::Esc => {
+        match self.guard.check(&key, true, true, self.is_dirty()) {
+            Dismiss::Close => {
                 self.hide();
-                Action::ClosePopup
+                return Action::ClosePopup;
             }
+            Dismiss::Stay | Dismiss::Back => return Action::None,
+            Dismiss::Pass => {}
+        }
+        match key.code {
             KeyCode::Tab => {
                 if self.active_field == Field::Host {
                     self.try_parse_host_url();
@@ -214,6 +238,12 @@ impl NewConnectionDialog {
             read_only: false,
             offline: false,
             labels: vec![],
+            hooks: Default::default(),
+            protected_dns: Vec::new(),
+            auth: Default::default(),
+            tls_options: Default::default(),
+            protection: Default::default(),
+            result_columns: Vec::new(),
         };
 
         let password = self.password.clone();
@@ -243,8 +273,8 @@ impl NewConnectionDialog {
         let block = Block::default()
             .title(" New Connection ")
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -265,9 +295,9 @@ impl NewConnectionDialog {
         // TLS Mode
         let tls_line = vec![
             Line::from(vec![
-                Span::styled("TLS Mode: ", self.theme.header),
-                Span::styled(self.tls_mode.label(), self.theme.success),
-                Span::styled("  (F2 to cycle)", self.theme.dimmed),
+                Span::styled("TLS Mode: ", self.theme.header()),
+                Span::styled(self.tls_mode.label(), self.theme.success()),
+                Span::styled("  (F2 to cycle)", self.theme.dimmed()),
             ]),
             Line::from(Span::raw("")),
         ];
@@ -305,9 +335,10 @@ impl NewConnectionDialog {
         // Hints
         let hints = Paragraph::new(Line::from(Span::styled(
             "Tab:next  Shift+Tab:prev  F2:TLS  Enter:connect  Esc:cancel",
-            self.theme.dimmed,
+            self.theme.dimmed(),
         )));
         frame.render_widget(hints, layout[7]);
+        self.guard.render(frame, inner, &self.theme);
     }
 
     fn render_field(
@@ -321,14 +352,14 @@ impl NewConnectionDialog {
     ) {
         let is_active = self.active_field == field;
         let label_style = if is_active {
-            self.theme.header
+            self.theme.header()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
         let value_style = if is_active {
-            self.theme.normal
+            self.theme.normal()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
 
         let display_value = if masked && !value.is_empty() {
@@ -342,7 +373,7 @@ impl NewConnectionDialog {
             Line::from(vec![
                 Span::styled(display_value, value_style),
                 if is_active {
-                    Span::styled("_", self.theme.command_prompt)
+                    Span::styled("_", self.theme.command_prompt())
                 } else {
                     Span::raw("")
                 },
@@ -0,0 +1,296 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use loom_core::password::PasswordMethod;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Password,
+    Confirm,
+    MustChange,
+}
+
+/// Sets an entry's password: typed twice, masked, with the option to make
+/// the user change it at their next login. What is typed only ever leaves
+/// in the `SetPassword` action.
+pub struct PasswordDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    dn: String,
+    method: PasswordMethod,
+    active_field: Field,
+    password: String,
+    confirm: String,
+    must_change: bool,
+}
+
+impl PasswordDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Set Password", theme.clone()).with_size(60, 40),
+            theme,
+            dn: String::new(),
+            method: PasswordMethod::Extended,
+            active_field: Field::Password,
+            password: String::new(),
+            confirm: String::new(),
+            must_change: false,
+        }
+    }
+
+    /// Ask for a new password for `dn`, to be sent by `method`.
+    pub fn show(&mut self, dn: String, method: PasswordMethod) {
+        self.dn = dn;
+        self.method = method;
+        self.active_field = Field::Password;
+        self.password.clear();
+        self.confirm.clear();
+        self.must_change = false;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+        self.password.clear();
+        self.confirm.clear();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if !self.visible {
+            return Action::None;
+        }
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Tab | KeyCode::Down => {
+                self.active_field = match self.active_field {
+                    Field::Password => Field::Confirm,
+                    Field::Confirm => Field::MustChange,
+                    Field::MustChange => Field::Password,
+                };
+                Action::None
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                self.active_field = match self.active_field {
+                    Field::Password => Field::MustChange,
+                    Field::Confirm => Field::Password,
+                    Field::MustChange => Field::Confirm,
+                };
+                Action::None
+            }
+            KeyCode::Enter => self.submit(),
+            KeyCode::Char(' ') if self.active_field == Field::MustChange => {
+                self.must_change = !self.must_change;
+                Action::None
+            }
+            KeyCode::Backspace => {
+                if let Some(buffer) = self.active_buffer_mut() {
+                    buffer.pop();
+                }
+                Action::None
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                if let Some(buffer) = self.active_buffer_mut() {
+                    buffer.push(c);
+                }
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn submit(&mut self) -> Action {
+        if self.password.is_empty() {
+            return Action::ErrorMessage("Password cannot be empty".to_string());
+        }
+        if self.password != self.confirm {
+            return Action::ErrorMessage("Passwords do not match".to_string());
+        }
+        let action = Action::SetPassword {
+            dn: std::mem::take(&mut self.dn),
+            password: std::mem::take(&mut self.password),
+            must_change: self.must_change,
+        };
+        self.hide();
+        action
+    }
+
+    fn active_buffer_mut(&mut self) -> Option<&mut String> {
+        match self.active_field {
+            Field::Password => Some(&mut self.password),
+            Field::Confirm => Some(&mut self.confirm),
+            Field::MustChange => None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([
+            Constraint::Length(2), // Entry
+            Constraint::Length(2), // Password
+            Constraint::Length(2), // Confirm
+            Constraint::Length(2), // Must change
+            Constraint::Min(1),    // Method note
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled("Entry: ", self.theme.dimmed()),
+                Span::styled(self.dn.as_str(), self.theme.normal()),
+            ])),
+            layout[0],
+        );
+        self.render_field(
+            frame,
+            layout[1],
+            "New password",
+            &self.password,
+            Field::Password,
+        );
+        self.render_field(frame, layout[2], "Confirm", &self.confirm, Field::Confirm);
+
+        let style = if self.active_field == Field::MustChange {
+            self.theme.header()
+        } else {
+            self.theme.dimmed()
+        };
+        let mark = if self.must_change { "[x]" } else { "[ ]" };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                format!("{} Must change at next login", mark),
+                style,
+            ))),
+            layout[3],
+        );
+
+        let note = match self.method.warning() {
+            Some(_) => Span::styled(
+                "No password modify operation: userPassword is replaced as typed, \
+                 and may be stored unhashed",
+                self.theme.warning(),
+            ),
+            None => Span::styled(
+                "Sent with the password modify operation; the server hashes it",
+                self.theme.dimmed(),
+            ),
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(note)).wrap(Wrap { trim: true }),
+            layout[4],
+        );
+
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                "Tab:next field  Space:toggle  Enter:set  Esc:cancel",
+                self.theme.dimmed(),
+            ))),
+            layout[5],
+        );
+    }
+
+    fn render_field(&self, frame: &mut Frame, area: Rect, label: &str, value: &str, field: Field) {
+        let is_active = self.active_field == field;
+        let (label_style, value_style) = if is_active {
+            (self.theme.header(), self.theme.normal())
+        } else {
+            (self.theme.dimmed(), self.theme.dimmed())
+        };
+        let lines = vec![
+            Line::from(Span::styled(format!("{}:", label), label_style)),
+            Line::from(vec![
+                Span::styled("*".repeat(value.chars().count()), value_style),
+                if is_active {
+                    Span::styled("_", self.theme.command_prompt())
+                } else {
+                    Span::raw("")
+                },
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn type_text(dialog: &mut PasswordDialog, text: &str) {
+        for c in text.chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_set_password_with_must_change() {
+        let mut dialog = PasswordDialog::new(Theme::load("dark"));
+        let dn = "uid=ada,ou=people,dc=example,dc=com";
+        dialog.show(dn.to_string(), PasswordMethod::Extended);
+
+        type_text(&mut dialog, "s3cret pw");
+        dialog.handle_key_event(key(KeyCode::Tab));
+        type_text(&mut dialog, "s3cret");
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(msg) if msg == "Passwords do not match"
+        ));
+        type_text(&mut dialog, " pw");
+        dialog.handle_key_event(key(KeyCode::Tab));
+        // Space ticks the box rather than typing
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+        assert_eq!(dialog.confirm, "s3cret pw");
+
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::SetPassword {
+                dn: to,
+                password,
+                must_change,
+            } => {
+                assert_eq!(to, dn);
+                assert_eq!(password, "s3cret pw");
+                assert!(must_change);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(!dialog.visible);
+        assert!(dialog.password.is_empty() && dialog.confirm.is_empty());
+    }
+
+    #[test]
+    fn test_empty_password_refused_and_cleared_on_cancel() {
+        let mut dialog = PasswordDialog::new(Theme::load("dark"));
+        dialog.show("cn=x".to_string(), PasswordMethod::Replace);
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(msg) if msg == "Password cannot be empty"
+        ));
+        type_text(&mut dialog, "typed");
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Esc)),
+            Action::ClosePopup
+        ));
+        assert!(!dialog.visible);
+        assert!(dialog.password.is_empty());
+    }
+}
@@ -0,0 +1,244 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+use crate::paths::expand_path;
+
+/// Candidates offered by the last Tab, cycled through by the next ones.
+#[derive(Debug, Clone)]
+struct Cycle {
+    /// Directory part of the path, up to and including the last `/`.
+    dir: String,
+    candidates: Vec<String>,
+    index: usize,
+}
+
+/// Text field for a file path that Tab-completes the last path segment
+/// against the filesystem, like a shell. Directories get a trailing `/`,
+/// and repeated Tabs cycle through the matches.
+#[derive(Debug, Clone, Default)]
+pub struct PathInput {
+    value: String,
+    cycle: Option<Cycle>,
+}
+
+impl PathInput {
+    pub fn new(value: impl Into<String>) -> Self {
+        Self {
+            value: value.into(),
+            cycle: None,
+        }
+    }
+
+    pub fn value(&self) -> &str {
+        &self.value
+    }
+
+    pub fn set(&mut self, value: impl Into<String>) {
+        self.value = value.into();
+        self.cycle = None;
+    }
+
+    /// The text itself, for edits the field does not know about. A
+    /// running completion cycle is dropped once the text no longer
+    /// matches it.
+    pub fn value_mut(&mut self) -> &mut String {
+        &mut self.value
+    }
+
+    /// Handle typing, Backspace and Tab. Returns false for keys the field
+    /// does not use, including a Tab with nothing to complete, so the
+    /// dialog can move to its next field instead.
+    pub fn handle_key(&mut self, key: KeyEvent) -> bool {
+        match key.code {
+            KeyCode::Tab => self.complete(),
+            KeyCode::Backspace => {
+                self.value.pop();
+                true
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.value.push(c);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Complete the last path segment. The first Tab fills in what all
+    /// matches share; once that is exhausted, each Tab moves to the next
+    /// match. Returns whether the text changed.
+    pub fn complete(&mut self) -> bool {
+        if let Some(cycle) = self.cycle.as_mut() {
+            let current = format!("{}{}", cycle.dir, cycle.candidates[cycle.index]);
+            if current == self.value && cycle.candidates.len() > 1 {
+                cycle.index = (cycle.index + 1) % cycle.candidates.len();
+                self.value = format!("{}{}", cycle.dir, cycle.candidates[cycle.index]);
+                return true;
+            }
+        }
+        self.cycle = None;
+
+        if self.value == "~" {
+            self.value.push('/');
+            return true;
+        }
+        let split = self.value.rfind('/').map(|i| i + 1).unwrap_or(0);
+        let (dir, prefix) = self.value.split_at(split);
+        let (dir, prefix) = (dir.to_string(), prefix.to_string());
+        let candidates = candidates(&dir, &prefix);
+        if candidates.is_empty() {
+            return false;
+        }
+
+        // Extend to what the matches share; a later Tab starts cycling
+        let common = common_prefix(&candidates);
+        if common.len() > prefix.len() {
+            self.value = format!("{}{}", dir, common);
+            return true;
+        }
+        if candidates.len() == 1 {
+            return false;
+        }
+        // Start after the match already typed out in full, if any
+        let index = candidates
+            .iter()
+            .position(|c| *c == prefix)
+            .map_or(0, |i| (i + 1) % candidates.len());
+        self.value = format!("{}{}", dir, candidates[index]);
+        self.cycle = Some(Cycle {
+            dir,
+            candidates,
+            index,
+        });
+        true
+    }
+}
+
+/// Names in `dir` starting with `prefix`, sorted, with `/` after
+/// directories. Hidden entries are only offered for a prefix starting
+/// with `.`.
+fn candidates(dir: &str, prefix: &str) -> Vec<String> {
+    let read_dir = if dir.is_empty() {
+        PathBuf::from(".")
+    } else {
+        expand_path(dir)
+    };
+    let Ok(entries) = std::fs::read_dir(&read_dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let mut name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            // Follows symlinks, so a link to a directory completes as one
+            if entry.path().is_dir() {
+                name.push('/');
+            }
+            Some(name)
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+/// Longest prefix shared by all `names`, on a char boundary.
+fn common_prefix(names: &[String]) -> &str {
+    let first = &names[0];
+    let mut end = first.len();
+    for name in &names[1..] {
+        let shared = first
+            .char_indices()
+            .zip(name.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((i, a), _)| i + a.len_utf8());
+        end = end.min(shared);
+    }
+    &first[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    /// A directory holding `people.ldif`, `people.csv`, `groups/` and a
+    /// hidden `.profiles.toml`, and a field pointing into it.
+    fn fixture() -> (tempfile::TempDir, String) {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("people.ldif"), "").unwrap();
+        std::fs::write(dir.path().join("people.csv"), "").unwrap();
+        std::fs::write(dir.path().join(".profiles.toml"), "").unwrap();
+        std::fs::create_dir(dir.path().join("groups")).unwrap();
+        let base = format!("{}/", dir.path().display());
+        (dir, base)
+    }
+
+    #[test]
+    fn test_completes_directory_with_slash() {
+        let (_dir, base) = fixture();
+        let mut input = PathInput::new(format!("{}gr", base));
+        assert!(input.handle_key(key(KeyCode::Tab)));
+        assert_eq!(input.value(), format!("{}groups/", base));
+        // Nothing inside it to complete
+        assert!(!input.handle_key(key(KeyCode::Tab)));
+    }
+
+    #[test]
+    fn test_extends_shared_prefix_then_cycles() {
+        let (_dir, base) = fixture();
+        let mut input = PathInput::new(format!("{}p", base));
+        input.complete();
+        assert_eq!(input.value(), format!("{}people.", base));
+        input.complete();
+        assert_eq!(input.value(), format!("{}people.csv", base));
+        input.complete();
+        assert_eq!(input.value(), format!("{}people.ldif", base));
+        input.complete();
+        assert_eq!(input.value(), format!("{}people.csv", base));
+
+        // Editing ends the cycle and completes afresh
+        for _ in 0.."ple.csv".len() {
+            input.handle_key(key(KeyCode::Backspace));
+        }
+        input.complete();
+        assert_eq!(input.value(), format!("{}people.", base));
+    }
+
+    #[test]
+    fn test_hidden_files_need_a_dot() {
+        let (_dir, base) = fixture();
+        let mut input = PathInput::new(base.clone());
+        input.complete();
+        assert_eq!(input.value(), format!("{}groups/", base));
+
+        let mut input = PathInput::new(format!("{}.", base));
+        input.complete();
+        assert_eq!(input.value(), format!("{}.profiles.toml", base));
+    }
+
+    #[test]
+    fn test_no_match_leaves_tab_to_the_dialog() {
+        let (_dir, base) = fixture();
+        let mut input = PathInput::new(format!("{}x", base));
+        assert!(!input.handle_key(key(KeyCode::Tab)));
+        assert_eq!(input.value(), format!("{}x", base));
+
+        let mut input = PathInput::new(format!("{}people.csv", base));
+        assert!(!input.handle_key(key(KeyCode::Tab)));
+    }
+
+    #[test]
+    fn test_common_prefix() {
+        let names = vec!["héllo".to_string(), "hélp".to_string()];
+        assert_eq!(common_prefix(&names), "hél");
+        let names = vec!["ab".to_string(), "abc".to_string()];
+        assert_eq!(common_prefix(&names), "ab");
+    }
+}
@@ -0,0 +1,331 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use loom_core::export::ldif_changes::write_changes;
+use loom_core::transaction::Change;
+
+use crate::action::Action;
+use crate::components::path_input::PathInput;
+use crate::components::popup::Popup;
+use crate::paths::{expand_path, write_replacing};
+use crate::theme::Theme;
+
+/// Changes recorded while offline edits are on, in the order they will
+/// be applied. Shift+J/K move the selected change, `d` discards it, and
+/// `w` writes them all as an LDIF changefile.
+pub struct PendingChangesPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    changes: Vec<Change>,
+    /// Whether the connection still records its writes.
+    recording: bool,
+    selected: usize,
+    /// File name being typed after `w`.
+    saving: Option<PathInput>,
+}
+
+impl PendingChangesPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Pending Changes", theme.clone()).with_size(80, 80),
+            theme,
+            changes: Vec::new(),
+            recording: false,
+            selected: 0,
+            saving: None,
+        }
+    }
+
+    pub fn show(&mut self, changes: Vec<Change>, recording: bool) {
+        self.changes = changes;
+        self.recording = recording;
+        self.selected = 0;
+        self.saving = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.saving = None;
+        self.popup.hide();
+    }
+
+    /// Swap the selected change with the one below or above it, keeping
+    /// it selected.
+    fn move_selected(&mut self, down: bool) -> Action {
+        let to = if down {
+            self.selected + 1
+        } else {
+            match self.selected.checked_sub(1) {
+                Some(to) => to,
+                None => return Action::None,
+            }
+        };
+        if to >= self.changes.len() {
+            return Action::None;
+        }
+        self.changes.swap(self.selected, to);
+        self.selected = to;
+        Action::PendingChangesEdited(self.changes.clone())
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if let Some(input) = self.saving.as_mut() {
+            return match key.code {
+                KeyCode::Esc => {
+                    self.saving = None;
+                    Action::None
+                }
+                KeyCode::Enter => self.save(),
+                _ => {
+                    input.handle_key(key);
+                    Action::None
+                }
+            };
+        }
+        let last = self.changes.len().saturating_sub(1);
+        let shift = key.modifiers.contains(KeyModifiers::SHIFT);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Char('J') => self.move_selected(true),
+            KeyCode::Char('K') => self.move_selected(false),
+            KeyCode::Down if shift => self.move_selected(true),
+            KeyCode::Up if shift => self.move_selected(false),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(last);
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Char('g') | KeyCode::Home => {
+                self.selected = 0;
+                Action::None
+            }
+            KeyCode::Char('G') | KeyCode::End => {
+                self.selected = last;
+                Action::None
+            }
+            KeyCode::Char('d') | KeyCode::Delete if !self.changes.is_empty() => {
+                self.changes.remove(self.selected);
+                self.selected = self.selected.min(self.changes.len().saturating_sub(1));
+                Action::PendingChangesEdited(self.changes.clone())
+            }
+            KeyCode::Char('D') if !self.changes.is_empty() => {
+                self.changes.clear();
+                self.selected = 0;
+                Action::PendingChangesEdited(Vec::new())
+            }
+            KeyCode::Char('w') | KeyCode::Char('s') if !self.changes.is_empty() => {
+                self.saving = Some(PathInput::new("changes.ldif"));
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Write the changes to the typed file.
+    fn save(&mut self) -> Action {
+        let Some(input) = self.saving.as_ref() else {
+            return Action::None;
+        };
+        if input.value().trim().is_empty() {
+            return Action::ErrorMessage("Filename is required".to_string());
+        }
+        let path = expand_path(input.value().trim());
+        let mut buf = Vec::new();
+        if let Err(e) = write_changes(&self.changes, &mut buf) {
+            return Action::ErrorMessage(format!("Failed to write {}: {}", path.display(), e));
+        }
+        match write_replacing(&path, &buf) {
+            Ok(()) => {
+                self.saving = None;
+                Action::StatusMessage(format!(
+                    "Wrote {} changes to {}",
+                    self.changes.len(),
+                    path.display()
+                ))
+            }
+            Err(e) => Action::ErrorMessage(format!("Failed to write {}: {}", path.display(), e)),
+        }
+    }
+
+    /// The selected change as it will be written.
+    fn preview(&self) -> String {
+        let Some(change) = self.changes.get(self.selected) else {
+            return String::new();
+        };
+        let mut buf = Vec::new();
+        let _ = write_changes(std::slice::from_ref(change), &mut buf);
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Percentage(50),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        let headline = match (self.changes.len(), self.recording) {
+            (n, true) => format!("{} pending; writes on this connection are recorded", n),
+            (n, false) => format!("{} pending; offline edits are off", n),
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(headline, self.theme.header()))),
+            layout[0],
+        );
+
+        if self.changes.is_empty() {
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(
+                    "No pending changes.",
+                    self.theme.dimmed(),
+                ))),
+                layout[1],
+            );
+        } else {
+            let items: Vec<ListItem> = self
+                .changes
+                .iter()
+                .enumerate()
+                .map(|(i, change)| {
+                    let style = match change {
+                        Change::Add { .. } => self.theme.success(),
+                        Change::Delete { .. } => self.theme.error(),
+                        Change::Modify { .. } | Change::Rename { .. } => self.theme.warning(),
+                    };
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!("{:>3}. ", i + 1), self.theme.dimmed()),
+                        Span::styled(change.dn().to_string(), self.theme.normal()),
+                        Span::styled(format!("  {}", change.summary()), style),
+                    ]))
+                })
+                .collect();
+            let mut state = ListState::default().with_selected(Some(self.selected));
+            frame.render_stateful_widget(
+                List::new(items).highlight_style(self.theme.selected()),
+                layout[1],
+                &mut state,
+            );
+        }
+
+        let preview: Vec<Line> = self
+            .preview()
+            .lines()
+            .map(|l| Line::from(Span::styled(l.to_string(), self.theme.normal())))
+            .collect();
+        frame.render_widget(
+            Paragraph::new(preview).block(
+                Block::default()
+                    .borders(Borders::TOP)
+                    .border_style(self.theme.dimmed()),
+            ),
+            layout[2],
+        );
+
+        let hint = match &self.saving {
+            Some(input) => Line::from(vec![
+                Span::styled("Write to: ", self.theme.header()),
+                Span::styled(input.value(), self.theme.normal()),
+                Span::styled("_", self.theme.command_prompt()),
+                Span::styled("  Enter:write  Esc:cancel", self.theme.dimmed()),
+            ]),
+            None => Line::from(Span::styled(
+                "\u{2191}/\u{2193}:select  J/K:move  d:discard  D:discard all  w:write changefile  Esc:close",
+                self.theme.dimmed(),
+            )),
+        };
+        frame.render_widget(Paragraph::new(hint), layout[3]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn delete(uid: &str) -> Change {
+        Change::Delete {
+            dn: format!("uid={},dc=example,dc=com", uid),
+        }
+    }
+
+    fn edited(action: Action) -> Vec<String> {
+        match action {
+            Action::PendingChangesEdited(changes) => {
+                changes.iter().map(|c| c.dn().to_string()).collect()
+            }
+            other => panic!("expected PendingChangesEdited, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_reorder_and_discard() {
+        let mut popup = PendingChangesPopup::new(Theme::load("dark"));
+        popup.show(vec![delete("a"), delete("b"), delete("c")], true);
+
+        // Moving the first change up does nothing
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Char('K'))),
+            Action::None
+        ));
+        assert_eq!(
+            edited(popup.handle_key_event(key(KeyCode::Char('J')))),
+            vec![
+                "uid=b,dc=example,dc=com",
+                "uid=a,dc=example,dc=com",
+                "uid=c,dc=example,dc=com"
+            ]
+        );
+        assert_eq!(popup.selected, 1);
+        assert_eq!(
+            edited(popup.handle_key_event(key(KeyCode::Char('d')))),
+            vec!["uid=b,dc=example,dc=com", "uid=c,dc=example,dc=com"]
+        );
+        assert!(edited(popup.handle_key_event(key(KeyCode::Char('D')))).is_empty());
+    }
+
+    #[test]
+    fn test_writes_changefile() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut popup = PendingChangesPopup::new(Theme::load("dark"));
+        popup.show(vec![delete("a"), delete("b")], true);
+
+        popup.handle_key_event(key(KeyCode::Char('w')));
+        let path = dir.path().join("pending.ldif");
+        popup
+            .saving
+            .as_mut()
+            .unwrap()
+            .set(path.display().to_string());
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Enter)),
+            Action::StatusMessage(ref m) if m.starts_with("Wrote 2 changes")
+        ));
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "dn: uid=a,dc=example,dc=com\nchangetype: delete\n\ndn: uid=b,dc=example,dc=com\nchangetype: delete\n"
+        );
+    }
+}
@@ -1,4 +1,8 @@
+use std::path::Path;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::{Constraint, Flex, Layout, Rect};
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
 use ratatui::Frame;
 
@@ -65,8 +69,8 @@ impl Popup {
         let block = Block::default()
             .title(format!(" {} ", self.title))
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -75,6 +79,163 @@ impl Popup {
     }
 }
 
+/// What a dialog should do with a key after [`DiscardGuard::check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dismiss {
+    /// Not a closing key; handle it as usual.
+    Pass,
+    /// Esc past the first phase: go back one phase.
+    Back,
+    /// Close the dialog, dropping its input.
+    Close,
+    /// The discard prompt took the key; leave the dialog as it is.
+    Stay,
+}
+
+/// Esc and cancel handling shared by dialogs that take input.
+///
+/// Esc goes back one phase, or closes the dialog from its first phase.
+/// Ctrl+C closes it from any phase, and so does `q` where it is not being
+/// typed. When the dialog reports input that differs from how it opened,
+/// closing first asks "Discard changes?", unless `confirm_discard` is off.
+#[derive(Debug, Clone)]
+pub struct DiscardGuard {
+    enabled: bool,
+    asking: bool,
+}
+
+impl DiscardGuard {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            asking: false,
+        }
+    }
+
+    pub fn is_asking(&self) -> bool {
+        self.asking
+    }
+
+    /// Drop a pending prompt, e.g. when the dialog is shown again.
+    pub fn reset(&mut self) {
+        self.asking = false;
+    }
+
+    /// Sort out `key` before the dialog handles it. `first_phase` says
+    /// whether Esc closes rather than goes back, `typing` whether `q` is
+    /// text, and `dirty` whether anything was entered.
+    pub fn check(
+        &mut self,
+        key: &KeyEvent,
+        first_phase: bool,
+        typing: bool,
+        dirty: bool,
+    ) -> Dismiss {
+        if self.asking {
+            return match key.code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => {
+                    self.asking = false;
+                    Dismiss::Close
+                }
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    self.asking = false;
+                    Dismiss::Stay
+                }
+                _ => Dismiss::Stay,
+            };
+        }
+        let cancel = match key.code {
+            KeyCode::Char('c') => key.modifiers.contains(KeyModifiers::CONTROL),
+            KeyCode::Char('q') => !typing && key.modifiers.is_empty(),
+            KeyCode::Esc => first_phase,
+            _ => false,
+        };
+        if !cancel {
+            return if key.code == KeyCode::Esc {
+                Dismiss::Back
+            } else {
+                Dismiss::Pass
+            };
+        }
+        if dirty && self.enabled {
+            self.asking = true;
+            Dismiss::Stay
+        } else {
+            Dismiss::Close
+        }
+    }
+
+    /// Draw the prompt over the last line of `area` while it is asking.
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        if !self.asking || area.height == 0 {
+            return;
+        }
+        let line = Rect::new(area.x, area.bottom() - 1, area.width, 1);
+        frame.render_widget(Clear, line);
+        let prompt = Line::from(vec![
+            Span::styled("Discard changes? ", theme.warning()),
+            Span::styled("y: discard  n: keep editing", theme.dimmed()),
+        ]);
+        frame.render_widget(Paragraph::new(prompt), line);
+    }
+}
+
+/// Asks before a dialog writes over a file that already exists:
+/// "profiles.toml exists, overwrite? y/N".
+#[derive(Debug, Clone, Default)]
+pub struct OverwriteGuard {
+    /// Name of the file in question while asking.
+    asking: Option<String>,
+}
+
+impl OverwriteGuard {
+    pub fn is_asking(&self) -> bool {
+        self.asking.is_some()
+    }
+
+    /// Drop a pending question, e.g. when the dialog is shown again.
+    pub fn reset(&mut self) {
+        self.asking = None;
+    }
+
+    /// Whether writing `path` has to be confirmed first, because a file is
+    /// there. Starts asking if so.
+    pub fn needs_confirm(&mut self, path: &Path) -> bool {
+        if !path.is_file() {
+            return false;
+        }
+        let name = path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.display().to_string());
+        self.asking = Some(name);
+        true
+    }
+
+    /// Answer the question with `key`: true for `y`, to go ahead. Any
+    /// other key says no.
+    pub fn confirm(&mut self, key: &KeyEvent) -> bool {
+        self.asking.take().is_some() && matches!(key.code, KeyCode::Char('y') | KeyCode::Char('Y'))
+    }
+
+    /// Draw the question over the last line of `area` while asking.
+    pub fn render(&self, frame: &mut Frame, area: Rect, theme: &Theme) {
+        let Some(name) = &self.asking else {
+            return;
+        };
+        if area.height == 0 {
+            return;
+        }
+        let line = Rect::new(area.x, area.bottom() - 1, area.width, 1);
+        frame.render_widget(Clear, line);
+        let prompt = Line::from(vec![
+            Span::styled(format!("{} exists, overwrite? ", name), theme.warning()),
+            Span::styled("y: overwrite  N: keep it", theme.dimmed()),
+        ]);
+        frame.render_widget(Paragraph::new(prompt), line);
+    }
+}
+
 /// A simple message popup with just text content.
 pub fn render_message_popup(
     frame: &mut Frame,
@@ -87,7 +248,83 @@ pub fn render_message_popup(
     let inner = popup.render_frame(frame, full);
 
     let paragraph = Paragraph::new(message)
-        .style(theme.normal)
+        .style(theme.normal())
         .wrap(Wrap { trim: true });
     frame.render_widget(paragraph, inner);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_guard_asks_only_when_dirty() {
+        let mut guard = DiscardGuard::new(true);
+        assert_eq!(
+            guard.check(&key(KeyCode::Esc), true, true, false),
+            Dismiss::Close
+        );
+        assert_eq!(
+            guard.check(&key(KeyCode::Esc), false, true, true),
+            Dismiss::Back
+        );
+        assert_eq!(
+            guard.check(&key(KeyCode::Char('q')), true, true, true),
+            Dismiss::Pass
+        );
+
+        assert_eq!(
+            guard.check(&key(KeyCode::Char('q')), false, false, true),
+            Dismiss::Stay
+        );
+        assert!(guard.is_asking());
+        // Anything but y/n leaves the prompt up
+        assert_eq!(
+            guard.check(&key(KeyCode::Char('x')), true, true, true),
+            Dismiss::Stay
+        );
+        assert_eq!(
+            guard.check(&key(KeyCode::Char('n')), true, true, true),
+            Dismiss::Stay
+        );
+        assert!(!guard.is_asking());
+
+        let ctrl_c = KeyEvent::new(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(guard.check(&ctrl_c, false, true, true), Dismiss::Stay);
+        assert_eq!(
+            guard.check(&key(KeyCode::Char('y')), true, true, true),
+            Dismiss::Close
+        );
+
+        // Turned off, dirty dialogs close straight away
+        let mut guard = DiscardGuard::new(false);
+        assert_eq!(
+            guard.check(&key(KeyCode::Esc), true, true, true),
+            Dismiss::Close
+        );
+    }
+
+    #[test]
+    fn test_overwrite_guard_asks_for_existing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles.toml");
+        let mut guard = OverwriteGuard::default();
+        assert!(!guard.needs_confirm(&path));
+        assert!(!guard.needs_confirm(dir.path()));
+
+        std::fs::write(&path, "").unwrap();
+        assert!(guard.needs_confirm(&path));
+        assert!(guard.is_asking());
+        assert!(!guard.confirm(&key(KeyCode::Enter)));
+        assert!(!guard.is_asking());
+
+        guard.needs_confirm(&path);
+        assert!(guard.confirm(&key(KeyCode::Char('y'))));
+        // Answered, so a stray y does not count again
+        assert!(!guard.confirm(&key(KeyCode::Char('y'))));
+    }
+}
@@ -6,14 +6,18 @@ use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use ratatui::Frame;
 
 use crate::action::Action;
-use crate::components::popup::Popup;
-use crate::config::{AppConfig, ConnectionProfile};
+use crate::components::path_input::PathInput;
+use crate::components::popup::{OverwriteGuard, Popup};
+use crate::config::ConnectionProfile;
+use crate::paths::expand_path;
 use crate::theme::Theme;
 
 /// Which part of the dialog is active.
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum ActiveField {
     ProfileList,
+    Passwords,
+    Passphrase,
     Filename,
 }
 
@@ -23,12 +27,24 @@ pub struct ProfileExportDialog {
     popup: Popup,
     theme: Theme,
     active_field: ActiveField,
-    /// Profile names and their selected state.
-    profiles: Vec<(String, bool)>,
-    /// Cursor position in the profile list.
+    /// Profiles offered and their selected state.
+    profiles: Vec<(ConnectionProfile, bool)>,
+    /// Indices into `profiles` that match the filter.
+    shown: Vec<usize>,
+    /// Cursor position in `shown`.
     cursor: usize,
+    /// Narrows the list to profiles whose name or host contains it.
+    filter: String,
+    /// The filter is being typed, after `/`.
+    filtering: bool,
     /// Output filename.
-    filename: String,
+    filename: PathInput,
+    /// Write stored passwords too; off so shared files carry no secrets.
+    include_passwords: bool,
+    /// Encrypts the file when passwords are included and it is not empty.
+    passphrase: String,
+    /// Asks before replacing an existing file.
+    overwrite: OverwriteGuard,
 }
 
 impl ProfileExportDialog {
@@ -39,16 +55,28 @@ impl ProfileExportDialog {
             theme,
             active_field: ActiveField::ProfileList,
             profiles: Vec::new(),
+            shown: Vec::new(),
             cursor: 0,
-            filename: String::new(),
+            filter: String::new(),
+            filtering: false,
+            filename: PathInput::default(),
+            include_passwords: false,
+            passphrase: String::new(),
+            overwrite: OverwriteGuard::default(),
         }
     }
 
     /// Show the dialog populated with the given profiles.
     pub fn show(&mut self, profiles: &[ConnectionProfile]) {
-        self.profiles = profiles.iter().map(|p| (p.name.clone(), true)).collect();
+        self.profiles = profiles.iter().map(|p| (p.clone(), true)).collect();
+        self.filter.clear();
+        self.filtering = false;
+        self.refilter();
         self.cursor = 0;
-        self.filename = "profiles.toml".to_string();
+        self.filename.set("profiles.toml");
+        self.include_passwords = false;
+        self.passphrase.clear();
+        self.overwrite.reset();
         self.active_field = ActiveField::ProfileList;
         self.visible = true;
         self.popup.show();
@@ -59,19 +87,54 @@ impl ProfileExportDialog {
         self.popup.hide();
     }
 
-    pub fn handle_key_event(
-        &mut self,
-        key: KeyEvent,
-        all_profiles: &[ConnectionProfile],
-    ) -> Action {
+    /// Show only the profiles matching the filter, keeping the cursor on
+    /// the list.
+    fn refilter(&mut self) {
+        let needle = self.filter.to_lowercase();
+        self.shown = self
+            .profiles
+            .iter()
+            .enumerate()
+            .filter(|(_, (p, _))| {
+                p.name.to_lowercase().contains(&needle) || p.host.to_lowercase().contains(&needle)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        self.cursor = self.cursor.min(self.shown.len().saturating_sub(1));
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if self.overwrite.is_asking() {
+            if self.overwrite.confirm(&key) {
+                return self.submit(true);
+            }
+            self.active_field = ActiveField::Filename;
+            return Action::None;
+        }
+        if self.filtering {
+            return self.handle_filter_key(key);
+        }
         match key.code {
+            // Clear the filter before closing
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.refilter();
+                Action::None
+            }
             KeyCode::Esc => {
                 self.hide();
                 Action::ClosePopup
             }
+            KeyCode::Tab
+                if self.active_field == ActiveField::Filename && self.filename.complete() =>
+            {
+                Action::None
+            }
             KeyCode::Tab => {
                 self.active_field = match self.active_field {
-                    ActiveField::ProfileList => ActiveField::Filename,
+                    ActiveField::ProfileList => ActiveField::Passwords,
+                    ActiveField::Passwords if self.include_passwords => ActiveField::Passphrase,
+                    ActiveField::Passwords | ActiveField::Passphrase => ActiveField::Filename,
                     ActiveField::Filename => ActiveField::ProfileList,
                 };
                 Action::None
@@ -79,18 +142,56 @@ impl ProfileExportDialog {
             KeyCode::BackTab => {
                 self.active_field = match self.active_field {
                     ActiveField::ProfileList => ActiveField::Filename,
-                    ActiveField::Filename => ActiveField::ProfileList,
+                    ActiveField::Passwords => ActiveField::ProfileList,
+                    ActiveField::Passphrase => ActiveField::Passwords,
+                    ActiveField::Filename if self.include_passwords => ActiveField::Passphrase,
+                    ActiveField::Filename => ActiveField::Passwords,
                 };
                 Action::None
             }
-            KeyCode::Enter => self.submit(all_profiles),
+            KeyCode::Enter => self.submit(false),
             _ => match self.active_field {
                 ActiveField::ProfileList => self.handle_list_key(key),
-                ActiveField::Filename => self.handle_filename_key(key),
+                ActiveField::Passwords => {
+                    if key.code == KeyCode::Char(' ') {
+                        self.include_passwords = !self.include_passwords;
+                    }
+                    Action::None
+                }
+                ActiveField::Passphrase => Self::edit(&mut self.passphrase, key),
+                ActiveField::Filename => {
+                    self.filename.handle_key(key);
+                    Action::None
+                }
             },
         }
     }
 
+    fn handle_filter_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.filtering = false;
+                self.refilter();
+            }
+            KeyCode::Enter => self.filtering = false,
+            KeyCode::Up => self.cursor = self.cursor.saturating_sub(1),
+            KeyCode::Down => {
+                self.cursor = (self.cursor + 1).min(self.shown.len().saturating_sub(1))
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.refilter();
+            }
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.refilter();
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
     fn handle_list_key(&mut self, key: KeyEvent) -> Action {
         match key.code {
             KeyCode::Up | KeyCode::Char('k') => {
@@ -100,81 +201,76 @@ impl ProfileExportDialog {
                 Action::None
             }
             KeyCode::Down | KeyCode::Char('j') => {
-                if self.cursor + 1 < self.profiles.len() {
+                if self.cursor + 1 < self.shown.len() {
                     self.cursor += 1;
                 }
                 Action::None
             }
             KeyCode::Char(' ') => {
-                if let Some(item) = self.profiles.get_mut(self.cursor) {
-                    item.1 = !item.1;
+                if let Some(&i) = self.shown.get(self.cursor) {
+                    self.profiles[i].1 = !self.profiles[i].1;
                 }
                 Action::None
             }
+            // Toggles the profiles shown, leaving filtered-out ones alone
             KeyCode::Char('a') => {
-                let all_selected = self.profiles.iter().all(|(_, sel)| *sel);
-                for item in &mut self.profiles {
-                    item.1 = !all_selected;
+                let all_selected = self.shown.iter().all(|&i| self.profiles[i].1);
+                for &i in &self.shown {
+                    self.profiles[i].1 = !all_selected;
                 }
                 Action::None
             }
+            KeyCode::Char('/') => {
+                self.filtering = true;
+                Action::None
+            }
             _ => Action::None,
         }
     }
 
-    fn handle_filename_key(&mut self, key: KeyEvent) -> Action {
+    fn edit(buf: &mut String, key: KeyEvent) -> Action {
         match key.code {
             KeyCode::Backspace => {
-                self.filename.pop();
-                Action::None
+                buf.pop();
             }
-            KeyCode::Char(c) => {
-                self.filename.push(c);
-                Action::None
-            }
-            _ => Action::None,
+            KeyCode::Char(c) => buf.push(c),
+            _ => {}
         }
+        Action::None
     }
 
-    fn submit(&mut self, all_profiles: &[ConnectionProfile]) -> Action {
-        if self.filename.trim().is_empty() {
+    /// Export the selection, once `confirmed` if the file exists.
+    fn submit(&mut self, confirmed: bool) -> Action {
+        if self.filename.value().trim().is_empty() {
             return Action::ErrorMessage("Filename is required".to_string());
         }
 
+        // Selected ones hidden by the filter go too
         let selected: Vec<ConnectionProfile> = self
             .profiles
             .iter()
-            .enumerate()
-            .filter(|(_, (_, sel))| *sel)
-            .filter_map(|(i, _)| all_profiles.get(i).cloned())
+            .filter(|(_, sel)| *sel)
+            .map(|(p, _)| p.clone())
             .collect();
 
         if selected.is_empty() {
             return Action::ErrorMessage("No profiles selected".to_string());
         }
 
-        let content = match AppConfig::export_profiles(&selected) {
-            Ok(c) => c,
-            Err(e) => {
-                self.hide();
-                return Action::ErrorMessage(e);
-            }
-        };
-
-        // Expand ~/
-        let path = expand_tilde(self.filename.trim());
-
-        if let Err(e) = std::fs::write(&path, &content) {
-            self.hide();
-            return Action::ErrorMessage(format!("Failed to write {}: {}", path, e));
+        let path = expand_path(self.filename.value().trim());
+        if !confirmed && self.overwrite.needs_confirm(&path) {
+            return Action::None;
         }
 
+        let passphrase =
+            Some(self.passphrase.clone()).filter(|p| self.include_passwords && !p.is_empty());
         self.hide();
-        Action::StatusMessage(format!(
-            "Exported {} profile(s) to {}",
-            selected.len(),
-            path
-        ))
+        Action::ConnMgrExportExecute {
+            profiles: selected,
+            path,
+            include_passwords: self.include_passwords,
+            passphrase,
+        }
     }
 
     pub fn render(&self, frame: &mut Frame, full: Rect) {
@@ -188,89 +284,362 @@ impl ProfileExportDialog {
         let block = Block::default()
             .title(" Export Profiles ")
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
 
-        let list_height = self.profiles.len().max(1) as u16 + 1; // +1 for label
+        // The list takes what the other fields leave, scrolling past that
+        let list_height = self.profiles.len().max(1) as u16 + 2; // label and filter
         let layout = Layout::vertical([
-            Constraint::Length(list_height), // Profile list
-            Constraint::Length(2),           // Filename
-            Constraint::Min(1),              // Hints
+            Constraint::Max(list_height), // Profile list
+            Constraint::Length(1),        // Include passwords
+            Constraint::Length(2),        // Passphrase
+            Constraint::Length(2),        // Filename
+            Constraint::Length(1),        // Hints
         ])
         .split(inner);
 
         // Profile list
         let list_active = self.active_field == ActiveField::ProfileList;
         let label_style = if list_active {
-            self.theme.header
+            self.theme.header()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
 
-        let mut lines = vec![Line::from(Span::styled("Profiles:", label_style))];
-        for (i, (name, selected)) in self.profiles.iter().enumerate() {
+        let show_filter = self.filtering || !self.filter.is_empty();
+        let rows = (layout[0].height as usize)
+            .saturating_sub(1 + usize::from(show_filter))
+            .max(1);
+        let offset = if self.cursor >= rows {
+            self.cursor + 1 - rows
+        } else {
+            0
+        };
+        let selected_count = self.profiles.iter().filter(|(_, sel)| *sel).count();
+        let mut label = format!(
+            "Profiles ({} of {} selected)",
+            selected_count,
+            self.profiles.len()
+        );
+        if self.shown.len() > rows {
+            // Which part of the list is on screen
+            label.push_str(&format!(
+                "  {}-{} of {}",
+                offset + 1,
+                (offset + rows).min(self.shown.len()),
+                self.shown.len()
+            ));
+        }
+        label.push(':');
+        let mut lines = vec![Line::from(Span::styled(label, label_style))];
+        if show_filter {
+            let mut spans = vec![
+                Span::styled("/", self.theme.command_prompt()),
+                Span::styled(self.filter.as_str(), self.theme.normal()),
+            ];
+            if self.filtering {
+                spans.push(Span::styled("_", self.theme.command_prompt()));
+            }
+            lines.push(Line::from(spans));
+        }
+        if self.shown.is_empty() && !self.profiles.is_empty() {
+            lines.push(Line::from(Span::styled(
+                "  (no profiles match)",
+                self.theme.dimmed(),
+            )));
+        }
+        for (row, &i) in self.shown.iter().enumerate().skip(offset).take(rows) {
+            let (profile, selected) = &self.profiles[i];
             let marker = if *selected { "[x] " } else { "[ ] " };
-            let is_cursor = list_active && i == self.cursor;
+            let is_cursor = list_active && row == self.cursor;
             let style = if is_cursor {
-                self.theme.selected.add_modifier(Modifier::BOLD)
+                self.theme.selected().add_modifier(Modifier::BOLD)
             } else if *selected {
-                self.theme.normal
+                self.theme.normal()
             } else {
-                self.theme.dimmed
+                self.theme.dimmed()
             };
             let prefix = if is_cursor { "> " } else { "  " };
-            lines.push(Line::from(Span::styled(
-                format!("{}{}{}", prefix, marker, name),
-                style,
-            )));
+            lines.push(Line::from(vec![
+                Span::styled(format!("{}{}{}", prefix, marker, profile.name), style),
+                Span::styled(format!("  {}", profile.host), self.theme.dimmed()),
+            ]));
         }
         frame.render_widget(Paragraph::new(lines), layout[0]);
 
+        // Include passwords checkbox
+        let pw_style = if self.active_field == ActiveField::Passwords {
+            self.theme.selected().add_modifier(Modifier::BOLD)
+        } else {
+            self.theme.dimmed()
+        };
+        let marker = if self.include_passwords {
+            "[x] "
+        } else {
+            "[ ] "
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                format!("{}Include passwords (from the vault)", marker),
+                pw_style,
+            ))),
+            layout[1],
+        );
+
+        // Passphrase, masked
+        let pp_active = self.active_field == ActiveField::Passphrase;
+        let (pp_label_style, pp_value_style) = if pp_active {
+            (self.theme.header(), self.theme.normal())
+        } else {
+            (self.theme.dimmed(), self.theme.dimmed())
+        };
+        let pp_value = if !self.include_passwords {
+            "(passwords not included)".to_string()
+        } else if self.passphrase.is_empty() && !pp_active {
+            "(none: passwords are written in plain text)".to_string()
+        } else {
+            "*".repeat(self.passphrase.chars().count())
+        };
+        let pp_lines = vec![
+            Line::from(Span::styled("Encrypt with passphrase:", pp_label_style)),
+            Line::from(vec![
+                Span::styled(pp_value, pp_value_style),
+                if pp_active {
+                    Span::styled("_", self.theme.command_prompt())
+                } else {
+                    Span::raw("")
+                },
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(pp_lines), layout[2]);
+
         // Filename field
         let fn_active = self.active_field == ActiveField::Filename;
         let fn_label_style = if fn_active {
-            self.theme.header
+            self.theme.header()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
         let fn_value_style = if fn_active {
-            self.theme.normal
+            self.theme.normal()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
         let fn_lines = vec![
             Line::from(Span::styled("Filename:", fn_label_style)),
             Line::from(vec![
-                Span::styled(&self.filename, fn_value_style),
+                Span::styled(self.filename.value(), fn_value_style),
                 if fn_active {
-                    Span::styled("_", self.theme.command_prompt)
+                    Span::styled("_", self.theme.command_prompt())
                 } else {
                     Span::raw("")
                 },
             ]),
         ];
-        frame.render_widget(Paragraph::new(fn_lines), layout[1]);
+        frame.render_widget(Paragraph::new(fn_lines), layout[3]);
 
         // Hints
-        let hint_text = if list_active {
-            "Space:toggle  a:all  Tab:filename  Enter:export  Esc:cancel"
-        } else {
-            "Tab:profiles  Enter:export  Esc:cancel"
+        let hint_text = match self.active_field {
+            ActiveField::ProfileList if self.filtering => {
+                "type to filter  \u{2191}/\u{2193}:move  Enter:done  Esc:clear"
+            }
+            ActiveField::ProfileList => {
+                "Space:toggle  a:all  /:filter  Tab:next  Enter:export  Esc:cancel"
+            }
+            ActiveField::Passwords => "Space:toggle  Tab:next  Enter:export  Esc:cancel",
+            ActiveField::Passphrase => "Tab:next  Enter:export  Esc:cancel",
+            ActiveField::Filename => "Tab:complete or next  Enter:export  Esc:cancel",
         };
-        let hints = Paragraph::new(Line::from(Span::styled(hint_text, self.theme.dimmed)));
-        frame.render_widget(hints, layout[2]);
+        let hints = Paragraph::new(Line::from(Span::styled(hint_text, self.theme.dimmed())));
+        frame.render_widget(hints, layout[4]);
+        self.overwrite.render(frame, inner, &self.theme);
     }
 }
 
-/// Expand a leading `~/` to the user's home directory.
-pub fn expand_tilde(path: &str) -> String {
-    if let Some(rest) = path.strip_prefix("~/") {
-        if let Some(home) = dirs::home_dir() {
-            return home.join(rest).to_string_lossy().to_string();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_passwords_off_by_default() {
+        let profiles = crate::config::AppConfig::import_profiles(
+            "[[connections]]\nname = \"prod\"\nhost = \"ldap.example.com\"\n",
+        )
+        .unwrap();
+        let mut dialog = ProfileExportDialog::new(Theme::load("dark"));
+        dialog.show(&profiles);
+        let Action::ConnMgrExportExecute {
+            include_passwords,
+            passphrase,
+            ..
+        } = dialog.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected an export");
+        };
+        assert!(!include_passwords);
+        assert_eq!(passphrase, None);
+
+        // Ticked, with a passphrase typed on the field after the checkbox
+        dialog.show(&profiles);
+        dialog.handle_key_event(key(KeyCode::Tab));
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+        dialog.handle_key_event(key(KeyCode::Tab));
+        assert_eq!(dialog.active_field, ActiveField::Passphrase);
+        for c in "pw".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        let Action::ConnMgrExportExecute {
+            profiles: selected,
+            include_passwords,
+            passphrase,
+            ..
+        } = dialog.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected an export");
+        };
+        assert_eq!(selected.len(), 1);
+        assert!(include_passwords);
+        assert_eq!(passphrase.as_deref(), Some("pw"));
+    }
+
+    #[test]
+    fn test_existing_file_asks_before_overwrite() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles.toml");
+        std::fs::write(&path, "# hand-curated\n").unwrap();
+        let profiles = crate::config::AppConfig::import_profiles(
+            "[[connections]]\nname = \"prod\"\nhost = \"ldap.example.com\"\n",
+        )
+        .unwrap();
+        let mut dialog = ProfileExportDialog::new(Theme::load("dark"));
+        dialog.show(&profiles);
+        dialog.filename.set(path.display().to_string());
+
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::None
+        ));
+        assert!(dialog.overwrite.is_asking());
+        // Anything but y goes back to the filename
+        dialog.handle_key_event(key(KeyCode::Char('n')));
+        assert!(!dialog.overwrite.is_asking());
+        assert_eq!(dialog.active_field, ActiveField::Filename);
+        assert!(dialog.visible);
+
+        dialog.handle_key_event(key(KeyCode::Enter));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('y'))),
+            Action::ConnMgrExportExecute { .. }
+        ));
+    }
+
+    #[test]
+    fn test_filter_keeps_selections_and_exports_the_right_profiles() {
+        let profiles = crate::config::AppConfig::import_profiles(
+            "[[connections]]\nname = \"prod\"\nhost = \"ldap.example.com\"\n\
+             [[connections]]\nname = \"staging\"\nhost = \"stage.example.com\"\n\
+             [[connections]]\nname = \"lab\"\nhost = \"ldap.lab.test\"\n",
+        )
+        .unwrap();
+        let mut dialog = ProfileExportDialog::new(Theme::load("dark"));
+        dialog.show(&profiles);
+        let type_filter = |dialog: &mut ProfileExportDialog, text: &str| {
+            dialog.handle_key_event(key(KeyCode::Char('/')));
+            for c in text.chars() {
+                dialog.handle_key_event(key(KeyCode::Char(c)));
+            }
+            dialog.handle_key_event(key(KeyCode::Enter));
+        };
+
+        // Matches on host as well as name
+        type_filter(&mut dialog, "LDAP");
+        assert_eq!(dialog.shown, vec![0, 2]);
+        // Untick "lab", second in the filtered list; `a` then ticks the
+        // view, and again unticks it
+        dialog.handle_key_event(key(KeyCode::Char('j')));
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+        assert!(!dialog.profiles[2].1);
+        dialog.handle_key_event(key(KeyCode::Char('a')));
+        assert!(dialog.profiles[0].1 && dialog.profiles[2].1);
+        dialog.handle_key_event(key(KeyCode::Char('a')));
+        assert!(!dialog.profiles[0].1 && !dialog.profiles[2].1);
+        assert!(
+            dialog.profiles[1].1,
+            "staging is filtered out, not deselected"
+        );
+
+        // Esc clears the filter rather than closing
+        dialog.handle_key_event(key(KeyCode::Esc));
+        assert!(dialog.visible);
+        assert_eq!(dialog.shown, vec![0, 1, 2]);
+
+        type_filter(&mut dialog, "stage");
+        assert_eq!(dialog.shown, vec![1]);
+        let Action::ConnMgrExportExecute {
+            profiles: selected, ..
+        } = dialog.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected an export");
+        };
+        let names: Vec<&str> = selected.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["staging"]);
+    }
+
+    #[test]
+    fn test_reordered_config_exports_what_was_ticked() {
+        let mut profiles = crate::config::AppConfig::import_profiles(
+            "[[connections]]\nname = \"prod\"\nhost = \"ldap.example.com\"\n\
+             [[connections]]\nname = \"staging\"\nhost = \"stage.example.com\"\n\
+             [[connections]]\nname = \"lab\"\nhost = \"lab.example.com\"\n",
+        )
+        .unwrap();
+        let mut dialog = ProfileExportDialog::new(Theme::load("dark"));
+        dialog.show(&profiles);
+        // The caller's list changes order while the dialog is open
+        profiles.reverse();
+
+        // Untick "prod" by name, wherever the dialog shows it
+        let row = dialog
+            .shown
+            .iter()
+            .position(|&i| dialog.profiles[i].0.name == "prod")
+            .unwrap();
+        for _ in 0..row {
+            dialog.handle_key_event(key(KeyCode::Down));
         }
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+
+        let Action::ConnMgrExportExecute {
+            profiles: selected, ..
+        } = dialog.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected an export");
+        };
+        let by_name = |list: &[ConnectionProfile]| {
+            let mut pairs: Vec<(String, String)> = list
+                .iter()
+                .map(|p| (p.name.clone(), p.host.clone()))
+                .collect();
+            pairs.sort();
+            pairs
+        };
+        let ticked: Vec<ConnectionProfile> =
+            profiles.into_iter().filter(|p| p.name != "prod").collect();
+        assert_eq!(by_name(&selected), by_name(&ticked));
+        assert_eq!(
+            by_name(&selected),
+            [
+                ("lab".to_string(), "lab.example.com".to_string()),
+                ("staging".to_string(), "stage.example.com".to_string()),
+            ]
+        );
     }
-    path.to_string()
 }
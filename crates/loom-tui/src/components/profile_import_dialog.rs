@@ -1,3 +1,5 @@
+use std::path::Path;
+
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::Modifier;
@@ -5,10 +7,15 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use ratatui::Frame;
 
+use loom_core::profile_import::ForeignFormat;
+
 use crate::action::Action;
-use crate::components::popup::Popup;
-use crate::components::profile_export_dialog::expand_tilde;
-use crate::config::{AppConfig, ConnectionProfile};
+use crate::components::path_input::PathInput;
+use crate::components::popup::{DiscardGuard, Dismiss, Popup};
+use crate::config::{
+    import_conflicts, AppConfig, ConnectionProfile, ImportConflict, ImportResolution, ProfileImport,
+};
+use crate::paths::expand_path;
 use crate::theme::Theme;
 
 /// Which phase the import dialog is in.
@@ -16,24 +23,82 @@ use crate::theme::Theme;
 enum Phase {
     /// User enters a file path.
     FilePath,
+    /// User confirms or changes the detected file format.
+    SourceFormat,
+    /// User enters the passphrase of an encrypted loom export.
+    Passphrase,
     /// User selects which profiles to import.
     SelectProfiles,
+    /// User decides what to do with profiles that clash with saved ones.
+    Conflicts,
+}
+
+/// Format of the file being imported.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SourceFormat {
+    /// loom's own `[[connections]]` TOML.
+    Loom,
+    /// Another LDAP tool's configuration.
+    Foreign(ForeignFormat),
+}
+
+impl SourceFormat {
+    const ALL: [SourceFormat; 4] = [
+        SourceFormat::Loom,
+        SourceFormat::Foreign(ForeignFormat::DirectoryStudio),
+        SourceFormat::Foreign(ForeignFormat::LdapConf),
+        SourceFormat::Foreign(ForeignFormat::UrlList),
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            SourceFormat::Loom => "loom profiles (TOML)",
+            SourceFormat::Foreign(format) => format.label(),
+        }
+    }
 }
 
-/// Dialog for importing connection profiles from a TOML file.
+/// A profile found in the file, with the settings that could not be imported.
+struct ImportRow {
+    profile: ConnectionProfile,
+    warnings: Vec<String>,
+    selected: bool,
+    /// Password exported along with the profile.
+    password: Option<String>,
+}
+
+/// Dialog for importing connection profiles from loom's TOML or another
+/// LDAP tool's configuration.
 pub struct ProfileImportDialog {
     pub visible: bool,
     popup: Popup,
     theme: Theme,
     phase: Phase,
     /// File path input.
-    file_path: String,
-    /// Parsed profiles with (name, host, selected).
-    parsed_profiles: Vec<(ConnectionProfile, bool)>,
+    file_path: PathInput,
+    /// Contents of the opened file.
+    content: String,
+    /// Passphrase of an encrypted export.
+    passphrase: String,
+    /// Index into `SourceFormat::ALL`, preset to the detected format.
+    format_cursor: usize,
+    /// Parsed profiles.
+    parsed_profiles: Vec<ImportRow>,
     /// Cursor position in profile list.
     cursor: usize,
+    /// Saved profiles, to find conflicts against.
+    existing: Vec<ConnectionProfile>,
+    /// Selected profiles with their conflicts, built on leaving the
+    /// selection step.
+    imports: Vec<ProfileImport>,
+    /// Cursor position among the conflicting entries of `imports`.
+    conflict_cursor: usize,
+    guard: DiscardGuard,
 }
 
+/// Path the file path step starts with.
+const DEFAULT_PATH: &str = "profiles.toml";
+
 impl ProfileImportDialog {
     pub fn new(theme: Theme) -> Self {
         Self {
@@ -41,61 +106,144 @@ impl ProfileImportDialog {
             popup: Popup::new("Import Profiles", theme.clone()).with_size(55, 60),
             theme,
             phase: Phase::FilePath,
-            file_path: String::new(),
+            file_path: PathInput::default(),
+            content: String::new(),
+            passphrase: String::new(),
+            format_cursor: 0,
             parsed_profiles: Vec::new(),
             cursor: 0,
+            existing: Vec::new(),
+            imports: Vec::new(),
+            conflict_cursor: 0,
+            guard: DiscardGuard::new(true),
         }
     }
 
-    pub fn show(&mut self) {
+    /// Ask before Ctrl+C or Esc throws away what was entered.
+    pub fn with_confirm_discard(mut self, enabled: bool) -> Self {
+        self.guard = DiscardGuard::new(enabled);
+        self
+    }
+
+    /// A path was typed or a file was opened.
+    fn is_dirty(&self) -> bool {
+        self.phase != Phase::FilePath || self.file_path.value() != DEFAULT_PATH
+    }
+
+    pub fn show(&mut self, existing: &[ConnectionProfile]) {
         self.phase = Phase::FilePath;
-        self.file_path = "profiles.toml".to_string();
+        self.file_path.set(DEFAULT_PATH);
+        self.content.clear();
+        self.passphrase.clear();
         self.parsed_profiles.clear();
         self.cursor = 0;
+        self.existing = existing.to_vec();
+        self.imports.clear();
+        self.conflict_cursor = 0;
+        self.guard.reset();
         self.visible = true;
         self.popup.show();
     }
 
+    /// Open `path` straight away, at the format step.
+    pub fn show_file(&mut self, existing: &[ConnectionProfile], path: &Path) -> Action {
+        self.show(existing);
+        self.file_path.set(path.display().to_string());
+        self.open_file()
+    }
+
     pub fn hide(&mut self) {
         self.visible = false;
         self.popup.hide();
     }
 
+    /// Esc goes back a phase; Ctrl+C, or `q` outside the path input,
+    /// cancels the import.
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
-        match key.code {
-            KeyCode::Esc => {
-                if self.phase == Phase::SelectProfiles {
-                    // Go back to file path phase
-                    self.phase = Phase::FilePath;
-                    self.parsed_profiles.clear();
-                    return Action::None;
-                }
+        let first_phase = self.phase == Phase::FilePath;
+        let typing = first_phase || self.phase == Phase::Passphrase;
+        match self.guard.check(&key, first_phase, typing, self.is_dirty()) {
+            Dismiss::Close => {
                 self.hide();
-                Action::ClosePopup
+                return Action::ClosePopup;
+            }
+            Dismiss::Back => {
+                self.back();
+                return Action::None;
             }
+            Dismiss::Stay => return Action::None,
+            Dismiss::Pass => {}
+        }
+        match key.code {
             KeyCode::Enter => match self.phase {
                 Phase::FilePath => self.open_file(),
-                Phase::SelectProfiles => self.submit(),
+                Phase::SourceFormat | Phase::Passphrase => self.parse_content(),
+                Phase::SelectProfiles => self.check_conflicts(),
+                Phase::Conflicts => self.submit(),
             },
             _ => match self.phase {
                 Phase::FilePath => self.handle_filepath_key(key),
+                Phase::SourceFormat => self.handle_format_key(key),
+                Phase::Passphrase => self.handle_passphrase_key(key),
                 Phase::SelectProfiles => self.handle_select_key(key),
+                Phase::Conflicts => self.handle_conflict_key(key),
             },
         }
     }
 
+    fn back(&mut self) {
+        match self.phase {
+            Phase::Conflicts => {
+                self.phase = Phase::SelectProfiles;
+                self.imports.clear();
+            }
+            Phase::SelectProfiles => {
+                // Go back to the format choice, or the passphrase
+                self.phase = if AppConfig::is_encrypted_profiles(&self.content) {
+                    Phase::Passphrase
+                } else {
+                    Phase::SourceFormat
+                };
+                self.parsed_profiles.clear();
+            }
+            Phase::SourceFormat | Phase::Passphrase => {
+                self.phase = Phase::FilePath;
+                self.content.clear();
+                self.passphrase.clear();
+            }
+            Phase::FilePath => {}
+        }
+    }
+
     fn handle_filepath_key(&mut self, key: KeyEvent) -> Action {
+        self.file_path.handle_key(key);
+        Action::None
+    }
+
+    fn handle_passphrase_key(&mut self, key: KeyEvent) -> Action {
         match key.code {
             KeyCode::Backspace => {
-                self.file_path.pop();
-                Action::None
+                self.passphrase.pop();
             }
-            KeyCode::Char(c) => {
-                self.file_path.push(c);
-                Action::None
+            KeyCode::Char(c) => self.passphrase.push(c),
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn handle_format_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.format_cursor = self.format_cursor.saturating_sub(1);
             }
-            _ => Action::None,
+            KeyCode::Down | KeyCode::Char('j')
+                if self.format_cursor + 1 < SourceFormat::ALL.len() =>
+            {
+                self.format_cursor += 1;
+            }
+            _ => {}
         }
+        Action::None
     }
 
     fn handle_select_key(&mut self, key: KeyEvent) -> Action {
@@ -113,15 +261,15 @@ impl ProfileImportDialog {
                 Action::None
             }
             KeyCode::Char(' ') => {
-                if let Some(item) = self.parsed_profiles.get_mut(self.cursor) {
-                    item.1 = !item.1;
+                if let Some(row) = self.parsed_profiles.get_mut(self.cursor) {
+                    row.selected = !row.selected;
                 }
                 Action::None
             }
             KeyCode::Char('a') => {
-                let all_selected = self.parsed_profiles.iter().all(|(_, sel)| *sel);
-                for item in &mut self.parsed_profiles {
-                    item.1 = !all_selected;
+                let all_selected = self.parsed_profiles.iter().all(|row| row.selected);
+                for row in &mut self.parsed_profiles {
+                    row.selected = !all_selected;
                 }
                 Action::None
             }
@@ -129,22 +277,122 @@ impl ProfileImportDialog {
         }
     }
 
+    fn handle_conflict_key(&mut self, key: KeyEvent) -> Action {
+        let count = self.conflicting().count();
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.conflict_cursor = self.conflict_cursor.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.conflict_cursor = (self.conflict_cursor + 1).min(count.saturating_sub(1));
+            }
+            KeyCode::Left | KeyCode::Char('h') => self.cycle_resolution(false),
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Tab => self.cycle_resolution(true),
+            _ => {}
+        }
+        Action::None
+    }
+
+    /// Imports that clash with a saved profile, in order.
+    fn conflicting(&self) -> impl Iterator<Item = &ProfileImport> {
+        self.imports.iter().filter(|i| i.conflict.is_some())
+    }
+
+    fn cycle_resolution(&mut self, forward: bool) {
+        let Some(import) = self
+            .imports
+            .iter_mut()
+            .filter(|i| i.conflict.is_some())
+            .nth(self.conflict_cursor)
+        else {
+            return;
+        };
+        let Some(conflict) = import.conflict else {
+            return;
+        };
+        let choices = ImportResolution::choices(conflict);
+        let current = choices
+            .iter()
+            .position(|r| *r == import.resolution)
+            .unwrap_or(0);
+        let next = if forward {
+            (current + 1) % choices.len()
+        } else {
+            (current + choices.len() - 1) % choices.len()
+        };
+        import.resolution = choices[next];
+    }
+
     fn open_file(&mut self) -> Action {
-        if self.file_path.trim().is_empty() {
+        if self.file_path.value().trim().is_empty() {
             return Action::ErrorMessage("File path is required".to_string());
         }
 
-        let path = expand_tilde(self.file_path.trim());
+        let path = expand_path(self.file_path.value().trim());
         let content = match std::fs::read_to_string(&path) {
             Ok(c) => c,
             Err(e) => {
-                return Action::ErrorMessage(format!("Failed to read {}: {}", path, e));
+                return Action::ErrorMessage(format!("Failed to read {}: {}", path.display(), e));
             }
         };
 
-        match AppConfig::import_profiles(&content) {
-            Ok(profiles) => {
-                self.parsed_profiles = profiles.into_iter().map(|p| (p, true)).collect();
+        let detected = match ForeignFormat::detect(Path::new(&path), &content) {
+            Some(format) => SourceFormat::Foreign(format),
+            None => SourceFormat::Loom,
+        };
+        self.format_cursor = SourceFormat::ALL
+            .iter()
+            .position(|f| *f == detected)
+            .unwrap_or(0);
+        // Only loom's own export is encrypted
+        self.phase = if AppConfig::is_encrypted_profiles(&content) {
+            self.format_cursor = 0;
+            self.passphrase.clear();
+            Phase::Passphrase
+        } else {
+            Phase::SourceFormat
+        };
+        self.content = content;
+        Action::None
+    }
+
+    fn parse_content(&mut self) -> Action {
+        let rows: Result<Vec<ImportRow>, String> = match SourceFormat::ALL[self.format_cursor] {
+            SourceFormat::Loom => {
+                let passphrase = Some(self.passphrase.as_str()).filter(|p| !p.is_empty());
+                AppConfig::import_profiles_with_passwords(&self.content, passphrase).map(
+                    |(profiles, mut passwords)| {
+                        profiles
+                            .into_iter()
+                            .map(|profile| ImportRow {
+                                password: passwords.remove(&profile.name),
+                                profile,
+                                warnings: Vec::new(),
+                                selected: true,
+                            })
+                            .collect()
+                    },
+                )
+            }
+            SourceFormat::Foreign(format) => format
+                .parse(&self.content)
+                .map(|profiles| {
+                    profiles
+                        .into_iter()
+                        .map(|mut foreign| ImportRow {
+                            warnings: std::mem::take(&mut foreign.warnings),
+                            profile: ConnectionProfile::from_foreign(foreign),
+                            selected: true,
+                            password: None,
+                        })
+                        .collect()
+                })
+                .map_err(|e| e.to_string()),
+        };
+
+        match rows {
+            Ok(rows) => {
+                self.parsed_profiles = rows;
                 self.cursor = 0;
                 self.phase = Phase::SelectProfiles;
                 Action::None
@@ -153,20 +401,44 @@ impl ProfileImportDialog {
         }
     }
 
-    fn submit(&mut self) -> Action {
-        let selected: Vec<ConnectionProfile> = self
+    /// Leave the selection step: straight to the import when nothing
+    /// clashes with a saved profile, else to the conflict step.
+    fn check_conflicts(&mut self) -> Action {
+        let (selected, passwords): (Vec<ConnectionProfile>, Vec<Option<String>>) = self
             .parsed_profiles
             .iter()
-            .filter(|(_, sel)| *sel)
-            .map(|(p, _)| p.clone())
-            .collect();
+            .filter(|row| row.selected)
+            .map(|row| (row.profile.clone(), row.password.clone()))
+            .unzip();
 
         if selected.is_empty() {
             return Action::ErrorMessage("No profiles selected".to_string());
         }
 
+        let conflicts = import_conflicts(&self.existing, &selected);
+        self.imports = selected
+            .into_iter()
+            .zip(conflicts)
+            .zip(passwords)
+            .map(|((profile, conflict), password)| ProfileImport {
+                profile,
+                conflict,
+                resolution: ImportResolution::default(),
+                password,
+            })
+            .collect();
+
+        if self.conflicting().next().is_none() {
+            return self.submit();
+        }
+        self.conflict_cursor = 0;
+        self.phase = Phase::Conflicts;
+        Action::None
+    }
+
+    fn submit(&mut self) -> Action {
         self.hide();
-        Action::ConnMgrImportExecute(selected)
+        Action::ConnMgrImportExecute(std::mem::take(&mut self.imports))
     }
 
     pub fn render(&self, frame: &mut Frame, full: Rect) {
@@ -180,16 +452,20 @@ impl ProfileImportDialog {
         let block = Block::default()
             .title(" Import Profiles ")
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
 
         match self.phase {
             Phase::FilePath => self.render_filepath(frame, inner),
+            Phase::SourceFormat => self.render_format(frame, inner),
+            Phase::Passphrase => self.render_passphrase(frame, inner),
             Phase::SelectProfiles => self.render_select(frame, inner),
+            Phase::Conflicts => self.render_conflicts(frame, inner),
         }
+        self.guard.render(frame, inner, &self.theme);
     }
 
     fn render_filepath(&self, frame: &mut Frame, area: Rect) {
@@ -200,23 +476,85 @@ impl ProfileImportDialog {
         .split(area);
 
         let lines = vec![
-            Line::from(Span::styled("File path:", self.theme.header)),
+            Line::from(Span::styled("File path:", self.theme.header())),
             Line::from(vec![
-                Span::styled(&self.file_path, self.theme.normal),
-                Span::styled("_", self.theme.command_prompt),
+                Span::styled(self.file_path.value(), self.theme.normal()),
+                Span::styled("_", self.theme.command_prompt()),
             ]),
         ];
         frame.render_widget(Paragraph::new(lines), layout[0]);
 
         let hints = Paragraph::new(Line::from(Span::styled(
-            "Enter:open file  Esc:cancel",
-            self.theme.dimmed,
+            "Tab:complete  Enter:open file  Esc:cancel",
+            self.theme.dimmed(),
+        )));
+        frame.render_widget(hints, layout[1]);
+    }
+
+    fn render_passphrase(&self, frame: &mut Frame, area: Rect) {
+        let layout = Layout::vertical([
+            Constraint::Length(2), // Passphrase
+            Constraint::Min(1),    // Hints
+        ])
+        .split(area);
+
+        let lines = vec![
+            Line::from(Span::styled(
+                "The file is encrypted. Passphrase:",
+                self.theme.header(),
+            )),
+            Line::from(vec![
+                Span::styled(
+                    "*".repeat(self.passphrase.chars().count()),
+                    self.theme.normal(),
+                ),
+                Span::styled("_", self.theme.command_prompt()),
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "Enter:decrypt  Esc:back  Ctrl+C:cancel",
+            self.theme.dimmed(),
+        )));
+        frame.render_widget(hints, layout[1]);
+    }
+
+    fn render_format(&self, frame: &mut Frame, area: Rect) {
+        let layout = Layout::vertical([
+            Constraint::Length(SourceFormat::ALL.len() as u16 + 1), // Format list
+            Constraint::Min(1),                                     // Hints
+        ])
+        .split(area);
+
+        let mut lines = vec![Line::from(Span::styled(
+            "File format:",
+            self.theme.header(),
+        ))];
+        for (i, format) in SourceFormat::ALL.iter().enumerate() {
+            let is_cursor = i == self.format_cursor;
+            let (prefix, style) = if is_cursor {
+                ("> ", self.theme.selected().add_modifier(Modifier::BOLD))
+            } else {
+                ("  ", self.theme.normal())
+            };
+            lines.push(Line::from(Span::styled(
+                format!("{}{}", prefix, format.label()),
+                style,
+            )));
+        }
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "j/k:choose  Enter:read profiles  Esc:back  q:cancel",
+            self.theme.dimmed(),
         )));
         frame.render_widget(hints, layout[1]);
     }
 
     fn render_select(&self, frame: &mut Frame, area: Rect) {
-        let list_height = self.parsed_profiles.len().max(1) as u16 + 1; // +1 for label
+        let warning_lines: usize = self.parsed_profiles.iter().map(|r| r.warnings.len()).sum();
+        let list_height = (self.parsed_profiles.len() + warning_lines).max(1) as u16 + 1; // +1 for label
         let layout = Layout::vertical([
             Constraint::Length(list_height), // Profile list
             Constraint::Min(1),              // Hints
@@ -225,18 +563,19 @@ impl ProfileImportDialog {
 
         let mut lines = vec![Line::from(Span::styled(
             format!("Found {} profile(s):", self.parsed_profiles.len()),
-            self.theme.header,
+            self.theme.header(),
         ))];
 
-        for (i, (profile, selected)) in self.parsed_profiles.iter().enumerate() {
-            let marker = if *selected { "[x] " } else { "[ ] " };
+        for (i, row) in self.parsed_profiles.iter().enumerate() {
+            let profile = &row.profile;
+            let marker = if row.selected { "[x] " } else { "[ ] " };
             let is_cursor = i == self.cursor;
             let style = if is_cursor {
-                self.theme.selected.add_modifier(Modifier::BOLD)
-            } else if *selected {
-                self.theme.normal
+                self.theme.selected().add_modifier(Modifier::BOLD)
+            } else if row.selected {
+                self.theme.normal()
             } else {
-                self.theme.dimmed
+                self.theme.dimmed()
             };
             let prefix = if is_cursor { "> " } else { "  " };
             let label = format!("{} ({}:{})", profile.name, profile.host, profile.port);
@@ -244,13 +583,174 @@ impl ProfileImportDialog {
                 format!("{}{}{}", prefix, marker, label),
                 style,
             )));
+            for warning in &row.warnings {
+                lines.push(Line::from(Span::styled(
+                    format!("      ! {}", warning),
+                    self.theme.warning(),
+                )));
+            }
         }
         frame.render_widget(Paragraph::new(lines), layout[0]);
 
         let hints = Paragraph::new(Line::from(Span::styled(
-            "Space:toggle  a:all  Enter:import  Esc:back",
-            self.theme.dimmed,
+            "Space:toggle  a:all  Enter:import  Esc:back  q:cancel",
+            self.theme.dimmed(),
         )));
         frame.render_widget(hints, layout[1]);
     }
+
+    fn render_conflicts(&self, frame: &mut Frame, area: Rect) {
+        let count = self.conflicting().count();
+        let layout = Layout::vertical([
+            Constraint::Length(count as u16 * 2 + 1), // Conflict list
+            Constraint::Min(1),                       // Hints
+        ])
+        .split(area);
+
+        let mut lines = vec![Line::from(Span::styled(
+            format!("{} profile(s) clash with saved ones:", count),
+            self.theme.header(),
+        ))];
+
+        for (i, import) in self.conflicting().enumerate() {
+            let Some(conflict) = import.conflict else {
+                continue;
+            };
+            let saved = self
+                .existing
+                .get(conflict.index())
+                .map(|p| p.name.as_str())
+                .unwrap_or_default();
+            let reason = match conflict {
+                ImportConflict::SameName(_) => "same name as existing profile".to_string(),
+                ImportConflict::LooksLike(_) => format!("looks like existing profile '{}'", saved),
+            };
+            let is_cursor = i == self.conflict_cursor;
+            let (prefix, style) = if is_cursor {
+                ("> ", self.theme.selected().add_modifier(Modifier::BOLD))
+            } else {
+                ("  ", self.theme.normal())
+            };
+            lines.push(Line::from(vec![
+                Span::styled(format!("{}{}", prefix, import.profile.name), style),
+                Span::styled(format!(" — {}", reason), self.theme.warning()),
+            ]));
+            lines.push(Line::from(Span::styled(
+                format!("      < {} >", import.resolution.label()),
+                style,
+            )));
+        }
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "j/k:move  h/l:choose  Enter:import  Esc:back  q:cancel",
+            self.theme.dimmed(),
+        )));
+        frame.render_widget(hints, layout[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn open_dialog() -> (ProfileImportDialog, tempfile::NamedTempFile) {
+        let file = tempfile::Builder::new().suffix(".txt").tempfile().unwrap();
+        std::fs::write(file.path(), "ldap://a.example\nldaps://b.example\n").unwrap();
+        let mut dialog = ProfileImportDialog::new(Theme::load("dark"));
+        assert!(matches!(dialog.show_file(&[], file.path()), Action::None));
+        assert_eq!(dialog.phase, Phase::SourceFormat);
+        (dialog, file)
+    }
+
+    #[test]
+    fn test_esc_goes_back_and_q_cancels() {
+        let (mut dialog, _file) = open_dialog();
+        dialog.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(dialog.phase, Phase::SelectProfiles);
+
+        dialog.handle_key_event(key(KeyCode::Esc));
+        assert_eq!(dialog.phase, Phase::SourceFormat);
+        assert!(dialog.visible);
+
+        // q cancels from here, after asking
+        dialog.handle_key_event(key(KeyCode::Char('q')));
+        assert!(dialog.guard.is_asking());
+        dialog.handle_key_event(key(KeyCode::Char('n')));
+        assert_eq!(dialog.phase, Phase::SourceFormat);
+        dialog.handle_key_event(key(KeyCode::Char('q')));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('y'))),
+            Action::ClosePopup
+        ));
+        assert!(!dialog.visible);
+    }
+
+    #[test]
+    fn test_esc_on_untouched_path_closes() {
+        let mut dialog = ProfileImportDialog::new(Theme::load("dark"));
+        dialog.show(&[]);
+        // q is part of the path here
+        dialog.handle_key_event(key(KeyCode::Char('q')));
+        assert_eq!(dialog.file_path.value(), "profiles.tomlq");
+        dialog.handle_key_event(key(KeyCode::Backspace));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Esc)),
+            Action::ClosePopup
+        ));
+
+        // A typed path is kept unless the discard is confirmed
+        let mut dialog = ProfileImportDialog::new(Theme::load("dark")).with_confirm_discard(true);
+        dialog.show(&[]);
+        dialog.handle_key_event(key(KeyCode::Char('x')));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Esc)),
+            Action::None
+        ));
+        assert!(dialog.visible);
+        dialog.handle_key_event(key(KeyCode::Esc));
+        assert_eq!(dialog.file_path.value(), "profiles.tomlx");
+    }
+
+    #[test]
+    fn test_encrypted_file_asks_for_passphrase() {
+        let profiles = AppConfig::import_profiles(
+            "[[connections]]\nname = \"prod\"\nhost = \"ldap.example.com\"\n",
+        )
+        .unwrap();
+        let passwords = std::collections::BTreeMap::from([("prod".to_string(), "pw".to_string())]);
+        let content =
+            AppConfig::export_profiles_with_passwords(&profiles, &passwords, Some("secret"))
+                .unwrap();
+        let file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+        std::fs::write(file.path(), content).unwrap();
+
+        let mut dialog = ProfileImportDialog::new(Theme::load("dark"));
+        dialog.show_file(&[], file.path());
+        assert_eq!(dialog.phase, Phase::Passphrase);
+        // q is part of the passphrase
+        for c in "secreq".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+        assert_eq!(dialog.phase, Phase::Passphrase);
+
+        dialog.handle_key_event(key(KeyCode::Backspace));
+        dialog.handle_key_event(key(KeyCode::Char('t')));
+        dialog.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(dialog.phase, Phase::SelectProfiles);
+        let Action::ConnMgrImportExecute(imports) = dialog.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected an import");
+        };
+        assert_eq!(imports[0].password.as_deref(), Some("pw"));
+    }
 }
@@ -0,0 +1,182 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::components::tree_panel::PROTECTED_MARKER;
+use crate::theme::Theme;
+
+/// Typed confirmation before writing to a protected entry: the user has to
+/// type the entry's RDN, so a stray Enter or `y` cannot go through.
+pub struct ProtectedWriteDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    dn: String,
+    pattern: String,
+    input: String,
+    pending: Option<Box<Action>>,
+}
+
+impl ProtectedWriteDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Protected Entry", theme.clone()).with_size(60, 40),
+            theme,
+            dn: String::new(),
+            pattern: String::new(),
+            input: String::new(),
+            pending: None,
+        }
+    }
+
+    /// Ask before running `action` against `dn`, which `pattern` protects.
+    pub fn show(&mut self, dn: String, pattern: String, action: Action) {
+        self.dn = dn;
+        self.pattern = pattern;
+        self.input.clear();
+        self.pending = Some(Box::new(action));
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+        self.pending = None;
+    }
+
+    /// What has to be typed to confirm.
+    fn expected(&self) -> &str {
+        loom_core::dn::rdn(&self.dn)
+    }
+
+    fn input_matches(&self) -> bool {
+        loom_core::dn::normalize(&self.input) == loom_core::dn::normalize(self.expected())
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Enter => {
+                if !self.input_matches() {
+                    return Action::ErrorMessage(format!(
+                        "Type {} to confirm the change",
+                        self.expected()
+                    ));
+                }
+                let dn = self.dn.clone();
+                match self.pending.take() {
+                    Some(action) => {
+                        self.hide();
+                        Action::ProtectedWriteConfirmed { dn, action }
+                    }
+                    None => Action::None,
+                }
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let input_style = if self.input_matches() {
+            self.theme.success()
+        } else {
+            self.theme.normal()
+        };
+        let lines = vec![
+            Line::from(Span::styled(
+                format!("{} This entry is protected", PROTECTED_MARKER),
+                self.theme.warning(),
+            )),
+            Line::from(""),
+            Line::from(Span::styled(self.dn.clone(), self.theme.header())),
+            Line::from(Span::styled(
+                format!("Matched by: {}", self.pattern),
+                self.theme.dimmed(),
+            )),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("Type ", self.theme.normal()),
+                Span::styled(self.expected().to_string(), self.theme.header()),
+                Span::styled(" to go ahead:", self.theme.normal()),
+            ]),
+            Line::from(vec![
+                Span::styled(self.input.clone(), input_style),
+                Span::styled("_", self.theme.command_prompt()),
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), layout[0]);
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                "Enter:confirm  Esc:cancel",
+                self.theme.dimmed(),
+            ))),
+            layout[1],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_requires_typed_rdn() {
+        let mut dialog = ProtectedWriteDialog::new(Theme::load("dark"));
+        dialog.show(
+            "cn=admin,dc=example,dc=com".to_string(),
+            "cn=admin,**".to_string(),
+            Action::DeleteEntry("cn=admin,dc=example,dc=com".to_string()),
+        );
+        // Enter alone, or a `y`, does not confirm
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+        dialog.handle_key_event(key(KeyCode::Char('y')));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+        dialog.handle_key_event(key(KeyCode::Backspace));
+
+        for c in "CN = Admin".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::ProtectedWriteConfirmed { dn, action } => {
+                assert_eq!(dn, "cn=admin,dc=example,dc=com");
+                assert!(matches!(*action, Action::DeleteEntry(_)));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+        assert!(!dialog.visible);
+    }
+}
@@ -0,0 +1,451 @@
+use std::collections::VecDeque;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::action::{Action, ActiveLayout, ConnectionId};
+use crate::theme::Theme;
+use crate::widgets::fuzzy_input::{FuzzyFilter, FuzzyMatch};
+
+/// Kind of place a switcher row jumps to, shown as a badge.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TargetCategory {
+    Layout,
+    Tab,
+    Recent,
+    Dialog,
+}
+
+impl TargetCategory {
+    pub fn badge(&self) -> &'static str {
+        match self {
+            TargetCategory::Layout => "layout",
+            TargetCategory::Tab => "tab",
+            TargetCategory::Recent => "recent",
+            TargetCategory::Dialog => "dialog",
+        }
+    }
+}
+
+/// Where a switcher row jumps to. The app checks the target still exists
+/// when it is picked, since tabs can close while the switcher is open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JumpTarget {
+    Layout(ActiveLayout),
+    Tab(ConnectionId),
+    /// An entry on a connection tab.
+    Entry(ConnectionId, String),
+    /// The search results panel, hidden but still holding results.
+    SearchResults,
+    LogPanel,
+}
+
+/// One row of the quick switcher.
+#[derive(Debug, Clone)]
+pub struct SwitchTarget {
+    pub category: TargetCategory,
+    pub label: String,
+    /// Secondary text, e.g. the tab an entry belongs to.
+    pub detail: String,
+    pub target: JumpTarget,
+}
+
+impl SwitchTarget {
+    pub fn new(category: TargetCategory, label: impl Into<String>, target: JumpTarget) -> Self {
+        Self {
+            category,
+            label: label.into(),
+            detail: String::new(),
+            target,
+        }
+    }
+
+    pub fn with_detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = detail.into();
+        self
+    }
+}
+
+/// Anything that offers places to jump to. Components implement this and
+/// the app gathers targets from all of them when the switcher opens.
+pub trait JumpTargetProvider {
+    fn jump_targets(&self) -> Vec<SwitchTarget>;
+}
+
+/// Entries most recently shown in the detail panel, newest first.
+#[derive(Debug, Default)]
+pub struct RecentEntries {
+    entries: VecDeque<RecentEntry>,
+}
+
+#[derive(Debug)]
+struct RecentEntry {
+    conn_id: ConnectionId,
+    tab_label: String,
+    dn: String,
+}
+
+impl RecentEntries {
+    const CAPACITY: usize = 20;
+
+    /// Record a visit, moving an earlier visit of the same entry to the front.
+    pub fn push(&mut self, conn_id: ConnectionId, tab_label: &str, dn: &str) {
+        self.entries
+            .retain(|e| !(e.conn_id == conn_id && e.dn.eq_ignore_ascii_case(dn)));
+        self.entries.push_front(RecentEntry {
+            conn_id,
+            tab_label: tab_label.to_string(),
+            dn: dn.to_string(),
+        });
+        self.entries.truncate(Self::CAPACITY);
+    }
+
+    /// Forget an entry, e.g. after it was deleted.
+    pub fn remove(&mut self, conn_id: ConnectionId, dn: &str) {
+        self.entries
+            .retain(|e| !(e.conn_id == conn_id && e.dn.eq_ignore_ascii_case(dn)));
+    }
+
+    /// Forget every entry of a closed tab.
+    pub fn remove_tab(&mut self, conn_id: ConnectionId) {
+        self.entries.retain(|e| e.conn_id != conn_id);
+    }
+}
+
+impl JumpTargetProvider for RecentEntries {
+    fn jump_targets(&self) -> Vec<SwitchTarget> {
+        self.entries
+            .iter()
+            .map(|e| {
+                SwitchTarget::new(
+                    TargetCategory::Recent,
+                    e.dn.clone(),
+                    JumpTarget::Entry(e.conn_id, e.dn.clone()),
+                )
+                .with_detail(e.tab_label.clone())
+            })
+            .collect()
+    }
+}
+
+/// Ctrl+P-style popup listing every place the user can jump to.
+pub struct QuickSwitcher {
+    pub visible: bool,
+    input: String,
+    cursor: usize,
+    targets: Vec<SwitchTarget>,
+    /// "badge label detail" per target, matched against the input.
+    haystacks: Vec<String>,
+    filtered: Vec<FuzzyMatch>,
+    selected_idx: usize,
+    fuzzy: FuzzyFilter,
+    theme: Theme,
+}
+
+impl QuickSwitcher {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            input: String::new(),
+            cursor: 0,
+            targets: Vec::new(),
+            haystacks: Vec::new(),
+            filtered: Vec::new(),
+            selected_idx: 0,
+            fuzzy: FuzzyFilter::new(),
+            theme,
+        }
+    }
+
+    /// Open the switcher over the given targets, in the order given.
+    pub fn show(&mut self, targets: Vec<SwitchTarget>) {
+        self.input.clear();
+        self.cursor = 0;
+        self.haystacks = targets
+            .iter()
+            .map(|t| format!("{} {} {}", t.category.badge(), t.label, t.detail))
+            .collect();
+        self.targets = targets;
+        self.refilter();
+        self.visible = true;
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.targets.clear();
+        self.haystacks.clear();
+        self.filtered.clear();
+    }
+
+    fn refilter(&mut self) {
+        // The sort is stable, so equal scores keep provider order
+        self.filtered = self.fuzzy.filter(&self.input, &self.haystacks);
+        self.selected_idx = 0;
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if !self.visible {
+            return Action::None;
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Enter => match self.filtered.get(self.selected_idx) {
+                Some(fm) => {
+                    let target = self.targets[fm.index].target.clone();
+                    self.hide();
+                    Action::QuickSwitch(target)
+                }
+                None => Action::None,
+            },
+            KeyCode::Up => {
+                self.selected_idx = self.selected_idx.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Char('p') if ctrl => {
+                self.selected_idx = self.selected_idx.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down => {
+                self.select_next();
+                Action::None
+            }
+            KeyCode::Char('n') if ctrl => {
+                self.select_next();
+                Action::None
+            }
+            KeyCode::Backspace => {
+                if let Some(c) = self.input[..self.cursor].chars().next_back() {
+                    self.cursor -= c.len_utf8();
+                    self.input.remove(self.cursor);
+                    self.refilter();
+                }
+                Action::None
+            }
+            KeyCode::Left => {
+                if let Some(c) = self.input[..self.cursor].chars().next_back() {
+                    self.cursor -= c.len_utf8();
+                }
+                Action::None
+            }
+            KeyCode::Right => {
+                if let Some(c) = self.input[self.cursor..].chars().next() {
+                    self.cursor += c.len_utf8();
+                }
+                Action::None
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.input.insert(self.cursor, c);
+                self.cursor += c.len_utf8();
+                self.refilter();
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn select_next(&mut self) {
+        if self.selected_idx + 1 < self.filtered.len() {
+            self.selected_idx += 1;
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let popup_width = (full.width as u32 * 60 / 100).clamp(40, 100) as u16;
+        let popup_height = (full.height as u32 * 50 / 100).clamp(10, 30) as u16;
+
+        let x = full.x + (full.width.saturating_sub(popup_width)) / 2;
+        let y = full.y + (full.height.saturating_sub(popup_height)) / 4;
+        let area = Rect::new(x, y, popup_width, popup_height).intersection(full);
+
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(" Go To ")
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if inner.height < 4 {
+            return;
+        }
+
+        let layout = Layout::vertical([
+            Constraint::Length(1), // input
+            Constraint::Length(1), // match count
+            Constraint::Min(1),    // list
+            Constraint::Length(1), // hints
+        ])
+        .split(inner);
+
+        let (before, after) = self.input.split_at(self.cursor);
+        let mut after_chars = after.chars();
+        let at_cursor = after_chars
+            .next()
+            .map(|c| c.to_string())
+            .unwrap_or_else(|| "_".to_string());
+        let input_line = Line::from(vec![
+            Span::styled("> ", self.theme.header()),
+            Span::styled(before, self.theme.normal()),
+            Span::styled(at_cursor, self.theme.selected()),
+            Span::styled(after_chars.as_str(), self.theme.normal()),
+        ]);
+        frame.render_widget(Paragraph::new(input_line), layout[0]);
+
+        let count_line = Line::from(Span::styled(
+            format!("{}/{} places", self.filtered.len(), self.targets.len()),
+            self.theme.dimmed(),
+        ));
+        frame.render_widget(Paragraph::new(count_line), layout[1]);
+
+        let list_area = layout[2];
+        let visible_count = list_area.height as usize;
+        let scroll_offset = if self.selected_idx >= visible_count {
+            self.selected_idx - visible_count + 1
+        } else {
+            0
+        };
+
+        let items: Vec<ListItem> = self
+            .filtered
+            .iter()
+            .skip(scroll_offset)
+            .take(visible_count)
+            .enumerate()
+            .map(|(display_idx, fm)| {
+                let target = &self.targets[fm.index];
+                let highlighted = display_idx + scroll_offset == self.selected_idx;
+                let (style, dim) = if highlighted {
+                    (self.theme.selected(), self.theme.selected())
+                } else {
+                    (self.theme.normal(), self.theme.dimmed())
+                };
+                let mut spans = vec![
+                    Span::styled(format!("{:<8}", target.category.badge()), dim),
+                    Span::styled(target.label.clone(), style),
+                ];
+                if !target.detail.is_empty() {
+                    spans.push(Span::styled(format!("  {}", target.detail), dim));
+                }
+                ListItem::new(Line::from(spans))
+            })
+            .collect();
+
+        let mut list_state = ListState::default();
+        if !self.filtered.is_empty() {
+            list_state.select(Some(self.selected_idx - scroll_offset));
+        }
+        frame.render_stateful_widget(List::new(items), list_area, &mut list_state);
+
+        let hint = Line::from(Span::styled(
+            "Enter:Go  Up/Down:Select  Esc:Cancel",
+            self.theme.dimmed(),
+        ));
+        frame.render_widget(Paragraph::new(hint), layout[3]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn targets() -> Vec<SwitchTarget> {
+        vec![
+            SwitchTarget::new(
+                TargetCategory::Layout,
+                "Profiles",
+                JumpTarget::Layout(ActiveLayout::Profiles),
+            ),
+            SwitchTarget::new(TargetCategory::Tab, "prod", JumpTarget::Tab(1)),
+            SwitchTarget::new(TargetCategory::Tab, "staging", JumpTarget::Tab(2)),
+            SwitchTarget::new(
+                TargetCategory::Recent,
+                "uid=alice,ou=people,dc=example,dc=com",
+                JumpTarget::Entry(2, "uid=alice,ou=people,dc=example,dc=com".to_string()),
+            )
+            .with_detail("staging"),
+        ]
+    }
+
+    #[test]
+    fn test_empty_query_keeps_provider_order() {
+        let mut switcher = QuickSwitcher::new(Theme::load("dark"));
+        switcher.show(targets());
+        assert_eq!(switcher.filtered.len(), 4);
+        switcher.handle_key_event(key(KeyCode::Down));
+        assert!(matches!(
+            switcher.handle_key_event(key(KeyCode::Enter)),
+            Action::QuickSwitch(JumpTarget::Tab(1))
+        ));
+        assert!(!switcher.visible);
+    }
+
+    #[test]
+    fn test_filter_and_pick() {
+        let mut switcher = QuickSwitcher::new(Theme::load("dark"));
+        switcher.show(targets());
+        for c in "alice".chars() {
+            switcher.handle_key_event(key(KeyCode::Char(c)));
+        }
+        assert_eq!(switcher.filtered.len(), 1);
+        match switcher.handle_key_event(key(KeyCode::Enter)) {
+            Action::QuickSwitch(JumpTarget::Entry(2, dn)) => {
+                assert!(dn.starts_with("uid=alice"))
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_badge_is_searchable_and_no_match_is_inert() {
+        let mut switcher = QuickSwitcher::new(Theme::load("dark"));
+        switcher.show(targets());
+        for c in "tab".chars() {
+            switcher.handle_key_event(key(KeyCode::Char(c)));
+        }
+        assert!(switcher.filtered.len() >= 2);
+        for c in "zzzz".chars() {
+            switcher.handle_key_event(key(KeyCode::Char(c)));
+        }
+        assert!(switcher.filtered.is_empty());
+        assert!(matches!(
+            switcher.handle_key_event(key(KeyCode::Enter)),
+            Action::None
+        ));
+        assert!(switcher.visible);
+    }
+
+    #[test]
+    fn test_recent_entries_dedup_and_cap() {
+        let mut recent = RecentEntries::default();
+        for i in 0..25 {
+            recent.push(1, "prod", &format!("cn=e{},dc=example,dc=com", i));
+        }
+        recent.push(1, "prod", "CN=e24,dc=example,dc=com");
+        let targets = recent.jump_targets();
+        assert_eq!(targets.len(), RecentEntries::CAPACITY);
+        assert_eq!(targets[0].label, "CN=e24,dc=example,dc=com");
+        assert_eq!(targets[1].label, "cn=e23,dc=example,dc=com");
+
+        recent.push(2, "staging", "cn=x,dc=example,dc=com");
+        recent.remove_tab(1);
+        assert_eq!(recent.jump_targets().len(), 1);
+    }
+}
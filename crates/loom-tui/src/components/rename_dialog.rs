@@ -0,0 +1,325 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+
+use loom_core::dn;
+
+use crate::action::Action;
+use crate::components::delete_dialog::Children;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Which field is being edited.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RenameField {
+    Rdn,
+    DeleteOldRdn,
+    NewParent,
+}
+
+/// Dialog for a ModifyDN: a new RDN for the entry and, optionally, a new
+/// parent to move it under.
+pub struct RenameDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    dn: String,
+    children: Children,
+    active_field: RenameField,
+    rdn: String,
+    delete_old_rdn: bool,
+    new_parent: String,
+    /// Why the last submit was refused.
+    error: Option<String>,
+}
+
+impl RenameDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Rename / Move", theme.clone()).with_size(60, 40),
+            theme,
+            dn: String::new(),
+            children: Children::None,
+            active_field: RenameField::Rdn,
+            rdn: String::new(),
+            delete_old_rdn: true,
+            new_parent: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn show(&mut self, dn: String, children: Children) {
+        self.rdn = dn::rdn(&dn).to_string();
+        self.dn = dn;
+        self.children = children;
+        self.active_field = RenameField::Rdn;
+        self.delete_old_rdn = true;
+        self.new_parent.clear();
+        self.error = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    fn moving(&self) -> bool {
+        !self.new_parent.trim().is_empty()
+    }
+
+    fn submit(&mut self) -> Action {
+        let parent = Some(self.new_parent.clone()).filter(|_| self.moving());
+        let new_dn = match dn::renamed(&self.dn, &self.rdn, parent.as_deref()) {
+            Ok(new_dn) => new_dn,
+            Err(e) => {
+                self.error = Some(e.message);
+                return Action::None;
+            }
+        };
+        if dn::normalize(&new_dn) == dn::normalize(&self.dn) {
+            self.error = Some("The DN would not change".to_string());
+            return Action::None;
+        }
+        self.hide();
+        Action::RenameEntry {
+            dn: self.dn.clone(),
+            new_rdn: self.rdn.trim().to_string(),
+            delete_old_rdn: self.delete_old_rdn,
+            new_superior: parent.map(|p| p.trim().to_string()),
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Tab | KeyCode::Down => {
+                self.active_field = match self.active_field {
+                    RenameField::Rdn => RenameField::DeleteOldRdn,
+                    RenameField::DeleteOldRdn => RenameField::NewParent,
+                    RenameField::NewParent => RenameField::Rdn,
+                };
+                Action::None
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                self.active_field = match self.active_field {
+                    RenameField::Rdn => RenameField::NewParent,
+                    RenameField::DeleteOldRdn => RenameField::Rdn,
+                    RenameField::NewParent => RenameField::DeleteOldRdn,
+                };
+                Action::None
+            }
+            KeyCode::Enter => self.submit(),
+            KeyCode::Char(' ') if self.active_field == RenameField::DeleteOldRdn => {
+                self.delete_old_rdn = !self.delete_old_rdn;
+                Action::None
+            }
+            KeyCode::Backspace => {
+                if let Some(buffer) = self.active_buffer_mut() {
+                    buffer.pop();
+                    self.error = None;
+                }
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                if let Some(buffer) = self.active_buffer_mut() {
+                    buffer.push(c);
+                    self.error = None;
+                }
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn active_buffer_mut(&mut self) -> Option<&mut String> {
+        match self.active_field {
+            RenameField::Rdn => Some(&mut self.rdn),
+            RenameField::NewParent => Some(&mut self.new_parent),
+            RenameField::DeleteOldRdn => None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([
+            Constraint::Length(2), // Current DN
+            Constraint::Length(2), // RDN
+            Constraint::Length(1), // Delete old RDN
+            Constraint::Length(1), // Spacer
+            Constraint::Length(2), // New parent
+            Constraint::Min(1),    // Warning or error
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(Span::styled("Entry:", self.theme.dimmed())),
+                Line::from(Span::styled(self.dn.as_str(), self.theme.normal())),
+            ]),
+            layout[0],
+        );
+        self.render_text_field(frame, layout[1], "New RDN", &self.rdn, RenameField::Rdn);
+
+        let check_style = if self.active_field == RenameField::DeleteOldRdn {
+            self.theme.selected().add_modifier(Modifier::BOLD)
+        } else {
+            self.theme.dimmed()
+        };
+        let marker = if self.delete_old_rdn { "[x] " } else { "[ ] " };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                format!("{}Delete old RDN attribute value", marker),
+                check_style,
+            ))),
+            layout[2],
+        );
+        self.render_text_field(
+            frame,
+            layout[4],
+            "New parent DN (empty to stay)",
+            &self.new_parent,
+            RenameField::NewParent,
+        );
+
+        let note = match (&self.error, self.children) {
+            (Some(error), _) => Some(Span::styled(error.as_str(), self.theme.error())),
+            (None, Children::None) => None,
+            (None, _) if self.moving() => Some(Span::styled(
+                "This entry may have children. Some servers refuse to move a \
+                 subtree; their error is shown if this one does.",
+                self.theme.warning(),
+            )),
+            _ => None,
+        };
+        if let Some(note) = note {
+            frame.render_widget(
+                Paragraph::new(Line::from(note)).wrap(Wrap { trim: true }),
+                layout[5],
+            );
+        }
+
+        let hints = if self.active_field == RenameField::DeleteOldRdn {
+            "Space:toggle  Tab:next field  Enter:rename  Esc:cancel"
+        } else {
+            "Tab:next field  Enter:rename  Esc:cancel"
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(hints, self.theme.dimmed()))),
+            layout[6],
+        );
+    }
+
+    fn render_text_field(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        label: &str,
+        value: &str,
+        field: RenameField,
+    ) {
+        let is_active = self.active_field == field;
+        let (label_style, value_style) = if is_active {
+            (self.theme.header(), self.theme.normal())
+        } else {
+            (self.theme.dimmed(), self.theme.dimmed())
+        };
+        let mut value_line = vec![Span::styled(value.to_string(), value_style)];
+        if is_active {
+            value_line.push(Span::styled("_", self.theme.command_prompt()));
+        }
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(Span::styled(format!("{}:", label), label_style)),
+                Line::from(value_line),
+            ]),
+            area,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    fn type_text(dialog: &mut RenameDialog, text: &str) {
+        for c in text.chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_rename_and_move() {
+        let mut dialog = RenameDialog::new(Theme::load("dark"));
+        dialog.show(
+            "cn=Old,ou=People,dc=example,dc=com".to_string(),
+            Children::None,
+        );
+        assert_eq!(dialog.rdn, "cn=Old");
+
+        // Unchanged is refused
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::None
+        ));
+        assert!(dialog.error.is_some());
+
+        for _ in 0..3 {
+            dialog.handle_key_event(key(KeyCode::Backspace));
+        }
+        type_text(&mut dialog, "New");
+        dialog.handle_key_event(key(KeyCode::Tab));
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+        dialog.handle_key_event(key(KeyCode::Tab));
+        type_text(&mut dialog, "ou=Staff,dc=example,dc=com");
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::RenameEntry {
+                dn,
+                new_rdn,
+                delete_old_rdn,
+                new_superior,
+            } => {
+                assert_eq!(dn, "cn=Old,ou=People,dc=example,dc=com");
+                assert_eq!(new_rdn, "cn=New");
+                assert!(!delete_old_rdn);
+                assert_eq!(new_superior.as_deref(), Some("ou=Staff,dc=example,dc=com"));
+            }
+            other => panic!("expected RenameEntry, got {:?}", other),
+        }
+        assert!(!dialog.visible);
+    }
+
+    #[test]
+    fn test_invalid_parent_is_reported() {
+        let mut dialog = RenameDialog::new(Theme::load("dark"));
+        dialog.show("cn=a,dc=example,dc=com".to_string(), Children::Some);
+        dialog.handle_key_event(key(KeyCode::BackTab));
+        type_text(&mut dialog, "not a dn");
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::None
+        ));
+        assert!(dialog.visible);
+        assert!(dialog.error.is_some());
+
+        // Editing clears the error
+        dialog.handle_key_event(key(KeyCode::Backspace));
+        assert!(dialog.error.is_none());
+    }
+}
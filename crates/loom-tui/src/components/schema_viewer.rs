@@ -2,136 +2,242 @@ use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::Modifier;
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Cell, Clear, Row, Table, TableState, Tabs};
+use ratatui::widgets::{Block, BorderType, Borders, List, ListItem, ListState, Paragraph, Tabs};
 use ratatui::Frame;
 
-use crate::action::Action;
-use crate::components::popup::Popup;
+use crate::action::{Action, ActiveLayout, ConnectionId, FocusTarget};
 use crate::theme::Theme;
-use loom_core::schema::{AttributeTypeInfo, ObjectClassInfo, ObjectClassKind, SchemaCache};
+use loom_core::schema::{AttributeSyntax, ObjectClassKind, SchemaCache};
 
-/// Which tab is active in the schema viewer.
+/// Which list the schema layout shows.
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum SchemaTab {
     ObjectClasses,
     AttributeTypes,
 }
 
-/// A full-screen schema browser overlay.
+/// A name in the definition pane that Enter opens.
+#[derive(Debug, Clone, PartialEq)]
+enum Link {
+    Class(String),
+    Attribute(String),
+}
+
+/// The Schema layout: object classes or attribute types of the active
+/// connection on the left, the selected definition on the right, with
+/// MUST and MAY resolved through the superior chain.
 pub struct SchemaViewer {
-    pub visible: bool,
-    popup: Popup,
     theme: Theme,
     tab: SchemaTab,
-    // Object classes
-    oc_items: Vec<ObjectClassInfo>,
-    oc_state: TableState,
-    // Attribute types
-    at_items: Vec<AttributeTypeInfo>,
-    at_state: TableState,
-    // Filter
+    /// The connection whose schema is shown.
+    source: Option<ConnectionId>,
+    schema: Option<SchemaCache>,
+    /// Canonical names, sorted.
+    classes: Vec<String>,
+    attributes: Vec<String>,
+    class_state: ListState,
+    attribute_state: ListState,
     filter: String,
     filter_active: bool,
+    /// Selected line of the definition pane; only lines with a link can be
+    /// selected.
+    definition_state: ListState,
+    /// Definitions left by following links, for Backspace.
+    history: Vec<(SchemaTab, String)>,
+    /// Attribute to show once the schema has loaded.
+    pending: Option<String>,
 }
 
 impl SchemaViewer {
     pub fn new(theme: Theme) -> Self {
         Self {
-            visible: false,
-            popup: Popup::new("Schema Browser", theme.clone()).with_size(80, 80),
             theme,
             tab: SchemaTab::ObjectClasses,
-            oc_items: Vec::new(),
-            oc_state: TableState::default(),
-            at_items: Vec::new(),
-            at_state: TableState::default(),
+            source: None,
+            schema: None,
+            classes: Vec::new(),
+            attributes: Vec::new(),
+            class_state: ListState::default(),
+            attribute_state: ListState::default(),
             filter: String::new(),
             filter_active: false,
+            definition_state: ListState::default(),
+            history: Vec::new(),
+            pending: None,
         }
     }
 
-    pub fn show(&mut self, schema: &SchemaCache) {
-        // Deduplicate by OID (schema entries are stored per-name)
-        let mut seen_oc = std::collections::HashSet::new();
-        self.oc_items = schema
-            .object_classes
-            .values()
-            .filter(|oc| seen_oc.insert(oc.oid.clone()))
-            .cloned()
-            .collect();
-        self.oc_items.sort_by(|a, b| {
-            a.names
-                .first()
-                .unwrap_or(&a.oid)
-                .cmp(b.names.first().unwrap_or(&b.oid))
+    /// Show the schema of connection `source`, unless it already is.
+    /// `None` or an empty schema means it has not loaded (yet).
+    pub fn sync(&mut self, source: ConnectionId, schema: Option<&SchemaCache>) {
+        let schema = schema.filter(|s| !s.attribute_types.is_empty());
+        if self.source == Some(source) && self.schema.is_some() == schema.is_some() {
+            return;
+        }
+        self.source = Some(source);
+        self.schema = schema.cloned();
+        self.classes = schema.map_or_else(Vec::new, |s| {
+            s.object_class_list()
+                .iter()
+                .map(|oc| oc.names.first().unwrap_or(&oc.oid).clone())
+                .collect()
         });
-
-        let mut seen_at = std::collections::HashSet::new();
-        self.at_items = schema
-            .attribute_types
-            .values()
-            .filter(|at| seen_at.insert(at.oid.clone()))
-            .cloned()
-            .collect();
-        self.at_items.sort_by(|a, b| {
-            a.names
-                .first()
-                .unwrap_or(&a.oid)
-                .cmp(b.names.first().unwrap_or(&b.oid))
+        self.attributes = schema.map_or_else(Vec::new, |s| {
+            s.attribute_type_list()
+                .iter()
+                .map(|at| at.names.first().unwrap_or(&at.oid).clone())
+                .collect()
         });
+        self.filter.clear();
+        self.filter_active = false;
+        self.history.clear();
+        self.class_state
+            .select((!self.classes.is_empty()).then_some(0));
+        self.attribute_state
+            .select((!self.attributes.is_empty()).then_some(0));
+        self.select_first_link();
+        if self.schema.is_some() {
+            if let Some(name) = self.pending.take() {
+                self.reveal(&name);
+            }
+        }
+    }
 
-        self.oc_state.select(if self.oc_items.is_empty() {
-            None
-        } else {
-            Some(0)
-        });
-        self.at_state.select(if self.at_items.is_empty() {
-            None
-        } else {
-            Some(0)
-        });
+    /// Show the schema again on the next `sync`, after it was reloaded.
+    pub fn invalidate(&mut self) {
+        self.source = None;
+    }
 
+    /// The connection whose schema is shown.
+    pub fn source(&self) -> Option<ConnectionId> {
+        self.source
+    }
+
+    /// Whether typed keys go to the filter.
+    pub fn is_filtering(&self) -> bool {
+        self.filter_active
+    }
+
+    /// Select attribute type `name`, or the object class of that name when
+    /// no attribute has it, clearing the filter. Before the schema has
+    /// loaded, remember it for then. False when the schema has neither.
+    pub fn reveal(&mut self, name: &str) -> bool {
+        let Some(schema) = &self.schema else {
+            self.pending = Some(name.to_string());
+            return true;
+        };
+        let (tab, canonical) = if let Some(at) = schema.get_attribute_type(name) {
+            let canonical = at.names.first().unwrap_or(&at.oid).clone();
+            (SchemaTab::AttributeTypes, canonical)
+        } else if let Some(oc) = schema.get_object_class(name) {
+            let canonical = oc.names.first().unwrap_or(&oc.oid).clone();
+            (SchemaTab::ObjectClasses, canonical)
+        } else {
+            return false;
+        };
         self.filter.clear();
         self.filter_active = false;
-        self.visible = true;
-        self.popup.show();
+        self.show(tab, &canonical);
+        true
+    }
+
+    /// Switch to `tab` and select `name` in it.
+    fn show(&mut self, tab: SchemaTab, name: &str) {
+        self.tab = tab;
+        let index = self.filtered().iter().position(|n| *n == name);
+        if let Some(index) = index {
+            self.list_state().select(Some(index));
+        }
+        self.select_first_link();
+    }
+
+    /// Names of the current list that match the filter.
+    fn filtered(&self) -> Vec<&String> {
+        let names = match self.tab {
+            SchemaTab::ObjectClasses => &self.classes,
+            SchemaTab::AttributeTypes => &self.attributes,
+        };
+        if self.filter.is_empty() {
+            return names.iter().collect();
+        }
+        let filter = self.filter.to_lowercase();
+        names
+            .iter()
+            .filter(|name| name.to_lowercase().contains(&filter) || self.describes(name, &filter))
+            .collect()
+    }
+
+    /// Whether the description of `name` contains `filter` (lowercase).
+    fn describes(&self, name: &str, filter: &str) -> bool {
+        let Some(schema) = &self.schema else {
+            return false;
+        };
+        let description = match self.tab {
+            SchemaTab::ObjectClasses => schema
+                .get_object_class(name)
+                .and_then(|oc| oc.description.as_ref()),
+            SchemaTab::AttributeTypes => schema
+                .get_attribute_type(name)
+                .and_then(|at| at.description.as_ref()),
+        };
+        description.is_some_and(|d| d.to_lowercase().contains(filter))
     }
 
-    pub fn hide(&mut self) {
-        self.visible = false;
-        self.popup.hide();
+    fn list_state(&mut self) -> &mut ListState {
+        match self.tab {
+            SchemaTab::ObjectClasses => &mut self.class_state,
+            SchemaTab::AttributeTypes => &mut self.attribute_state,
+        }
+    }
+
+    fn selected(&self) -> Option<String> {
+        let state = match self.tab {
+            SchemaTab::ObjectClasses => &self.class_state,
+            SchemaTab::AttributeTypes => &self.attribute_state,
+        };
+        self.filtered()
+            .get(state.selected()?)
+            .map(|name| name.to_string())
     }
 
-    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+    /// Handle a key while the list has focus.
+    pub fn handle_list_key(&mut self, key: KeyEvent) -> Action {
         if self.filter_active {
             return self.handle_filter_key(key);
         }
-
         match key.code {
-            KeyCode::Esc | KeyCode::Char('q') => {
-                self.hide();
-                Action::ClosePopup
-            }
-            KeyCode::Tab => {
-                self.tab = match self.tab {
-                    SchemaTab::ObjectClasses => SchemaTab::AttributeTypes,
-                    SchemaTab::AttributeTypes => SchemaTab::ObjectClasses,
-                };
-                Action::None
-            }
-            KeyCode::Up | KeyCode::Char('k') => {
-                self.move_selection(-1);
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.clamp_selection();
                 Action::None
             }
-            KeyCode::Down | KeyCode::Char('j') => {
-                self.move_selection(1);
+            KeyCode::Esc | KeyCode::Char('q') => Action::SwitchLayout(ActiveLayout::Browser),
+            KeyCode::Char('o') | KeyCode::Char('a') | KeyCode::Left | KeyCode::Right => {
+                let tab = match key.code {
+                    KeyCode::Char('o') => SchemaTab::ObjectClasses,
+                    KeyCode::Char('a') => SchemaTab::AttributeTypes,
+                    _ if self.tab == SchemaTab::ObjectClasses => SchemaTab::AttributeTypes,
+                    _ => SchemaTab::ObjectClasses,
+                };
+                if tab != self.tab {
+                    self.tab = tab;
+                    self.clamp_selection();
+                }
                 Action::None
             }
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::Home => self.move_selection(i32::MIN / 2),
+            KeyCode::End => self.move_selection(i32::MAX / 2),
             KeyCode::Char('/') => {
                 self.filter_active = true;
-                self.filter.clear();
                 Action::None
             }
+            KeyCode::Enter if self.selected().is_some() => {
+                Action::FocusPanel(FocusTarget::SchemaDefinition)
+            }
             _ => Action::None,
         }
     }
@@ -141,281 +247,487 @@ impl SchemaViewer {
             KeyCode::Esc => {
                 self.filter_active = false;
                 self.filter.clear();
+            }
+            KeyCode::Enter => self.filter_active = false,
+            KeyCode::Backspace => {
+                self.filter.pop();
+            }
+            KeyCode::Char(c) => self.filter.push(c),
+            _ => return Action::None,
+        }
+        self.clamp_selection();
+        Action::None
+    }
+
+    fn move_selection(&mut self, delta: i32) -> Action {
+        let len = self.filtered().len();
+        if len > 0 {
+            let state = self.list_state();
+            let current = state.selected().unwrap_or(0) as i64;
+            let next = (current + delta as i64).clamp(0, len as i64 - 1) as usize;
+            state.select(Some(next));
+            self.select_first_link();
+        }
+        Action::None
+    }
+
+    /// Keep the selection inside the (re)filtered list.
+    fn clamp_selection(&mut self) {
+        let len = self.filtered().len();
+        let state = self.list_state();
+        state.select(match state.selected() {
+            _ if len == 0 => None,
+            Some(i) => Some(i.min(len - 1)),
+            None => Some(0),
+        });
+        self.select_first_link();
+    }
+
+    /// Handle a key while the definition pane has focus.
+    pub fn handle_definition_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => Action::FocusPanel(FocusTarget::SchemaList),
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.move_link(-1);
                 Action::None
             }
-            KeyCode::Enter => {
-                self.filter_active = false;
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.move_link(1);
                 Action::None
             }
-            KeyCode::Backspace => {
-                self.filter.pop();
+            KeyCode::Enter => {
+                let lines = self.definition();
+                let link = self
+                    .definition_state
+                    .selected()
+                    .and_then(|i| lines.get(i))
+                    .and_then(|(_, link)| link.clone());
+                if let Some(link) = link {
+                    self.follow(link);
+                }
                 Action::None
             }
-            KeyCode::Char(c) => {
-                self.filter.push(c);
+            KeyCode::Backspace => {
+                if let Some((tab, name)) = self.history.pop() {
+                    self.filter.clear();
+                    self.show(tab, &name);
+                }
                 Action::None
             }
             _ => Action::None,
         }
     }
 
-    fn move_selection(&mut self, delta: i32) {
-        let len = match self.tab {
-            SchemaTab::ObjectClasses => self.filtered_oc_count(),
-            SchemaTab::AttributeTypes => self.filtered_at_count(),
-        };
-
-        if len == 0 {
+    /// Open the definition a link names, remembering the current one.
+    fn follow(&mut self, link: Link) {
+        let Some(current) = self.selected() else {
             return;
+        };
+        let (tab, name) = match link {
+            Link::Class(name) => (SchemaTab::ObjectClasses, name),
+            Link::Attribute(name) => (SchemaTab::AttributeTypes, name),
+        };
+        let canonical = self.schema.as_ref().and_then(|s| match tab {
+            SchemaTab::ObjectClasses => s
+                .get_object_class(&name)
+                .map(|oc| oc.names.first().unwrap_or(&oc.oid).clone()),
+            SchemaTab::AttributeTypes => s
+                .get_attribute_type(&name)
+                .map(|at| at.names.first().unwrap_or(&at.oid).clone()),
+        });
+        if let Some(canonical) = canonical {
+            self.history.push((self.tab, current));
+            self.filter.clear();
+            self.show(tab, &canonical);
         }
+    }
 
-        let state = match self.tab {
-            SchemaTab::ObjectClasses => &mut self.oc_state,
-            SchemaTab::AttributeTypes => &mut self.at_state,
+    /// Select the next (`1`) or previous (`-1`) line with a link.
+    fn move_link(&mut self, direction: i32) {
+        let lines = self.definition();
+        let current = self.definition_state.selected();
+        let next = if direction > 0 {
+            let start = current.map_or(0, |i| i + 1);
+            (start..lines.len()).find(|&i| lines[i].1.is_some())
+        } else {
+            let end = current.unwrap_or(0);
+            (0..end).rev().find(|&i| lines[i].1.is_some())
         };
+        if next.is_some() {
+            self.definition_state.select(next);
+        }
+    }
 
-        let current = state.selected().unwrap_or(0) as i32;
-        let next = (current + delta).clamp(0, len as i32 - 1) as usize;
-        state.select(Some(next));
+    fn select_first_link(&mut self) {
+        let first = self
+            .definition()
+            .iter()
+            .position(|(_, link)| link.is_some());
+        self.definition_state = ListState::default();
+        self.definition_state.select(first);
     }
 
-    fn filtered_oc_count(&self) -> usize {
-        if self.filter.is_empty() {
-            self.oc_items.len()
-        } else {
-            let f = self.filter.to_lowercase();
-            self.oc_items
-                .iter()
-                .filter(|oc| {
-                    oc.names.iter().any(|n| n.to_lowercase().contains(&f))
-                        || oc
-                            .description
-                            .as_deref()
-                            .unwrap_or("")
-                            .to_lowercase()
-                            .contains(&f)
-                })
-                .count()
+    /// Lines of the selected definition, each with the name it links to.
+    fn definition(&self) -> Vec<(Line<'static>, Option<Link>)> {
+        let (Some(schema), Some(name)) = (&self.schema, self.selected()) else {
+            return Vec::new();
+        };
+        match self.tab {
+            SchemaTab::ObjectClasses => self.class_definition(schema, &name),
+            SchemaTab::AttributeTypes => self.attribute_definition(schema, &name),
         }
     }
 
-    fn filtered_at_count(&self) -> usize {
-        if self.filter.is_empty() {
-            self.at_items.len()
-        } else {
-            let f = self.filter.to_lowercase();
-            self.at_items
-                .iter()
-                .filter(|at| {
-                    at.names.iter().any(|n| n.to_lowercase().contains(&f))
-                        || at
-                            .description
-                            .as_deref()
-                            .unwrap_or("")
-                            .to_lowercase()
-                            .contains(&f)
-                })
-                .count()
+    fn field(&self, label: &str, value: impl Into<String>) -> Line<'static> {
+        Line::from(vec![
+            Span::styled(format!("{:<10}", label), self.theme.dimmed()),
+            Span::styled(value.into(), self.theme.normal()),
+        ])
+    }
+
+    fn heading(&self, text: String) -> (Line<'static>, Option<Link>) {
+        (Line::from(Span::styled(text, self.theme.header())), None)
+    }
+
+    fn class_definition(
+        &self,
+        schema: &SchemaCache,
+        name: &str,
+    ) -> Vec<(Line<'static>, Option<Link>)> {
+        let Some(oc) = schema.get_object_class(name) else {
+            return Vec::new();
+        };
+        let mut lines = vec![self.heading(name.to_string())];
+        lines.push((self.field("OID", oc.oid.clone()), None));
+        if oc.names.len() > 1 {
+            lines.push((self.field("Aliases", oc.names[1..].join(", ")), None));
+        }
+        let kind = match oc.kind {
+            ObjectClassKind::Abstract => "ABSTRACT",
+            ObjectClassKind::Structural => "STRUCTURAL",
+            ObjectClassKind::Auxiliary => "AUXILIARY",
+        };
+        lines.push((self.field("Kind", kind), None));
+        for sup in &oc.superiors {
+            lines.push((
+                self.field("Superior", sup.clone()),
+                Some(Link::Class(sup.clone())),
+            ));
+        }
+        if let Some(desc) = &oc.description {
+            lines.push((self.field("Desc", desc.clone()), None));
         }
+
+        let attrs = schema.class_attributes(name);
+        for (title, required) in [("MUST", true), ("MAY", false)] {
+            let listed: Vec<_> = attrs.iter().filter(|a| a.required == required).collect();
+            lines.push((Line::from(""), None));
+            lines.push(self.heading(format!("{} ({})", title, listed.len())));
+            for attr in listed {
+                let mut spans = vec![Span::styled(
+                    format!("  {}", attr.name),
+                    if required {
+                        self.theme.warning()
+                    } else {
+                        self.theme.normal()
+                    },
+                )];
+                // Inherited through a superior
+                if !attr.declared_by.eq_ignore_ascii_case(name) {
+                    spans.push(Span::styled(
+                        format!("  from {}", attr.declared_by),
+                        self.theme.dimmed(),
+                    ));
+                }
+                lines.push((Line::from(spans), Some(Link::Attribute(attr.name.clone()))));
+            }
+        }
+        lines
     }
 
-    pub fn render(&mut self, frame: &mut Frame, full: Rect) {
-        if !self.visible {
-            return;
+    fn attribute_definition(
+        &self,
+        schema: &SchemaCache,
+        name: &str,
+    ) -> Vec<(Line<'static>, Option<Link>)> {
+        let Some(at) = schema.get_attribute_type(name) else {
+            return Vec::new();
+        };
+        let mut lines = vec![self.heading(name.to_string())];
+        lines.push((self.field("OID", at.oid.clone()), None));
+        if at.names.len() > 1 {
+            lines.push((self.field("Aliases", at.names[1..].join(", ")), None));
+        }
+        if let Some(desc) = &at.description {
+            lines.push((self.field("Desc", desc.clone()), None));
+        }
+        if let Some(sup) = &at.superior {
+            lines.push((
+                self.field("Superior", sup.clone()),
+                Some(Link::Attribute(sup.clone())),
+            ));
+        }
+        lines.push((self.field("Syntax", syntax_label(&at.syntax)), None));
+        let values = if at.single_value {
+            "single"
+        } else {
+            "multiple"
+        };
+        lines.push((self.field("Values", values), None));
+        if at.no_user_modification {
+            lines.push((self.field("Modify", "server only"), None));
         }
 
-        let area = self.popup.centered_area(full);
-        frame.render_widget(Clear, area);
+        let users = schema.classes_using(name);
+        lines.push((Line::from(""), None));
+        lines.push(self.heading(format!("Used by ({})", users.len())));
+        for oc in users {
+            let class = oc.names.first().unwrap_or(&oc.oid).clone();
+            let required = oc.must.iter().any(|a| {
+                schema
+                    .get_attribute_type(a)
+                    .map_or(a.eq_ignore_ascii_case(name), |t| t.oid == at.oid)
+            });
+            let mut spans = vec![Span::styled(format!("  {}", class), self.theme.normal())];
+            if required {
+                spans.push(Span::styled("  MUST", self.theme.warning()));
+            }
+            lines.push((Line::from(spans), Some(Link::Class(class))));
+        }
+        lines
+    }
 
-        let block = Block::default()
-            .title(" Schema Browser ")
+    fn block(&self, title: &str, focused: bool) -> Block<'static> {
+        let mut block = Block::default()
+            .title(format!(" {} ", title))
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(if focused {
+                self.theme.border_focused()
+            } else {
+                self.theme.border()
+            });
+        if focused {
+            block = block.border_type(BorderType::Double);
+        }
+        block
+    }
 
+    /// Draw the class or attribute list.
+    pub fn render_list(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let block = self.block("Schema", focused);
         let inner = block.inner(area);
         frame.render_widget(block, area);
 
-        // Layout: tabs (1) | table (flex) | filter/status (1)
+        if self.schema.is_none() {
+            let text = if self.source.is_some() {
+                "Loading schema\u{2026}"
+            } else {
+                "No connection"
+            };
+            frame.render_widget(Paragraph::new(text).style(self.theme.dimmed()), inner);
+            return;
+        }
+
+        // Tabs (1) | list | filter (1)
         let layout = Layout::vertical([
             Constraint::Length(1),
-            Constraint::Min(3),
+            Constraint::Min(1),
             Constraint::Length(1),
         ])
         .split(inner);
 
-        // Tab bar
-        let tab_titles = vec![
-            Line::from(Span::raw("Object Classes")),
-            Line::from(Span::raw("Attribute Types")),
+        let titles = vec![
+            Line::from(format!("Classes ({})", self.classes.len())),
+            Line::from(format!("Attributes ({})", self.attributes.len())),
         ];
         let selected_tab = match self.tab {
             SchemaTab::ObjectClasses => 0,
             SchemaTab::AttributeTypes => 1,
         };
-        let tabs = Tabs::new(tab_titles)
+        let tabs = Tabs::new(titles)
             .select(selected_tab)
-            .style(self.theme.tab_inactive)
-            .highlight_style(self.theme.tab_active);
+            .style(self.theme.tab_inactive())
+            .highlight_style(self.theme.tab_active());
         frame.render_widget(tabs, layout[0]);
 
-        // Table
-        match self.tab {
-            SchemaTab::ObjectClasses => self.render_oc_table(frame, layout[1]),
-            SchemaTab::AttributeTypes => self.render_at_table(frame, layout[1]),
-        }
+        let items: Vec<ListItem> = self
+            .filtered()
+            .into_iter()
+            .map(|name| ListItem::new(Span::styled(name.clone(), self.theme.normal())))
+            .collect();
+        let list =
+            List::new(items).highlight_style(self.theme.selected().add_modifier(Modifier::BOLD));
+        let state = match self.tab {
+            SchemaTab::ObjectClasses => &mut self.class_state,
+            SchemaTab::AttributeTypes => &mut self.attribute_state,
+        };
+        frame.render_stateful_widget(list, layout[1], state);
 
-        // Filter line
         let filter_line = if self.filter_active {
             Line::from(vec![
-                Span::styled("/ ", self.theme.command_prompt),
-                Span::styled(&self.filter, self.theme.normal),
-                Span::styled("_", self.theme.command_prompt),
+                Span::styled("/ ", self.theme.command_prompt()),
+                Span::styled(self.filter.clone(), self.theme.normal()),
+                Span::styled("_", self.theme.command_prompt()),
             ])
         } else if !self.filter.is_empty() {
             Line::from(vec![
-                Span::styled("Filter: ", self.theme.dimmed),
-                Span::styled(&self.filter, self.theme.normal),
-                Span::styled(" (/ to edit, Esc to clear)", self.theme.dimmed),
+                Span::styled("Filter: ", self.theme.dimmed()),
+                Span::styled(self.filter.clone(), self.theme.normal()),
+                Span::styled(" (Esc to clear)", self.theme.dimmed()),
             ])
         } else {
             Line::from(Span::styled(
-                "Tab:switch  \u{2191}/\u{2193}:navigate  /:filter  q:close",
-                self.theme.dimmed,
+                "o/a:classes/attributes  /:filter  Esc:back",
+                self.theme.dimmed(),
             ))
         };
-        frame.render_widget(ratatui::widgets::Paragraph::new(filter_line), layout[2]);
+        frame.render_widget(Paragraph::new(filter_line), layout[2]);
     }
 
-    fn render_oc_table(&mut self, frame: &mut Frame, area: Rect) {
-        let filter_lower = self.filter.to_lowercase();
-        let filtered: Vec<&ObjectClassInfo> = if self.filter.is_empty() {
-            self.oc_items.iter().collect()
+    /// Draw the selected definition.
+    pub fn render_definition(&mut self, frame: &mut Frame, area: Rect, focused: bool) {
+        let block = self.block("Definition", focused);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+        let items: Vec<ListItem> = self
+            .definition()
+            .into_iter()
+            .map(|(line, _)| ListItem::new(line))
+            .collect();
+        let highlight = if focused {
+            self.theme.selected().add_modifier(Modifier::BOLD)
         } else {
-            self.oc_items
-                .iter()
-                .filter(|oc| {
-                    oc.names
-                        .iter()
-                        .any(|n| n.to_lowercase().contains(&filter_lower))
-                        || oc
-                            .description
-                            .as_deref()
-                            .unwrap_or("")
-                            .to_lowercase()
-                            .contains(&filter_lower)
-                })
-                .collect()
+            Default::default()
         };
+        let list = List::new(items).highlight_style(highlight);
+        frame.render_stateful_widget(list, layout[0], &mut self.definition_state);
+
+        if focused {
+            let hint = if self.history.is_empty() {
+                "Enter:open  Esc:list"
+            } else {
+                "Enter:open  Backspace:back  Esc:list"
+            };
+            frame.render_widget(
+                Paragraph::new(Span::styled(hint, self.theme.dimmed())),
+                layout[1],
+            );
+        }
+    }
+}
 
-        let rows: Vec<Row> = filtered
-            .iter()
-            .map(|oc| {
-                let name = oc.names.first().map(|s| s.as_str()).unwrap_or(&oc.oid);
-                let kind = match oc.kind {
-                    ObjectClassKind::Abstract => "ABSTRACT",
-                    ObjectClassKind::Structural => "STRUCTURAL",
-                    ObjectClassKind::Auxiliary => "AUXILIARY",
-                };
-                let sup = oc.superior.as_deref().unwrap_or("-");
-                let must = oc.must.join(", ");
-                let may_str = if oc.may.len() > 3 {
-                    format!("{}... (+{})", oc.may[..3].join(", "), oc.may.len() - 3)
-                } else {
-                    oc.may.join(", ")
-                };
+/// How an attribute syntax reads in the definition pane.
+fn syntax_label(syntax: &AttributeSyntax) -> String {
+    match syntax {
+        AttributeSyntax::String => "String".to_string(),
+        AttributeSyntax::DirectoryString => "Directory String".to_string(),
+        AttributeSyntax::Integer => "Integer".to_string(),
+        AttributeSyntax::Boolean => "Boolean".to_string(),
+        AttributeSyntax::Dn => "DN".to_string(),
+        AttributeSyntax::OctetString => "Octet String".to_string(),
+        AttributeSyntax::GeneralizedTime => "Generalized Time".to_string(),
+        AttributeSyntax::TelephoneNumber => "Telephone Number".to_string(),
+        AttributeSyntax::Oid => "OID".to_string(),
+        AttributeSyntax::Other(oid) => oid.clone(),
+    }
+}
 
-                Row::new(vec![
-                    Cell::from(Span::styled(name, self.theme.normal)),
-                    Cell::from(Span::styled(kind, self.theme.dimmed)),
-                    Cell::from(Span::styled(sup, self.theme.dimmed)),
-                    Cell::from(Span::styled(must, self.theme.warning)),
-                    Cell::from(Span::styled(may_str, self.theme.normal)),
-                ])
-            })
-            .collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use loom_core::offline::OfflineDirectory;
 
-        let widths = [
-            Constraint::Percentage(25),
-            Constraint::Percentage(12),
-            Constraint::Percentage(13),
-            Constraint::Percentage(25),
-            Constraint::Percentage(25),
-        ];
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
 
-        let table = Table::new(rows, widths)
-            .header(
-                Row::new(vec![
-                    Cell::from(Span::styled("Name", self.theme.header)),
-                    Cell::from(Span::styled("Kind", self.theme.header)),
-                    Cell::from(Span::styled("Superior", self.theme.header)),
-                    Cell::from(Span::styled("MUST", self.theme.header)),
-                    Cell::from(Span::styled("MAY", self.theme.header)),
-                ])
-                .style(self.theme.header),
-            )
-            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
-
-        frame.render_stateful_widget(table, area, &mut self.oc_state);
-    }
-
-    fn render_at_table(&mut self, frame: &mut Frame, area: Rect) {
-        let filter_lower = self.filter.to_lowercase();
-        let filtered: Vec<&AttributeTypeInfo> = if self.filter.is_empty() {
-            self.at_items.iter().collect()
-        } else {
-            self.at_items
-                .iter()
-                .filter(|at| {
-                    at.names
-                        .iter()
-                        .any(|n| n.to_lowercase().contains(&filter_lower))
-                        || at
-                            .description
-                            .as_deref()
-                            .unwrap_or("")
-                            .to_lowercase()
-                            .contains(&filter_lower)
-                })
-                .collect()
-        };
+    fn viewer() -> SchemaViewer {
+        let schema = OfflineDirectory::load_embedded().schema().clone();
+        let mut viewer = SchemaViewer::new(Theme::load("dark"));
+        viewer.sync(1, Some(&schema));
+        viewer
+    }
 
-        let rows: Vec<Row> = filtered
-            .iter()
-            .map(|at| {
-                let name = at.names.first().map(|s| s.as_str()).unwrap_or(&at.oid);
-                let syntax = format!("{:?}", at.syntax);
-                let sv = if at.single_value { "Yes" } else { "No" };
-                let desc = at.description.as_deref().unwrap_or("-");
-
-                Row::new(vec![
-                    Cell::from(Span::styled(name, self.theme.normal)),
-                    Cell::from(Span::styled(syntax, self.theme.dimmed)),
-                    Cell::from(Span::styled(sv, self.theme.dimmed)),
-                    Cell::from(Span::styled(desc, self.theme.normal)),
-                ])
-            })
-            .collect();
+    fn text(line: &Line) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
 
-        let widths = [
-            Constraint::Percentage(25),
-            Constraint::Percentage(20),
-            Constraint::Percentage(10),
-            Constraint::Percentage(45),
-        ];
+    #[test]
+    fn test_class_definition_includes_inherited_attributes() {
+        let mut viewer = viewer();
+        assert!(viewer.reveal("inetOrgPerson"));
+        assert_eq!(viewer.tab, SchemaTab::ObjectClasses);
+        let lines: Vec<String> = viewer.definition().iter().map(|(l, _)| text(l)).collect();
+        assert_eq!(lines[0], "inetOrgPerson");
+        assert!(lines.iter().any(|l| l == "Superior  organizationalPerson"));
+        // cn and sn come from person, two superiors up
+        assert!(lines.iter().any(|l| l == "  cn  from person"));
+        assert!(lines.iter().any(|l| l == "  sn  from person"));
+        assert!(lines.iter().any(|l| l == "  mail"));
+    }
+
+    #[test]
+    fn test_follow_links_and_go_back() {
+        let mut viewer = viewer();
+        viewer.reveal("inetOrgPerson");
+        // The first link is the superior
+        viewer.handle_definition_key(key(KeyCode::Enter));
+        assert_eq!(viewer.selected().as_deref(), Some("organizationalPerson"));
+
+        // Down to an attribute and open it
+        viewer.move_link(1);
+        let lines = viewer.definition();
+        let (_, link) = &lines[viewer.definition_state.selected().unwrap()];
+        assert!(matches!(link, Some(Link::Attribute(_))));
+        viewer.handle_definition_key(key(KeyCode::Enter));
+        assert_eq!(viewer.tab, SchemaTab::AttributeTypes);
+
+        viewer.handle_definition_key(key(KeyCode::Backspace));
+        assert_eq!(viewer.selected().as_deref(), Some("organizationalPerson"));
+        viewer.handle_definition_key(key(KeyCode::Backspace));
+        assert_eq!(viewer.selected().as_deref(), Some("inetOrgPerson"));
+    }
+
+    #[test]
+    fn test_reveal_waits_for_the_schema() {
+        let mut viewer = SchemaViewer::new(Theme::load("dark"));
+        viewer.sync(1, None);
+        assert!(viewer.reveal("MAIL"));
+        assert!(viewer.selected().is_none());
+
+        let schema = OfflineDirectory::load_embedded().schema().clone();
+        viewer.sync(1, Some(&schema));
+        assert_eq!(viewer.tab, SchemaTab::AttributeTypes);
+        assert_eq!(viewer.selected().as_deref(), Some("mail"));
+        let lines: Vec<String> = viewer.definition().iter().map(|(l, _)| text(l)).collect();
+        assert!(lines.iter().any(|l| l.starts_with("Used by")));
+
+        assert!(!viewer.reveal("noSuchThing"));
+    }
 
-        let table = Table::new(rows, widths)
-            .header(
-                Row::new(vec![
-                    Cell::from(Span::styled("Name", self.theme.header)),
-                    Cell::from(Span::styled("Syntax", self.theme.header)),
-                    Cell::from(Span::styled("Single?", self.theme.header)),
-                    Cell::from(Span::styled("Description", self.theme.header)),
-                ])
-                .style(self.theme.header),
-            )
-            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
-
-        frame.render_stateful_widget(table, area, &mut self.at_state);
+    #[test]
+    fn test_filter_and_escape() {
+        let mut viewer = viewer();
+        viewer.handle_list_key(key(KeyCode::Char('/')));
+        assert!(viewer.is_filtering());
+        for c in "person".chars() {
+            viewer.handle_list_key(key(KeyCode::Char(c)));
+        }
+        viewer.handle_list_key(key(KeyCode::Enter));
+        assert!(viewer
+            .filtered()
+            .iter()
+            .all(|n| n.to_lowercase().contains("person")));
+        // Esc clears the filter first, then leaves the layout
+        assert!(matches!(
+            viewer.handle_list_key(key(KeyCode::Esc)),
+            Action::None
+        ));
+        assert!(matches!(
+            viewer.handle_list_key(key(KeyCode::Esc)),
+            Action::SwitchLayout(ActiveLayout::Browser)
+        ));
     }
 }
@@ -1,109 +1,682 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use std::collections::BTreeSet;
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::Modifier;
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState};
+use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState, Wrap};
 use ratatui::Frame;
 
 use crate::action::Action;
+use crate::components::quick_switcher::{
+    JumpTarget, JumpTargetProvider, SwitchTarget, TargetCategory,
+};
+use crate::config::DEFAULT_RESULT_COLUMNS;
+use crate::keymap::{Command, ContextKeys, KeyContext, Keymap};
 use crate::theme::Theme;
+use crate::widgets::index_strip::IndexStrip;
+use crate::widgets::width::{display_width, fit_widths, truncate_to_width};
+use loom_core::dn::{normalize as normalize_dn, rdn_display_name};
 use loom_core::entry::LdapEntry;
+use loom_core::filter::Filter;
+use loom_core::jump::{self, TypedPrefix};
+use loom_core::search::{natural_cmp, sort_entries};
+use loom_core::tree::format_count;
+
+/// Where the search behind the results table stands.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum SearchStatus {
+    /// No search was run yet.
+    #[default]
+    Idle,
+    Running,
+    /// Every result is in the table.
+    Done,
+    Failed(String),
+}
+
+/// How the quick filter tests a loaded entry.
+#[derive(Debug, Clone)]
+enum QuickMatch {
+    /// The text parsed as an LDAP filter.
+    Filter(Filter),
+    /// Lowercase text found in the DN or any value.
+    Text(String),
+}
+
+impl QuickMatch {
+    fn new(text: &str) -> Self {
+        match Filter::parse(text) {
+            Ok(filter) => QuickMatch::Filter(filter),
+            Err(_) => QuickMatch::Text(text.to_lowercase()),
+        }
+    }
+
+    fn matches(&self, entry: &LdapEntry) -> bool {
+        match self {
+            QuickMatch::Filter(filter) => filter.matches(entry),
+            QuickMatch::Text(text) => {
+                entry.dn.to_lowercase().contains(text)
+                    || entry
+                        .attributes
+                        .values()
+                        .flatten()
+                        .any(|v| v.to_lowercase().contains(text))
+            }
+        }
+    }
+}
+
+/// The text a results column shows for `entry`: every value of the
+/// attribute joined with `; `, or the DN for a `dn` column.
+fn cell_text(entry: &LdapEntry, column: &str) -> String {
+    if column.eq_ignore_ascii_case("dn") {
+        return entry.dn.clone();
+    }
+    entry
+        .values_ci(column)
+        .map(|values| values.to_text().join("; "))
+        .unwrap_or_default()
+}
 
 /// The search results panel, shown as an overlay when a search has results.
 pub struct SearchDialog {
     pub visible: bool,
     pub filter: String,
     pub results: Vec<LdapEntry>,
+    pub status: SearchStatus,
+    /// Where the search started, for the empty-result message.
+    base_dn: String,
+    /// Configured search key, e.g. `F9`, which also closes the panel.
+    search_key: String,
     table_state: TableState,
+    /// Letters typed since `'` started jump mode.
+    jump: Option<TypedPrefix>,
+    /// Narrows the table to loaded results, typed after `f`.
+    quick_filter: String,
+    /// The quick filter is being typed.
+    quick_editing: bool,
+    /// Indices into `results` that pass the quick filter; `None` shows
+    /// them all. The table selection indexes this.
+    shown: Option<Vec<usize>>,
+    /// How many results were tested against the quick filter so far.
+    tested: usize,
+    /// Indices into `results` picked out for a multi-entry action; they
+    /// survive scrolling and the quick filter.
+    marked: BTreeSet<usize>,
+    /// Index into `results` where a `V` range started.
+    visual_anchor: Option<usize>,
+    /// Attributes shown as columns, in order.
+    columns: Vec<String>,
+    /// Column the cursor is on, which `s` sorts by.
+    column: usize,
+    /// Column the results are sorted by, and whether descending.
+    sort: Option<(usize, bool)>,
+    index_strip: bool,
+    keys: ContextKeys,
     theme: Theme,
 }
 
 impl SearchDialog {
-    pub fn new(theme: Theme) -> Self {
+    pub fn new(theme: Theme, keymap: &Keymap) -> Self {
         Self {
             visible: false,
             filter: String::new(),
             results: Vec::new(),
+            status: SearchStatus::Idle,
+            base_dn: String::new(),
+            search_key: keymap.hint("search").to_string(),
             table_state: TableState::default(),
+            jump: None,
+            quick_filter: String::new(),
+            quick_editing: false,
+            shown: None,
+            tested: 0,
+            marked: BTreeSet::new(),
+            visual_anchor: None,
+            columns: DEFAULT_RESULT_COLUMNS.map(String::from).to_vec(),
+            column: 0,
+            sort: None,
+            index_strip: false,
+            keys: keymap.context(KeyContext::Results),
             theme,
         }
     }
 
-    pub fn show_results(&mut self, filter: String, results: Vec<LdapEntry>) {
+    /// Draw the A-Z index strip along the right edge of the results.
+    pub fn with_index_strip(mut self, enabled: bool) -> Self {
+        self.index_strip = enabled;
+        self
+    }
+
+    pub fn set_index_strip(&mut self, enabled: bool) {
+        self.index_strip = enabled;
+    }
+
+    /// Use the results keys and search hint of `keymap`.
+    pub fn set_keys(&mut self, keymap: &Keymap) {
+        self.search_key = keymap.hint("search").to_string();
+        self.keys = keymap.context(KeyContext::Results);
+    }
+
+    /// Whether typed letters jump to results rather than edit the filter.
+    pub fn is_jumping(&self) -> bool {
+        self.jump.is_some()
+    }
+
+    /// Show a finished set of results, e.g. a live preview.
+    pub fn show_results(&mut self, filter: String, base_dn: &str, results: Vec<LdapEntry>) {
         self.filter = filter;
+        self.base_dn = base_dn.to_string();
         self.results = results;
-        self.table_state.select(if self.results.is_empty() {
-            None
-        } else {
-            Some(0)
-        });
+        self.status = SearchStatus::Done;
+        self.sort = None;
+        self.clear_quick_filter();
+        self.clear_marks();
+        self.reset_selection();
         self.visible = true;
     }
 
+    /// Empty the table ahead of a search whose results arrive in batches.
+    pub fn clear_results(&mut self, filter: String, base_dn: &str) {
+        self.filter = filter;
+        self.base_dn = base_dn.to_string();
+        self.results.clear();
+        self.status = SearchStatus::Running;
+        self.sort = None;
+        self.clear_quick_filter();
+        self.clear_marks();
+        self.table_state.select(None);
+    }
+
+    /// The attributes shown as columns, in order.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Show `columns` from now on, the default ones when empty. The sort
+    /// stays while its column is still shown.
+    pub fn set_columns(&mut self, columns: Vec<String>) {
+        let sorted = self.sort.map(|(c, desc)| (self.columns[c].clone(), desc));
+        self.columns = if columns.is_empty() {
+            DEFAULT_RESULT_COLUMNS.map(String::from).to_vec()
+        } else {
+            columns
+        };
+        self.column = self.column.min(self.columns.len() - 1);
+        self.sort = sorted.and_then(|(attr, desc)| self.column_of(&attr).map(|c| (c, desc)));
+    }
+
+    fn column_of(&self, attr: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(attr))
+    }
+
+    /// Mark the table as sorted by `attr`, e.g. ahead of results the
+    /// server sends in that order.
+    pub fn set_sort(&mut self, attr: &str, descending: bool) {
+        self.sort = self.column_of(attr).map(|column| (column, descending));
+    }
+
+    /// Sort the loaded results by the chosen column, ignoring case and
+    /// with numbers in order. The selection is dropped, as it refers to
+    /// positions in the table.
+    pub fn sort_loaded(&mut self) {
+        let Some((column, descending)) = self.sort else {
+            return;
+        };
+        let attr = &self.columns[column];
+        if attr.eq_ignore_ascii_case("dn") {
+            self.results.sort_by(|a, b| {
+                if descending {
+                    natural_cmp(&b.dn, &a.dn)
+                } else {
+                    natural_cmp(&a.dn, &b.dn)
+                }
+            });
+        } else {
+            sort_entries(&mut self.results, attr, descending);
+        }
+        self.clear_marks();
+        self.refilter();
+        self.reset_selection();
+    }
+
+    /// Every result of the running search is in the table.
+    pub fn complete(&mut self) {
+        if self.status == SearchStatus::Running {
+            self.status = SearchStatus::Done;
+        }
+    }
+
+    pub fn fail(&mut self, error: String) {
+        self.status = SearchStatus::Failed(error);
+    }
+
+    /// What the panel says while the table is empty.
+    pub fn empty_message(&self) -> Line<'static> {
+        let hint = |text: String| Span::styled(text, self.theme.dimmed());
+        let key = |text: &str| Span::styled(text.to_string(), self.theme.header());
+        match self.status {
+            SearchStatus::Idle => Line::from(hint(
+                "Type an LDAP filter and press Enter to search.".to_string(),
+            )),
+            SearchStatus::Running => Line::from(hint(format!(
+                "Searching {} under {}\u{2026}",
+                self.filter, self.base_dn
+            ))),
+            SearchStatus::Done => Line::from(vec![
+                hint(format!(
+                    "No entries matched {} under {} \u{2014} press ",
+                    self.filter, self.base_dn
+                )),
+                key("/"),
+                hint(" to edit the filter, ".to_string()),
+                key(&self.search_key),
+                hint(" to close".to_string()),
+            ]),
+            SearchStatus::Failed(ref error) => Line::from(vec![
+                Span::styled(format!("Search failed: {}", error), self.theme.error()),
+                hint(" \u{2014} press ".to_string()),
+                key("/"),
+                hint(" to edit the filter".to_string()),
+            ]),
+        }
+    }
+
+    /// Called after a batch was appended to `results`. The first batch
+    /// opens the panel; the selection stays where the user left it.
+    pub fn results_appended(&mut self, first_batch: bool) {
+        if first_batch {
+            self.visible = true;
+        }
+        self.test_new_results();
+        if self.table_state.selected().is_none() && self.row_count() > 0 {
+            self.table_state.select(Some(0));
+        }
+    }
+
     pub fn hide(&mut self) {
         self.visible = false;
+        self.jump = None;
+        self.quick_editing = false;
     }
 
     /// Reset the table selection to the first row (or none if empty).
     pub fn reset_selection(&mut self) {
-        self.table_state.select(if self.results.is_empty() {
-            None
-        } else {
-            Some(0)
-        });
+        self.table_state
+            .select(if self.row_count() == 0 { None } else { Some(0) });
     }
 
     pub fn has_results(&self) -> bool {
         !self.results.is_empty()
     }
 
+    /// Whether typed keys edit the quick filter.
+    pub fn is_quick_filtering(&self) -> bool {
+        self.quick_editing
+    }
+
+    /// Whether the quick filter hides any results.
+    pub fn is_filtered(&self) -> bool {
+        self.shown.is_some()
+    }
+
+    /// The results passing the quick filter, all of them without one.
+    pub fn filtered_results(&self) -> Vec<LdapEntry> {
+        self.rows().cloned().collect()
+    }
+
+    /// Rows in the table, after the quick filter.
+    pub fn row_count(&self) -> usize {
+        self.shown.as_ref().map_or(self.results.len(), Vec::len)
+    }
+
+    /// The entry shown in table row `row`.
+    fn row(&self, row: usize) -> Option<&LdapEntry> {
+        match &self.shown {
+            Some(shown) => shown.get(row).and_then(|&i| self.results.get(i)),
+            None => self.results.get(row),
+        }
+    }
+
+    /// The entries in the table, in order.
+    fn rows(&self) -> impl Iterator<Item = &LdapEntry> {
+        rows_of(&self.results, &self.shown)
+    }
+
+    /// Index into `results` of the entry under the cursor.
+    fn cursor_index(&self) -> Option<usize> {
+        let row = self.table_state.selected()?;
+        match &self.shown {
+            Some(shown) => shown.get(row).copied(),
+            None => (row < self.results.len()).then_some(row),
+        }
+    }
+
+    /// Indices into `results` that are marked or inside the `V` range.
+    fn selected_indices(&self) -> BTreeSet<usize> {
+        let mut selected = self.marked.clone();
+        if let (Some(anchor), Some(cursor)) = (self.visual_anchor, self.cursor_index()) {
+            let (from, to) = (anchor.min(cursor), anchor.max(cursor));
+            let in_range = |i: &usize| (from..=to).contains(i);
+            match &self.shown {
+                Some(shown) => selected.extend(shown.iter().copied().filter(in_range)),
+                None => selected.extend(from..=to),
+            }
+        }
+        selected
+    }
+
+    /// How many results are selected.
+    pub fn selection_count(&self) -> usize {
+        self.selected_indices().len()
+    }
+
+    /// The selected results, in result order.
+    pub fn selected_results(&self) -> Vec<LdapEntry> {
+        self.selected_indices()
+            .into_iter()
+            .filter_map(|i| self.results.get(i).cloned())
+            .collect()
+    }
+
+    /// DNs a multi-entry action works on: the selection, or else the
+    /// entry under the cursor.
+    fn target_dns(&self) -> Vec<String> {
+        let selected = self.selected_indices();
+        if selected.is_empty() {
+            self.cursor_index()
+                .and_then(|i| self.results.get(i))
+                .map(|e| vec![e.dn.clone()])
+                .unwrap_or_default()
+        } else {
+            selected
+                .into_iter()
+                .filter_map(|i| self.results.get(i).map(|e| e.dn.clone()))
+                .collect()
+        }
+    }
+
+    /// Mark the `V` range and leave visual mode.
+    fn commit_visual(&mut self) {
+        self.marked = self.selected_indices();
+        self.visual_anchor = None;
+    }
+
+    /// What a cursor move says: the growing range in visual mode.
+    fn moved(&self) -> Action {
+        if self.visual_anchor.is_some() {
+            Action::StatusMessage(self.selection_line())
+        } else {
+            Action::None
+        }
+    }
+
+    /// Drop deleted entries from the results, and the selection with them.
+    pub fn forget_results(&mut self, dns: &[String]) {
+        let gone: BTreeSet<String> = dns.iter().map(|d| normalize_dn(d)).collect();
+        let before = self.results.len();
+        self.results
+            .retain(|e| !gone.contains(&normalize_dn(&e.dn)));
+        if self.results.len() == before {
+            return;
+        }
+        self.clear_marks();
+        self.tested = 0;
+        self.refilter();
+        let rows = self.row_count();
+        if self.table_state.selected().is_some_and(|row| row >= rows) {
+            self.table_state.select(rows.checked_sub(1));
+        }
+    }
+
+    fn clear_marks(&mut self) {
+        self.marked.clear();
+        self.visual_anchor = None;
+    }
+
+    /// Status line for the selection, e.g. `5 selected`.
+    fn selection_line(&self) -> String {
+        let count = self.selection_count();
+        if self.visual_anchor.is_some() {
+            format!("-- VISUAL -- {} selected", format_count(count as u64))
+        } else {
+            format!("{} selected", format_count(count as u64))
+        }
+    }
+
+    /// Status line for the table, e.g. `34/2,193 entries (filtered)`.
+    pub fn count_line(&self) -> String {
+        let total = format_count(self.results.len() as u64);
+        if self.is_filtered() {
+            format!(
+                "{}/{} entries (filtered)",
+                format_count(self.row_count() as u64),
+                total
+            )
+        } else {
+            format!("{} entries", total)
+        }
+    }
+
+    fn clear_quick_filter(&mut self) {
+        self.quick_filter.clear();
+        self.quick_editing = false;
+        self.shown = None;
+        self.tested = 0;
+    }
+
+    /// Test every loaded result against the quick filter afresh, keeping
+    /// the selected entry selected if it still passes.
+    fn refilter(&mut self) {
+        let selected = self.table_state.selected().and_then(|row| {
+            self.shown
+                .as_ref()
+                .map_or(Some(row), |s| s.get(row).copied())
+        });
+        if self.quick_filter.trim().is_empty() {
+            self.shown = None;
+        } else {
+            self.shown = Some(Vec::new());
+            self.tested = 0;
+            self.test_new_results();
+        }
+        let row = selected.and_then(|i| match &self.shown {
+            Some(shown) => shown.iter().position(|&s| s == i),
+            None => Some(i),
+        });
+        match row {
+            Some(row) => self.table_state.select(Some(row)),
+            None => self.reset_selection(),
+        }
+    }
+
+    /// Test results that arrived since the last call.
+    fn test_new_results(&mut self) {
+        let Some(shown) = self.shown.as_mut() else {
+            return;
+        };
+        let matcher = QuickMatch::new(self.quick_filter.trim());
+        shown.extend(
+            (self.tested..self.results.len()).filter(|&i| matcher.matches(&self.results[i])),
+        );
+        self.tested = self.results.len();
+    }
+
+    /// Keys while typing the quick filter.
+    fn handle_quick_filter_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.quick_filter.clear();
+                self.quick_editing = false;
+                self.refilter();
+                return Action::StatusMessage(self.count_line());
+            }
+            KeyCode::Enter => self.quick_editing = false,
+            KeyCode::Backspace => {
+                self.quick_filter.pop();
+                self.refilter();
+                return Action::StatusMessage(self.count_line());
+            }
+            KeyCode::Char(c)
+                if !key
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                self.quick_filter.push(c);
+                self.refilter();
+                return Action::StatusMessage(self.count_line());
+            }
+            KeyCode::Up | KeyCode::Down | KeyCode::PageUp | KeyCode::PageDown => {
+                self.quick_editing = false;
+                let action = self.handle_key_event(key);
+                self.quick_editing = true;
+                return action;
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
         if !self.visible {
             return Action::None;
         }
+        if self.quick_editing {
+            return self.handle_quick_filter_key(key);
+        }
 
-        match key.code {
-            KeyCode::Up | KeyCode::Char('k') => {
+        if let Some(typed) = self.jump.as_mut() {
+            match key.code {
+                KeyCode::Char(c)
+                    if !key
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                {
+                    let jump = typed.push(c, Instant::now());
+                    let names: Vec<_> = self.rows().map(|e| rdn_display_name(&e.dn)).collect();
+                    return match jump::find(&names, self.table_state.selected(), &jump) {
+                        Some(i) => {
+                            self.table_state.select(Some(i));
+                            Action::StatusMessage(format!("Jump: {}", jump.prefix))
+                        }
+                        None => Action::StatusMessage(format!(
+                            "No result starting with '{}'",
+                            jump.prefix
+                        )),
+                    };
+                }
+                KeyCode::Esc => {
+                    self.jump = None;
+                    return Action::StatusMessage("Jump mode off".to_string());
+                }
+                // Any other key leaves jump mode and does its usual job
+                _ => self.jump = None,
+            }
+        }
+
+        if key.code == KeyCode::Esc {
+            return self.escape();
+        }
+        let Some(command) = self.keys.command(&key) else {
+            return Action::None;
+        };
+        match command {
+            Command::Filter => {
+                self.quick_editing = true;
+                Action::StatusMessage(
+                    "Quick filter: text or an LDAP filter, matched against the loaded results"
+                        .to_string(),
+                )
+            }
+            Command::Sort | Command::SortReverse if !self.results.is_empty() => {
+                // o moves to the next column, O turns the order around
+                let (column, descending) = match (self.sort, command) {
+                    (Some((column, descending)), Command::SortReverse) => (column, !descending),
+                    (None, Command::SortReverse) => (0, true),
+                    (Some((column, _)), _) => ((column + 1) % self.columns.len(), false),
+                    (None, _) => (0, false),
+                };
+                self.sort = Some((column, descending));
+                self.column = column;
+                Action::SearchSort(self.columns[column].clone(), descending)
+            }
+            Command::SortColumn if !self.results.is_empty() => {
+                if self.status == SearchStatus::Running {
+                    return Action::StatusMessage(
+                        "Results are still arriving; sort once they are in".to_string(),
+                    );
+                }
+                // Sorting by the same column again turns the order around
+                let descending = self.sort == Some((self.column, false));
+                self.sort = Some((self.column, descending));
+                self.sort_loaded();
+                Action::StatusMessage(format!(
+                    "Sorted {} results by {}{}",
+                    self.results.len(),
+                    self.columns[self.column],
+                    if descending { ", descending" } else { "" }
+                ))
+            }
+            Command::PrevColumn => {
+                self.column = self.column.saturating_sub(1);
+                Action::None
+            }
+            Command::NextColumn => {
+                self.column = (self.column + 1).min(self.columns.len() - 1);
+                Action::None
+            }
+            Command::Columns => Action::ShowResultColumns(self.columns.clone()),
+            Command::Jump => {
+                self.jump = Some(TypedPrefix::default());
+                Action::StatusMessage(
+                    "Jump: type the start of a name, again to cycle, Esc to stop".to_string(),
+                )
+            }
+            Command::Up => {
                 let i = self.table_state.selected().unwrap_or(0);
                 if i > 0 {
                     self.table_state.select(Some(i - 1));
                 }
-                Action::None
+                self.moved()
             }
-            KeyCode::Down | KeyCode::Char('j') => {
+            Command::Down => {
                 let i = self.table_state.selected().unwrap_or(0);
-                if i + 1 < self.results.len() {
+                if i + 1 < self.row_count() {
                     self.table_state.select(Some(i + 1));
                 }
-                Action::None
+                self.moved()
             }
-            KeyCode::PageUp => {
+            Command::PageUp => {
                 let i = self.table_state.selected().unwrap_or(0);
                 self.table_state.select(Some(i.saturating_sub(10)));
-                Action::None
+                self.moved()
             }
-            KeyCode::PageDown => {
+            Command::PageDown => {
                 let i = self.table_state.selected().unwrap_or(0);
-                let max = self.results.len().saturating_sub(1);
+                let max = self.row_count().saturating_sub(1);
                 self.table_state.select(Some((i + 10).min(max)));
-                Action::None
+                self.moved()
             }
-            KeyCode::Home => {
-                if !self.results.is_empty() {
+            Command::Top => {
+                if self.row_count() > 0 {
                     self.table_state.select(Some(0));
                 }
-                Action::None
+                self.moved()
             }
-            KeyCode::End => {
-                if !self.results.is_empty() {
+            Command::Bottom => {
+                if self.row_count() > 0 {
                     self.table_state
-                        .select(Some(self.results.len().saturating_sub(1)));
+                        .select(Some(self.row_count().saturating_sub(1)));
                 }
-                Action::None
+                self.moved()
             }
-            KeyCode::Enter => {
+            Command::Open => {
                 if let Some(idx) = self.table_state.selected() {
-                    if let Some(entry) = self.results.get(idx) {
+                    if let Some(entry) = self.row(idx) {
                         let dn = entry.dn.clone();
                         self.visible = false;
                         return Action::TreeSelect(dn);
@@ -111,7 +684,58 @@ impl SearchDialog {
                 }
                 Action::None
             }
-            KeyCode::Esc | KeyCode::Char('q') => {
+            Command::ToggleSelect => {
+                if self.visual_anchor.is_some() {
+                    self.commit_visual();
+                } else if let Some(i) = self.cursor_index() {
+                    if !self.marked.remove(&i) {
+                        self.marked.insert(i);
+                    }
+                }
+                Action::StatusMessage(self.selection_line())
+            }
+            Command::SelectRange => {
+                if self.visual_anchor.is_some() {
+                    self.commit_visual();
+                } else {
+                    self.visual_anchor = self.cursor_index();
+                }
+                Action::StatusMessage(self.selection_line())
+            }
+            Command::SelectAll => {
+                self.visual_anchor = None;
+                match &self.shown {
+                    Some(shown) => self.marked.extend(shown.iter().copied()),
+                    None => self.marked.extend(0..self.results.len()),
+                }
+                Action::StatusMessage(self.selection_line())
+            }
+            Command::BatchModify => {
+                let dns = self.target_dns();
+                if dns.is_empty() {
+                    Action::StatusMessage("No results to modify".to_string())
+                } else {
+                    Action::ShowBatchModifyDialog(dns)
+                }
+            }
+            Command::CopyDn => {
+                let dns = self.target_dns();
+                if dns.is_empty() {
+                    Action::StatusMessage("No results to copy".to_string())
+                } else {
+                    Action::CopyToClipboard(dns.join("\n"))
+                }
+            }
+            Command::Delete => {
+                let dns = self.target_dns();
+                if dns.is_empty() {
+                    Action::StatusMessage("No results to delete".to_string())
+                } else {
+                    Action::ConfirmDeleteEntries(dns)
+                }
+            }
+            Command::Export => Action::ShowExportDialog,
+            Command::Close => {
                 self.hide();
                 Action::ClosePopup
             }
@@ -119,10 +743,28 @@ impl SearchDialog {
         }
     }
 
+    /// Esc stops a running search first, then clears a selection, then a
+    /// kept quick filter, and closes the panel last.
+    fn escape(&mut self) -> Action {
+        if self.status == SearchStatus::Running {
+            Action::SearchStop
+        } else if !self.marked.is_empty() || self.visual_anchor.is_some() {
+            self.clear_marks();
+            Action::StatusMessage("Selection cleared".to_string())
+        } else if self.is_filtered() {
+            self.quick_filter.clear();
+            self.refilter();
+            Action::StatusMessage(self.count_line())
+        } else {
+            self.hide();
+            Action::ClosePopup
+        }
+    }
+
     /// Render just the results table into a given area (no popup chrome, no Clear).
     pub fn render_results(&mut self, frame: &mut Frame, area: Rect) {
         if self.results.is_empty() {
-            let msg = Paragraph::new("No results found.").style(self.theme.dimmed);
+            let msg = Paragraph::new(self.empty_message()).wrap(Wrap { trim: false });
             frame.render_widget(msg, area);
             return;
         }
@@ -130,59 +772,91 @@ impl SearchDialog {
         // Layout: hint (1 line) | results table
         let layout = Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(area);
 
-        let hint = Line::from(vec![
-            Span::styled("  \u{2191}/\u{2193}", self.theme.header),
-            Span::styled(": navigate  ", self.theme.dimmed),
-            Span::styled("Enter", self.theme.header),
-            Span::styled(": select  ", self.theme.dimmed),
-            Span::styled("Esc", self.theme.header),
-            Span::styled(": close  ", self.theme.dimmed),
-            Span::styled("/", self.theme.header),
-            Span::styled(": edit filter", self.theme.dimmed),
-        ]);
+        let hint = if self.selection_count() > 0 && !self.quick_editing {
+            Line::from(vec![
+                Span::styled(
+                    format!("  {}  ", self.selection_line()),
+                    self.theme.marked(),
+                ),
+                Span::styled("Space", self.theme.header()),
+                Span::styled(": toggle  ", self.theme.dimmed()),
+                Span::styled("y", self.theme.header()),
+                Span::styled(": copy DNs  ", self.theme.dimmed()),
+                Span::styled("e", self.theme.header()),
+                Span::styled(": export  ", self.theme.dimmed()),
+                Span::styled("d", self.theme.header()),
+                Span::styled(": delete  ", self.theme.dimmed()),
+                Span::styled("m", self.theme.header()),
+                Span::styled(": batch modify  ", self.theme.dimmed()),
+                Span::styled("Esc", self.theme.header()),
+                Span::styled(": clear", self.theme.dimmed()),
+            ])
+        } else if self.quick_editing || self.is_filtered() {
+            let mut spans = vec![
+                Span::styled("  Quick filter: ", self.theme.header()),
+                Span::styled(self.quick_filter.as_str(), self.theme.normal()),
+            ];
+            if self.quick_editing {
+                spans.push(Span::styled("_", self.theme.command_prompt()));
+            }
+            spans.push(Span::styled(
+                format!("  {}  ", self.count_line()),
+                self.theme.dimmed(),
+            ));
+            spans.push(Span::styled("Esc", self.theme.header()));
+            spans.push(Span::styled(": clear", self.theme.dimmed()));
+            Line::from(spans)
+        } else {
+            Line::from(vec![
+                Span::styled("  \u{2191}/\u{2193}", self.theme.header()),
+                Span::styled(": navigate  ", self.theme.dimmed()),
+                Span::styled("Enter", self.theme.header()),
+                Span::styled(": select  ", self.theme.dimmed()),
+                Span::styled("Esc", self.theme.header()),
+                Span::styled(": close  ", self.theme.dimmed()),
+                Span::styled("/", self.theme.header()),
+                Span::styled(": edit filter  ", self.theme.dimmed()),
+                Span::styled("f", self.theme.header()),
+                Span::styled(": quick filter  ", self.theme.dimmed()),
+                Span::styled("'", self.theme.header()),
+                Span::styled(": jump  ", self.theme.dimmed()),
+                Span::styled("\u{2190}/\u{2192}", self.theme.header()),
+                Span::styled(": column  ", self.theme.dimmed()),
+                Span::styled("s", self.theme.header()),
+                Span::styled(": sort  ", self.theme.dimmed()),
+                Span::styled("c", self.theme.header()),
+                Span::styled(": columns  ", self.theme.dimmed()),
+                Span::styled("Space/V", self.theme.header()),
+                Span::styled(": select  ", self.theme.dimmed()),
+                Span::styled("m", self.theme.header()),
+                Span::styled(": batch modify", self.theme.dimmed()),
+            ])
+        };
         frame.render_widget(Paragraph::new(hint), layout[0]);
 
-        let header = Row::new(vec![
-            Cell::from(Span::styled("DN", self.theme.header)),
-            Cell::from(Span::styled("sAMAccountName", self.theme.header)),
-            Cell::from(Span::styled("Display Name", self.theme.header)),
-            Cell::from(Span::styled("Mail", self.theme.header)),
-        ]);
-
-        let rows: Vec<Row> = self
-            .results
-            .iter()
-            .map(|entry| {
-                Row::new(vec![
-                    Cell::from(Span::styled(&entry.dn, self.theme.normal)),
-                    Cell::from(Span::styled(
-                        entry.first_value("sAMAccountName").unwrap_or(""),
-                        self.theme.normal,
-                    )),
-                    Cell::from(Span::styled(
-                        entry.first_value("displayName").unwrap_or(""),
-                        self.theme.normal,
-                    )),
-                    Cell::from(Span::styled(
-                        entry.first_value("mail").unwrap_or(""),
-                        self.theme.normal,
-                    )),
-                ])
-            })
-            .collect();
-
-        let widths = [
-            Constraint::Percentage(40),
-            Constraint::Percentage(15),
-            Constraint::Percentage(20),
-            Constraint::Percentage(25),
-        ];
+        let table = self.table_widget(layout[1].width);
+        frame.render_stateful_widget(table, layout[1], &mut self.table_state);
 
-        let table = Table::new(rows, widths)
-            .header(header.style(self.theme.header))
-            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
-
-        frame.render_stateful_widget(table, layout[1], &mut self.table_state.clone());
+        if self.index_strip && layout[1].height > 1 {
+            // The rows in view, below the header
+            let rows = Rect {
+                y: layout[1].y + 1,
+                height: layout[1].height - 1,
+                ..layout[1]
+            };
+            let names: Vec<_> = self
+                .rows()
+                .skip(self.table_state.offset())
+                .take(rows.height as usize)
+                .map(|e| rdn_display_name(&e.dn))
+                .collect();
+            IndexStrip::new(
+                names.iter().map(|n| n.as_ref()),
+                self.theme.dimmed(),
+                self.theme.header(),
+            )
+            .render(rows, frame.buffer_mut());
+        }
     }
 
     pub fn render(&self, frame: &mut Frame, full: Rect) {
@@ -199,18 +873,18 @@ impl SearchDialog {
 
         frame.render_widget(Clear, area);
 
-        let title = format!(" Search: {} ({} results) ", self.filter, self.results.len());
+        let title = format!(" Search: {} ({}) ", self.filter, self.count_line());
         let block = Block::default()
             .title(title)
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
 
         if self.results.is_empty() {
-            let msg = Paragraph::new("No results found.").style(self.theme.dimmed);
+            let msg = Paragraph::new(self.empty_message()).wrap(Wrap { trim: false });
             frame.render_widget(msg, inner);
             return;
         }
@@ -219,55 +893,484 @@ impl SearchDialog {
         let layout = Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(inner);
 
         let hint = Line::from(vec![
-            Span::styled("  \u{2191}/\u{2193}", self.theme.header),
-            Span::styled(": navigate  ", self.theme.dimmed),
-            Span::styled("Enter", self.theme.header),
-            Span::styled(": select  ", self.theme.dimmed),
-            Span::styled("Esc", self.theme.header),
-            Span::styled(": close", self.theme.dimmed),
+            Span::styled("  \u{2191}/\u{2193}", self.theme.header()),
+            Span::styled(": navigate  ", self.theme.dimmed()),
+            Span::styled("Enter", self.theme.header()),
+            Span::styled(": select  ", self.theme.dimmed()),
+            Span::styled("Esc", self.theme.header()),
+            Span::styled(": close", self.theme.dimmed()),
         ]);
         frame.render_widget(Paragraph::new(hint), layout[0]);
 
-        let header = Row::new(vec![
-            Cell::from(Span::styled("DN", self.theme.header)),
-            Cell::from(Span::styled("sAMAccountName", self.theme.header)),
-            Cell::from(Span::styled("Display Name", self.theme.header)),
-            Cell::from(Span::styled("Mail", self.theme.header)),
-        ]);
+        let table = self.table_widget(layout[1].width);
+        frame.render_stateful_widget(table, layout[1], &mut self.table_state.clone());
+    }
+}
+
+impl SearchDialog {
+    /// The results table as shown: the column names, then the cells of
+    /// each row in table order, values in full.
+    pub fn table(&self) -> (Vec<String>, Vec<Vec<String>>) {
+        let rows = self
+            .rows()
+            .map(|entry| self.columns.iter().map(|c| cell_text(entry, c)).collect())
+            .collect();
+        (self.columns.clone(), rows)
+    }
 
-        let rows: Vec<Row> = self
-            .results
+    /// The results table drawn `width` columns wide. Each column is as
+    /// wide as its content; when they do not all fit the widest are cut,
+    /// ending in `…`.
+    fn table_widget(&self, width: u16) -> Table<'static> {
+        let selected = self.selected_indices();
+        let indices: Box<dyn Iterator<Item = usize>> = match &self.shown {
+            Some(shown) => Box::new(shown.iter().copied()),
+            None => Box::new(0..self.results.len()),
+        };
+        let cells: Vec<(usize, Vec<String>)> = indices
+            .filter_map(|i| self.results.get(i).map(|entry| (i, entry)))
+            .map(|(i, entry)| {
+                let row = self.columns.iter().map(|c| cell_text(entry, c)).collect();
+                (i, row)
+            })
+            .collect();
+        let titles: Vec<String> = self
+            .columns
+            .iter()
+            .enumerate()
+            .map(|(column, name)| match self.sort {
+                Some((sorted, false)) if sorted == column => format!("{} \u{25b2}", name),
+                Some((sorted, true)) if sorted == column => format!("{} \u{25bc}", name),
+                _ => name.clone(),
+            })
+            .collect();
+        let natural: Vec<usize> = titles
             .iter()
-            .map(|entry| {
-                Row::new(vec![
-                    Cell::from(Span::styled(&entry.dn, self.theme.normal)),
-                    Cell::from(Span::styled(
-                        entry.first_value("sAMAccountName").unwrap_or(""),
-                        self.theme.normal,
-                    )),
-                    Cell::from(Span::styled(
-                        entry.first_value("displayName").unwrap_or(""),
-                        self.theme.normal,
-                    )),
-                    Cell::from(Span::styled(
-                        entry.first_value("mail").unwrap_or(""),
-                        self.theme.normal,
-                    )),
-                ])
+            .enumerate()
+            .map(|(column, title)| {
+                cells
+                    .iter()
+                    .map(|(_, row)| display_width(&row[column]))
+                    .fold(display_width(title), usize::max)
             })
             .collect();
+        // The marker column, and a space after each column
+        let available = (width as usize).saturating_sub(1 + self.columns.len());
+        let widths = fit_widths(&natural, available);
 
-        let widths = [
-            Constraint::Percentage(40),
-            Constraint::Percentage(15),
-            Constraint::Percentage(20),
-            Constraint::Percentage(25),
-        ];
+        let header =
+            std::iter::once(Cell::from("")).chain(titles.iter().zip(&widths).enumerate().map(
+                |(column, (title, &w))| {
+                    let style = if column == self.column {
+                        self.theme.header().add_modifier(Modifier::UNDERLINED)
+                    } else {
+                        self.theme.header()
+                    };
+                    Cell::from(Span::styled(truncate_to_width(title, w), style))
+                },
+            ));
+        let rows: Vec<Row<'static>> = cells
+            .into_iter()
+            .map(|(i, row)| {
+                let (marker, style) = if selected.contains(&i) {
+                    ("\u{25cf}", self.theme.marked())
+                } else {
+                    ("", self.theme.normal())
+                };
+                let values = row
+                    .iter()
+                    .zip(&widths)
+                    .map(|(text, &w)| Cell::from(Span::styled(truncate_to_width(text, w), style)));
+                Row::new(
+                    std::iter::once(Cell::from(Span::styled(marker, self.theme.marked())))
+                        .chain(values),
+                )
+            })
+            .collect();
+        let constraints = std::iter::once(Constraint::Length(1))
+            .chain(widths.iter().map(|&w| Constraint::Length(w as u16)));
 
-        let table = Table::new(rows, widths)
-            .header(header.style(self.theme.header))
-            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
+        Table::new(rows, constraints)
+            .header(Row::new(header).style(self.theme.header()))
+            .highlight_style(self.theme.selected().add_modifier(Modifier::BOLD))
+    }
+}
 
-        frame.render_stateful_widget(table, layout[1], &mut self.table_state.clone());
+/// `results` narrowed to the indices in `shown`, if any. Borrows the two
+/// fields alone, so the table state stays free while drawing.
+fn rows_of<'a>(
+    results: &'a [LdapEntry],
+    shown: &'a Option<Vec<usize>>,
+) -> Box<dyn Iterator<Item = &'a LdapEntry> + 'a> {
+    match shown {
+        Some(shown) => Box::new(shown.iter().filter_map(|&i| results.get(i))),
+        None => Box::new(results.iter()),
+    }
+}
+
+impl JumpTargetProvider for SearchDialog {
+    /// Results closed with Esc stay around and can be reopened.
+    fn jump_targets(&self) -> Vec<SwitchTarget> {
+        if self.visible || !self.has_results() {
+            return Vec::new();
+        }
+        vec![SwitchTarget::new(
+            TargetCategory::Dialog,
+            "Search results",
+            JumpTarget::SearchResults,
+        )
+        .with_detail(format!("{} ({} entries)", self.filter, self.results.len()))]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failure_survives_completion() {
+        let mut dialog = SearchDialog::new(Theme::load("dark"), &Keymap::default());
+        assert_eq!(dialog.status, SearchStatus::Idle);
+        dialog.clear_results("(cn=x)".to_string(), "dc=example,dc=com");
+        assert_eq!(dialog.status, SearchStatus::Running);
+
+        // The worker reports the error, then that it is done
+        dialog.fail("Size limit exceeded".to_string());
+        dialog.complete();
+        assert_eq!(
+            dialog.status,
+            SearchStatus::Failed("Size limit exceeded".to_string())
+        );
+        let message: String = dialog
+            .empty_message()
+            .spans
+            .iter()
+            .map(|s| s.content.as_ref())
+            .collect();
+        assert!(message.starts_with("Search failed: Size limit exceeded"));
+    }
+
+    #[test]
+    fn test_esc_stops_running_search() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut dialog = SearchDialog::new(Theme::load("dark"), &Keymap::default());
+        dialog.clear_results("(cn=*)".to_string(), "dc=example,dc=com");
+        dialog.visible = true;
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Esc)),
+            Action::SearchStop
+        ));
+        assert!(dialog.visible);
+
+        dialog.complete();
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Esc)),
+            Action::ClosePopup
+        ));
+    }
+
+    #[test]
+    fn test_sort_column_cycles_and_sorts_loaded_results() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut dialog = SearchDialog::new(Theme::load("dark"), &Keymap::default());
+        let results = [("carol", "c@x"), ("alice", "z@x"), ("bob", "a@x")]
+            .iter()
+            .map(|(uid, mail)| {
+                LdapEntry::new(
+                    format!("uid={},dc=example,dc=com", uid),
+                    std::collections::BTreeMap::from([
+                        ("sAMAccountName".to_string(), vec![uid.to_string()]),
+                        ("mail".to_string(), vec![mail.to_string()]),
+                    ]),
+                )
+            })
+            .collect();
+        dialog.set_columns(
+            ["sAMAccountName", "displayName", "mail"]
+                .map(String::from)
+                .to_vec(),
+        );
+        dialog.show_results("(uid=*)".to_string(), "dc=example,dc=com", results);
+        dialog.visible = true;
+
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('o'))),
+            Action::SearchSort(ref attr, false) if attr == "sAMAccountName"
+        ));
+        dialog.sort_loaded();
+        assert_eq!(dialog.results[0].dn, "uid=alice,dc=example,dc=com");
+
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('O'))),
+            Action::SearchSort(ref attr, true) if attr == "sAMAccountName"
+        ));
+        dialog.handle_key_event(key(KeyCode::Char('o')));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('o'))),
+            Action::SearchSort(ref attr, false) if attr == "mail"
+        ));
+        dialog.sort_loaded();
+        assert_eq!(dialog.results[0].dn, "uid=bob,dc=example,dc=com");
+
+        // Results sorted by the server say so in the header
+        dialog.clear_results("(uid=*)".to_string(), "dc=example,dc=com");
+        assert!(dialog.sort.is_none());
+        dialog.set_sort("displayname", true);
+        assert_eq!(dialog.sort, Some((1, true)));
+    }
+
+    #[test]
+    fn test_columns_sort_naturally_and_export_as_shown() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut dialog = SearchDialog::new(Theme::load("dark"), &Keymap::default());
+        let results = [("host10", "Ops"), ("Host2", "dev"), ("host1", "")]
+            .iter()
+            .map(|(cn, department)| {
+                let mut attrs = std::collections::BTreeMap::from([
+                    ("cn".to_string(), vec![cn.to_string()]),
+                    (
+                        "mail".to_string(),
+                        vec![format!("{}@x", cn), "alias@x".into()],
+                    ),
+                ]);
+                if !department.is_empty() {
+                    attrs.insert("department".to_string(), vec![department.to_string()]);
+                }
+                LdapEntry::new(format!("cn={},dc=example,dc=com", cn), attrs)
+            })
+            .collect();
+        dialog.show_results("(cn=*)".to_string(), "dc=example,dc=com", results);
+        assert_eq!(dialog.columns(), DEFAULT_RESULT_COLUMNS);
+
+        // s sorts by the column under the cursor, numbers in order
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('s'))),
+            Action::StatusMessage(msg) if msg == "Sorted 3 results by cn"
+        ));
+        let (header, rows) = dialog.table();
+        assert_eq!(header, ["cn", "mail", "title", "department"]);
+        let names: Vec<_> = rows.iter().map(|r| r[0].as_str()).collect();
+        assert_eq!(names, ["host1", "Host2", "host10"]);
+        assert_eq!(rows[0][1], "host1@x; alias@x");
+
+        // Again turns the order around; another column starts ascending
+        dialog.handle_key_event(key(KeyCode::Char('s')));
+        assert_eq!(dialog.table().1[0][0], "host10");
+        for _ in 0..5 {
+            dialog.handle_key_event(key(KeyCode::Right));
+        }
+        dialog.handle_key_event(key(KeyCode::Char('s')));
+        assert_eq!(dialog.sort, Some((3, false)));
+        let departments: Vec<_> = dialog.table().1.iter().map(|r| r[3].clone()).collect();
+        assert_eq!(departments, ["dev", "Ops", ""]);
+
+        // The sort follows its column into a new set of columns
+        dialog.set_columns(vec!["department".to_string(), "dn".to_string()]);
+        assert_eq!(dialog.sort, Some((0, false)));
+        assert_eq!(dialog.column, 1);
+        dialog.handle_key_event(key(KeyCode::Char('s')));
+        assert_eq!(dialog.table().1[2][1], "cn=host10,dc=example,dc=com");
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('c'))),
+            Action::ShowResultColumns(columns) if columns == ["department", "dn"]
+        ));
+
+        // The quick filter narrows what is exported too
+        dialog.handle_key_event(key(KeyCode::Char('f')));
+        for c in "(department=*)".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        assert_eq!(dialog.table().1.len(), 2);
+    }
+
+    #[test]
+    fn test_jump_cycles_results_by_rdn() {
+        let key = |c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE);
+        let mut dialog = SearchDialog::new(Theme::load("dark"), &Keymap::default());
+        let results = ["alice", "Sam", "bob", "sally"]
+            .iter()
+            .map(|uid| {
+                LdapEntry::new(
+                    format!("uid={},ou=People,dc=example,dc=com", uid),
+                    Default::default(),
+                )
+            })
+            .collect();
+        dialog.show_results("(uid=*)".to_string(), "dc=example,dc=com", results);
+
+        // Outside jump mode letters are not for the dialog to take
+        assert!(matches!(dialog.handle_key_event(key('x')), Action::None));
+        assert_eq!(dialog.table_state.selected(), Some(0));
+
+        dialog.handle_key_event(key('\''));
+        assert!(dialog.is_jumping());
+        dialog.handle_key_event(key('s'));
+        assert_eq!(dialog.table_state.selected(), Some(1));
+        dialog.handle_key_event(key('s'));
+        assert_eq!(dialog.table_state.selected(), Some(3));
+        assert!(matches!(
+            dialog.handle_key_event(key('z')),
+            Action::StatusMessage(msg) if msg.contains("No result")
+        ));
+
+        // Enter leaves jump mode and opens the selected result
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        assert!(matches!(
+            dialog.handle_key_event(enter),
+            Action::TreeSelect(dn) if dn.starts_with("uid=sally")
+        ));
+        assert!(!dialog.is_jumping());
+    }
+
+    #[test]
+    fn test_quick_filter_narrows_loaded_results() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let person = |uid: &str, mail: &str| {
+            LdapEntry::new(
+                format!("uid={},ou=People,dc=example,dc=com", uid),
+                std::collections::BTreeMap::from([("mail".to_string(), vec![mail.to_string()])]),
+            )
+        };
+        let mut dialog = SearchDialog::new(Theme::load("dark"), &Keymap::default());
+        dialog.show_results(
+            "(uid=*)".to_string(),
+            "dc=example,dc=com",
+            vec![
+                person("alice", "alice@example.com"),
+                person("bob", "bob@EXAMPLE.org"),
+                person("carol", "carol@example.com"),
+            ],
+        );
+        dialog.handle_key_event(key(KeyCode::Down));
+        dialog.handle_key_event(key(KeyCode::Char('f')));
+        assert!(dialog.is_quick_filtering());
+
+        // Plain text matches the DN or any value, ignoring case
+        for c in "Example.COM".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        assert_eq!(dialog.row_count(), 2);
+        assert_eq!(dialog.count_line(), "2/3 entries (filtered)");
+        assert_eq!(dialog.table_state.selected(), Some(0));
+
+        // Later batches are filtered as they arrive
+        dialog.results.push(person("dave", "dave@example.com"));
+        dialog.results_appended(false);
+        assert_eq!(dialog.row_count(), 3);
+
+        // Text that parses as a filter is evaluated as one
+        for _ in 0.."Example.COM".len() {
+            dialog.handle_key_event(key(KeyCode::Backspace));
+        }
+        for c in "(|(mail=BOB@*)(mail=dave@*))".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        dialog.handle_key_event(key(KeyCode::Enter));
+        assert!(!dialog.is_quick_filtering());
+        let uids: Vec<_> = dialog.rows().map(|e| rdn_display_name(&e.dn)).collect();
+        assert_eq!(uids, vec!["bob", "dave"]);
+        // A batch modify takes the selection, else the entry under the cursor
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('m'))),
+            Action::ShowBatchModifyDialog(dns) if dns.len() == 1 && dns[0].starts_with("uid=bob")
+        ));
+        dialog.handle_key_event(key(KeyCode::Char('a')));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('m'))),
+            Action::ShowBatchModifyDialog(dns) if dns.len() == 2 && dns[1].starts_with("uid=dave")
+        ));
+        dialog.handle_key_event(key(KeyCode::Esc));
+        dialog.handle_key_event(key(KeyCode::Down));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::TreeSelect(dn) if dn.starts_with("uid=dave")
+        ));
+
+        // Esc clears the filter before it closes the panel
+        dialog.visible = true;
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Esc)),
+            Action::StatusMessage(msg) if msg == "4 entries"
+        ));
+        assert!(dialog.visible);
+        assert!(!dialog.is_filtered());
+        // Still on dave
+        assert_eq!(dialog.table_state.selected(), Some(3));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Esc)),
+            Action::ClosePopup
+        ));
+    }
+
+    #[test]
+    fn test_selection_survives_quick_filter_and_visual_range() {
+        let key = |code| KeyEvent::new(code, KeyModifiers::NONE);
+        let mut dialog = SearchDialog::new(Theme::load("dark"), &Keymap::default());
+        let results = ["alice", "bob", "carol", "dave", "erin"]
+            .iter()
+            .map(|uid| {
+                LdapEntry::new(
+                    format!("uid={},ou=People,dc=example,dc=com", uid),
+                    std::collections::BTreeMap::from([("uid".to_string(), vec![uid.to_string()])]),
+                )
+            })
+            .collect();
+        dialog.show_results("(uid=*)".to_string(), "dc=example,dc=com", results);
+
+        // Nothing selected: the cursor entry is the target
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('y'))),
+            Action::CopyToClipboard(text) if text == "uid=alice,ou=People,dc=example,dc=com"
+        ));
+
+        // Space toggles, V marks a range as the cursor moves
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char(' '))),
+            Action::StatusMessage(msg) if msg == "1 selected"
+        ));
+        dialog.handle_key_event(key(KeyCode::Down));
+        dialog.handle_key_event(key(KeyCode::Down));
+        dialog.handle_key_event(key(KeyCode::Char('V')));
+        dialog.handle_key_event(key(KeyCode::Down));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Down)),
+            Action::StatusMessage(msg) if msg == "-- VISUAL -- 4 selected"
+        ));
+        dialog.handle_key_event(key(KeyCode::Char('V')));
+        assert_eq!(dialog.selection_count(), 4);
+
+        // A quick filter hides bob but keeps the marks
+        dialog.handle_key_event(key(KeyCode::Char('f')));
+        for c in "(!(uid=bob))".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        dialog.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(dialog.row_count(), 4);
+        assert_eq!(dialog.selection_count(), 4);
+        let Action::ConfirmDeleteEntries(dns) = dialog.handle_key_event(key(KeyCode::Char('d')))
+        else {
+            panic!("expected a delete");
+        };
+        let uids: Vec<_> = dns.iter().map(|d| rdn_display_name(d)).collect();
+        assert_eq!(uids, vec!["alice", "carol", "dave", "erin"]);
+
+        // Esc clears the selection before the quick filter
+        dialog.handle_key_event(key(KeyCode::Esc));
+        assert_eq!(dialog.selection_count(), 0);
+        assert!(dialog.is_filtered());
+
+        // New results start with nothing selected
+        dialog.handle_key_event(key(KeyCode::Char('a')));
+        assert_eq!(dialog.selection_count(), 4);
+        dialog.forget_results(&["uid=carol,ou=People,dc=example,dc=com".to_string()]);
+        assert_eq!(dialog.selection_count(), 0);
+        assert_eq!(dialog.row_count(), 3);
+        dialog.handle_key_event(key(KeyCode::Char('a')));
+        dialog.show_results(
+            "(uid=a*)".to_string(),
+            "ou=People,dc=example,dc=com",
+            Vec::new(),
+        );
+        assert_eq!(dialog.selection_count(), 0);
     }
 }
@@ -0,0 +1,527 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use loom_core::bookmark::{BookmarkTarget, SavedSearch};
+use loom_core::filter::{validate, FilterError};
+use loom_core::Scope;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Scopes offered, in the order Left/Right cycles through them.
+const SCOPES: [(&str, Scope); 3] = [
+    ("base", Scope::Base),
+    ("one", Scope::OneLevel),
+    ("sub", Scope::Subtree),
+];
+
+/// Which field is being edited.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SearchField {
+    BaseDn,
+    Scope,
+    Filter,
+    Attributes,
+}
+
+/// Dialog for a search with every parameter spelled out: base DN, scope,
+/// filter and attributes. The filter is checked as it is typed, and Up and
+/// Down in it recall earlier filters. F2 bookmarks the search instead of
+/// running it.
+pub struct SearchFormDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    active_field: SearchField,
+    base_dn: String,
+    /// Index into `SCOPES`.
+    scope_idx: usize,
+    filter: String,
+    attributes: String,
+    /// Syntax error in `filter`, if any.
+    error: Option<FilterError>,
+    /// Earlier filters, most recent first.
+    history: Vec<String>,
+    /// Position in `history` while recalling; `None` on the typed filter.
+    history_idx: Option<usize>,
+    /// The filter being typed before Up went into the history.
+    draft: String,
+}
+
+impl SearchFormDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Search", theme.clone()).with_size(60, 40),
+            theme,
+            active_field: SearchField::Filter,
+            base_dn: String::new(),
+            scope_idx: 2,
+            filter: String::new(),
+            attributes: String::new(),
+            error: None,
+            history: Vec::new(),
+            history_idx: None,
+            draft: String::new(),
+        }
+    }
+
+    /// Open the form searching under `base_dn`, starting from the last
+    /// filter in `history`.
+    pub fn show(&mut self, base_dn: &str, history: &[String]) {
+        self.base_dn = base_dn.to_string();
+        self.scope_idx = 2;
+        self.history = history.to_vec();
+        self.history_idx = None;
+        self.draft.clear();
+        self.set_filter(
+            history
+                .first()
+                .cloned()
+                .unwrap_or_else(|| "(objectClass=*)".to_string()),
+        );
+        self.attributes = "*".to_string();
+        self.active_field = SearchField::Filter;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    fn set_filter(&mut self, filter: String) {
+        self.filter = filter;
+        self.revalidate();
+    }
+
+    fn revalidate(&mut self) {
+        self.error = validate(&self.filter).err();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Tab => {
+                self.active_field = match self.active_field {
+                    SearchField::BaseDn => SearchField::Scope,
+                    SearchField::Scope => SearchField::Filter,
+                    SearchField::Filter => SearchField::Attributes,
+                    SearchField::Attributes => SearchField::BaseDn,
+                };
+                Action::None
+            }
+            KeyCode::BackTab => {
+                self.active_field = match self.active_field {
+                    SearchField::BaseDn => SearchField::Attributes,
+                    SearchField::Scope => SearchField::BaseDn,
+                    SearchField::Filter => SearchField::Scope,
+                    SearchField::Attributes => SearchField::Filter,
+                };
+                Action::None
+            }
+            KeyCode::Enter => self.submit(),
+            KeyCode::F(2) => match self.search() {
+                Ok(search) => {
+                    self.hide();
+                    Action::PromptBookmark(BookmarkTarget::Search(search))
+                }
+                Err(e) => Action::ErrorMessage(e),
+            },
+            _ => match self.active_field {
+                SearchField::Scope => {
+                    match key.code {
+                        KeyCode::Left | KeyCode::Char('h') => {
+                            self.scope_idx = (self.scope_idx + SCOPES.len() - 1) % SCOPES.len();
+                        }
+                        KeyCode::Right | KeyCode::Char('l') | KeyCode::Char(' ') => {
+                            self.scope_idx = (self.scope_idx + 1) % SCOPES.len();
+                        }
+                        _ => {}
+                    }
+                    Action::None
+                }
+                SearchField::Filter => self.handle_filter_key(key),
+                SearchField::BaseDn => Self::edit(&mut self.base_dn, key),
+                SearchField::Attributes => Self::edit(&mut self.attributes, key),
+            },
+        }
+    }
+
+    fn handle_filter_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Up => {
+                let next = self.history_idx.map_or(0, |i| i + 1);
+                if next < self.history.len() {
+                    if self.history_idx.is_none() {
+                        self.draft = self.filter.clone();
+                    }
+                    self.history_idx = Some(next);
+                    self.set_filter(self.history[next].clone());
+                }
+            }
+            KeyCode::Down => match self.history_idx {
+                Some(0) => {
+                    self.history_idx = None;
+                    let draft = std::mem::take(&mut self.draft);
+                    self.set_filter(draft);
+                }
+                Some(i) => {
+                    self.history_idx = Some(i - 1);
+                    self.set_filter(self.history[i - 1].clone());
+                }
+                None => {}
+            },
+            KeyCode::Backspace | KeyCode::Char(_) => {
+                Self::edit(&mut self.filter, key);
+                self.history_idx = None;
+                self.revalidate();
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn edit(buf: &mut String, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Backspace => {
+                buf.pop();
+            }
+            KeyCode::Char(c) => buf.push(c),
+            _ => {}
+        }
+        Action::None
+    }
+
+    /// The search as filled in, or the error to report.
+    fn search(&mut self) -> Result<SavedSearch, String> {
+        let base = self.base_dn.trim().to_string();
+        if base.is_empty() {
+            return Err("Base DN is required".to_string());
+        }
+        if let Some(error) = &self.error {
+            self.active_field = SearchField::Filter;
+            return Err(format!("Invalid filter: {}", error));
+        }
+        Ok(SavedSearch {
+            base,
+            scope: SCOPES[self.scope_idx].1.into(),
+            filter: self.filter.trim().to_string(),
+            attributes: self
+                .attributes
+                .split(|c: char| c == ',' || c.is_whitespace())
+                .filter(|a| !a.is_empty())
+                .map(str::to_string)
+                .collect(),
+        })
+    }
+
+    fn submit(&mut self) -> Action {
+        let search = match self.search() {
+            Ok(search) => search,
+            Err(e) => return Action::ErrorMessage(e),
+        };
+        self.hide();
+        Action::Search {
+            attrs: search.attrs(),
+            base: search.base,
+            scope: search.scope.into(),
+            filter: search.filter,
+        }
+    }
+
+    /// The filter with the character at the error position marked.
+    fn filter_spans(&self, active: bool) -> Vec<Span<'_>> {
+        let normal = if active {
+            self.theme.normal()
+        } else {
+            self.theme.dimmed()
+        };
+        let mut spans = match &self.error {
+            Some(error) if error.position < self.filter.len() => {
+                let at = error.position;
+                let next = self.filter[at..]
+                    .chars()
+                    .next()
+                    .map_or(at, |c| at + c.len_utf8());
+                vec![
+                    Span::styled(&self.filter[..at], normal),
+                    Span::styled(
+                        &self.filter[at..next],
+                        self.theme.error().add_modifier(Modifier::UNDERLINED),
+                    ),
+                    Span::styled(&self.filter[next..], normal),
+                ]
+            }
+            _ => vec![Span::styled(self.filter.as_str(), normal)],
+        };
+        if active {
+            spans.push(Span::styled("_", self.theme.command_prompt()));
+        }
+        spans
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(" Search ")
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Length(2), // Base DN
+            Constraint::Length(2), // Scope
+            Constraint::Length(3), // Filter and its check
+            Constraint::Length(2), // Attributes
+            Constraint::Min(1),    // Hints
+        ])
+        .split(inner);
+
+        let label = |text: &'static str, field: SearchField| {
+            let style = if self.active_field == field {
+                self.theme.header()
+            } else {
+                self.theme.dimmed()
+            };
+            Line::from(Span::styled(text, style))
+        };
+        let text = |value: &str, field: SearchField| {
+            let active = self.active_field == field;
+            let mut spans = vec![Span::styled(
+                value.to_string(),
+                if active {
+                    self.theme.normal()
+                } else {
+                    self.theme.dimmed()
+                },
+            )];
+            if active {
+                spans.push(Span::styled("_", self.theme.command_prompt()));
+            }
+            Line::from(spans)
+        };
+
+        frame.render_widget(
+            Paragraph::new(vec![
+                label("Base DN:", SearchField::BaseDn),
+                text(&self.base_dn, SearchField::BaseDn),
+            ]),
+            layout[0],
+        );
+
+        let scope_active = self.active_field == SearchField::Scope;
+        let mut scopes = Vec::new();
+        for (i, (name, _)) in SCOPES.iter().enumerate() {
+            let style = if i == self.scope_idx {
+                if scope_active {
+                    self.theme.selected().add_modifier(Modifier::BOLD)
+                } else {
+                    self.theme.normal()
+                }
+            } else {
+                self.theme.dimmed()
+            };
+            scopes.push(Span::styled(format!(" {} ", name), style));
+        }
+        frame.render_widget(
+            Paragraph::new(vec![
+                label("Scope:", SearchField::Scope),
+                Line::from(scopes),
+            ]),
+            layout[1],
+        );
+
+        let filter_active = self.active_field == SearchField::Filter;
+        let mut filter_label = vec![Span::styled(
+            "Filter:",
+            if filter_active {
+                self.theme.header()
+            } else {
+                self.theme.dimmed()
+            },
+        )];
+        if let Some(i) = self.history_idx {
+            filter_label.push(Span::styled(
+                format!("  history {}/{}", i + 1, self.history.len()),
+                self.theme.dimmed(),
+            ));
+        }
+        let check = match &self.error {
+            Some(error) => Line::from(Span::styled(error.message.as_str(), self.theme.error())),
+            None => Line::from(Span::styled("Valid filter", self.theme.dimmed())),
+        };
+        frame.render_widget(
+            Paragraph::new(vec![
+                Line::from(filter_label),
+                Line::from(self.filter_spans(filter_active)),
+                check,
+            ]),
+            layout[2],
+        );
+
+        frame.render_widget(
+            Paragraph::new(vec![
+                label("Attributes:", SearchField::Attributes),
+                text(&self.attributes, SearchField::Attributes),
+            ]),
+            layout[3],
+        );
+
+        let hint = match self.active_field {
+            SearchField::Scope => {
+                "\u{2190}/\u{2192}:scope  Tab:next  Enter:search  F2:bookmark  Esc:cancel"
+            }
+            SearchField::Filter => {
+                "\u{2191}/\u{2193}:history  Tab:next  Enter:search  F2:bookmark  Esc:cancel"
+            }
+            _ => "Tab:next  Enter:search  F2:bookmark  Esc:cancel",
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(hint, self.theme.dimmed()))),
+            layout[4],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn type_text(dialog: &mut SearchFormDialog, text: &str) {
+        for c in text.chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_submit_builds_search() {
+        let mut dialog = SearchFormDialog::new(Theme::load("dark"));
+        dialog.show("ou=people,dc=example,dc=com", &[]);
+        assert_eq!(dialog.filter, "(objectClass=*)");
+
+        // Scope: sub -> base, wrapping to the left
+        dialog.handle_key_event(key(KeyCode::BackTab));
+        dialog.handle_key_event(key(KeyCode::Right));
+        dialog.handle_key_event(key(KeyCode::Tab));
+        for _ in 0.."(objectClass=*)".len() {
+            dialog.handle_key_event(key(KeyCode::Backspace));
+        }
+        type_text(&mut dialog, "(uid=jdoe)");
+        dialog.handle_key_event(key(KeyCode::Tab));
+        dialog.handle_key_event(key(KeyCode::Backspace));
+        type_text(&mut dialog, "cn, mail");
+
+        let Action::Search {
+            base,
+            scope,
+            filter,
+            attrs,
+        } = dialog.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected a search");
+        };
+        assert_eq!(base, "ou=people,dc=example,dc=com");
+        assert_eq!(scope, Scope::Base);
+        assert_eq!(filter, "(uid=jdoe)");
+        assert_eq!(attrs, vec!["cn", "mail"]);
+        assert!(!dialog.visible);
+    }
+
+    #[test]
+    fn test_f2_bookmarks_the_search() {
+        use loom_core::bookmark::SearchScope;
+
+        let mut dialog = SearchFormDialog::new(Theme::load("dark"));
+        dialog.show("ou=people,dc=example,dc=com", &["(uid=jdoe)".to_string()]);
+        dialog.handle_key_event(key(KeyCode::Tab));
+        dialog.handle_key_event(key(KeyCode::Backspace));
+        type_text(&mut dialog, "uid mail");
+
+        let Action::PromptBookmark(BookmarkTarget::Search(search)) =
+            dialog.handle_key_event(key(KeyCode::F(2)))
+        else {
+            panic!("expected a bookmark prompt");
+        };
+        assert_eq!(
+            search,
+            SavedSearch {
+                base: "ou=people,dc=example,dc=com".to_string(),
+                scope: SearchScope::Sub,
+                filter: "(uid=jdoe)".to_string(),
+                attributes: vec!["uid".to_string(), "mail".to_string()],
+            }
+        );
+        assert!(!dialog.visible);
+    }
+
+    #[test]
+    fn test_invalid_filter_is_marked_and_blocks_search() {
+        let mut dialog = SearchFormDialog::new(Theme::load("dark"));
+        dialog.show("dc=example,dc=com", &[]);
+        for _ in 0.."(objectClass=*)".len() {
+            dialog.handle_key_event(key(KeyCode::Backspace));
+        }
+        type_text(&mut dialog, "(&(cn=a)(=b))");
+        assert_eq!(dialog.error.as_ref().map(|e| e.position), Some(9));
+        let spans = dialog.filter_spans(true);
+        assert_eq!(spans[1].content, "=");
+
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+        assert!(dialog.visible);
+
+        // Fixing it clears the mark as it is typed
+        for _ in 0..5 {
+            dialog.handle_key_event(key(KeyCode::Backspace));
+        }
+        type_text(&mut dialog, "(sn=b))");
+        assert!(dialog.error.is_none());
+    }
+
+    #[test]
+    fn test_history_up_and_down() {
+        let history = vec!["(uid=b)".to_string(), "(uid=a)".to_string()];
+        let mut dialog = SearchFormDialog::new(Theme::load("dark"));
+        dialog.show("dc=example,dc=com", &history);
+        // Opens on the most recent filter
+        assert_eq!(dialog.filter, "(uid=b)");
+        type_text(&mut dialog, "x");
+
+        dialog.handle_key_event(key(KeyCode::Up));
+        assert_eq!(dialog.filter, "(uid=b)");
+        dialog.handle_key_event(key(KeyCode::Up));
+        assert_eq!(dialog.filter, "(uid=a)");
+        dialog.handle_key_event(key(KeyCode::Up));
+        assert_eq!(dialog.filter, "(uid=a)");
+        dialog.handle_key_event(key(KeyCode::Down));
+        dialog.handle_key_event(key(KeyCode::Down));
+        // Back to what was being typed
+        assert_eq!(dialog.filter, "(uid=b)x");
+        assert!(dialog.error.is_some());
+    }
+}
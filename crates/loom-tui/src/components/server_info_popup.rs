@@ -0,0 +1,317 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use loom_core::oids;
+use loom_core::server_detect::RootDse;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Root DSE attributes shown in their own sections rather than under
+/// "Other attributes", lowercased.
+const SHOWN: &[&str] = &[
+    "namingcontexts",
+    "subschemasubentry",
+    "vendorname",
+    "vendorversion",
+    "supportedcontrol",
+    "supportedextension",
+    "supportedfeatures",
+    "supportedcapabilities",
+    "supportedldapversion",
+    "supportedsaslmechanisms",
+    "objectclass",
+];
+
+/// One line of the report: text, and whether it is a supported (`true`)
+/// or missing (`false`) capability.
+struct Row {
+    text: String,
+    mark: Option<bool>,
+}
+
+impl Row {
+    fn text(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            mark: None,
+        }
+    }
+}
+
+/// What the server said about itself in its root DSE: naming contexts,
+/// versions, SASL mechanisms, and the controls, extended operations and
+/// features it advertises, by name where known.
+pub struct ServerInfoPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    label: String,
+    root_dse: Option<RootDse>,
+    scroll: u16,
+}
+
+impl ServerInfoPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Server Info", theme.clone()).with_size(80, 80),
+            theme,
+            label: String::new(),
+            root_dse: None,
+            scroll: 0,
+        }
+    }
+
+    /// Show the root DSE of the connection labelled `label`; `None` when
+    /// the server did not return one.
+    pub fn show(&mut self, label: &str, root_dse: Option<RootDse>) {
+        self.label = label.to_string();
+        self.root_dse = root_dse;
+        self.scroll = 0;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('i') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Char('c') | KeyCode::Char('y') => Action::CopyToClipboard(self.to_text()),
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll = self.scroll.saturating_add(1);
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll = self.scroll.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::PageDown => {
+                self.scroll = self.scroll.saturating_add(10);
+                Action::None
+            }
+            KeyCode::PageUp => {
+                self.scroll = self.scroll.saturating_sub(10);
+                Action::None
+            }
+            KeyCode::Char('g') | KeyCode::Home => {
+                self.scroll = 0;
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// The report as titled sections.
+    fn sections(&self) -> Vec<(&'static str, Vec<Row>)> {
+        let Some(dse) = &self.root_dse else {
+            return vec![(
+                "Server",
+                vec![Row::text("The server did not return a root DSE.")],
+            )];
+        };
+        let or_none = |values: &[String]| -> Vec<Row> {
+            if values.is_empty() {
+                vec![Row::text("(none advertised)")]
+            } else {
+                values.iter().map(|v| Row::text(v.clone())).collect()
+            }
+        };
+        let oid_rows = |values: &[String]| -> Vec<Row> {
+            if values.is_empty() {
+                return vec![Row::text("(none advertised)")];
+            }
+            let mut rows: Vec<(bool, String)> = values
+                .iter()
+                .map(|oid| (oids::describe(oid).is_none(), oids::label(oid)))
+                .collect();
+            // Known names first, alphabetically, then the raw OIDs
+            rows.sort();
+            rows.into_iter().map(|(_, text)| Row::text(text)).collect()
+        };
+
+        let mut server = vec![Row::text(format!("Type: {}", dse.server_type))];
+        if let Some(vendor) = &dse.vendor_name {
+            server.push(Row::text(format!("Vendor: {}", vendor)));
+        }
+        if let Some(version) = &dse.vendor_version {
+            server.push(Row::text(format!("Version: {}", version)));
+        }
+        if !dse.supported_ldap_versions.is_empty() {
+            server.push(Row::text(format!(
+                "LDAP versions: {}",
+                dse.supported_ldap_versions.join(", ")
+            )));
+        }
+        if let Some(subschema) = &dse.subschema_subentry {
+            server.push(Row::text(format!("Schema: {}", subschema)));
+        }
+
+        let feature = |text: &str, supported: bool| Row {
+            text: text.to_string(),
+            mark: Some(supported),
+        };
+        let used = vec![
+            feature(
+                "Paged results: large containers and searches come a page at a time",
+                dse.supports_paged_results(),
+            ),
+            feature(
+                "Server-side sort: :children sort= and o in search results sort on the server",
+                dse.supports_server_side_sort(),
+            ),
+            feature(
+                "Transactions: batch changes apply all or nothing",
+                dse.supports_transactions(),
+            ),
+            feature(
+                "Who am I?: the bound identity is shown after connecting",
+                dse.supports_extension(oids::WHOAMI),
+            ),
+            feature(
+                "Virtual list view: any page of a large container is one request",
+                dse.supports_vlv(),
+            ),
+            feature(
+                "Password policy",
+                dse.supports_control(oids::PASSWORD_POLICY),
+            ),
+        ];
+
+        let other: Vec<Row> = dse
+            .raw
+            .iter()
+            .filter(|(attr, _)| !SHOWN.contains(&attr.to_lowercase().as_str()))
+            .map(|(attr, values)| Row::text(format!("{}: {}", attr, values.join(", "))))
+            .collect();
+
+        let mut sections = vec![
+            ("Server", server),
+            ("Naming contexts", or_none(&dse.naming_contexts)),
+            ("Capabilities", used),
+            ("SASL mechanisms", or_none(&dse.supported_sasl_mechanisms)),
+            ("Controls", oid_rows(&dse.supported_controls)),
+            ("Extended operations", oid_rows(&dse.supported_extensions)),
+            ("Features", oid_rows(&dse.supported_features)),
+        ];
+        if !other.is_empty() {
+            sections.push(("Other attributes", other));
+        }
+        sections
+    }
+
+    /// The report as plain text, for the clipboard.
+    fn to_text(&self) -> String {
+        let mut text = format!("Server info for {}\n", self.label);
+        for (title, rows) in self.sections() {
+            text.push_str(&format!("\n{}\n", title));
+            for row in rows {
+                let mark = match row.mark {
+                    Some(true) => "[x] ",
+                    Some(false) => "[ ] ",
+                    None => "",
+                };
+                text.push_str(&format!("  {}{}\n", mark, row.text));
+            }
+        }
+        text
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let mut lines = vec![Line::from(Span::styled(
+            self.label.clone(),
+            self.theme.header(),
+        ))];
+        for (title, rows) in self.sections() {
+            lines.push(Line::default());
+            lines.push(Line::from(Span::styled(title, self.theme.header())));
+            for row in rows {
+                let mark = match row.mark {
+                    Some(true) => Span::styled("\u{2713} ", self.theme.success()),
+                    Some(false) => Span::styled("\u{2717} ", self.theme.dimmed()),
+                    None => Span::raw(""),
+                };
+                let style = match row.mark {
+                    Some(false) => self.theme.dimmed(),
+                    _ => self.theme.normal(),
+                };
+                lines.push(Line::from(vec![
+                    Span::raw("  "),
+                    mark,
+                    Span::styled(row.text, style),
+                ]));
+            }
+        }
+        frame.render_widget(Paragraph::new(lines).scroll((self.scroll, 0)), layout[0]);
+
+        let hints = Line::from(Span::styled(
+            "\u{2191}/\u{2193}:scroll  c:copy  Esc:close",
+            self.theme.dimmed(),
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+
+    fn root_dse() -> RootDse {
+        let values = |v: &[&str]| v.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        RootDse::from_attrs(BTreeMap::from([
+            ("vendorName".to_string(), values(&["OpenLDAP"])),
+            ("namingContexts".to_string(), values(&["dc=example,dc=com"])),
+            (
+                "supportedControl".to_string(),
+                values(&["1.2.3.4", oids::PAGED_RESULTS]),
+            ),
+            ("supportedExtension".to_string(), values(&[oids::WHOAMI])),
+            ("entryDN".to_string(), values(&[""])),
+        ]))
+    }
+
+    #[test]
+    fn test_report_names_oids_and_marks_capabilities() {
+        let mut popup = ServerInfoPopup::new(Theme::load("dark"));
+        popup.show("ldap1", Some(root_dse()));
+        let text = popup.to_text();
+        // Known controls by name first, unknown ones raw
+        assert!(text.contains("Controls\n  Paged results (1.2.840.113556.1.4.319)\n  1.2.3.4\n"));
+        assert!(text.contains("[x] Paged results"));
+        assert!(text.contains("[ ] Server-side sort"));
+        assert!(text.contains("Who am I? (1.3.6.1.4.1.4203.1.11.3)"));
+        assert!(text.contains("Other attributes\n  entryDN: \n"));
+    }
+
+    #[test]
+    fn test_without_root_dse() {
+        let mut popup = ServerInfoPopup::new(Theme::load("dark"));
+        popup.show("ldap1", None);
+        assert!(popup.to_text().contains("did not return a root DSE"));
+        assert!(matches!(
+            popup.handle_key_event(KeyEvent::from(KeyCode::Esc)),
+            Action::ClosePopup
+        ));
+        assert!(!popup.visible);
+    }
+}
@@ -0,0 +1,718 @@
+use std::path::PathBuf;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+
+use loom_core::connection::TlsMode;
+use loom_core::profile_import::ForeignFormat;
+
+use crate::action::Action;
+use crate::components::connection_form::{ConnectionForm, FormSection};
+use crate::components::popup::Popup;
+use crate::config::ConnectionProfile;
+use crate::theme::{Styles, Theme};
+
+/// A page of the setup wizard, in the order they are shown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    Welcome,
+    Server,
+    Security,
+    Auth,
+    BaseDn,
+    Test,
+    Import,
+    Theme,
+    Done,
+}
+
+impl Step {
+    fn title(self) -> &'static str {
+        match self {
+            Step::Welcome => "Welcome",
+            Step::Server => "Server",
+            Step::Security => "Security",
+            Step::Auth => "Authentication",
+            Step::BaseDn => "Base DN",
+            Step::Test => "Connection test",
+            Step::Import => "Import profiles",
+            Step::Theme => "Theme",
+            Step::Done => "Done",
+        }
+    }
+
+    /// The form fields edited on this page, if any.
+    fn section(self) -> Option<FormSection> {
+        match self {
+            Step::Server => Some(FormSection::Server),
+            Step::Security => Some(FormSection::Security),
+            Step::Auth => Some(FormSection::Auth),
+            Step::BaseDn => Some(FormSection::BaseDn),
+            _ => None,
+        }
+    }
+}
+
+/// Outcome of the connection test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TestState {
+    NotRun,
+    Running,
+    /// Connected; holds the base DN in use.
+    Passed(String),
+    Failed(String),
+}
+
+/// The wizard's steps and what has been done so far, without any UI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetupFlow {
+    pub step: Step,
+    /// A profile is being created (the server step was not skipped).
+    pub profile: bool,
+    pub test: TestState,
+    /// Other tools' configurations were found to import from.
+    pub sources: bool,
+}
+
+impl SetupFlow {
+    pub fn new(sources: bool) -> Self {
+        Self {
+            step: Step::Welcome,
+            profile: false,
+            test: TestState::NotRun,
+            sources,
+        }
+    }
+
+    /// Complete the current step and move on.
+    pub fn advance(&mut self) {
+        self.step = match self.step {
+            Step::Welcome => Step::Server,
+            Step::Server => {
+                self.profile = true;
+                Step::Security
+            }
+            Step::Security => Step::Auth,
+            Step::Auth => Step::BaseDn,
+            Step::BaseDn => Step::Test,
+            Step::Test => self.after_profile(),
+            Step::Import => Step::Theme,
+            Step::Theme | Step::Done => Step::Done,
+        };
+    }
+
+    /// Leave the current step without completing it. Skipping the server
+    /// step skips the rest of the profile, test included.
+    pub fn skip(&mut self) {
+        match self.step {
+            Step::Server => {
+                self.profile = false;
+                self.step = self.after_profile();
+            }
+            Step::Test if self.test == TestState::Running => {
+                self.test = TestState::Failed("Cancelled".to_string());
+                self.step = self.after_profile();
+            }
+            _ => self.advance(),
+        }
+    }
+
+    /// Skip everything that is left.
+    pub fn finish(&mut self) {
+        self.step = Step::Done;
+    }
+
+    /// Go back to the server step to correct the profile.
+    pub fn edit_profile(&mut self) {
+        self.test = TestState::NotRun;
+        self.step = Step::Server;
+    }
+
+    pub fn start_test(&mut self) {
+        self.step = Step::Test;
+        self.test = TestState::Running;
+    }
+
+    pub fn test_finished(&mut self, result: Result<String, String>) {
+        if self.test != TestState::Running {
+            return;
+        }
+        self.test = match result {
+            Ok(base_dn) => TestState::Passed(base_dn),
+            Err(e) => TestState::Failed(e),
+        };
+    }
+
+    fn after_profile(&self) -> Step {
+        if self.sources {
+            Step::Import
+        } else {
+            Step::Theme
+        }
+    }
+
+    /// 1-based position of the current step, and the number of steps.
+    pub fn position(&self) -> (usize, usize) {
+        let steps: &[Step] = if self.sources {
+            &[
+                Step::Welcome,
+                Step::Server,
+                Step::Security,
+                Step::Auth,
+                Step::BaseDn,
+                Step::Test,
+                Step::Import,
+                Step::Theme,
+                Step::Done,
+            ]
+        } else {
+            &[
+                Step::Welcome,
+                Step::Server,
+                Step::Security,
+                Step::Auth,
+                Step::BaseDn,
+                Step::Test,
+                Step::Theme,
+                Step::Done,
+            ]
+        };
+        let i = steps.iter().position(|s| *s == self.step).unwrap_or(0);
+        (i + 1, steps.len())
+    }
+}
+
+/// Guided first-run setup: the first profile, a connection test, imports
+/// from other tools and a theme. Every step can be skipped.
+pub struct SetupWizard {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    flow: SetupFlow,
+    /// The profile editor, one section per page.
+    form: ConnectionForm,
+    /// The profile under test, once built.
+    profile: Option<ConnectionProfile>,
+    /// What happened to the config directory, shown on the welcome page.
+    config_note: String,
+    sources: Vec<(ForeignFormat, PathBuf)>,
+    /// Cursor among `sources`; one past the end is "Continue".
+    source_cursor: usize,
+    themes: Vec<String>,
+    theme_cursor: usize,
+    /// Theme picked on the theme page.
+    chosen_theme: Option<String>,
+}
+
+impl SetupWizard {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Setup", theme.clone()).with_size(70, 70),
+            form: ConnectionForm::new(theme.clone()),
+            theme,
+            flow: SetupFlow::new(false),
+            profile: None,
+            config_note: String::new(),
+            sources: Vec::new(),
+            source_cursor: 0,
+            themes: Theme::BUILTIN.iter().map(|t| t.to_string()).collect(),
+            theme_cursor: 0,
+            chosen_theme: None,
+        }
+    }
+
+    pub fn show(
+        &mut self,
+        config_note: String,
+        sources: Vec<(ForeignFormat, PathBuf)>,
+        current_theme: &str,
+    ) {
+        self.flow = SetupFlow::new(!sources.is_empty());
+        self.form = ConnectionForm::new(self.theme.clone());
+        self.form.new_profile();
+        self.form.set_section(None);
+        self.profile = None;
+        self.config_note = config_note;
+        self.sources = sources;
+        self.source_cursor = 0;
+        self.theme_cursor = self
+            .themes
+            .iter()
+            .position(|t| t == current_theme)
+            .unwrap_or(0);
+        self.chosen_theme = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn step(&self) -> Step {
+        self.flow.step
+    }
+
+    /// Whether the connection test for `profile_name` is waiting on a result.
+    pub fn is_testing(&self, profile_name: &str) -> bool {
+        self.visible
+            && self.flow.test == TestState::Running
+            && self
+                .profile
+                .as_ref()
+                .is_some_and(|p| p.name == profile_name)
+    }
+
+    /// Record the test result: the base DN in use, or why it failed.
+    pub fn test_finished(&mut self, result: Result<String, String>) {
+        self.flow.test_finished(result);
+    }
+
+    /// Move to `step`, setting up the form for it.
+    fn enter(&mut self) {
+        let step = self.flow.step;
+        if step == Step::Security && *self.form.tls_mode() == TlsMode::Auto {
+            // Recommend encryption that suits the port
+            self.form.set_tls_mode(match self.form.port() {
+                Some(636) => TlsMode::Ldaps,
+                _ => TlsMode::StartTls,
+            });
+        }
+        self.form.set_section(step.section());
+    }
+
+    fn advance(&mut self) -> Action {
+        self.flow.advance();
+        self.enter();
+        Action::None
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::F(10) if self.flow.step != Step::Done => {
+                let action = self.leave_profile();
+                self.flow.finish();
+                self.enter();
+                action
+            }
+            KeyCode::Esc => self.skip(),
+            KeyCode::Enter => self.confirm(),
+            _ => match self.flow.step {
+                Step::Server | Step::Security | Step::Auth | Step::BaseDn => {
+                    self.form.handle_key_event(key)
+                }
+                Step::Test if key.code == KeyCode::Char('e') => {
+                    if self.flow.test != TestState::Running {
+                        self.flow.edit_profile();
+                        self.enter();
+                    }
+                    Action::None
+                }
+                Step::Import => {
+                    match key.code {
+                        KeyCode::Up | KeyCode::Char('k') => {
+                            self.source_cursor = self.source_cursor.saturating_sub(1);
+                        }
+                        KeyCode::Down | KeyCode::Char('j') => {
+                            self.source_cursor = (self.source_cursor + 1).min(self.sources.len());
+                        }
+                        _ => {}
+                    }
+                    Action::None
+                }
+                Step::Theme => {
+                    match key.code {
+                        KeyCode::Up | KeyCode::Char('k') => {
+                            self.theme_cursor = self.theme_cursor.saturating_sub(1);
+                        }
+                        KeyCode::Down | KeyCode::Char('j') => {
+                            self.theme_cursor =
+                                (self.theme_cursor + 1).min(self.themes.len().saturating_sub(1));
+                        }
+                        _ => {}
+                    }
+                    Action::None
+                }
+                _ => Action::None,
+            },
+        }
+    }
+
+    /// Enter: complete the current step.
+    fn confirm(&mut self) -> Action {
+        match self.flow.step {
+            Step::Server => match self.form.profile() {
+                Ok(_) => self.advance(),
+                Err(e) => Action::ErrorMessage(e),
+            },
+            Step::BaseDn => self.start_test(),
+            Step::Test => match self.flow.test {
+                TestState::Passed(_) => self.advance(),
+                TestState::Running => Action::None,
+                TestState::NotRun | TestState::Failed(_) => self.start_test(),
+            },
+            Step::Import => match self.sources.get(self.source_cursor) {
+                Some((_, path)) => {
+                    let path = path.clone();
+                    self.source_cursor += 1;
+                    Action::SetupImport(path)
+                }
+                None => self.advance(),
+            },
+            Step::Theme => {
+                self.chosen_theme = self.themes.get(self.theme_cursor).cloned();
+                self.advance()
+            }
+            Step::Done => {
+                self.hide();
+                Action::SetupFinished(self.chosen_theme.take())
+            }
+            Step::Welcome | Step::Security | Step::Auth => self.advance(),
+        }
+    }
+
+    /// Esc: leave the current step as it is.
+    fn skip(&mut self) -> Action {
+        let action = match self.flow.step {
+            Step::Test => self.leave_profile(),
+            Step::Done => return self.confirm(),
+            _ => Action::None,
+        };
+        self.flow.skip();
+        self.enter();
+        action
+    }
+
+    fn start_test(&mut self) -> Action {
+        match self.form.profile() {
+            Ok(profile) => {
+                self.flow.start_test();
+                self.enter();
+                self.profile = Some(profile.clone());
+                Action::SetupTestConnection(Box::new(profile))
+            }
+            Err(e) => Action::ErrorMessage(e),
+        }
+    }
+
+    /// Leaving the profile pages early: a running test is cancelled and
+    /// a profile that has not connected yet is saved as entered.
+    fn leave_profile(&mut self) -> Action {
+        let on_profile_pages = self.flow.step.section().is_some() || self.flow.step == Step::Test;
+        if !self.flow.profile || !on_profile_pages {
+            return Action::None;
+        }
+        match self.flow.test {
+            TestState::Running => Action::CancelConnect,
+            TestState::Passed(_) => Action::None,
+            TestState::NotRun | TestState::Failed(_) => match self.form.profile() {
+                Ok(profile) => {
+                    self.profile = Some(profile.clone());
+                    Action::SetupSaveProfile(Box::new(profile))
+                }
+                Err(_) => Action::None,
+            },
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let (n, total) = self.flow.position();
+        let step = self.flow.step;
+        let layout = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        let heading = Line::from(vec![
+            Span::styled(step.title(), self.theme.header()),
+            Span::styled(format!("  (step {} of {})", n, total), self.theme.dimmed()),
+        ]);
+        frame.render_widget(Paragraph::new(heading), layout[0]);
+
+        let body = layout[1];
+        let text = self.step_text();
+        if step.section().is_some() {
+            let height = text.len() as u16 + 1;
+            let parts =
+                Layout::vertical([Constraint::Length(height), Constraint::Min(1)]).split(body);
+            frame.render_widget(Paragraph::new(text).wrap(Wrap { trim: false }), parts[0]);
+            self.form.render_section(frame, parts[1]);
+        } else {
+            let mut lines = text;
+            lines.extend(self.step_items());
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), body);
+        }
+
+        let hints = match step {
+            Step::Done => "Enter:finish",
+            Step::Server | Step::BaseDn => "Tab:fields  Enter:next  Esc:skip  F10:skip setup",
+            Step::Auth => "Tab:fields  F3:credential  Enter:next  Esc:skip  F10:skip setup",
+            Step::Security => "F2:change  Enter:next  Esc:skip  F10:skip setup",
+            Step::Test => "Enter:test/next  e:edit profile  Esc:skip  F10:skip setup",
+            Step::Import => "\u{2191}/\u{2193}:select  Enter:import  Esc:skip  F10:skip setup",
+            Step::Theme => {
+                "\u{2191}/\u{2193}:select  Enter:choose  Esc:keep current  F10:skip setup"
+            }
+            _ => "Enter:next  Esc:skip  F10:skip setup",
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(hints, self.theme.dimmed()))),
+            layout[2],
+        );
+    }
+
+    /// The explanation shown at the top of each page.
+    fn step_text(&self) -> Vec<Line<'static>> {
+        let dim = self.theme.dimmed();
+        let normal = self.theme.normal();
+        let lines: Vec<(String, _)> = match self.flow.step {
+            Step::Welcome => vec![
+                ("Welcome to loom. This sets up your first connection.".to_string(), normal),
+                (self.config_note.clone(), dim),
+                (String::new(), dim),
+                ("Press Esc to skip a step, or F10 to skip the rest. Run :setup to come back here later.".to_string(), dim),
+            ],
+            Step::Server => vec![(
+                "The directory server to connect to. An ldap:// or ldaps:// URL in Host fills in the port and security.".to_string(),
+                normal,
+            )],
+            Step::Security => vec![
+                ("How the connection is encrypted.".to_string(), normal),
+                ("StartTLS (recommended) encrypts a connection on the standard port 389. Use LDAPS for port 636. None sends passwords in clear text.".to_string(), dim),
+            ],
+            Step::Auth => vec![
+                ("Who to bind as. Leave Bind DN empty to bind anonymously.".to_string(), normal),
+                ("Credential is where the password comes from: Prompt asks when connecting, Command runs Password Cmd.".to_string(), dim),
+            ],
+            Step::BaseDn => vec![
+                ("Where browsing starts.".to_string(), normal),
+                ("Leave it empty to discover it from the server's namingContexts on every connect (recommended). Enter runs the connection test.".to_string(), dim),
+            ],
+            Step::Test => {
+                let name = self.profile.as_ref().map_or("", |p| p.name.as_str());
+                match &self.flow.test {
+                    TestState::NotRun => vec![(format!("Press Enter to connect to {}.", name), normal)],
+                    TestState::Running => vec![(format!("Connecting to {}...", name), normal)],
+                    TestState::Passed(base_dn) => vec![
+                        (format!("Connected to {}; the profile is saved.", name), self.theme.success()),
+                        (format!("Base DN: {}", base_dn), normal),
+                    ],
+                    TestState::Failed(e) => vec![
+                        (format!("Could not connect to {}: {}", name, e), self.theme.error()),
+                        ("Enter tries again, e goes back to the profile, Esc saves it untested.".to_string(), dim),
+                    ],
+                }
+            }
+            Step::Import => vec![(
+                "Connections from other LDAP tools were found. Import them too?".to_string(),
+                normal,
+            )],
+            Step::Theme => vec![(
+                "Pick a color theme. It applies when setup finishes.".to_string(),
+                normal,
+            )],
+            Step::Done => {
+                let mut lines = vec![("Setup is complete.".to_string(), normal)];
+                lines.push(match (&self.flow.test, &self.profile) {
+                    (TestState::Passed(_), Some(p)) => (format!("Profile: {} (connected)", p.name), dim),
+                    (_, Some(p)) => (format!("Profile: {} (not tested)", p.name), dim),
+                    _ => ("No profile created; press the connect key to add one.".to_string(), dim),
+                });
+                if let Some(theme) = &self.chosen_theme {
+                    lines.push((format!("Theme: {}", theme), dim));
+                }
+                lines
+            }
+        };
+        lines
+            .into_iter()
+            .map(|(text, style)| Line::from(Span::styled(text, style)))
+            .collect()
+    }
+
+    /// The list on the import and theme pages.
+    fn step_items(&self) -> Vec<Line<'static>> {
+        let row = |text: String, selected: bool| {
+            let style = if selected {
+                self.theme.selected().add_modifier(Modifier::BOLD)
+            } else {
+                self.theme.normal()
+            };
+            Line::from(Span::styled(text, style))
+        };
+        let mut lines = vec![Line::from("")];
+        match self.flow.step {
+            Step::Import => {
+                for (i, (format, path)) in self.sources.iter().enumerate() {
+                    lines.push(row(
+                        format!(" {}: {}", format.label(), path.display()),
+                        i == self.source_cursor,
+                    ));
+                }
+                lines.push(row(
+                    " Continue".to_string(),
+                    self.source_cursor == self.sources.len(),
+                ));
+            }
+            Step::Theme => {
+                for (i, name) in self.themes.iter().enumerate() {
+                    let preview = Styles::builtin(name).unwrap_or_default();
+                    let selected = i == self.theme_cursor;
+                    let marker = if selected { "\u{25b8} " } else { "  " };
+                    lines.push(Line::from(vec![
+                        Span::styled(format!("{}{:<14}", marker, name), preview.header),
+                        Span::styled(" entry ", preview.normal),
+                        Span::styled(" selected ", preview.selected),
+                        Span::styled(" error ", preview.error),
+                    ]));
+                }
+            }
+            _ => {}
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_full_walk_through() {
+        let mut flow = SetupFlow::new(true);
+        flow.advance();
+        assert_eq!(flow.step, Step::Server);
+        flow.advance();
+        assert!(flow.profile);
+        flow.advance();
+        flow.advance();
+        assert_eq!(flow.step, Step::BaseDn);
+        flow.start_test();
+        assert_eq!(flow.step, Step::Test);
+        assert_eq!(flow.position(), (6, 9));
+
+        // Another test's result is ignored once this one ended
+        flow.test_finished(Ok("dc=example,dc=com".to_string()));
+        flow.test_finished(Err("late".to_string()));
+        assert_eq!(
+            flow.test,
+            TestState::Passed("dc=example,dc=com".to_string())
+        );
+        flow.advance();
+        assert_eq!(flow.step, Step::Import);
+        flow.advance();
+        flow.advance();
+        assert_eq!(flow.step, Step::Done);
+    }
+
+    #[test]
+    fn test_every_step_can_be_skipped() {
+        let mut flow = SetupFlow::new(true);
+        let mut visited = vec![flow.step];
+        while flow.step != Step::Done {
+            flow.skip();
+            visited.push(flow.step);
+        }
+        // Skipping the server step skips the rest of the profile
+        assert_eq!(
+            visited,
+            [
+                Step::Welcome,
+                Step::Server,
+                Step::Import,
+                Step::Theme,
+                Step::Done
+            ]
+        );
+        assert!(!flow.profile);
+
+        // Without anything to import that page is left out
+        let mut flow = SetupFlow::new(false);
+        flow.advance();
+        flow.advance();
+        flow.skip();
+        flow.skip();
+        assert_eq!(flow.step, Step::BaseDn);
+        flow.skip();
+        assert_eq!(flow.step, Step::Test);
+        flow.skip();
+        assert_eq!(flow.step, Step::Theme);
+        assert_eq!(flow.position(), (7, 8));
+    }
+
+    #[test]
+    fn test_failed_test_retry_and_edit() {
+        let mut flow = SetupFlow::new(false);
+        flow.start_test();
+        flow.test_finished(Err("connection refused".to_string()));
+        assert_eq!(flow.step, Step::Test);
+
+        flow.edit_profile();
+        assert_eq!((flow.step, &flow.test), (Step::Server, &TestState::NotRun));
+
+        // Skipping while the test runs cancels it
+        flow.start_test();
+        flow.skip();
+        assert_eq!(flow.test, TestState::Failed("Cancelled".to_string()));
+        assert_eq!(flow.step, Step::Theme);
+        flow.finish();
+        assert_eq!(flow.step, Step::Done);
+    }
+
+    #[test]
+    fn test_wizard_builds_profile_with_recommended_security() {
+        let key = |code| KeyEvent::new(code, crossterm::event::KeyModifiers::NONE);
+        let mut wizard = SetupWizard::new(Theme::load("dark"));
+        wizard.show(String::new(), Vec::new(), "nord");
+        wizard.handle_key_event(key(KeyCode::Enter));
+
+        // Host is required before moving on
+        assert!(matches!(
+            wizard.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+        wizard.handle_key_event(key(KeyCode::Tab));
+        for c in "ldap.example.com".chars() {
+            wizard.handle_key_event(key(KeyCode::Char(c)));
+        }
+        wizard.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(wizard.step(), Step::Security);
+        wizard.handle_key_event(key(KeyCode::Enter));
+        wizard.handle_key_event(key(KeyCode::Esc));
+        assert_eq!(wizard.step(), Step::BaseDn);
+
+        let Action::SetupTestConnection(profile) = wizard.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected the connection test");
+        };
+        assert_eq!(profile.host, "ldap.example.com");
+        assert_eq!(profile.tls_mode, TlsMode::StartTls);
+        assert_eq!(profile.base_dn, None);
+        assert!(wizard.is_testing("ldap.example.com:389"));
+
+        // Skipping the rest cancels the test; the profile was saved when it started
+        assert!(matches!(
+            wizard.handle_key_event(key(KeyCode::F(10))),
+            Action::CancelConnect
+        ));
+        assert_eq!(wizard.step(), Step::Done);
+        assert!(matches!(
+            wizard.handle_key_event(key(KeyCode::Enter)),
+            Action::SetupFinished(None)
+        ));
+        assert!(!wizard.visible);
+    }
+}
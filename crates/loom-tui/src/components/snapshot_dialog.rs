@@ -0,0 +1,250 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use loom_core::snapshot::{SnapshotProgress, EXTENSION};
+
+use crate::action::Action;
+use crate::components::path_input::PathInput;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Which field is being edited.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SnapshotField {
+    BaseDn,
+    Path,
+}
+
+/// Dialog for saving a read-only snapshot of a subtree: choose the base
+/// DN and file, then follow the progress until it is written.
+pub struct SnapshotDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    active_field: SnapshotField,
+    base_dn: String,
+    path: PathInput,
+    /// Latest stage of the running snapshot; `None` while editing.
+    progress: Option<SnapshotProgress>,
+}
+
+impl SnapshotDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Snapshot", theme.clone()).with_size(60, 30),
+            theme,
+            active_field: SnapshotField::Path,
+            base_dn: String::new(),
+            path: PathInput::default(),
+            progress: None,
+        }
+    }
+
+    /// Open the form for a subtree of the connection labelled `label`.
+    pub fn show(&mut self, base_dn: &str, label: &str) {
+        let date = chrono::Local::now().format("%Y%m%d");
+        let name: String = label
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.base_dn = base_dn.to_string();
+        self.path.set(format!("{}-{}.{}", name, date, EXTENSION));
+        self.active_field = SnapshotField::Path;
+        self.progress = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.progress = None;
+        self.popup.hide();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.progress.is_some()
+    }
+
+    pub fn set_progress(&mut self, progress: SnapshotProgress) {
+        if self.visible {
+            self.progress = Some(progress);
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if self.is_running() {
+            return match key.code {
+                KeyCode::Esc => Action::SnapshotCancel,
+                _ => Action::None,
+            };
+        }
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Tab if self.active_field == SnapshotField::Path && self.path.complete() => {
+                Action::None
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.active_field = match self.active_field {
+                    SnapshotField::BaseDn => SnapshotField::Path,
+                    SnapshotField::Path => SnapshotField::BaseDn,
+                };
+                Action::None
+            }
+            KeyCode::Enter => {
+                if self.base_dn.trim().is_empty() || self.path.value().trim().is_empty() {
+                    return Action::ErrorMessage("Base DN and file are required".to_string());
+                }
+                self.progress = Some(SnapshotProgress::RootDse);
+                Action::SnapshotExecute {
+                    base_dn: self.base_dn.trim().to_string(),
+                    path: self.path.value().trim().to_string(),
+                }
+            }
+            KeyCode::Backspace => {
+                self.active_buffer_mut().pop();
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                self.active_buffer_mut().push(c);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn active_buffer_mut(&mut self) -> &mut String {
+        match self.active_field {
+            SnapshotField::BaseDn => &mut self.base_dn,
+            SnapshotField::Path => self.path.value_mut(),
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(" Snapshot ")
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Length(5), // Fields or progress
+            Constraint::Min(1),    // Hints
+        ])
+        .split(inner);
+
+        let field = |label: &'static str, value: &str, active: bool| {
+            let style = if active {
+                self.theme.selected()
+            } else {
+                self.theme.normal()
+            };
+            let mut spans = vec![
+                Span::styled(label, self.theme.header()),
+                Span::styled(value.to_string(), style),
+            ];
+            if active && !self.is_running() {
+                spans.push(Span::styled("_", self.theme.command_prompt()));
+            }
+            Line::from(spans)
+        };
+        let mut lines = vec![
+            field(
+                "Base DN: ",
+                &self.base_dn,
+                self.active_field == SnapshotField::BaseDn,
+            ),
+            field(
+                "File:    ",
+                self.path.value(),
+                self.active_field == SnapshotField::Path,
+            ),
+            Line::default(),
+        ];
+        let hints = match self.progress {
+            Some(progress) => {
+                lines.push(Line::from(Span::styled(
+                    progress.label(),
+                    self.theme.normal(),
+                )));
+                "Esc:cancel and remove the partial file"
+            }
+            None => {
+                lines.push(Line::from(Span::styled(
+                    "Entries, schema and Root DSE, readable in offline mode",
+                    self.theme.dimmed(),
+                )));
+                "Tab:complete or switch field  Enter:start  Esc:cancel"
+            }
+        };
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(hints, self.theme.dimmed()))),
+            layout[1],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_start_then_cancel() {
+        let mut dialog = SnapshotDialog::new(Theme::load("dark"));
+        dialog.show("ou=People,dc=example,dc=com", "Prod LDAP");
+        assert!(dialog.path.value().starts_with("Prod_LDAP-"));
+        assert!(dialog.path.value().ends_with(".loomz"));
+
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::SnapshotExecute { base_dn, path } => {
+                assert_eq!(base_dn, "ou=People,dc=example,dc=com");
+                assert_eq!(path, dialog.path.value());
+            }
+            other => panic!("expected SnapshotExecute, got {:?}", other),
+        }
+        assert!(dialog.is_running());
+
+        // Typing is ignored while the snapshot runs; Esc cancels it
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('x'))),
+            Action::None
+        ));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Esc)),
+            Action::SnapshotCancel
+        ));
+    }
+
+    #[test]
+    fn test_progress_ignored_when_hidden() {
+        let mut dialog = SnapshotDialog::new(Theme::load("dark"));
+        dialog.set_progress(SnapshotProgress::Entries(10));
+        assert!(!dialog.is_running());
+    }
+}
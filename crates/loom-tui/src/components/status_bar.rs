@@ -1,104 +1,415 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
 use ratatui::layout::Rect;
+use ratatui::style::Style;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::Paragraph;
 use ratatui::Frame;
 
+use loom_core::health::ConnectionHealth;
+
 use crate::component::Component;
+use crate::components::connections_tree::READ_ONLY_MARKER;
 use crate::keymap::Keymap;
 use crate::theme::Theme;
+use crate::widgets::spinner::Spinner;
+use crate::widgets::width::{display_width, truncate_to_width};
+
+/// Messages kept for the `:messages` popup.
+const LOG_CAPACITY: usize = 200;
+
+/// Shortest time a message stays in the status bar before a newer one
+/// takes its place.
+pub const MIN_SHOWN: Duration = Duration::from_secs(5);
+
+/// How a posted message is colored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A message posted to the status bar, as kept in the message log.
+#[derive(Debug, Clone)]
+pub struct LoggedMessage {
+    pub at: DateTime<Local>,
+    pub severity: Severity,
+    pub text: String,
+}
+
+/// The message on screen, and how many newer ones were passed over for
+/// it while it was held.
+struct Shown {
+    message: LoggedMessage,
+    since: Instant,
+    skipped: usize,
+}
 
-/// Bottom status bar showing connection info (left) and keybinding hints (right).
+/// Bottom status bar: the profile and bound identity (left), the search
+/// base and selection with the latest message (middle), and the running
+/// operation or keybinding hints (right). Every message is kept in a log
+/// of the last 200 for the `:messages` popup.
 pub struct StatusBar {
-    pub connection_info: String,
-    pub entry_count: Option<usize>,
-    pub message: Option<String>,
-    pub message_is_error: bool,
+    /// Profile of the active tab; empty when not connected.
+    pub profile: String,
+    /// Who the active connection is bound as, when the server says.
+    pub identity: Option<String>,
+    /// Base DN searches on the active tab start from.
+    pub base_dn: Option<String>,
+    /// Search results picked out for a multi-entry action.
+    pub selected: usize,
+    /// Operations running on the active connection.
+    pub running: usize,
+    /// Label and elapsed time of the newest running operation.
+    pub current: Option<String>,
+    /// Connection attempts still in progress.
+    pub connecting: Option<String>,
+    /// Health of the active connection; `None` when offline or not connected.
+    pub health: Option<ConnectionHealth>,
+    /// Changes queued on the active connection while offline edits are
+    /// on; `None` when writes go to the server.
+    pub pending: Option<usize>,
+    /// The active tab's profile is read-only, so writes are refused.
+    pub read_only: bool,
+    log: VecDeque<LoggedMessage>,
+    /// The newest message posted since the one on screen went up, and
+    /// how many were.
+    next: Option<LoggedMessage>,
+    waiting: usize,
+    shown: Option<Shown>,
+    spinner: Spinner,
+    activity_key: String,
     theme: Theme,
     hints: String,
 }
 
 impl StatusBar {
     pub fn new(theme: Theme, keymap: &Keymap) -> Self {
-        let hints = format!(
+        let mut status_bar = Self {
+            profile: String::new(),
+            identity: None,
+            base_dn: None,
+            selected: 0,
+            running: 0,
+            current: None,
+            connecting: None,
+            health: None,
+            pending: None,
+            read_only: false,
+            log: VecDeque::new(),
+            next: None,
+            waiting: 0,
+            shown: None,
+            spinner: Spinner::new(theme.warning()),
+            activity_key: String::new(),
+            theme,
+            hints: String::new(),
+        };
+        status_bar.set_keys(keymap);
+        status_bar
+    }
+
+    /// Show the key hints of `keymap`.
+    pub fn set_keys(&mut self, keymap: &Keymap) {
+        self.hints = format!(
             "{}:profiles {}:help F3:about {}:quit",
             keymap.hint("switch_to_profiles"),
             keymap.hint("show_help"),
             keymap.hint("quit"),
         );
-        Self {
-            connection_info: String::new(),
-            entry_count: None,
-            message: None,
-            message_is_error: false,
-            theme,
-            hints,
-        }
+        self.activity_key = keymap.hint("show_activity").to_string();
     }
 
-    pub fn set_connected(&mut self, host: &str, server_type: &str) {
-        self.connection_info = format!("{} ({})", host, server_type);
+    pub fn set_connected(&mut self, profile: &str, identity: Option<&str>, base_dn: &str) {
+        self.profile = profile.to_string();
+        self.identity = identity.map(str::to_string);
+        self.base_dn = Some(base_dn.to_string());
     }
 
     pub fn set_disconnected(&mut self) {
-        self.connection_info = String::new();
-        self.entry_count = None;
+        self.profile = String::new();
+        self.identity = None;
+        self.base_dn = None;
+        self.selected = 0;
+        self.health = None;
+        self.read_only = false;
     }
 
     pub fn set_message(&mut self, text: String) {
-        self.message = Some(text);
-        self.message_is_error = false;
+        self.post(Severity::Info, text, Instant::now());
+    }
+
+    pub fn set_warning(&mut self, text: String) {
+        self.post(Severity::Warning, text, Instant::now());
     }
 
     pub fn set_error(&mut self, text: String) {
-        self.message = Some(text);
-        self.message_is_error = true;
+        self.post(Severity::Error, text, Instant::now());
+    }
+
+    /// Keep a message in the log without showing it in the bar, for
+    /// messages already on screen elsewhere.
+    pub fn log(&mut self, severity: Severity, text: String) {
+        self.record(severity, text);
+    }
+
+    /// Every message posted, oldest first.
+    pub fn messages(&self) -> &VecDeque<LoggedMessage> {
+        &self.log
+    }
+
+    /// The message posted last, shown or not.
+    pub fn latest(&self) -> Option<&LoggedMessage> {
+        self.log.back()
+    }
+
+    /// The text of the message posted last.
+    pub fn latest_text(&self) -> Option<&str> {
+        self.latest().map(|m| m.text.as_str())
+    }
+
+    /// The message on screen.
+    pub fn shown(&self) -> Option<&LoggedMessage> {
+        self.shown.as_ref().map(|s| &s.message)
+    }
+
+    fn record(&mut self, severity: Severity, text: String) -> LoggedMessage {
+        let message = LoggedMessage {
+            at: Local::now(),
+            severity,
+            text,
+        };
+        self.log.push_back(message.clone());
+        if self.log.len() > LOG_CAPACITY {
+            self.log.pop_front();
+        }
+        message
+    }
+
+    /// Log a message and show it, unless the one on screen went up less
+    /// than [`MIN_SHOWN`] ago; then it waits for [`StatusBar::tick`].
+    fn post(&mut self, severity: Severity, text: String, now: Instant) {
+        if self.shown.as_ref().is_some_and(|s| s.message.text == text) {
+            return;
+        }
+        let message = self.record(severity, text);
+        match &self.shown {
+            Some(shown) if now.duration_since(shown.since) < MIN_SHOWN => {
+                self.next = Some(message);
+                self.waiting += 1;
+            }
+            _ => self.show(message, now),
+        }
+    }
+
+    fn show(&mut self, message: LoggedMessage, now: Instant) {
+        self.shown = Some(Shown {
+            message,
+            since: now,
+            skipped: self.waiting.saturating_sub(1),
+        });
+        self.next = None;
+        self.waiting = 0;
+    }
+
+    /// Advance the activity spinner one frame, and put up the newest
+    /// waiting message once the one on screen has had its time.
+    pub fn tick(&mut self) {
+        self.spinner.tick();
+        self.release(Instant::now());
+    }
+
+    fn release(&mut self, now: Instant) {
+        let due = self
+            .shown
+            .as_ref()
+            .is_some_and(|s| now.duration_since(s.since) >= MIN_SHOWN);
+        if due {
+            if let Some(message) = self.next.take() {
+                self.show(message, now);
+            }
+        }
+    }
+
+    /// `⠋ Search (cn=x*) 1.2s (Esc:cancel)` while one operation runs,
+    /// `⠋ 3 running (Ctrl+t)` while several do, else empty.
+    fn activity(&self) -> String {
+        if self.running == 0 {
+            return match &self.connecting {
+                Some(line) => format!("{} {} ", self.spinner.frame(), line),
+                None => String::new(),
+            };
+        }
+        if let (1, Some(current)) = (self.running, &self.current) {
+            return format!("{} {} (Esc:cancel) ", self.spinner.frame(), current);
+        }
+        format!(
+            "{} {} running ({}) ",
+            self.spinner.frame(),
+            self.running,
+            self.activity_key
+        )
+    }
+
+    fn severity_style(&self, severity: Severity) -> Style {
+        match severity {
+            Severity::Info => self.theme.status_bar(),
+            Severity::Warning => self.theme.status_bar().patch(self.theme.warning()),
+            Severity::Error => self.theme.error(),
+        }
     }
 }
 
 impl Component for StatusBar {
     fn render(&self, frame: &mut Frame, area: Rect, _focused: bool) {
         let width = area.width as usize;
+        let bar = self.theme.status_bar();
 
-        // Build left side: connection info + entry count
-        let left = if self.connection_info.is_empty() {
-            String::new()
-        } else {
-            let mut s = format!(" {}", self.connection_info);
-            if let Some(count) = self.entry_count {
-                use std::fmt::Write;
-                let _ = write!(s, " | {} entries", count);
+        // Left: health symbol, profile and who it is bound as
+        let (symbol, symbol_style) = match self.health {
+            Some(health) if !self.profile.is_empty() => {
+                let style = match health {
+                    ConnectionHealth::Connected => self.theme.success(),
+                    ConnectionHealth::Reconnecting => self.theme.warning(),
+                    ConnectionHealth::Disconnected => self.theme.error(),
+                };
+                (format!(" {}", health.symbol()), style)
             }
-            s
+            _ => (String::new(), bar),
         };
-
-        // Middle: transient message
-        let mid = self
-            .message
-            .as_deref()
-            .map(|m| format!(" {} ", m))
+        let mut left = String::new();
+        if !self.profile.is_empty() {
+            left = format!(" {}", self.profile);
+            if self.read_only {
+                left.push_str(&format!(" {}", READ_ONLY_MARKER));
+            }
+            if let Some(identity) = &self.identity {
+                left.push_str(&format!(" as {}", truncate_to_width(identity, 40)));
+            }
+        }
+        let offline = self
+            .pending
+            .map(|n| format!(" [offline edits: {} pending]", n))
             .unwrap_or_default();
-        let mid_style = if self.message_is_error {
-            self.theme.error
+
+        // Right: the running operation, else keybinding hints
+        let activity = self.activity();
+        let right = if activity.is_empty() {
+            format!("{} ", self.hints)
+        } else {
+            activity
+        };
+        let right_style = if self.running > 0 || self.connecting.is_some() {
+            bar.patch(self.theme.warning())
         } else {
-            self.theme.status_bar
+            bar
         };
 
-        // Right side: keybinding hints (with trailing space)
-        let right = format!("{} ", self.hints);
-
-        let left_len = left.len();
-        let mid_len = mid.len();
-        let right_len = right.len();
-        let gap = width.saturating_sub(left_len + mid_len + right_len);
-        let padding = " ".repeat(gap);
-
-        let line = Line::from(vec![
-            Span::styled(left, self.theme.status_bar),
-            Span::styled(mid, mid_style),
-            Span::styled(padding, self.theme.status_bar),
-            Span::styled(right, self.theme.status_bar),
-        ]);
-        let bar = Paragraph::new(line);
-        frame.render_widget(bar, area);
+        let used = display_width(&symbol)
+            + display_width(&left)
+            + display_width(&offline)
+            + display_width(&right);
+        let mut room = width.saturating_sub(used);
+
+        // Middle: base DN and selection, then the message, cut to fit
+        let mut context = String::new();
+        if let Some(base) = &self.base_dn {
+            context = format!(" \u{2502} {}", base);
+            if self.selected > 0 {
+                context.push_str(&format!(" \u{2502} {} selected", self.selected));
+            }
+        }
+        let message = self.shown.as_ref().map(|shown| {
+            let more = if shown.skipped > 0 || self.waiting > 0 {
+                format!(" (+{} in :messages)", shown.skipped + self.waiting)
+            } else {
+                String::new()
+            };
+            (
+                format!(" \u{2502} {}{} ", shown.message.text, more),
+                self.severity_style(shown.message.severity),
+            )
+        });
+        // The message goes first when both do not fit
+        let message_width = message.as_ref().map_or(0, |(m, _)| display_width(m));
+        let context = truncate_to_width(&context, room.saturating_sub(message_width.min(room / 2)));
+        room = room.saturating_sub(display_width(&context));
+        let message = message.map(|(m, style)| (truncate_to_width(&m, room), style));
+        room = room.saturating_sub(message.as_ref().map_or(0, |(m, _)| display_width(m)));
+
+        let mut spans = vec![
+            Span::styled(symbol, bar.patch(symbol_style)),
+            Span::styled(left, bar),
+            Span::styled(offline, bar.patch(self.theme.warning())),
+            Span::styled(context, bar.patch(self.theme.dimmed())),
+        ];
+        if let Some((text, style)) = message {
+            spans.push(Span::styled(text, style));
+        }
+        spans.push(Span::styled(" ".repeat(room), bar));
+        spans.push(Span::styled(right, right_style));
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::KeybindingConfig;
+
+    fn bar() -> StatusBar {
+        StatusBar::new(
+            Theme::default(),
+            &Keymap::from_config(&KeybindingConfig::default()),
+        )
+    }
+
+    #[test]
+    fn test_message_held_for_min_shown() {
+        let mut bar = bar();
+        let start = Instant::now();
+        bar.post(Severity::Error, "Bind failed".to_string(), start);
+        bar.post(Severity::Info, "Loaded 3 entries".to_string(), start);
+        bar.post(Severity::Info, "Loaded 5 entries".to_string(), start);
+        assert_eq!(bar.shown().unwrap().text, "Bind failed");
+        assert_eq!(bar.latest_text(), Some("Loaded 5 entries"));
+
+        bar.release(start + Duration::from_secs(4));
+        assert_eq!(bar.shown().unwrap().text, "Bind failed");
+
+        // The newest waiting one goes up; the one passed over is counted
+        bar.release(start + MIN_SHOWN);
+        assert_eq!(bar.shown().unwrap().text, "Loaded 5 entries");
+        assert_eq!(bar.shown.as_ref().unwrap().skipped, 1);
+
+        let later = start + MIN_SHOWN * 2;
+        bar.post(Severity::Info, "Saved".to_string(), later);
+        assert_eq!(bar.shown().unwrap().text, "Saved");
+        assert_eq!(bar.shown.as_ref().unwrap().skipped, 0);
+    }
+
+    #[test]
+    fn test_log_keeps_last_200_with_severity() {
+        let mut bar = bar();
+        let now = Instant::now();
+        for i in 0..LOG_CAPACITY + 5 {
+            bar.post(Severity::Info, format!("message {}", i), now);
+        }
+        bar.post(Severity::Error, "failed".to_string(), now);
+        assert_eq!(bar.messages().len(), LOG_CAPACITY);
+        assert_eq!(bar.messages()[0].text, "message 6");
+        assert_eq!(bar.latest().unwrap().severity, Severity::Error);
+    }
+
+    #[test]
+    fn test_repeated_message_is_logged_once() {
+        let mut bar = bar();
+        let now = Instant::now();
+        bar.post(Severity::Info, "Reconnecting...".to_string(), now);
+        bar.post(Severity::Info, "Reconnecting...".to_string(), now);
+        assert_eq!(bar.messages().len(), 1);
     }
 }
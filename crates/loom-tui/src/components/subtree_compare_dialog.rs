@@ -0,0 +1,342 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use loom_core::subtree_diff::{parse_ignore_list, SubtreeCompareOptions, SubtreeCompareProgress};
+
+use crate::action::{Action, ConnectionId};
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Which field is being edited.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareField {
+    BaseDn,
+    Against,
+    Ignore,
+    Operational,
+}
+
+impl CompareField {
+    fn next(self) -> Self {
+        match self {
+            CompareField::BaseDn => CompareField::Against,
+            CompareField::Against => CompareField::Ignore,
+            CompareField::Ignore => CompareField::Operational,
+            CompareField::Operational => CompareField::BaseDn,
+        }
+    }
+
+    fn prev(self) -> Self {
+        match self {
+            CompareField::BaseDn => CompareField::Operational,
+            CompareField::Against => CompareField::BaseDn,
+            CompareField::Ignore => CompareField::Against,
+            CompareField::Operational => CompareField::Ignore,
+        }
+    }
+}
+
+/// Dialog for comparing a subtree with the same subtree on another open
+/// connection, e.g. a replica: choose the base DN, the other connection
+/// and what to ignore, then follow the progress until the report is in.
+pub struct SubtreeCompareDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    active_field: CompareField,
+    base_dn: String,
+    /// Label of the active connection, the left side.
+    label: String,
+    /// Other live connections to compare against, `(id, label)`.
+    targets: Vec<(ConnectionId, String)>,
+    target: usize,
+    /// Attributes to ignore, as typed; kept between comparisons.
+    ignore: String,
+    skip_operational: bool,
+    /// Latest stage of the running comparison; `None` while editing.
+    progress: Option<SubtreeCompareProgress>,
+}
+
+impl SubtreeCompareDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Compare Subtree", theme.clone()).with_size(60, 35),
+            theme,
+            active_field: CompareField::BaseDn,
+            base_dn: String::new(),
+            label: String::new(),
+            targets: Vec::new(),
+            target: 0,
+            ignore: String::new(),
+            skip_operational: true,
+            progress: None,
+        }
+    }
+
+    /// Open the form for `base_dn` on the connection labelled `label`.
+    pub fn show(&mut self, base_dn: &str, label: &str, targets: Vec<(ConnectionId, String)>) {
+        self.base_dn = base_dn.to_string();
+        self.label = label.to_string();
+        self.targets = targets;
+        self.target = 0;
+        self.active_field = CompareField::BaseDn;
+        self.progress = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.progress = None;
+        self.popup.hide();
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.progress.is_some()
+    }
+
+    pub fn set_progress(&mut self, progress: SubtreeCompareProgress) {
+        if self.visible {
+            self.progress = Some(progress);
+        }
+    }
+
+    /// Label of the connection compared against.
+    pub fn target_label(&self) -> &str {
+        self.targets
+            .get(self.target)
+            .map(|(_, label)| label.as_str())
+            .unwrap_or_default()
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if self.is_running() {
+            return match key.code {
+                KeyCode::Esc => Action::SubtreeCompareCancel,
+                _ => Action::None,
+            };
+        }
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Tab | KeyCode::Down => {
+                self.active_field = self.active_field.next();
+                Action::None
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                self.active_field = self.active_field.prev();
+                Action::None
+            }
+            KeyCode::Left if self.active_field == CompareField::Against => {
+                self.target =
+                    (self.target + self.targets.len().max(1) - 1) % self.targets.len().max(1);
+                Action::None
+            }
+            KeyCode::Right | KeyCode::Char(' ') if self.active_field == CompareField::Against => {
+                self.target = (self.target + 1) % self.targets.len().max(1);
+                Action::None
+            }
+            KeyCode::Char(' ') if self.active_field == CompareField::Operational => {
+                self.skip_operational = !self.skip_operational;
+                Action::None
+            }
+            KeyCode::Enter => {
+                if self.base_dn.trim().is_empty() {
+                    return Action::ErrorMessage("Base DN is required".to_string());
+                }
+                let Some(&(other, _)) = self.targets.get(self.target) else {
+                    return Action::ErrorMessage("No other connection to compare with".to_string());
+                };
+                self.progress = Some(SubtreeCompareProgress::Left(0));
+                Action::SubtreeCompareExecute {
+                    base_dn: self.base_dn.trim().to_string(),
+                    other,
+                    options: SubtreeCompareOptions {
+                        skip_operational: self.skip_operational,
+                        ignore: parse_ignore_list(&self.ignore),
+                    },
+                }
+            }
+            KeyCode::Backspace => {
+                if let Some(buffer) = self.active_buffer_mut() {
+                    buffer.pop();
+                }
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                if let Some(buffer) = self.active_buffer_mut() {
+                    buffer.push(c);
+                }
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn active_buffer_mut(&mut self) -> Option<&mut String> {
+        match self.active_field {
+            CompareField::BaseDn => Some(&mut self.base_dn),
+            CompareField::Ignore => Some(&mut self.ignore),
+            CompareField::Against | CompareField::Operational => None,
+        }
+    }
+
+    fn progress_label(&self, progress: SubtreeCompareProgress) -> String {
+        match progress {
+            SubtreeCompareProgress::Left(n) => format!("Reading {}: {} entries", self.label, n),
+            SubtreeCompareProgress::Right(n) => {
+                format!("Comparing with {}: {} entries", self.target_label(), n)
+            }
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([
+            Constraint::Length(7), // Fields and progress
+            Constraint::Min(1),    // Hints
+        ])
+        .split(inner);
+
+        let field = |label: &'static str, value: String, field: CompareField| {
+            let active = self.active_field == field;
+            let style = if active {
+                self.theme.selected()
+            } else {
+                self.theme.normal()
+            };
+            let mut spans = vec![
+                Span::styled(label, self.theme.header()),
+                Span::styled(value, style),
+            ];
+            let typed = matches!(field, CompareField::BaseDn | CompareField::Ignore);
+            if active && typed && !self.is_running() {
+                spans.push(Span::styled("_", self.theme.command_prompt()));
+            }
+            Line::from(spans)
+        };
+        let against = if self.targets.len() > 1 {
+            format!("\u{25c2} {} \u{25b8}", self.target_label())
+        } else {
+            self.target_label().to_string()
+        };
+        let mut lines = vec![
+            field("Base DN:     ", self.base_dn.clone(), CompareField::BaseDn),
+            Line::from(vec![
+                Span::styled("Compare:     ", self.theme.header()),
+                Span::styled(self.label.clone(), self.theme.normal()),
+            ]),
+            field("With:        ", against, CompareField::Against),
+            field("Ignore:      ", self.ignore.clone(), CompareField::Ignore),
+            field(
+                "Operational: ",
+                if self.skip_operational {
+                    "[x] skip".to_string()
+                } else {
+                    "[ ] compare".to_string()
+                },
+                CompareField::Operational,
+            ),
+            Line::default(),
+        ];
+        let hints = match self.progress {
+            Some(progress) => {
+                lines.push(Line::from(Span::styled(
+                    self.progress_label(progress),
+                    self.theme.normal(),
+                )));
+                "Esc:cancel"
+            }
+            None => {
+                lines.push(Line::from(Span::styled(
+                    "Attributes to ignore, e.g. modifyTimestamp, entryCSN",
+                    self.theme.dimmed(),
+                )));
+                "Tab:next field  \u{2190}/\u{2192}:connection  Space:toggle  Enter:start  Esc:cancel"
+            }
+        };
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(hints, self.theme.dimmed()))),
+            layout[1],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_start_with_options_then_cancel() {
+        let mut dialog = SubtreeCompareDialog::new(Theme::load("dark"));
+        dialog.show(
+            "dc=example,dc=com",
+            "ldap1",
+            vec![(2, "ldap2".to_string()), (3, "ldap3".to_string())],
+        );
+
+        // With: pick the second replica
+        dialog.handle_key_event(key(KeyCode::Tab));
+        dialog.handle_key_event(key(KeyCode::Right));
+        assert_eq!(dialog.target_label(), "ldap3");
+        // Ignore: typed list
+        dialog.handle_key_event(key(KeyCode::Tab));
+        for c in "modifyTimestamp, entryCSN".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        // Operational: compare them too
+        dialog.handle_key_event(key(KeyCode::Tab));
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::SubtreeCompareExecute {
+                base_dn,
+                other,
+                options,
+            } => {
+                assert_eq!(base_dn, "dc=example,dc=com");
+                assert_eq!(other, 3);
+                assert!(!options.skip_operational);
+                assert_eq!(options.ignore, vec!["modifyTimestamp", "entryCSN"]);
+            }
+            other => panic!("expected SubtreeCompareExecute, got {:?}", other),
+        }
+        assert!(dialog.is_running());
+        dialog.set_progress(SubtreeCompareProgress::Right(40));
+        assert_eq!(
+            dialog.progress_label(dialog.progress.unwrap()),
+            "Comparing with ldap3: 40 entries"
+        );
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Esc)),
+            Action::SubtreeCompareCancel
+        ));
+    }
+
+    #[test]
+    fn test_ignore_list_kept_between_comparisons() {
+        let mut dialog = SubtreeCompareDialog::new(Theme::load("dark"));
+        dialog.show("dc=example,dc=com", "ldap1", vec![(2, "ldap2".to_string())]);
+        dialog.handle_key_event(key(KeyCode::BackTab));
+        dialog.handle_key_event(key(KeyCode::BackTab));
+        dialog.handle_key_event(key(KeyCode::Char('x')));
+        dialog.hide();
+        dialog.show("dc=example,dc=com", "ldap1", vec![(2, "ldap2".to_string())]);
+        assert_eq!(dialog.ignore, "x");
+    }
+}
@@ -0,0 +1,338 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use loom_core::diff::DiffKind;
+use loom_core::subtree_diff::SubtreeDiff;
+
+use crate::action::Action;
+use crate::components::path_input::PathInput;
+use crate::components::popup::Popup;
+use crate::paths::expand_path;
+use crate::theme::Theme;
+
+/// One line of the report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportRow {
+    OnlyLeft(usize),
+    OnlyRight(usize),
+    Differs(usize),
+}
+
+/// Full-screen report of a subtree comparison: entries missing on either
+/// side and entries that differ, one per line. Enter opens the compare
+/// view on a differing entry, `s` saves the report as CSV or JSON.
+pub struct SubtreeDiffView {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    report: SubtreeDiff,
+    left_label: String,
+    right_label: String,
+    rows: Vec<ReportRow>,
+    selected: usize,
+    /// File name being typed after `s`.
+    saving: Option<PathInput>,
+}
+
+impl SubtreeDiffView {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Subtree Comparison", theme.clone()).with_size(100, 100),
+            theme,
+            report: SubtreeDiff::default(),
+            left_label: String::new(),
+            right_label: String::new(),
+            rows: Vec::new(),
+            selected: 0,
+            saving: None,
+        }
+    }
+
+    pub fn show(&mut self, report: SubtreeDiff, left_label: &str, right_label: &str) {
+        self.rows = (0..report.only_left.len())
+            .map(ReportRow::OnlyLeft)
+            .chain((0..report.only_right.len()).map(ReportRow::OnlyRight))
+            .chain((0..report.differing.len()).map(ReportRow::Differs))
+            .collect();
+        self.report = report;
+        self.left_label = left_label.to_string();
+        self.right_label = right_label.to_string();
+        self.selected = 0;
+        self.saving = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+        self.saving = None;
+    }
+
+    /// The DN a report row is about.
+    fn row_dn(&self, row: ReportRow) -> &str {
+        match row {
+            ReportRow::OnlyLeft(i) => &self.report.only_left[i],
+            ReportRow::OnlyRight(i) => &self.report.only_right[i],
+            ReportRow::Differs(i) => &self.report.differing[i].left_dn,
+        }
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if let Some(input) = self.saving.as_mut() {
+            return match key.code {
+                KeyCode::Esc => {
+                    self.saving = None;
+                    Action::None
+                }
+                KeyCode::Enter => self.save(),
+                _ => {
+                    input.handle_key(key);
+                    Action::None
+                }
+            };
+        }
+        let last = self.rows.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.selected = (self.selected + 1).min(last);
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::PageDown => {
+                self.selected = (self.selected + 10).min(last);
+                Action::None
+            }
+            KeyCode::PageUp => {
+                self.selected = self.selected.saturating_sub(10);
+                Action::None
+            }
+            KeyCode::Char('g') | KeyCode::Home => {
+                self.selected = 0;
+                Action::None
+            }
+            KeyCode::Char('G') | KeyCode::End => {
+                self.selected = last;
+                Action::None
+            }
+            KeyCode::Enter => match self.rows.get(self.selected) {
+                Some(&ReportRow::Differs(i)) => {
+                    Action::ShowEntryDiff(Box::new(self.report.differing[i].clone()))
+                }
+                _ => Action::None,
+            },
+            KeyCode::Char('c') | KeyCode::Char('y') => match self.rows.get(self.selected) {
+                Some(&row) => Action::CopyToClipboard(self.row_dn(row).to_string()),
+                None => Action::None,
+            },
+            KeyCode::Char('s') if !self.report.is_empty() => {
+                self.saving = Some(PathInput::new("subtree-compare.csv"));
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Write the report to the typed file.
+    fn save(&mut self) -> Action {
+        let Some(input) = self.saving.as_ref() else {
+            return Action::None;
+        };
+        if input.value().trim().is_empty() {
+            return Action::ErrorMessage("Filename is required".to_string());
+        }
+        let path = expand_path(input.value().trim());
+        match self.report.export(&path) {
+            Ok(rows) => {
+                self.saving = None;
+                Action::StatusMessage(format!("Saved {} report rows to {}", rows, path.display()))
+            }
+            Err(e) => Action::ErrorMessage(format!("Failed to write {}: {}", path.display(), e)),
+        }
+    }
+
+    fn row_item(&self, row: ReportRow) -> ListItem<'static> {
+        let dn = self.row_dn(row).to_string();
+        let line = match row {
+            ReportRow::OnlyLeft(_) => Line::from(vec![
+                Span::styled("- ", self.theme.error()),
+                Span::styled(dn, self.theme.normal()),
+                Span::styled(format!("  only on {}", self.left_label), self.theme.error()),
+            ]),
+            ReportRow::OnlyRight(_) => Line::from(vec![
+                Span::styled("+ ", self.theme.success()),
+                Span::styled(dn, self.theme.normal()),
+                Span::styled(
+                    format!("  only on {}", self.right_label),
+                    self.theme.success(),
+                ),
+            ]),
+            ReportRow::Differs(i) => {
+                let attrs: Vec<&str> = self.report.differing[i]
+                    .attributes
+                    .iter()
+                    .filter(|a| a.kind() != DiffKind::Same)
+                    .map(|a| a.attr.as_str())
+                    .collect();
+                Line::from(vec![
+                    Span::styled("~ ", self.theme.warning()),
+                    Span::styled(dn, self.theme.normal()),
+                    Span::styled(format!("  {}", attrs.join(", ")), self.theme.dimmed()),
+                ])
+            }
+        };
+        ListItem::new(line)
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([
+            Constraint::Length(2),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        let headline_style = if self.report.is_empty() {
+            self.theme.success()
+        } else {
+            self.theme.warning()
+        };
+        let header = vec![
+            Line::from(vec![
+                Span::styled(self.report.base_dn.clone(), self.theme.header()),
+                Span::styled(
+                    format!(": {} \u{2194} {}", self.left_label, self.right_label),
+                    self.theme.normal(),
+                ),
+            ]),
+            Line::from(Span::styled(self.report.summary(), headline_style)),
+        ];
+        frame.render_widget(Paragraph::new(header), layout[0]);
+
+        if self.rows.is_empty() {
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(
+                    "Both subtrees hold the same entries with the same values.",
+                    self.theme.success(),
+                ))),
+                layout[1],
+            );
+        } else {
+            let items: Vec<ListItem> = self.rows.iter().map(|&r| self.row_item(r)).collect();
+            let mut state = ListState::default().with_selected(Some(self.selected));
+            frame.render_stateful_widget(
+                List::new(items).highlight_style(self.theme.selected()),
+                layout[1],
+                &mut state,
+            );
+        }
+
+        let hint = match &self.saving {
+            Some(input) => Line::from(vec![
+                Span::styled("Save to: ", self.theme.header()),
+                Span::styled(input.value(), self.theme.normal()),
+                Span::styled("_", self.theme.command_prompt()),
+                Span::styled("  .csv, .json or .jsonl  Enter:save  Esc:cancel", self.theme.dimmed()),
+            ]),
+            None => Line::from(Span::styled(
+                "\u{2191}/\u{2193}:select  Enter:compare entry  c:copy DN  s:save report  Esc:close",
+                self.theme.dimmed(),
+            )),
+        };
+        frame.render_widget(Paragraph::new(hint), layout[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use loom_core::diff::diff_entries;
+    use loom_core::entry::LdapEntry;
+
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    fn report() -> SubtreeDiff {
+        let entry = |mail: &str| {
+            let attributes = BTreeMap::from([("mail".to_string(), vec![mail.to_string()])]);
+            LdapEntry::new("uid=alice,dc=example,dc=com".to_string(), attributes)
+        };
+        SubtreeDiff {
+            base_dn: "dc=example,dc=com".to_string(),
+            only_left: vec!["uid=carol,dc=example,dc=com".to_string()],
+            only_right: Vec::new(),
+            differing: vec![diff_entries(
+                &entry("alice@example.com"),
+                &entry("alice@example.org"),
+            )],
+            matched: 5,
+        }
+    }
+
+    #[test]
+    fn test_enter_opens_differing_entry() {
+        let mut view = SubtreeDiffView::new(Theme::load("dark"));
+        view.show(report(), "ldap1", "ldap2");
+
+        // A missing entry has nothing to compare
+        assert!(matches!(
+            view.handle_key_event(key(KeyCode::Enter)),
+            Action::None
+        ));
+        view.handle_key_event(key(KeyCode::Char('j')));
+        match view.handle_key_event(key(KeyCode::Enter)) {
+            Action::ShowEntryDiff(diff) => assert_eq!(diff.changes()[0].attr, "mail"),
+            other => panic!("expected ShowEntryDiff, got {:?}", other),
+        }
+        // Moving past the end stays on the last row
+        view.handle_key_event(key(KeyCode::Char('j')));
+        assert_eq!(view.selected, 1);
+    }
+
+    #[test]
+    fn test_saves_report() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut view = SubtreeDiffView::new(Theme::load("dark"));
+        view.show(report(), "ldap1", "ldap2");
+
+        view.handle_key_event(key(KeyCode::Char('s')));
+        let path = dir.path().join("replicas.json");
+        view.saving
+            .as_mut()
+            .unwrap()
+            .set(path.display().to_string());
+        assert!(matches!(
+            view.handle_key_event(key(KeyCode::Enter)),
+            Action::StatusMessage(ref m) if m.starts_with("Saved 2 report rows")
+        ));
+        assert!(path.is_file());
+
+        // Only CSV and JSON are offered
+        view.handle_key_event(key(KeyCode::Char('s')));
+        view.saving.as_mut().unwrap().set("report.ldif");
+        assert!(matches!(
+            view.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+    }
+}
@@ -4,6 +4,9 @@ use ratatui::widgets::Paragraph;
 use ratatui::Frame;
 
 use crate::action::ConnectionId;
+use crate::components::quick_switcher::{
+    JumpTarget, JumpTargetProvider, SwitchTarget, TargetCategory,
+};
 use crate::theme::Theme;
 
 /// A single tab entry.
@@ -71,21 +74,21 @@ impl TabBar {
 
     pub fn render(&self, frame: &mut Frame, area: Rect) {
         if self.tabs.is_empty() {
-            let line = Line::from(vec![Span::styled("  No profiles  ", self.theme.dimmed)]);
-            let bar = Paragraph::new(line).style(self.theme.status_bar);
+            let line = Line::from(vec![Span::styled("  No profiles  ", self.theme.dimmed())]);
+            let bar = Paragraph::new(line).style(self.theme.status_bar());
             frame.render_widget(bar, area);
             return;
         }
 
         let mut spans: Vec<Span> = Vec::new();
-        spans.push(Span::styled(" ", self.theme.status_bar));
+        spans.push(Span::styled(" ", self.theme.status_bar()));
 
         for tab in &self.tabs {
             let is_active = self.active_tab == Some(tab.id);
             let style = if is_active {
-                self.theme.tab_active
+                self.theme.tab_active()
             } else {
-                self.theme.tab_inactive
+                self.theme.tab_inactive()
             };
 
             if is_active {
@@ -93,20 +96,29 @@ impl TabBar {
                 spans.push(Span::styled(&tab.label, style));
                 spans.push(Span::styled("]", style));
             } else {
-                spans.push(Span::styled(" ", self.theme.status_bar));
+                spans.push(Span::styled(" ", self.theme.status_bar()));
                 spans.push(Span::styled(&tab.label, style));
-                spans.push(Span::styled(" ", self.theme.status_bar));
+                spans.push(Span::styled(" ", self.theme.status_bar()));
             }
-            spans.push(Span::styled(" ", self.theme.status_bar));
+            spans.push(Span::styled(" ", self.theme.status_bar()));
         }
 
         // Pad remaining width
         let content_len: usize = spans.iter().map(|s| s.content.len()).sum();
         let padding = " ".repeat(area.width as usize - content_len.min(area.width as usize));
-        spans.push(Span::styled(padding, self.theme.status_bar));
+        spans.push(Span::styled(padding, self.theme.status_bar()));
 
         let line = Line::from(spans);
         let bar = Paragraph::new(line);
         frame.render_widget(bar, area);
     }
 }
+
+impl JumpTargetProvider for TabBar {
+    fn jump_targets(&self) -> Vec<SwitchTarget> {
+        self.tabs
+            .iter()
+            .map(|t| SwitchTarget::new(TargetCategory::Tab, t.label.clone(), JumpTarget::Tab(t.id)))
+            .collect()
+    }
+}
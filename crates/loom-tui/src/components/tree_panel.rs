@@ -1,79 +1,223 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use std::time::Instant;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::Rect;
 use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
 use ratatui::Frame;
 use tui_tree_widget::{Tree, TreeItem, TreeState};
 
 use crate::action::{Action, ContextMenuSource};
+use crate::keymap::{display_key, Command, ContextKeys, KeyContext, Keymap};
 use crate::theme::Theme;
-use loom_core::tree::TreeNode;
+use crate::widgets::index_strip::IndexStrip;
+use crate::widgets::spinner::Spinner;
+use loom_core::bookmark::BookmarkTarget;
+use loom_core::jump::TypedPrefix;
+use loom_core::protect::ProtectedDns;
+use loom_core::tree::{
+    is_marker, page_marker_id, parse_page_marker, parse_status_marker, status_marker_id, LoadState,
+    PageDirection, TreeNode,
+};
+
+/// Marker shown next to protected entries.
+pub const PROTECTED_MARKER: &str = "\u{26E8}";
+
+/// Text of the pseudo-node under a container whose listing is in flight,
+/// came back empty or failed; `None` when its children say it all.
+/// `spinner` is the current frame of the loading spinner.
+fn status_text(node: &TreeNode, spinner: &str) -> Option<String> {
+    match node.load {
+        LoadState::Loading if node.children.as_ref().is_some_and(|c| !c.is_empty()) => {
+            Some(format!("{} refreshing\u{2026}", spinner))
+        }
+        LoadState::Loading => Some(format!("{} loading\u{2026}", spinner)),
+        LoadState::Loaded if node.is_empty_container() => Some("(empty)".to_string()),
+        LoadState::Failed(ref error) => Some(format!("(failed: {})", error)),
+        _ => None,
+    }
+}
 
 /// The left panel: directory tree browser.
 pub struct TreePanel {
     pub tree_state: TreeState<String>,
     pub theme: Theme,
     area: Option<Rect>,
+    /// Letters typed since `'` started jump mode.
+    jump: Option<TypedPrefix>,
+    index_strip: bool,
+    spinner: Spinner,
+    /// A first `d` was pressed; a second one deletes.
+    delete_pending: bool,
+    /// A first `y` copied the DN; a second one yanks the entry.
+    yank_pending: bool,
+    keys: ContextKeys,
 }
 
 impl TreePanel {
     pub fn new(theme: Theme) -> Self {
+        let spinner = Spinner::new(theme.dimmed());
         Self {
             tree_state: TreeState::default(),
             theme,
             area: None,
+            jump: None,
+            index_strip: false,
+            spinner,
+            delete_pending: false,
+            yank_pending: false,
+            keys: Keymap::default().context(KeyContext::Tree),
         }
     }
 
-    /// Build tree items from the directory tree for rendering.
-    pub fn build_tree_items(node: &TreeNode) -> Vec<TreeItem<'static, String>> {
+    /// Use the tree keys of `keymap` instead of the defaults.
+    pub fn with_keys(mut self, keymap: &Keymap) -> Self {
+        self.set_keys(keymap);
+        self
+    }
+
+    pub fn set_keys(&mut self, keymap: &Keymap) {
+        self.keys = keymap.context(KeyContext::Tree);
+    }
+
+    /// Advance the spinner shown under loading containers one frame.
+    pub fn tick(&mut self) {
+        self.spinner.tick();
+    }
+
+    /// Draw the A-Z index strip along the right border.
+    pub fn with_index_strip(mut self, enabled: bool) -> Self {
+        self.index_strip = enabled;
+        self
+    }
+
+    pub fn set_index_strip(&mut self, enabled: bool) {
+        self.index_strip = enabled;
+    }
+
+    /// Whether typed letters jump to entries rather than run commands.
+    pub fn is_jumping(&self) -> bool {
+        self.jump.is_some()
+    }
+
+    /// Build tree items from the directory tree for rendering. Protected
+    /// entries are marked, and containers say when they are loading, were
+    /// found empty or failed to load.
+    pub fn build_tree_items(
+        &self,
+        node: &TreeNode,
+        protected: &ProtectedDns,
+    ) -> Vec<TreeItem<'static, String>> {
         let mut items = Vec::new();
 
+        let paging = node.paging.as_ref();
+        if paging.is_some_and(|p| p.page > 0) {
+            items.push(Self::page_marker_item(&node.dn, PageDirection::Previous));
+        }
         if let Some(ref children) = node.children {
             for child in children {
-                let child_items = Self::build_tree_items(child);
-                let item = TreeItem::new(child.dn.clone(), child.display_name.clone(), child_items)
+                let child_items = self.build_tree_items(child, protected);
+                let label = if protected.is_protected(&child.dn) {
+                    format!("{} {}", PROTECTED_MARKER, child.label())
+                } else {
+                    child.label()
+                };
+                let item = TreeItem::new(child.dn.clone(), label, child_items)
                     .expect("tree item creation");
                 items.push(item);
             }
         }
+        if paging.is_some_and(|p| p.has_more) {
+            items.push(Self::page_marker_item(&node.dn, PageDirection::Next));
+        }
+        if let Some(text) = status_text(node, self.spinner.frame()) {
+            let line = Line::from(Span::styled(text, self.theme.dimmed()));
+            items.push(TreeItem::new_leaf(status_marker_id(&node.dn), line));
+        }
 
         items
     }
 
+    fn page_marker_item(parent_dn: &str, direction: PageDirection) -> TreeItem<'static, String> {
+        TreeItem::new_leaf(page_marker_id(parent_dn, direction), direction.label())
+    }
+
     /// Get the currently selected DN.
     pub fn selected_dn(&self) -> Option<&String> {
         self.tree_state.selected().last()
     }
 
+    /// The selected DN, or `None` when a page or status marker is selected.
+    pub fn selected_entry_dn(&self) -> Option<String> {
+        self.selected_dn().filter(|id| !is_marker(id)).cloned()
+    }
+
     /// Handle key events, mutating tree state.
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
-        match key.code {
-            KeyCode::Up | KeyCode::Char('k') => {
+        if let Some(typed) = self.jump.as_mut() {
+            match key.code {
+                KeyCode::Char(c)
+                    if !key
+                        .modifiers
+                        .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+                {
+                    return Action::TreeJump(typed.push(c, Instant::now()));
+                }
+                KeyCode::Esc => {
+                    self.jump = None;
+                    return Action::StatusMessage("Jump mode off".to_string());
+                }
+                // Any other key leaves jump mode and does its usual job
+                _ => self.jump = None,
+            }
+        }
+        let delete_pending = std::mem::take(&mut self.delete_pending);
+        let yank_pending = std::mem::take(&mut self.yank_pending);
+        let Some(command) = self.keys.command(&key) else {
+            return Action::None;
+        };
+        match command {
+            Command::Jump => {
+                self.jump = Some(TypedPrefix::default());
+                Action::StatusMessage(
+                    "Jump: type the start of a name, again to cycle, Esc to stop".to_string(),
+                )
+            }
+            Command::Up => {
                 self.tree_state.key_up();
-                if let Some(dn) = self.selected_dn().cloned() {
+                if let Some(dn) = self.selected_entry_dn() {
                     Action::TreeSelect(dn)
                 } else {
                     Action::None
                 }
             }
-            KeyCode::Down | KeyCode::Char('j') => {
+            Command::Down => {
                 self.tree_state.key_down();
-                if let Some(dn) = self.selected_dn().cloned() {
+                if let Some(dn) = self.selected_entry_dn() {
                     Action::TreeSelect(dn)
                 } else {
                     Action::None
                 }
             }
-            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => {
-                if let Some(dn) = self.selected_dn().cloned() {
+            Command::Expand => {
+                if let Some((direction, parent)) =
+                    self.selected_dn().and_then(|id| parse_page_marker(id))
+                {
+                    return Action::TreePage(parent.to_string(), direction);
+                }
+                // Enter on a status marker lists its container again
+                if let Some(parent) = self.selected_dn().and_then(|id| parse_status_marker(id)) {
+                    return Action::LoadChildren(parent.to_string());
+                }
+                if let Some(dn) = self.selected_entry_dn() {
                     self.tree_state.toggle_selected();
                     Action::TreeExpand(dn)
                 } else {
                     Action::None
                 }
             }
-            KeyCode::Left | KeyCode::Char('h') => {
+            Command::Collapse => {
                 if let Some(dn) = self.selected_dn().cloned() {
                     self.tree_state.key_left();
                     Action::TreeCollapse(dn)
@@ -81,41 +225,148 @@ impl TreePanel {
                     Action::None
                 }
             }
-            KeyCode::PageUp => {
+            Command::PageUp => {
                 self.tree_state
                     .select_relative(|current| current.map_or(0, |c| c.saturating_sub(10)));
-                if let Some(dn) = self.selected_dn().cloned() {
+                if let Some(dn) = self.selected_entry_dn() {
                     Action::TreeSelect(dn)
                 } else {
                     Action::None
                 }
             }
-            KeyCode::PageDown => {
+            Command::PageDown => {
                 self.tree_state
                     .select_relative(|current| current.map_or(0, |c| c.saturating_add(10)));
-                if let Some(dn) = self.selected_dn().cloned() {
+                if let Some(dn) = self.selected_entry_dn() {
                     Action::TreeSelect(dn)
                 } else {
                     Action::None
                 }
             }
-            KeyCode::Char('a') => {
-                if let Some(dn) = self.selected_dn().cloned() {
+            Command::Create => {
+                if let Some(dn) = self.selected_entry_dn() {
                     Action::ShowCreateEntryDialog(dn)
                 } else {
                     Action::None
                 }
             }
-            KeyCode::Char('d') | KeyCode::Delete => {
-                if let Some(dn) = self.selected_dn().cloned() {
-                    let msg = format!("Delete entry?\n{}", dn);
-                    Action::ShowConfirm(msg, Box::new(Action::DeleteEntry(dn)))
+            // A letter has to be pressed twice, like vim's dd
+            Command::Delete
+                if !delete_pending
+                    && matches!(key.code, KeyCode::Char(_))
+                    && self.selected_entry_dn().is_some() =>
+            {
+                self.delete_pending = true;
+                let typed = display_key(key.modifiers, key.code);
+                Action::StatusMessage(format!("{0}: press {0} again to delete", typed))
+            }
+            Command::Delete => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    Action::ShowDeleteDialog(dn)
                 } else {
                     Action::None
                 }
             }
-            KeyCode::Char(' ') => {
-                if let Some(dn) = self.selected_dn().cloned() {
+            Command::CopyDn if !yank_pending => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    self.yank_pending = true;
+                    Action::CopyToClipboard(dn)
+                } else {
+                    Action::None
+                }
+            }
+            Command::CopyDn => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    Action::YankEntry(dn)
+                } else {
+                    Action::None
+                }
+            }
+            Command::CopyLdif => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    Action::CopyEntryLdif(dn)
+                } else {
+                    Action::None
+                }
+            }
+            Command::Paste => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    Action::PasteEntry(dn)
+                } else {
+                    Action::None
+                }
+            }
+            Command::PasteSubtree => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    Action::PasteSubtree(dn)
+                } else {
+                    Action::None
+                }
+            }
+            Command::Clone => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    Action::ShowCopyEntryDialog {
+                        source: dn,
+                        parent: None,
+                    }
+                } else {
+                    Action::None
+                }
+            }
+            Command::Compare => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    Action::CompareEntry(dn)
+                } else {
+                    Action::None
+                }
+            }
+            Command::Rename => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    Action::ShowRenameDialog(dn)
+                } else {
+                    Action::None
+                }
+            }
+            Command::Bookmark => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    Action::PromptBookmark(BookmarkTarget::Dn(dn))
+                } else {
+                    Action::None
+                }
+            }
+            Command::Groups => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    Action::ShowGroups(dn)
+                } else {
+                    Action::None
+                }
+            }
+            Command::Export => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    Action::ShowSubtreeExport(dn)
+                } else {
+                    Action::None
+                }
+            }
+            Command::Bookmarks => Action::ShowBookmarks,
+            Command::ServerInfo => Action::ShowServerInfo(None),
+            // Cached children stay until asked for again
+            Command::Reload => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    Action::LoadChildren(dn)
+                } else {
+                    Action::None
+                }
+            }
+            Command::Filter => {
+                if let Some(dn) = self.selected_entry_dn() {
+                    Action::TreeChildQueryPrompt(dn)
+                } else {
+                    Action::None
+                }
+            }
+            Command::ContextMenu => {
+                if let Some(dn) = self.selected_entry_dn() {
                     Action::ShowContextMenu(ContextMenuSource::Tree { dn })
                 } else {
                     Action::None
@@ -135,9 +386,9 @@ impl TreePanel {
         title: &str,
     ) {
         let border_style = if focused {
-            self.theme.border_focused
+            self.theme.border_focused()
         } else {
-            self.theme.border
+            self.theme.border()
         };
 
         let mut block = Block::default()
@@ -151,18 +402,38 @@ impl TreePanel {
         let tree_widget = Tree::new(items)
             .expect("tree widget")
             .block(block)
-            .highlight_style(self.theme.tree_node_selected.add_modifier(Modifier::BOLD));
+            .highlight_style(self.theme.tree_node_selected().add_modifier(Modifier::BOLD));
 
         frame.render_stateful_widget(tree_widget, area, &mut self.tree_state);
         self.area = Some(area);
+
+        if self.index_strip && area.width > 2 && area.height > 2 {
+            // The names in view, between the top and bottom borders
+            let inner = area.inner(ratatui::layout::Margin::new(0, 1));
+            let flat = self.tree_state.flatten(items);
+            let names: Vec<String> = flat
+                .iter()
+                .skip(self.tree_state.get_offset())
+                .take(inner.height as usize)
+                .filter_map(|f| f.identifier.last())
+                .filter(|id| !is_marker(id))
+                .map(|dn| loom_core::dn::rdn_display_name(dn).to_string())
+                .collect();
+            IndexStrip::new(
+                names.iter().map(String::as_str),
+                self.theme.dimmed(),
+                self.theme.border_focused(),
+            )
+            .render(inner, frame.buffer_mut());
+        }
     }
 
     /// Render an empty placeholder (no connection).
     pub fn render_empty(&self, frame: &mut Frame, area: Rect, focused: bool) {
         let border_style = if focused {
-            self.theme.border_focused
+            self.theme.border_focused()
         } else {
-            self.theme.border
+            self.theme.border()
         };
 
         let mut block = Block::default()
@@ -174,7 +445,7 @@ impl TreePanel {
         }
 
         let empty = Paragraph::new("No connection")
-            .style(self.theme.dimmed)
+            .style(self.theme.dimmed())
             .block(block);
         frame.render_widget(empty, area);
     }
@@ -0,0 +1,248 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Bytes per hex dump line.
+const HEX_WIDTH: usize = 16;
+
+/// Full-screen view of one attribute value, for values too long for the
+/// detail pane: certificates, scripts, long descriptions.
+pub struct ValueViewer {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    value: Vec<u8>,
+    /// Shown as a hex dump rather than text.
+    hex: bool,
+    /// Long lines wrap; otherwise they scroll sideways.
+    wrap: bool,
+    scroll: u16,
+    hscroll: u16,
+}
+
+impl ValueViewer {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Value", theme.clone()).with_size(100, 100),
+            theme,
+            value: Vec::new(),
+            hex: false,
+            wrap: true,
+            scroll: 0,
+            hscroll: 0,
+        }
+    }
+
+    /// Open on a value; binary values start as a hex dump.
+    pub fn show(&mut self, attr: String, value: Vec<u8>, binary: bool) {
+        self.popup.title = format!("{} ({} bytes)", attr, value.len());
+        self.value = value;
+        self.hex = binary;
+        self.wrap = true;
+        self.scroll = 0;
+        self.hscroll = 0;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('v') => {
+                self.hide();
+                return Action::ClosePopup;
+            }
+            KeyCode::Char('c') | KeyCode::Char('y') => {
+                return Action::CopyToClipboard(self.lines().join("\n"));
+            }
+            KeyCode::Char('w') => {
+                self.wrap = !self.wrap;
+                self.hscroll = 0;
+            }
+            KeyCode::Char('x') => {
+                self.hex = !self.hex;
+                self.scroll = 0;
+                self.hscroll = 0;
+            }
+            KeyCode::Left | KeyCode::Char('h') if !self.wrap => {
+                self.hscroll = self.hscroll.saturating_sub(8);
+            }
+            KeyCode::Right | KeyCode::Char('l') if !self.wrap => {
+                self.hscroll = self.hscroll.saturating_add(8);
+            }
+            KeyCode::Up | KeyCode::Char('k') => self.scroll = self.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.scroll = self.scroll.saturating_add(1),
+            KeyCode::PageUp => self.scroll = self.scroll.saturating_sub(20),
+            KeyCode::PageDown => self.scroll = self.scroll.saturating_add(20),
+            KeyCode::Home | KeyCode::Char('g') => self.scroll = 0,
+            KeyCode::End | KeyCode::Char('G') => {
+                self.scroll = self.lines().len().saturating_sub(1) as u16;
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    /// The value as text lines, or as a hex dump with offsets and ASCII.
+    fn lines(&self) -> Vec<String> {
+        if self.hex {
+            return hex_dump(&self.value);
+        }
+        String::from_utf8_lossy(&self.value)
+            .lines()
+            .map(|line| line.replace('\t', "    "))
+            .collect()
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let lines: Vec<Line> = self
+            .lines()
+            .into_iter()
+            .map(|line| Line::from(Span::styled(line, self.theme.normal())))
+            .collect();
+        let mut content = Paragraph::new(lines).scroll((self.scroll, self.hscroll));
+        if self.wrap {
+            content = content.wrap(Wrap { trim: false });
+        }
+        frame.render_widget(content, layout[0]);
+
+        let mode = |on: bool| if on { "on" } else { "off" };
+        let hints = Line::from(Span::styled(
+            format!(
+                "w:wrap ({})  x:hex ({})  {}c:copy  Esc:close",
+                mode(self.wrap),
+                mode(self.hex),
+                if self.wrap { "" } else { "h/l:pan  " },
+            ),
+            self.theme.dimmed(),
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[1]);
+    }
+}
+
+/// Classic hex dump: offset, sixteen bytes in hex, then the printable ones.
+fn hex_dump(bytes: &[u8]) -> Vec<String> {
+    bytes
+        .chunks(HEX_WIDTH)
+        .enumerate()
+        .map(|(i, chunk)| {
+            let hex: Vec<String> = chunk.iter().map(|b| format!("{:02x}", b)).collect();
+            let ascii: String = chunk
+                .iter()
+                .map(|&b| {
+                    if b.is_ascii_graphic() || b == b' ' {
+                        b as char
+                    } else {
+                        '.'
+                    }
+                })
+                .collect();
+            format!(
+                "{:08x}  {:<width$}  {}",
+                i * HEX_WIDTH,
+                hex.join(" "),
+                ascii,
+                width = HEX_WIDTH * 3 - 1
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    fn render(viewer: &ValueViewer, width: u16, height: u16) -> Vec<String> {
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|frame| viewer.render(frame, frame.area()))
+            .unwrap();
+        let buffer = terminal.backend().buffer();
+        (1..height - 1)
+            .map(|y| {
+                let line: String = (1..width - 1).map(|x| buffer[(x, y)].symbol()).collect();
+                line.trim_end().to_string()
+            })
+            .collect()
+    }
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_hex_dump() {
+        let dump = hex_dump(b"loom\x00\x01 value, eighteen");
+        assert_eq!(
+            dump,
+            vec![
+                "00000000  6c 6f 6f 6d 00 01 20 76 61 6c 75 65 2c 20 65 69  loom.. value, ei",
+                "00000010  67 68 74 65 65 6e                                ghteen",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_binary_opens_as_hex() {
+        let mut viewer = ValueViewer::new(Theme::default());
+        viewer.show("objectGUID".into(), vec![0xde, 0xad, 0xbe, 0xef], true);
+        assert_eq!(
+            render(&viewer, 80, 5),
+            vec![
+                "00000000  de ad be ef                                      ....",
+                "",
+                "w:wrap (on)  x:hex (on)  c:copy  Esc:close",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrap_and_pan() {
+        let mut viewer = ValueViewer::new(Theme::default());
+        viewer.show(
+            "description".into(),
+            b"first line is long\nsecond".to_vec(),
+            false,
+        );
+        assert_eq!(
+            render(&viewer, 12, 6),
+            vec!["first line", "is long", "second", "w:wrap (on"]
+        );
+
+        viewer.handle_key_event(key(KeyCode::Char('w')));
+        viewer.handle_key_event(key(KeyCode::Char('l')));
+        assert_eq!(
+            render(&viewer, 20, 5),
+            vec!["ne is long", "", "w:wrap (off)  x:he"]
+        );
+
+        // Hex shows the same bytes
+        viewer.handle_key_event(key(KeyCode::Char('x')));
+        assert!(render(&viewer, 80, 5)[0].starts_with("00000000  66 69 72 73"));
+        assert!(matches!(
+            viewer.handle_key_event(key(KeyCode::Esc)),
+            Action::ClosePopup
+        ));
+        assert!(!viewer.visible);
+    }
+}
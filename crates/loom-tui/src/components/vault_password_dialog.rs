@@ -152,8 +152,8 @@ impl VaultPasswordDialog {
         let block = Block::default()
             .title(title)
             .borders(Borders::ALL)
-            .border_style(self.theme.popup_border)
-            .title_style(self.theme.popup_title);
+            .border_style(self.theme.popup_border())
+            .title_style(self.theme.popup_title());
 
         let inner = block.inner(area);
         frame.render_widget(block, area);
@@ -175,7 +175,7 @@ impl VaultPasswordDialog {
 
         let info = Paragraph::new(Line::from(Span::styled(
             "Choose a master password for the vault:",
-            self.theme.normal,
+            self.theme.normal(),
         )));
         frame.render_widget(info, layout[0]);
 
@@ -190,7 +190,7 @@ impl VaultPasswordDialog {
 
         let hints = Paragraph::new(Line::from(Span::styled(
             "Tab:switch field  Enter:create  Esc:cancel",
-            self.theme.dimmed,
+            self.theme.dimmed(),
         )));
         frame.render_widget(hints, layout[3]);
     }
@@ -205,8 +205,8 @@ impl VaultPasswordDialog {
 
         let profile_label = self.profile_name.as_deref().unwrap_or("(unknown)");
         let info = Paragraph::new(Line::from(vec![
-            Span::styled("Password for profile: ", self.theme.dimmed),
-            Span::styled(profile_label, self.theme.normal),
+            Span::styled("Password for profile: ", self.theme.dimmed()),
+            Span::styled(profile_label, self.theme.normal()),
         ]));
         frame.render_widget(info, layout[0]);
 
@@ -220,7 +220,7 @@ impl VaultPasswordDialog {
 
         let hints = Paragraph::new(Line::from(Span::styled(
             "Enter:store  Esc:cancel",
-            self.theme.dimmed,
+            self.theme.dimmed(),
         )));
         frame.render_widget(hints, layout[2]);
     }
@@ -228,14 +228,14 @@ impl VaultPasswordDialog {
     fn render_field(&self, frame: &mut Frame, area: Rect, label: &str, value: &str, field: Field) {
         let is_active = self.active_field == field;
         let label_style = if is_active {
-            self.theme.header
+            self.theme.header()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
         let value_style = if is_active {
-            self.theme.normal
+            self.theme.normal()
         } else {
-            self.theme.dimmed
+            self.theme.dimmed()
         };
 
         let display_value = if !value.is_empty() {
@@ -249,7 +249,7 @@ impl VaultPasswordDialog {
             Line::from(vec![
                 Span::styled(display_value, value_style),
                 if is_active {
-                    Span::styled("_", self.theme.command_prompt)
+                    Span::styled("_", self.theme.command_prompt())
                 } else {
                     Span::raw("")
                 },
@@ -0,0 +1,155 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::action::{Action, ConnectionId};
+use crate::components::connections_tree::READ_ONLY_MARKER;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Typed confirmation before `:set write on` lifts a profile's read-only
+/// flag: the user has to type the profile's name, so it is a deliberate
+/// choice of which directory to write to.
+pub struct WriteOverrideDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    conn_id: ConnectionId,
+    profile: String,
+    input: String,
+}
+
+impl WriteOverrideDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Allow Writes", theme.clone()).with_size(60, 40),
+            theme,
+            conn_id: 0,
+            profile: String::new(),
+            input: String::new(),
+        }
+    }
+
+    /// Ask before allowing writes on tab `conn_id`, opened from `profile`.
+    pub fn show(&mut self, conn_id: ConnectionId, profile: String) {
+        self.conn_id = conn_id;
+        self.profile = profile;
+        self.input.clear();
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Enter => {
+                if self.input.trim() != self.profile {
+                    return Action::ErrorMessage(format!("Type {} to allow writes", self.profile));
+                }
+                self.hide();
+                Action::AllowWrites(self.conn_id)
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let inner = self.popup.render_frame(frame, full);
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let input_style = if self.input.trim() == self.profile {
+            self.theme.success()
+        } else {
+            self.theme.normal()
+        };
+        let lines = vec![
+            Line::from(Span::styled(
+                format!("{} {} is read-only", READ_ONLY_MARKER, self.profile),
+                self.theme.warning(),
+            )),
+            Line::from(""),
+            Line::from(Span::styled(
+                "Writes will go to the server until the connection is reopened.",
+                self.theme.normal(),
+            )),
+            Line::from(""),
+            Line::from(vec![
+                Span::styled("Type ", self.theme.normal()),
+                Span::styled(self.profile.clone(), self.theme.header()),
+                Span::styled(" to allow writes:", self.theme.normal()),
+            ]),
+            Line::from(vec![
+                Span::styled(self.input.clone(), input_style),
+                Span::styled("_", self.theme.command_prompt()),
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), layout[0]);
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                "Enter:confirm  Esc:cancel",
+                self.theme.dimmed(),
+            ))),
+            layout[1],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, crossterm::event::KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_requires_typed_profile_name() {
+        let mut dialog = WriteOverrideDialog::new(Theme::load("dark"));
+        dialog.show(3, "Prod AD".to_string());
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(msg) if msg == "Type Prod AD to allow writes"
+        ));
+        for c in "prod ad".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+        for _ in 0..7 {
+            dialog.handle_key_event(key(KeyCode::Backspace));
+        }
+        for c in "Prod AD".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::AllowWrites(3)
+        ));
+        assert!(!dialog.visible);
+    }
+}
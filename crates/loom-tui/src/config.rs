@@ -1,8 +1,24 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
 use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
 
-use loom_core::connection::{ConnectionSettings, TlsMode};
+use loom_core::audit::AuditFormat;
+use loom_core::bookmark::Bookmark;
+use loom_core::connection::{AuthMethod, ConnectionSettings, TlsMode};
+use loom_core::copy_template::CopyTemplate;
 use loom_core::credentials::CredentialMethod;
-use loom_core::tls::TrustedCertEntry;
+use loom_core::dn::normalize;
+use loom_core::export::timestamps::ExportTemplate;
+use loom_core::export::{ExportFormat, ExportPreset};
+use loom_core::hooks::ProfileHooks;
+use loom_core::profile_import::ForeignProfile;
+use loom_core::protect::ProtectionMode;
+use loom_core::time::TimeDisplay;
+use loom_core::tls::{TlsOptions, TrustedCertEntry};
+use loom_core::vault::{seal, unseal};
 
 /// A saved connection profile.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -11,13 +27,19 @@ pub struct ConnectionProfile {
     pub host: String,
     #[serde(default = "default_port")]
     pub port: u16,
-    #[serde(default)]
+    #[serde(default, alias = "tls")]
     pub tls_mode: TlsMode,
+    /// Custom CA, client certificate and `verify` switch.
+    #[serde(flatten)]
+    pub tls_options: TlsOptions,
+    /// Simple bind, SASL EXTERNAL or GSSAPI; `simple` when absent.
+    #[serde(default, skip_serializing_if = "AuthMethod::is_simple")]
+    pub auth: AuthMethod,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub bind_dn: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub base_dn: Option<String>,
-    #[serde(default)]
+    #[serde(default, alias = "password_source")]
     pub credential_method: CredentialMethod,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub password_command: Option<String>,
@@ -35,8 +57,22 @@ pub struct ConnectionProfile {
     pub offline: bool,
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub labels: Vec<String>,
+    #[serde(default, skip_serializing_if = "ProfileHooks::is_empty")]
+    pub hooks: ProfileHooks,
+    /// DN patterns protected on top of the server type's defaults.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub protected_dns: Vec<String>,
+    #[serde(default, skip_serializing_if = "ProtectionMode::is_default")]
+    pub protection: ProtectionMode,
+    /// Attributes the search results table shows, in order; the default
+    /// columns when empty.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub result_columns: Vec<String>,
 }
 
+/// The search results columns of a profile that names none.
+pub const DEFAULT_RESULT_COLUMNS: [&str; 4] = ["cn", "mail", "title", "department"];
+
 fn is_false(v: &bool) -> bool {
     !v
 }
@@ -71,12 +107,181 @@ impl ConnectionProfile {
             page_size: self.page_size,
             timeout_secs: self.timeout_secs,
             relax_rules: self.relax_rules,
+            tls_options: self.tls_options.clone(),
+            auth: self.auth,
+        }
+    }
+
+    /// The attributes the search results table shows for this profile.
+    pub fn result_columns(&self) -> Vec<String> {
+        if self.result_columns.is_empty() {
+            DEFAULT_RESULT_COLUMNS.map(String::from).to_vec()
+        } else {
+            self.result_columns.clone()
+        }
+    }
+
+    /// Build a profile from one imported from another tool. Warnings are
+    /// not kept; the import dialog shows them before the profile is added.
+    pub fn from_foreign(foreign: ForeignProfile) -> Self {
+        Self {
+            name: foreign.name,
+            host: foreign.host,
+            port: foreign.port,
+            tls_mode: foreign.tls_mode,
+            bind_dn: foreign.bind_dn,
+            base_dn: foreign.base_dn,
+            credential_method: CredentialMethod::default(),
+            password_command: None,
+            page_size: foreign.page_size.unwrap_or_else(default_page_size),
+            timeout_secs: foreign.timeout_secs.unwrap_or_else(default_timeout),
+            relax_rules: false,
+            folder: None,
+            read_only: foreign.read_only,
+            offline: false,
+            labels: Vec::new(),
+            hooks: ProfileHooks::default(),
+            protected_dns: Vec::new(),
+            auth: foreign.auth,
+            tls_options: foreign.tls_options,
+            protection: ProtectionMode::default(),
+            result_columns: Vec::new(),
+        }
+    }
+}
+
+/// What identifies a profile's connection when looking for duplicates:
+/// host, port, bind DN and base DN, normalized so that spelling
+/// differences do not hide a match.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ProfileKey {
+    host: String,
+    /// `None` when the port is the default for the TLS mode.
+    port: Option<u16>,
+    bind_dn: String,
+    base_dn: String,
+}
+
+impl ProfileKey {
+    pub fn new(profile: &ConnectionProfile) -> Self {
+        let default_port = match profile.tls_mode {
+            TlsMode::Ldaps => profile.port == 636,
+            TlsMode::StartTls | TlsMode::None => profile.port == 389,
+            // Auto tries LDAPS on 636 and plain LDAP on 389
+            TlsMode::Auto => profile.port == 389 || profile.port == 636,
+        };
+        let dn = |dn: &Option<String>| dn.as_deref().map(normalize).unwrap_or_default();
+        Self {
+            host: profile.host.trim().trim_end_matches('.').to_lowercase(),
+            port: (!default_port).then_some(profile.port),
+            bind_dn: dn(&profile.bind_dn),
+            base_dn: dn(&profile.base_dn),
         }
     }
 }
 
-/// Configurable keybindings for global shortcuts.
-/// Each field holds a key string like "Alt+t", "Ctrl+c", "q", "F2", etc.
+/// How a profile being imported clashes with a saved one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportConflict {
+    /// The saved profile at this index has the same name.
+    SameName(usize),
+    /// The saved profile at this index has another name but the same
+    /// [`ProfileKey`].
+    LooksLike(usize),
+}
+
+impl ImportConflict {
+    /// Index of the saved profile.
+    pub fn index(&self) -> usize {
+        match self {
+            ImportConflict::SameName(i) | ImportConflict::LooksLike(i) => *i,
+        }
+    }
+}
+
+/// What to do with an imported profile that clashes with a saved one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImportResolution {
+    /// Leave the saved profile alone and drop the imported one.
+    #[default]
+    Skip,
+    /// Replace the saved profile with the imported one.
+    Overwrite,
+    /// Add the imported profile under a name not in use yet.
+    Rename,
+    /// Add the imported profile as it is.
+    KeepBoth,
+    /// Take the imported connection settings into the saved profile, which
+    /// keeps its name, folder, labels and hooks.
+    Adopt,
+}
+
+impl ImportResolution {
+    /// Choices offered for a conflict; adopting only makes sense when the
+    /// names differ.
+    pub fn choices(conflict: ImportConflict) -> &'static [ImportResolution] {
+        use ImportResolution::*;
+        match conflict {
+            ImportConflict::SameName(_) => &[Skip, Overwrite, Rename, KeepBoth],
+            ImportConflict::LooksLike(_) => &[Skip, Overwrite, Rename, KeepBoth, Adopt],
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ImportResolution::Skip => "skip",
+            ImportResolution::Overwrite => "overwrite",
+            ImportResolution::Rename => "rename",
+            ImportResolution::KeepBoth => "keep both",
+            ImportResolution::Adopt => "adopt settings",
+        }
+    }
+}
+
+/// For each profile in `incoming`, the saved profile it clashes with: one
+/// with the same name, else one that looks like the same connection.
+pub fn import_conflicts(
+    existing: &[ConnectionProfile],
+    incoming: &[ConnectionProfile],
+) -> Vec<Option<ImportConflict>> {
+    let keys: Vec<ProfileKey> = existing.iter().map(ProfileKey::new).collect();
+    incoming
+        .iter()
+        .map(|profile| {
+            if let Some(i) = existing.iter().position(|p| p.name == profile.name) {
+                return Some(ImportConflict::SameName(i));
+            }
+            let key = ProfileKey::new(profile);
+            keys.iter()
+                .position(|k| *k == key)
+                .map(ImportConflict::LooksLike)
+        })
+        .collect()
+}
+
+/// A profile chosen in the import dialog, with how to resolve its conflict.
+#[derive(Debug, Clone)]
+pub struct ProfileImport {
+    pub profile: ConnectionProfile,
+    pub conflict: Option<ImportConflict>,
+    pub resolution: ImportResolution,
+    /// Password exported along with the profile, if any.
+    pub password: Option<String>,
+}
+
+/// What [`AppConfig::merge_imported`] changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    /// Profiles added or changed.
+    pub changed: usize,
+    /// Passwords that came with them, by the name the profile was saved
+    /// under, for the caller to store.
+    pub passwords: Vec<(String, String)>,
+}
+
+/// Configurable keybindings for global shortcuts and the commands of each
+/// panel and dialog. Each field holds a key string like "Alt+t", "Ctrl+c",
+/// "q", "F2", etc.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(default)]
 pub struct KeybindingConfig {
@@ -91,11 +296,25 @@ pub struct KeybindingConfig {
     pub show_schema_viewer: String,
     pub show_help: String,
     pub toggle_log_panel: String,
+    pub show_log_viewer: String,
     pub save_connection: String,
     pub switch_to_profiles: String,
     pub next_tab: String,
     pub prev_tab: String,
     pub close_tab: String,
+    pub show_error_details: String,
+    pub quick_switcher: String,
+    pub show_activity: String,
+    pub grow_tree_pane: String,
+    pub shrink_tree_pane: String,
+    pub toggle_offline_edits: String,
+    pub show_pending_changes: String,
+    pub navigate_back: String,
+    pub reload_theme: String,
+    /// Keys by command name, e.g. `toggle_select = "space"` or
+    /// `down = ["j", "Down"]`, for the keys of each panel and dialog.
+    #[serde(flatten)]
+    pub commands: BTreeMap<String, KeyChords>,
 }
 
 impl Default for KeybindingConfig {
@@ -112,11 +331,40 @@ impl Default for KeybindingConfig {
             show_schema_viewer: "F6".to_string(),
             show_help: "F5".to_string(),
             toggle_log_panel: "F7".to_string(),
+            show_log_viewer: "Ctrl+l".to_string(),
             save_connection: "F10".to_string(),
             switch_to_profiles: "F1".to_string(),
             next_tab: "Ctrl+Right".to_string(),
             prev_tab: "Ctrl+Left".to_string(),
             close_tab: "Ctrl+w".to_string(),
+            show_error_details: "Ctrl+e".to_string(),
+            quick_switcher: "Ctrl+p".to_string(),
+            show_activity: "Ctrl+t".to_string(),
+            grow_tree_pane: "Alt+Right".to_string(),
+            shrink_tree_pane: "Alt+Left".to_string(),
+            toggle_offline_edits: "Ctrl+o".to_string(),
+            show_pending_changes: "Ctrl+g".to_string(),
+            navigate_back: "Ctrl+b".to_string(),
+            reload_theme: "F12".to_string(),
+            commands: BTreeMap::new(),
+        }
+    }
+}
+
+/// The keys bound to a command under `[keybindings]`: one chord such as
+/// `"ctrl-e"`, or a list of them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum KeyChords {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyChords {
+    pub fn chords(&self) -> Vec<&str> {
+        match self {
+            KeyChords::One(chord) => vec![chord.as_str()],
+            KeyChords::Many(chords) => chords.iter().map(String::as_str).collect(),
         }
     }
 }
@@ -129,6 +377,67 @@ pub struct FolderConfig {
     pub description: String,
 }
 
+/// Top-level keys of the single-connection layout that predates profiles.
+const LEGACY_KEYS: &[&str] = &[
+    "host",
+    "port",
+    "tls_mode",
+    "bind_dn",
+    "base_dn",
+    "password",
+    "password_command",
+    "page_size",
+    "timeout_secs",
+];
+
+/// Where [`AppConfig::save`] keeps a legacy config it replaces.
+pub const BACKUP_FILE: &str = "config.toml.bak";
+
+fn has_legacy_keys(content: &str) -> bool {
+    toml::from_str::<toml::Table>(content)
+        .map(|table| LEGACY_KEYS.iter().any(|key| table.contains_key(*key)))
+        .unwrap_or(false)
+}
+
+/// What [`AppConfig::migrate`] changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MigrationReport {
+    /// Legacy keys found at the top level; they are dropped.
+    pub legacy_keys: Vec<String>,
+    /// Name of the profile created from them, if any.
+    pub profile: Option<String>,
+    /// Anything the user should know, such as a password left behind.
+    pub notes: Vec<String>,
+    /// Password found with the legacy keys, for the caller to move into
+    /// the OS keychain when the file is rewritten. Never saved back.
+    pub password: Option<String>,
+}
+
+impl MigrationReport {
+    /// True when the config was already in the current layout.
+    pub fn is_empty(&self) -> bool {
+        self.legacy_keys.is_empty()
+    }
+}
+
+impl fmt::Display for MigrationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.profile {
+            Some(name) => write!(
+                f,
+                "moved {} into profile '{}'",
+                self.legacy_keys.join(", "),
+                name
+            )?,
+            None => write!(f, "dropped {}", self.legacy_keys.join(", "))?,
+        }
+        for note in &self.notes {
+            write!(f, ". {}", note)?;
+        }
+        Ok(())
+    }
+}
+
 /// Top-level application configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -142,25 +451,199 @@ pub struct AppConfig {
     pub folders: Vec<FolderConfig>,
     #[serde(default)]
     pub trusted_certificates: Vec<TrustedCertEntry>,
+    #[serde(default)]
+    pub detail: DetailConfig,
+    #[serde(default)]
+    pub layout: LayoutConfig,
+    /// Named timestamp formatting options selectable in the export dialog.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub export_templates: BTreeMap<String, ExportTemplate>,
+    /// Saved attribute selections offered in the export dialog.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub export_presets: Vec<ExportPreset>,
+    /// Filters run from the search form, most recent first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub search_history: Vec<String>,
+    /// Lines run from the `:` command line, most recent first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub command_history: Vec<String>,
+    /// "Copy as" templates offered in the context menu; built-ins when empty.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub copy_templates: BTreeMap<String, CopyTemplate>,
+    /// Bookmarked DNs and searches, by profile name.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub bookmarks: BTreeMap<String, Vec<Bookmark>>,
     /// True when no config file was found on disk (first launch).
     #[serde(skip)]
     pub first_launch: bool,
 }
 
+/// How many filters the search history keeps.
+pub const SEARCH_HISTORY_LEN: usize = 50;
+
+/// How many lines the `:` command history keeps.
+pub const COMMAND_HISTORY_LEN: usize = 100;
+
+fn push_history(history: &mut Vec<String>, item: &str, len: usize) {
+    let item = item.trim();
+    if item.is_empty() {
+        return;
+    }
+    history.retain(|h| h != item);
+    history.insert(0, item.to_string());
+    history.truncate(len);
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneralConfig {
     #[serde(default = "default_theme")]
     pub theme: String,
     #[serde(default = "default_tick_rate")]
     pub tick_rate_ms: u64,
+    /// Least severe messages logged: `error`, `warn`, `info`, `debug` or
+    /// `trace`. `RUST_LOG` overrides it.
     #[serde(default = "default_log_level")]
     pub log_level: String,
+    /// File the log is written to, created on the first message; unset
+    /// logs to `loom-ldapbrowser/loom-ldapbrowser.log` in the state directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub log_file: Option<String>,
+    /// Most recent log lines kept for the log viewer.
+    #[serde(default = "default_log_viewer_lines")]
+    pub log_viewer_lines: usize,
     #[serde(default = "default_true", skip_serializing_if = "is_true")]
     pub autocomplete: bool,
     #[serde(default = "default_true", skip_serializing_if = "is_true")]
     pub live_search: bool,
     #[serde(default, skip_serializing_if = "is_false")]
     pub vault_enabled: bool,
+    /// Treat search result values differing only in case as duplicates when merging.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub dedup_ignore_value_case: bool,
+    /// Global kill-switch for per-profile hooks.
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub hooks_enabled: bool,
+    /// Containers with more children than this are browsed a page at a time.
+    #[serde(default = "default_child_page_size")]
+    pub child_page_size: usize,
+    /// Search results moved into the results table per frame; the rest
+    /// wait in a queue so key presses stay responsive.
+    #[serde(default = "default_ingest_entries_per_tick")]
+    pub ingest_entries_per_tick: usize,
+    /// Show an A-Z strip along the tree and results, marking the letters
+    /// in view.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub index_strip: bool,
+    /// Ask before Esc or Ctrl+C throws away input typed into a dialog.
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub confirm_discard: bool,
+    /// Seconds between checks that an idle connection is still up;
+    /// 0 turns the checks off.
+    #[serde(default = "default_health_check_secs")]
+    pub health_check_secs: u64,
+    /// Changes per connection that `u` can take back; 0 keeps none.
+    #[serde(default = "default_undo_depth")]
+    pub undo_depth: usize,
+    /// File every write sent to a directory is appended to; unset keeps
+    /// no audit log.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub audit_log: Option<String>,
+    /// How audit records are written: `text` or `jsonl`.
+    #[serde(default, skip_serializing_if = "AuditFormat::is_default")]
+    pub audit_format: AuditFormat,
+    /// The audit log is rotated before it grows past this many megabytes.
+    #[serde(default = "default_audit_max_mb")]
+    pub audit_max_mb: u64,
+    /// Rotated audit logs kept, `.1` being the newest.
+    #[serde(default = "default_audit_keep")]
+    pub audit_keep: usize,
+}
+
+/// Column layout of the entry detail pane.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DetailConfig {
+    /// Widest the attribute name column may get, as a percentage of the pane.
+    #[serde(default = "default_name_column_max_percent")]
+    pub name_column_max_percent: u16,
+    /// Right-align attribute names against the value column.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub align_names_right: bool,
+    /// Panes narrower than this many columns show each value on its own
+    /// indented line under the name; 0 never does.
+    #[serde(default = "default_compact_below")]
+    pub compact_below: u16,
+    /// How timestamp values are shown: `raw`, `iso`, `relative` or `both`.
+    #[serde(default, skip_serializing_if = "TimeDisplay::is_default")]
+    pub time_display: TimeDisplay,
+    /// Attributes whose values Enter follows to the entry they name, on top
+    /// of those the schema gives DN syntax.
+    #[serde(
+        default = "default_link_attributes",
+        skip_serializing_if = "is_default_link_attributes"
+    )]
+    pub link_attributes: Vec<String>,
+}
+
+fn default_name_column_max_percent() -> u16 {
+    40
+}
+fn default_compact_below() -> u16 {
+    50
+}
+fn default_link_attributes() -> Vec<String> {
+    [
+        "member",
+        "uniqueMember",
+        "memberOf",
+        "manager",
+        "seeAlso",
+        "owner",
+        "secretary",
+        "roleOccupant",
+        "directReports",
+        "managedBy",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+fn is_default_link_attributes(attrs: &[String]) -> bool {
+    attrs == default_link_attributes()
+}
+
+impl Default for DetailConfig {
+    fn default() -> Self {
+        Self {
+            name_column_max_percent: default_name_column_max_percent(),
+            align_names_right: false,
+            compact_below: default_compact_below(),
+            time_display: TimeDisplay::default(),
+            link_attributes: default_link_attributes(),
+        }
+    }
+}
+
+/// Pane proportions of each layout, adjusted with the resize keys or by
+/// dragging the divider.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LayoutConfig {
+    pub browser: PaneRatios,
+    pub profiles: PaneRatios,
+    pub schema: PaneRatios,
+}
+
+/// How one layout's content area is split.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PaneRatios {
+    /// Width of the left pane as a percentage of the content area.
+    pub tree_percent: u16,
+}
+
+impl Default for PaneRatios {
+    fn default() -> Self {
+        Self { tree_percent: 25 }
+    }
 }
 
 fn default_theme() -> String {
@@ -172,6 +655,28 @@ fn default_tick_rate() -> u64 {
 fn default_log_level() -> String {
     "info".to_string()
 }
+fn default_log_viewer_lines() -> usize {
+    1000
+}
+fn default_child_page_size() -> usize {
+    1000
+}
+fn default_ingest_entries_per_tick() -> usize {
+    2000
+}
+
+fn default_health_check_secs() -> u64 {
+    30
+}
+fn default_audit_max_mb() -> u64 {
+    10
+}
+fn default_audit_keep() -> usize {
+    5
+}
+fn default_undo_depth() -> usize {
+    loom_core::undo::DEFAULT_UNDO_DEPTH
+}
 
 impl Default for GeneralConfig {
     fn default() -> Self {
@@ -179,32 +684,157 @@ impl Default for GeneralConfig {
             theme: default_theme(),
             tick_rate_ms: default_tick_rate(),
             log_level: default_log_level(),
+            log_file: None,
+            log_viewer_lines: default_log_viewer_lines(),
             autocomplete: true,
             live_search: true,
             vault_enabled: false,
+            dedup_ignore_value_case: false,
+            hooks_enabled: true,
+            child_page_size: default_child_page_size(),
+            ingest_entries_per_tick: default_ingest_entries_per_tick(),
+            index_strip: false,
+            confirm_discard: true,
+            health_check_secs: default_health_check_secs(),
+            undo_depth: default_undo_depth(),
+            audit_log: None,
+            audit_format: AuditFormat::default(),
+            audit_max_mb: default_audit_max_mb(),
+            audit_keep: default_audit_keep(),
         }
     }
 }
 
 impl AppConfig {
     /// Load config from ~/.config/loom-ldapbrowser/config.toml, with fallback to defaults.
+    ///
+    /// A config still in the legacy single-connection layout is migrated and
+    /// written back straight away, keeping the old file as `config.toml.bak`.
     pub fn load() -> Self {
-        let config_path = dirs::config_dir()
-            .map(|d| d.join("loom-ldapbrowser").join("config.toml"))
-            .unwrap_or_default();
-
-        if config_path.exists() {
-            if let Ok(content) = std::fs::read_to_string(&config_path) {
-                if let Ok(config) = toml::from_str::<AppConfig>(&content) {
-                    return config;
+        let (config, report) = Self::load_migrated();
+        if !report.is_empty() {
+            match config.save() {
+                Ok(()) => info!("Migrated legacy config: {}", report),
+                Err(e) => warn!("Migrated legacy config but could not save it: {}", e),
+            }
+        }
+        config
+    }
+
+    /// Load config like [`AppConfig::load`], but leave the file alone: the
+    /// report says what a migration changed, so the caller can ask first.
+    pub fn load_migrated() -> (Self, MigrationReport) {
+        if let Some(config_path) = Self::path().filter(|p| p.exists()) {
+            if let Ok(migrated) = Self::read(&config_path) {
+                return migrated;
+            }
+        }
+
+        (
+            Self {
+                first_launch: true,
+                ..Self::default()
+            },
+            MigrationReport::default(),
+        )
+    }
+
+    /// Path of the config file, `~/.config/loom-ldapbrowser/config.toml`.
+    pub fn path() -> Option<PathBuf> {
+        dirs::config_dir().map(|d| d.join("loom-ldapbrowser").join("config.toml"))
+    }
+
+    /// Read and migrate the config file at `path`, saying what is wrong
+    /// with it rather than falling back to defaults.
+    pub fn read(path: &Path) -> Result<(Self, MigrationReport), String> {
+        let content = std::fs::read_to_string(path)
+            .map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+        let value = toml::from_str::<toml::Value>(&content)
+            .map_err(|e| format!("{}: {}", path.display(), e.message()))?;
+        Self::migrate(value).map_err(|e| format!("{}: {}", path.display(), e.message()))
+    }
+
+    /// Upgrade a parsed config file to the current layout.
+    ///
+    /// Early versions kept a single connection in top-level keys (`host`,
+    /// `bind_dn`, ...). Those keys are removed and turned into a profile
+    /// named "default", or after the host when a "default" profile exists.
+    /// No profile is added when one already connects to the same server as
+    /// the same user. A saved password is not written back; it is handed
+    /// over in the report for the keychain.
+    pub fn migrate(mut value: toml::Value) -> Result<(Self, MigrationReport), toml::de::Error> {
+        let mut report = MigrationReport::default();
+        let mut legacy = toml::Table::new();
+        if let Some(table) = value.as_table_mut() {
+            for key in LEGACY_KEYS {
+                if let Some(v) = table.remove(*key) {
+                    legacy.insert(key.to_string(), v);
+                    report.legacy_keys.push(key.to_string());
                 }
             }
         }
 
-        Self {
-            first_launch: true,
-            ..Self::default()
+        let mut config: AppConfig = value.try_into()?;
+        if legacy.is_empty() {
+            return Ok((config, report));
         }
+
+        let password = legacy
+            .remove("password")
+            .and_then(|v| v.as_str().map(String::from))
+            .filter(|p| !p.is_empty());
+        let Some(host) = legacy
+            .get("host")
+            .and_then(|v| v.as_str())
+            .map(String::from)
+        else {
+            report
+                .notes
+                .push("No host was set, so no profile was created".to_string());
+            return Ok((config, report));
+        };
+
+        let name = if config.connections.iter().any(|p| p.name == "default") {
+            host.clone()
+        } else {
+            "default".to_string()
+        };
+        legacy.insert("name".to_string(), toml::Value::String(name.clone()));
+        if legacy.contains_key("password_command") {
+            legacy.insert(
+                "credential_method".to_string(),
+                toml::Value::String("command".to_string()),
+            );
+        }
+        let profile: ConnectionProfile = toml::Value::Table(legacy).try_into()?;
+
+        if let Some(existing) = config.connections.iter().find(|p| {
+            p.host.eq_ignore_ascii_case(&profile.host)
+                && p.port == profile.port
+                && p.bind_dn == profile.bind_dn
+        }) {
+            report.notes.push(format!(
+                "Profile '{}' already connects to {}, so no profile was created",
+                existing.name, host
+            ));
+            if password.is_some() {
+                report
+                    .notes
+                    .push("The saved password was not carried over".to_string());
+            }
+            return Ok((config, report));
+        }
+
+        config.connections.insert(0, profile);
+        report.profile = Some(name);
+        if password.is_some() {
+            report.notes.push(
+                "The saved password is moved to the OS keychain when the config is rewritten"
+                    .to_string(),
+            );
+            report.password = password;
+        }
+        Ok((config, report))
     }
 
     /// Parse config from a TOML string.
@@ -213,18 +843,34 @@ impl AppConfig {
     }
 
     /// Save the entire config to disk, overwriting the existing file.
+    ///
+    /// A file still holding legacy single-connection keys is first copied to
+    /// `config.toml.bak`, so the first save after a migration keeps it.
     pub fn save(&self) -> Result<(), String> {
-        let config_dir = dirs::config_dir()
-            .map(|d| d.join("loom-ldapbrowser"))
-            .ok_or_else(|| "Cannot determine config directory".to_string())?;
+        let config_path =
+            Self::path().ok_or_else(|| "Cannot determine config directory".to_string())?;
+        self.save_to(&config_path)
+    }
 
-        std::fs::create_dir_all(&config_dir)
-            .map_err(|e| format!("Failed to create config dir: {}", e))?;
+    /// Save the entire config to `config_path`, as [`save`](Self::save) does.
+    pub fn save_to(&self, config_path: &Path) -> Result<(), String> {
+        if let Some(config_dir) = config_path.parent() {
+            std::fs::create_dir_all(config_dir)
+                .map_err(|e| format!("Failed to create config dir: {}", e))?;
+        }
+
+        let has_legacy_keys = std::fs::read_to_string(config_path)
+            .map(|content| has_legacy_keys(&content))
+            .unwrap_or(false);
+        if has_legacy_keys {
+            std::fs::copy(config_path, config_path.with_file_name(BACKUP_FILE))
+                .map_err(|e| format!("Failed to back up config: {}", e))?;
+        }
 
         let content = toml::to_string_pretty(self)
             .map_err(|e| format!("Failed to serialize config: {}", e))?;
 
-        std::fs::write(config_dir.join("config.toml"), content)
+        std::fs::write(config_path, content)
             .map_err(|e| format!("Failed to write config: {}", e))?;
 
         Ok(())
@@ -239,46 +885,296 @@ impl AppConfig {
             .filter(|d| !d.is_empty())
     }
 
-    /// Update a connection profile at the given index.
+    /// Saved attribute selections, in the order they were saved.
+    pub fn export_presets(&self) -> &[ExportPreset] {
+        &self.export_presets
+    }
+
+    /// Save an attribute selection under `name`. A preset of the same name,
+    /// ignoring case, is only replaced with `replace`; otherwise saving fails.
+    pub fn save_export_preset(
+        &mut self,
+        name: &str,
+        attributes: Vec<String>,
+        format: Option<ExportFormat>,
+        replace: bool,
+    ) -> Result<(), String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err("Preset name is required".to_string());
+        }
+        let preset = ExportPreset {
+            name: name.to_string(),
+            attributes,
+            format,
+        };
+        match self
+            .export_presets
+            .iter()
+            .position(|p| p.name.eq_ignore_ascii_case(name))
+        {
+            Some(_) if !replace => Err(format!("An export preset named '{}' already exists", name)),
+            Some(i) => {
+                self.export_presets[i] = preset;
+                Ok(())
+            }
+            None => {
+                self.export_presets.push(preset);
+                Ok(())
+            }
+        }
+    }
+
+    /// Put `filter` at the front of the search history, dropping an earlier
+    /// copy and anything past [`SEARCH_HISTORY_LEN`].
+    pub fn push_search_history(&mut self, filter: &str) {
+        push_history(&mut self.search_history, filter, SEARCH_HISTORY_LEN);
+    }
+
+    /// Put a `:` command line at the front of the command history, the
+    /// same way as [`push_search_history`](Self::push_search_history).
+    pub fn push_command_history(&mut self, line: &str) {
+        push_history(&mut self.command_history, line, COMMAND_HISTORY_LEN);
+    }
+
+    /// Every bookmark with its profile filled in, by profile name and then
+    /// in the order saved.
+    pub fn bookmarks(&self) -> Vec<Bookmark> {
+        self.bookmarks
+            .iter()
+            .flat_map(|(profile, bookmarks)| {
+                bookmarks.iter().map(move |b| Bookmark {
+                    profile: profile.clone(),
+                    ..b.clone()
+                })
+            })
+            .collect()
+    }
+
+    /// Save a bookmark under its profile. One of the same name in that
+    /// profile, ignoring case, is replaced in place.
+    pub fn add_bookmark(&mut self, mut bookmark: Bookmark) -> Result<(), String> {
+        bookmark.name = bookmark.name.trim().to_string();
+        if bookmark.name.is_empty() {
+            return Err("Bookmark name is required".to_string());
+        }
+        let profile = std::mem::take(&mut bookmark.profile);
+        let bookmarks = self.bookmarks.entry(profile).or_default();
+        match bookmarks
+            .iter()
+            .position(|b| b.name.eq_ignore_ascii_case(&bookmark.name))
+        {
+            Some(i) => bookmarks[i] = bookmark,
+            None => bookmarks.push(bookmark),
+        }
+        Ok(())
+    }
+
+    /// Remove a profile's bookmark by name. Returns whether there was one.
+    pub fn remove_bookmark(&mut self, profile: &str, name: &str) -> bool {
+        let Some(bookmarks) = self.bookmarks.get_mut(profile) else {
+            return false;
+        };
+        let before = bookmarks.len();
+        bookmarks.retain(|b| !b.name.eq_ignore_ascii_case(name));
+        let removed = bookmarks.len() < before;
+        if bookmarks.is_empty() {
+            self.bookmarks.remove(profile);
+        }
+        removed
+    }
+
+    /// Update a connection profile at the given index. Its bookmarks
+    /// follow it when it is renamed.
     pub fn update_connection(&mut self, index: usize, profile: ConnectionProfile) {
         if index < self.connections.len() {
+            let old_name = std::mem::take(&mut self.connections[index].name);
+            if old_name != profile.name {
+                if let Some(bookmarks) = self.bookmarks.remove(&old_name) {
+                    self.bookmarks
+                        .entry(profile.name.clone())
+                        .or_default()
+                        .extend(bookmarks);
+                }
+            }
             self.connections[index] = profile;
         }
     }
 
-    /// Delete a connection profile at the given index.
+    /// Delete a connection profile at the given index, with its bookmarks.
     pub fn delete_connection(&mut self, index: usize) {
         if index < self.connections.len() {
-            self.connections.remove(index);
+            let profile = self.connections.remove(index);
+            self.bookmarks.remove(&profile.name);
         }
     }
 
+    /// Add imported profiles, resolving conflicts found by
+    /// [`import_conflicts`] against the current profiles.
+    pub fn merge_imported(&mut self, imports: Vec<ProfileImport>) -> MergeReport {
+        let mut report = MergeReport::default();
+        for import in imports {
+            let mut profile = import.profile;
+            let target = import.conflict.map(|c| c.index());
+            let saved_as = match (target, import.resolution) {
+                (Some(_), ImportResolution::Skip) => continue,
+                (Some(i), ImportResolution::Overwrite) if i < self.connections.len() => {
+                    let name = profile.name.clone();
+                    self.connections[i] = profile;
+                    name
+                }
+                (Some(i), ImportResolution::Adopt) if i < self.connections.len() => {
+                    let saved = &self.connections[i];
+                    profile.name = saved.name.clone();
+                    profile.folder = saved.folder.clone();
+                    profile.labels = saved.labels.clone();
+                    profile.hooks = saved.hooks.clone();
+                    profile.protected_dns = saved.protected_dns.clone();
+                    profile.protection = saved.protection;
+                    let name = profile.name.clone();
+                    self.connections[i] = profile;
+                    name
+                }
+                // Also when a profile earlier in the same file took the name
+                (Some(_), ImportResolution::Rename) | (None, _) => {
+                    profile.name = self.unused_name(&profile.name);
+                    let name = profile.name.clone();
+                    self.connections.push(profile);
+                    name
+                }
+                _ => {
+                    let name = profile.name.clone();
+                    self.connections.push(profile);
+                    name
+                }
+            };
+            if let Some(password) = import.password {
+                report.passwords.push((saved_as, password));
+            }
+            report.changed += 1;
+        }
+        report
+    }
+
+    /// `name`, or `name (2)`, `name (3)`, ... if it is taken.
+    fn unused_name(&self, name: &str) -> String {
+        let taken = |candidate: &str| self.connections.iter().any(|p| p.name == candidate);
+        if !taken(name) {
+            return name.to_string();
+        }
+        (2..)
+            .map(|n| format!("{} ({})", name, n))
+            .find(|candidate| !taken(candidate))
+            .unwrap_or_default()
+    }
+
     /// Serialize selected profiles to a TOML string with [[connections]] blocks.
     pub fn export_profiles(profiles: &[ConnectionProfile]) -> Result<String, String> {
+        Self::export_profiles_with_passwords(profiles, &BTreeMap::new(), None)
+    }
+
+    /// Serialize profiles like [`export_profiles`](Self::export_profiles),
+    /// with `passwords` by profile name in a `[passwords]` table. With a
+    /// `passphrase`, the whole file is encrypted and marked
+    /// `encrypted = true`.
+    pub fn export_profiles_with_passwords(
+        profiles: &[ConnectionProfile],
+        passwords: &BTreeMap<String, String>,
+        passphrase: Option<&str>,
+    ) -> Result<String, String> {
         let mut output = String::from("# loom-ldapbrowser — Exported Profiles\n");
+        if !passwords.is_empty() && passphrase.is_none() {
+            output.push_str("# Holds passwords in plain text\n");
+        }
         for profile in profiles {
             let block = toml::to_string(profile)
                 .map_err(|e| format!("Failed to serialize profile '{}': {}", profile.name, e))?;
             output.push_str("\n[[connections]]\n");
             output.push_str(&block);
         }
-        Ok(output)
+        if !passwords.is_empty() {
+            #[derive(Serialize)]
+            struct Passwords<'a> {
+                passwords: &'a BTreeMap<String, String>,
+            }
+            let table = toml::to_string(&Passwords { passwords })
+                .map_err(|e| format!("Failed to serialize passwords: {}", e))?;
+            output.push('\n');
+            output.push_str(&table);
+        }
+
+        let Some(passphrase) = passphrase else {
+            return Ok(output);
+        };
+        use base64::Engine;
+        let sealed = seal(output.as_bytes(), passphrase).map_err(|e| e.to_string())?;
+        Ok(format!(
+            "# loom-ldapbrowser — Exported Profiles (encrypted)\nencrypted = true\ndata = \"{}\"\n",
+            base64::engine::general_purpose::STANDARD.encode(sealed)
+        ))
+    }
+
+    /// Whether an exported profiles file needs a passphrase to import.
+    pub fn is_encrypted_profiles(content: &str) -> bool {
+        toml::from_str::<toml::Table>(content)
+            .ok()
+            .and_then(|table| table.get("encrypted").and_then(|v| v.as_bool()))
+            .unwrap_or(false)
     }
 
     /// Parse profiles from a TOML string (expects [[connections]] blocks).
+    /// Hooks on imported profiles are marked untrusted until the user approves them.
     pub fn import_profiles(content: &str) -> Result<Vec<ConnectionProfile>, String> {
+        Self::import_profiles_with_passwords(content, None).map(|(profiles, _)| profiles)
+    }
+
+    /// Parse exported profiles along with any passwords exported with
+    /// them, decrypting the file with `passphrase` when it is encrypted.
+    pub fn import_profiles_with_passwords(
+        content: &str,
+        passphrase: Option<&str>,
+    ) -> Result<(Vec<ConnectionProfile>, BTreeMap<String, String>), String> {
         // Wrap in a minimal AppConfig-like structure for parsing
         #[derive(Deserialize)]
         struct ProfilesFile {
+            #[serde(default)]
+            encrypted: bool,
+            data: Option<String>,
             #[serde(default)]
             connections: Vec<ConnectionProfile>,
+            #[serde(default)]
+            passwords: BTreeMap<String, String>,
         }
         let parsed: ProfilesFile =
             toml::from_str(content).map_err(|e| format!("Failed to parse TOML: {}", e))?;
+        if parsed.encrypted {
+            let Some(passphrase) = passphrase else {
+                return Err("The file is encrypted; a passphrase is needed".to_string());
+            };
+            use base64::Engine;
+            let sealed = parsed
+                .data
+                .as_deref()
+                .and_then(|data| base64::engine::general_purpose::STANDARD.decode(data).ok())
+                .ok_or_else(|| "The encrypted file has no valid data".to_string())?;
+            let plain = unseal(&sealed, passphrase).map_err(|e| e.to_string())?;
+            let plain = String::from_utf8(plain)
+                .map_err(|_| "The decrypted file is not text".to_string())?;
+            if Self::is_encrypted_profiles(&plain) {
+                return Err("The decrypted file is encrypted again".to_string());
+            }
+            return Self::import_profiles_with_passwords(&plain, None);
+        }
         if parsed.connections.is_empty() {
             return Err("No [[connections]] profiles found in file".to_string());
         }
-        Ok(parsed.connections)
+        let mut profiles = parsed.connections;
+        for profile in &mut profiles {
+            if !profile.hooks.is_empty() {
+                profile.hooks.untrusted = true;
+            }
+        }
+        Ok((profiles, parsed.passwords))
     }
 
     /// Append a connection profile to the config file on disk.
@@ -349,6 +1245,9 @@ theme = "nord"
 theme = "solarized"
 tick_rate_ms = 100
 log_level = "debug"
+log_file = "/tmp/loom.log"
+audit_log = "~/.local/state/loom/audit.log"
+audit_format = "jsonl"
 
 [[connections]]
 name = "Production"
@@ -364,6 +1263,14 @@ timeout_secs = 60
         let config = AppConfig::from_toml(toml).unwrap();
         assert_eq!(config.general.theme, "solarized");
         assert_eq!(config.general.tick_rate_ms, 100);
+        assert_eq!(config.general.log_file.as_deref(), Some("/tmp/loom.log"));
+        assert_eq!(config.general.log_viewer_lines, 1000);
+        assert_eq!(
+            config.general.audit_log.as_deref(),
+            Some("~/.local/state/loom/audit.log")
+        );
+        assert_eq!(config.general.audit_format, AuditFormat::Jsonl);
+        assert_eq!(config.general.audit_keep, 5);
         assert_eq!(config.connections.len(), 1);
 
         let conn = &config.connections[0];
@@ -392,6 +1299,12 @@ timeout_secs = 60
             read_only: false,
             offline: false,
             labels: vec![],
+            hooks: Default::default(),
+            protected_dns: Vec::new(),
+            auth: Default::default(),
+            tls_options: Default::default(),
+            protection: Default::default(),
+            result_columns: Vec::new(),
         };
 
         let settings = profile.to_connection_settings();
@@ -401,6 +1314,34 @@ timeout_secs = 60
         assert_eq!(settings.base_dn, Some("dc=test".to_string()));
     }
 
+    #[test]
+    fn test_result_columns_default_when_unset() {
+        let config: AppConfig = toml::from_str(
+            r#"
+[[connections]]
+name = "Plain"
+host = "localhost"
+
+[[connections]]
+name = "Hosts"
+host = "localhost"
+result_columns = ["cn", "ipHostNumber"]
+"#,
+        )
+        .unwrap();
+        assert_eq!(
+            config.connections[0].result_columns(),
+            DEFAULT_RESULT_COLUMNS
+        );
+        assert_eq!(
+            config.connections[1].result_columns(),
+            ["cn", "ipHostNumber"]
+        );
+        // Only columns that differ are written out
+        let saved = toml::to_string(&config).unwrap();
+        assert_eq!(saved.matches("result_columns").count(), 1);
+    }
+
     #[test]
     fn test_keybindings_config_defaults() {
         let config = AppConfig::default();
@@ -429,6 +1370,27 @@ switch_to_profiles = "F12"
         assert_eq!(config.keybindings.search, "F9");
     }
 
+    #[test]
+    fn test_parse_command_keys() {
+        let toml = r#"
+[keybindings]
+search = "/"
+toggle_select = "space"
+down = ["j", "Down", "ctrl-n"]
+"#;
+        let config = AppConfig::from_toml(toml).unwrap();
+        let keys = &config.keybindings;
+        assert_eq!(keys.search, "/");
+        assert!(!keys.commands.contains_key("search"));
+        assert_eq!(keys.commands["toggle_select"].chords(), vec!["space"]);
+        assert_eq!(keys.commands["down"].chords(), vec!["j", "Down", "ctrl-n"]);
+
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        let reparsed = AppConfig::from_toml(&serialized).unwrap();
+        assert_eq!(reparsed.keybindings.commands, keys.commands);
+        assert_eq!(reparsed.keybindings.search, "/");
+    }
+
     #[test]
     fn test_parse_defaults() {
         let toml = r#"
@@ -462,6 +1424,17 @@ host = "localhost"
                 read_only: false,
                 offline: false,
                 labels: vec![],
+                hooks: Default::default(),
+                protected_dns: Vec::new(),
+                auth: AuthMethod::SaslExternal,
+                tls_options: TlsOptions {
+                    ca_cert: Some("/etc/ssl/corp-ca.pem".into()),
+                    client_cert: Some("~/.ldap/client.pem".into()),
+                    client_key: Some("~/.ldap/client.key".into()),
+                    verify: true,
+                },
+                protection: Default::default(),
+                result_columns: Vec::new(),
             },
             ConnectionProfile {
                 name: "Staging".to_string(),
@@ -479,10 +1452,23 @@ host = "localhost"
                 read_only: false,
                 offline: false,
                 labels: vec![],
+                hooks: Default::default(),
+                protected_dns: Vec::new(),
+                auth: Default::default(),
+                tls_options: TlsOptions {
+                    verify: false,
+                    ..Default::default()
+                },
+                protection: Default::default(),
+                result_columns: Vec::new(),
             },
         ];
 
         let exported = AppConfig::export_profiles(&profiles).unwrap();
+        assert!(exported.contains("ca_cert = \"/etc/ssl/corp-ca.pem\""));
+        assert!(exported.contains("verify = false"));
+        assert!(exported.contains("auth = \"sasl-external\""));
+        assert_eq!(exported.matches("auth = ").count(), 1);
         assert!(exported.contains("[[connections]]"));
         assert!(exported.contains("Production"));
         assert!(exported.contains("Staging"));
@@ -495,6 +1481,18 @@ host = "localhost"
         assert_eq!(imported[0].port, 636);
         assert_eq!(imported[1].name, "Staging");
         assert_eq!(imported[1].host, "ldap-staging.internal");
+        assert_eq!(imported[0].tls_options, profiles[0].tls_options);
+        assert_eq!(imported[1].tls_options, profiles[1].tls_options);
+        assert_eq!(imported[0].auth, AuthMethod::SaslExternal);
+        assert_eq!(imported[1].auth, AuthMethod::Simple);
+
+        // `tls` is accepted for the TLS mode
+        let imported = AppConfig::import_profiles(
+            "[[connections]]\nname = \"Lab\"\nhost = \"lab\"\ntls = \"starttls\"\n",
+        )
+        .unwrap();
+        assert_eq!(imported[0].tls_mode, TlsMode::StartTls);
+        assert!(imported[0].tls_options.verify);
     }
 
     #[test]
@@ -510,6 +1508,31 @@ host = "localhost"
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_import_profiles_marks_hooks_untrusted() {
+        let toml = r#"
+[[connections]]
+name = "With Hooks"
+host = "ldap.example.com"
+
+[connections.hooks]
+on_connect = { command = "vpn-up", required = true }
+
+[[connections]]
+name = "Plain"
+host = "ldap2.example.com"
+"#;
+        let profiles = AppConfig::import_profiles(toml).unwrap();
+        assert!(profiles[0].hooks.untrusted);
+        assert!(profiles[0].hooks.on_connect.as_ref().unwrap().required);
+        assert!(!profiles[1].hooks.untrusted);
+
+        // Hooks in the user's own config are trusted
+        let config = AppConfig::from_toml(toml).unwrap();
+        assert!(!config.connections[0].hooks.untrusted);
+        assert!(config.general.hooks_enabled);
+    }
+
     #[test]
     fn test_autocomplete_and_live_search_default_true() {
         let config = AppConfig::default();
@@ -572,6 +1595,239 @@ description = ""
         assert_eq!(config.folders[1].description, "");
     }
 
+    #[test]
+    fn test_export_templates_parsing() {
+        let toml_str = r#"
+[export_templates.finance]
+timezone = "Europe/Berlin"
+timestamp_format = "%d.%m.%Y %H:%M"
+
+[export_templates.iso]
+"#;
+        let config: AppConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.export_templates.len(), 2);
+        assert_eq!(
+            config.export_templates["finance"].timestamp_format,
+            "%d.%m.%Y %H:%M"
+        );
+        assert_eq!(config.export_templates["iso"], ExportTemplate::default());
+
+        let serialized = toml::to_string_pretty(&AppConfig::default()).unwrap();
+        assert!(!serialized.contains("export_templates"));
+    }
+
+    #[test]
+    fn test_search_history() {
+        let mut config = AppConfig::default();
+        for i in 0..60 {
+            config.push_search_history(&format!("(uid=u{})", i));
+        }
+        config.push_search_history("(uid=u55)");
+        config.push_search_history("  ");
+        assert_eq!(config.search_history.len(), SEARCH_HISTORY_LEN);
+        assert_eq!(config.search_history[0], "(uid=u55)");
+        assert_eq!(config.search_history[1], "(uid=u59)");
+        assert_eq!(
+            config
+                .search_history
+                .iter()
+                .filter(|f| *f == "(uid=u55)")
+                .count(),
+            1
+        );
+
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        let reloaded: AppConfig = toml::from_str(&serialized).unwrap();
+        assert_eq!(reloaded.search_history, config.search_history);
+        let serialized = toml::to_string_pretty(&AppConfig::default()).unwrap();
+        assert!(!serialized.contains("search_history"));
+    }
+
+    #[test]
+    fn test_command_history() {
+        let mut config = AppConfig::default();
+        for i in 0..120 {
+            config.push_command_history(&format!("base ou=u{}", i));
+        }
+        config.push_command_history("set page_size 100");
+        config.push_command_history("base ou=u110");
+        assert_eq!(config.command_history.len(), COMMAND_HISTORY_LEN);
+        assert_eq!(config.command_history[0], "base ou=u110");
+        assert_eq!(config.command_history[1], "set page_size 100");
+        assert_eq!(config.command_history[2], "base ou=u119");
+
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        let reloaded: AppConfig = toml::from_str(&serialized).unwrap();
+        assert_eq!(reloaded.command_history, config.command_history);
+    }
+
+    #[test]
+    fn test_bookmarks_by_profile() {
+        use loom_core::bookmark::BookmarkTarget;
+
+        let dn = |d: &str| BookmarkTarget::Dn(d.to_string());
+        let mut config = AppConfig {
+            connections: vec![
+                profile("prod", "ldap.example.com", 389, TlsMode::StartTls),
+                profile("lab", "lab.example.com", 389, TlsMode::StartTls),
+            ],
+            ..Default::default()
+        };
+        config
+            .add_bookmark(Bookmark::new(
+                "admins",
+                "prod",
+                dn("cn=admins,dc=example,dc=com"),
+            ))
+            .unwrap();
+        config
+            .add_bookmark(Bookmark::new("people", "lab", dn("ou=people,dc=lab")))
+            .unwrap();
+        config
+            .add_bookmark(Bookmark::new(
+                " Admins ",
+                "prod",
+                dn("cn=root,dc=example,dc=com"),
+            ))
+            .unwrap();
+        assert!(config
+            .add_bookmark(Bookmark::new("  ", "prod", dn("dc=example,dc=com")))
+            .is_err());
+
+        let all = config.bookmarks();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].profile, "lab");
+        assert_eq!(all[1].profile, "prod");
+        assert_eq!(all[1].name, "Admins");
+        assert_eq!(all[1].target, dn("cn=root,dc=example,dc=com"));
+
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        assert!(serialized.contains("[[bookmarks.prod]]"));
+        let reloaded: AppConfig = toml::from_str(&serialized).unwrap();
+        assert_eq!(reloaded.bookmarks(), all);
+
+        // Bookmarks follow a renamed profile and go with a deleted one
+        config.update_connection(
+            0,
+            profile("production", "ldap.example.com", 389, TlsMode::StartTls),
+        );
+        assert_eq!(config.bookmarks()[1].profile, "production");
+        config.delete_connection(1);
+        assert_eq!(config.bookmarks().len(), 1);
+
+        assert!(config.remove_bookmark("production", "ADMINS"));
+        assert!(!config.remove_bookmark("production", "admins"));
+        assert!(config.bookmarks.is_empty());
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        assert!(!serialized.contains("bookmarks"));
+    }
+
+    #[test]
+    fn test_export_presets() {
+        let toml_str = r#"
+[[export_presets]]
+name = "people"
+attributes = ["cn", "mail", "title"]
+format = "csv"
+
+[[export_presets]]
+name = "everything"
+attributes = ["*", "+"]
+"#;
+        let mut config: AppConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.export_presets().len(), 2);
+        assert_eq!(config.export_presets()[0].format, Some(ExportFormat::Csv));
+        assert_eq!(config.export_presets()[1].format, None);
+
+        let err = config
+            .save_export_preset("People", vec!["cn".to_string()], None, false)
+            .unwrap_err();
+        assert!(err.contains("already exists"), "{}", err);
+        assert_eq!(config.export_presets()[0].attributes.len(), 3);
+
+        config
+            .save_export_preset(
+                "People",
+                vec!["cn".to_string()],
+                Some(ExportFormat::JsonLines),
+                true,
+            )
+            .unwrap();
+        config
+            .save_export_preset("groups", vec!["member".to_string()], None, false)
+            .unwrap();
+        assert!(config.save_export_preset(" ", vec![], None, false).is_err());
+        let names: Vec<&str> = config
+            .export_presets()
+            .iter()
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["People", "everything", "groups"]);
+
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        assert!(serialized.contains("format = \"jsonl\""), "{}", serialized);
+        let reloaded: AppConfig = toml::from_str(&serialized).unwrap();
+        assert_eq!(reloaded.export_presets, config.export_presets);
+        assert!(!toml::to_string_pretty(&AppConfig::default())
+            .unwrap()
+            .contains("export_presets"));
+    }
+
+    #[test]
+    fn test_copy_templates_parsing() {
+        let toml_str = r#"
+[copy_templates]
+cn_filter = "(cn={value})"
+filter_member = { template = "(member={value})", escape = "filter" }
+"#;
+        let config: AppConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.copy_templates.len(), 2);
+        assert_eq!(
+            config.copy_templates["filter_member"].template(),
+            "(member={value})"
+        );
+        let serialized = toml::to_string(&AppConfig::default()).unwrap();
+        assert!(!serialized.contains("copy_templates"));
+    }
+
+    #[test]
+    fn test_layout_ratios_parsing() {
+        let toml_str = r#"
+[layout.browser]
+tree_percent = 40
+"#;
+        let config: AppConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.layout.browser.tree_percent, 40);
+        assert_eq!(config.layout.profiles, PaneRatios::default());
+    }
+
+    #[test]
+    fn test_time_display_parsing() {
+        let toml_str = r#"
+[detail]
+time_display = "relative"
+"#;
+        let config: AppConfig = toml::from_str(toml_str).unwrap();
+        assert_eq!(config.detail.time_display, TimeDisplay::Relative);
+        assert_eq!(AppConfig::default().detail.time_display, TimeDisplay::Both);
+        let serialized = toml::to_string(&AppConfig::default()).unwrap();
+        assert!(!serialized.contains("time_display"));
+        assert!(toml::from_str::<AppConfig>("[detail]\ntime_display = \"fancy\"").is_err());
+    }
+
+    #[test]
+    fn test_link_attributes_parsing() {
+        let config: AppConfig =
+            toml::from_str("[detail]\nlink_attributes = [\"member\", \"sponsor\"]").unwrap();
+        assert_eq!(config.detail.link_attributes, vec!["member", "sponsor"]);
+        let default = AppConfig::default().detail.link_attributes;
+        assert!(default.iter().any(|a| a == "manager"));
+        let config: AppConfig = toml::from_str("[detail]\ncompact_below = 0").unwrap();
+        assert_eq!(config.detail.link_attributes, default);
+        let serialized = toml::to_string(&AppConfig::default()).unwrap();
+        assert!(!serialized.contains("link_attributes"));
+    }
+
     #[test]
     fn test_folder_description_lookup() {
         let config = AppConfig {
@@ -614,6 +1870,12 @@ description = ""
             read_only: false,
             offline: false,
             labels: vec![],
+            hooks: Default::default(),
+            protected_dns: Vec::new(),
+            auth: Default::default(),
+            tls_options: Default::default(),
+            protection: Default::default(),
+            result_columns: Vec::new(),
         }];
 
         let exported = AppConfig::export_profiles(&profiles).unwrap();
@@ -622,4 +1884,353 @@ description = ""
         // There is no password field in ConnectionProfile, so no secrets leak
         assert!(!exported.contains("password ="));
     }
+
+    fn migrate(content: &str) -> (AppConfig, MigrationReport) {
+        AppConfig::migrate(toml::from_str(content).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_migrate_legacy_only_config() {
+        let (config, report) = migrate(
+            r#"
+host = "ldap.example.com"
+port = 636
+tls_mode = "ldaps"
+bind_dn = "cn=admin,dc=example,dc=com"
+base_dn = "dc=example,dc=com"
+password = "secret"
+
+[general]
+theme = "nord"
+"#,
+        );
+        assert_eq!(config.general.theme, "nord");
+        assert_eq!(config.connections.len(), 1);
+        let profile = &config.connections[0];
+        assert_eq!(profile.name, "default");
+        assert_eq!(profile.host, "ldap.example.com");
+        assert_eq!(profile.port, 636);
+        assert_eq!(profile.tls_mode, TlsMode::Ldaps);
+        assert_eq!(profile.base_dn.as_deref(), Some("dc=example,dc=com"));
+        assert!(matches!(
+            profile.credential_method,
+            CredentialMethod::Prompt
+        ));
+
+        assert_eq!(report.profile.as_deref(), Some("default"));
+        assert!(report.legacy_keys.contains(&"password".to_string()));
+        assert_eq!(report.notes.len(), 1);
+        assert_eq!(report.password.as_deref(), Some("secret"));
+        // The rewritten file has neither the legacy keys nor the password
+        let saved = toml::to_string_pretty(&config).unwrap();
+        assert!(!has_legacy_keys(&saved));
+        assert!(!saved.contains("secret"));
+    }
+
+    #[test]
+    fn test_password_source_keyring_alias() {
+        let config: AppConfig = toml::from_str(
+            r#"
+[[connections]]
+name = "Prod"
+host = "ldap.example.com"
+password_source = "keyring"
+"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            config.connections[0].credential_method,
+            CredentialMethod::Keychain
+        ));
+        let saved = toml::to_string_pretty(&config).unwrap();
+        assert!(saved.contains("credential_method = \"keychain\""));
+    }
+
+    #[test]
+    fn test_migrate_mixed_legacy_and_profiles() {
+        let (config, report) = migrate(
+            r#"
+host = "ldap2.example.com"
+password_command = "pass show ldap2"
+
+[[connections]]
+name = "default"
+host = "ldap1.example.com"
+
+[[connections]]
+name = "Other"
+host = "ldap3.example.com"
+"#,
+        );
+        // Named after the host, since "default" is taken, and put first
+        let names: Vec<&str> = config.connections.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["ldap2.example.com", "default", "Other"]);
+        assert!(matches!(
+            config.connections[0].credential_method,
+            CredentialMethod::Command
+        ));
+        assert_eq!(report.profile.as_deref(), Some("ldap2.example.com"));
+
+        // A profile for the same server and user is not duplicated
+        let (config, report) = migrate(
+            r#"
+host = "LDAP1.example.com"
+
+[[connections]]
+name = "Main"
+host = "ldap1.example.com"
+"#,
+        );
+        assert_eq!(config.connections.len(), 1);
+        assert!(report.profile.is_none());
+        assert!(!report.is_empty());
+        assert!(report.notes[0].contains("'Main'"));
+    }
+
+    #[test]
+    fn test_migrate_current_config_is_unchanged() {
+        let content = r#"
+[general]
+theme = "dark"
+
+[[connections]]
+name = "Main"
+host = "ldap.example.com"
+"#;
+        let (config, report) = migrate(content);
+        assert!(report.is_empty());
+        assert_eq!(config.connections.len(), 1);
+        assert!(!has_legacy_keys(content));
+
+        // Legacy keys without a host are dropped and nothing is created
+        let (config, report) = migrate("port = 389\n");
+        assert!(config.connections.is_empty());
+        assert_eq!(report.legacy_keys, ["port"]);
+        assert!(report.profile.is_none());
+    }
+
+    fn profile(name: &str, host: &str, port: u16, tls_mode: TlsMode) -> ConnectionProfile {
+        let mut profile = ConnectionProfile::from_foreign(ForeignProfile {
+            name: name.to_string(),
+            host: host.to_string(),
+            port,
+            tls_mode,
+            bind_dn: Some("cn=admin,dc=example,dc=com".to_string()),
+            base_dn: Some("dc=example,dc=com".to_string()),
+            timeout_secs: None,
+            page_size: None,
+            read_only: false,
+            tls_options: TlsOptions::default(),
+            auth: AuthMethod::default(),
+            warnings: Vec::new(),
+        });
+        profile.folder = Some("Production".to_string());
+        profile
+    }
+
+    #[test]
+    fn test_profile_key_normalization() {
+        let key = |p: &ConnectionProfile| ProfileKey::new(p);
+        let base = profile("prod", "ldap.example.com", 389, TlsMode::StartTls);
+
+        // Host case, a trailing dot and DN spelling do not matter
+        let mut other = profile("x", "LDAP.Example.COM.", 389, TlsMode::StartTls);
+        other.bind_dn = Some("CN=Admin, DC=Example, DC=com".to_string());
+        assert_eq!(key(&base), key(&other));
+
+        // Default ports are the same as none given, per TLS mode
+        assert_eq!(
+            key(&profile("a", "h", 636, TlsMode::Ldaps)),
+            key(&profile("b", "h", 389, TlsMode::None))
+        );
+        assert_eq!(
+            key(&profile("a", "h", 636, TlsMode::Auto)),
+            key(&profile("b", "h", 389, TlsMode::Auto))
+        );
+        assert_ne!(
+            key(&profile("a", "h", 636, TlsMode::StartTls)),
+            key(&profile("b", "h", 389, TlsMode::StartTls))
+        );
+        assert_ne!(
+            key(&profile("a", "h", 389, TlsMode::Ldaps)),
+            key(&profile("b", "h", 389, TlsMode::None))
+        );
+
+        // A different bind DN or base DN is a different connection
+        let mut other = base.clone();
+        other.bind_dn = None;
+        assert_ne!(key(&base), key(&other));
+        let mut other = base.clone();
+        other.base_dn = Some("ou=people,dc=example,dc=com".to_string());
+        assert_ne!(key(&base), key(&other));
+    }
+
+    #[test]
+    fn test_import_conflicts_by_name_then_connection() {
+        let existing = vec![
+            profile("prod", "ldap.example.com", 636, TlsMode::Ldaps),
+            profile("dev", "dev.example.com", 389, TlsMode::Auto),
+        ];
+        let incoming = vec![
+            profile("production-ldap", "Ldap.Example.com", 636, TlsMode::Ldaps),
+            profile("dev", "other.example.com", 389, TlsMode::Auto),
+            profile("test", "test.example.com", 389, TlsMode::Auto),
+        ];
+        assert_eq!(
+            import_conflicts(&existing, &incoming),
+            vec![
+                Some(ImportConflict::LooksLike(0)),
+                Some(ImportConflict::SameName(1)),
+                None,
+            ]
+        );
+        assert!(ImportResolution::choices(ImportConflict::LooksLike(0))
+            .contains(&ImportResolution::Adopt));
+        assert!(!ImportResolution::choices(ImportConflict::SameName(0))
+            .contains(&ImportResolution::Adopt));
+    }
+
+    #[test]
+    fn test_merge_imported_resolutions() {
+        let mut config = AppConfig {
+            connections: vec![
+                profile("prod", "ldap.example.com", 636, TlsMode::Ldaps),
+                profile("dev", "dev.example.com", 389, TlsMode::Auto),
+            ],
+            ..AppConfig::default()
+        };
+        let mut adopted = profile("production-ldap", "ldap.example.com", 636, TlsMode::Ldaps);
+        adopted.folder = None;
+        adopted.page_size = 1000;
+        let import = |profile, conflict, resolution| ProfileImport {
+            profile,
+            conflict,
+            resolution,
+            password: None,
+        };
+        let report = config.merge_imported(vec![
+            import(
+                adopted,
+                Some(ImportConflict::LooksLike(0)),
+                ImportResolution::Adopt,
+            ),
+            import(
+                profile("dev", "other.example.com", 389, TlsMode::Auto),
+                Some(ImportConflict::SameName(1)),
+                ImportResolution::Rename,
+            ),
+            import(
+                profile("dev", "skipped.example.com", 389, TlsMode::Auto),
+                Some(ImportConflict::SameName(1)),
+                ImportResolution::Skip,
+            ),
+            import(
+                profile("test", "test.example.com", 389, TlsMode::Auto),
+                None,
+                ImportResolution::Skip,
+            ),
+        ]);
+        assert_eq!(report.changed, 3);
+        assert!(report.passwords.is_empty());
+        let names: Vec<&str> = config.connections.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["prod", "dev", "dev (2)", "test"]);
+        // Adopting keeps the saved name and folder but takes the settings
+        assert_eq!(config.connections[0].page_size, 1000);
+        assert_eq!(config.connections[0].folder.as_deref(), Some("Production"));
+        assert_eq!(config.connections[2].host, "other.example.com");
+    }
+
+    #[test]
+    fn test_export_profiles_with_passwords() {
+        let profiles = vec![
+            profile("prod", "ldap.example.com", 636, TlsMode::Ldaps),
+            profile("dev", "dev.example.com", 389, TlsMode::Auto),
+        ];
+        let passwords = BTreeMap::from([("prod".to_string(), "s3cret \"pw\"".to_string())]);
+
+        // Without passwords the file is as before
+        let plain = AppConfig::export_profiles(&profiles).unwrap();
+        assert!(!plain.contains("[passwords]"));
+        let (_, imported) = AppConfig::import_profiles_with_passwords(&plain, None).unwrap();
+        assert!(imported.is_empty());
+
+        let exported =
+            AppConfig::export_profiles_with_passwords(&profiles, &passwords, None).unwrap();
+        assert!(exported.contains("plain text"));
+        assert!(!AppConfig::is_encrypted_profiles(&exported));
+        let (parsed, imported) =
+            AppConfig::import_profiles_with_passwords(&exported, None).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(imported, passwords);
+
+        let encrypted =
+            AppConfig::export_profiles_with_passwords(&profiles, &passwords, Some("open sesame"))
+                .unwrap();
+        assert!(AppConfig::is_encrypted_profiles(&encrypted));
+        assert!(!encrypted.contains("s3cret"));
+        assert!(!encrypted.contains("ldap.example.com"));
+        let err = AppConfig::import_profiles(&encrypted).unwrap_err();
+        assert!(err.contains("passphrase"), "{}", err);
+        assert!(AppConfig::import_profiles_with_passwords(&encrypted, Some("wrong")).is_err());
+        let (parsed, imported) =
+            AppConfig::import_profiles_with_passwords(&encrypted, Some("open sesame")).unwrap();
+        assert_eq!(parsed[1].name, "dev");
+        assert_eq!(imported, passwords);
+    }
+
+    #[test]
+    fn test_merge_imported_reports_passwords_by_saved_name() {
+        let mut config = AppConfig {
+            connections: vec![profile("prod", "ldap.example.com", 636, TlsMode::Ldaps)],
+            ..AppConfig::default()
+        };
+        let report = config.merge_imported(vec![
+            ProfileImport {
+                profile: profile("prod", "other.example.com", 636, TlsMode::Ldaps),
+                conflict: Some(ImportConflict::SameName(0)),
+                resolution: ImportResolution::Rename,
+                password: Some("renamed".to_string()),
+            },
+            ProfileImport {
+                profile: profile("prod", "skipped.example.com", 636, TlsMode::Ldaps),
+                conflict: Some(ImportConflict::SameName(0)),
+                resolution: ImportResolution::Skip,
+                password: Some("skipped".to_string()),
+            },
+        ]);
+        assert_eq!(report.changed, 1);
+        assert_eq!(
+            report.passwords,
+            vec![("prod (2)".to_string(), "renamed".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_merge_imported_same_name_twice_in_one_file() {
+        let mut config = AppConfig {
+            connections: vec![profile("prod", "ldap.example.com", 636, TlsMode::Ldaps)],
+            ..AppConfig::default()
+        };
+        let incoming = vec![
+            profile("test", "a.example.com", 389, TlsMode::Auto),
+            profile("test", "b.example.com", 389, TlsMode::Auto),
+        ];
+        // Neither clashes with a saved profile
+        let conflicts = import_conflicts(&config.connections, &incoming);
+        assert_eq!(conflicts, vec![None, None]);
+        let imports = incoming
+            .into_iter()
+            .zip(conflicts)
+            .map(|(profile, conflict)| ProfileImport {
+                profile,
+                conflict,
+                resolution: ImportResolution::default(),
+                password: None,
+            })
+            .collect();
+        assert_eq!(config.merge_imported(imports).changed, 2);
+        let names: Vec<&str> = config.connections.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, ["prod", "test", "test (2)"]);
+        assert_eq!(config.connections[2].host, "b.example.com");
+    }
 }
@@ -0,0 +1,421 @@
+//! Connection attempts run as background tasks so DNS lookups and TCP/TLS
+//! handshakes never stall the event loop. Attempts are tracked per profile:
+//! a repeated request for a profile already connecting is coalesced, and
+//! Esc abandons every running attempt. Once connected, a health monitor
+//! pings idle connections and reconnects dropped ones with backoff.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Weak};
+use std::time::{Duration, Instant};
+
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::Mutex;
+use tokio::task::AbortHandle;
+use tracing::{debug, warn};
+
+use loom_core::connection::{AuthMethod, LdapConnection};
+use loom_core::error::CoreError;
+use loom_core::health::{
+    reconnect_with_backoff, Backoff, ConnectionHealth, HealthHandle, Reconnector,
+};
+use loom_core::protect::ProtectedDns;
+use loom_core::resolve::Resolver;
+use loom_core::server_detect::{RootDse, ServerType};
+use loom_core::tls::{CertificateInfo, TrustStore};
+
+use crate::action::{Action, ConnectionId};
+use crate::config::ConnectionProfile;
+use crate::paths::expand_path;
+
+const SPINNER: [char; 10] = [
+    '\u{280b}', '\u{2819}', '\u{2839}', '\u{2838}', '\u{283c}', '\u{2834}', '\u{2826}', '\u{2827}',
+    '\u{2807}', '\u{280f}',
+];
+
+/// What requested the connection; decides the follow-up once it succeeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConnectOrigin {
+    /// A saved profile (or the example directory).
+    Profile,
+    /// An ad-hoc connection from the quick-connect dialog.
+    AdHoc,
+    /// A retry with credentials typed into the prompt.
+    Credentials,
+}
+
+/// A bound connection and what its RootDSE reported.
+#[derive(Clone)]
+pub struct ConnectedServer {
+    pub connection: Arc<Mutex<LdapConnection>>,
+    pub base_dn: String,
+    pub server_type: String,
+    pub subschema_dn: Option<String>,
+    pub server_side_sort: bool,
+    /// Server advertises transactions (RFC 5805).
+    pub transactions: bool,
+    /// The root DSE as read after binding, if the server answered.
+    pub root_dse: Option<RootDse>,
+    /// The profile's protected DNs, with the detected server's defaults.
+    pub protected: ProtectedDns,
+    /// Who the server says the connection is bound as, if it answered.
+    pub identity: Option<String>,
+    pub health: HealthHandle,
+    /// Reopens the connection after it drops.
+    pub reconnector: Reconnector,
+}
+
+impl fmt::Debug for ConnectedServer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ConnectedServer")
+            .field("base_dn", &self.base_dn)
+            .field("server_type", &self.server_type)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Why an attempt failed, sorted by how the UI follows up.
+#[derive(Debug, Clone)]
+pub enum ConnectFailure {
+    /// The bind was rejected; prompt for credentials.
+    Auth(String),
+    /// The server certificate needs the user's approval.
+    CertUntrusted(Box<CertificateInfo>),
+    /// DNS, TCP, TLS or any other failure.
+    Other(String),
+}
+
+impl ConnectFailure {
+    fn classify(err: CoreError) -> Self {
+        if let CoreError::CertificateNotTrusted(info) = err {
+            return Self::CertUntrusted(info);
+        }
+        let msg = err.to_string();
+        let lower = msg.to_lowercase();
+        if lower.contains("bind failed")
+            || lower.contains("rc=49")
+            || lower.contains("invalid credentials")
+            || lower.contains("password must be provided")
+        {
+            Self::Auth(msg)
+        } else {
+            Self::Other(msg)
+        }
+    }
+}
+
+/// Resolve, connect, bind and read the RootDSE for `profile`.
+pub async fn open_connection(
+    profile: &ConnectionProfile,
+    password: &str,
+    trust_store: Arc<TrustStore>,
+    resolver: &dyn Resolver,
+) -> Result<ConnectedServer, ConnectFailure> {
+    profile.auth.available().map_err(ConnectFailure::Other)?;
+    let mut settings = profile.to_connection_settings();
+    settings
+        .tls_options
+        .map_paths(|path| expand_path(&path.to_string_lossy()));
+    let mut conn = LdapConnection::connect_with_resolver(settings, Some(trust_store), resolver)
+        .await
+        .map_err(ConnectFailure::classify)?;
+
+    conn.authenticate(profile.bind_dn.as_deref(), password)
+        .await
+        .map_err(|e| match profile.auth {
+            AuthMethod::Simple => ConnectFailure::classify(e),
+            // No password to prompt for; show the SASL failure as it is
+            _ => ConnectFailure::Other(e.to_string()),
+        })?;
+    let identity = match conn.whoami().await {
+        Ok(identity) => identity,
+        Err(e) => {
+            debug!("WhoAmI failed (non-fatal): {}", e);
+            None
+        }
+    };
+
+    // Read RootDSE to detect server type and auto-discover base DN
+    let root_dse = match conn.read_root_dse().await {
+        Ok(root_dse) => {
+            debug!(
+                "RootDSE: server_type={}, subschema_subentry={:?}, naming_contexts={:?}, vendor={:?}",
+                root_dse.server_type,
+                root_dse.subschema_subentry,
+                root_dse.naming_contexts,
+                root_dse.vendor_name,
+            );
+            // Log all raw RootDSE attribute keys for troubleshooting
+            let raw_keys: Vec<&String> = root_dse.raw.keys().collect();
+            debug!("RootDSE raw attribute keys: {:?}", raw_keys);
+            Some(root_dse)
+        }
+        Err(e) => {
+            debug!("RootDSE read failed (non-fatal): {}", e);
+            None
+        }
+    };
+    let server_kind = root_dse.as_ref().map(|dse| dse.server_type.clone());
+    let server_type = server_kind
+        .as_ref()
+        .map_or_else(|| "LDAP".to_string(), |kind| kind.to_string());
+    let protected = ProtectedDns::for_server(
+        &server_kind.unwrap_or_else(|| ServerType::Unknown(server_type.clone())),
+        &profile.protected_dns,
+        profile.protection,
+    );
+    conn.protected = protected.clone();
+    let health = conn.health();
+    let reconnector = conn.reconnector();
+
+    Ok(ConnectedServer {
+        base_dn: conn.base_dn.clone(),
+        connection: Arc::new(Mutex::new(conn)),
+        server_type,
+        subschema_dn: root_dse
+            .as_ref()
+            .and_then(|dse| dse.subschema_subentry.clone()),
+        server_side_sort: root_dse
+            .as_ref()
+            .is_some_and(|dse| dse.supports_server_side_sort()),
+        transactions: root_dse
+            .as_ref()
+            .is_some_and(|dse| dse.supports_transactions()),
+        root_dse,
+        protected,
+        identity,
+        health,
+        reconnector,
+    })
+}
+
+/// A running attempt and what is needed to follow it up.
+pub struct PendingConnect {
+    pub profile: ConnectionProfile,
+    pub password: String,
+    pub origin: ConnectOrigin,
+    started: Instant,
+    handle: Option<AbortHandle>,
+}
+
+impl PendingConnect {
+    fn is_for(&self, profile: &ConnectionProfile) -> bool {
+        self.profile.name == profile.name
+            && self.profile.host == profile.host
+            && self.profile.port == profile.port
+    }
+
+    /// Spinner frame and elapsed seconds, e.g. `"⠹ 3s"`.
+    fn progress(&self) -> String {
+        let elapsed = self.started.elapsed();
+        let frame = (elapsed.as_millis() / 100) as usize % SPINNER.len();
+        format!("{} {}s", SPINNER[frame], elapsed.as_secs())
+    }
+}
+
+/// Connection attempts currently in flight, keyed by attempt id.
+#[derive(Default)]
+pub struct ConnectAttempts {
+    next_id: u64,
+    pending: BTreeMap<u64, PendingConnect>,
+}
+
+impl ConnectAttempts {
+    /// Register a new attempt. Returns `None` when one for the same
+    /// profile is already running, so repeated requests coalesce.
+    pub fn begin(
+        &mut self,
+        profile: ConnectionProfile,
+        password: String,
+        origin: ConnectOrigin,
+    ) -> Option<u64> {
+        if self.pending.values().any(|p| p.is_for(&profile)) {
+            return None;
+        }
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pending.insert(
+            id,
+            PendingConnect {
+                profile,
+                password,
+                origin,
+                started: Instant::now(),
+                handle: None,
+            },
+        );
+        Some(id)
+    }
+
+    /// Attach the task running attempt `id`, so it can be cancelled.
+    pub fn attach(&mut self, id: u64, handle: AbortHandle) {
+        if let Some(pending) = self.pending.get_mut(&id) {
+            pending.handle = Some(handle);
+        }
+    }
+
+    /// Take a finished attempt. `None` means it was cancelled meanwhile
+    /// and its result should be dropped.
+    pub fn finish(&mut self, id: u64) -> Option<PendingConnect> {
+        self.pending.remove(&id)
+    }
+
+    /// Abandon every running attempt without waiting for it.
+    /// Returns the hosts that were being connected to.
+    pub fn cancel_all(&mut self) -> Vec<String> {
+        std::mem::take(&mut self.pending)
+            .into_values()
+            .map(|p| {
+                if let Some(handle) = p.handle {
+                    handle.abort();
+                }
+                p.profile.host
+            })
+            .collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Spinner and elapsed time for a profile that is connecting.
+    pub fn progress(&self, profile: &ConnectionProfile) -> Option<String> {
+        self.pending
+            .values()
+            .find(|p| p.is_for(profile))
+            .map(PendingConnect::progress)
+    }
+
+    /// Status bar text while attempts are running.
+    pub fn status_line(&self) -> Option<String> {
+        let hosts: Vec<String> = self
+            .pending
+            .values()
+            .map(|p| format!("{} {}", p.profile.host, p.progress()))
+            .collect();
+        if hosts.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "Connecting to {} (Esc to cancel)",
+            hosts.join(", ")
+        ))
+    }
+}
+
+/// Automatic reconnect attempts before a connection is given up on.
+const RECONNECT_ATTEMPTS: u32 = 6;
+
+/// Ping `connection` every `interval` while it is idle, and reconnect it
+/// when a ping fails. Ends when the connection's tab is closed.
+pub fn spawn_health_monitor(
+    conn_id: ConnectionId,
+    connection: &Arc<Mutex<LdapConnection>>,
+    health: HealthHandle,
+    reconnector: Reconnector,
+    interval: Duration,
+    tx: UnboundedSender<Action>,
+) {
+    let weak = Arc::downgrade(connection);
+    tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(interval).await;
+            let Some(connection) = weak.upgrade() else {
+                break;
+            };
+            if health.get() != ConnectionHealth::Connected {
+                continue;
+            }
+            // A busy connection is in use; its operations report failures
+            let Ok(mut conn) = connection.try_lock() else {
+                continue;
+            };
+            let pinged = conn.ping().await;
+            drop(conn);
+            drop(connection);
+            if let Err(e) = pinged {
+                warn!("Health check of connection {} failed: {}", conn_id, e);
+                if health.mark_lost() {
+                    recover(conn_id, weak.clone(), &health, &reconnector, &tx).await;
+                }
+            }
+        }
+    });
+}
+
+/// Reopen a connection already marked lost, reporting each change of
+/// health to the UI.
+pub async fn recover(
+    conn_id: ConnectionId,
+    connection: Weak<Mutex<LdapConnection>>,
+    health: &HealthHandle,
+    reconnector: &Reconnector,
+    tx: &UnboundedSender<Action>,
+) {
+    let _ = tx.send(Action::ConnectionHealthChanged(
+        conn_id,
+        ConnectionHealth::Reconnecting,
+    ));
+    match reconnect_with_backoff(reconnector, Backoff::default(), RECONNECT_ATTEMPTS).await {
+        Ok(fresh) => {
+            let Some(connection) = connection.upgrade() else {
+                return;
+            };
+            connection.lock().await.replace_with(fresh);
+            let _ = tx.send(Action::ConnectionHealthChanged(
+                conn_id,
+                ConnectionHealth::Connected,
+            ));
+        }
+        Err(e) => {
+            warn!("Giving up reconnecting connection {}: {}", conn_id, e);
+            health.set(ConnectionHealth::Disconnected);
+            let _ = tx.send(Action::ConnectionHealthChanged(
+                conn_id,
+                ConnectionHealth::Disconnected,
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile(name: &str) -> ConnectionProfile {
+        toml::from_str(&format!("name = \"{}\"\nhost = \"{}.example\"", name, name)).unwrap()
+    }
+
+    #[test]
+    fn test_repeat_attempts_coalesce() {
+        let mut attempts = ConnectAttempts::default();
+        let first = attempts.begin(profile("prod"), String::new(), ConnectOrigin::Profile);
+        assert!(first.is_some());
+        assert!(attempts
+            .begin(profile("prod"), String::new(), ConnectOrigin::Profile)
+            .is_none());
+        assert!(attempts
+            .begin(profile("test"), String::new(), ConnectOrigin::Profile)
+            .is_some());
+        assert_eq!(attempts.len(), 2);
+        assert!(attempts.progress(&profile("prod")).is_some());
+
+        assert!(attempts.finish(first.unwrap()).is_some());
+        assert!(attempts.progress(&profile("prod")).is_none());
+    }
+
+    #[test]
+    fn test_cancelled_attempt_result_is_dropped() {
+        let mut attempts = ConnectAttempts::default();
+        let id = attempts
+            .begin(profile("prod"), String::new(), ConnectOrigin::AdHoc)
+            .unwrap();
+        assert_eq!(attempts.cancel_all(), vec!["prod.example"]);
+        assert!(attempts.finish(id).is_none());
+        assert!(attempts.status_line().is_none());
+    }
+}
@@ -0,0 +1,189 @@
+//! Assembles the environment self-check report shown by `loom doctor`
+//! and the `:doctor` popup. Generic checks live in `loom_core::doctor`;
+//! the terminal, theme, keymap and clipboard checks live here.
+
+use std::path::PathBuf;
+
+use loom_core::doctor::{
+    check_config_file, check_config_version, check_directory_writable, check_keyring,
+    check_profiles, CheckResult, DoctorReport,
+};
+
+use crate::config::{AppConfig, KeybindingConfig};
+use crate::keymap::Keymap;
+use crate::theme::{Styles, Theme};
+
+/// Directory holding config.toml, themes and the vault.
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("loom-ldapbrowser")
+}
+
+/// Run every check against the loaded config.
+pub fn run_checks(config: &AppConfig) -> DoctorReport {
+    let mut report = DoctorReport::new();
+    let config_path = config_dir().join("config.toml");
+    let content = std::fs::read_to_string(&config_path).ok();
+
+    report.push(check_config_file(&config_path));
+    report.push(check_config_version(content.as_deref()));
+    let profiles: Vec<_> = config
+        .connections
+        .iter()
+        .map(|p| (p.name.as_str(), &p.credential_method))
+        .collect();
+    report.push(check_profiles(&profiles));
+    report.push(check_theme(&config.general.theme));
+    report.push(check_keymap(&config.keybindings));
+    report.push(check_clipboard());
+    report.push(check_terminal());
+    report.push(check_keyring());
+    report.push(check_features());
+    report.push(check_directory_writable("Config directory", &config_dir()));
+
+    for p in &config.connections {
+        report.add_redaction(&p.name);
+        report.add_redaction(&p.host);
+        if let Some(ref dn) = p.bind_dn {
+            report.add_redaction(dn);
+        }
+        if let Some(ref dn) = p.base_dn {
+            report.add_redaction(dn);
+        }
+    }
+    report
+}
+
+/// The configured theme is built in, or a custom file that parses.
+pub fn check_theme(name: &str) -> CheckResult {
+    const NAME: &str = "Theme";
+    if Styles::builtin(name).is_some() {
+        return CheckResult::pass(NAME, format!("built-in theme '{}'", name));
+    }
+    let Some(path) = Theme::custom_theme_path(name) else {
+        return CheckResult::warn(
+            NAME,
+            format!("'{}' not found, using dark", name),
+            "Cannot determine the config directory",
+        );
+    };
+    match std::fs::read_to_string(&path) {
+        Ok(content) => match Styles::from_toml(&content) {
+            Ok(_) => CheckResult::pass(NAME, format!("custom theme {}", path.display())),
+            Err(e) => CheckResult::fail(
+                NAME,
+                format!("{} does not parse: {}", path.display(), e),
+                "Fix the theme file; the dark theme is used meanwhile",
+            ),
+        },
+        Err(_) => CheckResult::warn(
+            NAME,
+            format!(
+                "'{}' is not built in and {} is missing",
+                name,
+                path.display()
+            ),
+            format!("Use one of: {}", Theme::BUILTIN.join(", ")),
+        ),
+    }
+}
+
+/// Every configured keybinding parses and is free where it is used.
+pub fn check_keymap(config: &KeybindingConfig) -> CheckResult {
+    const NAME: &str = "Keybindings";
+    let keymap = Keymap::from_config(config);
+    if keymap.problems().is_empty() {
+        CheckResult::pass(NAME, "all bindings OK")
+    } else {
+        CheckResult::warn(
+            NAME,
+            keymap.problems().join("; "),
+            "Fix these under [keybindings]; defaults are used meanwhile",
+        )
+    }
+}
+
+/// The system clipboard can be opened.
+pub fn check_clipboard() -> CheckResult {
+    match arboard::Clipboard::new() {
+        Ok(_) => CheckResult::pass("Clipboard", "available"),
+        Err(e) => CheckResult::warn(
+            "Clipboard",
+            format!("unavailable: {}", e),
+            "Copies fall back to the terminal (OSC 52); over SSH that is expected",
+        ),
+    }
+}
+
+/// Terminal size and color support.
+pub fn check_terminal() -> CheckResult {
+    const NAME: &str = "Terminal";
+    let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+    let term = std::env::var("TERM").unwrap_or_default();
+    let colors = if colorterm == "truecolor" || colorterm == "24bit" {
+        "truecolor"
+    } else if term.contains("256color") {
+        "256 colors"
+    } else {
+        "basic colors"
+    };
+    match crossterm::terminal::size() {
+        Ok((w, h)) if w < 80 || h < 24 => CheckResult::warn(
+            NAME,
+            format!("{}x{}, {}", w, h, colors),
+            "Use a terminal of at least 80x24",
+        ),
+        Ok((w, h)) if colors == "basic colors" => CheckResult::warn(
+            NAME,
+            format!("{}x{}, {} (TERM={})", w, h, colors, term),
+            "Hex colors in themes need a 256-color or truecolor terminal",
+        ),
+        Ok((w, h)) => CheckResult::pass(NAME, format!("{}x{}, {}", w, h, colors)),
+        Err(e) => CheckResult::warn(
+            NAME,
+            format!("size unknown ({}), {}", e, colors),
+            "Run loom in an interactive terminal",
+        ),
+    }
+}
+
+/// Optional capabilities compiled into this build.
+pub fn check_features() -> CheckResult {
+    let features = [
+        ("scripting", cfg!(feature = "scripting")),
+        ("collation", cfg!(feature = "collation")),
+        ("gssapi", cfg!(feature = "gssapi")),
+    ];
+    let list: Vec<String> = features
+        .iter()
+        .map(|(name, on)| format!("{} {}", name, if *on { "yes" } else { "no" }))
+        .collect();
+    CheckResult::pass("Optional features", list.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use loom_core::doctor::CheckStatus;
+
+    #[test]
+    fn test_check_theme_builtin() {
+        assert_eq!(check_theme("Nord").status, CheckStatus::Pass);
+    }
+
+    #[test]
+    fn test_check_keymap_reports_invalid_binding() {
+        assert_eq!(
+            check_keymap(&KeybindingConfig::default()).status,
+            CheckStatus::Pass
+        );
+        let config = KeybindingConfig {
+            quit: "Hyper+q".to_string(),
+            ..Default::default()
+        };
+        let result = check_keymap(&config);
+        assert_eq!(result.status, CheckStatus::Warn);
+        assert!(result.detail.contains("quit"));
+    }
+}
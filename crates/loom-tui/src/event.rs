@@ -14,13 +14,27 @@ pub enum AppEvent {
 /// Poll for the next event with a timeout.
 pub fn poll_event(tick_rate: Duration) -> Option<AppEvent> {
     if event::poll(tick_rate).ok()? {
-        match event::read().ok()? {
-            CrosstermEvent::Key(key) if key.kind == KeyEventKind::Press => Some(AppEvent::Key(key)),
-            CrosstermEvent::Mouse(mouse) => Some(AppEvent::Mouse(mouse)),
-            CrosstermEvent::Resize(w, h) => Some(AppEvent::Resize(w, h)),
-            _ => None,
-        }
+        read_event()
     } else {
         Some(AppEvent::Tick)
     }
 }
+
+/// Return an event only if the terminal already has one waiting.
+pub fn poll_ready() -> Option<AppEvent> {
+    while event::poll(Duration::ZERO).ok()? {
+        if let Some(event) = read_event() {
+            return Some(event);
+        }
+    }
+    None
+}
+
+fn read_event() -> Option<AppEvent> {
+    match event::read().ok()? {
+        CrosstermEvent::Key(key) if key.kind == KeyEventKind::Press => Some(AppEvent::Key(key)),
+        CrosstermEvent::Mouse(mouse) => Some(AppEvent::Mouse(mouse)),
+        CrosstermEvent::Resize(w, h) => Some(AppEvent::Resize(w, h)),
+        _ => None,
+    }
+}
@@ -44,6 +44,7 @@ impl FocusManager {
             ActiveLayout::Profiles => {
                 vec![FocusTarget::ConnectionsTree, FocusTarget::ConnectionForm]
             }
+            ActiveLayout::Schema => vec![FocusTarget::SchemaList, FocusTarget::SchemaDefinition],
         };
         if !self.panels.contains(&self.current) {
             self.current = self.panels[0];
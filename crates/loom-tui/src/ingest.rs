@@ -0,0 +1,205 @@
+//! Search results arrive from the worker a page at a time but are moved
+//! into the results table in bounded batches, one batch per frame, so a
+//! server streaming tens of thousands of entries never stalls key handling.
+
+use std::collections::VecDeque;
+
+use loom_core::entry::LdapEntry;
+use loom_core::merge::{DedupIndex, MergeOptions};
+use loom_core::schema::SchemaCache;
+
+use crate::action::ConnectionId;
+
+/// Queue between the search worker and the results table.
+#[derive(Default)]
+pub struct ResultIngest {
+    /// Id of the current search; batches from older searches are dropped.
+    generation: u64,
+    conn_id: Option<ConnectionId>,
+    pending: VecDeque<Vec<LdapEntry>>,
+    backlog: usize,
+    dedup: DedupIndex,
+    /// The worker has sent its last batch.
+    finished: bool,
+}
+
+impl ResultIngest {
+    /// Start a new search, discarding whatever is still queued.
+    /// Returns the id batches for it must carry.
+    pub fn start(&mut self, conn_id: ConnectionId) -> u64 {
+        self.generation += 1;
+        self.conn_id = Some(conn_id);
+        self.pending.clear();
+        self.backlog = 0;
+        self.dedup = DedupIndex::default();
+        self.finished = false;
+        self.generation
+    }
+
+    /// Stop ingesting; queued entries are dropped.
+    pub fn cancel(&mut self) {
+        self.conn_id = None;
+        self.pending.clear();
+        self.backlog = 0;
+    }
+
+    /// Whether a worker message belongs to the current search.
+    pub fn is_current(&self, conn_id: ConnectionId, generation: u64) -> bool {
+        self.conn_id == Some(conn_id) && self.generation == generation
+    }
+
+    /// Queue a batch from the worker. Returns false for a stale search.
+    pub fn accept(
+        &mut self,
+        conn_id: ConnectionId,
+        generation: u64,
+        batch: Vec<LdapEntry>,
+    ) -> bool {
+        if !self.is_current(conn_id, generation) {
+            return false;
+        }
+        self.backlog += batch.len();
+        self.pending.push_back(batch);
+        true
+    }
+
+    /// Mark the search as fully received. Returns false for a stale search.
+    pub fn finish(&mut self, conn_id: ConnectionId, generation: u64) -> bool {
+        if !self.is_current(conn_id, generation) {
+            return false;
+        }
+        self.finished = true;
+        true
+    }
+
+    /// Entries received but not yet in the table.
+    pub fn backlog(&self) -> usize {
+        self.backlog
+    }
+
+    /// The connection whose results are being ingested.
+    pub fn conn_id(&self) -> Option<ConnectionId> {
+        self.conn_id
+    }
+
+    /// Move at most `budget` queued entries into `results`, folding
+    /// duplicates into earlier rows. Batches are appended in arrival order
+    /// without re-sorting. Returns how many entries were taken.
+    pub fn drain_into(
+        &mut self,
+        results: &mut Vec<LdapEntry>,
+        budget: usize,
+        options: &MergeOptions,
+        schema: Option<&SchemaCache>,
+    ) -> usize {
+        let mut taken = 0;
+        results.reserve(budget.min(self.backlog));
+        while taken < budget {
+            let Some(batch) = self.pending.front_mut() else {
+                break;
+            };
+            let n = (budget - taken).min(batch.len());
+            for entry in batch.drain(..n) {
+                self.dedup.push(results, entry, options, schema);
+            }
+            if batch.is_empty() {
+                self.pending.pop_front();
+            }
+            taken += n;
+        }
+        self.backlog -= taken;
+        taken
+    }
+
+    /// Everything was received and moved into the table. Returns true
+    /// once per search, and stops further ingestion.
+    pub fn take_completed(&mut self) -> bool {
+        if self.conn_id.is_some() && self.finished && self.backlog == 0 {
+            self.conn_id = None;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Status bar text while entries are waiting.
+    pub fn status_line(&self, shown: usize) -> Option<String> {
+        if self.conn_id.is_none() || (self.backlog == 0 && self.finished) {
+            return None;
+        }
+        let receiving = if self.finished { "" } else { ", receiving" };
+        Some(format!(
+            "Loading results: {} shown, {} queued{}",
+            shown, self.backlog, receiving
+        ))
+    }
+
+    /// Final status message, e.g. `Found 10 entries (2 duplicates merged)`.
+    pub fn summary(&self, count: usize) -> String {
+        let mut msg = format!("Found {} entries", count);
+        if self.dedup.duplicates_merged > 0 {
+            msg.push_str(&format!(
+                " ({} duplicates merged",
+                self.dedup.duplicates_merged
+            ));
+            let conflicts = self.dedup.conflict_count();
+            if conflicts > 0 {
+                msg.push_str(&format!(", {} conflicting values kept", conflicts));
+            }
+            msg.push(')');
+        }
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn entry(i: usize) -> LdapEntry {
+        LdapEntry::new(format!("uid=user{},dc=example,dc=com", i), BTreeMap::new())
+    }
+
+    #[test]
+    fn test_budget_and_order() {
+        let mut ingest = ResultIngest::default();
+        let id = ingest.start(1);
+        assert!(ingest.accept(1, id, (0..5).map(entry).collect()));
+        assert!(ingest.accept(1, id, (5..8).map(entry).collect()));
+        assert!(ingest.finish(1, id));
+
+        let mut results = Vec::new();
+        let options = MergeOptions::default();
+        assert_eq!(ingest.drain_into(&mut results, 3, &options, None), 3);
+        assert_eq!(ingest.backlog(), 5);
+        assert!(!ingest.take_completed());
+        assert_eq!(ingest.drain_into(&mut results, 3, &options, None), 3);
+        assert_eq!(ingest.drain_into(&mut results, 3, &options, None), 2);
+        assert!(ingest.take_completed());
+        assert!(!ingest.take_completed());
+
+        let dns: Vec<String> = results.iter().map(|e| e.dn.clone()).collect();
+        let expected: Vec<String> = (0..8).map(|i| entry(i).dn).collect();
+        assert_eq!(dns, expected);
+    }
+
+    #[test]
+    fn test_stale_batches_dropped() {
+        let mut ingest = ResultIngest::default();
+        let old = ingest.start(1);
+        let new = ingest.start(1);
+        assert!(!ingest.accept(1, old, vec![entry(0)]));
+        assert!(!ingest.accept(2, new, vec![entry(0)]));
+        assert!(ingest.accept(1, new, vec![entry(0), entry(0)]));
+        ingest.finish(1, new);
+
+        let mut results = Vec::new();
+        ingest.drain_into(&mut results, 10, &MergeOptions::default(), None);
+        assert_eq!(results.len(), 1);
+        assert_eq!(
+            ingest.summary(results.len()),
+            "Found 1 entries (1 duplicates merged)"
+        );
+    }
+}
@@ -5,25 +5,34 @@ use tracing::warn;
 
 use crate::action::{Action, ActiveLayout, FocusTarget};
 use crate::config::KeybindingConfig;
+use crate::panes::RESIZE_STEP;
 
-/// Parse a key string like "Alt+t", "Ctrl+Shift+x", "q", "F2", "Tab" into (modifiers, code).
+/// Parse a key string like "Alt+t", "Ctrl+Shift+x", "ctrl-e", "q", "F2", "Tab" into (modifiers, code).
 pub fn parse_key(s: &str) -> Result<(KeyModifiers, KeyCode), String> {
-    let parts: Vec<&str> = s.split('+').collect();
-    if parts.is_empty() || s.is_empty() {
+    if s.is_empty() {
         return Err("empty key string".to_string());
     }
 
-    let key_name = parts.last().unwrap().trim();
-    let modifier_parts = &parts[..parts.len() - 1];
-
+    // Modifiers are joined to the key with '+' or '-'; a lone '+' or '-' is the key itself
     let mut modifiers = KeyModifiers::NONE;
-    for part in modifier_parts {
-        match part.trim().to_lowercase().as_str() {
+    let mut rest = s;
+    while rest.chars().count() > 1 {
+        let Some(at) = rest.find(['+', '-']).filter(|&at| at > 0) else {
+            break;
+        };
+        match rest[..at].trim().to_lowercase().as_str() {
             "alt" => modifiers |= KeyModifiers::ALT,
             "ctrl" | "control" => modifiers |= KeyModifiers::CONTROL,
             "shift" => modifiers |= KeyModifiers::SHIFT,
             other => return Err(format!("unknown modifier: {}", other)),
         }
+        rest = &rest[at + 1..];
+    }
+
+    let key_name = rest.trim();
+    let mut chars = key_name.chars();
+    if let (Some(c), None) = (chars.next(), chars.next()) {
+        return Ok((modifiers, KeyCode::Char(c)));
     }
 
     let code = match key_name.to_lowercase().as_str() {
@@ -47,15 +56,10 @@ pub fn parse_key(s: &str) -> Result<(KeyModifiers, KeyCode), String> {
         "end" => KeyCode::End,
         "pageup" => KeyCode::PageUp,
         "pagedown" => KeyCode::PageDown,
-        s if s.starts_with('f') && s.len() > 1 => {
-            if let Ok(n) = s[1..].parse::<u8>() {
-                KeyCode::F(n)
-            } else {
-                // single char 'f'
-                KeyCode::Char('f')
-            }
-        }
-        s if s.chars().count() == 1 => KeyCode::Char(s.chars().next().unwrap()),
+        s if s.starts_with('f') && s.len() > 1 => match s[1..].parse::<u8>() {
+            Ok(n) => KeyCode::F(n),
+            Err(_) => return Err(format!("unknown key: {}", key_name)),
+        },
         other => return Err(format!("unknown key: {}", other)),
     };
 
@@ -77,6 +81,7 @@ pub fn display_key(modifiers: KeyModifiers, code: KeyCode) -> String {
     }
 
     let key_part = match code {
+        KeyCode::Char(' ') => "Space".to_string(),
         KeyCode::Char(c) => c.to_string(),
         KeyCode::Tab => "Tab".to_string(),
         KeyCode::BackTab => {
@@ -105,130 +110,1117 @@ pub fn display_key(modifiers: KeyModifiers, code: KeyCode) -> String {
     format!("{}{}", prefix, key_part)
 }
 
+/// A typed character carries Shift in its case: "G" and "Shift+g" are the
+/// same key, however the terminal reports it.
+fn chord(modifiers: KeyModifiers, code: KeyCode) -> (KeyModifiers, KeyCode) {
+    match code {
+        KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => (
+            modifiers - KeyModifiers::SHIFT,
+            KeyCode::Char(c.to_ascii_uppercase()),
+        ),
+        _ => (modifiers, code),
+    }
+}
+
+/// Where a key is pressed. Each context has its own commands, so the same
+/// key can mean different things in the tree and in the detail pane.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyContext {
+    Global,
+    /// The browser layout outside any one panel.
+    Browser,
+    Tree,
+    Detail,
+    Connections,
+    /// The search results list.
+    Results,
+    /// The Yes/No confirmation dialog.
+    Confirm,
+    /// The log panel popup.
+    LogPanel,
+}
+
+impl KeyContext {
+    /// Heading of the context in the help popup, grouped as global,
+    /// browser, connections and dialogs.
+    pub fn title(self) -> &'static str {
+        match self {
+            KeyContext::Global => "GLOBAL SHORTCUTS",
+            KeyContext::Browser => "BROWSER",
+            KeyContext::Tree => "BROWSER: TREE PANEL",
+            KeyContext::Detail => "BROWSER: DETAIL PANEL",
+            KeyContext::Connections => "CONNECTIONS: PROFILES TREE",
+            KeyContext::Results => "DIALOGS: SEARCH RESULTS",
+            KeyContext::Confirm => "DIALOGS: CONFIRM DIALOG",
+            KeyContext::LogPanel => "DIALOGS: LOG PANEL",
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            KeyContext::Global => "everywhere",
+            KeyContext::Browser => "the browser",
+            KeyContext::Tree => "the tree panel",
+            KeyContext::Detail => "the detail panel",
+            KeyContext::Connections => "the profiles tree",
+            KeyContext::Results => "the search results",
+            KeyContext::Confirm => "the confirm dialog",
+            KeyContext::LogPanel => "the log panel",
+        }
+    }
+}
+
+impl From<FocusTarget> for KeyContext {
+    fn from(focus: FocusTarget) -> Self {
+        match focus {
+            FocusTarget::TreePanel => KeyContext::Tree,
+            FocusTarget::DetailPanel => KeyContext::Detail,
+            FocusTarget::CommandPanel => KeyContext::Browser,
+            FocusTarget::ConnectionsTree | FocusTarget::ConnectionForm => KeyContext::Connections,
+            FocusTarget::SchemaList | FocusTarget::SchemaDefinition => KeyContext::Global,
+        }
+    }
+}
+
+/// A rebindable command of a panel or dialog, named in `[keybindings]` by
+/// [`Command::name`].
+/// A name shared by several contexts rebinds the command in all of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Command {
+    About,
+    Help,
+    SearchForm,
+    CommandLine,
+    Undo,
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+    Expand,
+    Collapse,
+    Open,
+    Jump,
+    Create,
+    Edit,
+    Delete,
+    CopyDn,
+    CopyValue,
+    CopyLdif,
+    Paste,
+    PasteSubtree,
+    Clone,
+    Compare,
+    Rename,
+    Bookmark,
+    Bookmarks,
+    Groups,
+    SetPassword,
+    ToggleAccount,
+    UnlockAccount,
+    ServerInfo,
+    Reload,
+    Filter,
+    ContextMenu,
+    Find,
+    NextMatch,
+    PrevMatch,
+    AddAttribute,
+    AddValue,
+    Schema,
+    Refresh,
+    ViewValue,
+    GroupByClass,
+    ToggleRaw,
+    Connect,
+    Reconnect,
+    Duplicate,
+    Export,
+    Sort,
+    SortReverse,
+    SortColumn,
+    PrevColumn,
+    NextColumn,
+    Columns,
+    ToggleSelect,
+    SelectRange,
+    SelectAll,
+    BatchModify,
+    Close,
+    Yes,
+    No,
+}
+
+impl Command {
+    pub fn name(self) -> &'static str {
+        match self {
+            Command::About => "about",
+            Command::Help => "help",
+            Command::SearchForm => "search_form",
+            Command::CommandLine => "command_line",
+            Command::Undo => "undo",
+            Command::Up => "up",
+            Command::Down => "down",
+            Command::PageUp => "page_up",
+            Command::PageDown => "page_down",
+            Command::Top => "top",
+            Command::Bottom => "bottom",
+            Command::Expand => "expand",
+            Command::Collapse => "collapse",
+            Command::Open => "open",
+            Command::Jump => "jump",
+            Command::Create => "create",
+            Command::Edit => "edit",
+            Command::Delete => "delete",
+            Command::CopyDn => "copy_dn",
+            Command::CopyValue => "copy_value",
+            Command::CopyLdif => "copy_ldif",
+            Command::Paste => "paste",
+            Command::PasteSubtree => "paste_subtree",
+            Command::Clone => "clone",
+            Command::Compare => "compare",
+            Command::Rename => "rename",
+            Command::Bookmark => "bookmark",
+            Command::Bookmarks => "bookmarks",
+            Command::Groups => "groups",
+            Command::SetPassword => "set_password",
+            Command::ToggleAccount => "toggle_account",
+            Command::UnlockAccount => "unlock_account",
+            Command::ServerInfo => "server_info",
+            Command::Reload => "reload",
+            Command::Filter => "filter",
+            Command::ContextMenu => "context_menu",
+            Command::Find => "find",
+            Command::NextMatch => "next_match",
+            Command::PrevMatch => "prev_match",
+            Command::AddAttribute => "add_attribute",
+            Command::AddValue => "add_value",
+            Command::Schema => "schema",
+            Command::Refresh => "refresh",
+            Command::ViewValue => "view_value",
+            Command::GroupByClass => "group_by_class",
+            Command::ToggleRaw => "toggle_raw",
+            Command::Connect => "connect",
+            Command::Reconnect => "reconnect",
+            Command::Duplicate => "duplicate",
+            Command::Export => "export",
+            Command::Sort => "sort",
+            Command::SortReverse => "sort_reverse",
+            Command::SortColumn => "sort_column",
+            Command::PrevColumn => "prev_column",
+            Command::NextColumn => "next_column",
+            Command::Columns => "columns",
+            Command::ToggleSelect => "toggle_select",
+            Command::SelectRange => "select_range",
+            Command::SelectAll => "select_all",
+            Command::BatchModify => "batch_modify",
+            Command::Close => "close",
+            Command::Yes => "yes",
+            Command::No => "no",
+        }
+    }
+
+    /// What the command does when the focused panel leaves the key
+    /// alone, for the commands that need nothing from the panel.
+    pub fn action(self, context: KeyContext) -> Option<Action> {
+        match (context, self) {
+            (KeyContext::Global, Command::About) => Some(Action::ShowAbout),
+            (KeyContext::Global, Command::Help) => Some(Action::ShowHelp),
+            (KeyContext::Browser, Command::SearchForm) => Some(Action::ShowSearchForm),
+            (KeyContext::Browser, Command::CommandLine) => Some(Action::CommandFocusInput),
+            (KeyContext::Browser, Command::Undo) => Some(Action::ShowUndo),
+            (KeyContext::Tree, Command::Up) => Some(Action::TreeUp),
+            (KeyContext::Tree, Command::Down) => Some(Action::TreeDown),
+            (KeyContext::Tree, Command::Expand) => Some(Action::TreeToggle),
+            (KeyContext::Tree, Command::Collapse) => Some(Action::TreeCollapse(String::new())),
+            (KeyContext::Detail, Command::Refresh) => Some(Action::EntryRefresh),
+            _ => None,
+        }
+    }
+}
+
+/// The commands of each context with their default keys and help text, in
+/// the order the help popup lists them. Key sequences (`dd`, `yy`, `gt`)
+/// and typing in jump or filter mode are not rebindable.
+const COMMANDS: &[(KeyContext, Command, &[&str], &str)] = &[
+    (KeyContext::Global, Command::About, &["F3"], "About"),
+    (KeyContext::Global, Command::Help, &["?"], "Help"),
+    (
+        KeyContext::Browser,
+        Command::SearchForm,
+        &["/", "s"],
+        "Open search form",
+    ),
+    (
+        KeyContext::Browser,
+        Command::CommandLine,
+        &[":"],
+        "Activate command input",
+    ),
+    (
+        KeyContext::Browser,
+        Command::Undo,
+        &["u"],
+        "Undo the last change",
+    ),
+    (KeyContext::Tree, Command::Up, &["k", "Up"], "Move up"),
+    (KeyContext::Tree, Command::Down, &["j", "Down"], "Move down"),
+    (KeyContext::Tree, Command::PageUp, &["PageUp"], "Page up"),
+    (
+        KeyContext::Tree,
+        Command::PageDown,
+        &["PageDown"],
+        "Page down",
+    ),
+    (
+        KeyContext::Tree,
+        Command::Expand,
+        &["l", "Right", "Enter"],
+        "Expand / toggle node",
+    ),
+    (
+        KeyContext::Tree,
+        Command::Collapse,
+        &["h", "Left"],
+        "Collapse node",
+    ),
+    (
+        KeyContext::Tree,
+        Command::Create,
+        &["n", "a"],
+        "Create child entry",
+    ),
+    (
+        KeyContext::Tree,
+        Command::Delete,
+        &["d", "Delete"],
+        "Delete entry or subtree (a letter twice)",
+    ),
+    (KeyContext::Tree, Command::Clone, &["c"], "Clone entry"),
+    (
+        KeyContext::Tree,
+        Command::CopyDn,
+        &["y"],
+        "Copy DN; twice yanks the entry",
+    ),
+    (
+        KeyContext::Tree,
+        Command::CopyLdif,
+        &["Y"],
+        "Copy entry as LDIF",
+    ),
+    (
+        KeyContext::Tree,
+        Command::Paste,
+        &["p"],
+        "Paste yanked entry",
+    ),
+    (
+        KeyContext::Tree,
+        Command::PasteSubtree,
+        &["P"],
+        "Paste yanked subtree",
+    ),
+    (
+        KeyContext::Tree,
+        Command::Compare,
+        &["C"],
+        "Mark / compare entries",
+    ),
+    (
+        KeyContext::Tree,
+        Command::Rename,
+        &["r"],
+        "Rename / move entry",
+    ),
+    (
+        KeyContext::Tree,
+        Command::Export,
+        &["E"],
+        "Export the subtree",
+    ),
+    (
+        KeyContext::Tree,
+        Command::Filter,
+        &["f"],
+        "Filter/sort children",
+    ),
+    (KeyContext::Tree, Command::Reload, &["R"], "Reload children"),
+    (
+        KeyContext::Tree,
+        Command::Jump,
+        &["'"],
+        "Jump by typing a name",
+    ),
+    (
+        KeyContext::Tree,
+        Command::Bookmark,
+        &["m"],
+        "Bookmark entry",
+    ),
+    (KeyContext::Tree, Command::Bookmarks, &["b"], "Bookmarks"),
+    (
+        KeyContext::Tree,
+        Command::Groups,
+        &["G"],
+        "Groups of the entry",
+    ),
+    (
+        KeyContext::Tree,
+        Command::ServerInfo,
+        &["i"],
+        "Server info (root DSE)",
+    ),
+    (
+        KeyContext::Tree,
+        Command::ContextMenu,
+        &["space"],
+        "Context menu",
+    ),
+    (
+        KeyContext::Detail,
+        Command::Up,
+        &["k", "Up"],
+        "Previous attribute",
+    ),
+    (
+        KeyContext::Detail,
+        Command::Down,
+        &["j", "Down"],
+        "Next attribute",
+    ),
+    (KeyContext::Detail, Command::PageUp, &["PageUp"], "Page up"),
+    (
+        KeyContext::Detail,
+        Command::PageDown,
+        &["PageDown"],
+        "Page down",
+    ),
+    (
+        KeyContext::Detail,
+        Command::Edit,
+        &["e"],
+        "Edit attribute value",
+    ),
+    (
+        KeyContext::Detail,
+        Command::Open,
+        &["Enter"],
+        "Go to a DN; fold a section or long value",
+    ),
+    (
+        KeyContext::Detail,
+        Command::AddAttribute,
+        &["a"],
+        "Add new attribute",
+    ),
+    (
+        KeyContext::Detail,
+        Command::AddValue,
+        &["+"],
+        "Add value to attribute",
+    ),
+    (
+        KeyContext::Detail,
+        Command::Delete,
+        &["d", "Delete"],
+        "Delete attribute value",
+    ),
+    (
+        KeyContext::Detail,
+        Command::ToggleRaw,
+        &["x"],
+        "Raw / decoded values",
+    ),
+    (
+        KeyContext::Detail,
+        Command::Refresh,
+        &["r"],
+        "Refresh entry",
+    ),
+    (
+        KeyContext::Detail,
+        Command::ViewValue,
+        &["v"],
+        "View value full screen",
+    ),
+    (
+        KeyContext::Detail,
+        Command::GroupByClass,
+        &["V"],
+        "Toggle objectClass grouping",
+    ),
+    (
+        KeyContext::Detail,
+        Command::Find,
+        &["/"],
+        "Search names and values",
+    ),
+    (KeyContext::Detail, Command::NextMatch, &["n"], "Next match"),
+    (
+        KeyContext::Detail,
+        Command::PrevMatch,
+        &["N"],
+        "Previous match",
+    ),
+    (
+        KeyContext::Detail,
+        Command::Schema,
+        &["S"],
+        "Show attribute in schema",
+    ),
+    (
+        KeyContext::Detail,
+        Command::CopyValue,
+        &["y"],
+        "Copy attribute value(s)",
+    ),
+    (
+        KeyContext::Detail,
+        Command::CopyLdif,
+        &["Y"],
+        "Copy entry as LDIF",
+    ),
+    (
+        KeyContext::Detail,
+        Command::Bookmark,
+        &["m"],
+        "Bookmark entry",
+    ),
+    (KeyContext::Detail, Command::Bookmarks, &["b"], "Bookmarks"),
+    (
+        KeyContext::Detail,
+        Command::Groups,
+        &["G"],
+        "Groups of the entry",
+    ),
+    (
+        KeyContext::Detail,
+        Command::SetPassword,
+        &["P"],
+        "Set the entry's password",
+    ),
+    (
+        KeyContext::Detail,
+        Command::ToggleAccount,
+        &["A"],
+        "Enable / disable the account",
+    ),
+    (
+        KeyContext::Detail,
+        Command::UnlockAccount,
+        &["U"],
+        "Unlock the account",
+    ),
+    (
+        KeyContext::Detail,
+        Command::ContextMenu,
+        &["space"],
+        "Context menu",
+    ),
+    (
+        KeyContext::Connections,
+        Command::Up,
+        &["k", "Up"],
+        "Previous profile",
+    ),
+    (
+        KeyContext::Connections,
+        Command::Down,
+        &["j", "Down"],
+        "Next profile",
+    ),
+    (
+        KeyContext::Connections,
+        Command::Expand,
+        &["l", "Right"],
+        "Expand folder / view",
+    ),
+    (
+        KeyContext::Connections,
+        Command::Collapse,
+        &["h", "Left"],
+        "Collapse folder",
+    ),
+    (
+        KeyContext::Connections,
+        Command::Open,
+        &["Enter"],
+        "View profile",
+    ),
+    (
+        KeyContext::Connections,
+        Command::Edit,
+        &["e"],
+        "Edit / view profile",
+    ),
+    (
+        KeyContext::Connections,
+        Command::Connect,
+        &["c"],
+        "Connect to profile",
+    ),
+    (
+        KeyContext::Connections,
+        Command::Reconnect,
+        &["ctrl-r"],
+        "Reconnect",
+    ),
+    (
+        KeyContext::Connections,
+        Command::Create,
+        &["n"],
+        "New profile",
+    ),
+    (
+        KeyContext::Connections,
+        Command::Duplicate,
+        &["u"],
+        "Duplicate profile",
+    ),
+    (
+        KeyContext::Connections,
+        Command::Delete,
+        &["d", "Delete"],
+        "Delete profile",
+    ),
+    (
+        KeyContext::Connections,
+        Command::Export,
+        &["x"],
+        "Export profiles",
+    ),
+    (
+        KeyContext::Connections,
+        Command::ServerInfo,
+        &["i"],
+        "Server info (active connection) / import profiles",
+    ),
+    (
+        KeyContext::Results,
+        Command::Up,
+        &["k", "Up"],
+        "Previous result",
+    ),
+    (
+        KeyContext::Results,
+        Command::Down,
+        &["j", "Down"],
+        "Next result",
+    ),
+    (KeyContext::Results, Command::PageUp, &["PageUp"], "Page up"),
+    (
+        KeyContext::Results,
+        Command::PageDown,
+        &["PageDown"],
+        "Page down",
+    ),
+    (KeyContext::Results, Command::Top, &["Home"], "First result"),
+    (
+        KeyContext::Results,
+        Command::Bottom,
+        &["End"],
+        "Last result",
+    ),
+    (
+        KeyContext::Results,
+        Command::Open,
+        &["Enter"],
+        "Go to selected entry",
+    ),
+    (
+        KeyContext::Results,
+        Command::Filter,
+        &["f"],
+        "Quick filter loaded results",
+    ),
+    (
+        KeyContext::Results,
+        Command::Sort,
+        &["o"],
+        "Sort by next column",
+    ),
+    (
+        KeyContext::Results,
+        Command::SortReverse,
+        &["O"],
+        "Reverse the sort order",
+    ),
+    (
+        KeyContext::Results,
+        Command::PrevColumn,
+        &["h", "Left"],
+        "Previous column",
+    ),
+    (
+        KeyContext::Results,
+        Command::NextColumn,
+        &["l", "Right"],
+        "Next column",
+    ),
+    (
+        KeyContext::Results,
+        Command::SortColumn,
+        &["s"],
+        "Sort by the current column; again to reverse",
+    ),
+    (
+        KeyContext::Results,
+        Command::Columns,
+        &["c"],
+        "Choose the columns",
+    ),
+    (
+        KeyContext::Results,
+        Command::Jump,
+        &["'"],
+        "Jump by typing a name",
+    ),
+    (
+        KeyContext::Results,
+        Command::ToggleSelect,
+        &["space"],
+        "Select / unselect result",
+    ),
+    (
+        KeyContext::Results,
+        Command::SelectRange,
+        &["V"],
+        "Start / end a range selection",
+    ),
+    (
+        KeyContext::Results,
+        Command::SelectAll,
+        &["a"],
+        "Select all results shown",
+    ),
+    (KeyContext::Results, Command::CopyDn, &["y"], "Copy DNs"),
+    (
+        KeyContext::Results,
+        Command::Delete,
+        &["d"],
+        "Delete entries",
+    ),
+    (
+        KeyContext::Results,
+        Command::Export,
+        &["e"],
+        "Export results",
+    ),
+    (
+        KeyContext::Results,
+        Command::BatchModify,
+        &["m"],
+        "Batch modify entries",
+    ),
+    (KeyContext::Results, Command::Close, &["q"], "Close"),
+    (
+        KeyContext::Confirm,
+        Command::Yes,
+        &["y", "Y"],
+        "Confirm (Yes)",
+    ),
+    (
+        KeyContext::Confirm,
+        Command::No,
+        &["n", "N", "Esc"],
+        "Cancel (No)",
+    ),
+    (KeyContext::LogPanel, Command::Up, &["k", "Up"], "Scroll up"),
+    (
+        KeyContext::LogPanel,
+        Command::Down,
+        &["j", "Down"],
+        "Scroll down",
+    ),
+    (KeyContext::LogPanel, Command::Top, &["g", "Home"], "Top"),
+    (
+        KeyContext::LogPanel,
+        Command::Bottom,
+        &["G", "End"],
+        "Bottom",
+    ),
+    (KeyContext::LogPanel, Command::Close, &["q", "Esc"], "Close"),
+];
+
+/// The commands bound in one context, held by the component that handles
+/// the context's keys.
+#[derive(Debug, Clone, Default)]
+pub struct ContextKeys {
+    commands: HashMap<(KeyModifiers, KeyCode), Command>,
+}
+
+impl ContextKeys {
+    /// The command bound to `key`, if any.
+    pub fn command(&self, key: &KeyEvent) -> Option<Command> {
+        self.commands.get(&chord(key.modifiers, key.code)).copied()
+    }
+}
+
 /// Maps configured key bindings to actions and provides display hints.
 pub struct Keymap {
     global: HashMap<(KeyModifiers, KeyCode), Action>,
     hints: HashMap<&'static str, String>,
+    contexts: HashMap<KeyContext, ContextKeys>,
+    /// Key and help text of each global binding and context command, in
+    /// help order.
+    help: Vec<(KeyContext, String, &'static str)>,
+    /// Keys in the config that were left out, and why.
+    problems: Vec<String>,
 }
 
 impl Keymap {
-    /// Build a Keymap from user configuration.
-    /// On parse errors, falls back to the default binding for that action.
+    /// Build a Keymap from `[keybindings]`. A key that does not parse, or
+    /// that is already taken in its context, is reported in
+    /// [`Keymap::problems`] and the binding keeps its default keys.
     pub fn from_config(config: &KeybindingConfig) -> Self {
         let defaults = KeybindingConfig::default();
         let mut global = HashMap::new();
         let mut hints = HashMap::new();
+        let mut help = Vec::new();
+        let mut problems = Vec::new();
+        // Name of the binding holding each global key, to report clashes
+        let mut taken: HashMap<(KeyModifiers, KeyCode), &str> = HashMap::new();
 
-        let bindings: Vec<(&str, &str, &str, Action)> = vec![
-            ("quit", &config.quit, &defaults.quit, Action::Quit),
-            (
-                "force_quit",
-                &config.force_quit,
-                &defaults.force_quit,
-                Action::Quit,
-            ),
-            (
-                "focus_next",
-                &config.focus_next,
-                &defaults.focus_next,
-                Action::FocusNext,
-            ),
+        let bindings: Vec<(&str, &str, &str, Action, &str)> = vec![
             (
-                "focus_prev",
-                &config.focus_prev,
-                &defaults.focus_prev,
-                Action::FocusPrev,
+                "switch_to_profiles",
+                &config.switch_to_profiles,
+                &defaults.switch_to_profiles,
+                Action::SwitchLayout(ActiveLayout::Profiles),
+                "Profiles",
             ),
             (
                 "show_connect_dialog",
                 &config.show_connect_dialog,
                 &defaults.show_connect_dialog,
                 Action::ShowConnectDialog,
-            ),
-            (
-                "search",
-                &config.search,
-                &defaults.search,
-                Action::SearchFocusInput,
+                "Connect dialog",
             ),
             (
                 "show_export_dialog",
                 &config.show_export_dialog,
                 &defaults.show_export_dialog,
                 Action::ShowExportDialog,
+                "Export dialog",
             ),
             (
-                "show_bulk_update",
-                &config.show_bulk_update,
-                &defaults.show_bulk_update,
-                Action::ShowBulkUpdateDialog,
+                "show_help",
+                &config.show_help,
+                &defaults.show_help,
+                Action::ShowHelp,
+                "Help",
             ),
             (
                 "show_schema_viewer",
                 &config.show_schema_viewer,
                 &defaults.show_schema_viewer,
                 Action::ShowSchemaViewer,
-            ),
-            (
-                "show_help",
-                &config.show_help,
-                &defaults.show_help,
-                Action::ShowHelp,
+                "Schema viewer",
             ),
             (
                 "toggle_log_panel",
                 &config.toggle_log_panel,
                 &defaults.toggle_log_panel,
                 Action::ToggleLogPanel,
+                "Log panel",
+            ),
+            (
+                "show_log_viewer",
+                &config.show_log_viewer,
+                &defaults.show_log_viewer,
+                Action::ShowLogViewer,
+                "Log viewer",
+            ),
+            (
+                "show_error_details",
+                &config.show_error_details,
+                &defaults.show_error_details,
+                Action::ShowErrorDetails,
+                "Explain last error",
+            ),
+            (
+                "quick_switcher",
+                &config.quick_switcher,
+                &defaults.quick_switcher,
+                Action::ShowQuickSwitcher,
+                "Go to tab, entry or dialog",
+            ),
+            (
+                "show_activity",
+                &config.show_activity,
+                &defaults.show_activity,
+                Action::ShowActivity,
+                "Outstanding operations",
+            ),
+            (
+                "toggle_offline_edits",
+                &config.toggle_offline_edits,
+                &defaults.toggle_offline_edits,
+                Action::ToggleOfflineEdits,
+                "Offline edits on/off",
+            ),
+            (
+                "show_pending_changes",
+                &config.show_pending_changes,
+                &defaults.show_pending_changes,
+                Action::ShowPendingChanges,
+                "Pending changes",
+            ),
+            (
+                "navigate_back",
+                &config.navigate_back,
+                &defaults.navigate_back,
+                Action::NavigateBack,
+                "Back along followed links",
+            ),
+            (
+                "reload_theme",
+                &config.reload_theme,
+                &defaults.reload_theme,
+                Action::ReloadTheme,
+                "Reload theme",
+            ),
+            (
+                "show_bulk_update",
+                &config.show_bulk_update,
+                &defaults.show_bulk_update,
+                Action::ShowBulkUpdateDialog,
+                "Bulk update",
+            ),
+            (
+                "search",
+                &config.search,
+                &defaults.search,
+                Action::SearchFocusInput,
+                "Focus search input",
             ),
             (
                 "save_connection",
                 &config.save_connection,
                 &defaults.save_connection,
                 Action::SaveCurrentConnection,
+                "Save connection",
             ),
             (
-                "switch_to_profiles",
-                &config.switch_to_profiles,
-                &defaults.switch_to_profiles,
-                Action::SwitchLayout(ActiveLayout::Profiles),
+                "focus_next",
+                &config.focus_next,
+                &defaults.focus_next,
+                Action::FocusNext,
+                "Next panel",
+            ),
+            (
+                "focus_prev",
+                &config.focus_prev,
+                &defaults.focus_prev,
+                Action::FocusPrev,
+                "Previous panel",
             ),
             (
                 "next_tab",
                 &config.next_tab,
                 &defaults.next_tab,
                 Action::NextTab,
+                "Next tab",
             ),
             (
                 "prev_tab",
                 &config.prev_tab,
                 &defaults.prev_tab,
                 Action::PrevTab,
+                "Previous tab",
             ),
             (
                 "close_tab",
                 &config.close_tab,
                 &defaults.close_tab,
                 Action::CloseCurrentTab,
+                "Close tab",
+            ),
+            (
+                "grow_tree_pane",
+                &config.grow_tree_pane,
+                &defaults.grow_tree_pane,
+                Action::ResizePanes(RESIZE_STEP),
+                "Widen tree pane",
+            ),
+            (
+                "shrink_tree_pane",
+                &config.shrink_tree_pane,
+                &defaults.shrink_tree_pane,
+                Action::ResizePanes(-RESIZE_STEP),
+                "Narrow tree pane",
+            ),
+            ("quit", &config.quit, &defaults.quit, Action::Quit, "Quit"),
+            (
+                "force_quit",
+                &config.force_quit,
+                &defaults.force_quit,
+                Action::Quit,
+                "Force quit",
             ),
         ];
 
-        for (name, user_str, default_str, action) in bindings {
-            let (mods, code) = match parse_key(user_str) {
+        for (name, user_str, default_str, action, text) in bindings {
+            let mut parsed = match parse_key(user_str) {
                 Ok(parsed) => parsed,
                 Err(e) => {
                     warn!(
                         "Invalid keybinding for '{}': '{}' ({}), using default '{}'",
                         name, user_str, e, default_str
                     );
+                    problems.push(format!(
+                        "Key '{}' for {} does not parse ({}); using '{}'",
+                        user_str, name, e, default_str
+                    ));
                     parse_key(default_str).expect("default keybinding must parse")
                 }
             };
+            if let Some(other) = taken.get(&chord(parsed.0, parsed.1)) {
+                problems.push(format!(
+                    "Key '{}' for {} is already bound to {}; using '{}'",
+                    user_str, name, other, default_str
+                ));
+                parsed = parse_key(default_str).expect("default keybinding must parse");
+                if taken.contains_key(&chord(parsed.0, parsed.1)) {
+                    continue;
+                }
+            }
+            let (mods, code) = parsed;
+            taken.insert(chord(mods, code), name);
             global.insert((mods, code), action);
             hints.insert(name, display_key(mods, code));
+            // Key sequences that do the same without being configurable
+            let also = match name {
+                "next_tab" => "/gt",
+                "prev_tab" => "/gT",
+                _ => "",
+            };
+            help.push((
+                KeyContext::Global,
+                format!("{}{}", display_key(mods, code), also),
+                text,
+            ));
+        }
+        help.push((
+            KeyContext::Global,
+            "Esc".to_string(),
+            "Cancel newest operation",
+        ));
+
+        let keys = &config.commands;
+        for name in keys.keys() {
+            if !COMMANDS
+                .iter()
+                .any(|(_, command, _, _)| command.name() == name)
+            {
+                problems.push(format!("Unknown command '{}'", name));
+            }
         }
 
-        Self { global, hints }
+        let mut contexts: HashMap<KeyContext, ContextKeys> = HashMap::new();
+        for &(context, command, default_keys, text) in COMMANDS {
+            let name = command.name();
+            let parse_all = |chords: &[&str]| -> Result<Vec<_>, String> {
+                chords
+                    .iter()
+                    .map(|s| {
+                        parse_key(s)
+                            .map(|(mods, code)| chord(mods, code))
+                            .map_err(|e| format!("Key '{}' for {} does not parse ({})", s, name, e))
+                    })
+                    .collect()
+            };
+            let defaults = parse_all(default_keys).expect("default keys must parse");
+            let configured = match keys.get(name) {
+                Some(chords) => match parse_all(&chords.chords()) {
+                    Ok(parsed) => Some(parsed),
+                    Err(e) => {
+                        // Reported once for a name shared by several contexts
+                        let problem = format!("{}; using the default", e);
+                        if !problems.contains(&problem) {
+                            problems.push(problem);
+                        }
+                        None
+                    }
+                },
+                None => None,
+            };
+
+            let bound = contexts.entry(context).or_default();
+            let holder = |key: &(KeyModifiers, KeyCode)| {
+                bound
+                    .commands
+                    .get(key)
+                    .map(|other| other.name())
+                    .or_else(|| taken.get(key).copied())
+            };
+            let wanted = configured.as_ref().unwrap_or(&defaults);
+            let mut kept = Vec::new();
+            for key in wanted {
+                match holder(key) {
+                    Some(other) => problems.push(format!(
+                        "Key '{}' for {} is already bound to {} in {}{}",
+                        display_key(key.0, key.1),
+                        name,
+                        other,
+                        context.label(),
+                        if configured.is_some() {
+                            format!("; using '{}'", default_keys.join(", "))
+                        } else {
+                            String::new()
+                        }
+                    )),
+                    None => kept.push(*key),
+                }
+            }
+            // A clash among configured keys brings the defaults back
+            if kept.len() < wanted.len() && configured.is_some() {
+                kept = defaults
+                    .iter()
+                    .filter(|key| holder(key).is_none())
+                    .copied()
+                    .collect();
+            }
+            for &key in &kept {
+                bound.commands.insert(key, command);
+                // Global commands hold their keys in every other context
+                if context == KeyContext::Global {
+                    taken.insert(key, name);
+                }
+            }
+            let shown: Vec<_> = kept
+                .iter()
+                .map(|&(mods, code)| display_key(mods, code))
+                .collect();
+            let shown = if shown.is_empty() {
+                "(none)".to_string()
+            } else {
+                shown.join("/")
+            };
+            help.push((context, shown, text));
+        }
+        help.extend([
+            (
+                KeyContext::Confirm,
+                "h/l \u{2190}/\u{2192}".to_string(),
+                "Select Yes / No",
+            ),
+            (
+                KeyContext::Confirm,
+                "Enter".to_string(),
+                "Execute selection",
+            ),
+        ]);
+
+        Self {
+            global,
+            hints,
+            contexts,
+            help,
+            problems,
+        }
     }
 
     /// Check only the configured global bindings, ignoring context-specific fallbacks.
@@ -239,30 +1231,45 @@ impl Keymap {
             .unwrap_or(Action::None)
     }
 
-    /// Resolve a key event to an action based on global bindings and context.
-    pub fn resolve(&self, key: KeyEvent, focus: FocusTarget) -> Action {
-        // Check configured global bindings
+    /// Resolve a key the focused panel left alone: global bindings first,
+    /// then the global commands, then the context's commands that need
+    /// nothing from the panel.
+    pub fn resolve(&self, context: KeyContext, key: KeyEvent) -> Option<Action> {
         if let Some(action) = self.global.get(&(key.modifiers, key.code)) {
-            return action.clone();
+            return Some(action.clone());
         }
+        [KeyContext::Global, context]
+            .into_iter()
+            .find_map(|context| {
+                self.command(context, &key)
+                    .and_then(|command| command.action(context))
+            })
+    }
 
-        // Hardcoded F3 for About dialog
-        if key.code == KeyCode::F(3) && key.modifiers == KeyModifiers::NONE {
-            return Action::ShowAbout;
-        }
+    /// The command bound to `key` in `context`, if any.
+    pub fn command(&self, context: KeyContext, key: &KeyEvent) -> Option<Command> {
+        self.contexts
+            .get(&context)
+            .and_then(|keys| keys.command(key))
+    }
 
-        // Hardcoded '?' fallback for help (when not already bound)
-        if key.code == KeyCode::Char('?') && key.modifiers == KeyModifiers::NONE {
-            return Action::ShowHelp;
-        }
+    /// The commands of one context, for the component handling its keys.
+    pub fn context(&self, context: KeyContext) -> ContextKeys {
+        self.contexts.get(&context).cloned().unwrap_or_default()
+    }
 
-        // Context-specific hardcoded bindings (vim nav, panel-internal)
-        match focus {
-            FocusTarget::TreePanel => resolve_tree(key),
-            FocusTarget::DetailPanel => resolve_detail(key),
-            FocusTarget::CommandPanel => resolve_command(key),
-            FocusTarget::ConnectionsTree | FocusTarget::ConnectionForm => Action::None,
-        }
+    /// The effective keys of a context and what they do, for the help popup.
+    pub fn help(&self, context: KeyContext) -> Vec<(String, String)> {
+        self.help
+            .iter()
+            .filter(|(c, _, _)| *c == context)
+            .map(|(_, keys, text)| (keys.clone(), text.to_string()))
+            .collect()
+    }
+
+    /// Keys in the config that could not be used, one message each.
+    pub fn problems(&self) -> &[String] {
+        &self.problems
     }
 
     /// Get the display string for a named action (for status bar hints).
@@ -277,32 +1284,13 @@ impl Default for Keymap {
     }
 }
 
-fn resolve_tree(key: KeyEvent) -> Action {
-    match key.code {
-        KeyCode::Up | KeyCode::Char('k') => Action::TreeUp,
-        KeyCode::Down | KeyCode::Char('j') => Action::TreeDown,
-        KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => Action::TreeToggle,
-        KeyCode::Left | KeyCode::Char('h') => Action::TreeCollapse(String::new()),
-        _ => Action::None,
-    }
-}
-
-fn resolve_detail(key: KeyEvent) -> Action {
-    match key.code {
-        KeyCode::Char('r') => Action::EntryRefresh,
-        _ => Action::None,
-    }
-}
-
-fn resolve_command(_key: KeyEvent) -> Action {
-    Action::None
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
     use crossterm::event::KeyModifiers;
 
+    use crate::config::KeyChords;
+
     fn key(code: KeyCode) -> KeyEvent {
         KeyEvent::new(code, KeyModifiers::NONE)
     }
@@ -417,6 +1405,33 @@ mod tests {
         assert!(parse_key("Meta+x").is_err());
     }
 
+    #[test]
+    fn test_parse_key_dash_separator() {
+        assert_eq!(
+            parse_key("ctrl-e").unwrap(),
+            (KeyModifiers::CONTROL, KeyCode::Char('e'))
+        );
+        assert_eq!(
+            parse_key("alt-shift-x").unwrap(),
+            (KeyModifiers::ALT | KeyModifiers::SHIFT, KeyCode::Char('x'))
+        );
+        // A lone '+' or '-' is the key itself
+        assert_eq!(
+            parse_key("+").unwrap(),
+            (KeyModifiers::NONE, KeyCode::Char('+'))
+        );
+        assert_eq!(
+            parse_key("ctrl--").unwrap(),
+            (KeyModifiers::CONTROL, KeyCode::Char('-'))
+        );
+        assert_eq!(
+            parse_key("G").unwrap(),
+            (KeyModifiers::NONE, KeyCode::Char('G'))
+        );
+        assert!(parse_key("hyper-x").is_err());
+        assert!(parse_key("Fx").is_err());
+    }
+
     // --- display_key tests ---
 
     #[test]
@@ -462,80 +1477,119 @@ mod tests {
     #[test]
     fn test_default_quit() {
         let km = Keymap::default();
-        let action = km.resolve(ctrl(KeyCode::Char('q')), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, ctrl(KeyCode::Char('q')))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::Quit));
     }
 
     #[test]
     fn test_default_ctrl_c_quit() {
         let km = Keymap::default();
-        let action = km.resolve(ctrl(KeyCode::Char('c')), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, ctrl(KeyCode::Char('c')))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::Quit));
     }
 
     #[test]
     fn test_default_tab_focus() {
         let km = Keymap::default();
-        let action = km.resolve(key(KeyCode::Tab), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, key(KeyCode::Tab))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::FocusNext));
     }
 
     #[test]
     fn test_default_f2_connect() {
         let km = Keymap::default();
-        let action = km.resolve(key(KeyCode::F(2)), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, key(KeyCode::F(2)))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::ShowConnectDialog));
     }
 
     #[test]
     fn test_default_f4_export() {
         let km = Keymap::default();
-        let action = km.resolve(key(KeyCode::F(4)), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, key(KeyCode::F(4)))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::ShowExportDialog));
     }
 
     #[test]
     fn test_default_f6_schema() {
         let km = Keymap::default();
-        let action = km.resolve(key(KeyCode::F(6)), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, key(KeyCode::F(6)))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::ShowSchemaViewer));
     }
 
     #[test]
     fn test_default_f10_save_connection() {
         let km = Keymap::default();
-        let action = km.resolve(key(KeyCode::F(10)), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, key(KeyCode::F(10)))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::SaveCurrentConnection));
     }
 
     #[test]
     fn test_default_f9_search() {
         let km = Keymap::default();
-        let action = km.resolve(key(KeyCode::F(9)), FocusTarget::DetailPanel);
+        let action = km
+            .resolve(KeyContext::Detail, key(KeyCode::F(9)))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::SearchFocusInput));
     }
 
+    #[test]
+    fn test_default_alt_arrows_resize_panes() {
+        let km = Keymap::default();
+        let alt = |code| KeyEvent::new(code, KeyModifiers::ALT);
+        assert!(matches!(
+            km.resolve(KeyContext::Tree, alt(KeyCode::Right))
+                .unwrap_or(Action::None),
+            Action::ResizePanes(RESIZE_STEP)
+        ));
+        assert!(matches!(
+            km.resolve(KeyContext::Detail, alt(KeyCode::Left)).unwrap_or(Action::None),
+            Action::ResizePanes(step) if step == -RESIZE_STEP
+        ));
+    }
+
     #[test]
     fn test_tree_navigation() {
         let km = Keymap::default();
-        let action = km.resolve(key(KeyCode::Char('j')), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, key(KeyCode::Char('j')))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::TreeDown));
 
-        let action = km.resolve(key(KeyCode::Char('k')), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, key(KeyCode::Char('k')))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::TreeUp));
     }
 
     #[test]
     fn test_tree_panel_a_no_action() {
         let km = Keymap::default();
-        let action = km.resolve(key(KeyCode::Char('a')), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, key(KeyCode::Char('a')))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::None));
     }
 
     #[test]
     fn test_tree_panel_d_no_action() {
         let km = Keymap::default();
-        let action = km.resolve(key(KeyCode::Char('d')), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, key(KeyCode::Char('d')))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::None));
     }
 
@@ -549,18 +1603,24 @@ mod tests {
         let km = Keymap::from_config(&config);
 
         // Custom quit
-        let action = km.resolve(
-            KeyEvent::new(KeyCode::Char('q'), KeyModifiers::ALT),
-            FocusTarget::TreePanel,
-        );
+        let action = km
+            .resolve(
+                KeyContext::Tree,
+                KeyEvent::new(KeyCode::Char('q'), KeyModifiers::ALT),
+            )
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::Quit));
 
         // Custom connect dialog
-        let action = km.resolve(key(KeyCode::F(5)), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, key(KeyCode::F(5)))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::ShowConnectDialog));
 
         // Old Ctrl+q no longer quits (overridden)
-        let action = km.resolve(ctrl(KeyCode::Char('q')), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, ctrl(KeyCode::Char('q')))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::None));
     }
 
@@ -572,7 +1632,9 @@ mod tests {
         let km = Keymap::from_config(&config);
 
         // Should fall back to default "Ctrl+q"
-        let action = km.resolve(ctrl(KeyCode::Char('q')), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, ctrl(KeyCode::Char('q')))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::Quit));
     }
 
@@ -581,23 +1643,28 @@ mod tests {
         let km = Keymap::default();
 
         assert!(matches!(
-            km.resolve(key(KeyCode::F(8)), FocusTarget::TreePanel),
+            km.resolve(KeyContext::Tree, key(KeyCode::F(8)))
+                .unwrap_or(Action::None),
             Action::ShowBulkUpdateDialog
         ));
         assert!(matches!(
-            km.resolve(key(KeyCode::F(7)), FocusTarget::TreePanel),
+            km.resolve(KeyContext::Tree, key(KeyCode::F(7)))
+                .unwrap_or(Action::None),
             Action::ToggleLogPanel
         ));
         assert!(matches!(
-            km.resolve(key(KeyCode::F(9)), FocusTarget::TreePanel),
+            km.resolve(KeyContext::Tree, key(KeyCode::F(9)))
+                .unwrap_or(Action::None),
             Action::SearchFocusInput
         ));
         assert!(matches!(
-            km.resolve(key(KeyCode::F(10)), FocusTarget::TreePanel),
+            km.resolve(KeyContext::Tree, key(KeyCode::F(10)))
+                .unwrap_or(Action::None),
             Action::SaveCurrentConnection
         ));
         assert!(matches!(
-            km.resolve(key(KeyCode::F(1)), FocusTarget::TreePanel),
+            km.resolve(KeyContext::Tree, key(KeyCode::F(1)))
+                .unwrap_or(Action::None),
             Action::SwitchLayout(ActiveLayout::Profiles)
         ));
     }
@@ -622,14 +1689,154 @@ mod tests {
     #[test]
     fn test_default_f5_help() {
         let km = Keymap::default();
-        let action = km.resolve(key(KeyCode::F(5)), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, key(KeyCode::F(5)))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::ShowHelp));
     }
 
     #[test]
     fn test_question_mark_fallback_help() {
         let km = Keymap::default();
-        let action = km.resolve(key(KeyCode::Char('?')), FocusTarget::TreePanel);
+        let action = km
+            .resolve(KeyContext::Tree, key(KeyCode::Char('?')))
+            .unwrap_or(Action::None);
         assert!(matches!(action, Action::ShowHelp));
     }
+
+    fn with_commands(entries: &[(&str, &str)]) -> KeybindingConfig {
+        KeybindingConfig {
+            commands: entries
+                .iter()
+                .map(|(name, chord)| (name.to_string(), KeyChords::One(chord.to_string())))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_default_keys_have_no_problems() {
+        let km = Keymap::default();
+        assert!(km.problems().is_empty(), "{:?}", km.problems());
+        assert_eq!(
+            km.command(KeyContext::Tree, &key(KeyCode::Char('r'))),
+            Some(Command::Rename)
+        );
+        assert_eq!(
+            km.command(KeyContext::Detail, &key(KeyCode::Char('r'))),
+            Some(Command::Refresh)
+        );
+        // Terminals report capitals with or without Shift
+        let shifted = KeyEvent::new(KeyCode::Char('G'), KeyModifiers::SHIFT);
+        assert_eq!(
+            km.command(KeyContext::Tree, &shifted),
+            Some(Command::Groups)
+        );
+        assert!(matches!(
+            km.resolve(KeyContext::Browser, key(KeyCode::Char(':'))),
+            Some(Action::CommandFocusInput)
+        ));
+        assert!(km
+            .resolve(KeyContext::Tree, key(KeyCode::Char('r')))
+            .is_none());
+    }
+
+    #[test]
+    fn test_command_keys_rebind_commands() {
+        let mut config = with_commands(&[("toggle_select", "x"), ("down", "ctrl-n")]);
+        config.quit = "ctrl-x".to_string();
+        let km = Keymap::from_config(&config);
+        assert!(km.problems().is_empty(), "{:?}", km.problems());
+        assert_eq!(
+            km.command(KeyContext::Results, &key(KeyCode::Char('x'))),
+            Some(Command::ToggleSelect)
+        );
+        assert_eq!(
+            km.command(KeyContext::Results, &key(KeyCode::Char(' '))),
+            None
+        );
+        // A shared name rebinds the command everywhere it exists
+        for context in [
+            KeyContext::Tree,
+            KeyContext::Detail,
+            KeyContext::Results,
+            KeyContext::LogPanel,
+        ] {
+            assert_eq!(
+                km.command(context, &ctrl(KeyCode::Char('n'))),
+                Some(Command::Down)
+            );
+            assert_eq!(km.command(context, &key(KeyCode::Char('j'))), None);
+        }
+        assert!(matches!(
+            km.resolve(KeyContext::Tree, ctrl(KeyCode::Char('x'))),
+            Some(Action::Quit)
+        ));
+        assert_eq!(km.hint("quit"), "C-x");
+        assert!(km
+            .help(KeyContext::Results)
+            .contains(&("x".to_string(), "Select / unselect result".to_string())));
+    }
+
+    #[test]
+    fn test_command_keys_problems() {
+        let mut config =
+            with_commands(&[("rename", "C"), ("export", "hyper-e"), ("teleport", "t")]);
+        config.show_help = "F2".to_string();
+        let km = Keymap::from_config(&config);
+        assert_eq!(
+            km.problems(),
+            [
+                "Key 'F2' for show_help is already bound to show_connect_dialog; using 'F5'",
+                "Unknown command 'teleport'",
+                "Key 'C' for rename is already bound to compare in the tree panel; using 'r'",
+                "Key 'hyper-e' for export does not parse (unknown modifier: hyper); using the default",
+            ]
+        );
+        // Each command keeps its default keys
+        assert_eq!(
+            km.command(KeyContext::Tree, &key(KeyCode::Char('r'))),
+            Some(Command::Rename)
+        );
+        assert_eq!(
+            km.command(KeyContext::Tree, &key(KeyCode::Char('C'))),
+            Some(Command::Compare)
+        );
+        assert_eq!(
+            km.command(KeyContext::Results, &key(KeyCode::Char('e'))),
+            Some(Command::Export)
+        );
+        assert!(matches!(
+            km.resolve(KeyContext::Tree, key(KeyCode::F(5))),
+            Some(Action::ShowHelp)
+        ));
+    }
+
+    #[test]
+    fn test_about_and_help_keys_are_rebindable() {
+        let km = Keymap::default();
+        assert!(matches!(
+            km.resolve(KeyContext::Tree, key(KeyCode::F(3))),
+            Some(Action::ShowAbout)
+        ));
+        let km = Keymap::from_config(&with_commands(&[("about", "ctrl-a"), ("help", "F11")]));
+        assert!(km.problems().is_empty(), "{:?}", km.problems());
+        assert!(km.resolve(KeyContext::Tree, key(KeyCode::F(3))).is_none());
+        assert!(km
+            .resolve(KeyContext::Tree, key(KeyCode::Char('?')))
+            .is_none());
+        assert!(matches!(
+            km.resolve(KeyContext::Tree, ctrl(KeyCode::Char('a'))),
+            Some(Action::ShowAbout)
+        ));
+        assert!(matches!(
+            km.resolve(KeyContext::Detail, key(KeyCode::F(11))),
+            Some(Action::ShowHelp)
+        ));
+        // A global command's key is taken from every panel
+        let km = Keymap::from_config(&with_commands(&[("help", "h")]));
+        assert!(km.problems().contains(
+            &"Key 'h' for collapse is already bound to help in the tree panel".to_string()
+        ));
+    }
 }
@@ -1,11 +1,20 @@
 pub mod action;
 pub mod app;
+pub mod clipboard;
+pub mod command_line;
 pub mod component;
 pub mod components;
 pub mod config;
+pub mod connecting;
+pub mod doctor;
 pub mod event;
 pub mod focus;
+pub mod ingest;
 pub mod keymap;
+pub mod logging;
+pub mod panes;
+pub mod paths;
+pub mod settings;
 pub mod theme;
 pub mod tui;
 pub mod widgets;
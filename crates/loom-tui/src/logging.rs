@@ -0,0 +1,277 @@
+//! Where tracing output goes: the log file, opened on the first message
+//! written to it, and the most recent lines, kept in memory for the log
+//! viewer.
+
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use tracing::{Level, Metadata};
+use tracing_subscriber::fmt::MakeWriter;
+
+use crate::config::GeneralConfig;
+use crate::paths::{expand_path, state_dir};
+
+/// One formatted log line.
+#[derive(Debug, Clone)]
+pub struct LogLine {
+    pub level: Level,
+    pub text: String,
+}
+
+/// The most recent log lines, shared by the writer filling it and the
+/// log viewer reading it.
+#[derive(Clone)]
+pub struct LogBuffer {
+    inner: Arc<Mutex<Lines>>,
+}
+
+struct Lines {
+    lines: VecDeque<LogLine>,
+    capacity: usize,
+}
+
+impl LogBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Lines {
+                lines: VecDeque::new(),
+                capacity,
+            })),
+        }
+    }
+
+    /// Keep at most `capacity` lines, dropping the oldest.
+    pub fn set_capacity(&self, capacity: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.capacity = capacity;
+        let excess = inner.lines.len().saturating_sub(capacity);
+        inner.lines.drain(..excess);
+    }
+
+    pub fn push(&self, level: Level, text: String) {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.capacity == 0 {
+            return;
+        }
+        if inner.lines.len() == inner.capacity {
+            inner.lines.pop_front();
+        }
+        inner.lines.push_back(LogLine { level, text });
+    }
+
+    /// The lines at `level` or more severe, oldest first.
+    pub fn lines(&self, level: Level) -> Vec<LogLine> {
+        let inner = self.inner.lock().unwrap();
+        inner
+            .lines
+            .iter()
+            .filter(|line| line.level <= level)
+            .cloned()
+            .collect()
+    }
+}
+
+/// Writer for the tracing formatter: every message goes to the buffer
+/// and, once a path is set, to the log file.
+#[derive(Clone)]
+pub struct LogWriter {
+    buffer: LogBuffer,
+    file: Arc<Mutex<LogFile>>,
+}
+
+#[derive(Default)]
+struct LogFile {
+    path: Option<PathBuf>,
+    file: Option<File>,
+}
+
+impl LogWriter {
+    pub fn new(buffer: LogBuffer) -> Self {
+        Self {
+            buffer,
+            file: Arc::default(),
+        }
+    }
+
+    /// Write to `path` from the next message on. The file, and the
+    /// directories above it, are only created once there is something
+    /// to write; an existing file is started afresh.
+    pub fn set_path(&self, path: PathBuf) {
+        let mut log = self.file.lock().unwrap();
+        if log.path.as_ref() != Some(&path) {
+            log.path = Some(path);
+            log.file = None;
+        }
+    }
+
+    fn write_file(&self, buf: &[u8]) {
+        let mut log = self.file.lock().unwrap();
+        if log.file.is_none() {
+            let Some(path) = log.path.clone() else {
+                return;
+            };
+            let opened = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => fs::create_dir_all(dir),
+                _ => Ok(()),
+            }
+            .and_then(|()| File::create(&path));
+            match opened {
+                Ok(file) => log.file = Some(file),
+                Err(e) => {
+                    // Say so once, where it can be seen, and stop trying
+                    log.path = None;
+                    self.buffer.push(
+                        Level::ERROR,
+                        format!("Cannot write log file {}: {}", path.display(), e),
+                    );
+                    return;
+                }
+            }
+        }
+        if let Some(file) = &mut log.file {
+            let _ = file.write_all(buf);
+        }
+    }
+}
+
+/// Writes one message at a known level.
+pub struct LineWriter<'a> {
+    writer: &'a LogWriter,
+    level: Level,
+}
+
+impl Write for LineWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf).trim_end().to_string();
+        self.writer.buffer.push(self.level, text);
+        self.writer.write_file(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.writer.file.lock().unwrap().file {
+            Some(file) => file.flush(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'a> MakeWriter<'a> for LogWriter {
+    type Writer = LineWriter<'a>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        LineWriter {
+            writer: self,
+            level: Level::INFO,
+        }
+    }
+
+    fn make_writer_for(&'a self, meta: &Metadata<'_>) -> Self::Writer {
+        LineWriter {
+            writer: self,
+            level: *meta.level(),
+        }
+    }
+}
+
+/// Changes the filter of the running subscriber to the given directives.
+pub type FilterReload = Box<dyn Fn(&str) -> Result<(), String>>;
+
+/// The running log: the lines kept for the viewer, the file they are
+/// written to, and the filter, unless `RUST_LOG` set it.
+pub struct Logging {
+    pub buffer: LogBuffer,
+    pub writer: LogWriter,
+    pub filter: Option<FilterReload>,
+}
+
+impl Logging {
+    /// Follow the `log_*` settings of `general`.
+    pub fn apply(&self, general: &GeneralConfig) -> Result<(), String> {
+        self.buffer.set_capacity(general.log_viewer_lines);
+        self.writer.set_path(log_path(general));
+        match &self.filter {
+            Some(reload) => reload(&filter_directives(&general.log_level)?),
+            None => Ok(()),
+        }
+    }
+}
+
+/// The log file: `log_file` from the config, or
+/// `loom-ldapbrowser/loom-ldapbrowser.log` in the state directory
+/// (`$XDG_STATE_HOME`, `~/.local/state`), or the local data directory on
+/// systems without one.
+pub fn log_path(general: &GeneralConfig) -> PathBuf {
+    match &general.log_file {
+        Some(path) => expand_path(path),
+        None => state_dir().join("loom-ldapbrowser.log"),
+    }
+}
+
+/// Filter directives logging loom's own crates at `level` and everything
+/// else at warnings and worse.
+pub fn filter_directives(level: &str) -> Result<String, String> {
+    let level: Level = level
+        .trim()
+        .parse()
+        .map_err(|_| format!("unknown log level: {}", level))?;
+    Ok(format!("warn,loom={}", level.as_str().to_ascii_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_keeps_recent_lines_by_level() {
+        let buffer = LogBuffer::new(3);
+        buffer.push(Level::INFO, "one".to_string());
+        buffer.push(Level::DEBUG, "two".to_string());
+        buffer.push(Level::WARN, "three".to_string());
+        buffer.push(Level::ERROR, "four".to_string());
+
+        let texts =
+            |level| -> Vec<String> { buffer.lines(level).into_iter().map(|l| l.text).collect() };
+        assert_eq!(texts(Level::TRACE), vec!["two", "three", "four"]);
+        assert_eq!(texts(Level::WARN), vec!["three", "four"]);
+
+        buffer.set_capacity(1);
+        assert_eq!(texts(Level::TRACE), vec!["four"]);
+    }
+
+    #[test]
+    fn test_log_file_is_created_on_first_message() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("state").join("loom.log");
+        let buffer = LogBuffer::new(10);
+        let writer = LogWriter::new(buffer.clone());
+        writer.set_path(path.clone());
+        assert!(!path.exists());
+
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(writer)
+            .with_ansi(false)
+            .with_max_level(Level::DEBUG)
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!("search base='dc=example,dc=com' rc=0 entries=0");
+        });
+
+        let lines = buffer.lines(Level::DEBUG);
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].text.ends_with("rc=0 entries=0"));
+        assert!(buffer.lines(Level::INFO).is_empty());
+        let written = fs::read_to_string(&path).unwrap();
+        assert!(written.contains("base='dc=example,dc=com'"));
+    }
+
+    #[test]
+    fn test_filter_directives() {
+        assert_eq!(filter_directives("debug").unwrap(), "warn,loom=debug");
+        assert_eq!(filter_directives(" INFO").unwrap(), "warn,loom=info");
+        assert!(filter_directives("chatty").is_err());
+    }
+}
@@ -0,0 +1,162 @@
+//! Where each pane goes on screen.
+//!
+//! Rendering and mouse hit-testing take their rects from here, so split
+//! ratios and minimum widths are applied in one place.
+
+use ratatui::layout::Rect;
+
+use crate::config::PaneRatios;
+
+/// Narrowest and widest the left pane may be set, as a percentage.
+pub const MIN_PERCENT: u16 = 10;
+pub const MAX_PERCENT: u16 = 90;
+
+/// Percentage one press of a resize key moves the divider.
+pub const RESIZE_STEP: i16 = 5;
+
+/// Columns kept on either side of the divider when the terminal is wide
+/// enough for both; narrower terminals split by percentage alone.
+pub const MIN_PANE_WIDTH: u16 = 16;
+
+/// Rects for one frame of a two-pane layout.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScreenLayout {
+    pub layout_bar: Rect,
+    pub left: Rect,
+    pub right: Rect,
+    pub status: Rect,
+}
+
+impl ScreenLayout {
+    /// Split `area` into the layout bar, the two panes and the status bar.
+    pub fn compute(area: Rect, ratios: PaneRatios) -> Self {
+        let bar_height = area.height.min(1);
+        let status_height = area.height.saturating_sub(bar_height).min(1);
+        let content_height = area.height - bar_height - status_height;
+
+        let layout_bar = Rect::new(area.x, area.y, area.width, bar_height);
+        let content_y = area.y + bar_height;
+        let left_w = left_width(area.width, ratios.tree_percent);
+        let left = Rect::new(area.x, content_y, left_w, content_height);
+        let right = Rect::new(
+            area.x + left_w,
+            content_y,
+            area.width - left_w,
+            content_height,
+        );
+        let status = Rect::new(
+            area.x,
+            content_y + content_height,
+            area.width,
+            status_height,
+        );
+
+        Self {
+            layout_bar,
+            left,
+            right,
+            status,
+        }
+    }
+}
+
+/// Columns given to the left pane out of `total`.
+pub fn left_width(total: u16, percent: u16) -> u16 {
+    let width = proportional(total, percent.clamp(MIN_PERCENT, MAX_PERCENT));
+    if total < MIN_PANE_WIDTH * 2 {
+        width
+    } else {
+        width.clamp(MIN_PANE_WIDTH, total - MIN_PANE_WIDTH)
+    }
+}
+
+/// Move the divider by `delta` percent. Starts from where the divider is
+/// drawn at `total` columns, so a press always moves it visibly even when
+/// the terminal shrank since the ratio was set.
+pub fn resize(percent: u16, delta: i16, total: u16) -> u16 {
+    let percent = percent.clamp(MIN_PERCENT, MAX_PERCENT);
+    let width = left_width(total, percent);
+    let start = if total == 0 || width == proportional(total, percent) {
+        percent
+    } else {
+        percent_at(total, width)
+    };
+    (start as i16 + delta).clamp(MIN_PERCENT as i16, MAX_PERCENT as i16) as u16
+}
+
+/// The ratio that puts the divider `offset` columns into `total`, for
+/// dragging it with the mouse.
+pub fn percent_at(total: u16, offset: u16) -> u16 {
+    if total == 0 {
+        return PaneRatios::default().tree_percent;
+    }
+    let percent = (offset as u32 * 100 + total as u32 / 2) / total as u32;
+    (percent as u16).clamp(MIN_PERCENT, MAX_PERCENT)
+}
+
+fn proportional(total: u16, percent: u16) -> u16 {
+    (total as u32 * percent as u32 / 100) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ratios(tree_percent: u16) -> PaneRatios {
+        PaneRatios { tree_percent }
+    }
+
+    #[test]
+    fn test_compute_splits_content_area() {
+        let layout = ScreenLayout::compute(Rect::new(0, 0, 100, 30), ratios(25));
+        assert_eq!(layout.layout_bar, Rect::new(0, 0, 100, 1));
+        assert_eq!(layout.left, Rect::new(0, 1, 25, 28));
+        assert_eq!(layout.right, Rect::new(25, 1, 75, 28));
+        assert_eq!(layout.status, Rect::new(0, 29, 100, 1));
+    }
+
+    #[test]
+    fn test_compute_keeps_minimum_widths() {
+        let area = Rect::new(0, 0, 100, 30);
+        assert_eq!(ScreenLayout::compute(area, ratios(10)).left.width, 16);
+        assert_eq!(ScreenLayout::compute(area, ratios(90)).right.width, 16);
+        // Too narrow for both minimums: fall back to the percentage
+        let narrow = ScreenLayout::compute(Rect::new(0, 0, 20, 5), ratios(25));
+        assert_eq!(narrow.left.width, 5);
+        assert_eq!(narrow.right.width, 15);
+    }
+
+    #[test]
+    fn test_compute_tiny_terminal() {
+        let layout = ScreenLayout::compute(Rect::new(0, 0, 10, 1), ratios(25));
+        assert_eq!(layout.layout_bar.height, 1);
+        assert_eq!(layout.left.height, 0);
+        assert_eq!(layout.status.height, 0);
+    }
+
+    #[test]
+    fn test_resize_steps_and_clamps() {
+        assert_eq!(resize(25, RESIZE_STEP, 200), 30);
+        assert_eq!(resize(25, -RESIZE_STEP, 200), 20);
+        assert_eq!(resize(12, -RESIZE_STEP, 200), MIN_PERCENT);
+        assert_eq!(resize(88, RESIZE_STEP, 200), MAX_PERCENT);
+    }
+
+    #[test]
+    fn test_resize_after_terminal_shrank() {
+        // 10% of 80 columns is drawn as the 16-column minimum (20%), so
+        // growing moves on from what is on screen rather than from 10%
+        assert_eq!(left_width(80, 10), 16);
+        assert_eq!(resize(10, RESIZE_STEP, 80), 25);
+        assert_eq!(left_width(80, 25), 20);
+    }
+
+    #[test]
+    fn test_percent_at() {
+        assert_eq!(percent_at(100, 30), 30);
+        assert_eq!(percent_at(80, 21), 26);
+        assert_eq!(percent_at(100, 0), MIN_PERCENT);
+        assert_eq!(percent_at(100, 99), MAX_PERCENT);
+        assert_eq!(percent_at(0, 5), 25);
+    }
+}
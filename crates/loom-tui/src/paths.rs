@@ -0,0 +1,234 @@
+//! Paths typed into file fields: expanding them, and writing files
+//! without losing the old contents, shared by every dialog that reads or
+//! writes a file.
+
+use std::path::{Path, PathBuf};
+
+/// Where `~`, `~user` and environment variables are looked up.
+trait Env {
+    fn home(&self) -> Option<PathBuf>;
+    fn user_home(&self, user: &str) -> Option<PathBuf>;
+    fn var(&self, name: &str) -> Option<String>;
+}
+
+/// The running process's home directory, user database and environment.
+struct System;
+
+impl Env for System {
+    fn home(&self) -> Option<PathBuf> {
+        dirs::home_dir()
+    }
+
+    fn user_home(&self, user: &str) -> Option<PathBuf> {
+        if cfg!(unix) {
+            let passwd = std::fs::read_to_string("/etc/passwd").ok()?;
+            passwd_home(&passwd, user)
+        } else {
+            None
+        }
+    }
+
+    fn var(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok()
+    }
+}
+
+/// Expand a path typed into a dialog: a leading `~` or `~/` to the home
+/// directory, `~user` to that user's home on Unix, and environment
+/// variables, `$HOME` or `${HOME}` on Unix and `%USERPROFILE%` on Windows.
+/// Anything that cannot be looked up is left as typed.
+pub fn expand_path(path: &str) -> PathBuf {
+    expand(path, &System, cfg!(windows))
+}
+
+fn expand(path: &str, env: &dyn Env, windows: bool) -> PathBuf {
+    let is_sep = |c: char| c == '/' || (windows && c == '\\');
+    if let Some(after) = path.strip_prefix('~') {
+        let end = after.find(is_sep).unwrap_or(after.len());
+        let (user, rest) = after.split_at(end);
+        let home = if user.is_empty() {
+            env.home()
+        } else if windows {
+            None
+        } else {
+            env.user_home(user)
+        };
+        if let Some(home) = home {
+            let rest = rest.trim_start_matches(is_sep);
+            if rest.is_empty() {
+                return home;
+            }
+            return home.join(expand_vars(rest, env, windows));
+        }
+    }
+    PathBuf::from(expand_vars(path, env, windows))
+}
+
+/// Replace `$NAME` and `${NAME}`, or `%NAME%` when `windows`, with the
+/// variable's value. Unset variables are kept as written.
+fn expand_vars(s: &str, env: &dyn Env, windows: bool) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    let marker = if windows { '%' } else { '$' };
+    while let Some(start) = rest.find(marker) {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let (name, len) = if windows {
+            match after.find('%') {
+                Some(end) => (&after[..end], end + 1),
+                None => ("", 0),
+            }
+        } else if let Some(braced) = after.strip_prefix('{') {
+            match braced.find('}') {
+                Some(end) => (&braced[..end], end + 2),
+                None => ("", 0),
+            }
+        } else {
+            let end = after
+                .char_indices()
+                .find(|&(i, c)| {
+                    !(c == '_' || c.is_ascii_alphabetic() || (i > 0 && c.is_ascii_digit()))
+                })
+                .map_or(after.len(), |(i, _)| i);
+            (&after[..end], end)
+        };
+        match env.var(name).filter(|_| !name.is_empty()) {
+            Some(value) => {
+                out.push_str(&value);
+                rest = &after[len..];
+            }
+            None => {
+                out.push(marker);
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// loom's own state directory, `loom-ldapbrowser` in `$XDG_STATE_HOME`
+/// (`~/.local/state`), or in the local data directory on systems without
+/// one. Files loom writes unasked go here, never to the working directory.
+pub fn state_dir() -> PathBuf {
+    dirs::state_dir()
+        .or_else(dirs::data_local_dir)
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("loom-ldapbrowser")
+}
+
+/// Write `contents` to a file beside `path`, then move it over `path`, so
+/// a failed write never leaves an existing file truncated.
+pub fn write_replacing(path: &Path, contents: &[u8]) -> std::io::Result<()> {
+    let mut partial = path.as_os_str().to_owned();
+    partial.push(".partial");
+    let partial = PathBuf::from(partial);
+    let result = std::fs::write(&partial, contents).and_then(|_| std::fs::rename(&partial, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&partial);
+    }
+    result
+}
+
+/// Home directory of `user` in the contents of `/etc/passwd`.
+fn passwd_home(passwd: &str, user: &str) -> Option<PathBuf> {
+    passwd.lines().find_map(|line| {
+        let fields: Vec<&str> = line.split(':').collect();
+        (fields.len() >= 7 && fields[0] == user).then(|| PathBuf::from(fields[5]))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Mock;
+
+    impl Env for Mock {
+        fn home(&self) -> Option<PathBuf> {
+            Some(PathBuf::from("/home/me"))
+        }
+
+        fn user_home(&self, user: &str) -> Option<PathBuf> {
+            (user == "alice").then(|| PathBuf::from("/home/alice"))
+        }
+
+        fn var(&self, name: &str) -> Option<String> {
+            match name {
+                "HOME" => Some("/home/me".to_string()),
+                "USERPROFILE" => Some(r"C:\Users\me".to_string()),
+                "DIR" => Some("exports".to_string()),
+                _ => None,
+            }
+        }
+    }
+
+    fn unix(path: &str) -> PathBuf {
+        expand(path, &Mock, false)
+    }
+
+    #[test]
+    fn test_tilde() {
+        assert_eq!(unix("~"), Path::new("/home/me"));
+        assert_eq!(unix("~/"), Path::new("/home/me"));
+        assert_eq!(unix("~/out.ldif"), Path::new("/home/me/out.ldif"));
+        assert_eq!(unix("~alice/out.ldif"), Path::new("/home/alice/out.ldif"));
+        assert_eq!(unix("~alice"), Path::new("/home/alice"));
+        // Unknown users and a tilde later on are left alone
+        assert_eq!(unix("~bob/out.ldif"), Path::new("~bob/out.ldif"));
+        assert_eq!(unix("out~/x"), Path::new("out~/x"));
+    }
+
+    #[test]
+    fn test_unix_variables() {
+        assert_eq!(unix("$HOME/out.csv"), Path::new("/home/me/out.csv"));
+        assert_eq!(unix("${HOME}/out.csv"), Path::new("/home/me/out.csv"));
+        assert_eq!(
+            unix("~/$DIR/out.csv"),
+            Path::new("/home/me/exports/out.csv")
+        );
+        assert_eq!(unix("$DIRS/a"), Path::new("$DIRS/a"));
+        assert_eq!(unix("${DIR/a"), Path::new("${DIR/a"));
+        assert_eq!(unix("cost$5.csv"), Path::new("cost$5.csv"));
+        assert_eq!(unix("%USERPROFILE%/a"), Path::new("%USERPROFILE%/a"));
+    }
+
+    #[test]
+    fn test_windows_variables() {
+        let windows = |path| expand(path, &Mock, true);
+        assert_eq!(
+            windows(r"%USERPROFILE%\out.csv"),
+            PathBuf::from(r"C:\Users\me\out.csv")
+        );
+        assert_eq!(windows("%NOPE%/a"), PathBuf::from("%NOPE%/a"));
+        assert_eq!(windows("50%"), PathBuf::from("50%"));
+        assert_eq!(windows("$HOME"), PathBuf::from("$HOME"));
+        // No other users' homes outside Unix
+        assert_eq!(windows("~alice/a"), PathBuf::from("~alice/a"));
+    }
+
+    #[test]
+    fn test_passwd_home() {
+        let passwd = "# users\nroot:x:0:0:root:/root:/bin/sh\nalice:x:1000:1000:Alice A:/home/alice:/bin/bash\n";
+        assert_eq!(
+            passwd_home(passwd, "alice"),
+            Some(PathBuf::from("/home/alice"))
+        );
+        assert_eq!(passwd_home(passwd, "root"), Some(PathBuf::from("/root")));
+        assert_eq!(passwd_home(passwd, "ali"), None);
+    }
+
+    #[test]
+    fn test_write_replacing() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("profiles.toml");
+        std::fs::write(&path, "old").unwrap();
+        write_replacing(&path, b"new").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        // A failed write leaves nothing behind
+        let missing = dir.path().join("no-such-dir").join("profiles.toml");
+        assert!(write_replacing(&missing, b"new").is_err());
+    }
+}
@@ -0,0 +1,481 @@
+//! Changing the config while loom runs: `:set` overrides a setting for
+//! the session, `:reload-config` (or saving the file in an editor) reads
+//! it again, and either way the changes are sorted by when they take
+//! effect: at once, on the next connection, or after a restart.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::config::AppConfig;
+use crate::logging;
+use crate::theme::Styles;
+
+/// When a changed setting takes effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Takes {
+    Now,
+    /// On connections opened after the change; open ones keep the old value.
+    NewConnections,
+    Restart,
+}
+
+/// The config file section a setting is under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    General,
+    Detail,
+}
+
+/// A setting `:set` changes, named as in the config file.
+#[derive(Debug)]
+pub struct Setting {
+    pub name: &'static str,
+    section: Section,
+    pub takes: Takes,
+}
+
+const fn setting(name: &'static str, section: Section, takes: Takes) -> Setting {
+    Setting {
+        name,
+        section,
+        takes,
+    }
+}
+
+/// The `[general]` and `[detail]` settings.
+pub const SETTINGS: &[Setting] = &[
+    setting("theme", Section::General, Takes::Now),
+    setting("tick_rate_ms", Section::General, Takes::Restart),
+    setting("log_level", Section::General, Takes::Now),
+    setting("log_file", Section::General, Takes::Now),
+    setting("log_viewer_lines", Section::General, Takes::Now),
+    setting("autocomplete", Section::General, Takes::Now),
+    setting("live_search", Section::General, Takes::Now),
+    setting("vault_enabled", Section::General, Takes::Restart),
+    setting("dedup_ignore_value_case", Section::General, Takes::Now),
+    setting("hooks_enabled", Section::General, Takes::Now),
+    setting("child_page_size", Section::General, Takes::Now),
+    setting("ingest_entries_per_tick", Section::General, Takes::Now),
+    setting("index_strip", Section::General, Takes::Now),
+    setting("confirm_discard", Section::General, Takes::Restart),
+    setting("health_check_secs", Section::General, Takes::NewConnections),
+    setting("undo_depth", Section::General, Takes::NewConnections),
+    setting("audit_log", Section::General, Takes::NewConnections),
+    setting("audit_format", Section::General, Takes::NewConnections),
+    setting("audit_max_mb", Section::General, Takes::NewConnections),
+    setting("audit_keep", Section::General, Takes::NewConnections),
+    setting("name_column_max_percent", Section::Detail, Takes::Now),
+    setting("align_names_right", Section::Detail, Takes::Now),
+    setting("compact_below", Section::Detail, Takes::Now),
+    setting("time_display", Section::Detail, Takes::Now),
+    setting("link_attributes", Section::Detail, Takes::Now),
+];
+
+fn find(name: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|s| s.name == name)
+}
+
+fn table<T: Serialize>(section: &T) -> toml::Table {
+    match toml::Value::try_from(section) {
+        Ok(toml::Value::Table(table)) => table,
+        _ => toml::Table::new(),
+    }
+}
+
+/// The value `config` has for `setting`, as the file would have it;
+/// `None` when left at a default the file leaves out.
+fn get(config: &AppConfig, setting: &Setting) -> Option<toml::Value> {
+    let table = match setting.section {
+        Section::General => table(&config.general),
+        Section::Detail => table(&config.detail),
+    };
+    table.get(setting.name).cloned()
+}
+
+/// Give `setting` the value as the file would have it; `None` puts
+/// back the default.
+fn put(
+    config: &mut AppConfig,
+    setting: &Setting,
+    value: Option<toml::Value>,
+) -> Result<(), toml::de::Error> {
+    match setting.section {
+        Section::General => config.general = replaced(&config.general, setting.name, value)?,
+        Section::Detail => config.detail = replaced(&config.detail, setting.name, value)?,
+    }
+    Ok(())
+}
+
+fn replaced<T: Serialize + DeserializeOwned>(
+    section: &T,
+    name: &str,
+    value: Option<toml::Value>,
+) -> Result<T, toml::de::Error> {
+    let mut table = table(section);
+    match value {
+        Some(value) => table.insert(name.to_string(), value),
+        None => table.remove(name),
+    };
+    toml::Value::Table(table).try_into()
+}
+
+/// What `value` as typed could be, tried in turn: a boolean, a number,
+/// a comma-separated list, and last of all the text itself.
+fn candidates(value: &str) -> Vec<toml::Value> {
+    let mut candidates = Vec::new();
+    if let Ok(b) = value.parse::<bool>() {
+        candidates.push(toml::Value::Boolean(b));
+    }
+    if let Ok(n) = value.parse::<i64>() {
+        candidates.push(toml::Value::Integer(n));
+    }
+    candidates.push(toml::Value::Array(
+        value
+            .split(',')
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| toml::Value::String(v.to_string()))
+            .collect(),
+    ));
+    candidates.push(toml::Value::String(value.to_string()));
+    candidates
+}
+
+/// Checks the file format cannot make: that the theme exists and the log
+/// level is one.
+fn check(config: &AppConfig, setting: &Setting) -> Result<(), String> {
+    match setting.name {
+        "theme" => Styles::named(&config.general.theme)
+            .map(|_| ())
+            .map_err(|e| format!("Theme {}", e)),
+        "log_level" => logging::filter_directives(&config.general.log_level).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+/// Set the setting called `name` in `config` to `value` as typed.
+/// Nothing changes when the value does not suit the setting.
+pub fn set(config: &mut AppConfig, name: &str, value: &str) -> Result<&'static Setting, String> {
+    let setting = find(name).ok_or_else(|| format!("Unknown setting '{}'", name))?;
+    let mut error = String::new();
+    for candidate in candidates(value) {
+        let mut changed = config.clone();
+        match put(&mut changed, setting, Some(candidate)) {
+            Ok(()) => {
+                check(&changed, setting)?;
+                *config = changed;
+                return Ok(setting);
+            }
+            Err(e) => error = e.message().to_string(),
+        }
+    }
+    Err(format!("{} cannot be '{}': {}", name, value, error))
+}
+
+/// What differs between two configs, by when each change takes effect.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Changes {
+    pub now: Vec<&'static str>,
+    pub new_connections: Vec<&'static str>,
+    pub restart: Vec<&'static str>,
+}
+
+impl Changes {
+    pub fn between(old: &AppConfig, new: &AppConfig) -> Self {
+        fn same<T: Serialize>(a: &T, b: &T) -> bool {
+            toml::Value::try_from(a).ok() == toml::Value::try_from(b).ok()
+        }
+
+        let mut changes = Self::default();
+        for setting in SETTINGS {
+            if get(old, setting) != get(new, setting) {
+                changes.push(setting.name, setting.takes);
+            }
+        }
+        // Whole sections, named by their first key. The histories are
+        // loom's own record, not settings.
+        let sections = [
+            (
+                "profiles",
+                same(&old.connections, &new.connections) && same(&old.folders, &new.folders),
+                Takes::Now,
+            ),
+            ("layout", same(&old.layout, &new.layout), Takes::Now),
+            (
+                "bookmarks",
+                same(&old.bookmarks, &new.bookmarks),
+                Takes::Now,
+            ),
+            (
+                "export_templates",
+                same(&old.export_templates, &new.export_templates),
+                Takes::Now,
+            ),
+            (
+                "export_presets",
+                same(&old.export_presets, &new.export_presets),
+                Takes::Now,
+            ),
+            (
+                "copy_templates",
+                same(&old.copy_templates, &new.copy_templates),
+                Takes::Now,
+            ),
+            (
+                "keybindings",
+                same(&old.keybindings, &new.keybindings),
+                Takes::Now,
+            ),
+            (
+                "trusted_certificates",
+                same(&old.trusted_certificates, &new.trusted_certificates),
+                Takes::Restart,
+            ),
+        ];
+        for (name, same, takes) in sections {
+            if !same {
+                changes.push(name, takes);
+            }
+        }
+        changes
+    }
+
+    fn push(&mut self, name: &'static str, takes: Takes) {
+        match takes {
+            Takes::Now => self.now.push(name),
+            Takes::NewConnections => self.new_connections.push(name),
+            Takes::Restart => self.restart.push(name),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.now.is_empty() && self.new_connections.is_empty() && self.restart.is_empty()
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        [&self.now, &self.new_connections, &self.restart]
+            .iter()
+            .any(|names| names.contains(&name))
+    }
+
+    /// `theme, time_display applied; undo_depth on new connections;
+    /// tick_rate_ms after a restart`.
+    pub fn summary(&self) -> String {
+        let groups = [
+            (&self.now, "applied"),
+            (&self.new_connections, "on new connections"),
+            (&self.restart, "after a restart"),
+        ];
+        groups
+            .iter()
+            .filter(|(names, _)| !names.is_empty())
+            .map(|(names, when)| format!("{} {}", names.join(", "), when))
+            .collect::<Vec<_>>()
+            .join("; ")
+    }
+}
+
+/// Settings changed with `:set` for this session. Saving the config
+/// writes them as the file had them, so they last until loom exits
+/// unless `:set-save` keeps them.
+#[derive(Debug, Default)]
+pub struct Overrides {
+    settings: BTreeMap<&'static str, Override>,
+}
+
+#[derive(Debug)]
+struct Override {
+    setting: &'static Setting,
+    /// What the file has.
+    loaded: Option<toml::Value>,
+    /// What `:set` made it.
+    value: Option<toml::Value>,
+}
+
+impl Overrides {
+    /// Set `name` to `value` in `config` for the session.
+    pub fn set(
+        &mut self,
+        config: &mut AppConfig,
+        name: &str,
+        value: &str,
+    ) -> Result<&'static Setting, String> {
+        let loaded = find(name).and_then(|s| get(config, s));
+        let setting = set(config, name, value)?;
+        let value = get(config, setting);
+        self.settings
+            .entry(setting.name)
+            .or_insert(Override {
+                setting,
+                loaded,
+                value: None,
+            })
+            .value = value;
+        Ok(setting)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.settings.is_empty()
+    }
+
+    /// `config` as the file should have it: the overridden settings back
+    /// at the values the file gave them.
+    pub fn persisted(&self, config: &AppConfig) -> AppConfig {
+        let mut persisted = config.clone();
+        for o in self.settings.values() {
+            let _ = put(&mut persisted, o.setting, o.loaded.clone());
+        }
+        persisted
+    }
+
+    /// Lay the overrides over `config`, read again from the file; its
+    /// values become the ones a save goes back to.
+    pub fn reapply(&mut self, config: &mut AppConfig) {
+        for o in self.settings.values_mut() {
+            o.loaded = get(config, o.setting);
+            let _ = put(config, o.setting, o.value.clone());
+        }
+    }
+
+    /// Forget the overrides, so their values are the ones saved from now
+    /// on. Returns the names of the settings.
+    pub fn keep(&mut self) -> Vec<&'static str> {
+        std::mem::take(&mut self.settings).into_keys().collect()
+    }
+}
+
+/// Notices the config file being written by something other than loom.
+#[derive(Debug)]
+pub struct ConfigWatch {
+    path: PathBuf,
+    /// When the file was last written, and its length, so two writes
+    /// within the clock's resolution still tell apart.
+    stamp: Option<(SystemTime, u64)>,
+}
+
+impl ConfigWatch {
+    pub fn new(path: PathBuf) -> Self {
+        let stamp = stamp(&path);
+        Self { path, stamp }
+    }
+
+    /// Whether the file was written since the last look, or since loom
+    /// last [read or wrote](Self::seen) it.
+    pub fn changed(&mut self) -> bool {
+        let now = stamp(&self.path);
+        if now == self.stamp {
+            return false;
+        }
+        self.stamp = now;
+        now.is_some()
+    }
+
+    /// loom read or wrote the file as it is now; there is nothing new in it.
+    pub fn seen(&mut self) {
+        self.stamp = stamp(&self.path);
+    }
+}
+
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use loom_core::time::TimeDisplay;
+
+    #[test]
+    fn test_set_parses_by_setting_type() {
+        let mut config = AppConfig::default();
+        set(&mut config, "theme", "nord").unwrap();
+        set(&mut config, "autocomplete", "false").unwrap();
+        set(&mut config, "undo_depth", "5").unwrap();
+        set(&mut config, "time_display", "iso").unwrap();
+        set(&mut config, "link_attributes", "member, manager").unwrap();
+        assert_eq!(config.general.theme, "nord");
+        assert!(!config.general.autocomplete);
+        assert_eq!(config.general.undo_depth, 5);
+        assert_eq!(config.detail.time_display, TimeDisplay::Iso);
+        assert_eq!(config.detail.link_attributes, vec!["member", "manager"]);
+
+        let before = config.general.clone();
+        assert!(set(&mut config, "autocomplete", "maybe").is_err());
+        assert!(set(&mut config, "undo_depth", "-1").is_err());
+        assert!(set(&mut config, "theme", "no-such-theme").is_err());
+        assert!(set(&mut config, "log_level", "chatty").is_err());
+        assert_eq!(
+            set(&mut config, "colour", "red").unwrap_err(),
+            "Unknown setting 'colour'"
+        );
+        assert_eq!(config.general.theme, before.theme);
+        assert_eq!(config.general.undo_depth, before.undo_depth);
+    }
+
+    #[test]
+    fn test_overrides_are_not_saved_until_kept() {
+        let mut config = AppConfig::default();
+        let mut overrides = Overrides::default();
+        overrides.set(&mut config, "theme", "nord").unwrap();
+        overrides.set(&mut config, "theme", "light").unwrap();
+        assert_eq!(config.general.theme, "light");
+        assert_eq!(overrides.persisted(&config).general.theme, "dark");
+
+        // The file changes underneath; the override stays on top of it
+        let mut reread = AppConfig::default();
+        reread.general.theme = "matrix".to_string();
+        reread.general.undo_depth = 3;
+        overrides.reapply(&mut reread);
+        assert_eq!(reread.general.theme, "light");
+        assert_eq!(reread.general.undo_depth, 3);
+        assert_eq!(overrides.persisted(&reread).general.theme, "matrix");
+
+        assert_eq!(overrides.keep(), vec!["theme"]);
+        assert!(overrides.is_empty());
+        assert_eq!(overrides.persisted(&reread).general.theme, "light");
+    }
+
+    #[test]
+    fn test_changes_by_when_they_apply() {
+        let old = AppConfig::default();
+        let mut new = old.clone();
+        new.general.theme = "nord".to_string();
+        new.detail.time_display = TimeDisplay::Relative;
+        new.general.health_check_secs = 5;
+        new.general.tick_rate_ms = 100;
+        new.keybindings.quit = "Ctrl+x".to_string();
+        new.search_history.push("(cn=a*)".to_string());
+
+        let changes = Changes::between(&old, &new);
+        assert_eq!(changes.now, vec!["theme", "time_display", "keybindings"]);
+        assert_eq!(changes.new_connections, vec!["health_check_secs"]);
+        assert_eq!(changes.restart, vec!["tick_rate_ms"]);
+        assert_eq!(
+            changes.summary(),
+            "theme, time_display, keybindings applied; health_check_secs on new connections; \
+             tick_rate_ms after a restart"
+        );
+        assert!(Changes::between(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_watch_notices_writes_by_others() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        let mut watch = ConfigWatch::new(path.clone());
+        assert!(!watch.changed());
+
+        std::fs::write(&path, "[general]\n").unwrap();
+        assert!(watch.changed());
+        assert!(!watch.changed());
+
+        std::fs::write(&path, "[general]\ntheme = \"nord\"\n").unwrap();
+        watch.seen();
+        assert!(!watch.changed());
+    }
+}
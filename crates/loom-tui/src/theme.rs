@@ -1,32 +1,130 @@
+use std::cell::Cell;
+use std::path::PathBuf;
+use std::rc::Rc;
+
 use ratatui::style::{Color, Modifier, Style};
-use serde::Deserialize;
 use tracing::warn;
 
-/// Application theme with styles for every UI element.
-#[derive(Debug, Clone)]
-pub struct Theme {
-    pub border: Style,
-    pub border_focused: Style,
-    pub selected: Style,
-    pub header: Style,
-    pub normal: Style,
-    pub dimmed: Style,
-    pub error: Style,
-    pub warning: Style,
-    pub success: Style,
-    pub status_bar: Style,
-    pub tab_active: Style,
-    pub tab_inactive: Style,
-    pub tree_node: Style,
-    pub tree_node_expanded: Style,
-    pub tree_node_selected: Style,
-    pub popup_border: Style,
-    pub popup_title: Style,
-    pub command_prompt: Style,
-    pub attr_operational: Style,
+/// Declare the styles of a theme: the [`Styles`] fields, the [`Theme`]
+/// accessor of each, and the style names a theme file may set.
+macro_rules! styles {
+    ($($(#[$doc:meta])* $name:ident,)*) => {
+        /// The style of every UI element in one theme.
+        #[derive(Debug, Clone, Copy, PartialEq)]
+        pub struct Styles {
+            $($(#[$doc])* pub $name: Style,)*
+        }
+
+        impl Styles {
+            /// Every element in the terminal's own colors.
+            fn plain() -> Self {
+                Self {
+                    $($name: Style::default(),)*
+                }
+            }
+
+            fn get_mut(&mut self, name: &str) -> Option<&mut Style> {
+                match name {
+                    $(stringify!($name) => Some(&mut self.$name),)*
+                    _ => None,
+                }
+            }
+        }
+
+        impl Theme {
+            $($(#[$doc])* pub fn $name(&self) -> Style {
+                self.0.get().$name
+            })*
+        }
+
+        /// Names of the styles under `[colors]` in a theme file.
+        pub const STYLES: &[&str] = &[$(stringify!($name)),*];
+    };
+}
+
+styles! {
+    border,
+    border_focused,
+    selected,
+    header,
+    normal,
+    dimmed,
+    error,
+    warning,
+    success,
+    status_bar,
+    tab_active,
+    tab_inactive,
+    tree_node,
+    tree_node_expanded,
+    tree_node_selected,
+    popup_border,
+    popup_title,
+    command_prompt,
+    attr_operational,
+    /// Rows picked out for a multi-entry action.
+    marked,
 }
 
+/// Application theme, held by every component that draws. Clones share
+/// the styles, so [`Theme::set`] restyles the whole UI from the next frame.
+#[derive(Debug, Clone, Default)]
+pub struct Theme(Rc<Cell<Styles>>);
+
 impl Theme {
+    /// Names of the built-in themes.
+    pub const BUILTIN: [&'static str; 6] = [
+        "dark",
+        "light",
+        "high-contrast",
+        "solarized",
+        "nord",
+        "matrix",
+    ];
+
+    pub fn new(styles: Styles) -> Self {
+        Self(Rc::new(Cell::new(styles)))
+    }
+
+    /// The styles in use.
+    pub fn styles(&self) -> Styles {
+        self.0.get()
+    }
+
+    /// Switch every holder of this theme to `styles`.
+    pub fn set(&self, styles: Styles) {
+        self.0.set(styles);
+    }
+
+    /// Location of a custom theme file in the config themes directory.
+    pub fn custom_theme_path(name: &str) -> Option<PathBuf> {
+        dirs::config_dir().map(|d| {
+            d.join("loom-ldapbrowser")
+                .join("themes")
+                .join(format!("{}.toml", name))
+        })
+    }
+
+    /// Load a theme by name, built in or custom, falling back to the dark
+    /// theme if it cannot be found or does not parse.
+    pub fn load(name: &str) -> Self {
+        match Styles::named(name) {
+            Ok(styles) => Self::new(styles),
+            Err(e) => {
+                warn!("Theme {}, using dark", e);
+                Self::default()
+            }
+        }
+    }
+}
+
+impl Default for Styles {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Styles {
     /// Default dark theme based on the Catppuccin Mocha palette.
     /// Soft pastel accents on a dark blue-gray base for readability and eye comfort.
     pub fn dark() -> Self {
@@ -65,6 +163,7 @@ impl Theme {
             popup_title: Style::default().fg(mauve).add_modifier(Modifier::BOLD),
             command_prompt: Style::default().fg(peach),
             attr_operational: Style::default().fg(overlay0),
+            marked: Style::default().fg(peach).bg(surface0),
         }
     }
 
@@ -100,6 +199,9 @@ impl Theme {
                 .add_modifier(Modifier::BOLD),
             command_prompt: Style::default().fg(Color::Rgb(0, 255, 0)),
             attr_operational: Style::default().fg(Color::Rgb(0, 120, 0)),
+            marked: Style::default()
+                .fg(Color::Rgb(0, 255, 0))
+                .bg(Color::Rgb(0, 60, 0)),
         }
     }
 
@@ -131,6 +233,7 @@ impl Theme {
                 .add_modifier(Modifier::BOLD),
             command_prompt: Style::default().fg(Color::Blue),
             attr_operational: Style::default().fg(Color::Gray),
+            marked: Style::default().fg(Color::Magenta),
         }
     }
 
@@ -167,6 +270,7 @@ impl Theme {
             popup_title: Style::default().fg(yellow).add_modifier(Modifier::BOLD),
             command_prompt: Style::default().fg(cyan),
             attr_operational: Style::default().fg(base01),
+            marked: Style::default().fg(orange),
         }
     }
 
@@ -205,171 +309,211 @@ impl Theme {
                 .add_modifier(Modifier::BOLD),
             command_prompt: Style::default().fg(frost2),
             attr_operational: Style::default().fg(frost0),
+            marked: Style::default().fg(aurora_yellow),
         }
     }
 
-    /// Load a theme by name. Supports built-in names and custom TOML paths.
-    pub fn load(name: &str) -> Self {
-        match name.to_lowercase().as_str() {
-            "dark" => Self::dark(),
-            "light" => Self::light(),
-            "solarized" => Self::solarized(),
-            "nord" => Self::nord(),
-            "matrix" => Self::matrix(),
-            _ => {
-                // Try loading from config themes directory
-                if let Some(config_dir) = dirs::config_dir() {
-                    let theme_path = config_dir
-                        .join("loom-ldapbrowser")
-                        .join("themes")
-                        .join(format!("{}.toml", name));
-                    if theme_path.exists() {
-                        if let Ok(content) = std::fs::read_to_string(&theme_path) {
-                            if let Ok(theme_def) = toml::from_str::<ThemeDefinition>(&content) {
-                                return theme_def.to_theme();
-                            } else {
-                                warn!("Failed to parse theme file: {:?}", theme_path);
-                            }
-                        }
-                    }
-                }
-                warn!("Unknown theme '{}', using dark", name);
-                Self::dark()
-            }
+    /// High-contrast theme: white on the terminal background with bright,
+    /// bold accents and reversed selections.
+    pub fn high_contrast() -> Self {
+        Self {
+            border: Style::default().fg(Color::White),
+            border_focused: Style::default()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            selected: Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            header: Style::default()
+                .fg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD),
+            normal: Style::default().fg(Color::White),
+            dimmed: Style::default().fg(Color::Gray),
+            error: Style::default()
+                .fg(Color::White)
+                .bg(Color::Red)
+                .add_modifier(Modifier::BOLD),
+            warning: Style::default()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            success: Style::default()
+                .fg(Color::LightGreen)
+                .add_modifier(Modifier::BOLD),
+            status_bar: Style::default().fg(Color::Black).bg(Color::White),
+            tab_active: Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD),
+            tab_inactive: Style::default().fg(Color::White),
+            tree_node: Style::default().fg(Color::White),
+            tree_node_expanded: Style::default().fg(Color::LightGreen),
+            tree_node_selected: Style::default()
+                .fg(Color::Black)
+                .bg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            popup_border: Style::default()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            popup_title: Style::default()
+                .fg(Color::LightYellow)
+                .add_modifier(Modifier::BOLD),
+            command_prompt: Style::default()
+                .fg(Color::LightCyan)
+                .add_modifier(Modifier::BOLD),
+            attr_operational: Style::default()
+                .fg(Color::Gray)
+                .add_modifier(Modifier::ITALIC),
+            marked: Style::default().fg(Color::Black).bg(Color::LightMagenta),
         }
     }
-}
 
-impl Default for Theme {
-    fn default() -> Self {
-        Self::dark()
+    /// A built-in theme by name.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name.to_lowercase().replace('_', "-").as_str() {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "high-contrast" => Some(Self::high_contrast()),
+            "solarized" => Some(Self::solarized()),
+            "nord" => Some(Self::nord()),
+            "matrix" => Some(Self::matrix()),
+            _ => None,
+        }
     }
-}
-
-// --- TOML deserialization types ---
-
-#[derive(Debug, Deserialize)]
-struct ThemeDefinition {
-    colors: ThemeColors,
-}
 
-#[derive(Debug, Deserialize)]
-struct ThemeColors {
-    #[serde(default)]
-    border: StyleDef,
-    #[serde(default)]
-    border_focused: StyleDef,
-    #[serde(default)]
-    selected: StyleDef,
-    #[serde(default)]
-    header: StyleDef,
-    #[serde(default)]
-    normal: StyleDef,
-    #[serde(default)]
-    dimmed: StyleDef,
-    #[serde(default)]
-    error: StyleDef,
-    #[serde(default)]
-    warning: StyleDef,
-    #[serde(default)]
-    success: StyleDef,
-    #[serde(default = "default_white")]
-    status_bar_fg: String,
-    #[serde(default = "default_dark_gray")]
-    status_bar_bg: String,
-    #[serde(default)]
-    tab_active: StyleDef,
-    #[serde(default)]
-    tab_inactive: StyleDef,
-    #[serde(default)]
-    tree_node: StyleDef,
-    #[serde(default)]
-    tree_node_expanded: StyleDef,
-    #[serde(default)]
-    tree_node_selected: StyleDef,
-    #[serde(default)]
-    popup_border: StyleDef,
-    #[serde(default)]
-    popup_title: StyleDef,
-    #[serde(default)]
-    command_prompt: StyleDef,
-    #[serde(default)]
-    attr_operational: StyleDef,
-}
-
-fn default_white() -> String {
-    "white".to_string()
-}
-fn default_dark_gray() -> String {
-    "dark_gray".to_string()
-}
+    /// The theme `name`: a built-in one, or the custom file of that name in
+    /// the themes directory.
+    pub fn named(name: &str) -> Result<Self, String> {
+        if let Some(styles) = Self::builtin(name) {
+            return Ok(styles);
+        }
+        let path =
+            Theme::custom_theme_path(name).ok_or_else(|| format!("'{}' is not built in", name))?;
+        let content = std::fs::read_to_string(&path).map_err(|_| {
+            format!(
+                "'{}' is neither built in nor a file at {}",
+                name,
+                path.display()
+            )
+        })?;
+        Self::from_toml(&content).map_err(|e| format!("{}: {}", path.display(), e))
+    }
 
-#[derive(Debug, Default, Deserialize)]
-struct StyleDef {
-    #[serde(default)]
-    fg: Option<String>,
-    #[serde(default)]
-    bg: Option<String>,
-    #[serde(default)]
-    modifiers: Option<String>,
-}
+    /// Parse a custom theme definition from TOML. Errors name the key at
+    /// fault, e.g. `colors.header.fg: ...`.
+    pub fn from_toml(content: &str) -> Result<Self, String> {
+        let table: toml::Table = content
+            .parse()
+            .map_err(|e: toml::de::Error| e.message().to_string())?;
+        let colors = match table.get("colors") {
+            Some(toml::Value::Table(colors)) => colors,
+            Some(_) => return Err("colors: expected a table".to_string()),
+            None => return Err("missing [colors] table".to_string()),
+        };
 
-impl StyleDef {
-    fn to_style(&self) -> Style {
-        let mut style = Style::default();
-        if let Some(ref fg) = self.fg {
-            style = style.fg(parse_color(fg));
-        }
-        if let Some(ref bg) = self.bg {
-            style = style.bg(parse_color(bg));
-        }
-        if let Some(ref mods) = self.modifiers {
-            for m in mods.split('|') {
-                match m.trim().to_uppercase().as_str() {
-                    "BOLD" => style = style.add_modifier(Modifier::BOLD),
-                    "ITALIC" => style = style.add_modifier(Modifier::ITALIC),
-                    "UNDERLINED" => style = style.add_modifier(Modifier::UNDERLINED),
-                    "DIM" => style = style.add_modifier(Modifier::DIM),
-                    _ => {}
+        let mut styles = Self::plain();
+        // Older theme files give the status bar as two colors
+        let mut status_fg = Color::White;
+        let mut status_bg = Color::DarkGray;
+        for (key, value) in colors {
+            let at = format!("colors.{}", key);
+            match key.as_str() {
+                "status_bar_fg" => status_fg = parse_color(value, &at)?,
+                "status_bar_bg" => status_bg = parse_color(value, &at)?,
+                _ => {
+                    let slot = styles.get_mut(key).ok_or_else(|| {
+                        format!(
+                            "{}: unknown style; expected one of {}",
+                            at,
+                            STYLES.join(", ")
+                        )
+                    })?;
+                    *slot = parse_style(value, &at)?;
                 }
             }
         }
-        style
+        if !colors.contains_key("status_bar") {
+            styles.status_bar = Style::default().fg(status_fg).bg(status_bg);
+        }
+        Ok(styles)
     }
 }
 
-impl ThemeDefinition {
-    fn to_theme(&self) -> Theme {
-        let c = &self.colors;
-        Theme {
-            border: c.border.to_style(),
-            border_focused: c.border_focused.to_style(),
-            selected: c.selected.to_style(),
-            header: c.header.to_style(),
-            normal: c.normal.to_style(),
-            dimmed: c.dimmed.to_style(),
-            error: c.error.to_style(),
-            warning: c.warning.to_style(),
-            success: c.success.to_style(),
-            status_bar: Style::default()
-                .fg(parse_color(&c.status_bar_fg))
-                .bg(parse_color(&c.status_bar_bg)),
-            tab_active: c.tab_active.to_style(),
-            tab_inactive: c.tab_inactive.to_style(),
-            tree_node: c.tree_node.to_style(),
-            tree_node_expanded: c.tree_node_expanded.to_style(),
-            tree_node_selected: c.tree_node_selected.to_style(),
-            popup_border: c.popup_border.to_style(),
-            popup_title: c.popup_title.to_style(),
-            command_prompt: c.command_prompt.to_style(),
-            attr_operational: c.attr_operational.to_style(),
+// --- Theme file parsing ---
+
+/// A style is a table of `fg`, `bg` and `modifiers`, or a bare color for
+/// the foreground.
+fn parse_style(value: &toml::Value, at: &str) -> Result<Style, String> {
+    let table = match value {
+        toml::Value::Table(table) => table,
+        color => return Ok(Style::default().fg(parse_color(color, at)?)),
+    };
+    let mut style = Style::default();
+    for (field, value) in table {
+        let at = format!("{}.{}", at, field);
+        match field.as_str() {
+            "fg" => style = style.fg(parse_color(value, &at)?),
+            "bg" => style = style.bg(parse_color(value, &at)?),
+            "modifiers" => style = style.add_modifier(parse_modifiers(value, &at)?),
+            _ => {
+                return Err(format!(
+                    "{}: unknown field; expected fg, bg or modifiers",
+                    at
+                ))
+            }
         }
     }
+    Ok(style)
+}
+
+/// Modifiers as `"BOLD|ITALIC"` or `["BOLD", "ITALIC"]`.
+fn parse_modifiers(value: &toml::Value, at: &str) -> Result<Modifier, String> {
+    let names: Vec<&str> = match value {
+        toml::Value::String(s) => s.split('|').collect(),
+        toml::Value::Array(items) => items
+            .iter()
+            .map(|item| {
+                item.as_str()
+                    .ok_or_else(|| format!("{}: expected modifier names", at))
+            })
+            .collect::<Result<_, _>>()?,
+        _ => return Err(format!("{}: expected modifier names", at)),
+    };
+    names.iter().try_fold(Modifier::empty(), |mods, name| {
+        let modifier = match name.trim().to_uppercase().as_str() {
+            "" => Modifier::empty(),
+            "BOLD" => Modifier::BOLD,
+            "DIM" => Modifier::DIM,
+            "ITALIC" => Modifier::ITALIC,
+            "UNDERLINED" | "UNDERLINE" => Modifier::UNDERLINED,
+            "REVERSED" | "REVERSE" => Modifier::REVERSED,
+            "CROSSED_OUT" => Modifier::CROSSED_OUT,
+            _ => return Err(format!("{}: unknown modifier '{}'", at, name.trim())),
+        };
+        Ok(mods | modifier)
+    })
 }
 
-fn parse_color(s: &str) -> Color {
+/// A color is a name, `#rrggbb` for truecolor terminals, or an index into
+/// the 256-color palette given as a number.
+fn parse_color(value: &toml::Value, at: &str) -> Result<Color, String> {
+    let color = match value {
+        toml::Value::String(s) => color_from_str(s),
+        toml::Value::Integer(n) => u8::try_from(*n).ok().map(Color::Indexed),
+        _ => None,
+    };
+    color.ok_or_else(|| {
+        format!(
+            "{}: {} is not a color name, #rrggbb or a palette index 0-255",
+            at, value
+        )
+    })
+}
+
+fn color_from_str(s: &str) -> Option<Color> {
     let s = s.trim().to_lowercase();
-    match s.as_str() {
+    let color = match s.as_str() {
+        "default" | "reset" => Color::Reset,
         "black" => Color::Black,
         "red" => Color::Red,
         "green" => Color::Green,
@@ -387,20 +531,19 @@ fn parse_color(s: &str) -> Color {
         "light_cyan" | "lightcyan" => Color::LightCyan,
         "white" => Color::White,
         _ => {
-            // Try #RRGGBB or RRGGBB
+            if let Ok(index) = s.parse::<u8>() {
+                return Some(Color::Indexed(index));
+            }
+            // #RRGGBB or RRGGBB
             let hex = s.strip_prefix('#').unwrap_or(&s);
-            if hex.len() == 6 {
-                if let (Ok(r), Ok(g), Ok(b)) = (
-                    u8::from_str_radix(&hex[0..2], 16),
-                    u8::from_str_radix(&hex[2..4], 16),
-                    u8::from_str_radix(&hex[4..6], 16),
-                ) {
-                    return Color::Rgb(r, g, b);
-                }
+            if hex.len() != 6 || !hex.is_ascii() {
+                return None;
             }
-            Color::White
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+            Color::Rgb(channel(0)?, channel(2)?, channel(4)?)
         }
-    }
+    };
+    Some(color)
 }
 
 #[cfg(test)]
@@ -409,23 +552,120 @@ mod tests {
 
     #[test]
     fn test_parse_color_names() {
-        assert_eq!(parse_color("red"), Color::Red);
-        assert_eq!(parse_color("dark_gray"), Color::DarkGray);
-        assert_eq!(parse_color("DarkGray"), Color::DarkGray);
+        assert_eq!(color_from_str("red"), Some(Color::Red));
+        assert_eq!(color_from_str("dark_gray"), Some(Color::DarkGray));
+        assert_eq!(color_from_str("DarkGray"), Some(Color::DarkGray));
+        assert_eq!(color_from_str("default"), Some(Color::Reset));
+        assert_eq!(color_from_str("purplish"), None);
     }
 
     #[test]
     fn test_parse_color_hex() {
-        assert_eq!(parse_color("#FF0000"), Color::Rgb(255, 0, 0));
-        assert_eq!(parse_color("00FF00"), Color::Rgb(0, 255, 0));
+        assert_eq!(color_from_str("#FF0000"), Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(color_from_str("00FF00"), Some(Color::Rgb(0, 255, 0)));
+        assert_eq!(color_from_str("#12345"), None);
+        assert_eq!(color_from_str("#gg0000"), None);
+    }
+
+    #[test]
+    fn test_parse_color_palette_index() {
+        assert_eq!(color_from_str("208"), Some(Color::Indexed(208)));
+        let at = "colors.header.fg";
+        assert_eq!(
+            parse_color(&toml::Value::Integer(16), at),
+            Ok(Color::Indexed(16))
+        );
+        let err = parse_color(&toml::Value::Integer(256), at).unwrap_err();
+        assert!(err.starts_with("colors.header.fg: 256"), "{}", err);
     }
 
     #[test]
     fn test_load_builtin_themes() {
-        let _dark = Theme::load("dark");
-        let _light = Theme::load("light");
-        let _solarized = Theme::load("solarized");
-        let _nord = Theme::load("nord");
-        let _matrix = Theme::load("matrix");
+        for name in Theme::BUILTIN {
+            assert!(Styles::builtin(name).is_some(), "{}", name);
+        }
+        assert_eq!(
+            Styles::builtin("High_Contrast"),
+            Some(Styles::high_contrast())
+        );
+        assert_eq!(Theme::load("light").styles(), Styles::light());
+    }
+
+    #[test]
+    fn test_from_toml() {
+        let styles = Styles::from_toml(
+            r##"
+[colors]
+header = { fg = "#fabd2f", bg = 235, modifiers = "BOLD|ITALIC" }
+dimmed = "245"
+marked = { fg = "yellow", modifiers = ["reversed"] }
+"##,
+        )
+        .unwrap();
+        assert_eq!(
+            styles.header,
+            Style::default()
+                .fg(Color::Rgb(0xfa, 0xbd, 0x2f))
+                .bg(Color::Indexed(235))
+                .add_modifier(Modifier::BOLD | Modifier::ITALIC)
+        );
+        assert_eq!(styles.dimmed, Style::default().fg(Color::Indexed(245)));
+        assert_eq!(
+            styles.marked,
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::REVERSED)
+        );
+        assert_eq!(styles.normal, Style::default());
+        assert_eq!(
+            styles.status_bar,
+            Style::default().fg(Color::White).bg(Color::DarkGray)
+        );
+    }
+
+    #[test]
+    fn test_from_toml_legacy_status_bar() {
+        let styles =
+            Styles::from_toml("[colors]\nstatus_bar_fg = \"black\"\nstatus_bar_bg = \"gray\"")
+                .unwrap();
+        assert_eq!(
+            styles.status_bar,
+            Style::default().fg(Color::Black).bg(Color::Gray)
+        );
+    }
+
+    #[test]
+    fn test_from_toml_names_offending_key() {
+        let err = |toml: &str| Styles::from_toml(toml).unwrap_err();
+        assert!(err("[colors]\nheader = { fg = \"purplish\" }")
+            .starts_with("colors.header.fg: \"purplish\" is not a color"));
+        assert!(err("[colors]\nheadr = \"red\"").starts_with("colors.headr: unknown style"));
+        assert!(err("[colors]\nheader = { fg = \"red\", blink = true }")
+            .starts_with("colors.header.blink: unknown field"));
+        assert_eq!(
+            err("[colors]\nheader = { modifiers = \"BOLD|BLINKY\" }"),
+            "colors.header.modifiers: unknown modifier 'BLINKY'"
+        );
+        assert_eq!(err("[other]\nx = 1"), "missing [colors] table");
+    }
+
+    #[test]
+    fn test_shipped_theme_files_parse() {
+        let dir = concat!(env!("CARGO_MANIFEST_DIR"), "/../../config/themes");
+        for entry in std::fs::read_dir(dir).unwrap() {
+            let path = entry.unwrap().path();
+            let content = std::fs::read_to_string(&path).unwrap();
+            if let Err(e) = Styles::from_toml(&content) {
+                panic!("{}: {}", path.display(), e);
+            }
+        }
+    }
+
+    #[test]
+    fn test_clones_share_styles() {
+        let theme = Theme::default();
+        let held = theme.clone();
+        theme.set(Styles::high_contrast());
+        assert_eq!(held.header(), Styles::high_contrast().header);
     }
 }
@@ -18,7 +18,11 @@ pub struct Breadcrumb {
 impl Breadcrumb {
     pub fn new(dn: &str, separator_style: Style, part_style: Style, last_style: Style) -> Self {
         // Split DN into RDN components and reverse for left-to-right reading
-        let parts: Vec<String> = dn.split(',').map(|s| s.trim().to_string()).rev().collect();
+        let parts: Vec<String> = loom_core::dn::components(dn)
+            .into_iter()
+            .rev()
+            .map(str::to_string)
+            .collect();
 
         Self {
             parts,
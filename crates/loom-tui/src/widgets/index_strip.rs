@@ -0,0 +1,98 @@
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+
+use loom_core::jump::{index_letter, INDEX};
+
+/// A one-column A-Z strip along the right edge of a list, marking the
+/// letters of the names currently in view.
+pub struct IndexStrip {
+    /// Positions in `INDEX` of the first and last letter in view.
+    band: Option<(usize, usize)>,
+    style: Style,
+    band_style: Style,
+}
+
+impl IndexStrip {
+    /// Build the strip for the names in view, in list order.
+    pub fn new<'a>(
+        names: impl IntoIterator<Item = &'a str>,
+        style: Style,
+        band_style: Style,
+    ) -> Self {
+        let band = names
+            .into_iter()
+            .map(|name| {
+                let letter = index_letter(name);
+                INDEX.iter().position(|&l| l == letter).unwrap_or(0)
+            })
+            .fold(None, |band: Option<(usize, usize)>, i| match band {
+                Some((lo, hi)) => Some((lo.min(i), hi.max(i))),
+                None => Some((i, i)),
+            });
+        Self {
+            band,
+            style,
+            band_style,
+        }
+    }
+
+    /// The letter on each row of a strip `height` rows tall, and whether
+    /// that row is within the band. Short strips show every n-th letter.
+    pub fn rows(&self, height: u16) -> Vec<(char, bool)> {
+        let height = usize::from(height).min(INDEX.len());
+        (0..height)
+            .map(|row| {
+                let first = row * INDEX.len() / height;
+                let last = (row + 1) * INDEX.len() / height - 1;
+                let in_band = self.band.is_some_and(|(lo, hi)| first <= hi && last >= lo);
+                (INDEX[first], in_band)
+            })
+            .collect()
+    }
+
+    /// Draw the strip in the rightmost column of `area`.
+    pub fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.width == 0 {
+            return;
+        }
+        let x = area.right() - 1;
+        for (row, (letter, in_band)) in self.rows(area.height).into_iter().enumerate() {
+            let style = if in_band { self.band_style } else { self.style };
+            buf[(x, area.y + row as u16)]
+                .set_char(letter)
+                .set_style(style);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_band_covers_letters_in_view() {
+        let strip = IndexStrip::new(
+            ["miller", "Nguyen", "smith"],
+            Style::default(),
+            Style::default(),
+        );
+        let rows = strip.rows(27);
+        let marked: String = rows.iter().filter(|r| r.1).map(|r| r.0).collect();
+        assert_eq!(marked, "MNOPQRS");
+    }
+
+    #[test]
+    fn test_short_strip_samples_letters() {
+        let strip = IndexStrip::new(["zhang"], Style::default(), Style::default());
+        let rows = strip.rows(9);
+        assert_eq!(rows.len(), 9);
+        assert_eq!(rows[0].0, '#');
+        // The last row stands for X-Z and holds the band
+        assert_eq!(rows.iter().filter(|r| r.1).count(), 1);
+        assert!(rows[8].1);
+
+        let empty = IndexStrip::new([], Style::default(), Style::default());
+        assert!(empty.rows(27).iter().all(|r| !r.1));
+    }
+}
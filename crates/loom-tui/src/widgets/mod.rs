@@ -1,3 +1,5 @@
 pub mod breadcrumb;
 pub mod fuzzy_input;
+pub mod index_strip;
 pub mod spinner;
+pub mod width;
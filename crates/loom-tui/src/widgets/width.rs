@@ -0,0 +1,85 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+/// Columns `s` takes up in the terminal.
+pub fn display_width(s: &str) -> usize {
+    s.width()
+}
+
+/// Shorten `s` to at most `max` columns, ending in `…` when cut.
+/// Wide characters (CJK, emoji) count as two columns.
+pub fn truncate_to_width(s: &str, max: usize) -> String {
+    if s.width() <= max {
+        return s.to_string();
+    }
+    if max == 0 {
+        return String::new();
+    }
+    let mut out = take_width(s, max - 1).to_string();
+    out.push('\u{2026}');
+    out
+}
+
+/// The longest prefix of `s` that fits in `max` columns, never splitting a
+/// wide character.
+pub fn take_width(s: &str, max: usize) -> &str {
+    let mut used = 0;
+    for (i, c) in s.char_indices() {
+        let w = c.width().unwrap_or(0);
+        if used + w > max {
+            return &s[..i];
+        }
+        used += w;
+    }
+    s
+}
+
+/// Share `available` columns among fields wanting `natural` widths.
+/// Narrow fields keep their width; the wide ones are cut to an even
+/// share of what is left.
+pub fn fit_widths(natural: &[usize], available: usize) -> Vec<usize> {
+    if natural.iter().sum::<usize>() <= available {
+        return natural.to_vec();
+    }
+    let mut cap = available / natural.len();
+    while natural.iter().map(|&w| w.min(cap + 1)).sum::<usize>() <= available {
+        cap += 1;
+    }
+    let mut widths: Vec<usize> = natural.iter().map(|&w| w.min(cap)).collect();
+    let mut spare = available - widths.iter().sum::<usize>();
+    for (width, &wanted) in widths.iter_mut().zip(natural) {
+        if spare > 0 && wanted > *width {
+            *width += 1;
+            spare -= 1;
+        }
+    }
+    widths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_truncate_to_width() {
+        assert_eq!(truncate_to_width("prod-ldap", 20), "prod-ldap");
+        assert_eq!(truncate_to_width("prod-ldap", 9), "prod-ldap");
+        assert_eq!(truncate_to_width("prod-ldap", 5), "prod\u{2026}");
+        assert_eq!(truncate_to_width("prod-ldap", 1), "\u{2026}");
+        assert_eq!(truncate_to_width("prod-ldap", 0), "");
+        // Wide characters are never split
+        assert_eq!(truncate_to_width("東京本社", 5), "東京\u{2026}");
+        assert_eq!(truncate_to_width("東京本社", 4), "東\u{2026}");
+        assert_eq!(display_width("東京本社"), 8);
+        assert_eq!(take_width("東京本社", 3), "東");
+        assert_eq!(take_width("prod", 9), "prod");
+    }
+
+    #[test]
+    fn test_fit_widths() {
+        assert_eq!(fit_widths(&[5, 10, 3], 40), vec![5, 10, 3]);
+        // The narrow columns keep their width, the wide ones share the rest
+        assert_eq!(fit_widths(&[4, 30, 40, 3], 40), vec![4, 17, 16, 3]);
+        assert_eq!(fit_widths(&[20, 20], 9), vec![5, 4]);
+        assert_eq!(fit_widths(&[8, 8, 8], 0), vec![0, 0, 0]);
+    }
+}